@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use crate::block;
+use crate::json::{self, Json};
+use super::version::{ProtocolVersion, SUPPORTED};
+use super::{ConnectionState, Direction, PACKETS};
+
+// Daten je Client-Version in versions/<Version>/, etwa versions/1.20.4/. Alle Dateien sind optional; was fehlt,
+// kommt aus den eingebauten Tabellen:
+// - packets.json: {"clientbound": {"login_play": 41}, "serverbound": {...}} mit den Namen aus PACKETS
+// - registries.json und blocks.json: die Berichte des Vanilla-Datengenerators dieser Version, für die IDs von
+//   Items, Blöcken und Entity-Typen und die Block-States
+pub const ASSETS_DIR: &str = "versions";
+const PACKETS_FILE: &str = "packets.json";
+const REGISTRIES_FILE: &str = "registries.json";
+const BLOCKS_FILE: &str = "blocks.json";
+
+static ASSETS: OnceLock<HashMap<ProtocolVersion, VersionAssets>> = OnceLock::new();
+
+#[derive(Debug, Default)]
+pub struct VersionAssets {
+    // Interne ID -> ID dieser Version bzw. umgekehrt
+    clientbound: HashMap<i32, i32>,
+    serverbound: HashMap<i32, i32>,
+    items: HashMap<String, i32>,
+    blocks: HashMap<String, i32>,
+    entities: HashMap<String, i32>,
+    // Interner Block-State -> State dieser Version; leer, wenn blocks.json fehlt
+    states: Vec<u16>,
+}
+
+impl VersionAssets {
+    fn load(dir: &Path) -> Result<Option<VersionAssets>, String> {
+        let read = |file: &str| -> Result<Option<Json>, String> {
+            let path = dir.join(file);
+            match fs::read_to_string(&path) {
+                Ok(text) => json::parse(&text).map(Some).map_err(|e| format!("{}: {}", path.display(), e)),
+                Err(_) => Ok(None),
+            }
+        };
+        let (packets, registries, blocks) = (read(PACKETS_FILE)?, read(REGISTRIES_FILE)?, read(BLOCKS_FILE)?);
+        if packets.is_none() && registries.is_none() && blocks.is_none() {
+            return Ok(None);
+        }
+        let mut assets = VersionAssets::default();
+        if let Some(packets) = packets {
+            assets.clientbound = packet_ids(&packets, Direction::Clientbound)?.into_iter().collect();
+            assets.serverbound = packet_ids(&packets, Direction::Serverbound)?.into_iter().map(|(internal, wire)| (wire, internal)).collect();
+        }
+        if let Some(registries) = registries {
+            assets.items = registry(&registries, "minecraft:item");
+            assets.blocks = registry(&registries, "minecraft:block");
+            assets.entities = registry(&registries, "minecraft:entity_type");
+        }
+        if let Some(report) = blocks {
+            assets.states = block_states(&report)?;
+        }
+        Ok(Some(assets))
+    }
+}
+
+// (interne ID, ID dieser Version) für die Spielpakete einer Richtung
+fn packet_ids(packets: &Json, direction: Direction) -> Result<Vec<(i32, i32)>, String> {
+    let Some(entries) = packets.get(direction.name()).and_then(Json::as_object) else { return Ok(Vec::new()) };
+    let mut ids = Vec::new();
+    for (name, id) in entries {
+        let def = PACKETS
+            .iter()
+            .find(|p| p.name == name && p.direction == direction && p.state == ConnectionState::Play)
+            .ok_or(format!("Unbekanntes Paket {} ({})", name, direction.name()))?;
+        let id = id.as_f64().ok_or(format!("ID von {} ist keine Zahl", name))?;
+        ids.push((def.id, id as i32));
+    }
+    Ok(ids)
+}
+
+// Einträge eines Registers aus registries.json: Name -> protocol_id
+fn registry(report: &Json, name: &str) -> HashMap<String, i32> {
+    let entries = report.get(name).and_then(|r| r.get("entries")).and_then(Json::as_object);
+    entries
+        .into_iter()
+        .flatten()
+        .filter_map(|(entry, value)| Some((entry.clone(), value.get("protocol_id")?.as_f64()? as i32)))
+        .collect()
+}
+
+// Solange sich die Eigenschaften eines Blocks nicht ändern, liegen seine States in jeder Version in derselben
+// Reihenfolge; es verschiebt sich nur der erste
+fn block_states(report: &Json) -> Result<Vec<u16>, String> {
+    let end = block::state_ranges().map(|(_, first, count)| first + count).max().unwrap_or(0);
+    let mut states: Vec<u16> = (0..end).collect();
+    for (name, first, count) in block::state_ranges() {
+        let Some(ids) = report.get(name).and_then(|b| b.get("states")).and_then(Json::as_array) else { continue };
+        let ids: Vec<u16> = ids.iter().filter_map(|state| state.get("id")?.as_f64()).map(|id| id as u16).collect();
+        if ids.len() != count as usize {
+            return Err(format!("{} hat {} statt {} Zustände", name, ids.len(), count));
+        }
+        let version_first = ids.iter().copied().min().unwrap_or(first);
+        for offset in 0..count {
+            states[(first + offset) as usize] = version_first + offset;
+        }
+    }
+    Ok(states)
+}
+
+// Beim Start einmal für alle unterstützten Versionen. Gibt die Meldungen für das Log zurück, Warnungen als Err
+pub fn load(dir: &str) -> Vec<Result<String, String>> {
+    let mut messages = Vec::new();
+    let mut loaded = HashMap::new();
+    for version in SUPPORTED {
+        match VersionAssets::load(&Path::new(dir).join(version.name())) {
+            Ok(Some(assets)) => {
+                messages.push(Ok(format!("Daten für {} aus {} geladen", version.name(), dir)));
+                loaded.insert(version, assets);
+            }
+            Ok(None) => {}
+            Err(e) => messages.push(Err(format!("Daten für {} nicht geladen: {}", version.name(), e))),
+        }
+    }
+    if ASSETS.set(loaded).is_err() {
+        messages.push(Err(format!("Versionsdaten waren schon in Gebrauch, {} ignoriert", dir)));
+    }
+    messages
+}
+
+fn assets(version: ProtocolVersion) -> Option<&'static VersionAssets> {
+    ASSETS.get_or_init(HashMap::new).get(&version)
+}
+
+pub fn clientbound(version: ProtocolVersion, id: i32) -> Option<i32> {
+    assets(version)?.clientbound.get(&id).copied()
+}
+
+pub fn serverbound(version: ProtocolVersion, id: i32) -> Option<i32> {
+    assets(version)?.serverbound.get(&id).copied()
+}
+
+// Ob ein eingehendes Paket in dieser Version unter einer anderen ID kommt
+pub fn moves_serverbound(version: ProtocolVersion, internal: i32) -> bool {
+    assets(version).is_some_and(|assets| assets.serverbound.iter().any(|(wire, id)| *id == internal && *wire != internal))
+}
+
+// IDs aus dem Registerbericht der Version: None, wenn es keinen gibt, Some(None), wenn sie den Eintrag nicht kennt
+pub fn item_id(version: ProtocolVersion, item: &str) -> Option<Option<i32>> {
+    lookup(version, |assets| &assets.items, item)
+}
+
+pub fn block_id(version: ProtocolVersion, name: &str) -> Option<Option<i32>> {
+    lookup(version, |assets| &assets.blocks, name)
+}
+
+pub fn entity_id(version: ProtocolVersion, kind: &str) -> Option<Option<i32>> {
+    lookup(version, |assets| &assets.entities, kind)
+}
+
+fn lookup(version: ProtocolVersion, registry: fn(&VersionAssets) -> &HashMap<String, i32>, name: &str) -> Option<Option<i32>> {
+    let entries = registry(assets(version)?);
+    (!entries.is_empty()).then(|| entries.get(name).copied())
+}
+
+// Block-State für einen Client dieser Version
+pub fn block_state(version: ProtocolVersion, state: u16) -> u16 {
+    assets(version).and_then(|assets| assets.states.get(state as usize)).copied().unwrap_or(state)
+}
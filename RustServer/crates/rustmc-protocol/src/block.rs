@@ -0,0 +1,893 @@
+use crate::assets;
+use crate::version::ProtocolVersion;
+
+// Block-State-IDs aus dem globalen Palette-Register (1.21.1), jeweils der Standardzustand
+pub const AIR: u16 = 0;
+pub const STONE: u16 = 1;
+pub const GRANITE: u16 = 2;
+pub const POLISHED_GRANITE: u16 = 3;
+pub const DIORITE: u16 = 4;
+pub const POLISHED_DIORITE: u16 = 5;
+pub const ANDESITE: u16 = 6;
+pub const POLISHED_ANDESITE: u16 = 7;
+pub const GRASS_BLOCK: u16 = 9;
+pub const DIRT: u16 = 10;
+pub const COARSE_DIRT: u16 = 11;
+pub const PODZOL: u16 = 13;
+pub const COBBLESTONE: u16 = 14;
+pub const OAK_PLANKS: u16 = 15;
+pub const SPRUCE_PLANKS: u16 = 16;
+pub const BIRCH_PLANKS: u16 = 17;
+pub const JUNGLE_PLANKS: u16 = 18;
+pub const ACACIA_PLANKS: u16 = 19;
+pub const CHERRY_PLANKS: u16 = 20;
+pub const DARK_OAK_PLANKS: u16 = 21;
+pub const MANGROVE_PLANKS: u16 = 22;
+pub const BAMBOO_PLANKS: u16 = 23;
+pub const OAK_SAPLING: u16 = 25;
+pub const SPRUCE_SAPLING: u16 = 27;
+pub const JUNGLE_SAPLING: u16 = 31;
+pub const BEDROCK: u16 = 79;
+pub const WATER: u16 = 80;
+pub const LAVA: u16 = 96;
+pub const SAND: u16 = 112;
+pub const RED_SAND: u16 = 117;
+pub const GRAVEL: u16 = 118;
+pub const GOLD_ORE: u16 = 123;
+pub const DEEPSLATE_GOLD_ORE: u16 = 124;
+pub const IRON_ORE: u16 = 125;
+pub const DEEPSLATE_IRON_ORE: u16 = 126;
+pub const COAL_ORE: u16 = 127;
+pub const DEEPSLATE_COAL_ORE: u16 = 128;
+pub const OAK_LOG: u16 = 131;
+pub const SPRUCE_LOG: u16 = 134;
+pub const BIRCH_LOG: u16 = 137;
+pub const JUNGLE_LOG: u16 = 140;
+pub const ACACIA_LOG: u16 = 143;
+pub const CHERRY_LOG: u16 = 146;
+pub const DARK_OAK_LOG: u16 = 149;
+pub const OAK_LEAVES: u16 = 264;
+pub const SPRUCE_LEAVES: u16 = 292;
+pub const JUNGLE_LEAVES: u16 = 348;
+pub const GLASS: u16 = 519;
+pub const LAPIS_ORE: u16 = 520;
+pub const DEEPSLATE_LAPIS_ORE: u16 = 521;
+pub const LAPIS_BLOCK: u16 = 522;
+pub const WHITE_BED: u16 = 1691;
+pub const ORANGE_BED: u16 = 1707;
+pub const MAGENTA_BED: u16 = 1723;
+pub const LIGHT_BLUE_BED: u16 = 1739;
+pub const YELLOW_BED: u16 = 1755;
+pub const LIME_BED: u16 = 1771;
+pub const PINK_BED: u16 = 1787;
+pub const GRAY_BED: u16 = 1803;
+pub const LIGHT_GRAY_BED: u16 = 1819;
+pub const CYAN_BED: u16 = 1835;
+pub const PURPLE_BED: u16 = 1851;
+pub const BLUE_BED: u16 = 1867;
+pub const BROWN_BED: u16 = 1883;
+pub const GREEN_BED: u16 = 1899;
+pub const RED_BED: u16 = 1915;
+pub const BLACK_BED: u16 = 1931;
+pub const STICKY_PISTON: u16 = 2000;
+pub const PISTON: u16 = 2019;
+pub const PISTON_HEAD: u16 = 2033;
+pub const MOVING_PISTON: u16 = 2067;
+pub const GOLD_BLOCK: u16 = 2091;
+pub const IRON_BLOCK: u16 = 2092;
+pub const BRICKS: u16 = 2093;
+pub const TNT: u16 = 2095;
+pub const BOOKSHELF: u16 = 2096;
+pub const OBSIDIAN: u16 = 2354;
+pub const TORCH: u16 = 2355;
+pub const WALL_TORCH: u16 = 2356;
+pub const FIRE: u16 = 2391;
+pub const SOUL_FIRE: u16 = 2872;
+pub const SPAWNER: u16 = 2873;
+pub const OAK_STAIRS: u16 = 2885;
+pub const CHEST: u16 = 2955;
+pub const DIAMOND_ORE: u16 = 4274;
+pub const DEEPSLATE_DIAMOND_ORE: u16 = 4275;
+pub const DIAMOND_BLOCK: u16 = 4276;
+pub const CRAFTING_TABLE: u16 = 4277;
+pub const WHEAT: u16 = 4278;
+pub const FARMLAND: u16 = 4286;
+pub const FURNACE: u16 = 4295;
+pub const OAK_SIGN: u16 = 4303;
+pub const SPRUCE_SIGN: u16 = 4335;
+pub const BIRCH_SIGN: u16 = 4367;
+pub const ACACIA_SIGN: u16 = 4399;
+pub const CHERRY_SIGN: u16 = 4431;
+pub const JUNGLE_SIGN: u16 = 4463;
+pub const DARK_OAK_SIGN: u16 = 4495;
+pub const MANGROVE_SIGN: u16 = 4527;
+pub const BAMBOO_SIGN: u16 = 4559;
+pub const OAK_DOOR: u16 = 4601;
+pub const COBBLESTONE_STAIRS: u16 = 4693;
+pub const OAK_WALL_SIGN: u16 = 4763;
+pub const SPRUCE_WALL_SIGN: u16 = 4771;
+pub const BIRCH_WALL_SIGN: u16 = 4779;
+pub const ACACIA_WALL_SIGN: u16 = 4787;
+pub const CHERRY_WALL_SIGN: u16 = 4795;
+pub const JUNGLE_WALL_SIGN: u16 = 4803;
+pub const DARK_OAK_WALL_SIGN: u16 = 4811;
+pub const MANGROVE_WALL_SIGN: u16 = 4819;
+pub const BAMBOO_WALL_SIGN: u16 = 4827;
+pub const LEVER: u16 = 5635;
+pub const STONE_PRESSURE_PLATE: u16 = 5651;
+pub const IRON_DOOR: u16 = 5663;
+pub const OAK_PRESSURE_PLATE: u16 = 5717;
+pub const REDSTONE_ORE: u16 = 5735;
+pub const DEEPSLATE_REDSTONE_ORE: u16 = 5737;
+pub const STONE_BUTTON: u16 = 5757;
+pub const SNOW: u16 = 5772;
+pub const ICE: u16 = 5780;
+pub const SNOW_BLOCK: u16 = 5781;
+pub const GLOWSTONE: u16 = 5863;
+pub const JACK_O_LANTERN: u16 = 5870;
+pub const OAK_TRAPDOOR: u16 = 5976;
+pub const STONE_BRICKS: u16 = 6537;
+pub const MOSSY_STONE_BRICKS: u16 = 6538;
+pub const CRACKED_STONE_BRICKS: u16 = 6539;
+pub const CHISELED_STONE_BRICKS: u16 = 6540;
+pub const GLASS_PANE: u16 = 6810;
+pub const OAK_FENCE_GATE: u16 = 7004;
+pub const STONE_BRICK_STAIRS: u16 = 7120;
+pub const ENCHANTING_TABLE: u16 = 7389;
+pub const END_PORTAL: u16 = 7406;
+pub const END_STONE: u16 = 7415;
+pub const DRAGON_EGG: u16 = 7416;
+pub const EMERALD_ORE: u16 = 7511;
+pub const DEEPSLATE_EMERALD_ORE: u16 = 7512;
+pub const ENDER_CHEST: u16 = 7514;
+pub const TRIPWIRE_HOOK: u16 = 7530;
+pub const TRIPWIRE: u16 = 7664;
+pub const EMERALD_BLOCK: u16 = 7665;
+pub const CARROTS: u16 = 8595;
+pub const POTATOES: u16 = 8603;
+pub const OAK_BUTTON: u16 = 8620;
+pub const ANVIL: u16 = 9107;
+pub const CHIPPED_ANVIL: u16 = 9111;
+pub const DAMAGED_ANVIL: u16 = 9115;
+pub const LIGHT_WEIGHTED_PRESSURE_PLATE: u16 = 9143;
+pub const HEAVY_WEIGHTED_PRESSURE_PLATE: u16 = 9159;
+pub const HOPPER: u16 = 9225;
+pub const SLIME_BLOCK: u16 = 10364;
+pub const BARREL: u16 = 18413;
+pub const CARTOGRAPHY_TABLE: u16 = 18436;
+pub const SMITHING_TABLE: u16 = 18466;
+pub const STONECUTTER: u16 = 18467;
+pub const HONEY_BLOCK: u16 = 19445;
+
+// Bits pro Eintrag, wenn die Sektion direkt auf das globale Register verweist
+pub const GLOBAL_BITS: u8 = 15;
+
+// Name, Standardzustand und ID im Block-Register (für Block-Prädikate in Items)
+const BLOCKS: &[(&str, u16, i32)] = &[
+    ("minecraft:air", AIR, 0),
+    ("minecraft:stone", STONE, 1),
+    ("minecraft:granite", GRANITE, 2),
+    ("minecraft:polished_granite", POLISHED_GRANITE, 3),
+    ("minecraft:diorite", DIORITE, 4),
+    ("minecraft:polished_diorite", POLISHED_DIORITE, 5),
+    ("minecraft:andesite", ANDESITE, 6),
+    ("minecraft:polished_andesite", POLISHED_ANDESITE, 7),
+    ("minecraft:grass_block", GRASS_BLOCK, 8),
+    ("minecraft:dirt", DIRT, 9),
+    ("minecraft:coarse_dirt", COARSE_DIRT, 10),
+    ("minecraft:podzol", PODZOL, 11),
+    ("minecraft:cobblestone", COBBLESTONE, 12),
+    ("minecraft:oak_planks", OAK_PLANKS, 13),
+    ("minecraft:spruce_planks", SPRUCE_PLANKS, 14),
+    ("minecraft:birch_planks", BIRCH_PLANKS, 15),
+    ("minecraft:jungle_planks", JUNGLE_PLANKS, 16),
+    ("minecraft:acacia_planks", ACACIA_PLANKS, 17),
+    ("minecraft:cherry_planks", CHERRY_PLANKS, 18),
+    ("minecraft:dark_oak_planks", DARK_OAK_PLANKS, 19),
+    ("minecraft:mangrove_planks", MANGROVE_PLANKS, 20),
+    ("minecraft:bamboo_planks", BAMBOO_PLANKS, 21),
+    ("minecraft:oak_sapling", OAK_SAPLING, 23),
+    ("minecraft:spruce_sapling", SPRUCE_SAPLING, 24),
+    ("minecraft:jungle_sapling", JUNGLE_SAPLING, 26),
+    ("minecraft:bedrock", BEDROCK, 31),
+    ("minecraft:water", WATER, 32),
+    ("minecraft:lava", LAVA, 33),
+    ("minecraft:sand", SAND, 34),
+    ("minecraft:red_sand", RED_SAND, 36),
+    ("minecraft:gravel", GRAVEL, 37),
+    ("minecraft:gold_ore", GOLD_ORE, 39),
+    ("minecraft:deepslate_gold_ore", DEEPSLATE_GOLD_ORE, 40),
+    ("minecraft:iron_ore", IRON_ORE, 41),
+    ("minecraft:deepslate_iron_ore", DEEPSLATE_IRON_ORE, 42),
+    ("minecraft:coal_ore", COAL_ORE, 43),
+    ("minecraft:deepslate_coal_ore", DEEPSLATE_COAL_ORE, 44),
+    ("minecraft:oak_log", OAK_LOG, 46),
+    ("minecraft:spruce_log", SPRUCE_LOG, 47),
+    ("minecraft:birch_log", BIRCH_LOG, 48),
+    ("minecraft:jungle_log", JUNGLE_LOG, 49),
+    ("minecraft:acacia_log", ACACIA_LOG, 50),
+    ("minecraft:cherry_log", CHERRY_LOG, 51),
+    ("minecraft:dark_oak_log", DARK_OAK_LOG, 52),
+    ("minecraft:oak_leaves", OAK_LEAVES, 82),
+    ("minecraft:spruce_leaves", SPRUCE_LEAVES, 83),
+    ("minecraft:jungle_leaves", JUNGLE_LEAVES, 85),
+    ("minecraft:glass", GLASS, 94),
+    ("minecraft:lapis_ore", LAPIS_ORE, 95),
+    ("minecraft:deepslate_lapis_ore", DEEPSLATE_LAPIS_ORE, 96),
+    ("minecraft:lapis_block", LAPIS_BLOCK, 97),
+    ("minecraft:white_bed", WHITE_BED, 103),
+    ("minecraft:orange_bed", ORANGE_BED, 104),
+    ("minecraft:magenta_bed", MAGENTA_BED, 105),
+    ("minecraft:light_blue_bed", LIGHT_BLUE_BED, 106),
+    ("minecraft:yellow_bed", YELLOW_BED, 107),
+    ("minecraft:lime_bed", LIME_BED, 108),
+    ("minecraft:pink_bed", PINK_BED, 109),
+    ("minecraft:gray_bed", GRAY_BED, 110),
+    ("minecraft:light_gray_bed", LIGHT_GRAY_BED, 111),
+    ("minecraft:cyan_bed", CYAN_BED, 112),
+    ("minecraft:purple_bed", PURPLE_BED, 113),
+    ("minecraft:blue_bed", BLUE_BED, 114),
+    ("minecraft:brown_bed", BROWN_BED, 115),
+    ("minecraft:green_bed", GREEN_BED, 116),
+    ("minecraft:red_bed", RED_BED, 117),
+    ("minecraft:black_bed", BLACK_BED, 118),
+    ("minecraft:sticky_piston", STICKY_PISTON, 121),
+    ("minecraft:piston", PISTON, 128),
+    ("minecraft:piston_head", PISTON_HEAD, 129),
+    ("minecraft:moving_piston", MOVING_PISTON, 146),
+    ("minecraft:gold_block", GOLD_BLOCK, 163),
+    ("minecraft:iron_block", IRON_BLOCK, 164),
+    ("minecraft:bricks", BRICKS, 165),
+    ("minecraft:tnt", TNT, 166),
+    ("minecraft:bookshelf", BOOKSHELF, 167),
+    ("minecraft:obsidian", OBSIDIAN, 170),
+    ("minecraft:torch", TORCH, 171),
+    ("minecraft:wall_torch", WALL_TORCH, 172),
+    ("minecraft:fire", FIRE, 173),
+    ("minecraft:soul_fire", SOUL_FIRE, 174),
+    ("minecraft:spawner", SPAWNER, 175),
+    ("minecraft:oak_stairs", OAK_STAIRS, 176),
+    ("minecraft:chest", CHEST, 177),
+    ("minecraft:diamond_ore", DIAMOND_ORE, 179),
+    ("minecraft:deepslate_diamond_ore", DEEPSLATE_DIAMOND_ORE, 180),
+    ("minecraft:diamond_block", DIAMOND_BLOCK, 181),
+    ("minecraft:crafting_table", CRAFTING_TABLE, 182),
+    ("minecraft:wheat", WHEAT, 183),
+    ("minecraft:farmland", FARMLAND, 184),
+    ("minecraft:furnace", FURNACE, 185),
+    ("minecraft:oak_sign", OAK_SIGN, 186),
+    ("minecraft:spruce_sign", SPRUCE_SIGN, 187),
+    ("minecraft:birch_sign", BIRCH_SIGN, 188),
+    ("minecraft:acacia_sign", ACACIA_SIGN, 189),
+    ("minecraft:cherry_sign", CHERRY_SIGN, 190),
+    ("minecraft:jungle_sign", JUNGLE_SIGN, 191),
+    ("minecraft:dark_oak_sign", DARK_OAK_SIGN, 192),
+    ("minecraft:mangrove_sign", MANGROVE_SIGN, 193),
+    ("minecraft:bamboo_sign", BAMBOO_SIGN, 194),
+    ("minecraft:oak_door", OAK_DOOR, 195),
+    ("minecraft:cobblestone_stairs", COBBLESTONE_STAIRS, 198),
+    ("minecraft:oak_wall_sign", OAK_WALL_SIGN, 199),
+    ("minecraft:spruce_wall_sign", SPRUCE_WALL_SIGN, 200),
+    ("minecraft:birch_wall_sign", BIRCH_WALL_SIGN, 201),
+    ("minecraft:acacia_wall_sign", ACACIA_WALL_SIGN, 202),
+    ("minecraft:cherry_wall_sign", CHERRY_WALL_SIGN, 203),
+    ("minecraft:jungle_wall_sign", JUNGLE_WALL_SIGN, 204),
+    ("minecraft:dark_oak_wall_sign", DARK_OAK_WALL_SIGN, 205),
+    ("minecraft:mangrove_wall_sign", MANGROVE_WALL_SIGN, 206),
+    ("minecraft:bamboo_wall_sign", BAMBOO_WALL_SIGN, 207),
+    ("minecraft:lever", LEVER, 230),
+    ("minecraft:stone_pressure_plate", STONE_PRESSURE_PLATE, 231),
+    ("minecraft:iron_door", IRON_DOOR, 232),
+    ("minecraft:oak_pressure_plate", OAK_PRESSURE_PLATE, 233),
+    ("minecraft:redstone_ore", REDSTONE_ORE, 242),
+    ("minecraft:deepslate_redstone_ore", DEEPSLATE_REDSTONE_ORE, 243),
+    ("minecraft:stone_button", STONE_BUTTON, 246),
+    ("minecraft:snow", SNOW, 247),
+    ("minecraft:ice", ICE, 248),
+    ("minecraft:snow_block", SNOW_BLOCK, 249),
+    ("minecraft:glowstone", GLOWSTONE, 262),
+    ("minecraft:jack_o_lantern", JACK_O_LANTERN, 265),
+    ("minecraft:oak_trapdoor", OAK_TRAPDOOR, 284),
+    ("minecraft:stone_bricks", STONE_BRICKS, 293),
+    ("minecraft:mossy_stone_bricks", MOSSY_STONE_BRICKS, 294),
+    ("minecraft:cracked_stone_bricks", CRACKED_STONE_BRICKS, 295),
+    ("minecraft:chiseled_stone_bricks", CHISELED_STONE_BRICKS, 296),
+    ("minecraft:glass_pane", GLASS_PANE, 310),
+    ("minecraft:oak_fence_gate", OAK_FENCE_GATE, 319),
+    ("minecraft:stone_brick_stairs", STONE_BRICK_STAIRS, 321),
+    ("minecraft:enchanting_table", ENCHANTING_TABLE, 329),
+    ("minecraft:end_portal", END_PORTAL, 335),
+    ("minecraft:end_stone", END_STONE, 337),
+    ("minecraft:dragon_egg", DRAGON_EGG, 338),
+    ("minecraft:emerald_ore", EMERALD_ORE, 342),
+    ("minecraft:deepslate_emerald_ore", DEEPSLATE_EMERALD_ORE, 343),
+    ("minecraft:ender_chest", ENDER_CHEST, 344),
+    ("minecraft:tripwire_hook", TRIPWIRE_HOOK, 345),
+    ("minecraft:tripwire", TRIPWIRE, 346),
+    ("minecraft:emerald_block", EMERALD_BLOCK, 347),
+    ("minecraft:carrots", CARROTS, 383),
+    ("minecraft:potatoes", POTATOES, 384),
+    ("minecraft:oak_button", OAK_BUTTON, 385),
+    ("minecraft:anvil", ANVIL, 408),
+    ("minecraft:chipped_anvil", CHIPPED_ANVIL, 409),
+    ("minecraft:damaged_anvil", DAMAGED_ANVIL, 410),
+    ("minecraft:light_weighted_pressure_plate", LIGHT_WEIGHTED_PRESSURE_PLATE, 412),
+    ("minecraft:heavy_weighted_pressure_plate", HEAVY_WEIGHTED_PRESSURE_PLATE, 413),
+    ("minecraft:hopper", HOPPER, 418),
+    ("minecraft:slime_block", SLIME_BLOCK, 463),
+    ("minecraft:barrel", BARREL, 774),
+    ("minecraft:cartography_table", CARTOGRAPHY_TABLE, 777),
+    ("minecraft:smithing_table", SMITHING_TABLE, 781),
+    ("minecraft:stonecutter", STONECUTTER, 782),
+    ("minecraft:honey_block", HONEY_BLOCK, 838),
+];
+
+// Ohne Kollisionsformen gilt alles außer Luft, Flüssigkeiten, Feuer, Schildern und den Blöcken hier als voller Block
+const PASSABLE: &[&str] = &[
+    "minecraft:air", "minecraft:end_portal", "minecraft:wheat", "minecraft:carrots", "minecraft:potatoes", "minecraft:oak_sapling",
+    "minecraft:spruce_sapling", "minecraft:jungle_sapling", "minecraft:torch", "minecraft:wall_torch", "minecraft:unknown",
+    "minecraft:lever", "minecraft:stone_button", "minecraft:oak_button", "minecraft:stone_pressure_plate", "minecraft:oak_pressure_plate",
+    "minecraft:light_weighted_pressure_plate", "minecraft:heavy_weighted_pressure_plate", "minecraft:tripwire_hook", "minecraft:tripwire",
+    "minecraft:moving_piston",
+];
+
+// Explosionswiderstand wie bei Vanilla; Schilder halten 1.0, Betten 0.2, was fehlt, so viel wie Stein
+const BLAST_RESISTANCE: &[(&str, f32)] = &[
+    ("minecraft:air", 0.0), ("minecraft:grass_block", 0.6), ("minecraft:dirt", 0.5), ("minecraft:oak_planks", 3.0),
+    ("minecraft:bedrock", 3_600_000.0), ("minecraft:water", 100.0), ("minecraft:lava", 100.0), ("minecraft:sand", 0.5),
+    ("minecraft:gravel", 0.6), ("minecraft:oak_log", 2.0), ("minecraft:oak_leaves", 0.2), ("minecraft:tnt", 0.0),
+    ("minecraft:obsidian", 1200.0), ("minecraft:chest", 2.5), ("minecraft:furnace", 3.5), ("minecraft:end_portal", 3_600_000.0),
+    ("minecraft:end_stone", 9.0), ("minecraft:dragon_egg", 9.0), ("minecraft:ender_chest", 600.0), ("minecraft:anvil", 1200.0),
+    ("minecraft:chipped_anvil", 1200.0), ("minecraft:damaged_anvil", 1200.0), ("minecraft:hopper", 4.8), ("minecraft:fire", 0.0),
+    ("minecraft:soul_fire", 0.0), ("minecraft:wheat", 0.0), ("minecraft:farmland", 0.6), ("minecraft:carrots", 0.0),
+    ("minecraft:potatoes", 0.0), ("minecraft:oak_sapling", 0.0), ("minecraft:spruce_sapling", 0.0), ("minecraft:jungle_sapling", 0.0),
+    ("minecraft:spruce_log", 2.0), ("minecraft:jungle_log", 2.0), ("minecraft:spruce_leaves", 0.2), ("minecraft:jungle_leaves", 0.2),
+    ("minecraft:spawner", 5.0), ("minecraft:torch", 0.0), ("minecraft:wall_torch", 0.0), ("minecraft:snow", 0.1), ("minecraft:ice", 0.5),
+    ("minecraft:snow_block", 0.2), ("minecraft:glowstone", 0.3), ("minecraft:jack_o_lantern", 1.0),
+    ("minecraft:oak_door", 3.0), ("minecraft:iron_door", 5.0), ("minecraft:oak_trapdoor", 3.0), ("minecraft:oak_fence_gate", 3.0),
+    ("minecraft:lever", 0.5), ("minecraft:stone_button", 0.5), ("minecraft:oak_button", 0.5), ("minecraft:stone_pressure_plate", 0.5),
+    ("minecraft:oak_pressure_plate", 0.5), ("minecraft:light_weighted_pressure_plate", 0.5), ("minecraft:heavy_weighted_pressure_plate", 0.5),
+    ("minecraft:tripwire_hook", 0.0), ("minecraft:tripwire", 0.0), ("minecraft:piston", 1.5), ("minecraft:sticky_piston", 1.5),
+    ("minecraft:piston_head", 1.5), ("minecraft:slime_block", 0.0), ("minecraft:honey_block", 0.0), ("minecraft:coarse_dirt", 0.5),
+    ("minecraft:podzol", 0.5), ("minecraft:spruce_planks", 3.0), ("minecraft:birch_planks", 3.0), ("minecraft:jungle_planks", 3.0),
+    ("minecraft:acacia_planks", 3.0), ("minecraft:cherry_planks", 3.0), ("minecraft:dark_oak_planks", 3.0), ("minecraft:mangrove_planks", 3.0),
+    ("minecraft:bamboo_planks", 3.0), ("minecraft:red_sand", 0.5), ("minecraft:gold_ore", 3.0), ("minecraft:deepslate_gold_ore", 3.0),
+    ("minecraft:iron_ore", 3.0), ("minecraft:deepslate_iron_ore", 3.0), ("minecraft:coal_ore", 3.0), ("minecraft:deepslate_coal_ore", 3.0),
+    ("minecraft:birch_log", 2.0), ("minecraft:acacia_log", 2.0), ("minecraft:cherry_log", 2.0), ("minecraft:dark_oak_log", 2.0),
+    ("minecraft:glass", 0.3), ("minecraft:lapis_ore", 3.0), ("minecraft:deepslate_lapis_ore", 3.0), ("minecraft:lapis_block", 3.0),
+    ("minecraft:bookshelf", 1.5), ("minecraft:oak_stairs", 3.0), ("minecraft:diamond_ore", 3.0), ("minecraft:deepslate_diamond_ore", 3.0),
+    ("minecraft:crafting_table", 2.5), ("minecraft:redstone_ore", 3.0), ("minecraft:deepslate_redstone_ore", 3.0), ("minecraft:glass_pane", 0.3),
+    ("minecraft:enchanting_table", 1200.0), ("minecraft:emerald_ore", 3.0), ("minecraft:deepslate_emerald_ore", 3.0), ("minecraft:barrel", 2.5),
+    ("minecraft:cartography_table", 2.5), ("minecraft:smithing_table", 2.5), ("minecraft:stonecutter", 3.5),
+];
+const DEFAULT_BLAST_RESISTANCE: f32 = 6.0;
+
+// Wie leicht ein Block Feuer fängt und wie schnell er verbrennt, wie bei Vanilla; alles andere brennt nicht
+const FLAMMABILITY: &[(&str, u32, u32)] = &[
+    ("minecraft:oak_planks", 5, 20), ("minecraft:oak_log", 5, 5), ("minecraft:oak_leaves", 30, 60), ("minecraft:tnt", 15, 100),
+    ("minecraft:spruce_log", 5, 5), ("minecraft:jungle_log", 5, 5), ("minecraft:spruce_leaves", 30, 60), ("minecraft:jungle_leaves", 30, 60),
+    ("minecraft:oak_fence_gate", 5, 20), ("minecraft:spruce_planks", 5, 20), ("minecraft:birch_planks", 5, 20), ("minecraft:jungle_planks", 5, 20),
+    ("minecraft:acacia_planks", 5, 20), ("minecraft:cherry_planks", 5, 20), ("minecraft:dark_oak_planks", 5, 20), ("minecraft:mangrove_planks", 5, 20),
+    ("minecraft:bamboo_planks", 5, 20), ("minecraft:birch_log", 5, 5), ("minecraft:acacia_log", 5, 5), ("minecraft:cherry_log", 5, 5),
+    ("minecraft:dark_oak_log", 5, 5), ("minecraft:bookshelf", 30, 20), ("minecraft:oak_stairs", 5, 20),
+];
+
+// Wie Kolben mit Blöcken umgehen, wie bei Vanilla: diese gehen kaputt statt geschoben zu werden, dazu Flüssigkeiten,
+// Feuer, Betten und Türen
+const PUSH_DESTROY: &[&str] = &[
+    "minecraft:wheat", "minecraft:carrots", "minecraft:potatoes", "minecraft:oak_sapling", "minecraft:spruce_sapling",
+    "minecraft:jungle_sapling", "minecraft:torch", "minecraft:wall_torch", "minecraft:oak_leaves", "minecraft:spruce_leaves",
+    "minecraft:jungle_leaves", "minecraft:lever", "minecraft:stone_button", "minecraft:oak_button", "minecraft:stone_pressure_plate",
+    "minecraft:oak_pressure_plate", "minecraft:light_weighted_pressure_plate", "minecraft:heavy_weighted_pressure_plate",
+    "minecraft:tripwire_hook", "minecraft:tripwire", "minecraft:snow", "minecraft:dragon_egg",
+];
+// Und diese halten sie auf, ebenso ausgefahrene Kolben
+const PUSH_BLOCK: &[&str] = &[
+    "minecraft:obsidian", "minecraft:bedrock", "minecraft:end_portal", "minecraft:piston_head", "minecraft:moving_piston",
+    "minecraft:anvil", "minecraft:chipped_anvil", "minecraft:damaged_anvil",
+];
+
+// Blocklicht, das ein Block abgibt, wie bei Vanilla; ein Ofen leuchtet nur, solange er brennt
+const LIGHT_EMISSION: &[(&str, u8)] = &[
+    ("minecraft:lava", 15), ("minecraft:fire", 15), ("minecraft:soul_fire", 10), ("minecraft:torch", 14), ("minecraft:wall_torch", 14),
+    ("minecraft:glowstone", 15), ("minecraft:jack_o_lantern", 15), ("minecraft:end_portal", 15), ("minecraft:enchanting_table", 7),
+];
+const LIT_FURNACE_LIGHT: u8 = 13;
+
+// Schlucken je eine Stufe Licht; andere volle Blöcke alles, Betten und die Blöcke unten gar nichts
+const LIGHT_FILTERING: &[&str] = &[
+    "minecraft:water", "minecraft:ice", "minecraft:oak_leaves", "minecraft:spruce_leaves", "minecraft:jungle_leaves",
+    "minecraft:slime_block", "minecraft:honey_block",
+];
+const LIGHT_TRANSPARENT: &[&str] = &[
+    "minecraft:chest", "minecraft:ender_chest", "minecraft:anvil", "minecraft:chipped_anvil", "minecraft:damaged_anvil",
+    "minecraft:hopper", "minecraft:snow", "minecraft:dragon_egg", "minecraft:spawner", "minecraft:oak_door", "minecraft:iron_door",
+    "minecraft:oak_trapdoor", "minecraft:oak_fence_gate", "minecraft:lever", "minecraft:stone_button", "minecraft:oak_button",
+    "minecraft:stone_pressure_plate", "minecraft:oak_pressure_plate", "minecraft:light_weighted_pressure_plate",
+    "minecraft:heavy_weighted_pressure_plate", "minecraft:tripwire_hook", "minecraft:tripwire",
+    "minecraft:piston_head", "minecraft:moving_piston", "minecraft:glass", "minecraft:glass_pane", "minecraft:enchanting_table",
+    "minecraft:stonecutter",
+];
+
+// Eine Block-Eigenschaft mit ihren Werten in Register-Reihenfolge und dem Index des Standardwerts
+struct Property {
+    name: &'static str,
+    values: &'static [&'static str],
+    default: usize,
+}
+
+const LEVELS: &[&str] = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15"];
+const BOOLEANS: &[&str] = &["true", "false"];
+const HORIZONTAL: &[&str] = &["north", "south", "west", "east"];
+const DIRECTIONS: &[&str] = &["down", "up", "north", "south", "west", "east"];
+const PISTON_TYPE: &[&str] = &["normal", "sticky"];
+const SAPLING: &[Property] = &[Property { name: "stage", values: &["0", "1"], default: 0 }];
+const LOG: &[Property] = &[Property { name: "axis", values: &["x", "y", "z"], default: 1 }];
+const LEAVES: &[Property] = &[
+    Property { name: "distance", values: &["1", "2", "3", "4", "5", "6", "7"], default: 6 },
+    Property { name: "persistent", values: BOOLEANS, default: 1 },
+    Property { name: "waterlogged", values: BOOLEANS, default: 1 },
+];
+// Alle Farben teilen sich die Eigenschaften ihrer Betten
+const BED: &[Property] = &[
+    Property { name: "facing", values: HORIZONTAL, default: 0 },
+    Property { name: "occupied", values: BOOLEANS, default: 1 },
+    Property { name: "part", values: &["head", "foot"], default: 1 },
+];
+const DOOR: &[Property] = &[
+    Property { name: "facing", values: HORIZONTAL, default: 0 },
+    Property { name: "half", values: &["upper", "lower"], default: 1 },
+    Property { name: "hinge", values: &["left", "right"], default: 0 },
+    Property { name: "open", values: BOOLEANS, default: 1 },
+    Property { name: "powered", values: BOOLEANS, default: 1 },
+];
+const TRAPDOOR: &[Property] = &[
+    Property { name: "facing", values: HORIZONTAL, default: 0 },
+    Property { name: "half", values: &["top", "bottom"], default: 1 },
+    Property { name: "open", values: BOOLEANS, default: 1 },
+    Property { name: "powered", values: BOOLEANS, default: 1 },
+    Property { name: "waterlogged", values: BOOLEANS, default: 1 },
+];
+const FENCE_GATE: &[Property] = &[
+    Property { name: "facing", values: HORIZONTAL, default: 0 },
+    Property { name: "in_wall", values: BOOLEANS, default: 1 },
+    Property { name: "open", values: BOOLEANS, default: 1 },
+    Property { name: "powered", values: BOOLEANS, default: 1 },
+];
+// Hebel und alle Knöpfe
+const SWITCH: &[Property] = &[
+    Property { name: "face", values: &["floor", "wall", "ceiling"], default: 1 },
+    Property { name: "facing", values: HORIZONTAL, default: 0 },
+    Property { name: "powered", values: BOOLEANS, default: 1 },
+];
+const PRESSURE_PLATE: &[Property] = &[Property { name: "powered", values: BOOLEANS, default: 1 }];
+// Beschwerte Druckplatten geben je nach Gewicht Strom ab
+const WEIGHTED_PRESSURE_PLATE: &[Property] = &[Property { name: "power", values: LEVELS, default: 0 }];
+const PISTON_BASE: &[Property] = &[Property { name: "extended", values: BOOLEANS, default: 1 }, Property { name: "facing", values: DIRECTIONS, default: 2 }];
+const CROP_AGE: &[Property] = &[Property { name: "age", values: &["0", "1", "2", "3", "4", "5", "6", "7"], default: 0 }];
+// Alle Holzarten teilen sich die Eigenschaften ihrer Schilder
+const STANDING_SIGN: &[Property] = &[Property { name: "rotation", values: LEVELS, default: 0 }, Property { name: "waterlogged", values: BOOLEANS, default: 1 }];
+const WALL_SIGN: &[Property] = &[Property { name: "facing", values: HORIZONTAL, default: 0 }, Property { name: "waterlogged", values: BOOLEANS, default: 1 }];
+const ANVIL_FACING: &[Property] = &[Property { name: "facing", values: HORIZONTAL, default: 0 }];
+// Alle Treppen teilen sich diese Eigenschaften
+const STAIRS: &[Property] = &[
+    Property { name: "facing", values: HORIZONTAL, default: 0 },
+    Property { name: "half", values: &["top", "bottom"], default: 1 },
+    Property { name: "shape", values: &["straight", "inner_left", "inner_right", "outer_left", "outer_right"], default: 0 },
+    Property { name: "waterlogged", values: BOOLEANS, default: 1 },
+];
+const REDSTONE_ORE_LIT: &[Property] = &[Property { name: "lit", values: BOOLEANS, default: 1 }];
+
+// Die Zustände eines Blocks liegen hintereinander im Register, die letzte Eigenschaft ändert sich am schnellsten
+const PROPERTIES: &[(&str, &[Property])] = &[
+    ("minecraft:grass_block", &[Property { name: "snowy", values: BOOLEANS, default: 1 }]),
+    ("minecraft:podzol", &[Property { name: "snowy", values: BOOLEANS, default: 1 }]),
+    ("minecraft:water", &[Property { name: "level", values: LEVELS, default: 0 }]),
+    ("minecraft:lava", &[Property { name: "level", values: LEVELS, default: 0 }]),
+    ("minecraft:oak_sapling", SAPLING),
+    ("minecraft:spruce_sapling", SAPLING),
+    ("minecraft:jungle_sapling", SAPLING),
+    ("minecraft:oak_log", LOG),
+    ("minecraft:spruce_log", LOG),
+    ("minecraft:jungle_log", LOG),
+    ("minecraft:birch_log", LOG),
+    ("minecraft:acacia_log", LOG),
+    ("minecraft:cherry_log", LOG),
+    ("minecraft:dark_oak_log", LOG),
+    ("minecraft:white_bed", BED),
+    ("minecraft:orange_bed", BED),
+    ("minecraft:magenta_bed", BED),
+    ("minecraft:light_blue_bed", BED),
+    ("minecraft:yellow_bed", BED),
+    ("minecraft:lime_bed", BED),
+    ("minecraft:pink_bed", BED),
+    ("minecraft:gray_bed", BED),
+    ("minecraft:light_gray_bed", BED),
+    ("minecraft:cyan_bed", BED),
+    ("minecraft:purple_bed", BED),
+    ("minecraft:blue_bed", BED),
+    ("minecraft:brown_bed", BED),
+    ("minecraft:green_bed", BED),
+    ("minecraft:red_bed", BED),
+    ("minecraft:black_bed", BED),
+    ("minecraft:sticky_piston", PISTON_BASE),
+    ("minecraft:piston", PISTON_BASE),
+    (
+        "minecraft:piston_head",
+        &[
+            Property { name: "facing", values: DIRECTIONS, default: 2 },
+            Property { name: "short", values: BOOLEANS, default: 1 },
+            Property { name: "type", values: PISTON_TYPE, default: 0 },
+        ],
+    ),
+    ("minecraft:moving_piston", &[Property { name: "facing", values: DIRECTIONS, default: 2 }, Property { name: "type", values: PISTON_TYPE, default: 0 }]),
+    ("minecraft:tnt", &[Property { name: "unstable", values: BOOLEANS, default: 1 }]),
+    (
+        "minecraft:fire",
+        &[
+            Property { name: "age", values: LEVELS, default: 0 },
+            Property { name: "east", values: BOOLEANS, default: 1 },
+            Property { name: "north", values: BOOLEANS, default: 1 },
+            Property { name: "south", values: BOOLEANS, default: 1 },
+            Property { name: "up", values: BOOLEANS, default: 1 },
+            Property { name: "west", values: BOOLEANS, default: 1 },
+        ],
+    ),
+    ("minecraft:oak_leaves", LEAVES),
+    ("minecraft:spruce_leaves", LEAVES),
+    ("minecraft:jungle_leaves", LEAVES),
+    ("minecraft:oak_stairs", STAIRS),
+    ("minecraft:wall_torch", &[Property { name: "facing", values: HORIZONTAL, default: 0 }]),
+    (
+        "minecraft:chest",
+        &[
+            Property { name: "facing", values: HORIZONTAL, default: 0 },
+            Property { name: "type", values: &["single", "left", "right"], default: 0 },
+            Property { name: "waterlogged", values: BOOLEANS, default: 1 },
+        ],
+    ),
+    ("minecraft:wheat", CROP_AGE),
+    ("minecraft:farmland", &[Property { name: "moisture", values: &["0", "1", "2", "3", "4", "5", "6", "7"], default: 0 }]),
+    ("minecraft:furnace", &[Property { name: "facing", values: HORIZONTAL, default: 0 }, Property { name: "lit", values: BOOLEANS, default: 1 }]),
+    ("minecraft:oak_sign", STANDING_SIGN),
+    ("minecraft:spruce_sign", STANDING_SIGN),
+    ("minecraft:birch_sign", STANDING_SIGN),
+    ("minecraft:acacia_sign", STANDING_SIGN),
+    ("minecraft:cherry_sign", STANDING_SIGN),
+    ("minecraft:jungle_sign", STANDING_SIGN),
+    ("minecraft:dark_oak_sign", STANDING_SIGN),
+    ("minecraft:mangrove_sign", STANDING_SIGN),
+    ("minecraft:bamboo_sign", STANDING_SIGN),
+    ("minecraft:oak_wall_sign", WALL_SIGN),
+    ("minecraft:spruce_wall_sign", WALL_SIGN),
+    ("minecraft:birch_wall_sign", WALL_SIGN),
+    ("minecraft:acacia_wall_sign", WALL_SIGN),
+    ("minecraft:cherry_wall_sign", WALL_SIGN),
+    ("minecraft:jungle_wall_sign", WALL_SIGN),
+    ("minecraft:dark_oak_wall_sign", WALL_SIGN),
+    ("minecraft:mangrove_wall_sign", WALL_SIGN),
+    ("minecraft:bamboo_wall_sign", WALL_SIGN),
+    ("minecraft:cobblestone_stairs", STAIRS),
+    ("minecraft:oak_door", DOOR),
+    ("minecraft:iron_door", DOOR),
+    ("minecraft:oak_trapdoor", TRAPDOOR),
+    ("minecraft:oak_fence_gate", FENCE_GATE),
+    ("minecraft:lever", SWITCH),
+    ("minecraft:stone_pressure_plate", PRESSURE_PLATE),
+    ("minecraft:oak_pressure_plate", PRESSURE_PLATE),
+    ("minecraft:stone_button", SWITCH),
+    ("minecraft:oak_button", SWITCH),
+    ("minecraft:redstone_ore", REDSTONE_ORE_LIT),
+    ("minecraft:deepslate_redstone_ore", REDSTONE_ORE_LIT),
+    ("minecraft:snow", &[Property { name: "layers", values: &["1", "2", "3", "4", "5", "6", "7", "8"], default: 0 }]),
+    ("minecraft:jack_o_lantern", &[Property { name: "facing", values: HORIZONTAL, default: 0 }]),
+    (
+        "minecraft:glass_pane",
+        &[
+            Property { name: "east", values: BOOLEANS, default: 1 },
+            Property { name: "north", values: BOOLEANS, default: 1 },
+            Property { name: "south", values: BOOLEANS, default: 1 },
+            Property { name: "waterlogged", values: BOOLEANS, default: 1 },
+            Property { name: "west", values: BOOLEANS, default: 1 },
+        ],
+    ),
+    ("minecraft:stone_brick_stairs", STAIRS),
+    ("minecraft:carrots", CROP_AGE),
+    ("minecraft:potatoes", CROP_AGE),
+    ("minecraft:ender_chest", &[Property { name: "facing", values: HORIZONTAL, default: 0 }, Property { name: "waterlogged", values: BOOLEANS, default: 1 }]),
+    ("minecraft:anvil", ANVIL_FACING),
+    ("minecraft:chipped_anvil", ANVIL_FACING),
+    (
+        "minecraft:tripwire_hook",
+        &[
+            Property { name: "attached", values: BOOLEANS, default: 1 },
+            Property { name: "facing", values: HORIZONTAL, default: 0 },
+            Property { name: "powered", values: BOOLEANS, default: 1 },
+        ],
+    ),
+    (
+        "minecraft:tripwire",
+        &[
+            Property { name: "attached", values: BOOLEANS, default: 1 },
+            Property { name: "disarmed", values: BOOLEANS, default: 1 },
+            Property { name: "east", values: BOOLEANS, default: 1 },
+            Property { name: "north", values: BOOLEANS, default: 1 },
+            Property { name: "powered", values: BOOLEANS, default: 1 },
+            Property { name: "south", values: BOOLEANS, default: 1 },
+            Property { name: "west", values: BOOLEANS, default: 1 },
+        ],
+    ),
+    ("minecraft:damaged_anvil", ANVIL_FACING),
+    ("minecraft:light_weighted_pressure_plate", WEIGHTED_PRESSURE_PLATE),
+    ("minecraft:heavy_weighted_pressure_plate", WEIGHTED_PRESSURE_PLATE),
+    (
+        "minecraft:hopper",
+        &[
+            Property { name: "enabled", values: BOOLEANS, default: 0 },
+            Property { name: "facing", values: &["down", "north", "south", "west", "east"], default: 0 },
+        ],
+    ),
+    ("minecraft:barrel", &[Property { name: "facing", values: DIRECTIONS, default: 2 }, Property { name: "open", values: BOOLEANS, default: 1 }]),
+    ("minecraft:stonecutter", &[Property { name: "facing", values: HORIZONTAL, default: 0 }]),
+];
+
+fn properties(name: &str) -> &'static [Property] {
+    PROPERTIES.iter().find(|(n, _)| *n == name).map_or(&[], |(_, properties)| properties)
+}
+
+// Abstand zwischen zwei Werten jeder Eigenschaft im Register und die Gesamtzahl der Zustände
+fn strides(properties: &[Property]) -> (Vec<u16>, u16) {
+    let mut strides = vec![0; properties.len()];
+    let mut stride = 1;
+    for (i, property) in properties.iter().enumerate().rev() {
+        strides[i] = stride;
+        stride *= property.values.len() as u16;
+    }
+    (strides, stride)
+}
+
+// Erster Zustand eines Blocks und Anzahl seiner Zustände
+fn state_range(name: &str, default_state: u16) -> (u16, u16) {
+    let properties = properties(name);
+    let (strides, count) = strides(properties);
+    let offset: u16 = properties.iter().zip(&strides).map(|(p, stride)| p.default as u16 * stride).sum();
+    (default_state - offset, count)
+}
+
+pub fn state_id(name: &str) -> Option<u16> {
+    let name = if name.contains(':') { name.to_string() } else { format!("minecraft:{}", name) };
+    BLOCKS.iter().find(|(n, _, _)| *n == name).map(|(_, id, _)| *id)
+}
+
+// Block-Zustand aus Befehlen: stone, minecraft:oak_log[axis=x]; fehlende Eigenschaften behalten ihren Standardwert
+pub fn parse_state(spec: &str) -> Result<u16, String> {
+    let (name, properties_spec) = match spec.split_once('[') {
+        Some((name, rest)) => (name, Some(rest.strip_suffix(']').ok_or("Expected ']' at the end of the block state")?)),
+        None => (spec, None),
+    };
+    let name = if name.contains(':') { name.to_ascii_lowercase() } else { format!("minecraft:{}", name.to_ascii_lowercase()) };
+    let default_state = state_id(&name).ok_or_else(|| format!("Unknown block type '{}'", name))?;
+    let properties = properties(&name);
+    let mut indices: Vec<usize> = properties.iter().map(|p| p.default).collect();
+    for entry in properties_spec.into_iter().flat_map(|s| s.split(',')).map(str::trim).filter(|e| !e.is_empty()) {
+        let (key, value) = entry.split_once('=').ok_or_else(|| format!("Expected property=value, got '{}'", entry))?;
+        let (key, value) = (key.trim(), value.trim());
+        let i = properties.iter().position(|p| p.name == key).ok_or_else(|| format!("Block {} does not have property '{}'", name, key))?;
+        indices[i] = properties[i].values.iter().position(|v| *v == value).ok_or_else(|| format!("Block {} does not accept '{}' for {}", name, value, key))?;
+    }
+    let (first, _) = state_range(&name, default_state);
+    let (strides, _) = strides(properties);
+    Ok(first + indices.iter().zip(&strides).map(|(i, stride)| *i as u16 * stride).sum::<u16>())
+}
+
+// Block-Prädikat für /execute if block: stone passt auf jeden Zustand von Stein, oak_log[axis=x]
+// prüft nur die angegebenen Eigenschaften
+pub fn matches(state: u16, spec: &str) -> Result<bool, String> {
+    let (spec_name, properties_spec) = match spec.split_once('[') {
+        Some((name, rest)) => (name, rest.strip_suffix(']').ok_or("Expected ']' at the end of the block state")?),
+        None => (spec, ""),
+    };
+    parse_state(spec)?;
+    let name = name(state);
+    let wanted = if spec_name.contains(':') { spec_name.to_ascii_lowercase() } else { format!("minecraft:{}", spec_name.to_ascii_lowercase()) };
+    if name != wanted {
+        return Ok(false);
+    }
+    let properties = properties(name);
+    let (first, _) = state_range(name, state_id(name).unwrap_or(state));
+    let (strides, _) = strides(properties);
+    for entry in properties_spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let Some((key, value)) = entry.split_once('=') else { continue };
+        let Some(i) = properties.iter().position(|p| p.name == key.trim()) else { continue };
+        let index = ((state - first) / strides[i]) as usize % properties[i].values.len();
+        if properties[i].values[index] != value.trim() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Wert einer Eigenschaft eines Zustands, etwa facing eines Trichters
+pub fn property(state: u16, key: &str) -> Option<&'static str> {
+    let name = name(state);
+    let properties = properties(name);
+    let i = properties.iter().position(|p| p.name == key)?;
+    let (first, _) = state_range(name, state_id(name)?);
+    let (strides, _) = strides(properties);
+    Some(properties[i].values[((state - first) / strides[i]) as usize % properties[i].values.len()])
+}
+
+// Derselbe Block mit geändertem Wert einer Eigenschaft; None, wenn es sie oder den Wert nicht gibt
+pub fn with_property(state: u16, key: &str, value: &str) -> Option<u16> {
+    let name = name(state);
+    let properties = properties(name);
+    let i = properties.iter().position(|p| p.name == key)?;
+    let wanted = properties[i].values.iter().position(|v| *v == value)? as u16;
+    let (first, _) = state_range(name, state_id(name)?);
+    let (strides, _) = strides(properties);
+    let current = (state - first) / strides[i] % properties[i].values.len() as u16;
+    Some(state - current * strides[i] + wanted * strides[i])
+}
+
+pub fn name(state: u16) -> &'static str {
+    BLOCKS
+        .iter()
+        .find(|(n, id, _)| {
+            let (first, count) = state_range(n, *id);
+            (first..first + count).contains(&state)
+        })
+        .map(|(n, _, _)| *n)
+        .unwrap_or("minecraft:unknown")
+}
+
+// Name, erster Zustand und Anzahl der Zustände aller bekannten Blöcke
+pub fn state_ranges() -> impl Iterator<Item = (&'static str, u16, u16)> {
+    BLOCKS.iter().map(|(name, default_state, _)| {
+        let (first, count) = state_range(name, *default_state);
+        (*name, first, count)
+    })
+}
+
+pub fn registry_id(name: &str) -> Option<i32> {
+    BLOCKS.iter().find(|(n, _, _)| *n == name).map(|(_, _, id)| *id)
+}
+
+pub fn registry_id_in(name: &str, version: ProtocolVersion) -> Option<i32> {
+    assets::block_id(version, name).unwrap_or_else(|| registry_id(name))
+}
+
+pub fn is_fluid(state: u16) -> bool {
+    matches!(name(state), "minecraft:water" | "minecraft:lava")
+}
+
+pub fn is_water(state: u16) -> bool {
+    name(state) == "minecraft:water"
+}
+
+pub fn is_lava(state: u16) -> bool {
+    name(state) == "minecraft:lava"
+}
+
+// Stehende und Wandschilder aller Holzarten
+pub fn is_sign(state: u16) -> bool {
+    name(state).ends_with("_sign")
+}
+
+// Kopf- und Fußteil der Betten in allen Farben
+pub fn is_bed(state: u16) -> bool {
+    name(state).ends_with("_bed")
+}
+
+// Schneeschichten, nicht der volle Schneeblock. Wie bei Vanilla zählen sie nicht für MOTION_BLOCKING, damit neuer
+// Schnee auf dieselbe Schicht fällt
+pub fn is_snow(state: u16) -> bool {
+    name(state) == "minecraft:snow"
+}
+
+// Türen, Falltüren und Zauntore lassen sich öffnen; offen hält keiner davon auf
+pub fn is_door(state: u16) -> bool {
+    name(state).ends_with("_door")
+}
+
+pub fn is_trapdoor(state: u16) -> bool {
+    name(state).ends_with("_trapdoor")
+}
+
+pub fn is_fence_gate(state: u16) -> bool {
+    name(state).ends_with("_fence_gate")
+}
+
+pub fn is_pressure_plate(state: u16) -> bool {
+    name(state).ends_with("_pressure_plate")
+}
+
+pub fn is_open(state: u16) -> bool {
+    property(state, "open") == Some("true")
+}
+
+pub fn is_fire(state: u16) -> bool {
+    matches!(name(state), "minecraft:fire" | "minecraft:soul_fire")
+}
+
+pub fn is_solid(state: u16) -> bool {
+    !is_fluid(state) && !is_sign(state) && !is_fire(state) && !is_open(state) && !PASSABLE.contains(&name(state))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PushReaction {
+    Normal,
+    Destroy,
+    Block,
+}
+
+// Blöcke mit Block-Entity bewegt ein Kolben auch nicht, das prüft aber erst der Server
+pub fn push_reaction(state: u16) -> PushReaction {
+    let name = name(state);
+    if PUSH_BLOCK.contains(&name) || is_piston(state) && property(state, "extended") == Some("true") {
+        PushReaction::Block
+    } else if is_fluid(state) || is_fire(state) || is_bed(state) || is_door(state) || PUSH_DESTROY.contains(&name) {
+        PushReaction::Destroy
+    } else {
+        PushReaction::Normal
+    }
+}
+
+pub fn is_piston(state: u16) -> bool {
+    matches!(name(state), "minecraft:piston" | "minecraft:sticky_piston")
+}
+
+// Oberkante der Kollision innerhalb des Blocks; bisher sind nur Ackerboden, Betten, Schnee und untere Falltüren
+// niedriger als ein voller Block. Eine einzelne Schneeschicht hält nichts auf
+pub fn collision_height(state: u16) -> f64 {
+    if let Some(layers) = property(state, "layers").and_then(|l| l.parse::<f64>().ok()) {
+        (layers - 1.0) / 8.0
+    } else if is_bed(state) {
+        9.0 / 16.0
+    } else if name(state) == "minecraft:farmland" {
+        15.0 / 16.0
+    } else if is_trapdoor(state) && property(state, "half") == Some("bottom") {
+        3.0 / 16.0
+    } else {
+        1.0
+    }
+}
+
+pub fn blast_resistance(state: u16) -> f32 {
+    if is_sign(state) {
+        return 1.0;
+    }
+    if is_bed(state) {
+        return 0.2;
+    }
+    let name = name(state);
+    BLAST_RESISTANCE.iter().find(|(n, _)| *n == name).map_or(DEFAULT_BLAST_RESISTANCE, |&(_, resistance)| resistance)
+}
+
+pub fn light_emission(state: u16) -> u8 {
+    let name = name(state);
+    if name == "minecraft:furnace" {
+        return if property(state, "lit") == Some("true") { LIT_FURNACE_LIGHT } else { 0 };
+    }
+    LIGHT_EMISSION.iter().find(|(n, _)| *n == name).map_or(0, |&(_, light)| light)
+}
+
+// Wie viele Stufen Licht beim Durchqueren des Blocks verloren gehen, bis 15 für alles Undurchsichtige
+pub fn light_opacity(state: u16) -> u8 {
+    let name = name(state);
+    if LIGHT_FILTERING.contains(&name) {
+        1
+    } else if !is_solid(state) || is_bed(state) || LIGHT_TRANSPARENT.contains(&name) {
+        0
+    } else {
+        15
+    }
+}
+
+// Chance, Feuer zu fangen, und Chance, dabei zu verbrennen; None für Unbrennbares und alles unter Wasser
+pub fn flammability(state: u16) -> Option<(u32, u32)> {
+    if property(state, "waterlogged") == Some("true") {
+        return None;
+    }
+    let name = name(state);
+    FLAMMABILITY.iter().find(|(n, _, _)| *n == name).map(|&(_, ignite, burn)| (ignite, burn))
+}
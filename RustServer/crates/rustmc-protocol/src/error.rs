@@ -0,0 +1,65 @@
+use std::fmt;
+use std::io;
+use super::ConnectionState;
+
+// Warum ein Paket nicht gelesen werden konnte. Io betrifft die Verbindung selbst, alles andere nur dieses Paket
+#[derive(Debug)]
+pub enum ProtocolError {
+    Io(io::Error),
+    // Zu kurz, zu lang oder mit Werten, die das Protokoll nicht erlaubt
+    Malformed(String),
+    Unexpected { state: ConnectionState, id: i32 },
+}
+
+impl ProtocolError {
+    // Für das Lesen vom Socket, wo ein vorzeitiges Ende heißt, dass der Client weg ist
+    pub fn from_stream(e: io::Error) -> ProtocolError {
+        if e.kind() == io::ErrorKind::InvalidData {
+            ProtocolError::Malformed(e.to_string())
+        } else {
+            ProtocolError::Io(e)
+        }
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::Io(e) => write!(f, "Verbindungsfehler: {}", e),
+            ProtocolError::Malformed(reason) => write!(f, "Ungültiges Paket: {}", reason),
+            ProtocolError::Unexpected { state, id } => write!(f, "Unerwartetes Paket 0x{:02X} im Zustand {}", id, state.name()),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProtocolError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// Gelesen wird der Inhalt aus einem Puffer, der schon vollständig da ist: endet er zu früh, ist das Paket zu kurz
+impl From<io::Error> for ProtocolError {
+    fn from(e: io::Error) -> ProtocolError {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => ProtocolError::Malformed("Packet is too short".to_string()),
+            io::ErrorKind::InvalidData => ProtocolError::Malformed(e.to_string()),
+            _ => ProtocolError::Io(e),
+        }
+    }
+}
+
+impl From<String> for ProtocolError {
+    fn from(reason: String) -> ProtocolError {
+        ProtocolError::Malformed(reason)
+    }
+}
+
+impl From<ProtocolError> for String {
+    fn from(e: ProtocolError) -> String {
+        e.to_string()
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::{self, Read};
+use super::types::{read_varint, write_varint_to_vec, MAX_STRING_LENGTH};
+use super::ConnectionState;
+use crate::gzip;
+
+// Obergrenze von Vanilla: die Länge passt in eine VarInt aus höchstens drei Bytes
+pub const MAX_PACKET_LENGTH: usize = (1 << 21) - 1;
+// Wie Vanilla: größer darf ein komprimiertes Paket entpackt nicht werden
+const MAX_UNCOMPRESSED_LENGTH: usize = 1 << 23;
+
+// Vor dem Login reichen wenige Bytes; der Handshake trägt bei BungeeCord-Weiterleitung allerdings Profil und Skin,
+// die Login-Plugin-Antwort von Velocity ebenso
+pub fn max_packet_length(state: ConnectionState) -> usize {
+    match state {
+        ConnectionState::Handshake => MAX_STRING_LENGTH,
+        ConnectionState::Status => 64,
+        ConnectionState::Login => 1 << 20,
+        ConnectionState::Configuration | ConnectionState::Play => MAX_PACKET_LENGTH,
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Paket-ID und Inhalt samt Längenpräfix. Ab Set Compression steht davor die entpackte Länge, 0 für Pakete unter
+// der Schwelle, die unkomprimiert bleiben
+pub fn encode_frame(body: &[u8], compression: Option<usize>) -> Vec<u8> {
+    let body = match compression {
+        Some(threshold) if body.len() >= threshold => {
+            let mut data = write_varint_to_vec(body.len() as i32);
+            data.extend(gzip::zlib_compress(body));
+            data
+        }
+        Some(_) => {
+            let mut data = vec![0];
+            data.extend(body);
+            data
+        }
+        None => body.to_vec(),
+    };
+    let mut frame = write_varint_to_vec(body.len() as i32);
+    frame.extend(body);
+    frame
+}
+
+// Inhalt eines Pakets (bei Kompression entpackt) und seine Größe auf der Leitung samt Längenpräfix. Negative oder
+// zu große Längen werden als InvalidData abgewiesen, bevor Speicher dafür angelegt wird
+pub fn read_frame(reader: &mut impl Read, max: usize, compression: Option<usize>) -> io::Result<(Vec<u8>, usize)> {
+    let length = read_varint(reader)?;
+    if length <= 0 || length as usize > max {
+        return Err(invalid(format!("Packet length {} is outside 1..={}", length, max)));
+    }
+    let mut buffer = vec![0; length as usize];
+    reader.read_exact(&mut buffer)?;
+    let size = write_varint_to_vec(length).len() + buffer.len();
+    let Some(threshold) = compression else { return Ok((buffer, size)) };
+    let mut cursor = io::Cursor::new(buffer);
+    let data_length = read_varint(&mut cursor)?;
+    let body = cursor.get_ref()[cursor.position() as usize..].to_vec();
+    if data_length == 0 {
+        return Ok((body, size));
+    }
+    if data_length < threshold as i32 || data_length as usize > MAX_UNCOMPRESSED_LENGTH {
+        return Err(invalid(format!("Compressed packet length {} is outside {}..={}", data_length, threshold, MAX_UNCOMPRESSED_LENGTH)));
+    }
+    let body = gzip::zlib_decompress(&body, data_length as usize).map_err(invalid)?;
+    if body.len() != data_length as usize {
+        return Err(invalid(format!("Packet decompressed to {} bytes instead of {}", body.len(), data_length)));
+    }
+    Ok((body, size))
+}
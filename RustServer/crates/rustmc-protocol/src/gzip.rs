@@ -0,0 +1,411 @@
+// gzip (RFC 1952) mit Deflate (RFC 1951): LZ77 über eine Hash-Kette und die festen Huffman-Codes.
+// Für Logdateien reicht das, dynamische Huffman-Tabellen würden nur wenige Prozent mehr sparen.
+// Entpacken versteht dagegen alle drei Blocktypen
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+// Wie viele frühere Positionen pro Hash verglichen werden
+const MAX_CHAIN: usize = 64;
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { 0xEDB88320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    // Deflate füllt Bytes vom niedrigsten Bit an
+    fn write(&mut self, value: u32, count: u32) {
+        self.buffer |= (value as u64) << self.bits;
+        self.bits += count;
+        while self.bits >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    // Huffman-Codes stehen dagegen mit dem höchsten Bit zuerst im Strom
+    fn write_code(&mut self, code: u32, length: u32) {
+        self.write(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+fn write_literal(writer: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _ => writer.write_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let index = LENGTH_BASE.iter().rposition(|&base| base as usize <= length).unwrap();
+    write_literal(writer, 257 + index as u32);
+    writer.write((length - LENGTH_BASE[index] as usize) as u32, LENGTH_EXTRA[index] as u32);
+    let index = DISTANCE_BASE.iter().rposition(|&base| base as usize <= distance).unwrap();
+    writer.write_code(index as u32, 5);
+    writer.write((distance - DISTANCE_BASE[index] as usize) as u32, DISTANCE_EXTRA[index] as u32);
+}
+
+fn hash(data: &[u8], pos: usize) -> usize {
+    let value = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+    (value.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+// Ein einzelner Block mit festen Huffman-Codes
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter { out: Vec::with_capacity(data.len() / 2), buffer: 0, bits: 0 };
+    writer.write(1, 1);
+    writer.write(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW_SIZE];
+    let insert = |head: &mut Vec<usize>, prev: &mut Vec<usize>, pos: usize| {
+        if pos + MIN_MATCH <= data.len() {
+            let h = hash(data, pos);
+            prev[pos % WINDOW_SIZE] = head[h];
+            head[h] = pos;
+        }
+    };
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let mut best = (0, 0);
+        if pos + MIN_MATCH <= data.len() {
+            let max_length = MAX_MATCH.min(data.len() - pos);
+            let mut candidate = head[hash(data, pos)];
+            let mut chain = 0;
+            while candidate != usize::MAX && pos - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                let length = data[candidate..].iter().zip(&data[pos..pos + max_length]).take_while(|(a, b)| a == b).count();
+                if length > best.0 {
+                    best = (length, pos - candidate);
+                    if length == max_length {
+                        break;
+                    }
+                }
+                let next = prev[candidate % WINDOW_SIZE];
+                // Ältere Einträge wurden im Ringpuffer schon überschrieben
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+        if best.0 >= MIN_MATCH {
+            write_match(&mut writer, best.0, best.1);
+            for p in pos..pos + best.0 {
+                insert(&mut head, &mut prev, p);
+            }
+            pos += best.0;
+        } else {
+            write_literal(&mut writer, data[pos] as u32);
+            insert(&mut head, &mut prev, pos);
+            pos += 1;
+        }
+    }
+    write_literal(&mut writer, 256);
+    writer.finish()
+}
+
+pub fn compress(data: &[u8], mtime: u32) -> Vec<u8> {
+    // Magic, Methode Deflate, keine Flags, Änderungszeit, keine Extra-Flags, Betriebssystem Unix
+    let mut out = vec![0x1F, 0x8B, 8, 0];
+    out.extend(mtime.to_le_bytes());
+    out.extend([0, 3]);
+    out.extend(deflate(data));
+    out.extend(crc32(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out
+}
+
+// Gegenstück zu compress; überspringt die optionalen Kopffelder anderer Packer und prüft die Prüfsumme
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 18 || data[0..3] != [0x1F, 0x8B, 8] {
+        return Err("Keine Gzip-Daten".to_string());
+    }
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & 0x04 != 0 {
+        let length = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+        pos += 2 + length;
+    }
+    for flag in [0x08, 0x10] {
+        if flags & flag != 0 {
+            pos += data.get(pos..).and_then(|rest| rest.iter().position(|&b| b == 0)).ok_or("Gzip-Kopf endet unerwartet")? + 1;
+        }
+    }
+    if flags & 0x02 != 0 {
+        pos += 2;
+    }
+    let body = data.get(pos..data.len() - 8).ok_or("Gzip-Kopf endet unerwartet")?;
+    let out = inflate(body)?;
+    let trailer = &data[data.len() - 8..];
+    if crc32(&out) != u32::from_le_bytes(trailer[0..4].try_into().unwrap()) {
+        return Err("Gzip-Prüfsumme stimmt nicht".to_string());
+    }
+    Ok(out)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+// zlib (RFC 1950), wie es das Minecraft-Protokoll für komprimierte Pakete verwendet
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    // Deflate mit 32-KiB-Fenster, Stufe "schnell"; 0x7801 ist durch 31 teilbar, wie die Prüfbits verlangen
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+pub fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    if data.len() < 6 || data[0] & 0x0F != 8 || !u16::from_be_bytes([data[0], data[1]]).is_multiple_of(31) {
+        return Err("Keine zlib-Daten".to_string());
+    }
+    if data[1] & 0x20 != 0 {
+        return Err("zlib-Wörterbücher werden nicht unterstützt".to_string());
+    }
+    let out = inflate_limited(&data[2..data.len() - 4], limit)?;
+    if adler32(&out) != u32::from_be_bytes(data[data.len() - 4..].try_into().unwrap()) {
+        return Err("zlib-Prüfsumme stimmt nicht".to_string());
+    }
+    Ok(out)
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        while self.bits < count {
+            let byte = *self.data.get(self.pos).ok_or("Deflate-Daten enden unerwartet")?;
+            self.pos += 1;
+            self.buffer |= (byte as u32) << self.bits;
+            self.bits += 8;
+        }
+        let value = self.buffer & ((1u64 << count) - 1) as u32;
+        self.buffer >>= count;
+        self.bits -= count;
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.bits = 0;
+    }
+}
+
+// Kanonischer Huffman-Code: Anzahl der Codes pro Länge und die Symbole in Code-Reihenfolge
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman, String> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err("Überbestimmter Huffman-Code".to_string());
+            }
+        }
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    // Bitweise wie in zlibs puff.c; langsam, aber für Konfigurations- und Datendateien schnell genug
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("Ungültiger Huffman-Code".to_string())
+    }
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (Huffman::new(&lengths).unwrap(), Huffman::new(&[5; 30]).unwrap())
+}
+
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    const ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+    let literals = reader.bits(5)? as usize + 257;
+    let distances = reader.bits(5)? as usize + 1;
+    let code_lengths = reader.bits(4)? as usize + 4;
+    if literals > 286 || distances > 30 {
+        return Err("Zu viele Codes in dynamischem Block".to_string());
+    }
+    let mut lengths = [0u8; 19];
+    for &index in &ORDER[..code_lengths] {
+        lengths[index] = reader.bits(3)? as u8;
+    }
+    let lencode = Huffman::new(&lengths)?;
+    let mut lengths = vec![0u8; literals + distances];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = lencode.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => {
+                lengths[index] = symbol as u8;
+                index += 1;
+                continue;
+            }
+            16 if index == 0 => return Err("Wiederholung ohne vorherige Länge".to_string()),
+            16 => (lengths[index - 1], 3 + reader.bits(2)? as usize),
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if index + repeat > lengths.len() {
+            return Err("Zu viele Längen in dynamischem Block".to_string());
+        }
+        lengths[index..index + repeat].fill(value);
+        index += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err("Dynamischer Block ohne Endcode".to_string());
+    }
+    Ok((Huffman::new(&lengths[..literals])?, Huffman::new(&lengths[literals..])?))
+}
+
+// Entpackt einen vollständigen Deflate-Strom (RFC 1951)
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    inflate_limited(data, usize::MAX)
+}
+
+// Bricht ab, sobald die Ausgabe limit übersteigt, damit wenige Bytes aus dem Netz nicht Gigabytes anlegen
+fn inflate_limited(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    let mut reader = BitReader { data, pos: 0, buffer: 0, bits: 0 };
+    let mut out = Vec::with_capacity(data.len() * 4);
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let header = data.get(reader.pos..reader.pos + 4).ok_or("Deflate-Daten enden unerwartet")?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if length != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err("Ungültige Länge eines ungepackten Blocks".to_string());
+                }
+                reader.pos += 4;
+                out.extend_from_slice(data.get(reader.pos..reader.pos + length).ok_or("Deflate-Daten enden unerwartet")?);
+                reader.pos += length;
+                if out.len() > limit {
+                    return Err("Entpackte Daten sind zu groß".to_string());
+                }
+            }
+            kind @ (1 | 2) => {
+                let (literals, distances) = if kind == 1 { fixed_tables() } else { dynamic_tables(&mut reader)? };
+                loop {
+                    let symbol = literals.decode(&mut reader)? as usize;
+                    if symbol < 256 {
+                        out.push(symbol as u8);
+                        continue;
+                    }
+                    if symbol == 256 {
+                        break;
+                    }
+                    let index = symbol - 257;
+                    if index >= LENGTH_BASE.len() {
+                        return Err("Ungültiger Längencode".to_string());
+                    }
+                    let length = LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+                    let index = distances.decode(&mut reader)? as usize;
+                    if index >= DISTANCE_BASE.len() {
+                        return Err("Ungültiger Distanzcode".to_string());
+                    }
+                    let distance = DISTANCE_BASE[index] as usize + reader.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                    if distance > out.len() {
+                        return Err("Distanz zeigt vor den Anfang der Daten".to_string());
+                    }
+                    let start = out.len() - distance;
+                    for i in 0..length {
+                        out.push(out[start + i]);
+                    }
+                    if out.len() > limit {
+                        return Err("Entpackte Daten sind zu groß".to_string());
+                    }
+                }
+            }
+            _ => return Err("Ungültiger Blocktyp".to_string()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
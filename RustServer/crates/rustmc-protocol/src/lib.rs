@@ -0,0 +1,1283 @@
+// Paketformat, Versionsübersetzung und Blockzustände; kennt weder Server noch Welt
+pub mod assets;
+pub mod block;
+pub mod error;
+pub mod framing;
+pub mod gzip;
+pub mod json;
+pub mod manifest;
+pub mod nbt;
+pub mod packets;
+pub mod types;
+pub mod version;
+
+pub use error::ProtocolError;
+
+pub const PROTOCOL_VERSION: i32 = 767;
+pub const MINECRAFT_VERSION: &str = "1.21.1";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionState {
+    Handshake,
+    Status,
+    Login,
+    Configuration,
+    Play,
+}
+
+impl ConnectionState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConnectionState::Handshake => "handshake",
+            ConnectionState::Status => "status",
+            ConnectionState::Login => "login",
+            ConnectionState::Configuration => "configuration",
+            ConnectionState::Play => "play",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Serverbound,
+    Clientbound,
+}
+
+impl Direction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Serverbound => "serverbound",
+            Direction::Clientbound => "clientbound",
+        }
+    }
+}
+
+pub mod serverbound {
+    pub const HANDSHAKE: i32 = 0x00;
+    pub const STATUS_REQUEST: i32 = 0x00;
+    pub const CONFIRM_TELEPORTATION: i32 = 0x00;
+    pub const PING_REQUEST: i32 = 0x01;
+    pub const LOGIN_START: i32 = 0x00;
+    pub const LOGIN_PLUGIN_RESPONSE: i32 = 0x02;
+    pub const LOGIN_ACKNOWLEDGED: i32 = 0x03;
+    // Konfigurationsphase, IDs ab 1.20.5
+    pub const CONFIGURATION_CLIENT_INFORMATION: i32 = 0x00;
+    pub const CONFIGURATION_CUSTOM_PAYLOAD: i32 = 0x02;
+    pub const ACKNOWLEDGE_FINISH_CONFIGURATION: i32 = 0x03;
+    pub const CONFIGURATION_KEEP_ALIVE: i32 = 0x04;
+    pub const CONFIGURATION_PONG: i32 = 0x05;
+    pub const CONFIGURATION_RESOURCE_PACK_RESPONSE: i32 = 0x06;
+    pub const KNOWN_PACKS: i32 = 0x07;
+    pub const CHAT_COMMAND: i32 = 0x04;
+    pub const CHAT_MESSAGE: i32 = 0x06;
+    pub const PLAYER_SESSION: i32 = 0x07;
+    pub const CLIENT_COMMAND: i32 = 0x09;
+    pub const CLIENT_INFORMATION: i32 = 0x0A;
+    pub const CLICK_CONTAINER_BUTTON: i32 = 0x0D;
+    pub const CLICK_CONTAINER: i32 = 0x0E;
+    pub const CLOSE_CONTAINER: i32 = 0x0F;
+    pub const CUSTOM_PAYLOAD: i32 = 0x12;
+    pub const INTERACT: i32 = 0x16;
+    pub const KEEP_ALIVE: i32 = 0x18;
+    pub const SET_PLAYER_POSITION: i32 = 0x1A;
+    pub const SET_PLAYER_POSITION_AND_ROTATION: i32 = 0x1B;
+    pub const SET_PLAYER_ROTATION: i32 = 0x1C;
+    pub const MOVE_VEHICLE: i32 = 0x1E;
+    pub const PADDLE_BOAT: i32 = 0x1F;
+    pub const PLACE_RECIPE: i32 = 0x22;
+    pub const PLAYER_ACTION: i32 = 0x24;
+    pub const PLAYER_COMMAND: i32 = 0x25;
+    pub const PLAYER_INPUT: i32 = 0x26;
+    pub const CHANGE_RECIPE_BOOK_SETTINGS: i32 = 0x28;
+    pub const SET_SEEN_RECIPE: i32 = 0x29;
+    pub const RENAME_ITEM: i32 = 0x2A;
+    pub const RESOURCE_PACK_RESPONSE: i32 = 0x2B;
+    pub const SELECT_TRADE: i32 = 0x2D;
+    pub const SET_HELD_ITEM: i32 = 0x2F;
+    pub const SIGN_UPDATE: i32 = 0x35;
+    pub const SWING_ARM: i32 = 0x36;
+    pub const TELEPORT_TO_ENTITY: i32 = 0x37;
+    pub const USE_ITEM_ON: i32 = 0x38;
+    pub const USE_ITEM: i32 = 0x39;
+}
+
+pub mod clientbound {
+    pub const BUNDLE_DELIMITER: i32 = 0x00;
+    pub const STATUS_RESPONSE: i32 = 0x00;
+    pub const PONG_RESPONSE: i32 = 0x01;
+    pub const LOGIN_DISCONNECT: i32 = 0x00;
+    pub const LOGIN_SUCCESS: i32 = 0x02;
+    pub const SET_COMPRESSION: i32 = 0x03;
+    pub const LOGIN_PLUGIN_REQUEST: i32 = 0x04;
+    // Konfigurationsphase, IDs ab 1.20.5
+    pub const CONFIGURATION_CUSTOM_PAYLOAD: i32 = 0x01;
+    pub const CONFIGURATION_DISCONNECT: i32 = 0x02;
+    pub const FINISH_CONFIGURATION: i32 = 0x03;
+    pub const REGISTRY_DATA: i32 = 0x07;
+    pub const FEATURE_FLAGS: i32 = 0x0C;
+    pub const KNOWN_PACKS: i32 = 0x0E;
+    pub const SPAWN_ENTITY: i32 = 0x01;
+    pub const SPAWN_EXPERIENCE_ORB: i32 = 0x02;
+    pub const ENTITY_ANIMATION: i32 = 0x03;
+    pub const ACKNOWLEDGE_BLOCK_CHANGE: i32 = 0x05;
+    pub const BLOCK_ENTITY_DATA: i32 = 0x07;
+    pub const BLOCK_ACTION: i32 = 0x08;
+    pub const BLOCK_UPDATE: i32 = 0x09;
+    pub const BOSS_EVENT: i32 = 0x0A;
+    pub const CHANGE_DIFFICULTY: i32 = 0x0B;
+    pub const CLOSE_CONTAINER: i32 = 0x12;
+    pub const SET_CONTAINER_CONTENT: i32 = 0x13;
+    pub const SET_CONTAINER_PROPERTY: i32 = 0x14;
+    pub const SET_CONTAINER_SLOT: i32 = 0x15;
+    pub const CUSTOM_PAYLOAD: i32 = 0x19;
+    pub const DISCONNECT: i32 = 0x1D;
+    pub const ENTITY_EVENT: i32 = 0x1F;
+    pub const EXPLOSION: i32 = 0x20;
+    pub const UNLOAD_CHUNK: i32 = 0x21;
+    pub const GAME_EVENT: i32 = 0x22;
+    pub const HURT_ANIMATION: i32 = 0x24;
+    pub const INITIALIZE_WORLD_BORDER: i32 = 0x25;
+    pub const KEEP_ALIVE: i32 = 0x26;
+    pub const CHUNK_DATA: i32 = 0x27;
+    pub const WORLD_EVENT: i32 = 0x28;
+    pub const UPDATE_LIGHT: i32 = 0x2A;
+    pub const LOGIN_PLAY: i32 = 0x2B;
+    pub const MAP_DATA: i32 = 0x2C;
+    pub const MERCHANT_OFFERS: i32 = 0x2D;
+    pub const UPDATE_ENTITY_POSITION: i32 = 0x2E;
+    pub const UPDATE_ENTITY_POSITION_AND_ROTATION: i32 = 0x2F;
+    pub const UPDATE_ENTITY_ROTATION: i32 = 0x30;
+    pub const MOVE_VEHICLE: i32 = 0x31;
+    pub const OPEN_SCREEN: i32 = 0x33;
+    pub const OPEN_SIGN_EDITOR: i32 = 0x34;
+    pub const PLACE_GHOST_RECIPE: i32 = 0x37;
+    pub const PLAYER_ABILITIES: i32 = 0x38;
+    pub const COMBAT_DEATH: i32 = 0x3C;
+    pub const PLAYER_INFO_REMOVE: i32 = 0x3D;
+    pub const PLAYER_INFO_UPDATE: i32 = 0x3E;
+    pub const SYNCHRONIZE_PLAYER_POSITION: i32 = 0x40;
+    pub const UPDATE_RECIPE_BOOK: i32 = 0x41;
+    pub const REMOVE_ENTITIES: i32 = 0x42;
+    pub const REMOVE_ENTITY_EFFECT: i32 = 0x43;
+    pub const RESET_SCORE: i32 = 0x44;
+    pub const ADD_RESOURCE_PACK: i32 = 0x46;
+    pub const RESPAWN: i32 = 0x47;
+    pub const SET_HEAD_ROTATION: i32 = 0x48;
+    pub const UPDATE_SECTION_BLOCKS: i32 = 0x49;
+    pub const SERVER_DATA: i32 = 0x4B;
+    pub const SET_BORDER_CENTER: i32 = 0x4D;
+    pub const SET_BORDER_LERP_SIZE: i32 = 0x4E;
+    pub const SET_BORDER_SIZE: i32 = 0x4F;
+    pub const SET_BORDER_WARNING_DELAY: i32 = 0x50;
+    pub const SET_BORDER_WARNING_DISTANCE: i32 = 0x51;
+    pub const SET_CENTER_CHUNK: i32 = 0x54;
+    pub const SET_DEFAULT_SPAWN_POSITION: i32 = 0x56;
+    pub const DISPLAY_OBJECTIVE: i32 = 0x57;
+    pub const SET_ENTITY_METADATA: i32 = 0x58;
+    pub const SET_ENTITY_VELOCITY: i32 = 0x5A;
+    pub const SET_EQUIPMENT: i32 = 0x5B;
+    pub const SET_EXPERIENCE: i32 = 0x5C;
+    pub const SET_HEALTH: i32 = 0x5D;
+    pub const UPDATE_OBJECTIVES: i32 = 0x5E;
+    pub const SET_PASSENGERS: i32 = 0x5F;
+    pub const UPDATE_SCORE: i32 = 0x61;
+    pub const UPDATE_TIME: i32 = 0x64;
+    pub const SOUND_EFFECT: i32 = 0x68;
+    pub const SYSTEM_CHAT: i32 = 0x6C;
+    pub const TAKE_ITEM_ENTITY: i32 = 0x6F;
+    pub const TELEPORT_ENTITY: i32 = 0x70;
+    pub const UPDATE_ATTRIBUTES: i32 = 0x75;
+    pub const ENTITY_EFFECT: i32 = 0x76;
+    pub const UPDATE_RECIPES: i32 = 0x77;
+}
+
+// Beschreibung eines Pakets: Feldname und Protokolltyp in Reihenfolge
+pub struct PacketDef {
+    pub name: &'static str,
+    pub id: i32,
+    pub state: ConnectionState,
+    pub direction: Direction,
+    pub fields: &'static [(&'static str, &'static str)],
+}
+
+pub const PACKETS: &[PacketDef] = &[
+    PacketDef {
+        name: "handshake",
+        id: serverbound::HANDSHAKE,
+        state: ConnectionState::Handshake,
+        direction: Direction::Serverbound,
+        fields: &[
+            ("protocol_version", "varint"),
+            ("server_address", "string"),
+            ("server_port", "u16"),
+            ("next_state", "varint"),
+        ],
+    },
+    PacketDef {
+        name: "status_request",
+        id: serverbound::STATUS_REQUEST,
+        state: ConnectionState::Status,
+        direction: Direction::Serverbound,
+        fields: &[],
+    },
+    PacketDef {
+        name: "status_response",
+        id: clientbound::STATUS_RESPONSE,
+        state: ConnectionState::Status,
+        direction: Direction::Clientbound,
+        fields: &[("json_response", "string")],
+    },
+    PacketDef {
+        name: "ping_request",
+        id: serverbound::PING_REQUEST,
+        state: ConnectionState::Status,
+        direction: Direction::Serverbound,
+        fields: &[("payload", "i64")],
+    },
+    PacketDef {
+        name: "pong_response",
+        id: clientbound::PONG_RESPONSE,
+        state: ConnectionState::Status,
+        direction: Direction::Clientbound,
+        fields: &[("payload", "i64")],
+    },
+    PacketDef {
+        name: "login_start",
+        id: serverbound::LOGIN_START,
+        state: ConnectionState::Login,
+        direction: Direction::Serverbound,
+        fields: &[("username", "string")],
+    },
+    PacketDef {
+        name: "login_success",
+        id: clientbound::LOGIN_SUCCESS,
+        state: ConnectionState::Login,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("uuid", "uuid"),
+            ("username", "string"),
+            ("properties", "array<profile_property>"),
+            ("strict_error_handling", "bool"),
+        ],
+    },
+    PacketDef {
+        name: "login_disconnect",
+        id: clientbound::LOGIN_DISCONNECT,
+        state: ConnectionState::Login,
+        direction: Direction::Clientbound,
+        fields: &[("reason", "json_text_component")],
+    },
+    PacketDef {
+        name: "bundle_delimiter",
+        id: clientbound::BUNDLE_DELIMITER,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[],
+    },
+    PacketDef {
+        name: "set_compression",
+        id: clientbound::SET_COMPRESSION,
+        state: ConnectionState::Login,
+        direction: Direction::Clientbound,
+        fields: &[("threshold", "varint")],
+    },
+    PacketDef {
+        name: "login_plugin_request",
+        id: clientbound::LOGIN_PLUGIN_REQUEST,
+        state: ConnectionState::Login,
+        direction: Direction::Clientbound,
+        fields: &[("message_id", "varint"), ("channel", "identifier"), ("data", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "login_plugin_response",
+        id: serverbound::LOGIN_PLUGIN_RESPONSE,
+        state: ConnectionState::Login,
+        direction: Direction::Serverbound,
+        fields: &[("message_id", "varint"), ("successful", "bool"), ("data", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "login_acknowledged",
+        id: serverbound::LOGIN_ACKNOWLEDGED,
+        state: ConnectionState::Login,
+        direction: Direction::Serverbound,
+        fields: &[],
+    },
+    PacketDef {
+        name: "clientbound_known_packs",
+        id: clientbound::KNOWN_PACKS,
+        state: ConnectionState::Configuration,
+        direction: Direction::Clientbound,
+        fields: &[("packs", "array<known_pack>")],
+    },
+    PacketDef {
+        name: "serverbound_known_packs",
+        id: serverbound::KNOWN_PACKS,
+        state: ConnectionState::Configuration,
+        direction: Direction::Serverbound,
+        fields: &[("packs", "array<known_pack>")],
+    },
+    PacketDef {
+        name: "registry_data",
+        id: clientbound::REGISTRY_DATA,
+        state: ConnectionState::Configuration,
+        direction: Direction::Clientbound,
+        fields: &[("registry", "identifier"), ("entries", "array<registry_entry>")],
+    },
+    PacketDef {
+        name: "feature_flags",
+        id: clientbound::FEATURE_FLAGS,
+        state: ConnectionState::Configuration,
+        direction: Direction::Clientbound,
+        fields: &[("features", "array<identifier>")],
+    },
+    PacketDef {
+        name: "finish_configuration",
+        id: clientbound::FINISH_CONFIGURATION,
+        state: ConnectionState::Configuration,
+        direction: Direction::Clientbound,
+        fields: &[],
+    },
+    PacketDef {
+        name: "acknowledge_finish_configuration",
+        id: serverbound::ACKNOWLEDGE_FINISH_CONFIGURATION,
+        state: ConnectionState::Configuration,
+        direction: Direction::Serverbound,
+        fields: &[],
+    },
+    PacketDef {
+        name: "login_play",
+        id: clientbound::LOGIN_PLAY,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("entity_id", "i32"),
+            ("is_hardcore", "bool"),
+            ("dimension_names", "array<identifier>"),
+            ("max_players", "varint"),
+            ("view_distance", "varint"),
+            ("simulation_distance", "varint"),
+            ("reduced_debug_info", "bool"),
+            ("enable_respawn_screen", "bool"),
+            ("do_limited_crafting", "bool"),
+            ("dimension_type", "varint"),
+            ("dimension_name", "identifier"),
+            ("hashed_seed", "i64"),
+            ("game_mode", "u8"),
+            ("previous_game_mode", "i8"),
+            ("is_debug", "bool"),
+            ("is_flat", "bool"),
+            ("has_death_location", "bool"),
+            ("portal_cooldown", "varint"),
+            ("enforces_secure_chat", "bool"),
+        ],
+    },
+    PacketDef {
+        name: "chunk_data_and_update_light",
+        id: clientbound::CHUNK_DATA,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("chunk_x", "i32"),
+            ("chunk_z", "i32"),
+            ("heightmaps", "nbt"),
+            ("data", "byte_array"),
+            ("block_entities", "array<block_entity>"),
+            ("sky_light_mask", "bitset"),
+            ("block_light_mask", "bitset"),
+            ("empty_sky_light_mask", "bitset"),
+            ("empty_block_light_mask", "bitset"),
+            ("sky_light", "array<byte_array>"),
+            ("block_light", "array<byte_array>"),
+        ],
+    },
+    PacketDef {
+        name: "update_light",
+        id: clientbound::UPDATE_LIGHT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("chunk_x", "varint"),
+            ("chunk_z", "varint"),
+            ("sky_light_mask", "bitset"),
+            ("block_light_mask", "bitset"),
+            ("empty_sky_light_mask", "bitset"),
+            ("empty_block_light_mask", "bitset"),
+            ("sky_light", "array<byte_array>"),
+            ("block_light", "array<byte_array>"),
+        ],
+    },
+    PacketDef {
+        name: "acknowledge_block_change",
+        id: clientbound::ACKNOWLEDGE_BLOCK_CHANGE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("sequence", "varint")],
+    },
+    PacketDef {
+        name: "block_entity_data",
+        id: clientbound::BLOCK_ENTITY_DATA,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("location", "position"), ("type", "varint"), ("data", "nbt")],
+    },
+    PacketDef {
+        name: "block_action",
+        id: clientbound::BLOCK_ACTION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("location", "position"), ("action_id", "u8"), ("action_parameter", "u8"), ("block_type", "varint")],
+    },
+    PacketDef {
+        name: "open_screen",
+        id: clientbound::OPEN_SCREEN,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("window_id", "varint"), ("window_type", "varint"), ("window_title", "nbt_text_component")],
+    },
+    PacketDef {
+        name: "close_container",
+        id: clientbound::CLOSE_CONTAINER,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("window_id", "u8")],
+    },
+    PacketDef {
+        name: "open_sign_editor",
+        id: clientbound::OPEN_SIGN_EDITOR,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("location", "position"), ("is_front_text", "bool")],
+    },
+    PacketDef {
+        name: "block_update",
+        id: clientbound::BLOCK_UPDATE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("location", "position"), ("block_id", "varint")],
+    },
+    PacketDef {
+        name: "custom_payload",
+        id: clientbound::CUSTOM_PAYLOAD,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("channel", "identifier"), ("data", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "disconnect",
+        id: clientbound::DISCONNECT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("reason", "nbt_text_component")],
+    },
+    PacketDef {
+        name: "set_center_chunk",
+        id: clientbound::SET_CENTER_CHUNK,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("chunk_x", "varint"), ("chunk_z", "varint")],
+    },
+    PacketDef {
+        name: "boss_event",
+        id: clientbound::BOSS_EVENT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("uuid", "uuid"), ("action", "varint"), ("data", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "unload_chunk",
+        id: clientbound::UNLOAD_CHUNK,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("chunk_z", "i32"), ("chunk_x", "i32")],
+    },
+    PacketDef {
+        name: "game_event",
+        id: clientbound::GAME_EVENT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("event", "u8"), ("value", "f32")],
+    },
+    PacketDef {
+        name: "keep_alive",
+        id: clientbound::KEEP_ALIVE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("keep_alive_id", "i64")],
+    },
+    PacketDef {
+        name: "keep_alive",
+        id: serverbound::KEEP_ALIVE,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("keep_alive_id", "i64")],
+    },
+    PacketDef {
+        name: "update_time",
+        id: clientbound::UPDATE_TIME,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("world_age", "i64"), ("time_of_day", "i64")],
+    },
+    PacketDef {
+        name: "chat_command",
+        id: serverbound::CHAT_COMMAND,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("command", "string")],
+    },
+    PacketDef {
+        name: "chat_message",
+        id: serverbound::CHAT_MESSAGE,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[
+            ("message", "string"),
+            ("timestamp", "i64"),
+            ("salt", "i64"),
+            ("signature", "option<bytes256>"),
+            ("message_count", "varint"),
+            ("acknowledged", "fixed_bitset<20>"),
+        ],
+    },
+    PacketDef {
+        name: "player_session",
+        id: serverbound::PLAYER_SESSION,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("session_id", "uuid"), ("expires_at", "i64"), ("public_key", "byte_array"), ("key_signature", "byte_array")],
+    },
+    PacketDef {
+        name: "server_data",
+        id: clientbound::SERVER_DATA,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("motd", "nbt_text_component"), ("icon", "option<byte_array>"), ("enforces_secure_chat", "bool")],
+    },
+    PacketDef {
+        name: "custom_payload",
+        id: serverbound::CUSTOM_PAYLOAD,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("channel", "identifier"), ("data", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "system_chat",
+        id: clientbound::SYSTEM_CHAT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("content", "nbt_text_component"), ("overlay", "bool")],
+    },
+    PacketDef {
+        name: "confirm_teleportation",
+        id: serverbound::CONFIRM_TELEPORTATION,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("teleport_id", "varint")],
+    },
+    PacketDef {
+        name: "synchronize_player_position",
+        id: clientbound::SYNCHRONIZE_PLAYER_POSITION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("x", "f64"),
+            ("y", "f64"),
+            ("z", "f64"),
+            ("yaw", "f32"),
+            ("pitch", "f32"),
+            ("flags", "u8"),
+            ("teleport_id", "varint"),
+        ],
+    },
+    PacketDef {
+        name: "add_resource_pack",
+        id: clientbound::ADD_RESOURCE_PACK,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("uuid", "uuid"),
+            ("url", "string"),
+            ("hash", "string"),
+            ("forced", "bool"),
+            ("prompt", "option<nbt_text_component>"),
+        ],
+    },
+    PacketDef {
+        name: "update_section_blocks",
+        id: clientbound::UPDATE_SECTION_BLOCKS,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("section_position", "i64"), ("blocks", "array<varlong>")],
+    },
+    PacketDef {
+        name: "update_entity_position",
+        id: clientbound::UPDATE_ENTITY_POSITION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("delta_x", "i16"), ("delta_y", "i16"), ("delta_z", "i16"), ("on_ground", "bool")],
+    },
+    PacketDef {
+        name: "update_entity_position_and_rotation",
+        id: clientbound::UPDATE_ENTITY_POSITION_AND_ROTATION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("entity_id", "varint"),
+            ("delta_x", "i16"),
+            ("delta_y", "i16"),
+            ("delta_z", "i16"),
+            ("yaw", "angle"),
+            ("pitch", "angle"),
+            ("on_ground", "bool"),
+        ],
+    },
+    PacketDef {
+        name: "update_entity_rotation",
+        id: clientbound::UPDATE_ENTITY_ROTATION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("yaw", "angle"), ("pitch", "angle"), ("on_ground", "bool")],
+    },
+    PacketDef {
+        name: "set_head_rotation",
+        id: clientbound::SET_HEAD_ROTATION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("head_yaw", "angle")],
+    },
+    PacketDef {
+        name: "entity_animation",
+        id: clientbound::ENTITY_ANIMATION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("animation", "u8")],
+    },
+    PacketDef {
+        name: "hurt_animation",
+        id: clientbound::HURT_ANIMATION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("yaw", "f32")],
+    },
+    PacketDef {
+        name: "entity_event",
+        id: clientbound::ENTITY_EVENT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "i32"), ("event", "u8")],
+    },
+    PacketDef {
+        name: "set_entity_velocity",
+        id: clientbound::SET_ENTITY_VELOCITY,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("velocity_x", "i16"), ("velocity_y", "i16"), ("velocity_z", "i16")],
+    },
+    PacketDef {
+        name: "set_equipment",
+        id: clientbound::SET_EQUIPMENT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("equipment", "equipment_list")],
+    },
+    PacketDef {
+        name: "take_item_entity",
+        id: clientbound::TAKE_ITEM_ENTITY,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("collected_entity_id", "varint"), ("collector_entity_id", "varint"), ("count", "varint")],
+    },
+    PacketDef {
+        name: "teleport_entity",
+        id: clientbound::TELEPORT_ENTITY,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("entity_id", "varint"),
+            ("x", "f64"),
+            ("y", "f64"),
+            ("z", "f64"),
+            ("yaw", "angle"),
+            ("pitch", "angle"),
+            ("on_ground", "bool"),
+        ],
+    },
+    PacketDef {
+        name: "spawn_entity",
+        id: clientbound::SPAWN_ENTITY,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("entity_id", "varint"),
+            ("uuid", "uuid"),
+            ("type", "varint"),
+            ("x", "f64"),
+            ("y", "f64"),
+            ("z", "f64"),
+            ("pitch", "angle"),
+            ("yaw", "angle"),
+            ("head_yaw", "angle"),
+            ("data", "varint"),
+            ("velocity_x", "i16"),
+            ("velocity_y", "i16"),
+            ("velocity_z", "i16"),
+        ],
+    },
+    PacketDef {
+        name: "spawn_experience_orb",
+        id: clientbound::SPAWN_EXPERIENCE_ORB,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("x", "f64"), ("y", "f64"), ("z", "f64"), ("count", "i16")],
+    },
+    PacketDef {
+        name: "set_entity_metadata",
+        id: clientbound::SET_ENTITY_METADATA,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("metadata", "entity_metadata")],
+    },
+    PacketDef {
+        name: "set_container_content",
+        id: clientbound::SET_CONTAINER_CONTENT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("window_id", "u8"),
+            ("state_id", "varint"),
+            ("slots", "array<slot>"),
+            ("carried_item", "slot"),
+        ],
+    },
+    PacketDef {
+        name: "set_container_property",
+        id: clientbound::SET_CONTAINER_PROPERTY,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("window_id", "u8"), ("property", "i16"), ("value", "i16")],
+    },
+    PacketDef {
+        name: "map_data",
+        id: clientbound::MAP_DATA,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("map_id", "varint"),
+            ("scale", "u8"),
+            ("locked", "bool"),
+            ("icons", "option<array<map_icon>>"),
+            ("columns", "u8"),
+            ("data", "option<map_patch>"),
+        ],
+    },
+    PacketDef {
+        name: "merchant_offers",
+        id: clientbound::MERCHANT_OFFERS,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("window_id", "varint"),
+            ("offers", "array<merchant_offer>"),
+            ("villager_level", "varint"),
+            ("experience", "varint"),
+            ("is_regular_villager", "bool"),
+            ("can_restock", "bool"),
+        ],
+    },
+    PacketDef {
+        name: "world_event",
+        id: clientbound::WORLD_EVENT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("event", "i32"), ("location", "position"), ("data", "i32"), ("disable_relative_volume", "bool")],
+    },
+    PacketDef {
+        name: "sound_effect",
+        id: clientbound::SOUND_EFFECT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("sound_id", "varint"),
+            ("sound_name", "identifier"),
+            ("fixed_range", "option<f32>"),
+            ("category", "varint"),
+            ("x", "i32"),
+            ("y", "i32"),
+            ("z", "i32"),
+            ("volume", "f32"),
+            ("pitch", "f32"),
+            ("seed", "i64"),
+        ],
+    },
+    PacketDef {
+        name: "set_container_slot",
+        id: clientbound::SET_CONTAINER_SLOT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("window_id", "u8"),
+            ("state_id", "varint"),
+            ("slot", "i16"),
+            ("slot_data", "slot"),
+        ],
+    },
+    PacketDef {
+        name: "place_recipe",
+        id: serverbound::PLACE_RECIPE,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("window_id", "u8"), ("recipe", "identifier"), ("make_all", "bool")],
+    },
+    PacketDef {
+        name: "change_recipe_book_settings",
+        id: serverbound::CHANGE_RECIPE_BOOK_SETTINGS,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("book_id", "varint"), ("book_open", "bool"), ("filter_active", "bool")],
+    },
+    PacketDef {
+        name: "set_seen_recipe",
+        id: serverbound::SET_SEEN_RECIPE,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("recipe", "identifier")],
+    },
+    PacketDef {
+        name: "place_ghost_recipe",
+        id: clientbound::PLACE_GHOST_RECIPE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("window_id", "u8"), ("recipe", "identifier")],
+    },
+    PacketDef {
+        name: "update_recipe_book",
+        id: clientbound::UPDATE_RECIPE_BOOK,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("action", "varint"), ("book_settings", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "update_recipes",
+        id: clientbound::UPDATE_RECIPES,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("recipes", "array<recipe>")],
+    },
+    PacketDef {
+        name: "rename_item",
+        id: serverbound::RENAME_ITEM,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("item_name", "string")],
+    },
+    PacketDef {
+        name: "resource_pack_response",
+        id: serverbound::RESOURCE_PACK_RESPONSE,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("uuid", "uuid"), ("result", "varint")],
+    },
+    PacketDef {
+        name: "select_trade",
+        id: serverbound::SELECT_TRADE,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("selected_slot", "varint")],
+    },
+    PacketDef {
+        name: "player_command",
+        id: serverbound::PLAYER_COMMAND,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("entity_id", "varint"), ("action", "varint"), ("jump_boost", "varint")],
+    },
+    PacketDef {
+        name: "player_input",
+        id: serverbound::PLAYER_INPUT,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("sideways", "f32"), ("forward", "f32"), ("flags", "u8")],
+    },
+    PacketDef {
+        name: "move_vehicle",
+        id: serverbound::MOVE_VEHICLE,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("x", "f64"), ("y", "f64"), ("z", "f64"), ("yaw", "f32"), ("pitch", "f32")],
+    },
+    PacketDef {
+        name: "paddle_boat",
+        id: serverbound::PADDLE_BOAT,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("left", "bool"), ("right", "bool")],
+    },
+    PacketDef {
+        name: "swing_arm",
+        id: serverbound::SWING_ARM,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("hand", "varint")],
+    },
+    PacketDef {
+        name: "teleport_to_entity",
+        id: serverbound::TELEPORT_TO_ENTITY,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("target", "uuid")],
+    },
+    PacketDef {
+        name: "set_held_item",
+        id: serverbound::SET_HELD_ITEM,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("slot", "i16")],
+    },
+    PacketDef {
+        name: "click_container_button",
+        id: serverbound::CLICK_CONTAINER_BUTTON,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("window_id", "u8"), ("button_id", "u8")],
+    },
+    PacketDef {
+        name: "click_container",
+        id: serverbound::CLICK_CONTAINER,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[
+            ("window_id", "u8"),
+            ("state_id", "varint"),
+            ("slot", "i16"),
+            ("button", "i8"),
+            ("mode", "varint"),
+            ("changed_slots", "array"),
+            ("carried_item", "slot"),
+        ],
+    },
+    PacketDef {
+        name: "close_container",
+        id: serverbound::CLOSE_CONTAINER,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("window_id", "u8")],
+    },
+    PacketDef {
+        name: "sign_update",
+        id: serverbound::SIGN_UPDATE,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[
+            ("location", "position"),
+            ("is_front_text", "bool"),
+            ("line_1", "string"),
+            ("line_2", "string"),
+            ("line_3", "string"),
+            ("line_4", "string"),
+        ],
+    },
+    PacketDef {
+        name: "use_item_on",
+        id: serverbound::USE_ITEM_ON,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[
+            ("hand", "varint"),
+            ("location", "position"),
+            ("face", "varint"),
+            ("cursor_x", "f32"),
+            ("cursor_y", "f32"),
+            ("cursor_z", "f32"),
+            ("inside_block", "bool"),
+            ("sequence", "varint"),
+        ],
+    },
+    PacketDef {
+        name: "use_item",
+        id: serverbound::USE_ITEM,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("hand", "varint"), ("sequence", "varint"), ("yaw", "f32"), ("pitch", "f32")],
+    },
+    PacketDef {
+        name: "set_player_position",
+        id: serverbound::SET_PLAYER_POSITION,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("x", "f64"), ("y", "f64"), ("z", "f64"), ("on_ground", "bool")],
+    },
+    PacketDef {
+        name: "set_player_position_and_rotation",
+        id: serverbound::SET_PLAYER_POSITION_AND_ROTATION,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[
+            ("x", "f64"),
+            ("y", "f64"),
+            ("z", "f64"),
+            ("yaw", "f32"),
+            ("pitch", "f32"),
+            ("on_ground", "bool"),
+        ],
+    },
+    PacketDef {
+        name: "client_information",
+        id: serverbound::CLIENT_INFORMATION,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[
+            ("locale", "string"),
+            ("view_distance", "i8"),
+            ("chat_mode", "varint"),
+            ("chat_colors", "bool"),
+            ("displayed_skin_parts", "u8"),
+            ("main_hand", "varint"),
+            ("enable_text_filtering", "bool"),
+            ("allow_server_listings", "bool"),
+        ],
+    },
+    PacketDef {
+        name: "set_player_rotation",
+        id: serverbound::SET_PLAYER_ROTATION,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("yaw", "f32"), ("pitch", "f32"), ("on_ground", "bool")],
+    },
+    PacketDef {
+        name: "interact",
+        id: serverbound::INTERACT,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        // Zielpunkt und Hand je nach Art, danach sneaking (bool)
+        fields: &[("entity_id", "varint"), ("type", "varint"), ("data", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "player_action",
+        id: serverbound::PLAYER_ACTION,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("status", "varint"), ("location", "position"), ("face", "i8"), ("sequence", "varint")],
+    },
+    PacketDef {
+        name: "client_command",
+        id: serverbound::CLIENT_COMMAND,
+        state: ConnectionState::Play,
+        direction: Direction::Serverbound,
+        fields: &[("action", "varint")],
+    },
+    PacketDef {
+        name: "set_health",
+        id: clientbound::SET_HEALTH,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("health", "f32"), ("food", "varint"), ("saturation", "f32")],
+    },
+    PacketDef {
+        name: "explosion",
+        id: clientbound::EXPLOSION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("x", "f64"),
+            ("y", "f64"),
+            ("z", "f64"),
+            ("strength", "f32"),
+            ("records", "array<block_offset>"),
+            ("player_motion_x", "f32"),
+            ("player_motion_y", "f32"),
+            ("player_motion_z", "f32"),
+            ("block_interaction", "varint"),
+            ("small_particle", "particle"),
+            ("large_particle", "particle"),
+            ("sound", "sound_event"),
+        ],
+    },
+    PacketDef {
+        name: "set_passengers",
+        id: clientbound::SET_PASSENGERS,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("passengers", "array<varint>")],
+    },
+    PacketDef {
+        name: "move_vehicle",
+        id: clientbound::MOVE_VEHICLE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("x", "f64"), ("y", "f64"), ("z", "f64"), ("yaw", "f32"), ("pitch", "f32")],
+    },
+    PacketDef {
+        name: "player_abilities",
+        id: clientbound::PLAYER_ABILITIES,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("flags", "u8"), ("flying_speed", "f32"), ("field_of_view_modifier", "f32")],
+    },
+    PacketDef {
+        name: "player_info_remove",
+        id: clientbound::PLAYER_INFO_REMOVE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("uuids", "array<uuid>")],
+    },
+    PacketDef {
+        name: "player_info_update",
+        id: clientbound::PLAYER_INFO_UPDATE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("actions", "enum_set"), ("players", "array<player_info_entry>")],
+    },
+    PacketDef {
+        name: "combat_death",
+        id: clientbound::COMBAT_DEATH,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("player_id", "varint"), ("message", "nbt_text_component")],
+    },
+    PacketDef {
+        name: "remove_entities",
+        id: clientbound::REMOVE_ENTITIES,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_ids", "array<varint>")],
+    },
+    PacketDef {
+        name: "respawn",
+        id: clientbound::RESPAWN,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("dimension_type", "varint"),
+            ("dimension_name", "string"),
+            ("hashed_seed", "i64"),
+            ("game_mode", "u8"),
+            ("previous_game_mode", "i8"),
+            ("is_debug", "bool"),
+            ("is_flat", "bool"),
+            ("has_death_location", "bool"),
+            ("portal_cooldown", "varint"),
+            ("data_kept", "u8"),
+        ],
+    },
+    PacketDef {
+        name: "change_difficulty",
+        id: clientbound::CHANGE_DIFFICULTY,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("difficulty", "u8"), ("locked", "bool")],
+    },
+    PacketDef {
+        name: "set_default_spawn_position",
+        id: clientbound::SET_DEFAULT_SPAWN_POSITION,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("location", "position"), ("angle", "f32")],
+    },
+    PacketDef {
+        name: "initialize_world_border",
+        id: clientbound::INITIALIZE_WORLD_BORDER,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[
+            ("x", "f64"),
+            ("z", "f64"),
+            ("old_diameter", "f64"),
+            ("new_diameter", "f64"),
+            ("speed", "varlong"),
+            ("portal_teleport_boundary", "varint"),
+            ("warning_blocks", "varint"),
+            ("warning_time", "varint"),
+        ],
+    },
+    PacketDef {
+        name: "set_border_center",
+        id: clientbound::SET_BORDER_CENTER,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("x", "f64"), ("z", "f64")],
+    },
+    PacketDef {
+        name: "set_border_lerp_size",
+        id: clientbound::SET_BORDER_LERP_SIZE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("old_diameter", "f64"), ("new_diameter", "f64"), ("speed", "varlong")],
+    },
+    PacketDef {
+        name: "set_border_size",
+        id: clientbound::SET_BORDER_SIZE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("diameter", "f64")],
+    },
+    PacketDef {
+        name: "set_border_warning_delay",
+        id: clientbound::SET_BORDER_WARNING_DELAY,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("warning_time", "varint")],
+    },
+    PacketDef {
+        name: "set_border_warning_distance",
+        id: clientbound::SET_BORDER_WARNING_DISTANCE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("warning_blocks", "varint")],
+    },
+    PacketDef {
+        name: "remove_entity_effect",
+        id: clientbound::REMOVE_ENTITY_EFFECT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("effect_id", "varint")],
+    },
+    PacketDef {
+        name: "update_attributes",
+        id: clientbound::UPDATE_ATTRIBUTES,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("properties", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "entity_effect",
+        id: clientbound::ENTITY_EFFECT,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_id", "varint"), ("effect_id", "varint"), ("amplifier", "varint"), ("duration", "varint"), ("flags", "u8")],
+    },
+    PacketDef {
+        name: "set_experience",
+        id: clientbound::SET_EXPERIENCE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("experience_bar", "f32"), ("level", "varint"), ("total_experience", "varint")],
+    },
+    PacketDef {
+        name: "update_objectives",
+        id: clientbound::UPDATE_OBJECTIVES,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("objective_name", "string"), ("mode", "i8"), ("data", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "display_objective",
+        id: clientbound::DISPLAY_OBJECTIVE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("position", "varint"), ("score_name", "string")],
+    },
+    PacketDef {
+        name: "update_score",
+        id: clientbound::UPDATE_SCORE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_name", "string"), ("objective_name", "string"), ("value", "varint"), ("data", "remaining_bytes")],
+    },
+    PacketDef {
+        name: "reset_score",
+        id: clientbound::RESET_SCORE,
+        state: ConnectionState::Play,
+        direction: Direction::Clientbound,
+        fields: &[("entity_name", "string"), ("objective_name", "option<string>")],
+    },
+];
@@ -0,0 +1,58 @@
+use super::version::SUPPORTED;
+use super::{PacketDef, MINECRAFT_VERSION, PACKETS, PROTOCOL_VERSION};
+use crate::json::escape as json_string;
+
+// IDs je Protokollnummer; fehlt eine Version, gibt es das Paket dort nicht
+fn packet_json(packet: &PacketDef) -> String {
+    let fields: Vec<String> = packet
+        .fields
+        .iter()
+        .map(|(name, ty)| format!("{{\"name\":{},\"type\":{}}}", json_string(name), json_string(ty)))
+        .collect();
+    let ids: Vec<String> = SUPPORTED
+        .iter()
+        .filter_map(|version| version.packet_id(packet.state, packet.direction, packet.id).map(|id| format!("\"{}\":{}", version.protocol(), id)))
+        .collect();
+    format!(
+        "    {{\"name\":{},\"id\":{},\"ids\":{{{}}},\"state\":{},\"direction\":{},\"fields\":[{}]}}",
+        json_string(packet.name),
+        packet.id,
+        ids.join(","),
+        json_string(packet.state.name()),
+        json_string(packet.direction.name()),
+        fields.join(",")
+    )
+}
+
+// Maschinenlesbares Manifest aller unterstützten Pakete (für Proxies, Bots usw.). id gilt für protocol_version, ids
+// nennt die ID für jede Version aus supported_versions
+pub fn generate() -> String {
+    let packets: Vec<String> = PACKETS.iter().map(packet_json).collect();
+    let versions: Vec<String> = SUPPORTED.iter().map(|v| format!("{{\"name\":{},\"protocol\":{}}}", json_string(v.name()), v.protocol())).collect();
+    format!(
+        "{{\n  \"protocol_version\":{},\n  \"minecraft_version\":{},\n  \"supported_versions\":[{}],\n  \"packets\":[\n{}\n  ]\n}}\n",
+        PROTOCOL_VERSION,
+        json_string(MINECRAFT_VERSION),
+        versions.join(","),
+        packets.join(",\n")
+    )
+}
+
+pub fn write_to(path: &str) -> Result<(), String> {
+    std::fs::write(path, generate()).map_err(|e| format!("Fehler beim Schreiben des Protokoll-Manifests nach {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remapped_packets_list_the_id_of_each_version() {
+        let manifest = generate();
+        let game_event = manifest.lines().find(|line| line.contains("\"name\":\"game_event\"")).unwrap();
+        assert!(game_event.contains("\"ids\":{\"765\":32,\"766\":34,\"767\":34}"), "{}", game_event);
+        // Known Packs gibt es erst seit 1.20.5
+        let known_packs = manifest.lines().find(|line| line.contains("\"name\":\"serverbound_known_packs\"")).unwrap();
+        assert!(!known_packs.contains("\"765\""), "{}", known_packs);
+    }
+}
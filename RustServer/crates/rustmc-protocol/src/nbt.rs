@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use crate::json::Json;
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+// Vanilla begrenzt die Verschachtelung ebenfalls auf 512 Ebenen
+const MAX_DEPTH: usize = 512;
+
+// Json als Netzwerk-NBT (Wurzel ohne Namen, seit 1.20.2). Ganze Zahlen werden Int oder Long, alles andere Double;
+// der Client liest Zahlen ohnehin typunabhängig
+pub fn from_json(value: &Json) -> Vec<u8> {
+    let mut data = vec![tag_type(value)];
+    write_payload(&mut data, value);
+    data
+}
+
+// Compound aus benannten Long-Arrays, die Json nicht ausdrücken kann, etwa die Heightmaps eines Chunks
+pub fn long_arrays(arrays: &[(&str, Vec<u64>)]) -> Vec<u8> {
+    let mut data = vec![TAG_COMPOUND];
+    for (name, longs) in arrays {
+        data.push(TAG_LONG_ARRAY);
+        write_string(&mut data, name);
+        data.extend((longs.len() as i32).to_be_bytes());
+        for long in longs {
+            data.extend(long.to_be_bytes());
+        }
+    }
+    data.push(TAG_END);
+    data
+}
+
+fn tag_type(value: &Json) -> u8 {
+    match value {
+        Json::Null | Json::String(_) => TAG_STRING,
+        Json::Bool(_) => TAG_BYTE,
+        Json::Number(n) if n.fract() == 0.0 && *n >= i32::MIN as f64 && *n <= i32::MAX as f64 => TAG_INT,
+        Json::Number(n) if n.fract() == 0.0 && n.abs() < 9.0e18 => TAG_LONG,
+        Json::Number(_) => TAG_DOUBLE,
+        Json::Array(_) => TAG_LIST,
+        Json::Object(_) => TAG_COMPOUND,
+    }
+}
+
+fn write_string(data: &mut Vec<u8>, s: &str) {
+    data.extend((s.len() as u16).to_be_bytes());
+    data.extend(s.as_bytes());
+}
+
+fn write_payload(data: &mut Vec<u8>, value: &Json) {
+    match value {
+        Json::Null => write_string(data, ""),
+        Json::Bool(b) => data.push(*b as u8),
+        Json::String(s) => write_string(data, s),
+        Json::Number(n) => match tag_type(value) {
+            TAG_INT => data.extend((*n as i32).to_be_bytes()),
+            TAG_LONG => data.extend((*n as i64).to_be_bytes()),
+            _ => data.extend(n.to_be_bytes()),
+        },
+        Json::Array(items) => {
+            // NBT-Listen sind einheitlich; gemischte Listen (etwa "extra" in Textkomponenten) werden zu Compounds
+            let element = items.first().map_or(TAG_END, tag_type);
+            let mixed = items.iter().any(|i| tag_type(i) != element);
+            data.push(if mixed { TAG_COMPOUND } else { element });
+            data.extend((items.len() as i32).to_be_bytes());
+            for item in items {
+                match item {
+                    Json::Object(_) => write_payload(data, item),
+                    _ if mixed => {
+                        let mut wrapped = BTreeMap::new();
+                        wrapped.insert(String::new(), item.clone());
+                        write_payload(data, &Json::Object(wrapped));
+                    }
+                    _ => write_payload(data, item),
+                }
+            }
+        }
+        Json::Object(map) => {
+            for (key, value) in map {
+                data.push(tag_type(value));
+                write_string(data, key);
+                write_payload(data, value);
+            }
+            data.push(TAG_END);
+        }
+    }
+}
+
+// NBT aus Dateien wie Strukturvorlagen: Wurzel-Compound mit Namen, nicht komprimiert. Zahlen und Arrays werden zu
+// Json-Zahlen bzw. -Listen, ihr genauer Typ geht dabei verloren
+pub fn read(data: &[u8]) -> Result<Json, String> {
+    let mut reader = Reader { data, pos: 0 };
+    if reader.u8()? != TAG_COMPOUND {
+        return Err("NBT beginnt nicht mit einem Compound".to_string());
+    }
+    reader.string()?;
+    reader.payload(TAG_COMPOUND, 0)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn bytes(&mut self, n: usize) -> Result<&[u8], String> {
+        let bytes = self.data.get(self.pos..self.pos + n).ok_or("NBT endet unerwartet")?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn length(&mut self) -> Result<usize, String> {
+        let length = self.i32()?;
+        if length < 0 || length as usize > self.data.len() - self.pos {
+            return Err(format!("Ungültige NBT-Länge {}", length));
+        }
+        Ok(length as usize)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let length = u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()) as usize;
+        Ok(String::from_utf8_lossy(self.bytes(length)?).into_owned())
+    }
+
+    fn payload(&mut self, tag: u8, depth: usize) -> Result<Json, String> {
+        if depth > MAX_DEPTH {
+            return Err("NBT zu tief verschachtelt".to_string());
+        }
+        Ok(match tag {
+            TAG_BYTE => Json::Number(self.u8()? as i8 as f64),
+            TAG_SHORT => Json::Number(i16::from_be_bytes(self.bytes(2)?.try_into().unwrap()) as f64),
+            TAG_INT => Json::Number(self.i32()? as f64),
+            TAG_LONG => Json::Number(i64::from_be_bytes(self.bytes(8)?.try_into().unwrap()) as f64),
+            TAG_FLOAT => Json::Number(f32::from_be_bytes(self.bytes(4)?.try_into().unwrap()) as f64),
+            TAG_DOUBLE => Json::Number(f64::from_be_bytes(self.bytes(8)?.try_into().unwrap())),
+            TAG_STRING => Json::String(self.string()?),
+            TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY => {
+                let element = match tag {
+                    TAG_BYTE_ARRAY => TAG_BYTE,
+                    TAG_INT_ARRAY => TAG_INT,
+                    _ => TAG_LONG,
+                };
+                let length = self.length()?;
+                Json::Array((0..length).map(|_| self.payload(element, depth + 1)).collect::<Result<_, _>>()?)
+            }
+            TAG_LIST => {
+                let element = self.u8()?;
+                let length = self.length()?;
+                if element == TAG_END && length > 0 {
+                    return Err("NBT-Liste ohne Elementtyp".to_string());
+                }
+                Json::Array((0..length).map(|_| self.payload(element, depth + 1)).collect::<Result<_, _>>()?)
+            }
+            TAG_COMPOUND => {
+                let mut map = BTreeMap::new();
+                loop {
+                    let tag = self.u8()?;
+                    if tag == TAG_END {
+                        break;
+                    }
+                    let key = self.string()?;
+                    map.insert(key, self.payload(tag, depth + 1)?);
+                }
+                Json::Object(map)
+            }
+            other => return Err(format!("Unbekannter NBT-Typ {}", other)),
+        })
+    }
+}
+
+// SNBT, wie es in Befehlen vorkommt: {CustomName:'"Bob"',Health:20.0f}, [1,2], true. Ergebnis als Json
+pub fn parse_snbt(text: &str) -> Result<Json, String> {
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+    let value = parser.value()?;
+    if parser.peek().is_some() {
+        return Err(format!("Unexpected '{}' at position {}", parser.chars[parser.pos], parser.pos));
+    }
+    Ok(value)
+}
+
+// SNBT-Schlüssel-Wert-Paare ohne Klammern, etwa die Komponenten in stone[damage=3,unbreakable={}]. Werte als Json
+pub fn parse_snbt_entries(text: &str, separator: char) -> Result<BTreeMap<String, Json>, String> {
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+    let map = if parser.peek().is_some() { parser.entries(separator)? } else { BTreeMap::new() };
+    if parser.peek().is_some() {
+        return Err(format!("Unexpected '{}' at position {}", parser.chars[parser.pos], parser.pos));
+    }
+    Ok(map)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(format!("Expected '{}' but found '{}' at position {}", expected, c, self.pos)),
+            None => Err(format!("Expected '{}' but reached the end", expected)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        match self.peek() {
+            Some('{') => self.compound(),
+            Some('[') => self.list(),
+            Some('"' | '\'') => self.quoted().map(Json::String),
+            Some(_) => Ok(literal(&self.unquoted()?)),
+            None => Err("Expected a value but reached the end".to_string()),
+        }
+    }
+
+    fn compound(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(BTreeMap::new()));
+        }
+        let map = self.entries(':')?;
+        self.expect('}')?;
+        Ok(Json::Object(map))
+    }
+
+    fn entries(&mut self, separator: char) -> Result<BTreeMap<String, Json>, String> {
+        let mut map = BTreeMap::new();
+        loop {
+            let key = match self.peek() {
+                Some('"' | '\'') => self.quoted()?,
+                _ => self.unquoted()?,
+            };
+            self.expect(separator)?;
+            map.insert(key, self.value()?);
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                _ => return Ok(map),
+            }
+        }
+    }
+
+    fn list(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        // Typisierte Arrays wie [I;1,2,3] werden wie normale Listen behandelt
+        if self.chars.get(self.pos + 1) == Some(&';') {
+            self.pos += 2;
+        }
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                _ => break,
+            }
+        }
+        self.expect(']')?;
+        Ok(Json::Array(items))
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let quote = self.chars[self.pos];
+        self.pos += 1;
+        let mut out = String::new();
+        while let Some(&c) = self.chars.get(self.pos) {
+            self.pos += 1;
+            match c {
+                '\\' => {
+                    if let Some(&escaped) = self.chars.get(self.pos) {
+                        out.push(escaped);
+                        self.pos += 1;
+                    }
+                }
+                c if c == quote => return Ok(out),
+                c => out.push(c),
+            }
+        }
+        Err("Unterminated string".to_string())
+    }
+
+    fn unquoted(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_alphanumeric() || "_-.+".contains(*c)) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(format!("Expected a value at position {}", self.pos));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+}
+
+// Zahlen mit optionalem Typ-Suffix (b, s, l, f, d), true/false oder ein Wort ohne Anführungszeichen
+fn literal(word: &str) -> Json {
+    match word {
+        "true" => return Json::Bool(true),
+        "false" => return Json::Bool(false),
+        _ => {}
+    }
+    let number = word.strip_suffix(['b', 'B', 's', 'S', 'l', 'L', 'f', 'F', 'd', 'D']).unwrap_or(word);
+    match number.parse::<f64>() {
+        Ok(n) if n.is_finite() => Json::Number(n),
+        _ => Json::String(word.to_string()),
+    }
+}
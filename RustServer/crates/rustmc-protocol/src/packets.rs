@@ -0,0 +1,236 @@
+use std::io::{Cursor, Read};
+use byteorder::{BigEndian, ReadBytesExt};
+use uuid::Uuid;
+use super::types::{read_bounded_string, read_optional, read_string_from_cursor, read_uuid, read_varint, read_varint_from_cursor, MAX_CHAT_LENGTH, MAX_USERNAME_LENGTH};
+use super::{serverbound, ConnectionState, ProtocolError};
+
+// Paket-ID vom Inhalt eines Frames trennen. Wie alle Decoder hier ohne Socket und Server, damit sie sich auch mit
+// beliebigen Bytes füttern lassen
+pub const MESSAGE_SIGNATURE_LENGTH: usize = 256;
+// Höchstlängen für öffentlichen Schlüssel und Mojangs Signatur darüber, wie Vanilla
+pub const MAX_PUBLIC_KEY_LENGTH: usize = 512;
+pub const MAX_KEY_SIGNATURE_LENGTH: usize = 4096;
+// Bestätigte Nachrichten als Bitfeld über die letzten 20, aufgerundet auf ganze Bytes
+const ACKNOWLEDGED_BYTES: usize = 3;
+
+pub fn split_id(body: Vec<u8>) -> Result<(i32, Cursor<Vec<u8>>), ProtocolError> {
+    let mut cursor = Cursor::new(body);
+    let id = read_varint(&mut cursor)?;
+    Ok((id, cursor))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handshake {
+    pub protocol_version: i32,
+    // Bei BungeeCord-Weiterleitung samt Profil, durch \0 getrennt
+    pub server_address: String,
+    pub server_port: u16,
+    pub next_state: i32,
+}
+
+impl Handshake {
+    pub fn decode(id: i32, cursor: &mut Cursor<Vec<u8>>) -> Result<Handshake, ProtocolError> {
+        if id != serverbound::HANDSHAKE {
+            return Err(ProtocolError::Unexpected { state: ConnectionState::Handshake, id });
+        }
+        Ok(Handshake {
+            protocol_version: read_varint_from_cursor(cursor)?,
+            server_address: read_string_from_cursor(cursor)?,
+            server_port: cursor.read_u16::<BigEndian>()?,
+            next_state: read_varint_from_cursor(cursor)?,
+        })
+    }
+}
+
+// Die UUID danach schickt nicht jede Version mit, der Server rechnet sie ohnehin selbst aus
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginStart {
+    pub username: String,
+}
+
+impl LoginStart {
+    pub fn decode(id: i32, cursor: &mut Cursor<Vec<u8>>) -> Result<LoginStart, ProtocolError> {
+        if id != serverbound::LOGIN_START {
+            return Err(ProtocolError::Unexpected { state: ConnectionState::Login, id });
+        }
+        Ok(LoginStart { username: read_bounded_string(cursor, MAX_USERNAME_LENGTH)? })
+    }
+}
+
+// Spielpakete, die sich ohne Kenntnis der Welt zerlegen lassen. Die übrigen lesen ihre Handler selbst, weil der Aufbau
+// dort vom Fenster oder der Version abhängt
+#[derive(Debug, Clone, PartialEq)]
+pub enum Play {
+    SetPlayerPosition { position: (f64, f64, f64), on_ground: bool },
+    SetPlayerPositionAndRotation { position: (f64, f64, f64), rotation: (f32, f32), on_ground: bool },
+    SetPlayerRotation { rotation: (f32, f32), on_ground: bool },
+    KeepAlive { id: i64 },
+    ConfirmTeleportation { id: i32 },
+    // Ohne den Schrägstrich
+    ChatCommand { command: String },
+    // Zeitstempel in Millisekunden; ohne Signatur, wenn der Client keine Chat-Sitzung hat. offset und acknowledged
+    // beziehen sich auf Nachrichten anderer Spieler, die der Client gesehen hat
+    ChatMessage { message: String, timestamp: i64, salt: i64, signature: Option<Vec<u8>>, offset: i32, acknowledged: [u8; ACKNOWLEDGED_BYTES] },
+    ClientCommand { action: i32 },
+    SetHeldItem { slot: i16 },
+    PlayerAction { status: i32, position: i64, face: u8, sequence: i32 },
+    // Trefferpunkt im Block und ob der Kopf darin steckt werden gelesen, aber nicht gebraucht
+    UseItemOn { hand: i32, position: i64, face: i32, sequence: i32 },
+    UseItem { hand: i32, sequence: i32 },
+    CustomPayload { channel: String, data: Vec<u8> },
+    // Schlüssel der Chat-Sitzung im DER-Format, Ablauf in Millisekunden seit 1970
+    PlayerSession { session_id: Uuid, expires_at: i64, public_key: Vec<u8>, key_signature: Vec<u8> },
+}
+
+impl Play {
+    // None für Pakete, die hier nicht zerlegt werden; der Cursor steht dann noch hinter der ID
+    pub fn decode(id: i32, cursor: &mut Cursor<Vec<u8>>) -> Result<Option<Play>, ProtocolError> {
+        let packet = match id {
+            serverbound::SET_PLAYER_POSITION => Play::SetPlayerPosition { position: read_position(cursor)?, on_ground: read_bool(cursor)? },
+            serverbound::SET_PLAYER_POSITION_AND_ROTATION => {
+                Play::SetPlayerPositionAndRotation { position: read_position(cursor)?, rotation: read_rotation(cursor)?, on_ground: read_bool(cursor)? }
+            }
+            serverbound::SET_PLAYER_ROTATION => Play::SetPlayerRotation { rotation: read_rotation(cursor)?, on_ground: read_bool(cursor)? },
+            serverbound::KEEP_ALIVE => Play::KeepAlive { id: cursor.read_i64::<BigEndian>()? },
+            serverbound::CONFIRM_TELEPORTATION => Play::ConfirmTeleportation { id: read_varint_from_cursor(cursor)? },
+            serverbound::CHAT_COMMAND => Play::ChatCommand { command: read_string_from_cursor(cursor)? },
+            serverbound::CHAT_MESSAGE => Play::ChatMessage {
+                message: read_bounded_string(cursor, MAX_CHAT_LENGTH)?,
+                timestamp: cursor.read_i64::<BigEndian>()?,
+                salt: cursor.read_i64::<BigEndian>()?,
+                signature: read_optional(cursor, |cursor| {
+                    let mut signature = vec![0; MESSAGE_SIGNATURE_LENGTH];
+                    cursor.read_exact(&mut signature).map(|()| signature)
+                })?,
+                offset: read_varint_from_cursor(cursor)?,
+                acknowledged: {
+                    let mut acknowledged = [0; ACKNOWLEDGED_BYTES];
+                    cursor.read_exact(&mut acknowledged)?;
+                    acknowledged
+                },
+            },
+            serverbound::CLIENT_COMMAND => Play::ClientCommand { action: read_varint_from_cursor(cursor)? },
+            serverbound::SET_HELD_ITEM => Play::SetHeldItem { slot: cursor.read_i16::<BigEndian>()? },
+            serverbound::PLAYER_ACTION => Play::PlayerAction {
+                status: read_varint_from_cursor(cursor)?,
+                position: cursor.read_i64::<BigEndian>()?,
+                face: cursor.read_u8()?,
+                sequence: read_varint_from_cursor(cursor)?,
+            },
+            serverbound::USE_ITEM_ON => {
+                let (hand, position, face) = (read_varint_from_cursor(cursor)?, cursor.read_i64::<BigEndian>()?, read_varint_from_cursor(cursor)?);
+                let _ = (cursor.read_f32::<BigEndian>()?, cursor.read_f32::<BigEndian>()?, cursor.read_f32::<BigEndian>()?, cursor.read_u8()?);
+                Play::UseItemOn { hand, position, face, sequence: read_varint_from_cursor(cursor)? }
+            }
+            serverbound::USE_ITEM => Play::UseItem { hand: read_varint_from_cursor(cursor)?, sequence: read_varint_from_cursor(cursor)? },
+            serverbound::CUSTOM_PAYLOAD => {
+                let channel = read_string_from_cursor(cursor)?;
+                let mut data = Vec::new();
+                cursor.read_to_end(&mut data)?;
+                Play::CustomPayload { channel, data }
+            }
+            serverbound::PLAYER_SESSION => Play::PlayerSession {
+                session_id: read_uuid(cursor)?,
+                expires_at: cursor.read_i64::<BigEndian>()?,
+                public_key: read_byte_array(cursor, MAX_PUBLIC_KEY_LENGTH)?,
+                key_signature: read_byte_array(cursor, MAX_KEY_SIGNATURE_LENGTH)?,
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(packet))
+    }
+}
+
+fn read_position(cursor: &mut Cursor<Vec<u8>>) -> Result<(f64, f64, f64), ProtocolError> {
+    Ok((cursor.read_f64::<BigEndian>()?, cursor.read_f64::<BigEndian>()?, cursor.read_f64::<BigEndian>()?))
+}
+
+fn read_rotation(cursor: &mut Cursor<Vec<u8>>) -> Result<(f32, f32), ProtocolError> {
+    Ok((cursor.read_f32::<BigEndian>()?, cursor.read_f32::<BigEndian>()?))
+}
+
+fn read_byte_array(cursor: &mut Cursor<Vec<u8>>, max_length: usize) -> Result<Vec<u8>, ProtocolError> {
+    let length = read_varint(cursor)?;
+    if length < 0 || length as usize > max_length {
+        return Err(ProtocolError::Malformed(format!("Byte array length {} exceeds {}", length, max_length)));
+    }
+    let mut data = vec![0; length as usize];
+    cursor.read_exact(&mut data)?;
+    Ok(data)
+}
+
+fn read_bool(cursor: &mut Cursor<Vec<u8>>) -> Result<bool, ProtocolError> {
+    Ok(cursor.read_u8()? != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{write_string_to_vec, write_varint_to_vec};
+
+    fn decode_play(id: i32, payload: Vec<u8>) -> Result<Option<Play>, ProtocolError> {
+        Play::decode(id, &mut Cursor::new(payload))
+    }
+
+    #[test]
+    fn handshake_round_trip_and_truncation() {
+        let mut body = write_varint_to_vec(serverbound::HANDSHAKE);
+        body.extend(write_varint_to_vec(767));
+        body.extend(write_string_to_vec("localhost"));
+        body.extend(25565u16.to_be_bytes());
+        body.extend(write_varint_to_vec(2));
+        let (id, mut cursor) = split_id(body.clone()).unwrap();
+        let handshake = Handshake::decode(id, &mut cursor).unwrap();
+        assert_eq!((handshake.protocol_version, handshake.server_address.as_str(), handshake.server_port, handshake.next_state), (767, "localhost", 25565, 2));
+        for end in 1..body.len() {
+            let (id, mut cursor) = split_id(body[..end].to_vec()).unwrap();
+            assert!(matches!(Handshake::decode(id, &mut cursor), Err(ProtocolError::Malformed(_))), "{} Bytes", end);
+        }
+    }
+
+    #[test]
+    fn absurd_varints_are_malformed() {
+        assert!(matches!(split_id(vec![0xFF; 6]), Err(ProtocolError::Malformed(_))));
+        assert!(matches!(split_id(Vec::new()), Err(ProtocolError::Malformed(_))));
+        // Negative und riesige Stringlängen
+        for length in [-1, i32::MAX] {
+            let mut body = write_varint_to_vec(serverbound::LOGIN_START);
+            body.extend(write_varint_to_vec(length));
+            let (id, mut cursor) = split_id(body).unwrap();
+            assert!(matches!(LoginStart::decode(id, &mut cursor), Err(ProtocolError::Malformed(_))));
+        }
+    }
+
+    #[test]
+    fn truncated_play_packets_are_malformed() {
+        let mut position = Vec::new();
+        for value in [1.5f64, 64.0, -3.25] {
+            position.extend(value.to_be_bytes());
+        }
+        position.push(1);
+        assert_eq!(decode_play(serverbound::SET_PLAYER_POSITION, position.clone()).unwrap(), Some(Play::SetPlayerPosition { position: (1.5, 64.0, -3.25), on_ground: true }));
+        for end in 0..position.len() {
+            assert!(matches!(decode_play(serverbound::SET_PLAYER_POSITION, position[..end].to_vec()), Err(ProtocolError::Malformed(_))), "{} Bytes", end);
+        }
+        let mut chat = write_varint_to_vec(10);
+        chat.extend(b"hello");
+        assert!(matches!(decode_play(serverbound::CHAT_MESSAGE, chat), Err(ProtocolError::Malformed(_))));
+        let mut chat = write_string_to_vec("hello");
+        chat.extend(1_700_000_000_000i64.to_be_bytes());
+        chat.extend(42i64.to_be_bytes());
+        chat.push(0);
+        chat.extend(write_varint_to_vec(0));
+        chat.extend([0; 3]);
+        let unsigned = Play::ChatMessage { message: "hello".to_string(), timestamp: 1_700_000_000_000, salt: 42, signature: None, offset: 0, acknowledged: [0; 3] };
+        assert_eq!(decode_play(serverbound::CHAT_MESSAGE, chat.clone()).unwrap(), Some(unsigned));
+        for end in 0..chat.len() {
+            assert!(matches!(decode_play(serverbound::CHAT_MESSAGE, chat[..end].to_vec()), Err(ProtocolError::Malformed(_))), "{} Bytes", end);
+        }
+        // Signatur angekündigt, aber zu kurz
+        let mut signed = chat[..chat.len() - 4].to_vec();
+        *signed.last_mut().unwrap() = 1;
+        signed.extend([7; 100]);
+        assert!(matches!(decode_play(serverbound::CHAT_MESSAGE, signed), Err(ProtocolError::Malformed(_))));
+        assert_eq!(decode_play(serverbound::CLICK_CONTAINER, vec![0; 4]).unwrap(), None);
+    }
+}
@@ -0,0 +1,338 @@
+use std::io::{self, Cursor, Read};
+use byteorder::{BigEndian, ReadBytesExt};
+use uuid::Uuid;
+use super::ProtocolError;
+
+// Höchstlänge eines Strings in UTF-16-Einheiten, wenn das Feld keine eigene hat
+pub const MAX_STRING_LENGTH: usize = 32767;
+pub const MAX_USERNAME_LENGTH: usize = 16;
+pub const MAX_CHAT_LENGTH: usize = 256;
+const MAX_VARINT_BYTES: u32 = 5;
+const MAX_VARLONG_BYTES: u32 = 10;
+// Größte Verschiebung, die ein relatives Bewegungspaket noch ausdrücken kann (1/4096 Block als i16)
+const DELTA_SCALE: f64 = 4096.0;
+
+pub fn write_varint_to_vec(value: i32) -> Vec<u8> {
+    // Negative Werte als u32 schieben, sonst endet die Schleife nie (immer 5 Bytes)
+    write_varlong_to_vec(value as u32 as i64)
+}
+
+pub fn write_varlong_to_vec(value: i64) -> Vec<u8> {
+    let mut value = value as u64;
+    let mut buf = vec![];
+    loop {
+        let mut temp = (value & 0b0111_1111) as u8;
+        value >>= 7;
+        if value != 0 {
+            temp |= 0b1000_0000;
+        }
+        buf.push(temp);
+        if value == 0 {
+            break;
+        }
+    }
+    buf
+}
+
+pub fn write_string_to_vec(s: &str) -> Vec<u8> {
+    let mut buf = vec![];
+    buf.extend(write_varint_to_vec(s.len() as i32));
+    buf.extend(s.as_bytes());
+    buf
+}
+
+// Lese-Gegenstück für beliebige Quellen; bricht nach fünf Bytes ab, bevor die Verschiebung überläuft
+pub fn read_varint(reader: &mut impl Read) -> io::Result<i32> {
+    let mut result = 0u32;
+    for i in 0..MAX_VARINT_BYTES {
+        let byte = reader.read_u8()?;
+        result |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(result as i32);
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "VarInt too big"))
+}
+
+pub fn read_varlong(reader: &mut impl Read) -> io::Result<i64> {
+    let mut result = 0u64;
+    for i in 0..MAX_VARLONG_BYTES {
+        let byte = reader.read_u8()?;
+        result |= ((byte & 0x7F) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(result as i64);
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "VarLong too big"))
+}
+
+pub fn read_varint_from_cursor(cursor: &mut Cursor<Vec<u8>>) -> Result<i32, io::Error> {
+    read_varint(cursor)
+}
+
+pub fn read_string_from_cursor(cursor: &mut Cursor<Vec<u8>>) -> Result<String, ProtocolError> {
+    read_bounded_string(cursor, MAX_STRING_LENGTH)
+}
+
+fn remaining(cursor: &Cursor<Vec<u8>>) -> usize {
+    cursor.get_ref().len().saturating_sub(cursor.position() as usize)
+}
+
+// String mit höchstens max_length UTF-16-Einheiten; die Bytelänge wird vor dem Lesen gegen das Limit
+// und den Rest des Pakets geprüft
+pub fn read_bounded_string(cursor: &mut Cursor<Vec<u8>>, max_length: usize) -> Result<String, ProtocolError> {
+    let length = read_varint(cursor)?;
+    // UTF-8 braucht bis zu drei Bytes für eine UTF-16-Einheit
+    if length < 0 || length as usize > max_length * 3 {
+        return Err(ProtocolError::Malformed(format!("String length {} exceeds {} characters", length, max_length)));
+    }
+    if length as usize > remaining(cursor) {
+        return Err(ProtocolError::Malformed("String is longer than the packet".to_string()));
+    }
+    let mut buffer = vec![0u8; length as usize];
+    cursor.read_exact(&mut buffer)?;
+    let text = String::from_utf8(buffer).map_err(|_| ProtocolError::Malformed("Invalid UTF-8 string".to_string()))?;
+    if text.encode_utf16().count() > max_length {
+        return Err(ProtocolError::Malformed(format!("String exceeds {} characters", max_length)));
+    }
+    Ok(text)
+}
+
+// Blockposition als i64: 26 Bit X, 26 Bit Z, 12 Bit Y
+pub fn encode_position(x: i32, y: i32, z: i32) -> i64 {
+    ((x as i64 & 0x3FFFFFF) << 38) | ((z as i64 & 0x3FFFFFF) << 12) | (y as i64 & 0xFFF)
+}
+
+pub fn decode_position(value: i64) -> (i32, i32, i32) {
+    ((value >> 38) as i32, (value << 52 >> 52) as i32, (value << 26 >> 38) as i32)
+}
+
+// Winkel in 1/256 einer vollen Drehung
+pub fn angle_to_byte(degrees: f32) -> u8 {
+    ((degrees.rem_euclid(360.0) / 360.0) * 256.0) as u8
+}
+
+pub fn angle_from_byte(angle: u8) -> f32 {
+    angle as f32 * 360.0 / 256.0
+}
+
+// Relative Bewegung in 1/4096 Block; None, wenn sie nicht in ein i16 passt und ein Teleport nötig ist
+pub fn encode_delta(from: f64, to: f64) -> Option<i16> {
+    let delta = (to * DELTA_SCALE).round() - (from * DELTA_SCALE).round();
+    (i16::MIN as f64..=i16::MAX as f64).contains(&delta).then_some(delta as i16)
+}
+
+pub fn decode_delta(from: f64, delta: i16) -> f64 {
+    from + delta as f64 / DELTA_SCALE
+}
+
+// UUIDs sind seit 1.16 im Protokoll 16 rohe Bytes, nicht mehr der String mit Bindestrichen
+pub fn write_uuid(uuid: &Uuid) -> [u8; 16] {
+    *uuid.as_bytes()
+}
+
+pub fn read_uuid(reader: &mut impl Read) -> io::Result<Uuid> {
+    let mut bytes = [0u8; 16];
+    reader.read_exact(&mut bytes)?;
+    Ok(Uuid::from_bytes(bytes))
+}
+
+// Vorangestelltes bool, danach der Wert, falls vorhanden
+pub fn write_optional<T>(value: Option<&T>, write: impl Fn(&T) -> Vec<u8>) -> Vec<u8> {
+    match value {
+        Some(value) => {
+            let mut buf = vec![1];
+            buf.extend(write(value));
+            buf
+        }
+        None => vec![0],
+    }
+}
+
+pub fn read_optional<T, E: Into<ProtocolError>>(cursor: &mut Cursor<Vec<u8>>, read: impl Fn(&mut Cursor<Vec<u8>>) -> Result<T, E>) -> Result<Option<T>, ProtocolError> {
+    match cursor.read_u8()? {
+        0 => Ok(None),
+        _ => read(cursor).map(Some).map_err(Into::into),
+    }
+}
+
+// Länge als VarInt, danach die Elemente
+pub fn write_array<T>(values: &[T], write: impl Fn(&T) -> Vec<u8>) -> Vec<u8> {
+    let mut buf = write_varint_to_vec(values.len() as i32);
+    for value in values {
+        buf.extend(write(value));
+    }
+    buf
+}
+
+// Jedes Element ist mindestens ein Byte lang, mehr Elemente als Bytes im Paket kann es also nicht geben
+pub fn read_array<T, E: Into<ProtocolError>>(cursor: &mut Cursor<Vec<u8>>, read: impl Fn(&mut Cursor<Vec<u8>>) -> Result<T, E>) -> Result<Vec<T>, ProtocolError> {
+    let length = read_varint(cursor)?;
+    if length < 0 || length as usize > remaining(cursor) {
+        return Err(ProtocolError::Malformed(format!("Invalid array length {}", length)));
+    }
+    (0..length).map(|_| read(cursor).map_err(Into::into)).collect()
+}
+
+// Bitfeld aus Longs, etwa die Licht-Masken im Chunk-Paket
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> BitSet {
+        BitSet::default()
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        let word = index / 64;
+        if word >= self.words.len() {
+            if !value {
+                return;
+            }
+            self.words.resize(word + 1, 0);
+        }
+        if value {
+            self.words[word] |= 1 << (index % 64);
+        } else {
+            self.words[word] &= !(1 << (index % 64));
+        }
+        // Wie bei Java ohne abschließende leere Longs, damit gleiche Mengen gleich kodiert werden
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.words.get(index / 64).is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        write_array(&self.words, |word| word.to_be_bytes().to_vec())
+    }
+
+    pub fn read(cursor: &mut Cursor<Vec<u8>>) -> Result<BitSet, ProtocolError> {
+        let length = read_varint(cursor)?;
+        if length < 0 || length as usize > remaining(cursor) / 8 {
+            return Err(ProtocolError::Malformed(format!("Invalid bitset length {}", length)));
+        }
+        let words = (0..length).map(|_| cursor.read_u64::<BigEndian>()).collect::<io::Result<Vec<u64>>>()?;
+        let mut set = BitSet { words };
+        while set.words.last() == Some(&0) {
+            set.words.pop();
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(bytes: Vec<u8>) -> Cursor<Vec<u8>> {
+        Cursor::new(bytes)
+    }
+
+    #[test]
+    fn varint_round_trip() {
+        for value in [0, 1, 127, 128, 255, 25565, 2097151, i32::MAX, -1, i32::MIN] {
+            let bytes = write_varint_to_vec(value);
+            assert!(bytes.len() <= 5);
+            assert_eq!(read_varint(&mut cursor(bytes)).unwrap(), value);
+        }
+        assert_eq!(write_varint_to_vec(-1), vec![0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+    }
+
+    #[test]
+    fn varint_rejects_six_bytes() {
+        let error = read_varint(&mut cursor(vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01])).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn varlong_round_trip() {
+        for value in [0, 1, 300, i32::MAX as i64 + 1, i64::MAX, -1, i64::MIN] {
+            let bytes = write_varlong_to_vec(value);
+            assert!(bytes.len() <= 10);
+            assert_eq!(read_varlong(&mut cursor(bytes)).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn string_round_trip_and_bounds() {
+        let text = "Grüße, Steve";
+        assert_eq!(read_string_from_cursor(&mut cursor(write_string_to_vec(text))).unwrap(), text);
+        assert!(read_bounded_string(&mut cursor(write_string_to_vec("seventeen_letters")), MAX_USERNAME_LENGTH).is_err());
+        // Länge größer als der Rest des Pakets
+        let mut bytes = write_varint_to_vec(100);
+        bytes.extend(b"short");
+        assert!(read_string_from_cursor(&mut cursor(bytes)).is_err());
+        assert!(read_string_from_cursor(&mut cursor(write_varint_to_vec(-1))).is_err());
+    }
+
+    #[test]
+    fn position_round_trip() {
+        for (x, y, z) in [(0, 0, 0), (1, 64, -1), (-33554432, -2048, 33554431), (18357644, 831, -20882616)] {
+            assert_eq!(decode_position(encode_position(x, y, z)), (x, y, z));
+        }
+    }
+
+    #[test]
+    fn angle_round_trip() {
+        assert_eq!(angle_to_byte(0.0), 0);
+        assert_eq!(angle_to_byte(90.0), 64);
+        assert_eq!(angle_to_byte(-90.0), 192);
+        assert_eq!(angle_to_byte(360.0), 0);
+        for byte in [0u8, 1, 64, 128, 255] {
+            assert_eq!(angle_to_byte(angle_from_byte(byte)), byte);
+        }
+    }
+
+    #[test]
+    fn delta_round_trip() {
+        let delta = encode_delta(10.0, 12.5).unwrap();
+        assert_eq!(delta, 10240);
+        assert_eq!(decode_delta(10.0, delta), 12.5);
+        assert_eq!(encode_delta(0.0, -7.99), Some(-32727));
+        assert_eq!(encode_delta(0.0, 8.0), None);
+    }
+
+    #[test]
+    fn uuid_is_sixteen_raw_bytes() {
+        let uuid = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+        let bytes = write_uuid(&uuid);
+        assert_eq!(bytes[0], 0x06);
+        assert_eq!(read_uuid(&mut cursor(bytes.to_vec())).unwrap(), uuid);
+    }
+
+    #[test]
+    fn optional_and_array_round_trip() {
+        let some = write_optional(Some(&"hi"), |s| write_string_to_vec(s));
+        assert_eq!(read_optional(&mut cursor(some), read_string_from_cursor).unwrap().as_deref(), Some("hi"));
+        let none = write_optional(None::<&&str>, |s| write_string_to_vec(s));
+        assert_eq!(none, vec![0]);
+        assert_eq!(read_optional(&mut cursor(none), read_string_from_cursor).unwrap(), None);
+
+        let values = vec![1, 300, -5];
+        let bytes = write_array(&values, |v| write_varint_to_vec(*v));
+        let read = read_array(&mut cursor(bytes), |c| read_varint(c).map_err(|e| e.to_string())).unwrap();
+        assert_eq!(read, values);
+        assert!(read_array(&mut cursor(write_varint_to_vec(1000)), |c| read_varint(c).map_err(|e| e.to_string())).is_err());
+    }
+
+    #[test]
+    fn bitset_round_trip() {
+        let mut set = BitSet::new();
+        assert_eq!(set.to_bytes(), vec![0]);
+        set.set(0, true);
+        set.set(65, true);
+        set.set(200, false);
+        assert!(set.get(0) && set.get(65) && !set.get(1) && !set.get(200));
+        let bytes = set.to_bytes();
+        assert_eq!(bytes.len(), 1 + 2 * 8);
+        assert_eq!(BitSet::read(&mut cursor(bytes)).unwrap(), set);
+        set.set(65, false);
+        assert_eq!(set.to_bytes().len(), 1 + 8);
+    }
+}
@@ -0,0 +1,234 @@
+use super::{assets, clientbound, serverbound, ConnectionState, Direction};
+
+// Unterstützte Client-Versionen. Der Server arbeitet intern mit den Paket-IDs der neuesten (PROTOCOL_VERSION)
+// und übersetzt sie im Spielzustand für ältere Clients; Status- und Login-Pakete sind in allen gleich
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProtocolVersion {
+    V1_20_4,
+    V1_20_5,
+    V1_21,
+}
+
+pub const SUPPORTED: [ProtocolVersion; 3] = [ProtocolVersion::V1_20_4, ProtocolVersion::V1_20_5, ProtocolVersion::V1_21];
+pub const LATEST: ProtocolVersion = ProtocolVersion::V1_21;
+
+// (interne ID, ID in der jeweiligen Version)
+type IdMap = &'static [(i32, i32)];
+
+// Alle Spielpakete, die 1.20.5 verschoben hat
+const CLIENTBOUND_1_20_4: IdMap = &[
+    (clientbound::CUSTOM_PAYLOAD, 0x18),
+    (clientbound::DISCONNECT, 0x1B),
+    (clientbound::ENTITY_EVENT, 0x1D),
+    (clientbound::EXPLOSION, 0x1E),
+    (clientbound::UNLOAD_CHUNK, 0x1F),
+    (clientbound::GAME_EVENT, 0x20),
+    (clientbound::HURT_ANIMATION, 0x22),
+    (clientbound::INITIALIZE_WORLD_BORDER, 0x23),
+    (clientbound::KEEP_ALIVE, 0x24),
+    (clientbound::CHUNK_DATA, 0x25),
+    (clientbound::WORLD_EVENT, 0x26),
+    (clientbound::UPDATE_LIGHT, 0x28),
+    (clientbound::LOGIN_PLAY, 0x29),
+    (clientbound::MAP_DATA, 0x2A),
+    (clientbound::MERCHANT_OFFERS, 0x2B),
+    (clientbound::UPDATE_ENTITY_POSITION, 0x2C),
+    (clientbound::UPDATE_ENTITY_POSITION_AND_ROTATION, 0x2D),
+    (clientbound::UPDATE_ENTITY_ROTATION, 0x2E),
+    (clientbound::MOVE_VEHICLE, 0x2F),
+    (clientbound::OPEN_SCREEN, 0x31),
+    (clientbound::OPEN_SIGN_EDITOR, 0x32),
+    (clientbound::PLACE_GHOST_RECIPE, 0x35),
+    (clientbound::PLAYER_ABILITIES, 0x36),
+    (clientbound::COMBAT_DEATH, 0x3A),
+    (clientbound::PLAYER_INFO_REMOVE, 0x3B),
+    (clientbound::PLAYER_INFO_UPDATE, 0x3C),
+    (clientbound::SYNCHRONIZE_PLAYER_POSITION, 0x3E),
+    (clientbound::UPDATE_RECIPE_BOOK, 0x3F),
+    (clientbound::REMOVE_ENTITIES, 0x40),
+    (clientbound::REMOVE_ENTITY_EFFECT, 0x41),
+    (clientbound::RESET_SCORE, 0x42),
+    (clientbound::ADD_RESOURCE_PACK, 0x44),
+    (clientbound::RESPAWN, 0x45),
+    (clientbound::SET_HEAD_ROTATION, 0x46),
+    (clientbound::UPDATE_SECTION_BLOCKS, 0x47),
+    (clientbound::SERVER_DATA, 0x49),
+    (clientbound::SET_BORDER_CENTER, 0x4B),
+    (clientbound::SET_BORDER_LERP_SIZE, 0x4C),
+    (clientbound::SET_BORDER_SIZE, 0x4D),
+    (clientbound::SET_BORDER_WARNING_DELAY, 0x4E),
+    (clientbound::SET_BORDER_WARNING_DISTANCE, 0x4F),
+    (clientbound::SET_CENTER_CHUNK, 0x52),
+    (clientbound::SET_DEFAULT_SPAWN_POSITION, 0x54),
+    (clientbound::DISPLAY_OBJECTIVE, 0x55),
+    (clientbound::SET_ENTITY_METADATA, 0x56),
+    (clientbound::SET_ENTITY_VELOCITY, 0x58),
+    (clientbound::SET_EQUIPMENT, 0x59),
+    (clientbound::SET_EXPERIENCE, 0x5A),
+    (clientbound::SET_HEALTH, 0x5B),
+    (clientbound::UPDATE_OBJECTIVES, 0x5C),
+    (clientbound::SET_PASSENGERS, 0x5D),
+    (clientbound::UPDATE_SCORE, 0x5F),
+    (clientbound::UPDATE_TIME, 0x62),
+    (clientbound::SOUND_EFFECT, 0x66),
+    (clientbound::SYSTEM_CHAT, 0x69),
+    (clientbound::TAKE_ITEM_ENTITY, 0x6C),
+    (clientbound::TELEPORT_ENTITY, 0x6D),
+    (clientbound::UPDATE_ATTRIBUTES, 0x71),
+    (clientbound::ENTITY_EFFECT, 0x72),
+    (clientbound::UPDATE_RECIPES, 0x73),
+];
+const SERVERBOUND_1_20_4: IdMap = &[
+    (serverbound::CHAT_MESSAGE, 0x05),
+    (serverbound::PLAYER_SESSION, 0x06),
+    (serverbound::CLIENT_COMMAND, 0x08),
+    (serverbound::CLIENT_INFORMATION, 0x09),
+    (serverbound::CLICK_CONTAINER_BUTTON, 0x0C),
+    (serverbound::CLICK_CONTAINER, 0x0D),
+    (serverbound::CLOSE_CONTAINER, 0x0E),
+    (serverbound::CUSTOM_PAYLOAD, 0x10),
+    (serverbound::INTERACT, 0x13),
+    (serverbound::KEEP_ALIVE, 0x15),
+    (serverbound::SET_PLAYER_POSITION, 0x17),
+    (serverbound::SET_PLAYER_POSITION_AND_ROTATION, 0x18),
+    (serverbound::SET_PLAYER_ROTATION, 0x19),
+    (serverbound::MOVE_VEHICLE, 0x1B),
+    (serverbound::PADDLE_BOAT, 0x1C),
+    (serverbound::PLACE_RECIPE, 0x1F),
+    (serverbound::PLAYER_ACTION, 0x21),
+    (serverbound::PLAYER_COMMAND, 0x22),
+    (serverbound::PLAYER_INPUT, 0x23),
+    (serverbound::CHANGE_RECIPE_BOOK_SETTINGS, 0x25),
+    (serverbound::SET_SEEN_RECIPE, 0x26),
+    (serverbound::RENAME_ITEM, 0x27),
+    (serverbound::RESOURCE_PACK_RESPONSE, 0x28),
+    (serverbound::SELECT_TRADE, 0x2A),
+    (serverbound::SET_HELD_ITEM, 0x2C),
+    (serverbound::SIGN_UPDATE, 0x32),
+    (serverbound::SWING_ARM, 0x33),
+    (serverbound::TELEPORT_TO_ENTITY, 0x34),
+    (serverbound::USE_ITEM_ON, 0x35),
+    (serverbound::USE_ITEM, 0x36),
+];
+// Konfigurationspakete vor 1.20.5, als es noch keine Cookies, Known Packs und Reset Chat gab
+const CONFIGURATION_CLIENTBOUND_1_20_4: IdMap = &[
+    (clientbound::CONFIGURATION_CUSTOM_PAYLOAD, 0x00),
+    (clientbound::CONFIGURATION_DISCONNECT, 0x01),
+    (clientbound::FINISH_CONFIGURATION, 0x02),
+    (clientbound::REGISTRY_DATA, 0x05),
+    (clientbound::FEATURE_FLAGS, 0x08),
+];
+const CONFIGURATION_SERVERBOUND_1_20_4: IdMap = &[
+    (serverbound::CONFIGURATION_CLIENT_INFORMATION, 0x00),
+    (serverbound::CONFIGURATION_CUSTOM_PAYLOAD, 0x01),
+    (serverbound::ACKNOWLEDGE_FINISH_CONFIGURATION, 0x02),
+    (serverbound::CONFIGURATION_KEEP_ALIVE, 0x03),
+    (serverbound::CONFIGURATION_PONG, 0x04),
+    (serverbound::CONFIGURATION_RESOURCE_PACK_RESPONSE, 0x05),
+];
+
+impl ProtocolVersion {
+    // 1.20.5/1.20.6 und 1.21/1.21.1 teilen sich jeweils eine Protokollnummer
+    pub fn from_protocol(protocol: i32) -> Option<ProtocolVersion> {
+        SUPPORTED.into_iter().find(|v| v.protocol() == protocol)
+    }
+
+    pub fn protocol(self) -> i32 {
+        match self {
+            ProtocolVersion::V1_20_4 => 765,
+            ProtocolVersion::V1_20_5 => 766,
+            ProtocolVersion::V1_21 => 767,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProtocolVersion::V1_20_4 => "1.20.4",
+            ProtocolVersion::V1_20_5 => "1.20.6",
+            ProtocolVersion::V1_21 => "1.21.1",
+        }
+    }
+
+    // Bereich für Meldungen an Clients mit anderer Version, etwa "1.20.4-1.21.1"
+    pub fn range() -> String {
+        format!("{}-{}", SUPPORTED[0].name(), LATEST.name())
+    }
+
+    fn play_mappings(self) -> (IdMap, IdMap) {
+        match self {
+            ProtocolVersion::V1_20_4 => (CLIENTBOUND_1_20_4, SERVERBOUND_1_20_4),
+            ProtocolVersion::V1_20_5 | ProtocolVersion::V1_21 => (&[], &[]),
+        }
+    }
+
+    // Interne ID eines ausgehenden Spielpakets -> ID dieser Version; packets.json der Version hat Vorrang
+    pub fn clientbound(self, id: i32) -> i32 {
+        if let Some(wire) = assets::clientbound(self, id) {
+            return wire;
+        }
+        self.play_mappings().0.iter().find(|(internal, _)| *internal == id).map_or(id, |(_, wire)| *wire)
+    }
+
+    // ID eines eingehenden Spielpakets dieser Version -> interne ID. Pakete, die intern eine andere
+    // Bedeutung hätten, werden auf -1 abgebildet und damit ignoriert
+    pub fn serverbound(self, id: i32) -> i32 {
+        if let Some(internal) = assets::serverbound(self, id) {
+            return internal;
+        }
+        if assets::moves_serverbound(self, id) {
+            return -1;
+        }
+        let mappings = self.play_mappings().1;
+        if let Some((internal, _)) = mappings.iter().find(|(_, wire)| *wire == id) {
+            return *internal;
+        }
+        if mappings.iter().any(|(internal, _)| *internal == id) {
+            return -1;
+        }
+        id
+    }
+
+    // ID eines Pakets in dieser Version nach den eingebauten Tabellen, ohne packets.json, etwa für das Manifest. None
+    // für Konfigurationspakete, die es in der Version noch nicht gab
+    pub fn packet_id(self, state: ConnectionState, direction: Direction, internal: i32) -> Option<i32> {
+        let (mappings, complete) = match (state, direction) {
+            (ConnectionState::Play, Direction::Clientbound) => (self.play_mappings().0, false),
+            (ConnectionState::Play, Direction::Serverbound) => (self.play_mappings().1, false),
+            (ConnectionState::Configuration, Direction::Clientbound) if self == ProtocolVersion::V1_20_4 => (CONFIGURATION_CLIENTBOUND_1_20_4, true),
+            (ConnectionState::Configuration, Direction::Serverbound) if self == ProtocolVersion::V1_20_4 => (CONFIGURATION_SERVERBOUND_1_20_4, true),
+            _ => return Some(internal),
+        };
+        match mappings.iter().find(|(id, _)| *id == internal) {
+            Some((_, wire)) => Some(*wire),
+            None if complete => None,
+            None => Some(internal),
+        }
+    }
+
+    // Wie clientbound und serverbound, aber für die Konfigurationsphase; packets.json betrifft nur Spielpakete
+    pub fn configuration_clientbound(self, id: i32) -> i32 {
+        match self {
+            ProtocolVersion::V1_20_4 => CONFIGURATION_CLIENTBOUND_1_20_4.iter().find(|(internal, _)| *internal == id).map_or(id, |(_, wire)| *wire),
+            ProtocolVersion::V1_20_5 | ProtocolVersion::V1_21 => id,
+        }
+    }
+
+    pub fn configuration_serverbound(self, id: i32) -> i32 {
+        match self {
+            ProtocolVersion::V1_20_4 => CONFIGURATION_SERVERBOUND_1_20_4.iter().find(|(_, wire)| *wire == id).map_or(-1, |(internal, _)| *internal),
+            ProtocolVersion::V1_20_5 | ProtocolVersion::V1_21 => id,
+        }
+    }
+
+    // Seit 1.20.5 einigen sich Client und Server über Known Packs auf gemeinsame Datenpakete; Registry Data kommt
+    // dann je Register und ohne die Einträge, die der Client schon kennt. 1.20.4 bekommt alles als ein NBT
+    pub fn has_known_packs(self) -> bool {
+        self >= ProtocolVersion::V1_20_5
+    }
+
+    // Login Success mit "strict error handling" und Join Game mit "enforces secure chat" sowie
+    // numerischem Dimensionstyp gibt es erst seit 1.20.5
+    pub fn has_1_20_5_login(self) -> bool {
+        self >= ProtocolVersion::V1_20_5
+    }
+}
@@ -0,0 +1,618 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use rustmc_protocol::json::Json;
+use rustmc_protocol::types::write_varint_to_vec;
+use rustmc_protocol::{block, nbt};
+
+pub const MIN_Y: i32 = -64;
+pub const HEIGHT: i32 = 384;
+pub const SECTION_COUNT: usize = (HEIGHT / 16) as usize;
+pub const PLAINS_BIOME: u16 = 39;
+
+fn read_u8(input: &mut &[u8]) -> Result<u8, String> {
+    let (&byte, rest) = input.split_first().ok_or("Unerwartetes Ende der Chunk-Daten")?;
+    *input = rest;
+    Ok(byte)
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<i32, String> {
+    let mut result = 0;
+    for i in 0..5 {
+        let byte = read_u8(input)?;
+        result |= ((byte & 0x7F) as i32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err("VarInt in Chunk-Daten zu lang".to_string())
+}
+
+fn read_bytes<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], String> {
+    if input.len() < N {
+        return Err("Unerwartetes Ende der Chunk-Daten".to_string());
+    }
+    let (bytes, rest) = input.split_at(N);
+    *input = rest;
+    Ok(bytes.try_into().unwrap())
+}
+
+// Parameter eines Paletten-Containers: Anzahl der Einträge und erlaubte Bits pro Eintrag
+#[derive(Debug, Clone, Copy)]
+pub struct PaletteKind {
+    pub size: usize,
+    pub min_bits: u8,
+    pub max_indirect_bits: u8,
+    pub global_bits: u8,
+}
+
+pub const BLOCK_PALETTE: PaletteKind = PaletteKind {
+    size: 4096,
+    min_bits: 4,
+    max_indirect_bits: 8,
+    global_bits: block::GLOBAL_BITS,
+};
+
+pub const BIOME_PALETTE: PaletteKind = PaletteKind {
+    size: 64,
+    min_bits: 1,
+    max_indirect_bits: 3,
+    global_bits: 6,
+};
+
+// Bit-gepacktes Array mit Palette, im selben Layout wie das Netzwerkformat
+#[derive(Debug, Clone)]
+pub struct PalettedContainer {
+    kind: PaletteKind,
+    bits: u8,
+    palette: Vec<u16>,
+    data: Vec<u64>,
+}
+
+impl PalettedContainer {
+    pub fn new(kind: PaletteKind, value: u16) -> PalettedContainer {
+        PalettedContainer {
+            kind,
+            bits: 0,
+            palette: vec![value],
+            data: Vec::new(),
+        }
+    }
+
+    fn is_direct(&self) -> bool {
+        self.bits > self.kind.max_indirect_bits
+    }
+
+    fn raw(&self, index: usize) -> u16 {
+        if self.bits == 0 {
+            return 0;
+        }
+        let per_long = 64 / self.bits as usize;
+        let shift = (index % per_long) * self.bits as usize;
+        ((self.data[index / per_long] >> shift) & ((1u64 << self.bits) - 1)) as u16
+    }
+
+    fn set_raw(&mut self, index: usize, value: u16) {
+        let per_long = 64 / self.bits as usize;
+        let shift = (index % per_long) * self.bits as usize;
+        let mask = ((1u64 << self.bits) - 1) << shift;
+        let long = &mut self.data[index / per_long];
+        *long = (*long & !mask) | ((value as u64) << shift);
+    }
+
+    pub fn get(&self, index: usize) -> u16 {
+        let raw = self.raw(index);
+        if self.is_direct() {
+            raw
+        } else {
+            self.palette[raw as usize]
+        }
+    }
+
+    // Ob ein Wert test erfüllen könnte; die Palette kann auch nicht mehr benutzte Werte enthalten, die direkte muss
+    // man ganz durchsuchen
+    pub fn may_contain(&self, test: &dyn Fn(u16) -> bool) -> bool {
+        self.is_direct() || self.palette.iter().any(|&value| test(value))
+    }
+
+    pub fn set(&mut self, index: usize, value: u16) {
+        if self.is_direct() {
+            self.set_raw(index, value);
+            return;
+        }
+        let palette_index = match self.palette.iter().position(|v| *v == value) {
+            Some(i) => i,
+            None => {
+                self.palette.push(value);
+                if self.palette.len() > 1 << self.bits {
+                    self.resize();
+                    if self.is_direct() {
+                        self.set_raw(index, value);
+                        return;
+                    }
+                }
+                self.palette.len() - 1
+            }
+        };
+        if self.bits > 0 {
+            self.set_raw(index, palette_index as u16);
+        }
+    }
+
+    // Vergrößert die Bits pro Eintrag, sobald die Palette nicht mehr hineinpasst
+    fn resize(&mut self) {
+        let values: Vec<u16> = (0..self.kind.size)
+            .map(|i| if self.bits == 0 { self.palette[0] } else { self.get(i) })
+            .collect();
+        let needed = (usize::BITS - (self.palette.len() - 1).leading_zeros()) as u8;
+        let bits = needed.max(self.kind.min_bits);
+        self.bits = if bits > self.kind.max_indirect_bits { self.kind.global_bits } else { bits };
+        let per_long = 64 / self.bits as usize;
+        self.data = vec![0; self.kind.size.div_ceil(per_long)];
+        if self.is_direct() {
+            self.palette.clear();
+        }
+        for (i, value) in values.into_iter().enumerate() {
+            let raw = if self.is_direct() {
+                value
+            } else {
+                self.palette.iter().position(|v| *v == value).unwrap() as u16
+            };
+            self.set_raw(i, raw);
+        }
+    }
+
+    // Gegenstück zu write; prüft alles, was get später nicht abfangen würde
+    pub fn read(kind: PaletteKind, input: &mut &[u8]) -> Result<PalettedContainer, String> {
+        let bits = read_u8(input)?;
+        let direct = bits > kind.max_indirect_bits;
+        if (bits > 0 && bits < kind.min_bits) || (direct && bits != kind.global_bits) {
+            return Err(format!("Ungültige Bits pro Eintrag: {}", bits));
+        }
+        let mut palette = Vec::new();
+        if bits == 0 {
+            palette.push(read_varint(input)? as u16);
+        } else if !direct {
+            let length = read_varint(input)?;
+            if length < 1 || length > 1 << bits {
+                return Err(format!("Ungültige Palettengröße: {}", length));
+            }
+            for _ in 0..length {
+                palette.push(read_varint(input)? as u16);
+            }
+        }
+        let longs = read_varint(input)? as usize;
+        let expected = if bits == 0 { 0 } else { kind.size.div_ceil(64 / bits as usize) };
+        if longs != expected {
+            return Err(format!("Ungültige Datenlänge: {} statt {}", longs, expected));
+        }
+        let mut data = Vec::with_capacity(longs);
+        for _ in 0..longs {
+            data.push(u64::from_be_bytes(read_bytes(input)?));
+        }
+        let container = PalettedContainer { kind, bits, palette, data };
+        if bits > 0 && !direct && (0..kind.size).any(|i| container.raw(i) as usize >= container.palette.len()) {
+            return Err("Paletten-Index außerhalb der Palette".to_string());
+        }
+        Ok(container)
+    }
+
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        self.write_mapped(buf, &|value| value);
+    }
+
+    // Wie write, aber mit umgerechneten Werten, etwa den Block-States einer anderen Client-Version
+    pub fn write_mapped(&self, buf: &mut Vec<u8>, map: &dyn Fn(u16) -> u16) {
+        buf.push(self.bits);
+        if self.bits == 0 {
+            buf.extend(write_varint_to_vec(map(self.palette[0]) as i32));
+        } else if !self.is_direct() {
+            buf.extend(write_varint_to_vec(self.palette.len() as i32));
+            for value in &self.palette {
+                buf.extend(write_varint_to_vec(map(*value) as i32));
+            }
+        }
+        // Direkte Sektionen enthalten die Werte selbst und müssen neu gepackt werden, falls sich einer ändert
+        let mut remapped = None;
+        if self.is_direct() && (0..self.kind.size).any(|i| map(self.raw(i)) != self.raw(i)) {
+            let mut copy = self.clone();
+            for i in 0..self.kind.size {
+                copy.set_raw(i, map(self.raw(i)));
+            }
+            remapped = Some(copy.data);
+        }
+        let data = remapped.as_ref().unwrap_or(&self.data);
+        buf.extend(write_varint_to_vec(data.len() as i32));
+        for long in data {
+            buf.extend(long.to_be_bytes());
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChunkSection {
+    block_count: u16,
+    blocks: PalettedContainer,
+    biomes: PalettedContainer,
+}
+
+impl Default for ChunkSection {
+    fn default() -> ChunkSection {
+        ChunkSection::new()
+    }
+}
+
+impl ChunkSection {
+    pub fn new() -> ChunkSection {
+        ChunkSection {
+            block_count: 0,
+            blocks: PalettedContainer::new(BLOCK_PALETTE, block::AIR),
+            biomes: PalettedContainer::new(BIOME_PALETTE, PLAINS_BIOME),
+        }
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        (y << 8) | (z << 4) | x
+    }
+
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> u16 {
+        self.blocks.get(Self::index(x, y, z))
+    }
+
+    pub fn may_contain_block(&self, test: &dyn Fn(u16) -> bool) -> bool {
+        self.block_count > 0 && self.blocks.may_contain(test)
+    }
+
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, state: u16) {
+        let index = Self::index(x, y, z);
+        let old = self.blocks.get(index);
+        if old == state {
+            return;
+        }
+        if old == block::AIR {
+            self.block_count += 1;
+        } else if state == block::AIR {
+            self.block_count -= 1;
+        }
+        self.blocks.set(index, state);
+    }
+
+    // Biome liegen in Zellen von 4×4×4 Blöcken, Koordinaten hier also 0..4
+    pub fn get_biome(&self, x: usize, y: usize, z: usize) -> u16 {
+        self.biomes.get((y << 4) | (z << 2) | x)
+    }
+
+    pub fn set_biome(&mut self, x: usize, y: usize, z: usize, biome: u16) {
+        self.biomes.set((y << 4) | (z << 2) | x, biome);
+    }
+
+    pub fn read(input: &mut &[u8]) -> Result<ChunkSection, String> {
+        read_bytes::<2>(input)?;
+        let blocks = PalettedContainer::read(BLOCK_PALETTE, input)?;
+        let biomes = PalettedContainer::read(BIOME_PALETTE, input)?;
+        // Die gespeicherte Anzahl wird nicht geglaubt, sondern neu gezählt
+        let block_count = (0..BLOCK_PALETTE.size).filter(|&i| blocks.get(i) != block::AIR).count() as u16;
+        Ok(ChunkSection { block_count, blocks, biomes })
+    }
+
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend(self.block_count.to_be_bytes());
+        self.blocks.write(buf);
+        self.biomes.write(buf);
+    }
+
+    fn write_mapped(&self, buf: &mut Vec<u8>, map: &dyn Fn(u16) -> u16) {
+        buf.extend(self.block_count.to_be_bytes());
+        self.blocks.write_mapped(buf, map);
+        self.biomes.write(buf);
+    }
+}
+
+// Heightmaps aus dem Chunk-Data-Paket; MOTION_BLOCKING nutzt der Client etwa für Regen, WORLD_SURFACE für den Spawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heightmap {
+    MotionBlocking,
+    WorldSurface,
+}
+
+impl Heightmap {
+    pub const ALL: [Heightmap; 2] = [Heightmap::MotionBlocking, Heightmap::WorldSurface];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heightmap::MotionBlocking => "MOTION_BLOCKING",
+            Heightmap::WorldSurface => "WORLD_SURFACE",
+        }
+    }
+
+    // Ob ein Block die Säule für diese Heightmap abschließt
+    fn counts(self, state: u16) -> bool {
+        match self {
+            Heightmap::MotionBlocking => motion_blocking().get(state as usize).copied().unwrap_or(true),
+            Heightmap::WorldSurface => state != block::AIR,
+        }
+    }
+}
+
+// block::name sucht linear, für jeden gesetzten Block wäre das zu langsam; daher einmal für alle Zustände
+fn motion_blocking() -> &'static [bool] {
+    static TABLE: OnceLock<Vec<bool>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let end = block::state_ranges().map(|(_, first, count)| first + count).max().unwrap_or(0);
+        (0..end)
+            .map(|state| (block::is_solid(state) && !block::is_snow(state)) || block::is_fluid(state) || block::property(state, "waterlogged") == Some("true"))
+            .collect()
+    })
+}
+
+// Was ein Chunk neben seinen Blöcken trägt, etwa Truheninhalte. Welche Arten es gibt, bestimmt der Server; gespeichert
+// wird es als Json hinter den Sektionen
+pub trait BlockEntityData: Clone + Send + Sync + 'static {
+    fn to_json(&self, position: (i32, i32, i32)) -> Json;
+    fn from_json(data: &Json) -> Option<((i32, i32, i32), Self)>;
+    // Passt sie nicht mehr zum Block an ihrer Stelle, verwirft sie das Laden
+    fn belongs_to(&self, state: u16) -> bool;
+}
+
+#[derive(Clone)]
+pub struct Chunk<E> {
+    pub x: i32,
+    pub z: i32,
+    sections: Vec<ChunkSection>,
+    // Je Heightmap und Säule (z * 16 + x) die Höhe über MIN_Y, ab der nur noch Blöcke kommen, die nicht zählen
+    heightmaps: [[u16; 256]; 2],
+    // Nach Weltkoordinaten; der Server legt sie mit ihrem Block an und entfernt sie mit ihm
+    pub block_entities: HashMap<(i32, i32, i32), E>,
+}
+
+impl<E> Chunk<E> {
+    pub fn new(x: i32, z: i32) -> Chunk<E> {
+        Chunk {
+            x,
+            z,
+            sections: vec![ChunkSection::new(); SECTION_COUNT],
+            heightmaps: [[0; 256]; 2],
+            block_entities: HashMap::new(),
+        }
+    }
+
+    fn section_index(y: i32) -> Option<usize> {
+        if !(MIN_Y..MIN_Y + HEIGHT).contains(&y) {
+            return None;
+        }
+        Some(((y - MIN_Y) >> 4) as usize)
+    }
+
+    // Koordinaten relativ zum Chunk (x/z 0..16), y absolut
+    pub fn get_block(&self, x: usize, y: i32, z: usize) -> u16 {
+        match Self::section_index(y) {
+            Some(i) => self.sections[i].get_block(x, ((y - MIN_Y) & 15) as usize, z),
+            None => block::AIR,
+        }
+    }
+
+    // Sektionen von unten gezählt; etwa um Zufallsticks in Sektionen ohne passende Blöcke zu sparen
+    pub fn section_may_contain(&self, section: usize, test: &dyn Fn(u16) -> bool) -> bool {
+        self.sections.get(section).is_some_and(|s| s.may_contain_block(test))
+    }
+
+    pub fn set_block(&mut self, x: usize, y: i32, z: usize, state: u16) {
+        let Some(i) = Self::section_index(y) else { return };
+        self.sections[i].set_block(x, ((y - MIN_Y) & 15) as usize, z, state);
+        for (index, kind) in Heightmap::ALL.into_iter().enumerate() {
+            let top = self.heightmaps[index][z * 16 + x] as i32 + MIN_Y;
+            if kind.counts(state) {
+                if y >= top {
+                    self.heightmaps[index][z * 16 + x] = (y + 1 - MIN_Y) as u16;
+                }
+            } else if y == top - 1 {
+                // Der oberste Block der Säule ist weg, also von dort abwärts den nächsten suchen
+                self.heightmaps[index][z * 16 + x] = self.scan_height(kind, x, y - 1, z);
+            }
+        }
+    }
+
+    // Biom-ID der Zelle, in der der Block liegt; Koordinaten wie bei get_block
+    pub fn get_biome(&self, x: usize, y: i32, z: usize) -> u16 {
+        match Self::section_index(y) {
+            Some(i) => self.sections[i].get_biome(x >> 2, (((y - MIN_Y) & 15) >> 2) as usize, z >> 2),
+            None => PLAINS_BIOME,
+        }
+    }
+
+    pub fn set_biome(&mut self, x: usize, y: i32, z: usize, biome: u16) {
+        if let Some(i) = Self::section_index(y) {
+            self.sections[i].set_biome(x >> 2, (((y - MIN_Y) & 15) >> 2) as usize, z >> 2, biome);
+        }
+    }
+
+    fn scan_height(&self, kind: Heightmap, x: usize, from: i32, z: usize) -> u16 {
+        (MIN_Y..=from).rev().find(|&y| kind.counts(self.get_block(x, y, z))).map_or(0, |y| (y + 1 - MIN_Y) as u16)
+    }
+
+    fn compute_heightmaps(&mut self) {
+        for (index, kind) in Heightmap::ALL.into_iter().enumerate() {
+            for column in 0..256 {
+                self.heightmaps[index][column] = self.scan_height(kind, column & 15, MIN_Y + HEIGHT - 1, column >> 4);
+            }
+        }
+    }
+
+    // y direkt über dem obersten Block, der für die Heightmap zählt; MIN_Y bei einer leeren Säule
+    pub fn height(&self, kind: Heightmap, x: usize, z: usize) -> i32 {
+        let index = Heightmap::ALL.iter().position(|k| *k == kind).unwrap();
+        self.heightmaps[index][z * 16 + x] as i32 + MIN_Y
+    }
+
+    // Heightmaps für Chunk Data: NBT mit Long-Arrays, die Höhen relativ zum Boden der Dimension und so viele Bits
+    // pro Eintrag, wie ihre Höhe braucht; wie bei Paletten geht kein Eintrag über zwei Longs
+    pub fn write_heightmaps(&self, min_y: i32, height: i32) -> Vec<u8> {
+        let bits = (u32::BITS - (height as u32).leading_zeros()) as usize;
+        let per_long = 64 / bits;
+        let arrays: Vec<(&str, Vec<u64>)> = Heightmap::ALL
+            .into_iter()
+            .map(|kind| {
+                let mut longs = vec![0u64; 256usize.div_ceil(per_long)];
+                for column in 0..256 {
+                    let value = (self.height(kind, column & 15, column >> 4) - min_y).clamp(0, height) as u64;
+                    longs[column / per_long] |= value << ((column % per_long) * bits);
+                }
+                (kind.name(), longs)
+            })
+            .collect();
+        nbt::long_arrays(&arrays)
+    }
+
+    // Sektionsdaten im Format des Chunk-Data-Pakets
+    pub fn write_sections(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for section in &self.sections {
+            section.write(&mut buf);
+        }
+        buf
+    }
+
+    // Nur die Sektionen im Höhenbereich einer Dimension, etwa y 0 bis 256 im Ende
+    // Sektionen einer Dimension für Chunk Data, mit den Block-States des Clients
+    pub fn write_sections_in(&self, min_y: i32, height: i32, map: &dyn Fn(u16) -> u16) -> Vec<u8> {
+        let skip = ((min_y - MIN_Y) >> 4).max(0) as usize;
+        let mut buf = Vec::new();
+        for section in self.sections.iter().skip(skip).take((height >> 4) as usize) {
+            section.write_mapped(&mut buf, map);
+        }
+        buf
+    }
+
+    pub fn read_sections(x: i32, z: i32, mut data: &[u8]) -> Result<Chunk<E>, String> {
+        let sections = (0..SECTION_COUNT).map(|_| ChunkSection::read(&mut data)).collect::<Result<Vec<_>, _>>()?;
+        if !data.is_empty() {
+            return Err(format!("{} überzählige Bytes nach den Sektionen", data.len()));
+        }
+        let mut chunk = Chunk { x, z, sections, heightmaps: [[0; 256]; 2], block_entities: HashMap::new() };
+        chunk.compute_heightmaps();
+        Ok(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Setzt index auf value und prüft danach den ganzen Container gegen expected
+    fn set_and_check(container: &mut PalettedContainer, expected: &mut [u16], index: usize, value: u16) {
+        container.set(index, value);
+        expected[index] = value;
+        for (i, &value) in expected.iter().enumerate() {
+            assert_eq!(container.get(i), value, "Eintrag {} bei {} Bits", i, container.bits);
+        }
+    }
+
+    fn round_trip(container: &PalettedContainer, map: &dyn Fn(u16) -> u16) -> PalettedContainer {
+        let mut buf = Vec::new();
+        container.write_mapped(&mut buf, map);
+        let mut input = buf.as_slice();
+        let read = PalettedContainer::read(container.kind, &mut input).unwrap();
+        assert!(input.is_empty(), "{} Bytes übrig", input.len());
+        read
+    }
+
+    #[test]
+    fn set_resizes_from_single_value_through_indirect_to_global() {
+        let mut container = PalettedContainer::new(BLOCK_PALETTE, 0);
+        let mut expected = vec![0; BLOCK_PALETTE.size];
+        assert_eq!(container.bits, 0);
+        // Jeder neue Wert landet in der Palette; (Anzahl der Werte, erwartete Bits)
+        let steps = [(2, 4), (16, 4), (17, 5), (32, 5), (33, 6), (128, 7), (256, 8), (257, BLOCK_PALETTE.global_bits)];
+        let mut values = 1;
+        for (count, bits) in steps {
+            while values < count {
+                set_and_check(&mut container, &mut expected, values * 13 % BLOCK_PALETTE.size, values as u16 * 3);
+                values += 1;
+            }
+            assert_eq!(container.bits, bits, "{} Werte", count);
+            assert_eq!(container.is_direct(), bits == BLOCK_PALETTE.global_bits);
+        }
+        assert!(container.palette.is_empty());
+        // Auch direkt bleiben alle Werte erhalten, wenn weitere dazukommen
+        set_and_check(&mut container, &mut expected, 4095, 20000);
+    }
+
+    #[test]
+    fn biome_palette_uses_its_own_bit_limits() {
+        let mut container = PalettedContainer::new(BIOME_PALETTE, PLAINS_BIOME);
+        let mut expected = vec![PLAINS_BIOME; BIOME_PALETTE.size];
+        set_and_check(&mut container, &mut expected, 0, 1);
+        assert_eq!(container.bits, 1);
+        for (index, value) in (2..=7).enumerate() {
+            set_and_check(&mut container, &mut expected, index + 1, value);
+        }
+        assert_eq!(container.bits, 3);
+        set_and_check(&mut container, &mut expected, 63, 9);
+        assert_eq!(container.bits, BIOME_PALETTE.global_bits);
+    }
+
+    #[test]
+    fn write_and_read_round_trip_in_every_format() {
+        let single = PalettedContainer::new(BLOCK_PALETTE, 7);
+        let mut indirect = single.clone();
+        for i in 0..40 {
+            indirect.set(i * 100, i as u16);
+        }
+        let mut direct = indirect.clone();
+        for i in 0..300 {
+            direct.set(i, 1000 + i as u16);
+        }
+        assert_eq!((single.bits, indirect.bits, direct.bits), (0, 6, BLOCK_PALETTE.global_bits));
+        let shifted = |value: u16| value + 1;
+        for container in [&single, &indirect, &direct] {
+            let same = round_trip(container, &|value| value);
+            let mapped = round_trip(container, &shifted);
+            assert_eq!(same.bits, container.bits);
+            for i in 0..BLOCK_PALETTE.size {
+                assert_eq!(same.get(i), container.get(i));
+                assert_eq!(mapped.get(i), shifted(container.get(i)), "Eintrag {} bei {} Bits", i, container.bits);
+            }
+        }
+    }
+
+    // Bits, Palette und Daten von Hand, wie sie ein fehlerhafter Chunk enthalten könnte
+    fn encoded(bits: u8, palette: &[i32], longs: &[u64]) -> Vec<u8> {
+        let mut buf = vec![bits];
+        if bits <= BLOCK_PALETTE.max_indirect_bits {
+            if bits > 0 {
+                buf.extend(write_varint_to_vec(palette.len() as i32));
+            }
+            for &value in palette {
+                buf.extend(write_varint_to_vec(value));
+            }
+        }
+        buf.extend(write_varint_to_vec(longs.len() as i32));
+        for long in longs {
+            buf.extend(long.to_be_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn read_rejects_invalid_containers() {
+        let read = |buf: Vec<u8>| PalettedContainer::read(BLOCK_PALETTE, &mut buf.as_slice());
+        let longs = |bits: usize| vec![0u64; BLOCK_PALETTE.size.div_ceil(64 / bits)];
+        assert!(read(encoded(4, &[1, 2], &longs(4))).is_ok());
+        // Zu wenige Bits für eine Block-Palette und indirekte Bits über der Grenze, die nicht global sind
+        assert!(read(encoded(2, &[1, 2], &longs(2))).is_err());
+        assert!(read(encoded(9, &[], &longs(9))).is_err());
+        // Leere oder zu große Palette
+        assert!(read(encoded(4, &[], &longs(4))).is_err());
+        assert!(read(encoded(4, &(0..17).collect::<Vec<_>>(), &longs(4))).is_err());
+        // Falsche Datenlänge, auch für einen einzelnen Wert
+        assert!(read(encoded(4, &[1, 2], &longs(4)[1..])).is_err());
+        assert!(read(encoded(0, &[1], &[0])).is_err());
+        // Index 5 bei nur zwei Paletteneinträgen
+        let mut data = longs(4);
+        data[3] = 5 << 8;
+        assert_eq!(read(encoded(4, &[1, 2], &data)).unwrap_err(), "Paletten-Index außerhalb der Palette");
+        // Abgeschnittene Daten
+        let mut truncated = encoded(4, &[1, 2], &longs(4));
+        truncated.pop();
+        assert!(read(truncated).is_err());
+    }
+}
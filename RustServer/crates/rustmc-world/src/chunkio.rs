@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use rustmc_protocol::json::{self, Json};
+use crate::chunk::{BlockEntityData, Chunk};
+use crate::chunkstorage::ChunkStorage;
+
+const WORKERS: usize = 2;
+// Kennung und Formatversion am Anfang jeder Chunk-Datei. Version 1 enthält nur die Sektionen, ab Version 2
+// folgen auf deren Länge und die Sektionen die Block-Entities als JSON
+const CHUNK_MAGIC_V1: &[u8; 4] = b"RMC\x01";
+const CHUNK_MAGIC: &[u8; 4] = b"RMC\x02";
+
+type PendingChunks<E> = HashMap<(i32, i32), Arc<Chunk<E>>>;
+
+enum Job<E> {
+    Write((i32, i32)),
+    Read((i32, i32), Sender<Result<Chunk<E>, String>>),
+}
+
+struct Shared<E> {
+    backend: Arc<dyn ChunkStorage>,
+    // Gespeicherte, aber noch nicht geschriebene Chunks; speichert die Welt einen Chunk erneut, bevor er an der
+    // Reihe war, wird nur der neueste Stand geschrieben
+    pending: Mutex<PendingChunks<E>>,
+    written: Condvar,
+    // Beim nächsten Speichern wieder als geändert markiert
+    failed: Mutex<Vec<(i32, i32)>>,
+}
+
+impl<E: BlockEntityData> Shared<E> {
+    fn write(&self, coords: (i32, i32)) {
+        loop {
+            let Some(chunk) = self.pending.lock().unwrap().get(&coords).cloned() else { return };
+            if let Err(e) = self.backend.write(coords.0, coords.1, &encode_chunk(&chunk)) {
+                warn!("{}", e);
+                self.failed.lock().unwrap().push(coords);
+            }
+            let mut pending = self.pending.lock().unwrap();
+            if pending.get(&coords).is_some_and(|queued| Arc::ptr_eq(queued, &chunk)) {
+                pending.remove(&coords);
+                self.written.notify_all();
+                return;
+            }
+        }
+    }
+
+    // Was noch in der Warteschlange steht, ist neuer als die Datei
+    fn read(&self, (x, z): (i32, i32)) -> Result<Chunk<E>, String> {
+        if let Some(chunk) = self.pending.lock().unwrap().get(&(x, z)).cloned() {
+            return Ok((*chunk).clone());
+        }
+        let data = self.backend.read(x, z)?;
+        decode_chunk(x, z, &data).map_err(|e| format!("Chunk {}, {} ist beschädigt: {}", x, z, e))
+    }
+}
+
+// Ein Chunk, den ein Worker gerade liest
+pub struct ChunkFuture<E> {
+    result: Receiver<Result<Chunk<E>, String>>,
+}
+
+impl<E> ChunkFuture<E> {
+    // None, solange der Worker noch liest
+    pub fn poll(&self) -> Option<Result<Chunk<E>, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("Chunk-Worker beendet".to_string())),
+        }
+    }
+
+    pub fn wait(self) -> Result<Chunk<E>, String> {
+        self.result.recv().unwrap_or_else(|_| Err("Chunk-Worker beendet".to_string()))
+    }
+}
+
+// Worker für Kodierung und Dateizugriffe der Chunks einer Welt, damit weder Tick-Schleife noch Pakete auf die Platte
+// warten. Geschrieben wird verzögert, Lesen liefert ein ChunkFuture
+pub struct ChunkIo<E> {
+    // Hinter Sperren, weil shut_down über das geteilte Arc läuft
+    jobs: Mutex<Option<Sender<Job<E>>>>,
+    shared: Arc<Shared<E>>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl<E: BlockEntityData> std::fmt::Debug for ChunkIo<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkIo").field("backend", &self.shared.backend).field("backlog", &self.backlog()).finish()
+    }
+}
+
+impl<E: BlockEntityData> ChunkIo<E> {
+    pub fn new(backend: Arc<dyn ChunkStorage>) -> ChunkIo<E> {
+        let shared = Arc::new(Shared { backend, pending: Mutex::new(HashMap::new()), written: Condvar::new(), failed: Mutex::new(Vec::new()) });
+        let (jobs, receiver) = mpsc::channel::<Job<E>>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..WORKERS)
+            .map(|i| {
+                let receiver = Arc::clone(&receiver);
+                let shared = Arc::clone(&shared);
+                thread::Builder::new()
+                    .name(format!("chunk-io-{}", i))
+                    .spawn(move || loop {
+                        let job = receiver.lock().unwrap().recv();
+                        match job {
+                            Ok(Job::Write(coords)) => shared.write(coords),
+                            Ok(Job::Read(coords, result)) => {
+                                let _ = result.send(shared.read(coords));
+                            }
+                            Err(_) => return,
+                        }
+                    })
+                    .expect("Konnte Chunk-I/O-Thread nicht starten")
+            })
+            .collect();
+        ChunkIo { jobs: Mutex::new(Some(jobs)), shared, workers: Mutex::new(workers) }
+    }
+
+    fn submit(&self, job: Job<E>) {
+        if let Some(jobs) = &*self.jobs.lock().unwrap() {
+            let _ = jobs.send(job);
+        }
+    }
+
+    pub fn read(&self, x: i32, z: i32) -> ChunkFuture<E> {
+        let (sender, result) = mpsc::channel();
+        self.submit(Job::Read((x, z), sender));
+        ChunkFuture { result }
+    }
+
+    pub fn write(&self, chunk: Chunk<E>) {
+        let coords = (chunk.x, chunk.z);
+        // Steht der Chunk schon an, schreibt der Worker ohnehin den neuesten Stand
+        if self.shared.pending.lock().unwrap().insert(coords, Arc::new(chunk)).is_none() {
+            self.submit(Job::Write(coords));
+        }
+    }
+
+    pub fn backlog(&self) -> usize {
+        self.shared.pending.lock().unwrap().len()
+    }
+
+    // Wartet, bis alles geschrieben ist, und gibt die Zahl der bisher fehlgeschlagenen Chunks zurück
+    pub fn flush(&self) -> usize {
+        let mut pending = self.shared.pending.lock().unwrap();
+        while !pending.is_empty() {
+            pending = self.shared.written.wait(pending).unwrap();
+        }
+        drop(pending);
+        self.shared.failed.lock().unwrap().len()
+    }
+
+    pub fn take_failed(&self) -> Vec<(i32, i32)> {
+        std::mem::take(&mut *self.shared.failed.lock().unwrap())
+    }
+}
+
+impl<E> ChunkIo<E> {
+    // Beendet die Worker, nachdem sie alle schon angenommenen Aufträge erledigt haben; danach liefert read nur noch
+    // Fehler und write bleibt liegen
+    pub fn shut_down(&self) {
+        self.jobs.lock().unwrap().take();
+        let workers = std::mem::take(&mut *self.workers.lock().unwrap());
+        for worker in workers {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<E> Drop for ChunkIo<E> {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+pub fn encode_chunk<E: BlockEntityData>(chunk: &Chunk<E>) -> Vec<u8> {
+    let sections = chunk.write_sections();
+    let mut data = CHUNK_MAGIC.to_vec();
+    data.extend((sections.len() as u32).to_be_bytes());
+    data.extend(sections);
+    let entities = chunk.block_entities.iter().map(|(&position, entity)| entity.to_json(position)).collect();
+    data.extend(Json::Array(entities).to_json_string().as_bytes());
+    data
+}
+
+pub fn decode_chunk<E: BlockEntityData>(x: i32, z: i32, data: &[u8]) -> Result<Chunk<E>, String> {
+    if let Some(sections) = data.strip_prefix(CHUNK_MAGIC_V1) {
+        return Chunk::read_sections(x, z, sections);
+    }
+    let data = data.strip_prefix(CHUNK_MAGIC).ok_or("keine Chunk-Datei")?;
+    let (length, data) = data.split_first_chunk::<4>().ok_or("Länge der Sektionen fehlt")?;
+    let (sections, entities) = data.split_at_checked(u32::from_be_bytes(*length) as usize).ok_or("Sektionen unvollständig")?;
+    let mut chunk = Chunk::read_sections(x, z, sections)?;
+    let entities = std::str::from_utf8(entities).map_err(|e| e.to_string()).and_then(json::parse)?;
+    for (position, entity) in entities.as_array().into_iter().flatten().filter_map(E::from_json) {
+        let (bx, by, bz) = position;
+        // Gehört nicht (mehr) zum Block an der Stelle
+        if bx >> 4 != x || bz >> 4 != z || !entity.belongs_to(chunk.get_block((bx & 15) as usize, by, (bz & 15) as usize)) {
+            continue;
+        }
+        chunk.block_entities.insert(position, entity);
+    }
+    Ok(chunk)
+}
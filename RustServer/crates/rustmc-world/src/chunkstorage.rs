@@ -0,0 +1,144 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use crate::zstd;
+
+const CHUNK_DIR: &str = "chunks";
+const NATIVE_SUFFIX: &str = ".chunk";
+const ZSTD_SUFFIX: &str = ".chunk.zst";
+// Größer wird kein gültiger Chunk; schützt beim Entpacken beschädigter Dateien
+const MAX_CHUNK_SIZE: usize = 1 << 24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChunkStorageKind {
+    Native,
+    Zstd,
+}
+
+impl std::str::FromStr for ChunkStorageKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ChunkStorageKind, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "native" => Ok(ChunkStorageKind::Native),
+            "zstd" => Ok(ChunkStorageKind::Zstd),
+            other => Err(format!("Unknown chunk storage '{}'", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for ChunkStorageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ChunkStorageKind::Native => "native",
+            ChunkStorageKind::Zstd => "zstd",
+        })
+    }
+}
+
+// Wo die Chunks einer Welt liegen. Kodiert werden sie von chunkio, ein Backend speichert nur die fertigen Bytes;
+// Aufrufe kommen auch ohne gehaltene Weltsperre, etwa aus save_all
+pub trait ChunkStorage: std::fmt::Debug + Send + Sync {
+    // Beim Öffnen der Welt einmal abgefragt
+    fn list(&self) -> Vec<(i32, i32)>;
+    fn read(&self, x: i32, z: i32) -> Result<Vec<u8>, String>;
+    fn write(&self, x: i32, z: i32, data: &[u8]) -> Result<(), String>;
+}
+
+pub fn open(kind: ChunkStorageKind, world_dir: &Path) -> Arc<dyn ChunkStorage> {
+    let native = NativeStorage { dir: world_dir.join(CHUNK_DIR) };
+    match kind {
+        ChunkStorageKind::Native => {
+            let packed = list_files(&native.dir, ZSTD_SUFFIX).len();
+            if packed > 0 {
+                warn!("{} Chunks in {} sind mit zstd gepackt und werden mit chunk-storage=native nicht gelesen", packed, native.dir.display());
+            }
+            Arc::new(native)
+        }
+        ChunkStorageKind::Zstd => Arc::new(ZstdStorage { native }),
+    }
+}
+
+fn list_files(dir: &Path, suffix: &str) -> Vec<(i32, i32)> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().filter_map(|e| parse_chunk_name(&e.file_name().to_string_lossy(), suffix)).collect())
+        .unwrap_or_default()
+}
+
+fn parse_chunk_name(name: &str, suffix: &str) -> Option<(i32, i32)> {
+    let (x, z) = name.strip_prefix("c.")?.strip_suffix(suffix)?.split_once('.')?;
+    Some((x.parse().ok()?, z.parse().ok()?))
+}
+
+fn chunk_file(dir: &Path, x: i32, z: i32, suffix: &str) -> PathBuf {
+    dir.join(format!("c.{}.{}{}", x, z, suffix))
+}
+
+// Erst in eine Nachbardatei schreiben und dann umbenennen, damit ein Absturz keine halbe Datei hinterlässt
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Konnte {} nicht anlegen: {}", parent.display(), e))?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, data).map_err(|e| format!("Konnte {} nicht schreiben: {}", path.display(), e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Konnte {} nicht schreiben: {}", path.display(), e))
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("Konnte {} nicht lesen: {}", path.display(), e))
+}
+
+// Eine unkomprimierte Datei je Chunk: chunks/c.<x>.<z>.chunk
+#[derive(Debug)]
+pub struct NativeStorage {
+    dir: PathBuf,
+}
+
+impl ChunkStorage for NativeStorage {
+    fn list(&self) -> Vec<(i32, i32)> {
+        list_files(&self.dir, NATIVE_SUFFIX)
+    }
+
+    fn read(&self, x: i32, z: i32) -> Result<Vec<u8>, String> {
+        read_file(&chunk_file(&self.dir, x, z, NATIVE_SUFFIX))
+    }
+
+    fn write(&self, x: i32, z: i32, data: &[u8]) -> Result<(), String> {
+        write_atomic(&chunk_file(&self.dir, x, z, NATIVE_SUFFIX), data)
+    }
+}
+
+// Wie NativeStorage, aber mit zstd gepackt als c.<x>.<z>.chunk.zst. Liest auch noch ungepackte Chunks, damit
+// vorhandene Welten umstellen können; beim nächsten Speichern ersetzt die gepackte Datei die alte
+#[derive(Debug)]
+pub struct ZstdStorage {
+    native: NativeStorage,
+}
+
+impl ChunkStorage for ZstdStorage {
+    fn list(&self) -> Vec<(i32, i32)> {
+        let mut chunks = self.native.list();
+        chunks.extend(list_files(&self.native.dir, ZSTD_SUFFIX));
+        chunks
+    }
+
+    fn read(&self, x: i32, z: i32) -> Result<Vec<u8>, String> {
+        let path = chunk_file(&self.native.dir, x, z, ZSTD_SUFFIX);
+        if !path.exists() {
+            return self.native.read(x, z);
+        }
+        zstd::decompress(&read_file(&path)?, MAX_CHUNK_SIZE).map_err(|e| format!("Konnte {} nicht entpacken: {}", path.display(), e))
+    }
+
+    fn write(&self, x: i32, z: i32, data: &[u8]) -> Result<(), String> {
+        write_atomic(&chunk_file(&self.native.dir, x, z, ZSTD_SUFFIX), &zstd::compress(data))?;
+        let old = chunk_file(&self.native.dir, x, z, NATIVE_SUFFIX);
+        match fs::remove_file(&old) {
+            Err(e) if e.kind() != ErrorKind::NotFound => warn!("Konnte {} nicht löschen: {}", old.display(), e),
+            _ => {}
+        }
+        Ok(())
+    }
+}
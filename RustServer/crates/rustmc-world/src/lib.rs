@@ -0,0 +1,28 @@
+// Chunks, ihr Format auf der Platte und die Worker, die sie lesen und schreiben; kennt weder Spieler noch Entities
+use std::fmt;
+use std::sync::OnceLock;
+
+// Vor den Modulen, damit sie warn! benutzen können
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::warn(module_path!(), format_args!($($arg)+)) };
+}
+
+pub mod chunk;
+pub mod chunkio;
+pub mod chunkstorage;
+pub mod zstd;
+
+static WARN_HOOK: OnceLock<fn(&'static str, fmt::Arguments)> = OnceLock::new();
+
+// Warnungen, etwa über Chunks, die sich nicht schreiben ließen, gehen an den Hook des Servers, ohne ihn nach stderr.
+// Nur der erste Aufruf zählt
+pub fn set_warn_hook(hook: fn(&'static str, fmt::Arguments)) {
+    let _ = WARN_HOOK.set(hook);
+}
+
+fn warn(module_path: &'static str, message: fmt::Arguments) {
+    match WARN_HOOK.get() {
+        Some(hook) => hook(module_path, message),
+        None => eprintln!("[WARN] [{}] {}", module_path, message),
+    }
+}
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustmc_protocol::packets::{split_id, Handshake};
+use rustmc_protocol::{framing, ConnectionState};
+
+// Wie der Server nach dem Verbindungsaufbau: ein Frame mit der Obergrenze vor dem Login, darin der Handshake
+fuzz_target!(|data: &[u8]| {
+    let mut stream = data;
+    let Ok((body, _)) = framing::read_frame(&mut stream, framing::max_packet_length(ConnectionState::Handshake), None) else { return };
+    if let Ok((id, mut cursor)) = split_id(body) {
+        let _ = Handshake::decode(id, &mut cursor);
+    }
+});
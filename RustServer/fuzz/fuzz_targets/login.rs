@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustmc_protocol::packets::{split_id, LoginStart};
+use rustmc_protocol::{framing, ConnectionState};
+
+// Frames im Login-Zustand, bis einer nicht mehr gelesen werden kann
+fuzz_target!(|data: &[u8]| {
+    let mut stream = data;
+    while let Ok((body, _)) = framing::read_frame(&mut stream, framing::max_packet_length(ConnectionState::Login), None) {
+        if let Ok((id, mut cursor)) = split_id(body) {
+            let _ = LoginStart::decode(id, &mut cursor);
+        }
+    }
+});
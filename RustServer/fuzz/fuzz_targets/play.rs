@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustmc_protocol::packets::{split_id, Play};
+use rustmc_protocol::version::SUPPORTED;
+use rustmc_protocol::{framing, ConnectionState};
+
+// Das erste Byte wählt Kompression und Protokollversion, der Rest sind Frames wie nach dem Login
+fuzz_target!(|data: &[u8]| {
+    let Some((&settings, mut stream)) = data.split_first() else { return };
+    let compression = (settings & 1 != 0).then_some(256);
+    let version = SUPPORTED[(settings >> 1) as usize % SUPPORTED.len()];
+    while let Ok((body, _)) = framing::read_frame(&mut stream, framing::max_packet_length(ConnectionState::Play), compression) {
+        if let Ok((id, mut cursor)) = split_id(body) {
+            let _ = Play::decode(version.serverbound(id), &mut cursor);
+        }
+    }
+});
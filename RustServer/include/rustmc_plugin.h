@@ -0,0 +1,78 @@
+/* C-ABI für native RustServer-Plugins (muss zu src/plugin.rs passen). */
+#ifndef RUSTMC_PLUGIN_H
+#define RUSTMC_PLUGIN_H
+
+#include <stdint.h>
+
+#define RUSTMC_PLUGIN_API_VERSION 6
+
+#define RUSTMC_EVENT_PLAYER_JOIN 1
+#define RUSTMC_EVENT_PLAYER_QUIT 2
+#define RUSTMC_EVENT_CHAT 3
+#define RUSTMC_EVENT_BLOCK_BREAK 4
+
+/* Listener laufen in dieser Reihenfolge; MONITOR soll nur beobachten. */
+#define RUSTMC_PRIORITY_LOWEST 0
+#define RUSTMC_PRIORITY_LOW 1
+#define RUSTMC_PRIORITY_NORMAL 2
+#define RUSTMC_PRIORITY_HIGH 3
+#define RUSTMC_PRIORITY_HIGHEST 4
+#define RUSTMC_PRIORITY_MONITOR 5
+
+/* Nur cancelled darf direkt verändert werden (ungleich 0 = abbrechen; nur bei CHAT und BLOCK_BREAK wirksam),
+   die Nachricht über set_event_message. message ist nur bei CHAT gesetzt, x/y/z/state nur bei BLOCK_BREAK. */
+typedef struct rustmc_event {
+    uint32_t kind;
+    const char *player;
+    const char *message;
+    int32_t x;
+    int32_t y;
+    int32_t z;
+    uint32_t state;
+    int32_t cancelled;
+    /* Gehört dem Host, nicht verändern. */
+    void *reserved;
+} rustmc_event;
+
+/* Rückgabe 0 = Erfolg. sender ist "CONSOLE" oder der Spielername, args durch Leerzeichen getrennt. */
+typedef int32_t (*rustmc_command_callback)(void *user, const char *sender, const char *args);
+/* Wird aus den Client-Threads aufgerufen und muss daher threadsicher sein. */
+typedef void (*rustmc_event_callback)(void *user, rustmc_event *event);
+/* Nutzdaten einer Plugin-Nachricht; data ist nicht NUL-terminiert und nur während des Aufrufs gültig. */
+typedef void (*rustmc_channel_callback)(void *user, const char *player, const uint8_t *data, uint32_t length);
+
+typedef struct rustmc_host_api {
+    uint32_t api_version;
+    void *host;
+    void (*log)(void *host, const char *message);
+    int32_t (*register_command)(void *host, const char *name, const char *usage, rustmc_command_callback callback, void *user);
+    int32_t (*register_listener)(void *host, uint32_t event, int32_t priority, rustmc_event_callback callback, void *user);
+    int32_t (*send_message)(void *host, const char *player, const char *message);
+    void (*broadcast)(void *host, const char *message);
+    int32_t (*kick_player)(void *host, const char *player);
+    /* Schreibt x, y, z nach out[0..3]. */
+    int32_t (*player_position)(void *host, const char *player, double *out);
+    int32_t (*get_block)(void *host, int32_t x, int32_t y, int32_t z);
+    int32_t (*set_block)(void *host, int32_t x, int32_t y, int32_t z, uint32_t state);
+    /* Ersetzt die Nachricht des gerade zugestellten Events; nur im Listener gültig (seit Version 3). */
+    int32_t (*set_event_message)(void *host, rustmc_event *event, const char *message);
+    /* Auch in eine andere Welt, "world" ist die Oberwelt (seit Version 4). */
+    int32_t (*teleport)(void *host, const char *player, const char *world, double x, double y, double z);
+    /* region: zwei Ecken als x1, y1, z1, x2, y2, z2; position: x, y, z in destination. Ersetzt ein gleichnamiges Portal. */
+    int32_t (*link_portal)(void *host, const char *name, const char *world, const int32_t *region, const char *destination, const double *position);
+    int32_t (*unlink_portal)(void *host, const char *name);
+    /* Vom Client gemeldete Brand, gekürzt wie snprintf; liefert die volle Länge oder -1, wenn keine bekannt ist (seit Version 5). */
+    int32_t (*player_brand)(void *host, const char *player, char *out, uint32_t size);
+    /* Kanal der Form namespace:pfad; minecraft: ist reserviert. Online-Spieler erfahren ihn über minecraft:register (seit Version 5). */
+    int32_t (*register_channel)(void *host, const char *channel, rustmc_channel_callback callback, void *user);
+    int32_t (*send_plugin_message)(void *host, const char *player, const char *channel, const uint8_t *data, uint32_t length);
+    /* Geglättete Antwortzeit auf Keep-Alives in Millisekunden, 0 bis zur ersten Antwort, -1 wenn der Spieler nicht online ist (seit Version 6). */
+    int32_t (*player_ping)(void *host, const char *player);
+} rustmc_host_api;
+
+/* Vom Plugin zu exportieren. rustmc_plugin_disable ist optional. */
+uint32_t rustmc_plugin_api_version(void);
+int32_t rustmc_plugin_enable(const rustmc_host_api *api);
+void rustmc_plugin_disable(void);
+
+#endif
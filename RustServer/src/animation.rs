@@ -0,0 +1,37 @@
+use crate::entity;
+use crate::error::ServerError;
+use crate::protocol::clientbound;
+use crate::protocol::types::{read_varint_from_cursor, write_varint_to_vec};
+use crate::{Connection, Player};
+
+// Entity Animation
+const SWING_MAIN_ARM: u8 = 0;
+pub const LEAVE_BED: u8 = 2;
+const SWING_OFFHAND: u8 = 3;
+pub const CRITICAL_HIT: u8 = 4;
+const OFF_HAND: i32 = 1;
+
+pub fn send(connection: &Connection, id: i32, animation: u8) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(id);
+    packet_data.push(animation);
+    connection.send(clientbound::ENTITY_ANIMATION, &packet_data)
+}
+
+// Hurt Animation: rotes Aufleuchten. Die Richtung kippt nur die Kamera des Getroffenen, und die bewegt sein Client
+// schon selbst, sobald Set Health weniger Leben meldet
+pub fn send_hurt(connection: &Connection, id: i32) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(id);
+    packet_data.extend(0f32.to_be_bytes());
+    connection.send(clientbound::HURT_ANIMATION, &packet_data)
+}
+
+// Swing Arm: die anderen Spieler in Sichtweite sehen den Arm schwingen; Zuschauer sieht niemand
+pub fn handle_swing(players: &[Player], player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let hand = read_varint_from_cursor(cursor)?;
+    let Some(swinging) = players.iter().find(|p| p.uuid == player.uuid && p.game_mode.is_tracked()) else { return Ok(()) };
+    let animation = if hand == OFF_HAND { SWING_OFFHAND } else { SWING_MAIN_ARM };
+    for viewer in players.iter().filter(|p| p.uuid != swinging.uuid && entity::in_view(p, swinging.dimension, swinging.position)) {
+        let _ = send(&viewer.connection, swinging.entity_id, animation);
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use crate::error::ServerError;
+use crate::gamemode::GameMode;
+use crate::item::ItemStack;
+use crate::protocol::clientbound;
+use crate::protocol::types::read_bounded_string;
+use crate::window;
+use crate::worldevent::WorldEvent;
+use crate::{block, recipe, Connection, Player, World};
+
+// Slots des Amboss-Fensters: zu bearbeitendes Item, zweites Item bzw. Material, Ergebnis
+pub const LEFT: usize = 0;
+pub const RIGHT: usize = 1;
+pub const RESULT: usize = 2;
+pub const SLOTS: usize = 3;
+// Set Container Property: Property 0 ist beim Amboss der Preis in Stufen
+const PROPERTY_COST: i16 = 0;
+// Vanilla kürzt Namen aus Rename Item auf 50 Zeichen
+const MAX_NAME_LENGTH: usize = 50;
+// Wahrscheinlichkeit, dass der Amboss bei einer Benutzung eine Stufe beschädigter wird
+const DAMAGE_CHANCE: f64 = 0.12;
+
+// Was der Amboss neben seinen Slots weiß: den gewünschten Namen und die Bedingungen des aktuellen Ergebnisses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Anvil {
+    pub name: String,
+    pub cost: u32,
+    // Verbrauchtes Reparaturmaterial; 0 heißt, das rechte Item geht ganz drauf
+    materials: u8,
+}
+
+impl Anvil {
+    // Rechnet das Ergebnis für die Eingaben neu aus
+    pub fn update(&mut self, slots: &mut [ItemStack], creative: bool) {
+        match recipe::anvil(&slots[LEFT], &slots[RIGHT], &self.name, creative) {
+            Some(result) => {
+                slots[RESULT] = result.result;
+                self.cost = result.cost;
+                self.materials = result.materials;
+            }
+            None => {
+                slots[RESULT] = ItemStack::empty();
+                self.cost = 0;
+                self.materials = 0;
+            }
+        }
+    }
+
+    // Das Ergebnis darf nur nehmen, wer die Stufen hat; im Kreativmodus ist es umsonst
+    pub fn can_take(&self, slots: &[ItemStack], levels: i32, creative: bool) -> bool {
+        !slots[RESULT].is_empty() && self.cost > 0 && (creative || levels >= self.cost as i32)
+    }
+
+    // Nach dem Nehmen des Ergebnisses: das linke Item ist verbraucht, rechts das Material bzw. das ganze Item
+    pub fn consume(&self, slots: &mut [ItemStack]) {
+        slots[LEFT] = ItemStack::empty();
+        let right = &mut slots[RIGHT];
+        if self.materials > 0 && right.count > self.materials {
+            right.count -= self.materials;
+        } else {
+            *right = ItemStack::empty();
+        }
+        slots[RESULT] = ItemStack::empty();
+    }
+}
+
+// Set Container Property mit dem Preis, den der Client unter dem Ergebnis anzeigt
+pub fn send_cost(connection: &Connection, window_id: u8, cost: u32) -> Result<(), String> {
+    let mut packet_data = vec![window_id];
+    packet_data.extend(PROPERTY_COST.to_be_bytes());
+    packet_data.extend((cost.min(i16::MAX as u32) as i16).to_be_bytes());
+    connection.send(clientbound::SET_CONTAINER_PROPERTY, &packet_data)
+}
+
+// Rename Item: der Client schickt den Namen bei jeder Änderung im Textfeld; leer heißt Standardname
+pub fn handle_rename(players: &mut [Player], player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let name = read_bounded_string(cursor, i16::MAX as usize)?;
+    let name: String = name.chars().filter(|c| *c != '§' && !c.is_control()).take(MAX_NAME_LENGTH).collect();
+    let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    let creative = p.game_mode == GameMode::Creative;
+    let Some(window) = p.window.as_mut() else { return Ok(()) };
+    let Some(anvil) = window.anvil.as_mut() else { return Ok(()) };
+    anvil.name = name;
+    let before = window.slots[RESULT].clone();
+    anvil.update(&mut window.slots, creative);
+    let cost = anvil.cost;
+    window.set_sent(window.slots.clone());
+    if window.slots[RESULT] != before {
+        let _ = window::send_slot(&p.connection, window, RESULT, &window.slots[RESULT]);
+    }
+    let _ = send_cost(&p.connection, window.id, cost);
+    Ok(())
+}
+
+// Nach einer Benutzung: außerhalb des Kreativmodus wird mit 12 % Wahrscheinlichkeit aus dem Amboss ein
+// angeschlagener, daraus ein beschädigter, und der zerbricht. Fenster auf einem zerbrochenen Amboss schließt window::tick
+pub fn damage(world: &mut World, position: (i32, i32, i32), creative: bool) {
+    let (x, y, z) = position;
+    let state = world.get_block(x, y, z);
+    if creative || rand::random::<f64>() >= DAMAGE_CHANCE {
+        world.play_event(WorldEvent::AnvilUsed, position, None);
+        return;
+    }
+    let facing = block::property(state, "facing").unwrap_or("north").to_string();
+    let next = match block::name(state) {
+        "minecraft:anvil" => block::parse_state(&format!("minecraft:chipped_anvil[facing={}]", facing)).ok(),
+        "minecraft:chipped_anvil" => block::parse_state(&format!("minecraft:damaged_anvil[facing={}]", facing)).ok(),
+        _ => None,
+    };
+    let next = next.unwrap_or(block::AIR);
+    if !world.set_block_and_notify((x, y, z), next) {
+        return;
+    }
+    world.play_event(if next == block::AIR { WorldEvent::AnvilDestroyed } else { WorldEvent::AnvilUsed }, position, None);
+}
+
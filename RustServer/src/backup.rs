@@ -0,0 +1,156 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Instant, UNIX_EPOCH};
+use crate::clock::{self, UtcTime};
+use crate::storage::{self, world_dir};
+use crate::zip::ZipWriter;
+use crate::Server;
+
+const ARCHIVE_PREFIX: &str = "world-";
+const ARCHIVE_SUFFIX: &str = ".zip";
+
+#[derive(Debug, Default)]
+pub struct Backups {
+    // Solange ein Backup gepackt wird, pausiert der Autosave, damit das Archiv einen einheitlichen Stand zeigt
+    running: Arc<AtomicBool>,
+}
+
+// Aufbewahrung aus der Konfiguration; 0 heißt jeweils unbegrenzt
+#[derive(Debug, Clone, Copy)]
+struct Retention {
+    keep: usize,
+    max_age_days: u64,
+}
+
+impl Backups {
+    pub fn new() -> Backups {
+        Backups::default()
+    }
+
+    pub fn running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    // Speichert die Welt auf dem aufrufenden Thread und packt sie danach im Hintergrund.
+    // Gibt den Namen des entstehenden Archivs zurück
+    pub fn start(&self, server: &Server) -> Result<String, String> {
+        if self.running.swap(true, Ordering::Relaxed) {
+            return Err("A backup is already running".to_string());
+        }
+        let summary = storage::save_all(server, true);
+        if summary.failed > 0 {
+            warn!("Backup enthält {} Dateien im Stand vor dem letzten Speichern", summary.failed);
+        }
+        let dir = PathBuf::from(&server.config.backup_directory);
+        let archive = match archive_path(&dir) {
+            Ok(archive) => archive,
+            Err(e) => {
+                self.running.store(false, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+        let name = archive.file_name().unwrap().to_string_lossy().into_owned();
+        let retention = Retention { keep: server.config.backup_keep, max_age_days: server.config.backup_max_age_days };
+        let running = Arc::clone(&self.running);
+        thread::spawn(move || {
+            let started = Instant::now();
+            match write_archive(Path::new(world_dir()), &archive) {
+                Ok((files, bytes)) => {
+                    info!(files = files, size = format_args!("{:.1}MiB", bytes as f64 / 1048576.0), "Backup {} erstellt in {}ms", archive.display(), started.elapsed().as_millis());
+                    apply_retention(&dir, retention, &archive);
+                }
+                Err(e) => warn!("Backup {} fehlgeschlagen: {}", archive.display(), e),
+            }
+            running.store(false, Ordering::Relaxed);
+        });
+        Ok(name)
+    }
+}
+
+// backups/world-2024-05-01_12-00-00.zip, bei Kollisionen mit angehängtem Zähler
+fn archive_path(dir: &Path) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Konnte {} nicht anlegen: {}", dir.display(), e))?;
+    let now = UtcTime::now();
+    let stamp = format!("{}_{}", now.date(), now.time().replace(':', "-"));
+    let path = (1..)
+        .map(|n| if n == 1 { format!("{}{}{}", ARCHIVE_PREFIX, stamp, ARCHIVE_SUFFIX) } else { format!("{}{}-{}{}", ARCHIVE_PREFIX, stamp, n, ARCHIVE_SUFFIX) })
+        .map(|name| dir.join(name))
+        .find(|path| !path.exists())
+        .unwrap();
+    Ok(path)
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path).and_then(|m| m.modified()).ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map_or(0, |d| d.as_secs())
+}
+
+// Alle Dateien unterhalb von dir mit Pfaden relativ zum Archiv, halbfertige .tmp-Dateien ausgenommen
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Konnte {} nicht lesen: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            collect_files(&path, &name, files)?;
+        } else if path.extension().is_none_or(|ext| ext != "tmp") {
+            files.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+// Gibt die Anzahl der Dateien und die Archivgröße zurück
+fn write_archive(world: &Path, archive: &Path) -> Result<(usize, u64), String> {
+    let mut files = Vec::new();
+    collect_files(world, world_dir(), &mut files)?;
+    files.sort();
+    let mut temp = archive.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let result = (|| {
+        let file = File::create(&temp).map_err(|e| format!("Konnte {} nicht anlegen: {}", temp.display(), e))?;
+        let mut zip = ZipWriter::new(BufWriter::new(file));
+        for (name, path) in &files {
+            // Zwischen Auflisten und Lesen gelöschte Dateien fehlen einfach im Archiv
+            let Ok(data) = fs::read(path) else { continue };
+            zip.add_file(name, &data, UtcTime::from_unix(modified_secs(path))).map_err(|e| e.to_string())?;
+        }
+        zip.finish().map_err(|e| e.to_string())?;
+        fs::rename(&temp, archive).map_err(|e| format!("Konnte {} nicht umbenennen: {}", temp.display(), e))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result?;
+    Ok((files.len(), fs::metadata(archive).map(|m| m.len()).unwrap_or(0)))
+}
+
+// Löscht die ältesten Archive über der Höchstzahl und alle, die älter als erlaubt sind. Das neue bleibt immer erhalten
+fn apply_retention(dir: &Path, retention: Retention, newest: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut archives: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|path| path.file_name().map(|n| n.to_string_lossy()).is_some_and(|n| n.starts_with(ARCHIVE_PREFIX) && n.ends_with(ARCHIVE_SUFFIX)))
+        .collect();
+    // Die Zeitstempel im Namen sortieren chronologisch, neueste zuerst
+    archives.sort_by(|a, b| b.cmp(a));
+    let now = clock::unix_seconds();
+    for (index, path) in archives.iter().enumerate() {
+        if path == newest {
+            continue;
+        }
+        let too_many = retention.keep > 0 && index >= retention.keep;
+        let too_old = retention.max_age_days > 0 && now.saturating_sub(modified_secs(path)) > retention.max_age_days * 86400;
+        if too_many || too_old {
+            match fs::remove_file(path) {
+                Ok(()) => info!("Altes Backup {} gelöscht", path.display()),
+                Err(e) => warn!("Konnte altes Backup {} nicht löschen: {}", path.display(), e),
+            }
+        }
+    }
+}
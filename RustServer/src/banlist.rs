@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::fs;
+use uuid::Uuid;
+use crate::clock::UtcTime;
+use crate::json::{self, Json};
+
+pub const BANNED_PLAYERS_FILE: &str = "banned-players.json";
+pub const DEFAULT_REASON: &str = "Banned by an operator.";
+
+pub fn ban_message(reason: &str) -> String {
+    format!("You are banned from this server.\nReason: {}", reason)
+}
+
+#[derive(Debug, Clone)]
+pub struct Ban {
+    pub name: String,
+    pub uuid: Option<Uuid>,
+    pub reason: String,
+    pub source: String,
+    pub created: String,
+}
+
+impl Ban {
+    pub fn new(name: &str, uuid: Option<Uuid>, reason: &str, source: &str) -> Ban {
+        Ban {
+            name: name.to_string(),
+            uuid,
+            reason: reason.to_string(),
+            source: source.to_string(),
+            created: format_timestamp(UtcTime::now()),
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let mut entry = BTreeMap::new();
+        entry.insert("name".to_string(), Json::String(self.name.clone()));
+        if let Some(uuid) = self.uuid {
+            entry.insert("uuid".to_string(), Json::String(uuid.to_string()));
+        }
+        entry.insert("created".to_string(), Json::String(self.created.clone()));
+        entry.insert("source".to_string(), Json::String(self.source.clone()));
+        entry.insert("expires".to_string(), Json::String("forever".to_string()));
+        entry.insert("reason".to_string(), Json::String(self.reason.clone()));
+        Json::Object(entry)
+    }
+
+    fn from_json(entry: &Json) -> Option<Ban> {
+        Some(Ban {
+            name: entry.get("name")?.as_str()?.to_string(),
+            uuid: entry.get("uuid").and_then(Json::as_str).and_then(|u| Uuid::parse_str(u).ok()),
+            reason: entry.get("reason").and_then(Json::as_str).unwrap_or(DEFAULT_REASON).to_string(),
+            source: entry.get("source").and_then(Json::as_str).unwrap_or("Server").to_string(),
+            created: entry.get("created").and_then(Json::as_str).unwrap_or_default().to_string(),
+        })
+    }
+}
+
+// Format wie bei Vanilla, damit banned-players.json zwischen beiden Servern austauschbar bleibt
+fn format_timestamp(time: UtcTime) -> String {
+    format!("{} {} +0000", time.date(), time.time())
+}
+
+#[derive(Debug, Default)]
+pub struct BanList {
+    path: String,
+    entries: Vec<Ban>,
+}
+
+impl BanList {
+    pub fn load(path: &str) -> BanList {
+        let mut list = BanList { path: path.to_string(), entries: Vec::new() };
+        let Ok(text) = fs::read_to_string(path) else { return list };
+        match json::parse(&text) {
+            Ok(Json::Array(entries)) => list.entries = entries.iter().filter_map(Ban::from_json).collect(),
+            Ok(_) => warn!("{} enthält keine Liste", path),
+            Err(e) => warn!("{} konnte nicht gelesen werden: {}", path, e),
+        }
+        list
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let entries = Json::Array(self.entries.iter().map(Ban::to_json).collect());
+        fs::write(&self.path, entries.to_json_string()).map_err(|e| format!("Konnte {} nicht schreiben: {}", self.path, e))
+    }
+
+    // Ersetzt einen bestehenden Bann desselben Spielers
+    pub fn add(&mut self, ban: Ban) -> Result<(), String> {
+        self.entries.retain(|b| !b.name.eq_ignore_ascii_case(&ban.name));
+        self.entries.push(ban);
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<bool, String> {
+        let before = self.entries.len();
+        self.entries.retain(|b| !b.name.eq_ignore_ascii_case(name));
+        if self.entries.len() == before {
+            return Ok(false);
+        }
+        self.save().map(|_| true)
+    }
+
+    // UUIDs sind im Offline-Modus zufällig, daher zählt auch der Name
+    pub fn find(&self, name: &str, uuid: Uuid) -> Option<&Ban> {
+        self.entries.iter().find(|b| b.uuid == Some(uuid) || b.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn entries(&self) -> &[Ban] {
+        &self.entries
+    }
+}
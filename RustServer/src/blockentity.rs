@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use crate::chunk::BlockEntityData;
+use crate::item::{self, ItemStack};
+use crate::json::Json;
+use crate::protocol::clientbound;
+use crate::protocol::types::{encode_position, write_varint_to_vec};
+use crate::sign::SignText;
+use crate::spawner::Spawner;
+use crate::{block, nbt, recipe, Connection};
+
+// Slot-Anzahlen wie bei Vanilla
+const CHEST_SLOTS: usize = 27;
+const BARREL_SLOTS: usize = 27;
+const HOPPER_SLOTS: usize = 5;
+// Ofen: Zutat, Brennstoff, Ergebnis
+pub const FURNACE_INPUT: usize = 0;
+pub const FURNACE_FUEL: usize = 1;
+pub const FURNACE_RESULT: usize = 2;
+const FURNACE_SLOTS: usize = 3;
+
+// IDs im Register minecraft:block_entity_type (1.21.1), für Chunk Data
+const TYPE_FURNACE: i32 = 0;
+const TYPE_CHEST: i32 = 1;
+const TYPE_ENDER_CHEST: i32 = 3;
+const TYPE_SIGN: i32 = 7;
+const TYPE_SPAWNER: i32 = 9;
+const TYPE_HOPPER: i32 = 17;
+const TYPE_BED: i32 = 24;
+const TYPE_BARREL: i32 = 26;
+
+// Seite eines Blocks, über die ein Trichter Items hineinlegt oder herausnimmt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Face {
+    Down,
+    Up,
+    Side,
+}
+
+// Zustand, den ein Block über seinen Block-Zustand hinaus hat; liegt im Chunk an der Position des Blocks
+#[derive(Debug, Clone)]
+pub enum BlockEntity {
+    Chest { items: Vec<ItemStack> },
+    Barrel { items: Vec<ItemStack> },
+    // Der Inhalt gehört dem jeweiligen Spieler, die Block-Entity braucht der Client nur zum Zeichnen
+    EnderChest,
+    // Wie bei der Endertruhe nur zum Zeichnen; die Farbe steckt im Block
+    Bed,
+    // Verbleibende und volle Brenndauer des aktuellen Brennstoffs sowie Fortschritt des Schmelzens, in Ticks
+    Furnace { items: Vec<ItemStack>, burn: u32, burn_total: u32, cook: u32 },
+    // Ticks bis zur nächsten Übertragung
+    Hopper { items: Vec<ItemStack>, cooldown: i32 },
+    // Wer den Editor offen hat, darf als Einziger den Text schicken; wird nicht gespeichert
+    Sign { front: Box<SignText>, back: Box<SignText>, waxed: bool, editor: Option<Uuid> },
+    Spawner(Box<Spawner>),
+}
+
+impl BlockEntity {
+    // Leere Block-Entity für einen neu gesetzten Block, falls er eine hat
+    pub fn for_block(state: u16) -> Option<BlockEntity> {
+        match block::name(state) {
+            "minecraft:chest" => Some(BlockEntity::Chest { items: vec![ItemStack::empty(); CHEST_SLOTS] }),
+            "minecraft:barrel" => Some(BlockEntity::Barrel { items: vec![ItemStack::empty(); BARREL_SLOTS] }),
+            "minecraft:ender_chest" => Some(BlockEntity::EnderChest),
+            "minecraft:furnace" => Some(BlockEntity::Furnace { items: vec![ItemStack::empty(); FURNACE_SLOTS], burn: 0, burn_total: 0, cook: 0 }),
+            "minecraft:hopper" => Some(BlockEntity::Hopper { items: vec![ItemStack::empty(); HOPPER_SLOTS], cooldown: 0 }),
+            "minecraft:spawner" => Some(BlockEntity::Spawner(Box::default())),
+            _ if block::is_bed(state) => Some(BlockEntity::Bed),
+            _ if block::is_sign(state) => Some(BlockEntity::Sign { front: Box::new(SignText::new()), back: Box::new(SignText::new()), waxed: false, editor: None }),
+            _ => None,
+        }
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            BlockEntity::Chest { .. } => "minecraft:chest",
+            BlockEntity::Barrel { .. } => "minecraft:barrel",
+            BlockEntity::EnderChest => "minecraft:ender_chest",
+            BlockEntity::Bed => "minecraft:bed",
+            BlockEntity::Furnace { .. } => "minecraft:furnace",
+            BlockEntity::Hopper { .. } => "minecraft:hopper",
+            BlockEntity::Sign { .. } => "minecraft:sign",
+            BlockEntity::Spawner(_) => "minecraft:mob_spawner",
+        }
+    }
+
+    fn type_id(&self) -> i32 {
+        match self {
+            BlockEntity::Chest { .. } => TYPE_CHEST,
+            BlockEntity::Barrel { .. } => TYPE_BARREL,
+            BlockEntity::EnderChest => TYPE_ENDER_CHEST,
+            BlockEntity::Bed => TYPE_BED,
+            BlockEntity::Furnace { .. } => TYPE_FURNACE,
+            BlockEntity::Hopper { .. } => TYPE_HOPPER,
+            BlockEntity::Sign { .. } => TYPE_SIGN,
+            BlockEntity::Spawner(_) => TYPE_SPAWNER,
+        }
+    }
+
+    // Schilder, Endertruhen, Betten und Spawner haben keine eigenen Slots
+    pub fn items(&self) -> &[ItemStack] {
+        match self {
+            BlockEntity::Chest { items } | BlockEntity::Barrel { items } | BlockEntity::Furnace { items, .. } | BlockEntity::Hopper { items, .. } => items,
+            BlockEntity::EnderChest | BlockEntity::Bed | BlockEntity::Sign { .. } | BlockEntity::Spawner(_) => &[],
+        }
+    }
+
+    pub fn items_mut(&mut self) -> &mut [ItemStack] {
+        match self {
+            BlockEntity::Chest { items } | BlockEntity::Barrel { items } | BlockEntity::Furnace { items, .. } | BlockEntity::Hopper { items, .. } => items,
+            BlockEntity::EnderChest | BlockEntity::Bed | BlockEntity::Sign { .. } | BlockEntity::Spawner(_) => &mut [],
+        }
+    }
+
+    // Leert den Container, etwa wenn der Block abgebaut wird
+    pub fn take_items(&mut self) -> Vec<ItemStack> {
+        self.items_mut().iter_mut().map(|slot| std::mem::replace(slot, ItemStack::empty())).filter(|stack| !stack.is_empty()).collect()
+    }
+
+    // Slots, die über eine Seite erreichbar sind; beim Ofen oben die Zutat, seitlich der Brennstoff und unten
+    // Ergebnis und Brennstoff (für leere Eimer)
+    fn slots_for_face(&self, face: Face) -> Vec<usize> {
+        match (self, face) {
+            (BlockEntity::Furnace { .. }, Face::Up) => vec![FURNACE_INPUT],
+            (BlockEntity::Furnace { .. }, Face::Side) => vec![FURNACE_FUEL],
+            (BlockEntity::Furnace { .. }, Face::Down) => vec![FURNACE_RESULT, FURNACE_FUEL],
+            _ => (0..self.items().len()).collect(),
+        }
+    }
+
+    fn can_insert(&self, slot: usize, stack: &ItemStack) -> bool {
+        match self {
+            BlockEntity::Furnace { items, .. } => match slot {
+                FURNACE_RESULT => false,
+                FURNACE_FUEL => recipe::fuel_ticks(stack).is_some() || (stack.item == "minecraft:bucket" && items[FURNACE_FUEL].item != "minecraft:bucket"),
+                _ => true,
+            },
+            _ => true,
+        }
+    }
+
+    fn can_extract(&self, slot: usize, stack: &ItemStack, face: Face) -> bool {
+        match self {
+            BlockEntity::Furnace { .. } if face == Face::Down && slot == FURNACE_FUEL => matches!(stack.item.as_str(), "minecraft:bucket" | "minecraft:water_bucket"),
+            _ => true,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items().iter().all(|stack| !stack.is_empty() && stack.count >= item::max_stack_size(&stack.item))
+    }
+
+    // Legt so viel wie möglich über face hinein: zuerst auf passende Stapel bzw. freie Slots in Slot-Reihenfolge.
+    // Gibt den Rest zurück
+    pub fn insert(&mut self, mut stack: ItemStack, face: Face) -> ItemStack {
+        for slot in self.slots_for_face(face) {
+            if stack.is_empty() {
+                break;
+            }
+            if !self.can_insert(slot, &stack) {
+                continue;
+            }
+            let max = item::max_stack_size(&stack.item);
+            let target = &mut self.items_mut()[slot];
+            if target.is_empty() {
+                let moved = stack.count.min(max);
+                *target = ItemStack { count: moved, ..stack.clone() };
+                stack.count -= moved;
+            } else if target.stacks_with(&stack) {
+                let moved = stack.count.min(max.saturating_sub(target.count));
+                target.count += moved;
+                stack.count -= moved;
+            }
+        }
+        if stack.count == 0 {
+            ItemStack::empty()
+        } else {
+            stack
+        }
+    }
+
+    // Nimmt ein einzelnes Item aus dem ersten über face erreichbaren Slot, den accept annimmt
+    pub fn extract_one(&mut self, face: Face, accept: impl Fn(&ItemStack) -> bool) -> Option<ItemStack> {
+        let slot = self.slots_for_face(face).into_iter().find(|&slot| {
+            let stack = &self.items()[slot];
+            !stack.is_empty() && self.can_extract(slot, stack, face) && accept(&ItemStack { count: 1, ..stack.clone() })
+        })?;
+        let stack = &mut self.items_mut()[slot];
+        let taken = ItemStack { count: 1, ..stack.clone() };
+        stack.count -= 1;
+        if stack.count == 0 {
+            *stack = ItemStack::empty();
+        }
+        Some(taken)
+    }
+
+    // {"id": "minecraft:hopper", "x": .., "y": .., "z": .., "items": [{"slot": n, ...}], "cooldown": n}
+    pub fn to_json(&self, (x, y, z): (i32, i32, i32)) -> Json {
+        let mut data = BTreeMap::new();
+        data.insert("id".to_string(), Json::String(self.id().to_string()));
+        data.insert("x".to_string(), Json::Number(x as f64));
+        data.insert("y".to_string(), Json::Number(y as f64));
+        data.insert("z".to_string(), Json::Number(z as f64));
+        data.insert("items".to_string(), item::slots_to_json(self.items()));
+        match self {
+            BlockEntity::Hopper { cooldown, .. } => {
+                data.insert("cooldown".to_string(), Json::Number(*cooldown as f64));
+            }
+            BlockEntity::Furnace { burn, burn_total, cook, .. } => {
+                data.insert("lit_time_remaining".to_string(), Json::Number(*burn as f64));
+                data.insert("lit_total_time".to_string(), Json::Number(*burn_total as f64));
+                data.insert("cooking_time_spent".to_string(), Json::Number(*cook as f64));
+            }
+            BlockEntity::Sign { front, back, waxed, .. } => {
+                data.insert("front_text".to_string(), front.to_json());
+                data.insert("back_text".to_string(), back.to_json());
+                data.insert("is_waxed".to_string(), Json::Bool(*waxed));
+            }
+            BlockEntity::Spawner(spawner) => spawner.write_json(&mut data),
+            _ => {}
+        }
+        Json::Object(data)
+    }
+
+    // Position und Block-Entity; passt sie nicht zum Block an der Stelle, verwirft sie der Aufrufer
+    pub fn from_json(data: &Json) -> Option<((i32, i32, i32), BlockEntity)> {
+        let number = |key: &str| data.get(key).and_then(Json::as_f64);
+        let position = (number("x")? as i32, number("y")? as i32, number("z")? as i32);
+        let mut entity = match data.get("id")?.as_str()? {
+            "minecraft:chest" => BlockEntity::Chest { items: vec![ItemStack::empty(); CHEST_SLOTS] },
+            "minecraft:barrel" => BlockEntity::Barrel { items: vec![ItemStack::empty(); BARREL_SLOTS] },
+            "minecraft:ender_chest" => BlockEntity::EnderChest,
+            "minecraft:bed" => BlockEntity::Bed,
+            "minecraft:furnace" => BlockEntity::Furnace {
+                items: vec![ItemStack::empty(); FURNACE_SLOTS],
+                burn: number("lit_time_remaining").unwrap_or(0.0) as u32,
+                burn_total: number("lit_total_time").unwrap_or(0.0) as u32,
+                cook: number("cooking_time_spent").unwrap_or(0.0) as u32,
+            },
+            "minecraft:hopper" => BlockEntity::Hopper { items: vec![ItemStack::empty(); HOPPER_SLOTS], cooldown: number("cooldown").unwrap_or(0.0) as i32 },
+            "minecraft:sign" => BlockEntity::Sign {
+                front: Box::new(SignText::from_json(data.get("front_text"))),
+                back: Box::new(SignText::from_json(data.get("back_text"))),
+                waxed: data.get("is_waxed").and_then(Json::as_bool).unwrap_or(false),
+                editor: None,
+            },
+            "minecraft:mob_spawner" => BlockEntity::Spawner(Box::new(Spawner::from_json(data))),
+            _ => return None,
+        };
+        item::slots_from_json(data.get("items"), entity.items_mut());
+        Some((position, entity))
+    }
+
+    // Was der Client zum Zeichnen braucht: bei Schildern der Text, bei Spawnern der Mob, Container schicken nichts
+    fn client_data(&self) -> Json {
+        let mut data = BTreeMap::new();
+        match self {
+            BlockEntity::Sign { front, back, waxed, .. } => {
+                data.insert("front_text".to_string(), front.to_nbt_json());
+                data.insert("back_text".to_string(), back.to_nbt_json());
+                data.insert("is_waxed".to_string(), Json::Bool(*waxed));
+            }
+            BlockEntity::Spawner(spawner) => spawner.write_client_data(&mut data),
+            _ => {}
+        }
+        Json::Object(data)
+    }
+
+    // Eintrag im Chunk-Data-Paket: Position im Chunk, Typ und Daten
+    pub fn encode(&self, (x, y, z): (i32, i32, i32)) -> Vec<u8> {
+        let mut data = vec![(((x & 15) << 4) | (z & 15)) as u8];
+        data.extend((y as i16).to_be_bytes());
+        data.extend(write_varint_to_vec(self.type_id()));
+        data.extend(nbt::from_json(&self.client_data()));
+        data
+    }
+
+    // Block Entity Data, wenn sich etwas Sichtbares geändert hat
+    pub fn send_data(&self, connection: &Connection, (x, y, z): (i32, i32, i32)) -> Result<(), String> {
+        let mut packet_data = encode_position(x, y, z).to_be_bytes().to_vec();
+        packet_data.extend(write_varint_to_vec(self.type_id()));
+        packet_data.extend(nbt::from_json(&self.client_data()));
+        connection.send(clientbound::BLOCK_ENTITY_DATA, &packet_data)
+    }
+}
+
+impl BlockEntityData for BlockEntity {
+    fn to_json(&self, position: (i32, i32, i32)) -> Json {
+        BlockEntity::to_json(self, position)
+    }
+
+    fn from_json(data: &Json) -> Option<((i32, i32, i32), BlockEntity)> {
+        BlockEntity::from_json(data)
+    }
+
+    fn belongs_to(&self, state: u16) -> bool {
+        BlockEntity::for_block(state).is_some_and(|b| b.id() == self.id())
+    }
+}
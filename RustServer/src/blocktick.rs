@@ -0,0 +1,121 @@
+use std::collections::{BTreeSet, HashSet};
+use rand::Rng;
+use crate::{block, chunk, dimension, door, farming, fire, gamerule, leaves, piston, pressureplate, redstone, sapling, snow, tripwire, Player, Server, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Blöcke mit Zufallsticks neben Ackerboden, Feldfrüchten, Setzlingen, Blättern, Schnee und Eis; Feuer, Knöpfe, Platten und
+// Stolperdrähte nur, um nach einem Neustart wieder geplante Ticks zu bekommen
+const RANDOM_TICKING: &[&str] = &["minecraft:lava", fire::FIRE];
+
+// Geplante Block-Ticks einer Welt nach Fälligkeit; jeder Block steht höchstens einmal darin. Gespeichert werden sie
+// nicht
+#[derive(Debug, Default)]
+pub struct BlockTicks {
+    due: BTreeSet<(i64, BlockPos)>,
+    scheduled: HashSet<BlockPos>,
+}
+
+impl BlockTicks {
+    pub fn schedule(&mut self, tick: i64, position: BlockPos) {
+        if self.scheduled.insert(position) {
+            self.due.insert((tick, position));
+        }
+    }
+
+    pub fn is_scheduled(&self, position: BlockPos) -> bool {
+        self.scheduled.contains(&position)
+    }
+
+    fn take_due(&mut self, now: i64) -> Vec<BlockPos> {
+        let mut positions = Vec::new();
+        while let Some(&(tick, position)) = self.due.first() {
+            if tick > now {
+                break;
+            }
+            self.due.pop_first();
+            self.scheduled.remove(&position);
+            positions.push(position);
+        }
+        positions
+    }
+}
+
+// Führt die fälligen Block-Ticks aus, verteilt randomTickSpeed Zufallsticks auf jede Sektion der Chunks, die ein
+// Client geladen hat, und lässt dort Wasser frieren und Schnee fallen. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let _profile = server.profiler.world("block ticks", world.dimension);
+        let now = world.time.age;
+        for position in world.block_ticks.take_due(now) {
+            let (x, y, z) = position;
+            if !world.chunks.contains_key(&(x >> 4, z >> 4)) {
+                continue;
+            }
+            let state = world.get_block(x, y, z);
+            if block::name(state) == fire::FIRE {
+                fire::scheduled_tick(&players, &mut world, position, state);
+            } else if leaves::is_leaves(state) {
+                leaves::scheduled_tick(&mut world, position, state);
+            } else if redstone::is_button(state) {
+                redstone::scheduled_tick(&players, &mut world, position, state);
+            } else if block::is_pressure_plate(state) {
+                pressureplate::scheduled_tick(&players, &mut world, position, state);
+            } else if block::name(state).starts_with("minecraft:tripwire") {
+                tripwire::scheduled_tick(&players, &mut world, position, state);
+            } else if block::is_piston(state) {
+                piston::scheduled_tick(server, &players, &mut world, position, state);
+            } else if door::is_openable(state) {
+                door::scheduled_tick(&players, &mut world, position, state);
+            }
+        }
+        random_ticks(server, &players, &mut world);
+        snow::tick(&mut world);
+    }
+}
+
+fn random_ticks(server: &Server, players: &[Player], world: &mut World) {
+    let speed = world.rules.get_int(gamerule::RANDOM_TICK_SPEED).max(0);
+    if speed == 0 {
+        return;
+    }
+    // Nur die Zustandsbereiche vergleichen, statt für jeden Treffer den Namen nachzuschlagen
+    let lists = [
+        RANDOM_TICKING, farming::RANDOM_TICKING, sapling::RANDOM_TICKING, leaves::RANDOM_TICKING, redstone::RANDOM_TICKING,
+        pressureplate::RANDOM_TICKING, tripwire::RANDOM_TICKING, snow::RANDOM_TICKING,
+    ];
+    let ticking: Vec<(&str, u16, u16)> = block::state_ranges().filter(|(name, _, _)| lists.iter().any(|list| list.contains(name))).collect();
+    let kind = |state: u16| ticking.iter().find(|&&(_, first, count)| (first..first + count).contains(&state)).map(|&(name, _, _)| name);
+    let mut rng = rand::thread_rng();
+    let mut due = Vec::new();
+    for &(chunk_x, chunk_z) in world.chunk_viewers.keys() {
+        let Some(chunk) = world.chunks.get(&(chunk_x, chunk_z)) else { continue };
+        for section in 0..chunk::SECTION_COUNT {
+            if !chunk.section_may_contain(section, &|state| kind(state).is_some()) {
+                continue;
+            }
+            for _ in 0..speed {
+                let (x, y, z) = (rng.gen_range(0..16), chunk::MIN_Y + section as i32 * 16 + rng.gen_range(0..16), rng.gen_range(0..16));
+                if let Some(name) = kind(chunk.get_block(x, y, z)) {
+                    due.push((name, (chunk_x * 16 + x as i32, y, chunk_z * 16 + z as i32)));
+                }
+            }
+        }
+    }
+    for (name, position) in due {
+        match name {
+            "minecraft:lava" => fire::lava_tick(world, position),
+            fire::FIRE if !world.block_ticks.is_scheduled(position) => fire::schedule(world, position),
+            fire::FIRE => {}
+            name if sapling::RANDOM_TICKING.contains(&name) => sapling::random_tick(world, position),
+            name if leaves::RANDOM_TICKING.contains(&name) => leaves::random_tick(server, players, world, position),
+            name if redstone::RANDOM_TICKING.contains(&name) => redstone::random_tick(world, position),
+            name if pressureplate::RANDOM_TICKING.contains(&name) => pressureplate::random_tick(world, position),
+            name if tripwire::RANDOM_TICKING.contains(&name) => tripwire::random_tick(world, position),
+            name if snow::RANDOM_TICKING.contains(&name) => snow::random_tick(world, position),
+            _ => farming::random_tick(server, players, world, position),
+        }
+    }
+}
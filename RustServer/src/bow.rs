@@ -0,0 +1,197 @@
+use rand::Rng;
+use crate::gamemode::GameMode;
+use crate::item::{self, ItemStack};
+use crate::projectile::{self, Arrow};
+use crate::{entity, Player, Server, World};
+
+const BOW: &str = "minecraft:bow";
+const CROSSBOW: &str = "minecraft:crossbow";
+// Getippte und Spektralpfeile gibt es noch nicht, also nur normale Pfeile als Munition
+const ARROW_ITEM: &str = "minecraft:arrow";
+const POWER: &str = "minecraft:power";
+const PUNCH: &str = "minecraft:punch";
+const FLAME: &str = "minecraft:flame";
+const INFINITY: &str = "minecraft:infinity";
+const QUICK_CHARGE: &str = "minecraft:quick_charge";
+const MULTISHOT: &str = "minecraft:multishot";
+// Nach einer Sekunde ist der Bogen voll gespannt; darunter fliegt kein Pfeil
+const FULL_DRAW_TICKS: f64 = 20.0;
+const MIN_POWER: f64 = 0.1;
+// Blöcke pro Tick bei voller Kraft
+const BOW_VELOCITY: f64 = 3.0;
+const CROSSBOW_VELOCITY: f64 = 3.15;
+// Laden der Armbrust, jede Stufe von Quick Charge ist fünf Ticks schneller
+const CHARGE_TICKS: u32 = 25;
+const QUICK_CHARGE_TICKS: u32 = 5;
+// Streuung wie Projectile.shoot bei Vanilla
+const SPREAD: f64 = 0.0172275;
+// Multishot verschießt zwei weitere Pfeile um so viel Grad zur Seite
+const MULTISHOT_ANGLE: f32 = 10.0;
+const MULTISHOT_ARROWS: usize = 3;
+// Der Pfeil startet knapp unter den Augen
+const EYE_HEIGHT: f64 = 1.62 - 0.1;
+
+// Ein gespannter Bogen oder eine Armbrust beim Laden
+#[derive(Debug, Clone)]
+pub struct Drawing {
+    slot: usize,
+    item: String,
+    ticks: u32,
+}
+
+fn level(stack: &ItemStack, enchantment: &str) -> u32 {
+    stack.components.enchantments.get(enchantment).copied().unwrap_or(0)
+}
+
+fn is_ammo(stack: &ItemStack) -> bool {
+    stack.item == ARROW_ITEM
+}
+
+// Blickrichtung aus Yaw und Pitch in Grad, wie beim Client
+pub fn look_direction(yaw: f32, pitch: f32) -> (f64, f64, f64) {
+    let (yaw, pitch) = ((yaw as f64).to_radians(), (pitch as f64).to_radians());
+    (-yaw.sin() * pitch.cos(), -pitch.sin(), yaw.cos() * pitch.cos())
+}
+
+pub fn velocity(direction: (f64, f64, f64), speed: f64) -> (f64, f64, f64) {
+    let mut rng = rand::thread_rng();
+    let mut spread = || SPREAD * (rng.gen::<f64>() - rng.gen::<f64>());
+    (
+        (direction.0 + spread()) * speed,
+        (direction.1 + spread()) * speed,
+        (direction.2 + spread()) * speed,
+    )
+}
+
+// Zeigt den anderen in Sichtweite, dass der Spieler spannt oder lädt
+pub fn broadcast_hand(players: &[Player], index: usize, off_hand: Option<bool>) {
+    let player = &players[index];
+    for viewer in players.iter().filter(|p| p.uuid != player.uuid && entity::in_view(p, player.dimension, player.position)) {
+        let _ = entity::send_hand_state(&viewer.connection, player.entity_id, off_hand);
+    }
+}
+
+// Verbraucht einen Pfeil außer im Kreativmodus; false, wenn keiner da ist
+fn take_ammo(player: &mut Player) -> bool {
+    if player.game_mode == GameMode::Creative {
+        return true;
+    }
+    let Some(slot) = player.inventory.find_ammo(is_ammo) else { return false };
+    player.inventory.consume_one(slot);
+    let _ = player.inventory.send_slot(&player.connection, slot);
+    true
+}
+
+// Nutzt den Bogen oder die Armbrust ab, außer im Kreativmodus
+fn wear(player: &mut Player, slot: usize) {
+    if player.game_mode == GameMode::Creative {
+        return;
+    }
+    let mut stack = player.inventory.slot(slot).clone();
+    item::wear(&mut stack);
+    player.inventory.set(slot, stack);
+    let _ = player.inventory.send_slot(&player.connection, slot);
+}
+
+// Use Item mit Bogen oder Armbrust: eine geladene Armbrust schießt sofort, sonst beginnt das Spannen, sofern
+// Munition da ist. false, wenn das Item weder Bogen noch Armbrust ist
+pub fn use_item(players: &mut [Player], world: &mut World, index: usize, slot: usize) -> bool {
+    let player = &mut players[index];
+    let stack = player.inventory.slot(slot);
+    if stack.item != BOW && stack.item != CROSSBOW {
+        return false;
+    }
+    if stack.item == CROSSBOW && !stack.components.charged_projectiles.is_empty() {
+        shoot_crossbow(players, world, index, slot);
+        return true;
+    }
+    if player.inventory.find_ammo(is_ammo).is_none() && player.game_mode != GameMode::Creative {
+        return true;
+    }
+    player.drawing = Some(Drawing { slot, item: stack.item.clone(), ticks: 0 });
+    let off_hand = slot != player.inventory.hand_slot(false);
+    broadcast_hand(players, index, Some(off_hand));
+    true
+}
+
+// Player Action Release Use Item: der Bogen schießt mit der Kraft der Spannzeit, eine fertig geladene Armbrust
+// nimmt ihre Pfeile auf
+pub fn release(players: &mut [Player], world: &mut World, index: usize) {
+    let Some(drawing) = players[index].drawing.take() else { return };
+    broadcast_hand(players, index, None);
+    let player = &mut players[index];
+    if player.health <= 0.0 || player.inventory.slot(drawing.slot).item != drawing.item {
+        return;
+    }
+    let stack = player.inventory.slot(drawing.slot).clone();
+    if drawing.item == CROSSBOW {
+        if drawing.ticks < CHARGE_TICKS.saturating_sub(QUICK_CHARGE_TICKS * level(&stack, QUICK_CHARGE)) || !take_ammo(player) {
+            return;
+        }
+        let mut charged = stack;
+        let arrows = if level(&charged, MULTISHOT) > 0 { MULTISHOT_ARROWS } else { 1 };
+        charged.components.charged_projectiles = vec![ItemStack::new(ARROW_ITEM, 1); arrows];
+        debug!("{} lädt die Armbrust", player.username);
+        player.inventory.set(drawing.slot, charged);
+        let _ = player.inventory.send_slot(&player.connection, drawing.slot);
+        return;
+    }
+    let draw = drawing.ticks as f64 / FULL_DRAW_TICKS;
+    let power = ((draw * draw + draw * 2.0) / 3.0).min(1.0);
+    if power < MIN_POWER {
+        return;
+    }
+    let infinite = player.game_mode == GameMode::Creative || level(&stack, INFINITY) > 0;
+    if !infinite && !take_ammo(player) {
+        return;
+    }
+    wear(player, drawing.slot);
+    let power_level = level(&stack, POWER);
+    let damage = projectile::BASE_DAMAGE + if power_level > 0 { 0.5 * power_level as f64 + 0.5 } else { 0.0 };
+    let arrow = Arrow::new(Some(player.uuid), damage, power >= 1.0, level(&stack, PUNCH), level(&stack, FLAME) > 0);
+    let position = (player.position.0, player.position.1 + EYE_HEIGHT, player.position.2);
+    let direction = look_direction(player.rotation.0, player.rotation.1);
+    debug!("{} schießt einen Pfeil mit Kraft {:.2}", player.username, power);
+    projectile::shoot(players, world, position, velocity(direction, power * BOW_VELOCITY), arrow);
+}
+
+// Verschießt die geladenen Pfeile einer Armbrust; bei Multishot fliegen die weiteren schräg zur Seite
+fn shoot_crossbow(players: &mut [Player], world: &mut World, index: usize, slot: usize) {
+    let player = &mut players[index];
+    let mut stack = player.inventory.slot(slot).clone();
+    let arrows = std::mem::take(&mut stack.components.charged_projectiles);
+    let punch = level(&stack, PUNCH);
+    player.inventory.set(slot, stack);
+    let _ = player.inventory.send_slot(&player.connection, slot);
+    let (uuid, (yaw, pitch)) = (player.uuid, player.rotation);
+    let position = (player.position.0, player.position.1 + EYE_HEIGHT, player.position.2);
+    debug!("{} schießt mit der Armbrust", player.username);
+    for (i, _) in arrows.iter().enumerate() {
+        let offset = [0.0, -MULTISHOT_ANGLE, MULTISHOT_ANGLE][i % MULTISHOT_ARROWS];
+        wear(&mut players[index], slot);
+        let arrow = Arrow::new(Some(uuid), projectile::BASE_DAMAGE, true, punch, false);
+        projectile::shoot(players, world, position, velocity(look_direction(yaw + offset, pitch), CROSSBOW_VELOCITY), arrow);
+    }
+}
+
+// Set Held Item: wer den Platz wechselt, spannt nicht weiter
+pub fn stop(players: &mut [Player], index: usize) {
+    if players[index].drawing.take().is_some() {
+        broadcast_hand(players, index, None);
+    }
+}
+
+// Zählt die Spannzeit; wer stirbt oder das Item nicht mehr hält, hört auf. Darf nur ohne gehaltene Sperren
+// aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut players = server.players.lock().unwrap();
+    for index in 0..players.len() {
+        let player = &mut players[index];
+        let Some(drawing) = &mut player.drawing else { continue };
+        if player.health > 0.0 && player.inventory.slot(drawing.slot).item == drawing.item {
+            drawing.ticks += 1;
+            continue;
+        }
+        stop(&mut players, index);
+    }
+}
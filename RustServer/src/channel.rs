@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::protocol::types::write_string_to_vec;
+use crate::{Connection, Player, Server};
+use crate::protocol::clientbound;
+
+pub const BRAND: &str = "minecraft:brand";
+pub const REGISTER: &str = "minecraft:register";
+pub const UNREGISTER: &str = "minecraft:unregister";
+pub const SERVER_BRAND: &str = "RustServer";
+// Obergrenze des Vanilla-Clients für serverseitig empfangene Nutzdaten
+pub const MAX_PAYLOAD_SIZE: usize = 32767;
+// Schützt vor Clients, die beliebig viele Kanäle anmelden
+pub const MAX_CLIENT_CHANNELS: usize = 128;
+
+pub type ChannelHandler = Arc<dyn Fn(&Server, &Player, &[u8]) + Send + Sync>;
+
+#[derive(Default)]
+pub struct ChannelRegistry {
+    handlers: Mutex<HashMap<String, ChannelHandler>>,
+}
+
+// Kanalnamen sind Identifier der Form namespace:pfad
+pub fn is_valid_channel(channel: &str) -> bool {
+    let Some((namespace, path)) = channel.split_once(':') else { return false };
+    let allowed = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.');
+    !namespace.is_empty()
+        && !path.is_empty()
+        && channel.len() <= 255
+        && namespace.chars().all(allowed)
+        && path.chars().all(|c| allowed(c) || c == '/')
+}
+
+impl ChannelRegistry {
+    pub fn new() -> ChannelRegistry {
+        ChannelRegistry::default()
+    }
+
+    // Der Namensraum minecraft: gehört dem Server selbst (Brand und Kanal-Registrierung)
+    pub fn register<F>(&self, channel: &str, handler: F) -> Result<(), String>
+    where
+        F: Fn(&Server, &Player, &[u8]) + Send + Sync + 'static,
+    {
+        if !is_valid_channel(channel) {
+            return Err(format!("Ungültiger Kanalname \"{}\"", channel));
+        }
+        if channel.starts_with("minecraft:") {
+            return Err(format!("Kanal \"{}\" ist reserviert", channel));
+        }
+        let mut handlers = self.handlers.lock().unwrap();
+        if handlers.contains_key(channel) {
+            return Err(format!("Kanal \"{}\" ist bereits registriert", channel));
+        }
+        handlers.insert(channel.to_string(), Arc::new(handler));
+        Ok(())
+    }
+
+    pub fn unregister(&self, channel: &str) -> bool {
+        self.handlers.lock().unwrap().remove(channel).is_some()
+    }
+
+    pub fn channels(&self) -> Vec<String> {
+        let mut channels: Vec<String> = self.handlers.lock().unwrap().keys().cloned().collect();
+        channels.sort();
+        channels
+    }
+
+    // Liefert nur eine Kopie des Handlers, damit er ohne gehaltene Sperre laufen kann
+    pub fn handler(&self, channel: &str) -> Option<ChannelHandler> {
+        self.handlers.lock().unwrap().get(channel).cloned()
+    }
+}
+
+pub fn send_plugin_message(connection: &Connection, channel: &str, data: &[u8]) -> Result<(), String> {
+    let mut packet_data = write_string_to_vec(channel);
+    packet_data.extend(data);
+    connection.send(clientbound::CUSTOM_PAYLOAD, &packet_data)
+}
+
+// minecraft:register und minecraft:unregister tragen die Kanäle NUL-getrennt
+pub fn encode_channel_list(channels: &[String]) -> Vec<u8> {
+    channels.join("\0").into_bytes()
+}
+
+pub fn decode_channel_list(data: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(data).split('\0').filter(|c| is_valid_channel(c)).map(str::to_string).collect()
+}
+
+// Kanäle, die Plugins erst nach dem Beitritt an- oder abmelden, erfahren die schon verbundenen Clients hierüber.
+// Darf nicht mit gehaltener players-Sperre aufgerufen werden
+pub fn announce(server: &Server, channel: &str, registered: bool) {
+    let kind = if registered { REGISTER } else { UNREGISTER };
+    for player in server.players.lock().unwrap().iter() {
+        let _ = send_plugin_message(&player.connection, kind, channel.as_bytes());
+    }
+}
+
+// Wird direkt nach dem Beitritt gesendet: eigene Brand und alle serverseitig registrierten Kanäle
+pub fn send_server_channels(server: &Server, connection: &Connection) -> Result<(), String> {
+    send_plugin_message(connection, BRAND, &write_string_to_vec(SERVER_BRAND))?;
+    let channels = server.channels.channels();
+    if channels.is_empty() {
+        return Ok(());
+    }
+    send_plugin_message(connection, REGISTER, &encode_channel_list(&channels))
+}
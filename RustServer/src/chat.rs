@@ -0,0 +1,42 @@
+use crate::motd;
+use crate::text::Text;
+
+const MESSAGE: &str = "{message}";
+
+// Setzt {prefix}, {name} usw. ein; unbekannte Platzhalter bleiben stehen
+pub fn expand(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let value = tail.find('}').and_then(|end| values.iter().find(|(key, _)| *key == &tail[1..end]).map(|(_, value)| (*value, end)));
+        match value {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Chatzeile aus dem Format in server.properties. Format und Präfixe stammen vom Betreiber und dürfen immer
+// &-Farbcodes enthalten, die Nachricht selbst nur mit colors (Recht server.chat.color)
+pub fn format(template: &str, values: &[(&str, &str)], message: &str, colors: bool) -> Text {
+    let mut line = Text::new("");
+    for (i, part) in template.split(MESSAGE).enumerate() {
+        if i > 0 {
+            line.push(if colors { motd::legacy(message) } else { Text::new(message) });
+        }
+        if !part.is_empty() {
+            line.push(motd::legacy(&expand(part, values)));
+        }
+    }
+    line
+}
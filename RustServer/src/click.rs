@@ -0,0 +1,874 @@
+use byteorder::{BigEndian, ReadBytesExt};
+use crate::anvil::{self, Anvil};
+use crate::blockentity::{FURNACE_FUEL, FURNACE_INPUT, FURNACE_RESULT};
+use crate::enchanting::{self, Enchanting};
+use crate::error::ServerError;
+use crate::gamemode::GameMode;
+use crate::item::{self, ItemStack};
+use crate::map::{self, Cartography, MapStore};
+use crate::merchant::{self, Merchant};
+use crate::protocol::clientbound;
+use crate::protocol::types::{read_varint_from_cursor, write_varint_to_vec};
+use crate::protocol::version::ProtocolVersion;
+use crate::recipe::{self, Ingredient, Recipe, RecipeRegistry};
+use crate::stonecutter::{self, Stonecutter};
+use crate::window::{self, Container, Window};
+use crate::worldevent::WorldEvent;
+use crate::{entity, experience, farming, smithing, Connection, Player, Server, World};
+
+// Klick-Modi des Click-Container-Pakets
+const MODE_PICKUP: i32 = 0;
+const MODE_QUICK_MOVE: i32 = 1;
+const MODE_SWAP: i32 = 2;
+const MODE_CLONE: i32 = 3;
+const MODE_THROW: i32 = 4;
+const MODE_QUICK_CRAFT: i32 = 5;
+const MODE_PICKUP_ALL: i32 = 6;
+// Klick neben das Fenster: das gehaltene Item fallen lassen
+const OUTSIDE: i16 = -999;
+// Taste F tauscht mit der Zweithand, 0-8 mit der Schnellleiste
+const SWAP_OFF_HAND: i8 = 40;
+// Im eigenen Inventar: Crafting-Ergebnis, 2x2-Gitter, Rüstung, Zweithand
+const RESULT: usize = 0;
+const GRID: std::ops::Range<usize> = 1..5;
+const ARMOR: std::ops::Range<usize> = 5..9;
+const MAIN: std::ops::Range<usize> = 9..36;
+const HOTBAR: std::ops::Range<usize> = 36..45;
+const OFF_HAND: usize = 45;
+// An der Werkbank: Ergebnis und 3x3-Gitter
+const TABLE_RESULT: usize = 0;
+const TABLE_GRID: std::ops::Range<usize> = 1..window::CRAFTING_SLOTS;
+// Höchstens so viele Züge beim Shift-Klick auf das Crafting-Ergebnis
+const MAX_CRAFTS: usize = 64;
+// Augenhöhe, aus der geworfene Items fallen
+pub const THROW_HEIGHT: f64 = 1.3;
+
+// Ziehen mit gedrückter Maustaste: Art (0 links, 1 rechts, 2 mittel) und bisher überstrichene Slots
+#[derive(Debug, Clone, Default)]
+pub struct Drag {
+    kind: Option<i8>,
+    slots: Vec<usize>,
+}
+
+// Das Fenster eines Klicks als flache Slot-Liste wie beim Client: im eigenen Inventar dessen 46 Slots, sonst die
+// Container-Slots und darunter Hauptinventar und Schnellleiste
+struct View<'a> {
+    slots: Vec<ItemStack>,
+    // Anzahl der Container-Slots; 0 im eigenen Inventar
+    container: usize,
+    // Zweithand, die in einem Container-Fenster nicht zu sehen ist, aber mit F getauscht werden kann
+    off_hand: ItemStack,
+    creative: bool,
+    // Amboss mit den Stufen des Spielers; spent hält die ausgegebenen Stufen fest, used, ob an einem Arbeitsblock ein
+    // Ergebnis genommen wurde
+    anvil: Option<Anvil>,
+    levels: i32,
+    spent: u32,
+    used: bool,
+    // Die Karten, nur am Kartentisch
+    maps: Option<&'a mut MapStore>,
+    stonecutter: Option<Stonecutter>,
+    // Am Schmiedetisch die Items, die Vorlagen-, Basis- und Zusatzslot annehmen
+    smithing: Option<[Ingredient; 3]>,
+    // Der Händler mit dem gewählten Angebot und die Zahl der Handel dieses Klicks
+    merchant: Option<Merchant>,
+    trade: usize,
+    trades: u32,
+    // Die Angebote des Zaubertischs
+    enchanting: Option<Enchanting>,
+    crafting_table: bool,
+    furnace: bool,
+}
+
+impl View<'_> {
+    fn is_inventory(&self) -> bool {
+        self.container == 0
+    }
+
+    fn is_result(&self, slot: usize) -> bool {
+        (self.is_inventory() && slot == RESULT)
+            || (self.crafting_table && slot == TABLE_RESULT)
+            || (self.anvil.is_some() && slot == anvil::RESULT)
+            || (self.maps.is_some() && slot == map::TABLE_RESULT)
+            || (self.stonecutter.is_some() && slot == stonecutter::RESULT)
+            || (self.smithing.is_some() && slot == smithing::RESULT)
+            || (self.merchant.is_some() && slot == merchant::RESULT)
+    }
+
+    // Slot der Schnellleiste in dieser Ansicht
+    fn hotbar(&self, key: usize) -> usize {
+        if self.is_inventory() { HOTBAR.start + key } else { self.container + MAIN.len() + key }
+    }
+
+    // Wie viele Items des Stapels der Slot annimmt; 0 für Slots, in die er nicht darf
+    fn limit(&self, slot: usize, stack: &ItemStack) -> u8 {
+        if self.is_result(slot) {
+            return 0;
+        }
+        // Das Ofenergebnis kommt allein aus dem Schmelzen, in den Brennstoffslot dürfen nur Brennstoff und leere Eimer
+        if self.furnace && (slot == FURNACE_RESULT || (slot == FURNACE_FUEL && recipe::fuel_ticks(stack).is_none() && stack.item != "minecraft:bucket")) {
+            return 0;
+        }
+        if self.is_inventory() && ARMOR.contains(&slot) {
+            return if armor_slot(&stack.item) == Some(slot) { 1 } else { 0 };
+        }
+        if self.smithing.as_ref().and_then(|inputs| inputs.get(slot)).is_some_and(|accepted| !accepted.contains(&stack.item)) {
+            return 0;
+        }
+        if self.enchanting.is_some() && slot == enchanting::ITEM {
+            return 1;
+        }
+        if self.enchanting.is_some() && slot == enchanting::LAPIS && stack.item != enchanting::LAPIS_LAZULI {
+            return 0;
+        }
+        item::max_stack_size(&stack.item)
+    }
+}
+
+// Rüstungsslot eines Items nach seinem Namen, solange das Item-Register keine Ausrüstungsdaten kennt
+fn armor_slot(item: &str) -> Option<usize> {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+    if name.ends_with("_helmet") || name.ends_with("_head") || name.ends_with("_skull") || name == "carved_pumpkin" {
+        Some(ARMOR.start)
+    } else if name.ends_with("_chestplate") || name == "elytra" {
+        Some(ARMOR.start + 1)
+    } else if name.ends_with("_leggings") {
+        Some(ARMOR.start + 2)
+    } else if name.ends_with("_boots") {
+        Some(ARMOR.start + 3)
+    } else {
+        None
+    }
+}
+
+// Nimmt count Items von einem Stapel; ein geleerter Stapel wird zum leeren Slot
+fn split(stack: &mut ItemStack, count: u8) -> ItemStack {
+    let count = count.min(stack.count);
+    let taken = ItemStack { count, ..stack.clone() };
+    stack.count -= count;
+    if stack.count == 0 {
+        *stack = ItemStack::empty();
+    }
+    taken
+}
+
+// Legt bis zu count Items von from in den Slot (leer oder stapelbar); gibt die Zahl der verschobenen zurück
+fn put(view: &mut View, slot: usize, from: &mut ItemStack, count: u8) -> u8 {
+    let limit = view.limit(slot, from);
+    let target = &view.slots[slot];
+    if from.is_empty() || limit == 0 || !(target.is_empty() || target.stacks_with(from)) {
+        return 0;
+    }
+    let moved = count.min(from.count).min(limit.saturating_sub(target.count));
+    if moved == 0 {
+        return 0;
+    }
+    let taken = split(from, moved);
+    let target = &mut view.slots[slot];
+    if target.is_empty() {
+        *target = taken;
+    } else {
+        target.count += moved;
+    }
+    moved
+}
+
+// Verteilt einen Stapel wie Vanilla auf einen Bereich: erst auf passende Stapel, dann in freie Slots
+fn move_to(view: &mut View, stack: &mut ItemStack, range: std::ops::Range<usize>, reverse: bool) {
+    let order: Vec<usize> = if reverse { range.rev().collect() } else { range.collect() };
+    for pass in 0..2 {
+        for &slot in &order {
+            if stack.is_empty() {
+                return;
+            }
+            if (pass == 0) == view.slots[slot].is_empty() {
+                continue;
+            }
+            put(view, slot, stack, stack.count);
+        }
+    }
+}
+
+// Verbraucht je ein Item des Gitters, nachdem das Ergebnis genommen wurde
+fn consume_grid(view: &mut View) {
+    for slot in if view.crafting_table { TABLE_GRID } else { GRID } {
+        split(&mut view.slots[slot], 1);
+    }
+}
+
+// Ergebnis des Crafting-Gitters bzw. des Arbeitsblocks neu berechnen
+fn craft(view: &mut View, recipes: &RecipeRegistry) {
+    if view.is_inventory() {
+        view.slots[RESULT] = recipes.craft(&view.slots[GRID], 2).unwrap_or_else(ItemStack::empty);
+    } else if view.crafting_table {
+        view.slots[TABLE_RESULT] = recipes.craft(&view.slots[TABLE_GRID], 3).unwrap_or_else(ItemStack::empty);
+    } else if let Some(anvil) = view.anvil.as_mut() {
+        anvil.update(&mut view.slots[..anvil::SLOTS], view.creative);
+    } else if let Some(maps) = view.maps.as_deref() {
+        map::update_table(maps, &mut view.slots[..map::TABLE_SLOTS]);
+    } else if let Some(stonecutter) = view.stonecutter.as_mut() {
+        stonecutter.update(recipes, &mut view.slots[..stonecutter::SLOTS]);
+    } else if view.smithing.is_some() {
+        smithing::update(recipes, &mut view.slots[..smithing::SLOTS]);
+    } else if let Some(merchant) = view.merchant.as_ref() {
+        merchant::update(merchant, view.trade, &mut view.slots[..merchant::SLOTS]);
+    } else if let Some(enchanting) = view.enchanting.as_mut() {
+        enchanting.update(&view.slots[..enchanting::SLOTS]);
+    }
+}
+
+fn result_slot(view: &View) -> usize {
+    if view.is_inventory() {
+        RESULT
+    } else if view.crafting_table {
+        TABLE_RESULT
+    } else if view.anvil.is_some() {
+        anvil::RESULT
+    } else if view.stonecutter.is_some() {
+        stonecutter::RESULT
+    } else if view.smithing.is_some() {
+        smithing::RESULT
+    } else if view.merchant.is_some() {
+        merchant::RESULT
+    } else {
+        map::TABLE_RESULT
+    }
+}
+
+// Gezoomte und fixierte Karten bekommen erst beim Nehmen eigene Kartendaten und passen dann zu keinem Stapel
+fn needs_own_slot(view: &View) -> bool {
+    view.maps.as_deref().and_then(|maps| map::table_action(maps, &view.slots)).is_some_and(|action| action != Cartography::Clone)
+}
+
+fn finish_result(view: &mut View) {
+    if let Some(maps) = view.maps.as_deref_mut() {
+        map::finish_table(maps, &mut view.slots[..map::TABLE_SLOTS]);
+    }
+}
+
+// Beim Amboss kostet das Ergebnis Stufen, die der Spieler haben muss
+fn may_take(view: &View) -> bool {
+    match &view.anvil {
+        Some(anvil) => anvil.can_take(&view.slots, view.levels, view.creative),
+        None => !view.slots[result_slot(view)].is_empty(),
+    }
+}
+
+// Verbraucht die Eingaben eines genommenen Ergebnisses und rechnet das nächste aus
+fn consume_inputs(view: &mut View, recipes: &RecipeRegistry) {
+    match view.anvil.as_ref() {
+        Some(anvil) => {
+            anvil.consume(&mut view.slots);
+            view.spent += anvil.cost;
+            view.used = true;
+        }
+        None if view.maps.is_some() => {
+            split(&mut view.slots[map::TABLE_MAP], 1);
+            split(&mut view.slots[map::TABLE_EXTRA], 1);
+        }
+        None if view.stonecutter.is_some() => {
+            split(&mut view.slots[stonecutter::INPUT], 1);
+            view.used = true;
+        }
+        None if view.smithing.is_some() => {
+            for slot in [smithing::TEMPLATE, smithing::BASE, smithing::ADDITION] {
+                split(&mut view.slots[slot], 1);
+            }
+            view.used = true;
+        }
+        None if view.merchant.is_some() => {
+            if let Some(merchant) = view.merchant.as_mut() {
+                if merchant::take(merchant, view.trade, &mut view.slots[..merchant::SLOTS]) {
+                    view.trades += 1;
+                    view.used = true;
+                }
+            }
+        }
+        None => consume_grid(view),
+    }
+    craft(view, recipes);
+}
+
+// Nimmt das Ergebnis auf den Cursor, wenn es dort Platz hat
+fn take_result(view: &mut View, carried: &mut ItemStack, recipes: &RecipeRegistry) {
+    if !may_take(view) || (needs_own_slot(view) && !carried.is_empty()) {
+        return;
+    }
+    finish_result(view);
+    let result = view.slots[result_slot(view)].clone();
+    if carried.is_empty() {
+        *carried = result;
+    } else if carried.stacks_with(&result) && carried.count as u16 + result.count as u16 <= item::max_stack_size(&result.item) as u16 {
+        carried.count += result.count;
+    } else {
+        return;
+    }
+    consume_inputs(view, recipes);
+}
+
+fn pickup(view: &mut View, carried: &mut ItemStack, slot: usize, right: bool, recipes: &RecipeRegistry) {
+    if view.is_result(slot) {
+        return take_result(view, carried, recipes);
+    }
+    if carried.is_empty() {
+        let stack = &mut view.slots[slot];
+        let count = if right { stack.count.div_ceil(2) } else { stack.count };
+        *carried = split(stack, count);
+    } else if view.slots[slot].is_empty() || view.slots[slot].stacks_with(carried) {
+        let count = if right { 1 } else { carried.count };
+        if put(view, slot, carried, count) == 0 && view.limit(slot, carried) == 0 && view.slots[slot].stacks_with(carried) {
+            // In Slots, die nichts annehmen, sammelt ein Klick mit demselben Item auf
+            let room = item::max_stack_size(&carried.item).saturating_sub(carried.count);
+            carried.count += split(&mut view.slots[slot], room).count;
+        }
+    } else if carried.count <= view.limit(slot, carried) {
+        std::mem::swap(carried, &mut view.slots[slot]);
+    }
+}
+
+fn quick_move(view: &mut View, slot: usize, recipes: &RecipeRegistry) {
+    if view.is_result(slot) {
+        // Shift-Klick auf das Ergebnis craftet, solange das Gitter reicht und das Inventar Platz hat; der Amboss
+        // liefert sein Ergebnis nur einmal
+        let storage = if view.is_inventory() { MAIN.start..HOTBAR.end } else { view.container..view.slots.len() };
+        for _ in 0..MAX_CRAFTS {
+            if !may_take(view) || (needs_own_slot(view) && !view.slots[storage.clone()].iter().any(ItemStack::is_empty)) {
+                return;
+            }
+            finish_result(view);
+            let mut result = view.slots[slot].clone();
+            let before = view.slots.clone();
+            move_to(view, &mut result, storage.clone(), true);
+            if !result.is_empty() {
+                view.slots = before;
+                return;
+            }
+            consume_inputs(view, recipes);
+        }
+        return;
+    }
+    let mut stack = std::mem::replace(&mut view.slots[slot], ItemStack::empty());
+    if !view.is_inventory() {
+        let total = view.slots.len();
+        let main = view.container..view.container + MAIN.len();
+        if slot < view.container {
+            move_to(view, &mut stack, view.container..total, true);
+        } else if view.crafting_table {
+            // Wie im eigenen Inventar zwischen Hauptinventar und Schnellleiste, nicht ins Gitter
+            let target = if main.contains(&slot) { main.end..total } else { main };
+            move_to(view, &mut stack, target, false);
+        } else if view.furnace {
+            // Was sich schmelzen lässt, kommt in den Zutatenslot, Brennstoff in seinen
+            if recipes.smelt(&stack).is_some() {
+                move_to(view, &mut stack, FURNACE_INPUT..FURNACE_INPUT + 1, false);
+            } else {
+                move_to(view, &mut stack, FURNACE_FUEL..FURNACE_FUEL + 1, false);
+            }
+            let target = if main.contains(&slot) { main.end..total } else { main };
+            move_to(view, &mut stack, target, false);
+        } else {
+            move_to(view, &mut stack, 0..view.container, false);
+        }
+    } else if GRID.contains(&slot) || ARMOR.contains(&slot) || slot == OFF_HAND {
+        move_to(view, &mut stack, MAIN.start..HOTBAR.end, false);
+    } else {
+        if let Some(armor) = armor_slot(&stack.item).filter(|&armor| view.slots[armor].is_empty()) {
+            move_to(view, &mut stack, armor..armor + 1, false);
+        }
+        if MAIN.contains(&slot) {
+            move_to(view, &mut stack, HOTBAR, false);
+        } else {
+            move_to(view, &mut stack, MAIN, false);
+        }
+    }
+    // Was nicht unterkommt, bleibt im Ausgangsslot
+    if !stack.is_empty() {
+        if view.slots[slot].is_empty() {
+            view.slots[slot] = stack;
+        } else {
+            view.slots[slot].count += stack.count;
+        }
+    }
+}
+
+fn swap(view: &mut View, slot: usize, key: i8) {
+    if view.is_result(slot) {
+        return;
+    }
+    let other = if key == SWAP_OFF_HAND {
+        if view.is_inventory() {
+            OFF_HAND
+        } else {
+            let stack = view.slots[slot].clone();
+            if !view.off_hand.is_empty() && view.off_hand.count > view.limit(slot, &view.off_hand) {
+                return;
+            }
+            view.slots[slot] = std::mem::replace(&mut view.off_hand, stack);
+            return;
+        }
+    } else if (0..9).contains(&key) {
+        view.hotbar(key as usize)
+    } else {
+        return;
+    };
+    let (a, b) = (view.slots[slot].clone(), view.slots[other].clone());
+    let fits = |view: &View, slot: usize, stack: &ItemStack| stack.is_empty() || stack.count <= view.limit(slot, stack);
+    if fits(view, slot, &b) && fits(view, other, &a) {
+        view.slots.swap(slot, other);
+    }
+}
+
+// Am Ende des Ziehens: links gleichmäßig verteilen, rechts je eins, mittel (Kreativmodus) volle Stapel
+fn finish_drag(view: &mut View, carried: &mut ItemStack, drag: &Drag, creative: bool) {
+    let (Some(kind), false) = (drag.kind, drag.slots.is_empty()) else { return };
+    if kind == 2 && !creative {
+        return;
+    }
+    let share = match kind {
+        0 => (carried.count as usize / drag.slots.len()).max(1) as u8,
+        1 => 1,
+        _ => item::max_stack_size(&carried.item),
+    };
+    for &slot in &drag.slots {
+        if carried.is_empty() {
+            break;
+        }
+        if kind == 2 {
+            let mut copy = ItemStack { count: share, ..carried.clone() };
+            put(view, slot, &mut copy, share);
+        } else {
+            put(view, slot, carried, share);
+        }
+    }
+}
+
+fn pickup_all(view: &mut View, carried: &mut ItemStack) {
+    if carried.is_empty() {
+        return;
+    }
+    let max = item::max_stack_size(&carried.item);
+    // Erst angebrochene, dann volle Stapel
+    for pass in 0..2 {
+        for slot in 0..view.slots.len() {
+            if carried.count >= max {
+                return;
+            }
+            let stack = &view.slots[slot];
+            if view.is_result(slot) || !stack.stacks_with(carried) || stack.is_empty() || (pass == 0 && stack.count >= max) {
+                continue;
+            }
+            let taken = split(&mut view.slots[slot], max - carried.count);
+            carried.count += taken.count;
+        }
+    }
+}
+
+// Führt einen Klick aus; geworfene Items landen in drops
+#[allow(clippy::too_many_arguments)]
+fn apply(view: &mut View, carried: &mut ItemStack, drag: &mut Drag, slot: i16, button: i8, mode: i32, recipes: &RecipeRegistry, drops: &mut Vec<ItemStack>) {
+    let creative = view.creative;
+    let index = usize::try_from(slot).ok().filter(|&s| s < view.slots.len());
+    if mode != MODE_QUICK_CRAFT {
+        *drag = Drag::default();
+    }
+    match (mode, index) {
+        (MODE_PICKUP, None) if slot == OUTSIDE && !carried.is_empty() => {
+            let count = if button == 1 { 1 } else { carried.count };
+            drops.push(split(carried, count));
+        }
+        (MODE_PICKUP, Some(slot)) => pickup(view, carried, slot, button == 1, recipes),
+        (MODE_QUICK_MOVE, Some(slot)) => quick_move(view, slot, recipes),
+        (MODE_SWAP, Some(slot)) => swap(view, slot, button),
+        (MODE_CLONE, Some(slot)) if creative && carried.is_empty() && !view.slots[slot].is_empty() => {
+            let stack = &view.slots[slot];
+            *carried = ItemStack { count: item::max_stack_size(&stack.item), ..stack.clone() };
+        }
+        (MODE_THROW, Some(slot)) if carried.is_empty() && !view.is_result(slot) => {
+            let count = if button == 1 { view.slots[slot].count } else { 1 };
+            let thrown = split(&mut view.slots[slot], count);
+            if !thrown.is_empty() {
+                drops.push(thrown);
+            }
+        }
+        (MODE_QUICK_CRAFT, _) => match button {
+            0 | 4 | 8 => *drag = Drag { kind: Some(button / 4), slots: Vec::new() },
+            1 | 5 | 9 => {
+                let Some(slot) = index else { return };
+                let accepts = !carried.is_empty() && view.limit(slot, carried) > 0 && (view.slots[slot].is_empty() || view.slots[slot].stacks_with(carried));
+                if drag.kind == Some(button / 4) && accepts && !drag.slots.contains(&slot) {
+                    drag.slots.push(slot);
+                }
+            }
+            2 | 6 | 10 => {
+                let finished = std::mem::take(drag);
+                if finished.kind == Some(button / 4) {
+                    finish_drag(view, carried, &finished, creative);
+                }
+            }
+            _ => *drag = Drag::default(),
+        },
+        (MODE_PICKUP_ALL, Some(_)) => pickup_all(view, carried),
+        _ => {}
+    }
+    craft(view, recipes);
+}
+
+// Was der Client nach seinem Klick in einem Slot zu sehen glaubt: Protokoll-ID und Anzahl. None, wenn der Stapel
+// Komponenten bzw. NBT trägt, die der Server nicht zurückliest; dann lässt sich auch der Rest nicht mehr lesen
+fn read_claimed(cursor: &mut std::io::Cursor<Vec<u8>>, version: ProtocolVersion) -> std::io::Result<Option<(i32, u8)>> {
+    if version.has_1_20_5_login() {
+        let count = read_varint_from_cursor(cursor)?;
+        if count <= 0 {
+            return Ok(Some((0, 0)));
+        }
+        let id = read_varint_from_cursor(cursor)?;
+        let (added, removed) = (read_varint_from_cursor(cursor)?, read_varint_from_cursor(cursor)?);
+        Ok((added == 0 && removed == 0).then_some((id, count.min(u8::MAX as i32) as u8)))
+    } else {
+        if cursor.read_u8()? == 0 {
+            return Ok(Some((0, 0)));
+        }
+        let id = read_varint_from_cursor(cursor)?;
+        let count = cursor.read_i8()?;
+        let nbt = cursor.read_u8()?;
+        Ok((nbt == 0).then_some((id, count.max(0) as u8)))
+    }
+}
+
+// Wie der Client einen Stapel des Servers sieht, zum Vergleich mit read_claimed
+fn as_claim(stack: &ItemStack, version: ProtocolVersion) -> Option<(i32, u8)> {
+    if stack.is_empty() {
+        return Some((0, 0));
+    }
+    stack.components.is_empty().then(|| (item::protocol_id_in(&stack.item, version).unwrap_or(0), stack.count))
+}
+
+// Set Container Slot mit Fenster -1 und Slot -1: das Item am Cursor
+fn send_carried(connection: &Connection, state_id: i32, carried: &ItemStack) -> Result<(), String> {
+    let mut packet_data = vec![0xFF];
+    packet_data.extend(write_varint_to_vec(state_id));
+    packet_data.extend((-1i16).to_be_bytes());
+    packet_data.extend(carried.encode(connection.version));
+    connection.send(clientbound::SET_CONTAINER_SLOT, &packet_data)
+}
+
+fn build_view<'a>(player: &Player, world: &World, maps: Option<&'a mut MapStore>, recipes: &RecipeRegistry) -> View<'a> {
+    let mut view = View {
+        slots: player.inventory.slots().to_vec(),
+        container: 0,
+        off_hand: ItemStack::empty(),
+        creative: player.game_mode == GameMode::Creative,
+        anvil: None,
+        levels: player.experience.level,
+        spent: 0,
+        used: false,
+        maps: None,
+        stonecutter: None,
+        smithing: None,
+        merchant: None,
+        trade: 0,
+        trades: 0,
+        enchanting: None,
+        crafting_table: false,
+        furnace: false,
+    };
+    if let Some(window) = &player.window {
+        let mut slots = window.contents(world, player);
+        view.container = slots.len();
+        slots.extend(player.inventory.storage().iter().cloned());
+        view.slots = slots;
+        view.off_hand = player.inventory.slot(OFF_HAND).clone();
+        view.anvil = window.anvil.clone();
+        view.maps = maps.filter(|_| matches!(window.container, Container::CartographyTable(_)));
+        view.stonecutter = window.stonecutter.clone();
+        view.enchanting = window.enchanting.clone();
+        view.smithing = matches!(window.container, Container::SmithingTable(_)).then(|| recipes.smithing_inputs());
+        view.crafting_table = matches!(window.container, Container::CraftingTable(_));
+        view.furnace = matches!(window.container, Container::Furnace(_));
+        if let Container::Merchant(entity_id) = window.container {
+            view.merchant = world.mobs.iter().find(|m| m.entity_id == entity_id).and_then(|mob| mob.merchant.clone());
+            view.trade = window.trade;
+        }
+    }
+    view
+}
+
+// Schreibt die Ansicht zurück: Container-Slots in die Block-Entities bzw. die Endertruhe, den Rest ins Inventar
+fn store_view(player: &mut Player, world: &mut World, view: View) {
+    if view.is_inventory() {
+        for (slot, stack) in view.slots.into_iter().enumerate() {
+            player.inventory.set(slot, stack);
+        }
+        return;
+    }
+    let mut slots = view.slots;
+    let storage = slots.split_off(view.container);
+    if let Some(window) = player.window.as_mut() {
+        window.store(world, &mut player.ender_chest, &slots);
+        window.anvil = view.anvil;
+        window.stonecutter = view.stonecutter;
+        window.enchanting = view.enchanting;
+        if let (Container::Merchant(entity_id), Some(merchant)) = (&window.container, view.merchant) {
+            if let Some(mob) = world.mobs.iter_mut().find(|m| m.entity_id == *entity_id) {
+                mob.merchant = Some(merchant);
+            }
+        }
+    }
+    for (i, stack) in storage.into_iter().enumerate() {
+        player.inventory.set(MAIN.start + i, stack);
+    }
+    player.inventory.set(OFF_HAND, view.off_hand);
+}
+
+// Click Container: der Server führt den Klick selbst aus und vergleicht das Ergebnis mit dem, was der Client
+// vorhergesagt hat. Bei veralteter State-ID, abweichender Vorhersage oder nicht lesbaren Stapeln bekommt der Client
+// das ganze Fenster neu, sonst nur die Slots, in denen er sich verschätzt hat
+pub fn handle_click(server: &Server, players: &mut [Player], world: &mut World, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let window_id = cursor.read_u8()?;
+    let state_id = read_varint_from_cursor(cursor)?;
+    let slot = cursor.read_i16::<BigEndian>()?;
+    let button = cursor.read_i8()?;
+    let mode = read_varint_from_cursor(cursor)?;
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid) else { return Ok(()) };
+    let p = &players[index];
+    let open = p.window.as_ref().map_or(0, |w| w.id);
+    if window_id != open || p.window.as_ref().is_some_and(|w| w.dimension != world.dimension) || p.game_mode == GameMode::Spectator {
+        debug!("Klick von {} in Fenster {} verworfen, offen ist {}", p.username, window_id, open);
+        return Ok(());
+    }
+    let current_state = p.window.as_ref().map_or(p.inventory.state_id(), |w| w.state_id);
+    let mut maps = p.window.as_ref().filter(|w| matches!(w.container, Container::CartographyTable(_))).map(|_| server.maps.lock().unwrap());
+    let datapacks = server.datapacks.lock().unwrap();
+    let mut view = build_view(p, world, maps.as_deref_mut(), &datapacks.registries.recipes);
+    let before = view.slots.clone();
+    // Die Vorhersage des Clients: geänderte Slots und das Item am Cursor
+    let mut claimed: Vec<(usize, Option<(i32, u8)>)> = Vec::new();
+    let mut readable = true;
+    let count = read_varint_from_cursor(cursor)?.clamp(0, 128);
+    for _ in 0..count {
+        let changed = cursor.read_i16::<BigEndian>()?;
+        match read_claimed(cursor, p.connection.version) {
+            Ok(Some(claim)) => claimed.push((changed as usize, Some(claim))),
+            Ok(None) => {
+                readable = false;
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let claimed_carried = if readable { read_claimed(cursor, p.connection.version)? } else { None };
+
+    let version = p.connection.version;
+    let mut carried = p.carried.clone();
+    let mut drag = p.drag.clone();
+    let mut drops = Vec::new();
+    apply(&mut view, &mut carried, &mut drag, slot, button, mode, &datapacks.registries.recipes, &mut drops);
+    drop(datapacks);
+    let (spent, used, creative, trades) = (view.spent, view.used, view.creative, view.trades);
+    let cost = view.anvil.as_ref().map(|anvil| anvil.cost);
+    let enchanting = view.enchanting.clone();
+
+    let mut expected = before.clone();
+    let mut mismatched = Vec::new();
+    for (changed, claim) in &claimed {
+        if *changed < expected.len() && as_claim(&view.slots[*changed], version) != *claim {
+            mismatched.push(*changed);
+        }
+        if *changed >= expected.len() {
+            readable = false;
+        } else {
+            expected[*changed] = view.slots[*changed].clone();
+        }
+    }
+    // Slots, die der Server geändert hat, der Client aber nicht erwähnt (etwa das Crafting-Ergebnis)
+    mismatched.extend((0..view.slots.len()).filter(|&i| expected[i] != view.slots[i] && !claimed.iter().any(|(c, _)| *c == i)));
+    let carried_matches = claimed_carried.is_some() && as_claim(&carried, version) == claimed_carried;
+    let resync = !readable || state_id != current_state;
+
+    let p = &mut players[index];
+    p.carried = carried;
+    p.drag = drag;
+    let (slots, container) = (view.slots.clone(), view.container);
+    store_view(p, world, view);
+    drop(maps);
+    if let Some(window) = p.window.as_mut() {
+        window.set_sent(slots[..container].to_vec());
+    }
+    if resync {
+        debug!("Fenster von {} nicht synchron, schicke es neu", p.username);
+        resend(p);
+    } else {
+        for slot in mismatched {
+            let _ = match &p.window {
+                Some(window) => window::send_slot(&p.connection, window, slot, &slots[slot]),
+                None => p.inventory.send_slot(&p.connection, slot),
+            };
+        }
+        if !carried_matches {
+            let _ = send_carried(&p.connection, current_state, &p.carried);
+        }
+    }
+    if let (Some(window), Some(cost)) = (&p.window, cost) {
+        let _ = anvil::send_cost(&p.connection, window.id, cost);
+    }
+    if let (Some(window), Some(enchanting)) = (&p.window, enchanting) {
+        let _ = enchanting.send(&p.connection, window.id);
+    }
+    if spent > 0 && !creative {
+        p.experience.set_level(p.experience.level - spent as i32);
+        let _ = experience::send(&p.connection, &p.experience);
+    }
+    let (x, y, z) = p.position;
+    let position = p.window.as_ref().map(|window| window.container.clone());
+    for stack in drops {
+        entity::spawn_item(players, world, stack, (x, y + THROW_HEIGHT, z));
+    }
+    match position.filter(|_| used) {
+        Some(Container::Anvil(position)) => anvil::damage(world, position, creative),
+        Some(Container::Stonecutter(position)) => farming::play_sound(players, world, stonecutter::TAKE_SOUND, position),
+        Some(Container::SmithingTable(position)) => world.play_event(WorldEvent::SmithingTableUsed, position, None),
+        Some(Container::Merchant(_)) => merchant::traded(players, world, index, trades),
+        _ => {}
+    }
+    Ok(())
+}
+
+// Nimmt für jeden Gitterplatz crafts gleiche Items einer passenden Sorte aus dem Inventar; None, wenn es für einen
+// Platz nicht reicht
+fn take_ingredients(slots: &mut [ItemStack], layout: &[(usize, &Ingredient)], crafts: u8) -> Option<Vec<(usize, ItemStack)>> {
+    let mut taken = Vec::new();
+    for &(slot, ingredient) in layout {
+        let storage = MAIN.start..HOTBAR.end;
+        let source = storage.clone().find(|&i| {
+            let stack = &slots[i];
+            ingredient.contains(&stack.item)
+                && item::max_stack_size(&stack.item) >= crafts
+                && slots[storage.clone()].iter().filter(|other| other.stacks_with(stack)).map(|other| other.count as u32).sum::<u32>() >= crafts as u32
+        })?;
+        let kind = slots[source].clone();
+        let mut stack = ItemStack { count: 0, ..kind.clone() };
+        for i in storage {
+            if stack.count < crafts && slots[i].stacks_with(&kind) {
+                stack.count += split(&mut slots[i], crafts - stack.count).count;
+            }
+        }
+        taken.push((GRID.start + slot, stack));
+    }
+    Some(taken)
+}
+
+// Place Recipe aus dem Rezeptbuch: räumt das 2x2-Gitter ins Inventar und legt die Zutaten für einen Durchgang mehr
+// hinein als bisher, mit make_all für so viele, wie Zutaten und Stapelgrößen hergeben. false, wenn schon für einen
+// Durchgang Zutaten fehlen; das Gitter bleibt dann leer. Passt das Gitter nicht mehr ins Inventar, bleibt alles, wie
+// es ist
+pub fn place_recipe(player: &mut Player, recipes: &RecipeRegistry, recipe: &Recipe, make_all: bool) -> bool {
+    let Some(layout) = recipe.layout(2) else { return false };
+    let mut view = View {
+        slots: player.inventory.slots().to_vec(),
+        container: 0,
+        off_hand: ItemStack::empty(),
+        creative: player.game_mode == GameMode::Creative,
+        anvil: None,
+        levels: 0,
+        spent: 0,
+        used: false,
+        maps: None,
+        stonecutter: None,
+        smithing: None,
+        merchant: None,
+        trade: 0,
+        trades: 0,
+        enchanting: None,
+        crafting_table: false,
+        furnace: false,
+    };
+    let placed = if view.slots[RESULT].stacks_with(recipe.result()) { view.slots[GRID].iter().filter(|s| !s.is_empty()).map(|s| s.count).min().unwrap_or(0) } else { 0 };
+    for slot in GRID {
+        let mut stack = std::mem::replace(&mut view.slots[slot], ItemStack::empty());
+        move_to(&mut view, &mut stack, MAIN.start..HOTBAR.end, false);
+        if !stack.is_empty() {
+            return true;
+        }
+    }
+    let wanted = if make_all { MAX_CRAFTS as u8 } else { placed.saturating_add(1) };
+    let mut found = None;
+    for crafts in (1..=wanted).rev() {
+        let mut attempt = view.slots.clone();
+        if let Some(taken) = take_ingredients(&mut attempt, &layout, crafts) {
+            found = Some((attempt, taken));
+            break;
+        }
+    }
+    let success = found.is_some();
+    if let Some((slots, taken)) = found {
+        view.slots = slots;
+        for (slot, stack) in taken {
+            view.slots[slot] = stack;
+        }
+    }
+    craft(&mut view, recipes);
+    for (slot, stack) in view.slots.into_iter().enumerate() {
+        player.inventory.set(slot, stack);
+    }
+    let _ = player.inventory.send_all(&player.connection);
+    success
+}
+
+// Schickt das offene Fenster samt Cursor vollständig neu
+fn resend(player: &mut Player) {
+    match player.window.take() {
+        Some(mut window) => {
+            let _ = window::send_content(&player.connection, &mut window, player);
+            player.window = Some(window);
+        }
+        None => {
+            let _ = player.inventory.send_all(&player.connection);
+            let _ = send_carried(&player.connection, player.inventory.state_id(), &player.carried);
+        }
+    }
+}
+
+// Beim Schließen eines Fensters: Cursor, 2x2-Gitter bzw. die Eingaben eines Arbeitsblocks zurück ins Inventar; was nicht
+// passt, fällt vor den Spieler. window ist None für das eigene Inventar
+pub fn return_items(players: &mut [Player], world: &mut World, index: usize, window: Option<Window>) {
+    let player = &mut players[index];
+    let mut returned = vec![std::mem::replace(&mut player.carried, ItemStack::empty())];
+    player.drag = Drag::default();
+    let inventory_window = window.is_none();
+    // Alles außer dem Ergebnis, das wie im eigenen Inventar an der Werkbank vorne und sonst im letzten Slot liegt; der
+    // Zaubertisch hat keins
+    if let Some(window) = window.filter(|window| window.container.is_workstation()) {
+        let inputs = match window.container {
+            Container::CraftingTable(_) => TABLE_GRID,
+            _ if window.enchanting.is_some() => 0..window.slots.len(),
+            _ => 0..window.slots.len().saturating_sub(1),
+        };
+        returned.extend(window.slots[inputs].iter().cloned());
+    }
+    if inventory_window {
+        for slot in GRID {
+            returned.push(player.inventory.slot(slot).clone());
+            player.inventory.set(slot, ItemStack::empty());
+        }
+        player.inventory.set(RESULT, ItemStack::empty());
+    }
+    let mut drops = Vec::new();
+    for stack in returned.into_iter().filter(|stack| !stack.is_empty()) {
+        let (changed, rest) = player.inventory.add(stack);
+        for slot in changed {
+            let _ = player.inventory.send_slot(&player.connection, slot);
+        }
+        if !rest.is_empty() {
+            drops.push(rest);
+        }
+    }
+    if inventory_window {
+        for slot in GRID.chain([RESULT]) {
+            let _ = player.inventory.send_slot(&player.connection, slot);
+        }
+    }
+    let (x, y, z) = player.position;
+    for stack in drops {
+        entity::spawn_item(players, world, stack, (x, y + THROW_HEIGHT, z));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::damage::{self, DamageSource, Hit};
+use crate::error::ServerError;
+use crate::selector::Target;
+use crate::violation::{self, Violations};
+use crate::protocol::types::read_varint_from_cursor;
+use crate::{animation, block, dimension, effect, entity, hunger, merchant, motion, vehicle, Player, Server, World};
+
+const INTERACT: i32 = 0;
+const ATTACK: i32 = 1;
+const EYE_HEIGHT: f64 = 1.62;
+// Vanilla erlaubt drei Blöcke vom Auge bis zur Hitbox, der Rest gleicht Verzögerung und Bewegung aus
+const MAX_REACH: f64 = 3.5;
+// Winkel zwischen Blickrichtung und Mitte des Ziels, zuzüglich der Ausdehnung der Hitbox
+const MAX_ANGLE_DEGREES: f64 = 60.0;
+// Mehr Schläge pro Sekunde schafft niemand mit der Maus
+const MAX_ATTACKS_PER_SECOND: usize = 15;
+// Schrittweite für die Sichtlinie vom Auge zum Ziel
+const RAY_STEP: f64 = 0.1;
+// Jeder gültige Schlag baut Verstöße ab
+const VIOLATION_DECAY: f64 = 0.25;
+const FIST_DAMAGE: f32 = 1.0;
+const CRITICAL_MULTIPLIER: f32 = 1.5;
+const KNOCKBACK: &str = "minecraft:knockback";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitViolation {
+    Reach,
+    ThroughBlocks,
+    Angle,
+    Rate,
+}
+
+impl HitViolation {
+    fn describe(self) -> &'static str {
+        match self {
+            HitViolation::Reach => "attacked from too far away",
+            HitViolation::ThroughBlocks => "attacked through blocks",
+            HitViolation::Angle => "attacked outside their view",
+            HitViolation::Rate => "attacked too often",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CombatState {
+    // Zeitpunkte der Schläge der letzten Sekunde
+    attacks: VecDeque<Instant>,
+    violations: Violations,
+}
+
+type Vec3 = (f64, f64, f64);
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    (a.0 - b.0, a.1 - b.1, a.2 - b.2)
+}
+
+fn length(v: Vec3) -> f64 {
+    (v.0 * v.0 + v.1 * v.1 + v.2 * v.2).sqrt()
+}
+
+// Blickrichtung aus Yaw und Pitch in Grad, wie beim Client
+fn look_direction((yaw, pitch): (f32, f32)) -> Vec3 {
+    let (yaw, pitch) = ((yaw as f64).to_radians(), (pitch as f64).to_radians());
+    (-yaw.sin() * pitch.cos(), -pitch.sin(), yaw.cos() * pitch.cos())
+}
+
+// Ob zwischen from und to kein fester Block liegt; der Zielpunkt selbst zählt nicht mit
+fn visible(world: &World, from: Vec3, to: Vec3) -> bool {
+    let delta = sub(to, from);
+    let steps = (length(delta) / RAY_STEP) as u32;
+    (1..steps).all(|i| {
+        let t = i as f64 / steps as f64;
+        let (x, y, z) = (from.0 + delta.0 * t, from.1 + delta.1 * t, from.2 + delta.2 * t);
+        !block::is_solid(world.get_block(x.floor() as i32, y.floor() as i32, z.floor() as i32))
+    })
+}
+
+// Prüft Schlagrate, Reichweite, Blickwinkel und Sichtlinie eines Angriffs
+fn check(world: &World, attacker: &mut Player, target: &Target) -> Result<(), HitViolation> {
+    let now = Instant::now();
+    let attacks = &mut attacker.combat.attacks;
+    while attacks.front().is_some_and(|time| now.duration_since(*time) > Duration::from_secs(1)) {
+        attacks.pop_front();
+    }
+    attacks.push_back(now);
+    if attacks.len() > MAX_ATTACKS_PER_SECOND {
+        return Err(HitViolation::Rate);
+    }
+    let eye = (attacker.position.0, attacker.position.1 + EYE_HEIGHT, attacker.position.2);
+    let (width, height) = entity::hitbox(&target.kind);
+    let (x, y, z) = target.position;
+    let closest = (eye.0.clamp(x - width / 2.0, x + width / 2.0), eye.1.clamp(y, y + height), eye.2.clamp(z - width / 2.0, z + width / 2.0));
+    if length(sub(closest, eye)) > MAX_REACH {
+        return Err(HitViolation::Reach);
+    }
+    let center = (x, y + height / 2.0, z);
+    let to_center = sub(center, eye);
+    let distance = length(to_center);
+    // Steht der Angreifer in der Hitbox, gibt es keinen sinnvollen Winkel
+    if distance > width.max(height) / 2.0 {
+        let look = look_direction(attacker.rotation);
+        let cos = (look.0 * to_center.0 + look.1 * to_center.1 + look.2 * to_center.2) / distance;
+        let spread = (width.max(height) / 2.0 / distance).atan().to_degrees();
+        if cos.clamp(-1.0, 1.0).acos().to_degrees() > MAX_ANGLE_DEGREES + spread {
+            return Err(HitViolation::Angle);
+        }
+    }
+    // Ein Teil des Ziels genügt, etwa der Kopf über einer Mauer
+    if ![closest, center, (x, y + height, z)].into_iter().any(|point| visible(world, eye, point)) {
+        return Err(HitViolation::ThroughBlocks);
+    }
+    attacker.combat.violations.decay(VIOLATION_DECAY);
+    Ok(())
+}
+
+// Interact: Angriffe auf Mobs und andere Spieler derselben Welt, unmögliche Schläge werden verworfen; ein Rechtsklick
+// öffnet den Handel mit einem Händler oder kann auf ein Fahrzeug setzen. Wird ohne gehaltene Sperren aufgerufen, weil Schaden und Rückstoß sie selbst nehmen
+pub fn handle_interact(server: &Server, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let entity_id = read_varint_from_cursor(cursor)?;
+    let kind = read_varint_from_cursor(cursor)?;
+    if kind == INTERACT {
+        if merchant::open(server, player, entity_id) {
+            return Ok(());
+        }
+        return vehicle::mount(server, player, entity_id, cursor);
+    }
+    if kind != ATTACK {
+        return Ok(());
+    }
+    let mut players = server.players.lock().unwrap();
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid && p.health > 0.0 && p.game_mode.can_attack()) else { return Ok(()) };
+    let dimension = players[index].dimension;
+    let world = dimension::world_of(server, dimension).lock().unwrap();
+    let target = match world.mobs.iter().find(|m| m.entity_id == entity_id) {
+        Some(mob) => Target::of_mob(mob, dimension),
+        None => {
+            let victim = players.iter().find(|p| p.entity_id == entity_id && p.uuid != player.uuid && p.dimension == dimension && p.game_mode.is_tracked());
+            let Some(victim) = victim else { return Ok(()) };
+            Target::of_player(victim)
+        }
+    };
+    let result = check(&world, &mut players[index], &target);
+    drop(world);
+    let attacker = &mut players[index];
+    if let Err(hit) = result {
+        let alert = attacker.combat.violations.add();
+        let level = attacker.combat.violations.level();
+        debug!("Schlag von {} auf {} verworfen: {} ({:.1} Verstöße)", attacker.username, target.name, hit.describe(), level);
+        if alert {
+            let suspect = attacker.username.clone();
+            violation::alert(server, &players, &suspect, hit.describe(), level);
+        }
+        return Ok(());
+    }
+    // Kritisch wie bei Vanilla nur im Fall und nicht blind
+    let critical = attacker.movement.is_falling(attacker.position.1) && attacker.effects.get(effect::BLINDNESS).is_none();
+    let amount = (FIST_DAMAGE + attacker.effects.attack_bonus()).max(0.0) * if critical { CRITICAL_MULTIPLIER } else { 1.0 };
+    let knockback = attacker.inventory.main_hand().components.enchantments.get(KNOCKBACK).copied().unwrap_or(0);
+    let source = attacker.position;
+    if !attacker.game_mode.is_invulnerable() {
+        attacker.hunger.exhaust(hunger::ATTACK_EXHAUSTION);
+    }
+    drop(players);
+    let hit = damage::damage_from(server, &target, DamageSource::PlayerAttack, amount, source);
+    if critical && hit != Hit::Ignored {
+        let players = server.players.lock().unwrap();
+        for viewer in players.iter().filter(|p| p.uuid != target.uuid && entity::in_view(p, dimension, target.position)) {
+            let _ = animation::send(&viewer.connection, entity_id, animation::CRITICAL_HIT);
+        }
+    }
+    if hit != Hit::Ignored {
+        let players = server.players.lock().unwrap();
+        let mut scoreboard = server.scoreboard.lock().unwrap();
+        scoreboard.award(&players, &player.username, &["minecraft.custom:minecraft.damage_dealt"], (amount * 10.0).round() as i32);
+        if hit == Hit::Killed {
+            scoreboard.killed(&players, &player.username, &target);
+        }
+    }
+    if hit == Hit::Hurt {
+        motion::knockback(server, &target, source, motion::attack_strength(knockback));
+    }
+    Ok(())
+}
@@ -0,0 +1,2271 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use crate::selector::{self, Target};
+use crate::item::{self, ItemStack};
+use crate::text::{ClickEvent, Text};
+use crate::damage::{self, DamageSource};
+use crate::difficulty::Difficulty;
+use crate::dimension::{self, Dimension, DimensionType};
+use crate::gamemode::{self, GameMode};
+use crate::schematic::{self, Schematic};
+use crate::scoreboard::Objective;
+use crate::structure::{self, Mirror, Placement, Rotation};
+use crate::worldgen::Generator;
+use crate::worldsettings::WorldSettings;
+use crate::protocol::Direction;
+use crate::{block, chunk, edit, effect, entity, entitytag, experience, favicon, function, gamerule, metrics, multiworld, permission, portal, pregen, recipebook, restart, scoreboard, storage, tnt, worldborder, worldgen};
+use crate::{apply_block_changes, kick_player, send_localized, send_system_component, send_system_message, teleport_mob, teleport_player, Mob, Server, SpawnPoint, World, KICK_REASON};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandSender {
+    Console,
+    Player(Uuid),
+    // Befehle über die REST-API; die Nummer ordnet die Antworten der wartenden Anfrage zu
+    Remote(u64),
+    // Funktionen aus #minecraft:tick und #minecraft:load; Antworten werden verworfen
+    Server,
+}
+
+impl CommandSender {
+    pub fn name(self, server: &Server) -> String {
+        match self {
+            CommandSender::Console => "CONSOLE".to_string(),
+            CommandSender::Remote(_) => "REST".to_string(),
+            CommandSender::Server => "Server".to_string(),
+            CommandSender::Player(uuid) => {
+                let players = server.players.lock().unwrap();
+                players.iter().find(|p| p.uuid == uuid).map(|p| p.username.clone()).unwrap_or_default()
+            }
+        }
+    }
+
+    // Auch für Antworten, die erst nach dem Befehl kommen; ist der Spieler offline oder die REST-Anfrage schon beendet,
+    // geht sie verloren
+    pub fn reply(self, server: &Server, message: &str) {
+        match self {
+            CommandSender::Console => info!("{}", message),
+            CommandSender::Remote(id) => server.remote_replies.send(id, message),
+            CommandSender::Server => debug!("{}", message),
+            CommandSender::Player(uuid) => {
+                let players = server.players.lock().unwrap();
+                if let Some(player) = players.iter().find(|p| p.uuid == uuid) {
+                    let _ = send_system_message(&player.connection, message);
+                }
+            }
+        }
+    }
+}
+
+// Leitet Antworten an entfernte Absender weiter. Ist der Befehl fertig, wird der Sender verworfen,
+// sodass der Empfänger das Ende erkennt
+#[derive(Default)]
+pub struct RemoteReplies {
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, Sender<String>>>,
+}
+
+impl RemoteReplies {
+    pub fn new() -> RemoteReplies {
+        RemoteReplies::default()
+    }
+
+    pub fn open(&self) -> (CommandSender, Receiver<String>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let (sender, receiver) = mpsc::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+        (CommandSender::Remote(id), receiver)
+    }
+
+    fn send(&self, id: u64, message: &str) {
+        if let Some(sender) = self.pending.lock().unwrap().get(&id) {
+            let _ = sender.send(message.to_string());
+        }
+    }
+
+    pub fn finish(&self, id: u64) {
+        self.pending.lock().unwrap().remove(&id);
+    }
+}
+
+// Reiht den Befehl für den Tick-Thread ein und wartet höchstens `timeout` auf sein Ende.
+// Liefert die gesammelten Antworten und ob der Befehl rechtzeitig fertig wurde
+pub fn run_remote(server: &Server, line: &str, timeout: Duration) -> (Vec<String>, bool) {
+    let (sender, replies) = server.remote_replies.open();
+    server.pending_commands.lock().unwrap().push((sender, line.to_string()));
+    let deadline = Instant::now() + timeout;
+    let mut output = Vec::new();
+    loop {
+        match replies.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(message) => output.push(message),
+            Err(RecvTimeoutError::Disconnected) => return (output, true),
+            Err(RecvTimeoutError::Timeout) => return (output, false),
+        }
+    }
+}
+
+pub struct CommandContext<'a> {
+    pub server: &'a Server,
+    pub sender: CommandSender,
+    // Entity hinter @s; der ausführende Spieler oder mit /execute as gesetzt
+    pub entity: Option<Uuid>,
+    // Bezugspunkt für ~ und Selektoren, von /execute at und positioned überschrieben;
+    // sonst die Position von entity bzw. bei der Konsole 0 0 0
+    pub position: Option<(f64, f64, f64)>,
+    pub rotation: Option<(f32, f32)>,
+    // Mit /execute in gesetzt; Teleports dorthin wechseln die Dimension
+    pub dimension: Option<Dimension>,
+    // Verschachtelungstiefe von Funktionsaufrufen
+    pub depth: usize,
+}
+
+impl<'a> CommandContext<'a> {
+    pub fn new(server: &'a Server, sender: CommandSender) -> CommandContext<'a> {
+        let entity = match sender {
+            CommandSender::Player(uuid) => Some(uuid),
+            _ => None,
+        };
+        CommandContext { server, sender, entity, position: None, rotation: None, dimension: None, depth: 0 }
+    }
+
+    // Antworten und Rechte bleiben beim ursprünglichen Absender
+    fn with(&self, entity: Option<Uuid>, position: Option<(f64, f64, f64)>, rotation: Option<(f32, f32)>) -> CommandContext<'a> {
+        CommandContext { server: self.server, sender: self.sender, entity, position, rotation, dimension: self.dimension, depth: self.depth }
+    }
+
+    // Gleicher Kontext eine Funktionsebene tiefer
+    pub fn nested(&self) -> CommandContext<'a> {
+        CommandContext { depth: self.depth + 1, ..self.with(self.entity, self.position, self.rotation) }
+    }
+
+    pub fn origin(&self) -> (f64, f64, f64) {
+        self.position.unwrap_or_else(|| selector::executor(self).map_or((0.0, 0.0, 0.0), |t| t.position))
+    }
+
+    pub fn facing(&self) -> (f32, f32) {
+        self.rotation.unwrap_or_else(|| selector::executor(self).map_or((0.0, 0.0), |t| t.rotation))
+    }
+
+    // Dimension aus /execute in, sonst die des ausführenden Spielers
+    pub fn world_dimension(&self) -> Dimension {
+        self.dimension.or_else(|| self.entity.and_then(|uuid| dimension::of_player(self.server, uuid))).unwrap_or(Dimension::Overworld)
+    }
+
+    // Welt, deren Spielregeln, Schwierigkeit und Einstellungen für den Befehl gelten
+    pub fn world(&self) -> &'a Mutex<World> {
+        dimension::world_of(self.server, self.world_dimension())
+    }
+
+    // Konsole, REST-API und Funktionen haben alle Rechte
+    pub fn has_permission(&self, node: &str) -> bool {
+        match self.sender {
+            CommandSender::Player(uuid) => self.server.has_permission(uuid, node),
+            CommandSender::Console | CommandSender::Remote(_) | CommandSender::Server => true,
+        }
+    }
+
+    // Befehle aus Funktionen laufen wie bei Vanilla ohne Rückmeldung
+    pub fn reply(&self, message: &str) {
+        if self.depth > 0 {
+            return;
+        }
+        self.sender.reply(self.server, message);
+    }
+
+    // Formatierte Antwort für Spieler in ihrer Sprache; Konsole und REST-API bekommen nur den englischen Text
+    pub fn reply_component(&self, component: &Text) {
+        let CommandSender::Player(uuid) = self.sender else {
+            return self.reply(&self.server.lang.plain(component));
+        };
+        if self.depth > 0 {
+            return;
+        }
+        let players = self.server.players.lock().unwrap();
+        if let Some(player) = players.iter().find(|p| p.uuid == uuid) {
+            let _ = send_localized(self.server, player, component);
+        }
+    }
+}
+
+pub type CommandHandler = Arc<dyn Fn(&CommandContext, &[&str]) -> Result<(), String> + Send + Sync>;
+
+struct Command {
+    name: String,
+    usage: String,
+    // Ohne Knoten darf jeder den Befehl benutzen
+    permission: Option<String>,
+    handler: CommandHandler,
+}
+
+#[derive(Default)]
+pub struct CommandDispatcher {
+    commands: Vec<Command>,
+}
+
+impl CommandDispatcher {
+    pub fn new() -> CommandDispatcher {
+        CommandDispatcher::default()
+    }
+
+    pub fn with_defaults() -> CommandDispatcher {
+        let mut dispatcher = CommandDispatcher::new();
+        dispatcher.register("tps", "/tps", tps_command);
+        dispatcher.register_restricted("debug", "/debug <start|stop|report>", debug_command);
+        dispatcher.register("stop", "/stop", stop_command);
+        dispatcher.register("restart", "/restart [seconds|now|cancel]", restart_command);
+        dispatcher.register("save-all", "/save-all [flush]", save_all_command);
+        dispatcher.register("save-on", "/save-on", |ctx, _| set_autosave(ctx, true));
+        dispatcher.register("save-off", "/save-off", |ctx, _| set_autosave(ctx, false));
+        dispatcher.register("backup", "/backup", backup_command);
+        dispatcher.register_restricted("pregen", PREGEN_USAGE, pregen_command);
+        dispatcher.register("list", "/list [uuids]", list_command);
+        dispatcher.register("msg", "/msg <targets> <message>", msg_command);
+        dispatcher.register("tell", "/tell <targets> <message>", msg_command);
+        dispatcher.register("w", "/w <targets> <message>", msg_command);
+        dispatcher.register("r", "/r <message>", reply_command);
+        dispatcher.register("ignore", "/ignore <player>", ignore_command);
+        dispatcher.register_restricted("kick", "/kick <player> [reason]", kick_command);
+        dispatcher.register_restricted("netstat", "/netstat <player>", netstat_command);
+        dispatcher.register_restricted("give", "/give <targets> <item> [count]", give_command);
+        dispatcher.register_restricted("clear", "/clear [targets] [item] [count]", clear_command);
+        dispatcher.register_restricted("xp", XP_USAGE, xp_command);
+        dispatcher.register_restricted("experience", XP_USAGE, xp_command);
+        dispatcher.register_restricted("time", "/time set <time> | /time add <time> | /time query <daytime|gametime|day>", time_command);
+        dispatcher.register_restricted("weather", "/weather <clear|rain|thunder> [duration]", weather_command);
+        dispatcher.register_restricted("seed", "/seed", seed_command);
+        dispatcher.register_restricted("locate", "/locate <structure|biome> <id>", locate_command);
+        dispatcher.register_restricted("difficulty", "/difficulty [peaceful|easy|normal|hard]", difficulty_command);
+        dispatcher.register_restricted("gamemode", "/gamemode <survival|creative|adventure|spectator> [targets]", gamemode_command);
+        dispatcher.register_restricted("worldborder", WORLDBORDER_USAGE, worldborder_command);
+        dispatcher.register_restricted("gamerule", "/gamerule <rule> [value]", gamerule_command);
+        dispatcher.register_restricted("setblock", "/setblock <x> <y> <z> <block>", setblock_command);
+        dispatcher.register_restricted("summon", "/summon <entity> [x y z] [nbt]", summon_command);
+        dispatcher.register_restricted("kill", "/kill [targets]", kill_command);
+        dispatcher.register_restricted("tag", "/tag <targets> <add|remove> <name> | /tag <targets> list", tag_command);
+        dispatcher.register_restricted("scoreboard", SCOREBOARD_USAGE, scoreboard_command);
+        dispatcher.register_restricted("function", "/function <name|#tag>", function_command);
+        dispatcher.register_restricted("schedule", "/schedule function <name|#tag> <time> [append|replace] | /schedule clear <name|#tag>", schedule_command);
+        dispatcher.register_restricted("execute", "/execute [as|at <targets>] [positioned <x y z>|as <targets>] [in <dimension>] [if|unless block <x y z> <block>|biome <x y z> <biome>|entity <targets>] [run <command>]", execute_command);
+        dispatcher.register_restricted("effect", "/effect give <targets> <effect> [seconds|infinite] [amplifier] [hideParticles] | /effect clear [targets] [effect]", effect_command);
+        dispatcher.register_restricted("spawnpoint", "/spawnpoint [targets] [x y z] [angle]", spawnpoint_command);
+        dispatcher.register_restricted("setworldspawn", "/setworldspawn [x y z] [angle]", setworldspawn_command);
+        dispatcher.register_restricted("fill", "/fill <x1> <y1> <z1> <x2> <y2> <z2> <block> [replace|hollow|outline]", fill_command);
+        dispatcher.register_restricted("place", "/place template <template> [x y z] [rotation] [mirror] [integrity] [seed]", place_command);
+        dispatcher.register_restricted("schem", "/schem list | /schem load <name> | /schem paste [0|90|180|270] [-a]", schem_command);
+        dispatcher.register_restricted("pos1", "/pos1 [x y z]", |ctx, args| select_command(ctx, args, true));
+        dispatcher.register_restricted("pos2", "/pos2 [x y z]", |ctx, args| select_command(ctx, args, false));
+        dispatcher.register_restricted("wand", "/wand", wand_command);
+        dispatcher.register_restricted("set", "/set <block>", |ctx, args| region_command(ctx, args, RegionEdit::Set));
+        dispatcher.register_restricted("replace", "/replace <from> <to>", |ctx, args| region_command(ctx, args, RegionEdit::Replace));
+        dispatcher.register_restricted("walls", "/walls <block>", |ctx, args| region_command(ctx, args, RegionEdit::Walls));
+        dispatcher.register_restricted("copy", "/copy", copy_command);
+        dispatcher.register_restricted("paste", "/paste [-a]", paste_command);
+        dispatcher.register_restricted("undo", "/undo", undo_command);
+        dispatcher.register_restricted("world", WORLD_USAGE, world_command);
+        dispatcher.register_restricted("portal", PORTAL_USAGE, portal_command);
+        dispatcher.register_restricted("tp", TELEPORT_USAGE, teleport_command);
+        dispatcher.register_restricted("teleport", TELEPORT_USAGE, teleport_command);
+        dispatcher.register_restricted("permission", PERMISSION_USAGE, permission_command);
+        dispatcher.register("reload", "/reload", reload_command);
+        dispatcher.register("datapack", "/datapack <list|enable|disable> [name] [first|last]", datapack_command);
+        dispatcher
+    }
+
+    pub fn register<F>(&mut self, name: &str, usage: &str, handler: F)
+    where
+        F: Fn(&CommandContext, &[&str]) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.unregister(name);
+        self.commands.push(Command {
+            name: name.to_lowercase(),
+            usage: usage.to_string(),
+            permission: None,
+            handler: Arc::new(handler),
+        });
+    }
+
+    // Spieler brauchen dafür den Knoten server.command.<name>
+    pub fn register_restricted<F>(&mut self, name: &str, usage: &str, handler: F)
+    where
+        F: Fn(&CommandContext, &[&str]) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.register(name, usage, handler);
+        if let Some(command) = self.commands.last_mut() {
+            command.permission = Some(format!("server.command.{}", command.name));
+        }
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        self.commands.retain(|c| !c.name.eq_ignore_ascii_case(name));
+    }
+
+    fn find(&self, name: &str) -> Option<(CommandHandler, String, Option<String>)> {
+        self.commands.iter().find(|c| c.name == name).map(|c| (Arc::clone(&c.handler), c.usage.clone(), c.permission.clone()))
+    }
+}
+
+// Trennt an Leerzeichen, außer innerhalb von Klammern und Anführungszeichen, etwa bei @e[type=zombie, limit=1]
+// oder {CustomName:'"Bob der Zombie"'}
+fn split_arguments(line: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let (mut start, mut depth, mut quote) = (None, 0, None);
+    for (i, c) in line.char_indices() {
+        match c {
+            c if quote == Some(c) => quote = None,
+            _ if quote.is_some() => {}
+            '"' | '\'' if depth > 0 => quote = Some(c),
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = (depth - 1).max(0),
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(s) = start.take() {
+                    args.push(&line[s..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        args.push(&line[s..]);
+    }
+    args
+}
+
+// Führt eine Befehlszeile aus (ohne führenden Schrägstrich). Läuft im Tick-Thread, ohne gehaltene Sperren
+pub fn execute(server: &Server, sender: CommandSender, line: &str) {
+    run_line(&CommandContext::new(server, sender), line);
+}
+
+pub fn run_line(ctx: &CommandContext, line: &str) {
+    let line = line.trim().trim_start_matches('/');
+    let mut parts = split_arguments(line).into_iter();
+    let Some(name) = parts.next() else { return };
+    let args: Vec<&str> = parts.collect();
+    let found = ctx.server.commands.lock().unwrap().find(&name.to_lowercase());
+    match found {
+        Some((_, _, Some(node))) if !ctx.has_permission(&node) => ctx.reply_component(&Text::translate("server.command.permission").with(name)),
+        Some((handler, usage, _)) => {
+            if let Err(e) = handler(ctx, &args) {
+                ctx.reply_component(&Text::translate("server.command.usage").with(e).with(usage));
+            }
+        }
+        None => ctx.reply_component(&Text::translate("server.command.unknown").with(name)),
+    }
+}
+
+fn tps_command(ctx: &CommandContext, _args: &[&str]) -> Result<(), String> {
+    let stats = ctx.server.tick_stats.lock().unwrap();
+    let (one, five, fifteen) = (stats.tps(60), stats.tps(300), stats.tps(900));
+    let (average, worst) = (stats.average_mspt(), stats.worst_mspt());
+    drop(stats);
+    ctx.reply(&format!("TPS from last 1m, 5m, 15m: {:.2}, {:.2}, {:.2}", one, five, fifteen));
+    ctx.reply(&format!("MSPT average/worst (last {} ticks): {:.2}ms / {:.2}ms", crate::tick::MSPT_SAMPLES, average, worst));
+    Ok(())
+}
+
+// report zeigt den Zwischenstand, ohne die Messung zu beenden
+fn debug_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let profiler = &ctx.server.profiler;
+    let report = match args {
+        ["start"] => {
+            if !profiler.start() {
+                return Err("The profiler is already running".to_string());
+            }
+            info!("Tick-Profiler gestartet");
+            ctx.reply("Started the tick profiler, see /debug report or /debug stop for the results");
+            return Ok(());
+        }
+        ["stop"] => profiler.stop().inspect(|_| info!("Tick-Profiler beendet")),
+        ["report"] => profiler.report(),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    for line in report.ok_or("The profiler is not running, start it with /debug start")? {
+        ctx.reply(&line);
+    }
+    Ok(())
+}
+
+fn stop_command(ctx: &CommandContext, _args: &[&str]) -> Result<(), String> {
+    if ctx.sender != CommandSender::Console {
+        return Err("Only the console can stop the server".to_string());
+    }
+    ctx.reply("Stopping the server");
+    ctx.server.shutdown();
+}
+
+// Ohne Angabe mit restart-countdown Sekunden Vorwarnung
+fn restart_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    if matches!(ctx.sender, CommandSender::Player(_)) {
+        return Err("Only the console can restart the server".to_string());
+    }
+    let seconds = match args {
+        [] => ctx.server.config.restart_countdown_secs,
+        ["now"] => 0,
+        ["cancel"] => {
+            restart::cancel(ctx.server)?;
+            ctx.reply("Cancelled the restart");
+            return Ok(());
+        }
+        [seconds] => seconds.parse().map_err(|_| format!("Invalid number of seconds: {}", seconds))?,
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    ctx.reply(&restart::schedule(ctx.server, seconds)?);
+    Ok(())
+}
+
+// Ohne flush schreiben die Chunk-Worker im Hintergrund weiter
+fn save_all_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    if matches!(ctx.sender, CommandSender::Player(_)) {
+        return Err("Only the console can save the game".to_string());
+    }
+    let flush = match args {
+        [] => false,
+        ["flush"] => true,
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    ctx.reply("Saving the game (this may take a moment!)");
+    let summary = storage::save_all(ctx.server, flush);
+    if summary.failed > 0 {
+        return Err(format!("Saving failed for {} files, see the server log", summary.failed));
+    }
+    ctx.reply(&format!("Saved the game ({} chunks, {} players)", summary.chunks, summary.players));
+    Ok(())
+}
+
+fn set_autosave(ctx: &CommandContext, enabled: bool) -> Result<(), String> {
+    if matches!(ctx.sender, CommandSender::Player(_)) {
+        return Err("Only the console can change automatic saving".to_string());
+    }
+    if ctx.server.autosave.swap(enabled, Ordering::Relaxed) == enabled {
+        return Err(format!("Saving is already turned {}", if enabled { "on" } else { "off" }));
+    }
+    ctx.reply(if enabled { "Automatic saving is now enabled" } else { "Automatic saving is now disabled" });
+    Ok(())
+}
+
+fn backup_command(ctx: &CommandContext, _args: &[&str]) -> Result<(), String> {
+    if matches!(ctx.sender, CommandSender::Player(_)) {
+        return Err("Only the console can create backups".to_string());
+    }
+    let name = ctx.server.backups.start(ctx.server)?;
+    ctx.reply(&format!("Creating backup {} in the background", name));
+    Ok(())
+}
+
+const PREGEN_USAGE: &str = "/pregen <radius> [world] | /pregen pause | /pregen resume | /pregen cancel | /pregen status";
+
+fn pregen_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        ["pause"] => {
+            let paused = pregen::set_paused(ctx.server, true)?;
+            ctx.reply(if paused { "Pregeneration paused" } else { "Pregeneration is already paused" });
+        }
+        ["resume"] => {
+            let resumed = pregen::set_paused(ctx.server, false)?;
+            ctx.reply(if resumed { "Pregeneration resumed" } else { "Pregeneration is not paused" });
+        }
+        ["cancel"] => ctx.reply(&format!("Pregeneration cancelled at {}", pregen::cancel(ctx.server)?)),
+        ["status"] => ctx.reply(&pregen::status(ctx.server).unwrap_or_else(|| "No pregeneration is running".to_string())),
+        [radius, rest @ ..] if rest.len() <= 1 => {
+            let radius = radius.parse::<i32>().map_err(|_| format!("Invalid radius '{}'", radius))?;
+            let dimension = match rest.first() {
+                Some(name) => Dimension::by_world_name(name)?,
+                None => ctx.world_dimension(),
+            };
+            let reporter = match ctx.sender {
+                CommandSender::Player(uuid) => Some(uuid),
+                _ => None,
+            };
+            ctx.reply(&pregen::start(ctx.server, dimension, radius, reporter)?);
+        }
+        _ => return Err("Wrong number of arguments".to_string()),
+    }
+    Ok(())
+}
+
+const TELEPORT_USAGE: &str = "/tp [target] <x> <y> <z> [yaw] [pitch] | /tp [target] <destination>";
+// Wie bei Vanilla endet die Welt horizontal bei 30 Millionen Blöcken
+const WORLD_BORDER: f64 = 30_000_000.0;
+
+// Absolute Koordinate oder ~ bzw. ~n relativ zu base. Ganze x- und z-Werte landen wie bei Vanilla in der Blockmitte
+fn parse_coordinate(arg: &str, base: f64, center: bool) -> Result<f64, String> {
+    let invalid = || format!("Invalid coordinate '{}'", arg);
+    match arg.strip_prefix('~') {
+        Some("") => Ok(base),
+        Some(offset) => offset.parse::<f64>().map(|offset| base + offset).map_err(|_| invalid()),
+        None => {
+            let value = arg.parse::<f64>().map_err(|_| invalid())?;
+            Ok(if center && !arg.contains('.') { value + 0.5 } else { value })
+        }
+    }
+}
+
+// Spieler bekommen ein Positionspaket, nach /execute in gegebenenfalls mit Dimensionswechsel; Mobs werden nur verschoben
+fn teleport_target(ctx: &CommandContext, target: &Target, position: (f64, f64, f64), rotation: (f32, f32)) -> Result<(), String> {
+    if !target.is_player() {
+        return teleport_mob(ctx.server, target.uuid, position);
+    }
+    match ctx.dimension {
+        Some(dimension) => dimension::teleport(ctx.server, target.uuid, dimension, position, Some(rotation)),
+        None => teleport_player(ctx.server, target.uuid, position, rotation),
+    }
+}
+
+// Vanilla-Schlüssel unterscheiden zwischen einem Ziel (Name) und mehreren (Anzahl)
+fn translate_targets(key: &str, targets: &[Target]) -> Text {
+    match targets {
+        [target] => Text::translate(format!("{}.single", key)).with(target.name.as_str()),
+        _ => Text::translate(format!("{}.multiple", key)).with(targets.len().to_string()),
+    }
+}
+
+fn teleport_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let is_player = ctx.entity.is_some();
+    // Ein einzelnes Argument oder genau drei/fünf Koordinaten meinen den Absender selbst
+    let (targets, rest) = match args.len() {
+        1 | 3 | 5 if is_player => (selector::resolve(ctx, "@s")?, args),
+        1 | 3 | 5 => return Err("Only players can teleport themselves, specify a target".to_string()),
+        2 | 4 | 6 => (selector::resolve(ctx, args[0])?, &args[1..]),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    if let [destination] = rest {
+        let destination = selector::resolve_single(ctx, destination)?;
+        for target in &targets {
+            teleport_target(ctx, target, destination.position, destination.rotation)?;
+        }
+        ctx.reply_component(&translate_targets("commands.teleport.success.entity", &targets).with(destination.name));
+        return Ok(());
+    }
+    // ~ bezieht sich auf den Ausführenden bzw. /execute at, bei der Konsole auf das jeweilige Ziel
+    let executor = (is_player || ctx.position.is_some()).then(|| (ctx.origin(), ctx.facing()));
+    let mut last = (0.0, 0.0, 0.0);
+    for target in &targets {
+        let (position, facing) = executor.unwrap_or((target.position, target.rotation));
+        let x = parse_coordinate(rest[0], position.0, true)?;
+        let y = parse_coordinate(rest[1], position.1, false)?;
+        let z = parse_coordinate(rest[2], position.2, true)?;
+        if x.abs() >= WORLD_BORDER || z.abs() >= WORLD_BORDER || !y.is_finite() {
+            return Err("Invalid position for teleport".to_string());
+        }
+        let rotation = match rest {
+            [_, _, _, yaw, pitch] => {
+                let yaw = parse_coordinate(yaw, facing.0 as f64, false)? as f32;
+                let pitch = parse_coordinate(pitch, facing.1 as f64, false)? as f32;
+                (yaw, pitch.clamp(-90.0, 90.0))
+            }
+            _ => target.rotation,
+        };
+        teleport_target(ctx, target, (x, y, z), rotation)?;
+        last = (x, y, z);
+    }
+    let coordinates = [last.0, last.1, last.2].map(|c| format!("{:.2}", c));
+    ctx.reply_component(&coordinates.into_iter().fold(translate_targets("commands.teleport.success.location", &targets), Text::with));
+    Ok(())
+}
+
+fn kick_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let Some(&name) = args.first() else { return Err("Missing player name".to_string()) };
+    let reason = if args.len() > 1 { args[1..].join(" ") } else { KICK_REASON.to_string() };
+    // Namen in richtiger Schreibweise, noch bevor der Verbindungs-Thread den Spieler austrägt
+    let targets = selector::resolve_players(ctx, name)?;
+    for target in &targets {
+        kick_player(ctx.server, &target.name, &reason);
+        ctx.reply(&format!("Kicked {}: {}", target.name, reason));
+    }
+    let template = &ctx.server.config.kick_broadcast_message;
+    if !template.is_empty() {
+        for target in &targets {
+            let message = template.replace("{player}", &target.name).replace("{reason}", &reason);
+            for player in ctx.server.players.lock().unwrap().iter().filter(|p| !targets.iter().any(|t| t.uuid == p.uuid)) {
+                let _ = send_system_message(&player.connection, &message);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Für alle verfügbar, auch für Spieler ohne Operator-Rechte
+fn list_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let uuids = match args {
+        [] => false,
+        ["uuids"] => true,
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let names: Vec<String> = ctx
+        .server
+        .players
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|p| {
+            // Mit uuids auch die vom Client gemeldete Brand, damit Admins modifizierte Clients erkennen
+            if uuids {
+                format!("{} ({}, {}, {} ms)", p.username, p.uuid, p.brand.as_deref().unwrap_or("unknown client"), p.ping())
+            } else {
+                format!("{} ({} ms)", p.username, p.ping())
+            }
+        })
+        .collect();
+    ctx.reply(&format!("There are {} of a max of {} players online: {}", names.len(), ctx.server.config.max_players, names.join(", ")));
+    Ok(())
+}
+
+// Graue, kursive Flüsternachricht wie bei Vanilla
+fn whisper(text: String) -> Text {
+    Text::new(text).color("gray").italic()
+}
+
+// Stellt eine private Nachricht zu und merkt sich die Gesprächspartner für /r. Wer den Absender ignoriert,
+// bekommt nichts; der Absender erfährt davon nichts
+fn send_private_message(ctx: &CommandContext, targets: &[Target], message: &str) {
+    let sender = match ctx.sender {
+        CommandSender::Player(uuid) => Some(uuid),
+        _ => None,
+    };
+    let sender_name = if sender.is_some() { ctx.sender.name(ctx.server) } else { "Server".to_string() };
+    let mut players = ctx.server.players.lock().unwrap();
+    for target in targets {
+        let Some(recipient) = players.iter_mut().find(|p| p.uuid == target.uuid) else { continue };
+        if sender.is_some_and(|uuid| recipient.ignored.contains(&uuid)) || !recipient.settings.accepts_chat() {
+            continue;
+        }
+        if sender.is_some() {
+            recipient.reply_to = sender;
+        }
+        let _ = send_system_component(&recipient.connection, &whisper(format!("{} whispers to you: {}", sender_name, message)));
+    }
+    if let (Some(uuid), Some(last)) = (sender, targets.last()) {
+        if let Some(player) = players.iter_mut().find(|p| p.uuid == uuid) {
+            player.reply_to = Some(last.uuid);
+        }
+    }
+    drop(players);
+    for target in targets {
+        ctx.reply_component(&whisper(format!("You whisper to {}: {}", target.name, message)));
+    }
+}
+
+fn msg_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let [targets, words @ ..] = args else { return Err("Wrong number of arguments".to_string()) };
+    if words.is_empty() {
+        return Err("Wrong number of arguments".to_string());
+    }
+    let targets = selector::resolve_players(ctx, targets)?;
+    send_private_message(ctx, &targets, &words.join(" "));
+    Ok(())
+}
+
+fn reply_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let CommandSender::Player(uuid) = ctx.sender else { return Err("Only players can reply to messages".to_string()) };
+    if args.is_empty() {
+        return Err("Wrong number of arguments".to_string());
+    }
+    let target = {
+        let players = ctx.server.players.lock().unwrap();
+        let partner = players.iter().find(|p| p.uuid == uuid).and_then(|p| p.reply_to).ok_or("There is nobody to reply to")?;
+        let partner = players.iter().find(|p| p.uuid == partner).ok_or("The player you were talking to is no longer online")?;
+        Target::of_player(partner)
+    };
+    send_private_message(ctx, &[target], &args.join(" "));
+    Ok(())
+}
+
+// Schaltet um, ob private Nachrichten eines Spielers ankommen
+// Wie viele Pakettypen pro Richtung gezeigt werden
+const NETSTAT_TOP_TYPES: usize = 5;
+
+fn netstat_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let [name] = args else { return Err("Wrong number of arguments".to_string()) };
+    let target = selector::resolve_single(ctx, name)?;
+    if !target.is_player() {
+        return Err("Only players have a connection".to_string());
+    }
+    let stats = ctx.server.players.lock().unwrap().iter().find(|p| p.uuid == target.uuid).map(|p| Arc::clone(&p.connection.stats)).ok_or("Player is not online")?;
+    let (received, sent) = stats.totals();
+    ctx.reply(&format!("Connection of {}:", target.name));
+    let (received_types, sent_types) = stats.by_packet();
+    for (label, total, types, direction) in [("Received", received, received_types, Direction::Serverbound), ("Sent", sent, sent_types, Direction::Clientbound)] {
+        ctx.reply(&format!("{}: {} packets, {}", label, total.packets, metrics::format_bytes(total.bytes)));
+        for (id, count) in types.iter().take(NETSTAT_TOP_TYPES) {
+            ctx.reply(&format!("  {}: {} packets, {}", metrics::packet_name(direction, *id), count.packets, metrics::format_bytes(count.bytes)));
+        }
+    }
+    Ok(())
+}
+
+fn ignore_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let CommandSender::Player(uuid) = ctx.sender else { return Err("Only players can ignore other players".to_string()) };
+    let [name] = args else { return Err("Wrong number of arguments".to_string()) };
+    let target = selector::resolve_single(ctx, name)?;
+    if !target.is_player() {
+        return Err("Only players can be ignored".to_string());
+    }
+    if target.uuid == uuid {
+        return Err("You cannot ignore yourself".to_string());
+    }
+    let now_ignored = {
+        let mut players = ctx.server.players.lock().unwrap();
+        let player = players.iter_mut().find(|p| p.uuid == uuid).ok_or("Player is not online")?;
+        if player.ignored.remove(&target.uuid) { false } else { player.ignored.insert(target.uuid) }
+    };
+    ctx.reply(&if now_ignored { format!("You are now ignoring {}", target.name) } else { format!("You are no longer ignoring {}", target.name) });
+    Ok(())
+}
+
+// Wie bei Vanilla höchstens 100 volle Stapel auf einmal
+const MAX_GIVE_STACKS: u32 = 100;
+
+fn give_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let (targets, item, count) = match args {
+        [targets, item] => (*targets, *item, 1),
+        [targets, item, count] => (*targets, *item, count.parse::<u32>().map_err(|_| format!("Invalid count '{}'", count))?),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let stack = ItemStack::parse(item)?;
+    let max = item::max_stack_size(&stack.item);
+    if count == 0 || count > max as u32 * MAX_GIVE_STACKS {
+        return Err(format!("Count must be between 1 and {}", max as u32 * MAX_GIVE_STACKS));
+    }
+    let targets = selector::resolve_players(ctx, targets)?;
+    for target in &targets {
+        let mut overflow = Vec::new();
+        let mut players = ctx.server.players.lock().unwrap();
+        let Some(player) = players.iter_mut().find(|p| p.uuid == target.uuid) else { continue };
+        let mut changed = Vec::new();
+        let mut remaining = count;
+        while remaining > 0 {
+            let size = remaining.min(max as u32) as u8;
+            remaining -= size as u32;
+            let (slots, leftover) = player.inventory.add(ItemStack { count: size, ..stack.clone() });
+            changed.extend(slots);
+            if !leftover.is_empty() {
+                overflow.push(leftover);
+            }
+        }
+        changed.sort_unstable();
+        changed.dedup();
+        for slot in changed {
+            let _ = player.inventory.send_slot(&player.connection, slot);
+        }
+        let (position, dimension) = (player.position, player.dimension);
+        drop(players);
+        // Was nicht mehr ins Inventar passt, landet vor den Füßen des Spielers
+        for stack in overflow {
+            entity::drop_item(ctx.server, dimension, stack, position);
+        }
+    }
+    match targets.as_slice() {
+        [target] => ctx.reply_component(&Text::translate("commands.give.success.single").with(count.to_string()).with(format!("[{}]", stack.item)).with(target.name.as_str())),
+        _ => ctx.reply_component(&Text::translate("commands.give.success.multiple").with(count.to_string()).with(format!("[{}]", stack.item)).with(targets.len().to_string())),
+    }
+    Ok(())
+}
+
+const XP_USAGE: &str = "/xp add|set <targets> <amount> [levels|points] | /xp query <target> <levels|points>";
+
+fn xp_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let levels = |unit: Option<&&str>| match unit.copied() {
+        None | Some("points") => Ok(false),
+        Some("levels") => Ok(true),
+        Some(other) => Err(format!("Unknown unit '{}', expected levels or points", other)),
+    };
+    match args {
+        ["query", target, unit] => {
+            let levels = levels(Some(unit))?;
+            let target = selector::resolve_single(ctx, target)?;
+            if !target.is_player() {
+                return Err("Only players may be affected by this command, but the provided selector includes entities".to_string());
+            }
+            let players = ctx.server.players.lock().unwrap();
+            let experience = players.iter().find(|p| p.uuid == target.uuid).map(|p| p.experience).ok_or("Player is not online")?;
+            drop(players);
+            if levels {
+                ctx.reply(&format!("{} has {} experience levels", target.name, experience.level));
+            } else {
+                ctx.reply(&format!("{} has {} experience points", target.name, experience.points));
+            }
+        }
+        [action @ ("add" | "set"), targets, amount, rest @ ..] if rest.len() <= 1 => {
+            let amount: i32 = amount.parse().map_err(|_| format!("Invalid amount '{}'", amount))?;
+            let levels = levels(rest.first())?;
+            if *action == "set" && amount < 0 {
+                return Err("Amount must not be negative".to_string());
+            }
+            let targets = selector::resolve_players(ctx, targets)?;
+            let mut players = ctx.server.players.lock().unwrap();
+            let mut affected = 0;
+            for player in players.iter_mut().filter(|p| targets.iter().any(|t| t.uuid == p.uuid)) {
+                let mut experience = player.experience;
+                match (*action, levels) {
+                    ("add", true) => experience.set_level(experience.level.saturating_add(amount)),
+                    ("add", false) => experience.add_points(amount),
+                    (_, true) => experience.set_level(amount),
+                    // Wie bei Vanilla nur innerhalb der aktuellen Stufe
+                    (_, false) if amount >= experience::points_for_level(experience.level) => continue,
+                    (_, false) => experience.points = amount,
+                }
+                player.experience = experience;
+                let _ = experience::send(&player.connection, &experience);
+                affected += 1;
+            }
+            drop(players);
+            if affected == 0 {
+                return Err("Unable to set experience points above the maximum points for the player's current level".to_string());
+            }
+            let who = match targets.as_slice() {
+                [target] => target.name.clone(),
+                _ => format!("{} players", targets.len()),
+            };
+            let unit = if levels { "levels" } else { "points" };
+            if *action == "add" {
+                ctx.reply(&format!("Gave {} experience {} to {}", amount, unit, who));
+            } else {
+                ctx.reply(&format!("Set {} experience {} on {}", amount, unit, who));
+            }
+        }
+        _ => return Err("Unknown or incomplete experience command".to_string()),
+    }
+    Ok(())
+}
+
+// Ohne Item alles; * passt auf jedes Item, Komponenten in [] müssen genau übereinstimmen. Mit count 0 wird nur gezählt
+fn clear_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let targets = match args.first() {
+        Some(targets) => selector::resolve_players(ctx, targets)?,
+        None if ctx.entity.is_some() => selector::resolve(ctx, "@s")?,
+        None => return Err("The console must name a target".to_string()),
+    };
+    let filter = match args.get(1) {
+        None | Some(&"*") => None,
+        Some(item) => Some((ItemStack::parse(item)?, item.contains('['))),
+    };
+    let max = match args.get(2) {
+        None => None,
+        Some(count) => Some(count.parse::<u32>().map_err(|_| format!("Invalid count '{}'", count))?),
+    };
+    if args.len() > 3 {
+        return Err("Wrong number of arguments".to_string());
+    }
+    let matches = |stack: &ItemStack| match &filter {
+        None => true,
+        Some((wanted, true)) => stack.stacks_with(wanted),
+        Some((wanted, false)) => stack.item == wanted.item,
+    };
+    let mut total = 0;
+    let mut players = ctx.server.players.lock().unwrap();
+    for player in players.iter_mut().filter(|p| targets.iter().any(|t| t.uuid == p.uuid)) {
+        if max == Some(0) {
+            total += player.inventory.count_matching(matches);
+            continue;
+        }
+        let (changed, removed) = player.inventory.remove_matching(matches, max);
+        for slot in changed {
+            let _ = player.inventory.send_slot(&player.connection, slot);
+        }
+        total += removed;
+    }
+    drop(players);
+    let who = match targets.as_slice() {
+        [target] => format!("player {}", target.name),
+        _ => format!("{} players", targets.len()),
+    };
+    if total == 0 {
+        return Err(format!("No items were found on {}", who));
+    }
+    if max == Some(0) {
+        ctx.reply(&format!("Found {} matching item(s) on {}", total, who));
+    } else {
+        ctx.reply(&format!("Removed {} item(s) from {}", total, who));
+    }
+    Ok(())
+}
+
+const TICKS_PER_DAY: i64 = 24000;
+
+// Ticks mit optionaler Einheit wie bei Vanilla: 100, 100t, 5s oder 1d
+fn parse_ticks(arg: &str) -> Result<i64, String> {
+    let (number, unit) = match arg.char_indices().last() {
+        Some((i, 'd')) => (&arg[..i], TICKS_PER_DAY as f64),
+        Some((i, 's')) => (&arg[..i], 20.0),
+        Some((i, 't')) => (&arg[..i], 1.0),
+        _ => (arg, 1.0),
+    };
+    match number.parse::<f64>() {
+        Ok(value) if value < 0.0 => Err("Time must not be negative".to_string()),
+        Ok(value) if value * unit > i32::MAX as f64 => Err(format!("Time must not be more than {} ticks", i32::MAX)),
+        Ok(value) => Ok((value * unit).round() as i64),
+        Err(_) => Err(format!("Invalid time '{}'", arg)),
+    }
+}
+
+fn time_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let dimension = ctx.world_dimension();
+    let mut world = ctx.world().lock().unwrap();
+    match args {
+        ["set", value] => {
+            let ticks = match *value {
+                "day" => 1000,
+                "noon" => 6000,
+                "night" => 13000,
+                "midnight" => 18000,
+                value => parse_ticks(value)?,
+            };
+            world.time.time_of_day = ticks % TICKS_PER_DAY;
+            drop(world);
+            ctx.server.broadcast_time(dimension);
+            ctx.reply_component(&Text::translate("commands.time.set").with(ticks.to_string()));
+        }
+        ["add", value] => {
+            let ticks = parse_ticks(value)?;
+            world.time.time_of_day = (world.time.time_of_day + ticks) % TICKS_PER_DAY;
+            let time_of_day = world.time.time_of_day;
+            drop(world);
+            ctx.server.broadcast_time(dimension);
+            ctx.reply_component(&Text::translate("commands.time.set").with(time_of_day.to_string()));
+        }
+        ["query", query] => {
+            let value = match *query {
+                "daytime" => world.time.time_of_day,
+                "gametime" => world.time.age % i32::MAX as i64,
+                "day" => world.time.age / TICKS_PER_DAY,
+                other => return Err(format!("Unknown time query '{}'", other)),
+            };
+            drop(world);
+            ctx.reply(&format!("The time is {}", value));
+        }
+        _ => return Err("Wrong arguments".to_string()),
+    }
+    Ok(())
+}
+
+fn weather_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let (kind, duration) = match args {
+        [kind] => (*kind, None),
+        [kind, duration] => (*kind, Some(parse_ticks(duration)? as i32)),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let (raining, thundering, message) = match kind {
+        "clear" => (false, false, "Set the weather to clear"),
+        "rain" => (true, false, "Set the weather to rain"),
+        "thunder" => (true, true, "Set the weather to rain & thunder"),
+        other => return Err(format!("Unknown weather '{}'", other)),
+    };
+    if duration == Some(0) {
+        return Err("Duration must be at least 1 tick".to_string());
+    }
+    let dimension = ctx.world_dimension();
+    ctx.world().lock().unwrap().weather.set(raining, thundering, duration);
+    ctx.server.broadcast_weather(dimension);
+    ctx.reply(message);
+    Ok(())
+}
+
+// Grüner Text in eckigen Klammern mit Klick-Aktion und Tooltip, wie bei /seed und /locate
+fn clickable(text: &str, event: ClickEvent, hover: &str) -> Text {
+    Text::new(format!("[{}]", text)).color("green").click(event).hover(Text::new(hover))
+}
+
+fn seed_command(ctx: &CommandContext, _args: &[&str]) -> Result<(), String> {
+    // Vanilla zeigt den Seed vorzeichenbehaftet
+    let seed = (ctx.server.world.lock().unwrap().seed as i64).to_string();
+    ctx.reply_component(&Text::translate("commands.seed.success").with(clickable(&seed, ClickEvent::CopyToClipboard(seed.clone()), "Click to Copy to Clipboard")));
+    Ok(())
+}
+
+fn locate_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let [kind, id] = args else { return Err("Wrong number of arguments".to_string()) };
+    let id = item::namespaced(id);
+    let name = id.strip_prefix("minecraft:");
+    let origin = ctx.origin();
+    let origin = (origin.0.floor() as i32, origin.2.floor() as i32);
+    let found = match *kind {
+        "structure" => {
+            if !name.is_some_and(|name| worldgen::STRUCTURES.contains(&name)) {
+                return Err(format!("There is no structure with type \"{}\"", id));
+            }
+            // Der Generator erzeugt noch keine Strukturen, die Suche kann also nichts finden
+            return Err(format!("Could not find a structure of type \"{}\" nearby", id));
+        }
+        "biome" => {
+            let Some(name) = name.filter(|name| worldgen::BIOMES.contains(name)) else {
+                return Err(format!("There is no biome with type \"{}\"", id));
+            };
+            worldgen::locate_biome(ctx.world_dimension(), origin, name).ok_or_else(|| format!("Could not find a biome of type \"{}\" within reasonable distance", id))?
+        }
+        _ => return Err(format!("Unknown locate type '{}', expected structure or biome", kind)),
+    };
+    let distance = (((found.0 - origin.0) as f64).powi(2) + ((found.1 - origin.1) as f64).powi(2)).sqrt().floor();
+    let coordinates = format!("{}, ~, {}", found.0, found.1);
+    let teleport = format!("/tp @s {} ~ {}", found.0, found.1);
+    ctx.reply_component(
+        &Text::new(format!("The nearest {} is at ", id))
+            .append(clickable(&coordinates, ClickEvent::SuggestCommand(teleport), "Click to teleport"))
+            .append(format!(" ({} blocks away)", distance)),
+    );
+    Ok(())
+}
+
+fn difficulty_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let current = ctx.world().lock().unwrap().difficulty;
+    let difficulty = match args {
+        [] => {
+            ctx.reply_component(&Text::translate("commands.difficulty.query").with(current.display_text()));
+            return Ok(());
+        }
+        [name] => name.parse::<Difficulty>()?,
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    if difficulty == current {
+        return Err(format!("The difficulty did not change; it is already set to {}", difficulty.display_name()));
+    }
+    ctx.world().lock().unwrap().difficulty = difficulty;
+    ctx.server.broadcast_difficulty(ctx.world_dimension());
+    ctx.reply_component(&Text::translate("commands.difficulty.success").with(difficulty.display_text()));
+    Ok(())
+}
+
+fn gamemode_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let (name, targets) = match args {
+        [name] if ctx.entity.is_some() => (name, selector::resolve_players(ctx, "@s")?),
+        [_] => return Err("The console must name a target".to_string()),
+        [name, targets] => (name, selector::resolve_players(ctx, targets)?),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let mode = GameMode::from_name(name).ok_or_else(|| format!("Unknown game mode '{}'", name))?;
+    let mut changed = 0;
+    for target in &targets {
+        if !gamemode::set(ctx.server, target.uuid, mode)? {
+            continue;
+        }
+        changed += 1;
+        if ctx.entity == Some(target.uuid) {
+            ctx.reply_component(&Text::translate("commands.gamemode.success.self").with(mode.display_text()));
+            continue;
+        }
+        ctx.reply_component(&Text::translate("commands.gamemode.success.other").with(target.name.as_str()).with(mode.display_text()));
+        let players = ctx.server.players.lock().unwrap();
+        if let Some(player) = players.iter().find(|p| p.uuid == target.uuid) {
+            let _ = send_localized(ctx.server, player, &Text::translate("gameMode.changed").with(mode.display_text()));
+        }
+    }
+    if changed == 0 {
+        return Err(format!("Nothing changed; the game mode is already {}", mode.name()));
+    }
+    Ok(())
+}
+
+const WORLDBORDER_USAGE: &str =
+    "/worldborder get | /worldborder set|add <distance> [time] | /worldborder center <x> <z> | /worldborder warning distance|time <value>";
+
+fn worldborder_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let dimension = ctx.world_dimension();
+    let border = ctx.world().lock().unwrap().border;
+    match args {
+        ["get"] => ctx.reply(&format!("The world border is currently {:.0} block(s) wide", border.size())),
+        [action @ ("set" | "add"), distance, rest @ ..] => {
+            let distance: f64 = distance.parse().map_err(|_| format!("Invalid distance '{}'", distance))?;
+            // Dauer in Sekunden wie bei Vanilla
+            let seconds: i64 = match rest {
+                [] => 0,
+                [time] => time.parse().ok().filter(|t| *t >= 0).ok_or_else(|| format!("Invalid time '{}'", time))?,
+                _ => return Err("Wrong number of arguments".to_string()),
+            };
+            let size = if *action == "add" { border.size() + distance } else { distance };
+            if size < worldborder::MIN_SIZE {
+                return Err("The world border cannot be smaller than 1 block wide".to_string());
+            }
+            if size > worldborder::MAX_SIZE {
+                return Err("The world border cannot be bigger than 59,999,968 blocks wide".to_string());
+            }
+            if size == border.size() && seconds == 0 {
+                return Err("Nothing changed. The world border is already that size".to_string());
+            }
+            let message = if seconds == 0 {
+                format!("Set the world border to {:.1} block(s) wide", size)
+            } else if size < border.size() {
+                format!("Shrinking the world border to {:.1} block(s) wide over {} second(s)", size, seconds)
+            } else {
+                format!("Growing the world border to {:.1} block(s) wide over {} second(s)", size, seconds)
+            };
+            ctx.world().lock().unwrap().border.lerp_to(size, seconds * 1000);
+            ctx.server.broadcast_world_border(dimension, worldborder::send_size);
+            ctx.reply(&message);
+        }
+        ["center", x, z] => {
+            let origin = ctx.origin();
+            let center = (parse_coordinate(x, origin.0, true)?, parse_coordinate(z, origin.2, true)?);
+            if center.0.abs() > worldborder::MAX_CENTER || center.1.abs() > worldborder::MAX_CENTER {
+                return Err("The world border cannot be further than 29,999,984 blocks from the center of the world".to_string());
+            }
+            if center == border.center {
+                return Err("Nothing changed. The world border is already centered there".to_string());
+            }
+            ctx.world().lock().unwrap().border.center = center;
+            ctx.server.broadcast_world_border(dimension, worldborder::send_center);
+            ctx.reply(&format!("Set the center of the world border to {:.2}, {:.2}", center.0, center.1));
+        }
+        ["warning", kind @ ("distance" | "time"), value] => {
+            let value: i32 = value.parse().ok().filter(|v| *v >= 0).ok_or_else(|| format!("Invalid {} '{}'", kind, value))?;
+            let mut world = ctx.world().lock().unwrap();
+            if *kind == "distance" {
+                if world.border.warning_distance == value {
+                    return Err("Nothing changed. The world border warning is already that distance".to_string());
+                }
+                world.border.warning_distance = value;
+                drop(world);
+                ctx.server.broadcast_world_border(dimension, worldborder::send_warning_distance);
+                ctx.reply(&format!("Set the world border warning distance to {} block(s)", value));
+            } else {
+                if world.border.warning_time == value {
+                    return Err("Nothing changed. The world border warning is already that amount of time".to_string());
+                }
+                world.border.warning_time = value;
+                drop(world);
+                ctx.server.broadcast_world_border(dimension, worldborder::send_warning_time);
+                ctx.reply(&format!("Set the world border warning time to {} second(s)", value));
+            }
+        }
+        _ => return Err("Unknown or incomplete world border command".to_string()),
+    }
+    Ok(())
+}
+
+fn gamerule_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        [rule] => {
+            let value = ctx.world().lock().unwrap().rules.get(rule).ok_or_else(|| format!("Unknown game rule '{}'", rule))?;
+            ctx.reply(&format!("Gamerule {} is currently set to: {}", rule, value));
+        }
+        [rule, value] => {
+            let value = ctx.world().lock().unwrap().rules.set(rule, value)?;
+            if *rule == "doImmediateRespawn" {
+                ctx.server.broadcast_immediate_respawn(ctx.world_dimension());
+            }
+            ctx.reply(&format!("Gamerule {} is now set to: {}", rule, value));
+        }
+        _ => return Err("Wrong number of arguments".to_string()),
+    }
+    Ok(())
+}
+
+// Blockposition aus drei Argumenten; ~ bezieht sich auf den Bezugspunkt des Befehls, bei der Konsole auf 0 0 0
+fn parse_block_position(ctx: &CommandContext, args: &[&str]) -> Result<(i32, i32, i32), String> {
+    let origin = ctx.origin();
+    let x = parse_coordinate(args[0], origin.0, false)?.floor();
+    let y = parse_coordinate(args[1], origin.1, false)?.floor();
+    let z = parse_coordinate(args[2], origin.2, false)?.floor();
+    if x.abs() >= WORLD_BORDER || z.abs() >= WORLD_BORDER || y < chunk::MIN_Y as f64 || y >= (chunk::MIN_Y + chunk::HEIGHT) as f64 {
+        return Err("That position is out of this world".to_string());
+    }
+    Ok((x as i32, y as i32, z as i32))
+}
+
+fn setblock_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let [x, y, z, state] = args else { return Err("Wrong number of arguments".to_string()) };
+    let position = parse_block_position(ctx, &[x, y, z])?;
+    let state = block::parse_state(state)?;
+    if apply_block_changes(ctx.server, &[(position, state)]) == 0 {
+        return Err("Could not set the block".to_string());
+    }
+    ctx.reply(&format!("Changed the block at {}, {}, {}", position.0, position.1, position.2));
+    Ok(())
+}
+
+fn fill_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let (mode, args) = match args {
+        [rest @ .., mode] if args.len() == 8 => (*mode, rest),
+        _ if args.len() == 7 => ("replace", args),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let from = parse_block_position(ctx, &args[0..3])?;
+    let to = parse_block_position(ctx, &args[3..6])?;
+    let state = block::parse_state(args[6])?;
+    let min = (from.0.min(to.0), from.1.min(to.1), from.2.min(to.2));
+    let max = (from.0.max(to.0), from.1.max(to.1), from.2.max(to.2));
+    let volume = (max.0 - min.0 + 1) as i64 * (max.1 - min.1 + 1) as i64 * (max.2 - min.2 + 1) as i64;
+    let limit = ctx.world().lock().unwrap().rules.get_int(gamerule::COMMAND_MODIFICATION_BLOCK_LIMIT) as i64;
+    if volume > limit {
+        return Err(format!("Too many blocks in the specified area (maximum {}, specified {})", limit, volume));
+    }
+    let mut changes = Vec::with_capacity(volume as usize);
+    for x in min.0..=max.0 {
+        for y in min.1..=max.1 {
+            for z in min.2..=max.2 {
+                let edge = x == min.0 || x == max.0 || y == min.1 || y == max.1 || z == min.2 || z == max.2;
+                // hollow höhlt das Innere mit Luft aus, outline lässt es unverändert
+                let state = match mode {
+                    "replace" => state,
+                    "hollow" if edge => state,
+                    "hollow" => block::AIR,
+                    "outline" if edge => state,
+                    "outline" => continue,
+                    other => return Err(format!("Unknown fill mode '{}'", other)),
+                };
+                changes.push(((x, y, z), state));
+            }
+        }
+    }
+    match apply_block_changes(ctx.server, &changes) {
+        0 => Err("No blocks were filled".to_string()),
+        count => {
+            ctx.reply(&format!("Successfully filled {} block(s)", count));
+            Ok(())
+        }
+    }
+}
+
+// Wie bei Vanilla: ohne Position an der eigenen, ohne Drehung und Spiegelung so, wie die Vorlage gespeichert ist
+fn place_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let ["template", id, rest @ ..] = args else { return Err("Wrong number of arguments".to_string()) };
+    if rest.len() > 7 || (!rest.is_empty() && rest.len() < 3) {
+        return Err("Wrong number of arguments".to_string());
+    }
+    let id = item::namespaced(id);
+    let origin = match rest {
+        [x, y, z, ..] => parse_block_position(ctx, &[x, y, z])?,
+        _ => parse_block_position(ctx, &["~", "~", "~"])?,
+    };
+    let mut placement = Placement::default();
+    if let Some(rotation) = rest.get(3) {
+        placement.rotation = Rotation::parse(rotation).ok_or_else(|| format!("Unknown rotation '{}'", rotation))?;
+    }
+    if let Some(mirror) = rest.get(4) {
+        placement.mirror = Mirror::parse(mirror).ok_or_else(|| format!("Unknown mirror '{}'", mirror))?;
+    }
+    if let Some(integrity) = rest.get(5) {
+        placement.integrity = integrity.parse::<f32>().ok().filter(|i| (0.0..=1.0).contains(i)).ok_or("Integrity must be between 0.0 and 1.0")?;
+    }
+    placement.seed = match rest.get(6) {
+        Some(seed) => seed.parse::<i64>().map_err(|_| format!("Invalid seed '{}'", seed))? as u64,
+        None => rand::random(),
+    };
+    let datapacks = ctx.server.datapacks.lock().unwrap();
+    let template = datapacks.registries.structures.get(&id).ok_or_else(|| format!("Template {} not found", id))?;
+    let placed = template.resolve(origin, &placement);
+    let size = template.size;
+    drop(datapacks);
+    if placed.blocks.is_empty() && placed.entities.is_empty() {
+        return Err("Failed to place template".to_string());
+    }
+    structure::place(ctx.server, &placed);
+    ctx.reply(&format!("Structure {} ({}×{}×{}) placed at {}, {}, {}", id, size.0, size.1, size.2, origin.0, origin.1, origin.2));
+    Ok(())
+}
+
+// Schematics aus schematics/: load legt eines in die eigene Zwischenablage, paste setzt sie an der eigenen Position
+// ein, im Uhrzeigersinn gedreht; -a lässt wie bei WorldEdit die Luft aus
+fn schem_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let owner = sender_uuid(ctx);
+    match args {
+        ["list"] => {
+            let mut names: Vec<String> = std::fs::read_dir(schematic::SCHEMATIC_DIR)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".schem").map(String::from))
+                .collect();
+            names.sort();
+            if names.is_empty() {
+                ctx.reply(&format!("There are no schematics in {}", schematic::SCHEMATIC_DIR));
+            } else {
+                ctx.reply(&format!("There are {} schematics: {}", names.len(), names.join(", ")));
+            }
+            Ok(())
+        }
+        ["load", name] => {
+            let loaded = Schematic::load(schematic::SCHEMATIC_DIR, name)?;
+            let (x, y, z) = loaded.size;
+            ctx.reply(&format!("Loaded {} ({}×{}×{}) into your clipboard", name, x, y, z));
+            if !loaded.unknown.is_empty() {
+                ctx.reply(&format!("Unknown blocks will be skipped: {}", loaded.unknown.join(", ")));
+            }
+            ctx.server.edits.lock().unwrap().session(owner).clipboard = Some(loaded);
+            Ok(())
+        }
+        ["paste", options @ ..] if options.len() <= 2 => {
+            let mut placement = Placement::default();
+            let mut include_air = true;
+            for option in options {
+                match *option {
+                    "-a" => include_air = false,
+                    "0" => placement.rotation = Rotation::None,
+                    "90" => placement.rotation = Rotation::Clockwise90,
+                    "180" => placement.rotation = Rotation::Clockwise180,
+                    "270" => placement.rotation = Rotation::Counterclockwise90,
+                    other => return Err(format!("Unknown rotation '{}', expected 0, 90, 180 or 270", other)),
+                }
+            }
+            let origin = parse_block_position(ctx, &["~", "~", "~"])?;
+            paste_clipboard(ctx, owner, origin, &placement, include_air)
+        }
+        _ => Err("Wrong number of arguments".to_string()),
+    }
+}
+
+const WORLD_USAGE: &str = "/world list | /world create <name> [normal|amplified|large_biomes|flat|end|void] [seed|random] [dimension type] | /world tp <name> [targets] | /world unload <name> | /world config <name> [pvp|spawn-monsters|spawn-animals] [value]";
+
+// Benannte Welten neben Oberwelt und Ende; "world" steht wie der Ordner für die Oberwelt
+fn world_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        ["list"] => {
+            let mut names = vec![storage::world_dir().to_string(), "the_end".to_string()];
+            names.extend(multiworld::loaded().iter().map(|w| w.short_name().to_string()));
+            ctx.reply(&format!("There are {} loaded worlds: {}", names.len(), names.join(", ")));
+            let unloaded = multiworld::unloaded();
+            if !unloaded.is_empty() {
+                ctx.reply(&format!("Not loaded: {}", unloaded.join(", ")));
+            }
+            Ok(())
+        }
+        ["create", name, rest @ ..] if rest.len() <= 3 => {
+            let generator = rest.first().map(|g| Generator::from_name(g).ok_or_else(|| format!("Unknown generator '{}', expected {}", g, Generator::names()))).transpose()?;
+            let seed = rest.get(1).filter(|s| **s != "random").map(|s| s.parse::<u64>().map_err(|_| format!("Invalid seed '{}'", s))).transpose()?;
+            let kind = rest.get(2).map(|t| DimensionType::find(t).ok_or_else(|| format!("Unknown dimension type '{}'", t))).transpose()?;
+            multiworld::start_create(name, generator, seed, kind, &ctx.server.config, ctx.sender)?;
+            ctx.reply(&format!("Generating world {}...", name));
+            Ok(())
+        }
+        ["tp", name, rest @ ..] if rest.len() <= 1 => {
+            let dimension = Dimension::by_world_name(name)?;
+            let spawn = dimension::world(ctx.server, dimension)?.lock().unwrap().spawn;
+            let targets = selector::resolve_players(ctx, rest.first().copied().unwrap_or("@s"))?;
+            let (x, y, z) = spawn.position;
+            for target in &targets {
+                dimension::teleport(ctx.server, target.uuid, dimension, (x as f64 + 0.5, y as f64, z as f64 + 0.5), Some((spawn.angle, 0.0)))?;
+            }
+            ctx.reply_component(&translate_targets("commands.teleport.success.entity", &targets).with(*name));
+            Ok(())
+        }
+        ["config", name, rest @ ..] => {
+            let dimension = Dimension::by_world_name(name)?;
+            let mut world = dimension::world(ctx.server, dimension)?.lock().unwrap();
+            match rest {
+                [] => {
+                    let values: Vec<String> = WorldSettings::KEYS.iter().filter_map(|key| Some(format!("{}={}", key, world.settings.get(key)?))).collect();
+                    ctx.reply(&format!("World {}: difficulty={}, {}", name, world.difficulty, values.join(", ")));
+                    ctx.reply(&format!("Dimension type: {}", dimension.kind().describe()));
+                }
+                [key] => {
+                    let value = world.settings.get(key).ok_or_else(|| format!("Unknown world setting '{}'", key))?;
+                    ctx.reply(&format!("{} is currently set to {} in {}", key, value, name));
+                }
+                [key, value] => {
+                    let value = world.settings.set(key, value)?;
+                    ctx.reply(&format!("{} is now set to {} in {}", key, value, name));
+                }
+                _ => return Err("Wrong number of arguments".to_string()),
+            }
+            Ok(())
+        }
+        ["unload", name] => {
+            multiworld::unload(ctx.server, name)?;
+            ctx.reply(&format!("World {} unloaded", name));
+            Ok(())
+        }
+        _ => Err("Wrong number of arguments".to_string()),
+    }
+}
+
+const PORTAL_USAGE: &str = "/portal list | /portal create <name> <world> <x> <y> <z> [yaw pitch] | /portal remove <name>";
+
+// Verbindet die eigene Auswahl (/wand oder /pos1 und /pos2) in der aktuellen Welt mit einem Ziel in einer beliebigen Welt
+fn portal_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        ["list"] => {
+            let portals = ctx.server.portals.lock().unwrap();
+            if portals.list().is_empty() {
+                ctx.reply("There are no portals");
+            }
+            for portal in portals.list() {
+                let ((x1, y1, z1), (x2, y2, z2)) = portal.region;
+                let (x, y, z) = portal.position;
+                ctx.reply(&format!("{}: {} {} {} {} to {} {} {} -> {} {:.1} {:.1} {:.1}", portal.name, portal.world, x1, y1, z1, x2, y2, z2, portal.destination, x, y, z));
+            }
+            Ok(())
+        }
+        ["create", name, world, x, y, z, rotation @ ..] if matches!(rotation.len(), 0 | 2) => {
+            let region = ctx.server.edits.lock().unwrap().session(sender_uuid(ctx)).region()?;
+            let (origin, facing) = (ctx.origin(), ctx.facing());
+            let position = (parse_coordinate(x, origin.0, true)?, parse_coordinate(y, origin.1, false)?, parse_coordinate(z, origin.2, true)?);
+            let rotation = match rotation {
+                [yaw, pitch] => Some((parse_coordinate(yaw, facing.0 as f64, false)? as f32, (parse_coordinate(pitch, facing.1 as f64, false)? as f32).clamp(-90.0, 90.0))),
+                _ => None,
+            };
+            let portal = portal::new_portal(name, ctx.world_dimension().name(), region, world, position, rotation)?;
+            ctx.reply(&format!("Portal {} now leads to {} ({} blocks)", portal.name, portal.destination, edit::volume(region)));
+            ctx.server.portals.lock().unwrap().link(portal);
+            Ok(())
+        }
+        ["remove", name] => {
+            if !ctx.server.portals.lock().unwrap().unlink(name) {
+                return Err(format!("Unknown portal '{}'", name));
+            }
+            ctx.reply(&format!("Removed portal {}", name));
+            Ok(())
+        }
+        _ => Err("Wrong number of arguments".to_string()),
+    }
+}
+
+fn sender_uuid(ctx: &CommandContext) -> Option<Uuid> {
+    match ctx.sender {
+        CommandSender::Player(uuid) => Some(uuid),
+        _ => None,
+    }
+}
+
+// Fügt die eigene Zwischenablage über die Bearbeitungswarteschlange ein, damit /undo sie zurücknehmen kann
+fn paste_clipboard(ctx: &CommandContext, owner: Option<Uuid>, origin: (i32, i32, i32), placement: &Placement, include_air: bool) -> Result<(), String> {
+    let mut edits = ctx.server.edits.lock().unwrap();
+    let clipboard = edits.session(owner).clipboard.as_ref().ok_or("Your clipboard is empty, use /copy or /schem load first")?;
+    let mut placed = clipboard.resolve(origin, placement, include_air);
+    let blocks = std::mem::take(&mut placed.blocks);
+    ctx.reply(&format!("Pasting {} block(s) at {}, {}, {}", blocks.len(), origin.0, origin.1, origin.2));
+    edits.submit(owner, blocks, None, Some(placed));
+    Ok(())
+}
+
+// Ohne Koordinaten an der eigenen Blockposition
+fn select_command(ctx: &CommandContext, args: &[&str], first: bool) -> Result<(), String> {
+    let position = match args {
+        [] => parse_block_position(ctx, &["~", "~", "~"])?,
+        [x, y, z] => parse_block_position(ctx, &[x, y, z])?,
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let mut edits = ctx.server.edits.lock().unwrap();
+    let session = edits.session(sender_uuid(ctx));
+    if first {
+        session.pos1 = Some(position);
+    } else {
+        session.pos2 = Some(position);
+    }
+    let (x, y, z) = position;
+    let volume = session.region().map(|region| format!(" ({} blocks)", edit::volume(region))).unwrap_or_default();
+    ctx.reply(&format!("{} position set to {}, {}, {}{}", if first { "First" } else { "Second" }, x, y, z, volume));
+    Ok(())
+}
+
+fn wand_command(ctx: &CommandContext, _args: &[&str]) -> Result<(), String> {
+    let CommandSender::Player(uuid) = ctx.sender else { return Err("Only players can use the selection wand".to_string()) };
+    let mut players = ctx.server.players.lock().unwrap();
+    let player = players.iter_mut().find(|p| p.uuid == uuid).ok_or("Player not found")?;
+    let (slots, leftover) = player.inventory.add(edit::wand()?);
+    for slot in slots {
+        let _ = player.inventory.send_slot(&player.connection, slot);
+    }
+    if !leftover.is_empty() {
+        return Err("Your inventory is full".to_string());
+    }
+    ctx.reply("Left click: select position 1, right click: select position 2");
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum RegionEdit {
+    Set,
+    Replace,
+    Walls,
+}
+
+fn region_command(ctx: &CommandContext, args: &[&str], kind: RegionEdit) -> Result<(), String> {
+    let (filter, block) = match (kind, args) {
+        (RegionEdit::Replace, [from, to]) => {
+            // Ungültige Prädikate gleich melden statt erst beim Setzen
+            block::matches(block::AIR, from)?;
+            (Some(*from), *to)
+        }
+        (RegionEdit::Set | RegionEdit::Walls, [block]) => (None, *block),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let state = block::parse_state(block)?;
+    let owner = sender_uuid(ctx);
+    let mut edits = ctx.server.edits.lock().unwrap();
+    let region = edits.session(owner).region()?;
+    let volume = edit::volume(region);
+    if volume > edit::MAX_VOLUME {
+        return Err(format!("Too many blocks in the selection (maximum {}, selected {})", edit::MAX_VOLUME, volume));
+    }
+    let (min, max) = region;
+    let changes: Vec<_> = edit::positions(region)
+        .filter(|&(x, _, z)| !matches!(kind, RegionEdit::Walls) || x == min.0 || x == max.0 || z == min.2 || z == max.2)
+        .map(|position| (position, state))
+        .collect();
+    ctx.reply(&format!("Changing up to {} block(s)", changes.len()));
+    edits.submit(owner, changes, filter, None);
+    Ok(())
+}
+
+fn copy_command(ctx: &CommandContext, _args: &[&str]) -> Result<(), String> {
+    let owner = sender_uuid(ctx);
+    let region = ctx.server.edits.lock().unwrap().session(owner).region()?;
+    let volume = edit::volume(region);
+    if volume > edit::MAX_VOLUME {
+        return Err(format!("Too many blocks in the selection (maximum {}, selected {})", edit::MAX_VOLUME, volume));
+    }
+    let origin = parse_block_position(ctx, &["~", "~", "~"])?;
+    let clipboard = edit::copy(ctx.server, region, origin);
+    ctx.server.edits.lock().unwrap().session(owner).clipboard = Some(clipboard);
+    ctx.reply(&format!("{} block(s) copied", volume));
+    Ok(())
+}
+
+// Wie bei WorldEdit relativ zur eigenen Position beim Kopieren; -a lässt die Luft aus
+fn paste_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let include_air = match args {
+        [] => true,
+        ["-a"] => false,
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let origin = parse_block_position(ctx, &["~", "~", "~"])?;
+    paste_clipboard(ctx, sender_uuid(ctx), origin, &Placement::default(), include_air)
+}
+
+// Erst wenn die eigenen Bearbeitungen fertig sind, steht die letzte im Verlauf
+fn undo_command(ctx: &CommandContext, _args: &[&str]) -> Result<(), String> {
+    let owner = sender_uuid(ctx);
+    let mut edits = ctx.server.edits.lock().unwrap();
+    let pending = edits.pending(owner);
+    if pending > 0 {
+        return Err(format!("Wait until your current edit is done ({} block(s) left)", pending));
+    }
+    if edits.undo(owner) {
+        Ok(())
+    } else {
+        Err("Nothing left to undo".to_string())
+    }
+}
+
+fn summon_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let (kind, position, data) = match args {
+        [kind] => (*kind, None, None),
+        [kind, x, y, z] => (*kind, Some([*x, *y, *z]), None),
+        [kind, x, y, z, data] => (*kind, Some([*x, *y, *z]), Some(*data)),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let kind = item::namespaced(kind);
+    if !entity::is_known(&kind) {
+        return Err(format!("Unknown entity type '{}'", kind));
+    }
+    if !entity::is_summonable(&kind) {
+        return Err(format!("Unable to summon {}", kind));
+    }
+    let (difficulty, settings) = {
+        let world = ctx.world().lock().unwrap();
+        (world.difficulty, world.settings)
+    };
+    if entity::is_hostile(&kind) && difficulty == Difficulty::Peaceful {
+        return Err("Unable to summon monsters in Peaceful".to_string());
+    }
+    if !settings.allows_spawn(&kind) {
+        return Err(format!("Spawning {} is disabled in this world", kind));
+    }
+    let data = data.map(entity::SummonData::parse).transpose()?.unwrap_or_default();
+    let (origin, yaw) = match ctx.entity.is_some() || ctx.position.is_some() {
+        true => (ctx.origin(), ctx.facing().0),
+        false => ((0.5, 0.0, 0.5), 0.0),
+    };
+    let position = match position {
+        Some([x, y, z]) => (parse_coordinate(x, origin.0, true)?, parse_coordinate(y, origin.1, false)?, parse_coordinate(z, origin.2, true)?),
+        None => origin,
+    };
+    if position.0.abs() >= WORLD_BORDER || position.2.abs() >= WORLD_BORDER || !position.1.is_finite() {
+        return Err("Invalid position for summon".to_string());
+    }
+    let mut mob = Mob::new(&kind, position);
+    mob.yaw = yaw;
+    mob.custom_name = data.custom_name;
+    mob.custom_name_visible = data.custom_name_visible;
+    mob.tags = data.tags;
+    if let Some(health) = data.health.filter(|_| entity::is_living(&kind)) {
+        mob.health = health;
+    }
+    if kind == tnt::PRIMED {
+        mob.fuse = Some(data.fuse.unwrap_or(tnt::FUSE_TICKS));
+    }
+    let name = mob.display_name();
+    let players = ctx.server.players.lock().unwrap();
+    let mut world = ctx.world().lock().unwrap();
+    world.load_chunk_now((position.0.floor() as i32) >> 4, (position.2.floor() as i32) >> 4);
+    for player in players.iter().filter(|p| entity::in_view(p, world.dimension, position)) {
+        let _ = entity::send_mob(&player.connection, &mob);
+    }
+    world.spawn_mob(mob);
+    drop(world);
+    drop(players);
+    ctx.reply(&format!("Summoned new {}", name));
+    Ok(())
+}
+
+fn kill_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let targets = match args {
+        [] if ctx.entity.is_some() => selector::resolve(ctx, "@s")?,
+        [] => return Err("The console must name a target".to_string()),
+        [targets] => selector::resolve(ctx, targets)?,
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    // Wie bei Vanilla zählt auch, wer schon tot war
+    for target in &targets {
+        damage::damage(ctx.server, target, DamageSource::OutOfWorld, f32::MAX);
+    }
+    ctx.reply_component(&translate_targets("commands.kill.success", &targets));
+    Ok(())
+}
+
+fn tag_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        [targets, action @ ("add" | "remove"), name] => {
+            if !entitytag::is_valid(name) {
+                return Err(format!("Invalid tag name '{}'", name));
+            }
+            let targets = selector::resolve(ctx, targets)?;
+            let add = *action == "add";
+            let changed: Vec<&Target> = targets
+                .iter()
+                .filter(|t| if add { entitytag::add(ctx.server, t, name) } else { entitytag::remove(ctx.server, t, name) })
+                .collect();
+            match (add, changed.as_slice()) {
+                (true, []) => return Err("Nothing changed. The targets either already have the tag or have too many tags".to_string()),
+                (false, []) => return Err("Nothing changed. The targets do not have the tag".to_string()),
+                (true, [target]) => ctx.reply(&format!("Added tag '{}' to {}", name, target.name)),
+                (true, _) => ctx.reply(&format!("Added tag '{}' to {} entities", name, changed.len())),
+                (false, [target]) => ctx.reply(&format!("Removed tag '{}' from {}", name, target.name)),
+                (false, _) => ctx.reply(&format!("Removed tag '{}' from {} entities", name, changed.len())),
+            }
+        }
+        [targets, "list"] => {
+            let targets = selector::resolve(ctx, targets)?;
+            let mut tags: Vec<&str> = targets.iter().flat_map(|t| t.tags.iter().map(String::as_str)).collect();
+            tags.sort_unstable();
+            tags.dedup();
+            match (targets.as_slice(), tags.is_empty()) {
+                ([target], true) => ctx.reply(&format!("{} has no tags", target.name)),
+                ([target], false) => ctx.reply(&format!("{} has {} tags: {}", target.name, tags.len(), tags.join(", "))),
+                (_, true) => ctx.reply(&format!("The {} entities have no tags", targets.len())),
+                (_, false) => ctx.reply(&format!("The {} entities have {} total tags: {}", targets.len(), tags.len(), tags.join(", "))),
+            }
+        }
+        _ => return Err("Unknown or incomplete tag command".to_string()),
+    }
+    Ok(())
+}
+
+const SCOREBOARD_USAGE: &str = "/scoreboard objectives list | /scoreboard objectives add <objective> <criterion> [displayName] | /scoreboard objectives remove <objective> | /scoreboard objectives setdisplay <list|sidebar|below_name> [objective] | /scoreboard players list [target] | /scoreboard players get <target> <objective> | /scoreboard players set|add|remove <targets> <objective> <score> | /scoreboard players reset <targets> [objective]";
+
+// Punktehalter: Selektoren ergeben Spielernamen und die UUIDs anderer Entities, * alle mit einem Punktestand, alles
+// andere gilt als Name, auch von Spielern, die gerade nicht online sind
+fn score_holders(ctx: &CommandContext, arg: &str) -> Result<Vec<String>, String> {
+    if arg == "*" {
+        let holders: Vec<String> = ctx.server.scoreboard.lock().unwrap().holders().into_iter().map(str::to_string).collect();
+        return if holders.is_empty() { Err("No entity was found".to_string()) } else { Ok(holders) };
+    }
+    if !arg.starts_with('@') {
+        return Ok(vec![arg.to_string()]);
+    }
+    Ok(selector::resolve(ctx, arg)?.into_iter().map(|t| if t.is_player() { t.name } else { t.uuid.to_string() }).collect())
+}
+
+fn score_holder(ctx: &CommandContext, arg: &str) -> Result<String, String> {
+    let mut holders = score_holders(ctx, arg)?;
+    if holders.len() > 1 {
+        return Err("Only one entity is allowed, but the provided selector allows more than one".to_string());
+    }
+    Ok(holders.remove(0))
+}
+
+fn unknown_objective(name: &str) -> String {
+    format!("Unknown scoreboard objective '{}'", name)
+}
+
+fn scoreboard_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        ["objectives", rest @ ..] => scoreboard_objectives(ctx, rest),
+        ["players", rest @ ..] => scoreboard_players(ctx, rest),
+        _ => Err("Unknown or incomplete scoreboard command".to_string()),
+    }
+}
+
+fn scoreboard_objectives(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        ["list"] => {
+            let scoreboard = ctx.server.scoreboard.lock().unwrap();
+            let names: Vec<String> = scoreboard.objectives().iter().map(|o| format!("[{}]", o.display_name)).collect();
+            match names.len() {
+                0 => ctx.reply("There are no objectives"),
+                count => ctx.reply(&format!("There are {} objective(s): {}", count, names.join(", "))),
+            }
+        }
+        ["add", name, criterion, display_name @ ..] => {
+            // Zielnamen folgen denselben Regeln wie Entity-Tags
+            if !entitytag::is_valid(name) {
+                return Err(format!("Invalid objective name '{}'", name));
+            }
+            let criterion = scoreboard::parse_criterion(criterion)?;
+            let display_name = (!display_name.is_empty()).then(|| display_name.join(" "));
+            let objective = Objective::new(name, &criterion, display_name.as_deref());
+            let reply = format!("Created new objective [{}]", objective.display_name);
+            ctx.server.scoreboard.lock().unwrap().add(objective)?;
+            ctx.reply(&reply);
+        }
+        ["remove", name] => {
+            let players = ctx.server.players.lock().unwrap();
+            let removed = ctx.server.scoreboard.lock().unwrap().remove(&players, name).ok_or_else(|| unknown_objective(name))?;
+            drop(players);
+            ctx.reply(&format!("Removed objective [{}]", removed.display_name));
+        }
+        ["setdisplay", slot, objective @ ..] if objective.len() <= 1 => {
+            let index = scoreboard::SLOTS.iter().position(|s| s == slot).ok_or_else(|| format!("Unknown display slot '{}'", slot))?;
+            let players = ctx.server.players.lock().unwrap();
+            let mut scoreboard = ctx.server.scoreboard.lock().unwrap();
+            match objective.first() {
+                Some(name) => {
+                    if scoreboard.objective(name).is_none() {
+                        return Err(unknown_objective(name));
+                    }
+                    if scoreboard.displayed(index) == Some(*name) {
+                        return Err("Nothing changed. That display slot is already showing that objective".to_string());
+                    }
+                    scoreboard.set_display(&players, index, Some(name));
+                    ctx.reply(&format!("Set display slot {} to show objective {}", slot, name));
+                }
+                None => {
+                    if scoreboard.displayed(index).is_none() {
+                        return Err("Nothing changed. That display slot is already empty".to_string());
+                    }
+                    scoreboard.set_display(&players, index, None);
+                    ctx.reply(&format!("Cleared any objectives in display slot {}", slot));
+                }
+            }
+        }
+        _ => return Err("Unknown or incomplete scoreboard objectives command".to_string()),
+    }
+    Ok(())
+}
+
+fn scoreboard_players(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        ["list"] => {
+            let scoreboard = ctx.server.scoreboard.lock().unwrap();
+            match scoreboard.holders().as_slice() {
+                [] => ctx.reply("There are no tracked entities"),
+                holders => ctx.reply(&format!("There are {} tracked entity/entities: {}", holders.len(), holders.join(", "))),
+            }
+        }
+        ["list", target] => {
+            let holder = score_holder(ctx, target)?;
+            let scoreboard = ctx.server.scoreboard.lock().unwrap();
+            let scores: Vec<(String, i32)> = scoreboard
+                .objectives()
+                .iter()
+                .filter_map(|o| o.scores.get(&holder).map(|score| (o.display_name.clone(), *score)))
+                .collect();
+            drop(scoreboard);
+            if scores.is_empty() {
+                ctx.reply(&format!("{} has no scores", holder));
+            } else {
+                ctx.reply(&format!("{} has {} score(s):", holder, scores.len()));
+                for (name, score) in scores {
+                    ctx.reply(&format!("[{}]: {}", name, score));
+                }
+            }
+        }
+        ["get", target, name] => {
+            let holder = score_holder(ctx, target)?;
+            let scoreboard = ctx.server.scoreboard.lock().unwrap();
+            let objective = scoreboard.objective(name).ok_or_else(|| unknown_objective(name))?;
+            let score = scoreboard.get(name, &holder).ok_or_else(|| format!("Can't get value of {} for {}; none is set", name, holder))?;
+            ctx.reply(&format!("{} has {} [{}]", holder, score, objective.display_name));
+        }
+        [action @ ("set" | "add" | "remove"), targets, name, score] => {
+            let score: i32 = score.parse().map_err(|_| format!("Invalid integer '{}'", score))?;
+            if *action != "set" && score < 0 {
+                return Err(format!("Integer must not be less than 0, found {}", score));
+            }
+            let holders = score_holders(ctx, targets)?;
+            let players = ctx.server.players.lock().unwrap();
+            let mut scoreboard = ctx.server.scoreboard.lock().unwrap();
+            let objective = scoreboard.objective(name).ok_or_else(|| unknown_objective(name))?;
+            if objective.is_read_only() {
+                return Err(format!("Objective {} is read-only", name));
+            }
+            let display_name = objective.display_name.clone();
+            let mut last = 0;
+            for holder in &holders {
+                last = scoreboard.update(&players, name, holder, |old| match *action {
+                    "set" => score,
+                    "add" => old.unwrap_or(0).wrapping_add(score),
+                    _ => old.unwrap_or(0).wrapping_sub(score),
+                });
+            }
+            drop(scoreboard);
+            drop(players);
+            match (*action, holders.as_slice()) {
+                ("set", [holder]) => ctx.reply(&format!("Set [{}] for {} to {}", display_name, holder, score)),
+                ("set", _) => ctx.reply(&format!("Set [{}] for {} entities to {}", display_name, holders.len(), score)),
+                ("add", [holder]) => ctx.reply(&format!("Added {} to [{}] for {} (now {})", score, display_name, holder, last)),
+                ("add", _) => ctx.reply(&format!("Added {} to [{}] for {} entities", score, display_name, holders.len())),
+                (_, [holder]) => ctx.reply(&format!("Removed {} from [{}] for {} (now {})", score, display_name, holder, last)),
+                (_, _) => ctx.reply(&format!("Removed {} from [{}] for {} entities", score, display_name, holders.len())),
+            }
+        }
+        ["reset", targets, objective @ ..] if objective.len() <= 1 => {
+            let holders = score_holders(ctx, targets)?;
+            let players = ctx.server.players.lock().unwrap();
+            let mut scoreboard = ctx.server.scoreboard.lock().unwrap();
+            let display_name = match objective.first() {
+                Some(name) => Some(scoreboard.objective(name).ok_or_else(|| unknown_objective(name))?.display_name.clone()),
+                None => None,
+            };
+            for holder in &holders {
+                scoreboard.reset(&players, holder, objective.first().copied());
+            }
+            drop(scoreboard);
+            drop(players);
+            match (display_name, holders.as_slice()) {
+                (None, [holder]) => ctx.reply(&format!("Reset all scores for {}", holder)),
+                (None, _) => ctx.reply(&format!("Reset all scores for {} entities", holders.len())),
+                (Some(name), [holder]) => ctx.reply(&format!("Reset [{}] for {}", name, holder)),
+                (Some(name), _) => ctx.reply(&format!("Reset [{}] for {} entities", name, holders.len())),
+            }
+        }
+        _ => return Err("Unknown or incomplete scoreboard players command".to_string()),
+    }
+    Ok(())
+}
+
+fn function_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let [name] = args else { return Err("Macro arguments are not supported".to_string()) };
+    let (functions, commands) = function::run(ctx, name)?;
+    if name.starts_with('#') {
+        ctx.reply(&format!("Executed {} commands from {} functions", commands, functions));
+    } else {
+        ctx.reply(&format!("Executed {} commands from function '{}'", commands, item::namespaced(name)));
+    }
+    Ok(())
+}
+
+fn schedule_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    match args {
+        ["function", id, time, mode @ ..] => {
+            let replace = match mode {
+                [] | ["replace"] => true,
+                ["append"] => false,
+                _ => return Err("Expected append or replace".to_string()),
+            };
+            let delay = parse_ticks(time)?;
+            if delay == 0 {
+                return Err("Can't schedule for current tick".to_string());
+            }
+            let (function, due) = function::schedule(ctx.server, id, delay, replace)?;
+            match function.strip_prefix('#') {
+                Some(tag) => ctx.reply(&format!("Scheduled tag '{}' in {} ticks at gametime {}", tag, delay, due)),
+                None => ctx.reply(&format!("Scheduled function '{}' in {} ticks at gametime {}", function, delay, due)),
+            }
+        }
+        ["clear", id] => match function::clear(ctx.server, id) {
+            0 => return Err(format!("No schedule with id {}", id)),
+            removed => ctx.reply(&format!("Removed {} schedule(s) with id {}", removed, id)),
+        },
+        _ => return Err("Wrong arguments".to_string()),
+    }
+    Ok(())
+}
+
+// Führt run für jeden Kontext aus, den die Unterbefehle davor erzeugen. as und at verzweigen für jedes Ziel,
+// if/unless verwerfen Kontexte. Ohne run wird nur das Ergebnis der letzten Bedingung gemeldet
+fn execute_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let mut contexts = vec![ctx.with(ctx.entity, ctx.position, ctx.rotation)];
+    let mut rest = args;
+    loop {
+        match rest {
+            ["run", command @ ..] if !command.is_empty() => {
+                let line = command.join(" ");
+                for context in &contexts {
+                    run_line(context, &line);
+                }
+                return Ok(());
+            }
+            [] => {
+                if contexts.is_empty() {
+                    return Err("Test failed".to_string());
+                }
+                ctx.reply(&format!("Test passed, count: {}", contexts.len()));
+                return Ok(());
+            }
+            ["as", targets, tail @ ..] => {
+                let mut forked = Vec::new();
+                for context in &contexts {
+                    forked.extend(selector::resolve_all(context, targets)?.into_iter().map(|t| context.with(Some(t.uuid), context.position, context.rotation)));
+                }
+                contexts = forked;
+                rest = tail;
+            }
+            ["at", targets, tail @ ..] => {
+                let mut forked = Vec::new();
+                for context in &contexts {
+                    forked.extend(selector::resolve_all(context, targets)?.into_iter().map(|t| context.with(context.entity, Some(t.position), Some(t.rotation))));
+                }
+                contexts = forked;
+                rest = tail;
+            }
+            ["positioned", "as", targets, tail @ ..] => {
+                let mut forked = Vec::new();
+                for context in &contexts {
+                    forked.extend(selector::resolve_all(context, targets)?.into_iter().map(|t| context.with(context.entity, Some(t.position), context.rotation)));
+                }
+                contexts = forked;
+                rest = tail;
+            }
+            ["positioned", x, y, z, tail @ ..] => {
+                for context in &mut contexts {
+                    let origin = context.origin();
+                    let position = (parse_coordinate(x, origin.0, true)?, parse_coordinate(y, origin.1, false)?, parse_coordinate(z, origin.2, true)?);
+                    context.position = Some(position);
+                }
+                rest = tail;
+            }
+            ["in", name, tail @ ..] => {
+                let dimension = Dimension::from_name(name).ok_or_else(|| format!("Unknown dimension '{}'", name))?;
+                dimension::world(ctx.server, dimension)?;
+                for context in &mut contexts {
+                    context.dimension = Some(dimension);
+                }
+                rest = tail;
+            }
+            [mode @ ("if" | "unless"), "block", x, y, z, block, tail @ ..] => {
+                let mut kept = Vec::new();
+                for context in contexts {
+                    let (x, y, z) = parse_block_position(&context, &[x, y, z])?;
+                    let state = dimension::world_of(context.server, context.world_dimension()).lock().unwrap().get_block(x, y, z);
+                    if block::matches(state, block)? == (*mode == "if") {
+                        kept.push(context);
+                    }
+                }
+                contexts = kept;
+                rest = tail;
+            }
+            [mode @ ("if" | "unless"), "biome", x, y, z, biome, tail @ ..] => {
+                let name = item::namespaced(biome);
+                let wanted = name.strip_prefix("minecraft:").filter(|name| worldgen::BIOMES.contains(name)).ok_or_else(|| format!("There is no biome with type \"{}\"", name))?;
+                let mut kept = Vec::new();
+                for context in contexts {
+                    let (x, y, z) = parse_block_position(&context, &[x, y, z])?;
+                    let found = dimension::world(context.server, context.world_dimension())?.lock().unwrap().get_biome(x, y, z);
+                    if (found == wanted) == (*mode == "if") {
+                        kept.push(context);
+                    }
+                }
+                contexts = kept;
+                rest = tail;
+            }
+            [mode @ ("if" | "unless"), "entity", targets, tail @ ..] => {
+                let mut kept = Vec::new();
+                for context in contexts {
+                    let found = !selector::resolve_all(&context, targets)?.is_empty();
+                    if found == (*mode == "if") {
+                        kept.push(context);
+                    }
+                }
+                contexts = kept;
+                rest = tail;
+            }
+            [other, ..] => return Err(format!("Unknown or incomplete execute subcommand '{}'", other)),
+        }
+    }
+}
+
+// Wie bei Vanilla: 30 Sekunden, wenn keine Dauer angegeben ist
+const DEFAULT_EFFECT_SECONDS: i32 = 30;
+const MAX_EFFECT_SECONDS: i32 = 1_000_000;
+
+fn effect_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let parse_effect = |name: &str| effect::id(name).ok_or_else(|| format!("Unknown effect '{}'", name));
+    match args {
+        ["give", targets, name, rest @ ..] if rest.len() <= 3 => {
+            let id = parse_effect(name)?;
+            let duration = match rest.first() {
+                None => Some(DEFAULT_EFFECT_SECONDS * 20),
+                Some(&"infinite") => None,
+                Some(seconds) => match seconds.parse::<i32>() {
+                    Ok(seconds) if (1..=MAX_EFFECT_SECONDS).contains(&seconds) => Some(seconds * 20),
+                    _ => return Err(format!("Duration must be between 1 and {} seconds, or infinite", MAX_EFFECT_SECONDS)),
+                },
+            };
+            let amplifier = match rest.get(1) {
+                None => 0,
+                Some(amplifier) => amplifier.parse::<u8>().map_err(|_| format!("Amplifier must be between 0 and 255, found '{}'", amplifier))?,
+            };
+            let show_particles = match rest.get(2) {
+                None | Some(&"false") => true,
+                Some(&"true") => false,
+                Some(other) => return Err(format!("Invalid boolean, expected 'true' or 'false' but found '{}'", other)),
+            };
+            let targets = selector::resolve(ctx, targets)?;
+            let instance = effect::EffectInstance::new(amplifier, duration, show_particles);
+            let applied: Vec<Target> = targets.into_iter().filter(|t| effect::apply(ctx.server, t, id, instance)).collect();
+            if applied.is_empty() {
+                return Err("Unable to apply this effect (target is either immune to effects, or has something stronger)".to_string());
+            }
+            ctx.reply(&format!("Applied effect {} to {}", effect::display_name(id), describe_targets(&applied)));
+        }
+        ["clear", rest @ ..] if rest.len() <= 2 => {
+            let targets = match rest.first() {
+                Some(targets) => selector::resolve(ctx, targets)?,
+                None if ctx.entity.is_some() => selector::resolve(ctx, "@s")?,
+                None => return Err("The console must name a target".to_string()),
+            };
+            let id = rest.get(1).map(|name| parse_effect(name)).transpose()?;
+            let cleared: Vec<Target> = targets.into_iter().filter(|t| effect::remove(ctx.server, t, id)).collect();
+            match id {
+                _ if !cleared.is_empty() => {}
+                Some(_) => return Err("Target doesn't have the requested effect".to_string()),
+                None => return Err("Target has no effects to remove".to_string()),
+            }
+            match id {
+                Some(id) => ctx.reply(&format!("Removed effect {} from {}", effect::display_name(id), describe_targets(&cleared))),
+                None => ctx.reply(&format!("Removed every effect from {}", describe_targets(&cleared))),
+            }
+        }
+        _ => return Err("Unknown or incomplete effect command".to_string()),
+    }
+    Ok(())
+}
+
+// "Bob" oder "3 targets" wie in den Effekt-Meldungen von Vanilla
+fn describe_targets(targets: &[Target]) -> String {
+    match targets {
+        [target] => target.name.clone(),
+        _ => format!("{} targets", targets.len()),
+    }
+}
+
+// [x y z] [angle]; fehlende Angaben nehmen Position und Blickrichtung des ausführenden Spielers
+fn parse_spawn_point(ctx: &CommandContext, args: &[&str]) -> Result<SpawnPoint, String> {
+    let (position, angle) = match args {
+        [] => (["~", "~", "~"], "~"),
+        [x, y, z] => ([*x, *y, *z], "~"),
+        [x, y, z, angle] => ([*x, *y, *z], *angle),
+        _ => return Err("Wrong number of arguments".to_string()),
+    };
+    let yaw = ctx.facing().0;
+    let angle = parse_coordinate(angle, yaw as f64, false)? as f32;
+    Ok(SpawnPoint { position: parse_block_position(ctx, &position)?, angle })
+}
+
+fn spawnpoint_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let is_player = ctx.entity.is_some();
+    let (targets, rest) = match args.first() {
+        Some(targets) => (selector::resolve_players(ctx, targets)?, &args[1..]),
+        None if is_player => (selector::resolve(ctx, "@s")?, args),
+        None => return Err("The console must name a target".to_string()),
+    };
+    let spawn = parse_spawn_point(ctx, rest)?;
+    let mut players = ctx.server.players.lock().unwrap();
+    for player in players.iter_mut().filter(|p| targets.iter().any(|t| t.uuid == p.uuid)) {
+        player.spawn_point = Some(spawn);
+    }
+    drop(players);
+    let who = match targets.as_slice() {
+        [target] => target.name.clone(),
+        _ => format!("{} players", targets.len()),
+    };
+    let (x, y, z) = spawn.position;
+    ctx.reply(&format!("Set spawn point to {}, {}, {} [{:.2}] in minecraft:overworld for {}", x, y, z, spawn.angle, who));
+    Ok(())
+}
+
+fn setworldspawn_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let spawn = parse_spawn_point(ctx, args)?;
+    ctx.world().lock().unwrap().spawn = spawn;
+    ctx.server.broadcast_spawn_position(ctx.world_dimension());
+    let (x, y, z) = spawn.position;
+    ctx.reply(&format!("Set the world spawn point to {}, {}, {} [{:.2}]", x, y, z, spawn.angle));
+    Ok(())
+}
+
+// Liest Servericon und Datenpakete neu ein
+fn reload_command(ctx: &CommandContext, _args: &[&str]) -> Result<(), String> {
+    if matches!(ctx.sender, CommandSender::Player(_)) {
+        return Err("Only the console can reload the server".to_string());
+    }
+    ctx.reply("Reloading!");
+    let favicon = favicon::load(favicon::ICON_FILE);
+    ctx.reply(if favicon.is_some() { "Loaded the server icon" } else { "No valid server-icon.png found, see the server log" });
+    *ctx.server.favicon.lock().unwrap() = favicon;
+    let mut datapacks = ctx.server.datapacks.lock().unwrap();
+    datapacks.rescan();
+    datapacks.reload();
+    ctx.reply(&format!("Loaded: {}", datapacks.registries.summary()));
+    drop(datapacks);
+    recipebook::resend(ctx.server);
+    Ok(())
+}
+
+fn pack_list(packs: &[String]) -> String {
+    packs.iter().map(|id| format!("[{}]", id)).collect::<Vec<_>>().join(", ")
+}
+
+fn datapack_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    if matches!(ctx.sender, CommandSender::Player(_)) {
+        return Err("Only the console can manage data packs".to_string());
+    }
+    // Vanilla verlangt Anführungszeichen um Namen wie "file/pack.zip"
+    let name = args.get(1).map(|name| name.trim_matches('"'));
+    let mut datapacks = ctx.server.datapacks.lock().unwrap();
+    match (args.first().copied(), name) {
+        (Some("list"), _) => {
+            datapacks.rescan();
+            let filter = args.get(1).copied();
+            if filter != Some("available") {
+                let enabled = datapacks.enabled().to_vec();
+                ctx.reply(&format!("There are {} data pack(s) enabled: {}", enabled.len(), pack_list(&enabled)));
+            }
+            if filter != Some("enabled") {
+                let available: Vec<String> = datapacks.available().iter().filter(|p| !datapacks.is_enabled(&p.id)).map(|p| p.id.clone()).collect();
+                if available.is_empty() {
+                    ctx.reply("There are no more data packs available");
+                } else {
+                    ctx.reply(&format!("There are {} data pack(s) available: {}", available.len(), pack_list(&available)));
+                }
+            }
+            for pack in datapacks.available().iter().filter(|p| !p.compatible()) {
+                ctx.reply(&format!("[{}] is made for another version (pack_format {})", pack.id, pack.pack_format));
+            }
+            ctx.reply(&format!("Loaded: {}", datapacks.registries.summary()));
+            Ok(())
+        }
+        (Some("enable"), Some(name)) => {
+            let first = match args.get(2).copied() {
+                None | Some("last") => false,
+                Some("first") => true,
+                Some(other) => return Err(format!("Unknown position '{}', expected first or last", other)),
+            };
+            datapacks.rescan();
+            datapacks.enable(name, first)?;
+            ctx.reply(&format!("Enabled data pack [{}]", name));
+            drop(datapacks);
+            recipebook::resend(ctx.server);
+            Ok(())
+        }
+        (Some("disable"), Some(name)) => {
+            datapacks.disable(name)?;
+            ctx.reply(&format!("Disabled data pack [{}]", name));
+            drop(datapacks);
+            recipebook::resend(ctx.server);
+            Ok(())
+        }
+        _ => Err("Unknown subcommand".to_string()),
+    }
+}
+
+const PERMISSION_USAGE: &str = "/permission check <player> <node> | /permission info <player> | /permission player <player> set|unset <node> [true|false] | /permission player <player> group add|remove <group> | /permission group list | /permission group <group> create|delete|set|unset|prefix|parent ... | /permission reload";
+
+// Online Spieler mit UUID, sonst nur der Name
+fn permission_holder(ctx: &CommandContext, name: &str) -> (String, Option<Uuid>) {
+    let players = ctx.server.players.lock().unwrap();
+    match players.iter().find(|p| p.username.eq_ignore_ascii_case(name)) {
+        Some(player) => (player.username.clone(), Some(player.uuid)),
+        None => (name.to_string(), None),
+    }
+}
+
+fn parse_permission_value(value: Option<&&str>) -> Result<bool, String> {
+    match value.copied() {
+        None | Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        Some(other) => Err(format!("Expected true or false, got '{}'", other)),
+    }
+}
+
+fn permission_command(ctx: &CommandContext, args: &[&str]) -> Result<(), String> {
+    let mut permissions = ctx.server.permissions.lock().unwrap();
+    match args {
+        ["reload"] => {
+            permissions.reload();
+            ctx.reply(&format!("Reloaded {}", permission::PERMISSIONS_FILE));
+        }
+        ["check", name, node] => {
+            drop(permissions);
+            let (name, uuid) = permission_holder(ctx, name);
+            let value = ctx.server.permissions.lock().unwrap().has(&name, uuid, node);
+            ctx.reply(&format!("{} has {} set to {}", name, node, value));
+        }
+        ["info", name] => {
+            drop(permissions);
+            let (name, uuid) = permission_holder(ctx, name);
+            let permissions = ctx.server.permissions.lock().unwrap();
+            let nodes = permissions.player(&name, uuid).map(|p| p.nodes.iter().map(|(node, value)| format!("{}={}", node, value)).collect::<Vec<_>>()).unwrap_or_default();
+            ctx.reply(&format!("{} is in groups: {}", name, permissions.groups_of(&name, uuid).join(", ")));
+            ctx.reply(&format!("Prefix: '{}', own permissions: {}", permissions.prefix(&name, uuid), if nodes.is_empty() { "none".to_string() } else { nodes.join(", ") }));
+        }
+        ["player", name, rest @ ..] => {
+            drop(permissions);
+            let (name, uuid) = permission_holder(ctx, name);
+            let mut permissions = ctx.server.permissions.lock().unwrap();
+            match rest {
+                ["set", node, value @ ..] if value.len() <= 1 => {
+                    let value = parse_permission_value(value.first())?;
+                    permissions.set_player_node(&name, uuid, node, Some(value))?;
+                    ctx.reply(&format!("Set {} to {} for {}", node, value, name));
+                }
+                ["unset", node] => {
+                    permissions.set_player_node(&name, uuid, node, None)?;
+                    ctx.reply(&format!("Unset {} for {}", node, name));
+                }
+                ["group", "add", group] => match permissions.add_player_group(&name, uuid, group)? {
+                    true => ctx.reply(&format!("Added {} to group {}", name, group)),
+                    false => return Err(format!("{} is already in group {}", name, group)),
+                },
+                ["group", "remove", group] => match permissions.remove_player_group(&name, uuid, group)? {
+                    true => ctx.reply(&format!("Removed {} from group {}", name, group)),
+                    false => return Err(format!("{} is not in group {}", name, group)),
+                },
+                _ => return Err("Unknown subcommand".to_string()),
+            }
+        }
+        ["group", "list"] => {
+            let groups = permissions.group_names();
+            ctx.reply(&format!("There are {} groups: {}", groups.len(), groups.join(", ")));
+        }
+        ["group", group, rest @ ..] => match rest {
+            ["create"] => match permissions.create_group(group)? {
+                true => ctx.reply(&format!("Created group {}", group)),
+                false => return Err(format!("Group {} already exists", group)),
+            },
+            ["delete"] => {
+                permissions.delete_group(group)?;
+                ctx.reply(&format!("Deleted group {}", group));
+            }
+            ["set", node, value @ ..] if value.len() <= 1 => {
+                let value = parse_permission_value(value.first())?;
+                permissions.set_group_node(group, node, Some(value))?;
+                ctx.reply(&format!("Set {} to {} for group {}", node, value, group));
+            }
+            ["unset", node] => {
+                permissions.set_group_node(group, node, None)?;
+                ctx.reply(&format!("Unset {} for group {}", node, group));
+            }
+            // Ohne Angabe wird das Präfix entfernt; "[Admin] " in Anführungszeichen behält das Leerzeichen
+            ["prefix", prefix @ ..] => {
+                let prefix = prefix.join(" ");
+                let prefix = prefix.trim_matches('"');
+                permissions.set_group_prefix(group, prefix)?;
+                ctx.reply(&format!("Set the prefix of group {} to '{}'", group, prefix));
+            }
+            ["parent", "add", parent] => match permissions.add_group_parent(group, parent)? {
+                true => ctx.reply(&format!("Group {} now inherits from {}", group, parent)),
+                false => return Err(format!("Group {} already inherits from {}", group, parent)),
+            },
+            ["parent", "remove", parent] => match permissions.remove_group_parent(group, parent)? {
+                true => ctx.reply(&format!("Group {} no longer inherits from {}", group, parent)),
+                false => return Err(format!("Group {} does not inherit from {}", group, parent)),
+            },
+            _ => return Err("Unknown subcommand".to_string()),
+        },
+        _ => return Err("Unknown subcommand".to_string()),
+    }
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fs;
+use crate::chunkstorage::ChunkStorageKind;
+use crate::damage::HardcoreDeath;
+use crate::difficulty::Difficulty;
+use crate::listen::BindAddresses;
+use crate::protocol::framing;
+use crate::watchdog::WatchdogAction;
+use crate::worldgen::Generator;
+
+pub const CONFIG_FILE: &str = "server.properties";
+
+// Erzeugt die Konfigurationsstruktur samt Standardwerten und Lesen/Schreiben im server.properties-Format
+macro_rules! server_config {
+    ($($field:ident: $ty:ty = $key:literal, $default:expr;)*) => {
+        #[derive(Debug, Clone)]
+        pub struct ServerConfig {
+            $(pub $field: $ty,)*
+        }
+
+        impl Default for ServerConfig {
+            fn default() -> ServerConfig {
+                ServerConfig {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl ServerConfig {
+            fn apply(&mut self, values: &HashMap<String, String>) {
+                $(
+                    if let Some(value) = values.get($key) {
+                        match value.trim().parse::<$ty>() {
+                            Ok(parsed) => self.$field = parsed,
+                            Err(_) => warn!("Ungültiger Wert für {} in {}: {}", $key, CONFIG_FILE, value),
+                        }
+                    }
+                )*
+            }
+
+            // Überschreibt einen Wert, ohne die Datei anzufassen, etwa von der Kommandozeile
+            pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+                match key {
+                    $($key => self.$field = value.trim().parse::<$ty>().map_err(|_| format!("Invalid value for {}: {}", $key, value))?,)*
+                    _ => return Err(format!("Unknown property '{}'", key)),
+                }
+                Ok(())
+            }
+
+            fn entries(&self) -> Vec<(&'static str, String)> {
+                vec![$(($key, self.$field.to_string()),)*]
+            }
+        }
+    };
+}
+
+server_config! {
+    motd: String = "motd", "A Minecraft Server".to_string();
+    difficulty: Difficulty = "difficulty", Difficulty::Easy;
+    hardcore: bool = "hardcore", false;
+    // Vorgaben für neue Welten, danach gilt, was in deren level.json steht
+    pvp: bool = "pvp", true;
+    spawn_monsters: bool = "spawn-monsters", true;
+    spawn_animals: bool = "spawn-animals", true;
+    // Höchstzahl natürlich gespawnter Mobs je Kategorie für die 17×17 Chunks um einen Spieler wie bei Vanilla; mehrere
+    // Spieler teilen sich die Chunks, in denen sie zusammen stehen. 0 schaltet die Kategorie ab
+    mob_cap_monster: u32 = "mob-cap-monster", 70;
+    mob_cap_creature: u32 = "mob-cap-creature", 10;
+    mob_cap_ambient: u32 = "mob-cap-ambient", 15;
+    mob_cap_water: u32 = "mob-cap-water", 5;
+    // Ticks zwischen zwei Spawnrunden je Kategorie; Tiere bleiben für immer und kommen deshalb nur alle 20 Sekunden
+    spawn_interval_monster: u32 = "spawn-interval-monster", 1;
+    spawn_interval_creature: u32 = "spawn-interval-creature", 400;
+    spawn_interval_ambient: u32 = "spawn-interval-ambient", 1;
+    spawn_interval_water: u32 = "spawn-interval-water", 1;
+    // spectator oder ban
+    hardcore_death: HardcoreDeath = "hardcore-death", HardcoreDeath::Spectator;
+    // Wer server.join.full hat, kommt auch darüber hinaus noch herein
+    max_players: usize = "max-players", 100;
+    // Platzhalter: {prefix}, {name}, {message}, {world}, {ping}
+    chat_format: String = "chat-format", "<{prefix}{name}> {message}".to_string();
+    // Platzhalter: {player}; leer deaktiviert die Meldung
+    join_message: String = "join-message", "{player} joined the game".to_string();
+    quit_message: String = "quit-message", "{player} left the game".to_string();
+    kick_broadcast_message: String = "kick-broadcast-message", "{player} was kicked: {reason}".to_string();
+    // In Minuten, 0 schaltet das Trennen untätiger Spieler ab
+    player_idle_timeout: u64 = "player-idle-timeout", 0;
+    // Leer lauscht auf allen IPv4-Adressen, :: auch auf IPv6; Port 0 wählt einen freien Port, etwa für Tests
+    server_ip: String = "server-ip", String::new();
+    server_port: u16 = "server-port", 25565;
+    // Weitere Adressen als ip:port, durch Kommas getrennt, etwa 0.0.0.0:25566,[::1]:25565. :: belegt den Port auch
+    // für IPv4, daneben geht derselbe Port auf 0.0.0.0 nicht
+    server_addresses: BindAddresses = "server-addresses", BindAddresses::default();
+    // Ab dieser Größe in Bytes werden Pakete komprimiert; -1 schaltet die Kompression ab
+    network_compression_threshold: i32 = "network-compression-threshold", -1;
+    // Obergrenze für Pakete vom Client im Spiel, höchstens 2097151
+    max_packet_size: usize = "max-packet-size", framing::MAX_PACKET_LENGTH;
+    keep_alive_interval_secs: u64 = "keep-alive-interval", 15;
+    keep_alive_timeout_secs: u64 = "keep-alive-timeout", 30;
+    // Nagle-Algorithmus aus: weniger Verzögerung, dafür mehr kleine TCP-Segmente
+    tcp_no_delay: bool = "tcp-no-delay", true;
+    max_handshakes_per_second: u32 = "max-handshakes-per-second", 5;
+    reconnect_cooldown_ms: u64 = "reconnect-cooldown-ms", 250;
+    max_packets_per_second: u32 = "max-packets-per-second", 500;
+    max_pending_connections: usize = "max-pending-connections", 64;
+    // Pro IP; 0 schaltet die Login-Drosselung ab. Danach bleibt die IP so viele Sekunden gesperrt
+    max_logins_per_minute: u32 = "max-logins-per-minute", 10;
+    login_throttle_secs: u64 = "login-throttle-secs", 60;
+    login_timeout_secs: u64 = "login-timeout", 30;
+    proxy_protocol: bool = "proxy-protocol", false;
+    bungeecord: bool = "bungeecord", false;
+    velocity_forwarding: bool = "velocity-forwarding", false;
+    velocity_secret: String = "velocity-secret", String::new();
+    // Chat nur mit gültiger Signatur und nur von Clients mit Chat-Sitzung; braucht Mojang-UUIDs, also einen Proxy
+    enforce_secure_chat: bool = "enforce-secure-chat", false;
+    rest_api_enabled: bool = "rest-api-enabled", false;
+    rest_api_address: String = "rest-api-address", "127.0.0.1".to_string();
+    rest_api_port: u16 = "rest-api-port", 8080;
+    rest_api_token: String = "rest-api-token", String::new();
+    rest_api_console_read_token: String = "rest-api-console-read-token", String::new();
+    // Ordner der Oberwelt
+    level_name: String = "level-name", "world".to_string();
+    // Generator der Oberwelt: normal, amplified, large_biomes, flat oder void; gilt auch für neue Chunks einer bestehenden Welt
+    level_type: Generator = "level-type", Generator::Normal;
+    // Neue Welten mit dem Generator void bekommen eine kleine Steinplattform am Spawn
+    void_spawn_platform: bool = "void-spawn-platform", true;
+    // native oder zstd für alle Welten; mit zstd werden vorhandene Chunks beim nächsten Speichern gepackt
+    chunk_storage: ChunkStorageKind = "chunk-storage", ChunkStorageKind::Native;
+    autosave_interval_secs: u64 = "autosave-interval", 300;
+    backup_interval_secs: u64 = "backup-interval", 0;
+    backup_directory: String = "backup-directory", "backups".to_string();
+    backup_keep: usize = "backup-keep", 10;
+    backup_max_age_days: u64 = "backup-max-age-days", 0;
+    // In Chunks um den Spawn der Oberwelt, beim Start vorab generiert; 0 schaltet das ab
+    pregen_radius: i32 = "pregen-radius", 0;
+    // Höchstens so viele Chunks generiert /pregen gleichzeitig und speichert es pro Tick
+    pregen_chunks_per_tick: usize = "pregen-chunks-per-tick", 8;
+    // In Millisekunden wie bei Vanilla; dauert ein einzelner Tick länger, schreibt der Watchdog einen Absturzbericht.
+    // 0 oder -1 schaltet ihn ab
+    max_tick_time: i64 = "max-tick-time", 60000;
+    // none, kill oder restart, nachdem der Bericht geschrieben ist
+    watchdog_action: WatchdogAction = "watchdog-action", WatchdogAction::Kill;
+    // Vorwarnung in Sekunden, wenn /restart keine Zeit angibt, und bei SIGHUP
+    restart_countdown_secs: u64 = "restart-countdown", 10;
+    restart_message: String = "restart-message", "Server is restarting".to_string();
+    // Startet die Binärdatei selbst neu; sonst endet der Prozess mit Status 75 und ein Wrapper startet ihn neu
+    restart_exec: bool = "restart-exec", false;
+    // Übergibt systemd Sockets (LISTEN_FDS), lauscht der Server nur auf diesen statt auf server-ip und server-addresses
+    systemd_socket_activation: bool = "systemd-socket-activation", true;
+    log_filter: String = "log-filter", "info".to_string();
+    log_to_file: bool = "log-to-file", true;
+    log_max_file_size_mb: u64 = "log-max-file-size-mb", 10;
+    resource_pack: String = "resource-pack", String::new();
+    resource_pack_sha1: String = "resource-pack-sha1", String::new();
+    resource_pack_id: String = "resource-pack-id", String::new();
+    resource_pack_prompt: String = "resource-pack-prompt", String::new();
+    require_resource_pack: bool = "require-resource-pack", false;
+    // Chat, Beitritte und Tode gehen an den Webhook. Nachrichten aus dem Kanal kommen nur mit Bot-Token und Kanal-ID
+    // in den Spielchat; ohne Webhook sendet der Bot auch selbst
+    discord_webhook_url: String = "discord-webhook-url", String::new();
+    discord_bot_token: String = "discord-bot-token", String::new();
+    discord_channel_id: String = "discord-channel-id", String::new();
+    // Platzhalter: {name}, {message}; für Nachrichten aus Discord im Spiel
+    discord_chat_format: String = "discord-chat-format", "&9[Discord] &r<{name}> {message}".to_string();
+    // Platzhalter: {name}, {message}; für Chat nach Discord, wenn der Bot statt eines Webhooks sendet
+    discord_relay_format: String = "discord-relay-format", "**{name}**: {message}".to_string();
+    discord_poll_interval_secs: u64 = "discord-poll-interval", 3;
+}
+
+fn parse_properties(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .collect()
+}
+
+impl ServerConfig {
+    // Lädt server.properties und schreibt die Datei mit allen bekannten Schlüsseln zurück
+    pub fn load(path: &str) -> ServerConfig {
+        let mut config = ServerConfig::default();
+        let values = match fs::read_to_string(path) {
+            Ok(text) => parse_properties(&text),
+            Err(_) => {
+                info!("{} nicht gefunden, erstelle Standardkonfiguration", path);
+                HashMap::new()
+            }
+        };
+        config.apply(&values);
+        if let Err(e) = config.save(path, &values) {
+            warn!("Konnte {} nicht schreiben: {}", path, e);
+        }
+        config
+    }
+
+    fn save(&self, path: &str, previous: &HashMap<String, String>) -> std::io::Result<()> {
+        let entries = self.entries();
+        let mut text = String::from("#Minecraft server properties\n");
+        for (key, value) in &entries {
+            text.push_str(&format!("{}={}\n", key, value));
+        }
+        // Unbekannte Schlüssel (z.B. von Plugins) beibehalten
+        let mut unknown: Vec<_> = previous.iter().filter(|(k, _)| !entries.iter().any(|(key, _)| key == k)).collect();
+        unknown.sort();
+        for (key, value) in unknown {
+            text.push_str(&format!("{}={}\n", key, value));
+        }
+        fs::write(path, text)
+    }
+}
@@ -0,0 +1,329 @@
+use std::io::Cursor;
+use std::net::TcpStream;
+use crate::dimension::{self, DimensionType};
+use crate::json::Json;
+use crate::protocol::types::{read_string_from_cursor, read_varint_from_cursor, write_string_to_vec, write_varint_to_vec};
+use crate::protocol::version::ProtocolVersion;
+use crate::protocol::{clientbound, framing, packets, serverbound, ConnectionState, ProtocolError};
+use crate::settings::ClientSettings;
+use crate::text::Text;
+use crate::{channel, encode_packet, item, metrics, nbt, worldgen, write_packet, Player};
+
+// Den Namespace minecraft:core teilen Client und Server, solange beide dieselbe Version sprechen
+const CORE_PACK: &str = "minecraft";
+const CORE_PACK_ID: &str = "core";
+const FEATURES: &[&str] = &["minecraft:vanilla"];
+const MISSING_CORE_MESSAGE: &str = "Your client does not know the vanilla data pack of its version.";
+
+const CHAT_TYPES: &[(&str, &str)] = &[
+    ("minecraft:chat", "chat.type.text"),
+    ("minecraft:emote_command", "chat.type.emote"),
+    ("minecraft:msg_command_incoming", "commands.message.display.incoming"),
+    ("minecraft:msg_command_outgoing", "commands.message.display.outgoing"),
+    ("minecraft:say_command", "chat.type.announcement"),
+    ("minecraft:team_msg_command_incoming", "chat.type.team.text"),
+    ("minecraft:team_msg_command_outgoing", "chat.type.team.sent"),
+];
+// Alle Schadensarten bis 1.21.1. Der Client schlägt viele davon fest nach; damit keiner fehlt und keiner zu viel
+// ist, gehen sie immer mit Daten raus. Todesnachrichten baut der Server selbst, message_id ist daher nur der Name
+const DAMAGE_TYPES: &[&str] = &[
+    "arrow", "bad_respawn_point", "cactus", "campfire", "cramming", "dragon_breath", "drown", "dry_out", "ender_pearl",
+    "explosion", "fall", "falling_anvil", "falling_block", "falling_stalactite", "fireball", "fireworks", "fly_into_wall",
+    "freeze", "generic", "generic_kill", "hot_floor", "in_fire", "in_wall", "indirect_magic", "lava", "lightning_bolt",
+    "mace_smash", "magic", "mob_attack", "mob_attack_no_aggro", "mob_projectile", "on_fire", "out_of_world",
+    "outside_border", "player_attack", "player_explosion", "sonic_boom", "spit", "stalagmite", "starve", "sting",
+    "sweet_berry_bush", "thorns", "thrown", "trident", "unattributed_fireball", "wind_charge", "wither", "wither_skull",
+];
+const WOLF_VARIANTS: &[&str] = &["ashen", "black", "chestnut", "pale", "rusty", "snowy", "spotted", "striped", "woods"];
+// flow und guster kamen mit 1.21
+const BANNER_PATTERNS: &[(&str, bool)] = &[
+    ("base", false), ("border", false), ("bricks", false), ("circle", false), ("creeper", false), ("cross", false),
+    ("curly_border", false), ("diagonal_left", false), ("diagonal_right", false), ("diagonal_up_left", false),
+    ("diagonal_up_right", false), ("flow", true), ("flower", false), ("globe", false), ("gradient", false),
+    ("gradient_up", false), ("guster", true), ("half_horizontal", false), ("half_horizontal_bottom", false),
+    ("half_vertical", false), ("half_vertical_right", false), ("mojang", false), ("piglin", false), ("rhombus", false),
+    ("skull", false), ("small_stripes", false), ("square_bottom_left", false), ("square_bottom_right", false),
+    ("square_top_left", false), ("square_top_right", false), ("straight_cross", false), ("stripe_bottom", false),
+    ("stripe_center", false), ("stripe_downleft", false), ("stripe_downright", false), ("stripe_left", false),
+    ("stripe_middle", false), ("stripe_right", false), ("stripe_top", false), ("triangle_bottom", false),
+    ("triangle_top", false), ("triangles_bottom", false), ("triangles_top", false),
+];
+// Gemälde, Schallplatten und Verzauberungen sind erst seit 1.21 datengetrieben
+const PAINTING_VARIANTS: &[&str] = &[
+    "alban", "aztec", "aztec2", "backyard", "baroque", "bomb", "bouquet", "burning_skull", "bust", "cavebird", "changing",
+    "cotan", "courbet", "creebet", "donkey_kong", "earth", "endboss", "fern", "fighters", "finding", "fire", "graham",
+    "humble", "kebab", "lowmist", "match", "meditative", "orb", "owlemons", "passage", "pigscene", "plant", "pointer",
+    "pond", "pool", "prairie_ride", "sea", "skeleton", "skull_and_roses", "stage", "sunflowers", "sunset", "tides",
+    "unpacked", "void", "wanderer", "wasteland", "water", "wind", "wither",
+];
+const JUKEBOX_SONGS: &[&str] = &[
+    "11", "13", "5", "blocks", "cat", "chirp", "creator", "creator_music_box", "far", "mall", "mellohi", "otherside",
+    "pigstep", "precipice", "relic", "stal", "strad", "wait", "ward",
+];
+
+fn object(entries: Vec<(&str, Json)>) -> Json {
+    Json::Object(entries.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn namespaced(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| format!("minecraft:{}", name)).collect()
+}
+
+// Ein Register mit Name, Eintrag und (bei 1.20.5+ optional) den Daten des Eintrags
+struct Registry {
+    name: &'static str,
+    entries: Vec<(String, Option<Json>)>,
+}
+
+impl Registry {
+    fn known(name: &'static str, entries: Vec<String>) -> Registry {
+        Registry { name, entries: entries.into_iter().map(|entry| (entry, None)).collect() }
+    }
+
+    // Inhalt von Registry Data ab 1.20.5
+    fn encode(&self) -> Vec<u8> {
+        let mut data = write_string_to_vec(self.name);
+        data.extend(write_varint_to_vec(self.entries.len() as i32));
+        for (entry, element) in &self.entries {
+            data.extend(write_string_to_vec(entry));
+            data.push(element.is_some() as u8);
+            if let Some(element) = element {
+                data.extend(nbt::from_json(element));
+            }
+        }
+        data
+    }
+
+    // Abschnitt im Registry-Codec von 1.20.4, der jeden Eintrag mit Daten braucht
+    fn codec(&self) -> Json {
+        let value = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, (entry, element))| {
+                object(vec![("name", Json::String(entry.clone())), ("id", Json::Number(id as f64)), ("element", element.clone().unwrap_or(Json::Object(Default::default())))])
+            })
+            .collect();
+        object(vec![("type", Json::String(self.name.to_string())), ("value", Json::Array(value))])
+    }
+}
+
+// Wie beim Client von Vanilla; eigene Typen aus Datenpaketen kennt er nur mit diesen Daten
+fn dimension_type_element(kind: &DimensionType) -> Json {
+    let infiniburn = match kind.effects {
+        "minecraft:the_nether" => "#minecraft:infiniburn_nether",
+        "minecraft:the_end" => "#minecraft:infiniburn_end",
+        _ => "#minecraft:infiniburn_overworld",
+    };
+    object(vec![
+        ("has_skylight", Json::Bool(kind.has_skylight)),
+        ("has_ceiling", Json::Bool(kind.has_ceiling)),
+        ("ultrawarm", Json::Bool(kind.ultrawarm)),
+        ("natural", Json::Bool(kind.has_skylight)),
+        ("coordinate_scale", Json::Number(if kind.ultrawarm { 8.0 } else { 1.0 })),
+        ("bed_works", Json::Bool(kind.bed_works)),
+        ("respawn_anchor_works", Json::Bool(kind.ultrawarm)),
+        ("min_y", Json::Number(kind.min_y as f64)),
+        ("height", Json::Number(kind.height as f64)),
+        ("logical_height", Json::Number(kind.logical_height as f64)),
+        ("infiniburn", Json::String(infiniburn.to_string())),
+        ("effects", Json::String(kind.effects.to_string())),
+        ("ambient_light", Json::Number(kind.ambient_light as f64)),
+        ("piglin_safe", Json::Bool(kind.ultrawarm)),
+        ("has_raids", Json::Bool(!kind.ultrawarm)),
+        ("monster_spawn_light_level", Json::Number(0.0)),
+        ("monster_spawn_block_light_limit", Json::Number(if kind.ultrawarm { 15.0 } else { 0.0 })),
+    ])
+}
+
+// Farben wie in den Ebenen; 1.20.4 zeigt damit jedes Biom gleich an
+fn biome_element(biome: &str) -> Json {
+    let effects = object(vec![
+        ("sky_color", Json::Number(7907327.0)),
+        ("water_fog_color", Json::Number(329011.0)),
+        ("fog_color", Json::Number(12638463.0)),
+        ("water_color", Json::Number(4159204.0)),
+    ]);
+    object(vec![
+        ("has_precipitation", Json::Bool(worldgen::has_precipitation(biome))),
+        ("temperature", Json::Number(worldgen::temperature(biome) as f64)),
+        ("downfall", Json::Number(0.4)),
+        ("effects", effects),
+    ])
+}
+
+fn chat_type_element(translation_key: &str) -> Json {
+    let decoration = |key: &str| {
+        object(vec![
+            ("translation_key", Json::String(key.to_string())),
+            ("parameters", Json::Array(vec![Json::String("sender".to_string()), Json::String("content".to_string())])),
+        ])
+    };
+    object(vec![("chat", decoration(translation_key)), ("narration", decoration("chat.type.text.narrate"))])
+}
+
+fn damage_type_element(name: &str) -> Json {
+    object(vec![
+        ("message_id", Json::String(name.to_string())),
+        ("scaling", Json::String("when_caused_by_living_non_player".to_string())),
+        ("exhaustion", Json::Number(0.1)),
+    ])
+}
+
+fn translatable(key: String) -> Json {
+    object(vec![("translate", Json::String(key))])
+}
+
+// Register in der Reihenfolge, in der der Server ihre IDs ohnehin schon verwendet
+fn registries(player: &Player) -> Vec<Registry> {
+    let version = player.connection.version;
+    let with_data = !version.has_known_packs();
+    let data = |element: Json| with_data.then_some(element);
+    let dimension_types = dimension::all_types()
+        .into_iter()
+        .map(|kind| (kind.name.to_string(), (with_data || !kind.is_builtin()).then(|| dimension_type_element(kind))))
+        .collect();
+    let biomes = worldgen::BIOMES.iter().map(|biome| (format!("minecraft:{}", biome), data(biome_element(biome)))).collect();
+    let chat_types = CHAT_TYPES.iter().map(|(name, key)| (name.to_string(), data(chat_type_element(key)))).collect();
+    let damage_types = DAMAGE_TYPES.iter().map(|name| (format!("minecraft:{}", name), Some(damage_type_element(name)))).collect();
+    let latest = version >= ProtocolVersion::V1_21;
+    let trim_patterns = item::TRIM_PATTERNS
+        .iter()
+        .filter(|(_, _, v1_20_5)| latest || v1_20_5.is_some())
+        .map(|(name, _, _)| {
+            let path = name.trim_start_matches("minecraft:");
+            let element = object(vec![
+                ("asset_id", Json::String(name.to_string())),
+                ("template_item", Json::String(format!("minecraft:{}_armor_trim_smithing_template", path))),
+                ("description", translatable(format!("trim_pattern.minecraft.{}", path))),
+            ]);
+            (name.to_string(), data(element))
+        })
+        .collect();
+    let trim_materials = item::TRIM_MATERIALS
+        .iter()
+        .enumerate()
+        .map(|(index, (name, ingredient))| {
+            let path = name.trim_start_matches("minecraft:");
+            let element = object(vec![
+                ("asset_name", Json::String(path.to_string())),
+                ("ingredient", Json::String(ingredient.to_string())),
+                ("item_model_index", Json::Number((index + 1) as f64 / 10.0)),
+                ("description", translatable(format!("trim_material.minecraft.{}", path))),
+            ]);
+            (name.to_string(), data(element))
+        })
+        .collect();
+    let mut registries = vec![
+        Registry { name: "minecraft:dimension_type", entries: dimension_types },
+        Registry { name: "minecraft:worldgen/biome", entries: biomes },
+        Registry { name: "minecraft:chat_type", entries: chat_types },
+        Registry { name: "minecraft:damage_type", entries: damage_types },
+        Registry { name: "minecraft:trim_pattern", entries: trim_patterns },
+        Registry { name: "minecraft:trim_material", entries: trim_materials },
+    ];
+    if version.has_known_packs() {
+        registries.push(Registry::known("minecraft:wolf_variant", namespaced(WOLF_VARIANTS)));
+        let banner_patterns: Vec<&str> = BANNER_PATTERNS.iter().filter(|(_, v1_21)| latest || !v1_21).map(|(name, _)| *name).collect();
+        registries.push(Registry::known("minecraft:banner_pattern", namespaced(&banner_patterns)));
+    }
+    if latest {
+        registries.push(Registry::known("minecraft:painting_variant", namespaced(PAINTING_VARIANTS)));
+        registries.push(Registry::known("minecraft:jukebox_song", namespaced(JUKEBOX_SONGS)));
+        registries.push(Registry::known("minecraft:enchantment", item::enchantment_names().map(str::to_string).collect()));
+    }
+    registries
+}
+
+fn send(stream: &mut TcpStream, player: &Player, packet_id: i32, data: &[u8]) -> Result<(), String> {
+    let wire_id = player.connection.version.configuration_clientbound(packet_id);
+    write_packet(stream, wire_id, &encode_packet(wire_id, data, player.connection.compression))
+}
+
+// Ab Set Compression kommt auch Login Acknowledged schon komprimiert
+fn read(stream: &mut TcpStream, player: &Player, state: ConnectionState) -> Result<(i32, Cursor<Vec<u8>>), String> {
+    let (body, size) = framing::read_frame(stream, framing::max_packet_length(state), player.connection.compression).map_err(|e| ProtocolError::from_stream(e).to_string())?;
+    metrics::TOTAL.record_in(size);
+    packets::split_id(body).map_err(|e| e.to_string())
+}
+
+fn send_known_packs(stream: &mut TcpStream, player: &Player) -> Result<(), String> {
+    let mut data = write_varint_to_vec(1);
+    data.extend(write_string_to_vec(CORE_PACK));
+    data.extend(write_string_to_vec(CORE_PACK_ID));
+    data.extend(write_string_to_vec(player.connection.version.name()));
+    send(stream, player, clientbound::KNOWN_PACKS, &data)
+}
+
+// Nur wenn der Client minecraft:core in seiner Version kennt, dürfen Einträge ohne Daten kommen
+fn knows_core(cursor: &mut Cursor<Vec<u8>>, player: &Player) -> Result<bool, ProtocolError> {
+    let count = read_varint_from_cursor(cursor)?;
+    let mut core = false;
+    for _ in 0..count.clamp(0, 64) {
+        let (namespace, id, version) = (read_string_from_cursor(cursor)?, read_string_from_cursor(cursor)?, read_string_from_cursor(cursor)?);
+        core |= namespace == CORE_PACK && id == CORE_PACK_ID && version == player.connection.version.name();
+    }
+    Ok(core)
+}
+
+fn send_registries(stream: &mut TcpStream, player: &Player) -> Result<(), String> {
+    let registries = registries(player);
+    if player.connection.version.has_known_packs() {
+        for registry in &registries {
+            send(stream, player, clientbound::REGISTRY_DATA, &registry.encode())?;
+        }
+        return Ok(());
+    }
+    let codec = Json::Object(registries.iter().map(|registry| (registry.name.to_string(), registry.codec())).collect());
+    send(stream, player, clientbound::REGISTRY_DATA, &nbt::from_json(&codec))
+}
+
+// Zwischen Login Success und Join Game: Brand, Feature-Flags, Register, dann Finish Configuration. Brand, Kanäle
+// und Einstellungen, die der Client dabei schickt, landen direkt im noch nicht eingetragenen Spieler
+pub fn configure(stream: &mut TcpStream, player: &mut Player) -> Result<(), String> {
+    let (packet_id, _) = read(stream, player, ConnectionState::Login)?;
+    if packet_id != serverbound::LOGIN_ACKNOWLEDGED {
+        return Err(format!("Login Acknowledged erwartet, Paket-ID {} erhalten", packet_id));
+    }
+    send(stream, player, clientbound::CONFIGURATION_CUSTOM_PAYLOAD, &[write_string_to_vec(channel::BRAND), write_string_to_vec(channel::SERVER_BRAND)].concat())?;
+    let mut features = write_varint_to_vec(FEATURES.len() as i32);
+    for feature in FEATURES {
+        features.extend(write_string_to_vec(feature));
+    }
+    send(stream, player, clientbound::FEATURE_FLAGS, &features)?;
+    if player.connection.version.has_known_packs() {
+        send_known_packs(stream, player)?;
+    } else {
+        send_registries(stream, player)?;
+        send(stream, player, clientbound::FINISH_CONFIGURATION, &[])?;
+    }
+    loop {
+        let (packet_id, mut cursor) = read(stream, player, ConnectionState::Configuration)?;
+        match player.connection.version.configuration_serverbound(packet_id) {
+            serverbound::KNOWN_PACKS => {
+                if !knows_core(&mut cursor, player).map_err(|e| e.to_string())? {
+                    let _ = send(stream, player, clientbound::CONFIGURATION_DISCONNECT, &Text::new(MISSING_CORE_MESSAGE).to_nbt());
+                    return Err("Client kennt minecraft:core nicht".to_string());
+                }
+                send_registries(stream, player)?;
+                send(stream, player, clientbound::FINISH_CONFIGURATION, &[])?;
+            }
+            serverbound::CONFIGURATION_CLIENT_INFORMATION => player.settings = ClientSettings::read(&mut cursor).map_err(|e| e.to_string())?,
+            serverbound::CONFIGURATION_CUSTOM_PAYLOAD => {
+                let channel = read_string_from_cursor(&mut cursor).map_err(|e| e.to_string())?;
+                if channel == channel::BRAND {
+                    let brand = read_string_from_cursor(&mut cursor).map_err(|e| e.to_string())?;
+                    info!("{} nutzt den Client \"{}\"", player.username, brand);
+                    player.brand = Some(brand);
+                } else if channel == channel::REGISTER {
+                    let position = cursor.position() as usize;
+                    let channels = channel::decode_channel_list(&cursor.get_ref()[position..]);
+                    player.channels.extend(channels.into_iter().take(channel::MAX_CLIENT_CHANNELS));
+                }
+            }
+            serverbound::ACKNOWLEDGE_FINISH_CONFIGURATION => return Ok(()),
+            serverbound::CONFIGURATION_KEEP_ALIVE | serverbound::CONFIGURATION_PONG | serverbound::CONFIGURATION_RESOURCE_PACK_RESPONSE => {}
+            _ => return Err(ProtocolError::Unexpected { state: ConnectionState::Configuration, id: packet_id }.to_string()),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::os::fd::FromRawFd;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crate::command;
+use crate::json::Json;
+use crate::websocket::{self, Frame};
+use crate::Server;
+
+// Zeilen, die neue Sitzungen beim Verbinden nachgeliefert bekommen
+const HISTORY_LINES: usize = 100;
+// Puffer pro Sitzung; ist er voll, werden Zeilen verworfen und gezählt statt den Server zu bremsen
+const SESSION_QUEUE: usize = 1024;
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Permission {
+    // Nur Log mitlesen
+    Read,
+    // Log mitlesen und Befehle ausführen
+    Execute,
+}
+
+struct Subscriber {
+    sender: SyncSender<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+pub struct LogHub {
+    history: Mutex<VecDeque<String>>,
+    subscribers: Mutex<Vec<Subscriber>>,
+    tee: Mutex<Option<(JoinHandle<()>, i32)>>,
+}
+
+pub static LOG: LogHub = LogHub {
+    history: Mutex::new(VecDeque::new()),
+    subscribers: Mutex::new(Vec::new()),
+    tee: Mutex::new(None),
+};
+
+impl LogHub {
+    fn publish(&self, line: String) {
+        self.subscribers.lock().unwrap().retain(|s| match s.sender.try_send(line.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                s.dropped.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+        let mut history = self.history.lock().unwrap();
+        if history.len() == HISTORY_LINES {
+            history.pop_front();
+        }
+        history.push_back(line);
+    }
+
+    fn subscribe(&self) -> (Receiver<String>, Arc<AtomicU64>) {
+        let (sender, receiver) = mpsc::sync_channel(SESSION_QUEUE);
+        let dropped = Arc::new(AtomicU64::new(0));
+        // Unter der Subscriber-Sperre, damit zwischen Verlauf und Live-Zeilen nichts verloren geht
+        let mut subscribers = self.subscribers.lock().unwrap();
+        for line in self.history.lock().unwrap().iter() {
+            let _ = sender.try_send(line.clone());
+        }
+        subscribers.push(Subscriber { sender, dropped: Arc::clone(&dropped) });
+        (receiver, dropped)
+    }
+
+    // Leitet stdout über eine Pipe um, damit alle Logzeilen auch an die Konsolen-Sitzungen gehen.
+    // Ein eigener Thread schreibt jede Zeile weiter auf das ursprüngliche stdout
+    pub fn capture_stdout(&'static self) -> Result<(), String> {
+        let mut fds = [0; 2];
+        let original = unsafe {
+            if libc::pipe(fds.as_mut_ptr()) != 0 {
+                return Err("Konnte keine Pipe für stdout anlegen".to_string());
+            }
+            let original = libc::dup(libc::STDOUT_FILENO);
+            if original < 0 || libc::dup2(fds[1], libc::STDOUT_FILENO) < 0 {
+                libc::close(fds[0]);
+                libc::close(fds[1]);
+                return Err("Konnte stdout nicht umleiten".to_string());
+            }
+            libc::close(fds[1]);
+            original
+        };
+        let pipe = unsafe { File::from_raw_fd(fds[0]) };
+        let mut terminal = unsafe { File::from_raw_fd(libc::dup(original)) };
+        let handle = thread::spawn(move || {
+            for line in BufReader::new(pipe).split(b'\n') {
+                let Ok(mut line) = line else { break };
+                line.push(b'\n');
+                let _ = terminal.write_all(&line);
+                line.pop();
+                self.publish(String::from_utf8_lossy(&line).into_owned());
+            }
+        });
+        *self.tee.lock().unwrap() = Some((handle, original));
+        Ok(())
+    }
+
+    // Stellt stdout wieder her und wartet, bis alle bereits geschriebenen Zeilen weitergegeben sind.
+    // Muss vor process::exit laufen, sonst gehen die letzten Logzeilen verloren
+    pub fn release_stdout(&self) {
+        let Some((handle, original)) = self.tee.lock().unwrap().take() else { return };
+        let _ = std::io::stdout().flush();
+        unsafe {
+            libc::dup2(original, libc::STDOUT_FILENO);
+            libc::close(original);
+        }
+        let _ = handle.join();
+    }
+}
+
+fn message(kind: &str, fields: Vec<(&str, Json)>) -> String {
+    let mut object = BTreeMap::new();
+    object.insert("type".to_string(), Json::String(kind.to_string()));
+    for (key, value) in fields {
+        object.insert(key.to_string(), value);
+    }
+    Json::Object(object).to_json_string()
+}
+
+fn send_text(stream: &Mutex<TcpStream>, text: &str) -> std::io::Result<()> {
+    websocket::write_frame(&mut *stream.lock().unwrap(), websocket::OPCODE_TEXT, text.as_bytes())
+}
+
+// Läuft nach dem WebSocket-Handshake im Verbindungs-Thread der REST-API, bis der Client trennt.
+// Ein zweiter Thread schreibt die Logzeilen, dieser liest Befehle
+pub fn run_session(server: &Server, stream: TcpStream, permission: Permission) {
+    let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+    let _ = stream.set_read_timeout(None);
+    let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+    let Ok(mut reader) = stream.try_clone() else { return };
+    let writer = Arc::new(Mutex::new(stream));
+    info!("Konsolen-Sitzung von {} geöffnet ({:?})", peer, permission);
+
+    let level = if permission == Permission::Execute { "execute" } else { "read" };
+    if send_text(&writer, &message("hello", vec![("permission", Json::String(level.to_string()))])).is_err() {
+        return;
+    }
+    let (lines, dropped) = LOG.subscribe();
+    let closed = Arc::new(AtomicBool::new(false));
+    let log_writer = {
+        let (writer, closed) = (Arc::clone(&writer), Arc::clone(&closed));
+        thread::spawn(move || {
+            while !closed.load(Ordering::Relaxed) {
+                let line = match lines.recv_timeout(Duration::from_secs(1)) {
+                    Ok(line) => line,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                let skipped = dropped.swap(0, Ordering::Relaxed);
+                if skipped > 0 && send_text(&writer, &message("dropped", vec![("count", Json::Number(skipped as f64))])).is_err() {
+                    break;
+                }
+                if send_text(&writer, &message("log", vec![("line", Json::String(line))])).is_err() {
+                    break;
+                }
+            }
+            closed.store(true, Ordering::Relaxed);
+        })
+    };
+
+    while !closed.load(Ordering::Relaxed) {
+        let result = match websocket::read_frame(&mut reader) {
+            Ok(Frame::Text(line)) if line.trim().is_empty() => Ok(()),
+            Ok(Frame::Text(line)) => run_command(server, &writer, &peer, permission, &line),
+            Ok(Frame::Ping(payload)) => websocket::write_frame(&mut *writer.lock().unwrap(), websocket::OPCODE_PONG, &payload),
+            Ok(Frame::Pong | Frame::Binary) => Ok(()),
+            Ok(Frame::Close) => {
+                let _ = websocket::write_frame(&mut *writer.lock().unwrap(), websocket::OPCODE_CLOSE, &[]);
+                break;
+            }
+            Err(e) => {
+                warn!("Konsolen-Sitzung von {}: {}", peer, e);
+                break;
+            }
+        };
+        if result.is_err() {
+            break;
+        }
+    }
+    closed.store(true, Ordering::Relaxed);
+    let _ = writer.lock().unwrap().shutdown(std::net::Shutdown::Both);
+    let _ = log_writer.join();
+    info!("Konsolen-Sitzung von {} geschlossen", peer);
+}
+
+fn run_command(server: &Server, writer: &Mutex<TcpStream>, peer: &str, permission: Permission, line: &str) -> std::io::Result<()> {
+    if permission != Permission::Execute {
+        return send_text(writer, &message("error", vec![("message", Json::String("This session is read-only".to_string()))]));
+    }
+    info!("Konsole von {} führt Befehl aus: /{}", peer, line.trim().trim_start_matches('/'));
+    let (output, completed) = command::run_remote(server, line, COMMAND_TIMEOUT);
+    for reply in output {
+        send_text(writer, &message("reply", vec![("line", Json::String(reply))]))?;
+    }
+    send_text(writer, &message("done", vec![("completed", Json::Bool(completed))]))
+}
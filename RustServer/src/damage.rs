@@ -0,0 +1,324 @@
+use uuid::Uuid;
+use crate::experience::{self, Experience};
+use crate::banlist::{self, Ban};
+use crate::dimension::{self, Dimension};
+use crate::gamemode::{self, GameMode};
+use crate::hunger::Hunger;
+use crate::pose::PoseState;
+use crate::{animation, end, entity, fluid, gamerule, hunger, shield, sleep, totem, window};
+use crate::protocol::clientbound;
+use crate::selector::Target;
+use crate::text::Text;
+use crate::protocol::types::write_varint_to_vec;
+use crate::{broadcast_localized, kick_player, loot, Player, Server, SpawnPoint};
+
+pub const MAX_HEALTH: f32 = 20.0;
+const HARDCORE_BAN_REASON: &str = "Death in Hardcore";
+
+// Was im Hardcore-Modus nach dem Tod passiert: weiter als Zuschauer oder Bann
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HardcoreDeath {
+    Spectator,
+    Ban,
+}
+
+impl std::str::FromStr for HardcoreDeath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<HardcoreDeath, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "spectator" => Ok(HardcoreDeath::Spectator),
+            "ban" => Ok(HardcoreDeath::Ban),
+            other => Err(format!("Unknown hardcore death action '{}'", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for HardcoreDeath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            HardcoreDeath::Spectator => "spectator",
+            HardcoreDeath::Ban => "ban",
+        })
+    }
+}
+
+// Schadensarten mit ihrer Todesnachricht; bisher nur, was /kill, Nahkampf, Pfeile, Stürze, Ertrinken, Hunger, Feuer,
+// der Drachenkampf und Schadenstränke brauchen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DamageSource {
+    OutOfWorld,
+    PlayerAttack,
+    Arrow,
+    Dragon,
+    Explosion,
+    Fall,
+    Drown,
+    Starve,
+    // Im Feuerblock, brennend und in Lava
+    InFire,
+    OnFire,
+    Lava,
+    // Schadenstränke
+    Magic,
+}
+
+impl DamageSource {
+    pub fn death_message(self, victim: &str) -> Text {
+        match self {
+            DamageSource::OutOfWorld => Text::translate("death.attack.outOfWorld").with(victim),
+            DamageSource::PlayerAttack => Text::translate("server.death.slain").with(victim),
+            DamageSource::Arrow => Text::translate("server.death.shot").with(victim),
+            DamageSource::Dragon => Text::translate("death.attack.mob").with(victim).with(Text::translate("entity.minecraft.ender_dragon")),
+            DamageSource::Explosion => Text::translate("death.attack.explosion").with(victim),
+            DamageSource::Fall => Text::translate("death.attack.fall").with(victim),
+            DamageSource::Drown => Text::translate("death.attack.drown").with(victim),
+            DamageSource::Starve => Text::translate("death.attack.starve").with(victim),
+            DamageSource::InFire => Text::translate("death.attack.inFire").with(victim),
+            DamageSource::OnFire => Text::translate("death.attack.onFire").with(victim),
+            DamageSource::Lava => Text::translate("death.attack.lava").with(victim),
+            DamageSource::Magic => Text::translate("death.attack.magic").with(victim),
+        }
+    }
+
+    // Die Leere trifft auch Spieler im Kreativ- und Zuschauermodus, wie /kill
+    fn bypasses_invulnerability(self) -> bool {
+        match self {
+            DamageSource::OutOfWorld => true,
+            DamageSource::PlayerAttack | DamageSource::Arrow | DamageSource::Dragon | DamageSource::Explosion => false,
+            DamageSource::Fall | DamageSource::Drown | DamageSource::Starve => false,
+            DamageSource::InFire | DamageSource::OnFire | DamageSource::Lava | DamageSource::Magic => false,
+        }
+    }
+
+    // Nur Treffer und Feuer machen hungriger, nicht Stürze, Ersticken, die Leere, Tränke oder der Hunger selbst
+    fn exhaustion(self) -> f32 {
+        match self {
+            DamageSource::OutOfWorld | DamageSource::Fall | DamageSource::Drown | DamageSource::Starve | DamageSource::Magic => 0.0,
+            DamageSource::PlayerAttack | DamageSource::Arrow | DamageSource::Dragon | DamageSource::Explosion => hunger::DAMAGE_EXHAUSTION,
+            DamageSource::InFire | DamageSource::OnFire | DamageSource::Lava => hunger::DAMAGE_EXHAUSTION,
+        }
+    }
+
+    // Angriffe von Mobs und Explosionen hängen vom Schwierigkeitsgrad ab
+    fn scales_with_difficulty(self) -> bool {
+        match self {
+            DamageSource::OutOfWorld | DamageSource::PlayerAttack | DamageSource::Arrow => false,
+            DamageSource::Fall | DamageSource::Drown | DamageSource::Starve => false,
+            DamageSource::InFire | DamageSource::OnFire | DamageSource::Lava | DamageSource::Magic => false,
+            DamageSource::Dragon | DamageSource::Explosion => true,
+        }
+    }
+}
+
+// Ergebnis eines Treffers; Rückstoß gibt es nur für Ziele, die Schaden genommen und überlebt haben
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hit {
+    Ignored,
+    Hurt,
+    Killed,
+}
+
+// Zieht einem Spieler oder Mob Lebenspunkte ab. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn damage(server: &Server, target: &Target, source: DamageSource, amount: f32) -> Hit {
+    hit(server, target, source, amount, None)
+}
+
+// Wie damage, aber für Treffer aus einer Richtung, die ein erhobener Schild abwehren kann
+pub fn damage_from(server: &Server, target: &Target, source: DamageSource, amount: f32, origin: (f64, f64, f64)) -> Hit {
+    hit(server, target, source, amount, Some(origin))
+}
+
+fn hit(server: &Server, target: &Target, source: DamageSource, amount: f32, origin: Option<(f64, f64, f64)>) -> Hit {
+    if target.is_player() {
+        damage_player(server, target.uuid, source, amount, origin)
+    } else {
+        damage_mob(server, target.uuid, amount)
+    }
+}
+
+fn damage_player(server: &Server, uuid: Uuid, source: DamageSource, amount: f32, origin: Option<(f64, f64, f64)>) -> Hit {
+    let mut players = server.players.lock().unwrap();
+    let Some(index) = players.iter().position(|p| p.uuid == uuid) else { return Hit::Ignored };
+    let player = &mut players[index];
+    // Tote Spieler warten auf ihren Respawn
+    if player.health <= 0.0 || (player.game_mode.is_invulnerable() && !source.bypasses_invulnerability()) {
+        return Hit::Ignored;
+    }
+    let dimension = player.dimension;
+    let (rules, difficulty, pvp) = {
+        let world = dimension::world_of(server, dimension).lock().unwrap();
+        (world.rules.clone(), world.difficulty, world.settings.pvp)
+    };
+    if matches!(source, DamageSource::PlayerAttack | DamageSource::Arrow) && !pvp {
+        return Hit::Ignored;
+    }
+    let amount = if source.scales_with_difficulty() { difficulty.scale_damage(amount) } else { amount };
+    if amount <= 0.0 || origin.is_some_and(|origin| shield::block(&mut players, index, origin, amount)) {
+        return Hit::Ignored;
+    }
+    let player = &mut players[index];
+    let absorbed = amount.min(player.absorption);
+    if absorbed > 0.0 {
+        player.absorption -= absorbed;
+        let _ = entity::send_absorption(&player.connection, entity::OWN_PLAYER_ID, player.absorption);
+    }
+    player.health = (player.health - (amount - absorbed)).max(0.0);
+    // Wie bei Vanilla zählt der Treffer mit f32::MAX von /kill nicht
+    let taken = amount - absorbed;
+    if taken > 0.0 && taken < f32::MAX {
+        let name = player.username.clone();
+        server.scoreboard.lock().unwrap().award(&players, &name, &["minecraft.custom:minecraft.damage_taken"], (taken * 10.0).round() as i32);
+    }
+    let player = &mut players[index];
+    player.hunger.exhaust(source.exhaustion());
+    // Vor /kill und der Leere schützt auch kein Totem
+    if player.health <= 0.0 && !source.bypasses_invulnerability() {
+        totem::revive(&mut players, index);
+    }
+    let player = &mut players[index];
+    let _ = send_health(player);
+    // Wer getroffen wird, wacht auf; tot bleibt er liegen, wo er ist
+    if player.pose.bed.is_some() {
+        let stand_up = player.health > 0.0;
+        let mut world = dimension::world_of(server, dimension).lock().unwrap();
+        sleep::wake(&mut players, &mut world, index, stand_up);
+    }
+    let player = &players[index];
+    let (entity_id, position, health) = (player.entity_id, player.position, player.health);
+    for viewer in players.iter().filter(|p| p.uuid != uuid && entity::in_view(p, dimension, position)) {
+        let _ = animation::send_hurt(&viewer.connection, entity_id);
+    }
+    if health > 0.0 {
+        return Hit::Hurt;
+    }
+    // Mauszeiger und offene Fenster gehen zurück ins Inventar, damit sie mit ihm fallen oder bleiben
+    window::close(&mut players, &mut dimension::world_of(server, dimension).lock().unwrap(), index);
+    let player = &mut players[index];
+    let message = source.death_message(&player.username);
+    let name = player.username.clone();
+    let keep = rules.get_bool(gamerule::KEEP_INVENTORY);
+    let drops = if keep { Vec::new() } else { player.inventory.take_all() };
+    let _ = player.inventory.send_all(&player.connection);
+    // Der Respawn erzeugt die Spieler-Entity beim Client neu, dort sind die Effekte dann ohnehin weg
+    player.effects.clear();
+    player.absorption = 0.0;
+    let orbs = if keep { 0 } else { experience::death_drop(&player.experience) };
+    if !keep {
+        player.experience = Experience::default();
+    }
+    let mut packet_data = write_varint_to_vec(entity::OWN_PLAYER_ID);
+    packet_data.extend(message.localize(&server.lang, &player.settings.locale).to_nbt());
+    let _ = player.connection.send(clientbound::COMBAT_DEATH, &packet_data);
+    server.scoreboard.lock().unwrap().award(&players, &name, &["deathCount", "minecraft.custom:minecraft.deaths"], 1);
+    if rules.get_bool(gamerule::SHOW_DEATH_MESSAGES) {
+        broadcast_localized(server, &players, &message);
+        server.discord.notice(&server.lang.plain(&message));
+    } else {
+        info!("{}", server.lang.plain(&message));
+    }
+    drop(players);
+    entity::scatter_items(server, dimension, drops, position);
+    experience::drop_orbs(server, dimension, orbs, position);
+    if server.config.hardcore && server.config.hardcore_death == HardcoreDeath::Ban {
+        if let Err(e) = server.bans.lock().unwrap().add(Ban::new(&name, Some(uuid), HARDCORE_BAN_REASON, "Server")) {
+            warn!("Konnte {} nach dem Tod im Hardcore-Modus nicht bannen: {}", name, e);
+        }
+        kick_player(server, &name, &banlist::ban_message(HARDCORE_BAN_REASON));
+    }
+    Hit::Killed
+}
+
+// Nicht-lebende Entities wie Boote haben keine Lebenspunkte und werden direkt entfernt
+fn damage_mob(server: &Server, id: Uuid, amount: f32) -> Hit {
+    let players = server.players.lock().unwrap();
+    let Some(lock) = dimension::world_with_mob(server, id) else { return Hit::Ignored };
+    let mut world = lock.lock().unwrap();
+    let dimension = world.dimension;
+    let Some(index) = world.mobs.iter().position(|m| m.id == id) else { return Hit::Ignored };
+    let mob = &mut world.mobs[index];
+    let living = entity::is_living(&mob.mob_type);
+    // Ein sterbender Drache nimmt keinen Schaden mehr
+    if living && mob.health <= 0.0 {
+        return Hit::Ignored;
+    }
+    if living {
+        mob.health = (mob.health - amount).max(0.0);
+    }
+    let (entity_id, health, position, dragon) = (mob.entity_id, mob.health, mob.position, mob.mob_type == end::DRAGON);
+    if living && health > 0.0 || dragon && end::dragon_died(&mut world, id) {
+        for player in players.iter().filter(|p| entity::in_view(p, dimension, position)) {
+            let _ = entity::send_health(&player.connection, entity_id, health);
+            let _ = animation::send_hurt(&player.connection, entity_id);
+        }
+        return if health <= 0.0 { Hit::Killed } else { Hit::Hurt };
+    }
+    let mob = world.mobs.remove(index);
+    world.entities.remove(mob.id);
+    let drops = if living && world.rules.get_bool(gamerule::DO_MOB_LOOT) { mob_loot(server, &mob.mob_type) } else { Vec::new() };
+    drop(world);
+    for player in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+        let _ = entity::send_remove(&player.connection, &[mob.entity_id]);
+    }
+    drop(players);
+    for stack in drops {
+        entity::drop_item(server, dimension, stack, mob.position);
+    }
+    if mob.mob_type == end::CRYSTAL && dimension == Dimension::End {
+        end::crystal_destroyed(server, mob.id, mob.position);
+    }
+    Hit::Killed
+}
+
+// Beute aus der Loot-Tabelle des Entity-Typs (minecraft:entities/zombie), sofern ein Datenpaket sie liefert
+fn mob_loot(server: &Server, kind: &str) -> Vec<crate::item::ItemStack> {
+    let (namespace, path) = kind.split_once(':').unwrap_or(("minecraft", kind));
+    let datapacks = server.datapacks.lock().unwrap();
+    datapacks.registries.loot_tables.get(&format!("{}:entities/{}", namespace, path)).map(loot::generate).unwrap_or_default()
+}
+
+// Set Health mit Nahrung und Sättigung; unter 7 Nahrung hört der Client von selbst auf zu sprinten
+pub fn send_health(player: &Player) -> Result<(), String> {
+    let mut packet_data = player.health.to_be_bytes().to_vec();
+    packet_data.extend(write_varint_to_vec(player.hunger.food));
+    packet_data.extend(player.hunger.saturation.to_be_bytes());
+    player.connection.send(clientbound::SET_HEALTH, &packet_data)
+}
+
+// Eigener Spawnpunkt in der Oberwelt, neben dem Bett, falls er darin gesetzt wurde, oder der Weltspawn mit spawnRadius: in benannten Welten deren eigener, sonst
+// der der Oberwelt. Sperrt world
+pub fn spawn_location(server: &Server, personal: Option<SpawnPoint>, died_in: Dimension) -> (Dimension, (f64, f64, f64), f32) {
+    let named = matches!(died_in, Dimension::Named(_));
+    if let (Some(spawn), false) = (personal, named) {
+        let (x, y, z) = spawn.position;
+        // Im Bett gesetzt: daneben aufstehen
+        let bed = sleep::stand_up_position(&dimension::world_of(server, Dimension::Overworld).lock().unwrap(), spawn.position);
+        return (Dimension::Overworld, bed.unwrap_or((x as f64 + 0.5, y as f64, z as f64 + 0.5)), spawn.angle);
+    }
+    let mut world = dimension::world_of(server, if named { died_in } else { Dimension::Overworld }).lock().unwrap();
+    (world.dimension, world.spawn_position(), world.spawn.angle)
+}
+
+// Auf "Respawn" im Todesbildschirm: volle Lebenspunkte und zurück zum eigenen oder zum Weltspawn. Im
+// Hardcore-Modus heißt der Knopf "Zuschauen" und der Spieler macht als Zuschauer weiter
+pub fn respawn(server: &Server, uuid: Uuid) -> Result<(), String> {
+    let mut players = server.players.lock().unwrap();
+    let player = players.iter_mut().find(|p| p.uuid == uuid).ok_or("Player is not online")?;
+    if player.health > 0.0 {
+        return Ok(());
+    }
+    player.health = MAX_HEALTH;
+    player.hunger = Hunger::default();
+    player.air = fluid::MAX_AIR;
+    player.pose = PoseState::default();
+    player.fire_ticks = 0;
+    let (personal, died_in) = (player.spawn_point, player.dimension);
+    let pitch = player.rotation.1;
+    drop(players);
+    let (dimension, position, yaw) = spawn_location(server, personal, died_in);
+    dimension::change_dimension(server, uuid, dimension, position, (yaw, pitch), dimension::KEEP_NOTHING)?;
+    if server.config.hardcore {
+        gamemode::set(server, uuid, GameMode::Spectator)?;
+    }
+    Ok(())
+}
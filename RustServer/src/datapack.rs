@@ -0,0 +1,605 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::function;
+use crate::item::{self, Components, ItemStack};
+use crate::json::{self, Json};
+use crate::recipe::{Ingredient, RecipeRegistry, SmithingRecipe};
+use crate::structure::StructureTemplate;
+use crate::zip;
+
+pub const DATAPACK_DIR: &str = "datapacks";
+pub const VANILLA_PACK: &str = "vanilla";
+// pack_format der Datenpakete für 1.21.1
+pub const PACK_FORMAT: i64 = 48;
+const SELECTION_FILE: &str = "datapacks.json";
+
+#[derive(Debug, Clone)]
+enum PackSource {
+    // Eingebaute Daten des Servers
+    Builtin,
+    Directory(PathBuf),
+    Archive(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct Datapack {
+    // "vanilla" oder "file/<Ordner- bzw. Dateiname>" wie bei Vanilla
+    pub id: String,
+    pub description: String,
+    pub pack_format: i64,
+    source: PackSource,
+}
+
+impl Datapack {
+    pub fn compatible(&self) -> bool {
+        self.pack_format == PACK_FORMAT
+    }
+
+    // Dateien unterhalb von data/, Pfade mit "/" getrennt und ohne das führende "data/"
+    fn data_files(&self) -> Result<Vec<(String, Vec<u8>)>, String> {
+        match &self.source {
+            PackSource::Builtin => Ok(Vec::new()),
+            PackSource::Directory(dir) => {
+                let mut files = Vec::new();
+                collect_files(&dir.join("data"), "", &mut files)?;
+                Ok(files)
+            }
+            PackSource::Archive(path) => {
+                let data = fs::read(path).map_err(|e| format!("Konnte {} nicht lesen: {}", path.display(), e))?;
+                let files = zip::read_archive(&data).map_err(|e| format!("{}: {}", path.display(), e))?;
+                Ok(files.into_iter().filter_map(|(name, content)| Some((name.strip_prefix("data/")?.to_string(), content))).collect())
+            }
+        }
+    }
+}
+
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<(String, Vec<u8>)>) -> Result<(), String> {
+    let Ok(entries) = fs::read_dir(dir) else { return Ok(()) };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            collect_files(&path, &format!("{}/", name), files)?;
+        } else {
+            files.push((name, fs::read(&path).map_err(|e| format!("Konnte {} nicht lesen: {}", path.display(), e))?));
+        }
+    }
+    Ok(())
+}
+
+// Beschreibungen sind Textkomponenten; für Listen reicht der reine Text
+fn plain_text(component: &Json) -> String {
+    match component {
+        Json::String(text) => text.clone(),
+        Json::Array(parts) => parts.iter().map(plain_text).collect(),
+        Json::Object(_) => {
+            let text = component.get("text").and_then(Json::as_str).unwrap_or_default().to_string();
+            let extra = component.get("extra").map(plain_text).unwrap_or_default();
+            text + &extra
+        }
+        other => other.to_json_string(),
+    }
+}
+
+fn read_pack(id: String, source: PackSource, mcmeta: &[u8]) -> Result<Datapack, String> {
+    let meta = json::parse(&String::from_utf8_lossy(mcmeta)).map_err(|e| format!("pack.mcmeta von {}: {}", id, e))?;
+    let pack = meta.get("pack").ok_or_else(|| format!("pack.mcmeta von {} enthält kein \"pack\"", id))?;
+    let pack_format = pack.get("pack_format").and_then(Json::as_f64).ok_or_else(|| format!("pack.mcmeta von {} enthält kein pack_format", id))? as i64;
+    let description = pack.get("description").map(plain_text).unwrap_or_default();
+    Ok(Datapack { id, description, pack_format, source })
+}
+
+// Ordner und .zip-Dateien in world/datapacks, jeweils mit pack.mcmeta
+fn scan(dir: &Path) -> Vec<Datapack> {
+    let mut packs = vec![Datapack {
+        id: VANILLA_PACK.to_string(),
+        description: "The default data for Minecraft".to_string(),
+        pack_format: PACK_FORMAT,
+        source: PackSource::Builtin,
+    }];
+    let Ok(entries) = fs::read_dir(dir) else { return packs };
+    let mut found = Vec::new();
+    for path in entries.flatten().map(|e| e.path()) {
+        let id = format!("file/{}", path.file_name().unwrap().to_string_lossy());
+        let pack = if path.is_dir() {
+            fs::read(path.join("pack.mcmeta"))
+                .map_err(|_| format!("{} enthält keine pack.mcmeta", path.display()))
+                .and_then(|mcmeta| read_pack(id, PackSource::Directory(path), &mcmeta))
+        } else if path.extension().is_some_and(|ext| ext == "zip") {
+            fs::read(&path)
+                .map_err(|e| format!("Konnte {} nicht lesen: {}", path.display(), e))
+                .and_then(|data| zip::read_archive(&data).map_err(|e| format!("{}: {}", path.display(), e)))
+                .and_then(|files| files.into_iter().find(|(name, _)| name == "pack.mcmeta").ok_or_else(|| format!("{} enthält keine pack.mcmeta", path.display())))
+                .and_then(|(_, mcmeta)| read_pack(id, PackSource::Archive(path), &mcmeta))
+        } else {
+            continue;
+        };
+        match pack {
+            Ok(pack) => found.push(pack),
+            Err(e) => warn!("{}", e),
+        }
+    }
+    found.sort_by(|a, b| a.id.cmp(&b.id));
+    packs.extend(found);
+    packs
+}
+
+// Eintrag eines Tags vor der Auflösung: eine ID oder ein Verweis auf ein anderes Tag ("#...")
+#[derive(Debug, Clone)]
+struct TagEntry {
+    id: String,
+    required: bool,
+}
+
+// Aus den aktiven Paketen zusammengeführte Daten
+pub struct DataRegistries {
+    // Eingebaute und geladene Rezepte für das Crafting-Gitter
+    pub recipes: RecipeRegistry,
+    // Zusätzlich zu den eingebauten Rezepten aus Paketen geladene
+    pub recipe_ids: Vec<String>,
+    pub loot_tables: HashMap<String, Json>,
+    // Registry (z.B. "item", "block", "function") -> Tag-ID -> aufgelöste Einträge
+    pub tags: HashMap<String, HashMap<String, Vec<String>>>,
+    pub functions: HashMap<String, Vec<String>>,
+    // Strukturvorlagen aus structure/*.nbt
+    pub structures: HashMap<String, StructureTemplate>,
+    // Eigene Dimensionstypen; gelesen werden sie nur beim Start (dimension::register_types)
+    pub dimension_types: BTreeMap<String, Json>,
+}
+
+impl DataRegistries {
+    pub fn summary(&self) -> String {
+        let tags: usize = self.tags.values().map(HashMap::len).sum();
+        format!(
+            "{} recipes, {} loot tables, {} tags, {} functions, {} structures, {} dimension types",
+            self.recipe_ids.len(),
+            self.loot_tables.len(),
+            tags,
+            self.functions.len(),
+            self.structures.len(),
+            self.dimension_types.len()
+        )
+    }
+}
+
+// Ordnernamen vor 1.21 standen im Plural
+fn singular(kind: &str) -> &str {
+    match kind {
+        "recipes" => "recipe",
+        "loot_tables" => "loot_table",
+        "functions" => "function",
+        "items" => "item",
+        "blocks" => "block",
+        "entity_types" => "entity_type",
+        "fluids" => "fluid",
+        "game_events" => "game_event",
+        "structures" => "structure",
+        "dimension_types" => "dimension_type",
+        other => other,
+    }
+}
+
+fn namespaced(id: &str) -> String {
+    if id.contains(':') {
+        id.to_string()
+    } else {
+        format!("minecraft:{}", id)
+    }
+}
+
+// "minecraft/recipe/stick.json" -> ("recipe", "minecraft:stick"); Tags als ("tags/item", ...)
+fn resource_id(path: &str) -> Option<(String, String)> {
+    let (namespace, rest) = path.split_once('/')?;
+    let (kind, rest) = rest.split_once('/')?;
+    let (kind, rest) = if kind == "tags" {
+        let (registry, rest) = rest.split_once('/')?;
+        // Worldgen-Registries sind zweistufig, etwa tags/worldgen/biome
+        if registry == "worldgen" {
+            let (sub, rest) = rest.split_once('/')?;
+            (format!("tags/worldgen/{}", sub), rest)
+        } else {
+            (format!("tags/{}", singular(registry)), rest)
+        }
+    } else {
+        (singular(kind).to_string(), rest)
+    };
+    let name = rest.strip_suffix(".json").or_else(|| rest.strip_suffix(".mcfunction")).or_else(|| rest.strip_suffix(".nbt"))?;
+    Some((kind, format!("{}:{}", namespace, name)))
+}
+
+fn parse_tag(json: &Json) -> Result<(bool, Vec<TagEntry>), String> {
+    let replace = json.get("replace").and_then(Json::as_bool).unwrap_or(false);
+    let values = json.get("values").and_then(Json::as_array).ok_or("Tag ohne \"values\"")?;
+    let entries = values
+        .iter()
+        .map(|value| match value {
+            Json::String(id) => Ok(TagEntry { id: id.clone(), required: true }),
+            _ => {
+                let id = value.get("id").and_then(Json::as_str).ok_or("Tag-Eintrag ohne \"id\"")?;
+                Ok(TagEntry { id: id.to_string(), required: value.get("required").and_then(Json::as_bool).unwrap_or(true) })
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok((replace, entries))
+}
+
+// Löst Verweise auf andere Tags rekursiv auf; Zyklen und fehlende Pflicht-Verweise werden gemeldet
+fn resolve_tag(raw: &HashMap<String, Vec<TagEntry>>, id: &str, visiting: &mut HashSet<String>, resolved: &mut HashMap<String, Vec<String>>) -> Result<Vec<String>, String> {
+    if let Some(values) = resolved.get(id) {
+        return Ok(values.clone());
+    }
+    if !visiting.insert(id.to_string()) {
+        return Err(format!("Tag #{} verweist auf sich selbst", id));
+    }
+    let mut values = Vec::new();
+    for entry in raw.get(id).map(Vec::as_slice).unwrap_or_default() {
+        match entry.id.strip_prefix('#') {
+            Some(reference) => {
+                let reference = namespaced(reference);
+                if !raw.contains_key(&reference) {
+                    if entry.required {
+                        return Err(format!("Tag #{} verweist auf unbekanntes Tag #{}", id, reference));
+                    }
+                    continue;
+                }
+                values.extend(resolve_tag(raw, &reference, visiting, resolved)?);
+            }
+            None => values.push(namespaced(&entry.id)),
+        }
+    }
+    let mut seen = HashSet::new();
+    values.retain(|value| seen.insert(value.clone()));
+    visiting.remove(id);
+    resolved.insert(id.to_string(), values.clone());
+    Ok(values)
+}
+
+// Items, die das Item-Register nicht kennt, fallen aus der Zutat heraus
+fn parse_ingredient(json: &Json, item_tags: &HashMap<String, Vec<String>>) -> Result<Ingredient, String> {
+    let mut items = parse_ingredient_items(json, item_tags)?;
+    items.retain(|item| item::protocol_id(item).is_some());
+    if items.is_empty() {
+        return Err("Zutat ohne bekannte Items".to_string());
+    }
+    Ok(items)
+}
+
+fn parse_ingredient_items(json: &Json, item_tags: &HashMap<String, Vec<String>>) -> Result<Ingredient, String> {
+    let lookup = |tag: &str| item_tags.get(&namespaced(tag)).cloned().ok_or_else(|| format!("Unbekanntes Item-Tag #{}", tag));
+    match json {
+        Json::String(id) => match id.strip_prefix('#') {
+            Some(tag) => lookup(tag),
+            None => Ok(vec![namespaced(id)]),
+        },
+        Json::Array(options) => {
+            let mut items = Vec::new();
+            for option in options {
+                items.extend(parse_ingredient_items(option, item_tags)?);
+            }
+            Ok(items)
+        }
+        _ => {
+            if let Some(item) = json.get("item").and_then(Json::as_str) {
+                Ok(vec![namespaced(item)])
+            } else if let Some(tag) = json.get("tag").and_then(Json::as_str) {
+                lookup(tag)
+            } else {
+                Err("Zutat ohne \"item\" oder \"tag\"".to_string())
+            }
+        }
+    }
+}
+
+// Ab 1.20.5 "id", davor "item"; Schmelzrezepte hatten früher nur einen String
+fn parse_result(json: &Json) -> Result<ItemStack, String> {
+    let id = match json {
+        Json::String(id) => id.as_str(),
+        _ => json.get("id").or_else(|| json.get("item")).and_then(Json::as_str).ok_or("Ergebnis ohne \"id\"")?,
+    };
+    if item::protocol_id(&namespaced(id)).is_none() {
+        return Err(format!("Unbekanntes Item {} im Ergebnis", id));
+    }
+    if let Json::String(id) = json {
+        return Ok(ItemStack::new(&namespaced(id), 1));
+    }
+    let count = json.get("count").and_then(Json::as_f64).unwrap_or(1.0);
+    if !(1.0..=99.0).contains(&count) {
+        return Err(format!("Ungültige Anzahl {} im Ergebnis", count));
+    }
+    let mut result = ItemStack::new(&namespaced(id), count as u8);
+    if let Some(components) = json.get("components") {
+        let components = components.as_object().ok_or("\"components\" ist kein Objekt")?;
+        result.components = Components::from_map(components).map_err(|e| format!("Ungültige Komponenten im Ergebnis: {}", e))?;
+    }
+    Ok(result)
+}
+
+// Gibt false für Rezepttypen zurück, die der Server (noch) nicht kennt
+fn add_recipe(registry: &mut RecipeRegistry, id: &str, recipe: &Json, item_tags: &HashMap<String, Vec<String>>) -> Result<bool, String> {
+    let kind = recipe.get("type").and_then(Json::as_str).ok_or("Rezept ohne \"type\"")?;
+    match namespaced(kind).as_str() {
+        "minecraft:crafting_shaped" => {
+            let pattern: Vec<&str> = recipe
+                .get("pattern")
+                .and_then(Json::as_array)
+                .ok_or("Rezept ohne \"pattern\"")?
+                .iter()
+                .map(|row| row.as_str().ok_or("Musterzeile ist kein Text"))
+                .collect::<Result<_, _>>()?;
+            if pattern.is_empty() || pattern.len() > 3 || pattern.iter().any(|row| row.chars().count() > 3) {
+                return Err("Muster ist größer als 3x3".to_string());
+            }
+            let mut keys = Vec::new();
+            for (symbol, ingredient) in recipe.get("key").and_then(Json::as_object).ok_or("Rezept ohne \"key\"")? {
+                let mut chars = symbol.chars();
+                let (Some(symbol), None) = (chars.next(), chars.next()) else {
+                    return Err(format!("Schlüssel \"{}\" ist kein einzelnes Zeichen", symbol));
+                };
+                keys.push((symbol, parse_ingredient(ingredient, item_tags)?));
+            }
+            if let Some(missing) = pattern.iter().flat_map(|row| row.chars()).find(|&c| c != ' ' && !keys.iter().any(|(k, _)| *k == c)) {
+                return Err(format!("Zeichen '{}' im Muster fehlt in \"key\"", missing));
+            }
+            registry.add_shaped(id, &pattern, &keys, parse_result(recipe.get("result").ok_or("Rezept ohne \"result\"")?)?);
+        }
+        "minecraft:crafting_shapeless" => {
+            let ingredients = recipe
+                .get("ingredients")
+                .and_then(Json::as_array)
+                .ok_or("Rezept ohne \"ingredients\"")?
+                .iter()
+                .map(|ingredient| parse_ingredient(ingredient, item_tags))
+                .collect::<Result<Vec<_>, _>>()?;
+            if ingredients.is_empty() || ingredients.len() > 9 {
+                return Err(format!("{} Zutaten passen nicht in ein Crafting-Grid", ingredients.len()));
+            }
+            registry.add_shapeless(id, ingredients, parse_result(recipe.get("result").ok_or("Rezept ohne \"result\"")?)?);
+        }
+        "minecraft:smelting" => {
+            let input = parse_ingredient(recipe.get("ingredient").ok_or("Rezept ohne \"ingredient\"")?, item_tags)?;
+            registry.add_smelting(id, input, parse_result(recipe.get("result").ok_or("Rezept ohne \"result\"")?)?);
+        }
+        "minecraft:stonecutting" => {
+            let input = parse_ingredient(recipe.get("ingredient").ok_or("Rezept ohne \"ingredient\"")?, item_tags)?;
+            let mut result = parse_result(recipe.get("result").ok_or("Rezept ohne \"result\"")?)?;
+            // Bis 1.20.4 stand die Anzahl neben dem Ergebnis
+            if let Some(count) = recipe.get("count").and_then(Json::as_f64).filter(|_| matches!(recipe.get("result"), Some(Json::String(_)))) {
+                if !(1.0..=99.0).contains(&count) {
+                    return Err(format!("Ungültige Anzahl {} im Ergebnis", count));
+                }
+                result.count = count as u8;
+            }
+            registry.add_stonecutting(id, input, result);
+        }
+        "minecraft:smithing_transform" | "minecraft:smithing_trim" => {
+            let slot = |key: &str| parse_ingredient(recipe.get(key).ok_or_else(|| format!("Rezept ohne \"{}\"", key))?, item_tags);
+            let (template, base, addition) = (slot("template")?, slot("base")?, slot("addition")?);
+            let smithing = if kind.ends_with("smithing_trim") {
+                SmithingRecipe::Trim { template, base, addition }
+            } else {
+                SmithingRecipe::Transform { template, base, addition, result: Box::new(parse_result(recipe.get("result").ok_or("Rezept ohne \"result\"")?)?) }
+            };
+            registry.add_smithing(id, smithing);
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn parse_json(path: &str, data: &[u8]) -> Result<Json, String> {
+    json::parse(&String::from_utf8_lossy(data)).map_err(|e| format!("{}: {}", path, e))
+}
+
+// Spätere Pakete überschreiben gleichnamige Einträge früherer, außer bei Tags ohne "replace"
+fn build_registries(packs: &[&Datapack]) -> DataRegistries {
+    let mut recipes: BTreeMap<String, Json> = BTreeMap::new();
+    let mut loot_tables = HashMap::new();
+    let mut raw_tags: HashMap<String, HashMap<String, Vec<TagEntry>>> = HashMap::new();
+    let mut functions = HashMap::new();
+    let mut structures = HashMap::new();
+    let mut dimension_types = BTreeMap::new();
+    for pack in packs {
+        let files = match pack.data_files() {
+            Ok(files) => files,
+            Err(e) => {
+                warn!("Datenpaket {} übersprungen: {}", pack.id, e);
+                continue;
+            }
+        };
+        for (path, data) in files {
+            let Some((kind, id)) = resource_id(&path) else { continue };
+            let result = match kind.as_str() {
+                "recipe" => parse_json(&path, &data).map(|recipe| {
+                    recipes.insert(id, recipe);
+                }),
+                "loot_table" => parse_json(&path, &data).map(|table| {
+                    loot_tables.insert(id, table);
+                }),
+                "dimension_type" => parse_json(&path, &data).map(|kind| {
+                    dimension_types.insert(id, kind);
+                }),
+                "structure" if path.ends_with(".nbt") => StructureTemplate::parse(&data).map_err(|e| format!("{}: {}", path, e)).map(|template| {
+                    let unknown = template.unknown_blocks();
+                    if !unknown.is_empty() {
+                        debug!("Vorlage {}: unbekannte Blöcke werden ausgelassen: {}", id, unknown.join(", "));
+                    }
+                    structures.insert(id, template);
+                }),
+                "function" if path.ends_with(".mcfunction") => {
+                    functions.insert(id, function::parse(&String::from_utf8_lossy(&data)));
+                    Ok(())
+                }
+                kind if kind.starts_with("tags/") => parse_json(&path, &data).and_then(|tag| parse_tag(&tag).map_err(|e| format!("{}: {}", path, e))).map(|(replace, entries)| {
+                    let tag = raw_tags.entry(kind["tags/".len()..].to_string()).or_default().entry(id).or_default();
+                    if replace {
+                        tag.clear();
+                    }
+                    tag.extend(entries);
+                }),
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                warn!("Datenpaket {}: {}", pack.id, e);
+            }
+        }
+    }
+
+    let mut tags = HashMap::new();
+    for (registry, raw) in &raw_tags {
+        let mut resolved = HashMap::new();
+        let mut ids: Vec<&String> = raw.keys().collect();
+        ids.sort();
+        for id in ids {
+            if let Err(e) = resolve_tag(raw, id, &mut HashSet::new(), &mut resolved) {
+                warn!("{} (Registry {})", e, registry);
+            }
+        }
+        tags.insert(registry.clone(), resolved);
+    }
+
+    let mut registry = RecipeRegistry::with_defaults();
+    let mut recipe_ids = Vec::new();
+    let mut unsupported = 0;
+    let empty = HashMap::new();
+    let item_tags = tags.get("item").unwrap_or(&empty);
+    for (id, recipe) in &recipes {
+        match add_recipe(&mut registry, id, recipe, item_tags) {
+            Ok(true) => recipe_ids.push(id.clone()),
+            Ok(false) => unsupported += 1,
+            Err(e) => warn!("Rezept {}: {}", id, e),
+        }
+    }
+    if unsupported > 0 {
+        debug!("{} Rezepte mit nicht unterstütztem Typ übersprungen", unsupported);
+    }
+    DataRegistries { recipes: registry, recipe_ids, loot_tables, tags, functions, structures, dimension_types }
+}
+
+pub struct DatapackManager {
+    dir: PathBuf,
+    selection_file: PathBuf,
+    available: Vec<Datapack>,
+    // In Ladereihenfolge; spätere Pakete haben Vorrang
+    enabled: Vec<String>,
+    pub registries: DataRegistries,
+    // Nach jedem Laden laufen einmal die Funktionen aus #minecraft:load
+    load_pending: bool,
+}
+
+impl DatapackManager {
+    // Sucht in <Welt>/datapacks nach Paketen. Neue Pakete werden wie bei Vanilla automatisch aktiviert
+    pub fn load(world_dir: &str) -> DatapackManager {
+        let dir = Path::new(world_dir).join(DATAPACK_DIR);
+        let selection_file = Path::new(world_dir).join(SELECTION_FILE);
+        let available = scan(&dir);
+        let (saved_enabled, disabled) = match fs::read_to_string(&selection_file).map(|text| json::parse(&text)) {
+            Ok(Ok(selection)) => {
+                let list = |key: &str| -> Vec<String> {
+                    selection.get(key).and_then(Json::as_array).map(|ids| ids.iter().filter_map(Json::as_str).map(String::from).collect()).unwrap_or_default()
+                };
+                (list("enabled"), list("disabled"))
+            }
+            Ok(Err(e)) => {
+                warn!("{} konnte nicht gelesen werden: {}", selection_file.display(), e);
+                (Vec::new(), Vec::new())
+            }
+            Err(_) => (Vec::new(), Vec::new()),
+        };
+        let is_available = |id: &String| available.iter().any(|p| &p.id == id);
+        let mut enabled = vec![VANILLA_PACK.to_string()];
+        enabled.extend(saved_enabled.into_iter().filter(|id| id != VANILLA_PACK && is_available(id)));
+        for pack in &available {
+            if !enabled.contains(&pack.id) && !disabled.contains(&pack.id) {
+                info!("Neues Datenpaket {} gefunden und aktiviert: {}", pack.id, pack.description);
+                enabled.push(pack.id.clone());
+            }
+        }
+        let mut manager = DatapackManager { dir, selection_file, available, enabled, registries: build_registries(&[]), load_pending: false };
+        manager.save_selection();
+        manager.reload();
+        manager
+    }
+
+    pub fn available(&self) -> &[Datapack] {
+        &self.available
+    }
+
+    pub fn enabled(&self) -> &[String] {
+        &self.enabled
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        self.enabled.iter().any(|e| e == id)
+    }
+
+    fn find(&self, id: &str) -> Result<&Datapack, String> {
+        self.available.iter().find(|p| p.id == id).ok_or_else(|| format!("Unknown data pack '{}'", id))
+    }
+
+    pub fn enable(&mut self, id: &str, first: bool) -> Result<(), String> {
+        self.find(id)?;
+        if self.is_enabled(id) {
+            return Err(format!("Pack '{}' is already enabled", id));
+        }
+        // "first" heißt direkt nach vanilla, das immer zuerst geladen wird
+        if first {
+            self.enabled.insert(1, id.to_string());
+        } else {
+            self.enabled.push(id.to_string());
+        }
+        self.save_selection();
+        self.reload();
+        Ok(())
+    }
+
+    pub fn disable(&mut self, id: &str) -> Result<(), String> {
+        self.find(id)?;
+        if id == VANILLA_PACK {
+            return Err("The vanilla pack cannot be disabled".to_string());
+        }
+        if !self.is_enabled(id) {
+            return Err(format!("Pack '{}' is not enabled", id));
+        }
+        self.enabled.retain(|e| e != id);
+        self.save_selection();
+        self.reload();
+        Ok(())
+    }
+
+    // Liest den Ordner neu ein, etwa nachdem ein Paket hineinkopiert wurde
+    pub fn rescan(&mut self) {
+        self.available = scan(&self.dir);
+        let available: Vec<String> = self.available.iter().map(|p| p.id.clone()).collect();
+        self.enabled.retain(|id| available.contains(id));
+    }
+
+    pub fn reload(&mut self) {
+        let started = Instant::now();
+        let packs: Vec<&Datapack> = self.enabled.iter().filter_map(|id| self.available.iter().find(|p| &p.id == id)).collect();
+        for pack in packs.iter().filter(|p| !p.compatible()) {
+            warn!("Datenpaket {} hat pack_format {}, erwartet wird {}", pack.id, pack.pack_format, PACK_FORMAT);
+        }
+        self.registries = build_registries(&packs);
+        self.load_pending = true;
+        info!("{} Datenpakete geladen ({}) in {}ms", packs.len(), self.registries.summary(), started.elapsed().as_millis());
+    }
+
+    pub fn take_load_pending(&mut self) -> bool {
+        std::mem::take(&mut self.load_pending)
+    }
+
+    fn save_selection(&self) {
+        let ids = |ids: Vec<&String>| Json::Array(ids.into_iter().map(|id| Json::String(id.clone())).collect());
+        let mut selection = BTreeMap::new();
+        selection.insert("enabled".to_string(), ids(self.enabled.iter().collect()));
+        selection.insert("disabled".to_string(), ids(self.available.iter().map(|p| &p.id).filter(|id| !self.is_enabled(id)).collect()));
+        if let Some(parent) = self.selection_file.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(&self.selection_file, Json::Object(selection).to_json_string()) {
+            warn!("Konnte {} nicht schreiben: {}", self.selection_file.display(), e);
+        }
+    }
+}
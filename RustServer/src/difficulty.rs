@@ -0,0 +1,76 @@
+use crate::protocol::clientbound;
+use crate::text::Text;
+use crate::Connection;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+}
+
+const ALL: [Difficulty; 4] = [Difficulty::Peaceful, Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+impl Difficulty {
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Peaceful => "peaceful",
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    // Für Befehlsantworten, etwa "Normal"
+    // Übersetzbar wie im Optionsmenü des Clients
+    pub fn display_text(self) -> Text {
+        Text::translate(format!("options.difficulty.{}", self.name()))
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Difficulty::Peaceful => "Peaceful",
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    // Schaden von Mobs an Spielern wie bei Vanilla: friedlich keiner, leicht halbiert (+1), schwer das 1,5-Fache
+    pub fn scale_damage(self, amount: f32) -> f32 {
+        match self {
+            Difficulty::Peaceful => 0.0,
+            Difficulty::Easy => (amount / 2.0 + 1.0).min(amount),
+            Difficulty::Normal => amount,
+            Difficulty::Hard => amount * 1.5,
+        }
+    }
+}
+
+// Wie in server.properties bei Vanilla: Name oder Zahl (0-3)
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Difficulty, String> {
+        let s = s.trim().to_ascii_lowercase();
+        ALL.into_iter()
+            .find(|d| d.name() == s || d.id().to_string() == s)
+            .ok_or_else(|| format!("Unknown difficulty '{}'", s))
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Change Difficulty; gesperrt ist sie nur im Einzelspieler
+pub fn send(connection: &Connection, difficulty: Difficulty) -> Result<(), String> {
+    connection.send(clientbound::CHANGE_DIFFICULTY, &[difficulty.id(), 0])
+}
@@ -0,0 +1,387 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+use crate::gamemode::GameMode;
+use crate::json::Json;
+use crate::protocol::clientbound;
+use crate::protocol::types::{write_string_to_vec, write_varint_to_vec};
+use crate::multiworld::{self, NamedWorld};
+use crate::worldgen::{self, Generator};
+use crate::{chunk, damage, difficulty, effect, entity, experience, gamemode, item, sleep, storage, worldborder};
+use crate::{send_game_event, send_spawn_position, send_time, send_weather, teleport_player, Connection, Server, World, GAME_EVENT_WAIT_FOR_CHUNKS};
+
+// Was der Client von der alten Spieler-Entity übernimmt (Respawn-Flags)
+pub const KEEP_NOTHING: u8 = 0x00;
+pub const KEEP_ALL: u8 = 0x03;
+
+// Eigenschaften einer Welt aus minecraft:dimension_type. Die vier von Vanilla stehen fest vorne in der Registry,
+// eigene aus Datenpaketen (dimension_type/*.json) folgen beim Start nach Namen sortiert
+#[derive(Debug, PartialEq)]
+pub struct DimensionType {
+    pub name: &'static str,
+    pub id: i32,
+    pub min_y: i32,
+    pub height: i32,
+    // Bis hierhin reichen Portale und Chorusfrüchte
+    pub logical_height: i32,
+    // Grundhelligkeit auch ohne Licht, 0.1 im Nether
+    pub ambient_light: f32,
+    pub has_skylight: bool,
+    pub has_ceiling: bool,
+    pub bed_works: bool,
+    // Wasser verdampft, Eis schmilzt zu nichts
+    pub ultrawarm: bool,
+    // Himmel, Nebel und Wolken auf dem Client: minecraft:overworld, minecraft:the_nether oder minecraft:the_end
+    pub effects: &'static str,
+}
+
+pub const OVERWORLD_TYPE: DimensionType = DimensionType {
+    name: "minecraft:overworld",
+    id: 0,
+    min_y: chunk::MIN_Y,
+    height: chunk::HEIGHT,
+    logical_height: chunk::HEIGHT,
+    ambient_light: 0.0,
+    has_skylight: true,
+    has_ceiling: false,
+    bed_works: true,
+    ultrawarm: false,
+    effects: "minecraft:overworld",
+};
+pub const OVERWORLD_CAVES_TYPE: DimensionType = DimensionType { name: "minecraft:overworld_caves", id: 1, has_ceiling: true, ..OVERWORLD_TYPE };
+pub const END_TYPE: DimensionType = DimensionType {
+    name: "minecraft:the_end",
+    id: 2,
+    min_y: 0,
+    height: 256,
+    logical_height: 256,
+    ambient_light: 0.0,
+    has_skylight: false,
+    has_ceiling: false,
+    bed_works: false,
+    ultrawarm: false,
+    effects: "minecraft:the_end",
+};
+pub const NETHER_TYPE: DimensionType = DimensionType {
+    name: "minecraft:the_nether",
+    id: 3,
+    min_y: 0,
+    height: 256,
+    logical_height: 128,
+    ambient_light: 0.1,
+    has_skylight: false,
+    has_ceiling: true,
+    bed_works: false,
+    ultrawarm: true,
+    effects: "minecraft:the_nether",
+};
+const BUILTIN_TYPES: [&DimensionType; 4] = [&OVERWORLD_TYPE, &OVERWORLD_CAVES_TYPE, &END_TYPE, &NETHER_TYPE];
+const EFFECTS: [&str; 3] = ["minecraft:overworld", "minecraft:the_nether", "minecraft:the_end"];
+
+// Wie benannte Welten leben eigene Typen bis zum Ende des Prozesses, damit Dimension Copy bleibt
+static CUSTOM_TYPES: Mutex<Vec<&'static DimensionType>> = Mutex::new(Vec::new());
+
+impl DimensionType {
+    // Felder wie bei Vanilla; was fehlt, kommt von der Oberwelt. Die Chunks des Servers reichen von chunk::MIN_Y
+    // über chunk::HEIGHT Blöcke, darüber hinaus geht auch ein eigener Typ nicht
+    fn from_json(name: &str, id: i32, json: &Json) -> Result<DimensionType, String> {
+        let int = |key: &str, default: i32| match json.get(key) {
+            None => Ok(default),
+            Some(value) => value.as_f64().filter(|v| v.fract() == 0.0).map(|v| v as i32).ok_or_else(|| format!("\"{}\" must be an integer", key)),
+        };
+        let flag = |key: &str, default: bool| match json.get(key) {
+            None => Ok(default),
+            Some(value) => value.as_bool().ok_or_else(|| format!("\"{}\" must be true or false", key)),
+        };
+        let min_y = int("min_y", OVERWORLD_TYPE.min_y)?;
+        let height = int("height", OVERWORLD_TYPE.height)?;
+        if min_y % 16 != 0 || height % 16 != 0 || height < 16 {
+            return Err("min_y and height must be multiples of 16".to_string());
+        }
+        if min_y < chunk::MIN_Y || min_y + height > chunk::MIN_Y + chunk::HEIGHT {
+            return Err(format!("Blocks must stay between y={} and y={}", chunk::MIN_Y, chunk::MIN_Y + chunk::HEIGHT));
+        }
+        let logical_height = int("logical_height", height)?;
+        if !(0..=height).contains(&logical_height) {
+            return Err("logical_height must not exceed height".to_string());
+        }
+        let ambient_light = match json.get("ambient_light") {
+            None => 0.0,
+            Some(value) => value.as_f64().filter(|v| (0.0..=1.0).contains(v)).ok_or("\"ambient_light\" must be between 0 and 1")? as f32,
+        };
+        let effects = match json.get("effects") {
+            None => OVERWORLD_TYPE.effects,
+            Some(value) => {
+                let effects = value.as_str().map(item::namespaced).ok_or("\"effects\" must be a string")?;
+                EFFECTS.into_iter().find(|e| *e == effects).ok_or_else(|| format!("Unknown effects '{}'", effects))?
+            }
+        };
+        Ok(DimensionType {
+            name: Box::leak(name.to_string().into_boxed_str()),
+            id,
+            min_y,
+            height,
+            logical_height,
+            ambient_light,
+            has_skylight: flag("has_skylight", true)?,
+            has_ceiling: flag("has_ceiling", false)?,
+            bed_works: flag("bed_works", true)?,
+            ultrawarm: flag("ultrawarm", false)?,
+            effects,
+        })
+    }
+
+    pub fn find(name: &str) -> Option<&'static DimensionType> {
+        let name = item::namespaced(name);
+        BUILTIN_TYPES.into_iter().chain(CUSTOM_TYPES.lock().unwrap().iter().copied()).find(|t| t.name == name)
+    }
+
+    // Die Typen von Vanilla kennt der Client selbst, eigene nur aus der Konfigurationsphase
+    pub fn is_builtin(&self) -> bool {
+        (self.id as usize) < BUILTIN_TYPES.len()
+    }
+
+    // Für /world config: was sich von der Oberwelt unterscheidet, sieht man nur auf dem Client
+    pub fn describe(&self) -> String {
+        format!(
+            "{} (y {} to {}, logical height {}, ambient light {}, {}, {}effects {})",
+            self.name,
+            self.min_y,
+            self.min_y + self.height,
+            self.logical_height,
+            self.ambient_light,
+            if self.has_ceiling { "ceiling" } else if self.has_skylight { "sky" } else { "no sky" },
+            if self.bed_works { "" } else { "beds explode, " },
+            self.effects
+        )
+    }
+}
+
+// Nach Registry-ID sortiert
+pub fn all_types() -> Vec<&'static DimensionType> {
+    BUILTIN_TYPES.into_iter().chain(CUSTOM_TYPES.lock().unwrap().iter().copied()).collect()
+}
+
+// Nur beim Start: Registry-IDs und Höhen bestehender Welten dürfen sich nicht ändern, solange Clients verbunden sind
+pub fn register_types(types: &BTreeMap<String, Json>) {
+    let mut custom = CUSTOM_TYPES.lock().unwrap();
+    if !custom.is_empty() {
+        return;
+    }
+    for (name, json) in types {
+        if BUILTIN_TYPES.iter().any(|t| t.name == name) {
+            warn!("Dimensionstyp {} ist eingebaut und wird nicht überschrieben", name);
+            continue;
+        }
+        let id = (BUILTIN_TYPES.len() + custom.len()) as i32;
+        match DimensionType::from_json(name, id, json) {
+            Ok(kind) => custom.push(Box::leak(Box::new(kind))),
+            Err(e) => warn!("Dimensionstyp {}: {}", name, e),
+        }
+    }
+    if !custom.is_empty() {
+        info!("{} eigene Dimensionstypen geladen: {}", custom.len(), custom.iter().map(|t| t.name).collect::<Vec<_>>().join(", "));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dimension {
+    Overworld,
+    Nether,
+    End,
+    // Zusätzliche Welt aus worlds/, etwa mit /world create angelegt
+    Named(&'static NamedWorld),
+}
+
+impl Dimension {
+    pub fn name(self) -> &'static str {
+        match self {
+            Dimension::Overworld => "minecraft:overworld",
+            Dimension::Nether => "minecraft:the_nether",
+            Dimension::End => "minecraft:the_end",
+            Dimension::Named(world) => &world.name,
+        }
+    }
+
+    // Auch geladene benannte Welten
+    pub fn from_name(name: &str) -> Option<Dimension> {
+        let name = item::namespaced(name);
+        [Dimension::Overworld, Dimension::Nether, Dimension::End]
+            .into_iter()
+            .chain(multiworld::loaded().into_iter().map(Dimension::Named))
+            .find(|d| d.name() == name)
+    }
+
+    // Weltname, wie ihn /world, Portale und Plugins annehmen: "world" ist die Oberwelt
+    pub fn by_world_name(name: &str) -> Result<Dimension, String> {
+        if name == storage::world_dir() {
+            return Ok(Dimension::Overworld);
+        }
+        Dimension::from_name(name).ok_or_else(|| format!("World {} is not loaded", name))
+    }
+
+    // Dimensionstyp, dessen Höhe und Eigenschaften die Welt hat
+    pub fn kind(self) -> &'static DimensionType {
+        match self {
+            Dimension::Overworld => &OVERWORLD_TYPE,
+            Dimension::Nether => &NETHER_TYPE,
+            Dimension::End => &END_TYPE,
+            Dimension::Named(world) => world.kind,
+        }
+    }
+
+    pub fn generator(self) -> Generator {
+        match self {
+            Dimension::Overworld => worldgen::overworld_generator(),
+            Dimension::Nether => Generator::Normal,
+            Dimension::End => Generator::End,
+            Dimension::Named(world) => world.generator,
+        }
+    }
+
+    // Registry-ID in minecraft:dimension_type
+    pub fn type_id(self) -> i32 {
+        self.kind().id
+    }
+
+    // Unterste Blockebene und Höhe aus dem Dimensionstyp; Chunks werden auf diesen Bereich zugeschnitten
+    pub fn min_y(self) -> i32 {
+        self.kind().min_y
+    }
+
+    pub fn height(self) -> i32 {
+        self.kind().height
+    }
+
+    // Regen und Gewitter nur unter freiem Himmel
+    pub fn has_weather(self) -> bool {
+        self.kind().has_skylight && !self.kind().has_ceiling
+    }
+
+    // Anderswo explodieren Betten
+    pub fn beds_work(self) -> bool {
+        self.kind().bed_works
+    }
+
+    pub fn has_sky_light(self) -> bool {
+        self.kind().has_skylight
+    }
+
+    // Bis 1.20.4 wird der Dimensionstyp als Name geschickt
+    pub fn write_type(self, connection: &Connection) -> Vec<u8> {
+        if connection.version.has_1_20_5_login() {
+            write_varint_to_vec(self.type_id())
+        } else {
+            write_string_to_vec(self.kind().name)
+        }
+    }
+}
+
+// Geladen sind Oberwelt, Ende und die benannten Welten bis /world unload; der Nether ist bekannt, aber nicht geladen
+pub fn world(server: &Server, dimension: Dimension) -> Result<&Mutex<World>, String> {
+    match dimension {
+        Dimension::Overworld => Ok(&server.world),
+        Dimension::End => Ok(&server.end),
+        Dimension::Named(world) => world.world().ok_or_else(|| format!("World {} is not loaded", world.short_name())),
+        other => Err(format!("Dimension {} is not loaded", other.name())),
+    }
+}
+
+// Welt einer Dimension, in der sich schon ein Spieler oder eine Entity befindet; dorthin kommt nur, wer über
+// change_dimension gegangen ist, sie ist also geladen
+pub fn world_of(server: &Server, dimension: Dimension) -> &Mutex<World> {
+    world(server, dimension).unwrap_or(&server.world)
+}
+
+// Alle geladenen Welten, einzeln nacheinander zu sperren
+pub fn worlds(server: &Server) -> Vec<&Mutex<World>> {
+    let mut worlds = vec![&server.world, &server.end];
+    worlds.extend(multiworld::loaded().into_iter().filter_map(NamedWorld::world));
+    worlds
+}
+
+// Welt, in der gerade der Mob id lebt
+pub fn world_with_mob(server: &Server, id: Uuid) -> Option<&Mutex<World>> {
+    worlds(server).into_iter().find(|w| w.lock().unwrap().mobs.iter().any(|m| m.id == id))
+}
+
+pub fn of_player(server: &Server, uuid: Uuid) -> Option<Dimension> {
+    server.players.lock().unwrap().iter().find(|p| p.uuid == uuid).map(|p| p.dimension)
+}
+
+// Respawn: in einer anderen Dimension verwirft der Client dabei alle Chunks und Entities
+fn send_respawn(connection: &Connection, dimension: Dimension, game_mode: GameMode, keep: u8) -> Result<(), String> {
+    let mut packet_data = dimension.write_type(connection);
+    packet_data.extend(write_string_to_vec(dimension.name()));
+    packet_data.extend(0i64.to_be_bytes()); // Gehashter Seed
+    packet_data.push(game_mode.id());
+    packet_data.push(255u8); // Vorheriger Spielmodus
+    packet_data.extend([0, 0, 0]); // Debug-Welt, flache Welt, Todesposition
+    packet_data.extend(write_varint_to_vec(0)); // Portal-Cooldown
+    packet_data.push(keep);
+    connection.send(clientbound::RESPAWN, &packet_data)?;
+    send_game_event(connection, GAME_EVENT_WAIT_FOR_CHUNKS, 0.0)
+}
+
+// Bringt einen Spieler in eine (auch dieselbe) Dimension: Respawn, Zustand des Spielers und der Welt, dann die
+// Position. Die Chunks streamt danach der Verbindungs-Thread, der den Wechsel am Feld dimension erkennt.
+// Für Portale, /execute in ... run tp und den Respawn nach dem Tod. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn change_dimension(server: &Server, uuid: Uuid, dimension: Dimension, position: (f64, f64, f64), rotation: (f32, f32), keep: u8) -> Result<(), String> {
+    let target = world(server, dimension)?;
+    let mut players = server.players.lock().unwrap();
+    let index = players.iter().position(|p| p.uuid == uuid).ok_or("Player is not online")?;
+    // Aus dem Bett, bevor er es verlässt
+    if players[index].pose.bed.is_some() {
+        let mut old = world_of(server, players[index].dimension).lock().unwrap();
+        sleep::wake(&mut players, &mut old, index, false);
+    }
+    let player = &mut players[index];
+    let changed = player.dimension != dimension;
+    let username = player.username.clone();
+    player.dimension = dimension;
+    let connection = &player.connection;
+    send_respawn(connection, dimension, player.game_mode, keep)?;
+    gamemode::send_abilities(connection, player.game_mode)?;
+    damage::send_health(player)?;
+    experience::send(connection, &player.experience)?;
+    player.inventory.send_all(connection)?;
+    for (id, effect) in player.effects.iter() {
+        effect::send_add(connection, entity::OWN_PLAYER_ID, id, effect)?;
+    }
+    effect::send_attributes(connection, entity::OWN_PLAYER_ID, &player.effects)?;
+    let world = target.lock().unwrap();
+    send_spawn_position(connection, world.spawn)?;
+    worldborder::send_init(connection, &world.border)?;
+    difficulty::send(connection, world.difficulty)?;
+    send_time(connection, &world.time)?;
+    send_weather(connection, &world.weather)?;
+    drop(world);
+    drop(players);
+    teleport_player(server, uuid, position, rotation)?;
+    if !changed {
+        return Ok(());
+    }
+    info!("{} wechselt nach {}", username, dimension.name());
+    server.tracker.lock().unwrap().forget_viewer(uuid);
+    // Die Entities der alten Dimension hat der Client verworfen, die der neuen kennt er noch nicht
+    let players = server.players.lock().unwrap();
+    let player = players.iter().find(|p| p.uuid == uuid).ok_or("Player is not online")?;
+    let world = target.lock().unwrap();
+    world.items.iter().filter(|item| entity::in_view(player, dimension, item.position)).try_for_each(|item| item.send_spawn(&player.connection))?;
+    world.orbs.iter().filter(|orb| entity::in_view(player, dimension, orb.position)).try_for_each(|orb| orb.send_spawn(&player.connection))?;
+    world.mobs.iter().filter(|mob| entity::in_view(player, dimension, mob.position)).try_for_each(|mob| entity::send_mob(&player.connection, mob))
+}
+
+// Bringt einen Spieler in eine beliebige geladene Welt: in derselben nur ein Positionspaket, sonst der ganze
+// Dimensionswechsel. Ohne Blickrichtung bleibt die bisherige. Für Befehle, Portale und Plugins; darf nur ohne
+// gehaltene Sperren aufgerufen werden
+pub fn teleport(server: &Server, uuid: Uuid, dimension: Dimension, position: (f64, f64, f64), rotation: Option<(f32, f32)>) -> Result<(), String> {
+    world(server, dimension)?;
+    let (current, old_rotation) = server.players.lock().unwrap().iter().find(|p| p.uuid == uuid).map(|p| (p.dimension, p.rotation)).ok_or("Player is not online")?;
+    let rotation = rotation.unwrap_or(old_rotation);
+    if current == dimension {
+        teleport_player(server, uuid, position, rotation)
+    } else {
+        change_dimension(server, uuid, dimension, position, rotation, KEEP_ALL)
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::chat;
+use crate::event::{ChatEvent, EventPriority, PlayerJoinEvent, PlayerQuitEvent};
+use crate::json::{self, Json};
+use crate::motd;
+use crate::{send_system_component, Server};
+
+const API: &str = "https://discord.com/api/v10";
+// Discord lehnt längere Nachrichten ab
+const MAX_CONTENT: usize = 2000;
+// Ohne TLS im Server geht jede Anfrage über curl; Token und Webhook-URL stehen in der Konfiguration auf stdin, nicht
+// in der Prozessliste
+const CURL: &str = "curl";
+const REQUEST_TIMEOUT_SECS: &str = "10";
+const RATE_LIMITED: u16 = 429;
+// 2015-01-01, Beginn der Discord-Snowflakes
+const DISCORD_EPOCH_MILLIS: u64 = 1_420_070_400_000;
+
+#[derive(Debug)]
+enum Outgoing {
+    Chat { name: String, message: String },
+    // Beitreten, Verlassen und Tode, schon als fertiger Text
+    Notice(String),
+}
+
+// Chat, Beitritte und Tode gehen an einen Discord-Kanal, Nachrichten aus dem Kanal in den Spielchat. Ein eigener
+// Thread erledigt alle Anfragen, damit weder Tick-Schleife noch Chat auf Discord warten
+#[derive(Debug, Default)]
+pub struct DiscordBridge {
+    outgoing: OnceLock<Sender<Outgoing>>,
+}
+
+impl DiscordBridge {
+    pub fn notice(&self, text: &str) {
+        if let Some(outgoing) = self.outgoing.get() {
+            let _ = outgoing.send(Outgoing::Notice(motd::legacy(text).plain()));
+        }
+    }
+
+    fn chat(&self, name: &str, message: &str) {
+        if let Some(outgoing) = self.outgoing.get() {
+            let _ = outgoing.send(Outgoing::Chat { name: name.to_string(), message: message.to_string() });
+        }
+    }
+}
+
+// Ohne discord-webhook-url und discord-bot-token bleibt die Brücke aus
+pub fn start(server: &Arc<Server>) {
+    let config = &server.config;
+    let has_bot = !config.discord_bot_token.is_empty() && !config.discord_channel_id.is_empty();
+    if config.discord_webhook_url.is_empty() && !has_bot {
+        if !config.discord_bot_token.is_empty() {
+            warn!("discord-bot-token ist gesetzt, aber discord-channel-id ist leer! Discord-Brücke wird nicht gestartet");
+        }
+        return;
+    }
+    let (sender, receiver) = mpsc::channel();
+    let _ = server.discord.outgoing.set(sender);
+    server.events.listen(EventPriority::Monitor, |server, event: &mut ChatEvent| {
+        if !event.cancelled {
+            server.discord.chat(&event.player, &event.message);
+        }
+    });
+    server.events.listen(EventPriority::Monitor, |server, event: &mut PlayerJoinEvent| {
+        if !event.message.is_empty() {
+            server.discord.notice(&event.message);
+        }
+    });
+    server.events.listen(EventPriority::Monitor, |server, event: &mut PlayerQuitEvent| {
+        if !event.message.is_empty() {
+            server.discord.notice(&event.message);
+        }
+    });
+    info!("Discord-Brücke aktiv ({})", if has_bot { "Webhook und Bot" } else { "nur Webhook, ohne Nachrichten aus Discord" });
+    let server = Arc::clone(server);
+    thread::Builder::new()
+        .name("discord".to_string())
+        .spawn(move || run(&server, receiver, has_bot))
+        .expect("Konnte Discord-Thread nicht starten");
+}
+
+fn run(server: &Server, receiver: Receiver<Outgoing>, has_bot: bool) {
+    let config = &server.config;
+    let interval = Duration::from_secs(config.discord_poll_interval_secs.max(1));
+    // Was vor dem Start im Kanal stand, wird nicht nachgeholt
+    let mut last_seen = snowflake_now();
+    let mut last_poll = Instant::now();
+    loop {
+        match receiver.recv_timeout(interval) {
+            Ok(message) => {
+                if let Err(e) = post(server, message) {
+                    warn!("Konnte Nachricht nicht an Discord senden: {}", e);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if has_bot && last_poll.elapsed() >= interval {
+            last_poll = Instant::now();
+            match poll(server, last_seen) {
+                Ok(Some(id)) => last_seen = id,
+                Ok(None) => {}
+                Err(e) => warn!("Konnte Discord-Kanal nicht lesen: {}", e),
+            }
+        }
+    }
+}
+
+fn post(server: &Server, message: Outgoing) -> Result<(), String> {
+    let config = &server.config;
+    let (username, content) = match message {
+        // Über den Webhook erscheint der Spieler selbst als Absender
+        Outgoing::Chat { name, message } if !config.discord_webhook_url.is_empty() => (Some(name), message),
+        Outgoing::Chat { name, message } => (None, chat::expand(&config.discord_relay_format, &[("name", &name), ("message", &message)])),
+        Outgoing::Notice(text) => (None, text),
+    };
+    let mut body = BTreeMap::new();
+    body.insert("content".to_string(), Json::String(content.chars().take(MAX_CONTENT).collect()));
+    // Kein @everyone und keine Erwähnungen aus dem Spielchat
+    body.insert("allowed_mentions".to_string(), Json::Object(BTreeMap::from([("parse".to_string(), Json::Array(Vec::new()))])));
+    if let Some(username) = username {
+        body.insert("username".to_string(), Json::String(username));
+    }
+    let body = Json::Object(body).to_json_string();
+    if config.discord_webhook_url.is_empty() {
+        let url = format!("{}/channels/{}/messages", API, config.discord_channel_id);
+        request(server, "POST", &url, true, Some(&body))?;
+    } else {
+        request(server, "POST", &config.discord_webhook_url, false, Some(&body))?;
+    }
+    Ok(())
+}
+
+// Snowflake-ID für den Jetzt-Zeitpunkt: Millisekunden seit 2015 in den oberen Bits
+fn snowflake_now() -> u64 {
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+    millis.saturating_sub(DISCORD_EPOCH_MILLIS) << 22
+}
+
+// Neue Nachrichten in den Spielchat; gibt die ID der neuesten zurück. Nachrichten von Bots und Webhooks bleiben
+// draußen, sonst käme der eigene Chat zurück
+fn poll(server: &Server, after: u64) -> Result<Option<u64>, String> {
+    let config = &server.config;
+    let url = format!("{}/channels/{}/messages?after={}&limit=100", API, config.discord_channel_id, after);
+    let messages = json::parse(&request(server, "GET", &url, true, None)?)?;
+    let id = |message: &Json| message.get("id").and_then(Json::as_str).and_then(|id| id.parse::<u64>().ok());
+    let mut messages: Vec<&Json> = messages.as_array().ok_or("Unexpected response")?.iter().collect();
+    // Discord liefert die neuesten zuerst; Snowflakes steigen mit der Zeit
+    messages.sort_by_key(|message| id(message));
+    let newest = messages.last().and_then(|message| id(message));
+    for message in messages {
+        let author = message.get("author");
+        let bot = author.and_then(|a| a.get("bot")).and_then(Json::as_bool).unwrap_or(false);
+        let content = message.get("content").and_then(Json::as_str).unwrap_or("");
+        if bot || message.get("webhook_id").is_some() || content.is_empty() {
+            continue;
+        }
+        let name = author.and_then(|a| a.get("global_name").and_then(Json::as_str).or_else(|| a.get("username")?.as_str())).unwrap_or("?");
+        // Keine Farbcodes über den Namen, die Nachricht ist ohnehin ohne
+        let name: String = name.chars().filter(|&c| c != '&' && c != '§').collect();
+        let line = chat::format(&config.discord_chat_format, &[("name", &name)], content, false);
+        info!("{}", line.plain());
+        for player in server.players.lock().unwrap().iter().filter(|p| p.settings.accepts_chat()) {
+            let _ = send_system_component(&player.connection, &line);
+        }
+    }
+    Ok(newest)
+}
+
+// Ein Versuch mehr nach einer Drosselung durch Discord (429), dann wird die Nachricht verworfen
+fn request(server: &Server, method: &str, url: &str, bot: bool, body: Option<&str>) -> Result<String, String> {
+    let (status, response) = curl(server, method, url, bot, body)?;
+    let (status, response) = if status == RATE_LIMITED {
+        let retry_after = json::parse(&response).ok().and_then(|r| r.get("retry_after")?.as_f64()).unwrap_or(1.0);
+        thread::sleep(Duration::from_secs_f64(retry_after.clamp(0.0, 60.0)));
+        curl(server, method, url, bot, body)?
+    } else {
+        (status, response)
+    };
+    if !(200..300).contains(&status) {
+        return Err(format!("HTTP {}: {}", status, response.trim()));
+    }
+    Ok(response)
+}
+
+fn curl(server: &Server, method: &str, url: &str, bot: bool, body: Option<&str>) -> Result<(u16, String), String> {
+    let mut options = vec![
+        format!("url = {}", quote(url)),
+        format!("request = {}", quote(method)),
+        "header = \"Content-Type: application/json\"".to_string(),
+        "header = \"User-Agent: DiscordBot (https://github.com/MimoAlexer/RustMcServer, 0.1)\"".to_string(),
+    ];
+    if bot {
+        options.push(format!("header = {}", quote(&format!("Authorization: Bot {}", server.config.discord_bot_token))));
+    }
+    if let Some(body) = body {
+        options.push(format!("data-binary = {}", quote(body)));
+    }
+    let mut child = Command::new(CURL)
+        .args(["--silent", "--show-error", "--max-time", REQUEST_TIMEOUT_SECS, "--write-out", "\\n%{http_code}", "--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Konnte {} nicht starten: {}", CURL, e))?;
+    let written = child.stdin.take().map(|mut stdin| stdin.write_all(options.join("\n").as_bytes()));
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        let _ = stdout.read_to_string(&mut output);
+    }
+    let mut errors = String::new();
+    if let Some(mut stderr) = child.stderr.take() {
+        let _ = stderr.read_to_string(&mut errors);
+    }
+    let exit = child.wait().map_err(|e| e.to_string())?;
+    if let Some(Err(e)) = written {
+        return Err(e.to_string());
+    }
+    if !exit.success() {
+        return Err(format!("{} ({})", errors.trim(), exit));
+    }
+    let (response, status) = output.rsplit_once('\n').ok_or("No response")?;
+    Ok((status.trim().parse().map_err(|_| format!("Invalid status {}", status))?, response.to_string()))
+}
+
+// Zeichenketten in curl-Konfigurationsdateien
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
@@ -0,0 +1,157 @@
+use uuid::Uuid;
+use crate::gamemode::GameMode;
+use crate::{block, entity, redstone, worldevent, Player, World};
+
+type BlockPos = (i32, i32, i32);
+
+pub fn is_openable(state: u16) -> bool {
+    block::is_door(state) || block::is_trapdoor(state) || block::is_fence_gate(state)
+}
+
+// Eisentüren und -falltüren gehen nur mit Redstone auf
+fn opens_by_hand(state: u16) -> bool {
+    !block::name(state).starts_with("minecraft:iron_")
+}
+
+// (Öffnen, Schließen) wie bei Vanilla je nach Block und Material
+fn sounds(state: u16) -> (&'static str, &'static str) {
+    let iron = !opens_by_hand(state);
+    if block::is_door(state) && iron {
+        ("minecraft:block.iron_door.open", "minecraft:block.iron_door.close")
+    } else if block::is_door(state) {
+        ("minecraft:block.wooden_door.open", "minecraft:block.wooden_door.close")
+    } else if block::is_trapdoor(state) && iron {
+        ("minecraft:block.iron_trapdoor.open", "minecraft:block.iron_trapdoor.close")
+    } else if block::is_trapdoor(state) {
+        ("minecraft:block.wooden_trapdoor.open", "minecraft:block.wooden_trapdoor.close")
+    } else {
+        ("minecraft:block.fence_gate.open", "minecraft:block.fence_gate.close")
+    }
+}
+
+fn facing_offset(facing: &str) -> (i32, i32) {
+    match facing {
+        "north" => (0, -1),
+        "south" => (0, 1),
+        "west" => (-1, 0),
+        _ => (1, 0),
+    }
+}
+
+fn clockwise(facing: &str) -> &'static str {
+    match facing {
+        "north" => "east",
+        "east" => "south",
+        "south" => "west",
+        _ => "north",
+    }
+}
+
+fn opposite(facing: &str) -> &'static str {
+    clockwise(clockwise(facing))
+}
+
+// Die andere Hälfte einer Tür, oben oder unten
+fn other_half(state: u16, (x, y, z): BlockPos) -> BlockPos {
+    if block::property(state, "half") == Some("upper") {
+        (x, y - 1, z)
+    } else {
+        (x, y + 1, z)
+    }
+}
+
+// Zweiter Flügel einer Doppeltür: dieselbe Tür daneben auf der Seite gegenüber den Angeln, gleich ausgerichtet, mit
+// den Angeln auf der anderen Seite und gerade genauso offen
+fn partner(world: &World, state: u16, (x, y, z): BlockPos) -> Option<BlockPos> {
+    let facing = block::property(state, "facing")?;
+    let hinge = block::property(state, "hinge")?;
+    let side = if hinge == "left" { clockwise(facing) } else { opposite(clockwise(facing)) };
+    let (dx, dz) = facing_offset(side);
+    let position = (x + dx, y, z + dz);
+    let other = world.get_block(position.0, position.1, position.2);
+    let matches = block::name(other) == block::name(state)
+        && block::property(other, "half") == block::property(state, "half")
+        && block::property(other, "facing") == Some(facing)
+        && block::property(other, "hinge") != Some(hinge)
+        && block::is_open(other) == block::is_open(state);
+    matches.then_some(position)
+}
+
+// Setzt den neuen Zustand, bei Türen auch für die andere Hälfte, und lässt beim Öffnen oder Schließen den Klang
+// hören; der Klickende hört ihn schon durch seinen Client
+fn set(players: &[Player], world: &mut World, position: BlockPos, state: u16, except: Option<Uuid>) {
+    let old = world.get_block(position.0, position.1, position.2);
+    if !world.set_block_and_notify(position, state) {
+        return;
+    }
+    if block::is_door(state) {
+        let other = other_half(state, position);
+        let other_state = world.get_block(other.0, other.1, other.2);
+        if block::name(other_state) == block::name(state) {
+            let half = block::property(other_state, "half").unwrap_or("upper");
+            world.set_block_and_notify(other, block::with_property(state, "half", half).unwrap_or(other_state));
+        }
+    }
+    if block::is_open(old) != block::is_open(state) {
+        let (open, close) = sounds(state);
+        let sound = if block::is_open(state) { open } else { close };
+        let center = (position.0 as f64 + 0.5, position.1 as f64 + 0.5, position.2 as f64 + 0.5);
+        for viewer in players.iter().filter(|p| Some(p.uuid) != except && entity::in_view(p, world.dimension, center)) {
+            let _ = worldevent::send_sound(&viewer.connection, sound, center, 1.0);
+        }
+    }
+}
+
+fn with_open(state: u16, open: bool) -> u16 {
+    block::with_property(state, "open", &open.to_string()).unwrap_or(state)
+}
+
+// Use Item On auf eine Tür, Falltür oder ein Zauntor: öffnet oder schließt sie, eine Doppeltür mit beiden Flügeln.
+// false, wenn nichts davon angeklickt wurde oder es sich nicht von Hand öffnen lässt
+pub fn use_on(players: &[Player], world: &mut World, index: usize, position: BlockPos) -> bool {
+    let state = world.get_block(position.0, position.1, position.2);
+    let player = &players[index];
+    if !is_openable(state) || !opens_by_hand(state) || player.game_mode == GameMode::Spectator {
+        return false;
+    }
+    let open = !block::is_open(state);
+    let mut new = with_open(state, open);
+    // Ein Zauntor schwingt vom Spieler weg auf
+    if open && block::is_fence_gate(state) {
+        let facing = ["south", "west", "north", "east"][((player.rotation.0 as f64 / 90.0 + 0.5).floor() as i32 & 3) as usize];
+        if block::property(state, "facing") == Some(opposite(facing)) {
+            new = block::with_property(new, "facing", facing).unwrap_or(new);
+        }
+    }
+    let partner = block::is_door(state).then(|| partner(world, state, position)).flatten();
+    trace!("{} {} {} bei {:?}", player.username, if open { "öffnet" } else { "schließt" }, block::name(state), position);
+    set(players, world, position, new, Some(player.uuid));
+    if let Some(partner) = partner {
+        let partner_state = world.get_block(partner.0, partner.1, partner.2);
+        set(players, world, partner, with_open(partner_state, open), None);
+    }
+    true
+}
+
+// Geplanter Tick nach einer Änderung in der Nachbarschaft: mit Strom geht es auf, ohne wieder zu. Bei Türen zählt
+// Strom an beiden Hälften
+pub fn scheduled_tick(players: &[Player], world: &mut World, position: BlockPos, state: u16) {
+    let other = block::is_door(state).then(|| other_half(state, position));
+    let powered = redstone::power_at(world, position) > 0 || other.is_some_and(|other| redstone::power_at(world, other) > 0);
+    if (block::property(state, "powered") == Some("true")) == powered {
+        return;
+    }
+    let new = block::with_property(with_open(state, powered), "powered", &powered.to_string()).unwrap_or(state);
+    set(players, world, position, new, None);
+}
+
+// Geht eine Türhälfte verloren, verschwindet die andere mit
+pub fn block_changed(world: &mut World, position: BlockPos, old: u16, new: u16) {
+    if !block::is_door(old) || block::name(new) == block::name(old) {
+        return;
+    }
+    let other = other_half(old, position);
+    if block::name(world.get_block(other.0, other.1, other.2)) == block::name(old) {
+        world.set_block_and_notify(other, block::AIR);
+    }
+}
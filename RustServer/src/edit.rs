@@ -0,0 +1,207 @@
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+use crate::item::ItemStack;
+use crate::schematic::Schematic;
+use crate::structure::{self, Placed};
+use crate::{apply_block_changes, block, send_system_message, BlockChange, Connection, Server};
+
+// Holzaxt wie bei WorldEdit: Linksklick setzt die erste, Rechtsklick die zweite Ecke
+pub const WAND: &str = "minecraft:wooden_axe";
+// Größte Auswahl, die eine Bearbeitung auf einmal anfasst
+pub const MAX_VOLUME: i64 = 1 << 20;
+// So viele Blöcke setzen Bearbeitungen zusammen höchstens pro Tick
+const BLOCKS_PER_TICK: usize = 8192;
+// Rückgängig machbare Bearbeitungen je Spieler
+const UNDO_LIMIT: usize = 16;
+
+pub type Region = ((i32, i32, i32), (i32, i32, i32));
+
+// Auswahl, Zwischenablage und Verlauf eines Spielers bzw. der Konsole
+#[derive(Debug, Default)]
+pub struct Session {
+    pub pos1: Option<(i32, i32, i32)>,
+    pub pos2: Option<(i32, i32, i32)>,
+    pub clipboard: Option<Schematic>,
+    // Vorherige Blöcke der letzten Bearbeitungen, die jüngste zuletzt
+    history: VecDeque<Vec<BlockChange>>,
+}
+
+impl Session {
+    // Kleinste und größte Ecke der Auswahl
+    pub fn region(&self) -> Result<Region, String> {
+        let (Some(a), Some(b)) = (self.pos1, self.pos2) else { return Err("Make a region selection first (/wand or /pos1 and /pos2)".to_string()) };
+        Ok(((a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)), (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2))))
+    }
+}
+
+pub fn volume((min, max): Region) -> i64 {
+    (max.0 - min.0 + 1) as i64 * (max.1 - min.1 + 1) as i64 * (max.2 - min.2 + 1) as i64
+}
+
+pub fn positions((min, max): Region) -> impl Iterator<Item = (i32, i32, i32)> {
+    (min.1..=max.1).flat_map(move |y| (min.2..=max.2).flat_map(move |z| (min.0..=max.0).map(move |x| (x, y, z))))
+}
+
+// Nur Blöcke ersetzen, die auf ein Prädikat wie bei /execute if block passen; das Ergebnis je Zustand wird gemerkt
+#[derive(Debug)]
+struct Filter {
+    spec: String,
+    cache: HashMap<u16, bool>,
+}
+
+impl Filter {
+    fn accepts(&mut self, state: u16) -> bool {
+        let spec = &self.spec;
+        *self.cache.entry(state).or_insert_with(|| block::matches(state, spec).unwrap_or(false))
+    }
+}
+
+// Eine laufende Bearbeitung; tick setzt sie in Portionen, damit große Auswahlen den Tick nicht aufhalten
+#[derive(Debug)]
+struct Job {
+    owner: Option<Uuid>,
+    changes: Vec<BlockChange>,
+    next: usize,
+    filter: Option<Filter>,
+    // Vorherige Blöcke für /undo; None bei /undo selbst
+    undo: Option<Vec<BlockChange>>,
+    // Block-Entities und Entities, die nach den Blöcken gesetzt werden
+    finish: Option<Placed>,
+    changed: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Editor {
+    sessions: HashMap<Option<Uuid>, Session>,
+    jobs: VecDeque<Job>,
+}
+
+impl Editor {
+    pub fn session(&mut self, owner: Option<Uuid>) -> &mut Session {
+        self.sessions.entry(owner).or_default()
+    }
+
+    pub fn remove(&mut self, owner: Option<Uuid>) {
+        self.sessions.remove(&owner);
+        self.jobs.retain(|job| job.owner != owner);
+    }
+
+    // Reiht Blockänderungen ein; mit filter nur dort, wo der alte Block passt
+    pub fn submit(&mut self, owner: Option<Uuid>, changes: Vec<BlockChange>, filter: Option<&str>, finish: Option<Placed>) {
+        let filter = filter.map(|spec| Filter { spec: spec.to_string(), cache: HashMap::new() });
+        self.jobs.push_back(Job { owner, changes, next: 0, filter, undo: Some(Vec::new()), finish, changed: 0 });
+    }
+
+    // Reiht die letzte Bearbeitung rückwärts wieder ein; false, wenn es keine gibt
+    pub fn undo(&mut self, owner: Option<Uuid>) -> bool {
+        let Some(mut previous) = self.session(owner).history.pop_back() else { return false };
+        previous.reverse();
+        self.jobs.push_back(Job { owner, changes: previous, next: 0, filter: None, undo: None, finish: None, changed: 0 });
+        true
+    }
+
+    pub fn pending(&self, owner: Option<Uuid>) -> usize {
+        self.jobs.iter().filter(|job| job.owner == owner).map(|job| job.changes.len() - job.next).sum()
+    }
+}
+
+fn notify(server: &Server, owner: Option<Uuid>, message: &str) {
+    match owner {
+        Some(uuid) => {
+            if let Some(player) = server.players.lock().unwrap().iter().find(|p| p.uuid == uuid) {
+                let _ = send_system_message(&player.connection, message);
+            }
+        }
+        None => info!("{}", message),
+    }
+}
+
+// Setzt pro Tick die nächste Portion der ältesten Bearbeitung in die Oberwelt
+pub fn tick(server: &Server) {
+    // Die Sperre auf den Editor kommt nach players und world, darf also beim Setzen nicht gehalten werden
+    let Some(mut job) = server.edits.lock().unwrap().jobs.pop_front() else { return };
+    let end = (job.next + BLOCKS_PER_TICK).min(job.changes.len());
+    let mut batch = Vec::with_capacity(end - job.next);
+    {
+        let mut world = server.world.lock().unwrap();
+        for &(position, state) in &job.changes[job.next..end] {
+            world.load_chunk_now(position.0 >> 4, position.2 >> 4);
+            let old = world.get_block(position.0, position.1, position.2);
+            if old == state || !job.filter.as_mut().is_none_or(|filter| filter.accepts(old)) {
+                continue;
+            }
+            if let Some(undo) = job.undo.as_mut() {
+                undo.push((position, old));
+            }
+            batch.push((position, state));
+        }
+    }
+    job.next = end;
+    job.changed += apply_block_changes(server, &batch);
+    if job.next < job.changes.len() {
+        server.edits.lock().unwrap().jobs.push_front(job);
+        return;
+    }
+    if let Some(finish) = &job.finish {
+        structure::place(server, finish);
+    }
+    let message = match job.undo {
+        Some(undo) => {
+            if !undo.is_empty() {
+                let mut edits = server.edits.lock().unwrap();
+                let history = &mut edits.session(job.owner).history;
+                history.push_back(undo);
+                while history.len() > UNDO_LIMIT {
+                    history.pop_front();
+                }
+            }
+            format!("{} block(s) changed", job.changed)
+        }
+        None => format!("Undid {} block change(s)", job.changed),
+    };
+    notify(server, job.owner, &message);
+}
+
+// Liest die Auswahl samt Block-Entities in eine Zwischenablage; origin ist die Position, an der später eingefügt wird
+pub fn copy(server: &Server, region: Region, origin: (i32, i32, i32)) -> Schematic {
+    let (min, max) = region;
+    let mut world = server.world.lock().unwrap();
+    let mut clipboard = Schematic {
+        size: (max.0 - min.0 + 1, max.1 - min.1 + 1, max.2 - min.2 + 1),
+        offset: (min.0 - origin.0, min.1 - origin.1, min.2 - origin.2),
+        ..Schematic::default()
+    };
+    for (x, y, z) in positions(region) {
+        world.load_chunk_now(x >> 4, z >> 4);
+        let relative = (x - min.0, y - min.1, z - min.2);
+        clipboard.blocks.push((relative, world.get_block(x, y, z)));
+        if let Some(entity) = world.block_entity((x, y, z)) {
+            clipboard.block_entities.push((relative, entity.to_json(relative)));
+        }
+    }
+    clipboard
+}
+
+// Gibt dem Spieler die Auswahlaxt
+pub fn wand() -> Result<ItemStack, String> {
+    ItemStack::parse(WAND)
+}
+
+// Auswahl per Axt; true, wenn der Klick damit erledigt ist. Wer /pos1 bzw. /pos2 nicht darf, baut ganz normal
+pub fn select_with_wand(server: &Server, (uuid, username): (Uuid, &str), connection: &Connection, held: &ItemStack, position: (i32, i32, i32), first: bool) -> bool {
+    let node = if first { "server.command.pos1" } else { "server.command.pos2" };
+    if held.item != WAND || !server.permissions.lock().unwrap().has(username, Some(uuid), node) {
+        return false;
+    }
+    let mut edits = server.edits.lock().unwrap();
+    let session = edits.session(Some(uuid));
+    if first {
+        session.pos1 = Some(position);
+    } else {
+        session.pos2 = Some(position);
+    }
+    let volume = session.region().map(|region| format!(" ({} blocks)", volume(region))).unwrap_or_default();
+    let (x, y, z) = position;
+    let _ = send_system_message(connection, &format!("{} position set to {}, {}, {}{}", if first { "First" } else { "Second" }, x, y, z, volume));
+    true
+}
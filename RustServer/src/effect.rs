@@ -0,0 +1,377 @@
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use crate::protocol::clientbound;
+use crate::protocol::version::ProtocolVersion;
+use crate::selector::Target;
+use crate::protocol::types::{write_string_to_vec, write_varint_to_vec};
+use crate::{damage, dimension, entity, Connection, Player, Server};
+
+// Registry minecraft:mob_effect von 1.21.1 in Protokollreihenfolge
+const EFFECTS: &[&str] = &[
+    "speed", "slowness", "haste", "mining_fatigue", "strength", "instant_health", "instant_damage", "jump_boost", "nausea",
+    "regeneration", "resistance", "fire_resistance", "water_breathing", "invisibility", "blindness", "night_vision", "hunger",
+    "weakness", "poison", "wither", "health_boost", "absorption", "saturation", "glowing", "levitation", "luck", "unluck",
+    "slow_falling", "conduit_power", "dolphins_grace", "bad_omen", "hero_of_the_village", "darkness", "trial_omen", "raid_omen",
+    "wind_charged", "weaving", "oozing", "infested",
+];
+// Ab trial_omen erst seit 1.20.5 bekannt
+const FIRST_1_20_5: usize = 33;
+
+pub const SPEED: usize = 0;
+pub const SLOWNESS: usize = 1;
+pub const STRENGTH: usize = 4;
+pub const INSTANT_HEALTH: usize = 5;
+pub const INSTANT_DAMAGE: usize = 6;
+pub const JUMP_BOOST: usize = 7;
+pub const REGENERATION: usize = 9;
+pub const RESISTANCE: usize = 10;
+pub const FIRE_RESISTANCE: usize = 11;
+pub const WATER_BREATHING: usize = 12;
+pub const INVISIBILITY: usize = 13;
+pub const BLINDNESS: usize = 14;
+pub const NIGHT_VISION: usize = 15;
+pub const HUNGER: usize = 16;
+pub const WEAKNESS: usize = 17;
+pub const POISON: usize = 18;
+pub const ABSORPTION: usize = 21;
+pub const LEVITATION: usize = 24;
+pub const LUCK: usize = 25;
+pub const SLOW_FALLING: usize = 27;
+pub const DOLPHINS_GRACE: usize = 29;
+pub const WIND_CHARGED: usize = 35;
+pub const WEAVING: usize = 36;
+pub const OOZING: usize = 37;
+pub const INFESTED: usize = 38;
+
+const FLAG_SHOW_PARTICLES: u8 = 0x02;
+const FLAG_SHOW_ICON: u8 = 0x04;
+
+// Dauer in Ticks, None für unendlich
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectInstance {
+    pub amplifier: u8,
+    pub duration: Option<i32>,
+    pub show_particles: bool,
+    // Ticks seit dem Anwenden; bestimmt, wann Regeneration und Gift wirken
+    elapsed: u32,
+}
+
+impl EffectInstance {
+    pub fn new(amplifier: u8, duration: Option<i32>, show_particles: bool) -> EffectInstance {
+        EffectInstance { amplifier, duration, show_particles, elapsed: 0 }
+    }
+
+    // Wie bei Vanilla: alle 50 bzw. 25 Ticks, mit jeder Stufe doppelt so oft
+    fn pulses(&self, interval: u32) -> bool {
+        let interval = interval >> self.amplifier.min(31);
+        interval == 0 || self.elapsed.is_multiple_of(interval)
+    }
+}
+
+// Was ein Tick der aktiven Effekte bewirkt hat
+#[derive(Debug, Default)]
+pub struct EffectTick {
+    pub heal: f32,
+    pub poison: f32,
+    pub expired: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Effects {
+    active: BTreeMap<usize, EffectInstance>,
+}
+
+impl Effects {
+    pub fn new() -> Effects {
+        Effects::default()
+    }
+
+    pub fn get(&self, id: usize) -> Option<&EffectInstance> {
+        self.active.get(&id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &EffectInstance)> {
+        self.active.iter().map(|(id, effect)| (*id, effect))
+    }
+
+    // Wie bei Vanilla ersetzt nur eine höhere Stufe oder bei gleicher Stufe eine längere Dauer den aktiven Effekt
+    pub fn add(&mut self, id: usize, effect: EffectInstance) -> bool {
+        let longer = |current: &EffectInstance| match (effect.duration, current.duration) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(new), Some(old)) => new > old,
+        };
+        match self.active.get(&id) {
+            Some(current) if effect.amplifier < current.amplifier => false,
+            Some(current) if effect.amplifier == current.amplifier && !longer(current) => false,
+            _ => {
+                self.active.insert(id, effect);
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: usize) -> bool {
+        self.active.remove(&id).is_some()
+    }
+
+    pub fn clear(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.active).into_keys().collect()
+    }
+
+    pub fn tick(&mut self) -> EffectTick {
+        let mut result = EffectTick::default();
+        for (id, effect) in self.active.iter_mut() {
+            match *id {
+                REGENERATION if effect.pulses(50) => result.heal += 1.0,
+                POISON if effect.pulses(25) => result.poison += 1.0,
+                _ => {}
+            }
+            effect.elapsed += 1;
+            if let Some(duration) = &mut effect.duration {
+                *duration -= 1;
+                if *duration <= 0 {
+                    result.expired.push(*id);
+                }
+            }
+        }
+        for id in &result.expired {
+            self.active.remove(id);
+        }
+        result
+    }
+
+    // Attributwerte, die Schnelligkeit/Langsamkeit und Stärke/Schwäche verändern
+    fn speed_multiplier(&self) -> f64 {
+        let level = |id| self.get(id).map_or(0.0, |e| e.amplifier as f64 + 1.0);
+        level(SPEED) * 0.2 - level(SLOWNESS) * 0.15
+    }
+
+    pub fn attack_bonus(&self) -> f32 {
+        let level = |id| self.get(id).map_or(0.0, |e| e.amplifier as f32 + 1.0);
+        level(STRENGTH) * 3.0 - level(WEAKNESS) * 4.0
+    }
+}
+
+// speed oder minecraft:speed
+pub fn id(name: &str) -> Option<usize> {
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    EFFECTS.iter().position(|effect| *effect == name)
+}
+
+// Englischer Anzeigename für Befehlsantworten, etwa "Jump Boost"
+pub fn display_name(id: usize) -> String {
+    match EFFECTS[id] {
+        "unluck" => "Bad Luck".to_string(),
+        "hero_of_the_village" => "Hero of the Village".to_string(),
+        name => name
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| first.to_ascii_uppercase().to_string() + chars.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+// Entity Effect; 1.20.4 kennt die neueren Effekte nicht und bekommt die Stufe als Byte
+pub fn send_add(connection: &Connection, entity_id: i32, id: usize, effect: &EffectInstance) -> Result<(), String> {
+    if connection.version == ProtocolVersion::V1_20_4 && id >= FIRST_1_20_5 {
+        return Ok(());
+    }
+    let mut packet_data = write_varint_to_vec(entity_id);
+    packet_data.extend(write_varint_to_vec(id as i32));
+    if connection.version == ProtocolVersion::V1_20_4 {
+        packet_data.push(effect.amplifier);
+    } else {
+        packet_data.extend(write_varint_to_vec(effect.amplifier as i32));
+    }
+    packet_data.extend(write_varint_to_vec(effect.duration.unwrap_or(-1)));
+    packet_data.push(FLAG_SHOW_ICON | if effect.show_particles { FLAG_SHOW_PARTICLES } else { 0 });
+    if connection.version == ProtocolVersion::V1_20_4 {
+        packet_data.push(0); // Keine Faktordaten
+    }
+    connection.send(clientbound::ENTITY_EFFECT, &packet_data)
+}
+
+pub fn send_remove(connection: &Connection, entity_id: i32, id: usize) -> Result<(), String> {
+    if connection.version == ProtocolVersion::V1_20_4 && id >= FIRST_1_20_5 {
+        return Ok(());
+    }
+    let mut packet_data = write_varint_to_vec(entity_id);
+    packet_data.extend(write_varint_to_vec(id as i32));
+    connection.send(clientbound::REMOVE_ENTITY_EFFECT, &packet_data)
+}
+
+// Update Attributes für Bewegungsgeschwindigkeit und Angriffsschaden. Der Client rechnet Effekte nicht selbst
+// in seine Attribute ein; die IDs der Attribute und Modifikatoren unterscheiden sich je nach Version
+pub fn send_attributes(connection: &Connection, entity_id: i32, effects: &Effects) -> Result<(), String> {
+    // (1.20.4-Schlüssel, ID 1.20.5, ID 1.21, Grundwert, Modifikator, Operation, Wert)
+    let attributes = [
+        ("minecraft:generic.movement_speed", 17, 21, 0.1, "91AEAA56-376B-4498-935B-2F7F68070635", "minecraft:effect.speed", 2u8, effects.speed_multiplier()),
+        ("minecraft:generic.attack_damage", 2, 2, 1.0, "648D7064-6A60-4F59-8ABE-C2C23A6DD7A9", "minecraft:effect.strength", 0u8, effects.attack_bonus() as f64),
+    ];
+    let mut packet_data = write_varint_to_vec(entity_id);
+    packet_data.extend(write_varint_to_vec(attributes.len() as i32));
+    for (key, id_1_20_5, id_1_21, base, uuid, modifier, operation, amount) in attributes {
+        match connection.version {
+            ProtocolVersion::V1_20_4 => packet_data.extend(write_string_to_vec(key)),
+            ProtocolVersion::V1_20_5 => packet_data.extend(write_varint_to_vec(id_1_20_5)),
+            ProtocolVersion::V1_21 => packet_data.extend(write_varint_to_vec(id_1_21)),
+        }
+        packet_data.extend(f64::to_be_bytes(base));
+        if amount == 0.0 {
+            packet_data.extend(write_varint_to_vec(0));
+            continue;
+        }
+        packet_data.extend(write_varint_to_vec(1));
+        match connection.version {
+            ProtocolVersion::V1_21 => packet_data.extend(write_string_to_vec(modifier)),
+            _ => packet_data.extend(Uuid::parse_str(uuid).map_err(|e| e.to_string())?.as_bytes()),
+        }
+        packet_data.extend(amount.to_be_bytes());
+        packet_data.push(operation);
+    }
+    connection.send(clientbound::UPDATE_ATTRIBUTES, &packet_data)
+}
+
+fn changes_attributes(id: usize) -> bool {
+    [SPEED, SLOWNESS, STRENGTH, WEAKNESS].contains(&id)
+}
+
+// Absorption füllt beim Anwenden 4 zusätzliche Lebenspunkte je Stufe auf; endet der Effekt, verfallen die übrigen
+fn sync_absorption(player: &mut Player, added: bool) {
+    let absorption = match player.effects.get(ABSORPTION) {
+        Some(effect) if added => player.absorption.max(4.0 * (effect.amplifier as f32 + 1.0)),
+        Some(_) => player.absorption,
+        None => 0.0,
+    };
+    if absorption != player.absorption {
+        player.absorption = absorption;
+        let _ = entity::send_absorption(&player.connection, entity::OWN_PLAYER_ID, absorption);
+    }
+}
+
+// Nimmt einem Spieler alle Effekte und gibt ihm stattdessen die neuen, etwa beim Totem der Unsterblichkeit
+pub fn reset(player: &mut Player, effects: &[(usize, EffectInstance)]) {
+    let removed = player.effects.clear();
+    for id in &removed {
+        let _ = send_remove(&player.connection, entity::OWN_PLAYER_ID, *id);
+    }
+    for &(id, effect) in effects {
+        player.effects.add(id, effect);
+        let _ = send_add(&player.connection, entity::OWN_PLAYER_ID, id, &effect);
+    }
+    if removed.iter().chain(effects.iter().map(|(id, _)| id)).any(|id| changes_attributes(*id)) {
+        let _ = send_attributes(&player.connection, entity::OWN_PLAYER_ID, &player.effects);
+    }
+    sync_absorption(player, true);
+}
+
+// Für /effect give; false, wenn das Ziel keine Effekte tragen kann oder schon einen stärkeren hat.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn apply(server: &Server, target: &Target, id: usize, effect: EffectInstance) -> bool {
+    let mut players = server.players.lock().unwrap();
+    if target.is_player() {
+        let Some(player) = players.iter_mut().find(|p| p.uuid == target.uuid && p.health > 0.0) else { return false };
+        if !player.effects.add(id, effect) {
+            return false;
+        }
+        let _ = send_add(&player.connection, entity::OWN_PLAYER_ID, id, &effect);
+        if changes_attributes(id) {
+            let _ = send_attributes(&player.connection, entity::OWN_PLAYER_ID, &player.effects);
+        }
+        sync_absorption(player, id == ABSORPTION);
+        return true;
+    }
+    let Some(lock) = dimension::world_with_mob(server, target.uuid) else { return false };
+    let mut world = lock.lock().unwrap();
+    let dimension = world.dimension;
+    let Some(mob) = world.mobs.iter_mut().find(|m| m.id == target.uuid) else { return false };
+    if !entity::is_living(&mob.mob_type) || !mob.effects.add(id, effect) {
+        return false;
+    }
+    for player in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+        let _ = send_add(&player.connection, mob.entity_id, id, &effect);
+    }
+    true
+}
+
+// Für /effect clear: einen bestimmten oder alle Effekte; false, wenn es nichts zu entfernen gab
+pub fn remove(server: &Server, target: &Target, id: Option<usize>) -> bool {
+    let take = |effects: &mut Effects| match id {
+        Some(id) => if effects.remove(id) { vec![id] } else { Vec::new() },
+        None => effects.clear(),
+    };
+    let mut players = server.players.lock().unwrap();
+    if target.is_player() {
+        let Some(player) = players.iter_mut().find(|p| p.uuid == target.uuid) else { return false };
+        let removed = take(&mut player.effects);
+        for id in &removed {
+            let _ = send_remove(&player.connection, entity::OWN_PLAYER_ID, *id);
+        }
+        if removed.iter().any(|id| changes_attributes(*id)) {
+            let _ = send_attributes(&player.connection, entity::OWN_PLAYER_ID, &player.effects);
+        }
+        sync_absorption(player, false);
+        return !removed.is_empty();
+    }
+    let Some(lock) = dimension::world_with_mob(server, target.uuid) else { return false };
+    let mut world = lock.lock().unwrap();
+    let dimension = world.dimension;
+    let Some(mob) = world.mobs.iter_mut().find(|m| m.id == target.uuid) else { return false };
+    let removed = take(&mut mob.effects);
+    for player in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+        for id in &removed {
+            let _ = send_remove(&player.connection, mob.entity_id, *id);
+        }
+    }
+    !removed.is_empty()
+}
+
+// Ein Tick aller Effekte von Spielern und Mobs. Gift lässt wie bei Vanilla mindestens einen Lebenspunkt übrig.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut players = server.players.lock().unwrap();
+    for player in players.iter_mut().filter(|p| p.health > 0.0 && !p.effects.is_empty()) {
+        let result = player.effects.tick();
+        let health = (player.health + result.heal).min(damage::MAX_HEALTH);
+        let health = if result.poison > 0.0 && health > 1.0 { (health - result.poison).max(1.0) } else { health };
+        if health != player.health {
+            player.health = health;
+            let _ = damage::send_health(player);
+        }
+        for id in &result.expired {
+            let _ = send_remove(&player.connection, entity::OWN_PLAYER_ID, *id);
+        }
+        if result.expired.iter().any(|id| changes_attributes(*id)) {
+            let _ = send_attributes(&player.connection, entity::OWN_PLAYER_ID, &player.effects);
+        }
+        sync_absorption(player, false);
+    }
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let dimension = world.dimension;
+        for mob in world.mobs.iter_mut().filter(|m| !m.effects.is_empty()) {
+            let result = mob.effects.tick();
+            let health = (mob.health + result.heal).min(entity::max_health(&mob.mob_type));
+            let health = if result.poison > 0.0 && health > 1.0 { (health - result.poison).max(1.0) } else { health };
+            let changed = health != mob.health;
+            mob.health = health;
+            for player in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+                if changed {
+                    let _ = entity::send_health(&player.connection, mob.entity_id, health);
+                }
+                for id in &result.expired {
+                    let _ = send_remove(&player.connection, mob.entity_id, *id);
+                }
+            }
+        }
+    }
+}
+
@@ -0,0 +1,129 @@
+use rand::Rng;
+use uuid::Uuid;
+use crate::gamemode::GameMode;
+use crate::{effect, entity, inventory, item, pose, Player, Server};
+
+const ELYTRA: &str = "minecraft:elytra";
+const FIREWORK_ROCKET: &str = "minecraft:firework_rocket";
+// Jede Sekunde im Gleitflug kostet einen Punkt Haltbarkeit
+const DAMAGE_INTERVAL: u32 = 20;
+// Flugdauer einer Rakete ohne fireworks-Komponente; sie brennt 10 Ticks je Stufe und einen Zufallsanteil länger
+const FLIGHT_DURATION: u32 = 1;
+
+#[derive(Debug, Clone)]
+struct Rocket {
+    entity_id: i32,
+    ticks_left: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Glide {
+    ticks: u32,
+    rockets: Vec<Rocket>,
+}
+
+// Eine Elytra trägt nur, solange ihr mehr als ein Punkt Haltbarkeit bleibt
+fn usable(player: &Player) -> bool {
+    let chest = player.inventory.slot(inventory::CHEST);
+    let max = item::max_damage(ELYTRA).unwrap_or(u32::MAX);
+    chest.item == ELYTRA && chest.components.damage.unwrap_or(0) + 1 < max
+}
+
+// Start Fall Flying: in der Luft, mit brauchbarer Elytra und ohne Schwebeeffekt
+pub fn can_glide(player: &Player) -> bool {
+    player.game_mode != GameMode::Spectator
+        && !player.pose.gliding
+        && !player.movement.on_ground()
+        && player.effects.get(effect::LEVITATION).is_none()
+        && usable(player)
+}
+
+// Beendet den Gleitflug; der eigene Client erfährt es mit, falls die Elytra ihm unter dem Flug kaputtgeht
+pub fn stop(players: &mut [Player], index: usize) {
+    let player = &mut players[index];
+    if !std::mem::take(&mut player.pose.gliding) {
+        return;
+    }
+    player.glide.ticks = 0;
+    let _ = entity::send_pose(&player.connection, entity::OWN_PLAYER_ID, &player.pose);
+    pose::broadcast(players, index);
+}
+
+// Use Item mit einer Feuerwerksrakete im Gleitflug; false, wenn das Item keine ist oder der Spieler nicht gleitet
+pub fn boost(players: &mut [Player], index: usize, slot: usize) -> bool {
+    let player = &mut players[index];
+    if player.inventory.slot(slot).item != FIREWORK_ROCKET || !player.pose.gliding {
+        return false;
+    }
+    let mut stack = player.inventory.slot(slot).clone();
+    stack.count = 1;
+    if player.game_mode != GameMode::Creative {
+        player.inventory.consume_one(slot);
+        let _ = player.inventory.send_slot(&player.connection, slot);
+    }
+    let mut rng = rand::thread_rng();
+    let ticks_left = 10 * (FLIGHT_DURATION + 1) + rng.gen_range(0..6) + rng.gen_range(0..7);
+    let rocket = Rocket { entity_id: entity::next_id(), ticks_left };
+    let (uuid, position) = (Uuid::new_v4(), player.position);
+    let _ = entity::send_firework(&player.connection, rocket.entity_id, uuid, &stack, position, entity::OWN_PLAYER_ID);
+    player.glide.rockets.push(rocket.clone());
+    let (owner, shooter, dimension) = (player.uuid, player.entity_id, player.dimension);
+    for viewer in players.iter().filter(|p| p.uuid != owner && entity::in_view(p, dimension, position)) {
+        let _ = entity::send_firework(&viewer.connection, rocket.entity_id, uuid, &stack, position, shooter);
+    }
+    true
+}
+
+// Nutzt gleitende Elytren ab, beendet Flüge ohne brauchbare Elytra und entfernt ausgebrannte Raketen.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut players = server.players.lock().unwrap();
+    for index in 0..players.len() {
+        burn_rockets(&mut players, index);
+        let player = &mut players[index];
+        if !player.pose.gliding {
+            continue;
+        }
+        player.glide.ticks += 1;
+        if player.glide.ticks.is_multiple_of(DAMAGE_INTERVAL) && player.game_mode != GameMode::Creative {
+            wear(player);
+        }
+        if !usable(&players[index]) || players[index].health <= 0.0 {
+            stop(&mut players, index);
+        }
+    }
+}
+
+// Zerbrechen kann sie dabei nicht, vorher endet der Flug mit dem letzten Punkt
+fn wear(player: &mut Player) {
+    let mut chest = player.inventory.slot(inventory::CHEST).clone();
+    let before = chest.components.damage;
+    item::wear(&mut chest);
+    if chest.components.damage == before {
+        return;
+    }
+    player.inventory.set(inventory::CHEST, chest);
+    let _ = player.inventory.send_slot(&player.connection, inventory::CHEST);
+}
+
+fn burn_rockets(players: &mut [Player], index: usize) {
+    let rockets = &mut players[index].glide.rockets;
+    if rockets.is_empty() {
+        return;
+    }
+    let mut burnt = Vec::new();
+    rockets.retain_mut(|rocket| {
+        rocket.ticks_left -= 1;
+        if rocket.ticks_left == 0 {
+            burnt.push(rocket.entity_id);
+        }
+        rocket.ticks_left > 0
+    });
+    if burnt.is_empty() {
+        return;
+    }
+    let player = &players[index];
+    for viewer in players.iter().filter(|p| p.uuid == player.uuid || entity::in_view(p, player.dimension, player.position)) {
+        let _ = entity::send_remove(&viewer.connection, &burnt);
+    }
+}
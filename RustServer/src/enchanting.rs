@@ -0,0 +1,244 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::gamemode::GameMode;
+use crate::item::{self, ItemStack};
+use crate::protocol::clientbound;
+use crate::window::{self, Window};
+use crate::{block, experience, recipe, Connection, Player, World};
+
+// Slots des Zaubertischs: das zu verzaubernde Item und Lapislazuli
+pub const ITEM: usize = 0;
+pub const LAPIS: usize = 1;
+pub const SLOTS: usize = 2;
+pub const LAPIS_LAZULI: &str = "minecraft:lapis_lazuli";
+const BOOK: &str = "minecraft:book";
+const ENCHANTED_BOOK: &str = "minecraft:enchanted_book";
+const BOOKSHELF: &str = "minecraft:bookshelf";
+pub const USE_SOUND: &str = "minecraft:block.enchantment_table.use";
+const MAX_BOOKSHELVES: u32 = 15;
+// Set Container Property: 0-2 Kosten der drei Knöpfe, 3 Seed für die Runen, 4-6 Verzauberung und 7-9 Stufe des Hinweises
+const PROPERTY_SEED: i16 = 3;
+const PROPERTY_CLUE: i16 = 4;
+const PROPERTY_CLUE_LEVEL: i16 = 7;
+
+// Verzauberungen des Zaubertischs wie bei Vanilla: Name, Gewicht, Höchststufe, Mindest- und Höchstkosten (Grundwert
+// und Zuwachs je Stufe) und die Gruppe der Items, für die sie der Tisch anbietet. Schätze wie Mending fehlen
+type TableEnchantment = (&'static str, u32, u32, (i32, i32), (i32, i32), &'static str);
+
+const TABLE_ENCHANTMENTS: &[TableEnchantment] = &[
+    ("minecraft:protection", 10, 4, (1, 11), (12, 11), "armor"),
+    ("minecraft:fire_protection", 5, 4, (10, 8), (18, 8), "armor"),
+    ("minecraft:feather_falling", 5, 4, (5, 6), (11, 6), "feet"),
+    ("minecraft:blast_protection", 2, 4, (5, 8), (13, 8), "armor"),
+    ("minecraft:projectile_protection", 5, 4, (3, 6), (9, 6), "armor"),
+    ("minecraft:respiration", 2, 3, (10, 10), (40, 10), "head"),
+    ("minecraft:aqua_affinity", 2, 1, (1, 0), (41, 0), "head"),
+    ("minecraft:thorns", 1, 3, (10, 20), (60, 20), "chest"),
+    ("minecraft:depth_strider", 2, 3, (10, 10), (25, 10), "feet"),
+    ("minecraft:sharpness", 10, 5, (1, 11), (21, 11), "sword"),
+    ("minecraft:smite", 5, 5, (5, 8), (25, 8), "sword"),
+    ("minecraft:bane_of_arthropods", 5, 5, (5, 8), (25, 8), "sword"),
+    ("minecraft:knockback", 5, 2, (5, 20), (55, 20), "sword"),
+    ("minecraft:fire_aspect", 2, 2, (10, 20), (60, 20), "fire_aspect"),
+    ("minecraft:looting", 2, 3, (15, 9), (65, 9), "sword"),
+    ("minecraft:sweeping_edge", 2, 3, (5, 9), (20, 9), "sword"),
+    ("minecraft:efficiency", 10, 5, (1, 10), (51, 10), "mining"),
+    ("minecraft:silk_touch", 1, 1, (15, 0), (65, 0), "mining_loot"),
+    ("minecraft:unbreaking", 5, 3, (5, 8), (55, 8), "durability"),
+    ("minecraft:fortune", 2, 3, (15, 9), (65, 9), "mining_loot"),
+    ("minecraft:power", 10, 5, (1, 10), (16, 10), "bow"),
+    ("minecraft:punch", 2, 2, (12, 20), (37, 20), "bow"),
+    ("minecraft:flame", 2, 1, (20, 0), (50, 0), "bow"),
+    ("minecraft:infinity", 1, 1, (20, 0), (50, 0), "bow"),
+    ("minecraft:luck_of_the_sea", 2, 3, (15, 9), (65, 9), "fishing"),
+    ("minecraft:lure", 2, 3, (15, 9), (65, 9), "fishing"),
+    ("minecraft:loyalty", 5, 3, (12, 7), (50, 0), "trident"),
+    ("minecraft:impaling", 2, 5, (1, 8), (21, 8), "trident"),
+    ("minecraft:riptide", 2, 3, (17, 7), (50, 0), "trident"),
+    ("minecraft:channeling", 1, 1, (25, 0), (50, 0), "trident"),
+    ("minecraft:multishot", 2, 1, (20, 0), (50, 0), "crossbow"),
+    ("minecraft:quick_charge", 5, 3, (12, 20), (50, 0), "crossbow"),
+    ("minecraft:piercing", 10, 4, (1, 10), (50, 0), "crossbow"),
+    ("minecraft:density", 5, 5, (5, 8), (25, 8), "mace"),
+    ("minecraft:breach", 2, 4, (15, 9), (65, 9), "mace"),
+];
+
+// Was der Zaubertisch zu seinen Slots weiß: Bücherregale in Reichweite beim Öffnen, den Seed des Spielers und die
+// daraus berechneten Kosten und Hinweise der drei Knöpfe
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Enchanting {
+    bookshelves: u32,
+    seed: i32,
+    pub costs: [i32; 3],
+    clues: [Option<(String, u32)>; 3],
+}
+
+impl Enchanting {
+    pub fn new(bookshelves: u32, seed: i32) -> Enchanting {
+        Enchanting { bookshelves: bookshelves.min(MAX_BOOKSHELVES), seed, ..Enchanting::default() }
+    }
+
+    // Kosten wie bei Vanilla nach Bücherregalen und Zufall aus dem Seed; ohne verzauberbares Item bleiben alle 0
+    pub fn update(&mut self, slots: &[ItemStack]) {
+        self.costs = [0; 3];
+        self.clues = Default::default();
+        let stack = &slots[ITEM];
+        if !is_enchantable(stack) {
+            return;
+        }
+        let shelves = self.bookshelves as i32;
+        let mut rng = StdRng::seed_from_u64(self.seed as u32 as u64);
+        for button in 0..3 {
+            let base = rng.gen_range(1..=8) + (shelves >> 1) + rng.gen_range(0..=shelves);
+            let cost = match button {
+                0 => (base / 3).max(1),
+                1 => base * 2 / 3 + 1,
+                _ => base.max(shelves * 2),
+            };
+            self.costs[button] = if cost < button as i32 + 1 { 0 } else { cost };
+        }
+        for button in 0..3 {
+            if self.costs[button] > 0 {
+                let enchantments = self.enchantments(stack, button);
+                self.clues[button] = enchantments.get(rng.gen_range(0..enchantments.len().max(1))).cloned();
+            }
+        }
+    }
+
+    // Die Verzauberungen, die ein Knopf geben wird; hängen nur von Seed, Knopf, Kosten und Item ab
+    fn enchantments(&self, stack: &ItemStack, button: usize) -> Vec<(String, u32)> {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(button as i32) as u32 as u64);
+        let Some(enchantability) = item::enchantability(&stack.item) else { return Vec::new() };
+        let book = stack.item == BOOK;
+        let quarter = enchantability as i32 / 4 + 1;
+        let level = self.costs[button] + 1 + rng.gen_range(0..quarter) + rng.gen_range(0..quarter);
+        let spread = (rng.gen::<f32>() + rng.gen::<f32>() - 1.0) * 0.15;
+        let mut level = ((level as f32 + level as f32 * spread).round() as i32).max(1);
+        let mut available: Vec<(&TableEnchantment, u32)> = TABLE_ENCHANTMENTS
+            .iter()
+            .filter(|enchantment| book || recipe::supports(enchantment.5, &stack.item))
+            .filter_map(|enchantment| {
+                let &(_, _, max_level, (min_base, min_step), (max_base, max_step), _) = enchantment;
+                (1..=max_level).rev().find(|&l| level >= min_base + min_step * (l as i32 - 1) && level <= max_base + max_step * (l as i32 - 1)).map(|l| (enchantment, l))
+            })
+            .collect();
+        let mut chosen: Vec<(String, u32)> = Vec::new();
+        loop {
+            let total: u32 = available.iter().map(|(enchantment, _)| enchantment.1).sum();
+            if total == 0 {
+                break;
+            }
+            let mut pick = rng.gen_range(0..total);
+            let Some(&(enchantment, l)) = available.iter().find(|(enchantment, _)| {
+                if pick < enchantment.1 {
+                    return true;
+                }
+                pick -= enchantment.1;
+                false
+            }) else {
+                break;
+            };
+            chosen.push((enchantment.0.to_string(), l));
+            available.retain(|(other, _)| other.0 != enchantment.0 && !recipe::exclusive(other.0, enchantment.0));
+            if rng.gen_range(0..50) > level {
+                break;
+            }
+            level /= 2;
+        }
+        // Ein Buch bekommt wie bei Vanilla eine Verzauberung weniger
+        if book && chosen.len() > 1 {
+            chosen.remove(rng.gen_range(0..chosen.len()));
+        }
+        chosen
+    }
+
+    // Set Container Property für Kosten, Runen-Seed und Hinweise
+    pub fn send(&self, connection: &Connection, window_id: u8) -> Result<(), String> {
+        let mut properties = vec![(PROPERTY_SEED, self.seed & -16)];
+        for button in 0..3 {
+            let clue = self.clues[button].as_ref();
+            let id = clue.and_then(|(name, _)| item::enchantment_id(name, connection.version)).unwrap_or(-1);
+            properties.push((button as i16, self.costs[button]));
+            properties.push((PROPERTY_CLUE + button as i16, id));
+            properties.push((PROPERTY_CLUE_LEVEL + button as i16, if id >= 0 { clue.map_or(-1, |(_, level)| *level as i32) } else { -1 }));
+        }
+        for (property, value) in properties {
+            let mut packet_data = vec![window_id];
+            packet_data.extend(property.to_be_bytes());
+            // Der Wert ist ein Short; den Seed schneidet auch Vanilla auf die unteren 16 Bit
+            packet_data.extend((value as i16).to_be_bytes());
+            connection.send(clientbound::SET_CONTAINER_PROPERTY, &packet_data)?;
+        }
+        Ok(())
+    }
+}
+
+// Ein einzelnes, noch unverzaubertes Item, das der Tisch kennt
+fn is_enchantable(stack: &ItemStack) -> bool {
+    !stack.is_empty() && stack.components.enchantments.is_empty() && item::enchantability(&stack.item).is_some()
+}
+
+// Bücherregale im Ring zwei Blöcke um den Tisch auf dessen Höhe und eins darüber; der Block dazwischen muss frei sein
+pub fn bookshelves(world: &World, (x, y, z): (i32, i32, i32)) -> u32 {
+    let mut count = 0;
+    for dx in -2i32..=2 {
+        for dz in -2i32..=2 {
+            if i32::max(dx.abs(), dz.abs()) != 2 {
+                continue;
+            }
+            for dy in 0..=1 {
+                let between = world.get_block(x + dx / 2, y + dy, z + dz / 2);
+                if block::name(world.get_block(x + dx, y + dy, z + dz)) == BOOKSHELF && (block::name(between) == "minecraft:air" || block::is_fluid(between)) {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+// Click Container Button am Zaubertisch: Knopf 0-2 kostet so viele Stufen und Lapislazuli, wie er zählt, verlangt aber
+// die angezeigten Stufen. Danach würfelt der Spieler einen neuen Seed. true, wenn verzaubert wurde
+pub fn enchant(player: &mut Player, window: &mut Window, button: u8) -> bool {
+    let Some(enchanting) = window.enchanting.as_mut() else { return false };
+    let button = button as usize;
+    let creative = player.game_mode == GameMode::Creative;
+    let paid = button as i32 + 1;
+    let Some(&cost) = enchanting.costs.get(button) else { return false };
+    let lapis = &window.slots[LAPIS];
+    if cost <= 0 || (!creative && ((lapis.item != LAPIS_LAZULI || (lapis.count as i32) < paid) || player.experience.level < cost)) {
+        return false;
+    }
+    let enchantments = enchanting.enchantments(&window.slots[ITEM], button);
+    if enchantments.is_empty() {
+        return false;
+    }
+    let stack = &mut window.slots[ITEM];
+    debug!("{} verzaubert {} mit {:?}", player.username, stack.item, enchantments);
+    if stack.item == BOOK {
+        stack.item = ENCHANTED_BOOK.to_string();
+        stack.components.stored_enchantments.extend(enchantments);
+    } else {
+        stack.components.enchantments.extend(enchantments);
+    }
+    if !creative {
+        let lapis = &mut window.slots[LAPIS];
+        lapis.count -= paid as u8;
+        if lapis.count == 0 {
+            *lapis = ItemStack::empty();
+        }
+        player.experience.set_level(player.experience.level - paid);
+        let _ = experience::send(&player.connection, &player.experience);
+    }
+    player.enchantment_seed = rand::random();
+    enchanting.seed = player.enchantment_seed;
+    enchanting.update(&window.slots);
+    window.set_sent(window.slots.clone());
+    for slot in [ITEM, LAPIS] {
+        let _ = window::send_slot(&player.connection, window, slot, &window.slots[slot]);
+    }
+    if let Some(enchanting) = window.enchanting.as_ref() {
+        let _ = enchanting.send(&player.connection, window.id);
+    }
+    true
+}
@@ -0,0 +1,554 @@
+use std::collections::{BTreeMap, HashSet};
+use std::f64::consts::PI;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use uuid::Uuid;
+use crate::damage::{self, DamageSource, Hit};
+use crate::dimension::{self, Dimension};
+use crate::json::Json;
+use crate::lang::Translations;
+use crate::protocol::clientbound;
+use crate::protocol::types::{write_uuid, write_varint_to_vec};
+use crate::selector::Target;
+use crate::text::Text;
+use crate::{block, entity, experience, explosion, motion, Connection, Mob, Player, Server, World};
+
+pub const DRAGON: &str = "minecraft:ender_dragon";
+pub const CRYSTAL: &str = "minecraft:end_crystal";
+
+// Hauptinsel aus Endstein: Oberkante über dem Ursprung und Radius
+pub const ISLAND_TOP: i32 = 64;
+const ISLAND_RADIUS: f64 = 90.0;
+const ISLAND_DEPTH: f64 = 40.0;
+// Hier kommen Spieler an, auf einer Obsidianplattform wie bei Vanilla
+pub const ENTRY_POINT: (i32, i32, i32) = (100, 49, 0);
+// Zehn Obsidiansäulen im Kreis um das Austrittsportal
+const SPIKE_COUNT: usize = 10;
+const SPIKE_DISTANCE: f64 = 42.0;
+// Das Austrittsportal steht auf der Insel über dem Ursprung, mit einer vier Blöcke hohen Säule in der Mitte
+const PORTAL_Y: i32 = ISLAND_TOP + 1;
+const PORTAL_PILLAR_HEIGHT: i32 = 4;
+
+// Der Kampf erfasst Spieler bis 192 Blöcke um den Ursprung, wie bei Vanilla
+const ARENA_RADIUS: f64 = 192.0;
+const VIEWER_UPDATE_TICKS: u32 = 20;
+const DRAGON_SPAWN: (f64, f64, f64) = (0.5, 128.0, 0.5);
+// Kreisflug um die Säulen, Sturzflug auf Spieler und Landung auf dem Portal
+const CIRCLE_RADIUS: f64 = 60.0;
+const CIRCLE_HEIGHT: f64 = 90.0;
+const CRUISE_SPEED: f64 = 0.6;
+const CHARGE_SPEED: f64 = 1.0;
+const DECISION_TICKS: u32 = 100;
+const CHARGE_TICKS: u32 = 100;
+const PERCH_TICKS: u32 = 200;
+const TAKEOFF_HEIGHT: f64 = 100.0;
+const DEATH_TICKS: u32 = 200;
+const DEATH_RISE: f64 = 0.1;
+// Trifft der Drache im Sturzflug einen Spieler
+const HIT_RANGE: f64 = 4.0;
+const HIT_DAMAGE: f32 = 10.0;
+// Stoß weg vom Drachen wie bei Vanilla
+const DRAGON_PUSH: f64 = 4.0;
+const DRAGON_LIFT: f64 = 0.2;
+// Kristalle heilen den Drachen in der Nähe alle zehn Ticks um einen Lebenspunkt
+const HEAL_RANGE: f64 = 32.0;
+const HEAL_INTERVAL: u32 = 10;
+// Explodiert der heilende Kristall, trifft es den Drachen mit
+const CRYSTAL_DRAGON_DAMAGE: f32 = 10.0;
+const EXPLOSION_POWER: f64 = 6.0;
+const FIRST_KILL_XP: i32 = 12000;
+const KILL_XP: i32 = 500;
+
+// Boss Event: Aktionen, Farbe Pink ohne Unterteilung, Bossmusik und Nebel
+const BAR_ADD: i32 = 0;
+const BAR_REMOVE: i32 = 1;
+const BAR_UPDATE_HEALTH: i32 = 2;
+const BAR_PINK: i32 = 0;
+const BAR_NO_DIVISIONS: i32 = 0;
+const BAR_FLAGS: u8 = 0x02 | 0x04;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Spike {
+    pub x: i32,
+    pub z: i32,
+    pub radius: i32,
+    pub height: i32,
+}
+
+impl Spike {
+    pub fn contains(&self, x: i32, z: i32) -> bool {
+        let (dx, dz) = (x - self.x, z - self.z);
+        dx * dx + dz * dz <= self.radius * self.radius + 1
+    }
+
+    // Der Kristall steht auf dem Bedrock an der Spitze
+    fn crystal_position(&self) -> (f64, f64, f64) {
+        (self.x as f64 + 0.5, (self.height + 1) as f64, self.z as f64 + 0.5)
+    }
+}
+
+// Wie bei Vanilla: die Größen nach dem Seed gemischt, Radius 2 bis 5 und Höhe 76 bis 103
+pub fn spikes(seed: u64) -> Vec<Spike> {
+    let mut sizes: Vec<i32> = (0..SPIKE_COUNT as i32).collect();
+    sizes.shuffle(&mut StdRng::seed_from_u64(seed));
+    sizes
+        .into_iter()
+        .enumerate()
+        .map(|(i, size)| {
+            let angle = 2.0 * (-PI + PI / SPIKE_COUNT as f64 * i as f64);
+            Spike {
+                x: (SPIKE_DISTANCE * angle.cos()).floor() as i32,
+                z: (SPIKE_DISTANCE * angle.sin()).floor() as i32,
+                radius: 2 + size / 3,
+                height: 76 + size * 3,
+            }
+        })
+        .collect()
+}
+
+// Ober- und Unterkante der Insel in einer Säule: oben flach, nach unten gewölbt
+pub fn island_column(x: i32, z: i32) -> Option<(i32, i32)> {
+    let r = ((x * x + z * z) as f64).sqrt() / ISLAND_RADIUS;
+    if r >= 1.0 {
+        return None;
+    }
+    let top = ISLAND_TOP - (r.powi(3) * 10.0) as i32;
+    let bottom = ISLAND_TOP - ((1.0 - r * r).sqrt() * ISLAND_DEPTH) as i32;
+    Some((top, bottom))
+}
+
+// Bedrock-Schale mit Säule in der Mitte; erst nach dem Sieg füllt End-Portal die Schale
+pub fn exit_portal(active: bool) -> Vec<((i32, i32, i32), u16)> {
+    let mut blocks = Vec::new();
+    for dx in -3i32..=3 {
+        for dz in -3i32..=3 {
+            let distance = dx * dx + dz * dz;
+            if distance > 12 {
+                continue;
+            }
+            blocks.push(((dx, PORTAL_Y, dz), block::BEDROCK));
+            let state = match distance {
+                0 => block::BEDROCK,
+                d if d > 6 => block::BEDROCK,
+                _ if active => block::END_PORTAL,
+                _ => block::AIR,
+            };
+            blocks.push(((dx, PORTAL_Y + 1, dz), state));
+        }
+    }
+    for dy in 2..=PORTAL_PILLAR_HEIGHT {
+        blocks.push(((0, PORTAL_Y + dy, 0), block::BEDROCK));
+    }
+    blocks
+}
+
+// Auf der Säule liegt nach dem ersten Sieg das Drachenei, dort landet auch der Drache
+fn pillar_top() -> (i32, i32, i32) {
+    (0, PORTAL_Y + PORTAL_PILLAR_HEIGHT + 1, 0)
+}
+
+pub fn entry_platform() -> Vec<((i32, i32, i32), u16)> {
+    let (x, y, z) = ENTRY_POINT;
+    (-2..=2).flat_map(|dx| (-2..=2).map(move |dz| ((x + dx, y - 1, z + dz), block::OBSIDIAN))).collect()
+}
+
+// IDs wie im Phasenfeld der Drachen-Metadaten
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    HoldingPattern,
+    LandingApproach,
+    Takeoff,
+    Perching,
+    Charging,
+    Dying,
+}
+
+impl Phase {
+    fn id(self) -> i32 {
+        match self {
+            Phase::HoldingPattern => 0,
+            Phase::LandingApproach => 2,
+            Phase::Takeoff => 4,
+            Phase::Perching => 6,
+            Phase::Charging => 8,
+            Phase::Dying => 9,
+        }
+    }
+}
+
+// Zustand des Drachenkampfs in der Welt des Endes; gespeichert wird nur, ob der Drache schon besiegt wurde
+#[derive(Debug, Clone)]
+pub struct DragonFight {
+    dragon_killed: bool,
+    previously_killed: bool,
+    dragon: Option<Uuid>,
+    phase: Phase,
+    phase_ticks: u32,
+    phase_changed: bool,
+    target: Option<Uuid>,
+    angle: f64,
+    healing_crystal: Option<Uuid>,
+    bar: Uuid,
+    viewers: HashSet<Uuid>,
+    sent_health: f32,
+    ticks: u32,
+}
+
+impl DragonFight {
+    pub fn new() -> DragonFight {
+        DragonFight {
+            dragon_killed: false,
+            previously_killed: false,
+            dragon: None,
+            phase: Phase::HoldingPattern,
+            phase_ticks: 0,
+            phase_changed: false,
+            target: None,
+            angle: 0.0,
+            healing_crystal: None,
+            bar: Uuid::new_v4(),
+            viewers: HashSet::new(),
+            sent_health: 0.0,
+            ticks: 0,
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let mut data = BTreeMap::new();
+        data.insert("dragon_killed".to_string(), Json::Bool(self.dragon_killed));
+        data.insert("previously_killed".to_string(), Json::Bool(self.previously_killed));
+        Json::Object(data)
+    }
+
+    pub fn from_json(data: &Json) -> DragonFight {
+        let flag = |key: &str| data.get(key).and_then(Json::as_bool).unwrap_or(false);
+        DragonFight { dragon_killed: flag("dragon_killed"), previously_killed: flag("previously_killed"), ..DragonFight::new() }
+    }
+
+    fn enter(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.phase_ticks = 0;
+        self.phase_changed = true;
+    }
+
+    fn tick(&mut self, world: &mut World, players: &mut [Player], lang: &Translations, hits: &mut Vec<Target>) {
+        self.ticks += 1;
+        if self.dragon.is_none() && !self.dragon_killed && players.iter().any(in_arena) {
+            self.start(world, players);
+        }
+        let Some(id) = self.dragon else {
+            self.update_viewers(players, lang, None);
+            return;
+        };
+        let Some(index) = world.mobs.iter().position(|m| m.id == id) else {
+            // Von außen entfernt, etwa durch ein Plugin; beim nächsten Spieler im Ende beginnt der Kampf neu
+            self.dragon = None;
+            return;
+        };
+        let position = world.mobs[index].position;
+        let crystals: Vec<(Uuid, (f64, f64, f64))> = world.mobs.iter().filter(|m| m.mob_type == CRYSTAL).map(|m| (m.id, m.position)).collect();
+        self.healing_crystal = crystals
+            .iter()
+            .map(|(id, p)| (*id, distance(*p, position)))
+            .filter(|&(_, d)| d <= HEAL_RANGE && self.phase != Phase::Dying)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id);
+        self.phase_ticks += 1;
+        let next = self.steer(position, crystals.len(), players, hits);
+        let dragon = &mut world.mobs[index];
+        let yaw = if next == position { dragon.yaw } else { dragon_yaw(position, next) };
+        dragon.position = next;
+        dragon.yaw = yaw;
+        let max = entity::max_health(DRAGON);
+        let healed = self.healing_crystal.is_some() && self.ticks.is_multiple_of(HEAL_INTERVAL) && dragon.health > 0.0 && dragon.health < max;
+        if healed {
+            dragon.health = (dragon.health + 1.0).min(max);
+        }
+        let dragon = dragon.clone();
+        world.entities.update(id, next);
+        for player in players.iter().filter(|p| entity::in_view(p, Dimension::End, next)) {
+            if self.phase_changed {
+                let _ = entity::send_dragon_phase(&player.connection, dragon.entity_id, self.phase.id());
+            }
+            if healed {
+                let _ = entity::send_health(&player.connection, dragon.entity_id, dragon.health);
+            }
+        }
+        self.phase_changed = false;
+        self.update_viewers(players, lang, Some(dragon.health / max));
+        if self.phase == Phase::Dying && self.phase_ticks >= DEATH_TICKS {
+            self.finish(world, players, &dragon);
+        }
+    }
+
+    // Stellt die Kristalle wieder auf die Säulen und lässt den Drachen über dem Portal erscheinen
+    fn start(&mut self, world: &mut World, players: &[Player]) {
+        for spike in spikes(world.seed) {
+            let position = spike.crystal_position();
+            if world.mobs.iter().any(|m| m.mob_type == CRYSTAL && distance(m.position, position) < 1.0) {
+                continue;
+            }
+            spawn(world, players, Mob::new(CRYSTAL, position));
+        }
+        let dragon = Mob::new(DRAGON, DRAGON_SPAWN);
+        self.dragon = Some(dragon.id);
+        self.angle = 0.0;
+        self.target = None;
+        self.enter(Phase::HoldingPattern);
+        spawn(world, players, dragon);
+        info!("Der Enderdrache ist erschienen");
+    }
+
+    // Neue Position des Drachen für diesen Tick; wechselt dabei die Phase
+    fn steer(&mut self, position: (f64, f64, f64), crystals: usize, players: &[Player], hits: &mut Vec<Target>) -> (f64, f64, f64) {
+        match self.phase {
+            Phase::HoldingPattern => {
+                self.angle = (self.angle + CRUISE_SPEED / CIRCLE_RADIUS) % (2.0 * PI);
+                let goal = (CIRCLE_RADIUS * self.angle.cos(), CIRCLE_HEIGHT + 10.0 * (self.angle * 3.0).sin(), CIRCLE_RADIUS * self.angle.sin());
+                if self.phase_ticks >= DECISION_TICKS {
+                    self.decide(position, crystals, players);
+                }
+                fly(position, goal, CRUISE_SPEED * 1.5)
+            }
+            Phase::Charging => {
+                let Some(target) = players.iter().find(|p| Some(p.uuid) == self.target && is_prey(p)) else {
+                    self.enter(Phase::HoldingPattern);
+                    return position;
+                };
+                let goal = (target.position.0, target.position.1 + 1.0, target.position.2);
+                if distance(position, goal) <= HIT_RANGE {
+                    hits.push(Target::of_player(target));
+                    self.enter(Phase::HoldingPattern);
+                } else if self.phase_ticks >= CHARGE_TICKS {
+                    self.enter(Phase::HoldingPattern);
+                }
+                fly(position, goal, CHARGE_SPEED)
+            }
+            Phase::LandingApproach => {
+                let (x, y, z) = pillar_top();
+                let goal = (x as f64 + 0.5, y as f64, z as f64 + 0.5);
+                if distance(position, goal) < 1.0 {
+                    self.enter(Phase::Perching);
+                }
+                fly(position, goal, CRUISE_SPEED)
+            }
+            Phase::Perching => {
+                if self.phase_ticks >= PERCH_TICKS {
+                    self.enter(Phase::Takeoff);
+                }
+                position
+            }
+            Phase::Takeoff => {
+                let goal = (0.5, TAKEOFF_HEIGHT, 0.5);
+                if distance(position, goal) < 1.0 {
+                    self.angle = position.2.atan2(position.0);
+                    self.enter(Phase::HoldingPattern);
+                }
+                fly(position, goal, CRUISE_SPEED)
+            }
+            Phase::Dying => (position.0, position.1 + DEATH_RISE, position.2),
+        }
+    }
+
+    // Je weniger Kristalle noch stehen, desto öfter landet der Drache oder stürzt sich auf einen Spieler
+    fn decide(&mut self, position: (f64, f64, f64), crystals: usize, players: &[Player]) {
+        let roll = rand::thread_rng().gen_range(0..crystals + 3);
+        let prey = players.iter().filter(|p| is_prey(p)).min_by(|a, b| distance(a.position, position).total_cmp(&distance(b.position, position)));
+        match (roll, prey) {
+            (0, _) => self.enter(Phase::LandingApproach),
+            (1, Some(prey)) => {
+                self.target = Some(prey.uuid);
+                self.enter(Phase::Charging);
+            }
+            _ => self.phase_ticks = 0,
+        }
+    }
+
+    // Boss-Bar für alle Spieler in der Arena, solange der Drache lebt
+    fn update_viewers(&mut self, players: &[Player], lang: &Translations, health: Option<f32>) {
+        if let Some(health) = health.filter(|h| *h != self.sent_health) {
+            for player in players.iter().filter(|p| self.viewers.contains(&p.uuid)) {
+                let _ = send_bar_health(&player.connection, self.bar, health);
+            }
+            self.sent_health = health;
+        }
+        if health.is_some() && !self.ticks.is_multiple_of(VIEWER_UPDATE_TICKS) {
+            return;
+        }
+        let wanted: HashSet<Uuid> = players.iter().filter(|p| health.is_some() && in_arena(p)).map(|p| p.uuid).collect();
+        for player in players {
+            match (self.viewers.contains(&player.uuid), wanted.contains(&player.uuid)) {
+                (false, true) => {
+                    let title = Text::translate("entity.minecraft.ender_dragon").localize(lang, &player.settings.locale);
+                    let _ = send_bar_add(&player.connection, self.bar, &title, self.sent_health);
+                }
+                (true, false) => {
+                    let _ = send_bar_remove(&player.connection, self.bar);
+                }
+                _ => {}
+            }
+        }
+        self.viewers = wanted;
+    }
+
+    // Ende des Todeskampfs: Erfahrung für die Spieler in der Arena, Austrittsportal und beim ersten Sieg das Ei
+    fn finish(&mut self, world: &mut World, players: &mut [Player], dragon: &Mob) {
+        world.mobs.retain(|m| m.id != dragon.id);
+        world.entities.remove(dragon.id);
+        for player in players.iter().filter(|p| entity::in_view(p, Dimension::End, dragon.position)) {
+            let _ = entity::send_remove(&player.connection, &[dragon.entity_id]);
+        }
+        let xp = if self.previously_killed { KILL_XP } else { FIRST_KILL_XP };
+        let winners = players.iter().filter(|p| in_arena(p)).count().max(1) as i32;
+        for player in players.iter_mut().filter(|p| in_arena(p)) {
+            player.experience.add_points(xp / winners);
+            let _ = experience::send(&player.connection, &player.experience);
+        }
+        let mut blocks = exit_portal(true);
+        if !self.previously_killed {
+            blocks.push((pillar_top(), block::DRAGON_EGG));
+        }
+        for ((x, y, z), state) in blocks {
+            world.load_chunk_now(x >> 4, z >> 4);
+            world.set_block_and_notify((x, y, z), state);
+        }
+        self.dragon = None;
+        self.dragon_killed = true;
+        self.previously_killed = true;
+        info!("Der Enderdrache wurde besiegt");
+    }
+}
+
+fn in_arena(player: &Player) -> bool {
+    let (x, _, z) = player.position;
+    player.dimension == Dimension::End && player.health > 0.0 && (x * x + z * z).sqrt() <= ARENA_RADIUS
+}
+
+// Wie bei Vanilla greift der Drache niemanden im Kreativ- oder Zuschauermodus an
+fn is_prey(player: &Player) -> bool {
+    in_arena(player) && !player.game_mode.is_invulnerable()
+}
+
+fn distance(a: (f64, f64, f64), b: (f64, f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+// Höchstens speed Blöcke in Richtung goal
+fn fly(from: (f64, f64, f64), goal: (f64, f64, f64), speed: f64) -> (f64, f64, f64) {
+    let d = distance(from, goal);
+    if d <= speed {
+        return goal;
+    }
+    let t = speed / d;
+    (from.0 + (goal.0 - from.0) * t, from.1 + (goal.1 - from.1) * t, from.2 + (goal.2 - from.2) * t)
+}
+
+// Das Modell des Drachen blickt entgegen seiner Yaw-Richtung
+fn dragon_yaw(from: (f64, f64, f64), to: (f64, f64, f64)) -> f32 {
+    ((-(to.0 - from.0)).atan2(to.2 - from.2).to_degrees() + 180.0) as f32
+}
+
+fn spawn(world: &mut World, players: &[Player], mob: Mob) {
+    for player in players.iter().filter(|p| entity::in_view(p, Dimension::End, mob.position)) {
+        let _ = entity::send_mob(&player.connection, &mob);
+    }
+    world.spawn_mob(mob);
+}
+
+fn send_bar_add(connection: &Connection, bar: Uuid, title: &Text, health: f32) -> Result<(), String> {
+    let mut packet_data = write_uuid(&bar).to_vec();
+    packet_data.extend(write_varint_to_vec(BAR_ADD));
+    packet_data.extend(title.to_nbt());
+    packet_data.extend(health.to_be_bytes());
+    packet_data.extend(write_varint_to_vec(BAR_PINK));
+    packet_data.extend(write_varint_to_vec(BAR_NO_DIVISIONS));
+    packet_data.push(BAR_FLAGS);
+    connection.send(clientbound::BOSS_EVENT, &packet_data)
+}
+
+fn send_bar_health(connection: &Connection, bar: Uuid, health: f32) -> Result<(), String> {
+    let mut packet_data = write_uuid(&bar).to_vec();
+    packet_data.extend(write_varint_to_vec(BAR_UPDATE_HEALTH));
+    packet_data.extend(health.to_be_bytes());
+    connection.send(clientbound::BOSS_EVENT, &packet_data)
+}
+
+fn send_bar_remove(connection: &Connection, bar: Uuid) -> Result<(), String> {
+    let mut packet_data = write_uuid(&bar).to_vec();
+    packet_data.extend(write_varint_to_vec(BAR_REMOVE));
+    connection.send(clientbound::BOSS_EVENT, &packet_data)
+}
+
+// Ein Tick des Drachenkampfs; wer im Austrittsportal steht, kehrt zu seinem Spawnpunkt zurück.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut hits = Vec::new();
+    let mut leaving = Vec::new();
+    let dragon;
+    {
+        let mut players = server.players.lock().unwrap();
+        let mut world = server.end.lock().unwrap();
+        let Some(mut fight) = world.dragon_fight.take() else { return };
+        for player in players.iter().filter(|p| p.dimension == Dimension::End && p.health > 0.0) {
+            let (x, y, z) = player.position;
+            if world.get_block(x.floor() as i32, y.floor() as i32, z.floor() as i32) == block::END_PORTAL {
+                leaving.push(player.uuid);
+            }
+        }
+        fight.tick(&mut world, &mut players, &server.lang, &mut hits);
+        dragon = fight.dragon.and_then(|id| world.mobs.iter().find(|m| m.id == id)).map(|m| m.position);
+        world.dragon_fight = Some(fight);
+    }
+    for target in hits {
+        if damage::damage(server, &target, DamageSource::Dragon, HIT_DAMAGE) == Hit::Hurt {
+            if let Some(dragon) = dragon {
+                motion::push(server, &target, dragon, DRAGON_PUSH, DRAGON_LIFT);
+            }
+        }
+    }
+    for uuid in leaving {
+        if let Err(e) = leave(server, uuid) {
+            warn!("Konnte Spieler nicht aus dem Ende zurückbringen: {}", e);
+        }
+    }
+}
+
+// Anders als beim Respawn nach dem Tod behält der Spieler Inventar, Erfahrung und Effekte
+fn leave(server: &Server, uuid: Uuid) -> Result<(), String> {
+    let players = server.players.lock().unwrap();
+    let player = players.iter().find(|p| p.uuid == uuid).ok_or("Player is not online")?;
+    let (name, personal, pitch) = (player.username.clone(), player.spawn_point, player.rotation.1);
+    drop(players);
+    let (dimension, position, yaw) = damage::spawn_location(server, personal, Dimension::End);
+    info!("{} verlässt das Ende durch das Austrittsportal", name);
+    dimension::change_dimension(server, uuid, dimension, position, (yaw, pitch), dimension::KEEP_ALL)
+}
+
+// Aus damage_mob, wenn der Drache keine Lebenspunkte mehr hat: statt zu verschwinden steigt er im Todeskampf
+// auf. false, wenn die Welt keinen Drachenkampf hat, etwa für einen beschworenen Drachen in der Oberwelt
+pub fn dragon_died(world: &mut World, dragon: Uuid) -> bool {
+    let Some(fight) = world.dragon_fight.as_mut().filter(|f| f.dragon == Some(dragon)) else { return false };
+    if fight.phase != Phase::Dying {
+        fight.enter(Phase::Dying);
+    }
+    true
+}
+
+// Ein zerstörter Kristall explodiert wie bei Vanilla mit Stärke 6; heilte er gerade den Drachen, trifft es
+// auch diesen. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn crystal_destroyed(server: &Server, crystal: Uuid, position: (f64, f64, f64)) {
+    let world = server.end.lock().unwrap();
+    let dragon = world
+        .dragon_fight
+        .as_ref()
+        .filter(|f| f.healing_crystal == Some(crystal))
+        .and_then(|f| f.dragon)
+        .and_then(|id| world.mobs.iter().find(|m| m.id == id))
+        .map(|mob| Target::of_mob(mob, Dimension::End));
+    drop(world);
+    explosion::explode(server, Dimension::End, position, EXPLOSION_POWER, explosion::Kind::Block);
+    if let Some(dragon) = dragon {
+        damage::damage(server, &dragon, DamageSource::Explosion, CRYSTAL_DRAGON_DAMAGE);
+    }
+}
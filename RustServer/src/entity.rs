@@ -0,0 +1,559 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+use rand::Rng;
+use uuid::Uuid;
+use crate::item::ItemStack;
+use crate::json::Json;
+use crate::protocol::{assets, clientbound};
+use crate::protocol::version::ProtocolVersion;
+use crate::protocol::types::{angle_to_byte, encode_position, write_uuid, write_varint_to_vec};
+use crate::dimension::{self, Dimension};
+use crate::pose::PoseState;
+use crate::settings::{ClientSettings, MainHand};
+use crate::{entitytag, json, motion, nbt, potion, vehicle, Connection, Mob, Player, Server, World};
+
+// Entity-IDs für das Protokoll; OWN_PLAYER_ID ist die, die jeder Spieler im Join Game für sich selbst bekommt
+pub const OWN_PLAYER_ID: i32 = 1;
+static NEXT_ID: AtomicI32 = AtomicI32::new(OWN_PLAYER_ID + 1);
+
+// Entity-Typ-Register (1.21.1) in Register-Reihenfolge, der Index ist die Protokoll-ID
+const TYPES: &[&str] = &[
+    "allay", "area_effect_cloud", "armadillo", "armor_stand", "arrow", "axolotl", "bat", "bee", "blaze", "block_display",
+    "boat", "bogged", "breeze", "breeze_wind_charge", "camel", "cat", "cave_spider", "chest_boat", "chest_minecart", "chicken",
+    "cod", "command_block_minecart", "cow", "creeper", "dolphin", "donkey", "dragon_fireball", "drowned", "egg", "elder_guardian",
+    "end_crystal", "ender_dragon", "ender_pearl", "enderman", "endermite", "evoker", "evoker_fangs", "experience_bottle",
+    "experience_orb", "eye_of_ender", "falling_block", "firework_rocket", "fox", "frog", "furnace_minecart", "ghast", "giant",
+    "glow_item_frame", "glow_squid", "goat", "guardian", "hoglin", "hopper_minecart", "horse", "husk", "illusioner",
+    "interaction", "iron_golem", "item", "item_display", "item_frame", "ominous_item_spawner", "fireball", "leash_knot",
+    "lightning_bolt", "llama", "llama_spit", "magma_cube", "marker", "minecart", "mooshroom", "mule", "ocelot", "painting",
+    "panda", "parrot", "phantom", "pig", "piglin", "piglin_brute", "pillager", "polar_bear", "potion", "pufferfish", "rabbit",
+    "ravager", "salmon", "sheep", "shulker", "shulker_bullet", "silverfish", "skeleton", "skeleton_horse", "slime",
+    "small_fireball", "sniffer", "snow_golem", "snowball", "spawner_minecart", "spectral_arrow", "spider", "squid", "stray",
+    "strider", "tadpole", "text_display", "tnt", "tnt_minecart", "trader_llama", "trident", "tropical_fish", "turtle", "vex",
+    "villager", "vindicator", "wandering_trader", "warden", "wind_charge", "witch", "wither", "wither_skeleton", "wither_skull",
+    "wolf", "zoglin", "zombie", "zombie_horse", "zombie_villager", "zombified_piglin", "player", "fishing_bobber",
+];
+// Erst mit 1.20.5 hinzugekommen; in 1.20.4 verschieben sich alle folgenden IDs entsprechend
+const ADDED_IN_1_20_5: &[&str] = &["armadillo", "bogged", "breeze_wind_charge", "ominous_item_spawner"];
+// Typen ohne Lebenspunkte (keine LivingEntity-Metadaten)
+const NON_LIVING: &[&str] = &[
+    "area_effect_cloud", "arrow", "block_display", "boat", "breeze_wind_charge", "chest_boat", "chest_minecart",
+    "command_block_minecart", "dragon_fireball", "egg", "end_crystal", "ender_pearl", "evoker_fangs", "experience_bottle",
+    "experience_orb", "eye_of_ender", "falling_block", "firework_rocket", "furnace_minecart", "glow_item_frame",
+    "hopper_minecart", "interaction", "item", "item_display", "item_frame", "ominous_item_spawner", "fireball", "leash_knot",
+    "lightning_bolt", "llama_spit", "marker", "minecart", "painting", "potion", "shulker_bullet", "small_fireball", "snowball",
+    "spawner_minecart", "spectral_arrow", "text_display", "tnt", "tnt_minecart", "trident", "wind_charge", "wither_skull",
+    "fishing_bobber",
+];
+// Monster, die auf friedlich verschwinden und sich dort nicht beschwören lassen
+const HOSTILE: &[&str] = &[
+    "blaze", "bogged", "breeze", "cave_spider", "creeper", "drowned", "elder_guardian", "enderman", "endermite", "evoker",
+    "ghast", "giant", "guardian", "husk", "illusioner", "magma_cube", "phantom", "piglin", "piglin_brute", "pillager",
+    "ravager", "shulker", "silverfish", "skeleton", "slime", "spider", "stray", "vex", "vindicator", "warden", "witch",
+    "wither", "wither_skeleton", "zoglin", "zombie", "zombie_villager", "zombified_piglin",
+];
+// Breite und Höhe der Hitbox, wo sie deutlich von der eines Spielers abweicht
+const HITBOXES: &[(&str, f64, f64)] = &[
+    ("boat", 1.375, 0.5625), ("camel", 1.7, 2.375), ("cave_spider", 0.7, 0.5), ("chest_boat", 1.375, 0.5625),
+    ("chicken", 0.4, 0.7), ("cow", 0.9, 1.4), ("creeper", 0.6, 1.7), ("donkey", 1.4, 1.5), ("end_crystal", 2.0, 2.0),
+    ("ender_dragon", 16.0, 8.0), ("enderman", 0.6, 2.9),
+    ("ghast", 4.0, 4.0), ("giant", 3.6, 12.0), ("horse", 1.4, 1.6), ("iron_golem", 1.4, 2.7), ("minecart", 0.98, 0.7),
+    ("mule", 1.4, 1.6), ("pig", 0.9, 0.9), ("ravager", 1.95, 2.2), ("sheep", 0.9, 1.3), ("skeleton_horse", 1.4, 1.6),
+    ("slime", 2.04, 2.04), ("spider", 1.4, 0.9), ("tnt", 0.98, 0.98), ("warden", 0.9, 2.9), ("wither", 0.9, 3.5), ("wolf", 0.6, 0.85),
+    ("zombie_horse", 1.4, 1.6),
+];
+const PLAYER_HITBOX: (f64, f64) = (0.6, 1.8);
+// Beim Tod verstreute Items: bis zu 0.5 Blöcke pro Tick zur Seite und leicht nach oben, aus Brusthöhe
+const SCATTER_SPEED: f64 = 0.5;
+const SCATTER_LIFT: f64 = 0.2;
+const SCATTER_HEIGHT: f64 = 1.62 - 0.3;
+// Maximale Lebenspunkte, wo sie von DEFAULT_HEALTH abweichen
+const MAX_HEALTH: &[(&str, f32)] = &[("ender_dragon", 200.0)];
+// Fliegen und fallen deshalb nicht
+const NO_GRAVITY: &[&str] = &["allay", "area_effect_cloud", "bat", "bee", "blaze", "end_crystal", "ender_dragon", "ghast", "parrot", "phantom", "vex", "wither"];
+// Lassen sich nicht mit /summon erzeugen
+const NOT_SUMMONABLE: &[&str] = &["player", "fishing_bobber", "item"];
+
+// Metadaten-Indizes und -Typen (Entity bzw. LivingEntity), in allen unterstützten Versionen gleich
+const METADATA_FLAGS: u8 = 0;
+const METADATA_AIR: u8 = 1;
+const METADATA_CUSTOM_NAME: u8 = 2;
+const METADATA_CUSTOM_NAME_VISIBLE: u8 = 3;
+const METADATA_POSE: u8 = 6;
+const METADATA_ITEM: u8 = 8;
+const METADATA_HEALTH: u8 = 9;
+const METADATA_FIREWORK_SHOOTER: u8 = 9;
+// Pfeile: Bits für kritisch und durch Blöcke fliegend; lebende Entities: benutzte Hand
+const METADATA_ARROW_FLAGS: u8 = 8;
+const ARROW_CRITICAL: u8 = 0x01;
+const METADATA_HAND_STATES: u8 = 8;
+// Gezündetes TNT: Ticks bis zur Explosion, davon hängt das Blinken ab
+const METADATA_FUSE: u8 = 8;
+const HAND_ACTIVE: u8 = 0x01;
+const HAND_OFF_HAND: u8 = 0x02;
+const FLAG_ON_FIRE: u8 = 0x01;
+// Boot: Ruder links und rechts
+const METADATA_PADDLE_LEFT: u8 = 12;
+const METADATA_PADDLE_RIGHT: u8 = 13;
+// Spieler: zusätzliche Lebenspunkte durch Absorption
+const METADATA_ABSORPTION: u8 = 15;
+const METADATA_SKIN_PARTS: u8 = 17;
+const METADATA_MAIN_HAND: u8 = 18;
+const METADATA_DRAGON_PHASE: u8 = 16;
+// Lebende Entities: Bett, in dem sie liegen
+const METADATA_SLEEPING_POSITION: u8 = 14;
+// Trankwolken; bis 1.20.4 steht vor den übrigen Einträgen noch die Farbe
+const METADATA_CLOUD_RADIUS: u8 = 8;
+const METADATA_CLOUD_WAITING: u8 = 9;
+const METADATA_CLOUD_PARTICLE: u8 = 10;
+const METADATA_CLOUD_COLOR_1_20_4: u8 = 9;
+const PARTICLE_ENTITY_EFFECT: i32 = 20;
+const PARTICLE_ENTITY_EFFECT_1_20_4: i32 = 21;
+const TYPE_BYTE: i32 = 0;
+const TYPE_VARINT: i32 = 1;
+const TYPE_FLOAT: i32 = 3;
+const TYPE_OPTIONAL_TEXT: i32 = 6;
+const TYPE_SLOT: i32 = 7;
+const TYPE_BOOLEAN: i32 = 8;
+const TYPE_OPTIONAL_POSITION: i32 = 11;
+const TYPE_PARTICLE: i32 = 17;
+// Vor diesen kam mit 1.20.5 der Typ für Partikellisten hinzu
+const TYPE_OPTIONAL_VARINT: i32 = 20;
+const TYPE_OPTIONAL_VARINT_1_20_4: i32 = 19;
+const TYPE_POSE: i32 = 21;
+const TYPE_POSE_1_20_4: i32 = 20;
+const METADATA_END: u8 = 0xFF;
+
+pub const DEFAULT_HEALTH: f32 = 20.0;
+
+pub fn next_id() -> i32 {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn path(kind: &str) -> &str {
+    kind.strip_prefix("minecraft:").unwrap_or(kind)
+}
+
+pub fn is_known(kind: &str) -> bool {
+    TYPES.contains(&path(kind))
+}
+
+pub fn is_living(kind: &str) -> bool {
+    is_known(kind) && !NON_LIVING.contains(&path(kind))
+}
+
+pub fn is_hostile(kind: &str) -> bool {
+    HOSTILE.contains(&path(kind))
+}
+
+pub fn is_summonable(kind: &str) -> bool {
+    is_known(kind) && !NOT_SUMMONABLE.contains(&path(kind))
+}
+
+// (Breite, Höhe) in Blöcken; unbekannte Typen bekommen die Maße eines Spielers
+pub fn hitbox(kind: &str) -> (f64, f64) {
+    let kind = path(kind);
+    HITBOXES.iter().find(|(name, ..)| *name == kind).map_or(PLAYER_HITBOX, |&(_, width, height)| (width, height))
+}
+
+pub fn max_health(kind: &str) -> f32 {
+    let kind = path(kind);
+    MAX_HEALTH.iter().find(|(name, _)| *name == kind).map_or(DEFAULT_HEALTH, |&(_, health)| health)
+}
+
+pub fn has_gravity(kind: &str) -> bool {
+    !NO_GRAVITY.contains(&path(kind))
+}
+
+// Protokoll-ID für die Version des Clients; None, wenn es den Typ dort nicht gibt
+pub fn type_id(kind: &str, version: ProtocolVersion) -> Option<i32> {
+    if let Some(id) = assets::entity_id(version, &format!("minecraft:{}", path(kind))) {
+        return id;
+    }
+    let kind = path(kind);
+    let index = TYPES.iter().position(|t| *t == kind)?;
+    if version.has_1_20_5_login() {
+        return Some(index as i32);
+    }
+    if ADDED_IN_1_20_5.contains(&kind) {
+        return None;
+    }
+    Some((index - TYPES[..index].iter().filter(|t| ADDED_IN_1_20_5.contains(t)).count()) as i32)
+}
+
+// Anzeigename wie bei Vanilla, etwa "Zombified Piglin" für minecraft:zombified_piglin
+pub fn display_name(kind: &str) -> String {
+    path(kind)
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| first.to_ascii_uppercase().to_string() + chars.as_str())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Sichtweite der Clients in Chunks, wer weiter weg oder in einer anderen Dimension ist, bekommt die Entity nicht
+// zu sehen
+pub fn in_view(player: &Player, dimension: Dimension, position: (f64, f64, f64)) -> bool {
+    let chunk = |v: f64| (v.floor() as i32) >> 4;
+    let view_distance = player.settings.view_distance();
+    player.dimension == dimension
+        && (chunk(player.position.0) - chunk(position.0)).abs() <= view_distance && (chunk(player.position.2) - chunk(position.2)).abs() <= view_distance
+}
+
+// Alles zwischen zwei Bundle Delimitern wendet der Client im selben Frame an, so taucht keine halb eingerichtete
+// Entity auf. Der Client kennt nur auf und zu, Bündel dürfen also nicht verschachtelt werden
+pub fn bundle(connection: &Connection, send: impl FnOnce() -> Result<(), String>) -> Result<(), String> {
+    connection.send(clientbound::BUNDLE_DELIMITER, &[])?;
+    let result = send();
+    connection.send(clientbound::BUNDLE_DELIMITER, &[])?;
+    result
+}
+
+fn send_spawn(connection: &Connection, id: i32, uuid: Uuid, kind: &str, position: (f64, f64, f64), (yaw, pitch): (f32, f32)) -> Result<(), String> {
+    // Ältere Clients kennen manche Typen nicht, sie bekommen die Entity einfach nicht zu sehen
+    let Some(kind) = type_id(kind, connection.version) else { return Ok(()) };
+    let angle = angle_to_byte(yaw);
+    let mut packet_data = write_varint_to_vec(id);
+    packet_data.extend(write_uuid(&uuid));
+    packet_data.extend(write_varint_to_vec(kind));
+    packet_data.extend(position.0.to_be_bytes());
+    packet_data.extend(position.1.to_be_bytes());
+    packet_data.extend(position.2.to_be_bytes());
+    packet_data.extend([angle_to_byte(pitch), angle, angle]); // Pitch, Yaw, Kopf-Yaw
+    packet_data.extend(write_varint_to_vec(0)); // Objektdaten
+    packet_data.extend([0; 6]); // Geschwindigkeit
+    connection.send(clientbound::SPAWN_ENTITY, &packet_data)
+}
+
+fn send_metadata(connection: &Connection, id: i32, entries: &[(u8, i32, Vec<u8>)]) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut packet_data = write_varint_to_vec(id);
+    for (index, kind, value) in entries {
+        packet_data.push(*index);
+        packet_data.extend(write_varint_to_vec(*kind));
+        packet_data.extend(value);
+    }
+    packet_data.push(METADATA_END);
+    connection.send(clientbound::SET_ENTITY_METADATA, &packet_data)
+}
+
+// Lebenspunkte eines Mobs nach Schaden
+pub fn send_health(connection: &Connection, id: i32, health: f32) -> Result<(), String> {
+    send_metadata(connection, id, &[(METADATA_HEALTH, TYPE_FLOAT, health.to_be_bytes().to_vec())])
+}
+
+// Phase des Enderdrachen, der Client spielt danach Animationen wie den Todeskampf ab
+pub fn send_dragon_phase(connection: &Connection, id: i32, phase: i32) -> Result<(), String> {
+    send_metadata(connection, id, &[(METADATA_DRAGON_PHASE, TYPE_VARINT, write_varint_to_vec(phase))])
+}
+
+fn player_metadata(settings: &ClientSettings) -> Vec<(u8, i32, Vec<u8>)> {
+    let main_hand = match settings.main_hand {
+        MainHand::Left => 0,
+        MainHand::Right => 1,
+    };
+    vec![(METADATA_SKIN_PARTS, TYPE_BYTE, vec![settings.skin_parts]), (METADATA_MAIN_HAND, TYPE_BYTE, vec![main_hand])]
+}
+
+// Skin-Ebenen und Haupthand des eigenen Spielers
+pub fn send_player_settings(connection: &Connection, settings: &ClientSettings) -> Result<(), String> {
+    send_metadata(connection, OWN_PLAYER_ID, &player_metadata(settings))
+}
+
+fn pose_metadata(version: ProtocolVersion, pose: &PoseState) -> Vec<(u8, i32, Vec<u8>)> {
+    let kind = if version >= ProtocolVersion::V1_20_5 { TYPE_POSE } else { TYPE_POSE_1_20_4 };
+    let bed = match pose.bed {
+        Some((x, y, z)) => [vec![1], encode_position(x, y, z).to_be_bytes().to_vec()].concat(),
+        None => vec![0],
+    };
+    vec![(METADATA_FLAGS, TYPE_BYTE, vec![pose.flags()]), (METADATA_POSE, kind, write_varint_to_vec(pose.pose())), (METADATA_SLEEPING_POSITION, TYPE_OPTIONAL_POSITION, bed)]
+}
+
+// Goldene Herzen über den Lebenspunkten
+pub fn send_absorption(connection: &Connection, id: i32, amount: f32) -> Result<(), String> {
+    send_metadata(connection, id, &[(METADATA_ABSORPTION, TYPE_FLOAT, amount.to_be_bytes().to_vec())])
+}
+
+// Luftvorrat unter Wasser; der eigene Client zeigt danach die Blasen über der Hungerleiste
+pub fn send_air(connection: &Connection, id: i32, air: i32) -> Result<(), String> {
+    send_metadata(connection, id, &[(METADATA_AIR, TYPE_VARINT, write_varint_to_vec(air))])
+}
+
+// Ducken, Sprinten, Schwimmen und Gleiten; geduckt verbirgt der Client das Namensschild hinter Wänden
+pub fn send_pose(connection: &Connection, id: i32, pose: &PoseState) -> Result<(), String> {
+    send_metadata(connection, id, &pose_metadata(connection.version, pose))
+}
+
+// Ein anderer Spieler als Entity; seinen Eintrag in der Tab-Liste muss der Client schon haben
+pub fn send_player(connection: &Connection, player: &Player) -> Result<(), String> {
+    send_spawn(connection, player.entity_id, player.uuid, "player", player.position, (player.rotation.0, 0.0))?;
+    let mut entries = player_metadata(&player.settings);
+    if player.pose != PoseState::default() {
+        entries.extend(pose_metadata(connection.version, &player.pose));
+    }
+    send_metadata(connection, player.entity_id, &entries)
+}
+
+// Eine Feuerwerksrakete, die an shooter hängt und ihn beim Gleiten mit der Elytra antreibt; den Schub berechnet
+// jeder Client selbst
+pub fn send_firework(connection: &Connection, id: i32, uuid: Uuid, stack: &ItemStack, position: (f64, f64, f64), shooter: i32) -> Result<(), String> {
+    let kind = if connection.version >= ProtocolVersion::V1_20_5 { TYPE_OPTIONAL_VARINT } else { TYPE_OPTIONAL_VARINT_1_20_4 };
+    let entries = [(METADATA_ITEM, TYPE_SLOT, stack.encode(connection.version)), (METADATA_FIREWORK_SHOOTER, kind, write_varint_to_vec(shooter + 1))];
+    bundle(connection, || {
+        send_spawn(connection, id, uuid, "firework_rocket", position, (0.0, 0.0))?;
+        send_metadata(connection, id, &entries)
+    })
+}
+
+// Take Item Entity: der Client lässt das Item oder die Kugel zum Sammler fliegen und spielt den Ton
+pub fn send_take(connection: &Connection, collected: i32, collector: i32, count: u8) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(collected);
+    packet_data.extend(write_varint_to_vec(collector));
+    packet_data.extend(write_varint_to_vec(count as i32));
+    connection.send(clientbound::TAKE_ITEM_ENTITY, &packet_data)
+}
+
+pub fn send_remove(connection: &Connection, ids: &[i32]) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(ids.len() as i32);
+    for id in ids {
+        packet_data.extend(write_varint_to_vec(*id));
+    }
+    connection.send(clientbound::REMOVE_ENTITIES, &packet_data)
+}
+
+// Was /summon aus dem NBT-Argument übernimmt; alle anderen Tags werden wie bei Vanilla ignoriert
+#[derive(Debug, Clone, Default)]
+pub struct SummonData {
+    pub custom_name: Option<Json>,
+    pub custom_name_visible: bool,
+    pub health: Option<f32>,
+    pub tags: Vec<String>,
+    // Nur für TNT
+    pub fuse: Option<u32>,
+}
+
+impl SummonData {
+    pub fn parse(snbt: &str) -> Result<SummonData, String> {
+        let tag = nbt::parse_snbt(snbt)?;
+        if tag.as_object().is_none() {
+            return Err("Expected a compound tag".to_string());
+        }
+        let mut data = SummonData::default();
+        if let Some(name) = tag.get("CustomName") {
+            let name = name.as_str().ok_or("CustomName must be a JSON text component")?;
+            data.custom_name = Some(json::parse(name).map_err(|e| format!("Invalid CustomName: {}", e))?);
+        }
+        data.custom_name_visible = tag.get("CustomNameVisible").and_then(Json::as_f64).is_some_and(|v| v != 0.0);
+        if let Some(health) = tag.get("Health") {
+            match health.as_f64() {
+                Some(health) if health > 0.0 && health.is_finite() => data.health = Some(health as f32),
+                _ => return Err("Health must be a positive number".to_string()),
+            }
+        }
+        if let Some(tags) = tag.get("Tags") {
+            let tags = tags.as_array().ok_or("Tags must be a list of strings")?;
+            for name in tags {
+                let name = name.as_str().ok_or("Tags must be a list of strings")?;
+                if !data.tags.iter().any(|t| t == name) && data.tags.len() < entitytag::MAX_TAGS {
+                    data.tags.push(name.to_string());
+                }
+            }
+        }
+        if let Some(fuse) = tag.get("fuse") {
+            match fuse.as_f64() {
+                Some(fuse) if (0.0..=i16::MAX as f64).contains(&fuse) => data.fuse = Some(fuse as u32),
+                _ => return Err("fuse must be a number of ticks".to_string()),
+            }
+        }
+        Ok(data)
+    }
+}
+
+// Auf dem Boden liegender Stapel, etwa der Überschuss von /give
+#[derive(Debug, Clone)]
+pub struct ItemEntity {
+    pub id: i32,
+    pub uuid: Uuid,
+    pub stack: ItemStack,
+    pub position: (f64, f64, f64),
+    // Beim Tod verstreute Items fliegen, bis sie liegen bleiben
+    pub velocity: motion::Velocity,
+}
+
+impl ItemEntity {
+    pub fn new(stack: ItemStack, position: (f64, f64, f64)) -> ItemEntity {
+        ItemEntity { id: next_id(), uuid: Uuid::new_v4(), stack, position, velocity: (0.0, 0.0, 0.0) }
+    }
+
+    // Spawn Entity und anschließend die Metadaten mit dem Stapel; ein fliegendes Item bewegt der Client dann selbst
+    pub fn send_spawn(&self, connection: &Connection) -> Result<(), String> {
+        bundle(connection, || {
+            send_spawn(connection, self.id, self.uuid, "item", self.position, (0.0, 0.0))?;
+            self.send_stack(connection)?;
+            if self.velocity != (0.0, 0.0, 0.0) {
+                motion::send(connection, self.id, self.velocity)?;
+            }
+            Ok(())
+        })
+    }
+
+    // Nach einer Änderung des Stapels, etwa wenn ein Trichter einen Teil aufgenommen hat
+    pub fn send_stack(&self, connection: &Connection) -> Result<(), String> {
+        send_metadata(connection, self.id, &[(METADATA_ITEM, TYPE_SLOT, self.stack.encode(connection.version))])
+    }
+}
+
+// Legt einen Stapel in die Welt der Dimension und zeigt ihn den Spielern in der Nähe
+pub fn drop_item(server: &Server, dimension: Dimension, stack: ItemStack, position: (f64, f64, f64)) {
+    let players = server.players.lock().unwrap();
+    let mut world = dimension::world_of(server, dimension).lock().unwrap();
+    spawn_item(&players, &mut world, stack, position);
+}
+
+// Wie beim Tod eines Spielers bei Vanilla: die Stapel fliegen aus Brusthöhe in zufällige Richtungen auseinander
+pub fn scatter_items(server: &Server, dimension: Dimension, stacks: Vec<ItemStack>, (x, y, z): (f64, f64, f64)) {
+    let players = server.players.lock().unwrap();
+    let mut world = dimension::world_of(server, dimension).lock().unwrap();
+    let mut rng = rand::thread_rng();
+    for stack in stacks {
+        let (speed, angle) = (rng.gen::<f64>() * SCATTER_SPEED, rng.gen::<f64>() * std::f64::consts::TAU);
+        let mut item = ItemEntity::new(stack, (x, y + SCATTER_HEIGHT, z));
+        item.velocity = (-angle.sin() * speed, SCATTER_LIFT, angle.cos() * speed);
+        add_item(&players, &mut world, item);
+    }
+}
+
+// Wie drop_item, für Aufrufer, die Spieler und Welt schon gesperrt haben
+pub fn spawn_item(players: &[Player], world: &mut World, stack: ItemStack, position: (f64, f64, f64)) {
+    add_item(players, world, ItemEntity::new(stack, position));
+}
+
+fn add_item(players: &[Player], world: &mut World, item: ItemEntity) {
+    world.entities.update(item.uuid, item.position);
+    for player in players.iter().filter(|p| in_view(p, world.dimension, item.position)) {
+        if let Err(e) = item.send_spawn(&player.connection) {
+            warn!("Konnte Item-Entity nicht an {} senden: {}", player.username, e);
+        }
+    }
+    world.items.push(item);
+}
+
+// Metadaten einer Mob-Entity, soweit sie vom Standard abweichen
+fn mob_metadata(mob: &Mob, version: ProtocolVersion) -> Vec<(u8, i32, Vec<u8>)> {
+    let mut entries = Vec::new();
+    if let Some(name) = &mob.custom_name {
+        let mut value = vec![1];
+        value.extend(nbt::from_json(name));
+        entries.push((METADATA_CUSTOM_NAME, TYPE_OPTIONAL_TEXT, value));
+    }
+    if mob.custom_name_visible {
+        entries.push((METADATA_CUSTOM_NAME_VISIBLE, TYPE_BOOLEAN, vec![1]));
+    }
+    if mob.fire_ticks > 0 || mob.projectile.as_ref().is_some_and(|arrow| arrow.burning) {
+        entries.push((METADATA_FLAGS, TYPE_BYTE, vec![FLAG_ON_FIRE]));
+    }
+    if let Some(arrow) = &mob.projectile {
+        if arrow.critical {
+            entries.push((METADATA_ARROW_FLAGS, TYPE_BYTE, vec![ARROW_CRITICAL]));
+        }
+    }
+    if let Some(thrown) = &mob.potion {
+        entries.push((METADATA_ITEM, TYPE_SLOT, thrown.stack.encode(version)));
+    }
+    if let Some(cloud) = &mob.cloud {
+        entries.extend(cloud_metadata(version, cloud));
+    }
+    if let Some(fuse) = mob.fuse {
+        entries.push((METADATA_FUSE, TYPE_VARINT, write_varint_to_vec(fuse as i32)));
+    }
+    if is_living(&mob.mob_type) && mob.health != max_health(&mob.mob_type) {
+        entries.push((METADATA_HEALTH, TYPE_FLOAT, mob.health.to_be_bytes().to_vec()));
+    }
+    entries
+}
+
+// Mitfahrer kennt der Client erst, wenn der Tracker sie ihm zeigt; der schickt die Mitfahrer dann noch einmal.
+// Fliegende Pfeile und gestoßene Mobs bekommen ihre Geschwindigkeit mit, damit der Client sie weiterbewegt
+pub fn send_mob(connection: &Connection, mob: &Mob) -> Result<(), String> {
+    bundle(connection, || {
+        send_spawn(connection, mob.entity_id, mob.id, &mob.mob_type, mob.position, (mob.yaw, mob.pitch))?;
+        send_metadata(connection, mob.entity_id, &mob_metadata(mob, connection.version))?;
+        if mob.velocity != (0.0, 0.0, 0.0) {
+            motion::send(connection, mob.entity_id, mob.velocity)?;
+        }
+        if mob.passengers.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<i32> = mob.passengers.iter().map(|&(_, id)| id).collect();
+        vehicle::send_passengers(connection, mob.entity_id, &ids)
+    })
+}
+
+// Radius, ob die Wolke noch wartet, und ihre Partikel; ab 1.20.5 tragen die Partikel selbst die Farbe
+fn cloud_metadata(version: ProtocolVersion, cloud: &potion::Cloud) -> Vec<(u8, i32, Vec<u8>)> {
+    let radius = (METADATA_CLOUD_RADIUS, TYPE_FLOAT, cloud.radius.to_be_bytes().to_vec());
+    let waiting = vec![cloud.is_waiting() as u8];
+    if version >= ProtocolVersion::V1_20_5 {
+        let mut particle = write_varint_to_vec(PARTICLE_ENTITY_EFFECT);
+        particle.extend((cloud.color as u32 | 0xFF00_0000).to_be_bytes());
+        return vec![radius, (METADATA_CLOUD_WAITING, TYPE_BOOLEAN, waiting), (METADATA_CLOUD_PARTICLE, TYPE_PARTICLE, particle)];
+    }
+    vec![
+        radius,
+        (METADATA_CLOUD_COLOR_1_20_4, TYPE_VARINT, write_varint_to_vec(cloud.color)),
+        (METADATA_CLOUD_WAITING + 1, TYPE_BOOLEAN, waiting),
+        (METADATA_CLOUD_PARTICLE + 1, TYPE_PARTICLE, write_varint_to_vec(PARTICLE_ENTITY_EFFECT_1_20_4)),
+    ]
+}
+
+// Schrumpfende oder nicht mehr wartende Trankwolke
+pub fn send_cloud(connection: &Connection, id: i32, cloud: &potion::Cloud) -> Result<(), String> {
+    send_metadata(connection, id, &cloud_metadata(connection.version, cloud))
+}
+
+// Ein gespannter Bogen oder eine Armbrust beim Laden; None, wenn der Spieler nichts mehr benutzt
+pub fn send_hand_state(connection: &Connection, id: i32, off_hand: Option<bool>) -> Result<(), String> {
+    let state = match off_hand {
+        Some(true) => HAND_ACTIVE | HAND_OFF_HAND,
+        Some(false) => HAND_ACTIVE,
+        None => 0,
+    };
+    send_metadata(connection, id, &[(METADATA_HAND_STATES, TYPE_BYTE, vec![state])])
+}
+
+// Entity Event, etwa ein abgewehrter Treffer; die ID ist hier ein Int statt eines VarInt
+pub fn send_event(connection: &Connection, id: i32, event: u8) -> Result<(), String> {
+    let mut packet_data = id.to_be_bytes().to_vec();
+    packet_data.push(event);
+    connection.send(clientbound::ENTITY_EVENT, &packet_data)
+}
+
+// Set Equipment mit (Slot, Item); ein gesetztes oberstes Bit im Slot kündigt einen weiteren Eintrag an
+pub fn send_equipment(connection: &Connection, id: i32, equipment: &[(u8, &ItemStack)]) -> Result<(), String> {
+    if equipment.is_empty() {
+        return Ok(());
+    }
+    let mut packet_data = write_varint_to_vec(id);
+    for (i, (slot, stack)) in equipment.iter().enumerate() {
+        packet_data.push(if i + 1 < equipment.len() { slot | 0x80 } else { *slot });
+        packet_data.extend(stack.encode(connection.version));
+    }
+    connection.send(clientbound::SET_EQUIPMENT, &packet_data)
+}
+
+// Ein Mob fängt Feuer oder ist gelöscht; bei Spielern gehört das Bit zu den Flags der Pose
+pub fn send_on_fire(connection: &Connection, id: i32, on_fire: bool) -> Result<(), String> {
+    send_metadata(connection, id, &[(METADATA_FLAGS, TYPE_BYTE, vec![if on_fire { FLAG_ON_FIRE } else { 0 }])])
+}
+
+// Der Pfeil steckt und ist nicht mehr kritisch
+pub fn send_arrow_landed(connection: &Connection, id: i32) -> Result<(), String> {
+    send_metadata(connection, id, &[(METADATA_ARROW_FLAGS, TYPE_BYTE, vec![0])])
+}
+
+pub fn send_paddles(connection: &Connection, id: i32, left: bool, right: bool) -> Result<(), String> {
+    send_metadata(connection, id, &[(METADATA_PADDLE_LEFT, TYPE_BOOLEAN, vec![left as u8]), (METADATA_PADDLE_RIGHT, TYPE_BOOLEAN, vec![right as u8])])
+}
@@ -0,0 +1,41 @@
+use crate::selector::Target;
+use crate::{dimension, Server};
+
+// Mehr Tags nimmt ein Entity wie bei Vanilla nicht an
+pub const MAX_TAGS: usize = 1024;
+
+// Wie ein SNBT-Wort ohne Anführungszeichen: Buchstaben, Ziffern und _ - . +
+pub fn is_valid(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'))
+}
+
+// Ändert die Tags eines Spielers oder Mobs; None, wenn das Ziel nicht mehr da ist
+fn modify<R>(server: &Server, target: &Target, change: impl FnOnce(&mut Vec<String>) -> R) -> Option<R> {
+    if target.is_player() {
+        let mut players = server.players.lock().unwrap();
+        return players.iter_mut().find(|p| p.uuid == target.uuid).map(|p| change(&mut p.tags));
+    }
+    let mut world = dimension::world_with_mob(server, target.uuid)?.lock().unwrap();
+    world.mobs.iter_mut().find(|m| m.id == target.uuid).map(|m| change(&mut m.tags))
+}
+
+// false, wenn das Ziel den Tag schon hat oder keinen mehr tragen kann
+pub fn add(server: &Server, target: &Target, tag: &str) -> bool {
+    modify(server, target, |tags| {
+        if tags.len() >= MAX_TAGS || tags.iter().any(|t| t == tag) {
+            return false;
+        }
+        tags.push(tag.to_string());
+        true
+    })
+    .unwrap_or(false)
+}
+
+pub fn remove(server: &Server, target: &Target, tag: &str) -> bool {
+    modify(server, target, |tags| {
+        let before = tags.len();
+        tags.retain(|t| t != tag);
+        tags.len() != before
+    })
+    .unwrap_or(false)
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+use std::io;
+use crate::protocol::ProtocolError;
+
+// Was beim Behandeln eines Pakets im Spiel schiefgehen kann; was daraus für die Verbindung folgt, legt policy fest
+#[derive(Debug)]
+pub enum ServerError {
+    Protocol(ProtocolError),
+    // Lesbar, aber unzulässig, etwa Koordinaten außerhalb der Welt; der Text geht als Begründung an den Spieler
+    Kick(String),
+    // Auf Seiten des Servers, wofür der Spieler nichts kann
+    Internal(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Policy {
+    // Die Verbindung ist schon weg
+    Close,
+    Disconnect(String),
+    // Nur protokollieren, der Spieler bleibt verbunden
+    Log,
+}
+
+impl ServerError {
+    // Wie bei Vanilla trennt alles, was der Client verschuldet. Fehler des Servers kosten den Spieler die Verbindung nicht
+    pub fn policy(&self) -> Policy {
+        match self {
+            ServerError::Protocol(ProtocolError::Io(_)) => Policy::Close,
+            ServerError::Protocol(_) => Policy::Disconnect("Invalid packet".to_string()),
+            ServerError::Kick(reason) => Policy::Disconnect(reason.clone()),
+            ServerError::Internal(_) => Policy::Log,
+        }
+    }
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::Protocol(e) => e.fmt(f),
+            ServerError::Kick(reason) => write!(f, "Unzulässiges Paket: {}", reason),
+            ServerError::Internal(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ServerError::Protocol(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProtocolError> for ServerError {
+    fn from(e: ProtocolError) -> ServerError {
+        ServerError::Protocol(e)
+    }
+}
+
+// Gelesen wird aus dem schon empfangenen Paket, siehe ProtocolError
+impl From<io::Error> for ServerError {
+    fn from(e: io::Error) -> ServerError {
+        ServerError::Protocol(e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_client_faults_disconnect() {
+        let short = io::Error::from(io::ErrorKind::UnexpectedEof);
+        assert_eq!(ServerError::from(short).policy(), Policy::Disconnect("Invalid packet".to_string()));
+        let gone = ProtocolError::from_stream(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert_eq!(ServerError::from(gone).policy(), Policy::Close);
+        assert_eq!(ServerError::Kick("Invalid move".to_string()).policy(), Policy::Disconnect("Invalid move".to_string()));
+        assert_eq!(ServerError::Internal("Speichern fehlgeschlagen".to_string()).policy(), Policy::Log);
+    }
+}
@@ -0,0 +1,337 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use crate::Server;
+
+// Die numerischen IDs sind Teil der Plugin-Schnittstellen (C-ABI und WASM) und dürfen sich nicht ändern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    PlayerJoin = 1,
+    PlayerQuit = 2,
+    Chat = 3,
+    BlockBreak = 4,
+    ServerListPing = 5,
+}
+
+const EVENT_KINDS: [EventKind; 5] = [EventKind::PlayerJoin, EventKind::PlayerQuit, EventKind::Chat, EventKind::BlockBreak, EventKind::ServerListPing];
+
+impl EventKind {
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_id(id: u32) -> Option<EventKind> {
+        EVENT_KINDS.into_iter().find(|k| k.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::PlayerJoin => "player_join",
+            EventKind::PlayerQuit => "player_quit",
+            EventKind::Chat => "chat",
+            EventKind::BlockBreak => "block_break",
+            EventKind::ServerListPing => "server_list_ping",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<EventKind> {
+        EVENT_KINDS.into_iter().find(|k| k.name() == name)
+    }
+}
+
+// Listener laufen von Lowest nach Monitor; Monitor-Listener sollen nur beobachten, nicht verändern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum EventPriority {
+    Lowest,
+    Low,
+    #[default]
+    Normal,
+    High,
+    Highest,
+    Monitor,
+}
+
+const PRIORITIES: [EventPriority; 6] = [
+    EventPriority::Lowest,
+    EventPriority::Low,
+    EventPriority::Normal,
+    EventPriority::High,
+    EventPriority::Highest,
+    EventPriority::Monitor,
+];
+
+impl EventPriority {
+    pub fn from_index(index: i32) -> Option<EventPriority> {
+        usize::try_from(index).ok().and_then(|i| PRIORITIES.get(i).copied())
+    }
+
+    pub fn from_name(name: &str) -> Option<EventPriority> {
+        let index = ["lowest", "low", "normal", "high", "highest", "monitor"].iter().position(|n| n.eq_ignore_ascii_case(name))?;
+        Some(PRIORITIES[index])
+    }
+}
+
+// Gemeinsame Sicht auf alle Events, damit Plugins und Skripte sie ohne Kenntnis des Typs bedienen können
+pub trait Event: std::any::Any {
+    fn kind(&self) -> EventKind;
+    fn player(&self) -> &str;
+
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+
+    // Nicht abbrechbare Events ignorieren den Aufruf
+    fn set_cancelled(&mut self, _cancelled: bool) {}
+
+    fn message(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_message(&mut self, _message: String) {}
+
+    fn format(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_format(&mut self, _format: String) {}
+
+    // (x, y, z, Blockzustand)
+    fn block(&self) -> Option<(i32, i32, i32, u16)> {
+        None
+    }
+}
+
+pub trait TypedEvent: Event + Sized {
+    const KIND: EventKind;
+}
+
+// Die Nachricht stammt aus join-message mit eingesetztem Namen; leer setzen unterdrückt sie
+pub struct PlayerJoinEvent {
+    pub player: String,
+    pub message: String,
+}
+
+// Wie PlayerJoinEvent, mit quit-message
+pub struct PlayerQuitEvent {
+    pub player: String,
+    pub message: String,
+}
+
+// Abbrechen unterdrückt die Nachricht; Listener dürfen sie auch umschreiben
+pub struct ChatEvent {
+    pub player: String,
+    pub message: String,
+    // Aus chat-format; die Platzhalter werden erst nach allen Listenern ersetzt
+    pub format: String,
+    pub cancelled: bool,
+}
+
+// Abbrechen lässt den Block stehen und setzt ihn beim Client zurück
+pub struct BlockBreakEvent {
+    pub player: String,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub state: u16,
+    pub cancelled: bool,
+}
+
+// Statusabfrage aus der Serverliste. Listener können die MOTD pro Abfrage ersetzen (&-Codes oder JSON)
+pub struct ServerListPingEvent {
+    pub address: String,
+    pub motd: String,
+}
+
+impl Event for PlayerJoinEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PlayerJoin
+    }
+
+    fn player(&self) -> &str {
+        &self.player
+    }
+
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+}
+
+impl TypedEvent for PlayerJoinEvent {
+    const KIND: EventKind = EventKind::PlayerJoin;
+}
+
+impl Event for PlayerQuitEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PlayerQuit
+    }
+
+    fn player(&self) -> &str {
+        &self.player
+    }
+
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+}
+
+impl TypedEvent for PlayerQuitEvent {
+    const KIND: EventKind = EventKind::PlayerQuit;
+}
+
+impl Event for ChatEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Chat
+    }
+
+    fn player(&self) -> &str {
+        &self.player
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn set_cancelled(&mut self, cancelled: bool) {
+        self.cancelled = cancelled;
+    }
+
+    fn message(&self) -> Option<&str> {
+        Some(&self.message)
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    fn format(&self) -> Option<&str> {
+        Some(&self.format)
+    }
+
+    fn set_format(&mut self, format: String) {
+        self.format = format;
+    }
+}
+
+impl TypedEvent for ChatEvent {
+    const KIND: EventKind = EventKind::Chat;
+}
+
+impl Event for BlockBreakEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::BlockBreak
+    }
+
+    fn player(&self) -> &str {
+        &self.player
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn set_cancelled(&mut self, cancelled: bool) {
+        self.cancelled = cancelled;
+    }
+
+    fn block(&self) -> Option<(i32, i32, i32, u16)> {
+        Some((self.x, self.y, self.z, self.state))
+    }
+}
+
+impl TypedEvent for BlockBreakEvent {
+    const KIND: EventKind = EventKind::BlockBreak;
+}
+
+impl Event for ServerListPingEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::ServerListPing
+    }
+
+    // Es gibt noch keinen Spieler; Plugins sehen stattdessen die anfragende Adresse
+    fn player(&self) -> &str {
+        &self.address
+    }
+
+    fn message(&self) -> Option<&str> {
+        Some(&self.motd)
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.motd = message;
+    }
+}
+
+impl TypedEvent for ServerListPingEvent {
+    const KIND: EventKind = EventKind::ServerListPing;
+}
+
+pub type ListenerId = u64;
+type Handler = Arc<dyn Fn(&Server, &mut dyn Event) + Send + Sync>;
+
+struct Listener {
+    id: ListenerId,
+    kind: EventKind,
+    priority: EventPriority,
+    handler: Handler,
+}
+
+#[derive(Default)]
+pub struct EventBus {
+    // Nach Priorität sortiert, bei gleicher Priorität in Registrierungsreihenfolge
+    listeners: Mutex<Vec<Listener>>,
+    next_id: AtomicU64,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus::default()
+    }
+
+    pub fn listen<E, F>(&self, priority: EventPriority, handler: F) -> ListenerId
+    where
+        E: TypedEvent,
+        F: Fn(&Server, &mut E) + Send + Sync + 'static,
+    {
+        self.listen_kind(E::KIND, priority, move |server, event| {
+            if let Some(event) = (event as &mut dyn std::any::Any).downcast_mut::<E>() {
+                handler(server, event);
+            }
+        })
+    }
+
+    // Für Plugins und Skripte, die Events nur über EventKind kennen
+    pub fn listen_kind<F>(&self, kind: EventKind, priority: EventPriority, handler: F) -> ListenerId
+    where
+        F: Fn(&Server, &mut dyn Event) + Send + Sync + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut listeners = self.listeners.lock().unwrap();
+        let index = listeners.partition_point(|l| l.priority <= priority);
+        listeners.insert(index, Listener { id, kind, priority, handler: Arc::new(handler) });
+        id
+    }
+
+    pub fn unlisten(&self, id: ListenerId) -> bool {
+        let mut listeners = self.listeners.lock().unwrap();
+        let before = listeners.len();
+        listeners.retain(|l| l.id != id);
+        listeners.len() != before
+    }
+
+    // Ruft alle Listener ohne gehaltene Sperre auf, damit sie sich selbst (ab)melden können.
+    // Abgebrochene Events erreichen weiterhin alle Listener, die den Abbruch auch zurücknehmen können
+    pub fn fire(&self, server: &Server, event: &mut dyn Event) {
+        let kind = event.kind();
+        let handlers: Vec<Handler> = self.listeners.lock().unwrap().iter().filter(|l| l.kind == kind).map(|l| Arc::clone(&l.handler)).collect();
+        for handler in handlers {
+            handler(server, event);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use rand::Rng;
+use uuid::Uuid;
+use crate::dimension::{self, Dimension};
+use crate::json::Json;
+use crate::motion::Velocity;
+use crate::protocol::clientbound;
+use crate::protocol::types::write_varint_to_vec;
+use crate::{entity, Connection, Server};
+
+// Wie Vanilla verteilt sich Erfahrung auf Kugeln dieser Werte, die größten zuerst
+const ORB_VALUES: &[i32] = &[2477, 1237, 617, 307, 149, 73, 37, 17, 7, 3, 1];
+// Beim Tod fallen 7 Punkte je Stufe als Kugeln, höchstens 100; der Rest ist verloren
+const DEATH_DROP_PER_LEVEL: i32 = 7;
+const MAX_DEATH_DROP: i32 = 100;
+// Kugeln fliegen auf Spieler bis acht Blöcke weit zu und verschwinden nach fünf Minuten
+const FOLLOW_RANGE: f64 = 8.0;
+const FOLLOW_ACCELERATION: f64 = 0.1;
+const DESPAWN_TICKS: u32 = 6000;
+// Eingesammelt wird, sobald die Kugel die um einen Block vergrößerte Hitbox des Spielers berührt
+const PICKUP_REACH: f64 = 0.3 + 1.0 + 0.25;
+const PICKUP_HEIGHT: f64 = 1.8 + 0.5;
+
+// Stufe und Punkte innerhalb der aktuellen Stufe; die Gesamtzahl ergibt sich daraus
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Experience {
+    pub level: i32,
+    pub points: i32,
+}
+
+// Punkte bis zur nächsten Stufe, wie bei Vanilla
+pub fn points_for_level(level: i32) -> i32 {
+    match level {
+        30.. => 112 + (level - 30) * 9,
+        15.. => 37 + (level - 15) * 5,
+        _ => 7 + level * 2,
+    }
+}
+
+impl Experience {
+    pub fn total(&self) -> i32 {
+        (0..self.level).fold(self.points, |total, level| total.saturating_add(points_for_level(level)))
+    }
+
+    // Negative Werte ziehen Punkte ab und können Stufen kosten
+    pub fn add_points(&mut self, amount: i32) {
+        self.points = self.points.saturating_add(amount);
+        while self.points < 0 && self.level > 0 {
+            self.level -= 1;
+            self.points += points_for_level(self.level);
+        }
+        self.points = self.points.max(0);
+        while self.points >= points_for_level(self.level) {
+            self.points -= points_for_level(self.level);
+            self.level += 1;
+        }
+    }
+
+    // Der Fortschritt in der Stufe bleibt anteilig erhalten
+    pub fn set_level(&mut self, level: i32) {
+        let progress = self.progress();
+        self.level = level.max(0);
+        self.points = ((progress * points_for_level(self.level) as f32) as i32).min(points_for_level(self.level) - 1);
+    }
+
+    // Anteil des Balkens zwischen 0 und 1
+    pub fn progress(&self) -> f32 {
+        self.points as f32 / points_for_level(self.level) as f32
+    }
+
+    pub fn to_json(self) -> Json {
+        let mut data = BTreeMap::new();
+        data.insert("level".to_string(), Json::Number(self.level as f64));
+        data.insert("points".to_string(), Json::Number(self.points as f64));
+        Json::Object(data)
+    }
+}
+
+// Was ein Spieler beim Tod als Kugeln fallen lässt
+pub fn death_drop(experience: &Experience) -> i32 {
+    experience.level.saturating_mul(DEATH_DROP_PER_LEVEL).clamp(0, MAX_DEATH_DROP)
+}
+
+// Erfahrungskugel; fällt wie ein Item und fliegt auf den nächsten Spieler in der Nähe zu
+#[derive(Debug, Clone)]
+pub struct Orb {
+    pub id: i32,
+    pub uuid: Uuid,
+    pub value: i32,
+    pub position: (f64, f64, f64),
+    pub velocity: Velocity,
+    age: u32,
+}
+
+impl Orb {
+    // Mit zufälligem Schwung wie bei Vanilla
+    fn new(value: i32, position: (f64, f64, f64)) -> Orb {
+        let mut rng = rand::thread_rng();
+        let velocity = ((rng.gen::<f64>() * 0.2 - 0.1) * 2.0, rng.gen::<f64>() * 0.2 * 2.0, (rng.gen::<f64>() * 0.2 - 0.1) * 2.0);
+        Orb { id: entity::next_id(), uuid: Uuid::new_v4(), value, position, velocity, age: 0 }
+    }
+
+    // Spawn Experience Orb; die Bewegung rechnet der Client ab da selbst
+    pub fn send_spawn(&self, connection: &Connection) -> Result<(), String> {
+        let mut packet_data = write_varint_to_vec(self.id);
+        for v in [self.position.0, self.position.1, self.position.2] {
+            packet_data.extend(v.to_be_bytes());
+        }
+        packet_data.extend((self.value.min(i16::MAX as i32) as i16).to_be_bytes());
+        entity::bundle(connection, || {
+            connection.send(clientbound::SPAWN_EXPERIENCE_ORB, &packet_data)?;
+            crate::motion::send(connection, self.id, self.velocity)
+        })
+    }
+}
+
+// Teilt die Punkte auf Kugeln auf und zeigt sie den Spielern in der Nähe
+pub fn drop_orbs(server: &Server, dimension: Dimension, mut amount: i32, position: (f64, f64, f64)) {
+    let players = server.players.lock().unwrap();
+    let mut world = dimension::world_of(server, dimension).lock().unwrap();
+    while amount > 0 {
+        let value = ORB_VALUES.iter().copied().find(|&value| value <= amount).unwrap_or(1);
+        amount -= value;
+        let orb = Orb::new(value, position);
+        for player in players.iter().filter(|p| entity::in_view(p, dimension, position)) {
+            let _ = orb.send_spawn(&player.connection);
+        }
+        world.entities.update(orb.uuid, position);
+        world.orbs.push(orb);
+    }
+}
+
+// Ein Tick der Kugeln: sie fliegen auf den nächsten lebenden Spieler in Reichweite zu, der sie bei Berührung
+// einsammelt; alte verschwinden. Bewegt werden sie in motion::tick. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let dimension = world.dimension;
+        let _profile = server.profiler.world("entities", dimension);
+        let mut removed = Vec::new();
+        for (index, orb) in world.orbs.iter_mut().enumerate() {
+            orb.age += 1;
+            if orb.age >= DESPAWN_TICKS {
+                removed.push((index, None));
+                continue;
+            }
+            let (x, y, z) = orb.position;
+            let nearest = players
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.dimension == dimension && p.health > 0.0 && p.game_mode.is_tracked())
+                .map(|(i, p)| (i, (p.position.0 - x, p.position.1 + 0.9 - y, p.position.2 - z)))
+                .map(|(i, d)| (i, d, (d.0 * d.0 + d.1 * d.1 + d.2 * d.2).sqrt()))
+                .filter(|&(_, _, distance)| distance < FOLLOW_RANGE)
+                .min_by(|a, b| a.2.total_cmp(&b.2));
+            let Some((player, (dx, dy, dz), distance)) = nearest else { continue };
+            let feet = players[player].position.1;
+            if dx.abs() <= PICKUP_REACH && dz.abs() <= PICKUP_REACH && y + 0.5 >= feet - 0.5 && y <= feet + PICKUP_HEIGHT {
+                removed.push((index, Some(player)));
+                continue;
+            }
+            let pull = (1.0 - distance / FOLLOW_RANGE).powi(2) * FOLLOW_ACCELERATION / distance.max(1.0e-4);
+            orb.velocity = (orb.velocity.0 + dx * pull, orb.velocity.1 + dy * pull, orb.velocity.2 + dz * pull);
+        }
+        for &(index, collector) in removed.iter().rev() {
+            let orb = world.orbs.remove(index);
+            world.entities.remove(orb.uuid);
+            let taken_by = collector.map(|i| (players[i].uuid, players[i].entity_id));
+            for viewer in players.iter().filter(|p| entity::in_view(p, dimension, orb.position)) {
+                if let Some((uuid, entity_id)) = taken_by {
+                    let id = if viewer.uuid == uuid { entity::OWN_PLAYER_ID } else { entity_id };
+                    let _ = entity::send_take(&viewer.connection, orb.id, id, 1);
+                }
+                let _ = entity::send_remove(&viewer.connection, &[orb.id]);
+            }
+            if let Some(index) = collector {
+                let player = &mut players[index];
+                player.experience.add_points(orb.value);
+                let _ = send(&player.connection, &player.experience);
+            }
+        }
+    }
+}
+
+// Set Experience: Balken, Stufe und Gesamtpunkte
+pub fn send(connection: &Connection, experience: &Experience) -> Result<(), String> {
+    let mut packet_data = experience.progress().to_be_bytes().to_vec();
+    packet_data.extend(write_varint_to_vec(experience.level));
+    packet_data.extend(write_varint_to_vec(experience.total()));
+    connection.send(clientbound::SET_EXPERIENCE, &packet_data)
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use rand::Rng;
+use crate::blockentity::BlockEntity;
+use crate::damage::{self, DamageSource, Hit};
+use crate::dimension::{self, Dimension};
+use crate::gamerule::{self, BoolRule};
+use crate::protocol::clientbound;
+use crate::protocol::types::{write_string_to_vec, write_varint_to_vec};
+use crate::protocol::version::ProtocolVersion;
+use crate::selector::Target;
+use crate::{block, chunk, entity, loot, motion, tnt, Connection, Server, World};
+
+type Vec3 = (f64, f64, f64);
+
+// Strahlen von jedem Punkt auf der Oberfläche eines 16er-Würfels, wie bei Vanilla
+const RAY_GRID: i32 = 16;
+const RAY_STEP: f64 = 0.3;
+// Mit jedem Schritt verliert ein Strahl so viel Kraft, zusätzlich zum Widerstand des Blocks
+const STEP_DECAY: f64 = 0.225;
+// Explosion: die Blöcke setzt der Client selbst schon auf Luft, mit Zerfall fallen nicht alle als Item heraus
+const INTERACTION_DESTROY: i32 = 1;
+const INTERACTION_DESTROY_WITH_DECAY: i32 = 2;
+// Partikel-Register (1.21.1); 1.20.5 hat davor ambient_entity_effect entfernt
+const PARTICLE_EXPLOSION_EMITTER: i32 = 21;
+const PARTICLE_EXPLOSION: i32 = 22;
+const PARTICLE_EXPLOSION_EMITTER_1_20_4: i32 = 22;
+const PARTICLE_EXPLOSION_1_20_4: i32 = 23;
+const SOUND: &str = "minecraft:entity.generic.explode";
+const ITEM_HITBOX: (f64, f64) = (0.25, 0.25);
+
+// Auslöser einer Explosion; davon hängen Spielregeln und Schaden an Blöcken ab
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Tnt,
+    // Creeper und andere Mobs zerstören Blöcke nur mit mobGriefing
+    Mob,
+    // Endkristalle und Betten außerhalb der Oberwelt
+    Block,
+}
+
+impl Kind {
+    fn decay_rule(self) -> BoolRule {
+        match self {
+            Kind::Tnt => gamerule::TNT_EXPLOSION_DROP_DECAY,
+            Kind::Mob => gamerule::MOB_EXPLOSION_DROP_DECAY,
+            Kind::Block => gamerule::BLOCK_EXPLOSION_DROP_DECAY,
+        }
+    }
+}
+
+fn distance(a: Vec3, b: Vec3) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+fn block_at(p: Vec3) -> (i32, i32, i32) {
+    (p.0.floor() as i32, p.1.floor() as i32, p.2.floor() as i32)
+}
+
+// Blöcke, die die Explosion zerstört: jeder Strahl verliert mit jedem Block Kraft nach dessen Widerstand
+fn affected_blocks(world: &World, center: Vec3, power: f64) -> Vec<(i32, i32, i32)> {
+    let mut rng = rand::thread_rng();
+    let mut blocks = HashSet::new();
+    let edge = RAY_GRID - 1;
+    for i in 0..RAY_GRID {
+        for j in 0..RAY_GRID {
+            for k in 0..RAY_GRID {
+                if ![i, j, k].iter().any(|&v| v == 0 || v == edge) {
+                    continue;
+                }
+                let direction = [i, j, k].map(|v| v as f64 / edge as f64 * 2.0 - 1.0);
+                let length = (direction[0].powi(2) + direction[1].powi(2) + direction[2].powi(2)).sqrt();
+                let step = direction.map(|v| v / length * RAY_STEP);
+                let mut intensity = power * (0.7 + rng.gen::<f64>() * 0.6);
+                let mut position = center;
+                while intensity > 0.0 {
+                    let (x, y, z) = block_at(position);
+                    if !(chunk::MIN_Y..chunk::MIN_Y + chunk::HEIGHT).contains(&y) || !world.chunks.contains_key(&(x >> 4, z >> 4)) {
+                        break;
+                    }
+                    let state = world.get_block(x, y, z);
+                    if state != block::AIR {
+                        intensity -= (block::blast_resistance(state) as f64 + RAY_STEP) * RAY_STEP;
+                        if intensity > 0.0 && !block::is_fluid(state) {
+                            blocks.insert((x, y, z));
+                        }
+                    }
+                    position = (position.0 + step[0], position.1 + step[1], position.2 + step[2]);
+                    intensity -= STEP_DECAY;
+                }
+            }
+        }
+    }
+    blocks.into_iter().collect()
+}
+
+// Anteil der Punkte in der Hitbox, die freie Sicht auf das Zentrum haben
+fn exposure(world: &World, center: Vec3, position: Vec3, (width, height): (f64, f64)) -> f64 {
+    let steps = |size: f64| ((size * 2.0 + 1.0).floor() as i32).max(1);
+    let (sx, sy) = (steps(width), steps(height));
+    let (mut seen, mut total) = (0, 0);
+    for i in 0..=sx {
+        for j in 0..=sy {
+            for k in 0..=sx {
+                let point = (
+                    position.0 - width / 2.0 + width * i as f64 / sx as f64,
+                    position.1 + height * j as f64 / sy as f64,
+                    position.2 - width / 2.0 + width * k as f64 / sx as f64,
+                );
+                total += 1;
+                if clear(world, point, center) {
+                    seen += 1;
+                }
+            }
+        }
+    }
+    seen as f64 / total as f64
+}
+
+fn clear(world: &World, from: Vec3, to: Vec3) -> bool {
+    let steps = (distance(from, to) / RAY_STEP).ceil() as u32;
+    (0..steps).all(|i| {
+        let t = i as f64 / steps as f64;
+        let (x, y, z) = block_at((from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t, from.2 + (to.2 - from.2) * t));
+        !block::is_solid(world.get_block(x, y, z))
+    })
+}
+
+// Vanillas Schaden: 1 im Zentrum bei voller Sicht, 0 am Rand der Reichweite von zweimal der Stärke
+fn explosion_damage(impact: f64, reach: f64) -> f32 {
+    ((impact * impact + impact) / 2.0 * 7.0 * reach + 1.0) as f32
+}
+
+// Explosion mit Partikeln und Klang; die zerstörten Blöcke bekommt der Client als Abstand zum Zentrum, den Stoß
+// schickt motion gesondert
+fn send(connection: &Connection, center: Vec3, power: f64, blocks: &[(i32, i32, i32)], interaction: i32) -> Result<(), String> {
+    let origin = block_at(center);
+    let offsets: Vec<[i8; 3]> = blocks
+        .iter()
+        .filter_map(|&(x, y, z)| Some([i8::try_from(x - origin.0).ok()?, i8::try_from(y - origin.1).ok()?, i8::try_from(z - origin.2).ok()?]))
+        .collect();
+    let mut packet_data = vec![];
+    for v in [center.0, center.1, center.2] {
+        packet_data.extend(v.to_be_bytes());
+    }
+    packet_data.extend((power as f32).to_be_bytes());
+    packet_data.extend(write_varint_to_vec(offsets.len() as i32));
+    for offset in offsets {
+        packet_data.extend(offset.map(|v| v as u8));
+    }
+    for _ in 0..3 {
+        packet_data.extend(0f32.to_be_bytes());
+    }
+    packet_data.extend(write_varint_to_vec(interaction));
+    let modern = connection.version >= ProtocolVersion::V1_20_5;
+    let (small, large) = if modern { (PARTICLE_EXPLOSION, PARTICLE_EXPLOSION_EMITTER) } else { (PARTICLE_EXPLOSION_1_20_4, PARTICLE_EXPLOSION_EMITTER_1_20_4) };
+    packet_data.extend(write_varint_to_vec(small));
+    packet_data.extend(write_varint_to_vec(large));
+    // Seit 1.20.5 darf statt des Namens eine Registry-ID stehen, 0 heißt: Name folgt
+    if modern {
+        packet_data.extend(write_varint_to_vec(0));
+    }
+    packet_data.extend(write_string_to_vec(SOUND));
+    packet_data.push(0);
+    connection.send(clientbound::EXPLOSION, &packet_data)
+}
+
+// Lässt an center eine Explosion der Stärke power los: sie zerstört Blöcke nach ihrem Widerstand, zündet TNT in
+// Reichweite, vernichtet Items und trifft Spieler und Mobs nach Abstand und Deckung. Darf nur ohne gehaltene
+// Sperren aufgerufen werden
+pub fn explode(server: &Server, dimension: Dimension, center: Vec3, power: f64, kind: Kind) {
+    let players = server.players.lock().unwrap();
+    let mut world = dimension::world_of(server, dimension).lock().unwrap();
+    let griefing = kind != Kind::Mob || world.rules.get_bool(gamerule::MOB_GRIEFING);
+    let blocks = if griefing { affected_blocks(&world, center, power) } else { Vec::new() };
+    let decay = world.rules.get_bool(kind.decay_rule());
+    let tile_drops = world.rules.get_bool(gamerule::DO_TILE_DROPS);
+    let reach = power * 2.0;
+    let mut victims: Vec<(Target, f64)> = Vec::new();
+    let mut hit = |target: Target, world: &World| {
+        let scaled = distance(target.position, center) / reach;
+        if scaled > 1.0 {
+            return;
+        }
+        let impact = (1.0 - scaled) * exposure(world, center, target.position, entity::hitbox(&target.kind));
+        if impact > 0.0 {
+            victims.push((target, impact));
+        }
+    };
+    for p in players.iter().filter(|p| p.dimension == dimension && p.health > 0.0 && p.game_mode.is_tracked()) {
+        hit(Target::of_player(p), &world);
+    }
+    for m in &world.mobs {
+        hit(Target::of_mob(m, dimension), &world);
+    }
+    let (destroyed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut world.items).into_iter().partition(|item| {
+        distance(item.position, center) <= reach && exposure(&world, center, item.position, ITEM_HITBOX) > 0.0
+    });
+    world.items = kept;
+    for item in &destroyed {
+        world.entities.remove(item.uuid);
+    }
+    let destroyed: Vec<i32> = destroyed.iter().map(|item| item.id).collect();
+    let (mut contents, mut broken) = (Vec::new(), Vec::new());
+    for &(x, y, z) in &blocks {
+        let state = world.get_block(x, y, z);
+        let items = world.block_entity_mut((x, y, z)).map(BlockEntity::take_items).unwrap_or_default();
+        if !world.set_block_and_notify((x, y, z), block::AIR) {
+            continue;
+        }
+        let position = (x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5);
+        contents.extend(items.into_iter().map(|stack| (stack, position)));
+        if block::name(state) == tnt::BLOCK {
+            tnt::prime(&players, &mut world, (x, y, z), tnt::chain_fuse());
+        } else if tile_drops && (!decay || rand::thread_rng().gen::<f64>() < 1.0 / power) {
+            broken.push((state, position));
+        }
+    }
+    let interaction = if decay { INTERACTION_DESTROY_WITH_DECAY } else { INTERACTION_DESTROY };
+    for viewer in players.iter().filter(|p| entity::in_view(p, dimension, center)) {
+        let _ = send(&viewer.connection, center, power, &blocks, interaction);
+        if !destroyed.is_empty() {
+            let _ = entity::send_remove(&viewer.connection, &destroyed);
+        }
+    }
+    drop(world);
+    drop(players);
+    debug!("Explosion der Stärke {} bei {:.1} {:.1} {:.1}: {} Blöcke, {} Ziele", power, center.0, center.1, center.2, blocks.len(), victims.len());
+    for (stack, position) in contents {
+        entity::drop_item(server, dimension, stack, position);
+    }
+    for (state, position) in broken {
+        for stack in loot::block_drops(server, state) {
+            entity::drop_item(server, dimension, stack, position);
+        }
+    }
+    for (target, impact) in victims {
+        // Gezündetes TNT nimmt keinen Schaden, es wird nur weggestoßen
+        if target.kind == tnt::PRIMED || damage::damage_from(server, &target, DamageSource::Explosion, explosion_damage(impact, reach), center) == Hit::Hurt {
+            motion::explosion(server, &target, center, impact);
+        }
+    }
+}
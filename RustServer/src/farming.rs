@@ -0,0 +1,216 @@
+use rand::Rng;
+use crate::gamemode::GameMode;
+use crate::worldevent::{self, WorldEvent};
+use crate::{block, entity, face_offset, item, loot, Player, Server, World};
+
+type BlockPos = (i32, i32, i32);
+
+pub const FARMLAND: &str = "minecraft:farmland";
+// Saatgut und die Feldfrucht, die daraus wächst
+const CROPS: &[(&str, &str)] = &[("minecraft:wheat_seeds", "minecraft:wheat"), ("minecraft:carrot", "minecraft:carrots"), ("minecraft:potato", "minecraft:potatoes")];
+pub const RANDOM_TICKING: &[&str] = &[FARMLAND, "minecraft:wheat", "minecraft:carrots", "minecraft:potatoes"];
+const TILLABLE: &[&str] = &["minecraft:dirt", "minecraft:grass_block"];
+pub const BONE_MEAL: &str = "minecraft:bone_meal";
+const MAX_AGE: u32 = 7;
+const MAX_MOISTURE: u32 = 7;
+// Wasser hält den Ackerboden in vier Blöcken Umkreis feucht, auf gleicher Höhe oder einen Block darüber
+const WATER_REACH: i32 = 4;
+// Wer mehr als einen halben Block tief auf Ackerboden fällt, tritt ihn vielleicht zu Erde
+const TRAMPLE_HEIGHT: f64 = 0.5;
+// Eine Wachstumsstufe kommt mit der Chance 1 zu 25 / Tempo + 1
+const GROWTH_CHANCE: f32 = 25.0;
+// Darunter wachsen Feldfrüchte nicht, wie bei Vanilla
+const MIN_LIGHT: u8 = 9;
+const TILL_SOUND: &str = "minecraft:item.hoe.till";
+const PLANT_SOUND: &str = "minecraft:item.crop.plant";
+
+fn is_crop(state: u16) -> bool {
+    CROPS.iter().any(|&(_, crop)| block::name(state) == crop)
+}
+
+fn number(state: u16, key: &str) -> u32 {
+    block::property(state, key).and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+fn with_number(state: u16, key: &str, value: u32) -> u16 {
+    block::with_property(state, key, &value.to_string()).unwrap_or(state)
+}
+
+pub fn play_sound(players: &[Player], world: &World, sound: &str, (x, y, z): BlockPos) {
+    let center = (x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5);
+    for viewer in players.iter().filter(|p| entity::in_view(p, world.dimension, center)) {
+        let _ = worldevent::send_sound(&viewer.connection, sound, center, 1.0);
+    }
+}
+
+// Verbraucht ein Item außer im Kreativmodus; Hacken nutzen sich stattdessen ab
+pub fn spend(player: &mut Player, slot: usize, wears: bool) {
+    if player.game_mode == GameMode::Creative {
+        return;
+    }
+    if wears {
+        let mut stack = player.inventory.slot(slot).clone();
+        item::wear(&mut stack);
+        player.inventory.set(slot, stack);
+    } else {
+        player.inventory.consume_one(slot);
+    }
+    let _ = player.inventory.send_slot(&player.connection, slot);
+}
+
+// Use Item On mit Hacke, Saatgut oder Knochenmehl: Erde und Gras werden zu Ackerboden, auf Ackerboden kommt die
+// Feldfrucht, Knochenmehl lässt sie zwei bis fünf Stufen wachsen. false, wenn nichts davon passt
+pub fn use_on(players: &mut [Player], world: &mut World, index: usize, slot: usize, (x, y, z): BlockPos, face: i32) -> bool {
+    let held = players[index].inventory.slot(slot).item.clone();
+    let clicked = world.get_block(x, y, z);
+    if held.ends_with("_hoe") {
+        // Nicht von unten und nur mit Luft darüber
+        if face == 0 || !TILLABLE.contains(&block::name(clicked)) || world.get_block(x, y + 1, z) != block::AIR {
+            return false;
+        }
+        if !world.set_block_and_notify((x, y, z), block::FARMLAND) {
+            return false;
+        }
+        debug!("{} pflügt bei {:?}", players[index].username, (x, y, z));
+        spend(&mut players[index], slot, true);
+        play_sound(players, world, TILL_SOUND, (x, y, z));
+        return true;
+    }
+    if let Some(&(_, crop)) = CROPS.iter().find(|&&(seed, _)| seed == held) {
+        let (dx, dy, dz) = face_offset(face);
+        let position = (x + dx, y + dy, z + dz);
+        if world.get_block(position.0, position.1, position.2) != block::AIR || block::name(world.get_block(position.0, position.1 - 1, position.2)) != FARMLAND {
+            return false;
+        }
+        let Some(state) = block::state_id(crop) else { return false };
+        if !world.set_block_and_notify(position, state) {
+            return false;
+        }
+        spend(&mut players[index], slot, false);
+        play_sound(players, world, PLANT_SOUND, position);
+        return true;
+    }
+    if held == BONE_MEAL && is_crop(clicked) {
+        let age = number(clicked, "age");
+        if age >= MAX_AGE {
+            return false;
+        }
+        let grown = (age + rand::thread_rng().gen_range(2..=5)).min(MAX_AGE);
+        if !world.set_block_and_notify((x, y, z), with_number(clicked, "age", grown)) {
+            return false;
+        }
+        spend(&mut players[index], slot, false);
+        world.play_event(WorldEvent::BoneMeal, (x, y, z), None);
+        return true;
+    }
+    false
+}
+
+// Zerstört den Block wie beim Abbauen mit Partikeln und der Beute aus seiner Loot-Tabelle
+fn break_block(server: &Server, players: &[Player], world: &mut World, (x, y, z): BlockPos) {
+    let state = world.get_block(x, y, z);
+    if !world.set_block_and_notify((x, y, z), block::AIR) {
+        return;
+    }
+    world.play_event(WorldEvent::DestroyBlock(state), (x, y, z), None);
+    for stack in loot::block_drops(server, state) {
+        entity::spawn_item(players, world, stack, (x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5));
+    }
+}
+
+// Ackerboden wird zu Erde; eine Feldfrucht darauf bricht ab
+fn turn_to_dirt(server: &Server, players: &[Player], world: &mut World, (x, y, z): BlockPos) {
+    if !world.set_block_and_notify((x, y, z), block::DIRT) {
+        return;
+    }
+    if is_crop(world.get_block(x, y + 1, z)) {
+        break_block(server, players, world, (x, y + 1, z));
+    }
+}
+
+fn near_water(world: &World, (x, y, z): BlockPos) -> bool {
+    (-WATER_REACH..=WATER_REACH).any(|dx| {
+        (0..=1).any(|dy| {
+            (-WATER_REACH..=WATER_REACH).any(|dz| {
+                let state = world.get_block(x + dx, y + dy, z + dz);
+                block::is_water(state) || block::property(state, "waterlogged") == Some("true")
+            })
+        })
+    })
+}
+
+// Vanillas Wachstumstempo: feuchter Ackerboden darunter und ringsum beschleunigt, gleiche Feldfrüchte diagonal oder
+// in beiden Richtungen daneben bremsen
+fn growth_speed(world: &World, (x, y, z): BlockPos, crop: &str) -> f32 {
+    let mut speed = 1.0;
+    for dx in -1..=1 {
+        for dz in -1..=1 {
+            let below = world.get_block(x + dx, y - 1, z + dz);
+            let mut bonus = match block::name(below) {
+                FARMLAND if number(below, "moisture") > 0 => 3.0,
+                FARMLAND => 1.0,
+                _ => 0.0,
+            };
+            if dx != 0 || dz != 0 {
+                bonus /= 4.0;
+            }
+            speed += bonus;
+        }
+    }
+    let same = |dx: i32, dz: i32| block::name(world.get_block(x + dx, y, z + dz)) == crop;
+    let row_x = same(-1, 0) || same(1, 0);
+    let row_z = same(0, -1) || same(0, 1);
+    if row_x && row_z || same(-1, -1) || same(1, -1) || same(1, 1) || same(-1, 1) {
+        speed /= 2.0;
+    }
+    speed
+}
+
+// Zufallstick: Ackerboden wird nahe Wasser oder im Regen feucht und trocknet sonst aus, bis er ohne Feldfrucht
+// wieder zu Erde wird. Feldfrüchte wachsen unter freiem Himmel und brechen ohne Ackerboden ab
+pub fn random_tick(server: &Server, players: &[Player], world: &mut World, (x, y, z): BlockPos) {
+    let state = world.get_block(x, y, z);
+    if block::name(state) == FARMLAND {
+        if block::is_solid(world.get_block(x, y + 1, z)) {
+            turn_to_dirt(server, players, world, (x, y, z));
+            return;
+        }
+        let moisture = number(state, "moisture");
+        if near_water(world, (x, y, z)) || world.rains_at((x, y + 1, z)) {
+            if moisture < MAX_MOISTURE {
+                world.set_block_and_notify((x, y, z), with_number(state, "moisture", MAX_MOISTURE));
+            }
+        } else if moisture > 0 {
+            world.set_block_and_notify((x, y, z), with_number(state, "moisture", moisture - 1));
+        } else if !is_crop(world.get_block(x, y + 1, z)) {
+            turn_to_dirt(server, players, world, (x, y, z));
+        }
+        return;
+    }
+    if !is_crop(state) {
+        return;
+    }
+    if block::name(world.get_block(x, y - 1, z)) != FARMLAND {
+        break_block(server, players, world, (x, y, z));
+        return;
+    }
+    let age = number(state, "age");
+    if age >= MAX_AGE || world.light_level((x, y, z)) < MIN_LIGHT {
+        return;
+    }
+    let speed = growth_speed(world, (x, y, z), block::name(state));
+    if rand::thread_rng().gen_range(0..=(GROWTH_CHANCE / speed) as u32) == 0 {
+        world.set_block_and_notify((x, y, z), with_number(state, "age", age + 1));
+    }
+}
+
+// Landet ein Spieler nach distance Blöcken Fall auf Ackerboden, tritt er ihn mit steigender Fallhöhe eher zu Erde
+pub fn trample(server: &Server, players: &[Player], world: &mut World, position: (f64, f64, f64), distance: f64) {
+    // Der Block unter den Füßen, wie Vanillas getOnPosLegacy
+    let (x, y, z) = (position.0.floor() as i32, (position.1 - 0.2).floor() as i32, position.2.floor() as i32);
+    if block::name(world.get_block(x, y, z)) != FARMLAND || rand::thread_rng().gen::<f64>() >= distance - TRAMPLE_HEIGHT {
+        return;
+    }
+    debug!("Ackerboden bei {:?} zertreten", (x, y, z));
+    turn_to_dirt(server, players, world, (x, y, z));
+}
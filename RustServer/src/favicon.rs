@@ -0,0 +1,47 @@
+use std::fs;
+use std::io::ErrorKind;
+use crate::hash;
+
+pub const ICON_FILE: &str = "server-icon.png";
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const SIZE: u32 = 64;
+
+// Größe aus dem IHDR-Chunk, der bei PNG immer direkt auf die Signatur folgt
+fn png_size(data: &[u8]) -> Result<(u32, u32), String> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return Err("keine PNG-Datei".to_string());
+    }
+    let header = data.get(8..24).ok_or("PNG-Datei ist abgeschnitten")?;
+    if &header[4..8] != b"IHDR" {
+        return Err("PNG-Datei ohne IHDR-Chunk".to_string());
+    }
+    let width = u32::from_be_bytes(header[8..12].try_into().unwrap());
+    let height = u32::from_be_bytes(header[12..16].try_into().unwrap());
+    Ok((width, height))
+}
+
+// Data-URI für das favicon-Feld der Statusantwort; None, wenn keine (gültige) Datei vorhanden ist
+pub fn load(path: &str) -> Option<String> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!("Konnte {} nicht lesen: {}", path, e);
+            return None;
+        }
+    };
+    match png_size(&data) {
+        Ok((SIZE, SIZE)) => {
+            info!("Servericon {} geladen", path);
+            Some(format!("data:image/png;base64,{}", hash::base64_encode(&data)))
+        }
+        Ok((width, height)) => {
+            warn!("{} muss {}x{} Pixel groß sein, ist aber {}x{}", path, SIZE, SIZE, width, height);
+            None
+        }
+        Err(e) => {
+            warn!("{} ist ungültig: {}", path, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,438 @@
+use rand::Rng;
+use crate::damage::{self, DamageSource};
+use crate::dimension::{self, Dimension};
+use crate::gamemode::GameMode;
+use crate::selector::Target;
+use crate::{block, effect, entity, face_offset, gamerule, item, pose, tnt, worldevent, Player, Server, World};
+
+type BlockPos = (i32, i32, i32);
+
+pub const FIRE: &str = "minecraft:fire";
+const SOUL_FIRE: &str = "minecraft:soul_fire";
+pub const FLINT_AND_STEEL: &str = "minecraft:flint_and_steel";
+pub const FIRE_CHARGE: &str = "minecraft:fire_charge";
+// Feuer tickt alle 30 bis 39 Ticks und altert dabei bis 15
+const TICK_DELAY: i64 = 30;
+const TICK_SPREAD: i64 = 10;
+const MAX_AGE: u32 = 15;
+// Chancen, dass ein Nachbar verbrennt: seitlich ist sie kleiner als oben und unten, in feuchten Biomen größer
+const BURN_OUT_SIDE: u32 = 300;
+const BURN_OUT_VERTICAL: u32 = 250;
+const HUMID_BURN_OUT: u32 = 50;
+// Biome aus #increased_fire_burnout
+const HUMID_BIOMES: &[&str] = &["bamboo_jungle", "mushroom_fields", "mangrove_swamp", "snowy_slopes", "frozen_peaks", "jagged_peaks", "swamp", "jungle"];
+// Brenndauer in Ticks: Feuer acht, Lava fünfzehn, Flame-Pfeile fünf Sekunden
+const FIRE_TICKS: i32 = 160;
+const LAVA_TICKS: i32 = 300;
+pub const ARROW_TICKS: i32 = 100;
+// Brennende nehmen jede Sekunde einen Punkt Schaden; Feuer- und Lavablöcke treffen ohne Unverwundbarkeitsticks wie
+// bei Vanilla effektiv nur alle zehn Ticks
+const BURN_INTERVAL: i32 = 20;
+const BURN_DAMAGE: f32 = 1.0;
+const CONTACT_INTERVAL: i64 = 10;
+const FIRE_DAMAGE: f32 = 1.0;
+const SOUL_FIRE_DAMAGE: f32 = 2.0;
+const LAVA_DAMAGE: f32 = 4.0;
+const PLAYER_HEIGHT: f64 = 1.8;
+// Fangen kein Feuer
+const FIRE_IMMUNE: &[&str] = &[
+    "minecraft:blaze", "minecraft:ender_dragon", "minecraft:ghast", "minecraft:magma_cube", "minecraft:strider", "minecraft:warden",
+    "minecraft:wither", "minecraft:wither_skeleton", "minecraft:zoglin", "minecraft:zombified_piglin",
+];
+const IGNITE_SOUND: &str = "minecraft:item.flintandsteel.use";
+const FIRE_CHARGE_SOUND: &str = "minecraft:item.firecharge.use";
+const EXTINGUISH_SOUND: &str = "minecraft:entity.generic.extinguish_fire";
+
+fn neighbours((x, y, z): BlockPos) -> [BlockPos; 6] {
+    [(x, y - 1, z), (x, y + 1, z), (x, y, z - 1), (x, y, z + 1), (x - 1, y, z), (x + 1, y, z)]
+}
+
+fn can_burn(state: u16) -> bool {
+    block::flammability(state).is_some()
+}
+
+fn flammable_neighbour(world: &World, position: BlockPos) -> bool {
+    neighbours(position).iter().any(|&(x, y, z)| can_burn(world.get_block(x, y, z)))
+}
+
+// Feuer hält auf festem Grund oder an etwas Brennbarem
+fn can_survive(world: &World, (x, y, z): BlockPos) -> bool {
+    block::is_solid(world.get_block(x, y - 1, z)) || flammable_neighbour(world, (x, y, z))
+}
+
+// Auf festem oder brennbarem Grund liegt das Feuer flach, sonst hängt es an den brennbaren Seiten
+fn state_for(world: &World, (x, y, z): BlockPos, age: u32) -> u16 {
+    let below = world.get_block(x, y - 1, z);
+    let mut state = block::FIRE;
+    if !block::is_solid(below) && !can_burn(below) {
+        for (side, (dx, dy, dz)) in [("up", (0, 1, 0)), ("north", (0, 0, -1)), ("south", (0, 0, 1)), ("west", (-1, 0, 0)), ("east", (1, 0, 0))] {
+            if can_burn(world.get_block(x + dx, y + dy, z + dz)) {
+                state = block::with_property(state, side, "true").unwrap_or(state);
+            }
+        }
+    }
+    block::with_property(state, "age", &age.to_string()).unwrap_or(state)
+}
+
+fn age(state: u16) -> u32 {
+    block::property(state, "age").and_then(|age| age.parse().ok()).unwrap_or(0)
+}
+
+// Plant den nächsten Tick eines Feuers
+pub fn schedule(world: &mut World, position: BlockPos) {
+    let delay = TICK_DELAY + rand::thread_rng().gen_range(0..TICK_SPREAD);
+    world.schedule_block_tick(position, delay);
+}
+
+// Setzt Feuer in einen Luftblock, sofern es dort halten kann; false, wenn nicht
+pub fn place(world: &mut World, position: BlockPos, age: u32) -> bool {
+    if world.get_block(position.0, position.1, position.2) != block::AIR || !can_survive(world, position) {
+        return false;
+    }
+    let state = state_for(world, position, age);
+    if !world.set_block_and_notify(position, state) {
+        return false;
+    }
+    schedule(world, position);
+    true
+}
+
+fn rain_near(world: &World, (x, y, z): BlockPos) -> bool {
+    [(x, y, z), (x - 1, y, z), (x + 1, y, z), (x, y, z - 1), (x, y, z + 1)].into_iter().any(|position| world.rains_at(position))
+}
+
+// Ein Luftblock fängt so leicht Feuer wie sein brennbarster Nachbar
+fn ignite_odds(world: &World, (x, y, z): BlockPos) -> u32 {
+    if world.get_block(x, y, z) != block::AIR {
+        return 0;
+    }
+    neighbours((x, y, z)).iter().filter_map(|&(x, y, z)| block::flammability(world.get_block(x, y, z))).map(|(ignite, _)| ignite).max().unwrap_or(0)
+}
+
+// Ein brennbarer Nachbar verbrennt vielleicht und fängt dabei manchmal selbst Feuer; TNT wird gezündet
+fn burn_out(players: &[Player], world: &mut World, position: BlockPos, chance: u32, age: u32) {
+    let state = world.get_block(position.0, position.1, position.2);
+    let Some((_, burn)) = block::flammability(state) else { return };
+    let mut rng = rand::thread_rng();
+    if rng.gen_range(0..chance) >= burn {
+        return;
+    }
+    world.set_block_and_notify(position, block::AIR);
+    if rng.gen_range(0..age + 10) < 5 && !world.rains_at(position) {
+        place(world, position, (age + rng.gen_range(0..5) / 4).min(MAX_AGE));
+    }
+    if block::name(state) == tnt::BLOCK {
+        tnt::prime(players, world, position, tnt::FUSE_TICKS);
+    }
+}
+
+// Geplanter Tick eines Feuers wie FireBlock bei Vanilla: ohne Halt oder im Regen geht es aus, sonst altert es,
+// verbrennt Nachbarn und springt auf Luft in der Nähe über. Im Ende brennt es auf Grundgestein ewig
+pub fn scheduled_tick(players: &[Player], world: &mut World, position: BlockPos, state: u16) {
+    schedule(world, position);
+    if !world.rules.get_bool(gamerule::DO_FIRE_TICK) {
+        return;
+    }
+    if !can_survive(world, position) {
+        world.set_block_and_notify(position, block::AIR);
+        return;
+    }
+    let (x, y, z) = position;
+    let below = world.get_block(x, y - 1, z);
+    let eternal = world.dimension == Dimension::End && below == block::BEDROCK;
+    let mut rng = rand::thread_rng();
+    let mut age = age(state);
+    if !eternal && rain_near(world, position) && rng.gen::<f32>() < 0.2 + age as f32 * 0.03 {
+        world.set_block_and_notify(position, block::AIR);
+        return;
+    }
+    let older = (age + rng.gen_range(0..3) / 2).min(MAX_AGE);
+    if older != age {
+        age = older;
+        world.set_block_and_notify(position, block::with_property(state, "age", &age.to_string()).unwrap_or(state));
+    }
+    if !eternal {
+        if !flammable_neighbour(world, position) {
+            if !block::is_solid(below) || age > 3 {
+                world.set_block_and_notify(position, block::AIR);
+            }
+            return;
+        }
+        if age == MAX_AGE && rng.gen_range(0..4) == 0 && !can_burn(below) {
+            world.set_block_and_notify(position, block::AIR);
+            return;
+        }
+    }
+    let humid = HUMID_BIOMES.contains(&world.get_biome(x, y, z));
+    let bonus = if humid { HUMID_BURN_OUT } else { 0 };
+    for (dx, dy, dz) in [(1, 0, 0), (-1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1)] {
+        let chance = if dy == 0 { BURN_OUT_SIDE } else { BURN_OUT_VERTICAL };
+        burn_out(players, world, (x + dx, y + dy, z + dz), chance - bonus, age);
+    }
+    let difficulty = world.difficulty.id() as u32;
+    for dx in -1..=1 {
+        for dz in -1..=1 {
+            for dy in -1..=4 {
+                if (dx, dy, dz) == (0, 0, 0) {
+                    continue;
+                }
+                let target = (x + dx, y + dy, z + dz);
+                let odds = ignite_odds(world, target);
+                if odds == 0 {
+                    continue;
+                }
+                // Nach oben springt es leichter als zur Seite
+                let chance = if dy > 1 { 100 + (dy as u32 - 1) * 100 } else { 100 };
+                let mut spread = (odds + 40 + difficulty * 7) / (age + 30);
+                if humid {
+                    spread /= 2;
+                }
+                if spread > 0 && rng.gen_range(0..chance) <= spread && !rain_near(world, target) {
+                    place(world, target, (age + rng.gen_range(0..5) / 4).min(MAX_AGE));
+                }
+            }
+        }
+    }
+}
+
+// Zufallstick von Lava: entzündet Luft über sich neben Brennbarem oder Brennbares neben sich, wie bei Vanilla
+pub fn lava_tick(world: &mut World, position: BlockPos) {
+    if !world.rules.get_bool(gamerule::DO_FIRE_TICK) {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let loaded = |world: &World, (x, _, z): BlockPos| world.chunks.contains_key(&(x >> 4, z >> 4));
+    let rises = rng.gen_range(0..3);
+    if rises > 0 {
+        let mut target = position;
+        for _ in 0..rises {
+            target = (target.0 + rng.gen_range(-1..=1), target.1 + 1, target.2 + rng.gen_range(-1..=1));
+            if !loaded(world, target) {
+                return;
+            }
+            let state = world.get_block(target.0, target.1, target.2);
+            if state == block::AIR {
+                if flammable_neighbour(world, target) {
+                    place(world, target, 0);
+                    return;
+                }
+            } else if block::is_solid(state) {
+                return;
+            }
+        }
+        return;
+    }
+    for _ in 0..3 {
+        let (x, y, z) = (position.0 + rng.gen_range(-1..=1), position.1, position.2 + rng.gen_range(-1..=1));
+        if !loaded(world, (x, y, z)) {
+            return;
+        }
+        if world.get_block(x, y + 1, z) == block::AIR && can_burn(world.get_block(x, y, z)) {
+            place(world, (x, y + 1, z), 0);
+        }
+    }
+}
+
+// Verbraucht die Feuerkugel oder nutzt das Feuerzeug ab, außer im Kreativmodus
+pub fn spend_igniter(player: &mut Player, slot: usize) {
+    if player.game_mode == GameMode::Creative {
+        return;
+    }
+    if player.inventory.slot(slot).item == FIRE_CHARGE {
+        player.inventory.consume_one(slot);
+    } else {
+        let mut stack = player.inventory.slot(slot).clone();
+        item::wear(&mut stack);
+        player.inventory.set(slot, stack);
+    }
+    let _ = player.inventory.send_slot(&player.connection, slot);
+}
+
+// Use Item On mit Feuerzeug oder Feuerkugel: Feuer vor der angeklickten Seite; false, wenn das Item keins ist oder
+// dort kein Feuer halten kann
+pub fn use_on(players: &mut [Player], world: &mut World, index: usize, slot: usize, (x, y, z): BlockPos, face: i32) -> bool {
+    let held = players[index].inventory.slot(slot).item.clone();
+    if held != FLINT_AND_STEEL && held != FIRE_CHARGE {
+        return false;
+    }
+    let (dx, dy, dz) = face_offset(face);
+    let position = (x + dx, y + dy, z + dz);
+    if !place(world, position, 0) {
+        return false;
+    }
+    let player = &mut players[index];
+    debug!("{} entzündet Feuer bei {:?}", player.username, position);
+    spend_igniter(player, slot);
+    let sound = if held == FIRE_CHARGE { FIRE_CHARGE_SOUND } else { IGNITE_SOUND };
+    let center = (position.0 as f64 + 0.5, position.1 as f64 + 0.5, position.2 as f64 + 0.5);
+    for viewer in players.iter().filter(|p| entity::in_view(p, world.dimension, center)) {
+        let _ = worldevent::send_sound(&viewer.connection, sound, center, 1.0);
+    }
+    true
+}
+
+// Was ein Spieler oder Mob in diesem Tick berührt
+#[derive(Debug, Default)]
+struct Contact {
+    // Schaden des Feuerblocks, Seelenfeuer brennt doppelt
+    fire: Option<f32>,
+    lava: bool,
+    // Wasser oder Regen
+    wet: bool,
+}
+
+fn contact(world: &World, position: (f64, f64, f64), height: f64) -> Contact {
+    let (x, z) = (position.0.floor() as i32, position.2.floor() as i32);
+    let (feet, head) = (position.1.floor() as i32, (position.1 + height).floor() as i32);
+    let mut contact = Contact::default();
+    for y in feet..=head {
+        let state = world.get_block(x, y, z);
+        match block::name(state) {
+            FIRE => {
+                contact.fire.get_or_insert(FIRE_DAMAGE);
+            }
+            SOUL_FIRE => contact.fire = Some(SOUL_FIRE_DAMAGE),
+            _ if block::is_lava(state) => contact.lava = true,
+            _ if block::is_water(state) => contact.wet = true,
+            _ => {}
+        }
+    }
+    contact.wet |= world.rains_at((x, feet, z)) || world.rains_at((x, head, z));
+    contact
+}
+
+// Ein Tick Brennen: Nässe löscht, Feuer und Lava entzünden neu, wer brennt, nimmt jede Sekunde Schaden. Gibt den
+// Schaden zurück und ob gerade gelöscht wurde
+fn burn(fire_ticks: &mut i32, contact: &Contact, ignitable: bool, now: i64) -> (Option<(DamageSource, f32)>, bool) {
+    let extinguished = contact.wet && *fire_ticks > 0;
+    if contact.wet {
+        *fire_ticks = 0;
+    }
+    let hurts = now % CONTACT_INTERVAL == 0;
+    let touching = match (contact.lava, contact.fire) {
+        _ if !ignitable => None,
+        (true, _) => Some((LAVA_TICKS, DamageSource::Lava, LAVA_DAMAGE)),
+        (false, Some(amount)) => Some((FIRE_TICKS, DamageSource::InFire, amount)),
+        (false, None) => None,
+    };
+    // Wer im Feuer oder in der Lava steht, nimmt nur deren Schaden
+    if let Some((ticks, source, amount)) = touching {
+        *fire_ticks = (*fire_ticks).max(ticks);
+        return (hurts.then_some((source, amount)), extinguished);
+    }
+    let mut damage = None;
+    if *fire_ticks > 0 {
+        if *fire_ticks % BURN_INTERVAL == 0 {
+            damage = Some((DamageSource::OnFire, BURN_DAMAGE));
+        }
+        *fire_ticks -= 1;
+    }
+    (damage, extinguished)
+}
+
+fn extinguish_sound(players: &[Player], dimension: Dimension, position: (f64, f64, f64)) {
+    for viewer in players.iter().filter(|p| entity::in_view(p, dimension, position)) {
+        let _ = worldevent::send_sound(&viewer.connection, EXTINGUISH_SOUND, position, 0.7);
+    }
+}
+
+// Zeigt allen in Sichtweite und dem Spieler selbst, ob er brennt
+fn show_player(players: &mut [Player], index: usize) {
+    let player = &mut players[index];
+    let on_fire = player.fire_ticks > 0;
+    if player.pose.on_fire == on_fire {
+        return;
+    }
+    player.pose.on_fire = on_fire;
+    let _ = entity::send_pose(&player.connection, entity::OWN_PLAYER_ID, &player.pose);
+    pose::broadcast(players, index);
+}
+
+fn ignitable_mob(kind: &str) -> bool {
+    entity::is_living(kind) && !FIRE_IMMUNE.contains(&kind)
+}
+
+// Setzt ein Ziel für ticks in Brand, etwa nach einem Treffer mit einem Flame-Pfeil. Darf nur ohne gehaltene Sperren
+// aufgerufen werden
+pub fn ignite(server: &Server, target: &Target, ticks: i32) {
+    let mut players = server.players.lock().unwrap();
+    if target.is_player() {
+        let Some(index) = players.iter().position(|p| p.uuid == target.uuid) else { return };
+        if players[index].game_mode.is_invulnerable() {
+            return;
+        }
+        players[index].fire_ticks = players[index].fire_ticks.max(ticks);
+        show_player(&mut players, index);
+        return;
+    }
+    let Some(lock) = dimension::world_with_mob(server, target.uuid) else { return };
+    let mut world = lock.lock().unwrap();
+    let dimension = world.dimension;
+    let Some(mob) = world.mobs.iter_mut().find(|m| m.id == target.uuid && ignitable_mob(&m.mob_type)) else { return };
+    let was_burning = mob.fire_ticks > 0;
+    mob.fire_ticks = mob.fire_ticks.max(ticks);
+    if was_burning {
+        return;
+    }
+    for viewer in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+        let _ = entity::send_on_fire(&viewer.connection, mob.entity_id, true);
+    }
+}
+
+// Feuer, Lava, Wasser und Regen für alle Spieler und lebenden Mobs; Feuerresistenz schützt vor dem Schaden, nicht vor
+// den Flammen. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut hurt: Vec<(Target, DamageSource, f32)> = Vec::new();
+    let mut players = server.players.lock().unwrap();
+    for index in 0..players.len() {
+        let player = &players[index];
+        if player.health <= 0.0 || player.game_mode == GameMode::Spectator {
+            continue;
+        }
+        let (contact, now) = {
+            let world = dimension::world_of(server, player.dimension).lock().unwrap();
+            (contact(&world, player.position, PLAYER_HEIGHT), world.time.age)
+        };
+        let player = &mut players[index];
+        let ignitable = !player.game_mode.is_invulnerable();
+        let (damage, extinguished) = burn(&mut player.fire_ticks, &contact, ignitable, now);
+        if let (Some((source, amount)), None) = (damage, player.effects.get(effect::FIRE_RESISTANCE)) {
+            hurt.push((Target::of_player(player), source, amount));
+        }
+        if extinguished {
+            let (dimension, position) = (player.dimension, player.position);
+            extinguish_sound(&players, dimension, position);
+        }
+        show_player(&mut players, index);
+    }
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let dimension = world.dimension;
+        let now = world.time.age;
+        for index in 0..world.mobs.len() {
+            let mob = &world.mobs[index];
+            if !entity::is_living(&mob.mob_type) || mob.health <= 0.0 {
+                continue;
+            }
+            let contact = contact(&world, mob.position, entity::hitbox(&mob.mob_type).1);
+            let mob = &mut world.mobs[index];
+            let was_burning = mob.fire_ticks > 0;
+            let (damage, extinguished) = burn(&mut mob.fire_ticks, &contact, ignitable_mob(&mob.mob_type), now);
+            if let (Some((source, amount)), None) = (damage, mob.effects.get(effect::FIRE_RESISTANCE)) {
+                hurt.push((Target::of_mob(mob, dimension), source, amount));
+            }
+            if extinguished {
+                extinguish_sound(&players, dimension, mob.position);
+            }
+            if was_burning != (mob.fire_ticks > 0) {
+                for viewer in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+                    let _ = entity::send_on_fire(&viewer.connection, mob.entity_id, !was_burning);
+                }
+            }
+        }
+    }
+    drop(players);
+    for (target, source, amount) in hurt {
+        damage::damage(server, &target, source, amount);
+    }
+}
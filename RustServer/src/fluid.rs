@@ -0,0 +1,78 @@
+use rand::Rng;
+use crate::damage::{self, DamageSource};
+use crate::selector::Target;
+use crate::{block, dimension, effect, entity, gamerule, inventory, pose, Player, Server, World};
+
+// Luftvorrat in Ticks wie bei Vanilla: unter Wasser einer weniger pro Tick, an der Luft vier mehr
+pub const MAX_AIR: i32 = 300;
+const AIR_REFILL: i32 = 4;
+// Ist der Vorrat aufgebraucht, kostet jede weitere Sekunde zwei Lebenspunkte
+const DROWNING_AT: i32 = -20;
+const DROWNING_DAMAGE: f32 = 2.0;
+const EYE_HEIGHT: f64 = 1.62;
+const SWIMMING_EYE_HEIGHT: f64 = 0.4;
+const RESPIRATION: &str = "minecraft:respiration";
+
+fn block_at(world: &World, (x, y, z): (f64, f64, f64)) -> u16 {
+    world.get_block(x.floor() as i32, y.floor() as i32, z.floor() as i32)
+}
+
+pub fn in_water(world: &World, position: (f64, f64, f64)) -> bool {
+    block::is_water(block_at(world, position))
+}
+
+pub fn in_lava(world: &World, position: (f64, f64, f64)) -> bool {
+    block::is_lava(block_at(world, position))
+}
+
+fn eyes_in_water(world: &World, player: &Player) -> bool {
+    let eye = if player.pose.swimming { SWIMMING_EYE_HEIGHT } else { EYE_HEIGHT };
+    let (x, y, z) = player.position;
+    in_water(world, (x, y + eye, z))
+}
+
+// Nach jeder Bewegung: wie Vanilla beginnt das Schwimmen beim Sprinten mit dem Kopf unter Wasser und endet erst,
+// wenn der Spieler nicht mehr sprintet oder das Wasser verlässt
+pub fn update_swimming(world: &World, players: &mut [Player], index: usize) {
+    let player = &players[index];
+    let swimming = player.pose.sprinting && !player.pose.gliding && if player.pose.swimming { in_water(world, player.position) } else { eyes_in_water(world, player) };
+    if swimming != player.pose.swimming {
+        players[index].pose.swimming = swimming;
+        pose::broadcast(players, index);
+    }
+}
+
+// Ein Tick Luftvorrat für alle Spieler; Respiration am Helm lässt wie bei Vanilla nur einen von Stufe + 1 Ticks
+// zählen. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut drowning: Vec<Target> = Vec::new();
+    let mut players = server.players.lock().unwrap();
+    for player in players.iter_mut().filter(|p| p.health > 0.0) {
+        let (underwater, damage) = {
+            let world = dimension::world_of(server, player.dimension).lock().unwrap();
+            (eyes_in_water(&world, player), world.rules.get_bool(gamerule::DROWNING_DAMAGE))
+        };
+        let before = player.air;
+        if underwater && !player.game_mode.is_invulnerable() && player.effects.get(effect::WATER_BREATHING).is_none() {
+            let respiration = player.inventory.slot(inventory::HEAD).components.enchantments.get(RESPIRATION).copied().unwrap_or(0);
+            if rand::thread_rng().gen_range(0..=respiration) == 0 {
+                player.air -= 1;
+            }
+            if player.air <= DROWNING_AT {
+                player.air = 0;
+                if damage {
+                    drowning.push(Target::of_player(player));
+                }
+            }
+        } else if player.air < MAX_AIR {
+            player.air = (player.air + AIR_REFILL).min(MAX_AIR);
+        }
+        if player.air != before {
+            let _ = entity::send_air(&player.connection, entity::OWN_PLAYER_ID, player.air);
+        }
+    }
+    drop(players);
+    for target in drowning {
+        damage::damage(server, &target, DamageSource::Drown, DROWNING_DAMAGE);
+    }
+}
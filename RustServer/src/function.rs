@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::Ordering;
+use crate::command::{self, CommandContext, CommandSender};
+use crate::json::Json;
+use crate::tick::{Scheduler, TaskId};
+use crate::{gamerule, item, Server};
+
+pub const TICK_TAG: &str = "minecraft:tick";
+pub const LOAD_TAG: &str = "minecraft:load";
+// Verschachtelte Aufrufe laufen rekursiv; tiefer geht es nicht, damit sich eine Funktion nicht endlos selbst aufruft
+const MAX_DEPTH: usize = 64;
+
+// Befehle einer .mcfunction-Datei: ohne Leerzeilen und Kommentare, ein \ am Zeilenende setzt die Zeile fort.
+// Makrozeilen ($) brauchen Argumente, die es noch nicht gibt, und werden übersprungen
+pub fn parse(text: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut pending = String::new();
+    for line in text.lines().map(str::trim) {
+        match line.strip_suffix('\\') {
+            Some(start) => {
+                pending.push_str(start.trim_start());
+                continue;
+            }
+            None => pending.push_str(line),
+        }
+        let command = std::mem::take(&mut pending);
+        if !command.is_empty() && !command.starts_with('#') && !command.starts_with('$') {
+            commands.push(command.trim_start_matches('/').to_string());
+        }
+    }
+    commands
+}
+
+// Von /schedule geplanter Aufruf einer Funktion oder eines Tags, fällig zur Weltzeit due.
+// Die Aufgabe im Scheduler fehlt, bis gespeicherte Aufrufe nach dem Start wieder angelegt sind
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledFunction {
+    pub function: String,
+    pub due: i64,
+    task: Option<TaskId>,
+}
+
+impl ScheduledFunction {
+    // {"function": "foo:bar", "due": 1234} für level.json
+    pub fn to_json(&self) -> Json {
+        let mut data = BTreeMap::new();
+        data.insert("function".to_string(), Json::String(self.function.clone()));
+        data.insert("due".to_string(), Json::Number(self.due as f64));
+        Json::Object(data)
+    }
+
+    pub fn from_json(json: &Json) -> Option<ScheduledFunction> {
+        Some(ScheduledFunction { function: json.get("function")?.as_str()?.to_string(), due: json.get("due")?.as_f64()? as i64, task: None })
+    }
+}
+
+// foo:bar bzw. #foo:bar mit ergänztem Namespace
+fn canonical(id: &str) -> String {
+    match id.strip_prefix('#') {
+        Some(tag) => format!("#{}", item::namespaced(tag)),
+        None => item::namespaced(id),
+    }
+}
+
+// Funktions-IDs zu foo:bar oder einem Tag #foo:bar
+fn resolve(server: &Server, id: &str) -> Result<Vec<String>, String> {
+    let datapacks = server.datapacks.lock().unwrap();
+    let registries = &datapacks.registries;
+    let id = canonical(id);
+    match id.strip_prefix('#') {
+        Some(tag) => registries.tags.get("function").and_then(|tags| tags.get(tag)).cloned().ok_or_else(|| format!("Unknown function tag '{}'", id)),
+        None if registries.functions.contains_key(&id) => Ok(vec![id]),
+        None => Err(format!("Unknown function '{}'", id)),
+    }
+}
+
+// Führt eine Funktion oder alle eines Tags im Kontext des Aufrufers aus, sodass @s und ~ erhalten bleiben.
+// Alle Funktionen eines Ticks teilen sich maxCommandChainLength Befehle. Gibt die Zahl der Funktionen
+// und der ausgeführten Befehle zurück
+pub fn run(ctx: &CommandContext, id: &str) -> Result<(usize, usize), String> {
+    if ctx.depth >= MAX_DEPTH {
+        return Err(format!("Functions are nested deeper than {} levels", MAX_DEPTH));
+    }
+    let functions = resolve(ctx.server, id)?;
+    let limit = ctx.world().lock().unwrap().rules.get_int(gamerule::MAX_COMMAND_CHAIN_LENGTH).max(0) as usize;
+    let nested = ctx.nested();
+    let mut executed = 0;
+    for function in &functions {
+        let lines = ctx.server.datapacks.lock().unwrap().registries.functions.get(function).cloned().unwrap_or_default();
+        for line in &lines {
+            if ctx.server.function_commands.fetch_add(1, Ordering::Relaxed) >= limit {
+                warn!("Funktion {} abgebrochen: mehr als {} Befehle in diesem Tick", function, limit);
+                return Ok((functions.len(), executed));
+            }
+            command::run_line(&nested, line);
+            executed += 1;
+        }
+    }
+    Ok((functions.len(), executed))
+}
+
+// Zu Beginn jedes Ticks: nach einem (Neu-)Laden der Datenpakete einmal #minecraft:load, dann #minecraft:tick.
+// Die Funktionen laufen als Server ohne Rückmeldung. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    server.function_commands.store(0, Ordering::Relaxed);
+    let tags: Vec<&str> = {
+        let mut datapacks = server.datapacks.lock().unwrap();
+        let load = datapacks.take_load_pending();
+        // Ohne Datenpaket, das den Tag anlegt, gibt es nichts zu tun
+        let defined = datapacks.registries.tags.get("function");
+        [LOAD_TAG, TICK_TAG].into_iter().filter(|tag| (load || *tag == TICK_TAG) && defined.is_some_and(|tags| tags.contains_key(*tag))).collect()
+    };
+    let ctx = CommandContext::new(server, CommandSender::Server);
+    for tag in tags {
+        if let Err(e) = run(&ctx, &format!("#{}", tag)) {
+            warn!("#{}: {}", tag, e);
+        }
+    }
+}
+
+// Plant eine Funktion oder einen Tag in delay Ticks ein; replace ersetzt bisherige Aufrufe derselben ID.
+// Gibt die ID und die Weltzeit der Ausführung zurück
+pub fn schedule(server: &Server, id: &str, delay: i64, replace: bool) -> Result<(String, i64), String> {
+    resolve(server, id)?;
+    let function = canonical(id);
+    let mut world = server.world.lock().unwrap();
+    let mut scheduler = server.scheduler.lock().unwrap();
+    if replace {
+        cancel(&mut world.scheduled_functions, &mut scheduler, &function);
+    }
+    let due = world.time.age + delay;
+    let task = start(&mut scheduler, &function, due, delay);
+    world.scheduled_functions.push(ScheduledFunction { function: function.clone(), due, task: Some(task) });
+    Ok((function, due))
+}
+
+// Entfernt alle geplanten Aufrufe einer ID und gibt ihre Anzahl zurück
+pub fn clear(server: &Server, id: &str) -> usize {
+    let mut world = server.world.lock().unwrap();
+    let mut scheduler = server.scheduler.lock().unwrap();
+    cancel(&mut world.scheduled_functions, &mut scheduler, &canonical(id))
+}
+
+fn cancel(schedules: &mut Vec<ScheduledFunction>, scheduler: &mut Scheduler, function: &str) -> usize {
+    let before = schedules.len();
+    schedules.retain(|entry| {
+        if entry.function != function {
+            return true;
+        }
+        if let Some(task) = entry.task {
+            scheduler.cancel(task);
+        }
+        false
+    });
+    before - schedules.len()
+}
+
+fn start(scheduler: &mut Scheduler, function: &str, due: i64, delay: i64) -> TaskId {
+    let function = function.to_string();
+    scheduler.run_later(delay.max(1) as u64, move |server| run_scheduled(server, &function, due))
+}
+
+// Mehrere Aufrufe mit gleicher ID und Weltzeit sind austauschbar, jede Aufgabe entfernt einen davon
+fn run_scheduled(server: &Server, function: &str, due: i64) {
+    {
+        let mut world = server.world.lock().unwrap();
+        let Some(index) = world.scheduled_functions.iter().position(|entry| entry.function == function && entry.due == due) else { return };
+        world.scheduled_functions.remove(index);
+    }
+    if let Err(e) = run(&CommandContext::new(server, CommandSender::Server), function) {
+        warn!("Geplante Funktion {}: {}", function, e);
+    }
+}
+
+// Nach dem Start: aus level.json geladene Aufrufe im Scheduler anlegen, überfällige im nächsten Tick
+pub fn resume_schedules(server: &Server) {
+    let mut world = server.world.lock().unwrap();
+    let mut scheduler = server.scheduler.lock().unwrap();
+    let age = world.time.age;
+    for entry in world.scheduled_functions.iter_mut().filter(|entry| entry.task.is_none()) {
+        entry.task = Some(start(&mut scheduler, &entry.function, entry.due, entry.due - age));
+    }
+}
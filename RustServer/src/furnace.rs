@@ -0,0 +1,98 @@
+use crate::blockentity::{BlockEntity, FURNACE_FUEL, FURNACE_INPUT, FURNACE_RESULT};
+use crate::item::{self, ItemStack};
+use crate::recipe::{self, RecipeRegistry};
+use crate::{block, Chunk, World};
+
+// Garzeit eines Ofenrezepts, wie bei Vanilla
+const COOK_TICKS: u32 = 200;
+// So schnell kühlt ein erloschener Ofen den Fortschritt wieder ab
+const COOL_DOWN: u32 = 2;
+
+// Ein Tick der Öfen in world.furnaces. Das Ergebnis berechnet allein der Server aus den Ofenrezepten; Brennstoff wird
+// nur angezündet, wenn es etwas zu schmelzen gibt und das Ergebnis noch in den Ergebnis-Slot passt. Erloschene und
+// abgekühlte Öfen fallen heraus, bis wake sie wieder einträgt
+pub fn tick(world: &mut World, recipes: &RecipeRegistry) {
+    let furnaces: Vec<(i32, i32, i32)> = world.furnaces.iter().copied().collect();
+    for position in furnaces {
+        if !tick_furnace(world, recipes, position) {
+            world.furnaces.remove(&position);
+        }
+    }
+}
+
+// Nach einer Änderung am Inhalt, durch Klicks im Fenster oder Trichter
+pub fn wake(world: &mut World, position: (i32, i32, i32)) {
+    if matches!(world.block_entity(position), Some(BlockEntity::Furnace { .. })) {
+        world.furnaces.insert(position);
+    }
+}
+
+// Öfen eines geladenen Chunks, die noch brennen, abkühlen oder etwas zum Schmelzen haben
+pub fn active_in(chunk: &Chunk) -> impl Iterator<Item = (i32, i32, i32)> + '_ {
+    chunk.block_entities.iter().filter_map(|(&position, entity)| match entity {
+        BlockEntity::Furnace { items, burn, cook, .. } if *burn > 0 || *cook > 0 || !items[FURNACE_INPUT].is_empty() => Some(position),
+        _ => None,
+    })
+}
+
+fn fits(output: &ItemStack, result: &ItemStack) -> bool {
+    output.is_empty() || (output.stacks_with(result) && output.count as u16 + result.count as u16 <= item::max_stack_size(&output.item) as u16)
+}
+
+// false, sobald der Ofen weder brennt noch Fortschritt hat oder nicht mehr steht
+fn tick_furnace(world: &mut World, recipes: &RecipeRegistry, position: (i32, i32, i32)) -> bool {
+    let Some(BlockEntity::Furnace { items, burn, burn_total, cook }) = world.block_entity_mut(position) else { return false };
+    // Brennstoff und Zutat ändern sich nur zusammen mit Brenndauer oder Fortschritt
+    let before = (*burn, *cook);
+    *burn = burn.saturating_sub(1);
+    let result = recipes.smelt(&items[FURNACE_INPUT]).filter(|result| fits(&items[FURNACE_RESULT], result));
+    if *burn == 0 && result.is_some() {
+        if let Some(ticks) = recipe::fuel_ticks(&items[FURNACE_FUEL]) {
+            (*burn, *burn_total) = (ticks, ticks);
+            let fuel = &mut items[FURNACE_FUEL];
+            if fuel.item == "minecraft:lava_bucket" {
+                *fuel = ItemStack { item: "minecraft:bucket".to_string(), ..fuel.clone() };
+            } else {
+                fuel.count -= 1;
+                if fuel.count == 0 {
+                    *fuel = ItemStack::empty();
+                }
+            }
+        }
+    }
+    match result {
+        Some(result) if *burn > 0 => {
+            *cook += 1;
+            if *cook >= COOK_TICKS {
+                *cook = 0;
+                let input = &mut items[FURNACE_INPUT];
+                input.count -= 1;
+                if input.count == 0 {
+                    *input = ItemStack::empty();
+                }
+                let output = &mut items[FURNACE_RESULT];
+                if output.is_empty() {
+                    *output = result;
+                } else {
+                    output.count += result.count;
+                }
+            }
+        }
+        // Brennt der Ofen ohne passende Zutat, fängt das Schmelzen von vorne an
+        _ if *burn > 0 => *cook = 0,
+        _ => *cook = cook.saturating_sub(COOL_DOWN),
+    }
+    let (lit, active) = (*burn > 0, *burn > 0 || *cook > 0);
+    if (*burn, *cook) == before {
+        return active;
+    }
+    let (x, y, z) = position;
+    world.dirty_chunks.insert((x >> 4, z >> 4));
+    let state = world.get_block(x, y, z);
+    if block::property(state, "lit") != Some(if lit { "true" } else { "false" }) {
+        if let Some(state) = block::with_property(state, "lit", &lit.to_string()) {
+            world.set_block_and_notify(position, state);
+        }
+    }
+    active
+}
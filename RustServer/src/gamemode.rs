@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::error::ServerError;
+use crate::item::ItemStack;
+use crate::protocol::clientbound;
+use crate::protocol::types::read_uuid;
+use crate::dimension::{self, KEEP_ALL};
+use crate::tablist;
+use crate::text::Text;
+use crate::{send_game_event, teleport_player, track_player, Connection, Player, Server, GAME_EVENT_CHANGE_GAME_MODE};
+
+// Flags im Player-Abilities-Paket
+const INVULNERABLE: u8 = 0x01;
+const FLYING: u8 = 0x02;
+const ALLOW_FLYING: u8 = 0x04;
+const INSTANT_BREAK: u8 = 0x08;
+// Vanilla-Standardwerte für Fluggeschwindigkeit und Sichtfeld
+const FLYING_SPEED: f32 = 0.05;
+const FOV_MODIFIER: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    // Wie im Protokoll (Join Game, Game Event, Player Info)
+    pub fn id(self) -> u8 {
+        match self {
+            GameMode::Survival => 0,
+            GameMode::Creative => 1,
+            GameMode::Adventure => 2,
+            GameMode::Spectator => 3,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Adventure => "adventure",
+            GameMode::Spectator => "spectator",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GameMode> {
+        [GameMode::Survival, GameMode::Creative, GameMode::Adventure, GameMode::Spectator].into_iter().find(|mode| mode.name() == name)
+    }
+
+    pub fn display_text(self) -> Text {
+        Text::translate(format!("gameMode.{}", self.name()))
+    }
+
+    // Nur die Leere und /kill treffen Spieler in diesen Modi
+    pub fn is_invulnerable(self) -> bool {
+        matches!(self, GameMode::Creative | GameMode::Spectator)
+    }
+
+    pub fn allows_flight(self) -> bool {
+        matches!(self, GameMode::Creative | GameMode::Spectator)
+    }
+
+    // Der Client schickt im Kreativmodus nur den Beginn des Abbaus
+    pub fn breaks_instantly(self) -> bool {
+        self == GameMode::Creative
+    }
+
+    // Zuschauer fliegen durch Blöcke und tauchen in keiner Entity-Abfrage auf
+    pub fn has_collision(self) -> bool {
+        self != GameMode::Spectator
+    }
+
+    pub fn is_tracked(self) -> bool {
+        self != GameMode::Spectator
+    }
+
+    pub fn can_attack(self) -> bool {
+        self != GameMode::Spectator
+    }
+
+    pub fn drops_blocks(self) -> bool {
+        matches!(self, GameMode::Survival | GameMode::Adventure)
+    }
+}
+
+// Abbauen: Zuschauer nie, der Abenteuermodus nur mit passendem can_break am gehaltenen Item
+pub fn may_break(mode: GameMode, held: &ItemStack, state: u16, block_tags: Option<&HashMap<String, Vec<String>>>) -> bool {
+    match mode {
+        GameMode::Spectator => false,
+        GameMode::Adventure => held.components.can_break.iter().any(|predicate| predicate.matches(state, block_tags)),
+        GameMode::Survival | GameMode::Creative => true,
+    }
+}
+
+// Bauen auf den Block clicked; im Abenteuermodus nur mit passendem can_place_on
+pub fn may_place_on(mode: GameMode, held: &ItemStack, clicked: u16, block_tags: Option<&HashMap<String, Vec<String>>>) -> bool {
+    match mode {
+        GameMode::Spectator => false,
+        GameMode::Adventure => held.components.can_place_on.iter().any(|predicate| predicate.matches(clicked, block_tags)),
+        GameMode::Survival | GameMode::Creative => true,
+    }
+}
+
+pub fn send_abilities(connection: &Connection, mode: GameMode) -> Result<(), String> {
+    let mut flags = 0;
+    if mode.is_invulnerable() {
+        flags |= INVULNERABLE;
+    }
+    if mode.allows_flight() {
+        flags |= ALLOW_FLYING;
+    }
+    // Zuschauer fliegen immer, wer den Kreativmodus verlässt, fällt
+    if mode == GameMode::Spectator {
+        flags |= FLYING;
+    }
+    if mode.breaks_instantly() {
+        flags |= INSTANT_BREAK;
+    }
+    let mut packet_data = vec![flags];
+    packet_data.extend(FLYING_SPEED.to_be_bytes());
+    packet_data.extend(FOV_MODIFIER.to_be_bytes());
+    connection.send(clientbound::PLAYER_ABILITIES, &packet_data)
+}
+
+// Wechselt den Spielmodus sofort: Client, Fähigkeiten und Tab-Liste aller Spieler. false, wenn der Spieler
+// ihn schon hatte
+pub fn set(server: &Server, uuid: Uuid, mode: GameMode) -> Result<bool, String> {
+    let mut players = server.players.lock().unwrap();
+    let player = players.iter_mut().find(|p| p.uuid == uuid).ok_or("Player is not online")?;
+    if player.game_mode == mode {
+        return Ok(false);
+    }
+    player.game_mode = mode;
+    track_player(&mut dimension::world_of(server, player.dimension).lock().unwrap(), uuid, mode, player.position);
+    send_game_event(&player.connection, GAME_EVENT_CHANGE_GAME_MODE, mode.id() as f32)?;
+    send_abilities(&player.connection, mode)?;
+    info!("Spielmodus von {} ist jetzt {}", player.username, mode.name());
+    for other in players.iter() {
+        let _ = tablist::send_game_mode(&other.connection, uuid, mode);
+    }
+    Ok(true)
+}
+
+// Spectate-Paket: Zuschauer springen per Hotbar-Menü zu einem Spieler oder Mob. Wird ohne gehaltene Sperren
+// aufgerufen, weil die Teleportation sie selbst nimmt
+pub fn handle_spectate(server: &Server, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let target = read_uuid(cursor)?;
+    let players = server.players.lock().unwrap();
+    let Some((rotation, dimension)) = players.iter().find(|p| p.uuid == player.uuid && p.game_mode == GameMode::Spectator).map(|p| (p.rotation, p.dimension)) else { return Ok(()) };
+    // Spieler auch in anderen Dimensionen, Mobs nur in der eigenen
+    let found = players.iter().find(|p| p.uuid == target).map(|p| (p.position, p.dimension));
+    let found = found.or_else(|| dimension::world_of(server, dimension).lock().unwrap().mobs.iter().find(|m| m.id == target).map(|m| (m.position, dimension)));
+    drop(players);
+    let Some((position, target_dimension)) = found else {
+        debug!("Zuschauer {} wollte zu unbekannter Entity {}", player.username, target);
+        return Ok(());
+    };
+    let result = if target_dimension == dimension {
+        teleport_player(server, player.uuid, position, rotation)
+    } else {
+        dimension::change_dimension(server, player.uuid, target_dimension, position, rotation, KEEP_ALL)
+    };
+    result.map_err(|e| ServerError::Internal(format!("Fehler beim Teleportieren zu {}: {}", target, e)))
+}
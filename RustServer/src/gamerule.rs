@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use crate::json::Json;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+impl std::fmt::Display for RuleValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleValue::Bool(value) => write!(f, "{}", value),
+            RuleValue::Int(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+// Typisierte Schlüssel, damit Abfragen im Code nicht am falschen Typ scheitern können
+#[derive(Debug, Clone, Copy)]
+pub struct BoolRule(&'static str);
+#[derive(Debug, Clone, Copy)]
+pub struct IntRule(&'static str);
+
+pub const BLOCK_EXPLOSION_DROP_DECAY: BoolRule = BoolRule("blockExplosionDropDecay");
+pub const DO_DAYLIGHT_CYCLE: BoolRule = BoolRule("doDaylightCycle");
+pub const DO_FIRE_TICK: BoolRule = BoolRule("doFireTick");
+pub const DO_IMMEDIATE_RESPAWN: BoolRule = BoolRule("doImmediateRespawn");
+pub const DO_MOB_LOOT: BoolRule = BoolRule("doMobLoot");
+pub const DO_MOB_SPAWNING: BoolRule = BoolRule("doMobSpawning");
+pub const DO_TILE_DROPS: BoolRule = BoolRule("doTileDrops");
+pub const DO_WEATHER_CYCLE: BoolRule = BoolRule("doWeatherCycle");
+pub const DROWNING_DAMAGE: BoolRule = BoolRule("drowningDamage");
+pub const FALL_DAMAGE: BoolRule = BoolRule("fallDamage");
+pub const KEEP_INVENTORY: BoolRule = BoolRule("keepInventory");
+pub const MOB_EXPLOSION_DROP_DECAY: BoolRule = BoolRule("mobExplosionDropDecay");
+pub const MOB_GRIEFING: BoolRule = BoolRule("mobGriefing");
+pub const NATURAL_REGENERATION: BoolRule = BoolRule("naturalRegeneration");
+pub const SHOW_DEATH_MESSAGES: BoolRule = BoolRule("showDeathMessages");
+pub const TNT_EXPLOSION_DROP_DECAY: BoolRule = BoolRule("tntExplosionDropDecay");
+pub const MAX_COMMAND_CHAIN_LENGTH: IntRule = IntRule("maxCommandChainLength");
+pub const PLAYERS_SLEEPING_PERCENTAGE: IntRule = IntRule("playersSleepingPercentage");
+pub const RANDOM_TICK_SPEED: IntRule = IntRule("randomTickSpeed");
+pub const SNOW_ACCUMULATION_HEIGHT: IntRule = IntRule("snowAccumulationHeight");
+pub const SPAWN_RADIUS: IntRule = IntRule("spawnRadius");
+pub const COMMAND_MODIFICATION_BLOCK_LIMIT: IntRule = IntRule("commandModificationBlockLimit");
+
+// Alle Regeln von Vanilla 1.21.1 mit Standardwert. Auch die, die der Server noch nicht auswertet,
+// lassen sich setzen und bleiben mit der Welt gespeichert
+const RULES: &[(&str, RuleValue)] = &[
+    ("announceAdvancements", RuleValue::Bool(true)),
+    ("blockExplosionDropDecay", RuleValue::Bool(true)),
+    ("commandBlockOutput", RuleValue::Bool(true)),
+    ("commandModificationBlockLimit", RuleValue::Int(32768)),
+    ("disableElytraMovementCheck", RuleValue::Bool(false)),
+    ("disablePlayerMovementCheck", RuleValue::Bool(false)),
+    ("disableRaids", RuleValue::Bool(false)),
+    ("doDaylightCycle", RuleValue::Bool(true)),
+    ("doEntityDrops", RuleValue::Bool(true)),
+    ("doFireTick", RuleValue::Bool(true)),
+    ("doImmediateRespawn", RuleValue::Bool(false)),
+    ("doInsomnia", RuleValue::Bool(true)),
+    ("doLimitedCrafting", RuleValue::Bool(false)),
+    ("doMobLoot", RuleValue::Bool(true)),
+    ("doMobSpawning", RuleValue::Bool(true)),
+    ("doPatrolSpawning", RuleValue::Bool(true)),
+    ("doTileDrops", RuleValue::Bool(true)),
+    ("doTraderSpawning", RuleValue::Bool(true)),
+    ("doVinesSpread", RuleValue::Bool(true)),
+    ("doWardenSpawning", RuleValue::Bool(true)),
+    ("doWeatherCycle", RuleValue::Bool(true)),
+    ("drowningDamage", RuleValue::Bool(true)),
+    ("enderPearlsVanishOnDeath", RuleValue::Bool(true)),
+    ("fallDamage", RuleValue::Bool(true)),
+    ("fireDamage", RuleValue::Bool(true)),
+    ("forgiveDeadPlayers", RuleValue::Bool(true)),
+    ("freezeDamage", RuleValue::Bool(true)),
+    ("globalSoundEvents", RuleValue::Bool(true)),
+    ("keepInventory", RuleValue::Bool(false)),
+    ("lavaSourceConversion", RuleValue::Bool(false)),
+    ("logAdminCommands", RuleValue::Bool(true)),
+    ("maxCommandChainLength", RuleValue::Int(65536)),
+    ("maxCommandForkCount", RuleValue::Int(65536)),
+    ("maxEntityCramming", RuleValue::Int(24)),
+    ("mobExplosionDropDecay", RuleValue::Bool(true)),
+    ("mobGriefing", RuleValue::Bool(true)),
+    ("naturalRegeneration", RuleValue::Bool(true)),
+    ("playersNetherPortalCreativeDelay", RuleValue::Int(1)),
+    ("playersNetherPortalDefaultDelay", RuleValue::Int(80)),
+    ("playersSleepingPercentage", RuleValue::Int(100)),
+    ("projectilesCanBreakBlocks", RuleValue::Bool(true)),
+    ("randomTickSpeed", RuleValue::Int(3)),
+    ("reducedDebugInfo", RuleValue::Bool(false)),
+    ("sendCommandFeedback", RuleValue::Bool(true)),
+    ("showDeathMessages", RuleValue::Bool(true)),
+    ("snowAccumulationHeight", RuleValue::Int(1)),
+    ("spawnChunkRadius", RuleValue::Int(2)),
+    ("spawnRadius", RuleValue::Int(10)),
+    ("spectatorsGenerateChunks", RuleValue::Bool(true)),
+    ("tntExplosionDropDecay", RuleValue::Bool(false)),
+    ("universalAnger", RuleValue::Bool(false)),
+    ("waterSourceConversion", RuleValue::Bool(true)),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRules {
+    values: BTreeMap<&'static str, RuleValue>,
+}
+
+impl GameRules {
+    pub fn new() -> GameRules {
+        GameRules { values: RULES.iter().copied().collect() }
+    }
+
+    pub fn get(&self, name: &str) -> Option<RuleValue> {
+        self.values.get(name).copied()
+    }
+
+    pub fn get_bool(&self, rule: BoolRule) -> bool {
+        matches!(self.values.get(rule.0), Some(RuleValue::Bool(true)))
+    }
+
+    pub fn get_int(&self, rule: IntRule) -> i32 {
+        match self.values.get(rule.0) {
+            Some(RuleValue::Int(value)) => *value,
+            _ => 0,
+        }
+    }
+
+    // Setzt eine Regel aus einem Befehlsargument; der Typ richtet sich nach dem Standardwert
+    pub fn set(&mut self, name: &str, text: &str) -> Result<RuleValue, String> {
+        let (name, current) = self.values.iter_mut().find(|(n, _)| **n == name).ok_or_else(|| format!("Unknown game rule '{}'", name))?;
+        let value = match current {
+            RuleValue::Bool(_) => match text {
+                "true" => RuleValue::Bool(true),
+                "false" => RuleValue::Bool(false),
+                _ => return Err(format!("Invalid boolean, expected 'true' or 'false' but found '{}'", text)),
+            },
+            RuleValue::Int(_) => RuleValue::Int(text.parse().map_err(|_| format!("Invalid integer '{}' for {}", text, name))?),
+        };
+        *current = value;
+        Ok(value)
+    }
+
+    // {"keepInventory": true, "randomTickSpeed": 3, ...} für level.json
+    pub fn to_json(&self) -> Json {
+        let values = self
+            .values
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    RuleValue::Bool(value) => Json::Bool(*value),
+                    RuleValue::Int(value) => Json::Number(*value as f64),
+                };
+                (name.to_string(), value)
+            })
+            .collect();
+        Json::Object(values)
+    }
+
+    // Unbekannte oder falsch typisierte Einträge werden übergangen, fehlende behalten ihren Standardwert
+    pub fn from_json(json: &Json) -> GameRules {
+        let mut rules = GameRules::new();
+        for (name, value) in rules.values.iter_mut() {
+            match (value, json.get(name)) {
+                (RuleValue::Bool(current), Some(Json::Bool(saved))) => *current = *saved,
+                (RuleValue::Int(current), Some(Json::Number(saved))) if saved.fract() == 0.0 => *current = *saved as i32,
+                _ => {}
+            }
+        }
+        rules
+    }
+}
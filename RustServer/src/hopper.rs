@@ -0,0 +1,142 @@
+use crate::blockentity::{BlockEntity, Face};
+use crate::item::ItemStack;
+use crate::{block, entity, furnace, Player, World};
+
+// Ticks zwischen zwei Übertragungen, wie bei Vanilla
+const TRANSFER_COOLDOWN: i32 = 8;
+// Items werden vom Rand der Schüssel bis einen Block über dem Trichter eingesammelt
+const COLLECT_BOTTOM: f64 = 0.6875;
+const COLLECT_TOP: f64 = 2.0;
+
+// Ein Tick aller Trichter der Welt: erst ein Item in den Container in Blickrichtung schieben, dann eins aus dem
+// Container darüber ziehen oder ein Item-Entity darauf aufsammeln
+pub fn tick(players: &[Player], world: &mut World) {
+    let hoppers: Vec<(i32, i32, i32)> = world
+        .chunks
+        .values()
+        .flat_map(|chunk| chunk.block_entities.iter().filter(|(_, entity)| matches!(entity, BlockEntity::Hopper { .. })).map(|(&position, _)| position))
+        .collect();
+    for position in hoppers {
+        tick_hopper(players, world, position);
+    }
+}
+
+fn tick_hopper(players: &[Player], world: &mut World, position: (i32, i32, i32)) {
+    let (x, y, z) = position;
+    let state = world.get_block(x, y, z);
+    let Some(BlockEntity::Hopper { cooldown, .. }) = world.block_entity_mut(position) else { return };
+    *cooldown -= 1;
+    // Ohne Redstone lässt sich enabled nur über /setblock abschalten
+    if *cooldown > 0 || block::property(state, "enabled") == Some("false") {
+        return;
+    }
+    *cooldown = 0;
+    let pushed = push(world, position, state);
+    let pulled = pull(players, world, position);
+    if pushed || pulled {
+        if let Some(BlockEntity::Hopper { cooldown, .. }) = world.block_entity_mut(position) {
+            *cooldown = TRANSFER_COOLDOWN;
+        }
+        world.dirty_chunks.insert((x >> 4, z >> 4));
+    }
+}
+
+fn facing(state: u16) -> (i32, i32, i32) {
+    match block::property(state, "facing") {
+        Some("north") => (0, 0, -1),
+        Some("south") => (0, 0, 1),
+        Some("west") => (-1, 0, 0),
+        Some("east") => (1, 0, 0),
+        _ => (0, -1, 0),
+    }
+}
+
+// Ein Item aus dem ersten Slot, das in den Zielcontainer passt. Nach unten landet es oben im Ziel, etwa in der
+// Zutat eines Ofens, zur Seite in dessen Brennstoff
+fn push(world: &mut World, position: (i32, i32, i32), state: u16) -> bool {
+    let (dx, dy, dz) = facing(state);
+    let target = (position.0 + dx, position.1 + dy, position.2 + dz);
+    let face = if dy < 0 { Face::Up } else { Face::Side };
+    let Some(items) = world.block_entity(position).map(|hopper| hopper.items().to_vec()) else { return false };
+    for (slot, stack) in items.iter().enumerate().filter(|(_, stack)| !stack.is_empty()) {
+        let Some(destination) = world.block_entity_mut(target) else { return false };
+        // Ein gerade befüllter Trichter wartet wie bei Vanilla, bevor er weiterschiebt
+        let was_empty = matches!(destination, BlockEntity::Hopper { .. }) && destination.items().iter().all(ItemStack::is_empty);
+        if !destination.insert(ItemStack { count: 1, ..stack.clone() }, face).is_empty() {
+            continue;
+        }
+        if let BlockEntity::Hopper { cooldown, .. } = destination {
+            if was_empty {
+                *cooldown = TRANSFER_COOLDOWN;
+            }
+        }
+        world.dirty_chunks.insert((target.0 >> 4, target.2 >> 4));
+        furnace::wake(world, target);
+        if let Some(hopper) = world.block_entity_mut(position) {
+            let source = &mut hopper.items_mut()[slot];
+            source.count -= 1;
+            if source.count == 0 {
+                *source = ItemStack::empty();
+            }
+        }
+        return true;
+    }
+    false
+}
+
+fn pull(players: &[Player], world: &mut World, position: (i32, i32, i32)) -> bool {
+    let Some(hopper) = world.block_entity(position).cloned() else { return false };
+    if hopper.is_full() {
+        return false;
+    }
+    let above = (position.0, position.1 + 1, position.2);
+    if let Some(source) = world.block_entity_mut(above) {
+        // Aus einem Ofen darüber nur das Ergebnis (und leere Eimer)
+        let Some(taken) = source.extract_one(Face::Down, |one| hopper.clone().insert(one.clone(), Face::Up).is_empty()) else { return false };
+        world.dirty_chunks.insert((above.0 >> 4, above.2 >> 4));
+        // Ein voller Ergebnis-Slot hat den Ofen darüber vielleicht angehalten
+        furnace::wake(world, above);
+        if let Some(hopper) = world.block_entity_mut(position) {
+            hopper.insert(taken, Face::Up);
+        }
+        return true;
+    }
+    if block::is_solid(world.get_block(above.0, above.1, above.2)) {
+        return false;
+    }
+    collect(players, world, position)
+}
+
+// Nimmt so viel wie möglich vom ersten Item-Entity im Sammelbereich auf
+fn collect(players: &[Player], world: &mut World, (x, y, z): (i32, i32, i32)) -> bool {
+    let inside = |(px, py, pz): (f64, f64, f64)| {
+        px >= x as f64 && px < x as f64 + 1.0 && pz >= z as f64 && pz < z as f64 + 1.0 && py >= y as f64 + COLLECT_BOTTOM && py < y as f64 + COLLECT_TOP
+    };
+    for index in 0..world.items.len() {
+        if !inside(world.items[index].position) {
+            continue;
+        }
+        let stack = world.items[index].stack.clone();
+        let Some(hopper) = world.block_entity_mut((x, y, z)) else { return false };
+        let rest = hopper.insert(stack.clone(), Face::Up);
+        if rest.count == stack.count && !rest.is_empty() {
+            continue;
+        }
+        let dimension = world.dimension;
+        if rest.is_empty() {
+            let item = world.items.remove(index);
+            world.entities.remove(item.uuid);
+            for player in players.iter().filter(|p| entity::in_view(p, dimension, item.position)) {
+                let _ = entity::send_remove(&player.connection, &[item.id]);
+            }
+        } else {
+            let item = &mut world.items[index];
+            item.stack = rest;
+            for player in players.iter().filter(|p| entity::in_view(p, dimension, item.position)) {
+                let _ = item.send_stack(&player.connection);
+            }
+        }
+        return true;
+    }
+    false
+}
@@ -0,0 +1,205 @@
+use crate::damage::{self, DamageSource};
+use crate::difficulty::Difficulty;
+use crate::gamemode::GameMode;
+use crate::selector::Target;
+use crate::{dimension, effect, gamerule, item, Player, Server};
+
+// Nahrung und Sättigung wie FoodData bei Vanilla
+pub const MAX_FOOD: i32 = 20;
+const START_SATURATION: f32 = 5.0;
+// Je so viel Erschöpfung kostet einen Punkt Sättigung oder, wenn die leer ist, Nahrung
+const EXHAUSTION_PER_POINT: f32 = 4.0;
+const MAX_EXHAUSTION: f32 = 40.0;
+// Darüber darf gesprintet werden
+const SPRINT_FOOD: i32 = 6;
+pub const SPRINT_EXHAUSTION_PER_BLOCK: f32 = 0.1;
+pub const JUMP_EXHAUSTION: f32 = 0.05;
+pub const SPRINT_JUMP_EXHAUSTION: f32 = 0.2;
+pub const ATTACK_EXHAUSTION: f32 = 0.1;
+pub const DAMAGE_EXHAUSTION: f32 = 0.1;
+// Der Effekt Hunger pro Stufe und Tick
+const HUNGER_EFFECT_EXHAUSTION: f32 = 0.005;
+// Natürliche Regeneration: mit voller Nahrung alle 10 Ticks aus der Sättigung, ab 18 alle 80 Ticks ein Lebenspunkt
+const SATURATED_HEAL_INTERVAL: u32 = 10;
+const REGEN_FOOD: i32 = 18;
+const HEAL_INTERVAL: u32 = 80;
+const HEAL_EXHAUSTION: f32 = 6.0;
+const STARVE_INTERVAL: u32 = 80;
+// Auf friedlich füllen sich Leben und Nahrung von selbst
+const PEACEFUL_HEAL_INTERVAL: i64 = 20;
+const PEACEFUL_FOOD_INTERVAL: i64 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Eating {
+    slot: usize,
+    item: String,
+    ticks_left: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunger {
+    pub food: i32,
+    pub saturation: f32,
+    exhaustion: f32,
+    timer: u32,
+    eating: Option<Eating>,
+}
+
+impl Default for Hunger {
+    fn default() -> Hunger {
+        Hunger { food: MAX_FOOD, saturation: START_SATURATION, exhaustion: 0.0, timer: 0, eating: None }
+    }
+}
+
+impl Hunger {
+    pub fn exhaust(&mut self, amount: f32) {
+        self.exhaustion = (self.exhaustion + amount).min(MAX_EXHAUSTION);
+    }
+
+    pub fn can_sprint(&self) -> bool {
+        self.food > SPRINT_FOOD
+    }
+
+    // Sättigung kann nie über die Nahrung steigen
+    fn eat(&mut self, food: &item::Food) {
+        self.food = (self.food + food.nutrition as i32).min(MAX_FOOD);
+        self.saturation = (self.saturation + food.saturation).min(self.food as f32);
+    }
+
+    // Loslassen der Maustaste oder ein anderer Platz in der Schnellleiste
+    pub fn stop_eating(&mut self) {
+        self.eating = None;
+    }
+}
+
+// Use Item mit Nahrung in der Hand: gegessen ist nach eat_seconds, wie beim Client. Satt nur im Kreativmodus und
+// bei Nahrung wie goldenen Äpfeln. false, wenn das Item keine Nahrung ist
+pub fn start_eating(player: &mut Player, slot: usize) -> bool {
+    let stack = player.inventory.slot(slot);
+    let Some(food) = item::food(&stack.item) else { return false };
+    if player.hunger.food >= MAX_FOOD && !food.always_edible && player.game_mode != GameMode::Creative {
+        return true;
+    }
+    let ticks_left = (food.eat_seconds * 20.0).round().max(1.0) as u32;
+    player.hunger.eating = Some(Eating { slot, item: stack.item.clone(), ticks_left });
+    true
+}
+
+fn uses_food(player: &Player) -> bool {
+    player.health > 0.0 && !player.game_mode.is_invulnerable()
+}
+
+// Ein Tick für Essen, Erschöpfung, Regeneration und Verhungern. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut starving: Vec<Target> = Vec::new();
+    let mut players = server.players.lock().unwrap();
+    for player in players.iter_mut().filter(|p| p.health > 0.0) {
+        finish_eating(player);
+        if !uses_food(player) {
+            continue;
+        }
+        let (difficulty, regeneration, age) = {
+            let world = dimension::world_of(server, player.dimension).lock().unwrap();
+            (world.difficulty, world.rules.get_bool(gamerule::NATURAL_REGENERATION), world.time.age)
+        };
+        let before = (player.health, player.hunger.food, player.hunger.saturation);
+        if let Some(hunger) = player.effects.get(effect::HUNGER) {
+            let amount = HUNGER_EFFECT_EXHAUSTION * (hunger.amplifier as f32 + 1.0);
+            player.hunger.exhaust(amount);
+        }
+        if difficulty == Difficulty::Peaceful && regeneration {
+            if age % PEACEFUL_HEAL_INTERVAL == 0 {
+                player.health = (player.health + 1.0).min(damage::MAX_HEALTH);
+            }
+            if age % PEACEFUL_FOOD_INTERVAL == 0 && player.hunger.food < MAX_FOOD {
+                player.hunger.food += 1;
+            }
+        }
+        let hurt = player.health < damage::MAX_HEALTH;
+        let hunger = &mut player.hunger;
+        if hunger.exhaustion > EXHAUSTION_PER_POINT {
+            hunger.exhaustion -= EXHAUSTION_PER_POINT;
+            if hunger.saturation > 0.0 {
+                hunger.saturation = (hunger.saturation - 1.0).max(0.0);
+            } else if difficulty != Difficulty::Peaceful {
+                hunger.food = (hunger.food - 1).max(0);
+            }
+        }
+        if regeneration && hurt && hunger.saturation > 0.0 && hunger.food >= MAX_FOOD {
+            hunger.timer += 1;
+            if hunger.timer >= SATURATED_HEAL_INTERVAL {
+                let amount = hunger.saturation.min(HEAL_EXHAUSTION);
+                hunger.exhaust(amount);
+                hunger.timer = 0;
+                player.health = (player.health + amount / HEAL_EXHAUSTION).min(damage::MAX_HEALTH);
+            }
+        } else if regeneration && hurt && hunger.food >= REGEN_FOOD {
+            hunger.timer += 1;
+            if hunger.timer >= HEAL_INTERVAL {
+                hunger.exhaust(HEAL_EXHAUSTION);
+                hunger.timer = 0;
+                player.health = (player.health + 1.0).min(damage::MAX_HEALTH);
+            }
+        } else if hunger.food <= 0 {
+            hunger.timer += 1;
+            if hunger.timer >= STARVE_INTERVAL {
+                hunger.timer = 0;
+                // Leicht lässt zehn Lebenspunkte übrig, normal einen, schwer verhungert man
+                let floor = match difficulty {
+                    Difficulty::Hard => 0.0,
+                    Difficulty::Normal => 1.0,
+                    _ => 10.0,
+                };
+                if player.health > floor {
+                    starving.push(Target::of_player(player));
+                }
+            }
+        } else {
+            hunger.timer = 0;
+        }
+        if (player.health, player.hunger.food, player.hunger.saturation) != before {
+            let _ = damage::send_health(player);
+        }
+    }
+    drop(players);
+    for target in starving {
+        damage::damage(server, &target, DamageSource::Starve, 1.0);
+    }
+}
+
+// Zählt das Essen herunter; wer den Platz gewechselt oder das Item nicht mehr hat, isst nicht weiter
+fn finish_eating(player: &mut Player) {
+    let Some(eating) = &mut player.hunger.eating else { return };
+    if player.inventory.slot(eating.slot).item != eating.item {
+        player.hunger.eating = None;
+        return;
+    }
+    eating.ticks_left -= 1;
+    if eating.ticks_left > 0 {
+        return;
+    }
+    let slot = eating.slot;
+    player.hunger.eating = None;
+    let Some(food) = item::food(&player.inventory.slot(slot).item) else { return };
+    player.hunger.eat(food);
+    if player.game_mode != GameMode::Creative {
+        player.inventory.consume_one(slot);
+        let _ = player.inventory.send_slot(&player.connection, slot);
+    }
+    let _ = damage::send_health(player);
+}
+
+// Sprinten am Boden kostet je Block, ein Sprung einmalig. Ob der Spieler Halt hat, meldet sein Client
+pub fn exhaust_movement(player: &mut Player, to: (f64, f64, f64), on_ground: bool) {
+    let (dx, dy, dz) = (to.0 - player.position.0, to.1 - player.position.1, to.2 - player.position.2);
+    let jumped = player.movement.take_off(on_ground) && dy > 0.0;
+    if !uses_food(player) {
+        return;
+    }
+    let sprinting = player.pose.sprinting;
+    if jumped {
+        player.hunger.exhaust(if sprinting { SPRINT_JUMP_EXHAUSTION } else { JUMP_EXHAUSTION });
+    } else if sprinting && on_ground {
+        player.hunger.exhaust(SPRINT_EXHAUSTION_PER_BLOCK * (dx * dx + dz * dz).sqrt() as f32);
+    }
+}
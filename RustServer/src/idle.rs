@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+use crate::protocol::serverbound;
+use crate::{kick_player, Player, Server};
+
+pub const IDLE_MESSAGE: &str = "You have been idle for too long!";
+
+// Diese Pakete schickt der Client auch ohne Zutun des Spielers. Bewegungen zählen erst, wenn sich Position oder
+// Blickrichtung wirklich ändern, siehe moved
+pub fn is_input(packet_id: i32) -> bool {
+    !matches!(
+        packet_id,
+        serverbound::KEEP_ALIVE
+            | serverbound::CONFIRM_TELEPORTATION
+            | serverbound::CLIENT_INFORMATION
+            | serverbound::CUSTOM_PAYLOAD
+            | serverbound::RESOURCE_PACK_RESPONSE
+            | serverbound::SET_PLAYER_POSITION
+            | serverbound::SET_PLAYER_POSITION_AND_ROTATION
+            | serverbound::SET_PLAYER_ROTATION
+            | serverbound::PLAYER_INPUT
+            | serverbound::MOVE_VEHICLE
+    )
+}
+
+pub fn touch(player: &mut Player) {
+    player.last_input = Instant::now();
+}
+
+pub fn moved(player: &mut Player, position: (f64, f64, f64), rotation: (f32, f32)) {
+    if player.position != position || player.rotation != rotation {
+        touch(player);
+    }
+}
+
+// Trennt Spieler, die länger als player-idle-timeout Minuten nichts getan haben; 0 schaltet das ab. Darf nur ohne
+// gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    if server.config.player_idle_timeout == 0 {
+        return;
+    }
+    let timeout = Duration::from_secs(server.config.player_idle_timeout * 60);
+    let idle: Vec<String> = server.players.lock().unwrap().iter().filter(|p| p.last_input.elapsed() > timeout).map(|p| p.username.clone()).collect();
+    for name in idle {
+        kick_player(server, &name, IDLE_MESSAGE);
+    }
+}
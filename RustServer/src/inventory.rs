@@ -0,0 +1,205 @@
+use crate::item::{self, ItemStack};
+use crate::json::Json;
+use crate::protocol::clientbound;
+use crate::protocol::types::write_varint_to_vec;
+use crate::Connection;
+
+// Fenster 0: Crafting-Ergebnis, 2x2-Crafting, Rüstung, Hauptinventar (9-35), Schnellleiste (36-44), Zweithand
+pub const SLOTS: usize = 46;
+const MAIN: std::ops::Range<usize> = 9..36;
+const HOTBAR: std::ops::Range<usize> = 36..45;
+const OFF_HAND: usize = 45;
+pub const HEAD: usize = 5;
+// Brustplatte oder Elytra
+pub const CHEST: usize = 6;
+const LEGS: usize = 7;
+pub const FEET: usize = 8;
+const PLAYER_WINDOW: u8 = 0;
+// Das Crafting-Ergebnis entsteht aus dem Gitter und gehört nicht zum Inhalt
+const CRAFTING_RESULT: usize = 0;
+
+#[derive(Debug, Clone)]
+pub struct Inventory {
+    slots: Vec<ItemStack>,
+    // Wird bei jeder Änderung erhöht; der Client schickt sie bei Klicks zurück
+    state_id: i32,
+    // Ausgewählter Platz der Schnellleiste (0-8)
+    selected: usize,
+}
+
+impl Inventory {
+    pub fn new() -> Inventory {
+        Inventory { slots: vec![ItemStack::empty(); SLOTS], state_id: 0, selected: 0 }
+    }
+
+    // Set Held Item; false bei einem Platz außerhalb der Schnellleiste
+    pub fn select(&mut self, hotbar_slot: usize) -> bool {
+        if hotbar_slot >= HOTBAR.len() {
+            return false;
+        }
+        self.selected = hotbar_slot;
+        true
+    }
+
+    pub fn main_hand(&self) -> &ItemStack {
+        &self.slots[HOTBAR.start + self.selected]
+    }
+
+    // Slot der Haupt- oder Zweithand
+    pub fn hand_slot(&self, off_hand: bool) -> usize {
+        if off_hand { OFF_HAND } else { HOTBAR.start + self.selected }
+    }
+
+    // Was andere an dem Spieler sehen, in der Reihenfolge der Slots von Set Equipment: Haupthand, Zweithand, Füße,
+    // Beine, Brust, Kopf
+    pub fn equipment(&self) -> [&ItemStack; 6] {
+        [self.hand_slot(false), OFF_HAND, FEET, LEGS, CHEST, HEAD].map(|slot| &self.slots[slot])
+    }
+
+    // Tauscht Haupt- und Zweithand, die Taste F
+    pub fn swap_hands(&mut self) {
+        let hand = self.hand_slot(false);
+        self.slots.swap(hand, OFF_HAND);
+        self.state_id = self.state_id.wrapping_add(1);
+    }
+
+    pub fn slot(&self, slot: usize) -> &ItemStack {
+        &self.slots[slot]
+    }
+
+    // Hauptinventar und Schnellleiste, wie sie unter einem Container-Fenster liegen
+    pub fn storage(&self) -> &[ItemStack] {
+        &self.slots[MAIN.start..HOTBAR.end]
+    }
+
+    pub fn slots(&self) -> &[ItemStack] {
+        &self.slots
+    }
+
+    // Setzt einen Slot nach einem Klick. Die State-ID bleibt, denn der Client hat die Änderung meist selbst
+    // vorhergesagt; der Aufrufer schickt abweichende Slots
+    pub fn set(&mut self, slot: usize, stack: ItemStack) {
+        self.slots[slot] = stack;
+    }
+
+    pub fn state_id(&self) -> i32 {
+        self.state_id
+    }
+
+    // Verbraucht ein Item aus dem Slot, etwa beim Platzieren eines Blocks
+    pub fn consume_one(&mut self, slot: usize) {
+        let stack = &mut self.slots[slot];
+        if stack.is_empty() {
+            return;
+        }
+        stack.count -= 1;
+        if stack.count == 0 {
+            *stack = ItemStack::empty();
+        }
+        self.state_id = self.state_id.wrapping_add(1);
+    }
+
+    // Wie bei Vanilla zuerst vorhandene Stapel auffüllen, dann freie Plätze belegen, jeweils Schnellleiste
+    // vor Hauptinventar. Gibt die geänderten Slots und den Rest zurück, der nicht mehr hineinpasst
+    pub fn add(&mut self, mut stack: ItemStack) -> (Vec<usize>, ItemStack) {
+        let max = item::max_stack_size(&stack.item);
+        let order: Vec<usize> = HOTBAR.chain(MAIN).collect();
+        let mut changed = Vec::new();
+        for slot in order.iter().copied() {
+            if !self.slots[slot].stacks_with(&stack) || self.slots[slot].is_empty() {
+                continue;
+            }
+            let moved = stack.count.min(max.saturating_sub(self.slots[slot].count));
+            if moved > 0 {
+                self.slots[slot].count += moved;
+                stack.count -= moved;
+                changed.push(slot);
+            }
+            if stack.count == 0 {
+                break;
+            }
+        }
+        for slot in order.iter().copied() {
+            if stack.count == 0 {
+                break;
+            }
+            if self.slots[slot].is_empty() {
+                let moved = stack.count.min(max);
+                self.slots[slot] = ItemStack { count: moved, ..stack.clone() };
+                stack.count -= moved;
+                changed.push(slot);
+            }
+        }
+        if !changed.is_empty() {
+            self.state_id = self.state_id.wrapping_add(1);
+        }
+        (changed, stack)
+    }
+
+    // Leert alle Slots und gibt die belegten zurück, etwa um sie beim Tod fallen zu lassen
+    pub fn take_all(&mut self) -> Vec<ItemStack> {
+        let taken: Vec<ItemStack> = self.slots.iter_mut().map(|slot| std::mem::replace(slot, ItemStack::empty())).filter(|stack| !stack.is_empty()).collect();
+        self.state_id = self.state_id.wrapping_add(1);
+        taken
+    }
+
+    // Munition sucht Vanilla erst in Zweit- und Haupthand, dann in Schnellleiste und Hauptinventar
+    pub fn find_ammo(&self, matches: impl Fn(&ItemStack) -> bool) -> Option<usize> {
+        [OFF_HAND, self.hand_slot(false)].into_iter().chain(HOTBAR).chain(MAIN).find(|&slot| !self.slots[slot].is_empty() && matches(&self.slots[slot]))
+    }
+
+    pub fn count_matching(&self, matches: impl Fn(&ItemStack) -> bool) -> u32 {
+        self.slots.iter().skip(CRAFTING_RESULT + 1).filter(|stack| !stack.is_empty() && matches(stack)).map(|stack| stack.count as u32).sum()
+    }
+
+    // Entfernt bis zu max passende Items aus Gitter, Rüstung, Inventar und Zweithand.
+    // Gibt die geänderten Slots und die Zahl der entfernten Items zurück
+    pub fn remove_matching(&mut self, matches: impl Fn(&ItemStack) -> bool, max: Option<u32>) -> (Vec<usize>, u32) {
+        let mut changed = Vec::new();
+        let mut removed = 0;
+        for (slot, stack) in self.slots.iter_mut().enumerate().skip(CRAFTING_RESULT + 1) {
+            if stack.is_empty() || !matches(stack) {
+                continue;
+            }
+            let take = max.map_or(stack.count as u32, |max| (max - removed).min(stack.count as u32));
+            if take == 0 {
+                break;
+            }
+            stack.count -= take as u8;
+            if stack.count == 0 {
+                *stack = ItemStack::empty();
+            }
+            removed += take;
+            changed.push(slot);
+        }
+        if !changed.is_empty() {
+            self.state_id = self.state_id.wrapping_add(1);
+        }
+        (changed, removed)
+    }
+
+    // Belegte Slots für die Spielerdaten
+    pub fn to_json(&self) -> Json {
+        item::slots_to_json(&self.slots)
+    }
+
+    // Set Container Content mit allen Slots, etwa beim Beitritt
+    pub fn send_all(&self, connection: &Connection) -> Result<(), String> {
+        let mut packet_data = vec![PLAYER_WINDOW];
+        packet_data.extend(write_varint_to_vec(self.state_id));
+        packet_data.extend(write_varint_to_vec(self.slots.len() as i32));
+        for stack in &self.slots {
+            packet_data.extend(stack.encode(connection.version));
+        }
+        packet_data.extend(ItemStack::empty().encode(connection.version)); // Gehaltenes Item
+        connection.send(clientbound::SET_CONTAINER_CONTENT, &packet_data)
+    }
+
+    pub fn send_slot(&self, connection: &Connection, slot: usize) -> Result<(), String> {
+        let mut packet_data = vec![PLAYER_WINDOW];
+        packet_data.extend(write_varint_to_vec(self.state_id));
+        packet_data.extend((slot as i16).to_be_bytes());
+        packet_data.extend(self.slots[slot].encode(connection.version));
+        connection.send(clientbound::SET_CONTAINER_SLOT, &packet_data)
+    }
+}
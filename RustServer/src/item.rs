@@ -0,0 +1,1306 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use rand::Rng;
+use crate::json::{self, Json};
+use crate::{block, nbt, potion};
+use crate::protocol::assets;
+use crate::protocol::version::ProtocolVersion;
+use crate::protocol::types::{write_string_to_vec, write_varint_to_vec};
+
+// Das Item-Register (1.21.1) in der Reihenfolge der Protokoll-IDs, falls keine Berichte vorliegen. Die IDs bis
+// gravel sind in 1.20.4 dieselben
+const ITEMS: &[&str] = &[
+    "air", "stone", "granite", "polished_granite", "diorite", "polished_diorite", "andesite", "polished_andesite", "deepslate",
+    "cobbled_deepslate", "polished_deepslate", "calcite", "tuff", "tuff_slab", "tuff_stairs", "tuff_wall", "chiseled_tuff", "polished_tuff",
+    "polished_tuff_slab", "polished_tuff_stairs", "polished_tuff_wall", "tuff_bricks", "tuff_brick_slab", "tuff_brick_stairs",
+    "tuff_brick_wall", "chiseled_tuff_bricks", "dripstone_block", "grass_block", "dirt", "coarse_dirt", "podzol", "rooted_dirt", "mud",
+    "crimson_nylium", "warped_nylium", "cobblestone", "oak_planks", "spruce_planks", "birch_planks", "jungle_planks", "acacia_planks",
+    "cherry_planks", "dark_oak_planks", "mangrove_planks", "bamboo_planks", "crimson_planks", "warped_planks", "bamboo_mosaic",
+    "oak_sapling", "spruce_sapling", "birch_sapling", "jungle_sapling", "acacia_sapling", "cherry_sapling", "dark_oak_sapling",
+    "mangrove_propagule", "bedrock", "sand", "suspicious_sand", "suspicious_gravel", "red_sand", "gravel", "coal_ore", "deepslate_coal_ore",
+    "iron_ore", "deepslate_iron_ore", "copper_ore", "deepslate_copper_ore", "gold_ore", "deepslate_gold_ore", "redstone_ore",
+    "deepslate_redstone_ore", "emerald_ore", "deepslate_emerald_ore", "lapis_ore", "deepslate_lapis_ore", "diamond_ore",
+    "deepslate_diamond_ore", "nether_gold_ore", "nether_quartz_ore", "ancient_debris", "coal_block", "raw_iron_block", "raw_copper_block",
+    "raw_gold_block", "heavy_core", "amethyst_block", "budding_amethyst", "iron_block", "copper_block", "gold_block", "diamond_block",
+    "netherite_block", "exposed_copper", "weathered_copper", "oxidized_copper", "chiseled_copper", "exposed_chiseled_copper",
+    "weathered_chiseled_copper", "oxidized_chiseled_copper", "cut_copper", "exposed_cut_copper", "weathered_cut_copper",
+    "oxidized_cut_copper", "cut_copper_stairs", "exposed_cut_copper_stairs", "weathered_cut_copper_stairs", "oxidized_cut_copper_stairs",
+    "cut_copper_slab", "exposed_cut_copper_slab", "weathered_cut_copper_slab", "oxidized_cut_copper_slab", "waxed_copper_block",
+    "waxed_exposed_copper", "waxed_weathered_copper", "waxed_oxidized_copper", "waxed_chiseled_copper", "waxed_exposed_chiseled_copper",
+    "waxed_weathered_chiseled_copper", "waxed_oxidized_chiseled_copper", "waxed_cut_copper", "waxed_exposed_cut_copper",
+    "waxed_weathered_cut_copper", "waxed_oxidized_cut_copper", "waxed_cut_copper_stairs", "waxed_exposed_cut_copper_stairs",
+    "waxed_weathered_cut_copper_stairs", "waxed_oxidized_cut_copper_stairs", "waxed_cut_copper_slab", "waxed_exposed_cut_copper_slab",
+    "waxed_weathered_cut_copper_slab", "waxed_oxidized_cut_copper_slab", "oak_log", "spruce_log", "birch_log", "jungle_log", "acacia_log",
+    "cherry_log", "dark_oak_log", "mangrove_log", "mangrove_roots", "muddy_mangrove_roots", "crimson_stem", "warped_stem", "bamboo_block",
+    "stripped_oak_log", "stripped_spruce_log", "stripped_birch_log", "stripped_jungle_log", "stripped_acacia_log", "stripped_cherry_log",
+    "stripped_dark_oak_log", "stripped_mangrove_log", "stripped_crimson_stem", "stripped_warped_stem", "stripped_oak_wood",
+    "stripped_spruce_wood", "stripped_birch_wood", "stripped_jungle_wood", "stripped_acacia_wood", "stripped_cherry_wood",
+    "stripped_dark_oak_wood", "stripped_mangrove_wood", "stripped_crimson_hyphae", "stripped_warped_hyphae", "stripped_bamboo_block",
+    "oak_wood", "spruce_wood", "birch_wood", "jungle_wood", "acacia_wood", "cherry_wood", "dark_oak_wood", "mangrove_wood",
+    "crimson_hyphae", "warped_hyphae", "oak_leaves", "spruce_leaves", "birch_leaves", "jungle_leaves", "acacia_leaves", "cherry_leaves",
+    "dark_oak_leaves", "mangrove_leaves", "azalea_leaves", "flowering_azalea_leaves", "sponge", "wet_sponge", "glass", "tinted_glass",
+    "lapis_block", "sandstone", "chiseled_sandstone", "cut_sandstone", "cobweb", "short_grass", "fern", "azalea", "flowering_azalea",
+    "dead_bush", "seagrass", "sea_pickle", "white_wool", "orange_wool", "magenta_wool", "light_blue_wool", "yellow_wool", "lime_wool",
+    "pink_wool", "gray_wool", "light_gray_wool", "cyan_wool", "purple_wool", "blue_wool", "brown_wool", "green_wool", "red_wool",
+    "black_wool", "dandelion", "poppy", "blue_orchid", "allium", "azure_bluet", "red_tulip", "orange_tulip", "white_tulip", "pink_tulip",
+    "oxeye_daisy", "cornflower", "lily_of_the_valley", "wither_rose", "torchflower", "pitcher_plant", "spore_blossom", "brown_mushroom",
+    "red_mushroom", "crimson_fungus", "warped_fungus", "crimson_roots", "warped_roots", "nether_sprouts", "weeping_vines", "twisting_vines",
+    "sugar_cane", "kelp", "moss_carpet", "pink_petals", "moss_block", "hanging_roots", "big_dripleaf", "small_dripleaf", "bamboo",
+    "oak_slab", "spruce_slab", "birch_slab", "jungle_slab", "acacia_slab", "cherry_slab", "dark_oak_slab", "mangrove_slab", "bamboo_slab",
+    "bamboo_mosaic_slab", "crimson_slab", "warped_slab", "stone_slab", "smooth_stone_slab", "sandstone_slab", "cut_sandstone_slab",
+    "petrified_oak_slab", "cobblestone_slab", "brick_slab", "stone_brick_slab", "mud_brick_slab", "nether_brick_slab", "quartz_slab",
+    "red_sandstone_slab", "cut_red_sandstone_slab", "purpur_slab", "prismarine_slab", "prismarine_brick_slab", "dark_prismarine_slab",
+    "smooth_quartz", "smooth_red_sandstone", "smooth_sandstone", "smooth_stone", "bricks", "bookshelf", "chiseled_bookshelf",
+    "decorated_pot", "mossy_cobblestone", "obsidian", "torch", "end_rod", "chorus_plant", "chorus_flower", "purpur_block", "purpur_pillar",
+    "purpur_stairs", "spawner", "chest", "crafting_table", "farmland", "furnace", "ladder", "cobblestone_stairs", "snow", "ice",
+    "snow_block", "cactus", "clay", "jukebox", "oak_fence", "spruce_fence", "birch_fence", "jungle_fence", "acacia_fence", "cherry_fence",
+    "dark_oak_fence", "mangrove_fence", "bamboo_fence", "crimson_fence", "warped_fence", "pumpkin", "carved_pumpkin", "jack_o_lantern",
+    "netherrack", "soul_sand", "soul_soil", "basalt", "polished_basalt", "smooth_basalt", "soul_torch", "glowstone", "infested_stone",
+    "infested_cobblestone", "infested_stone_bricks", "infested_mossy_stone_bricks", "infested_cracked_stone_bricks",
+    "infested_chiseled_stone_bricks", "infested_deepslate", "stone_bricks", "mossy_stone_bricks", "cracked_stone_bricks",
+    "chiseled_stone_bricks", "packed_mud", "mud_bricks", "deepslate_bricks", "cracked_deepslate_bricks", "deepslate_tiles",
+    "cracked_deepslate_tiles", "chiseled_deepslate", "reinforced_deepslate", "brown_mushroom_block", "red_mushroom_block", "mushroom_stem",
+    "iron_bars", "chain", "glass_pane", "melon", "vine", "glow_lichen", "brick_stairs", "stone_brick_stairs", "mud_brick_stairs",
+    "mycelium", "lily_pad", "nether_bricks", "cracked_nether_bricks", "chiseled_nether_bricks", "nether_brick_fence", "nether_brick_stairs",
+    "sculk", "sculk_vein", "sculk_catalyst", "sculk_shrieker", "enchanting_table", "end_portal_frame", "end_stone", "end_stone_bricks",
+    "dragon_egg", "sandstone_stairs", "ender_chest", "emerald_block", "oak_stairs", "spruce_stairs", "birch_stairs", "jungle_stairs",
+    "acacia_stairs", "cherry_stairs", "dark_oak_stairs", "mangrove_stairs", "bamboo_stairs", "bamboo_mosaic_stairs", "crimson_stairs",
+    "warped_stairs", "command_block", "beacon", "cobblestone_wall", "mossy_cobblestone_wall", "brick_wall", "prismarine_wall",
+    "red_sandstone_wall", "mossy_stone_brick_wall", "granite_wall", "stone_brick_wall", "mud_brick_wall", "nether_brick_wall",
+    "andesite_wall", "red_nether_brick_wall", "sandstone_wall", "end_stone_brick_wall", "diorite_wall", "blackstone_wall",
+    "polished_blackstone_wall", "polished_blackstone_brick_wall", "cobbled_deepslate_wall", "polished_deepslate_wall",
+    "deepslate_brick_wall", "deepslate_tile_wall", "anvil", "chipped_anvil", "damaged_anvil", "chiseled_quartz_block", "quartz_block",
+    "quartz_bricks", "quartz_pillar", "quartz_stairs", "white_terracotta", "orange_terracotta", "magenta_terracotta",
+    "light_blue_terracotta", "yellow_terracotta", "lime_terracotta", "pink_terracotta", "gray_terracotta", "light_gray_terracotta",
+    "cyan_terracotta", "purple_terracotta", "blue_terracotta", "brown_terracotta", "green_terracotta", "red_terracotta", "black_terracotta",
+    "barrier", "light", "hay_block", "white_carpet", "orange_carpet", "magenta_carpet", "light_blue_carpet", "yellow_carpet", "lime_carpet",
+    "pink_carpet", "gray_carpet", "light_gray_carpet", "cyan_carpet", "purple_carpet", "blue_carpet", "brown_carpet", "green_carpet",
+    "red_carpet", "black_carpet", "terracotta", "packed_ice", "dirt_path", "sunflower", "lilac", "rose_bush", "peony", "tall_grass",
+    "large_fern", "white_stained_glass", "orange_stained_glass", "magenta_stained_glass", "light_blue_stained_glass",
+    "yellow_stained_glass", "lime_stained_glass", "pink_stained_glass", "gray_stained_glass", "light_gray_stained_glass",
+    "cyan_stained_glass", "purple_stained_glass", "blue_stained_glass", "brown_stained_glass", "green_stained_glass", "red_stained_glass",
+    "black_stained_glass", "white_stained_glass_pane", "orange_stained_glass_pane", "magenta_stained_glass_pane",
+    "light_blue_stained_glass_pane", "yellow_stained_glass_pane", "lime_stained_glass_pane", "pink_stained_glass_pane",
+    "gray_stained_glass_pane", "light_gray_stained_glass_pane", "cyan_stained_glass_pane", "purple_stained_glass_pane",
+    "blue_stained_glass_pane", "brown_stained_glass_pane", "green_stained_glass_pane", "red_stained_glass_pane", "black_stained_glass_pane",
+    "prismarine", "prismarine_bricks", "dark_prismarine", "prismarine_stairs", "prismarine_brick_stairs", "dark_prismarine_stairs",
+    "sea_lantern", "red_sandstone", "chiseled_red_sandstone", "cut_red_sandstone", "red_sandstone_stairs", "repeating_command_block",
+    "chain_command_block", "magma_block", "nether_wart_block", "warped_wart_block", "red_nether_bricks", "bone_block", "structure_void",
+    "shulker_box", "white_shulker_box", "orange_shulker_box", "magenta_shulker_box", "light_blue_shulker_box", "yellow_shulker_box",
+    "lime_shulker_box", "pink_shulker_box", "gray_shulker_box", "light_gray_shulker_box", "cyan_shulker_box", "purple_shulker_box",
+    "blue_shulker_box", "brown_shulker_box", "green_shulker_box", "red_shulker_box", "black_shulker_box", "white_glazed_terracotta",
+    "orange_glazed_terracotta", "magenta_glazed_terracotta", "light_blue_glazed_terracotta", "yellow_glazed_terracotta",
+    "lime_glazed_terracotta", "pink_glazed_terracotta", "gray_glazed_terracotta", "light_gray_glazed_terracotta", "cyan_glazed_terracotta",
+    "purple_glazed_terracotta", "blue_glazed_terracotta", "brown_glazed_terracotta", "green_glazed_terracotta", "red_glazed_terracotta",
+    "black_glazed_terracotta", "white_concrete", "orange_concrete", "magenta_concrete", "light_blue_concrete", "yellow_concrete",
+    "lime_concrete", "pink_concrete", "gray_concrete", "light_gray_concrete", "cyan_concrete", "purple_concrete", "blue_concrete",
+    "brown_concrete", "green_concrete", "red_concrete", "black_concrete", "white_concrete_powder", "orange_concrete_powder",
+    "magenta_concrete_powder", "light_blue_concrete_powder", "yellow_concrete_powder", "lime_concrete_powder", "pink_concrete_powder",
+    "gray_concrete_powder", "light_gray_concrete_powder", "cyan_concrete_powder", "purple_concrete_powder", "blue_concrete_powder",
+    "brown_concrete_powder", "green_concrete_powder", "red_concrete_powder", "black_concrete_powder", "turtle_egg", "sniffer_egg",
+    "dead_tube_coral_block", "dead_brain_coral_block", "dead_bubble_coral_block", "dead_fire_coral_block", "dead_horn_coral_block",
+    "tube_coral_block", "brain_coral_block", "bubble_coral_block", "fire_coral_block", "horn_coral_block", "tube_coral", "brain_coral",
+    "bubble_coral", "fire_coral", "horn_coral", "dead_brain_coral", "dead_bubble_coral", "dead_fire_coral", "dead_horn_coral",
+    "dead_tube_coral", "tube_coral_fan", "brain_coral_fan", "bubble_coral_fan", "fire_coral_fan", "horn_coral_fan", "dead_tube_coral_fan",
+    "dead_brain_coral_fan", "dead_bubble_coral_fan", "dead_fire_coral_fan", "dead_horn_coral_fan", "blue_ice", "conduit",
+    "polished_granite_stairs", "smooth_red_sandstone_stairs", "mossy_stone_brick_stairs", "polished_diorite_stairs",
+    "mossy_cobblestone_stairs", "end_stone_brick_stairs", "stone_stairs", "smooth_sandstone_stairs", "smooth_quartz_stairs",
+    "granite_stairs", "andesite_stairs", "red_nether_brick_stairs", "polished_andesite_stairs", "diorite_stairs",
+    "cobbled_deepslate_stairs", "polished_deepslate_stairs", "deepslate_brick_stairs", "deepslate_tile_stairs", "polished_granite_slab",
+    "smooth_red_sandstone_slab", "mossy_stone_brick_slab", "polished_diorite_slab", "mossy_cobblestone_slab", "end_stone_brick_slab",
+    "smooth_sandstone_slab", "smooth_quartz_slab", "granite_slab", "andesite_slab", "red_nether_brick_slab", "polished_andesite_slab",
+    "diorite_slab", "cobbled_deepslate_slab", "polished_deepslate_slab", "deepslate_brick_slab", "deepslate_tile_slab", "scaffolding",
+    "redstone", "redstone_torch", "redstone_block", "repeater", "comparator", "piston", "sticky_piston", "slime_block", "honey_block",
+    "observer", "hopper", "dispenser", "dropper", "lectern", "target", "lever", "lightning_rod", "daylight_detector", "sculk_sensor",
+    "calibrated_sculk_sensor", "tripwire_hook", "trapped_chest", "tnt", "redstone_lamp", "note_block", "stone_button",
+    "polished_blackstone_button", "oak_button", "spruce_button", "birch_button", "jungle_button", "acacia_button", "cherry_button",
+    "dark_oak_button", "mangrove_button", "bamboo_button", "crimson_button", "warped_button", "stone_pressure_plate",
+    "polished_blackstone_pressure_plate", "light_weighted_pressure_plate", "heavy_weighted_pressure_plate", "oak_pressure_plate",
+    "spruce_pressure_plate", "birch_pressure_plate", "jungle_pressure_plate", "acacia_pressure_plate", "cherry_pressure_plate",
+    "dark_oak_pressure_plate", "mangrove_pressure_plate", "bamboo_pressure_plate", "crimson_pressure_plate", "warped_pressure_plate",
+    "iron_door", "oak_door", "spruce_door", "birch_door", "jungle_door", "acacia_door", "cherry_door", "dark_oak_door", "mangrove_door",
+    "bamboo_door", "crimson_door", "warped_door", "copper_door", "exposed_copper_door", "weathered_copper_door", "oxidized_copper_door",
+    "waxed_copper_door", "waxed_exposed_copper_door", "waxed_weathered_copper_door", "waxed_oxidized_copper_door", "iron_trapdoor",
+    "oak_trapdoor", "spruce_trapdoor", "birch_trapdoor", "jungle_trapdoor", "acacia_trapdoor", "cherry_trapdoor", "dark_oak_trapdoor",
+    "mangrove_trapdoor", "bamboo_trapdoor", "crimson_trapdoor", "warped_trapdoor", "copper_trapdoor", "exposed_copper_trapdoor",
+    "weathered_copper_trapdoor", "oxidized_copper_trapdoor", "waxed_copper_trapdoor", "waxed_exposed_copper_trapdoor",
+    "waxed_weathered_copper_trapdoor", "waxed_oxidized_copper_trapdoor", "oak_fence_gate", "spruce_fence_gate", "birch_fence_gate",
+    "jungle_fence_gate", "acacia_fence_gate", "cherry_fence_gate", "dark_oak_fence_gate", "mangrove_fence_gate", "bamboo_fence_gate",
+    "crimson_fence_gate", "warped_fence_gate", "powered_rail", "detector_rail", "rail", "activator_rail", "saddle", "minecart",
+    "chest_minecart", "furnace_minecart", "tnt_minecart", "hopper_minecart", "carrot_on_a_stick", "warped_fungus_on_a_stick",
+    "phantom_membrane", "elytra", "oak_boat", "oak_chest_boat", "spruce_boat", "spruce_chest_boat", "birch_boat", "birch_chest_boat",
+    "jungle_boat", "jungle_chest_boat", "acacia_boat", "acacia_chest_boat", "cherry_boat", "cherry_chest_boat", "dark_oak_boat",
+    "dark_oak_chest_boat", "mangrove_boat", "mangrove_chest_boat", "bamboo_raft", "bamboo_chest_raft", "structure_block", "jigsaw",
+    "turtle_helmet", "turtle_scute", "armadillo_scute", "wolf_armor", "flint_and_steel", "bowl", "apple", "bow", "arrow", "coal",
+    "charcoal", "diamond", "emerald", "lapis_lazuli", "quartz", "amethyst_shard", "raw_iron", "iron_ingot", "raw_copper", "copper_ingot",
+    "raw_gold", "gold_ingot", "netherite_ingot", "netherite_scrap", "wooden_sword", "wooden_shovel", "wooden_pickaxe", "wooden_axe",
+    "wooden_hoe", "stone_sword", "stone_shovel", "stone_pickaxe", "stone_axe", "stone_hoe", "golden_sword", "golden_shovel",
+    "golden_pickaxe", "golden_axe", "golden_hoe", "iron_sword", "iron_shovel", "iron_pickaxe", "iron_axe", "iron_hoe", "diamond_sword",
+    "diamond_shovel", "diamond_pickaxe", "diamond_axe", "diamond_hoe", "netherite_sword", "netherite_shovel", "netherite_pickaxe",
+    "netherite_axe", "netherite_hoe", "stick", "mushroom_stew", "string", "feather", "gunpowder", "wheat_seeds", "wheat", "bread",
+    "leather_helmet", "leather_chestplate", "leather_leggings", "leather_boots", "chainmail_helmet", "chainmail_chestplate",
+    "chainmail_leggings", "chainmail_boots", "iron_helmet", "iron_chestplate", "iron_leggings", "iron_boots", "diamond_helmet",
+    "diamond_chestplate", "diamond_leggings", "diamond_boots", "golden_helmet", "golden_chestplate", "golden_leggings", "golden_boots",
+    "netherite_helmet", "netherite_chestplate", "netherite_leggings", "netherite_boots", "flint", "porkchop", "cooked_porkchop", "painting",
+    "golden_apple", "enchanted_golden_apple", "oak_sign", "spruce_sign", "birch_sign", "jungle_sign", "acacia_sign", "cherry_sign",
+    "dark_oak_sign", "mangrove_sign", "bamboo_sign", "crimson_sign", "warped_sign", "oak_hanging_sign", "spruce_hanging_sign",
+    "birch_hanging_sign", "jungle_hanging_sign", "acacia_hanging_sign", "cherry_hanging_sign", "dark_oak_hanging_sign",
+    "mangrove_hanging_sign", "bamboo_hanging_sign", "crimson_hanging_sign", "warped_hanging_sign", "bucket", "water_bucket", "lava_bucket",
+    "powder_snow_bucket", "snowball", "leather", "milk_bucket", "pufferfish_bucket", "salmon_bucket", "cod_bucket", "tropical_fish_bucket",
+    "axolotl_bucket", "tadpole_bucket", "brick", "clay_ball", "dried_kelp_block", "paper", "book", "slime_ball", "egg", "compass",
+    "recovery_compass", "bundle", "fishing_rod", "clock", "spyglass", "glowstone_dust", "cod", "salmon", "tropical_fish", "pufferfish",
+    "cooked_cod", "cooked_salmon", "ink_sac", "glow_ink_sac", "cocoa_beans", "white_dye", "orange_dye", "magenta_dye", "light_blue_dye",
+    "yellow_dye", "lime_dye", "pink_dye", "gray_dye", "light_gray_dye", "cyan_dye", "purple_dye", "blue_dye", "brown_dye", "green_dye",
+    "red_dye", "black_dye", "bone_meal", "bone", "sugar", "cake", "white_bed", "orange_bed", "magenta_bed", "light_blue_bed", "yellow_bed",
+    "lime_bed", "pink_bed", "gray_bed", "light_gray_bed", "cyan_bed", "purple_bed", "blue_bed", "brown_bed", "green_bed", "red_bed",
+    "black_bed", "cookie", "crafter", "filled_map", "shears", "melon_slice", "dried_kelp", "pumpkin_seeds", "melon_seeds", "beef",
+    "cooked_beef", "chicken", "cooked_chicken", "rotten_flesh", "ender_pearl", "blaze_rod", "ghast_tear", "gold_nugget", "nether_wart",
+    "potion", "glass_bottle", "spider_eye", "fermented_spider_eye", "blaze_powder", "magma_cream", "brewing_stand", "cauldron", "ender_eye",
+    "glistering_melon_slice", "armadillo_spawn_egg", "allay_spawn_egg", "axolotl_spawn_egg", "bat_spawn_egg", "bee_spawn_egg",
+    "blaze_spawn_egg", "bogged_spawn_egg", "breeze_spawn_egg", "cat_spawn_egg", "camel_spawn_egg", "cave_spider_spawn_egg",
+    "chicken_spawn_egg", "cod_spawn_egg", "cow_spawn_egg", "creeper_spawn_egg", "dolphin_spawn_egg", "donkey_spawn_egg",
+    "drowned_spawn_egg", "elder_guardian_spawn_egg", "ender_dragon_spawn_egg", "enderman_spawn_egg", "endermite_spawn_egg",
+    "evoker_spawn_egg", "fox_spawn_egg", "frog_spawn_egg", "ghast_spawn_egg", "glow_squid_spawn_egg", "goat_spawn_egg",
+    "guardian_spawn_egg", "hoglin_spawn_egg", "horse_spawn_egg", "husk_spawn_egg", "iron_golem_spawn_egg", "llama_spawn_egg",
+    "magma_cube_spawn_egg", "mooshroom_spawn_egg", "mule_spawn_egg", "ocelot_spawn_egg", "panda_spawn_egg", "parrot_spawn_egg",
+    "phantom_spawn_egg", "pig_spawn_egg", "piglin_spawn_egg", "piglin_brute_spawn_egg", "pillager_spawn_egg", "polar_bear_spawn_egg",
+    "pufferfish_spawn_egg", "rabbit_spawn_egg", "ravager_spawn_egg", "salmon_spawn_egg", "sheep_spawn_egg", "shulker_spawn_egg",
+    "silverfish_spawn_egg", "skeleton_spawn_egg", "skeleton_horse_spawn_egg", "slime_spawn_egg", "sniffer_spawn_egg",
+    "snow_golem_spawn_egg", "spider_spawn_egg", "squid_spawn_egg", "stray_spawn_egg", "strider_spawn_egg", "tadpole_spawn_egg",
+    "trader_llama_spawn_egg", "tropical_fish_spawn_egg", "turtle_spawn_egg", "vex_spawn_egg", "villager_spawn_egg", "vindicator_spawn_egg",
+    "wandering_trader_spawn_egg", "warden_spawn_egg", "witch_spawn_egg", "wither_spawn_egg", "wither_skeleton_spawn_egg", "wolf_spawn_egg",
+    "zoglin_spawn_egg", "zombie_spawn_egg", "zombie_horse_spawn_egg", "zombie_villager_spawn_egg", "zombified_piglin_spawn_egg",
+    "experience_bottle", "fire_charge", "wind_charge", "writable_book", "written_book", "mace", "item_frame", "glow_item_frame",
+    "flower_pot", "carrot", "potato", "baked_potato", "poisonous_potato", "map", "golden_carrot", "skeleton_skull", "wither_skeleton_skull",
+    "player_head", "zombie_head", "creeper_head", "dragon_head", "piglin_head", "nether_star", "pumpkin_pie", "firework_rocket",
+    "firework_star", "enchanted_book", "nether_brick", "prismarine_shard", "prismarine_crystals", "rabbit", "cooked_rabbit", "rabbit_stew",
+    "rabbit_foot", "rabbit_hide", "armor_stand", "iron_horse_armor", "golden_horse_armor", "diamond_horse_armor", "leather_horse_armor",
+    "lead", "name_tag", "command_block_minecart", "mutton", "cooked_mutton", "white_banner", "orange_banner", "magenta_banner",
+    "light_blue_banner", "yellow_banner", "lime_banner", "pink_banner", "gray_banner", "light_gray_banner", "cyan_banner", "purple_banner",
+    "blue_banner", "brown_banner", "green_banner", "red_banner", "black_banner", "end_crystal", "chorus_fruit", "popped_chorus_fruit",
+    "torchflower_seeds", "pitcher_pod", "beetroot", "beetroot_seeds", "beetroot_soup", "dragon_breath", "splash_potion", "spectral_arrow",
+    "tipped_arrow", "lingering_potion", "shield", "totem_of_undying", "shulker_shell", "iron_nugget", "knowledge_book", "debug_stick",
+    "music_disc_13", "music_disc_cat", "music_disc_blocks", "music_disc_chirp", "music_disc_creator", "music_disc_creator_music_box",
+    "music_disc_far", "music_disc_mall", "music_disc_mellohi", "music_disc_stal", "music_disc_strad", "music_disc_ward", "music_disc_11",
+    "music_disc_wait", "music_disc_otherside", "music_disc_relic", "music_disc_5", "music_disc_pigstep", "music_disc_precipice",
+    "disc_fragment_5", "trident", "nautilus_shell", "heart_of_the_sea", "crossbow", "suspicious_stew", "loom", "flower_banner_pattern",
+    "creeper_banner_pattern", "skull_banner_pattern", "mojang_banner_pattern", "globe_banner_pattern", "piglin_banner_pattern",
+    "flow_banner_pattern", "guster_banner_pattern", "goat_horn", "composter", "barrel", "smoker", "blast_furnace", "cartography_table",
+    "fletching_table", "grindstone", "smithing_table", "stonecutter", "bell", "lantern", "soul_lantern", "sweet_berries", "glow_berries",
+    "campfire", "soul_campfire", "shroomlight", "honeycomb", "bee_nest", "beehive", "honey_bottle", "honeycomb_block", "lodestone",
+    "crying_obsidian", "blackstone", "blackstone_slab", "blackstone_stairs", "gilded_blackstone", "polished_blackstone",
+    "polished_blackstone_slab", "polished_blackstone_stairs", "chiseled_polished_blackstone", "polished_blackstone_bricks",
+    "polished_blackstone_brick_slab", "polished_blackstone_brick_stairs", "cracked_polished_blackstone_bricks", "respawn_anchor", "candle",
+    "white_candle", "orange_candle", "magenta_candle", "light_blue_candle", "yellow_candle", "lime_candle", "pink_candle", "gray_candle",
+    "light_gray_candle", "cyan_candle", "purple_candle", "blue_candle", "brown_candle", "green_candle", "red_candle", "black_candle",
+    "small_amethyst_bud", "medium_amethyst_bud", "large_amethyst_bud", "amethyst_cluster", "pointed_dripstone", "ochre_froglight",
+    "verdant_froglight", "pearlescent_froglight", "frogspawn", "echo_shard", "brush", "netherite_upgrade_smithing_template",
+    "sentry_armor_trim_smithing_template", "dune_armor_trim_smithing_template", "coast_armor_trim_smithing_template",
+    "wild_armor_trim_smithing_template", "ward_armor_trim_smithing_template", "eye_armor_trim_smithing_template",
+    "vex_armor_trim_smithing_template", "tide_armor_trim_smithing_template", "snout_armor_trim_smithing_template",
+    "rib_armor_trim_smithing_template", "spire_armor_trim_smithing_template", "wayfinder_armor_trim_smithing_template",
+    "shaper_armor_trim_smithing_template", "silence_armor_trim_smithing_template", "raiser_armor_trim_smithing_template",
+    "host_armor_trim_smithing_template", "flow_armor_trim_smithing_template", "bolt_armor_trim_smithing_template", "angler_pottery_sherd",
+    "archer_pottery_sherd", "arms_up_pottery_sherd", "blade_pottery_sherd", "brewer_pottery_sherd", "burn_pottery_sherd",
+    "danger_pottery_sherd", "explorer_pottery_sherd", "flow_pottery_sherd", "friend_pottery_sherd", "guster_pottery_sherd",
+    "heart_pottery_sherd", "heartbreak_pottery_sherd", "howl_pottery_sherd", "miner_pottery_sherd", "mourner_pottery_sherd",
+    "plenty_pottery_sherd", "prize_pottery_sherd", "scrape_pottery_sherd", "sheaf_pottery_sherd", "shelter_pottery_sherd",
+    "skull_pottery_sherd", "snort_pottery_sherd", "copper_grate", "exposed_copper_grate", "weathered_copper_grate", "oxidized_copper_grate",
+    "waxed_copper_grate", "waxed_exposed_copper_grate", "waxed_weathered_copper_grate", "waxed_oxidized_copper_grate", "copper_bulb",
+    "exposed_copper_bulb", "weathered_copper_bulb", "oxidized_copper_bulb", "waxed_copper_bulb", "waxed_exposed_copper_bulb",
+    "waxed_weathered_copper_bulb", "waxed_oxidized_copper_bulb", "trial_spawner", "trial_key", "ominous_trial_key", "vault",
+    "ominous_bottle", "breeze_rod",
+];
+
+// Nahrung ohne Berichte: Name, Hunger, Sättigung und ob es auch ohne Hunger geht
+const FOODS: &[(&str, u32, f32, bool)] = &[
+    ("apple", 4, 2.4, false), ("baked_potato", 5, 6.0, false), ("beef", 3, 1.8, false), ("beetroot", 1, 1.2, false),
+    ("beetroot_soup", 6, 7.2, false), ("bread", 5, 6.0, false), ("carrot", 3, 3.6, false), ("chicken", 2, 1.2, false),
+    ("chorus_fruit", 4, 2.4, true), ("cod", 2, 0.4, false), ("cooked_beef", 8, 12.8, false), ("cooked_chicken", 6, 7.2, false),
+    ("cooked_cod", 5, 6.0, false), ("cooked_mutton", 6, 9.6, false), ("cooked_porkchop", 8, 12.8, false), ("cooked_rabbit", 5, 6.0, false),
+    ("cooked_salmon", 6, 9.6, false), ("cookie", 2, 0.4, false), ("dried_kelp", 1, 0.6, false), ("enchanted_golden_apple", 4, 9.6, true),
+    ("glow_berries", 2, 0.4, false), ("golden_apple", 4, 9.6, true), ("golden_carrot", 6, 14.4, false), ("honey_bottle", 6, 1.2, false),
+    ("melon_slice", 2, 1.2, false), ("mushroom_stew", 6, 7.2, false), ("mutton", 2, 1.2, false), ("poisonous_potato", 2, 1.2, false),
+    ("porkchop", 3, 1.8, false), ("potato", 1, 0.6, false), ("pufferfish", 1, 0.2, false), ("pumpkin_pie", 8, 4.8, false),
+    ("rabbit", 3, 1.8, false), ("rabbit_stew", 10, 12.0, false), ("rotten_flesh", 4, 0.8, false), ("salmon", 2, 0.4, false),
+    ("spider_eye", 2, 3.2, false), ("suspicious_stew", 6, 7.2, true), ("sweet_berries", 2, 0.4, false), ("tropical_fish", 1, 0.2, false),
+];
+
+pub fn namespaced(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    if name.contains(':') { name } else { format!("minecraft:{}", name) }
+}
+
+// Berichte des Vanilla-Datengenerators (java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar --reports)
+pub const REPORTS_DIR: &str = "generated/reports";
+const REGISTRIES_REPORT: &str = "registries.json";
+const ITEMS_REPORT: &str = "items.json";
+const DEFAULT_STACK_SIZE: u8 = 64;
+
+static REGISTRY: OnceLock<ItemRegistry> = OnceLock::new();
+
+// Werkzeugstufe, nach der sich richtet, welche Blöcke ein Werkzeug abbauen kann
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ToolTier {
+    Wood,
+    Gold,
+    Stone,
+    Iron,
+    Diamond,
+    Netherite,
+}
+
+impl ToolTier {
+    // Aus dem Namen des Materials bzw. dem Tag minecraft:incorrect_for_<stufe>_tool
+    fn from_material(material: &str) -> Option<ToolTier> {
+        match material {
+            "wooden" => Some(ToolTier::Wood),
+            "golden" | "gold" => Some(ToolTier::Gold),
+            "stone" => Some(ToolTier::Stone),
+            "iron" => Some(ToolTier::Iron),
+            "diamond" => Some(ToolTier::Diamond),
+            "netherite" => Some(ToolTier::Netherite),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Food {
+    pub nutrition: u32,
+    pub saturation: f32,
+    pub always_edible: bool,
+    pub eat_seconds: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemInfo {
+    pub protocol_id: i32,
+    pub max_stack_size: u8,
+    pub max_damage: Option<u32>,
+    pub food: Option<Food>,
+    pub tier: Option<ToolTier>,
+    // Übersetzungsschlüssel aus minecraft:item_name, z.B. block.minecraft.stone_slab
+    pub translation_key: Option<String>,
+}
+
+// Alle Items mit Protokoll-ID und Eigenschaften. Ohne Berichte gilt der Auszug aus ITEMS
+#[derive(Debug)]
+pub struct ItemRegistry {
+    items: HashMap<String, ItemInfo>,
+}
+
+impl ItemRegistry {
+    fn builtin() -> ItemRegistry {
+        let items = ITEMS
+            .iter()
+            .enumerate()
+            .map(|(id, name)| {
+                let name = format!("minecraft:{}", name);
+                let info = ItemInfo {
+                    protocol_id: id as i32,
+                    max_stack_size: builtin_stack_size(&name),
+                    max_damage: durability(&name),
+                    food: builtin_food(&name),
+                    tier: tier_from_name(&name),
+                    translation_key: None,
+                };
+                (name, info)
+            })
+            .collect();
+        ItemRegistry { items }
+    }
+
+    // registries.json liefert die IDs aus minecraft:item, items.json die Standardkomponenten jedes Items
+    pub fn load(dir: &str) -> Result<ItemRegistry, String> {
+        let dir = Path::new(dir);
+        let read = |file: &str| -> Result<Json, String> {
+            let path = dir.join(file);
+            let text = fs::read_to_string(&path).map_err(|e| format!("{} konnte nicht gelesen werden: {}", path.display(), e))?;
+            json::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+        };
+        let registries = read(REGISTRIES_REPORT)?;
+        let entries = registries.get("minecraft:item").and_then(|r| r.get("entries")).and_then(Json::as_object).ok_or("minecraft:item fehlt im Registerbericht")?;
+        // Ohne items.json bleiben Stapelgrößen und Haltbarkeit beim eingebauten Stand
+        let components = match read(ITEMS_REPORT) {
+            Ok(report) => Some(report),
+            Err(e) => {
+                warn!("{}", e);
+                None
+            }
+        };
+        let builtin = ItemRegistry::builtin();
+        let mut items = HashMap::new();
+        for (name, entry) in entries {
+            let id = entry.get("protocol_id").and_then(Json::as_f64).ok_or(format!("{} ohne protocol_id", name))?;
+            let mut info = builtin.items.get(name).cloned().unwrap_or(ItemInfo {
+                protocol_id: 0,
+                max_stack_size: DEFAULT_STACK_SIZE,
+                max_damage: durability(name),
+                food: None,
+                tier: tier_from_name(name),
+                translation_key: None,
+            });
+            info.protocol_id = id as i32;
+            if let Some(default) = components.as_ref().and_then(|report| report.get(name)).and_then(|item| item.get("components")) {
+                apply_components(&mut info, default);
+            }
+            items.insert(name.clone(), info);
+        }
+        Ok(ItemRegistry { items })
+    }
+
+    pub fn get(&self, item: &str) -> Option<&ItemInfo> {
+        self.items.get(item)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+// Stapelgrößen wie bei Vanilla nach dem Namen, solange items.json fehlt
+fn builtin_stack_size(item: &str) -> u8 {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+    const SINGLE: &[&str] = &[
+        "saddle", "minecart", "chest_minecart", "furnace_minecart", "tnt_minecart", "hopper_minecart", "command_block_minecart", "potion",
+        "splash_potion", "lingering_potion", "cake", "totem_of_undying", "enchanted_book", "writable_book", "knowledge_book", "debug_stick",
+        "goat_horn", "bundle", "spyglass", "shulker_box", "mushroom_stew", "rabbit_stew", "beetroot_soup", "suspicious_stew",
+    ];
+    const SIXTEEN: &[&str] = &["bucket", "snowball", "egg", "ender_pearl", "honey_bottle", "armor_stand", "written_book"];
+    let single_suffix = ["_bucket", "_boat", "_raft", "_bed", "_shulker_box", "_horse_armor", "_banner_pattern"].iter().any(|s| name.ends_with(s));
+    if durability(item).is_some() || SINGLE.contains(&name) || name.starts_with("music_disc_") || (single_suffix && name != "bucket") {
+        1
+    } else if SIXTEEN.contains(&name) || name.ends_with("_sign") || name.ends_with("_banner") {
+        16
+    } else {
+        DEFAULT_STACK_SIZE
+    }
+}
+
+fn builtin_food(item: &str) -> Option<Food> {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+    let &(_, nutrition, saturation, always_edible) = FOODS.iter().find(|(food, ..)| *food == name)?;
+    let eat_seconds = match name {
+        "dried_kelp" => 0.8,
+        "honey_bottle" => 2.0,
+        _ => 1.6,
+    };
+    Some(Food { nutrition, saturation, always_edible, eat_seconds })
+}
+
+// Standardkomponenten aus items.json
+fn apply_components(info: &mut ItemInfo, components: &Json) {
+    let number = |key: &str| components.get(key).and_then(Json::as_f64);
+    if let Some(max) = number("minecraft:max_stack_size") {
+        info.max_stack_size = max.clamp(1.0, 99.0) as u8;
+    }
+    info.max_damage = number("minecraft:max_damage").map(|max| max as u32);
+    info.food = components.get("minecraft:food").map(|food| Food {
+        nutrition: food.get("nutrition").and_then(Json::as_f64).unwrap_or(0.0) as u32,
+        saturation: food.get("saturation").and_then(Json::as_f64).unwrap_or(0.0) as f32,
+        always_edible: food.get("can_always_eat").and_then(Json::as_bool).unwrap_or(false),
+        eat_seconds: food.get("eat_seconds").and_then(Json::as_f64).unwrap_or(1.6) as f32,
+    });
+    info.translation_key = components.get("minecraft:item_name").and_then(|name| match name {
+        Json::String(text) => json::parse(text).ok()?.get("translate").and_then(Json::as_str).map(str::to_string),
+        _ => name.get("translate").and_then(Json::as_str).map(str::to_string),
+    });
+    // Die Stufe steckt in der Regel, die Blöcke aus #minecraft:incorrect_for_<stufe>_tool ausschließt
+    info.tier = components.get("minecraft:tool").and_then(|tool| tool.get("rules")).and_then(Json::as_array).and_then(|rules| {
+        rules.iter().filter_map(|rule| rule.get("blocks").and_then(Json::as_str)).find_map(|blocks| {
+            ToolTier::from_material(blocks.strip_prefix("#minecraft:incorrect_for_")?.strip_suffix("_tool")?)
+        })
+    });
+}
+
+fn registry() -> &'static ItemRegistry {
+    REGISTRY.get_or_init(ItemRegistry::builtin)
+}
+
+// Beim Start, bevor Items verwendet werden. Fehlen die Berichte, gilt das eingebaute Register; liegen sie vor, lassen
+// sich aber nicht lesen, startet der Server nicht mit halben Daten
+pub fn load_registry(dir: &str) -> Result<(), String> {
+    let registry = if Path::new(dir).join(REGISTRIES_REPORT).exists() {
+        let registry = ItemRegistry::load(dir)?;
+        info!("{} Items aus {} geladen", registry.len(), dir);
+        registry
+    } else {
+        warn!("Keine Berichte in {}, verwende das eingebaute Item-Register von 1.21.1 ({} Items)", dir, ITEMS.len());
+        warn!("Erzeugen mit: java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar --reports");
+        ItemRegistry::builtin()
+    };
+    if REGISTRY.set(registry).is_err() {
+        warn!("Item-Register war schon in Gebrauch, Berichte aus {} ignoriert", dir);
+    }
+    Ok(())
+}
+
+pub fn info(item: &str) -> Option<&'static ItemInfo> {
+    registry().get(item)
+}
+
+// None für unbekannte Items
+pub fn protocol_id(item: &str) -> Option<i32> {
+    info(item).map(|info| info.protocol_id)
+}
+
+// ID für die Version des Clients: aus deren Registerbericht, sonst die des Servers
+pub fn protocol_id_in(item: &str, version: ProtocolVersion) -> Option<i32> {
+    assets::item_id(version, item).unwrap_or_else(|| protocol_id(item))
+}
+
+pub fn max_stack_size(item: &str) -> u8 {
+    info(item).map_or(DEFAULT_STACK_SIZE, |info| info.max_stack_size)
+}
+
+// Haltbarkeit laut Register; Items, die es nicht kennt, schätzt durability nach dem Namen
+pub fn max_damage(item: &str) -> Option<u32> {
+    info(item).map_or_else(|| durability(item), |info| info.max_damage)
+}
+
+// Verzauberbarkeit am Zaubertisch nach dem Namen wie bei Vanilla; None für Items, die er nicht verzaubert
+pub fn enchantability(item: &str) -> Option<u32> {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+    match name {
+        "book" | "bow" | "crossbow" | "trident" | "fishing_rod" => return Some(1),
+        "mace" => return Some(15),
+        "turtle_helmet" => return Some(9),
+        _ => {}
+    }
+    let (material, kind) = name.split_once('_')?;
+    if ["sword", "shovel", "pickaxe", "axe", "hoe"].contains(&kind) {
+        return match material {
+            "wooden" | "netherite" => Some(15),
+            "stone" => Some(5),
+            "iron" => Some(14),
+            "golden" => Some(22),
+            "diamond" => Some(10),
+            _ => None,
+        };
+    }
+    if !["helmet", "chestplate", "leggings", "boots"].contains(&kind) {
+        return None;
+    }
+    match material {
+        "leather" | "netherite" => Some(15),
+        "chainmail" => Some(12),
+        "iron" => Some(9),
+        "golden" => Some(25),
+        "diamond" => Some(10),
+        _ => None,
+    }
+}
+
+// Nutzt ein Item um einen Punkt ab; Unbreaking lässt wie bei Vanilla nur einen von Stufe + 1 Punkten zu. true, wenn
+// es dabei zerbricht
+pub fn wear(stack: &mut ItemStack) -> bool {
+    let Some(max) = max_damage(&stack.item) else { return false };
+    let level = stack.components.enchantments.get(UNBREAKING).copied().unwrap_or(0);
+    if stack.components.unbreakable || rand::thread_rng().gen_range(0..=level) != 0 {
+        return false;
+    }
+    let damage = stack.components.damage.unwrap_or(0) + 1;
+    if damage >= max {
+        *stack = ItemStack::empty();
+        return true;
+    }
+    stack.components.damage = Some(damage);
+    false
+}
+
+// Ohne Berichte geraten: Items, die es auch als Block gibt, heißen block.<namespace>.<name>
+pub fn translation_key(item: &str) -> String {
+    if let Some(key) = info(item).and_then(|info| info.translation_key.clone()) {
+        return key;
+    }
+    let (namespace, name) = item.split_once(':').unwrap_or(("minecraft", item));
+    let kind = if block::state_id(item).is_some() { "block" } else { "item" };
+    format!("{}.{}.{}", kind, namespace, name)
+}
+
+pub fn food(item: &str) -> Option<&'static Food> {
+    info(item)?.food.as_ref()
+}
+
+pub fn tool_tier(item: &str) -> Option<ToolTier> {
+    info(item)?.tier
+}
+
+fn tier_from_name(item: &str) -> Option<ToolTier> {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+    let (material, kind) = name.split_once('_')?;
+    if !["sword", "shovel", "pickaxe", "axe", "hoe"].contains(&kind) {
+        return None;
+    }
+    ToolTier::from_material(material)
+}
+
+// Haltbarkeit von Werkzeugen, Waffen und Rüstung nach dem Namen; None für Items, die sich nicht abnutzen
+fn durability(item: &str) -> Option<u32> {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+    let fixed = match name {
+        "bow" => Some(384),
+        "crossbow" => Some(465),
+        "trident" => Some(250),
+        "shears" => Some(238),
+        "fishing_rod" | "flint_and_steel" | "brush" => Some(64),
+        "carrot_on_a_stick" => Some(25),
+        "warped_fungus_on_a_stick" => Some(100),
+        "elytra" => Some(432),
+        "shield" => Some(336),
+        "mace" => Some(500),
+        "turtle_helmet" => Some(275),
+        "wolf_armor" => Some(64),
+        _ => None,
+    };
+    if fixed.is_some() {
+        return fixed;
+    }
+    let (material, kind) = name.split_once('_')?;
+    if ["sword", "shovel", "pickaxe", "axe", "hoe"].contains(&kind) {
+        return match material {
+            "wooden" => Some(59),
+            "stone" => Some(131),
+            "iron" => Some(250),
+            "golden" => Some(32),
+            "diamond" => Some(1561),
+            "netherite" => Some(2031),
+            _ => None,
+        };
+    }
+    // Rüstung: Faktor des Materials mal Grundwert des Teils
+    let factor = match material {
+        "leather" => 5,
+        "chainmail" | "iron" => 15,
+        "golden" => 7,
+        "diamond" => 33,
+        "netherite" => 37,
+        _ => return None,
+    };
+    let base = match kind {
+        "helmet" => 11,
+        "chestplate" => 16,
+        "leggings" => 15,
+        "boots" => 13,
+        _ => return None,
+    };
+    Some(factor * base)
+}
+
+// Verzauberungen: Name, Register-ID seit 1.21 (datengetrieben, alphabetisch) und in 1.20.5/1.20.6
+const ENCHANTMENTS: &[(&str, i32, i32)] = &[
+    ("minecraft:aqua_affinity", 0, 6),
+    ("minecraft:bane_of_arthropods", 1, 15),
+    ("minecraft:binding_curse", 2, 10),
+    ("minecraft:blast_protection", 3, 3),
+    ("minecraft:breach", 4, 38),
+    ("minecraft:channeling", 5, 33),
+    ("minecraft:density", 6, 37),
+    ("minecraft:depth_strider", 7, 8),
+    ("minecraft:efficiency", 8, 20),
+    ("minecraft:feather_falling", 9, 2),
+    ("minecraft:fire_aspect", 10, 17),
+    ("minecraft:fire_protection", 11, 1),
+    ("minecraft:flame", 12, 26),
+    ("minecraft:fortune", 13, 23),
+    ("minecraft:frost_walker", 14, 9),
+    ("minecraft:impaling", 15, 31),
+    ("minecraft:infinity", 16, 27),
+    ("minecraft:knockback", 17, 16),
+    ("minecraft:looting", 18, 18),
+    ("minecraft:loyalty", 19, 30),
+    ("minecraft:luck_of_the_sea", 20, 28),
+    ("minecraft:lure", 21, 29),
+    ("minecraft:mending", 22, 40),
+    ("minecraft:multishot", 23, 34),
+    ("minecraft:piercing", 24, 36),
+    ("minecraft:power", 25, 24),
+    ("minecraft:projectile_protection", 26, 4),
+    ("minecraft:protection", 27, 0),
+    ("minecraft:punch", 28, 25),
+    ("minecraft:quick_charge", 29, 35),
+    ("minecraft:respiration", 30, 5),
+    ("minecraft:riptide", 31, 32),
+    ("minecraft:sharpness", 32, 13),
+    ("minecraft:silk_touch", 33, 21),
+    ("minecraft:smite", 34, 14),
+    ("minecraft:soul_speed", 35, 11),
+    ("minecraft:sweeping_edge", 36, 19),
+    ("minecraft:swift_sneak", 37, 12),
+    ("minecraft:thorns", 38, 7),
+    ("minecraft:unbreaking", 39, 22),
+    ("minecraft:vanishing_curse", 40, 41),
+    ("minecraft:wind_burst", 41, 39),
+];
+
+// Rüstungsbesatz-Materialien mit dem Item, das am Schmiedetisch dafür steht; Register-ID alphabetisch wie die
+// Reihenfolge hier
+pub const TRIM_MATERIALS: &[(&str, &str)] = &[
+    ("minecraft:amethyst", "minecraft:amethyst_shard"),
+    ("minecraft:copper", "minecraft:copper_ingot"),
+    ("minecraft:diamond", "minecraft:diamond"),
+    ("minecraft:emerald", "minecraft:emerald"),
+    ("minecraft:gold", "minecraft:gold_ingot"),
+    ("minecraft:iron", "minecraft:iron_ingot"),
+    ("minecraft:lapis", "minecraft:lapis_lazuli"),
+    ("minecraft:netherite", "minecraft:netherite_ingot"),
+    ("minecraft:quartz", "minecraft:quartz"),
+    ("minecraft:redstone", "minecraft:redstone"),
+];
+
+// Besatzmuster: Name, Register-ID seit 1.21 und in 1.20.5/1.20.6, wo es bolt und flow noch nicht gab
+pub const TRIM_PATTERNS: &[(&str, i32, Option<i32>)] = &[
+    ("minecraft:bolt", 0, None),
+    ("minecraft:coast", 1, Some(0)),
+    ("minecraft:dune", 2, Some(1)),
+    ("minecraft:eye", 3, Some(2)),
+    ("minecraft:flow", 4, None),
+    ("minecraft:host", 5, Some(3)),
+    ("minecraft:raiser", 6, Some(4)),
+    ("minecraft:rib", 7, Some(5)),
+    ("minecraft:sentry", 8, Some(6)),
+    ("minecraft:shaper", 9, Some(7)),
+    ("minecraft:silence", 10, Some(8)),
+    ("minecraft:snout", 11, Some(9)),
+    ("minecraft:spire", 12, Some(10)),
+    ("minecraft:tide", 13, Some(11)),
+    ("minecraft:vex", 14, Some(12)),
+    ("minecraft:ward", 15, Some(13)),
+    ("minecraft:wayfinder", 16, Some(14)),
+    ("minecraft:wild", 17, Some(15)),
+];
+
+// IDs der Datenkomponenten-Typen (1.20.5 bis 1.21.1)
+const DAMAGE: i32 = 3;
+const UNBREAKABLE: i32 = 4;
+const CUSTOM_NAME: i32 = 5;
+const LORE: i32 = 7;
+const ENCHANTMENTS_COMPONENT: i32 = 9;
+const CAN_PLACE_ON: i32 = 10;
+const CAN_BREAK: i32 = 11;
+const REPAIR_COST: i32 = 16;
+const STORED_ENCHANTMENTS: i32 = 23;
+const MAP_ID: i32 = 26;
+const CHARGED_PROJECTILES: i32 = 29;
+const POTION_CONTENTS: i32 = 31;
+const TRIM: i32 = 35;
+const UNBREAKING: &str = "minecraft:unbreaking";
+const MAX_LORE_LINES: usize = 256;
+const MAX_ENCHANTMENT_LEVEL: u32 = 255;
+
+// In Registry-Reihenfolge von 1.21
+pub fn enchantment_names() -> impl Iterator<Item = &'static str> {
+    ENCHANTMENTS.iter().map(|(name, _, _)| *name)
+}
+
+// 1.20.4 kennt die drei Verzauberungen des Streitkolbens (37 bis 39) noch nicht, die beiden danach rücken auf
+pub fn enchantment_id(name: &str, version: ProtocolVersion) -> Option<i32> {
+    let &(_, latest, v1_20_5) = ENCHANTMENTS.iter().find(|(n, _, _)| *n == name)?;
+    match version {
+        ProtocolVersion::V1_21 => Some(latest),
+        ProtocolVersion::V1_20_5 => Some(v1_20_5),
+        ProtocolVersion::V1_20_4 => match v1_20_5 {
+            37..=39 => None,
+            40.. => Some(v1_20_5 - 3),
+            _ => Some(v1_20_5),
+        },
+    }
+}
+
+// Stufen aus enchantments bzw. stored_enchantments. Seit 1.21 {levels:{sharpness:5}}; die kurze Form {sharpness:5}
+// wird ebenfalls akzeptiert
+fn enchantment_levels(value: &Json, key: &str) -> Result<BTreeMap<String, u32>, String> {
+    let levels = value.get("levels").unwrap_or(value).as_object().ok_or_else(|| format!("{} must be a compound", key))?;
+    let mut result = BTreeMap::new();
+    for (name, level) in levels {
+        let name = namespaced(name);
+        if !ENCHANTMENTS.iter().any(|(n, _, _)| *n == name) {
+            return Err(format!("Unknown enchantment '{}'", name));
+        }
+        match level.as_f64() {
+            Some(level) if level >= 1.0 && level <= MAX_ENCHANTMENT_LEVEL as f64 && level.fract() == 0.0 => {
+                result.insert(name, level as u32);
+            }
+            _ => return Err(format!("Enchantment level must be between 1 and {}", MAX_ENCHANTMENT_LEVEL)),
+        }
+    }
+    Ok(result)
+}
+
+fn enchantments_json(levels: &BTreeMap<String, u32>) -> Json {
+    let levels = levels.iter().map(|(name, level)| (name.clone(), Json::Number(*level as f64))).collect();
+    let mut enchantments = BTreeMap::new();
+    enchantments.insert("levels".to_string(), Json::Object(levels));
+    Json::Object(enchantments)
+}
+
+fn encode_enchantments(levels: &BTreeMap<String, u32>, version: ProtocolVersion) -> Vec<u8> {
+    let known: Vec<(i32, u32)> = levels.iter().filter_map(|(name, level)| Some((enchantment_id(name, version)?, *level))).collect();
+    let mut data = write_varint_to_vec(known.len() as i32);
+    for (id, level) in known {
+        data.extend(write_varint_to_vec(id));
+        data.extend(write_varint_to_vec(level as i32));
+    }
+    data.push(1); // Im Tooltip anzeigen
+    data
+}
+
+fn legacy_enchantments(levels: &BTreeMap<String, u32>) -> Json {
+    let list = levels
+        .iter()
+        .map(|(name, level)| {
+            let mut entry = BTreeMap::new();
+            entry.insert("id".to_string(), Json::String(name.clone()));
+            entry.insert("lvl".to_string(), Json::Number(*level as f64));
+            Json::Object(entry)
+        })
+        .collect();
+    Json::Array(list)
+}
+
+// Textkomponente aus einem Komponentenwert: JSON-Text als String, wie in Datenpaketen und Befehlen
+fn text_component(value: &Json, key: &str) -> Result<Json, String> {
+    let text = value.as_str().ok_or_else(|| format!("{} must be a JSON text component", key))?;
+    json::parse(text).map_err(|e| format!("Invalid text component in {}: {}", key, e))
+}
+
+// Ein Eintrag aus can_break bzw. can_place_on. Ohne blocks passt jeder Block; Zustände und NBT werden nicht geprüft
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockPredicate {
+    // Block-IDs oder ein einzelnes "#tag"
+    pub blocks: Option<Vec<String>>,
+}
+
+impl BlockPredicate {
+    fn from_json(value: &Json, key: &str) -> Result<BlockPredicate, String> {
+        let predicate = value.as_object().ok_or_else(|| format!("{} predicates must be compounds", key))?;
+        if let Some(field) = predicate.keys().find(|field| *field != "blocks") {
+            return Err(format!("Block predicate field '{}' in {} is not supported", field, key));
+        }
+        let Some(blocks) = predicate.get("blocks") else { return Ok(BlockPredicate::default()) };
+        let names: Vec<&str> = match blocks {
+            Json::String(name) => vec![name.as_str()],
+            Json::Array(names) => names.iter().map(|name| name.as_str().ok_or_else(|| format!("blocks in {} must be block IDs", key))).collect::<Result<_, _>>()?,
+            _ => return Err(format!("blocks in {} must be a block ID, a list of them or a #tag", key)),
+        };
+        let mut blocks = Vec::new();
+        for name in &names {
+            if let Some(tag) = name.strip_prefix('#') {
+                if names.len() > 1 {
+                    return Err(format!("A block tag in {} cannot be combined with other blocks", key));
+                }
+                blocks.push(format!("#{}", namespaced(tag)));
+            } else {
+                let name = namespaced(name);
+                if block::state_id(&name).is_none() {
+                    return Err(format!("Unknown block type '{}'", name));
+                }
+                blocks.push(name);
+            }
+        }
+        Ok(BlockPredicate { blocks: Some(blocks) })
+    }
+
+    fn to_json(&self) -> Json {
+        let mut map = BTreeMap::new();
+        match self.blocks.as_deref() {
+            Some([name]) => {
+                map.insert("blocks".to_string(), Json::String(name.clone()));
+            }
+            Some(names) => {
+                map.insert("blocks".to_string(), Json::Array(names.iter().cloned().map(Json::String).collect()));
+            }
+            None => {}
+        }
+        Json::Object(map)
+    }
+
+    // block_tags: die aufgelösten Block-Tags der Datenpakete
+    pub fn matches(&self, state: u16, block_tags: Option<&HashMap<String, Vec<String>>>) -> bool {
+        let Some(blocks) = &self.blocks else { return true };
+        let name = block::name(state);
+        blocks.iter().any(|entry| match entry.strip_prefix('#') {
+            Some(tag) => block_tags.and_then(|tags| tags.get(tag)).is_some_and(|members| members.iter().any(|member| member == name)),
+            None => entry == name,
+        })
+    }
+
+    // HolderSet: 0 und Tag-Name oder Anzahl + 1 und Register-IDs, dann Eigenschaften und NBT (beide nicht gesetzt)
+    fn encode(&self, version: ProtocolVersion) -> Vec<u8> {
+        let mut data = vec![];
+        match self.blocks.as_deref() {
+            None => data.push(0),
+            Some([tag]) if tag.starts_with('#') => {
+                data.push(1);
+                data.extend(write_varint_to_vec(0));
+                data.extend(write_string_to_vec(&tag[1..]));
+            }
+            Some(names) => {
+                let ids: Vec<i32> = names.iter().filter_map(|name| block::registry_id_in(name, version)).collect();
+                data.push(1);
+                data.extend(write_varint_to_vec(ids.len() as i32 + 1));
+                for id in ids {
+                    data.extend(write_varint_to_vec(id));
+                }
+            }
+        }
+        data.extend([0, 0]);
+        data
+    }
+}
+
+// can_break und can_place_on: {predicates: [...], show_in_tooltip: ...} oder kurz ein Prädikat bzw. eine Liste davon
+fn block_predicates(value: &Json, key: &str) -> Result<Vec<BlockPredicate>, String> {
+    match value.get("predicates").unwrap_or(value) {
+        Json::Array(entries) => entries.iter().map(|entry| BlockPredicate::from_json(entry, key)).collect(),
+        single => Ok(vec![BlockPredicate::from_json(single, key)?]),
+    }
+}
+
+fn encode_block_predicates(predicates: &[BlockPredicate], version: ProtocolVersion) -> Vec<u8> {
+    let mut data = write_varint_to_vec(predicates.len() as i32);
+    for predicate in predicates {
+        data.extend(predicate.encode(version));
+    }
+    data.push(1); // Im Tooltip anzeigen
+    data
+}
+
+fn block_predicates_json(predicates: &[BlockPredicate]) -> Json {
+    let mut map = BTreeMap::new();
+    map.insert("predicates".to_string(), Json::Array(predicates.iter().map(BlockPredicate::to_json).collect()));
+    Json::Object(map)
+}
+
+// Bis 1.20.4 nur eine Liste von Block-IDs und Tags; Prädikate ohne Blöcke gibt es dort nicht
+fn legacy_block_list(predicates: &[BlockPredicate]) -> Json {
+    Json::Array(predicates.iter().flat_map(|p| p.blocks.iter().flatten()).cloned().map(Json::String).collect())
+}
+
+// Rüstungsbesatz vom Schmiedetisch, Material und Muster mit Namespace
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trim {
+    pub material: String,
+    pub pattern: String,
+}
+
+impl Trim {
+    fn parse(value: &Json) -> Result<Trim, String> {
+        let field = |key: &str| value.get(key).and_then(Json::as_str).map(namespaced).ok_or_else(|| format!("trim needs a {}", key));
+        let (material, pattern) = (field("material")?, field("pattern")?);
+        if !TRIM_MATERIALS.iter().any(|(m, _)| *m == material) {
+            return Err(format!("Unknown trim material '{}'", material));
+        }
+        if !TRIM_PATTERNS.iter().any(|(p, _, _)| *p == pattern) {
+            return Err(format!("Unknown trim pattern '{}'", pattern));
+        }
+        Ok(Trim { material, pattern })
+    }
+
+    fn to_json(&self) -> Json {
+        let mut map = BTreeMap::new();
+        map.insert("material".to_string(), Json::String(self.material.clone()));
+        map.insert("pattern".to_string(), Json::String(self.pattern.clone()));
+        Json::Object(map)
+    }
+
+    // Material und Muster als Verweis ins Register (ID + 1); None, wenn die Version das Muster nicht kennt
+    fn encode(&self, version: ProtocolVersion) -> Option<Vec<u8>> {
+        let material = TRIM_MATERIALS.iter().position(|(m, _)| *m == self.material)? as i32;
+        let pattern = TRIM_PATTERNS.iter().find(|(p, _, _)| *p == self.pattern).and_then(|(_, latest, v1_20_5)| if version >= ProtocolVersion::V1_21 { Some(*latest) } else { *v1_20_5 })?;
+        let mut data = write_varint_to_vec(material + 1);
+        data.extend(write_varint_to_vec(pattern + 1));
+        data.push(1); // Im Tooltip anzeigen
+        Some(data)
+    }
+}
+
+// Die unterstützten Datenkomponenten; fehlende entsprechen dem Standard des Items
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Components {
+    pub custom_name: Option<Json>,
+    pub lore: Vec<Json>,
+    // Name mit Namespace -> Stufe, sortiert wie im Register
+    pub enchantments: BTreeMap<String, u32>,
+    pub damage: Option<u32>,
+    pub unbreakable: bool,
+    // Im Abenteuermodus darf nur abgebaut bzw. auf das gebaut werden, was hier erlaubt ist
+    pub can_break: Vec<BlockPredicate>,
+    pub can_place_on: Vec<BlockPredicate>,
+    // Verzauberungen eines verzauberten Buchs, die erst im Amboss auf ein Item übergehen
+    pub stored_enchantments: BTreeMap<String, u32>,
+    // Aufschlag für frühere Amboss-Arbeiten, verdoppelt sich mit jeder
+    pub repair_cost: u32,
+    // Nummer der Kartendaten einer gefüllten Karte
+    pub map_id: Option<i32>,
+    // Pfeile einer gespannten Armbrust
+    pub charged_projectiles: Vec<ItemStack>,
+    pub trim: Option<Trim>,
+    // Grundtrank von Tränken, Wurf- und Verweiltränken, etwa minecraft:healing
+    pub potion: Option<String>,
+}
+
+impl Components {
+    pub fn is_empty(&self) -> bool {
+        *self == Components::default()
+    }
+
+    // Aus der Komponentenliste einer Item-Angabe, als Json (aus SNBT oder JSON). Schlüssel ohne Namespace sind erlaubt
+    pub fn from_map(map: &BTreeMap<String, Json>) -> Result<Components, String> {
+        let mut components = Components::default();
+        for (key, value) in map {
+            match namespaced(key).as_str() {
+                "minecraft:custom_name" => components.custom_name = Some(text_component(value, key)?),
+                "minecraft:lore" => {
+                    let lines = value.as_array().ok_or("lore must be a list")?;
+                    if lines.len() > MAX_LORE_LINES {
+                        return Err(format!("lore can have at most {} lines", MAX_LORE_LINES));
+                    }
+                    components.lore = lines.iter().map(|line| text_component(line, key)).collect::<Result<_, _>>()?;
+                }
+                "minecraft:damage" => match value.as_f64() {
+                    Some(damage) if damage >= 0.0 && damage.fract() == 0.0 => components.damage = Some(damage as u32),
+                    _ => return Err("damage must be a non-negative integer".to_string()),
+                },
+                "minecraft:unbreakable" => components.unbreakable = value.as_object().is_some(),
+                "minecraft:can_break" => components.can_break = block_predicates(value, key)?,
+                "minecraft:can_place_on" => components.can_place_on = block_predicates(value, key)?,
+                "minecraft:enchantments" => components.enchantments = enchantment_levels(value, key)?,
+                "minecraft:stored_enchantments" => components.stored_enchantments = enchantment_levels(value, key)?,
+                "minecraft:map_id" => match value.as_f64() {
+                    Some(id) if id >= 0.0 && id <= i32::MAX as f64 && id.fract() == 0.0 => components.map_id = Some(id as i32),
+                    _ => return Err("map_id must be a non-negative integer".to_string()),
+                },
+                "minecraft:charged_projectiles" => {
+                    let entries = value.as_array().ok_or("charged_projectiles must be a list of items")?;
+                    components.charged_projectiles = entries.iter().map(|entry| ItemStack::from_json(entry).ok_or("charged_projectiles must be a list of items")).collect::<Result<_, _>>()?;
+                }
+                "minecraft:trim" => components.trim = Some(Trim::parse(value)?),
+                // {potion: "healing"} oder kurz nur der Name
+                "minecraft:potion_contents" => {
+                    let name = value.get("potion").unwrap_or(value).as_str().map(namespaced).ok_or("potion_contents needs a potion")?;
+                    if !potion::is_known(&name) {
+                        return Err(format!("Unknown potion '{}'", name));
+                    }
+                    components.potion = Some(name);
+                }
+                "minecraft:repair_cost" => match value.as_f64() {
+                    Some(cost) if cost >= 0.0 && cost <= i32::MAX as f64 && cost.fract() == 0.0 => components.repair_cost = cost as u32,
+                    _ => return Err("repair_cost must be a non-negative integer".to_string()),
+                },
+                other => return Err(format!("Unknown item component '{}'", other)),
+            }
+        }
+        Ok(components)
+    }
+
+    // Vanilla-Form aus Datenpaketen, so auch in den Spielerdaten gespeichert
+    pub fn to_json(&self) -> Json {
+        let mut map = BTreeMap::new();
+        if let Some(name) = &self.custom_name {
+            map.insert("minecraft:custom_name".to_string(), Json::String(name.to_json_string()));
+        }
+        if !self.lore.is_empty() {
+            map.insert("minecraft:lore".to_string(), Json::Array(self.lore.iter().map(|l| Json::String(l.to_json_string())).collect()));
+        }
+        if let Some(damage) = self.damage {
+            map.insert("minecraft:damage".to_string(), Json::Number(damage as f64));
+        }
+        if self.unbreakable {
+            map.insert("minecraft:unbreakable".to_string(), Json::Object(BTreeMap::new()));
+        }
+        if !self.enchantments.is_empty() {
+            map.insert("minecraft:enchantments".to_string(), enchantments_json(&self.enchantments));
+        }
+        if !self.stored_enchantments.is_empty() {
+            map.insert("minecraft:stored_enchantments".to_string(), enchantments_json(&self.stored_enchantments));
+        }
+        if self.repair_cost > 0 {
+            map.insert("minecraft:repair_cost".to_string(), Json::Number(self.repair_cost as f64));
+        }
+        if let Some(id) = self.map_id {
+            map.insert("minecraft:map_id".to_string(), Json::Number(id as f64));
+        }
+        if !self.can_break.is_empty() {
+            map.insert("minecraft:can_break".to_string(), block_predicates_json(&self.can_break));
+        }
+        if !self.charged_projectiles.is_empty() {
+            map.insert("minecraft:charged_projectiles".to_string(), Json::Array(self.charged_projectiles.iter().map(ItemStack::to_json).collect()));
+        }
+        if !self.can_place_on.is_empty() {
+            map.insert("minecraft:can_place_on".to_string(), block_predicates_json(&self.can_place_on));
+        }
+        if let Some(trim) = &self.trim {
+            map.insert("minecraft:trim".to_string(), trim.to_json());
+        }
+        if let Some(name) = &self.potion {
+            let mut contents = BTreeMap::new();
+            contents.insert("potion".to_string(), Json::String(name.clone()));
+            map.insert("minecraft:potion_contents".to_string(), Json::Object(contents));
+        }
+        Json::Object(map)
+    }
+
+    // Strukturiertes Format seit 1.20.5: Anzahl hinzugefügter und entfernter Komponenten, dann je Typ-ID und Wert
+    fn encode(&self, version: ProtocolVersion) -> Vec<u8> {
+        let mut added: Vec<(i32, Vec<u8>)> = Vec::new();
+        if let Some(damage) = self.damage {
+            added.push((DAMAGE, write_varint_to_vec(damage as i32)));
+        }
+        if self.unbreakable {
+            added.push((UNBREAKABLE, vec![1])); // Im Tooltip anzeigen
+        }
+        if let Some(name) = &self.custom_name {
+            added.push((CUSTOM_NAME, nbt::from_json(name)));
+        }
+        if !self.lore.is_empty() {
+            let mut data = write_varint_to_vec(self.lore.len() as i32);
+            for line in &self.lore {
+                data.extend(nbt::from_json(line));
+            }
+            added.push((LORE, data));
+        }
+        if !self.enchantments.is_empty() {
+            added.push((ENCHANTMENTS_COMPONENT, encode_enchantments(&self.enchantments, version)));
+        }
+        if !self.can_place_on.is_empty() {
+            added.push((CAN_PLACE_ON, encode_block_predicates(&self.can_place_on, version)));
+        }
+        if !self.can_break.is_empty() {
+            added.push((CAN_BREAK, encode_block_predicates(&self.can_break, version)));
+        }
+        if self.repair_cost > 0 {
+            added.push((REPAIR_COST, write_varint_to_vec(self.repair_cost as i32)));
+        }
+        if !self.stored_enchantments.is_empty() {
+            added.push((STORED_ENCHANTMENTS, encode_enchantments(&self.stored_enchantments, version)));
+        }
+        if let Some(id) = self.map_id {
+            added.push((MAP_ID, write_varint_to_vec(id)));
+        }
+        if !self.charged_projectiles.is_empty() {
+            let mut data = write_varint_to_vec(self.charged_projectiles.len() as i32);
+            for stack in &self.charged_projectiles {
+                data.extend(stack.encode(version));
+            }
+            added.push((CHARGED_PROJECTILES, data));
+        }
+        if let Some(id) = self.potion.as_deref().and_then(potion::registry_id) {
+            let mut data = vec![1];
+            data.extend(write_varint_to_vec(id));
+            data.push(0); // Keine eigene Farbe
+            data.extend(write_varint_to_vec(0)); // Keine eigenen Effekte
+            added.push((POTION_CONTENTS, data));
+        }
+        if let Some(trim) = self.trim.as_ref().and_then(|trim| trim.encode(version)) {
+            added.push((TRIM, trim));
+        }
+        let mut data = write_varint_to_vec(added.len() as i32);
+        data.extend(write_varint_to_vec(0)); // Entfernte Komponenten
+        for (id, value) in added {
+            data.extend(write_varint_to_vec(id));
+            data.extend(value);
+        }
+        data
+    }
+
+    // Bis 1.20.4 steckt dasselbe im NBT des Items; Name und Lore dort als JSON-Strings
+    fn legacy_nbt(&self) -> Json {
+        let mut tag = BTreeMap::new();
+        let mut display = BTreeMap::new();
+        if let Some(name) = &self.custom_name {
+            display.insert("Name".to_string(), Json::String(name.to_json_string()));
+        }
+        if !self.lore.is_empty() {
+            display.insert("Lore".to_string(), Json::Array(self.lore.iter().map(|l| Json::String(l.to_json_string())).collect()));
+        }
+        if !display.is_empty() {
+            tag.insert("display".to_string(), Json::Object(display));
+        }
+        if let Some(damage) = self.damage {
+            tag.insert("Damage".to_string(), Json::Number(damage as f64));
+        }
+        if self.unbreakable {
+            tag.insert("Unbreakable".to_string(), Json::Bool(true));
+        }
+        if !self.enchantments.is_empty() {
+            tag.insert("Enchantments".to_string(), legacy_enchantments(&self.enchantments));
+        }
+        if !self.stored_enchantments.is_empty() {
+            tag.insert("StoredEnchantments".to_string(), legacy_enchantments(&self.stored_enchantments));
+        }
+        if self.repair_cost > 0 {
+            tag.insert("RepairCost".to_string(), Json::Number(self.repair_cost as f64));
+        }
+        if let Some(id) = self.map_id {
+            tag.insert("map".to_string(), Json::Number(id as f64));
+        }
+        if !self.can_break.is_empty() {
+            tag.insert("CanDestroy".to_string(), legacy_block_list(&self.can_break));
+        }
+        if !self.can_place_on.is_empty() {
+            tag.insert("CanPlaceOn".to_string(), legacy_block_list(&self.can_place_on));
+        }
+        if !self.charged_projectiles.is_empty() {
+            let projectiles = self.charged_projectiles.iter().map(|stack| {
+                let mut entry = BTreeMap::new();
+                entry.insert("id".to_string(), Json::String(stack.item.clone()));
+                entry.insert("Count".to_string(), Json::Number(stack.count as f64));
+                Json::Object(entry)
+            });
+            tag.insert("Charged".to_string(), Json::Bool(true));
+            tag.insert("ChargedProjectiles".to_string(), Json::Array(projectiles.collect()));
+        }
+        if let Some(trim) = &self.trim {
+            tag.insert("Trim".to_string(), trim.to_json());
+        }
+        if let Some(name) = &self.potion {
+            tag.insert("Potion".to_string(), Json::String(name.clone()));
+        }
+        Json::Object(tag)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    pub item: String,
+    pub count: u8,
+    pub components: Components,
+}
+
+impl ItemStack {
+    pub fn new(item: &str, count: u8) -> ItemStack {
+        ItemStack {
+            item: item.to_string(),
+            count,
+            components: Components::default(),
+        }
+    }
+
+    pub fn empty() -> ItemStack {
+        ItemStack::new("minecraft:air", 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0 || self.item == "minecraft:air"
+    }
+
+    // Gleiches Item mit gleichen Komponenten, darf also auf denselben Stapel
+    pub fn stacks_with(&self, other: &ItemStack) -> bool {
+        self.item == other.item && self.components == other.components
+    }
+
+    // Item-Angabe aus Befehlen: stone oder minecraft:stone[custom_name='"Stein"',damage=3,unbreakable={}]
+    pub fn parse(spec: &str) -> Result<ItemStack, String> {
+        let (id, components) = match spec.split_once('[') {
+            Some((id, rest)) => {
+                let list = rest.strip_suffix(']').ok_or("Expected ']' at the end of the item components")?;
+                (id, Components::from_map(&nbt::parse_snbt_entries(list, '=')?)?)
+            }
+            None => (spec, Components::default()),
+        };
+        let id = namespaced(id);
+        if protocol_id(&id).is_none() || id == "minecraft:air" {
+            return Err(format!("Unknown item '{}'", id));
+        }
+        Ok(ItemStack { item: id, count: 1, components })
+    }
+
+    // {"id": ..., "count": ..., "components": {...}} wie in Datenpaketen
+    pub fn to_json(&self) -> Json {
+        let mut map = BTreeMap::new();
+        map.insert("id".to_string(), Json::String(self.item.clone()));
+        map.insert("count".to_string(), Json::Number(self.count as f64));
+        if !self.components.is_empty() {
+            map.insert("components".to_string(), self.components.to_json());
+        }
+        Json::Object(map)
+    }
+
+    // Gegenstück zu to_json, etwa für Container-Inhalte; None bei unbekannten Items oder Komponenten
+    pub fn from_json(data: &Json) -> Option<ItemStack> {
+        let item = namespaced(data.get("id")?.as_str()?);
+        protocol_id(&item)?;
+        let count = data.get("count").and_then(Json::as_f64).unwrap_or(1.0).clamp(1.0, u8::MAX as f64) as u8;
+        let components = match data.get("components").and_then(Json::as_object) {
+            Some(map) => Components::from_map(map).ok()?,
+            None => Components::default(),
+        };
+        Some(ItemStack { item, count, components })
+    }
+
+    // Slot-Format: seit 1.20.5 Anzahl, ID und Komponenten, davor Vorhanden-Flag, ID, Anzahl und NBT
+    pub fn encode(&self, version: ProtocolVersion) -> Vec<u8> {
+        let id = protocol_id_in(&self.item, version).filter(|_| !self.is_empty());
+        let mut data = vec![];
+        if version.has_1_20_5_login() {
+            let Some(id) = id else { return write_varint_to_vec(0) };
+            data.extend(write_varint_to_vec(self.count as i32));
+            data.extend(write_varint_to_vec(id));
+            data.extend(self.components.encode(version));
+        } else {
+            let Some(id) = id else { return vec![0] };
+            data.push(1);
+            data.extend(write_varint_to_vec(id));
+            data.push(self.count);
+            if self.components.is_empty() {
+                data.push(0); // Kein NBT (TAG_End)
+            } else {
+                data.extend(nbt::from_json(&self.components.legacy_nbt()));
+            }
+        }
+        data
+    }
+}
+
+// Belegte Slots als [{"slot": n, "id": ..., "count": ..., "components": {...}}], für Spielerdaten und Container
+pub fn slots_to_json(slots: &[ItemStack]) -> Json {
+    let slots = slots
+        .iter()
+        .enumerate()
+        .filter(|(_, stack)| !stack.is_empty())
+        .map(|(slot, stack)| {
+            let mut entry = stack.to_json();
+            if let Json::Object(map) = &mut entry {
+                map.insert("slot".to_string(), Json::Number(slot as f64));
+            }
+            entry
+        })
+        .collect();
+    Json::Array(slots)
+}
+
+// Gegenstück zu slots_to_json; Einträge außerhalb der Slots oder mit unbekannten Items werden übergangen
+pub fn slots_from_json(data: Option<&Json>, slots: &mut [ItemStack]) {
+    for entry in data.and_then(Json::as_array).into_iter().flatten() {
+        let Some(slot) = entry.get("slot").and_then(Json::as_f64).map(|s| s as usize).filter(|&s| s < slots.len()) else { continue };
+        if let Some(stack) = ItemStack::from_json(entry) {
+            slots[slot] = stack;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_registry_covers_vanilla_items() {
+        let registry = ItemRegistry::builtin();
+        assert_eq!(registry.len(), 1333);
+        let item = |name: &str| registry.get(&format!("minecraft:{}", name)).unwrap_or_else(|| panic!("{} fehlt", name));
+        for (name, id) in [("gravel", 61), ("glass", 188), ("oak_stairs", 383), ("diamond", 806), ("stick", 849), ("breeze_rod", 1332)] {
+            assert_eq!(item(name).protocol_id, id, "{}", name);
+        }
+        assert_eq!(item("diamond_pickaxe").max_stack_size, 1);
+        assert_eq!(item("water_bucket").max_stack_size, 1);
+        assert_eq!(item("bucket").max_stack_size, 16);
+        assert_eq!(item("oak_sign").max_stack_size, 16);
+        assert_eq!(item("glass").max_stack_size, 64);
+        assert_eq!(item("bread").food.as_ref().map(|f| f.nutrition), Some(5));
+        assert!(item("golden_apple").food.as_ref().is_some_and(|f| f.always_edible));
+    }
+}
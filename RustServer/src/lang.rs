@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::json::{self, Json};
+use crate::text::Text;
+
+// Sprachdateien wie die von Vanilla: lang/de_de.json mit {"schlüssel": "Text mit %s"}
+pub const LANG_DIR: &str = "lang";
+pub const DEFAULT_LOCALE: &str = "en_us";
+pub const MAX_LOCALE_LENGTH: usize = 16;
+
+// Englische Texte aller Schlüssel, die der Server verwendet. Vanilla-Schlüssel übersetzt der Client selbst,
+// sie stehen hier nur für Konsole, Log und englische Clients; eigene Schlüssel beginnen mit server.
+const DEFAULTS: &[(&str, &str)] = &[
+    ("multiplayer.player.joined", "%s joined the game"),
+    ("multiplayer.player.left", "%s left the game"),
+    ("chat.disabled.options", "Chat disabled in client options."),
+    ("death.attack.outOfWorld", "%s fell out of the world"),
+    ("death.attack.mob", "%s was slain by %s"),
+    ("death.attack.explosion", "%s blew up"),
+    ("death.attack.inFire", "%s went up in flames"),
+    ("death.attack.onFire", "%s burned to death"),
+    ("death.attack.lava", "%s tried to swim in lava"),
+    ("entity.minecraft.ender_dragon", "Ender Dragon"),
+    ("server.death.slain", "%s was slain"),
+    ("server.death.shot", "%s was shot"),
+    ("server.command.unknown", "Unknown command: %s"),
+    ("server.command.permission", "You do not have permission to use /%s"),
+    ("server.command.usage", "%s (Usage: %s)"),
+    ("commands.seed.success", "Seed: %s"),
+    ("commands.time.set", "Set the time to %s"),
+    ("commands.difficulty.query", "The difficulty is %s"),
+    ("commands.difficulty.success", "The difficulty has been set to %s"),
+    ("options.difficulty.peaceful", "Peaceful"),
+    ("options.difficulty.easy", "Easy"),
+    ("options.difficulty.normal", "Normal"),
+    ("options.difficulty.hard", "Hard"),
+    ("commands.kill.success.single", "Killed %s"),
+    ("commands.kill.success.multiple", "Killed %s entities"),
+    ("commands.teleport.success.entity.single", "Teleported %s to %s"),
+    ("commands.teleport.success.entity.multiple", "Teleported %s entities to %s"),
+    ("commands.teleport.success.location.single", "Teleported %s to %s, %s, %s"),
+    ("commands.teleport.success.location.multiple", "Teleported %s entities to %s, %s, %s"),
+    ("commands.give.success.single", "Gave %s %s to %s"),
+    ("commands.give.success.multiple", "Gave %s %s to %s players"),
+    ("commands.gamemode.success.self", "Set own game mode to %s"),
+    ("commands.gamemode.success.other", "Set %s's game mode to %s"),
+    ("gameMode.changed", "Your game mode has been updated to %s"),
+    ("gameMode.survival", "Survival Mode"),
+    ("gameMode.creative", "Creative Mode"),
+    ("gameMode.adventure", "Adventure Mode"),
+    ("gameMode.spectator", "Spectator Mode"),
+];
+
+// Teil einer Übersetzung: fester Text oder das n-te Argument
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+    Literal(String),
+    Argument(usize),
+}
+
+// Zerlegt "%s", "%2$s" und "%%" wie Vanillas TranslatableContents; unbekannte Sequenzen bleiben Text
+pub fn parts(template: &str) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(start) = rest.find('%') {
+        literal.push_str(&rest[..start]);
+        let tail = &rest[start + 1..];
+        let digits = tail.len() - tail.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let argument = if let Some(after) = tail.strip_prefix('%') {
+            literal.push('%');
+            rest = after;
+            continue;
+        } else if let Some(after) = tail.strip_prefix('s') {
+            next += 1;
+            rest = after;
+            Some(next - 1)
+        } else if let Some(after) = tail[digits..].strip_prefix("$s").filter(|_| digits > 0) {
+            rest = after;
+            Some(tail[..digits].parse::<usize>().unwrap_or(1).max(1) - 1)
+        } else {
+            literal.push('%');
+            rest = tail;
+            None
+        };
+        if let Some(index) = argument {
+            if !literal.is_empty() {
+                parts.push(Part::Literal(std::mem::take(&mut literal)));
+            }
+            parts.push(Part::Argument(index));
+        }
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+    parts
+}
+
+// Vom Client gemeldete Sprache, etwa "de_DE" oder "en_us"; Unsinn fällt auf Englisch zurück
+pub fn normalize_locale(locale: &str) -> String {
+    let locale = locale.trim().to_ascii_lowercase();
+    if locale.is_empty() || locale.len() > MAX_LOCALE_LENGTH || !locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return DEFAULT_LOCALE.to_string();
+    }
+    locale
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+impl Translations {
+    // Eingebautes Englisch, ergänzt und überschrieben durch die Dateien in dir
+    pub fn load(dir: &str) -> Translations {
+        let mut translations = Translations::default();
+        let defaults = DEFAULTS.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        translations.locales.insert(DEFAULT_LOCALE.to_string(), defaults);
+        let Ok(entries) = fs::read_dir(Path::new(dir)) else { return translations };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|s| s.to_str()).map(normalize_locale) else { continue };
+            let parsed = fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| json::parse(&text));
+            let Some(entries) = parsed.as_ref().ok().and_then(Json::as_object) else {
+                warn!("Sprachdatei {} konnte nicht gelesen werden: {}", path.display(), parsed.err().unwrap_or_else(|| "kein JSON-Objekt".to_string()));
+                continue;
+            };
+            let table = translations.locales.entry(locale.clone()).or_default();
+            for (key, value) in entries {
+                if let Some(value) = value.as_str() {
+                    table.insert(key.clone(), value.to_string());
+                }
+            }
+            info!("Sprachdatei {} geladen ({} Einträge)", locale, entries.len());
+        }
+        translations
+    }
+
+    pub fn get(&self, locale: &str, key: &str) -> Option<&str> {
+        self.locales.get(locale)?.get(key).map(String::as_str)
+    }
+
+    // Für Konsole und Log
+    pub fn plain(&self, text: &Text) -> String {
+        text.localize(self, DEFAULT_LOCALE).plain()
+    }
+}
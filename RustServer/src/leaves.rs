@@ -0,0 +1,88 @@
+use std::collections::{HashSet, VecDeque};
+use crate::{block, entity, loot, Player, Server, World};
+
+type BlockPos = (i32, i32, i32);
+
+pub const RANDOM_TICKING: &[&str] = &["minecraft:oak_leaves", "minecraft:spruce_leaves", "minecraft:jungle_leaves"];
+// Weiter als sechs Blöcke von einem Stamm entfernt hängt ein Blatt an nichts mehr
+const MAX_DISTANCE: u32 = 7;
+const NEIGHBOURS: [BlockPos; 6] = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+
+pub fn is_leaves(state: u16) -> bool {
+    block::name(state).ends_with("_leaves")
+}
+
+fn is_log(state: u16) -> bool {
+    block::name(state).ends_with("_log")
+}
+
+fn stored_distance(state: u16) -> u32 {
+    block::property(state, "distance").and_then(|d| d.parse().ok()).unwrap_or(MAX_DISTANCE)
+}
+
+// Kürzester Weg durch Blätter zum nächsten Stamm, höchstens MAX_DISTANCE
+fn distance(world: &World, (x, y, z): BlockPos) -> u32 {
+    let mut visited = HashSet::from([(x, y, z)]);
+    let mut queue = VecDeque::from([((x, y, z), 0)]);
+    while let Some(((x, y, z), steps)) = queue.pop_front() {
+        if steps + 1 >= MAX_DISTANCE {
+            continue;
+        }
+        for (dx, dy, dz) in NEIGHBOURS {
+            let next = (x + dx, y + dy, z + dz);
+            let state = world.get_block(next.0, next.1, next.2);
+            if is_log(state) {
+                return steps + 1;
+            }
+            if is_leaves(state) && visited.insert(next) {
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    MAX_DISTANCE
+}
+
+fn update_distance(world: &mut World, position: BlockPos, state: u16) -> u32 {
+    let distance = distance(world, position);
+    if distance != stored_distance(state) {
+        if let Some(updated) = block::with_property(state, "distance", &distance.to_string()) {
+            world.set_block_and_notify(position, updated);
+        }
+    }
+    distance
+}
+
+// Ein Stamm oder Blatt wurde gesetzt oder entfernt: die Blätter daneben prüfen im nächsten Tick ihren Abstand
+pub fn block_changed(world: &mut World, (x, y, z): BlockPos, old: u16, new: u16) {
+    if !is_leaves(old) && !is_log(old) && !is_leaves(new) && !is_log(new) {
+        return;
+    }
+    if is_leaves(new) {
+        world.schedule_block_tick((x, y, z), 1);
+    }
+    for (dx, dy, dz) in NEIGHBOURS {
+        let neighbour = (x + dx, y + dy, z + dz);
+        if is_leaves(world.get_block(neighbour.0, neighbour.1, neighbour.2)) {
+            world.schedule_block_tick(neighbour, 1);
+        }
+    }
+}
+
+pub fn scheduled_tick(world: &mut World, position: BlockPos, state: u16) {
+    update_distance(world, position, state);
+}
+
+// Zufallstick: nicht persistente Blätter ohne Stamm in Reichweite zerfallen mit der Beute ihrer Loot-Tabelle. Der
+// gespeicherte Abstand wird vorher nachgerechnet, damit Blätter aus älteren Welten nicht grundlos abfallen
+pub fn random_tick(server: &Server, players: &[Player], world: &mut World, (x, y, z): BlockPos) {
+    let state = world.get_block(x, y, z);
+    if block::property(state, "persistent") == Some("true") || stored_distance(state) < MAX_DISTANCE {
+        return;
+    }
+    if update_distance(world, (x, y, z), state) < MAX_DISTANCE || !world.set_block_and_notify((x, y, z), block::AIR) {
+        return;
+    }
+    for stack in loot::block_drops(server, state) {
+        entity::spawn_item(players, world, stack, (x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5));
+    }
+}
@@ -0,0 +1,2796 @@
+//! A Minecraft server that can run on its own or be embedded; start one with [`Server::builder`].
+
+#[macro_use]
+mod log;
+mod animation;
+mod anvil;
+mod backup;
+mod banlist;
+mod blockentity;
+mod blocktick;
+mod bow;
+mod channel;
+mod chat;
+mod click;
+mod clock;
+mod combat;
+mod command;
+mod config;
+mod configuration;
+mod console;
+mod damage;
+mod datapack;
+mod difficulty;
+mod dimension;
+mod discord;
+mod door;
+mod edit;
+mod effect;
+mod elytra;
+mod enchanting;
+mod end;
+mod entity;
+mod entitytag;
+mod error;
+mod event;
+mod experience;
+mod explosion;
+mod farming;
+mod favicon;
+mod fire;
+mod fluid;
+mod function;
+mod furnace;
+mod gamemode;
+mod gamerule;
+mod hash;
+mod hopper;
+mod hunger;
+mod idle;
+mod inventory;
+mod item;
+mod lang;
+mod leaves;
+mod light;
+mod listen;
+mod logfile;
+mod loot;
+mod lua;
+mod map;
+mod merchant;
+mod metrics;
+mod motd;
+mod motion;
+mod movement;
+mod multiworld;
+mod naturalspawn;
+mod permission;
+mod piston;
+mod plugin;
+mod portal;
+mod pose;
+mod potion;
+mod pregen;
+mod pressureplate;
+mod profiler;
+mod projectile;
+mod proxy;
+mod recipe;
+mod recipebook;
+mod redstone;
+mod resourcepack;
+mod rest;
+mod restart;
+mod rsa;
+mod sapling;
+mod schematic;
+mod scoreboard;
+mod securechat;
+mod selector;
+mod settings;
+mod shield;
+mod sign;
+mod sleep;
+mod smithing;
+mod snow;
+mod spawner;
+mod spatial;
+mod stonecutter;
+mod storage;
+mod structure;
+mod systemd;
+mod tablist;
+mod text;
+mod throttle;
+mod tick;
+mod tnt;
+mod totem;
+mod tracker;
+mod tripwire;
+mod vehicle;
+mod violation;
+mod websocket;
+mod window;
+mod wasm;
+mod watchdog;
+mod worldborder;
+mod worldevent;
+mod worldgen;
+mod worldsettings;
+mod zip;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use byteorder::{ReadBytesExt, BigEndian}; // `WriteBytesExt` entfernt
+use uuid::Uuid;
+use rand::Rng;
+use rustmc_protocol::{self as protocol, block, gzip, json, nbt};
+use protocol::{assets, clientbound, framing, serverbound, ConnectionState, ProtocolError};
+use protocol::packets::{self, Handshake, LoginStart, Play};
+use protocol::types::{decode_position, encode_position, read_string_from_cursor, read_varint_from_cursor, MAX_CHAT_LENGTH};
+use protocol::types::{write_string_to_vec, write_uuid, write_varint_to_vec, write_varlong_to_vec};
+use protocol::version::{ProtocolVersion, LATEST as LATEST_VERSION, SUPPORTED as SUPPORTED_VERSIONS};
+use rustmc_world::{chunk, chunkio, chunkstorage};
+use backup::Backups;
+use banlist::BanList;
+use blockentity::BlockEntity;
+use blocktick::BlockTicks;
+use channel::ChannelRegistry;
+use chunk::Heightmap;
+use chunkstorage::ChunkStorageKind;
+use light::{ChunkLight, LightEngine};
+use sleep::SleepStatus;
+use spatial::SpatialIndex;
+use settings::{ChatMode, ClientSettings};
+use storage::WorldStorage;
+use text::Text;
+use combat::CombatState;
+use command::{CommandDispatcher, CommandSender, RemoteReplies};
+use config::ServerConfig;
+use datapack::DatapackManager;
+use difficulty::Difficulty;
+use discord::DiscordBridge;
+use effect::Effects;
+use end::DragonFight;
+use entity::ItemEntity;
+use error::{Policy, ServerError};
+use gamerule::GameRules;
+use event::{BlockBreakEvent, ChatEvent, Event, EventBus, EventPriority, PlayerJoinEvent, PlayerQuitEvent, ServerListPingEvent};
+use experience::Experience;
+use function::ScheduledFunction;
+use dimension::Dimension;
+use gamemode::GameMode;
+use inventory::Inventory;
+use item::ItemStack;
+use json::Json;
+use lang::Translations;
+use lua::ScriptEngine;
+use edit::Editor;
+use map::MapStore;
+use metrics::NetStats;
+use hunger::Hunger;
+use movement::MovementState;
+use permission::Permissions;
+use plugin::PluginManager;
+use portal::Portals;
+use pose::PoseState;
+use pregen::Pregen;
+use profiler::Profiler;
+use proxy::{ForwardedInfo, ProfileProperty};
+use resourcepack::ResourcePack;
+use restart::PendingRestart;
+use scoreboard::Scoreboard;
+use throttle::{ConnectionThrottle, LoginThrottle, PacketCategory, PacketLimiter, PendingGuard};
+use tick::{Scheduler, TickStats};
+use tracker::EntityTracker;
+use wasm::WasmPluginManager;
+use watchdog::Watchdog;
+use window::Window;
+use worldborder::WorldBorder;
+use worldevent::{QueuedEvent, WorldEvent};
+use worldgen::{ChunkGenPool, Generator};
+use worldsettings::WorldSettings;
+
+// Die Chunks des Servers tragen seine Block-Entities
+type Chunk = chunk::Chunk<BlockEntity>;
+type ChunkFuture = chunkio::ChunkFuture<BlockEntity>;
+type ChunkIo = chunkio::ChunkIo<BlockEntity>;
+
+const VIEW_DISTANCE: i32 = 10;
+const GAME_EVENT_START_RAINING: u8 = 1;
+const GAME_EVENT_STOP_RAINING: u8 = 2;
+const GAME_EVENT_CHANGE_GAME_MODE: u8 = 3;
+const GAME_EVENT_RAIN_LEVEL: u8 = 7;
+const GAME_EVENT_THUNDER_LEVEL: u8 = 8;
+const GAME_EVENT_IMMEDIATE_RESPAWN: u8 = 11;
+const GAME_EVENT_WAIT_FOR_CHUNKS: u8 = 13;
+const KICK_REASON: &str = "Kicked by an operator.";
+const SERVER_FULL_MESSAGE: &str = "The server is full!";
+const LOGIN_THROTTLED_MESSAGE: &str = "Connection throttled, please wait before reconnecting.";
+const INVALID_MOVE: &str = "Invalid move player packet received";
+// Aktion im Client-Command-Paket: 0 = Respawn, 1 = Statistik angefordert
+const CLIENT_COMMAND_RESPAWN: i32 = 0;
+// Status im Player-Action-Paket: 0 = Abbau begonnen, 1 = abgebrochen, 2 = abgeschlossen, 5 = Benutzen beendet,
+// 6 = Haupt- und Zweithand getauscht
+const DIG_STARTED: i32 = 0;
+// Hand in Use Item On: 0 = Haupthand, 1 = Zweithand
+const OFF_HAND: i32 = 1;
+const DIG_FINISHED: i32 = 2;
+const RELEASE_USE_ITEM: i32 = 5;
+const SWAP_ITEM_WITH_OFFHAND: i32 = 6;
+// Meeresspiegel 63 plus 17: darüber wird es mit der Höhe kälter
+const SNOW_LINE: i32 = 80;
+
+// Gemeinsamer Serverzustand. Sperr-Reihenfolge: players -> world oder end (nie beide zugleich) -> scheduler -> übrige
+pub struct Server {
+    config: ServerConfig,
+    players: Mutex<Vec<Player>>,
+    // Eingeloggte Spieler, die noch nicht in players stehen; zählen schon gegen max-players. Erhöht wird nur unter
+    // der Sperre von players
+    joining: AtomicUsize,
+    world: Mutex<World>,
+    end: Mutex<World>,
+    scheduler: Mutex<Scheduler>,
+    tick_stats: Mutex<TickStats>,
+    // Von /debug gesteuert
+    profiler: Profiler,
+    watchdog: Watchdog,
+    commands: Mutex<CommandDispatcher>,
+    pending_commands: Mutex<Vec<(CommandSender, String)>>,
+    logins: Mutex<LoginThrottle>,
+    remote_replies: RemoteReplies,
+    bans: Mutex<BanList>,
+    permissions: Mutex<Permissions>,
+    // Mit save-off abschaltbar, etwa während ein externes Backup läuft
+    autosave: AtomicBool,
+    // Von Funktionen in diesem Tick ausgeführte Befehle, begrenzt durch maxCommandChainLength
+    function_commands: AtomicUsize,
+    backups: Backups,
+    // Vor der Weltsperre zu nehmen
+    pregen: Mutex<Option<Pregen>>,
+    // Von /restart und SIGHUP geplant
+    restart: Mutex<Option<PendingRestart>>,
+    datapacks: Mutex<DatapackManager>,
+    // Gefüllte Karten aller Dimensionen
+    maps: Mutex<MapStore>,
+    // Nach players zu nehmen
+    scoreboard: Mutex<Scoreboard>,
+    // Auswahl, Zwischenablage und laufende Bearbeitungen je Spieler; None gehört der Konsole
+    edits: Mutex<Editor>,
+    // Bereiche, die Spieler beim Betreten in eine andere Welt bringen
+    portals: Mutex<Portals>,
+    // Zuletzt gesendete Positionen und welche Spieler einander sehen
+    tracker: Mutex<EntityTracker>,
+    resource_pack: Option<ResourcePack>,
+    // Aus server.properties mit aufgelösten Escapes; Listener von ServerListPingEvent können sie pro Abfrage ersetzen
+    motd: String,
+    // Mit /reload neu einlesbar
+    favicon: Mutex<Option<String>>,
+    // Eingebautes Englisch und die Dateien aus lang/
+    lang: Translations,
+    events: EventBus,
+    channels: ChannelRegistry,
+    discord: DiscordBridge,
+    plugins: PluginManager,
+    wasm_plugins: WasmPluginManager,
+    scripts: ScriptEngine,
+}
+
+// Schreibende Hälfte einer Verbindung, damit auch Tick-Thread und andere Spieler senden können
+#[derive(Clone)]
+struct Connection {
+    stream: Arc<Mutex<TcpStream>>,
+    addr: SocketAddr,
+    stats: Arc<NetStats>,
+    // Paket-IDs werden beim Senden für die Version des Clients übersetzt
+    version: ProtocolVersion,
+    // Schwelle aus Set Compression; None, solange der Client unkomprimierte Pakete erwartet
+    compression: Option<usize>,
+}
+
+impl Connection {
+    fn new(stream: &TcpStream, addr: SocketAddr, version: ProtocolVersion, compression: Option<usize>) -> Result<Connection, String> {
+        let stream = stream.try_clone().map_err(|e| format!("Konnte Verbindung nicht klonen: {}", e))?;
+        Ok(Connection {
+            stream: Arc::new(Mutex::new(stream)),
+            addr,
+            stats: Arc::new(NetStats::new()),
+            version,
+            compression,
+        })
+    }
+
+    fn send(&self, packet_id: i32, data: &[u8]) -> Result<(), String> {
+        let wire_id = self.version.clientbound(packet_id);
+        let packet = encode_packet(wire_id, data, self.compression);
+        write_packet(&mut self.stream.lock().unwrap(), wire_id, &packet)?;
+        self.stats.record_packet_out(packet_id, packet.len());
+        Ok(())
+    }
+
+    fn close(&self) {
+        let _ = self.stream.lock().unwrap().shutdown(Shutdown::Both);
+    }
+}
+
+impl std::fmt::Debug for Connection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Connection({})", self.addr)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct KeepAlive {
+    pending: Option<(i64, Instant)>,
+    // Geglättete Antwortzeit in Millisekunden, 0 bis zur ersten Antwort
+    ping_ms: u32,
+}
+
+// Bis der Client eine Teleportation bestätigt, werden seine Bewegungspakete verworfen,
+// damit sie die neue Position nicht mit der alten überschreiben
+#[derive(Debug, Clone, Default)]
+struct Teleport {
+    last_id: i32,
+    pending: Option<i32>,
+}
+
+#[derive(Debug, Clone)]
+struct Player {
+    uuid: Uuid,
+    // Unter dieser ID sehen andere Clients den Spieler; sein eigener kennt sich als entity::OWN_PLAYER_ID
+    entity_id: i32,
+    username: String,
+    position: (f64, f64, f64),
+    // (Yaw, Pitch) in Grad
+    rotation: (f32, f32),
+    health: f32,
+    // Zusätzliche Lebenspunkte durch Absorption, die Treffer zuerst aufbrauchen
+    absorption: f32,
+    hunger: Hunger,
+    // Luftvorrat unter Wasser in Ticks
+    air: i32,
+    // Verbleibende Ticks, die der Spieler noch brennt
+    fire_ticks: i32,
+    effects: Effects,
+    // Mit /tag vergebene Markierungen, nach denen Selektoren wie @e[tag=quest_npc] filtern
+    tags: Vec<String>,
+    experience: Experience,
+    game_mode: GameMode,
+    dimension: Dimension,
+    // Geduckt oder sprintend, wie der Client über Player Command meldet
+    pose: PoseState,
+    glide: elytra::Glide,
+    // Gespannter Bogen oder ladende Armbrust
+    drawing: Option<bow::Drawing>,
+    // Erhobener Schild
+    blocking: Option<shield::Blocking>,
+    properties: Vec<ProfileProperty>,
+    connection: Connection,
+    keep_alive: KeepAlive,
+    // Letzte Eingabe des Spielers für player-idle-timeout
+    last_input: Instant,
+    teleport: Teleport,
+    movement: MovementState,
+    combat: CombatState,
+    inventory: Inventory,
+    // Inhalt der Endertruhen, für jeden Spieler ein eigener
+    ender_chest: Vec<ItemStack>,
+    // Offener Container und die zuletzt vergebene Fenster-ID
+    window: Option<Window>,
+    window_id: u8,
+    // Item am Mauszeiger und ein laufendes Ziehen über mehrere Slots
+    carried: ItemStack,
+    drag: click::Drag,
+    // Freigeschaltete Rezepte und Einstellungen des Rezeptbuchs
+    recipe_book: recipebook::RecipeBook,
+    // Vom Client über minecraft:brand gemeldet, z.B. "vanilla" oder "fabric"
+    brand: Option<String>,
+    // Kanäle, die der Client über minecraft:register angemeldet hat
+    channels: HashSet<String>,
+    // Mit /spawnpoint oder im Bett gesetzt; sonst wird am Weltspawn wiederbelebt
+    spawn_point: Option<SpawnPoint>,
+    // Ticks im Bett, höchstens bis zum Tiefschlaf gezählt; davon hängt das Überspringen der Nacht ab
+    sleep_ticks: u32,
+    // Letzter Gesprächspartner für /r
+    reply_to: Option<Uuid>,
+    // Spieler, deren private Nachrichten verworfen werden; gilt bis zum Verlassen des Servers
+    ignored: HashSet<Uuid>,
+    // Chat-Sitzung und Zeitstempel der letzten Nachricht
+    chat: securechat::ChatState,
+    // Aus Client Information: Sprache der Servertexte, Sichtweite, Chat-Modus und Skin-Ebenen
+    settings: ClientSettings,
+    // Bestimmt die Angebote des Zaubertischs und wird nach jeder Verzauberung neu gewürfelt
+    enchantment_seed: i32,
+}
+
+impl Player {
+    /// Smoothed round-trip time of the keep-alives in milliseconds, 0 until the client answered the first one.
+    /// Plugins read it through `player_ping` in the host API, scripts through `server.player_ping`.
+    pub fn ping(&self) -> u32 {
+        self.keep_alive.ping_ms
+    }
+}
+
+// Block, auf dem gespawnt wird, und Blickrichtung (Yaw)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SpawnPoint {
+    position: (i32, i32, i32),
+    angle: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Mob {
+    id: Uuid,
+    entity_id: i32,
+    // Namespaced Entity-Typ, z.B. minecraft:zombie
+    mob_type: String,
+    position: (f64, f64, f64),
+    yaw: f32,
+    pitch: f32,
+    // Nach Rückstoß; bewegt wird der Mob in motion::tick, ein Pfeil in projectile::tick
+    velocity: motion::Velocity,
+    health: f32,
+    effects: Effects,
+    tags: Vec<String>,
+    custom_name: Option<Json>,
+    custom_name_visible: bool,
+    // Mitfahrende Spieler samt Entity-ID; der erste lenkt
+    passengers: Vec<(Uuid, i32)>,
+    // Nur bei Pfeilen gesetzt
+    projectile: Option<projectile::Arrow>,
+    // Geworfene Tränke und die Wolken von Verweiltränken
+    potion: Option<potion::Thrown>,
+    cloud: Option<potion::Cloud>,
+    // Verbleibende Ticks bei gezündetem TNT
+    fuse: Option<u32>,
+    // Wie bei Spielern; brennen können nur lebende Mobs
+    fire_ticks: i32,
+    // Angebote von Dorfbewohnern und fahrenden Händlern, erst beim ersten Handel ausgewürfelt
+    merchant: Option<merchant::Merchant>,
+}
+
+impl Mob {
+    fn new(mob_type: &str, position: (f64, f64, f64)) -> Mob {
+        Mob {
+            id: Uuid::new_v4(),
+            entity_id: entity::next_id(),
+            mob_type: mob_type.to_string(),
+            position,
+            yaw: 0.0,
+            pitch: 0.0,
+            velocity: (0.0, 0.0, 0.0),
+            health: entity::max_health(mob_type),
+            effects: Effects::new(),
+            tags: Vec::new(),
+            custom_name: None,
+            custom_name_visible: false,
+            passengers: Vec::new(),
+            projectile: None,
+            potion: None,
+            cloud: None,
+            fuse: None,
+            fire_ticks: 0,
+            merchant: None,
+        }
+    }
+
+    // Eigener Name oder der Typname, etwa für Selektoren und Befehlsantworten
+    fn display_name(&self) -> String {
+        match &self.custom_name {
+            Some(Json::String(text)) => text.clone(),
+            Some(component) => component.get("text").and_then(Json::as_str).unwrap_or_default().to_string(),
+            None => entity::display_name(&self.mob_type),
+        }
+    }
+}
+
+struct World {
+    dimension: Dimension,
+    chunks: HashMap<(i32, i32), Chunk>,
+    chunk_pool: ChunkGenPool,
+    // Gespeicherte Chunks, die die Chunk-Worker gerade lesen
+    loading: HashMap<(i32, i32), ChunkFuture>,
+    light: LightEngine,
+    seed: u64,
+    mobs: Vec<Mob>,
+    items: Vec<ItemEntity>,
+    orbs: Vec<experience::Orb>,
+    entities: SpatialIndex,
+    time: WorldTime,
+    weather: Weather,
+    rules: GameRules,
+    spawn: SpawnPoint,
+    border: WorldBorder,
+    // Von /schedule geplante Funktionsaufrufe
+    scheduled_functions: Vec<ScheduledFunction>,
+    // Etwa für Feuer, das sich in festen Abständen ausbreitet
+    block_ticks: BlockTicks,
+    // Was Kolben gerade verschieben, bis es an seinem Ziel ankommt; wird wie block_ticks nicht gespeichert
+    moving_blocks: HashMap<(i32, i32, i32), piston::Moving>,
+    // Aus level.json, für neue Welten aus server.properties
+    difficulty: Difficulty,
+    settings: WorldSettings,
+    storage: WorldStorage,
+    // Seit dem letzten Speichern geänderte oder neu generierte Chunks
+    dirty_chunks: HashSet<(i32, i32)>,
+    // In diesem Tick geänderte Blöcke, die flush_block_changes am Ende des Ticks nach Sektionen gebündelt verschickt
+    changed_blocks: HashSet<(i32, i32, i32)>,
+    // Spieler, deren Client den Chunk gerade geladen hat
+    chunk_viewers: HashMap<(i32, i32), HashSet<Uuid>>,
+    // Wie changed_blocks erst am Ende des Ticks verschickt
+    world_events: Vec<QueuedEvent>,
+    // Nur im Ende
+    dragon_fight: Option<DragonFight>,
+    // Betrachter je Truhe, wie zuletzt an die Clients geschickt (für den Deckel)
+    chest_viewers: HashMap<(i32, i32, i32), u8>,
+    // Öfen, die brennen, abkühlen oder gerade neuen Inhalt bekommen haben; nur sie tickt furnace::tick
+    furnaces: HashSet<(i32, i32, i32)>,
+    // Für die Meldung der Schlafenden über der Schnellleiste
+    sleep_status: SleepStatus,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct WorldTime {
+    age: i64,
+    time_of_day: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Weather {
+    raining: bool,
+    thundering: bool,
+    rain_time: i32,
+    thunder_time: i32,
+}
+
+impl Weather {
+    fn new() -> Weather {
+        let mut rng = rand::thread_rng();
+        Weather {
+            raining: false,
+            thundering: false,
+            rain_time: rng.gen_range(12000..180000),
+            thunder_time: rng.gen_range(12000..180000),
+        }
+    }
+
+    // Für /weather: setzt Regen und Gewitter für duration Ticks bzw. eine zufällige Dauer wie bei Vanilla
+    fn set(&mut self, raining: bool, thundering: bool, duration: Option<i32>) {
+        let mut rng = rand::thread_rng();
+        let duration = duration.unwrap_or_else(|| match (raining, thundering) {
+            (false, _) => rng.gen_range(12000..180000),
+            (true, false) => rng.gen_range(12000..24000),
+            (true, true) => rng.gen_range(3600..15600),
+        });
+        self.raining = raining;
+        self.thundering = thundering;
+        self.rain_time = duration;
+        self.thunder_time = duration;
+    }
+
+    // Zählt die Wetterdauern herunter, gibt true zurück wenn sich Regen oder Gewitter ändert
+    fn tick(&mut self) -> bool {
+        let mut rng = rand::thread_rng();
+        let mut changed = false;
+        self.thunder_time -= 1;
+        if self.thunder_time <= 0 {
+            self.thundering = !self.thundering;
+            self.thunder_time = if self.thundering { rng.gen_range(3600..15600) } else { rng.gen_range(12000..180000) };
+            changed |= self.raining;
+        }
+        self.rain_time -= 1;
+        if self.rain_time <= 0 {
+            self.raining = !self.raining;
+            self.rain_time = if self.raining { rng.gen_range(12000..24000) } else { rng.gen_range(12000..180000) };
+            changed = true;
+        }
+        changed
+    }
+}
+
+const SPAWN_CHUNK_RADIUS: i32 = 7;
+
+impl World {
+    fn new(seed: u64, storage: WorldStorage, dimension: Dimension) -> World {
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(2);
+        World {
+            dimension,
+            chunks: HashMap::new(),
+            chunk_pool: ChunkGenPool::new(threads, seed, dimension),
+            loading: HashMap::new(),
+            light: LightEngine::new(dimension),
+            seed,
+            mobs: Vec::new(),
+            items: Vec::new(),
+            orbs: Vec::new(),
+            entities: SpatialIndex::new(),
+            time: WorldTime::default(),
+            weather: Weather::new(),
+            rules: GameRules::new(),
+            spawn: SpawnPoint { position: (0, 0, 0), angle: 0.0 },
+            border: WorldBorder::new(),
+            scheduled_functions: Vec::new(),
+            block_ticks: BlockTicks::default(),
+            moving_blocks: HashMap::new(),
+            difficulty: Difficulty::Easy,
+            settings: WorldSettings::default(),
+            storage,
+            dirty_chunks: HashSet::new(),
+            changed_blocks: HashSet::new(),
+            chunk_viewers: HashMap::new(),
+            world_events: Vec::new(),
+            dragon_fight: None,
+            chest_viewers: HashMap::new(),
+            furnaces: HashSet::new(),
+            sleep_status: SleepStatus::default(),
+        }
+    }
+
+    fn generate(&mut self) {
+        info!("Generiere {} mit Seed {}...", self.dimension.name(), self.seed);
+        let started = std::time::Instant::now();
+        self.request_spawn_area();
+        let loading: Vec<(i32, i32)> = self.loading.keys().copied().collect();
+        for (chunk_x, chunk_z) in loading {
+            self.load_chunk_now(chunk_x, chunk_z);
+        }
+        while let Some(chunk) = self.chunk_pool.wait_next() {
+            self.insert_generated_chunk(chunk);
+        }
+        info!("Welt generiert: {} Chunks in {:?}", self.chunks.len(), started.elapsed());
+    }
+
+    // Fordert die Chunks um den Ursprung an, ohne auf sie zu warten; fertig sind sie, sobald spawn_area_ready gilt
+    fn request_spawn_area(&mut self) {
+        for chunk_x in -SPAWN_CHUNK_RADIUS..SPAWN_CHUNK_RADIUS {
+            for chunk_z in -SPAWN_CHUNK_RADIUS..SPAWN_CHUNK_RADIUS {
+                self.request_chunk(chunk_x, chunk_z);
+            }
+        }
+    }
+
+    fn spawn_area_ready(&self) -> bool {
+        (-SPAWN_CHUNK_RADIUS..SPAWN_CHUNK_RADIUS).all(|x| (-SPAWN_CHUNK_RADIUS..SPAWN_CHUNK_RADIUS).all(|z| self.chunks.contains_key(&(x, z))))
+    }
+
+    // Lässt einen gespeicherten Chunk lesen oder fordert ihn beim Generator-Pool an, falls er noch nicht existiert
+    fn request_chunk(&mut self, chunk_x: i32, chunk_z: i32) {
+        if self.chunks.contains_key(&(chunk_x, chunk_z)) || self.loading.contains_key(&(chunk_x, chunk_z)) {
+            return;
+        }
+        if self.storage.has_chunk(chunk_x, chunk_z) {
+            self.loading.insert((chunk_x, chunk_z), self.storage.load_chunk(chunk_x, chunk_z));
+            return;
+        }
+        self.chunk_pool.request(chunk_x, chunk_z);
+    }
+
+    // Wie request_chunk, wartet aber, bis der Chunk gelesen oder generiert ist
+    fn load_chunk_now(&mut self, chunk_x: i32, chunk_z: i32) {
+        self.request_chunk(chunk_x, chunk_z);
+        if let Some(future) = self.loading.remove(&(chunk_x, chunk_z)) {
+            self.insert_loaded_chunk(chunk_x, chunk_z, future.wait());
+        }
+        while !self.chunks.contains_key(&(chunk_x, chunk_z)) {
+            let Some(chunk) = self.chunk_pool.wait_next() else { break };
+            self.insert_generated_chunk(chunk);
+        }
+    }
+
+    // Übernimmt fertig gelesene und generierte Chunks
+    fn poll_chunks(&mut self) {
+        let mut loaded = Vec::new();
+        self.loading.retain(|&coords, future| match future.poll() {
+            Some(result) => {
+                loaded.push((coords, result));
+                false
+            }
+            None => true,
+        });
+        for ((chunk_x, chunk_z), result) in loaded {
+            self.insert_loaded_chunk(chunk_x, chunk_z, result);
+        }
+        for chunk in self.chunk_pool.poll() {
+            self.insert_generated_chunk(chunk);
+        }
+    }
+
+    fn insert_loaded_chunk(&mut self, chunk_x: i32, chunk_z: i32, result: Result<Chunk, String>) {
+        match result {
+            Ok(chunk) => {
+                self.furnaces.extend(furnace::active_in(&chunk));
+                self.chunks.insert((chunk_x, chunk_z), chunk);
+                self.light.chunk_added((chunk_x, chunk_z));
+            }
+            // Neu generieren statt den Spieler vor einem Loch stehen zu lassen
+            Err(e) => {
+                warn!("{}", e);
+                self.chunk_pool.request(chunk_x, chunk_z);
+            }
+        }
+    }
+
+    fn get_block(&self, x: i32, y: i32, z: i32) -> u16 {
+        match self.chunks.get(&(x >> 4, z >> 4)) {
+            Some(chunk) => chunk.get_block((x & 15) as usize, y, (z & 15) as usize),
+            None => block::AIR,
+        }
+    }
+
+    // Weltspawn mit zufälligem Versatz bis spawnRadius, dann auf dem obersten Block der Säule
+    fn spawn_position(&mut self) -> (f64, f64, f64) {
+        let (x, y, z) = self.spawn.position;
+        let radius = self.rules.get_int(gamerule::SPAWN_RADIUS).max(0);
+        if radius == 0 {
+            return (x as f64 + 0.5, y as f64, z as f64 + 0.5);
+        }
+        let mut rng = rand::thread_rng();
+        let (x, z) = (x + rng.gen_range(-radius..=radius), z + rng.gen_range(-radius..=radius));
+        self.load_chunk_now(x >> 4, z >> 4);
+        (x as f64 + 0.5, self.highest_block_y(x, z) as f64 + 1.0, z as f64 + 0.5)
+    }
+
+    fn highest_block_y(&self, x: i32, z: i32) -> i32 {
+        self.surface_y(Heightmap::WorldSurface, x, z).map_or(chunk::MIN_Y, |y| (y - 1).max(chunk::MIN_Y))
+    }
+
+    // Biom (ohne Namespace) an einer Position; ungeladene Chunks fragt es beim Generator an
+    fn get_biome(&self, x: i32, y: i32, z: i32) -> &'static str {
+        match self.chunks.get(&(x >> 4, z >> 4)) {
+            Some(chunk) => worldgen::biome_name(chunk.get_biome((x & 15) as usize, y, (z & 15) as usize)),
+            None => worldgen::biome_at(self.dimension, x, z),
+        }
+    }
+
+    // y über dem obersten Block der Säule laut Heightmap; None, solange der Chunk nicht geladen ist
+    fn surface_y(&self, kind: Heightmap, x: i32, z: i32) -> Option<i32> {
+        self.chunks.get(&(x >> 4, z >> 4)).map(|chunk| chunk.height(kind, (x & 15) as usize, (z & 15) as usize))
+    }
+
+    // Wie bei Vanilla wird es ab 17 Blöcken über dem Meeresspiegel mit jedem Block etwas kälter; unter 0.15 schneit es
+    // statt zu regnen und Wasser friert
+    fn is_cold(&self, (x, y, z): (i32, i32, i32)) -> bool {
+        let temperature = worldgen::temperature(self.get_biome(x, y, z));
+        let height = (y - SNOW_LINE).max(0) as f32;
+        temperature - height * 0.05 / 40.0 < 0.15
+    }
+
+    // Regen oder Schnee erreicht den Block, wenn nichts Bewegungshemmendes darüber liegt und das Biom nicht trocken ist
+    fn precipitation_reaches(&self, (x, y, z): (i32, i32, i32)) -> bool {
+        self.dimension.has_weather()
+            && self.weather.raining
+            && worldgen::has_precipitation(self.get_biome(x, y, z))
+            && self.surface_y(Heightmap::MotionBlocking, x, z).is_some_and(|top| y >= top)
+    }
+
+    fn rains_at(&self, position: (i32, i32, i32)) -> bool {
+        self.precipitation_reaches(position) && !self.is_cold(position)
+    }
+
+    fn snows_at(&self, position: (i32, i32, i32)) -> bool {
+        self.precipitation_reaches(position) && self.is_cold(position)
+    }
+
+    // Helligkeit wie getRawBrightness bei Vanilla: das hellere aus Himmels- und Blocklicht, ohne Verdunkelung durch die
+    // Tageszeit; 0, solange der Chunk noch nicht gerechnet ist
+    fn light_level(&self, position: (i32, i32, i32)) -> u8 {
+        self.light.level_at(self.dimension, position).map_or(0, |(sky, block)| sky.max(block))
+    }
+
+    // Ab world.time.age gezählt; ein schon geplanter Tick bleibt, wie er ist
+    fn schedule_block_tick(&mut self, position: (i32, i32, i32), delay: i64) {
+        self.block_ticks.schedule(self.time.age + delay, position);
+    }
+
+    // Ein Welt-Tick: Zeit, Wetter, Weltgrenze und Entities. Gibt true zurück, wenn sich das Wetter geändert hat
+    fn tick(&mut self) -> bool {
+        self.time.age += 1;
+        self.border.tick();
+        if self.rules.get_bool(gamerule::DO_DAYLIGHT_CYCLE) {
+            self.time.time_of_day = (self.time.time_of_day + 1) % 24000;
+        }
+        let weather_changed = self.dimension.has_weather() && self.rules.get_bool(gamerule::DO_WEATHER_CYCLE) && self.weather.tick();
+        self.tick_mobs();
+        weather_changed
+    }
+
+    // Einfache Schwerkraft: Mobs fallen bis auf den nächsten festen Block, fliegende schweben. Mobs mit
+    // Geschwindigkeit bewegt motion::tick, Fahrzeuge mit Fahrer dessen Client
+    fn tick_mobs(&mut self) {
+        let mut mobs = std::mem::take(&mut self.mobs);
+        for mob in mobs.iter_mut().filter(|m| entity::has_gravity(&m.mob_type) && m.velocity == (0.0, 0.0, 0.0) && m.passengers.is_empty() && m.projectile.is_none() && m.potion.is_none()) {
+            let (x, y, z) = mob.position;
+            let (bx, by, bz) = (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+            let new_y = if self.get_block(bx, by, bz) != block::AIR {
+                by as f64 + 1.0
+            } else if by > chunk::MIN_Y && self.get_block(bx, by - 1, bz) == block::AIR {
+                y - 1.0
+            } else {
+                y
+            };
+            if new_y != y {
+                mob.position.1 = new_y;
+                self.entities.update(mob.id, mob.position);
+            }
+        }
+        self.mobs = mobs;
+    }
+
+    fn spawn_mob(&mut self, mob: Mob) {
+        if (self.difficulty == Difficulty::Peaceful && entity::is_hostile(&mob.mob_type)) || !self.settings.allows_spawn(&mob.mob_type) {
+            return;
+        }
+        self.entities.update(mob.id, mob.position);
+        self.mobs.push(mob);
+    }
+
+    // Gibt false zurück, wenn der Chunk nicht geladen ist oder y außerhalb der Welt liegt
+    fn set_block(&mut self, x: i32, y: i32, z: i32, state: u16) -> bool {
+        if !(chunk::MIN_Y..chunk::MIN_Y + chunk::HEIGHT).contains(&y) {
+            return false;
+        }
+        match self.chunks.get_mut(&(x >> 4, z >> 4)) {
+            Some(chunk) => {
+                let previous = chunk.get_block((x & 15) as usize, y, (z & 15) as usize);
+                chunk.set_block((x & 15) as usize, y, (z & 15) as usize, state);
+                // Die Block-Entity bleibt nur bei einem anderen Zustand desselben Blocks, etwa einem gedrehten Trichter
+                if block::name(previous) != block::name(state) {
+                    chunk.block_entities.remove(&(x, y, z));
+                    if let Some(entity) = BlockEntity::for_block(state) {
+                        chunk.block_entities.insert((x, y, z), entity);
+                    }
+                }
+                self.dirty_chunks.insert((x >> 4, z >> 4));
+                self.light.block_changed((x, y, z), previous, state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Wie set_block, dazu erfahren alle Spieler, die den Chunk geladen haben, am Ende des Ticks von der Änderung
+    fn set_block_and_notify(&mut self, (x, y, z): (i32, i32, i32), state: u16) -> bool {
+        let old = self.get_block(x, y, z);
+        let changed = self.set_block(x, y, z, state);
+        if changed {
+            self.changed_blocks.insert((x, y, z));
+            door::block_changed(self, (x, y, z), old, state);
+            leaves::block_changed(self, (x, y, z), old, state);
+            piston::block_changed(self, (x, y, z), old, state);
+            redstone::block_changed(self, (x, y, z), old, state);
+            sleep::block_changed(self, (x, y, z), old, state);
+            snow::block_changed(self, (x, y, z), old, state);
+            tripwire::block_changed(self, (x, y, z), old, state);
+        }
+        changed
+    }
+
+    // World Event für alle, die den Chunk geladen haben, bis auf except
+    fn play_event(&mut self, event: WorldEvent, position: (i32, i32, i32), except: Option<Uuid>) {
+        self.world_events.push(QueuedEvent { event, position, except });
+    }
+
+    // Nach dem Verlassen der Welt oder des Servers
+    fn forget_viewer(&mut self, uuid: Uuid) {
+        self.chunk_viewers.retain(|_, viewers| {
+            viewers.remove(&uuid);
+            !viewers.is_empty()
+        });
+    }
+
+    fn block_entity(&self, (x, y, z): (i32, i32, i32)) -> Option<&BlockEntity> {
+        self.chunks.get(&(x >> 4, z >> 4))?.block_entities.get(&(x, y, z))
+    }
+
+    // Wer den Inhalt ändert, markiert den Chunk selbst als geändert
+    fn block_entity_mut(&mut self, (x, y, z): (i32, i32, i32)) -> Option<&mut BlockEntity> {
+        self.chunks.get_mut(&(x >> 4, z >> 4))?.block_entities.get_mut(&(x, y, z))
+    }
+
+    // Beendet Generator-, Licht- und Chunk-I/O-Threads einer entladenen Welt; beim nächsten Laden entsteht eine neue
+    fn shut_down(&mut self) {
+        self.chunk_pool.shut_down();
+        self.light.shut_down();
+        self.storage.shut_down();
+    }
+
+    fn insert_generated_chunk(&mut self, chunk: Chunk) {
+        let coords = (chunk.x, chunk.z);
+        self.chunks.insert(coords, chunk);
+        self.dirty_chunks.insert(coords);
+        self.light.chunk_added(coords);
+    }
+}
+
+impl Server {
+    fn tick(&self) {
+        let mut players = self.players.lock().unwrap();
+        for world in dimension::worlds(self) {
+            let mut world = world.lock().unwrap();
+            let _profile = self.profiler.world("world tick", world.dimension);
+            if world.difficulty == Difficulty::Peaceful {
+                remove_hostile_mobs(&players, &mut world);
+            }
+            hopper::tick(&players, &mut world);
+            furnace::tick(&mut world, &self.datapacks.lock().unwrap().registries.recipes);
+            spawner::tick(&players, &mut world);
+            naturalspawn::tick(&self.config, &players, &mut world);
+            piston::tick(&mut world);
+            pressureplate::tick(&players, &mut world);
+            window::tick(&mut players, &mut world);
+            map::tick(&mut self.maps.lock().unwrap(), &players, &world);
+            if world.tick() {
+                let weather = world.weather;
+                info!("Wetter geändert: Regen={}, Gewitter={}", weather.raining, weather.thundering);
+                for player in players.iter().filter(|p| p.dimension == world.dimension) {
+                    let _ = send_weather(&player.connection, &weather);
+                }
+            }
+        }
+    }
+
+    fn broadcast_weather(&self, dimension: Dimension) {
+        let players = self.players.lock().unwrap();
+        let weather = dimension::world_of(self, dimension).lock().unwrap().weather;
+        for player in players.iter().filter(|p| p.dimension == dimension) {
+            let _ = send_weather(&player.connection, &weather);
+        }
+    }
+
+    // Der Client zeigt den Todesbildschirm nur, solange doImmediateRespawn aus ist
+    fn broadcast_immediate_respawn(&self, dimension: Dimension) {
+        let players = self.players.lock().unwrap();
+        let immediate = dimension::world_of(self, dimension).lock().unwrap().rules.get_bool(gamerule::DO_IMMEDIATE_RESPAWN);
+        for player in players.iter().filter(|p| p.dimension == dimension) {
+            let _ = send_game_event(&player.connection, GAME_EVENT_IMMEDIATE_RESPAWN, if immediate { 1.0 } else { 0.0 });
+        }
+    }
+
+    fn broadcast_time(&self, dimension: Dimension) {
+        let players = self.players.lock().unwrap();
+        let time = dimension::world_of(self, dimension).lock().unwrap().time;
+        for player in players.iter().filter(|p| p.dimension == dimension) {
+            let _ = send_time(&player.connection, &time);
+        }
+    }
+
+    // Jede Welt hat ihre eigene Uhr
+    fn broadcast_all_times(&self) {
+        let players = self.players.lock().unwrap();
+        for world in dimension::worlds(self) {
+            let world = world.lock().unwrap();
+            for player in players.iter().filter(|p| p.dimension == world.dimension) {
+                let _ = send_time(&player.connection, &world.time);
+            }
+        }
+    }
+
+    fn broadcast_spawn_position(&self, dimension: Dimension) {
+        let players = self.players.lock().unwrap();
+        let spawn = dimension::world_of(self, dimension).lock().unwrap().spawn;
+        for player in players.iter().filter(|p| p.dimension == dimension) {
+            let _ = send_spawn_position(&player.connection, spawn);
+        }
+    }
+
+    // Nur für online Spieler; Konsole und REST-API prüfen selbst nicht. Sperrt players
+    fn has_permission(&self, uuid: Uuid, node: &str) -> bool {
+        let Some(name) = self.players.lock().unwrap().iter().find(|p| p.uuid == uuid).map(|p| p.username.clone()) else { return false };
+        self.permissions.lock().unwrap().has(&name, Some(uuid), node)
+    }
+
+    // Nach /worldborder: send wählt das passende Paket, etwa worldborder::send_center
+    fn broadcast_world_border(&self, dimension: Dimension, send: fn(&Connection, &WorldBorder) -> Result<(), String>) {
+        let players = self.players.lock().unwrap();
+        let border = dimension::world_of(self, dimension).lock().unwrap().border;
+        for player in players.iter().filter(|p| p.dimension == dimension) {
+            let _ = send(&player.connection, &border);
+        }
+    }
+
+    fn broadcast_difficulty(&self, dimension: Dimension) {
+        let players = self.players.lock().unwrap();
+        let difficulty = dimension::world_of(self, dimension).lock().unwrap().difficulty;
+        for player in players.iter().filter(|p| p.dimension == dimension) {
+            let _ = difficulty::send(&player.connection, difficulty);
+        }
+    }
+
+    // Stellt ein Event allen Listenern zu (Kern, Plugins, Skripte). Darf nur ohne gehaltene Sperren aufgerufen werden
+    fn fire_event(&self, event: &mut dyn Event) {
+        self.events.fire(self, event);
+    }
+
+    // Entlädt Skripte und Plugins, trennt alle Spieler und beendet den Prozess
+    fn shutdown(&self) -> ! {
+        info!("Server wird gestoppt...");
+        systemd::stopping();
+        self.close("");
+        console::LOG.release_stdout();
+        std::process::exit(0);
+    }
+
+    // Alles vor dem Beenden oder Neustarten; ist kick_message leer, werden die Verbindungen nur geschlossen
+    fn close(&self, kick_message: &str) {
+        self.watchdog.disarm();
+        ScriptEngine::unload_all(self);
+        WasmPluginManager::unload_all(self);
+        PluginManager::unload_all(self);
+        storage::save_all(self, true);
+        for player in self.players.lock().unwrap().iter() {
+            if !kick_message.is_empty() {
+                let _ = send_disconnect(&player.connection, kick_message);
+            }
+            player.connection.close();
+        }
+    }
+
+    // Sendet Keep-Alives und trennt Clients, die zu lange nicht geantwortet haben. Die Tab-Liste bekommt dabei die
+    // Antwortzeiten der letzten Runde
+    fn keep_alive(&self) {
+        let mut players = self.players.lock().unwrap();
+        let everyone: Vec<&Player> = players.iter().collect();
+        for player in players.iter() {
+            let _ = tablist::send_latency(&player.connection, &everyone);
+        }
+        for player in players.iter_mut() {
+            if let Some((_, sent_at)) = player.keep_alive.pending {
+                if sent_at.elapsed() > Duration::from_secs(self.config.keep_alive_timeout_secs) {
+                    warn!("{} hat nicht auf Keep-Alive geantwortet, trenne Verbindung.", player.username);
+                    player.connection.close();
+                }
+                continue;
+            }
+            let id = rand::thread_rng().gen::<i64>();
+            if player.connection.send(clientbound::KEEP_ALIVE, &id.to_be_bytes()).is_ok() {
+                player.keep_alive.pending = Some((id, Instant::now()));
+            }
+        }
+    }
+}
+
+// Liest den PROXY-Header und drosselt anschließend anhand der echten Client-IP
+fn accept_proxied(mut stream: TcpStream, proxy_addr: SocketAddr, server: Arc<Server>, throttle: &Mutex<ConnectionThrottle>) {
+    let login_timeout = Duration::from_secs(server.config.login_timeout_secs.max(1));
+    if let Err(e) = stream.set_read_timeout(Some(login_timeout)) {
+        warn!("Konnte Timeout für {} nicht setzen: {}", proxy_addr, e);
+        return;
+    }
+    let peer_addr = match proxy::read_proxy_header(&mut stream, proxy_addr) {
+        Ok(addr) => addr,
+        Err(e) => {
+            warn!("Verbindung von {} abgelehnt: {}", proxy_addr, e);
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+    };
+    let pending = match throttle.lock().unwrap().try_accept(peer_addr.ip()) {
+        Ok(guard) => guard,
+        Err(e) => {
+            warn!("Verbindung abgelehnt: {}", e);
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+    };
+    handle_client(stream, peer_addr, server, pending);
+}
+
+// Platz, den ein einloggender Spieler belegt, bis er in players steht; bricht der Login ab, wird er wieder frei
+struct JoinSlot<'a> {
+    joining: &'a AtomicUsize,
+}
+
+impl Drop for JoinSlot<'_> {
+    fn drop(&mut self) {
+        self.joining.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn handle_client(mut stream: TcpStream, peer_addr: SocketAddr, server: Arc<Server>, pending: PendingGuard) {
+    let _span = span!("connection", addr = peer_addr);
+    info!("Neue Verbindung von: {}", peer_addr);
+    // Nicht eingeloggte Verbindungen dürfen den Thread nicht ewig blockieren
+    let login_timeout = Duration::from_secs(server.config.login_timeout_secs.max(1));
+    if let Err(e) = stream.set_read_timeout(Some(login_timeout)) {
+        warn!("Konnte Timeout für {} nicht setzen: {}", peer_addr, e);
+        return;
+    }
+    if let Err(e) = stream.set_nodelay(server.config.tcp_no_delay) {
+        warn!("Konnte TCP_NODELAY für {} nicht setzen: {}", peer_addr, e);
+    }
+
+    let handshake = match handle_handshake(&mut stream) {
+        Ok(handshake) => handshake,
+        Err(e) => {
+            warn!("Handshake fehlgeschlagen: {}", e);
+            return;
+        }
+    };
+
+    let version = ProtocolVersion::from_protocol(handshake.protocol_version);
+    if handshake.next_state == 1 {
+        if let Err(e) = handle_status(&mut stream, &server, peer_addr, version) {
+            debug!("Statusabfrage von {} abgebrochen: {}", peer_addr, e);
+        }
+        return;
+    }
+
+    let Some(version) = version else {
+        // Login Start zuerst lesen: schließt der Server mit ungelesenen Daten, setzt TCP die Verbindung zurück und der
+        // Client sieht statt der Meldung nur einen Verbindungsabbruch
+        let username = handle_login(&mut stream).unwrap_or_default();
+        warn!("{} ({}) verwendet das nicht unterstützte Protokoll {}", peer_addr, username, handshake.protocol_version);
+        let reason = if handshake.protocol_version < SUPPORTED_VERSIONS[0].protocol() {
+            format!("Outdated client! Please use {}", ProtocolVersion::range())
+        } else {
+            format!("Outdated server! I'm still on {}", ProtocolVersion::range())
+        };
+        let _ = send_login_disconnect(&mut stream, &reason);
+        return;
+    };
+    debug!("Client-Version: {}", version.name());
+
+    let mut forwarded = if server.config.bungeecord && !server.config.velocity_forwarding {
+        match proxy::parse_bungeecord(&handshake.server_address) {
+            Ok(info) => {
+                info!("BungeeCord-Weiterleitung: {} ist {} ({}) über {}", peer_addr, info.address, info.uuid, info.host);
+                Some(info)
+            }
+            Err(e) => {
+                warn!("Direkte Verbindung von {} abgelehnt: {}", peer_addr, e);
+                let _ = send_login_disconnect(&mut stream, "If you wish to use IP forwarding, please enable it in your BungeeCord config as well!");
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut username = match handle_login(&mut stream) {
+        Ok(username) => {
+            info!("Login erfolgreich für: {}", username);
+            username
+        }
+        Err(e) => {
+            warn!("Login fehlgeschlagen: {}", e);
+            return;
+        }
+    };
+
+    if server.config.velocity_forwarding {
+        match velocity_login(&mut stream, &server.config.velocity_secret) {
+            Ok(info) => {
+                info!("Velocity-Weiterleitung: {} ist {} ({})", peer_addr, info.address, info.uuid);
+                if let Some(name) = &info.username {
+                    username = name.clone();
+                }
+                forwarded = Some(info);
+            }
+            Err(e) => {
+                warn!("Velocity-Login von {} abgelehnt: {}", peer_addr, e);
+                let _ = send_login_disconnect(&mut stream, "This server requires you to connect with Velocity.");
+                return;
+            }
+        }
+    }
+
+    let (uuid, properties, address) = match forwarded {
+        Some(info) => (info.uuid, info.properties, SocketAddr::new(info.address, peer_addr.port())),
+        None => (offline_uuid(&username), Vec::new(), peer_addr),
+    };
+    let _player_span = span!("player", player = username);
+    // Erst hier ist hinter einem Proxy die echte IP bekannt
+    if let Err(e) = server.logins.lock().unwrap().try_login(address.ip()) {
+        warn!("Login von {} abgelehnt: {}", username, e);
+        let _ = send_login_disconnect(&mut stream, LOGIN_THROTTLED_MESSAGE);
+        return;
+    }
+    if let Some(ban) = server.bans.lock().unwrap().find(&username, uuid) {
+        warn!("{} ist gebannt: {}", username, ban.reason);
+        let _ = send_login_disconnect(&mut stream, &banlist::ban_message(&ban.reason));
+        return;
+    }
+    // Prüfen und Reservieren in einem Schritt, sonst kommen gleichzeitige Logins alle durch
+    let slot = {
+        let players = server.players.lock().unwrap();
+        let full = players.len() + server.joining.load(Ordering::SeqCst) >= server.config.max_players;
+        if full && !server.permissions.lock().unwrap().has(&username, Some(uuid), permission::JOIN_FULL) {
+            None
+        } else {
+            server.joining.fetch_add(1, Ordering::SeqCst);
+            Some(JoinSlot { joining: &server.joining })
+        }
+    };
+    let Some(slot) = slot else {
+        info!("{} abgewiesen, der Server ist voll", username);
+        let _ = send_login_disconnect(&mut stream, SERVER_FULL_MESSAGE);
+        return;
+    };
+    let compression = usize::try_from(server.config.network_compression_threshold).ok();
+    let connection = match Connection::new(&stream, address, version, compression) {
+        Ok(connection) => connection,
+        Err(e) => {
+            warn!("{}", e);
+            return;
+        }
+    };
+    let (position, yaw) = {
+        let mut world = server.world.lock().unwrap();
+        (world.spawn_position(), world.spawn.angle)
+    };
+    let mut player = Player {
+        uuid,
+        entity_id: entity::next_id(),
+        username: username.clone(),
+        position,
+        rotation: (yaw, 0.0),
+        health: damage::MAX_HEALTH,
+        absorption: 0.0,
+        hunger: Hunger::default(),
+        air: fluid::MAX_AIR,
+        fire_ticks: 0,
+        effects: Effects::new(),
+        tags: Vec::new(),
+        experience: Experience::default(),
+        game_mode: GameMode::Survival,
+        dimension: Dimension::Overworld,
+        pose: PoseState::default(),
+        glide: elytra::Glide::default(),
+        drawing: None,
+        blocking: None,
+        properties,
+        connection,
+        keep_alive: KeepAlive::default(),
+        last_input: Instant::now(),
+        teleport: Teleport::default(),
+        movement: MovementState::new(position),
+        combat: CombatState::default(),
+        inventory: Inventory::new(),
+        ender_chest: vec![ItemStack::empty(); window::ENDER_CHEST_SLOTS],
+        window: None,
+        window_id: 0,
+        carried: ItemStack::empty(),
+        drag: click::Drag::default(),
+        brand: None,
+        channels: HashSet::new(),
+        spawn_point: None,
+        sleep_ticks: 0,
+        recipe_book: recipebook::RecipeBook::default(),
+        reply_to: None,
+        ignored: HashSet::new(),
+        chat: securechat::ChatState::default(),
+        settings: ClientSettings::default(),
+        enchantment_seed: rand::random(),
+    };
+
+    if let Some(threshold) = compression {
+        if send_packet(&mut stream, clientbound::SET_COMPRESSION, &write_varint_to_vec(threshold as i32)).is_err() {
+            warn!("Fehler beim Aktivieren der Kompression für {}", username);
+            return;
+        }
+    }
+
+    if send_login_success(&mut stream, &player).is_err() {
+        warn!("Fehler beim Senden des Login-Erfolgs an {}", username);
+        return;
+    }
+
+    if let Err(e) = configuration::configure(&mut stream, &mut player) {
+        warn!("Konfiguration von {} fehlgeschlagen: {}", username, e);
+        return;
+    }
+
+    if send_join_game(&mut stream, &player, &server.world.lock().unwrap(), &server.config).is_err() {
+        warn!("Fehler beim Senden des Beitritts an {}", username);
+        return;
+    }
+
+    if let Err(e) = channel::send_server_channels(&server, &player.connection) {
+        warn!("Fehler beim Senden der Plugin-Kanäle an {}: {}", username, e);
+        return;
+    }
+
+    if let Err(e) = securechat::send_server_data(&server, &player.connection) {
+        warn!("Fehler beim Senden der Serverdaten an {}: {}", username, e);
+        return;
+    }
+
+    if let Some(pack) = &server.resource_pack {
+        if let Err(e) = pack.send(&player.connection) {
+            warn!("Fehler beim Senden des Ressourcenpakets an {}: {}", username, e);
+            return;
+        }
+    }
+
+    if let Err(e) = send_spawn_position(&player.connection, server.world.lock().unwrap().spawn) {
+        warn!("Fehler beim Senden des Spawnpunkts an {}: {}", username, e);
+        return;
+    }
+
+    if let Err(e) = worldborder::send_init(&player.connection, &server.world.lock().unwrap().border) {
+        warn!("Fehler beim Senden der Weltgrenze an {}: {}", username, e);
+        return;
+    }
+
+    if let Err(e) = difficulty::send(&player.connection, server.world.lock().unwrap().difficulty) {
+        warn!("Fehler beim Senden des Schwierigkeitsgrads an {}: {}", username, e);
+        return;
+    }
+
+    if let Err(e) = gamemode::send_abilities(&player.connection, player.game_mode) {
+        warn!("Fehler beim Senden der Fähigkeiten an {}: {}", username, e);
+        return;
+    }
+
+    if let Err(e) = player.inventory.send_all(&player.connection) {
+        warn!("Fehler beim Senden des Inventars an {}: {}", username, e);
+        return;
+    }
+
+    if let Err(e) = server.scoreboard.lock().unwrap().send_all(&player.connection) {
+        warn!("Fehler beim Senden der Anzeigetafel an {}: {}", username, e);
+        return;
+    }
+
+    let sent = recipebook::send_recipes(&player.connection, &server.datapacks.lock().unwrap().registries.recipes).and_then(|_| recipebook::send_init(&player.connection, &player.recipe_book));
+    if let Err(e) = sent {
+        warn!("Fehler beim Senden der Rezepte an {}: {}", username, e);
+        return;
+    }
+
+    {
+        let world = server.world.lock().unwrap();
+        let spawned = world
+            .items
+            .iter()
+            .filter(|item| entity::in_view(&player, player.dimension, item.position))
+            .try_for_each(|item| item.send_spawn(&player.connection))
+            .and_then(|_| world.orbs.iter().filter(|orb| entity::in_view(&player, player.dimension, orb.position)).try_for_each(|orb| orb.send_spawn(&player.connection)))
+            .and_then(|_| world.mobs.iter().filter(|mob| entity::in_view(&player, player.dimension, mob.position)).try_for_each(|mob| entity::send_mob(&player.connection, mob)));
+        if let Err(e) = spawned {
+            warn!("Fehler beim Senden der Entities an {}: {}", username, e);
+            return;
+        }
+    }
+
+    drop(pending);
+    let _ = stream.set_read_timeout(None);
+    {
+        let mut players = server.players.lock().unwrap();
+        players.push(player.clone());
+        drop(slot);
+        // Der Neue bekommt die ganze Tab-Liste, alle anderen nur seinen Eintrag
+        let _ = tablist::send_add(&player.connection, &players.iter().collect::<Vec<_>>());
+        for other in players.iter().filter(|p| p.uuid != player.uuid) {
+            let _ = tablist::send_add(&other.connection, &[&player]);
+        }
+    }
+    debug!("Spielerliste: {:?}", server.players.lock().unwrap());
+    {
+        let mut world = server.world.lock().unwrap();
+        track_player(&mut world, player.uuid, player.game_mode, player.position);
+        let nearby = world.entities.query_radius(player.position, 16.0).iter().filter(|&&id| id != player.uuid).count();
+        debug!("Entities in der Nähe von {}: {}", username, nearby);
+    }
+    // Bis zur Bestätigung verwirft der Server Bewegungen, die Bewegungsprüfung beginnt also an einer bekannten Position
+    if let Err(e) = teleport_player(&server, player.uuid, player.position, player.rotation) {
+        warn!("Fehler beim Senden der Position an {}: {}", username, e);
+    }
+    let mut event = PlayerJoinEvent { player: username.clone(), message: server.config.join_message.replace("{player}", &username) };
+    server.fire_event(&mut event);
+    broadcast_connection_message(&server, &event.message);
+
+    let mut sent_chunks = HashSet::new();
+    let mut center_chunk = None;
+    let mut streamed_dimension = player.dimension;
+    let mut limiter = PacketLimiter::new(&server.config);
+    let max_packet_length = server.config.max_packet_size.min(framing::MAX_PACKET_LENGTH);
+
+    loop {
+        let view = server.players.lock().unwrap().iter().find(|p| p.uuid == player.uuid).map(|p| (p.position, p.settings.view_distance(), p.dimension));
+        if let Some((position, view_distance, dimension)) = view {
+            // Nach einem Dimensionswechsel hat der Client keine Chunks mehr
+            if dimension != streamed_dimension {
+                dimension::world_of(&server, streamed_dimension).lock().unwrap().forget_viewer(player.uuid);
+                sent_chunks.clear();
+                center_chunk = None;
+                streamed_dimension = dimension;
+            }
+            // Erst world, dann den Schreib-Stream sperren (siehe Sperr-Reihenfolge bei Server)
+            let mut world = dimension::world_of(&server, dimension).lock().unwrap();
+            if let Err(e) = stream_chunks(&player, &mut world, position, view_distance, &mut center_chunk, &mut sent_chunks) {
+                warn!("{}", e);
+                drop(world);
+                remove_player(&server, &player);
+                return;
+            }
+        }
+
+        let result = framing::read_frame(&mut stream, max_packet_length, player.connection.compression)
+            .map_err(|e| ServerError::Protocol(ProtocolError::from_stream(e)))
+            .and_then(|(buffer, size)| {
+                metrics::TOTAL.record_in(size);
+                let (id, cursor) = packets::split_id(buffer)?;
+                let packet_id = player.connection.version.serverbound(id);
+                player.connection.stats.record_packet_in(packet_id, size);
+                if let Err(reason) = limiter.record(PacketCategory::of(packet_id)) {
+                    debug!("Paket 0x{:02X} von {} überschreitet das Limit", packet_id, username);
+                    return Err(ServerError::Kick(reason));
+                }
+                handle_packet(&server, &player, packet_id, cursor)
+            });
+        let Err(e) = result else { continue };
+        match e.policy() {
+            Policy::Log => error!("Fehler beim Behandeln eines Pakets von {}: {}", username, e),
+            Policy::Close => {
+                info!("Client {} hat die Verbindung getrennt.", username);
+                remove_player(&server, &player);
+                return;
+            }
+            Policy::Disconnect(reason) => {
+                warn!("{} wurde getrennt: {}", username, e);
+                let _ = send_disconnect(&player.connection, &reason);
+                player.connection.close();
+                remove_player(&server, &player);
+                return;
+            }
+        }
+    }
+}
+
+fn remove_player(server: &Server, player: &Player) {
+    let removed = {
+        let mut players = server.players.lock().unwrap();
+        players.iter().position(|p| p.uuid == player.uuid).map(|i| {
+            let mut world = dimension::world_of(server, players[i].dimension).lock().unwrap();
+            sleep::wake(&mut players, &mut world, i, false);
+            drop(world);
+            players.remove(i)
+        })
+    };
+    if let Some(removed) = &removed {
+        storage::save_player(server, removed);
+        let players = server.players.lock().unwrap();
+        server.scoreboard.lock().unwrap().award(&players, &removed.username, &["minecraft.custom:minecraft.leave_game"], 1);
+    }
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        world.entities.remove(player.uuid);
+        world.forget_viewer(player.uuid);
+    }
+    server.edits.lock().unwrap().remove(Some(player.uuid));
+    let mut event = PlayerQuitEvent { player: player.username.clone(), message: server.config.quit_message.replace("{player}", &player.username) };
+    server.fire_event(&mut event);
+    if removed.is_some() {
+        for other in server.players.lock().unwrap().iter() {
+            let _ = tablist::send_remove(&other.connection, &[player.uuid]);
+        }
+        broadcast_connection_message(server, &event.message);
+    }
+}
+
+// Gelbe Systemnachricht beim Betreten und Verlassen; &-Farbcodes aus der Konfiguration oder von Plugins gelten
+fn broadcast_connection_message(server: &Server, message: &str) {
+    if message.is_empty() {
+        return;
+    }
+    let line = motd::legacy(message).color("yellow");
+    info!("{}", line.plain());
+    for player in server.players.lock().unwrap().iter() {
+        let _ = send_system_component(&player.connection, &line);
+    }
+}
+
+fn send_login_success(stream: &mut TcpStream, player: &Player) -> Result<(), String> {
+    let mut packet_data = vec![];
+    packet_data.extend(write_varint_to_vec(clientbound::LOGIN_SUCCESS)); // Packet ID für Login Success
+
+    packet_data.extend(write_uuid(&player.uuid));
+    debug!("Sende UUID: {}", player.uuid);
+
+    packet_data.extend(write_string_to_vec(&player.username));
+    debug!("Sende Benutzernamen: {}", player.username);
+
+    packet_data.extend(proxy::write_properties(&player.properties)); // Profil-Eigenschaften (Skin)
+    if player.connection.version.has_1_20_5_login() {
+        packet_data.push(1); // Strikte Fehlerbehandlung
+    }
+
+    let packet = framing::encode_frame(&packet_data, player.connection.compression);
+    debug!("Login-Erfolgs-Paketlänge: {}", packet.len());
+
+    stream.write_all(&packet).map_err(|e| format!("Fehler beim Senden des Login-Erfolgspakets: {}", e))?;
+    metrics::TOTAL.record_out(packet.len());
+    debug!("Login-Erfolgs-Paket erfolgreich gesendet.");
+    Ok(())
+}
+
+fn send_login_disconnect(stream: &mut TcpStream, reason: &str) -> Result<(), String> {
+    send_packet(stream, clientbound::LOGIN_DISCONNECT, &write_string_to_vec(&Text::new(reason).to_json_string()))
+}
+
+fn send_join_game(stream: &mut TcpStream, player: &Player, world: &World, config: &ServerConfig) -> Result<(), String> {
+    let mut packet_data = vec![];
+    let version = player.connection.version;
+    packet_data.extend(write_varint_to_vec(version.clientbound(clientbound::LOGIN_PLAY))); // Packet ID für Join Game
+
+    let entity_id = 1i32.to_be_bytes();
+    packet_data.extend(&entity_id);
+    debug!("Sende Entity ID: {:?}", entity_id);
+
+    packet_data.push(config.hardcore as u8); // Hardcore-Herzen und "Zuschauen" statt "Wiederbeleben" im Todesbildschirm
+
+    // Alle geladenen Welten, der Client bietet sie etwa bei /execute in an
+    let worlds: Vec<&str> = [Dimension::Overworld, Dimension::End].into_iter().chain(multiworld::loaded().into_iter().map(Dimension::Named)).map(Dimension::name).collect();
+    packet_data.extend(write_varint_to_vec(worlds.len() as i32)); // Anzahl der Welten
+    for name in &worlds {
+        packet_data.extend(write_string_to_vec(name)); // Name der Welt
+    }
+    debug!("Sende Welten: {:?}", worlds);
+    let world_name = player.dimension.name();
+
+    packet_data.extend(write_varint_to_vec(config.max_players as i32)); // Maximale Spieleranzahl
+    debug!("Sende maximale Spieleranzahl: {}", config.max_players);
+
+    let view_distance = VIEW_DISTANCE;
+    packet_data.extend(write_varint_to_vec(view_distance));
+    debug!("Sende Sichtweite: {}", view_distance);
+
+    let simulation_distance = 10;
+    packet_data.extend(write_varint_to_vec(simulation_distance));
+    debug!("Sende Simulations-Distanz: {}", simulation_distance);
+
+    packet_data.push(0); // Reduzierte Debug-Info (Boolean)
+    debug!("Sende reduzierte Debug-Info: false");
+
+    let respawn_screen = !world.rules.get_bool(gamerule::DO_IMMEDIATE_RESPAWN);
+    packet_data.push(respawn_screen as u8); // Respawn-Bildschirm aktiviert (Boolean)
+    debug!("Sende Respawn-Bildschirm: {}", respawn_screen);
+
+    packet_data.push(0); // Eingeschränktes Crafting (Boolean)
+
+    packet_data.extend(player.dimension.write_type(&player.connection));
+    packet_data.extend(write_string_to_vec(world_name)); // Dimension Name
+    debug!("Sende Dimension Name: {}", world_name);
+
+    let hashed_seed = 0i64.to_be_bytes();
+    packet_data.extend(&hashed_seed); // Gehashter Seed
+    debug!("Sende gehashten Seed: {:?}", hashed_seed);
+
+    let game_mode = player.game_mode.id();
+    packet_data.push(game_mode);
+    debug!("Sende Spielmodus: {}", game_mode);
+
+    packet_data.push(255u8); // Vorheriger Spielmodus (Byte)
+    debug!("Sende vorherigen Spielmodus: -1");
+
+    packet_data.push(0); // Ist Debug-Welt (Boolean)
+    debug!("Sende Debug-Welt: false");
+
+    packet_data.push(0); // Ist flache Welt (Boolean)
+    debug!("Sende flache Welt: false");
+
+    packet_data.push(0); // Hat Todesposition (Boolean)
+    packet_data.extend(write_varint_to_vec(0)); // Portal-Cooldown
+    if version.has_1_20_5_login() {
+        packet_data.push(config.enforce_secure_chat as u8); // Erzwingt sicheren Chat (Boolean)
+    }
+
+    let packet = framing::encode_frame(&packet_data, player.connection.compression);
+    debug!("Beitrittspaket-Länge: {}", packet.len());
+
+    stream.write_all(&packet).map_err(|e| format!("Fehler beim Senden des Beitrittspakets: {}", e))?;
+    metrics::TOTAL.record_out(packet.len());
+    debug!("Beitrittspaket erfolgreich gesendet.");
+    Ok(())
+}
+
+fn encode_packet(packet_id: i32, data: &[u8], compression: Option<usize>) -> Vec<u8> {
+    let mut body = write_varint_to_vec(packet_id);
+    body.extend(data);
+    framing::encode_frame(&body, compression)
+}
+
+fn write_packet(stream: &mut TcpStream, packet_id: i32, packet: &[u8]) -> Result<(), String> {
+    stream.write_all(packet).map_err(|e| format!("Fehler beim Senden von Paket 0x{:02X}: {}", packet_id, e))?;
+    metrics::TOTAL.record_out(packet.len());
+    Ok(())
+}
+
+fn send_packet(stream: &mut TcpStream, packet_id: i32, data: &[u8]) -> Result<(), String> {
+    write_packet(stream, packet_id, &encode_packet(packet_id, data, None))
+}
+
+fn send_time(connection: &Connection, time: &WorldTime) -> Result<(), String> {
+    let mut packet_data = vec![];
+    packet_data.extend(time.age.to_be_bytes());
+    packet_data.extend(time.time_of_day.to_be_bytes());
+    connection.send(clientbound::UPDATE_TIME, &packet_data)
+}
+
+fn send_block_update(connection: &Connection, x: i32, y: i32, z: i32, state: u16) -> Result<(), String> {
+    let mut packet_data = encode_position(x, y, z).to_be_bytes().to_vec();
+    packet_data.extend(write_varint_to_vec(assets::block_state(connection.version, state) as i32));
+    connection.send(clientbound::BLOCK_UPDATE, &packet_data)
+}
+
+// Set Default Spawn Position: Kompassziel und Ort, an dem der Client vor dem ersten Positions-Paket steht
+fn send_spawn_position(connection: &Connection, spawn: SpawnPoint) -> Result<(), String> {
+    let (x, y, z) = spawn.position;
+    let mut packet_data = encode_position(x, y, z).to_be_bytes().to_vec();
+    packet_data.extend(spawn.angle.to_be_bytes());
+    connection.send(clientbound::SET_DEFAULT_SPAWN_POSITION, &packet_data)
+}
+
+// Blockposition und neuer Zustand
+type BlockChange = ((i32, i32, i32), u16);
+
+// Update Section Blocks: mehrere Änderungen innerhalb einer 16x16x16-Sektion in einem Paket
+fn send_section_blocks(connection: &Connection, section: (i32, i32, i32), blocks: &[BlockChange]) -> Result<(), String> {
+    let (sx, sy, sz) = section;
+    let position = ((sx as i64 & 0x3FFFFF) << 42) | ((sz as i64 & 0x3FFFFF) << 20) | (sy as i64 & 0xFFFFF);
+    let mut packet_data = position.to_be_bytes().to_vec();
+    packet_data.extend(write_varint_to_vec(blocks.len() as i32));
+    for ((x, y, z), state) in blocks {
+        let local = ((x & 15) << 8) | ((z & 15) << 4) | (y & 15);
+        let state = assets::block_state(connection.version, *state);
+        packet_data.extend(write_varlong_to_vec(((state as i64) << 12) | local as i64));
+    }
+    connection.send(clientbound::UPDATE_SECTION_BLOCKS, &packet_data)
+}
+
+// Setzt Blöcke, die Spieler sehen sie am Ende des Ticks. Lädt fehlende Chunks; gibt die Zahl der tatsächlich
+// geänderten Blöcke zurück
+fn apply_block_changes(server: &Server, changes: &[BlockChange]) -> usize {
+    let mut world = server.world.lock().unwrap();
+    let mut count = 0;
+    for &((x, y, z), state) in changes {
+        world.load_chunk_now(x >> 4, z >> 4);
+        if world.get_block(x, y, z) != state && world.set_block_and_notify((x, y, z), state) {
+            count += 1;
+        }
+    }
+    count
+}
+
+// Schickt die Blockänderungen des Ticks an die Spieler, die den Chunk geladen haben: einzelne als Block Update,
+// mehrere in einer Sektion als Update Section Blocks. Darf nur ohne gehaltene Sperren aufgerufen werden
+fn flush_block_changes(server: &Server) {
+    let players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        if world.changed_blocks.is_empty() {
+            continue;
+        }
+        let mut sections: HashMap<(i32, i32, i32), Vec<BlockChange>> = HashMap::new();
+        for (x, y, z) in std::mem::take(&mut world.changed_blocks) {
+            sections.entry((x >> 4, y >> 4, z >> 4)).or_default().push(((x, y, z), world.get_block(x, y, z)));
+        }
+        for (section, blocks) in &sections {
+            let Some(viewers) = world.chunk_viewers.get(&(section.0, section.2)) else { continue };
+            for player in players.iter().filter(|p| p.dimension == world.dimension && viewers.contains(&p.uuid)) {
+                let _ = match blocks.as_slice() {
+                    [((x, y, z), state)] => send_block_update(&player.connection, *x, *y, *z, *state),
+                    blocks => send_section_blocks(&player.connection, *section, blocks),
+                };
+            }
+        }
+    }
+}
+
+fn send_game_event(connection: &Connection, event: u8, value: f32) -> Result<(), String> {
+    let mut packet_data = vec![event];
+    packet_data.extend(value.to_be_bytes());
+    connection.send(clientbound::GAME_EVENT, &packet_data)
+}
+
+fn send_weather(connection: &Connection, weather: &Weather) -> Result<(), String> {
+    let (event, level) = if weather.raining { (GAME_EVENT_START_RAINING, 1.0) } else { (GAME_EVENT_STOP_RAINING, 0.0) };
+    send_game_event(connection, event, 0.0)?;
+    send_game_event(connection, GAME_EVENT_RAIN_LEVEL, level)?;
+    let thunder = if weather.raining && weather.thundering { 1.0 } else { 0.0 };
+    send_game_event(connection, GAME_EVENT_THUNDER_LEVEL, thunder)
+}
+
+// Systemnachricht im Chat
+fn send_system_message(connection: &Connection, message: &str) -> Result<(), String> {
+    send_system_component(connection, &Text::new(message))
+}
+
+// Wie send_system_message, aber mit formatierter Textkomponente
+fn send_system_component(connection: &Connection, component: &Text) -> Result<(), String> {
+    let mut packet_data = component.to_nbt();
+    packet_data.push(0); // Overlay (Aktionsleiste)
+    connection.send(clientbound::SYSTEM_CHAT, &packet_data)
+}
+
+// Systemnachricht über der Schnellleiste statt im Chat
+fn send_action_bar(connection: &Connection, component: &Text) -> Result<(), String> {
+    let mut packet_data = component.to_nbt();
+    packet_data.push(1);
+    connection.send(clientbound::SYSTEM_CHAT, &packet_data)
+}
+
+// Übersetzt die Komponente in die Sprache des Empfängers
+// und lässt sie weg, wenn der Chat in den Optionen ausgeblendet ist
+fn send_localized(server: &Server, player: &Player, component: &Text) -> Result<(), String> {
+    if !player.settings.accepts_system_messages() {
+        return Ok(());
+    }
+    send_system_component(&player.connection, &component.localize(&server.lang, &player.settings.locale))
+}
+
+// Nachricht an alle Spieler, jeweils in ihrer Sprache, und ins Log
+fn broadcast_localized(server: &Server, players: &[Player], component: &Text) {
+    info!("{}", server.lang.plain(component));
+    for player in players {
+        let _ = send_localized(server, player, component);
+    }
+}
+
+fn send_disconnect(connection: &Connection, reason: &str) -> Result<(), String> {
+    connection.send(clientbound::DISCONNECT, &Text::new(reason).to_nbt())
+}
+
+// Setzt Position und Blickrichtung eines Spielers und lädt den Zielchunk, falls nötig
+fn teleport_player(server: &Server, uuid: Uuid, position: (f64, f64, f64), rotation: (f32, f32)) -> Result<(), String> {
+    let mut players = server.players.lock().unwrap();
+    let player = players.iter_mut().find(|p| p.uuid == uuid).ok_or("Player is not online")?;
+    let mut world = dimension::world_of(server, player.dimension).lock().unwrap();
+    world.load_chunk_now((position.0.floor() as i32) >> 4, (position.2.floor() as i32) >> 4);
+    track_player(&mut world, uuid, player.game_mode, position);
+    drop(world);
+    player.position = position;
+    player.rotation = rotation;
+    send_teleport(player)
+}
+
+// Schickt den Spieler an seine gespeicherte Position, z.B. zurück nach einer unmöglichen Bewegung
+fn send_teleport(player: &mut Player) -> Result<(), String> {
+    let (position, rotation) = (player.position, player.rotation);
+    player.teleport.last_id = player.teleport.last_id.wrapping_add(1);
+    player.teleport.pending = Some(player.teleport.last_id);
+    let mut packet_data = vec![];
+    packet_data.extend(position.0.to_be_bytes());
+    packet_data.extend(position.1.to_be_bytes());
+    packet_data.extend(position.2.to_be_bytes());
+    packet_data.extend(rotation.0.to_be_bytes());
+    packet_data.extend(rotation.1.to_be_bytes());
+    packet_data.push(0); // Keine relativen Koordinaten
+    packet_data.extend(write_varint_to_vec(player.teleport.last_id));
+    player.connection.send(clientbound::SYNCHRONIZE_PLAYER_POSITION, &packet_data)
+}
+
+// Auf friedlich verschwinden Monster sofort
+fn remove_hostile_mobs(players: &[Player], world: &mut World) {
+    let (hostile, others): (Vec<Mob>, Vec<Mob>) = std::mem::take(&mut world.mobs).into_iter().partition(|m| entity::is_hostile(&m.mob_type));
+    world.mobs = others;
+    for mob in hostile {
+        world.entities.remove(mob.id);
+        for player in players.iter().filter(|p| entity::in_view(p, world.dimension, mob.position)) {
+            let _ = entity::send_remove(&player.connection, &[mob.entity_id]);
+        }
+    }
+}
+
+// Verschiebt einen Mob; die neue Position bekommen die Spieler in der Nähe im nächsten Tick vom Tracker
+fn teleport_mob(server: &Server, id: Uuid, position: (f64, f64, f64)) -> Result<(), String> {
+    // Mobs bleiben in ihrer Dimension
+    let mut world = dimension::world_with_mob(server, id).ok_or("Entity no longer exists")?.lock().unwrap();
+    let mob = world.mobs.iter_mut().find(|m| m.id == id).ok_or("Entity no longer exists")?;
+    mob.position = position;
+    world.load_chunk_now((position.0.floor() as i32) >> 4, (position.2.floor() as i32) >> 4);
+    world.entities.update(id, position);
+    Ok(())
+}
+
+// Trennt einen Spieler mit Begründung; das Aufräumen übernimmt sein Verbindungs-Thread
+fn kick_player(server: &Server, name: &str, reason: &str) -> bool {
+    let players = server.players.lock().unwrap();
+    let Some(player) = players.iter().find(|p| p.username.eq_ignore_ascii_case(name)) else { return false };
+    info!("{} wurde getrennt: {}", player.username, reason);
+    let _ = send_disconnect(&player.connection, reason);
+    player.connection.close();
+    true
+}
+
+fn send_chunk(connection: &Connection, chunk: &Chunk, light: Option<&ChunkLight>, dimension: Dimension) -> Result<(), String> {
+    let mut packet_data = vec![];
+    packet_data.extend(chunk.x.to_be_bytes());
+    packet_data.extend(chunk.z.to_be_bytes());
+    packet_data.extend(chunk.write_heightmaps(dimension.min_y(), dimension.height()));
+    let version = connection.version;
+    let sections = chunk.write_sections_in(dimension.min_y(), dimension.height(), &|state| assets::block_state(version, state));
+    packet_data.extend(write_varint_to_vec(sections.len() as i32));
+    packet_data.extend(sections);
+    packet_data.extend(write_varint_to_vec(chunk.block_entities.len() as i32));
+    for (&position, entity) in &chunk.block_entities {
+        packet_data.extend(entity.encode(position));
+    }
+    light::write_light(light, dimension, &mut packet_data);
+    connection.send(clientbound::CHUNK_DATA, &packet_data)
+}
+
+// Schickt alle geladenen, noch nicht gesendeten Chunks in Sichtweite und fordert fehlende an
+fn stream_chunks(player: &Player, world: &mut World, position: (f64, f64, f64), view_distance: i32, center: &mut Option<(i32, i32)>, sent: &mut HashSet<(i32, i32)>) -> Result<(), String> {
+    let connection = &player.connection;
+    let (chunk_x, chunk_z) = ((position.0.floor() as i32) >> 4, (position.2.floor() as i32) >> 4);
+    if *center != Some((chunk_x, chunk_z)) {
+        let mut packet_data = write_varint_to_vec(chunk_x);
+        packet_data.extend(write_varint_to_vec(chunk_z));
+        connection.send(clientbound::SET_CENTER_CHUNK, &packet_data)?;
+        *center = Some((chunk_x, chunk_z));
+    }
+    // Wie bei Vanilla bleibt ein Ring von einem Chunk außerhalb der Sichtweite geladen
+    let far: Vec<(i32, i32)> = sent.iter().copied().filter(|&(x, z)| (x - chunk_x).abs().max((z - chunk_z).abs()) > view_distance + 1).collect();
+    for (x, z) in far {
+        let mut packet_data = z.to_be_bytes().to_vec();
+        packet_data.extend(x.to_be_bytes());
+        connection.send(clientbound::UNLOAD_CHUNK, &packet_data)?;
+        sent.remove(&(x, z));
+        if let Some(viewers) = world.chunk_viewers.get_mut(&(x, z)) {
+            viewers.remove(&player.uuid);
+        }
+    }
+    for dx in -view_distance..=view_distance {
+        for dz in -view_distance..=view_distance {
+            let coords = (chunk_x + dx, chunk_z + dz);
+            if sent.contains(&coords) {
+                continue;
+            }
+            if let Some(chunk) = world.chunks.get(&coords) {
+                send_chunk(connection, chunk, world.light.get(coords), world.dimension)?;
+                sent.insert(coords);
+                world.chunk_viewers.entry(coords).or_default().insert(player.uuid);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_handshake(stream: &mut TcpStream) -> Result<Handshake, String> {
+    let (packet_id, mut cursor) = read_packet(stream, ConnectionState::Handshake)?;
+    let handshake = Handshake::decode(packet_id, &mut cursor)?;
+    debug!("Handshake erhalten: protocol_version={}, server_address={}, server_port={}, next_state={}",
+             handshake.protocol_version, handshake.server_address.split('\0').next().unwrap_or(""), handshake.server_port, handshake.next_state);
+    Ok(handshake)
+}
+
+// Serverliste: Statusantwort mit MOTD, danach Ping/Pong zur Latenzmessung
+fn handle_status(stream: &mut TcpStream, server: &Server, peer_addr: SocketAddr, version: Option<ProtocolVersion>) -> Result<(), String> {
+    let (packet_id, _) = read_packet(stream, ConnectionState::Status)?;
+    if packet_id != serverbound::STATUS_REQUEST {
+        return Err(format!("Unerwartetes Paket 0x{:02X} statt Status-Anfrage", packet_id));
+    }
+    debug!("Status-Anfrage erhalten.");
+    let mut ping = ServerListPingEvent { address: peer_addr.ip().to_string(), motd: server.motd.clone() };
+    server.fire_event(&mut ping);
+    let status = status_json(server, version, motd::component(&ping.motd));
+    send_packet(stream, clientbound::STATUS_RESPONSE, &write_string_to_vec(&status.to_json_string()))?;
+
+    // Manche Clients fragen nur den Status ab und schließen dann
+    let Ok((packet_id, mut cursor)) = read_packet(stream, ConnectionState::Status) else { return Ok(()) };
+    if packet_id != serverbound::PING_REQUEST {
+        return Err(format!("Unerwartetes Paket 0x{:02X} statt Ping", packet_id));
+    }
+    let payload = cursor.read_i64::<BigEndian>().map_err(|e| format!("Fehler beim Lesen des Pings: {}", e))?;
+    send_packet(stream, clientbound::PONG_RESPONSE, &payload.to_be_bytes())
+}
+
+// Unterstützte Versionen bekommen ihre eigene Protokollnummer zurück, andere sehen den Server als inkompatibel
+fn status_json(server: &Server, version: Option<ProtocolVersion>, description: Json) -> Json {
+    let players = server.players.lock().unwrap();
+    // Wie Vanilla höchstens 12 Namen für den Tooltip
+    let sample = players
+        .iter()
+        .take(12)
+        .map(|p| json_object(vec![("name", Json::String(p.username.clone())), ("id", Json::String(p.uuid.to_string()))]))
+        .collect();
+    let players = json_object(vec![
+        ("max", Json::Number(server.config.max_players as f64)),
+        ("online", Json::Number(players.len() as f64)),
+        ("sample", Json::Array(sample)),
+    ]);
+    let mut status = vec![
+        ("version", json_object(vec![("name", Json::String(ProtocolVersion::range())), ("protocol", Json::Number(version.unwrap_or(LATEST_VERSION).protocol() as f64))])),
+        ("players", players),
+        ("description", description),
+        ("enforcesSecureChat", Json::Bool(server.config.enforce_secure_chat)),
+    ];
+    if let Some(favicon) = server.favicon.lock().unwrap().clone() {
+        status.push(("favicon", Json::String(favicon)));
+    }
+    json_object(status)
+}
+
+fn json_object(entries: Vec<(&str, Json)>) -> Json {
+    Json::Object(entries.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+// Wie Vanilla im Offline-Modus: UUID der Version 3 aus "OfflinePlayer:<Name>", für jeden Namen immer dieselbe
+fn offline_uuid(username: &str) -> Uuid {
+    let mut bytes = hash::md5(format!("OfflinePlayer:{}", username).as_bytes());
+    bytes[6] = (bytes[6] & 0x0F) | 0x30;
+    bytes[8] = (bytes[8] & 0x3F) | 0x80;
+    Uuid::from_bytes(bytes)
+}
+
+fn handle_login(stream: &mut TcpStream) -> Result<String, String> {
+    let (packet_id, mut cursor) = read_packet(stream, ConnectionState::Login)?;
+    let LoginStart { username } = LoginStart::decode(packet_id, &mut cursor)?;
+    info!("Login-Versuch von Benutzername: {}", username);
+    Ok(username)
+}
+
+// Fordert über einen Login-Plugin-Request die von Velocity signierten Spielerdaten an
+fn velocity_login(stream: &mut TcpStream, secret: &str) -> Result<ForwardedInfo, String> {
+    let message_id = rand::thread_rng().gen_range(0..i32::MAX);
+    let mut packet_data = write_varint_to_vec(message_id);
+    packet_data.extend(write_string_to_vec(proxy::VELOCITY_CHANNEL));
+    packet_data.push(proxy::VELOCITY_FORWARDING_VERSION);
+    send_packet(stream, clientbound::LOGIN_PLUGIN_REQUEST, &packet_data)?;
+
+    let (packet_id, mut cursor) = read_packet(stream, ConnectionState::Login)?;
+    if packet_id != serverbound::LOGIN_PLUGIN_RESPONSE {
+        return Err(format!("Login-Plugin-Antwort erwartet, Paket-ID {} erhalten", packet_id));
+    }
+    let response_id = read_varint_from_cursor(&mut cursor).map_err(|e| format!("Nachrichten-ID fehlt: {}", e))?;
+    if response_id != message_id {
+        return Err(format!("Falsche Nachrichten-ID {} (erwartet {})", response_id, message_id));
+    }
+    let successful = cursor.read_u8().map_err(|e| format!("Antwort unvollständig: {}", e))? != 0;
+    if !successful {
+        return Err("Proxy hat keine Weiterleitungsdaten geliefert".to_string());
+    }
+    let position = cursor.position() as usize;
+    proxy::parse_velocity(secret.as_bytes(), &cursor.get_ref()[position..])
+}
+
+// Vor dem Einloggen; im Login bekommt der Client für ungültige Längen noch eine Begründung
+fn read_packet(stream: &mut TcpStream, state: ConnectionState) -> Result<(i32, std::io::Cursor<Vec<u8>>), ProtocolError> {
+    let (packet_data, size) = framing::read_frame(stream, framing::max_packet_length(state), None).map_err(|e| {
+        let e = ProtocolError::from_stream(e);
+        if state == ConnectionState::Login && matches!(e, ProtocolError::Malformed(_)) {
+            let _ = send_login_disconnect(stream, "Invalid packet");
+        }
+        e
+    })?;
+    metrics::TOTAL.record_in(size);
+    packets::split_id(packet_data)
+}
+
+fn handle_packet(server: &Server, player: &Player, packet_id: i32, mut cursor: std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let _span = span!("packet", id = format_args!("0x{:02X}", packet_id));
+    let profile = server.profiler.connection("packet handling");
+    if idle::is_input(packet_id) {
+        if let Some(p) = server.players.lock().unwrap().iter_mut().find(|p| p.uuid == player.uuid) {
+            idle::touch(p);
+        }
+    }
+    let packet = Play::decode(packet_id, &mut cursor)?;
+    // Diese Pakete lösen Events aus und müssen deshalb ohne gehaltene Sperren behandelt werden
+    match packet {
+        Some(Play::ChatCommand { command }) => return handle_chat_command(server, player, command),
+        Some(Play::ChatMessage { message, timestamp, salt, signature, offset, acknowledged }) => {
+            let chat = securechat::SignedChat { message: &message, timestamp, salt, signature: signature.as_deref(), offset, acknowledged };
+            return handle_chat_message(server, player, &chat);
+        }
+        Some(Play::PlayerSession { session_id, expires_at, public_key, key_signature }) => {
+            return securechat::handle_session(server, player, session_id, expires_at, public_key, key_signature)
+        }
+        Some(Play::PlayerAction { status, position, sequence, .. }) => return handle_player_action(server, player, status, position, sequence),
+        Some(Play::CustomPayload { channel, data }) => return handle_custom_payload(server, player, channel, data),
+        Some(Play::ClientCommand { action }) => return handle_client_command(server, player, action),
+        None if packet_id == serverbound::INTERACT => return combat::handle_interact(server, player, &mut cursor),
+        None if packet_id == serverbound::RESOURCE_PACK_RESPONSE => return resourcepack::handle_response(server, player, &mut cursor),
+        None if packet_id == serverbound::TELEPORT_TO_ENTITY => return gamemode::handle_spectate(server, player, &mut cursor),
+        _ => {}
+    }
+    let mut players = server.players.lock().unwrap();
+    let players = &mut players[..];
+    let dimension = players.iter().find(|p| p.uuid == player.uuid).map_or(player.dimension, |p| p.dimension);
+    let world = &mut dimension::world_of(server, dimension).lock().unwrap();
+    let result = match packet {
+        Some(Play::SetPlayerPosition { position, on_ground }) => handle_player_move(server, players, world, player, position, None, on_ground),
+        Some(Play::SetPlayerPositionAndRotation { position, rotation, on_ground }) => handle_player_move(server, players, world, player, position, Some(rotation), on_ground),
+        Some(Play::SetPlayerRotation { rotation, .. }) => handle_player_rotation(players, player, rotation),
+        Some(Play::KeepAlive { id }) => handle_keep_alive(players, player, id),
+        Some(Play::ConfirmTeleportation { id }) => handle_confirm_teleportation(players, player, id),
+        Some(Play::SetHeldItem { slot }) => handle_set_held_item(players, player, slot),
+        Some(Play::UseItemOn { hand, position, face, sequence }) => handle_use_item_on(server, players, world, player, hand, position, face, sequence),
+        Some(Play::UseItem { hand, sequence }) => handle_use_item(server, players, world, player, hand, sequence),
+        // Schon oben ohne Sperren behandelt
+        Some(_) => Ok(()),
+        None => match packet_id {
+            serverbound::CLIENT_INFORMATION => handle_client_information(players, player, &mut cursor),
+            serverbound::RENAME_ITEM => anvil::handle_rename(players, player, &mut cursor),
+            serverbound::PLACE_RECIPE => recipebook::handle_place(server, players, player, &mut cursor),
+            serverbound::CHANGE_RECIPE_BOOK_SETTINGS => recipebook::handle_settings(players, player, &mut cursor),
+            serverbound::SET_SEEN_RECIPE => recipebook::handle_seen(players, player, &mut cursor),
+            serverbound::CLICK_CONTAINER_BUTTON => window::handle_button(players, world, player, &server.datapacks.lock().unwrap().registries.recipes, &mut cursor),
+            serverbound::CLICK_CONTAINER => click::handle_click(server, players, world, player, &mut cursor),
+            serverbound::SELECT_TRADE => merchant::handle_select(players, world, player, &mut cursor),
+            serverbound::CLOSE_CONTAINER => window::handle_close(players, world, player, &mut cursor),
+            serverbound::SIGN_UPDATE => sign::handle_update(players, world, player, &mut cursor),
+            serverbound::SWING_ARM => animation::handle_swing(players, player, &mut cursor),
+            serverbound::PLAYER_COMMAND => pose::handle_player_command(players, world, player, &mut cursor),
+            serverbound::PLAYER_INPUT => vehicle::handle_input(players, world, player, &mut cursor),
+            serverbound::MOVE_VEHICLE => vehicle::handle_move(players, world, player, &mut cursor),
+            serverbound::PADDLE_BOAT => vehicle::handle_paddle(players, world, player, &mut cursor),
+            _ => {
+                debug!("Unbekannte Paket-ID: {}", packet_id);
+                Ok(())
+            }
+        },
+    };
+    drop(profile);
+    let _profile = server.profiler.connection("chunk loading");
+    if let Some(p) = players.iter().find(|p| p.uuid == player.uuid) {
+        let (chunk_x, chunk_z) = ((p.position.0 as i32) >> 4, (p.position.2 as i32) >> 4);
+        let view_distance = p.settings.view_distance();
+        for dx in -view_distance..=view_distance {
+            for dz in -view_distance..=view_distance {
+                world.request_chunk(chunk_x + dx, chunk_z + dz);
+            }
+        }
+    }
+    world.poll_chunks();
+    result
+}
+
+fn handle_custom_payload(server: &Server, player: &Player, channel: String, data: Vec<u8>) -> Result<(), ServerError> {
+    if !channel::is_valid_channel(&channel) {
+        return Err(ProtocolError::Malformed(format!("Invalid channel name {}", channel)).into());
+    }
+    if data.len() > channel::MAX_PAYLOAD_SIZE {
+        warn!("Plugin-Nachricht von {} auf {} ist zu groß ({} Bytes)", player.username, channel, data.len());
+        return Ok(());
+    }
+    match channel.as_str() {
+        channel::BRAND => {
+            let mut payload = std::io::Cursor::new(data);
+            let brand = read_string_from_cursor(&mut payload)?;
+            info!("{} nutzt den Client \"{}\"", player.username, brand);
+            if let Some(p) = server.players.lock().unwrap().iter_mut().find(|p| p.uuid == player.uuid) {
+                p.brand = Some(brand);
+            }
+        }
+        channel::REGISTER | channel::UNREGISTER => {
+            let channels = channel::decode_channel_list(&data);
+            let mut players = server.players.lock().unwrap();
+            let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+            for name in channels {
+                if channel == channel::REGISTER {
+                    if p.channels.len() >= channel::MAX_CLIENT_CHANNELS {
+                        warn!("{} hat zu viele Plugin-Kanäle angemeldet", p.username);
+                        break;
+                    }
+                    p.channels.insert(name);
+                } else {
+                    p.channels.remove(&name);
+                }
+            }
+        }
+        // Nachrichten auf unbekannten Kanälen verwirft auch Vanilla stillschweigend
+        _ => {
+            if let Some(handler) = server.channels.handler(&channel) {
+                handler(server, player, &data);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_chat_command(server: &Server, player: &Player, command: String) -> Result<(), ServerError> {
+    if accepts_chat_input(server, player, ChatMode::CommandsOnly) {
+        info!("{} führt Befehl aus: /{}", player.username, command);
+        server.pending_commands.lock().unwrap().push((CommandSender::Player(player.uuid), command));
+    }
+    Ok(())
+}
+
+// Wie bei Vanilla darf nur schreiben, wer den Chat auch sieht: Nachrichten verlangen "Vollständig",
+// Befehle mindestens "Nur Befehle"
+fn accepts_chat_input(server: &Server, player: &Player, required: ChatMode) -> bool {
+    let players = server.players.lock().unwrap();
+    let Some(sender) = players.iter().find(|p| p.uuid == player.uuid) else { return false };
+    let accepted = match required {
+        ChatMode::Full => sender.settings.accepts_chat(),
+        _ => sender.settings.accepts_system_messages(),
+    };
+    if !accepted {
+        let _ = send_system_component(&sender.connection, &Text::translate("chat.disabled.options").color("red").localize(&server.lang, &sender.settings.locale));
+    }
+    accepted
+}
+
+fn handle_chat_message(server: &Server, player: &Player, chat: &securechat::SignedChat) -> Result<(), ServerError> {
+    let message = chat.message;
+    // Wie bei Vanilla ein Kick und kein stilles Verwerfen
+    if message.len() > MAX_CHAT_LENGTH || message.chars().any(|c| c.is_control() || c == '§') {
+        return Err(ServerError::Kick("Illegal characters in chat".to_string()));
+    }
+    securechat::check_message(server, player, chat)?;
+    if !accepts_chat_input(server, player, ChatMode::Full) {
+        return Ok(());
+    }
+    if !server.permissions.lock().unwrap().has(&player.username, Some(player.uuid), permission::CHAT) {
+        let _ = send_system_message(&player.connection, "You do not have permission to chat");
+        return Ok(());
+    }
+    server.fire_event(&mut ChatEvent { player: player.username.clone(), message: message.to_string(), format: server.config.chat_format.clone(), cancelled: false });
+    Ok(())
+}
+
+fn handle_client_command(server: &Server, player: &Player, action: i32) -> Result<(), ServerError> {
+    if action != CLIENT_COMMAND_RESPAWN {
+        return Ok(());
+    }
+    damage::respawn(server, player.uuid).map_err(|e| ServerError::Internal(format!("Respawn fehlgeschlagen: {}", e)))
+}
+
+fn handle_player_action(server: &Server, player: &Player, status: i32, position: i64, sequence: i32) -> Result<(), ServerError> {
+    if status == RELEASE_USE_ITEM {
+        let mut players = server.players.lock().unwrap();
+        let Some(index) = players.iter().position(|p| p.uuid == player.uuid) else { return Ok(()) };
+        players[index].hunger.stop_eating();
+        let mut world = dimension::world_of(server, players[index].dimension).lock().unwrap();
+        bow::release(&mut players, &mut world, index);
+        shield::stop(&mut players, index);
+        return Ok(());
+    }
+    if status == SWAP_ITEM_WITH_OFFHAND {
+        swap_hands(&mut server.players.lock().unwrap(), player);
+        return Ok(());
+    }
+    let live = server.players.lock().unwrap().iter().find(|p| p.uuid == player.uuid).map(|p| (p.game_mode, p.inventory.main_hand().clone(), p.dimension));
+    let Some((game_mode, held, dimension)) = live else { return Ok(()) };
+    let (x, y, z) = decode_position(position);
+    if status == DIG_STARTED && dimension == Dimension::Overworld && edit::select_with_wand(server, (player.uuid, &player.username), &player.connection, &held, (x, y, z), true) {
+        let state = server.world.lock().unwrap().get_block(x, y, z);
+        let _ = send_block_update(&player.connection, x, y, z, state);
+        let _ = player.connection.send(clientbound::ACKNOWLEDGE_BLOCK_CHANGE, &write_varint_to_vec(sequence));
+        return Ok(());
+    }
+    if status == DIG_FINISHED || (status == DIG_STARTED && game_mode.breaks_instantly()) {
+        let state = dimension::world_of(server, dimension).lock().unwrap().get_block(x, y, z);
+        if state != block::AIR {
+            let datapacks = server.datapacks.lock().unwrap();
+            let allowed = gamemode::may_break(game_mode, &held, state, datapacks.registries.tags.get("block"));
+            drop(datapacks);
+            if allowed && server.permissions.lock().unwrap().has(&player.username, Some(player.uuid), permission::BUILD) {
+                server.fire_event(&mut BlockBreakEvent { player: player.username.clone(), x, y, z, state, cancelled: false });
+            } else {
+                // Ohne Baurecht (oder als Zuschauer bzw. im Abenteuermodus) stellt der Client den Block wieder her
+                let _ = send_block_update(&player.connection, x, y, z, state);
+            }
+        }
+    }
+    // Bestätigt die Vorhersage des Clients, nachdem etwaige Blockänderungen verschickt wurden
+    if status <= DIG_FINISHED {
+        let _ = player.connection.send(clientbound::ACKNOWLEDGE_BLOCK_CHANGE, &write_varint_to_vec(sequence));
+    }
+    Ok(())
+}
+
+// Taste F: der Client sagt den Tausch nicht voraus, also gehen beide Slots zurück. Zuschauer tauschen nicht
+fn swap_hands(players: &mut [Player], player: &Player) {
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid) else { return };
+    if players[index].game_mode == GameMode::Spectator {
+        return;
+    }
+    bow::stop(players, index);
+    shield::stop(players, index);
+    let p = &mut players[index];
+    p.hunger.stop_eating();
+    p.inventory.swap_hands();
+    for off_hand in [false, true] {
+        let _ = p.inventory.send_slot(&p.connection, p.inventory.hand_slot(off_hand));
+    }
+}
+
+fn handle_set_held_item(players: &mut [Player], player: &Player, slot: i16) -> Result<(), ServerError> {
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid) else { return Ok(()) };
+    bow::stop(players, index);
+    shield::stop(players, index);
+    let p = &mut players[index];
+    p.hunger.stop_eating();
+    if slot < 0 || !p.inventory.select(slot as usize) {
+        debug!("Ungültiger Schnellleisten-Platz {} von {}", slot, player.username);
+    }
+    Ok(())
+}
+
+// Richtung der angeklickten Seite aus Use Item On: unten, oben, Norden, Süden, Westen, Osten
+fn face_offset(face: i32) -> (i32, i32, i32) {
+    match face {
+        0 => (0, -1, 0),
+        1 => (0, 1, 0),
+        2 => (0, 0, -1),
+        3 => (0, 0, 1),
+        4 => (-1, 0, 0),
+        _ => (1, 0, 0),
+    }
+}
+
+// Erst der angeklickte Block (Schilder, Container, Türen, Hebel), dann das Item in der Hand. Von den Blöcken setzt der Server bisher nur
+// Schilder, Feuer, Ackerboden, Feldfrüchte und Setzlinge selbst. Nicht erlaubtes Bauen (Abenteuermodus ohne passendes
+// can_place_on, Zuschauer, ohne Baurecht) wird zurückgerollt, damit beim Client kein Geisterblock stehen bleibt
+#[allow(clippy::too_many_arguments)]
+fn handle_use_item_on(server: &Server, players: &mut [Player], world: &mut World, player: &Player, hand: i32, position: i64, face: i32, sequence: i32) -> Result<(), ServerError> {
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid) else { return Ok(()) };
+    let (x, y, z) = decode_position(position);
+    let hand_slot = players[index].inventory.hand_slot(hand == OFF_HAND);
+    let p = &players[index];
+    if p.dimension == Dimension::Overworld && edit::select_with_wand(server, (p.uuid, &p.username), &p.connection, p.inventory.slot(hand_slot), (x, y, z), false) {
+        let _ = p.connection.send(clientbound::ACKNOWLEDGE_BLOCK_CHANGE, &write_varint_to_vec(sequence));
+        return Ok(());
+    }
+    if sign::use_on(server, players, world, index, hand_slot, (x, y, z)) || window::open(players, world, index, (x, y, z)) || sleep::use_on(server, players, world, index, (x, y, z))
+        || door::use_on(players, world, index, (x, y, z)) || redstone::use_on(players, world, index, (x, y, z)) {
+        let _ = players[index].connection.send(clientbound::ACKNOWLEDGE_BLOCK_CHANGE, &write_varint_to_vec(sequence));
+        return Ok(());
+    }
+    let p = &players[index];
+    let held = p.inventory.slot(hand_slot);
+    let clicked = world.get_block(x, y, z);
+    let datapacks = server.datapacks.lock().unwrap();
+    let allowed = gamemode::may_place_on(p.game_mode, held, clicked, datapacks.registries.tags.get("block"))
+        && server.permissions.lock().unwrap().has(&p.username, Some(p.uuid), permission::BUILD);
+    drop(datapacks);
+    if allowed
+        && !tnt::use_on(players, world, index, hand_slot, (x, y, z))
+        && !fire::use_on(players, world, index, hand_slot, (x, y, z), face)
+        && !farming::use_on(players, world, index, hand_slot, (x, y, z), face)
+        && !sapling::use_on(players, world, index, hand_slot, (x, y, z), face)
+        && !spawner::use_on(players, world, index, hand_slot, (x, y, z))
+        && !sleep::place(players, world, index, hand_slot, (x, y, z), face)
+    {
+        sign::place(players, world, index, hand_slot, (x, y, z), face);
+    }
+    let p = &players[index];
+    let held = p.inventory.slot(hand_slot);
+    if !allowed && !held.is_empty() {
+        let (dx, dy, dz) = face_offset(face);
+        let _ = send_block_update(&p.connection, x, y, z, clicked);
+        let _ = send_block_update(&p.connection, x + dx, y + dy, z + dz, world.get_block(x + dx, y + dy, z + dz));
+    }
+    let _ = p.connection.send(clientbound::ACKNOWLEDGE_BLOCK_CHANGE, &write_varint_to_vec(sequence));
+    Ok(())
+}
+
+// Use Item mit der Hand, die der Client nennt: Nahrung wird gegessen, eine Rakete treibt im Gleitflug an, ein Schild
+// wird gehoben, ein Wurftrank fliegt los, eine leere Karte wird zur Karte der Umgebung
+fn handle_use_item(server: &Server, players: &mut [Player], world: &mut World, player: &Player, hand: i32, sequence: i32) -> Result<(), ServerError> {
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid) else { return Ok(()) };
+    let p = &mut players[index];
+    let _ = p.connection.send(clientbound::ACKNOWLEDGE_BLOCK_CHANGE, &write_varint_to_vec(sequence));
+    let slot = p.inventory.hand_slot(hand == OFF_HAND);
+    if p.health <= 0.0 || hunger::start_eating(p, slot) || elytra::boost(players, index, slot) || bow::use_item(players, world, index, slot) || shield::use_item(players, index, slot)
+        || potion::use_item(players, world, index, slot) {
+        return Ok(());
+    }
+    map::use_item(server, players, world, index, slot);
+    Ok(())
+}
+
+// Kernverhalten als Listener mit Priorität Monitor, damit Plugins und Skripte es vorher abbrechen können
+fn register_core_listeners(server: &Server) {
+    server.events.listen(EventPriority::Monitor, |server, event: &mut ChatEvent| {
+        if event.cancelled {
+            return;
+        }
+        let players = server.players.lock().unwrap();
+        let sender = players.iter().find(|p| p.username == event.player);
+        let (uuid, ping) = (sender.map(|p| p.uuid), sender.map_or(0, Player::ping).to_string());
+        let permissions = server.permissions.lock().unwrap();
+        let prefix = permissions.prefix(&event.player, uuid);
+        let colors = permissions.has(&event.player, uuid, permission::CHAT_COLOR);
+        drop(permissions);
+        let values = [("prefix", prefix.as_str()), ("name", event.player.as_str()), ("world", storage::world_dir()), ("ping", ping.as_str())];
+        let line = chat::format(&event.format, &values, &event.message, colors);
+        info!("{}", line.plain());
+        for player in players.iter().filter(|p| p.settings.accepts_chat()) {
+            let _ = send_system_component(&player.connection, &line);
+        }
+    });
+    server.events.listen(EventPriority::Monitor, |server, event: &mut BlockBreakEvent| {
+        let players = server.players.lock().unwrap();
+        if event.cancelled {
+            if let Some(player) = players.iter().find(|p| p.username == event.player) {
+                let _ = send_block_update(&player.connection, event.x, event.y, event.z, event.state);
+            }
+            return;
+        }
+        let breaker = players.iter().find(|p| p.username == event.player);
+        let dimension = breaker.map_or(Dimension::Overworld, |p| p.dimension);
+        let mut world = dimension::world_of(server, dimension).lock().unwrap();
+        // Container lassen ihren Inhalt immer fallen, auch im Kreativmodus
+        let contents = world.block_entity_mut((event.x, event.y, event.z)).map(BlockEntity::take_items).unwrap_or_default();
+        if !world.set_block_and_notify((event.x, event.y, event.z), block::AIR) {
+            return;
+        }
+        // Der Abbauende sieht die Partikel schon durch seinen Client, das Löschen von Feuer hören alle
+        let position = (event.x, event.y, event.z);
+        let breaker_uuid = breaker.map(|p| p.uuid);
+        world.play_event(WorldEvent::DestroyBlock(event.state), position, breaker_uuid);
+        if block::is_fire(event.state) {
+            world.play_event(WorldEvent::ExtinguishFire, position, None);
+        }
+        // Abgebaut zählt wie bei Vanilla nur außerhalb des Kreativmodus
+        if let Some(breaker) = breaker.filter(|p| p.game_mode.drops_blocks()) {
+            server.scoreboard.lock().unwrap().award(&players, &breaker.username, &[&scoreboard::stat("mined", block::name(event.state))], 1);
+        }
+        let drops = world.rules.get_bool(gamerule::DO_TILE_DROPS)
+            && breaker.is_some_and(|p| p.game_mode.drops_blocks() && loot::can_harvest(event.state, p.inventory.main_hand()));
+        drop(world);
+        drop(players);
+        let position = (event.x as f64 + 0.5, event.y as f64 + 0.5, event.z as f64 + 0.5);
+        for stack in contents {
+            entity::drop_item(server, dimension, stack, position);
+        }
+        if drops {
+            for stack in loot::block_drops(server, event.state) {
+                entity::drop_item(server, dimension, stack, position);
+            }
+        }
+    });
+}
+
+fn handle_client_information(players: &mut [Player], player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let settings = ClientSettings::read(cursor)?;
+    let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    debug!("Einstellungen von {}: {:?}", p.username, settings);
+    // Ohne Metadaten zeigt der Client die eigenen Skin-Ebenen in der Außenansicht nicht an
+    if (p.settings.skin_parts, p.settings.main_hand) != (settings.skin_parts, settings.main_hand) {
+        let _ = entity::send_player_settings(&p.connection, &settings);
+    }
+    p.settings = settings;
+    Ok(())
+}
+
+fn handle_keep_alive(players: &mut [Player], player: &Player, id: i64) -> Result<(), ServerError> {
+    if let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) {
+        match p.keep_alive.pending {
+            Some((pending_id, sent_at)) if pending_id == id => {
+                let sample = sent_at.elapsed().as_millis().min(u32::MAX as u128) as u32;
+                // Wie bei Vanilla: neue Messungen zählen zu einem Viertel
+                p.keep_alive.ping_ms = if p.keep_alive.ping_ms == 0 { sample } else { (p.keep_alive.ping_ms * 3 + sample) / 4 };
+                p.keep_alive.pending = None;
+            }
+            _ => warn!("Unerwartete Keep-Alive-ID {} von {}", id, player.username),
+        }
+    }
+    Ok(())
+}
+
+fn handle_confirm_teleportation(players: &mut [Player], player: &Player, id: i32) -> Result<(), ServerError> {
+    if let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) {
+        if p.teleport.pending == Some(id) {
+            p.teleport.pending = None;
+            p.movement.confirm_teleport(p.position);
+        }
+    }
+    Ok(())
+}
+
+// NaN und Unendlich würden sich über Position und Chunk-Koordinaten durch die ganze Welt ziehen; Vanilla trennt dafür
+fn handle_player_move(server: &Server, players: &mut [Player], world: &mut World, player: &Player, position: (f64, f64, f64), rotation: Option<(f32, f32)>, on_ground: bool) -> Result<(), ServerError> {
+    if !(position.0.is_finite() && position.1.is_finite() && position.2.is_finite()) || rotation.is_some_and(|(yaw, pitch)| !(yaw.is_finite() && pitch.is_finite())) {
+        return Err(ServerError::Kick(INVALID_MOVE.to_string()));
+    }
+    trace!("Spieler {} bewegte sich zu Position: {:?}", player.username, position);
+    move_player(server, players, world, player, position, rotation, on_ground);
+    Ok(())
+}
+
+// Die Blickrichtung braucht unter anderem die Prüfung von Angriffen
+fn handle_player_rotation(players: &mut [Player], player: &Player, (yaw, pitch): (f32, f32)) -> Result<(), ServerError> {
+    if !(yaw.is_finite() && pitch.is_finite()) {
+        return Err(ServerError::Kick(INVALID_MOVE.to_string()));
+    }
+    if let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid && p.teleport.pending.is_none()) {
+        idle::moved(p, p.position, (yaw, pitch));
+        p.rotation = (yaw, pitch);
+    }
+    Ok(())
+}
+
+// Unmögliche Bewegungen werden verworfen und der Spieler auf die letzte gültige Position zurückgesetzt. Gültige
+// kosten beim Sprinten und Springen Nahrung
+fn move_player(server: &Server, players: &mut [Player], world: &mut World, player: &Player, position: (f64, f64, f64), rotation: Option<(f32, f32)>, on_ground: bool) {
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid && p.teleport.pending.is_none()) else { return };
+    if let Some(violation) = movement::check(world, &mut players[index], position) {
+        movement::flag(server, players, index, violation);
+        if let Err(e) = send_teleport(&mut players[index]) {
+            warn!("Fehler beim Zurücksetzen von {}: {}", player.username, e);
+        }
+        return;
+    }
+    let p = &mut players[index];
+    hunger::exhaust_movement(p, position, on_ground);
+    let landed = movement::fall(world, p, position, on_ground);
+    idle::moved(p, position, rotation.unwrap_or(p.rotation));
+    p.position = position;
+    if let Some(rotation) = rotation {
+        p.rotation = rotation;
+    }
+    track_player(world, player.uuid, p.game_mode, position);
+    if on_ground {
+        elytra::stop(players, index);
+    }
+    if landed > 0.0 {
+        farming::trample(server, players, world, position, landed);
+    }
+    fluid::update_swimming(world, players, index);
+}
+
+// Zuschauer bleiben aus dem Entity-Index, sonst fänden Abfragen in der Nähe sie wie jeden anderen Spieler
+fn track_player(world: &mut World, uuid: Uuid, game_mode: GameMode, position: (f64, f64, f64)) {
+    if game_mode.is_tracked() {
+        world.entities.update(uuid, position);
+    } else {
+        world.entities.remove(uuid);
+    }
+}
+
+// Liest Befehle von der Standardeingabe und reiht sie für den Tick-Thread ein
+fn read_console(server: Arc<Server>) {
+    let stdin = std::io::stdin();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match stdin.read_line(&mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {
+                if !line.trim().is_empty() {
+                    server.pending_commands.lock().unwrap().push((CommandSender::Console, line.trim().to_string()));
+                }
+            }
+        }
+    }
+}
+
+// Das Ende teilt den Seed mit der Oberwelt und übernimmt beim ersten Start deren Spielregeln und Einstellungen
+fn load_end(overworld: &World) -> World {
+    let storage = WorldStorage::open(&storage::end_dir(), overworld.storage.kind());
+    let level = storage.load_level();
+    let mut end = World::new(overworld.seed, storage, Dimension::End);
+    end.rules = overworld.rules.clone();
+    end.difficulty = overworld.difficulty;
+    end.settings = overworld.settings;
+    end.spawn = SpawnPoint { position: end::ENTRY_POINT, angle: 0.0 };
+    end.dragon_fight = Some(DragonFight::new());
+    if let Some(level) = level {
+        end.time = level.time;
+        end.rules = level.rules;
+        end.difficulty = level.difficulty.unwrap_or(end.difficulty);
+        end.settings = level.settings.unwrap_or(end.settings);
+        end.spawn = level.spawn.unwrap_or(end.spawn);
+        end.dragon_fight = level.dragon_fight.or(end.dragon_fight);
+    }
+    end.generate();
+    end
+}
+
+// Oberwelt, End und alle benannten Welten, auch die gerade nicht geladenen
+fn upgrade_worlds(kind: ChunkStorageKind) {
+    let mut dirs = vec![storage::world_dir().to_string(), storage::end_dir()];
+    if let Ok(entries) = fs::read_dir(multiworld::WORLDS_DIR) {
+        dirs.extend(entries.flatten().filter(|e| e.path().is_dir()).map(|e| e.path().to_string_lossy().into_owned()));
+    }
+    for dir in dirs.iter().filter(|dir| Path::new(dir).is_dir()) {
+        let (upgraded, failed) = WorldStorage::open(dir, kind).upgrade();
+        if failed > 0 {
+            warn!("{}: {} Chunks aktualisiert, {} fehlgeschlagen", dir, upgraded, failed);
+        } else {
+            info!("{}: {} Chunks aktualisiert", dir, upgraded);
+        }
+    }
+}
+
+/// Configures and starts a server.
+///
+/// Anything not set here comes from `server.properties` (or the file given to [`config_file`](Self::config_file)),
+/// which is created with all known keys if it is missing. Worlds, logs, plugins and other data files are resolved
+/// relative to the working directory. Logging, the block and item registries and the signal handlers are
+/// process-wide, so only one server can run per process.
+///
+/// ```no_run
+/// use rustmc_server::Server;
+///
+/// Server::builder().bind(([127, 0, 0, 1], 25565)).world("survival").run().unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct ServerBuilder {
+    config_file: Option<String>,
+    addresses: Vec<SocketAddr>,
+    world: Option<String>,
+    properties: Vec<(String, String)>,
+    force_upgrade: bool,
+    bonus_chest: bool,
+}
+
+impl Server {
+    /// Starts configuring a server, see [`ServerBuilder`].
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+}
+
+impl ServerBuilder {
+    /// Reads the configuration from `path` instead of `server.properties`.
+    pub fn config_file(mut self, path: &str) -> ServerBuilder {
+        self.config_file = Some(path.to_string());
+        self
+    }
+
+    /// Listens on `address` instead of `server-ip`, `server-port` and `server-addresses`. Call it again to listen on
+    /// several addresses; `[::]` also accepts IPv4. Port 0 picks a free port, which is logged.
+    pub fn bind(mut self, address: impl Into<SocketAddr>) -> ServerBuilder {
+        self.addresses.push(address.into());
+        self
+    }
+
+    /// Keeps the overworld in the directory `dir` instead of `level-name`. The end is stored inside it.
+    pub fn world(mut self, dir: &str) -> ServerBuilder {
+        self.world = Some(dir.to_string());
+        self
+    }
+
+    /// Overrides the `server.properties` key `key` for this run without writing it to the file. Later calls win; an
+    /// unknown key or a value that does not parse makes [`run`](Self::run) fail.
+    pub fn property(mut self, key: &str, value: &str) -> ServerBuilder {
+        self.properties.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Rewrites every stored chunk of every world in the current format and `chunk-storage` backend before the
+    /// worlds are loaded, for example after switching to `zstd`.
+    pub fn force_upgrade(mut self) -> ServerBuilder {
+        self.force_upgrade = true;
+        self
+    }
+
+    /// Places a chest with starter items next to the spawn if the overworld is created by this run, like vanilla's
+    /// `--bonusChest`. A datapack can replace the loot table `minecraft:chests/spawn_bonus_chest`.
+    pub fn bonus_chest(mut self) -> ServerBuilder {
+        self.bonus_chest = true;
+        self
+    }
+
+    /// Loads the worlds, starts the tick loop and serves connections on the calling thread.
+    ///
+    /// Only returns if a property override is invalid, the generated reports cannot be read or one of the addresses
+    /// cannot be bound; the server stops the process itself on `/stop`.
+    pub fn run(self) -> Result<(), String> {
+        let mut config = ServerConfig::load(self.config_file.as_deref().unwrap_or(config::CONFIG_FILE));
+        for (key, value) in &self.properties {
+            if let Err(e) = config.set(key, value) {
+                error!("{}", e);
+                return Err(e);
+            }
+        }
+        if let Some((first, rest)) = self.addresses.split_first() {
+            config.server_ip = first.ip().to_string();
+            config.server_port = first.port();
+            config.server_addresses = listen::BindAddresses(rest.to_vec());
+        }
+        if let Some(world) = self.world {
+            config.level_name = world;
+        }
+        run(config, self.force_upgrade, self.bonus_chest)
+    }
+}
+
+fn run(config: ServerConfig, force_upgrade: bool, bonus_chest: bool) -> Result<(), String> {
+    // RUST_LOG hat Vorrang vor log-filter, etwa für eine einmalige Fehlersuche
+    let log_filter = std::env::var("RUST_LOG").unwrap_or_else(|_| config.log_filter.clone());
+    if let Err(e) = log::configure(&log_filter) {
+        warn!("{}", e);
+    }
+    if config.log_to_file {
+        if let Err(e) = log::log_to_file(logfile::LOG_DIR, config.log_max_file_size_mb * 1024 * 1024) {
+            warn!("{}", e);
+        }
+    }
+    log::log_panics();
+    // Warnungen der Chunk-Worker laufen über denselben Filter und dieselben Ziele wie die des Servers
+    rustmc_world::set_warn_hook(|module_path, message| {
+        if log::enabled(log::Level::Warn, module_path) {
+            log::write(log::Level::Warn, module_path, message, &[]);
+        }
+    });
+    if let Err(e) = item::load_registry(item::REPORTS_DIR) {
+        error!("{}", e);
+        return Err(e);
+    }
+    for message in assets::load(assets::ASSETS_DIR) {
+        match message {
+            Ok(message) => info!("{}", message),
+            Err(e) => warn!("{}", e),
+        }
+    }
+    if !storage::set_world_dir(&config.level_name) {
+        warn!("Der Weltordner stand schon fest, level-name {} ignoriert", config.level_name);
+    }
+    if !worldgen::set_overworld_generator(config.level_type) {
+        warn!("Der Generator der Oberwelt stand schon fest, level-type {} ignoriert", config.level_type);
+    }
+    // Nur die REST-API bietet eine Live-Konsole an, sonst bleibt stdout unangetastet
+    if config.rest_api_enabled {
+        if let Err(e) = console::LOG.capture_stdout() {
+            warn!("{}", e);
+        }
+    }
+
+    if force_upgrade {
+        upgrade_worlds(config.chunk_storage);
+    }
+    let storage = WorldStorage::open(storage::world_dir(), config.chunk_storage);
+    let level = storage.load_level();
+    let mut world = World::new(level.as_ref().map_or_else(|| rand::thread_rng().gen(), |level| level.seed), storage, Dimension::Overworld);
+    let saved_spawn = level.as_ref().and_then(|level| level.spawn);
+    world.difficulty = config.difficulty;
+    world.settings = WorldSettings::from_config(&config);
+    if let Some(level) = level {
+        info!("Lade Welt aus {}", storage::world_dir());
+        world.time = level.time;
+        world.weather = level.weather;
+        world.rules = level.rules;
+        world.border = level.border;
+        world.scheduled_functions = level.scheduled_functions;
+        world.difficulty = level.difficulty.unwrap_or(world.difficulty);
+        world.settings = level.settings.unwrap_or(world.settings);
+    }
+    world.spawn_mob(Mob::new("minecraft:zombie", (10.0, 64.0, 10.0)));
+    world.spawn_mob(Mob::new("minecraft:skeleton", (15.0, 64.0, 15.0)));
+    world.generate();
+    if saved_spawn.is_none() && config.level_type == Generator::Void && config.void_spawn_platform {
+        worldgen::place_spawn_platform(&mut world);
+    }
+    // Neue Welten spawnen auf dem obersten Block über dem Ursprung
+    world.spawn = saved_spawn.unwrap_or(SpawnPoint { position: (0, world.highest_block_y(0, 0) + 1, 0), angle: 0.0 });
+    let end = load_end(&world);
+    let datapacks = DatapackManager::load(storage::world_dir());
+    dimension::register_types(&datapacks.registries.dimension_types);
+    if bonus_chest && saved_spawn.is_none() {
+        worldgen::place_bonus_chest(&mut world, datapacks.registries.loot_tables.get("minecraft:chests/spawn_bonus_chest"));
+    }
+    multiworld::load_all(&config);
+    let resource_pack = ResourcePack::from_config(&config);
+    let motd = motd::from_config(&config.motd);
+    if config.bungeecord && config.velocity_forwarding {
+        warn!("Sowohl bungeecord als auch velocity-forwarding sind aktiv, verwende Velocity.");
+    }
+    if config.velocity_forwarding && config.velocity_secret.is_empty() {
+        warn!("velocity-forwarding ist aktiv, aber velocity-secret ist leer!");
+    }
+    securechat::check_config(&config);
+    let throttle = ConnectionThrottle::new(&config);
+    let logins = LoginThrottle::new(&config);
+    let server = Arc::new(Server {
+        config,
+        players: Mutex::new(Vec::new()),
+        joining: AtomicUsize::new(0),
+        world: Mutex::new(world),
+        end: Mutex::new(end),
+        scheduler: Mutex::new(Scheduler::new()),
+        tick_stats: Mutex::new(TickStats::new()),
+        profiler: Profiler::new(),
+        watchdog: Watchdog::new(),
+        commands: Mutex::new(CommandDispatcher::with_defaults()),
+        pending_commands: Mutex::new(Vec::new()),
+        logins: Mutex::new(logins),
+        remote_replies: RemoteReplies::new(),
+        autosave: AtomicBool::new(true),
+        function_commands: AtomicUsize::new(0),
+        backups: Backups::new(),
+        pregen: Mutex::new(None),
+        restart: Mutex::new(None),
+        datapacks: Mutex::new(datapacks),
+        maps: Mutex::new(MapStore::load(storage::world_dir())),
+        scoreboard: Mutex::new(Scoreboard::load(storage::world_dir())),
+        edits: Mutex::new(Editor::default()),
+        portals: Mutex::new(Portals::load()),
+        tracker: Mutex::new(EntityTracker::default()),
+        resource_pack,
+        motd,
+        favicon: Mutex::new(favicon::load(favicon::ICON_FILE)),
+        lang: Translations::load(lang::LANG_DIR),
+        bans: Mutex::new(BanList::load(banlist::BANNED_PLAYERS_FILE)),
+        permissions: Mutex::new(Permissions::load(permission::PERMISSIONS_FILE)),
+        events: EventBus::new(),
+        channels: ChannelRegistry::new(),
+        discord: DiscordBridge::default(),
+        plugins: PluginManager::new(),
+        wasm_plugins: WasmPluginManager::new(),
+        scripts: ScriptEngine::new(),
+    });
+    register_core_listeners(&server);
+    PluginManager::load_all(&server);
+    WasmPluginManager::load_all(&server);
+    ScriptEngine::load_all(&server);
+    rest::start(&server);
+    discord::start(&server);
+    function::resume_schedules(&server);
+    if server.config.pregen_radius > 0 {
+        if let Err(e) = pregen::start(&server, Dimension::Overworld, server.config.pregen_radius, None) {
+            warn!("Vorgenerierung nicht gestartet: {}", e);
+        }
+    }
+    {
+        let mut scheduler = server.scheduler.lock().unwrap();
+        scheduler.run_repeating(tick::TICKS_PER_SECOND, tick::TICKS_PER_SECOND, |server| server.broadcast_all_times());
+        let keep_alive_ticks = server.config.keep_alive_interval_secs.max(1) * tick::TICKS_PER_SECOND;
+        scheduler.run_repeating(keep_alive_ticks, keep_alive_ticks, |server| server.keep_alive());
+        scheduler.run_repeating(tick::TICKS_PER_SECOND, tick::TICKS_PER_SECOND, idle::tick);
+        let autosave_ticks = server.config.autosave_interval_secs * tick::TICKS_PER_SECOND;
+        if autosave_ticks > 0 {
+            scheduler.run_repeating(autosave_ticks, autosave_ticks, storage::autosave);
+        }
+        let backup_ticks = server.config.backup_interval_secs * tick::TICKS_PER_SECOND;
+        if backup_ticks > 0 {
+            scheduler.run_repeating(backup_ticks, backup_ticks, |server| {
+                if let Err(e) = server.backups.start(server) {
+                    warn!("Geplantes Backup übersprungen: {}", e);
+                }
+            });
+        }
+    }
+    let console_server = Arc::clone(&server);
+    thread::Builder::new()
+        .name("console".to_string())
+        .spawn(move || read_console(console_server))
+        .expect("Konnte Konsolen-Thread nicht starten");
+    let tick_server = Arc::clone(&server);
+    thread::Builder::new()
+        .name("tick".to_string())
+        .spawn(move || tick::run_tick_loop(tick_server))
+        .expect("Konnte Tick-Thread nicht starten");
+    watchdog::start(&server);
+    restart::handle_hangup();
+
+    let activated = if server.config.systemd_socket_activation { systemd::listeners() } else { Vec::new() };
+    if !activated.is_empty() {
+        info!("{} Sockets von systemd übernommen", activated.len());
+    }
+    let bound = if activated.is_empty() { listen::addresses(&server.config).and_then(|addresses| listen::bind_all(&addresses)) } else { Ok(activated) };
+    let listeners = match bound {
+        Ok(listeners) => listeners,
+        Err(message) => {
+            error!("{}", message);
+            console::LOG.release_stdout();
+            return Err(message);
+        }
+    };
+    for address in listeners.iter().filter_map(|listener| listener.local_addr().ok()) {
+        info!("Lausche auf {}", address);
+    }
+    let port = listeners[0].local_addr().map_or(server.config.server_port, |addr| addr.port());
+    info!("Server hört auf Port {}...", port);
+    systemd::ready(port);
+    listen::serve(&server, listeners, throttle);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Vanilla, also UUID.nameUUIDFromBytes, gibt Notch offline diese UUID
+    #[test]
+    fn offline_uuid_matches_vanilla() {
+        let uuid = offline_uuid("Notch");
+        assert_eq!(uuid.to_string(), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+        assert_eq!(uuid.get_version_num(), 3);
+        assert_eq!(uuid.get_variant(), uuid::Variant::RFC4122);
+    }
+}
@@ -0,0 +1,393 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use crate::block;
+use crate::chunk::{Heightmap, MIN_Y, SECTION_COUNT};
+use crate::dimension::{self, Dimension};
+use crate::protocol::clientbound;
+use crate::protocol::types::{write_varint_to_vec, BitSet};
+use crate::{Chunk, Player, Server, World};
+
+const WORKERS: usize = 2;
+const MAX_LEVEL: u8 = 15;
+// Weiter als 15 Blöcke reicht kein Licht, für einen Chunk genügen also er selbst und seine acht Nachbarn
+const REGION: usize = 48;
+// Jeder Auftrag kopiert bis zu neun Chunks auf dem Tick-Thread
+const MAX_JOBS_PER_TICK: usize = 32;
+const NIBBLES: usize = 2048;
+
+// Lichtstufen einer Sektion, zwei je Byte in der Reihenfolge der Blöcke. Meist überall gleich, etwa volles
+// Himmelslicht in der Luft
+#[derive(Clone, PartialEq)]
+enum Nibbles {
+    Uniform(u8),
+    Data(Box<[u8; NIBBLES]>),
+}
+
+impl Nibbles {
+    fn pack(levels: &[u8]) -> Nibbles {
+        if levels.iter().all(|&level| level == levels[0]) {
+            return Nibbles::Uniform(levels[0]);
+        }
+        let mut data = Box::new([0; NIBBLES]);
+        for (byte, pair) in data.iter_mut().zip(levels.chunks_exact(2)) {
+            *byte = pair[0] | pair[1] << 4;
+        }
+        Nibbles::Data(data)
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        match self {
+            Nibbles::Uniform(level) => *level,
+            Nibbles::Data(data) => data[index / 2] >> (index % 2 * 4) & 0x0F,
+        }
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            Nibbles::Uniform(level) => vec![level | level << 4; NIBBLES],
+            Nibbles::Data(data) => data.to_vec(),
+        }
+    }
+}
+
+// Himmels- und Blocklicht eines Chunks für alle Sektionen der Dimension und wie im Protokoll je eine darunter und
+// darüber
+pub struct ChunkLight {
+    sky: Vec<Nibbles>,
+    block: Vec<Nibbles>,
+    // Nachbarn, die beim Rechnen noch fehlten (Bit (dz + 1) * 3 + dx + 1); kommen sie dazu, wird neu gerechnet
+    missing: u16,
+}
+
+impl ChunkLight {
+    // Lichtteil von Chunk Data und Update Light mit den Sektionen, für die include gilt
+    fn write(&self, has_sky: bool, include: &dyn Fn(usize) -> bool, buf: &mut Vec<u8>) {
+        let mut masks = [BitSet::new(), BitSet::new(), BitSet::new(), BitSet::new()];
+        let mut arrays = [Vec::new(), Vec::new()];
+        for (layer, sections) in [&self.sky, &self.block].into_iter().enumerate() {
+            if layer == 0 && !has_sky {
+                continue;
+            }
+            for (i, nibbles) in sections.iter().enumerate().filter(|&(i, _)| include(i)) {
+                if *nibbles == Nibbles::Uniform(0) {
+                    masks[layer + 2].set(i, true);
+                } else {
+                    masks[layer].set(i, true);
+                    arrays[layer].push(nibbles.bytes());
+                }
+            }
+        }
+        for mask in &masks {
+            buf.extend(mask.to_bytes());
+        }
+        for layer in arrays {
+            buf.extend(write_varint_to_vec(layer.len() as i32));
+            for array in layer {
+                buf.extend(write_varint_to_vec(array.len() as i32));
+                buf.extend(array);
+            }
+        }
+    }
+}
+
+// Für Chunk Data; ohne berechnetes Licht gehen leere Masken mit, das Licht folgt dann als Update Light
+pub fn write_light(light: Option<&ChunkLight>, dimension: Dimension, buf: &mut Vec<u8>) {
+    match light {
+        Some(light) => light.write(dimension.has_sky_light(), &|_| true, buf),
+        None => {
+            for _ in 0..4 {
+                buf.extend(BitSet::new().to_bytes());
+            }
+            buf.extend(write_varint_to_vec(0));
+            buf.extend(write_varint_to_vec(0));
+        }
+    }
+}
+
+struct Job {
+    coords: (i32, i32),
+    // Kopien des Chunks und seiner Nachbarn, Index (dz + 1) * 3 + dx + 1
+    region: [Option<Arc<Chunk>>; 9],
+}
+
+// Berechnet das Licht einer Welt auf eigenen Threads, damit große Änderungen wie Explosionen oder /fill den Tick
+// nicht aufhalten. Jeder Auftrag rechnet einen ganzen Chunk neu; geschickt werden nur die Sektionen, die sich geändert
+// haben
+pub struct LightEngine {
+    levels: HashMap<(i32, i32), ChunkLight>,
+    // Sektionen mit geänderten Blöcken als (Chunk x, Sektion von unten, Chunk z)
+    dirty: HashSet<(i32, i32, i32)>,
+    queued: HashSet<(i32, i32)>,
+    in_flight: HashSet<(i32, i32)>,
+    jobs: Option<Sender<Job>>,
+    results: Receiver<((i32, i32), ChunkLight)>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl LightEngine {
+    pub fn new(dimension: Dimension) -> LightEngine {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let (done, results) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..WORKERS)
+            .map(|i| {
+                let receiver = Arc::clone(&receiver);
+                let done = done.clone();
+                thread::Builder::new()
+                    .name(format!("light-{}", i))
+                    .spawn(move || loop {
+                        let job = receiver.lock().unwrap().recv();
+                        let Ok(job) = job else { return };
+                        if done.send((job.coords, compute(dimension, &job.region))).is_err() {
+                            return;
+                        }
+                    })
+                    .expect("Konnte Licht-Thread nicht starten")
+            })
+            .collect();
+        LightEngine {
+            levels: HashMap::new(),
+            dirty: HashSet::new(),
+            queued: HashSet::new(),
+            in_flight: HashSet::new(),
+            jobs: Some(jobs),
+            results,
+            workers,
+        }
+    }
+
+    pub fn get(&self, coords: (i32, i32)) -> Option<&ChunkLight> {
+        self.levels.get(&coords)
+    }
+
+    // (Himmelslicht, Blocklicht) an einer Stelle, ohne Verdunkelung durch die Tageszeit; None, solange der Chunk noch
+    // nicht gerechnet ist
+    pub fn level_at(&self, dimension: Dimension, (x, y, z): (i32, i32, i32)) -> Option<(u8, u8)> {
+        let light = self.levels.get(&(x >> 4, z >> 4))?;
+        let section = (y - dimension.min_y()).div_euclid(16) + 1;
+        let section = (section.max(0) as usize).min(light.sky.len() - 1);
+        let index = ((y.rem_euclid(16) as usize) << 8) | ((z & 15) as usize) << 4 | (x & 15) as usize;
+        Some((light.sky[section].get(index), light.block[section].get(index)))
+    }
+
+    pub fn block_changed(&mut self, (x, y, z): (i32, i32, i32), old: u16, new: u16) {
+        if properties(old) != properties(new) {
+            self.dirty.insert((x >> 4, (y - MIN_Y) >> 4, z >> 4));
+        }
+    }
+
+    // Ein neu geladener oder generierter Chunk; Nachbarn, die ohne ihn gerechnet wurden, kommen noch einmal dran
+    pub fn chunk_added(&mut self, (chunk_x, chunk_z): (i32, i32)) {
+        self.queued.insert((chunk_x, chunk_z));
+        for (i, (dx, dz)) in neighbors().enumerate() {
+            let neighbor = (chunk_x - dx, chunk_z - dz);
+            if self.levels.get(&neighbor).is_some_and(|light| light.missing & 1 << i != 0) {
+                self.queued.insert(neighbor);
+            }
+        }
+    }
+
+    // Ergebnisse noch laufender Aufträge verwirft apply, weil ihre Chunks fehlen
+    pub fn clear(&mut self) {
+        self.levels.clear();
+        self.dirty.clear();
+        self.queued.clear();
+    }
+
+    pub fn shut_down(&mut self) {
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for LightEngine {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+// (dx, dz) in der Reihenfolge der Region
+fn neighbors() -> impl Iterator<Item = (i32, i32)> {
+    (0..9).map(|i| (i % 3 - 1, i / 3 - 1))
+}
+
+// (Lichtdurchlässigkeit, Leuchtkraft) je Zustand; block::light_opacity und light_emission suchen nach Namen
+fn properties(state: u16) -> (u8, u8) {
+    static TABLE: OnceLock<Vec<(u8, u8)>> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let end = block::state_ranges().map(|(_, first, count)| first + count).max().unwrap_or(0);
+        (0..end).map(|state| (block::light_opacity(state), block::light_emission(state))).collect()
+    });
+    table.get(state as usize).copied().unwrap_or((MAX_LEVEL, 0))
+}
+
+// Rechnet das Licht des mittleren Chunks der Region von Grund auf. Fehlende Nachbarn gelten als undurchsichtig
+fn compute(dimension: Dimension, region: &[Option<Arc<Chunk>>; 9]) -> ChunkLight {
+    let (min_y, height) = (dimension.min_y(), dimension.height());
+    let has_sky = dimension.has_sky_light();
+    let missing = region.iter().enumerate().filter(|(_, chunk)| chunk.is_none()).fold(0, |missing, (i, _)| missing | 1 << i);
+    // Über dem höchsten Block ist nur Luft mit vollem Himmelslicht, und Blocklicht reicht höchstens 15 darüber hinaus
+    let top = region.iter().flatten().flat_map(|chunk| (0..256).map(|column| chunk.height(Heightmap::WorldSurface, column & 15, column >> 4))).max().unwrap_or(min_y);
+    let span = (top - min_y + MAX_LEVEL as i32).clamp(0, height) as usize;
+    let index = |x: usize, y: usize, z: usize| (y * REGION + z) * REGION + x;
+    let mut opacity = vec![0; REGION * REGION * span];
+    let mut block_light = vec![0; opacity.len()];
+    for (i, chunk) in region.iter().enumerate() {
+        let (origin_x, origin_z) = (i % 3 * 16, i / 3 * 16);
+        let empty: Vec<bool> = (0..SECTION_COUNT).map(|s| chunk.as_ref().is_some_and(|c| !c.section_may_contain(s, &|state| state != block::AIR))).collect();
+        for y in 0..span {
+            let world_y = min_y + y as i32;
+            if empty[((world_y - MIN_Y) >> 4) as usize] {
+                continue;
+            }
+            for z in 0..16 {
+                for x in 0..16 {
+                    let cell = index(origin_x + x, y, origin_z + z);
+                    let (cell_opacity, emission) = match chunk {
+                        Some(chunk) => properties(chunk.get_block(x, world_y, z)),
+                        None => (MAX_LEVEL, 0),
+                    };
+                    opacity[cell] = cell_opacity;
+                    block_light[cell] = emission;
+                }
+            }
+        }
+    }
+    let mut sky_light = vec![0; opacity.len()];
+    if has_sky {
+        for column in 0..REGION * REGION {
+            for y in (0..span).rev() {
+                let cell = y * REGION * REGION + column;
+                if opacity[cell] != 0 {
+                    break;
+                }
+                sky_light[cell] = MAX_LEVEL;
+            }
+        }
+        propagate(&mut sky_light, &opacity, span);
+    }
+    propagate(&mut block_light, &opacity, span);
+
+    let sections = (height >> 4) as usize;
+    let above = if has_sky { MAX_LEVEL } else { 0 };
+    let mut light = ChunkLight { sky: vec![Nibbles::Uniform(0)], block: vec![Nibbles::Uniform(0)], missing };
+    let mut levels = vec![0; 4096];
+    for section in 0..sections {
+        for (values, out, above) in [(&sky_light, &mut light.sky, above), (&block_light, &mut light.block, 0)] {
+            for (local, level) in levels.iter_mut().enumerate() {
+                let y = section * 16 + (local >> 8);
+                *level = if y < span { values[index(16 + (local & 15), y, 16 + (local >> 4 & 15))] } else { above };
+            }
+            out.push(Nibbles::pack(&levels));
+        }
+    }
+    light.sky.push(Nibbles::Uniform(above));
+    light.block.push(Nibbles::Uniform(0));
+    light
+}
+
+// Breitet Licht von allen hellen Zellen aus; jeder Schritt kostet eine Stufe oder die Lichtdurchlässigkeit des Ziels
+fn propagate(levels: &mut [u8], opacity: &[u8], span: usize) {
+    let layer = REGION * REGION;
+    let mut queue: VecDeque<usize> = (0..levels.len()).filter(|&cell| levels[cell] > 1).collect();
+    while let Some(cell) = queue.pop_front() {
+        let level = levels[cell];
+        let (x, z, y) = (cell % REGION, cell / REGION % REGION, cell / layer);
+        let mut spread = |next: usize| {
+            let lit = level.saturating_sub(opacity[next].max(1));
+            if lit > levels[next] {
+                levels[next] = lit;
+                if lit > 1 {
+                    queue.push_back(next);
+                }
+            }
+        };
+        if x > 0 {
+            spread(cell - 1);
+        }
+        if x + 1 < REGION {
+            spread(cell + 1);
+        }
+        if z > 0 {
+            spread(cell - REGION);
+        }
+        if z + 1 < REGION {
+            spread(cell + REGION);
+        }
+        if y > 0 {
+            spread(cell - layer);
+        }
+        if y + 1 < span {
+            spread(cell + layer);
+        }
+    }
+}
+
+// Übernimmt fertiges Licht und schickt geänderte Sektionen an alle, die den Chunk geladen haben
+fn apply(players: &[Player], world: &mut World) {
+    let has_sky = world.dimension.has_sky_light();
+    let finished: Vec<((i32, i32), ChunkLight)> = world.light.results.try_iter().collect();
+    for (coords, light) in finished {
+        world.light.in_flight.remove(&coords);
+        if !world.chunks.contains_key(&coords) {
+            continue;
+        }
+        let changed: Vec<bool> = match world.light.levels.get(&coords) {
+            Some(old) => (0..light.sky.len()).map(|i| old.sky[i] != light.sky[i] || old.block[i] != light.block[i]).collect(),
+            None => vec![true; light.sky.len()],
+        };
+        if changed.contains(&true) {
+            if let Some(viewers) = world.chunk_viewers.get(&coords) {
+                let mut packet_data = write_varint_to_vec(coords.0);
+                packet_data.extend(write_varint_to_vec(coords.1));
+                light.write(has_sky, &|i| changed[i], &mut packet_data);
+                for player in players.iter().filter(|p| p.dimension == world.dimension && viewers.contains(&p.uuid)) {
+                    let _ = player.connection.send(clientbound::UPDATE_LIGHT, &packet_data);
+                }
+            }
+        }
+        world.light.levels.insert(coords, light);
+    }
+}
+
+// Vergibt Aufträge für Chunks mit geänderten Sektionen und deren Nachbarn; was gerade gerechnet wird, wartet auf das
+// Ergebnis und kommt danach noch einmal dran
+fn dispatch(world: &mut World) {
+    let light = &mut world.light;
+    for (chunk_x, _, chunk_z) in std::mem::take(&mut light.dirty) {
+        light.queued.extend(neighbors().map(|(dx, dz)| (chunk_x + dx, chunk_z + dz)));
+    }
+    let ready: Vec<(i32, i32)> = light.queued.iter().copied().filter(|coords| !light.in_flight.contains(coords)).take(MAX_JOBS_PER_TICK).collect();
+    let mut snapshots: HashMap<(i32, i32), Option<Arc<Chunk>>> = HashMap::new();
+    for coords in ready {
+        light.queued.remove(&coords);
+        if !world.chunks.contains_key(&coords) {
+            continue;
+        }
+        let mut region: [Option<Arc<Chunk>>; 9] = Default::default();
+        for (slot, (dx, dz)) in region.iter_mut().zip(neighbors()) {
+            let neighbor = (coords.0 + dx, coords.1 + dz);
+            *slot = snapshots.entry(neighbor).or_insert_with(|| world.chunks.get(&neighbor).map(|chunk| Arc::new(chunk.clone()))).clone();
+        }
+        if let Some(jobs) = &light.jobs {
+            light.in_flight.insert(coords);
+            let _ = jobs.send(Job { coords, region });
+        }
+    }
+}
+
+// Nach den Blockänderungen des Ticks, damit das Licht nicht vor den Blöcken ankommt. Darf nur ohne gehaltene Sperren
+// aufgerufen werden
+pub fn tick(server: &Server) {
+    let players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let _profile = server.profiler.world("lighting", world.dimension);
+        apply(&players, &mut world);
+        dispatch(&mut world);
+    }
+}
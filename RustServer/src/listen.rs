@@ -0,0 +1,150 @@
+use std::io;
+use std::net::{IpAddr, Shutdown, SocketAddr, SocketAddrV6, TcpListener, ToSocketAddrs};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use crate::config::ServerConfig;
+use crate::throttle::ConnectionThrottle;
+use crate::{accept_proxied, handle_client, Server};
+
+// Wie TcpListener::bind unter Linux
+const BACKLOG: libc::c_int = 128;
+
+// server-addresses: weitere Adressen als ip:port, durch Kommas getrennt, IPv6 in eckigen Klammern wie [::1]:25565
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BindAddresses(pub Vec<SocketAddr>);
+
+impl std::str::FromStr for BindAddresses {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<BindAddresses, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(|a| a.parse().map_err(|_| format!("Invalid address '{}', expected ip:port", a)))
+            .collect::<Result<_, _>>()
+            .map(BindAddresses)
+    }
+}
+
+impl std::fmt::Display for BindAddresses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let addresses: Vec<String> = self.0.iter().map(SocketAddr::to_string).collect();
+        f.write_str(&addresses.join(","))
+    }
+}
+
+// server-ip und server-port zuerst, dann server-addresses ohne Doppelte. Port 0 darf mehrfach vorkommen, jeder
+// Listener bekommt dann einen eigenen freien Port
+pub fn addresses(config: &ServerConfig) -> Result<Vec<SocketAddr>, String> {
+    let ip = if config.server_ip.is_empty() { "0.0.0.0" } else { config.server_ip.as_str() };
+    let primary = (ip, config.server_port)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut found| found.next())
+        .ok_or_else(|| format!("Konnte server-ip {} nicht auflösen", ip))?;
+    let mut addresses = vec![primary];
+    for address in &config.server_addresses.0 {
+        if address.port() == 0 || !addresses.contains(address) {
+            addresses.push(*address);
+        }
+    }
+    Ok(addresses)
+}
+
+// Öffnet alle Adressen, bevor die erste Verbindung angenommen wird: schlägt eine fehl, startet der Server gar nicht
+pub fn bind_all(addresses: &[SocketAddr]) -> Result<Vec<TcpListener>, String> {
+    addresses.iter().map(|address| bind(*address).map_err(|e| format!("Konnte {} nicht öffnen: {}", address, e))).collect()
+}
+
+fn bind(address: SocketAddr) -> io::Result<TcpListener> {
+    match address {
+        SocketAddr::V6(v6) if v6.ip().is_unspecified() => bind_dual_stack(v6),
+        _ => TcpListener::bind(address),
+    }
+}
+
+// [::] nimmt auch IPv4 an. std lässt IPV6_V6ONLY dem System (net.ipv6.bindv6only), setzen lässt es sich nur vor bind
+fn bind_dual_stack(address: SocketAddrV6) -> io::Result<TcpListener> {
+    let check = |result: libc::c_int| if result < 0 { Err(io::Error::last_os_error()) } else { Ok(result) };
+    let fd = check(unsafe { libc::socket(libc::AF_INET6, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0) })?;
+    // Schließt den Socket auch, wenn einer der folgenden Schritte scheitert
+    let listener = unsafe { TcpListener::from_raw_fd(fd) };
+    let set = |level, option, value: libc::c_int| {
+        let size = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+        check(unsafe { libc::setsockopt(fd, level, option, &value as *const libc::c_int as *const libc::c_void, size) })
+    };
+    set(libc::IPPROTO_IPV6, libc::IPV6_V6ONLY, 0)?;
+    // Wie TcpListener::bind, damit ein Neustart den Port sofort wieder bekommt
+    set(libc::SOL_SOCKET, libc::SO_REUSEADDR, 1)?;
+    let mut raw: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+    raw.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+    raw.sin6_port = address.port().to_be();
+    raw.sin6_addr.s6_addr = address.ip().octets();
+    raw.sin6_flowinfo = address.flowinfo();
+    raw.sin6_scope_id = address.scope_id();
+    let size = std::mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t;
+    check(unsafe { libc::bind(listener.as_raw_fd(), &raw as *const libc::sockaddr_in6 as *const libc::sockaddr, size) })?;
+    check(unsafe { libc::listen(listener.as_raw_fd(), BACKLOG) })?;
+    Ok(listener)
+}
+
+// IPv4-Clients an einem Dual-Stack-Listener kommen als ::ffff:a.b.c.d an; Bans, Drosselung und Logs sollen sie
+// aber unter ihrer IPv4-Adresse sehen
+pub fn canonical(address: SocketAddr) -> SocketAddr {
+    match address {
+        SocketAddr::V6(v6) => match v6.ip().to_ipv4_mapped() {
+            Some(v4) => SocketAddr::new(IpAddr::V4(v4), v6.port()),
+            None => address,
+        },
+        v4 => v4,
+    }
+}
+
+// Jeder Listener nimmt in einem eigenen Thread an; alle laufen in dieselbe Drosselung und denselben Login. Kehrt nicht
+// zurück, solange einer davon läuft
+pub fn serve(server: &Arc<Server>, listeners: Vec<TcpListener>, throttle: ConnectionThrottle) {
+    let throttle = Arc::new(Mutex::new(throttle));
+    let threads: Vec<_> = listeners
+        .into_iter()
+        .map(|listener| {
+            let (server, throttle) = (Arc::clone(server), Arc::clone(&throttle));
+            thread::Builder::new()
+                .name("listener".to_string())
+                .spawn(move || accept(listener, server, throttle))
+                .expect("Konnte Listener-Thread nicht starten")
+        })
+        .collect();
+    for thread in threads {
+        let _ = thread.join();
+    }
+}
+
+fn accept(listener: TcpListener, server: Arc<Server>, throttle: Arc<Mutex<ConnectionThrottle>>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Verbindung fehlgeschlagen: {}", e);
+                continue;
+            }
+        };
+        let Ok(peer_addr) = stream.peer_addr().map(canonical) else { continue };
+        let server = Arc::clone(&server);
+        if server.config.proxy_protocol {
+            // Hinter einem Load Balancer ist die echte IP erst nach dem PROXY-Header bekannt
+            let throttle = Arc::clone(&throttle);
+            thread::spawn(move || accept_proxied(stream, peer_addr, server, &throttle));
+            continue;
+        }
+        let pending = match throttle.lock().unwrap().try_accept(peer_addr.ip()) {
+            Ok(guard) => guard,
+            Err(e) => {
+                warn!("Verbindung abgelehnt: {}", e);
+                let _ = stream.shutdown(Shutdown::Both);
+                continue;
+            }
+        };
+        thread::spawn(move || handle_client(stream, peer_addr, server, pending));
+    }
+}
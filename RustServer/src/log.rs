@@ -0,0 +1,193 @@
+use std::cell::RefCell;
+use std::fmt::{self, Display, Write as _};
+use std::io::Write as _;
+use std::marker::PhantomData;
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+use crate::clock::UtcTime;
+use crate::logfile::LogFile;
+
+// Protokolliert eine Meldung mit optionalen Feldern: info!(player = name, "Spieler {} beigetreten", name)
+macro_rules! log_event {
+    ($level:expr, $($key:ident = $value:expr,)* $fmt:literal $($arg:tt)*) => {{
+        let level = $level;
+        if $crate::log::enabled(level, module_path!()) {
+            $crate::log::write(level, module_path!(), format_args!($fmt $($arg)*), &[$((stringify!($key), &$value as &dyn ::std::fmt::Display)),*]);
+        }
+    }};
+}
+
+macro_rules! error {
+    ($($arg:tt)+) => { log_event!($crate::log::Level::Error, $($arg)+) };
+}
+
+macro_rules! warn {
+    ($($arg:tt)+) => { log_event!($crate::log::Level::Warn, $($arg)+) };
+}
+
+macro_rules! info {
+    ($($arg:tt)+) => { log_event!($crate::log::Level::Info, $($arg)+) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)+) => { log_event!($crate::log::Level::Debug, $($arg)+) };
+}
+
+macro_rules! trace {
+    ($($arg:tt)+) => { log_event!($crate::log::Level::Trace, $($arg)+) };
+}
+
+// Öffnet einen Span bis zum Ende des Gültigkeitsbereichs: let _span = span!("packet", id = packet_id);
+// Seine Felder hängen an allen Meldungen des Threads, beim Schließen wird die Dauer auf TRACE protokolliert
+macro_rules! span {
+    ($name:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::log::enter($name, module_path!(), &[$((stringify!($key), &$value as &dyn ::std::fmt::Display)),*])
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+// None heißt "off"
+fn parse_level(text: &str) -> Result<Option<Level>, String> {
+    let level = match text.trim().to_ascii_lowercase().as_str() {
+        "off" => None,
+        "error" => Some(Level::Error),
+        "warn" => Some(Level::Warn),
+        "info" => Some(Level::Info),
+        "debug" => Some(Level::Debug),
+        "trace" => Some(Level::Trace),
+        other => return Err(format!("Unbekanntes Log-Level \"{}\"", other)),
+    };
+    Ok(level)
+}
+
+pub type Field<'a> = (&'static str, &'a dyn Display);
+
+struct Filter {
+    default: Option<Level>,
+    // Nach Länge absteigend, damit lua::interp vor lua greift
+    targets: Vec<(String, Option<Level>)>,
+}
+
+static FILTER: RwLock<Filter> = RwLock::new(Filter { default: Some(Level::Info), targets: Vec::new() });
+
+// Filter im Stil von RUST_LOG: "info,lua=debug,rest=warn". Ziele sind Modulpfade ohne Crate-Namen
+pub fn configure(spec: &str) -> Result<(), String> {
+    let mut filter = Filter { default: Some(Level::Info), targets: Vec::new() };
+    for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        match directive.split_once('=') {
+            Some((target, level)) => filter.targets.push((target.trim().to_string(), parse_level(level)?)),
+            None => filter.default = parse_level(directive)?,
+        }
+    }
+    filter.targets.sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+    *FILTER.write().unwrap() = filter;
+    Ok(())
+}
+
+// Die Wurzel der Crate heißt im Log "server"
+fn target(module_path: &'static str) -> &'static str {
+    module_path.split_once("::").map_or("server", |(_, rest)| rest)
+}
+
+pub fn enabled(level: Level, module_path: &'static str) -> bool {
+    let target = target(module_path);
+    let filter = FILTER.read().unwrap();
+    let max = filter
+        .targets
+        .iter()
+        .find(|(t, _)| target.strip_prefix(t.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with("::")))
+        .map_or(filter.default, |(_, level)| *level);
+    max.is_some_and(|max| level <= max)
+}
+
+fn append_fields(line: &mut String, fields: &[Field]) {
+    for (key, value) in fields {
+        let _ = write!(line, " {}={}", key, value);
+    }
+}
+
+static FILE: Mutex<Option<LogFile>> = Mutex::new(None);
+
+// Schreibt ab jetzt zusätzlich nach logs/latest.log
+pub fn log_to_file(dir: &str, max_size: u64) -> Result<(), String> {
+    *FILE.lock().unwrap() = Some(LogFile::open(dir, max_size)?);
+    Ok(())
+}
+
+pub fn write(level: Level, module_path: &'static str, message: fmt::Arguments, fields: &[Field]) {
+    let now = UtcTime::now();
+    let mut line = format!("[{} {}] [{}] {}", now.time(), level.name(), target(module_path), message);
+    append_fields(&mut line, fields);
+    SPANS.with(|spans| {
+        for span in spans.borrow().iter() {
+            line.push_str(&span.fields);
+        }
+    });
+    let _ = writeln!(std::io::stdout().lock(), "{}", line);
+    let result = FILE.lock().unwrap().as_mut().map(|file| file.write_line(now.date(), &line));
+    // Nur auf die Konsole, ein Fehler beim Schreiben in die Datei würde sich sonst endlos wiederholen
+    if let Some(Err(e)) = result {
+        let _ = writeln!(std::io::stdout().lock(), "[{} {}] [log] {}", now.time(), Level::Error.name(), e);
+    }
+}
+
+// Panics landen sonst nur auf stderr und fehlen in der Logdatei
+pub fn log_panics() {
+    std::panic::set_hook(Box::new(|info| {
+        let thread = std::thread::current();
+        error!("Panic im Thread {}: {}", thread.name().unwrap_or("<unbenannt>"), info);
+    }));
+}
+
+struct SpanData {
+    name: &'static str,
+    module_path: &'static str,
+    fields: String,
+    start: Instant,
+}
+
+thread_local! {
+    static SPANS: RefCell<Vec<SpanData>> = const { RefCell::new(Vec::new()) };
+}
+
+// Spans gehören zum Thread, der sie geöffnet hat, daher ist der Guard nicht Send
+pub struct Span {
+    _thread: PhantomData<*const ()>,
+}
+
+pub fn enter(name: &'static str, module_path: &'static str, fields: &[Field]) -> Span {
+    let mut rendered = String::new();
+    append_fields(&mut rendered, fields);
+    SPANS.with(|spans| spans.borrow_mut().push(SpanData { name, module_path, fields: rendered, start: Instant::now() }));
+    Span { _thread: PhantomData }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(span) = SPANS.with(|spans| spans.borrow_mut().pop()) else { return };
+        if enabled(Level::Trace, span.module_path) {
+            let elapsed = format!("{:.3}ms", span.start.elapsed().as_secs_f64() * 1000.0);
+            write(Level::Trace, span.module_path, format_args!("{}{{{}}} beendet", span.name, span.fields.trim_start()), &[("elapsed", &elapsed)]);
+        }
+    }
+}
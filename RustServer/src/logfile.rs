@@ -0,0 +1,91 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::UNIX_EPOCH;
+use crate::clock::UtcTime;
+use crate::gzip;
+
+pub const LOG_DIR: &str = "logs";
+const LATEST_LOG: &str = "latest.log";
+
+// logs/latest.log, das täglich und ab einer Maximalgröße als logs/<Datum>-<N>.log.gz archiviert wird
+pub struct LogFile {
+    dir: PathBuf,
+    file: File,
+    date: String,
+    size: u64,
+    max_size: u64,
+}
+
+impl LogFile {
+    // max_size 0 schaltet die Rotation nach Größe ab
+    pub fn open(dir: &str, max_size: u64) -> Result<LogFile, String> {
+        let dir = PathBuf::from(dir);
+        fs::create_dir_all(&dir).map_err(|e| format!("Konnte {} nicht anlegen: {}", dir.display(), e))?;
+        compress_leftovers(&dir);
+        let latest = dir.join(LATEST_LOG);
+        // Wie bei Vanilla wird die latest.log des letzten Laufs beim Start archiviert
+        if let Ok(metadata) = fs::metadata(&latest) {
+            let modified = metadata.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map_or(0, |d| d.as_secs());
+            archive(&dir, &UtcTime::from_unix(modified).date())?;
+        }
+        Ok(LogFile { file: open_latest(&dir)?, dir, date: UtcTime::now().date(), size: 0, max_size })
+    }
+
+    pub fn write_line(&mut self, date: String, line: &str) -> Result<(), String> {
+        if date != self.date || (self.max_size > 0 && self.size >= self.max_size) {
+            // Auch bei einem Fehler nicht bei jeder Zeile erneut rotieren
+            let previous = std::mem::replace(&mut self.date, date);
+            self.size = 0;
+            archive(&self.dir, &previous)?;
+            self.file = open_latest(&self.dir)?;
+        }
+        self.file.write_all(format!("{}\n", line).as_bytes()).map_err(|e| format!("Konnte nicht in {} schreiben: {}", LATEST_LOG, e))?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+}
+
+fn open_latest(dir: &Path) -> Result<File, String> {
+    let path = dir.join(LATEST_LOG);
+    OpenOptions::new().create(true).append(true).open(&path).map_err(|e| format!("Konnte {} nicht öffnen: {}", path.display(), e))
+}
+
+// Benennt latest.log nach dem ersten freien Index des Tages um und packt sie im Hintergrund
+fn archive(dir: &Path, date: &str) -> Result<(), String> {
+    let index = (1..)
+        .find(|n| !dir.join(format!("{}-{}.log.gz", date, n)).exists() && !dir.join(format!("{}-{}.log", date, n)).exists())
+        .unwrap();
+    let target = dir.join(format!("{}-{}.log", date, index));
+    fs::rename(dir.join(LATEST_LOG), &target).map_err(|e| format!("Konnte {} nicht archivieren: {}", LATEST_LOG, e))?;
+    thread::spawn(move || {
+        if let Err(e) = compress_file(&target) {
+            warn!("{}", e);
+        }
+    });
+    Ok(())
+}
+
+fn compress_file(path: &Path) -> Result<(), String> {
+    let data = fs::read(path).map_err(|e| format!("Konnte {} nicht lesen: {}", path.display(), e))?;
+    let mtime = fs::metadata(path).ok().and_then(|m| m.modified().ok()).and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map_or(0, |d| d.as_secs());
+    let mut target = path.as_os_str().to_owned();
+    target.push(".gz");
+    fs::write(&target, gzip::compress(&data, mtime as u32)).map_err(|e| format!("Konnte {} nicht schreiben: {}", Path::new(&target).display(), e))?;
+    fs::remove_file(path).map_err(|e| format!("Konnte {} nicht löschen: {}", path.display(), e))
+}
+
+// Archive, deren Komprimierung beim letzten Beenden abgebrochen wurde
+fn compress_leftovers(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().is_some_and(|ext| ext == "log") && path.file_name().is_some_and(|name| name != LATEST_LOG) {
+            thread::spawn(move || {
+                if let Err(e) = compress_file(&path) {
+                    warn!("{}", e);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use rand::rngs::ThreadRng;
+use rand::Rng;
+use crate::item::{self, ItemStack, ToolTier};
+use crate::json::Json;
+use crate::{block, Server};
+
+// Beute eines abgebauten Blocks aus minecraft:blocks/<name>, sofern ein Datenpaket die Tabelle liefert
+pub fn block_drops(server: &Server, state: u16) -> Vec<ItemStack> {
+    let datapacks = server.datapacks.lock().unwrap();
+    let name = block::name(state);
+    let table = format!("minecraft:blocks/{}", name.strip_prefix("minecraft:").unwrap_or(name));
+    datapacks.registries.loot_tables.get(&table).map(|table| roll(table, Some(state))).unwrap_or_default()
+}
+
+// Wie die Block-Tags minecraft:needs_<stufe>_tool: ohne Spitzhacke dieser Stufe lassen die Blöcke nichts fallen
+const NEEDS_STONE_TOOL: &[&str] = &[
+    "iron_ore", "deepslate_iron_ore", "iron_block", "raw_iron_block", "copper_ore", "deepslate_copper_ore", "copper_block", "raw_copper_block",
+    "lapis_ore", "deepslate_lapis_ore", "lapis_block",
+];
+const NEEDS_IRON_TOOL: &[&str] = &[
+    "diamond_ore", "deepslate_diamond_ore", "diamond_block", "gold_ore", "deepslate_gold_ore", "gold_block", "raw_gold_block", "emerald_ore",
+    "deepslate_emerald_ore", "emerald_block", "redstone_ore", "deepslate_redstone_ore",
+];
+const NEEDS_DIAMOND_TOOL: &[&str] = &["obsidian", "crying_obsidian", "netherite_block", "ancient_debris", "respawn_anchor"];
+
+pub fn can_harvest(state: u16, tool: &ItemStack) -> bool {
+    let name = block::name(state);
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    let required = if NEEDS_DIAMOND_TOOL.contains(&name) {
+        ToolTier::Diamond
+    } else if NEEDS_IRON_TOOL.contains(&name) {
+        ToolTier::Iron
+    } else if NEEDS_STONE_TOOL.contains(&name) {
+        ToolTier::Stone
+    } else {
+        return true;
+    };
+    tool.item.ends_with("_pickaxe") && item::tool_tier(&tool.item).is_some_and(|tier| tier >= required)
+}
+
+// Wertet eine Loot-Tabelle aus Datenpaketen aus. Unterstützt werden Pools mit rolls, gewichtete item-, empty- und
+// alternatives-Einträge, die Bedingungen random_chance, table_bonus, survives_explosion und block_state_property samt
+// inverted, any_of und all_of sowie die Funktionen set_count und apply_bonus. Einträge mit anderen Bedingungen (etwa killed_by_player) greifen nie,
+// unbekannte Funktionen werden übergangen
+pub fn generate(table: &Json) -> Vec<ItemStack> {
+    roll(table, None)
+}
+
+// state ist der abgebaute Block, falls die Tabelle zu einem gehört
+fn roll(table: &Json, state: Option<u16>) -> Vec<ItemStack> {
+    let mut rng = rand::thread_rng();
+    let mut drops = Vec::new();
+    for pool in table.get("pools").and_then(Json::as_array).into_iter().flatten() {
+        if !conditions_pass(pool, state, &mut rng) {
+            continue;
+        }
+        let rolls = pool.get("rolls").map_or(1.0, |r| number(r, &mut rng)).max(0.0) as u32;
+        let entries: Vec<&Json> = pool
+            .get("entries")
+            .and_then(Json::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|e| conditions_pass(e, state, &mut rng))
+            .collect();
+        for _ in 0..rolls {
+            let Some(entry) = pick(&entries, &mut rng) else { continue };
+            if let Some(stack) = entry_stack(entry, state, &mut rng) {
+                drops.push(stack);
+            }
+        }
+    }
+    drops
+}
+
+fn conditions_pass(value: &Json, state: Option<u16>, rng: &mut ThreadRng) -> bool {
+    value.get("conditions").and_then(Json::as_array).into_iter().flatten().all(|condition| holds(condition, state, rng))
+}
+
+// Werkzeuge kennt der Kontext nicht: match_tool gilt nie, table_bonus nimmt die Chance ohne Verzauberung
+fn holds(condition: &Json, state: Option<u16>, rng: &mut ThreadRng) -> bool {
+    let terms = || condition.get("terms").and_then(Json::as_array).into_iter().flatten();
+    match condition.get("condition").and_then(Json::as_str).map(item::namespaced).as_deref() {
+        Some("minecraft:random_chance") => rng.gen::<f64>() < condition.get("chance").map_or(1.0, |c| number(c, rng)),
+        Some("minecraft:table_bonus") => {
+            let chance = condition.get("chances").and_then(Json::as_array).and_then(|c| c.first()).and_then(Json::as_f64).unwrap_or(0.0);
+            rng.gen::<f64>() < chance
+        }
+        // Den Zerfall bei Explosionen würfelt explosion selbst aus
+        Some("minecraft:survives_explosion") => true,
+        Some("minecraft:block_state_property") => state.is_some_and(|state| state_matches(condition, state)),
+        Some("minecraft:inverted") => condition.get("term").is_some_and(|term| !holds(term, state, rng)),
+        Some("minecraft:any_of") => terms().any(|term| holds(term, state, rng)),
+        Some("minecraft:all_of") => terms().all(|term| holds(term, state, rng)),
+        _ => false,
+    }
+}
+
+// Nur genaue Werte, keine Bereiche mit min und max
+fn state_matches(condition: &Json, state: u16) -> bool {
+    let block = condition.get("block").and_then(Json::as_str).map(item::namespaced);
+    block.as_deref() == Some(block::name(state))
+        && condition.get("properties").and_then(Json::as_object).into_iter().flatten().all(|(key, value)| {
+            let expected = value.as_str().map(str::to_string).or_else(|| value.as_f64().map(|n| n.to_string()));
+            block::property(state, key).is_some_and(|actual| Some(actual) == expected.as_deref())
+        })
+}
+
+fn pick<'a>(entries: &[&'a Json], rng: &mut ThreadRng) -> Option<&'a Json> {
+    let weight = |e: &Json| e.get("weight").and_then(Json::as_f64).unwrap_or(1.0).max(0.0);
+    let total: f64 = entries.iter().map(|e| weight(e)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut roll = rng.gen_range(0.0..total);
+    for entry in entries {
+        roll -= weight(entry);
+        if roll < 0.0 {
+            return Some(entry);
+        }
+    }
+    entries.last().copied()
+}
+
+fn entry_stack(entry: &Json, state: Option<u16>, rng: &mut ThreadRng) -> Option<ItemStack> {
+    match entry.get("type").and_then(Json::as_str).map(item::namespaced).as_deref() {
+        Some("minecraft:item") => {}
+        // Das erste Kind, dessen Bedingungen gelten
+        Some("minecraft:alternatives") => {
+            let children = entry.get("children").and_then(Json::as_array)?;
+            let child = children.iter().find(|child| conditions_pass(child, state, rng))?;
+            return entry_stack(child, state, rng);
+        }
+        _ => return None,
+    }
+    let id = item::namespaced(entry.get("name").and_then(Json::as_str)?);
+    if item::protocol_id(&id).is_none() {
+        debug!("Loot-Tabelle nennt unbekanntes Item {}", id);
+        return None;
+    }
+    let mut count = 1.0;
+    for function in entry.get("functions").and_then(Json::as_array).into_iter().flatten() {
+        if function.get("function").and_then(Json::as_str).map(item::namespaced).as_deref() == Some("minecraft:set_count") {
+            let value = function.get("count").map_or(1.0, |c| number(c, rng));
+            let add = function.get("add").and_then(Json::as_bool).unwrap_or(false);
+            count = if add { count + value } else { value };
+        }
+        // Werkzeuge mit Glück gibt es noch nicht, also nur der Grundbonus von binomial_with_bonus_count
+        if function.get("function").and_then(Json::as_str).map(item::namespaced).as_deref() == Some("minecraft:apply_bonus")
+            && function.get("formula").and_then(Json::as_str).map(item::namespaced).as_deref() == Some("minecraft:binomial_with_bonus_count")
+        {
+            let parameters = function.get("parameters");
+            let extra = parameters.and_then(|p| p.get("extra")).and_then(Json::as_f64).unwrap_or(0.0).max(0.0) as u32;
+            let probability = parameters.and_then(|p| p.get("probability")).and_then(Json::as_f64).unwrap_or(0.0);
+            count += (0..extra).filter(|_| rng.gen::<f64>() < probability).count() as f64;
+        }
+    }
+    let count = count.floor().clamp(0.0, item::max_stack_size(&id) as f64) as u8;
+    (count > 0).then(|| ItemStack::new(&id, count))
+}
+
+// Zahlenquellen: 3, {"min": 1, "max": 3}, {"type": "minecraft:uniform", ...}, constant und binomial
+fn number(value: &Json, rng: &mut ThreadRng) -> f64 {
+    if let Some(n) = value.as_f64() {
+        return n;
+    }
+    let field = |name: &str, rng: &mut ThreadRng| value.get(name).map_or(0.0, |v| number(v, rng));
+    match value.get("type").and_then(Json::as_str).map(item::namespaced).as_deref() {
+        Some("minecraft:constant") => field("value", rng),
+        Some("minecraft:binomial") => {
+            let (n, p) = (field("n", rng).max(0.0) as u32, field("p", rng));
+            (0..n).filter(|_| rng.gen::<f64>() < p).count() as f64
+        }
+        _ => {
+            let (min, max) = (field("min", rng), field("max", rng));
+            if max > min { rng.gen_range(min..=max) } else { min }
+        }
+    }
+}
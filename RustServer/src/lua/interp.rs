@@ -0,0 +1,1232 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use rand::Rng;
+use super::parser::{BinOp, Block, Expr, FuncBody, Parser, Stat, TableField, UnOp};
+
+// Rekursionstiefe (Lua-Aufrufe und __index-Ketten) und Größe erzeugter Strings
+const MAX_CALL_DEPTH: usize = 200;
+// Skripte laufen auf Threads mit 2 MiB Stack; die Hälfte bleibt dem Server
+const MAX_STACK_USAGE: usize = 1 << 20;
+const MAX_STRING_LEN: usize = 1 << 20;
+
+pub type TableRef = Rc<RefCell<Table>>;
+pub type NativeFn = dyn Fn(&mut Interp, Vec<Value>) -> Result<Vec<Value>, String>;
+
+pub struct Closure {
+    body: Rc<FuncBody>,
+    env: Env,
+    chunk: Rc<str>,
+}
+
+#[derive(Clone)]
+pub enum Function {
+    Lua(Rc<Closure>),
+    Native(Rc<NativeFn>),
+}
+
+#[derive(Clone, Default)]
+pub enum Value {
+    #[default]
+    Nil,
+    Bool(bool),
+    Num(f64),
+    Str(Rc<str>),
+    Table(TableRef),
+    Func(Function),
+}
+
+impl Value {
+    pub fn native<F: Fn(&mut Interp, Vec<Value>) -> Result<Vec<Value>, String> + 'static>(f: F) -> Value {
+        Value::Func(Function::Native(Rc::new(f)))
+    }
+
+    pub fn str(text: &str) -> Value {
+        Value::Str(text.into())
+    }
+
+    pub fn is_nil(&self) -> bool {
+        matches!(self, Value::Nil)
+    }
+
+    pub fn truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Bool(_) => "boolean",
+            Value::Num(_) => "number",
+            Value::Str(_) => "string",
+            Value::Table(_) => "table",
+            Value::Func(_) => "function",
+        }
+    }
+
+    // Zahlen und numerische Strings, wie bei Lua-Arithmetik
+    pub fn to_number(&self) -> Option<f64> {
+        match self {
+            Value::Num(n) => Some(*n),
+            Value::Str(s) => parse_number(s),
+            _ => None,
+        }
+    }
+
+    fn to_integer(&self) -> Option<i64> {
+        self.to_number().filter(|n| n.fract() == 0.0 && n.abs() < 9.3e18).map(|n| n as i64)
+    }
+
+    // Nur Strings und Zahlen, wie bei der Verkettung
+    pub fn to_str(&self) -> Option<Rc<str>> {
+        match self {
+            Value::Str(s) => Some(s.clone()),
+            Value::Num(n) => Some(number_to_string(*n).into()),
+            _ => None,
+        }
+    }
+
+    fn raw_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Num(a), Value::Num(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Table(a), Value::Table(b)) => Rc::ptr_eq(a, b),
+            (Value::Func(Function::Lua(a)), Value::Func(Function::Lua(b))) => Rc::ptr_eq(a, b),
+            (Value::Func(Function::Native(a)), Value::Func(Function::Native(b))) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    fn address(&self) -> usize {
+        match self {
+            Value::Table(t) => Rc::as_ptr(t) as *const () as usize,
+            Value::Func(Function::Lua(f)) => Rc::as_ptr(f) as *const () as usize,
+            Value::Func(Function::Native(f)) => Rc::as_ptr(f) as *const () as usize,
+            _ => 0,
+        }
+    }
+}
+
+pub fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        return "nan".to_string();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "inf" } else { "-inf" }.to_string();
+    }
+    if n.fract() == 0.0 && n.abs() < 1e16 {
+        return format!("{}", n as i64);
+    }
+    format!("{}", n)
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let value = if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()? as f64
+    } else if !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit() || matches!(c, b'.' | b'e' | b'E' | b'+' | b'-')) {
+        digits.parse::<f64>().ok()?
+    } else {
+        return None;
+    };
+    Some(if negative { -value } else { value })
+}
+
+// Tabellenschlüssel; Tabellen und Funktionen werden über ihre Adresse verglichen
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Bool(bool),
+    Int(i64),
+    Float(u64),
+    Str(Rc<str>),
+    Ref(usize),
+}
+
+fn key_of(value: &Value) -> Option<Key> {
+    Some(match value {
+        Value::Nil => return None,
+        Value::Num(n) if n.is_nan() => return None,
+        Value::Num(n) if n.fract() == 0.0 && n.abs() < 9.3e18 => Key::Int(*n as i64),
+        Value::Num(n) => Key::Float(n.to_bits()),
+        Value::Bool(b) => Key::Bool(*b),
+        Value::Str(s) => Key::Str(s.clone()),
+        Value::Table(_) | Value::Func(_) => Key::Ref(value.address()),
+    })
+}
+
+// Array-Teil für die Schlüssel 1..n, alles andere im Hash-Teil (mit Originalschlüssel für pairs)
+#[derive(Default)]
+pub struct Table {
+    array: Vec<Value>,
+    hash: HashMap<Key, (Value, Value)>,
+    pub metatable: Option<TableRef>,
+}
+
+impl Table {
+    pub fn new_ref() -> TableRef {
+        Rc::new(RefCell::new(Table::default()))
+    }
+
+    pub fn get(&self, key: &Value) -> Value {
+        match key_of(key) {
+            Some(Key::Int(i)) if i >= 1 && (i as usize) <= self.array.len() => self.array[i as usize - 1].clone(),
+            Some(key) => self.hash.get(&key).map(|(_, v)| v.clone()).unwrap_or_default(),
+            None => Value::Nil,
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Value {
+        self.hash.get(&Key::Str(key.into())).map(|(_, v)| v.clone()).unwrap_or_default()
+    }
+
+    pub fn set(&mut self, key: Value, value: Value) -> Result<(), String> {
+        let k = key_of(&key).ok_or("Tabellenindex ist nil oder NaN")?;
+        if let Key::Int(i) = k {
+            let len = self.array.len() as i64;
+            if i >= 1 && i <= len {
+                self.array[i as usize - 1] = value;
+                while self.array.last().is_some_and(|v| v.is_nil()) {
+                    self.array.pop();
+                }
+                return Ok(());
+            }
+            if i == len + 1 && !value.is_nil() {
+                self.array.push(value);
+                self.hash.remove(&k);
+                // Nachfolgende Einträge aus dem Hash-Teil in das Array übernehmen
+                while let Some((_, next)) = self.hash.remove(&Key::Int(self.array.len() as i64 + 1)) {
+                    self.array.push(next);
+                }
+                return Ok(());
+            }
+        }
+        if value.is_nil() {
+            self.hash.remove(&k);
+        } else {
+            self.hash.insert(k, (key, value));
+        }
+        Ok(())
+    }
+
+    pub fn set_str(&mut self, key: &str, value: Value) {
+        let _ = self.set(Value::str(key), value);
+    }
+
+    pub fn len(&self) -> usize {
+        self.array.len()
+    }
+
+    pub fn push(&mut self, value: Value) {
+        let _ = self.set(Value::Num((self.array.len() + 1) as f64), value);
+    }
+
+    pub fn entries(&self) -> Vec<(Value, Value)> {
+        let array = self.array.iter().enumerate().filter(|(_, v)| !v.is_nil()).map(|(i, v)| (Value::Num((i + 1) as f64), v.clone()));
+        array.chain(self.hash.values().cloned()).collect()
+    }
+}
+
+// Lokale Variablen als unveränderliche verkettete Liste; Closures halten ihren Anfang fest
+struct Scope {
+    name: Rc<str>,
+    cell: Rc<RefCell<Value>>,
+    parent: Env,
+}
+
+type Env = Option<Rc<Scope>>;
+
+fn declare(env: &Env, name: Rc<str>, value: Value) -> Env {
+    Some(Rc::new(Scope { name, cell: Rc::new(RefCell::new(value)), parent: env.clone() }))
+}
+
+fn lookup(env: &Env, name: &str) -> Option<Rc<RefCell<Value>>> {
+    let mut scope = env.as_ref();
+    while let Some(s) = scope {
+        if &*s.name == name {
+            return Some(s.cell.clone());
+        }
+        scope = s.parent.as_ref();
+    }
+    None
+}
+
+enum Flow {
+    Normal,
+    Break,
+    Return(Vec<Value>),
+}
+
+pub struct Interp {
+    pub globals: TableRef,
+    string_lib: TableRef,
+    varargs: Vec<Rc<Vec<Value>>>,
+    depth: usize,
+    steps: u64,
+    stack_base: usize,
+    chunk: Rc<str>,
+    // Gesetzt, sobald die aktuelle Fehlermeldung eine Zeilenangabe hat
+    located: bool,
+}
+
+impl Interp {
+    pub fn new() -> Interp {
+        let mut interp = Interp {
+            globals: Table::new_ref(),
+            string_lib: Table::new_ref(),
+            varargs: Vec::new(),
+            depth: 0,
+            steps: 0,
+            stack_base: 0,
+            chunk: "?".into(),
+            located: false,
+        };
+        stdlib(&mut interp);
+        interp
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.globals.borrow_mut().set_str(name, value);
+    }
+
+    // Übersetzt ein Skript in eine Funktion ohne Parameter
+    pub fn load(&self, chunk: &str, source: &str) -> Result<Value, String> {
+        let body = Parser::new(source).and_then(|p| p.parse_chunk()).map_err(|e| format!("{}: {}", chunk, e))?;
+        let body = Rc::new(FuncBody { params: Vec::new(), vararg: true, body });
+        Ok(Value::Func(Function::Lua(Rc::new(Closure { body, env: None, chunk: chunk.into() }))))
+    }
+
+    // Einstiegspunkt für den Host: jede Ausführung bekommt ein eigenes Befehlsbudget
+    pub fn run(&mut self, function: &Value, args: Vec<Value>, steps: u64) -> Result<Vec<Value>, String> {
+        self.steps = steps;
+        self.stack_base = stack_address();
+        self.depth = 0;
+        self.located = false;
+        self.varargs.clear();
+        self.call(function, args)
+    }
+
+    fn tick(&mut self) -> Result<(), String> {
+        if self.steps == 0 {
+            return Err("Rechenbudget des Skripts aufgebraucht (Endlosschleife?)".to_string());
+        }
+        self.steps -= 1;
+        Ok(())
+    }
+
+    fn locate(&mut self, error: String, line: u32) -> String {
+        if self.located {
+            return error;
+        }
+        self.located = true;
+        format!("{}:{}: {}", self.chunk, line, error)
+    }
+
+    pub fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Vec<Value>, String> {
+        if self.depth >= MAX_CALL_DEPTH || self.stack_base.abs_diff(stack_address()) > MAX_STACK_USAGE {
+            return Err("Stapelüberlauf".to_string());
+        }
+        self.tick()?;
+        self.depth += 1;
+        let result = match function {
+            Value::Func(Function::Native(f)) => {
+                let f = f.clone();
+                f(self, args)
+            }
+            Value::Func(Function::Lua(closure)) => self.call_closure(closure.clone(), args),
+            _ => match self.metamethod(function, "__call") {
+                Some(handler) => {
+                    let mut all = vec![function.clone()];
+                    all.extend(args);
+                    self.call(&handler, all)
+                }
+                None => Err(format!("Versuch, einen {}-Wert aufzurufen", function.type_name())),
+            },
+        };
+        self.depth -= 1;
+        result
+    }
+
+    fn call_closure(&mut self, closure: Rc<Closure>, args: Vec<Value>) -> Result<Vec<Value>, String> {
+        let body = &closure.body;
+        let mut env = closure.env.clone();
+        let mut args = args.into_iter();
+        for param in &body.params {
+            env = declare(&env, param.clone(), args.next().unwrap_or_default());
+        }
+        self.varargs.push(Rc::new(if body.vararg { args.collect() } else { Vec::new() }));
+        let chunk = std::mem::replace(&mut self.chunk, closure.chunk.clone());
+        let flow = self.exec_block(&body.body, env);
+        self.chunk = chunk;
+        self.varargs.pop();
+        match flow? {
+            Flow::Return(values) => Ok(values),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    pub fn call1(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, String> {
+        Ok(self.call(function, args)?.into_iter().next().unwrap_or_default())
+    }
+
+    fn metamethod(&self, value: &Value, name: &str) -> Option<Value> {
+        let Value::Table(t) = value else { return None };
+        let meta = t.borrow().metatable.clone()?;
+        let handler = meta.borrow().get_str(name);
+        (!handler.is_nil()).then_some(handler)
+    }
+
+    pub fn index(&mut self, object: &Value, key: &Value) -> Result<Value, String> {
+        let mut object = object.clone();
+        for _ in 0..MAX_CALL_DEPTH {
+            let table = match &object {
+                Value::Table(t) => t.clone(),
+                Value::Str(_) => return Ok(self.string_lib.borrow().get(key)),
+                _ => {
+                    let key = key.to_str().map(|k| format!(" (Feld '{}')", k)).unwrap_or_default();
+                    return Err(format!("Versuch, einen {}-Wert zu indizieren{}", object.type_name(), key));
+                }
+            };
+            let raw = table.borrow().get(key);
+            if !raw.is_nil() {
+                return Ok(raw);
+            }
+            match self.metamethod(&object, "__index") {
+                None => return Ok(Value::Nil),
+                Some(handler @ Value::Func(_)) => return self.call1(&handler, vec![object.clone(), key.clone()]),
+                Some(next) => object = next,
+            }
+        }
+        Err("__index-Kette zu lang".to_string())
+    }
+
+    fn set_index(&mut self, object: &Value, key: Value, value: Value) -> Result<(), String> {
+        let Value::Table(table) = object else {
+            return Err(format!("Versuch, einen {}-Wert zu indizieren", object.type_name()));
+        };
+        if table.borrow().get(&key).is_nil() {
+            if let Some(handler) = self.metamethod(object, "__newindex") {
+                return self.call(&handler, vec![object.clone(), key, value]).map(|_| ());
+            }
+        }
+        table.borrow_mut().set(key, value)
+    }
+
+    pub fn tostring(&mut self, value: &Value) -> Result<Rc<str>, String> {
+        if let Some(handler) = self.metamethod(value, "__tostring") {
+            return match self.call1(&handler, vec![value.clone()])? {
+                Value::Str(s) => Ok(s),
+                _ => Err("__tostring muss einen String liefern".to_string()),
+            };
+        }
+        Ok(match value {
+            Value::Nil => "nil".into(),
+            Value::Bool(b) => b.to_string().into(),
+            Value::Num(n) => number_to_string(*n).into(),
+            Value::Str(s) => s.clone(),
+            Value::Table(_) => format!("table: 0x{:08x}", value.address()).into(),
+            Value::Func(_) => format!("function: 0x{:08x}", value.address()).into(),
+        })
+    }
+
+    fn exec_block(&mut self, block: &Block, env: Env) -> Result<Flow, String> {
+        self.exec_scoped(block, env).map(|(flow, _)| flow)
+    }
+
+    // Führt einen Block aus und liefert auch dessen Umgebung (für die Bedingung von repeat-until)
+    fn exec_scoped(&mut self, block: &Block, mut env: Env) -> Result<(Flow, Env), String> {
+        // Auch leere Schleifenrümpfe verbrauchen Budget
+        self.tick()?;
+        for (stat, line) in block {
+            match self.tick().and_then(|_| self.exec(stat, &mut env)) {
+                Ok(Flow::Normal) => {}
+                Ok(flow) => return Ok((flow, env)),
+                Err(e) => return Err(self.locate(e, *line)),
+            }
+        }
+        Ok((Flow::Normal, env))
+    }
+
+    fn exec(&mut self, stat: &Stat, env: &mut Env) -> Result<Flow, String> {
+        match stat {
+            Stat::Local(names, exprs) => {
+                let mut values = self.eval_list(exprs, env)?.into_iter();
+                for name in names {
+                    *env = declare(env, name.clone(), values.next().unwrap_or_default());
+                }
+            }
+            Stat::LocalFunction(name, body) => {
+                *env = declare(env, name.clone(), Value::Nil);
+                let function = self.closure(body, env);
+                *env.as_ref().unwrap().cell.borrow_mut() = function;
+            }
+            Stat::Assign(targets, exprs) => {
+                let values = self.eval_list(exprs, env)?;
+                for (i, target) in targets.iter().enumerate() {
+                    let value = values.get(i).cloned().unwrap_or_default();
+                    match target {
+                        Expr::Name(name) => match lookup(env, name) {
+                            Some(cell) => *cell.borrow_mut() = value,
+                            None => self.globals.borrow_mut().set_str(name, value),
+                        },
+                        Expr::Index(object, key) => {
+                            let object = self.eval(object, env)?;
+                            let key = self.eval(key, env)?;
+                            self.set_index(&object, key, value)?;
+                        }
+                        _ => unreachable!("Parser lässt nur Variablen als Ziel zu"),
+                    }
+                }
+            }
+            Stat::Call(expr) => {
+                self.eval_multi(expr, env)?;
+            }
+            Stat::Do(block) => return self.exec_block(block, env.clone()),
+            Stat::While(condition, body) => {
+                while self.eval(condition, env)?.truthy() {
+                    match self.exec_block(body, env.clone())? {
+                        Flow::Break => break,
+                        Flow::Return(values) => return Ok(Flow::Return(values)),
+                        Flow::Normal => {}
+                    }
+                }
+            }
+            Stat::Repeat(body, condition) => loop {
+                let (flow, inner) = self.exec_scoped(body, env.clone())?;
+                match flow {
+                    Flow::Break => break,
+                    Flow::Return(values) => return Ok(Flow::Return(values)),
+                    Flow::Normal => {}
+                }
+                if self.eval(condition, &inner)?.truthy() {
+                    break;
+                }
+            },
+            Stat::If(branches, otherwise) => {
+                for (condition, body) in branches {
+                    if self.eval(condition, env)?.truthy() {
+                        return self.exec_block(body, env.clone());
+                    }
+                }
+                if let Some(body) = otherwise {
+                    return self.exec_block(body, env.clone());
+                }
+            }
+            Stat::NumericFor(name, start, limit, step, body) => return self.numeric_for(name, [start, limit], step.as_ref(), body, env),
+            Stat::GenericFor(names, exprs, body) => {
+                let mut values = self.eval_list(exprs, env)?.into_iter();
+                let (function, state, mut control) = (values.next().unwrap_or_default(), values.next().unwrap_or_default(), values.next().unwrap_or_default());
+                loop {
+                    let results = self.call(&function, vec![state.clone(), control.clone()])?;
+                    let first = results.first().cloned().unwrap_or_default();
+                    if first.is_nil() {
+                        break;
+                    }
+                    control = first;
+                    let mut inner = env.clone();
+                    let mut results = results.into_iter();
+                    for name in names {
+                        inner = declare(&inner, name.clone(), results.next().unwrap_or_default());
+                    }
+                    match self.exec_block(body, inner)? {
+                        Flow::Break => break,
+                        Flow::Return(values) => return Ok(Flow::Return(values)),
+                        Flow::Normal => {}
+                    }
+                }
+            }
+            Stat::Return(exprs) => return Ok(Flow::Return(self.eval_list(exprs, env)?)),
+            Stat::Break => return Ok(Flow::Break),
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn numeric_for(&mut self, name: &Rc<str>, [start, limit]: [&Expr; 2], step: Option<&Expr>, body: &Block, env: &Env) -> Result<Flow, String> {
+        let number = |value: Value, what: &str| value.to_number().ok_or_else(|| format!("'for'-{} muss eine Zahl sein", what));
+        let start = number(self.eval(start, env)?, "Startwert")?;
+        let limit = number(self.eval(limit, env)?, "Grenze")?;
+        let step = match step {
+            Some(step) => number(self.eval(step, env)?, "Schrittweite")?,
+            None => 1.0,
+        };
+        if step == 0.0 {
+            return Err("'for'-Schrittweite ist null".to_string());
+        }
+        let mut i = start;
+        while (step > 0.0 && i <= limit) || (step < 0.0 && i >= limit) {
+            match self.exec_block(body, declare(env, name.clone(), Value::Num(i)))? {
+                Flow::Break => break,
+                Flow::Return(values) => return Ok(Flow::Return(values)),
+                Flow::Normal => {}
+            }
+            i += step;
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn closure(&self, body: &Rc<FuncBody>, env: &Env) -> Value {
+        Value::Func(Function::Lua(Rc::new(Closure { body: body.clone(), env: env.clone(), chunk: self.chunk.clone() })))
+    }
+
+    fn eval_list(&mut self, exprs: &[Expr], env: &Env) -> Result<Vec<Value>, String> {
+        let mut values = Vec::with_capacity(exprs.len());
+        for (i, expr) in exprs.iter().enumerate() {
+            if i + 1 == exprs.len() {
+                values.extend(self.eval_multi(expr, env)?);
+            } else {
+                values.push(self.eval(expr, env)?);
+            }
+        }
+        Ok(values)
+    }
+
+    // Ausdrücke, die mehrere Werte liefern können: Aufrufe und "..."
+    fn eval_multi(&mut self, expr: &Expr, env: &Env) -> Result<Vec<Value>, String> {
+        match expr {
+            Expr::Call(function, args) => {
+                let callee = self.eval(function, env)?;
+                let args = self.eval_list(args, env)?;
+                if let (Value::Nil, Expr::Name(name)) = (&callee, function.as_ref()) {
+                    return Err(format!("Versuch, einen nil-Wert aufzurufen (global '{}')", name));
+                }
+                self.call(&callee, args)
+            }
+            Expr::Method(object, method, args) => {
+                let object = self.eval(object, env)?;
+                let function = self.index(&object, &Value::Str(method.clone()))?;
+                if function.is_nil() {
+                    return Err(format!("Versuch, einen nil-Wert aufzurufen (Methode '{}')", method));
+                }
+                let mut all = vec![object];
+                all.extend(self.eval_list(args, env)?);
+                self.call(&function, all)
+            }
+            Expr::Vararg => Ok(self.varargs.last().map(|v| v.to_vec()).unwrap_or_default()),
+            _ => Ok(vec![self.eval(expr, env)?]),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr, env: &Env) -> Result<Value, String> {
+        Ok(match expr {
+            Expr::Nil => Value::Nil,
+            Expr::True => Value::Bool(true),
+            Expr::False => Value::Bool(false),
+            Expr::Number(n) => Value::Num(*n),
+            Expr::Str(s) => Value::Str(s.clone()),
+            Expr::Vararg => self.varargs.last().and_then(|v| v.first().cloned()).unwrap_or_default(),
+            Expr::Function(body) => self.closure(body, env),
+            Expr::Name(name) => match lookup(env, name) {
+                Some(cell) => cell.borrow().clone(),
+                None => self.globals.borrow().get_str(name),
+            },
+            Expr::Index(object, key) => {
+                let object = self.eval(object, env)?;
+                let key = self.eval(key, env)?;
+                self.index(&object, &key)?
+            }
+            Expr::Call(..) | Expr::Method(..) => self.eval_multi(expr, env)?.into_iter().next().unwrap_or_default(),
+            Expr::Paren(inner) => self.eval(inner, env)?,
+            Expr::Binary(BinOp::And, left, right) => {
+                let left = self.eval(left, env)?;
+                if left.truthy() { self.eval(right, env)? } else { left }
+            }
+            Expr::Binary(BinOp::Or, left, right) => {
+                let left = self.eval(left, env)?;
+                if left.truthy() { left } else { self.eval(right, env)? }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left, env)?;
+                let right = self.eval(right, env)?;
+                self.binary(*op, left, right)?
+            }
+            Expr::Unary(op, operand) => {
+                let value = self.eval(operand, env)?;
+                self.unary(*op, value)?
+            }
+            Expr::Table(fields) => self.table(fields, env)?,
+        })
+    }
+
+    fn table(&mut self, fields: &[TableField], env: &Env) -> Result<Value, String> {
+        let table = Table::new_ref();
+        let mut position = 0;
+        for (i, field) in fields.iter().enumerate() {
+            let values = match field {
+                TableField::Keyed(key, value) => {
+                    let key = self.eval(key, env)?;
+                    let value = self.eval(value, env)?;
+                    table.borrow_mut().set(key, value)?;
+                    continue;
+                }
+                TableField::Positional(value) if i + 1 == fields.len() => self.eval_multi(value, env)?,
+                TableField::Positional(value) => vec![self.eval(value, env)?],
+            };
+            for value in values {
+                position += 1;
+                table.borrow_mut().set(Value::Num(position as f64), value)?;
+            }
+        }
+        Ok(Value::Table(table))
+    }
+
+    fn binary(&mut self, op: BinOp, left: Value, right: Value) -> Result<Value, String> {
+        let arithmetic = |l: &Value, r: &Value| match (l.to_number(), r.to_number()) {
+            (Some(a), Some(b)) => Ok((a, b)),
+            _ => {
+                let bad = if l.to_number().is_none() { l } else { r };
+                Err(format!("Versuch, mit einem {}-Wert zu rechnen", bad.type_name()))
+            }
+        };
+        let bitwise = |l: &Value, r: &Value| match (l.to_integer(), r.to_integer()) {
+            (Some(a), Some(b)) => Ok((a, b)),
+            _ => Err("Bitoperation mit einem Wert ohne Ganzzahldarstellung".to_string()),
+        };
+        Ok(match op {
+            BinOp::Add => arithmetic(&left, &right).map(|(a, b)| Value::Num(a + b))?,
+            BinOp::Sub => arithmetic(&left, &right).map(|(a, b)| Value::Num(a - b))?,
+            BinOp::Mul => arithmetic(&left, &right).map(|(a, b)| Value::Num(a * b))?,
+            BinOp::Div => arithmetic(&left, &right).map(|(a, b)| Value::Num(a / b))?,
+            BinOp::Pow => arithmetic(&left, &right).map(|(a, b)| Value::Num(a.powf(b)))?,
+            BinOp::IDiv => arithmetic(&left, &right).map(|(a, b)| Value::Num((a / b).floor()))?,
+            BinOp::Mod => arithmetic(&left, &right).map(|(a, b)| Value::Num(a - (a / b).floor() * b))?,
+            BinOp::BAnd => bitwise(&left, &right).map(|(a, b)| Value::Num((a & b) as f64))?,
+            BinOp::BOr => bitwise(&left, &right).map(|(a, b)| Value::Num((a | b) as f64))?,
+            BinOp::BXor => bitwise(&left, &right).map(|(a, b)| Value::Num((a ^ b) as f64))?,
+            BinOp::Shl => bitwise(&left, &right).map(|(a, b)| Value::Num(if b >= 64 { 0.0 } else { ((a as u64) << b.max(0)) as i64 as f64 }))?,
+            BinOp::Shr => bitwise(&left, &right).map(|(a, b)| Value::Num(if b >= 64 { 0.0 } else { ((a as u64) >> b.max(0)) as i64 as f64 }))?,
+            BinOp::Concat => match (left.to_str(), right.to_str()) {
+                (Some(a), Some(b)) => {
+                    if a.len() + b.len() > MAX_STRING_LEN {
+                        return Err("String zu lang".to_string());
+                    }
+                    Value::Str(format!("{}{}", a, b).into())
+                }
+                _ => {
+                    let bad = if left.to_str().is_none() { &left } else { &right };
+                    return Err(format!("Versuch, einen {}-Wert zu verketten", bad.type_name()));
+                }
+            },
+            BinOp::Eq => Value::Bool(left.raw_equals(&right)),
+            BinOp::Ne => Value::Bool(!left.raw_equals(&right)),
+            BinOp::Lt => Value::Bool(compare(&left, &right)?.is_lt()),
+            BinOp::Le => Value::Bool(compare(&left, &right)?.is_le()),
+            BinOp::Gt => Value::Bool(compare(&left, &right)?.is_gt()),
+            BinOp::Ge => Value::Bool(compare(&left, &right)?.is_ge()),
+            BinOp::And | BinOp::Or => unreachable!("wird in eval kurzgeschlossen"),
+        })
+    }
+
+    fn unary(&mut self, op: UnOp, value: Value) -> Result<Value, String> {
+        Ok(match op {
+            UnOp::Not => Value::Bool(!value.truthy()),
+            UnOp::Neg => match value.to_number() {
+                Some(n) => Value::Num(-n),
+                None => return Err(format!("Versuch, mit einem {}-Wert zu rechnen", value.type_name())),
+            },
+            UnOp::BNot => match value.to_integer() {
+                Some(n) => Value::Num(!n as f64),
+                None => return Err("Bitoperation mit einem Wert ohne Ganzzahldarstellung".to_string()),
+            },
+            UnOp::Len => match &value {
+                Value::Str(s) => Value::Num(s.len() as f64),
+                Value::Table(t) => Value::Num(t.borrow().len() as f64),
+                _ => return Err(format!("Versuch, die Länge eines {}-Werts zu bestimmen", value.type_name())),
+            },
+        })
+    }
+}
+
+// Ungefähre Position im Stack des aktuellen Threads
+fn stack_address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+// Fehler statt Panik bei NaN; nur Zahlen mit Zahlen und Strings mit Strings
+fn compare(left: &Value, right: &Value) -> Result<std::cmp::Ordering, String> {
+    match (left, right) {
+        (Value::Num(a), Value::Num(b)) => Ok(a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Greater)),
+        (Value::Str(a), Value::Str(b)) => Ok(a.cmp(b)),
+        _ => Err(format!("Versuch, {} mit {} zu vergleichen", left.type_name(), right.type_name())),
+    }
+}
+
+pub fn arg(args: &[Value], index: usize) -> Value {
+    args.get(index).cloned().unwrap_or_default()
+}
+
+fn bad_argument(index: usize, function: &str, expected: &str, got: &Value) -> String {
+    format!("Ungültiges Argument #{} für '{}' ({} erwartet, {} erhalten)", index + 1, function, expected, got.type_name())
+}
+
+pub fn check_number(args: &[Value], index: usize, function: &str) -> Result<f64, String> {
+    let value = arg(args, index);
+    value.to_number().ok_or_else(|| bad_argument(index, function, "number", &value))
+}
+
+pub fn check_integer(args: &[Value], index: usize, function: &str) -> Result<i64, String> {
+    let value = arg(args, index);
+    value.to_integer().ok_or_else(|| bad_argument(index, function, "integer", &value))
+}
+
+pub fn check_string(args: &[Value], index: usize, function: &str) -> Result<Rc<str>, String> {
+    let value = arg(args, index);
+    value.to_str().ok_or_else(|| bad_argument(index, function, "string", &value))
+}
+
+pub fn check_table(args: &[Value], index: usize, function: &str) -> Result<TableRef, String> {
+    match arg(args, index) {
+        Value::Table(t) => Ok(t),
+        other => Err(bad_argument(index, function, "table", &other)),
+    }
+}
+
+pub fn check_function(args: &[Value], index: usize, function: &str) -> Result<Value, String> {
+    match arg(args, index) {
+        value @ Value::Func(_) => Ok(value),
+        other => Err(bad_argument(index, function, "function", &other)),
+    }
+}
+
+fn opt_integer(args: &[Value], index: usize, function: &str, default: i64) -> Result<i64, String> {
+    if arg(args, index).is_nil() { Ok(default) } else { check_integer(args, index, function) }
+}
+
+fn one(value: Value) -> Result<Vec<Value>, String> {
+    Ok(vec![value])
+}
+
+fn register(table: &TableRef, name: &str, f: impl Fn(&mut Interp, Vec<Value>) -> Result<Vec<Value>, String> + 'static) {
+    table.borrow_mut().set_str(name, Value::native(f));
+}
+
+// Lua-Indizes (1-basiert, negativ vom Ende) in einen Byte-Bereich übersetzen
+fn string_range(len: usize, i: i64, j: i64) -> (usize, usize) {
+    let len = len as i64;
+    let resolve = |k: i64| if k < 0 { (len + k + 1).max(0) } else { k };
+    let start = resolve(i).max(1);
+    let end = resolve(j).min(len);
+    if start > end { (0, 0) } else { (start as usize - 1, end as usize) }
+}
+
+fn stdlib(interp: &mut Interp) {
+    let g = interp.globals.clone();
+    g.borrow_mut().set_str("_G", Value::Table(g.clone()));
+    g.borrow_mut().set_str("_VERSION", Value::str("Lua 5.4"));
+    register(&g, "print", |interp, args| {
+        let mut parts = Vec::new();
+        for value in &args {
+            parts.push(interp.tostring(value)?.to_string());
+        }
+        info!("[{}] {}", interp.chunk, parts.join("\t"));
+        Ok(Vec::new())
+    });
+    register(&g, "type", |_, args| {
+        if args.is_empty() {
+            return Err("Argument #1 für 'type' fehlt".to_string());
+        }
+        one(Value::str(args[0].type_name()))
+    });
+    register(&g, "tostring", |interp, args| one(Value::Str(interp.tostring(&arg(&args, 0))?)));
+    register(&g, "tonumber", |_, args| {
+        let value = arg(&args, 0);
+        if arg(&args, 1).is_nil() {
+            return one(value.to_number().map(Value::Num).unwrap_or_default());
+        }
+        let base = check_integer(&args, 1, "tonumber")?;
+        if !(2..=36).contains(&base) {
+            return Err("Basis außerhalb des Bereichs".to_string());
+        }
+        let text = check_string(&args, 0, "tonumber")?;
+        one(i64::from_str_radix(text.trim(), base as u32).map(|n| Value::Num(n as f64)).unwrap_or_default())
+    });
+    register(&g, "ipairs", |_, args| {
+        let table = arg(&args, 0);
+        let iter = Value::native(|interp, args| {
+            let i = check_integer(&args, 1, "ipairs")? + 1;
+            let value = interp.index(&arg(&args, 0), &Value::Num(i as f64))?;
+            if value.is_nil() { one(Value::Nil) } else { Ok(vec![Value::Num(i as f64), value]) }
+        });
+        Ok(vec![iter, table, Value::Num(0.0)])
+    });
+    register(&g, "pairs", |_, args| {
+        let entries = check_table(&args, 0, "pairs")?.borrow().entries();
+        let position = Cell::new(0);
+        let iter = Value::native(move |_, _| {
+            let i = position.get();
+            position.set(i + 1);
+            match entries.get(i) {
+                Some((key, value)) => Ok(vec![key.clone(), value.clone()]),
+                None => one(Value::Nil),
+            }
+        });
+        Ok(vec![iter, arg(&args, 0), Value::Nil])
+    });
+    register(&g, "pcall", |interp, mut args| {
+        if args.is_empty() {
+            return Err("Argument #1 für 'pcall' fehlt".to_string());
+        }
+        let function = args.remove(0);
+        let (depth, varargs) = (interp.depth, interp.varargs.len());
+        match interp.call(&function, args) {
+            Ok(mut results) => {
+                results.insert(0, Value::Bool(true));
+                Ok(results)
+            }
+            // Budget-Erschöpfung ist nicht abfangbar: das Budget bleibt leer und bricht beim nächsten Schritt ab
+            Err(e) => {
+                interp.depth = depth;
+                interp.varargs.truncate(varargs);
+                interp.located = false;
+                Ok(vec![Value::Bool(false), Value::Str(e.into())])
+            }
+        }
+    });
+    register(&g, "error", |interp, args| {
+        let message = interp.tostring(&arg(&args, 0))?;
+        Err(message.to_string())
+    });
+    register(&g, "assert", |interp, args| {
+        if arg(&args, 0).truthy() {
+            return Ok(args);
+        }
+        match arg(&args, 1) {
+            Value::Nil => Err("Assertion fehlgeschlagen!".to_string()),
+            message => Err(interp.tostring(&message)?.to_string()),
+        }
+    });
+    register(&g, "select", |_, args| {
+        if let Value::Str(s) = arg(&args, 0) {
+            if &*s == "#" {
+                return one(Value::Num((args.len() - 1) as f64));
+            }
+        }
+        let n = check_integer(&args, 0, "select")?;
+        let rest = args.len() as i64 - 1;
+        let start = if n < 0 { rest + n } else { n - 1 };
+        if start < 0 || n == 0 {
+            return Err("Ungültiges Argument #1 für 'select' (Index außerhalb des Bereichs)".to_string());
+        }
+        Ok(args.into_iter().skip(1 + start as usize).collect())
+    });
+    register(&g, "setmetatable", |_, args| {
+        let table = check_table(&args, 0, "setmetatable")?;
+        table.borrow_mut().metatable = match arg(&args, 1) {
+            Value::Nil => None,
+            Value::Table(meta) => Some(meta),
+            other => return Err(bad_argument(1, "setmetatable", "nil or table", &other)),
+        };
+        one(Value::Table(table))
+    });
+    register(&g, "getmetatable", |_, args| match arg(&args, 0) {
+        Value::Table(t) => one(t.borrow().metatable.clone().map(Value::Table).unwrap_or_default()),
+        _ => one(Value::Nil),
+    });
+    register(&g, "rawget", |_, args| one(check_table(&args, 0, "rawget")?.borrow().get(&arg(&args, 1))));
+    register(&g, "rawset", |_, args| {
+        check_table(&args, 0, "rawset")?.borrow_mut().set(arg(&args, 1), arg(&args, 2))?;
+        one(arg(&args, 0))
+    });
+    register(&g, "rawequal", |_, args| one(Value::Bool(arg(&args, 0).raw_equals(&arg(&args, 1)))));
+
+    let string = interp.string_lib.clone();
+    g.borrow_mut().set_str("string", Value::Table(string.clone()));
+    register(&string, "len", |_, args| one(Value::Num(check_string(&args, 0, "len")?.len() as f64)));
+    register(&string, "upper", |_, args| one(Value::str(&check_string(&args, 0, "upper")?.to_uppercase())));
+    register(&string, "lower", |_, args| one(Value::str(&check_string(&args, 0, "lower")?.to_lowercase())));
+    register(&string, "reverse", |_, args| one(Value::str(&check_string(&args, 0, "reverse")?.chars().rev().collect::<String>())));
+    register(&string, "sub", |_, args| {
+        let s = check_string(&args, 0, "sub")?;
+        let (start, end) = string_range(s.len(), opt_integer(&args, 1, "sub", 1)?, opt_integer(&args, 2, "sub", -1)?);
+        one(Value::str(&String::from_utf8_lossy(&s.as_bytes()[start..end])))
+    });
+    register(&string, "rep", |_, args| {
+        let s = check_string(&args, 0, "rep")?;
+        let n = check_integer(&args, 1, "rep")?.max(0) as usize;
+        let separator = if arg(&args, 2).is_nil() { "".into() } else { check_string(&args, 2, "rep")? };
+        if (s.len() + separator.len()).saturating_mul(n) > MAX_STRING_LEN {
+            return Err("String zu lang".to_string());
+        }
+        one(Value::str(&vec![&*s; n].join(&separator)))
+    });
+    register(&string, "byte", |_, args| {
+        let s = check_string(&args, 0, "byte")?;
+        let i = opt_integer(&args, 1, "byte", 1)?;
+        let (start, end) = string_range(s.len(), i, opt_integer(&args, 2, "byte", i)?);
+        Ok(s.as_bytes()[start..end].iter().map(|b| Value::Num(*b as f64)).collect())
+    });
+    register(&string, "char", |_, args| {
+        let mut bytes = Vec::new();
+        for i in 0..args.len() {
+            bytes.push(u8::try_from(check_integer(&args, i, "char")?).map_err(|_| "Wert außerhalb des Bereichs".to_string())?);
+        }
+        one(Value::str(&String::from_utf8_lossy(&bytes)))
+    });
+    register(&string, "find", |_, args| {
+        let s = check_string(&args, 0, "find")?;
+        let pattern = check_string(&args, 1, "find")?;
+        let (start, _) = string_range(s.len(), opt_integer(&args, 2, "find", 1)?, -1);
+        // Lua-Muster werden nicht unterstützt, nur die einfache Suche (plain)
+        if !arg(&args, 3).truthy() && pattern.contains(['^', '$', '*', '+', '?', '.', '(', ')', '[', ']', '%', '-']) {
+            return Err("string.find unterstützt nur einfache Suche (viertes Argument true)".to_string());
+        }
+        match s.as_bytes()[start..].windows(pattern.len().max(1)).position(|w| w == pattern.as_bytes()) {
+            Some(offset) => Ok(vec![Value::Num((start + offset + 1) as f64), Value::Num((start + offset + pattern.len()) as f64)]),
+            None => one(Value::Nil),
+        }
+    });
+    register(&string, "format", |interp, args| one(Value::str(&format(interp, &args)?)));
+
+    let math = Table::new_ref();
+    g.borrow_mut().set_str("math", Value::Table(math.clone()));
+    math.borrow_mut().set_str("pi", Value::Num(std::f64::consts::PI));
+    math.borrow_mut().set_str("huge", Value::Num(f64::INFINITY));
+    register(&math, "floor", |_, args| one(Value::Num(check_number(&args, 0, "floor")?.floor())));
+    register(&math, "ceil", |_, args| one(Value::Num(check_number(&args, 0, "ceil")?.ceil())));
+    register(&math, "abs", |_, args| one(Value::Num(check_number(&args, 0, "abs")?.abs())));
+    register(&math, "sqrt", |_, args| one(Value::Num(check_number(&args, 0, "sqrt")?.sqrt())));
+    register(&math, "fmod", |_, args| one(Value::Num(check_number(&args, 0, "fmod")? % check_number(&args, 1, "fmod")?)));
+    register(&math, "max", |_, args| {
+        let mut best = check_number(&args, 0, "max")?;
+        for i in 1..args.len() {
+            best = best.max(check_number(&args, i, "max")?);
+        }
+        one(Value::Num(best))
+    });
+    register(&math, "min", |_, args| {
+        let mut best = check_number(&args, 0, "min")?;
+        for i in 1..args.len() {
+            best = best.min(check_number(&args, i, "min")?);
+        }
+        one(Value::Num(best))
+    });
+    register(&math, "random", |_, args| {
+        let mut rng = rand::thread_rng();
+        let (low, high) = match args.len() {
+            0 => return one(Value::Num(rng.gen::<f64>())),
+            1 => (1, check_integer(&args, 0, "random")?),
+            _ => (check_integer(&args, 0, "random")?, check_integer(&args, 1, "random")?),
+        };
+        if low > high {
+            return Err("Leeres Intervall für 'random'".to_string());
+        }
+        one(Value::Num(rng.gen_range(low..=high) as f64))
+    });
+
+    let table = Table::new_ref();
+    g.borrow_mut().set_str("table", Value::Table(table.clone()));
+    register(&table, "insert", |_, args| {
+        let table = check_table(&args, 0, "insert")?;
+        let mut table = table.borrow_mut();
+        if args.len() < 3 {
+            table.push(arg(&args, 1));
+            return Ok(Vec::new());
+        }
+        let len = table.len() as i64;
+        let position = check_integer(&args, 1, "insert")?;
+        if position < 1 || position > len + 1 {
+            return Err("Position für 'insert' außerhalb des Bereichs".to_string());
+        }
+        for i in (position..=len).rev() {
+            let value = table.get(&Value::Num(i as f64));
+            table.set(Value::Num((i + 1) as f64), value)?;
+        }
+        table.set(Value::Num(position as f64), arg(&args, 2))?;
+        Ok(Vec::new())
+    });
+    register(&table, "remove", |_, args| {
+        let table = check_table(&args, 0, "remove")?;
+        let mut table = table.borrow_mut();
+        let len = table.len() as i64;
+        let position = opt_integer(&args, 1, "remove", len)?;
+        if len == 0 && position == 0 {
+            return one(Value::Nil);
+        }
+        if position < 1 || position > len + 1 {
+            return Err("Position für 'remove' außerhalb des Bereichs".to_string());
+        }
+        let removed = table.get(&Value::Num(position as f64));
+        for i in position..len {
+            let next = table.get(&Value::Num((i + 1) as f64));
+            table.set(Value::Num(i as f64), next)?;
+        }
+        table.set(Value::Num(len as f64), Value::Nil)?;
+        one(removed)
+    });
+    register(&table, "concat", |_, args| {
+        let table = check_table(&args, 0, "concat")?;
+        let separator = if arg(&args, 1).is_nil() { "".into() } else { check_string(&args, 1, "concat")? };
+        let table = table.borrow();
+        let start = opt_integer(&args, 2, "concat", 1)?;
+        let end = opt_integer(&args, 3, "concat", table.len() as i64)?;
+        let mut parts = Vec::new();
+        for i in start..=end {
+            let value = table.get(&Value::Num(i as f64));
+            parts.push(value.to_str().ok_or_else(|| format!("Ungültiger Wert ({}) an Index {} in 'concat'", value.type_name(), i))?);
+        }
+        let joined = parts.join(&separator);
+        if joined.len() > MAX_STRING_LEN {
+            return Err("String zu lang".to_string());
+        }
+        one(Value::str(&joined))
+    });
+    register(&table, "unpack", |_, args| {
+        let table = check_table(&args, 0, "unpack")?;
+        let table = table.borrow();
+        let start = opt_integer(&args, 1, "unpack", 1)?;
+        let end = opt_integer(&args, 2, "unpack", table.len() as i64)?;
+        if end - start >= 10_000 {
+            return Err("Zu viele Werte für 'unpack'".to_string());
+        }
+        Ok((start..=end).map(|i| table.get(&Value::Num(i as f64))).collect())
+    });
+    register(&table, "sort", |interp, args| {
+        let table = check_table(&args, 0, "sort")?;
+        let comparator = arg(&args, 1);
+        let mut items: Vec<Value> = (1..=table.borrow().len()).map(|i| table.borrow().get(&Value::Num(i as f64))).collect();
+        // Einfügesortierung, da der Vergleich fehlschlagen darf
+        for i in 1..items.len() {
+            let mut j = i;
+            while j > 0 {
+                let less = if comparator.is_nil() {
+                    compare(&items[j], &items[j - 1])?.is_lt()
+                } else {
+                    interp.call1(&comparator, vec![items[j].clone(), items[j - 1].clone()])?.truthy()
+                };
+                if !less {
+                    break;
+                }
+                items.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+        let mut table = table.borrow_mut();
+        for (i, value) in items.into_iter().enumerate() {
+            table.set(Value::Num((i + 1) as f64), value)?;
+        }
+        Ok(Vec::new())
+    });
+
+    let os = Table::new_ref();
+    g.borrow_mut().set_str("os", Value::Table(os.clone()));
+    register(&os, "time", |_, _| {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        one(Value::Num(now.as_secs() as f64))
+    });
+    let started = std::time::Instant::now();
+    register(&os, "clock", move |_, _| one(Value::Num(started.elapsed().as_secs_f64())));
+}
+
+// string.format mit %d %i %u %c %x %X %o %e %f %g %s %q und %%, inklusive Flags, Breite und Genauigkeit
+fn format(interp: &mut Interp, args: &[Value]) -> Result<String, String> {
+    let template = check_string(args, 0, "format")?;
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+    let mut next_arg = 1;
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            out.push('%');
+            continue;
+        }
+        let mut flags = String::new();
+        while let Some(&f) = chars.peek().filter(|f| "-+ #0".contains(**f)) {
+            flags.push(f);
+            chars.next();
+        }
+        let mut width = 0;
+        while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+            width = width * 10 + d as usize;
+            chars.next();
+        }
+        let mut precision = None;
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut p = 0;
+            while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                p = p * 10 + d as usize;
+                chars.next();
+            }
+            precision = Some(p);
+        }
+        if width > 99 || precision.is_some_and(|p| p > 99) {
+            return Err("Ungültiges Format (Breite oder Genauigkeit zu groß)".to_string());
+        }
+        let conversion = chars.next().ok_or("Ungültiges Format (endet mit '%')")?;
+        let index = next_arg;
+        next_arg += 1;
+        if index >= args.len() {
+            return Err(format!("Ungültiges Argument #{} für 'format' (kein Wert)", index + 1));
+        }
+        let sign = |n: f64, text: String| {
+            if n >= 0.0 && flags.contains('+') {
+                format!("+{}", text)
+            } else if n >= 0.0 && flags.contains(' ') {
+                format!(" {}", text)
+            } else {
+                text
+            }
+        };
+        let (text, numeric) = match conversion {
+            'd' | 'i' | 'u' => {
+                let n = check_integer(args, index, "format")?;
+                (sign(n as f64, n.to_string()), true)
+            }
+            'c' => (char::from(u8::try_from(check_integer(args, index, "format")?).unwrap_or(b'?')).to_string(), false),
+            'x' => (format!("{:x}", check_integer(args, index, "format")?), true),
+            'X' => (format!("{:X}", check_integer(args, index, "format")?), true),
+            'o' => (format!("{:o}", check_integer(args, index, "format")?), true),
+            'f' | 'F' => {
+                let n = check_number(args, index, "format")?;
+                (sign(n, format!("{:.*}", precision.unwrap_or(6), n)), true)
+            }
+            'e' | 'E' => {
+                let n = check_number(args, index, "format")?;
+                let text = format!("{:.*e}", precision.unwrap_or(6), n);
+                (sign(n, if conversion == 'E' { text.to_uppercase() } else { text }), true)
+            }
+            'g' | 'G' => {
+                let n = check_number(args, index, "format")?;
+                (sign(n, number_to_string(n)), true)
+            }
+            's' => {
+                let text = interp.tostring(&args[index])?.to_string();
+                (match precision {
+                    Some(p) => text.chars().take(p).collect(),
+                    None => text,
+                }, false)
+            }
+            'q' => (format!("{:?}", &*check_string(args, index, "format")?), false),
+            other => return Err(format!("Ungültige Umwandlung '%{}' in 'format'", other)),
+        };
+        let padding = width.saturating_sub(text.chars().count());
+        if flags.contains('-') {
+            out.push_str(&text);
+            out.extend(std::iter::repeat_n(' ', padding));
+        } else if flags.contains('0') && numeric {
+            let (sign, digits) = match text.strip_prefix(['-', '+', ' ']) {
+                Some(rest) => (&text[..1], rest),
+                None => ("", text.as_str()),
+            };
+            out.push_str(sign);
+            out.extend(std::iter::repeat_n('0', padding));
+            out.push_str(digits);
+        } else {
+            out.extend(std::iter::repeat_n(' ', padding));
+            out.push_str(&text);
+        }
+        if out.len() > MAX_STRING_LEN {
+            return Err("String zu lang".to_string());
+        }
+    }
+    Ok(out)
+}
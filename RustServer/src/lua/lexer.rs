@@ -0,0 +1,227 @@
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Name(Rc<str>),
+    Number(f64),
+    Str(Rc<str>),
+    Keyword(&'static str),
+    Symbol(&'static str),
+    Eof,
+}
+
+const KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in", "local", "nil", "not", "or", "repeat", "return", "then",
+    "true", "until", "while",
+];
+
+// Längste Symbole zuerst, damit z.B. "..." vor ".." und "." erkannt wird
+const SYMBOLS: &[&str] = &[
+    "...", "..", "==", "~=", "<=", ">=", "//", "::", "<<", ">>", "+", "-", "*", "/", "%", "^", "#", "&", "~", "|", "<", ">", "=", "(", ")", "{", "}", "[", "]",
+    ";", ":", ",", ".",
+];
+
+pub struct Lexer<'a> {
+    source: &'a [u8],
+    pos: usize,
+    pub line: u32,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Lexer<'a> {
+        Lexer { source: source.as_bytes(), pos: 0, line: 1 }
+    }
+
+    fn peek(&self, offset: usize) -> u8 {
+        self.source.get(self.pos + offset).copied().unwrap_or(0)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Zeile {}: {}", self.line, message)
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), String> {
+        loop {
+            match self.peek(0) {
+                b'\n' => {
+                    self.line += 1;
+                    self.pos += 1;
+                }
+                b' ' | b'\t' | b'\r' => self.pos += 1,
+                b'-' if self.peek(1) == b'-' => {
+                    self.pos += 2;
+                    if self.peek(0) == b'[' {
+                        if let Some(level) = self.long_bracket_level() {
+                            self.long_string(level)?;
+                            continue;
+                        }
+                    }
+                    while self.pos < self.source.len() && self.peek(0) != b'\n' {
+                        self.pos += 1;
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    // Prüft auf "[", "[=", "[==" ... gefolgt von "[" und liefert die Anzahl der Gleichheitszeichen
+    fn long_bracket_level(&self) -> Option<usize> {
+        let mut level = 0;
+        while self.peek(1 + level) == b'=' {
+            level += 1;
+        }
+        (self.peek(1 + level) == b'[').then_some(level)
+    }
+
+    fn long_string(&mut self, level: usize) -> Result<String, String> {
+        self.pos += level + 2;
+        if self.peek(0) == b'\r' {
+            self.pos += 1;
+        }
+        if self.peek(0) == b'\n' {
+            self.line += 1;
+            self.pos += 1;
+        }
+        let start = self.pos;
+        loop {
+            if self.pos >= self.source.len() {
+                return Err(self.error("Unbeendeter langer String"));
+            }
+            if self.peek(0) == b']' && (1..=level).all(|i| self.peek(i) == b'=') && self.peek(level + 1) == b']' {
+                let text = String::from_utf8_lossy(&self.source[start..self.pos]).into_owned();
+                self.pos += level + 2;
+                return Ok(text);
+            }
+            if self.peek(0) == b'\n' {
+                self.line += 1;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn quoted_string(&mut self, quote: u8) -> Result<String, String> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let c = self.peek(0);
+            if self.pos >= self.source.len() || c == b'\n' {
+                return Err(self.error("Unbeendeter String"));
+            }
+            self.pos += 1;
+            if c == quote {
+                return Ok(String::from_utf8_lossy(&bytes).into_owned());
+            }
+            if c != b'\\' {
+                bytes.push(c);
+                continue;
+            }
+            let escaped = self.peek(0);
+            self.pos += 1;
+            match escaped {
+                b'n' => bytes.push(b'\n'),
+                b't' => bytes.push(b'\t'),
+                b'r' => bytes.push(b'\r'),
+                b'a' => bytes.push(7),
+                b'b' => bytes.push(8),
+                b'f' => bytes.push(12),
+                b'v' => bytes.push(11),
+                b'\\' | b'"' | b'\'' => bytes.push(escaped),
+                b'\n' => {
+                    self.line += 1;
+                    bytes.push(b'\n');
+                }
+                b'x' => {
+                    let hex = std::str::from_utf8(&self.source[self.pos..(self.pos + 2).min(self.source.len())]).unwrap_or("");
+                    let value = u8::from_str_radix(hex, 16).map_err(|_| self.error("Ungültige \\x-Escape-Sequenz"))?;
+                    bytes.push(value);
+                    self.pos += 2;
+                }
+                b'z' => {
+                    while self.peek(0).is_ascii_whitespace() {
+                        if self.peek(0) == b'\n' {
+                            self.line += 1;
+                        }
+                        self.pos += 1;
+                    }
+                }
+                b'0'..=b'9' => {
+                    let mut value = (escaped - b'0') as u32;
+                    for _ in 0..2 {
+                        if !self.peek(0).is_ascii_digit() {
+                            break;
+                        }
+                        value = value * 10 + (self.peek(0) - b'0') as u32;
+                        self.pos += 1;
+                    }
+                    bytes.push(u8::try_from(value).map_err(|_| self.error("Dezimale Escape-Sequenz zu groß"))?);
+                }
+                _ => return Err(self.error("Ungültige Escape-Sequenz")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        if self.peek(0) == b'0' && matches!(self.peek(1), b'x' | b'X') {
+            self.pos += 2;
+            let digits_start = self.pos;
+            while self.peek(0).is_ascii_hexdigit() {
+                self.pos += 1;
+            }
+            let digits = std::str::from_utf8(&self.source[digits_start..self.pos]).unwrap();
+            return u64::from_str_radix(digits, 16).map(|v| v as f64).map_err(|_| self.error("Ungültige Hexadezimalzahl"));
+        }
+        while self.peek(0).is_ascii_digit() || self.peek(0) == b'.' {
+            self.pos += 1;
+        }
+        if matches!(self.peek(0), b'e' | b'E') {
+            self.pos += 1;
+            if matches!(self.peek(0), b'+' | b'-') {
+                self.pos += 1;
+            }
+            while self.peek(0).is_ascii_digit() {
+                self.pos += 1;
+            }
+        }
+        let text = std::str::from_utf8(&self.source[start..self.pos]).unwrap();
+        text.parse::<f64>().map_err(|_| self.error(&format!("Ungültige Zahl {}", text)))
+    }
+
+    pub fn next_token(&mut self) -> Result<Token, String> {
+        self.skip_whitespace_and_comments()?;
+        let c = self.peek(0);
+        if self.pos >= self.source.len() {
+            return Ok(Token::Eof);
+        }
+        if c.is_ascii_alphabetic() || c == b'_' {
+            let start = self.pos;
+            while self.peek(0).is_ascii_alphanumeric() || self.peek(0) == b'_' {
+                self.pos += 1;
+            }
+            let word = std::str::from_utf8(&self.source[start..self.pos]).unwrap();
+            return Ok(match KEYWORDS.iter().find(|k| **k == word) {
+                Some(keyword) => Token::Keyword(keyword),
+                None => Token::Name(word.into()),
+            });
+        }
+        if c.is_ascii_digit() || (c == b'.' && self.peek(1).is_ascii_digit()) {
+            return Ok(Token::Number(self.number()?));
+        }
+        if c == b'"' || c == b'\'' {
+            return Ok(Token::Str(self.quoted_string(c)?.into()));
+        }
+        if c == b'[' {
+            if let Some(level) = self.long_bracket_level() {
+                return Ok(Token::Str(self.long_string(level)?.into()));
+            }
+        }
+        for symbol in SYMBOLS {
+            if self.source[self.pos..].starts_with(symbol.as_bytes()) {
+                self.pos += symbol.len();
+                return Ok(Token::Symbol(symbol));
+            }
+        }
+        Err(self.error(&format!("Unerwartetes Zeichen '{}'", c as char)))
+    }
+}
@@ -0,0 +1,484 @@
+pub mod interp;
+mod lexer;
+mod parser;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, Weak};
+use crate::dimension::{self, Dimension};
+use crate::event::{Event, EventKind, EventPriority, ListenerId};
+use crate::tick::TaskId;
+use crate::{block, channel, portal, send_system_message, Server};
+use interp::{arg, check_function, check_integer, check_number, check_string, Interp, Table, Value};
+
+pub const SCRIPT_DIR: &str = "scripts";
+// Befehlsbudget pro Aufruf aus dem Server (Event, Befehl, Aufgabe) gegen Endlosschleifen
+const STEPS_PER_CALL: u64 = 1_000_000;
+
+// Der Server lebt bis zum Prozessende, daher darf jedes Skript einen Zeiger darauf halten
+#[derive(Clone, Copy)]
+struct ServerRef(*const Server);
+
+#[derive(Default)]
+struct ScriptState {
+    this: Weak<Mutex<Script>>,
+    commands: Vec<String>,
+    tasks: Vec<TaskId>,
+    listeners: Vec<ListenerId>,
+    channels: Vec<String>,
+    // Lua-Funktionen für Befehle, Listener und geplante Aufgaben, über eine Nummer aus dem Server erreichbar
+    callbacks: HashMap<u64, Value>,
+    next_callback: u64,
+}
+
+struct ScriptContext {
+    name: String,
+    server: ServerRef,
+    state: RefCell<ScriptState>,
+}
+
+impl ScriptContext {
+    fn server(&self) -> &Server {
+        unsafe { &*self.server.0 }
+    }
+
+    fn add_callback(&self, function: Value) -> u64 {
+        let mut state = self.state.borrow_mut();
+        state.next_callback += 1;
+        let id = state.next_callback;
+        state.callbacks.insert(id, function);
+        id
+    }
+}
+
+pub struct Script {
+    interp: Interp,
+    context: Rc<ScriptContext>,
+}
+
+// Der Lua-Zustand enthält Rc-Werte. Er wird nur unter dem Mutex des Skripts angefasst, und keine
+// Rc-Kopie verlässt ihn, daher greifen nie zwei Threads gleichzeitig auf die Zähler zu
+unsafe impl Send for Script {}
+
+impl Script {
+    fn run(&mut self, function: &Value, args: Vec<Value>) -> Result<Vec<Value>, String> {
+        self.interp.run(function, args, STEPS_PER_CALL)
+    }
+
+    fn run_callback(&mut self, id: u64, args: Vec<Value>) -> Result<Vec<Value>, String> {
+        let function = self.context.state.borrow().callbacks.get(&id).cloned();
+        match function {
+            Some(function) => self.run(&function, args),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ScriptEngine {
+    scripts: Mutex<Vec<Arc<Mutex<Script>>>>,
+}
+
+type ApiFn = fn(&ScriptContext, &mut Interp, Vec<Value>) -> Result<Vec<Value>, String>;
+
+fn coordinate(args: &[Value], index: usize, function: &str) -> Result<i32, String> {
+    i32::try_from(check_integer(args, index, function)?).map_err(|_| format!("Koordinate außerhalb des Bereichs in '{}'", function))
+}
+
+fn event_table(event: &dyn Event) -> Value {
+    let table = Table::new_ref();
+    let mut t = table.borrow_mut();
+    t.set_str("type", Value::str(event.kind().name()));
+    t.set_str("player", Value::str(event.player()));
+    t.set_str("cancelled", Value::Bool(event.is_cancelled()));
+    if let Some(message) = event.message() {
+        t.set_str("message", Value::str(message));
+    }
+    if let Some(format) = event.format() {
+        t.set_str("format", Value::str(format));
+    }
+    if let Some((x, y, z, state)) = event.block() {
+        for (key, value) in [("x", x), ("y", y), ("z", z), ("state", state as i32)] {
+            t.set_str(key, Value::Num(value as f64));
+        }
+    }
+    drop(t);
+    Value::Table(table)
+}
+
+// Übernimmt, was ein Listener an der Event-Tabelle geändert hat
+fn apply_event_table(table: &Value, event: &mut dyn Event) {
+    let Value::Table(table) = table else { return };
+    let table = table.borrow();
+    event.set_cancelled(table.get_str("cancelled").truthy());
+    if event.message().is_some() {
+        if let Some(message) = table.get_str("message").to_str() {
+            event.set_message(message.to_string());
+        }
+    }
+    if event.format().is_some() {
+        if let Some(format) = table.get_str("format").to_str() {
+            event.set_format(format.to_string());
+        }
+    }
+}
+
+// server.on(event, function(event) [, priority]): Listener können event.cancelled, event.message und beim Chat event.format ändern
+fn api_on(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let name = check_string(&args, 0, "on")?;
+    let kind = EventKind::from_name(&name).ok_or_else(|| format!("Unbekanntes Event \"{}\"", name))?;
+    let callback = context.add_callback(check_function(&args, 1, "on")?);
+    let priority = match arg(&args, 2) {
+        Value::Nil => EventPriority::Normal,
+        value => {
+            let priority = value.to_str().unwrap_or_default();
+            EventPriority::from_name(&priority).ok_or_else(|| format!("Unbekannte Priorität \"{}\"", priority))?
+        }
+    };
+    let script = context.state.borrow().this.clone();
+    let id = context.server().events.listen_kind(kind, priority, move |_server, event| {
+        let Some(script) = script.upgrade() else { return };
+        let mut script = script.lock().unwrap();
+        let table = event_table(event);
+        match script.run_callback(callback, vec![table.clone()]) {
+            Ok(_) => apply_event_table(&table, event),
+            Err(e) => warn!("[{}] Fehler in Event {}: {}", script.context.name, kind.name(), e),
+        }
+    });
+    context.state.borrow_mut().listeners.push(id);
+    Ok(Vec::new())
+}
+
+// server.command(name, usage, function(sender, args)): ein zurückgegebener String geht an den Absender,
+// false meldet einen Fehlschlag samt Verwendung
+fn api_command(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let name = check_string(&args, 0, "command")?.to_lowercase();
+    let usage = check_string(&args, 1, "command")?.to_string();
+    let id = context.add_callback(check_function(&args, 2, "command")?);
+    let script = context.state.borrow().this.clone();
+    let command = name.clone();
+    context.server().commands.lock().unwrap().register(&name, &usage, move |ctx, args| {
+        let Some(script) = script.upgrade() else { return Err("Script is not loaded".to_string()) };
+        let sender = ctx.sender.name(ctx.server);
+        let mut script = script.lock().unwrap();
+        let arg_table = Table::new_ref();
+        for arg in args {
+            arg_table.borrow_mut().push(Value::str(arg));
+        }
+        match script.run_callback(id, vec![Value::str(&sender), Value::Table(arg_table)]) {
+            Ok(results) => match results.into_iter().next().unwrap_or_default() {
+                Value::Bool(false) => Err("Command failed".to_string()),
+                Value::Str(reply) => {
+                    ctx.reply(&reply);
+                    Ok(())
+                }
+                _ => Ok(()),
+            },
+            Err(e) => {
+                warn!("[{}] Fehler in Befehl /{}: {}", script.context.name, command, e);
+                Err("An internal error occurred while running this command".to_string())
+            }
+        }
+    });
+    context.state.borrow_mut().commands.push(name);
+    Ok(Vec::new())
+}
+
+// server.schedule(delay, period, function) -> id; period 0 = einmalig
+fn api_schedule(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let delay = check_integer(&args, 0, "schedule")?.max(0) as u64;
+    let period = check_integer(&args, 1, "schedule")?.max(0) as u64;
+    let callback = context.add_callback(check_function(&args, 2, "schedule")?);
+    let script = context.state.borrow().this.clone();
+    let run = move |_server: &Server| {
+        let Some(script) = script.upgrade() else { return };
+        let mut script = script.lock().unwrap();
+        if let Err(e) = script.run_callback(callback, Vec::new()) {
+            warn!("[{}] Fehler in geplanter Aufgabe: {}", script.context.name, e);
+        }
+        if period == 0 {
+            script.context.state.borrow_mut().callbacks.remove(&callback);
+        }
+    };
+    let mut scheduler = context.server().scheduler.lock().unwrap();
+    let id = if period == 0 { scheduler.run_later(delay, run) } else { scheduler.run_repeating(delay, period, run) };
+    drop(scheduler);
+    context.state.borrow_mut().tasks.push(id);
+    Ok(vec![Value::Num(id as f64)])
+}
+
+fn api_cancel(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let id = check_integer(&args, 0, "cancel")? as TaskId;
+    let mut state = context.state.borrow_mut();
+    if !state.tasks.contains(&id) {
+        return Ok(vec![Value::Bool(false)]);
+    }
+    state.tasks.retain(|t| *t != id);
+    drop(state);
+    Ok(vec![Value::Bool(context.server().scheduler.lock().unwrap().cancel(id))])
+}
+
+// server.channel(name, function(player, data)): Nutzdaten kommen als String an
+fn api_channel(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let name = check_string(&args, 0, "channel")?.to_string();
+    let callback = context.add_callback(check_function(&args, 1, "channel")?);
+    let script = context.state.borrow().this.clone();
+    let channel = name.clone();
+    context.server().channels.register(&name, move |_server, player, data| {
+        let Some(script) = script.upgrade() else { return };
+        let mut script = script.lock().unwrap();
+        let args = vec![Value::str(&player.username), Value::str(&String::from_utf8_lossy(data))];
+        if let Err(e) = script.run_callback(callback, args) {
+            warn!("[{}] Fehler auf Kanal {}: {}", script.context.name, channel, e);
+        }
+    })?;
+    channel::announce(context.server(), &name, true);
+    context.state.borrow_mut().channels.push(name);
+    Ok(Vec::new())
+}
+
+// server.send_plugin_message(player, channel, data) -> bool
+fn api_send_plugin_message(context: &ScriptContext, interp: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let player = check_string(&args, 0, "send_plugin_message")?;
+    let name = check_string(&args, 1, "send_plugin_message")?;
+    if !channel::is_valid_channel(&name) {
+        return Err(format!("Ungültiger Kanalname \"{}\"", name));
+    }
+    let data = interp.tostring(&arg(&args, 2))?;
+    let players = context.server().players.lock().unwrap();
+    let sent = players.iter().find(|p| *p.username == *player).is_some_and(|p| channel::send_plugin_message(&p.connection, &name, data.as_bytes()).is_ok());
+    Ok(vec![Value::Bool(sent)])
+}
+
+fn api_broadcast(context: &ScriptContext, interp: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let message = interp.tostring(&arg(&args, 0))?;
+    for player in context.server().players.lock().unwrap().iter() {
+        let _ = send_system_message(&player.connection, &message);
+    }
+    Ok(Vec::new())
+}
+
+// server.send_message(player, message); "CONSOLE" schreibt auf die Konsole
+fn api_send_message(context: &ScriptContext, interp: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let player = check_string(&args, 0, "send_message")?;
+    let message = interp.tostring(&arg(&args, 1))?;
+    if &*player == "CONSOLE" {
+        info!("{}", message);
+        return Ok(vec![Value::Bool(true)]);
+    }
+    let players = context.server().players.lock().unwrap();
+    let sent = players.iter().find(|p| *p.username == *player).is_some_and(|p| send_system_message(&p.connection, &message).is_ok());
+    Ok(vec![Value::Bool(sent)])
+}
+
+fn api_kick(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let player = check_string(&args, 0, "kick")?;
+    let players = context.server().players.lock().unwrap();
+    let Some(p) = players.iter().find(|p| *p.username == *player) else { return Ok(vec![Value::Bool(false)]) };
+    info!("[{}] Trenne {}", context.name, p.username);
+    p.connection.close();
+    Ok(vec![Value::Bool(true)])
+}
+
+// server.has_permission(player, node): false für Spieler, die nicht online sind
+fn api_has_permission(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let player = check_string(&args, 0, "has_permission")?;
+    let node = check_string(&args, 1, "has_permission")?;
+    let server = context.server();
+    let uuid = server.players.lock().unwrap().iter().find(|p| *p.username == *player).map(|p| p.uuid);
+    Ok(vec![Value::Bool(uuid.is_some_and(|uuid| server.has_permission(uuid, &node)))])
+}
+
+fn api_players(context: &ScriptContext, _: &mut Interp, _: Vec<Value>) -> Result<Vec<Value>, String> {
+    let names = Table::new_ref();
+    for player in context.server().players.lock().unwrap().iter() {
+        names.borrow_mut().push(Value::str(&player.username));
+    }
+    Ok(vec![Value::Table(names)])
+}
+
+// server.player_position(player) -> x, y, z oder nil
+fn api_player_position(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let player = check_string(&args, 0, "player_position")?;
+    let position = context.server().players.lock().unwrap().iter().find(|p| *p.username == *player).map(|p| p.position);
+    Ok(match position {
+        Some((x, y, z)) => vec![Value::Num(x), Value::Num(y), Value::Num(z)],
+        None => vec![Value::Nil],
+    })
+}
+
+// server.player_brand(player) -> z.B. "vanilla" oder "fabric", nil solange der Client keine gemeldet hat
+fn api_player_brand(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let player = check_string(&args, 0, "player_brand")?;
+    let brand = context.server().players.lock().unwrap().iter().find(|p| *p.username == *player).and_then(|p| p.brand.clone());
+    Ok(vec![brand.map_or(Value::Nil, |brand| Value::str(&brand))])
+}
+
+// server.player_ping(player) -> Millisekunden, 0 bis zur ersten Keep-Alive-Antwort, nil wenn nicht online
+fn api_player_ping(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let player = check_string(&args, 0, "player_ping")?;
+    let ping = context.server().players.lock().unwrap().iter().find(|p| *p.username == *player).map(|p| p.ping());
+    Ok(vec![ping.map_or(Value::Nil, |ping| Value::Num(ping as f64))])
+}
+
+fn api_get_block(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let (x, y, z) = (coordinate(&args, 0, "get_block")?, coordinate(&args, 1, "get_block")?, coordinate(&args, 2, "get_block")?);
+    let state = context.server().world.lock().unwrap().get_block(x, y, z);
+    Ok(vec![Value::Num(state as f64)])
+}
+
+fn api_set_block(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let (x, y, z) = (coordinate(&args, 0, "set_block")?, coordinate(&args, 1, "set_block")?, coordinate(&args, 2, "set_block")?);
+    let state = check_integer(&args, 3, "set_block")?;
+    if !(0..1 << block::GLOBAL_BITS).contains(&state) {
+        return Err(format!("Ungültiger Blockzustand {}", state));
+    }
+    let server = context.server();
+    if !server.world.lock().unwrap().set_block_and_notify((x, y, z), state as u16) {
+        return Ok(vec![Value::Bool(false)]);
+    }
+    Ok(vec![Value::Bool(true)])
+}
+
+// server.teleport(player, world, x, y, z [, yaw, pitch]); "world" ist die Oberwelt
+fn api_teleport(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let player = check_string(&args, 0, "teleport")?;
+    let world = check_string(&args, 1, "teleport")?;
+    let position = (check_number(&args, 2, "teleport")?, check_number(&args, 3, "teleport")?, check_number(&args, 4, "teleport")?);
+    let rotation = if arg(&args, 5).is_nil() { None } else { Some((check_number(&args, 5, "teleport")? as f32, check_number(&args, 6, "teleport")? as f32)) };
+    let server = context.server();
+    let Some(uuid) = server.players.lock().unwrap().iter().find(|p| *p.username == *player).map(|p| p.uuid) else { return Ok(vec![Value::Bool(false)]) };
+    let dimension = Dimension::by_world_name(&world)?;
+    dimension::teleport(server, uuid, dimension, position, rotation)?;
+    Ok(vec![Value::Bool(true)])
+}
+
+// server.link_portal(name, world, x1, y1, z1, x2, y2, z2, destination, x, y, z)
+fn api_link_portal(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let name = check_string(&args, 0, "link_portal")?;
+    let world = check_string(&args, 1, "link_portal")?;
+    let mut corners = [0; 6];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        *corner = coordinate(&args, 2 + i, "link_portal")?;
+    }
+    let destination = check_string(&args, 8, "link_portal")?;
+    let position = (check_number(&args, 9, "link_portal")?, check_number(&args, 10, "link_portal")?, check_number(&args, 11, "link_portal")?);
+    let [x1, y1, z1, x2, y2, z2] = corners;
+    let region = ((x1.min(x2), y1.min(y2), z1.min(z2)), (x1.max(x2), y1.max(y2), z1.max(z2)));
+    let portal = portal::new_portal(&name, &world, region, &destination, position, None)?;
+    context.server().portals.lock().unwrap().link(portal);
+    Ok(vec![])
+}
+
+fn api_unlink_portal(context: &ScriptContext, _: &mut Interp, args: Vec<Value>) -> Result<Vec<Value>, String> {
+    let name = check_string(&args, 0, "unlink_portal")?;
+    Ok(vec![Value::Bool(context.server().portals.lock().unwrap().unlink(&name))])
+}
+
+fn install_api(interp: &mut Interp, context: &Rc<ScriptContext>) {
+    let functions: [(&str, ApiFn); 20] = [
+        ("on", api_on),
+        ("command", api_command),
+        ("schedule", api_schedule),
+        ("cancel", api_cancel),
+        ("channel", api_channel),
+        ("send_plugin_message", api_send_plugin_message),
+        ("broadcast", api_broadcast),
+        ("send_message", api_send_message),
+        ("kick", api_kick),
+        ("has_permission", api_has_permission),
+        ("players", api_players),
+        ("player_position", api_player_position),
+        ("player_brand", api_player_brand),
+        ("player_ping", api_player_ping),
+        ("get_block", api_get_block),
+        ("set_block", api_set_block),
+        ("teleport", api_teleport),
+        ("link_portal", api_link_portal),
+        ("unlink_portal", api_unlink_portal),
+        ("script_name", |context, _, _| Ok(vec![Value::str(&context.name)])),
+    ];
+    let api = Table::new_ref();
+    for (name, function) in functions {
+        let context = context.clone();
+        api.borrow_mut().set_str(name, Value::native(move |interp, args| function(&context, interp, args)));
+    }
+    interp.set_global("server", Value::Table(api));
+}
+
+impl ScriptEngine {
+    pub fn new() -> ScriptEngine {
+        ScriptEngine::default()
+    }
+
+    // Lädt alle .lua-Dateien aus scripts/. Der Server muss bis zum Prozessende leben
+    pub fn load_all(server: &Server) {
+        let Ok(entries) = fs::read_dir(SCRIPT_DIR) else { return };
+        let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.extension().is_some_and(|ext| ext == "lua")).collect();
+        paths.sort();
+        for path in paths {
+            if let Err(e) = Self::load(server, &path) {
+                warn!("Skript {} konnte nicht geladen werden: {}", path.display(), e);
+            }
+        }
+    }
+
+    fn load(server: &Server, path: &Path) -> Result<(), String> {
+        let name = path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut interp = Interp::new();
+        let main = interp.load(&name, &source)?;
+        let context = Rc::new(ScriptContext { name: name.clone(), server: ServerRef(server), state: RefCell::default() });
+        install_api(&mut interp, &context);
+        let script = Arc::new_cyclic(|this| {
+            context.state.borrow_mut().this = this.clone();
+            Mutex::new(Script { interp, context })
+        });
+        {
+            let mut script = script.lock().unwrap();
+            if let Err(e) = script.run(&main, Vec::new()) {
+                Self::cleanup(server, &script);
+                return Err(e);
+            }
+        }
+        info!("Skript {} geladen", name);
+        server.scripts.scripts.lock().unwrap().push(script);
+        Ok(())
+    }
+
+    // Entfernt alles, was das Skript beim Server registriert hat
+    fn cleanup(server: &Server, script: &Script) {
+        let mut state = script.context.state.borrow_mut();
+        {
+            let mut commands = server.commands.lock().unwrap();
+            for command in state.commands.drain(..) {
+                commands.unregister(&command);
+            }
+        }
+        for listener in state.listeners.drain(..) {
+            server.events.unlisten(listener);
+        }
+        for channel in state.channels.drain(..) {
+            if server.channels.unregister(&channel) {
+                channel::announce(server, &channel, false);
+            }
+        }
+        let mut scheduler = server.scheduler.lock().unwrap();
+        for task in state.tasks.drain(..) {
+            scheduler.cancel(task);
+        }
+        state.callbacks.clear();
+    }
+
+    pub fn unload_all(server: &Server) {
+        let scripts = std::mem::take(&mut *server.scripts.scripts.lock().unwrap());
+        for script in scripts.iter().rev() {
+            let script = script.lock().unwrap();
+            Self::cleanup(server, &script);
+            info!("Skript {} entladen", script.context.name);
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use std::rc::Rc;
+use super::lexer::{Lexer, Token};
+
+// Schützt den Host-Stack vor absichtlich tief verschachtelten Ausdrücken
+const MAX_NESTING: usize = 150;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    IDiv,
+    Mod,
+    Pow,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    BAnd,
+    BOr,
+    BXor,
+    Shl,
+    Shr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnOp {
+    Neg,
+    Not,
+    Len,
+    BNot,
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    Nil,
+    True,
+    False,
+    Number(f64),
+    Str(Rc<str>),
+    Vararg,
+    Function(Rc<FuncBody>),
+    Name(Rc<str>),
+    Index(Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+    Method(Box<Expr>, Rc<str>, Vec<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Unary(UnOp, Box<Expr>),
+    Table(Vec<TableField>),
+    Paren(Box<Expr>),
+}
+
+#[derive(Debug)]
+pub enum TableField {
+    Positional(Expr),
+    Keyed(Expr, Expr),
+}
+
+#[derive(Debug)]
+pub struct FuncBody {
+    pub params: Vec<Rc<str>>,
+    pub vararg: bool,
+    pub body: Block,
+}
+
+#[derive(Debug)]
+pub enum Stat {
+    Local(Vec<Rc<str>>, Vec<Expr>),
+    Assign(Vec<Expr>, Vec<Expr>),
+    Call(Expr),
+    Do(Block),
+    While(Expr, Block),
+    Repeat(Block, Expr),
+    If(Vec<(Expr, Block)>, Option<Block>),
+    NumericFor(Rc<str>, Expr, Expr, Option<Expr>, Block),
+    GenericFor(Vec<Rc<str>>, Vec<Expr>, Block),
+    LocalFunction(Rc<str>, Rc<FuncBody>),
+    Return(Vec<Expr>),
+    Break,
+}
+
+pub type Block = Vec<(Stat, u32)>;
+
+pub struct Parser<'a> {
+    lexer: Lexer<'a>,
+    token: Token,
+    line: u32,
+    ahead: Option<(Token, u32)>,
+    depth: usize,
+}
+
+fn binary_priority(token: &Token) -> Option<(BinOp, u8, u8)> {
+    let op = match token {
+        Token::Symbol(s) => *s,
+        Token::Keyword(k) => *k,
+        _ => return None,
+    };
+    Some(match op {
+        "or" => (BinOp::Or, 1, 1),
+        "and" => (BinOp::And, 2, 2),
+        "<" => (BinOp::Lt, 3, 3),
+        ">" => (BinOp::Gt, 3, 3),
+        "<=" => (BinOp::Le, 3, 3),
+        ">=" => (BinOp::Ge, 3, 3),
+        "~=" => (BinOp::Ne, 3, 3),
+        "==" => (BinOp::Eq, 3, 3),
+        "|" => (BinOp::BOr, 4, 4),
+        "~" => (BinOp::BXor, 5, 5),
+        "&" => (BinOp::BAnd, 6, 6),
+        "<<" => (BinOp::Shl, 7, 7),
+        ">>" => (BinOp::Shr, 7, 7),
+        ".." => (BinOp::Concat, 9, 8),
+        "+" => (BinOp::Add, 10, 10),
+        "-" => (BinOp::Sub, 10, 10),
+        "*" => (BinOp::Mul, 11, 11),
+        "/" => (BinOp::Div, 11, 11),
+        "//" => (BinOp::IDiv, 11, 11),
+        "%" => (BinOp::Mod, 11, 11),
+        "^" => (BinOp::Pow, 14, 13),
+        _ => return None,
+    })
+}
+
+const UNARY_PRIORITY: u8 = 12;
+
+impl<'a> Parser<'a> {
+    pub fn new(source: &'a str) -> Result<Parser<'a>, String> {
+        let mut lexer = Lexer::new(source);
+        let token = lexer.next_token()?;
+        let line = lexer.line;
+        Ok(Parser { lexer, token, line, ahead: None, depth: 0 })
+    }
+
+    fn advance(&mut self) -> Result<Token, String> {
+        let (next, line) = match self.ahead.take() {
+            Some(ahead) => ahead,
+            None => {
+                let token = self.lexer.next_token()?;
+                (token, self.lexer.line)
+            }
+        };
+        self.line = line;
+        Ok(std::mem::replace(&mut self.token, next))
+    }
+
+    fn peek_ahead(&mut self) -> Result<&Token, String> {
+        if self.ahead.is_none() {
+            let token = self.lexer.next_token()?;
+            self.ahead = Some((token, self.lexer.line));
+        }
+        Ok(&self.ahead.as_ref().unwrap().0)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Zeile {}: {}", self.line, message)
+    }
+
+    fn check(&self, symbol: &str) -> bool {
+        matches!(&self.token, Token::Symbol(s) | Token::Keyword(s) if *s == symbol)
+    }
+
+    fn accept(&mut self, symbol: &str) -> Result<bool, String> {
+        if self.check(symbol) {
+            self.advance()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if !self.accept(symbol)? {
+            return Err(self.error(&format!("'{}' erwartet, gefunden {:?}", symbol, self.token)));
+        }
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<Rc<str>, String> {
+        match self.advance()? {
+            Token::Name(name) => Ok(name),
+            other => Err(self.error(&format!("Name erwartet, gefunden {:?}", other))),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(self.error("Zu tief verschachtelt"));
+        }
+        Ok(())
+    }
+
+    pub fn parse_chunk(mut self) -> Result<Block, String> {
+        let block = self.block()?;
+        if self.token != Token::Eof {
+            return Err(self.error(&format!("Unerwartetes {:?}", self.token)));
+        }
+        Ok(block)
+    }
+
+    fn block_end(&self) -> bool {
+        self.token == Token::Eof || self.check("end") || self.check("else") || self.check("elseif") || self.check("until")
+    }
+
+    fn block(&mut self) -> Result<Block, String> {
+        self.enter()?;
+        let mut block = Vec::new();
+        while !self.block_end() {
+            if self.accept(";")? {
+                continue;
+            }
+            let line = self.line;
+            if self.accept("return")? {
+                let values = if self.block_end() || self.check(";") { Vec::new() } else { self.expr_list()? };
+                self.accept(";")?;
+                block.push((Stat::Return(values), line));
+                break;
+            }
+            let stat = self.statement()?;
+            block.push((stat, line));
+        }
+        self.depth -= 1;
+        Ok(block)
+    }
+
+    fn statement(&mut self) -> Result<Stat, String> {
+        if self.accept("break")? {
+            return Ok(Stat::Break);
+        }
+        if self.accept("do")? {
+            let body = self.block()?;
+            self.expect("end")?;
+            return Ok(Stat::Do(body));
+        }
+        if self.accept("while")? {
+            let condition = self.expr()?;
+            self.expect("do")?;
+            let body = self.block()?;
+            self.expect("end")?;
+            return Ok(Stat::While(condition, body));
+        }
+        if self.accept("repeat")? {
+            let body = self.block()?;
+            self.expect("until")?;
+            return Ok(Stat::Repeat(body, self.expr()?));
+        }
+        if self.accept("if")? {
+            let mut branches = Vec::new();
+            let condition = self.expr()?;
+            self.expect("then")?;
+            branches.push((condition, self.block()?));
+            let mut otherwise = None;
+            loop {
+                if self.accept("elseif")? {
+                    let condition = self.expr()?;
+                    self.expect("then")?;
+                    branches.push((condition, self.block()?));
+                } else if self.accept("else")? {
+                    otherwise = Some(self.block()?);
+                    self.expect("end")?;
+                    break;
+                } else {
+                    self.expect("end")?;
+                    break;
+                }
+            }
+            return Ok(Stat::If(branches, otherwise));
+        }
+        if self.accept("for")? {
+            let first = self.name()?;
+            if self.accept("=")? {
+                let start = self.expr()?;
+                self.expect(",")?;
+                let limit = self.expr()?;
+                let step = if self.accept(",")? { Some(self.expr()?) } else { None };
+                self.expect("do")?;
+                let body = self.block()?;
+                self.expect("end")?;
+                return Ok(Stat::NumericFor(first, start, limit, step, body));
+            }
+            let mut names = vec![first];
+            while self.accept(",")? {
+                names.push(self.name()?);
+            }
+            self.expect("in")?;
+            let values = self.expr_list()?;
+            self.expect("do")?;
+            let body = self.block()?;
+            self.expect("end")?;
+            return Ok(Stat::GenericFor(names, values, body));
+        }
+        if self.accept("function")? {
+            // function a.b.c:m() ... end wird zu einer Zuweisung
+            let first = self.name()?;
+            let mut target = Expr::Name(first);
+            let mut is_method = false;
+            while self.check(".") || self.check(":") {
+                is_method = self.check(":");
+                self.advance()?;
+                let key = self.name()?;
+                target = Expr::Index(Box::new(target), Box::new(Expr::Str(key)));
+                if is_method {
+                    break;
+                }
+            }
+            let body = self.function_body(is_method)?;
+            return Ok(Stat::Assign(vec![target], vec![Expr::Function(body)]));
+        }
+        if self.accept("local")? {
+            if self.accept("function")? {
+                let name = self.name()?;
+                let body = self.function_body(false)?;
+                return Ok(Stat::LocalFunction(name, body));
+            }
+            let mut names = vec![self.name()?];
+            // Attribute wie <const> werden akzeptiert und ignoriert
+            self.skip_attribute()?;
+            while self.accept(",")? {
+                names.push(self.name()?);
+                self.skip_attribute()?;
+            }
+            let values = if self.accept("=")? { self.expr_list()? } else { Vec::new() };
+            return Ok(Stat::Local(names, values));
+        }
+        if self.check("::") || self.check("goto") {
+            return Err(self.error("goto wird nicht unterstützt"));
+        }
+
+        let expr = self.suffixed_expr()?;
+        if self.check("=") || self.check(",") {
+            let mut targets = vec![expr];
+            while self.accept(",")? {
+                targets.push(self.suffixed_expr()?);
+            }
+            self.expect("=")?;
+            for target in &targets {
+                if !matches!(target, Expr::Name(_) | Expr::Index(..)) {
+                    return Err(self.error("Zuweisung an einen Ausdruck, der keine Variable ist"));
+                }
+            }
+            return Ok(Stat::Assign(targets, self.expr_list()?));
+        }
+        match expr {
+            Expr::Call(..) | Expr::Method(..) => Ok(Stat::Call(expr)),
+            _ => Err(self.error("Anweisung erwartet")),
+        }
+    }
+
+    fn skip_attribute(&mut self) -> Result<(), String> {
+        if self.accept("<")? {
+            self.name()?;
+            self.expect(">")?;
+        }
+        Ok(())
+    }
+
+    fn function_body(&mut self, is_method: bool) -> Result<Rc<FuncBody>, String> {
+        self.expect("(")?;
+        let mut params: Vec<Rc<str>> = if is_method { vec!["self".into()] } else { Vec::new() };
+        let mut vararg = false;
+        if !self.check(")") {
+            loop {
+                if self.accept("...")? {
+                    vararg = true;
+                    break;
+                }
+                params.push(self.name()?);
+                if !self.accept(",")? {
+                    break;
+                }
+            }
+        }
+        self.expect(")")?;
+        let body = self.block()?;
+        self.expect("end")?;
+        Ok(Rc::new(FuncBody { params, vararg, body }))
+    }
+
+    fn expr_list(&mut self) -> Result<Vec<Expr>, String> {
+        let mut list = vec![self.expr()?];
+        while self.accept(",")? {
+            list.push(self.expr()?);
+        }
+        Ok(list)
+    }
+
+    pub fn expr(&mut self) -> Result<Expr, String> {
+        self.sub_expr(0)
+    }
+
+    fn sub_expr(&mut self, limit: u8) -> Result<Expr, String> {
+        self.enter()?;
+        let unary = match &self.token {
+            Token::Keyword("not") => Some(UnOp::Not),
+            Token::Symbol("-") => Some(UnOp::Neg),
+            Token::Symbol("#") => Some(UnOp::Len),
+            Token::Symbol("~") => Some(UnOp::BNot),
+            _ => None,
+        };
+        let mut left = match unary {
+            Some(op) => {
+                self.advance()?;
+                let operand = self.sub_expr(UNARY_PRIORITY)?;
+                Expr::Unary(op, Box::new(operand))
+            }
+            None => self.simple_expr()?,
+        };
+        while let Some((op, left_priority, right_priority)) = binary_priority(&self.token) {
+            if left_priority <= limit {
+                break;
+            }
+            self.advance()?;
+            let right = self.sub_expr(right_priority)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth -= 1;
+        Ok(left)
+    }
+
+    fn simple_expr(&mut self) -> Result<Expr, String> {
+        let expr = match &self.token {
+            Token::Number(n) => Expr::Number(*n),
+            Token::Str(s) => Expr::Str(s.clone()),
+            Token::Keyword("nil") => Expr::Nil,
+            Token::Keyword("true") => Expr::True,
+            Token::Keyword("false") => Expr::False,
+            Token::Symbol("...") => Expr::Vararg,
+            Token::Symbol("{") => return self.table(),
+            Token::Keyword("function") => {
+                self.advance()?;
+                return Ok(Expr::Function(self.function_body(false)?));
+            }
+            _ => return self.suffixed_expr(),
+        };
+        self.advance()?;
+        Ok(expr)
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr, String> {
+        match self.advance()? {
+            Token::Name(name) => Ok(Expr::Name(name)),
+            Token::Symbol("(") => {
+                let inner = self.expr()?;
+                self.expect(")")?;
+                Ok(Expr::Paren(Box::new(inner)))
+            }
+            other => Err(self.error(&format!("Unerwartetes {:?}", other))),
+        }
+    }
+
+    fn suffixed_expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary_expr()?;
+        loop {
+            if self.accept(".")? {
+                let key = self.name()?;
+                expr = Expr::Index(Box::new(expr), Box::new(Expr::Str(key)));
+            } else if self.accept("[")? {
+                let key = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(key));
+            } else if self.accept(":")? {
+                let method = self.name()?;
+                let args = self.call_args()?;
+                expr = Expr::Method(Box::new(expr), method, args);
+            } else if self.check("(") || self.check("{") || matches!(self.token, Token::Str(_)) {
+                let args = self.call_args()?;
+                expr = Expr::Call(Box::new(expr), args);
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn call_args(&mut self) -> Result<Vec<Expr>, String> {
+        if let Token::Str(s) = &self.token {
+            let arg = Expr::Str(s.clone());
+            self.advance()?;
+            return Ok(vec![arg]);
+        }
+        if self.check("{") {
+            return Ok(vec![self.table()?]);
+        }
+        self.expect("(")?;
+        if self.accept(")")? {
+            return Ok(Vec::new());
+        }
+        let args = self.expr_list()?;
+        self.expect(")")?;
+        Ok(args)
+    }
+
+    fn table(&mut self) -> Result<Expr, String> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        while !self.check("}") {
+            if self.accept("[")? {
+                let key = self.expr()?;
+                self.expect("]")?;
+                self.expect("=")?;
+                fields.push(TableField::Keyed(key, self.expr()?));
+            } else if matches!(self.token, Token::Name(_)) && self.peek_ahead()? == &Token::Symbol("=") {
+                let key = self.name()?;
+                self.expect("=")?;
+                fields.push(TableField::Keyed(Expr::Str(key), self.expr()?));
+            } else {
+                fields.push(TableField::Positional(self.expr()?));
+            }
+            if !self.accept(",")? && !self.accept(";")? {
+                break;
+            }
+        }
+        self.expect("}")?;
+        Ok(Expr::Table(fields))
+    }
+}
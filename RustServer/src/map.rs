@@ -0,0 +1,462 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use crate::chunk::Heightmap;
+use crate::dimension::Dimension;
+use crate::gamemode::GameMode;
+use crate::item::ItemStack;
+use crate::json::{self, Json};
+use crate::protocol::clientbound;
+use crate::protocol::types::write_varint_to_vec;
+use crate::{block, click, entity, Connection, Player, Server, World};
+
+pub const FILLED_MAP: &str = "minecraft:filled_map";
+pub const EMPTY_MAP: &str = "minecraft:map";
+pub const MAX_SCALE: u8 = 4;
+// Slots des Kartentisches: Karte, Papier bzw. Glasscheibe bzw. leere Karte, Ergebnis
+pub const TABLE_MAP: usize = 0;
+pub const TABLE_EXTRA: usize = 1;
+pub const TABLE_RESULT: usize = 2;
+pub const TABLE_SLOTS: usize = 3;
+const SIZE: usize = 128;
+const MAP_DIR: &str = "data";
+// Kennung und Formatversion am Anfang jeder Kartendatei; danach Länge und Kopf als JSON, dann die Farben
+const MAP_MAGIC: &[u8; 4] = b"RMM\x01";
+// Wie bei Vanilla rendert jeder Tick nur jede 16. Pixelspalte um den Spieler
+const RENDER_STEPS: i32 = 16;
+// Markierungen in minecraft:map_decoration_type
+const MARKER_PLAYER: i32 = 0;
+const MARKER_PLAYER_OFF_MAP: i32 = 6;
+// Bis wie weit hinter dem Kartenrand ein Spieler noch am Rand angezeigt wird
+const OFF_MAP_RANGE: f64 = 320.0;
+// Helligkeitsstufen der Kartenfarben: jede Grundfarbe gibt es in vier Schattierungen
+const SHADE_DARK: u8 = 0;
+const SHADE_NORMAL: u8 = 1;
+const SHADE_BRIGHT: u8 = 2;
+
+// Grundfarben aus MapColor, soweit es die Blöcke dieses Servers brauchen
+const COLOR_NONE: u8 = 0;
+const COLOR_GRASS: u8 = 1;
+const COLOR_SAND: u8 = 2;
+const COLOR_FIRE: u8 = 4;
+const COLOR_METAL: u8 = 6;
+const COLOR_PLANT: u8 = 7;
+const COLOR_DIRT: u8 = 10;
+const COLOR_STONE: u8 = 11;
+const COLOR_WATER: u8 = 12;
+const COLOR_WOOD: u8 = 13;
+const COLOR_BLACK: u8 = 29;
+const COLOR_PODZOL: u8 = 34;
+
+fn base_color(state: u16) -> u8 {
+    let name = block::name(state);
+    if block::is_sign(state) {
+        return COLOR_WOOD;
+    }
+    match name {
+        "minecraft:air" | "minecraft:unknown" => COLOR_NONE,
+        "minecraft:grass_block" => COLOR_GRASS,
+        "minecraft:dirt" => COLOR_DIRT,
+        "minecraft:sand" | "minecraft:end_stone" => COLOR_SAND,
+        "minecraft:water" => COLOR_WATER,
+        "minecraft:lava" => COLOR_FIRE,
+        "minecraft:oak_planks" | "minecraft:chest" => COLOR_WOOD,
+        // Von oben zeigt ein stehender Stamm sein Holz, liegend die Rinde
+        "minecraft:oak_log" if block::property(state, "axis") == Some("y") => COLOR_WOOD,
+        "minecraft:oak_log" => COLOR_PODZOL,
+        "minecraft:oak_leaves" => COLOR_PLANT,
+        "minecraft:obsidian" | "minecraft:end_portal" | "minecraft:dragon_egg" => COLOR_BLACK,
+        "minecraft:anvil" | "minecraft:chipped_anvil" | "minecraft:damaged_anvil" => COLOR_METAL,
+        _ => COLOR_STONE,
+    }
+}
+
+// Oberster sichtbarer Block einer Säule: Höhe, Grundfarbe und bei Wasser dessen Tiefe. None, wenn der Chunk nicht
+// geladen ist
+fn sample(world: &World, x: i32, z: i32) -> Option<(i32, u8, i32)> {
+    let chunk = world.chunks.get(&(x >> 4, z >> 4))?;
+    let (cx, cz) = ((x & 15) as usize, (z & 15) as usize);
+    let bottom = world.dimension.min_y();
+    let mut y = (bottom + world.dimension.height()).min(chunk.height(Heightmap::WorldSurface, cx, cz)) - 1;
+    while y >= bottom {
+        let state = chunk.get_block(cx, y, cz);
+        let color = base_color(state);
+        if color != COLOR_NONE {
+            let mut depth = 0;
+            if color == COLOR_WATER {
+                while y - depth > bottom && base_color(chunk.get_block(cx, y - depth, cz)) == COLOR_WATER {
+                    depth += 1;
+                }
+            }
+            return Some((y, color, depth));
+        }
+        y -= 1;
+    }
+    None
+}
+
+// Eine Markierung wie im Map-Data-Paket: Typ, Position in halben Pixeln um die Mitte, Richtung in 16 Schritten
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Marker {
+    kind: i32,
+    x: i8,
+    z: i8,
+    rotation: u8,
+}
+
+// Was ein Spieler, der die Karte bei sich trägt, schon bekommen hat
+#[derive(Debug, Clone, Default)]
+struct Carrier {
+    // Noch nicht geschickter Bereich (x0, z0, x1, z1), beide Enden einschließlich
+    dirty: Option<(usize, usize, usize, usize)>,
+    markers: Vec<Marker>,
+    // Erst nach dem ersten vollständigen Paket werden nur noch Änderungen geschickt
+    initialized: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct MapData {
+    pub dimension: Dimension,
+    pub center: (i32, i32),
+    pub scale: u8,
+    pub locked: bool,
+    colors: Vec<u8>,
+    carriers: HashMap<Uuid, Carrier>,
+    changed: bool,
+}
+
+impl MapData {
+    // Vanilla legt Karten auf ein Raster, damit sich Karten derselben Stufe lückenlos aneinanderreihen
+    pub fn new(dimension: Dimension, (x, z): (f64, f64), scale: u8) -> MapData {
+        let size = SIZE as i32 * (1 << scale);
+        let grid = |v: f64| ((v + 64.0) / size as f64).floor() as i32 * size + size / 2 - 64;
+        MapData { dimension, center: (grid(x), grid(z)), scale, locked: false, colors: vec![0; SIZE * SIZE], carriers: HashMap::new(), changed: true }
+    }
+
+    fn set_pixel(&mut self, x: usize, z: usize, color: u8) {
+        let pixel = &mut self.colors[z * SIZE + x];
+        if *pixel == color {
+            return;
+        }
+        *pixel = color;
+        self.changed = true;
+        for carrier in self.carriers.values_mut() {
+            carrier.dirty = Some(match carrier.dirty {
+                Some((x0, z0, x1, z1)) => (x0.min(x), z0.min(z), x1.max(x), z1.max(z)),
+                None => (x, z, x, z),
+            });
+        }
+    }
+
+    // Rendert die Spalten dieses Schritts im Umkreis des Spielers; Höhenunterschiede zum Pixel im Norden geben
+    // die Schattierung, bei Wasser die Tiefe
+    fn render(&mut self, world: &World, (x, _, z): (f64, f64, f64), step: i32) {
+        let size = 1 << self.scale;
+        let player_x = ((x - self.center.0 as f64) / size as f64).floor() as i32 + SIZE as i32 / 2;
+        let player_z = ((z - self.center.1 as f64) / size as f64).floor() as i32 + SIZE as i32 / 2;
+        let radius = SIZE as i32 / size;
+        let origin = (self.center.0 / size - SIZE as i32 / 2, self.center.1 / size - SIZE as i32 / 2);
+        for px in (player_x - radius..player_x + radius).filter(|px| px.rem_euclid(RENDER_STEPS) == step && (0..SIZE as i32).contains(px)) {
+            let mut north: Option<i32> = None;
+            for pz in player_z - radius - 1..player_z + radius {
+                let (dx, dz) = (px - player_x, pz - player_z);
+                if pz < -1 || pz >= SIZE as i32 || dx * dx + dz * dz > radius * radius {
+                    north = None;
+                    continue;
+                }
+                let Some((height, color, depth)) = sample(world, (origin.0 + px) * size, (origin.1 + pz) * size) else {
+                    north = None;
+                    continue;
+                };
+                if pz >= 0 {
+                    let dither = ((px + pz) & 1) as f64;
+                    let shade = if color == COLOR_WATER {
+                        match depth as f64 * 0.1 + dither * 0.2 {
+                            d if d < 0.5 => SHADE_BRIGHT,
+                            d if d > 0.9 => SHADE_DARK,
+                            _ => SHADE_NORMAL,
+                        }
+                    } else {
+                        let slope = north.map_or(0.0, |n| (height - n) as f64 * 4.0 / (size + 4) as f64) + (dither - 0.5) * 0.4;
+                        match slope {
+                            s if s > 0.6 => SHADE_BRIGHT,
+                            s if s < -0.6 => SHADE_DARK,
+                            _ => SHADE_NORMAL,
+                        }
+                    };
+                    self.set_pixel(px as usize, pz as usize, color * 4 + shade);
+                }
+                north = Some(height);
+            }
+        }
+    }
+
+    // Markierung für einen Spieler in der Dimension der Karte; weit außerhalb gibt es keine
+    fn marker(&self, player: &Player) -> Option<Marker> {
+        let size = (1 << self.scale) as f64;
+        let dx = (player.position.0 - self.center.0 as f64) / size;
+        let dz = (player.position.2 - self.center.1 as f64) / size;
+        let half = |d: f64| (d * 2.0 + 0.5).floor().clamp(-128.0, 127.0) as i8;
+        if dx.abs() <= 63.0 && dz.abs() <= 63.0 {
+            let yaw = player.rotation.0 as f64;
+            let rotation = ((yaw + if yaw < 0.0 { -8.0 } else { 8.0 }) * 16.0 / 360.0) as i32;
+            return Some(Marker { kind: MARKER_PLAYER, x: half(dx), z: half(dz), rotation: rotation.rem_euclid(16) as u8 });
+        }
+        if dx.abs() < OFF_MAP_RANGE && dz.abs() < OFF_MAP_RANGE {
+            return Some(Marker { kind: MARKER_PLAYER_OFF_MAP, x: half(dx), z: half(dz), rotation: 0 });
+        }
+        None
+    }
+
+    // Map Data mit den Markierungen und dem geänderten Ausschnitt der Farben
+    fn encode(&self, id: i32, markers: &[Marker], area: Option<(usize, usize, usize, usize)>) -> Vec<u8> {
+        let mut packet_data = write_varint_to_vec(id);
+        packet_data.extend([self.scale, self.locked as u8, 1]);
+        packet_data.extend(write_varint_to_vec(markers.len() as i32));
+        for marker in markers {
+            packet_data.extend(write_varint_to_vec(marker.kind));
+            packet_data.extend([marker.x as u8, marker.z as u8, marker.rotation, 0]);
+        }
+        let Some((x0, z0, x1, z1)) = area else {
+            packet_data.push(0);
+            return packet_data;
+        };
+        let (columns, rows) = (x1 - x0 + 1, z1 - z0 + 1);
+        packet_data.extend([columns as u8, rows as u8, x0 as u8, z0 as u8]);
+        packet_data.extend(write_varint_to_vec((columns * rows) as i32));
+        for z in z0..=z1 {
+            packet_data.extend(&self.colors[z * SIZE + x0..=z * SIZE + x1]);
+        }
+        packet_data
+    }
+
+    fn to_file(&self) -> Vec<u8> {
+        let mut header = BTreeMap::new();
+        header.insert("dimension".to_string(), Json::String(self.dimension.name().to_string()));
+        header.insert("x".to_string(), Json::Number(self.center.0 as f64));
+        header.insert("z".to_string(), Json::Number(self.center.1 as f64));
+        header.insert("scale".to_string(), Json::Number(self.scale as f64));
+        header.insert("locked".to_string(), Json::Bool(self.locked));
+        let header = Json::Object(header).to_json_string();
+        let mut data = MAP_MAGIC.to_vec();
+        data.extend((header.len() as u32).to_be_bytes());
+        data.extend(header.as_bytes());
+        data.extend(&self.colors);
+        data
+    }
+
+    fn from_file(data: &[u8]) -> Result<MapData, String> {
+        let data = data.strip_prefix(MAP_MAGIC).ok_or("keine Kartendatei")?;
+        let (length, data) = data.split_first_chunk::<4>().ok_or("Länge des Kopfs fehlt")?;
+        let (header, colors) = data.split_at_checked(u32::from_be_bytes(*length) as usize).ok_or("Kopf unvollständig")?;
+        let header = std::str::from_utf8(header).map_err(|e| e.to_string()).and_then(json::parse)?;
+        let number = |key: &str| header.get(key).and_then(Json::as_f64).ok_or(format!("{} fehlt", key));
+        if colors.len() != SIZE * SIZE {
+            return Err("Farben unvollständig".to_string());
+        }
+        let dimension = header.get("dimension").and_then(Json::as_str).and_then(Dimension::from_name).ok_or("unbekannte Dimension")?;
+        Ok(MapData {
+            dimension,
+            center: (number("x")? as i32, number("z")? as i32),
+            scale: (number("scale")? as u8).min(MAX_SCALE),
+            locked: header.get("locked").and_then(Json::as_bool).unwrap_or(false),
+            colors: colors.to_vec(),
+            carriers: HashMap::new(),
+            changed: false,
+        })
+    }
+}
+
+// Alle Karten des Servers, gespeichert als data/map_<id>.dat in der Oberwelt
+#[derive(Debug)]
+pub struct MapStore {
+    dir: PathBuf,
+    maps: BTreeMap<i32, MapData>,
+}
+
+impl MapStore {
+    pub fn load(world_dir: &str) -> MapStore {
+        let dir = Path::new(world_dir).join(MAP_DIR);
+        let mut maps = BTreeMap::new();
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(id) = name.strip_prefix("map_").and_then(|n| n.strip_suffix(".dat")).and_then(|n| n.parse::<i32>().ok()) else { continue };
+            match fs::read(entry.path()).map_err(|e| e.to_string()).and_then(|data| MapData::from_file(&data)) {
+                Ok(map) => {
+                    maps.insert(id, map);
+                }
+                Err(e) => warn!("{} ist beschädigt: {}", entry.path().display(), e),
+            }
+        }
+        if !maps.is_empty() {
+            info!("{} Karten geladen", maps.len());
+        }
+        MapStore { dir, maps }
+    }
+
+    pub fn get(&self, id: i32) -> Option<&MapData> {
+        self.maps.get(&id)
+    }
+
+    // Neue Karte mit der nächsten freien Nummer
+    pub fn create(&mut self, map: MapData) -> i32 {
+        let id = self.maps.keys().next_back().map_or(0, |id| id + 1);
+        self.maps.insert(id, map);
+        id
+    }
+
+    // Geänderte Karten als (Pfad, Inhalt) zum Speichern; danach gelten sie als gespeichert
+    pub fn take_changed(&mut self) -> Vec<(PathBuf, Vec<u8>)> {
+        let dir = self.dir.clone();
+        self.maps.iter_mut().filter(|(_, map)| map.changed).map(|(id, map)| {
+            map.changed = false;
+            (dir.join(format!("map_{}.dat", id)), map.to_file())
+        }).collect()
+    }
+
+    // Nach einem fehlgeschlagenen Speichern beim nächsten Mal erneut versuchen
+    pub fn mark_changed(&mut self, path: &Path) {
+        let id = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_prefix("map_")?.strip_suffix(".dat")?.parse::<i32>().ok());
+        if let Some(map) = id.and_then(|id| self.maps.get_mut(&id)) {
+            map.changed = true;
+        }
+    }
+}
+
+pub fn map_id(stack: &ItemStack) -> Option<i32> {
+    (stack.item == FILLED_MAP).then_some(stack.components.map_id).flatten()
+}
+
+// Gefüllte Karte zu einer Kartennummer
+pub fn filled_map(id: i32, count: u8) -> ItemStack {
+    let mut stack = ItemStack::new(FILLED_MAP, count);
+    stack.components.map_id = Some(id);
+    stack
+}
+
+fn send(connection: &Connection, packet_data: &[u8]) -> Result<(), String> {
+    connection.send(clientbound::MAP_DATA, packet_data)
+}
+
+// Ein Tick der Karten für die Spieler einer Welt: Karten in der Hand werden um den Spieler gerendert, und jeder, der
+// eine Karte im Inventar hat, bekommt neue Pixel und die Markierungen der Spieler, die sie ebenfalls tragen
+pub fn tick(maps: &mut MapStore, players: &[Player], world: &World) {
+    let here: Vec<&Player> = players.iter().filter(|p| p.dimension == world.dimension).collect();
+    let step = world.time.age.rem_euclid(RENDER_STEPS as i64) as i32;
+    let carried: Vec<(Uuid, Vec<i32>)> = here
+        .iter()
+        .map(|p| {
+            let mut ids: Vec<i32> = p.inventory.slots().iter().filter_map(map_id).collect();
+            ids.sort();
+            ids.dedup();
+            (p.uuid, ids)
+        })
+        .collect();
+    for player in &here {
+        let held = [player.inventory.main_hand(), player.inventory.slot(player.inventory.hand_slot(true))];
+        for id in held.into_iter().filter_map(map_id) {
+            if let Some(map) = maps.maps.get_mut(&id).filter(|map| !map.locked && map.dimension == world.dimension) {
+                map.render(world, player.position, step);
+            }
+        }
+    }
+    // Wer die Karte nicht mehr trägt, bekommt beim nächsten Mal wieder den ganzen Inhalt
+    for (id, map) in maps.maps.iter_mut() {
+        for (uuid, ids) in &carried {
+            if ids.contains(id) {
+                map.carriers.entry(*uuid).or_insert_with(|| Carrier { dirty: Some((0, 0, SIZE - 1, SIZE - 1)), ..Carrier::default() });
+            } else {
+                map.carriers.remove(uuid);
+            }
+        }
+    }
+    for (player, (_, ids)) in here.iter().zip(&carried) {
+        for id in ids {
+            let Some(map) = maps.maps.get_mut(id) else { continue };
+            let markers: Vec<Marker> = if map.dimension == world.dimension {
+                here.iter().zip(&carried).filter(|(_, (_, ids))| ids.contains(id)).filter_map(|(other, _)| map.marker(other)).collect()
+            } else {
+                Vec::new()
+            };
+            let Some(carrier) = map.carriers.get(&player.uuid).cloned() else { continue };
+            if carrier.initialized && carrier.dirty.is_none() && carrier.markers == markers {
+                continue;
+            }
+            let _ = send(&player.connection, &map.encode(*id, &markers, carrier.dirty));
+            map.carriers.insert(player.uuid, Carrier { dirty: None, markers, initialized: true });
+        }
+    }
+}
+
+// Was der Kartentisch aus einer Karte macht
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cartography {
+    // Mit Papier eine Stufe herauszoomen
+    Zoom,
+    // Mit einer Glasscheibe den aktuellen Stand festhalten
+    Lock,
+    // Mit einer leeren Karte kopieren
+    Clone,
+}
+
+// Aktion für Karte und Zusatz im Kartentisch; None, wenn die Kombination nichts ergibt
+pub fn table_action(maps: &MapStore, slots: &[ItemStack]) -> Option<Cartography> {
+    let data = maps.get(map_id(&slots[TABLE_MAP])?)?;
+    match slots[TABLE_EXTRA].item.as_str() {
+        "minecraft:paper" if !data.locked && data.scale < MAX_SCALE => Some(Cartography::Zoom),
+        "minecraft:glass_pane" if !data.locked => Some(Cartography::Lock),
+        EMPTY_MAP => Some(Cartography::Clone),
+        _ => None,
+    }
+}
+
+// Rechnet das Ergebnis des Kartentisches neu aus. Bis zum Nehmen zeigt es auf die alte Karte
+pub fn update_table(maps: &MapStore, slots: &mut [ItemStack]) {
+    slots[TABLE_RESULT] = match table_action(maps, slots) {
+        Some(action) => ItemStack { count: if action == Cartography::Clone { 2 } else { 1 }, ..slots[TABLE_MAP].clone() },
+        None => ItemStack::empty(),
+    };
+}
+
+// Beim Nehmen des Ergebnisses bekommen gezoomte und fixierte Karten eigene Kartendaten
+pub fn finish_table(maps: &mut MapStore, slots: &mut [ItemStack]) {
+    let Some(action) = table_action(maps, slots) else { return };
+    let Some(data) = map_id(&slots[TABLE_RESULT]).and_then(|id| maps.get(id)).cloned() else { return };
+    let map = match action {
+        Cartography::Zoom => MapData::new(data.dimension, (data.center.0 as f64, data.center.1 as f64), data.scale + 1),
+        Cartography::Lock => MapData { locked: true, carriers: HashMap::new(), changed: true, ..data },
+        Cartography::Clone => return,
+    };
+    slots[TABLE_RESULT].components.map_id = Some(maps.create(map));
+}
+
+// Eine leere Karte im Slot der benutzten Hand legt eine neue Karte um den Spieler an, wie bei Vanilla mit
+// Stufe 0. Im Kreativmodus bleibt die leere Karte erhalten
+pub fn use_item(server: &Server, players: &mut [Player], world: &mut World, index: usize, slot: usize) {
+    let p = &mut players[index];
+    if p.inventory.slot(slot).item != EMPTY_MAP {
+        return;
+    }
+    let id = server.maps.lock().unwrap().create(MapData::new(p.dimension, (p.position.0, p.position.2), 0));
+    info!("{} hat Karte #{} angelegt", p.username, id);
+    let filled = filled_map(id, 1);
+    let creative = p.game_mode == GameMode::Creative;
+    if !creative && p.inventory.slot(slot).count == 1 {
+        p.inventory.set(slot, filled);
+        let _ = p.inventory.send_slot(&p.connection, slot);
+        return;
+    }
+    if !creative {
+        p.inventory.consume_one(slot);
+        let _ = p.inventory.send_slot(&p.connection, slot);
+    }
+    let (changed, rest) = p.inventory.add(filled);
+    for slot in changed {
+        let _ = p.inventory.send_slot(&p.connection, slot);
+    }
+    if !rest.is_empty() {
+        let position = p.position;
+        entity::spawn_item(players, world, rest, (position.0, position.1 + click::THROW_HEIGHT, position.2));
+    }
+}
@@ -0,0 +1,311 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::error::ServerError;
+use crate::gamemode::GameMode;
+use crate::item::{self, ItemStack};
+use crate::protocol::clientbound;
+use crate::protocol::types::{read_varint_from_cursor, write_varint_to_vec};
+use crate::protocol::version::ProtocolVersion;
+use crate::text::Text;
+use crate::window::{self, Container, Window};
+use crate::{dimension, experience, farming, Connection, Player, Server, World};
+
+pub const VILLAGER: &str = "minecraft:villager";
+pub const WANDERING_TRADER: &str = "minecraft:wandering_trader";
+// Slots des Handelsfensters: erster und zweiter Preis, Ware
+pub const COST: usize = 0;
+pub const SECOND_COST: usize = 1;
+pub const RESULT: usize = 2;
+pub const SLOTS: usize = 3;
+const EMERALD: &str = "minecraft:emerald";
+// Erfahrung des Dorfbewohners, ab der er die nächste Stufe erreicht (Lehrling bis Meister)
+const LEVEL_XP: [i32; 4] = [10, 70, 150, 250];
+const MAX_LEVEL: i32 = 5;
+// Neue Angebote eines Dorfbewohners je Stufe bzw. gewöhnliche und seltene des fahrenden Händlers
+const OFFERS_PER_LEVEL: usize = 2;
+const WANDERING_OFFERS: usize = 5;
+const WANDERING_RARE_OFFERS: usize = 1;
+// Vanilla zeigt den Preis nach Nachfrage so an, ohne Rabatte und Nachfrage bleibt er unverändert
+const PRICE_MULTIPLIER: f32 = 0.05;
+const TRADE_SOUND: &str = "minecraft:entity.villager.yes";
+const WANDERING_TRADE_SOUND: &str = "minecraft:entity.wandering_trader.yes";
+
+// (Stufe, Preis, Anzahl, Ware, Anzahl, Verwendungen bis zum Auffüllen, Erfahrung für den Händler)
+type Trade = (i32, &'static str, u8, &'static str, u8, i32, i32);
+
+// Die Angebote eines Bauern, denn Berufe kennt der Server noch nicht
+const VILLAGER_TRADES: &[Trade] = &[
+    (1, "minecraft:wheat", 20, EMERALD, 1, 16, 2),
+    (1, "minecraft:potato", 26, EMERALD, 1, 16, 2),
+    (1, "minecraft:carrot", 22, EMERALD, 1, 16, 2),
+    (1, "minecraft:beetroot", 15, EMERALD, 1, 16, 2),
+    (1, EMERALD, 1, "minecraft:bread", 6, 16, 1),
+    (2, "minecraft:pumpkin", 6, EMERALD, 1, 12, 10),
+    (2, EMERALD, 1, "minecraft:pumpkin_pie", 4, 12, 5),
+    (2, EMERALD, 1, "minecraft:apple", 4, 16, 5),
+    (3, EMERALD, 3, "minecraft:cookie", 18, 12, 10),
+    (3, "minecraft:melon", 4, EMERALD, 1, 12, 20),
+    (4, EMERALD, 1, "minecraft:cake", 1, 12, 15),
+    (5, EMERALD, 3, "minecraft:golden_carrot", 3, 12, 30),
+    (5, EMERALD, 4, "minecraft:glistering_melon_slice", 3, 12, 30),
+];
+
+// Stufe 1 sind die gewöhnlichen, Stufe 2 die seltenen Angebote
+const WANDERING_TRADES: &[Trade] = &[
+    (1, EMERALD, 2, "minecraft:sea_pickle", 1, 5, 1),
+    (1, EMERALD, 4, "minecraft:slime_ball", 1, 5, 1),
+    (1, EMERALD, 2, "minecraft:glowstone", 1, 5, 1),
+    (1, EMERALD, 5, "minecraft:nautilus_shell", 1, 5, 1),
+    (1, EMERALD, 1, "minecraft:fern", 1, 12, 1),
+    (1, EMERALD, 1, "minecraft:sugar_cane", 1, 8, 1),
+    (1, EMERALD, 1, "minecraft:pumpkin", 1, 4, 1),
+    (1, EMERALD, 3, "minecraft:kelp", 1, 12, 1),
+    (1, EMERALD, 3, "minecraft:cactus", 1, 8, 1),
+    (1, EMERALD, 1, "minecraft:dandelion", 1, 12, 1),
+    (1, EMERALD, 1, "minecraft:poppy", 1, 12, 1),
+    (1, EMERALD, 1, "minecraft:vine", 1, 12, 1),
+    (1, EMERALD, 1, "minecraft:brown_mushroom", 1, 12, 1),
+    (1, EMERALD, 1, "minecraft:red_mushroom", 1, 12, 1),
+    (1, EMERALD, 1, "minecraft:lily_pad", 2, 5, 1),
+    (1, EMERALD, 1, "minecraft:sand", 8, 8, 1),
+    (1, EMERALD, 1, "minecraft:red_sand", 4, 6, 1),
+    (2, EMERALD, 3, "minecraft:packed_ice", 1, 6, 1),
+    (2, EMERALD, 6, "minecraft:blue_ice", 1, 6, 1),
+    (2, EMERALD, 1, "minecraft:gunpowder", 1, 8, 1),
+    (2, EMERALD, 3, "minecraft:podzol", 3, 6, 1),
+];
+
+pub fn is_merchant(kind: &str) -> bool {
+    kind == VILLAGER || kind == WANDERING_TRADER
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offer {
+    pub cost: ItemStack,
+    // Leer, wenn das Angebot nur einen Preis hat
+    pub second_cost: ItemStack,
+    pub result: ItemStack,
+    pub uses: i32,
+    pub max_uses: i32,
+    pub xp: i32,
+}
+
+impl Offer {
+    fn from_trade(&(_, cost, cost_count, result, result_count, max_uses, xp): &Trade) -> Offer {
+        Offer {
+            cost: ItemStack::new(cost, cost_count),
+            second_cost: ItemStack::empty(),
+            result: ItemStack::new(result, result_count),
+            uses: 0,
+            max_uses,
+            xp,
+        }
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.uses >= self.max_uses
+    }
+
+    // Ob die Items in den Preisslots das Angebot bezahlen; Komponenten prüft Vanilla bei Preisen ohne Vorgabe nicht
+    fn paid_by(&self, first: &ItemStack, second: &ItemStack) -> bool {
+        let covers = |cost: &ItemStack, stack: &ItemStack| if cost.is_empty() { stack.is_empty() } else { stack.item == cost.item && stack.count >= cost.count };
+        covers(&self.cost, first) && covers(&self.second_cost, second)
+    }
+}
+
+// Angebote, Stufe und Erfahrung eines Händlers; entsteht beim ersten Handel und wird wie die Mobs nicht gespeichert
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merchant {
+    pub offers: Vec<Offer>,
+    pub level: i32,
+    pub xp: i32,
+    // Nur Dorfbewohner steigen auf; der Client zeigt dann Stufe und Erfahrungsbalken
+    villager: bool,
+}
+
+impl Merchant {
+    pub fn new(kind: &str) -> Merchant {
+        let mut merchant = Merchant { offers: Vec::new(), level: 1, xp: 0, villager: kind == VILLAGER };
+        if merchant.villager {
+            merchant.unlock();
+        } else {
+            let mut rng = rand::thread_rng();
+            for (level, count) in [(1, WANDERING_OFFERS), (2, WANDERING_RARE_OFFERS)] {
+                let pool: Vec<&Trade> = WANDERING_TRADES.iter().filter(|trade| trade.0 == level).collect();
+                merchant.offers.extend(pool.choose_multiple(&mut rng, count).map(|trade| Offer::from_trade(trade)));
+            }
+        }
+        merchant
+    }
+
+    // Neue Angebote der aktuellen Stufe, zufällig aus denen dieser Stufe
+    fn unlock(&mut self) {
+        let pool: Vec<&Trade> = VILLAGER_TRADES.iter().filter(|trade| trade.0 == self.level).collect();
+        self.offers.extend(pool.choose_multiple(&mut rand::thread_rng(), OFFERS_PER_LEVEL).map(|trade| Offer::from_trade(trade)));
+    }
+
+    // Das gewählte Angebot, wenn die Preisslots es bezahlen, sonst wie bei Vanilla das erste, das sie bezahlen
+    fn offer_for(&self, selected: usize, first: &ItemStack, second: &ItemStack) -> Option<usize> {
+        let available = |index: &usize| self.offers.get(*index).is_some_and(|offer| !offer.is_disabled() && offer.paid_by(first, second));
+        Some(selected).filter(available).or_else(|| (0..self.offers.len()).find(available))
+    }
+
+    // Zieht den Preis ab, zählt die Verwendung und schreibt dem Händler Erfahrung gut. Ein Dorfbewohner steigt dabei
+    // gegebenenfalls auf und bekommt neue Angebote
+    fn trade(&mut self, index: usize, slots: &mut [ItemStack]) {
+        let offer = &mut self.offers[index];
+        for (slot, cost) in [(COST, &offer.cost), (SECOND_COST, &offer.second_cost)] {
+            let stack = &mut slots[slot];
+            stack.count -= cost.count.min(stack.count);
+            if stack.count == 0 {
+                *stack = ItemStack::empty();
+            }
+        }
+        offer.uses += 1;
+        self.xp += offer.xp;
+        while self.villager && self.level < MAX_LEVEL && self.xp >= LEVEL_XP[self.level as usize - 1] {
+            self.level += 1;
+            self.unlock();
+        }
+    }
+}
+
+// Die Ware des Angebots, das die Preisslots bezahlen
+pub fn update(merchant: &Merchant, selected: usize, slots: &mut [ItemStack]) {
+    slots[RESULT] = merchant.offer_for(selected, &slots[COST], &slots[SECOND_COST]).map_or_else(ItemStack::empty, |index| merchant.offers[index].result.clone());
+}
+
+// Nach dem Nehmen der Ware: bezahlt und rechnet die nächste aus. false, wenn kein Angebot mehr passt
+pub fn take(merchant: &mut Merchant, selected: usize, slots: &mut [ItemStack]) -> bool {
+    let Some(index) = merchant.offer_for(selected, &slots[COST], &slots[SECOND_COST]) else { return false };
+    merchant.trade(index, slots);
+    update(merchant, selected, slots);
+    true
+}
+
+// Preis als Trade Item ab 1.20.5: Item, Anzahl und keine Komponentenvorgaben; davor ein gewöhnlicher Slot
+fn encode_cost(stack: &ItemStack, version: ProtocolVersion) -> Vec<u8> {
+    if !version.has_1_20_5_login() {
+        return stack.encode(version);
+    }
+    let mut data = write_varint_to_vec(item::protocol_id_in(&stack.item, version).unwrap_or(0));
+    data.extend(write_varint_to_vec(stack.count as i32));
+    data.extend(write_varint_to_vec(0));
+    data
+}
+
+// Merchant Offers: Angebote samt Verwendungen, dann Stufe und Erfahrung für den Balken des Dorfbewohners
+pub fn send_offers(connection: &Connection, window_id: u8, merchant: &Merchant) -> Result<(), String> {
+    let version = connection.version;
+    let mut packet_data = write_varint_to_vec(window_id as i32);
+    packet_data.extend(write_varint_to_vec(merchant.offers.len() as i32));
+    for offer in &merchant.offers {
+        packet_data.extend(encode_cost(&offer.cost, version));
+        packet_data.extend(offer.result.encode(version));
+        if version.has_1_20_5_login() {
+            packet_data.push(!offer.second_cost.is_empty() as u8);
+            if !offer.second_cost.is_empty() {
+                packet_data.extend(encode_cost(&offer.second_cost, version));
+            }
+        } else {
+            packet_data.extend(offer.second_cost.encode(version));
+        }
+        packet_data.push(offer.is_disabled() as u8);
+        packet_data.extend(offer.uses.to_be_bytes());
+        packet_data.extend(offer.max_uses.to_be_bytes());
+        packet_data.extend(offer.xp.to_be_bytes());
+        packet_data.extend(0i32.to_be_bytes()); // Sonderpreis
+        packet_data.extend(PRICE_MULTIPLIER.to_be_bytes());
+        packet_data.extend(0i32.to_be_bytes()); // Nachfrage
+    }
+    packet_data.extend(write_varint_to_vec(merchant.level));
+    packet_data.extend(write_varint_to_vec(merchant.xp));
+    packet_data.push(merchant.villager as u8);
+    packet_data.push(merchant.villager as u8);
+    connection.send(clientbound::MERCHANT_OFFERS, &packet_data)
+}
+
+fn merchant_of(world: &World, window: &Window) -> Option<Merchant> {
+    let Container::Merchant(entity_id) = window.container else { return None };
+    world.mobs.iter().find(|m| m.entity_id == entity_id)?.merchant.clone()
+}
+
+// Interact mit einem Dorfbewohner oder fahrenden Händler öffnet das Handelsfenster; wie bei Vanilla handelt ein Händler
+// nur mit einem Spieler zugleich. false, wenn das Ziel kein Händler ist
+pub fn open(server: &Server, player: &Player, entity_id: i32) -> bool {
+    let mut players = server.players.lock().unwrap();
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid && p.health > 0.0) else { return false };
+    let mut world = dimension::world_of(server, players[index].dimension).lock().unwrap();
+    let Some(mob) = world.mobs.iter_mut().find(|m| m.entity_id == entity_id && is_merchant(&m.mob_type)) else { return false };
+    let busy = players.iter().any(|p| p.uuid != player.uuid && p.window.as_ref().is_some_and(|w| w.container == Container::Merchant(entity_id)));
+    if busy || players[index].game_mode == GameMode::Spectator {
+        return true;
+    }
+    let title = match mob.custom_name {
+        Some(_) => Text::new(mob.display_name()),
+        None if mob.mob_type == VILLAGER => Text::translate("entity.minecraft.villager.farmer"),
+        None => Text::translate("entity.minecraft.wandering_trader"),
+    };
+    let merchant = mob.merchant.get_or_insert_with(|| Merchant::new(&mob.mob_type)).clone();
+    debug!("{} handelt mit {}", players[index].username, mob.display_name());
+    let player = &mut players[index];
+    if window::show(player, &world, Container::Merchant(entity_id), title) {
+        let window_id = player.window_id;
+        let _ = send_offers(&player.connection, window_id, &merchant);
+    }
+    true
+}
+
+// Select Trade: merkt sich das Angebot, räumt fremde Items aus den Preisslots ins Inventar und legt wie bei Vanilla
+// passende aus dem Inventar hinein, so viele ein Stapel fasst
+pub fn handle_select(players: &mut [Player], world: &World, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let selected = read_varint_from_cursor(cursor)?;
+    let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    let Some(mut window) = p.window.take() else { return Ok(()) };
+    let Some(merchant) = merchant_of(world, &window) else {
+        p.window = Some(window);
+        return Ok(());
+    };
+    let Some(offer) = usize::try_from(selected).ok().and_then(|index| merchant.offers.get(index)) else {
+        p.window = Some(window);
+        return Ok(());
+    };
+    window.trade = selected as usize;
+    for (slot, cost) in [(COST, &offer.cost), (SECOND_COST, &offer.second_cost)] {
+        if !window.slots[slot].is_empty() && window.slots[slot].item != cost.item {
+            let (_, rest) = p.inventory.add(std::mem::replace(&mut window.slots[slot], ItemStack::empty()));
+            window.slots[slot] = rest;
+        }
+        if cost.is_empty() || (!window.slots[slot].is_empty() && window.slots[slot].item != cost.item) {
+            continue;
+        }
+        let Some(kind) = (if window.slots[slot].is_empty() { p.inventory.storage().iter().find(|s| s.item == cost.item).cloned() } else { Some(window.slots[slot].clone()) }) else { continue };
+        let room = item::max_stack_size(&kind.item).saturating_sub(window.slots[slot].count);
+        let (_, taken) = p.inventory.remove_matching(|stack| stack.stacks_with(&kind), Some(room as u32));
+        if taken > 0 {
+            window.slots[slot] = ItemStack { count: window.slots[slot].count + taken as u8, ..kind };
+        }
+    }
+    update(&merchant, window.trade, &mut window.slots);
+    window.set_sent(window.slots.clone());
+    let _ = window::send_content(&p.connection, &mut window, p);
+    p.window = Some(window);
+    Ok(())
+}
+
+// Nach einem Handel: Erfahrung für den Spieler wie bei Vanilla, die Angebote mit neuen Verwendungen und der Klang
+pub fn traded(players: &mut [Player], world: &World, index: usize, trades: u32) {
+    let player = &mut players[index];
+    let Some(window) = player.window.as_ref() else { return };
+    let Some(merchant) = merchant_of(world, window) else { return };
+    let mut rng = rand::thread_rng();
+    let xp = (0..trades).map(|_| rng.gen_range(3..=6)).sum();
+    player.experience.add_points(xp);
+    let _ = experience::send(&player.connection, &player.experience);
+    let _ = send_offers(&player.connection, window.id, &merchant);
+    let Container::Merchant(entity_id) = window.container else { return };
+    let Some(mob) = world.mobs.iter().find(|m| m.entity_id == entity_id) else { return };
+    let sound = if merchant.villager { TRADE_SOUND } else { WANDERING_TRADE_SOUND };
+    let (x, y, z) = mob.position;
+    farming::play_sound(players, world, sound, (x.floor() as i32, y.floor() as i32, z.floor() as i32));
+}
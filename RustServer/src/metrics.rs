@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use crate::protocol::{self, ConnectionState, Direction};
+use crate::Server;
+
+// Paket- und Byte-Zähler einer Verbindung bzw. des ganzen Servers. Verbindungen zählen zusätzlich pro Paket-ID
+#[derive(Debug, Default)]
+pub struct NetStats {
+    packets_in: AtomicU64,
+    packets_out: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    by_id_in: Mutex<BTreeMap<i32, PacketCount>>,
+    by_id_out: Mutex<BTreeMap<i32, PacketCount>>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PacketCount {
+    pub packets: u64,
+    pub bytes: u64,
+}
+
+// Paket-ID und Zähler, nach Bytes absteigend
+pub type PacketCounts = Vec<(i32, PacketCount)>;
+
+// (Name, Beschreibung, Wert)
+type Counter = (&'static str, &'static str, u64);
+
+// Alle Verbindungen samt Status-Anfragen und Login-Phase
+pub static TOTAL: NetStats = NetStats::new();
+
+impl NetStats {
+    pub const fn new() -> NetStats {
+        NetStats {
+            packets_in: AtomicU64::new(0),
+            packets_out: AtomicU64::new(0),
+            bytes_in: AtomicU64::new(0),
+            bytes_out: AtomicU64::new(0),
+            by_id_in: Mutex::new(BTreeMap::new()),
+            by_id_out: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn record_in(&self, bytes: usize) {
+        self.packets_in.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_out(&self, bytes: usize) {
+        self.packets_out.fetch_add(1, Ordering::Relaxed);
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    // Paket-IDs wie im Server, also unabhängig von der Version des Clients
+    pub fn record_packet_in(&self, packet_id: i32, bytes: usize) {
+        self.record_in(bytes);
+        count(&self.by_id_in, packet_id, bytes);
+    }
+
+    pub fn record_packet_out(&self, packet_id: i32, bytes: usize) {
+        self.record_out(bytes);
+        count(&self.by_id_out, packet_id, bytes);
+    }
+
+    // (empfangen, gesendet)
+    pub fn by_packet(&self) -> (PacketCounts, PacketCounts) {
+        let sorted = |map: &Mutex<BTreeMap<i32, PacketCount>>| {
+            let mut list: Vec<_> = map.lock().unwrap().iter().map(|(&id, &count)| (id, count)).collect();
+            list.sort_by_key(|(_, count)| std::cmp::Reverse(count.bytes));
+            list
+        };
+        (sorted(&self.by_id_in), sorted(&self.by_id_out))
+    }
+
+    pub fn totals(&self) -> (PacketCount, PacketCount) {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        (PacketCount { packets: load(&self.packets_in), bytes: load(&self.bytes_in) }, PacketCount { packets: load(&self.packets_out), bytes: load(&self.bytes_out) })
+    }
+
+    fn values(&self) -> [Counter; 4] {
+        [
+            ("packets_received_total", "Packets received", self.packets_in.load(Ordering::Relaxed)),
+            ("packets_sent_total", "Packets sent", self.packets_out.load(Ordering::Relaxed)),
+            ("bytes_received_total", "Bytes received including length prefix", self.bytes_in.load(Ordering::Relaxed)),
+            ("bytes_sent_total", "Bytes sent including length prefix", self.bytes_out.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+fn count(map: &Mutex<BTreeMap<i32, PacketCount>>, packet_id: i32, bytes: usize) {
+    let mut map = map.lock().unwrap();
+    let entry = map.entry(packet_id).or_default();
+    entry.packets += 1;
+    entry.bytes += bytes as u64;
+}
+
+// Name aus der Paketliste, sonst die ID in Hex
+pub fn packet_name(direction: Direction, packet_id: i32) -> String {
+    protocol::PACKETS
+        .iter()
+        .find(|p| p.state == ConnectionState::Play && p.direction == direction && p.id == packet_id)
+        .map_or_else(|| format!("0x{:02X}", packet_id), |p| p.name.to_string())
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1048575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    fn metric(&mut self, name: &str, kind: &str, help: &str, samples: &[(String, f64)]) {
+        let _ = writeln!(self.out, "# HELP rustmc_{} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE rustmc_{} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(self.out, "rustmc_{}{} {}", name, labels, value);
+        }
+    }
+}
+
+// Textformat 0.0.4 von Prometheus
+pub fn render(server: &Server) -> String {
+    let players = server.players.lock().unwrap();
+    let world = server.world.lock().unwrap();
+    let online = players.len();
+    let chunks = world.chunks.len();
+    let entities = world.entities.len();
+    let connections: Vec<(String, [Counter; 4])> = players.iter().map(|p| (escape_label(&p.username), p.connection.stats.values())).collect();
+    let packet_types: Vec<_> = players.iter().map(|p| (escape_label(&p.username), p.connection.stats.by_packet())).collect();
+    drop(world);
+    drop(players);
+    let stats = server.tick_stats.lock().unwrap();
+    let tps = [("1m", stats.tps(60)), ("5m", stats.tps(300)), ("15m", stats.tps(900))];
+    let (average, worst) = (stats.average_mspt(), stats.worst_mspt());
+    drop(stats);
+
+    let mut exposition = Exposition { out: String::new() };
+    exposition.metric("players_online", "gauge", "Players currently online", &[(String::new(), online as f64)]);
+    exposition.metric("players_max", "gauge", "Maximum number of players", &[(String::new(), server.config.max_players as f64)]);
+    let tps: Vec<_> = tps.iter().map(|(window, value)| (format!("{{window=\"{}\"}}", window), *value)).collect();
+    exposition.metric("tps", "gauge", "Ticks per second averaged over the window", &tps);
+    exposition.metric("mspt_average", "gauge", "Average milliseconds per tick over recent ticks", &[(String::new(), average)]);
+    exposition.metric("mspt_worst", "gauge", "Slowest tick in milliseconds over recent ticks", &[(String::new(), worst)]);
+    exposition.metric("chunks_loaded", "gauge", "Chunks held in memory", &[(String::new(), chunks as f64)]);
+    exposition.metric("entities", "gauge", "Tracked entities including players", &[(String::new(), entities as f64)]);
+    for (name, help, value) in TOTAL.values() {
+        exposition.metric(name, "counter", help, &[(String::new(), value as f64)]);
+    }
+    for (i, (name, help, _)) in TOTAL.values().into_iter().enumerate() {
+        let samples: Vec<_> = connections.iter().map(|(player, values)| (format!("{{player=\"{}\"}}", player), values[i].2 as f64)).collect();
+        exposition.metric(&format!("connection_{}", name), "counter", &format!("{} on a player connection", help), &samples);
+    }
+    let mut packets = Vec::new();
+    let mut bytes = Vec::new();
+    for (player, (received, sent)) in &packet_types {
+        for (direction, list) in [(Direction::Serverbound, received), (Direction::Clientbound, sent)] {
+            for (id, count) in list {
+                let labels = format!("{{player=\"{}\",direction=\"{}\",packet=\"{}\"}}", player, direction.name(), packet_name(direction, *id));
+                packets.push((labels.clone(), count.packets as f64));
+                bytes.push((labels, count.bytes as f64));
+            }
+        }
+    }
+    exposition.metric("connection_packet_type_packets_total", "counter", "Packets per packet type on a player connection", &packets);
+    exposition.metric("connection_packet_type_bytes_total", "counter", "Bytes per packet type on a player connection including length prefix", &bytes);
+    exposition.out
+}
@@ -0,0 +1,154 @@
+use crate::json::{self, Json};
+use crate::text::{Decoration, Text};
+
+// Der Client zeigt in der Serverliste nur zwei Zeilen an
+const MAX_LINES: usize = 2;
+
+// Formatierungscodes nach & oder §, wie bei Vanilla-Chatfarben
+const COLORS: [(char, &str); 16] = [
+    ('0', "black"),
+    ('1', "dark_blue"),
+    ('2', "dark_green"),
+    ('3', "dark_aqua"),
+    ('4', "dark_red"),
+    ('5', "dark_purple"),
+    ('6', "gold"),
+    ('7', "gray"),
+    ('8', "dark_gray"),
+    ('9', "blue"),
+    ('a', "green"),
+    ('b', "aqua"),
+    ('c', "red"),
+    ('d', "light_purple"),
+    ('e', "yellow"),
+    ('f', "white"),
+];
+const DECORATIONS: [(char, Decoration); 5] = [
+    ('k', Decoration::Obfuscated),
+    ('l', Decoration::Bold),
+    ('m', Decoration::Strikethrough),
+    ('n', Decoration::Underlined),
+    ('o', Decoration::Italic),
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Style {
+    color: Option<String>,
+    decorations: Vec<Decoration>,
+}
+
+// server.properties-Escapes wie bei Java-Properties auflösen, damit motd=Zeile 1\nZeile 2 zweizeilig wird
+pub fn from_config(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('u') => {
+                let hex: String = chars.clone().take(4).collect();
+                match u32::from_str_radix(&hex, 16).ok().filter(|_| hex.len() == 4).and_then(char::from_u32) {
+                    Some(decoded) => {
+                        out.push(decoded);
+                        chars.nth(3);
+                    }
+                    None => out.push('u'),
+                }
+            }
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    if is_json(&out) {
+        if let Err(e) = json::parse(&out) {
+            warn!("motd sieht nach JSON aus, ist aber ungültig ({}), verwende sie als Text", e);
+        }
+    }
+    out
+}
+
+fn is_json(text: &str) -> bool {
+    matches!(text.trim_start().chars().next(), Some('{' | '['))
+}
+
+// Textkomponente für die Statusantwort: gültiges JSON wird übernommen, sonst werden &-Codes umgesetzt
+pub fn component(motd: &str) -> Json {
+    if is_json(motd) {
+        if let Ok(component) = json::parse(motd) {
+            return component;
+        }
+    }
+    let text: Vec<&str> = motd.lines().take(MAX_LINES).collect();
+    legacy(&text.join("\n")).to_json()
+}
+
+fn segment(text: &str, style: &Style) -> Text {
+    let mut segment = Text::new(text);
+    if let Some(color) = &style.color {
+        segment = segment.color(color.clone());
+    }
+    for decoration in &style.decorations {
+        segment = segment.decoration(*decoration, true);
+    }
+    segment
+}
+
+// &#RRGGBB für Hex-Farben (seit 1.16)
+fn hex_color(chars: &[char]) -> Option<String> {
+    let digits = chars.get(..7)?;
+    (digits[0] == '#' && digits[1..].iter().all(|c| c.is_ascii_hexdigit())).then(|| digits.iter().collect::<String>().to_ascii_lowercase())
+}
+
+// Farbcodes setzen die Formatierung zurück, &r alles
+pub fn legacy(text: &str) -> Text {
+    let chars: Vec<char> = text.chars().collect();
+    let mut extra = Vec::new();
+    let mut style = Style::default();
+    let mut current = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if (c == '&' || c == '§') && i + 1 < chars.len() {
+            let code = chars[i + 1].to_ascii_lowercase();
+            let mut next = style.clone();
+            let mut consumed = 2;
+            if let Some((_, color)) = COLORS.iter().find(|(k, _)| *k == code) {
+                next = Style { color: Some(color.to_string()), decorations: Vec::new() };
+            } else if let Some((_, decoration)) = DECORATIONS.iter().find(|(k, _)| *k == code) {
+                if !next.decorations.contains(decoration) {
+                    next.decorations.push(*decoration);
+                }
+            } else if code == 'r' {
+                next = Style::default();
+            } else if let Some(color) = hex_color(&chars[i + 1..]) {
+                next = Style { color: Some(color), decorations: Vec::new() };
+                consumed = 8;
+            } else {
+                current.push(c);
+                i += 1;
+                continue;
+            }
+            if !current.is_empty() {
+                extra.push(segment(&current, &style));
+                current.clear();
+            }
+            style = next;
+            i += consumed;
+            continue;
+        }
+        current.push(c);
+        i += 1;
+    }
+    if !current.is_empty() {
+        extra.push(segment(&current, &style));
+    }
+    match extra.len() {
+        0 => segment("", &Style::default()),
+        1 => extra.pop().unwrap(),
+        _ => extra.into_iter().fold(Text::new(""), Text::append),
+    }
+}
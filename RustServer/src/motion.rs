@@ -0,0 +1,191 @@
+use rand::Rng;
+use crate::dimension;
+use crate::entity;
+use crate::protocol::clientbound;
+use crate::protocol::types::write_varint_to_vec;
+use crate::selector::Target;
+use crate::{block, Connection, Server, World};
+
+// Blöcke pro Tick
+pub type Velocity = (f64, f64, f64);
+
+// Set Entity Velocity zählt in 1/8000 Block pro Tick und begrenzt wie Vanilla auf knapp 4 Blöcke pro Tick
+const VELOCITY_SCALE: f64 = 8000.0;
+const MAX_VELOCITY: f64 = 3.9;
+// Schwerkraft und Luftwiderstand lebender Entities pro Tick
+const GRAVITY: f64 = 0.08;
+const AIR_DRAG: f64 = 0.98;
+// Waagerechte Bremsung in der Luft und auf normalen Blöcken (Rutschigkeit 0.6)
+const AIR_FRICTION: f64 = 0.91;
+const GROUND_FRICTION: f64 = 0.6 * 0.91;
+// In Wasser und Lava bremst die Flüssigkeit in alle Richtungen, und die Schwerkraft wirkt nur schwach
+const WATER_DRAG: f64 = 0.8;
+const LAVA_DRAG: f64 = 0.5;
+const FLUID_GRAVITY: f64 = 0.02;
+// Darunter gilt die Bewegung als beendet
+const MIN_VELOCITY: f64 = 0.003;
+// Rückstoß eines Schlags; jede Stufe von Rückstoß am Schwert kommt hinzu
+pub const ATTACK_KNOCKBACK: f64 = 0.4;
+const KNOCKBACK_PER_LEVEL: f64 = 0.5;
+const MAX_KNOCKBACK_Y: f64 = 0.4;
+// Augenhöhe als Anteil der Hitbox, bei Spielern 1.62 von 1.8
+const EYE_RATIO: f64 = 0.9;
+
+pub fn send(connection: &Connection, id: i32, velocity: Velocity) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(id);
+    for v in [velocity.0, velocity.1, velocity.2] {
+        packet_data.extend(((v.clamp(-MAX_VELOCITY, MAX_VELOCITY) * VELOCITY_SCALE) as i16).to_be_bytes());
+    }
+    connection.send(clientbound::SET_ENTITY_VELOCITY, &packet_data)
+}
+
+pub fn attack_strength(knockback_level: u32) -> f64 {
+    ATTACK_KNOCKBACK + knockback_level as f64 * KNOCKBACK_PER_LEVEL
+}
+
+// Gibt einer Entity eine neue Geschwindigkeit. Spieler bewegt ihr eigener Client, die Bewegungsprüfung lässt ihnen
+// den Stoß durchgehen; Mobs bewegt tick auf dem Server. Die Clients in Sichtweite bekommen sie sofort.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn set_velocity(server: &Server, target: &Target, velocity: Velocity) -> bool {
+    let mut players = server.players.lock().unwrap();
+    let (entity_id, dimension, position) = if target.is_player() {
+        let Some(player) = players.iter_mut().find(|p| p.uuid == target.uuid) else { return false };
+        let _ = send(&player.connection, entity::OWN_PLAYER_ID, velocity);
+        player.movement.push(velocity);
+        (player.entity_id, player.dimension, player.position)
+    } else {
+        let Some(world) = dimension::world_with_mob(server, target.uuid) else { return false };
+        let mut world = world.lock().unwrap();
+        let dimension = world.dimension;
+        let Some(mob) = world.mobs.iter_mut().find(|m| m.id == target.uuid) else { return false };
+        mob.velocity = velocity;
+        (mob.entity_id, dimension, mob.position)
+    };
+    for viewer in players.iter().filter(|p| p.uuid != target.uuid && entity::in_view(p, dimension, position)) {
+        let _ = send(&viewer.connection, entity_id, velocity);
+    }
+    true
+}
+
+// Waagerechte Richtung von source zum Ziel; genau übereinander zufällig wie bei Vanilla
+fn away(source: (f64, f64, f64), position: (f64, f64, f64)) -> (f64, f64) {
+    let (mut dx, mut dz) = (position.0 - source.0, position.2 - source.2);
+    while dx * dx + dz * dz < 1.0e-4 {
+        let mut rng = rand::thread_rng();
+        (dx, dz) = (rng.gen_range(-0.01..0.01), rng.gen_range(-0.01..0.01));
+    }
+    let length = (dx * dx + dz * dz).sqrt();
+    (dx / length, dz / length)
+}
+
+// Stößt eine Entity von source weg wie LivingEntity.knockback bei Vanilla: die halbe bisherige Geschwindigkeit
+// bleibt, dazu strength waagerecht und höchstens 0.4 nach oben
+pub fn knockback(server: &Server, target: &Target, source: (f64, f64, f64), strength: f64) -> bool {
+    let (dx, dz) = away(source, target.position);
+    let old = if target.is_player() { (0.0, 0.0, 0.0) } else { mob_velocity(server, target).unwrap_or((0.0, 0.0, 0.0)) };
+    let velocity = (old.0 / 2.0 + dx * strength, (old.1 / 2.0 + strength).min(MAX_KNOCKBACK_Y), old.2 / 2.0 + dz * strength);
+    set_velocity(server, target, velocity)
+}
+
+// Fester Stoß weg von source, etwa wenn der Enderdrache einen Spieler rammt
+pub fn push(server: &Server, target: &Target, source: (f64, f64, f64), horizontal: f64, vertical: f64) -> bool {
+    let (dx, dz) = away(source, target.position);
+    set_velocity(server, target, (dx * horizontal, vertical, dz * horizontal))
+}
+
+// Druckwelle einer Explosion: vom Zentrum zu den Augen des Ziels, impact ist 1 im Zentrum und 0 am Rand
+pub fn explosion(server: &Server, target: &Target, center: (f64, f64, f64), impact: f64) -> bool {
+    let eye = target.position.1 + entity::hitbox(&target.kind).1 * EYE_RATIO;
+    let (dx, dy, dz) = (target.position.0 - center.0, eye - center.1, target.position.2 - center.2);
+    let length = (dx * dx + dy * dy + dz * dz).sqrt();
+    if length < 1.0e-4 {
+        return false;
+    }
+    let old = if target.is_player() { (0.0, 0.0, 0.0) } else { mob_velocity(server, target).unwrap_or((0.0, 0.0, 0.0)) };
+    set_velocity(server, target, (old.0 + dx / length * impact, old.1 + dy / length * impact, old.2 + dz / length * impact))
+}
+
+fn mob_velocity(server: &Server, target: &Target) -> Option<Velocity> {
+    let world = dimension::world_with_mob(server, target.uuid)?.lock().unwrap();
+    world.mobs.iter().find(|m| m.id == target.uuid).map(|m| m.velocity)
+}
+
+// Ein Bewegungsschritt bis zur Ruhe am Boden. Feste Blöcke halten waagerecht und von unten auf, Flüssigkeiten
+// bremsen; None in nicht geladenen Chunks oder unter der Welt, dort endet die Bewegung
+fn step(world: &World, (x, y, z): (f64, f64, f64), (mut vx, mut vy, mut vz): Velocity, gravity: bool) -> Option<((f64, f64, f64), Velocity)> {
+    let (mut nx, mut ny, mut nz) = (x + vx, y + vy, z + vz);
+    if !world.chunks.contains_key(&((nx.floor() as i32) >> 4, (nz.floor() as i32) >> 4)) || ny < world.dimension.min_y() as f64 - 64.0 {
+        return None;
+    }
+    let solid = |x: f64, y: f64, z: f64| block::is_solid(world.get_block(x.floor() as i32, y.floor() as i32, z.floor() as i32));
+    if solid(nx, y, nz) {
+        (nx, nz, vx, vz) = (x, z, 0.0, 0.0);
+    }
+    let on_ground = vy <= 0.0 && solid(nx, ny - 1.0e-3, nz);
+    let state = world.get_block(nx.floor() as i32, ny.floor() as i32, nz.floor() as i32);
+    let fluid_drag = if block::is_water(state) { Some(WATER_DRAG) } else if block::is_lava(state) { Some(LAVA_DRAG) } else { None };
+    if on_ground {
+        (ny, vy) = ((ny - 1.0e-3).floor() + 1.0, 0.0);
+    } else if let Some(drag) = fluid_drag {
+        vy = vy * drag - if gravity { FLUID_GRAVITY } else { 0.0 };
+    } else if gravity {
+        vy = (vy - GRAVITY) * AIR_DRAG;
+    } else {
+        vy *= AIR_DRAG;
+    }
+    let friction = fluid_drag.unwrap_or(if on_ground { GROUND_FRICTION } else { AIR_FRICTION });
+    (vx, vz) = (vx * friction, vz * friction);
+    let resting = vx.abs() < MIN_VELOCITY && vz.abs() < MIN_VELOCITY && (on_ground || !gravity) && vy.abs() < MIN_VELOCITY;
+    Some(((nx, ny, nz), if resting { (0.0, 0.0, 0.0) } else { (vx, vy, vz) }))
+}
+
+// Bewegt Mobs, Items und Erfahrungskugeln mit Geschwindigkeit. Die Positionen der Mobs sendet der Tracker; Items und
+// Kugeln bewegt der Client ab dem Spawn selbst genauso
+pub fn tick(server: &Server) {
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let _profile = server.profiler.world("entities", world.dimension);
+        for index in 0..world.mobs.len() {
+            let mob = &world.mobs[index];
+            if mob.velocity == (0.0, 0.0, 0.0) || mob.projectile.is_some() || mob.potion.is_some() {
+                continue;
+            }
+            let (id, moved) = (mob.id, step(&world, mob.position, mob.velocity, entity::has_gravity(&mob.mob_type)));
+            let mob = &mut world.mobs[index];
+            let Some((position, velocity)) = moved else {
+                mob.velocity = (0.0, 0.0, 0.0);
+                continue;
+            };
+            (mob.position, mob.velocity) = (position, velocity);
+            world.entities.update(id, position);
+        }
+        for index in 0..world.items.len() {
+            let item = &world.items[index];
+            if item.velocity == (0.0, 0.0, 0.0) {
+                continue;
+            }
+            let (id, moved) = (item.uuid, step(&world, item.position, item.velocity, true));
+            let item = &mut world.items[index];
+            let Some((position, velocity)) = moved else {
+                item.velocity = (0.0, 0.0, 0.0);
+                continue;
+            };
+            (item.position, item.velocity) = (position, velocity);
+            world.entities.update(id, position);
+        }
+        for index in 0..world.orbs.len() {
+            let orb = &world.orbs[index];
+            if orb.velocity == (0.0, 0.0, 0.0) {
+                continue;
+            }
+            let (id, moved) = (orb.uuid, step(&world, orb.position, orb.velocity, true));
+            let orb = &mut world.orbs[index];
+            let Some((position, velocity)) = moved else {
+                orb.velocity = (0.0, 0.0, 0.0);
+                continue;
+            };
+            (orb.position, orb.velocity) = (position, velocity);
+            world.entities.update(id, position);
+        }
+    }
+}
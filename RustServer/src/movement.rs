@@ -0,0 +1,288 @@
+use std::time::{Duration, Instant};
+use crate::damage::{self, DamageSource};
+use crate::selector::Target;
+use crate::violation::{self, Violations};
+use crate::{block, dimension, effect, fluid, gamerule, inventory, Player, Server, World};
+
+// Sprinten mit Sprüngen schafft knapp 0,7 Blöcke pro Tick, Gehen mit Sprüngen knapp 0,4; freier Fall höchstens 3,92
+const MAX_HORIZONTAL_PER_TICK: f64 = 0.8;
+const MAX_WALK_PER_TICK: f64 = 0.5;
+// Schwimmend mit Sprint knapp 0,3 Blöcke pro Tick, im Wasser Gehen deutlich weniger. Wasserläufer und Gunst des
+// Delfins heben die Grenze auf
+const MAX_SWIM_PER_TICK: f64 = 0.4;
+const DEPTH_STRIDER: &str = "minecraft:depth_strider";
+// Die ersten drei Blöcke eines Sturzes sind frei, jeder weitere kostet einen Lebenspunkt
+const SAFE_FALL_DISTANCE: f64 = 3.0;
+// Im Sturzflug mit der Elytra knapp 4 Blöcke pro Tick, mit Raketen waagerecht gut 1,7
+const MAX_GLIDE_PER_TICK: f64 = 4.0;
+// So lange trägt der Schwung nach dem Ende des Sprints noch weiter
+const SPRINT_MOMENTUM: Duration = Duration::from_secs(1);
+// Rückstoß klingt in der Luft mit derselben Reibung ab wie bei Mobs
+const PUSH_DECAY: f64 = 0.91;
+// Sprintendes Fliegen im Kreativmodus schafft gut 1,1 Blöcke pro Tick
+const FLIGHT_FACTOR: f64 = 2.5;
+const MAX_FALL_PER_TICK: f64 = 4.0;
+// Ein Sprung hebt um 0,42 pro Tick, das automatische Hochsteigen um 0,6
+const MAX_RISE_PER_TICK: f64 = 0.65;
+// Sprunghöhe ohne Sprungkraft (1,25) mit etwas Spielraum
+const MAX_JUMP_HEIGHT: f64 = 1.35;
+// Nach längerer Pause darf ein Paket nicht beliebig viel Weg auf einmal nachholen
+const MAX_BANKED_TICKS: f64 = 10.0;
+// Pakete in der Luft, ohne zu sinken, bevor das als Schweben gilt
+const MAX_HOVER_PACKETS: u32 = 20;
+// Jede plausible Bewegung baut Verstöße ab
+const VIOLATION_DECAY: f64 = 0.05;
+// Halbe Breite der Spieler-Hitbox (0,6), knapp darunter, damit Wände nicht schon beim Berühren zählen
+const HALF_WIDTH: f64 = 0.299;
+// Schrittweite, in der der Weg zwischen zwei Positionen auf Blöcke geprüft wird
+const PATH_STEP: f64 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Violation {
+    TooFast,
+    Flying,
+    Hovering,
+    NoClip,
+}
+
+impl Violation {
+    fn describe(self) -> &'static str {
+        match self {
+            Violation::TooFast => "moved too quickly",
+            Violation::Flying => "rose too high",
+            Violation::Hovering => "hovered in the air",
+            Violation::NoClip => "moved through blocks",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MovementState {
+    last_move: Instant,
+    // Höhe, auf der der Spieler zuletzt Halt hatte
+    ground_y: f64,
+    hover_packets: u32,
+    violations: Violations,
+    // Der Server hat den Spieler zurückgesetzt; die Bestätigung gilt dann nicht als neuer Halt
+    correcting: bool,
+    // Zuletzt vom Client gemeldet, für die Erkennung von Sprüngen
+    on_ground: bool,
+    sprint_ended: Option<Instant>,
+    // Waagerechter Weg pro Tick, den Rückstoß und Explosionen zusätzlich erlauben
+    push: f64,
+    fall_distance: f64,
+    // Fallschaden seit dem letzten Tick; ihn fügt tick zu, weil Bewegungen unter Sperren verarbeitet werden
+    fall_damage: f32,
+}
+
+impl MovementState {
+    pub fn new(position: (f64, f64, f64)) -> MovementState {
+        MovementState {
+            last_move: Instant::now(),
+            ground_y: position.1,
+            hover_packets: 0,
+            violations: Violations::default(),
+            correcting: false,
+            on_ground: true,
+            sprint_ended: None,
+            push: 0.0,
+            fall_distance: 0.0,
+            fall_damage: 0.0,
+        }
+    }
+
+    pub fn stop_sprinting(&mut self) {
+        self.sprint_ended = Some(Instant::now());
+    }
+
+    pub fn push(&mut self, velocity: (f64, f64, f64)) {
+        self.push = self.push.max((velocity.0 * velocity.0 + velocity.2 * velocity.2).sqrt());
+    }
+
+    pub fn on_ground(&self) -> bool {
+        self.on_ground
+    }
+
+    // Meldet der Client nach Halt erstmals keinen mehr, hat der Spieler abgehoben
+    pub fn take_off(&mut self, on_ground: bool) -> bool {
+        std::mem::replace(&mut self.on_ground, on_ground) && !on_ground
+    }
+
+    // Unter dem letzten Halt, also mitten im Fall
+    pub fn is_falling(&self, y: f64) -> bool {
+        y < self.ground_y
+    }
+
+    // Nach bestätigter Teleportation; sonst könnte man sich nach jeder Korrektur ein Stück höher arbeiten
+    pub fn confirm_teleport(&mut self, position: (f64, f64, f64)) {
+        self.last_move = Instant::now();
+        if !std::mem::take(&mut self.correcting) {
+            self.ground_y = position.1;
+            self.hover_packets = 0;
+        }
+        self.fall_distance = 0.0;
+    }
+}
+
+fn solid_at(world: &World, x: f64, y: f64, z: f64) -> bool {
+    let state = world.get_block(x.floor() as i32, y.floor() as i32, z.floor() as i32);
+    block::is_solid(state) && y - y.floor() < block::collision_height(state)
+}
+
+fn fluid_at(world: &World, x: f64, y: f64, z: f64) -> bool {
+    block::is_fluid(world.get_block(x.floor() as i32, y.floor() as i32, z.floor() as i32))
+}
+
+fn corners(x: f64, z: f64) -> [(f64, f64); 4] {
+    [(x - HALF_WIDTH, z - HALF_WIDTH), (x + HALF_WIDTH, z - HALF_WIDTH), (x - HALF_WIDTH, z + HALF_WIDTH), (x + HALF_WIDTH, z + HALF_WIDTH)]
+}
+
+// Fester Block unter einer Ecke der Hitbox oder Flüssigkeit auf Höhe von Füßen bzw. Kopf
+fn supported(world: &World, (x, y, z): (f64, f64, f64)) -> bool {
+    corners(x, z).iter().any(|&(cx, cz)| solid_at(world, cx, y - 0.1, cz) || fluid_at(world, cx, y, cz) || fluid_at(world, cx, y + 1.0, cz))
+}
+
+// Füße und Brust der Hitbox; der Kopf bleibt frei, damit Sprünge gegen Decken nicht scheitern
+fn collides(world: &World, (x, y, z): (f64, f64, f64)) -> bool {
+    [0.05, 0.55].iter().any(|dy| corners(x, z).iter().any(|&(cx, cz)| solid_at(world, cx, y + dy, cz)))
+}
+
+fn loaded(world: &World, (x, _, z): (f64, f64, f64)) -> bool {
+    world.chunks.contains_key(&((x.floor() as i32) >> 4, (z.floor() as i32) >> 4))
+}
+
+fn amplifier(player: &Player, id: usize) -> Option<f64> {
+    player.effects.get(id).map(|effect| effect.amplifier as f64 + 1.0)
+}
+
+// Prüft die Bewegung von der zuletzt akzeptierten Position nach to; None, wenn sie plausibel ist
+pub fn check(world: &World, player: &mut Player, to: (f64, f64, f64)) -> Option<Violation> {
+    let from = player.position;
+    let (dx, dy, dz) = (to.0 - from.0, to.1 - from.1, to.2 - from.2);
+    let ticks = (player.movement.last_move.elapsed().as_secs_f64() * 20.0).clamp(1.0, MAX_BANKED_TICKS);
+    let flying = player.game_mode.allows_flight();
+    let speed = (1.0 + amplifier(player, effect::SPEED).map_or(0.0, |level| 0.2 * level)) * if flying { FLIGHT_FACTOR } else { 1.0 };
+    let jump = amplifier(player, effect::JUMP_BOOST).unwrap_or(0.0);
+    let distance = (dx * dx + dz * dz).sqrt();
+    let sprinting = player.pose.sprinting || player.movement.sprint_ended.is_some_and(|ended| ended.elapsed() < SPRINT_MOMENTUM);
+    let gliding = player.pose.gliding;
+    let swimming = loaded(world, from) && loaded(world, to) && fluid_at(world, from.0, from.1, from.2) && fluid_at(world, to.0, to.1, to.2);
+    let strider = player.inventory.slot(inventory::FEET).components.enchantments.contains_key(DEPTH_STRIDER);
+    let max_per_tick = if gliding {
+        MAX_GLIDE_PER_TICK
+    } else if swimming && !flying && !strider && player.effects.get(effect::DOLPHINS_GRACE).is_none() {
+        MAX_SWIM_PER_TICK
+    } else if sprinting || flying {
+        MAX_HORIZONTAL_PER_TICK
+    } else {
+        MAX_WALK_PER_TICK
+    };
+    let push = player.movement.push;
+    player.movement.push *= PUSH_DECAY.powf(ticks);
+    if !to.0.is_finite() || !to.1.is_finite() || !to.2.is_finite() || distance > (max_per_tick * speed + push) * ticks || dy < -MAX_FALL_PER_TICK * ticks {
+        return Some(Violation::TooFast);
+    }
+    // Ohne geladene Blöcke lässt sich weder Halt noch Kollision beurteilen
+    if !loaded(world, from) || !loaded(world, to) {
+        player.movement.last_move = Instant::now();
+        return None;
+    }
+    // Wer schon in einem Block steckt (etwa weil dort gebaut wurde), muss sich herausbewegen dürfen
+    if player.game_mode.has_collision() && !collides(world, from) {
+        let steps = ((distance * distance + dy * dy).sqrt() / PATH_STEP).ceil().max(1.0) as u32;
+        let path = (1..=steps).map(|i| i as f64 / steps as f64).map(|t| (from.0 + dx * t, from.1 + dy * t, from.2 + dz * t));
+        if path.into_iter().any(|point| collides(world, point)) {
+            return Some(Violation::NoClip);
+        }
+    }
+    // Wer fliegen darf, hat überall Halt; im Gleitflug trägt der Schwung auch aufwärts, und gefallen wird erst ab dem
+    // Ende des Flugs
+    let levitating = flying || gliding || player.effects.get(effect::LEVITATION).is_some();
+    let in_fluid = corners(to.0, to.2).iter().any(|&(cx, cz)| fluid_at(world, cx, to.1, cz) || fluid_at(world, cx, to.1 + 1.0, cz));
+    if !levitating && !in_fluid && dy > (MAX_RISE_PER_TICK + 0.1 * jump) * ticks {
+        return Some(Violation::Flying);
+    }
+    let state = &mut player.movement;
+    if supported(world, to) || levitating {
+        state.ground_y = to.1;
+        state.hover_packets = 0;
+    } else {
+        if to.1 - state.ground_y > MAX_JUMP_HEIGHT + 0.6 * jump {
+            return Some(Violation::Flying);
+        }
+        if dy >= 0.0 {
+            state.hover_packets += 1;
+            if state.hover_packets > MAX_HOVER_PACKETS {
+                return Some(Violation::Hovering);
+            }
+        } else {
+            state.hover_packets = 0;
+        }
+    }
+    state.last_move = Instant::now();
+    state.violations.decay(VIOLATION_DECAY);
+    None
+}
+
+// Zählt den Verstoß und meldet Spieler, die die Schwelle erreichen. Das Zurücksetzen auf die letzte
+// gültige Position übernimmt der Aufrufer
+pub fn flag(server: &Server, players: &mut [Player], index: usize, violation: Violation) {
+    let player = &mut players[index];
+    let state = &mut player.movement;
+    state.correcting = true;
+    let alert = state.violations.add();
+    let level = state.violations.level();
+    debug!("{} zurückgesetzt: {} ({:.1} Verstöße)", player.username, violation.describe(), level);
+    if alert {
+        let suspect = player.username.clone();
+        violation::alert(server, players, &suspect, violation.describe(), level);
+    }
+}
+
+// Zählt die Fallhöhe einer akzeptierten Bewegung wie Vanilla: Wasser fängt jeden Sturz ab, Lava halbiert ihn, und
+// wer fliegt, gleitet oder schwebt, fällt nicht. Kommt der Spieler laut seinem Client auf, wird daraus Fallschaden,
+// und die Fallhöhe wird zurückgegeben; sonst 0
+pub fn fall(world: &World, player: &mut Player, to: (f64, f64, f64), on_ground: bool) -> f64 {
+    let dy = to.1 - player.position.1;
+    let floating = player.game_mode.allows_flight()
+        || player.pose.gliding
+        || player.effects.get(effect::LEVITATION).is_some()
+        || player.effects.get(effect::SLOW_FALLING).is_some();
+    let jump = amplifier(player, effect::JUMP_BOOST).unwrap_or(0.0);
+    let state = &mut player.movement;
+    if floating || fluid::in_water(world, to) {
+        state.fall_distance = 0.0;
+        return 0.0;
+    }
+    if fluid::in_lava(world, to) {
+        state.fall_distance *= 0.5;
+    }
+    if dy < 0.0 {
+        state.fall_distance -= dy;
+    }
+    if on_ground {
+        let damage = (state.fall_distance - SAFE_FALL_DISTANCE - jump).ceil();
+        if damage > 0.0 {
+            state.fall_damage += damage as f32;
+        }
+        return std::mem::take(&mut state.fall_distance);
+    }
+    0.0
+}
+
+// Fügt den Fallschaden des letzten Ticks zu, sofern die Spielregel fallDamage gilt. Darf nur ohne gehaltene
+// Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut landed: Vec<(Target, f32)> = Vec::new();
+    let mut players = server.players.lock().unwrap();
+    for player in players.iter_mut().filter(|p| p.movement.fall_damage > 0.0) {
+        let amount = std::mem::take(&mut player.movement.fall_damage);
+        if dimension::world_of(server, player.dimension).lock().unwrap().rules.get_bool(gamerule::FALL_DAMAGE) {
+            landed.push((Target::of_player(player), amount));
+        }
+    }
+    drop(players);
+    for (target, amount) in landed {
+        damage::damage(server, &target, DamageSource::Fall, amount);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use rand::Rng;
+use crate::command::CommandSender;
+use crate::config::ServerConfig;
+use crate::dimension::{self, Dimension, DimensionType};
+use crate::json::{self, Json};
+use crate::storage::{self, WorldStorage};
+use crate::worldgen::{self, Generator};
+use crate::worldsettings::WorldSettings;
+use crate::{item, Server, SpawnPoint, World};
+
+// Benannte Welten liegen als worlds/<Name> neben der Hauptwelt
+pub const WORLDS_DIR: &str = "worlds";
+// Alle bekannten Welten mit Generator und ob sie beim Start geladen werden
+const CATALOG_FILE: &str = "worlds/worlds.json";
+
+// Eine benannte Welt lebt bis zum Ende des Prozesses, damit Dimension Copy bleibt und Spieler wie Entities einfach
+// auf sie verweisen können. Nach /world unload bleibt nur der leere Eintrag ohne laufende Threads; ein erneutes Laden
+// legt darin eine neue World mit eigenen Threads ab
+pub struct NamedWorld {
+    // Mit Namespace wie der Client ihn sieht, etwa minecraft:creative
+    pub name: String,
+    pub generator: Generator,
+    pub kind: &'static DimensionType,
+    world: OnceLock<Mutex<World>>,
+    loaded: AtomicBool,
+}
+
+impl NamedWorld {
+    pub fn short_name(&self) -> &str {
+        self.name.strip_prefix("minecraft:").unwrap_or(&self.name)
+    }
+
+    pub fn world(&self) -> Option<&Mutex<World>> {
+        self.world.get().filter(|_| self.loaded.load(Ordering::Acquire))
+    }
+}
+
+impl PartialEq for NamedWorld {
+    fn eq(&self, other: &NamedWorld) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for NamedWorld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+static REGISTRY: Mutex<Vec<&'static NamedWorld>> = Mutex::new(Vec::new());
+// Welten aus /world create, deren Spawn-Umgebung noch generiert wird
+static CREATING: Mutex<Vec<Creation>> = Mutex::new(Vec::new());
+
+struct Creation {
+    entry: &'static NamedWorld,
+    world: World,
+    saved_spawn: Option<SpawnPoint>,
+    spawn_platform: bool,
+    reply_to: CommandSender,
+    started: Instant,
+}
+
+pub fn loaded() -> Vec<&'static NamedWorld> {
+    REGISTRY.lock().unwrap().iter().copied().filter(|w| w.loaded.load(Ordering::Acquire)).collect()
+}
+
+pub fn find(name: &str) -> Option<&'static NamedWorld> {
+    let name = item::namespaced(name);
+    loaded().into_iter().find(|w| w.name == name)
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 32 && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+// {"creative": {"generator": "flat", "load": true}}; "type" nur bei eigenem Dimensionstyp
+fn read_catalog() -> BTreeMap<String, Json> {
+    fs::read_to_string(CATALOG_FILE).ok().and_then(|text| json::parse(&text).ok()).and_then(|c| c.as_object().cloned()).unwrap_or_default()
+}
+
+fn write_catalog(name: &str, generator: Generator, kind: &DimensionType, load: bool) {
+    let mut catalog = read_catalog();
+    let mut entry = BTreeMap::new();
+    entry.insert("generator".to_string(), Json::String(generator.name().to_string()));
+    if kind != default_kind(generator) {
+        entry.insert("type".to_string(), Json::String(kind.name.to_string()));
+    }
+    entry.insert("load".to_string(), Json::Bool(load));
+    catalog.insert(name.to_string(), Json::Object(entry));
+    let result = fs::create_dir_all(WORLDS_DIR).and_then(|_| fs::write(CATALOG_FILE, Json::Object(catalog).to_json_string()));
+    if let Err(e) = result {
+        warn!("Konnte {} nicht schreiben: {}", CATALOG_FILE, e);
+    }
+}
+
+// Bekannte, gerade nicht geladene Welten für /world list
+pub fn unloaded() -> Vec<String> {
+    read_catalog().into_keys().filter(|name| find(name).is_none()).collect()
+}
+
+// Lädt beim Start alle Welten, die beim letzten Lauf geladen waren
+pub fn load_all(config: &ServerConfig) {
+    for (name, entry) in read_catalog() {
+        if entry.get("load").and_then(Json::as_bool) != Some(true) {
+            continue;
+        }
+        if let Err(e) = create(&name, config) {
+            warn!("Welt {} konnte nicht geladen werden: {}", name, e);
+        }
+    }
+}
+
+// Ohne eigenen Typ hat eine Welt die Höhe und den Himmel, die zu ihrem Generator passen
+fn default_kind(generator: Generator) -> &'static DimensionType {
+    match generator {
+        Generator::End => &dimension::END_TYPE,
+        _ => &dimension::OVERWORLD_TYPE,
+    }
+}
+
+// Legt eine Welt an oder lädt sie aus worlds/<Name>, ohne die Umgebung des Spawns zu generieren. Ohne Generator und
+// Dimensionstyp gelten die gespeicherten, sonst normal und der passende Typ; der Seed und die Vorgaben aus
+// server.properties zählen nur für neue Welten
+fn prepare(name: &str, generator: Option<Generator>, seed: Option<u64>, kind: Option<&'static DimensionType>, config: &ServerConfig, reply_to: CommandSender) -> Result<Creation, String> {
+    if !valid_name(name) {
+        return Err(format!("Invalid world name '{}', use lowercase letters, digits, _ and -", name));
+    }
+    let id = item::namespaced(name);
+    if name == storage::world_dir() || Dimension::from_name(&id).is_some() {
+        return Err(format!("World {} is already loaded", name));
+    }
+    if CREATING.lock().unwrap().iter().any(|c| c.entry.name == id) {
+        return Err(format!("World {} is still being generated", name));
+    }
+    let catalog = read_catalog();
+    let saved = catalog.get(name).and_then(|entry| entry.get("generator")?.as_str().and_then(Generator::from_name));
+    if let (Some(saved), Some(requested)) = (saved, generator) {
+        if saved != requested {
+            return Err(format!("World {} already exists with the {} generator", name, saved.name()));
+        }
+    }
+    let generator = generator.or(saved).unwrap_or(Generator::Normal);
+    let saved_kind = match catalog.get(name).and_then(|entry| entry.get("type")?.as_str()) {
+        Some(saved) => Some(DimensionType::find(saved).ok_or_else(|| format!("Unknown dimension type {}, is its datapack enabled?", saved))?),
+        None if saved.is_some() => Some(default_kind(generator)),
+        None => None,
+    };
+    if let (Some(saved), Some(requested)) = (saved_kind, kind) {
+        if saved != requested {
+            return Err(format!("World {} already exists with the dimension type {}", name, saved.name));
+        }
+    }
+    let kind = kind.or(saved_kind).unwrap_or_else(|| default_kind(generator));
+    let entry = {
+        let mut registry = REGISTRY.lock().unwrap();
+        match registry.iter().find(|w| w.name == id) {
+            Some(entry) if entry.generator == generator && entry.kind == kind => *entry,
+            // Mit anderem Generator oder Typ neu angelegt, nachdem die Dateien gelöscht wurden
+            _ => {
+                let entry: &'static NamedWorld = Box::leak(Box::new(NamedWorld { name: id, generator, kind, world: OnceLock::new(), loaded: AtomicBool::new(false) }));
+                registry.retain(|w| w.name != entry.name);
+                registry.push(entry);
+                entry
+            }
+        }
+    };
+
+    let dir = Path::new(WORLDS_DIR).join(name);
+    let storage = WorldStorage::open(&dir.to_string_lossy(), config.chunk_storage);
+    let level = storage.load_level();
+    let seed = level.as_ref().map(|level| level.seed).or(seed).unwrap_or_else(|| rand::thread_rng().gen());
+    let mut world = World::new(seed, storage, Dimension::Named(entry));
+    let saved_spawn = level.as_ref().and_then(|level| level.spawn);
+    world.difficulty = config.difficulty;
+    world.settings = WorldSettings::from_config(config);
+    if let Some(level) = level {
+        info!("Lade Welt aus {}", dir.display());
+        world.time = level.time;
+        world.weather = level.weather;
+        world.rules = level.rules;
+        world.border = level.border;
+        world.scheduled_functions = level.scheduled_functions;
+        world.difficulty = level.difficulty.unwrap_or(world.difficulty);
+        world.settings = level.settings.unwrap_or(world.settings);
+    }
+    Ok(Creation { entry, world, saved_spawn, spawn_platform: config.void_spawn_platform, reply_to, started: Instant::now() })
+}
+
+// Macht die Welt sichtbar, sobald die Umgebung des Spawns da ist
+fn finish(creation: Creation) -> &'static NamedWorld {
+    let Creation { entry, mut world, saved_spawn, spawn_platform, .. } = creation;
+    if saved_spawn.is_none() && entry.generator == Generator::Void && spawn_platform {
+        worldgen::place_spawn_platform(&mut world);
+    }
+    world.spawn = saved_spawn.unwrap_or(SpawnPoint { position: (0, world.highest_block_y(0, 0) + 1, 0), angle: 0.0 });
+    match entry.world.get() {
+        Some(slot) => *slot.lock().unwrap() = world,
+        None => {
+            let _ = entry.world.set(Mutex::new(world));
+        }
+    }
+    entry.loaded.store(true, Ordering::Release);
+    write_catalog(entry.short_name(), entry.generator, entry.kind, true);
+    entry
+}
+
+// Beim Start, bevor die Tick-Schleife läuft; wartet auf die Umgebung des Spawns
+fn create(name: &str, config: &ServerConfig) -> Result<&'static NamedWorld, String> {
+    let mut creation = prepare(name, None, None, None, config, CommandSender::Server)?;
+    creation.world.generate();
+    Ok(finish(creation))
+}
+
+// Für /world create: die Generator-Threads der Welt erzeugen die Umgebung des Spawns, während der Server weiterläuft.
+// reply_to erfährt von tick, wann die Welt bereit ist
+pub fn start_create(name: &str, generator: Option<Generator>, seed: Option<u64>, kind: Option<&'static DimensionType>, config: &ServerConfig, reply_to: CommandSender) -> Result<(), String> {
+    let mut creation = prepare(name, generator, seed, kind, config, reply_to)?;
+    info!("Generiere {} mit Seed {}...", creation.world.dimension.name(), creation.world.seed);
+    creation.world.request_spawn_area();
+    CREATING.lock().unwrap().push(creation);
+    Ok(())
+}
+
+// Übernimmt fertige Chunks der Welten aus start_create und gibt die frei, deren Spawn-Umgebung vollständig ist.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let ready: Vec<Creation> = {
+        let mut creating = CREATING.lock().unwrap();
+        for creation in creating.iter_mut() {
+            creation.world.poll_chunks();
+        }
+        let (ready, waiting) = std::mem::take(&mut *creating).into_iter().partition(|c| c.world.spawn_area_ready());
+        *creating = waiting;
+        ready
+    };
+    for creation in ready {
+        let (reply_to, started, chunks) = (creation.reply_to, creation.started, creation.world.chunks.len());
+        let world = finish(creation);
+        info!("Welt {} generiert: {} Chunks in {:?}", world.short_name(), chunks, started.elapsed());
+        reply_to.reply(server, &format!("World {} is ready ({} generator, dimension type {})", world.short_name(), world.generator.name(), world.kind.name));
+    }
+}
+
+// Schickt alle Spieler der Welt zum Spawn der Oberwelt, speichert und gibt Chunks und Entities frei.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn unload(server: &Server, name: &str) -> Result<(), String> {
+    let entry = find(name).ok_or_else(|| format!("World {} is not loaded", name))?;
+    let dimension = Dimension::Named(entry);
+    let inside: Vec<_> = server.players.lock().unwrap().iter().filter(|p| p.dimension == dimension).map(|p| (p.uuid, p.rotation)).collect();
+    let spawn = server.world.lock().unwrap().spawn;
+    let (x, y, z) = spawn.position;
+    for (uuid, (_, pitch)) in inside {
+        dimension::change_dimension(server, uuid, Dimension::Overworld, (x as f64 + 0.5, y as f64, z as f64 + 0.5), (spawn.angle, pitch), dimension::KEEP_ALL)?;
+    }
+    storage::save_all(server, true);
+    entry.loaded.store(false, Ordering::Release);
+    if let Some(world) = entry.world.get() {
+        let mut world = world.lock().unwrap();
+        world.chunks.clear();
+        world.loading.clear();
+        world.light.clear();
+        world.mobs.clear();
+        world.items.clear();
+        world.orbs.clear();
+        world.entities = Default::default();
+        world.dirty_chunks.clear();
+        world.chunk_viewers.clear();
+        world.shut_down();
+    }
+    write_catalog(entry.short_name(), entry.generator, entry.kind, false);
+    info!("Welt {} entladen", entry.short_name());
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+use std::f64::consts::TAU;
+use rand::Rng;
+use crate::config::ServerConfig;
+use crate::difficulty::Difficulty;
+use crate::gamemode::GameMode;
+use crate::{block, chunk, entity, gamerule, worldsettings, Mob, Player, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Spawnbereich je Spieler wie bei Vanilla: 17×17 Chunks, auf die sich die Mob-Obergrenzen beziehen
+const SPAWN_RADIUS: i32 = 8;
+const CHUNKS_PER_PLAYER: usize = 289;
+// Näher an einem Spieler spawnt nichts
+const MIN_PLAYER_DISTANCE: f64 = 24.0;
+// Weiter von allen Spielern weg verschwinden Monster, Fledermäuse und Wassertiere
+const DESPAWN_DISTANCE: f64 = 128.0;
+// Weitere Mobs derselben Art um den ersten herum
+const GROUP_SIZE: usize = 4;
+const GROUP_SPREAD: i32 = 4;
+const SEA_LEVEL: i32 = 63;
+
+const OVERWORLD_MONSTERS: &[&str] = &["minecraft:zombie", "minecraft:skeleton", "minecraft:creeper", "minecraft:spider"];
+const NETHER_MONSTERS: &[&str] = &["minecraft:zombified_piglin", "minecraft:magma_cube"];
+const END_MONSTERS: &[&str] = &["minecraft:enderman"];
+const CREATURES: &[&str] = &["minecraft:pig", "minecraft:cow", "minecraft:sheep", "minecraft:chicken"];
+const AMBIENT: &[&str] = &["minecraft:bat"];
+const WATER: &[&str] = &["minecraft:squid", "minecraft:cod", "minecraft:salmon"];
+// Zählen für die Obergrenze mit, auch wenn sie nicht natürlich spawnen
+const OTHER_WATER: &[&str] = &["axolotl", "dolphin", "glow_squid", "pufferfish", "tadpole", "tropical_fish"];
+// Bosse verschwinden nie
+const BOSSES: &[&str] = &["elder_guardian", "wither"];
+
+// Kategorien der natürlichen Spawns mit eigener Obergrenze und eigenem Takt aus server.properties
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpawnCategory {
+    Monster,
+    Creature,
+    Ambient,
+    Water,
+}
+
+impl SpawnCategory {
+    const ALL: [SpawnCategory; 4] = [SpawnCategory::Monster, SpawnCategory::Creature, SpawnCategory::Ambient, SpawnCategory::Water];
+
+    pub fn of(kind: &str) -> Option<SpawnCategory> {
+        let kind = path(kind);
+        let listed = |kinds: &[&str]| kinds.iter().any(|k| path(k) == kind);
+        if entity::is_hostile(kind) {
+            Some(SpawnCategory::Monster)
+        } else if worldsettings::ANIMALS.contains(&kind) {
+            Some(SpawnCategory::Creature)
+        } else if listed(AMBIENT) {
+            Some(SpawnCategory::Ambient)
+        } else if listed(WATER) || listed(OTHER_WATER) {
+            Some(SpawnCategory::Water)
+        } else {
+            None
+        }
+    }
+
+    fn cap(self, config: &ServerConfig) -> u32 {
+        match self {
+            SpawnCategory::Monster => config.mob_cap_monster,
+            SpawnCategory::Creature => config.mob_cap_creature,
+            SpawnCategory::Ambient => config.mob_cap_ambient,
+            SpawnCategory::Water => config.mob_cap_water,
+        }
+    }
+
+    fn interval(self, config: &ServerConfig) -> u32 {
+        match self {
+            SpawnCategory::Monster => config.spawn_interval_monster,
+            SpawnCategory::Creature => config.spawn_interval_creature,
+            SpawnCategory::Ambient => config.spawn_interval_ambient,
+            SpawnCategory::Water => config.spawn_interval_water,
+        }
+    }
+
+    // Was hier von selbst spawnt; außerhalb der Oberwelt bisher nur Monster
+    fn kinds(self, world: &World) -> &'static [&'static str] {
+        let effects = world.dimension.kind().effects;
+        match (self, effects) {
+            (SpawnCategory::Monster, "minecraft:the_nether") => NETHER_MONSTERS,
+            (SpawnCategory::Monster, "minecraft:the_end") => END_MONSTERS,
+            (SpawnCategory::Monster, _) => OVERWORLD_MONSTERS,
+            (_, "minecraft:the_nether" | "minecraft:the_end") => &[],
+            (SpawnCategory::Creature, _) => CREATURES,
+            (SpawnCategory::Ambient, _) => AMBIENT,
+            (SpawnCategory::Water, _) => WATER,
+        }
+    }
+}
+
+fn path(kind: &str) -> &str {
+    kind.strip_prefix("minecraft:").unwrap_or(kind)
+}
+
+// Tiere bleiben wie bei Vanilla für immer, ebenso Bosse
+fn despawns(kind: &str) -> bool {
+    SpawnCategory::of(kind).is_some_and(|c| c != SpawnCategory::Creature) && !BOSSES.contains(&path(kind))
+}
+
+// Wie weit die Tageszeit das Himmelslicht dämpft, von 0 am Mittag bis 11 um Mitternacht
+fn sky_darkening(world: &World) -> u8 {
+    let angle = world.time.time_of_day.rem_euclid(24000) as f64 / 24000.0 - 0.25;
+    let brightness = ((angle * TAU).cos() * 2.0 + 0.5).clamp(0.0, 1.0);
+    ((1.0 - brightness) * 11.0).round() as u8
+}
+
+fn active_players<'a>(players: &'a [Player], world: &'a World) -> impl Iterator<Item = &'a Player> + 'a {
+    players.iter().filter(move |p| p.dimension == world.dimension && p.game_mode != GameMode::Spectator)
+}
+
+fn chunk_of(position: (f64, f64, f64)) -> (i32, i32) {
+    ((position.0.floor() as i32) >> 4, (position.2.floor() as i32) >> 4)
+}
+
+// Jeden Tick: ferne Mobs verschwinden, dann spawnt jede fällige Kategorie in den geladenen Chunks um die Spieler, bis
+// ihre Obergrenze erreicht ist
+pub fn tick(config: &ServerConfig, players: &[Player], world: &mut World) {
+    despawn(players, world);
+    if !world.rules.get_bool(gamerule::DO_MOB_SPAWNING) {
+        return;
+    }
+    let age = world.time.age;
+    let due: Vec<SpawnCategory> = SpawnCategory::ALL
+        .into_iter()
+        .filter(|c| c.cap(config) > 0 && c.interval(config) > 0 && age % c.interval(config) as i64 == 0)
+        .filter(|c| !c.kinds(world).is_empty())
+        .collect();
+    if due.is_empty() {
+        return;
+    }
+    let mut area: HashSet<(i32, i32)> = HashSet::new();
+    for player in active_players(players, world) {
+        let (chunk_x, chunk_z) = chunk_of(player.position);
+        for dx in -SPAWN_RADIUS..=SPAWN_RADIUS {
+            for dz in -SPAWN_RADIUS..=SPAWN_RADIUS {
+                area.insert((chunk_x + dx, chunk_z + dz));
+            }
+        }
+    }
+    let area: Vec<(i32, i32)> = area.into_iter().filter(|coords| world.chunks.contains_key(coords)).collect();
+    if area.is_empty() {
+        return;
+    }
+    for category in due {
+        let cap = category.cap(config) as usize * area.len() / CHUNKS_PER_PLAYER;
+        let mut count = world.mobs.iter().filter(|m| SpawnCategory::of(&m.mob_type) == Some(category) && area.contains(&chunk_of(m.position))).count();
+        for &coords in &area {
+            if count >= cap {
+                break;
+            }
+            count += spawn_group(players, world, category, coords, cap - count);
+        }
+    }
+}
+
+// Ein Versuch in einem Chunk: eine zufällige Stelle, und passt sie, bis zu GROUP_SIZE Mobs derselben Art darum herum
+fn spawn_group(players: &[Player], world: &mut World, category: SpawnCategory, (chunk_x, chunk_z): (i32, i32), limit: usize) -> usize {
+    let mut rng = rand::thread_rng();
+    let kinds = category.kinds(world);
+    let kind = kinds[rng.gen_range(0..kinds.len())];
+    if (world.difficulty == Difficulty::Peaceful && entity::is_hostile(kind)) || !world.settings.allows_spawn(kind) {
+        return 0;
+    }
+    let (x, z) = (chunk_x * 16 + rng.gen_range(0..16), chunk_z * 16 + rng.gen_range(0..16));
+    let top = world.highest_block_y(x, z) + 1;
+    let y = match category {
+        SpawnCategory::Creature => top,
+        _ => rng.gen_range(world.dimension.min_y()..=top),
+    };
+    let mut spawned = 0;
+    for i in 0..GROUP_SIZE.min(limit) {
+        let position = if i == 0 {
+            (x, y, z)
+        } else {
+            (x + rng.gen_range(-GROUP_SPREAD..=GROUP_SPREAD), y, z + rng.gen_range(-GROUP_SPREAD..=GROUP_SPREAD))
+        };
+        if !can_spawn_at(players, world, category, position, &mut rng) {
+            if i == 0 {
+                return 0;
+            }
+            continue;
+        }
+        let target = (position.0 as f64 + 0.5, position.1 as f64, position.2 as f64 + 0.5);
+        let mut mob = Mob::new(kind, target);
+        mob.yaw = rng.gen_range(0.0..360.0);
+        for player in players.iter().filter(|p| entity::in_view(p, world.dimension, target)) {
+            let _ = entity::send_mob(&player.connection, &mob);
+        }
+        world.spawn_mob(mob);
+        spawned += 1;
+    }
+    if spawned > 0 {
+        trace!("{} {} gespawnt bei {:?} in {}", spawned, kind, (x, y, z), world.dimension.name());
+    }
+    spawned
+}
+
+fn can_spawn_at(players: &[Player], world: &World, category: SpawnCategory, (x, y, z): BlockPos, rng: &mut impl Rng) -> bool {
+    if !world.chunks.contains_key(&(x >> 4, z >> 4)) || y <= world.dimension.min_y() || y + 1 >= chunk::MIN_Y + chunk::HEIGHT {
+        return false;
+    }
+    let center = (x as f64 + 0.5, y as f64, z as f64 + 0.5);
+    let near = |p: &Player| (p.position.0 - center.0).powi(2) + (p.position.1 - center.1).powi(2) + (p.position.2 - center.2).powi(2) < MIN_PLAYER_DISTANCE * MIN_PLAYER_DISTANCE;
+    if players.iter().any(|p| p.dimension == world.dimension && near(p)) {
+        return false;
+    }
+    let Some((sky, block_light)) = world.light.level_at(world.dimension, (x, y, z)) else { return false };
+    let sky = sky.saturating_sub(sky_darkening(world));
+    let (feet, head, ground) = (world.get_block(x, y, z), world.get_block(x, y + 1, z), world.get_block(x, y - 1, z));
+    let free = !block::is_solid(feet) && !block::is_solid(head) && !block::is_fluid(feet) && !block::is_fluid(head);
+    match category {
+        // Nur ohne Blocklicht und im Dunkeln, je dunkler desto eher
+        SpawnCategory::Monster => free && block::is_solid(ground) && block_light == 0 && sky <= rng.gen_range(0..=7),
+        SpawnCategory::Creature => free && ground == block::GRASS_BLOCK && sky.max(block_light) > 8,
+        SpawnCategory::Ambient => free && y < SEA_LEVEL && sky.max(block_light) <= rng.gen_range(0..=3),
+        SpawnCategory::Water => block::is_water(feet) && block::is_water(head),
+    }
+}
+
+// Nur solange ein Spieler in der Welt ist, sonst bliebe nach dem Verlassen nichts übrig. Benannte Mobs und solche mit
+// Reiter bleiben
+fn despawn(players: &[Player], world: &mut World) {
+    let positions: Vec<(f64, f64, f64)> = active_players(players, world).map(|p| p.position).collect();
+    if positions.is_empty() {
+        return;
+    }
+    let far = |mob: &Mob| {
+        despawns(&mob.mob_type)
+            && mob.custom_name.is_none()
+            && mob.passengers.is_empty()
+            && positions.iter().all(|p| (p.0 - mob.position.0).powi(2) + (p.1 - mob.position.1).powi(2) + (p.2 - mob.position.2).powi(2) > DESPAWN_DISTANCE * DESPAWN_DISTANCE)
+    };
+    if !world.mobs.iter().any(far) {
+        return;
+    }
+    let (gone, kept): (Vec<Mob>, Vec<Mob>) = std::mem::take(&mut world.mobs).into_iter().partition(far);
+    world.mobs = kept;
+    for mob in gone {
+        world.entities.remove(mob.id);
+        for player in players.iter().filter(|p| entity::in_view(p, world.dimension, mob.position)) {
+            let _ = entity::send_remove(&player.connection, &[mob.entity_id]);
+        }
+    }
+}
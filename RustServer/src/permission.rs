@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+use crate::json::{self, Json};
+
+pub const PERMISSIONS_FILE: &str = "permissions.json";
+// Gruppe, in der jeder Spieler ist, auch ohne eigenen Eintrag
+pub const DEFAULT_GROUP: &str = "default";
+pub const BUILD: &str = "server.build";
+pub const CHAT: &str = "server.chat";
+// &-Farbcodes in eigenen Chatnachrichten
+pub const CHAT_COLOR: &str = "server.chat.color";
+// Beitreten, obwohl max-players erreicht ist
+pub const JOIN_FULL: &str = "server.join.full";
+// Meldungen über verdächtiges Verhalten anderer Spieler
+pub const ALERTS: &str = "server.alerts";
+// Inhaber dürfen alle eingeschränkten Befehle; wird bei Bedarf dieser Gruppe zugewiesen
+const OPERATOR_GROUP: &str = "operator";
+// Schützt vor Ketten wie a -> b -> a
+const MAX_INHERITANCE_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Group {
+    // Vor dem Namen im Chat, etwa "[Admin] "
+    pub prefix: String,
+    pub inherits: Vec<String>,
+    pub nodes: BTreeMap<String, bool>,
+}
+
+impl Group {
+    fn to_json(&self) -> Json {
+        let mut group = BTreeMap::new();
+        group.insert("prefix".to_string(), Json::String(self.prefix.clone()));
+        group.insert("inherits".to_string(), Json::Array(self.inherits.iter().cloned().map(Json::String).collect()));
+        group.insert("permissions".to_string(), nodes_json(&self.nodes));
+        Json::Object(group)
+    }
+
+    fn from_json(json: &Json) -> Group {
+        Group {
+            prefix: json.get("prefix").and_then(Json::as_str).unwrap_or_default().to_string(),
+            inherits: strings(json.get("inherits")),
+            nodes: parse_nodes(json.get("permissions")),
+        }
+    }
+}
+
+// Eigene Gruppen und Einzelrechte eines Spielers; Einzelrechte haben Vorrang vor allen Gruppen
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPermissions {
+    pub name: String,
+    pub uuid: Option<Uuid>,
+    pub groups: Vec<String>,
+    pub nodes: BTreeMap<String, bool>,
+}
+
+impl PlayerPermissions {
+    fn to_json(&self) -> Json {
+        let mut entry = BTreeMap::new();
+        entry.insert("name".to_string(), Json::String(self.name.clone()));
+        if let Some(uuid) = self.uuid {
+            entry.insert("uuid".to_string(), Json::String(uuid.to_string()));
+        }
+        entry.insert("groups".to_string(), Json::Array(self.groups.iter().cloned().map(Json::String).collect()));
+        entry.insert("permissions".to_string(), nodes_json(&self.nodes));
+        Json::Object(entry)
+    }
+
+    fn from_json(json: &Json) -> Option<PlayerPermissions> {
+        Some(PlayerPermissions {
+            name: json.get("name")?.as_str()?.to_string(),
+            uuid: json.get("uuid").and_then(Json::as_str).and_then(|u| Uuid::parse_str(u).ok()),
+            groups: strings(json.get("groups")),
+            nodes: parse_nodes(json.get("permissions")),
+        })
+    }
+}
+
+fn strings(json: Option<&Json>) -> Vec<String> {
+    json.and_then(Json::as_array).map(|items| items.iter().filter_map(Json::as_str).map(String::from).collect()).unwrap_or_default()
+}
+
+fn parse_nodes(json: Option<&Json>) -> BTreeMap<String, bool> {
+    let Some(nodes) = json.and_then(Json::as_object) else { return BTreeMap::new() };
+    nodes.iter().filter_map(|(node, value)| Some((node.to_lowercase(), value.as_bool()?))).collect()
+}
+
+fn nodes_json(nodes: &BTreeMap<String, bool>) -> Json {
+    Json::Object(nodes.iter().map(|(node, value)| (node.clone(), Json::Bool(*value))).collect())
+}
+
+// Der Knoten selbst und dann immer allgemeinere Platzhalter: a.b.c, a.b.*, a.*, *
+fn candidates(node: &str) -> Vec<String> {
+    let node = node.to_lowercase();
+    let mut candidates = vec![node.clone()];
+    let mut rest = node.as_str();
+    while let Some((parent, _)) = rest.rsplit_once('.') {
+        candidates.push(format!("{}.*", parent));
+        rest = parent;
+    }
+    candidates.push("*".to_string());
+    candidates
+}
+
+// Der speziellste Eintrag entscheidet, etwa server.command.tp: false vor server.command.*: true
+fn lookup(nodes: &BTreeMap<String, bool>, candidates: &[String]) -> Option<bool> {
+    candidates.iter().find_map(|candidate| nodes.get(candidate).copied())
+}
+
+fn default_groups() -> BTreeMap<String, Group> {
+    let mut groups = BTreeMap::new();
+    let nodes = [BUILD, CHAT].into_iter().map(|node| (node.to_string(), true)).collect();
+    groups.insert(DEFAULT_GROUP.to_string(), Group { prefix: String::new(), inherits: Vec::new(), nodes });
+    let nodes = [("*".to_string(), true)].into_iter().collect();
+    groups.insert(OPERATOR_GROUP.to_string(), Group { prefix: "[Op] ".to_string(), inherits: vec![DEFAULT_GROUP.to_string()], nodes });
+    groups
+}
+
+// Gruppen mit Vererbung und Einzelrechte pro Spieler aus permissions.json. Geprüft wird in dieser Reihenfolge:
+// Einzelrechte des Spielers, seine Gruppen samt geerbten in der eingetragenen Reihenfolge, zuletzt "default".
+// Wer zuerst einen passenden Eintrag hat, entscheidet; ohne Eintrag ist das Recht nicht vergeben
+#[derive(Debug, Default)]
+pub struct Permissions {
+    path: String,
+    groups: BTreeMap<String, Group>,
+    players: Vec<PlayerPermissions>,
+}
+
+impl Permissions {
+    // Fehlt die Datei, wird sie mit den Gruppen "default" und "operator" angelegt
+    pub fn load(path: &str) -> Permissions {
+        let mut permissions = Permissions { path: path.to_string(), groups: default_groups(), players: Vec::new() };
+        if !Path::new(path).exists() {
+            info!("{} nicht gefunden, erstelle Standardgruppen", path);
+            if let Err(e) = permissions.save() {
+                warn!("{}", e);
+            }
+            return permissions;
+        }
+        let json = match fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|text| json::parse(&text)) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("{} konnte nicht gelesen werden: {}", path, e);
+                return permissions;
+            }
+        };
+        if let Some(groups) = json.get("groups").and_then(Json::as_object) {
+            permissions.groups = groups.iter().map(|(name, group)| (name.to_lowercase(), Group::from_json(group))).collect();
+        }
+        permissions.groups.entry(DEFAULT_GROUP.to_string()).or_default();
+        permissions.players = json.get("players").and_then(Json::as_array).map(|players| players.iter().filter_map(PlayerPermissions::from_json).collect()).unwrap_or_default();
+        permissions
+    }
+
+    pub fn reload(&mut self) {
+        *self = Permissions::load(&self.path);
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let mut data = BTreeMap::new();
+        data.insert("groups".to_string(), Json::Object(self.groups.iter().map(|(name, group)| (name.clone(), group.to_json())).collect()));
+        data.insert("players".to_string(), Json::Array(self.players.iter().map(PlayerPermissions::to_json).collect()));
+        fs::write(&self.path, Json::Object(data).to_json_string()).map_err(|e| format!("Konnte {} nicht schreiben: {}", self.path, e))
+    }
+
+    // UUIDs sind im Offline-Modus zufällig, daher zählt auch der Name
+    pub fn player(&self, name: &str, uuid: Option<Uuid>) -> Option<&PlayerPermissions> {
+        self.players.iter().find(|p| (uuid.is_some() && p.uuid == uuid) || p.name.eq_ignore_ascii_case(name))
+    }
+
+    fn player_mut(&mut self, name: &str, uuid: Option<Uuid>) -> &mut PlayerPermissions {
+        let index = match self.players.iter().position(|p| (uuid.is_some() && p.uuid == uuid) || p.name.eq_ignore_ascii_case(name)) {
+            Some(index) => index,
+            None => {
+                self.players.push(PlayerPermissions { name: name.to_string(), uuid, groups: Vec::new(), nodes: BTreeMap::new() });
+                self.players.len() - 1
+            }
+        };
+        let entry = &mut self.players[index];
+        entry.name = name.to_string();
+        entry.uuid = uuid.or(entry.uuid);
+        entry
+    }
+
+    pub fn group_names(&self) -> Vec<String> {
+        self.groups.keys().cloned().collect()
+    }
+
+    // Alle Gruppen des Spielers samt geerbten, ohne Doppelte, in Prüfreihenfolge
+    pub fn groups_of(&self, name: &str, uuid: Option<Uuid>) -> Vec<String> {
+        let mut order = Vec::new();
+        let own = self.player(name, uuid).map(|p| p.groups.clone()).unwrap_or_default();
+        for group in own.iter().map(String::as_str).chain([DEFAULT_GROUP]) {
+            self.collect_groups(&group.to_lowercase(), 0, &mut order);
+        }
+        order
+    }
+
+    fn collect_groups(&self, name: &str, depth: usize, order: &mut Vec<String>) {
+        if depth > MAX_INHERITANCE_DEPTH || order.iter().any(|g| g == name) {
+            return;
+        }
+        let Some(group) = self.groups.get(name) else { return };
+        order.push(name.to_string());
+        for parent in &group.inherits {
+            self.collect_groups(&parent.to_lowercase(), depth + 1, order);
+        }
+    }
+
+    pub fn has(&self, name: &str, uuid: Option<Uuid>, node: &str) -> bool {
+        let candidates = candidates(node);
+        if let Some(value) = self.player(name, uuid).and_then(|p| lookup(&p.nodes, &candidates)) {
+            return value;
+        }
+        self.groups_of(name, uuid).iter().filter_map(|group| self.groups.get(group)).find_map(|group| lookup(&group.nodes, &candidates)).unwrap_or(false)
+    }
+
+    // Präfix der ersten Gruppe, die eines hat
+    pub fn prefix(&self, name: &str, uuid: Option<Uuid>) -> String {
+        self.groups_of(name, uuid).iter().filter_map(|group| self.groups.get(group)).map(|group| group.prefix.clone()).find(|prefix| !prefix.is_empty()).unwrap_or_default()
+    }
+
+    // None entfernt das Einzelrecht wieder
+    pub fn set_player_node(&mut self, name: &str, uuid: Option<Uuid>, node: &str, value: Option<bool>) -> Result<(), String> {
+        let entry = self.player_mut(name, uuid);
+        match value {
+            Some(value) => entry.nodes.insert(node.to_lowercase(), value),
+            None => entry.nodes.remove(&node.to_lowercase()),
+        };
+        self.save()
+    }
+
+    pub fn add_player_group(&mut self, name: &str, uuid: Option<Uuid>, group: &str) -> Result<bool, String> {
+        let group = self.existing_group(group)?;
+        let entry = self.player_mut(name, uuid);
+        if entry.groups.contains(&group) {
+            return Ok(false);
+        }
+        entry.groups.push(group);
+        self.save().map(|_| true)
+    }
+
+    pub fn remove_player_group(&mut self, name: &str, uuid: Option<Uuid>, group: &str) -> Result<bool, String> {
+        let group = group.to_lowercase();
+        let entry = self.player_mut(name, uuid);
+        let before = entry.groups.len();
+        entry.groups.retain(|g| *g != group);
+        if entry.groups.len() == before {
+            return Ok(false);
+        }
+        self.save().map(|_| true)
+    }
+
+    fn existing_group(&self, name: &str) -> Result<String, String> {
+        let name = name.to_lowercase();
+        if self.groups.contains_key(&name) { Ok(name) } else { Err(format!("Unknown group '{}'", name)) }
+    }
+
+    pub fn create_group(&mut self, name: &str) -> Result<bool, String> {
+        let name = name.to_lowercase();
+        if self.groups.contains_key(&name) {
+            return Ok(false);
+        }
+        self.groups.insert(name, Group::default());
+        self.save().map(|_| true)
+    }
+
+    // Die Gruppe verschwindet auch aus Spielern und Vererbungen
+    pub fn delete_group(&mut self, name: &str) -> Result<(), String> {
+        let name = self.existing_group(name)?;
+        if name == DEFAULT_GROUP {
+            return Err("The default group cannot be deleted".to_string());
+        }
+        self.groups.remove(&name);
+        for group in self.groups.values_mut() {
+            group.inherits.retain(|g| *g != name);
+        }
+        for player in &mut self.players {
+            player.groups.retain(|g| *g != name);
+        }
+        self.save()
+    }
+
+    pub fn set_group_node(&mut self, group: &str, node: &str, value: Option<bool>) -> Result<(), String> {
+        let group = self.existing_group(group)?;
+        let nodes = &mut self.groups.get_mut(&group).unwrap().nodes;
+        match value {
+            Some(value) => nodes.insert(node.to_lowercase(), value),
+            None => nodes.remove(&node.to_lowercase()),
+        };
+        self.save()
+    }
+
+    pub fn set_group_prefix(&mut self, group: &str, prefix: &str) -> Result<(), String> {
+        let group = self.existing_group(group)?;
+        self.groups.get_mut(&group).unwrap().prefix = prefix.to_string();
+        self.save()
+    }
+
+    // Gibt false zurück, wenn die Gruppe schon erbt
+    pub fn add_group_parent(&mut self, group: &str, parent: &str) -> Result<bool, String> {
+        let group = self.existing_group(group)?;
+        let parent = self.existing_group(parent)?;
+        let mut ancestors = Vec::new();
+        self.collect_groups(&parent, 0, &mut ancestors);
+        if ancestors.contains(&group) {
+            return Err(format!("Group '{}' already inherits from '{}'", parent, group));
+        }
+        let inherits = &mut self.groups.get_mut(&group).unwrap().inherits;
+        if inherits.contains(&parent) {
+            return Ok(false);
+        }
+        inherits.push(parent);
+        self.save().map(|_| true)
+    }
+
+    pub fn remove_group_parent(&mut self, group: &str, parent: &str) -> Result<bool, String> {
+        let group = self.existing_group(group)?;
+        let parent = parent.to_lowercase();
+        let inherits = &mut self.groups.get_mut(&group).unwrap().inherits;
+        let before = inherits.len();
+        inherits.retain(|g| *g != parent);
+        if inherits.len() == before {
+            return Ok(false);
+        }
+        self.save().map(|_| true)
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::HashSet;
+use crate::block::PushReaction;
+use crate::protocol::clientbound;
+use crate::protocol::types::{encode_position, write_varint_to_vec};
+use crate::{block, chunk, entity, gamerule, loot, redstone, worldevent, Player, Server, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Mehr Blöcke schiebt oder zieht ein Kolben nicht
+const PUSH_LIMIT: usize = 12;
+// So lange sind bewegte Blöcke als moving_piston unterwegs, wie bei Vanilla
+const MOVE_TICKS: i64 = 2;
+// Block Action: der Client spielt die Bewegung selbst ab
+const ACTION_EXTEND: u8 = 0;
+const ACTION_RETRACT: u8 = 1;
+const SOUND_VOLUME: f32 = 0.5;
+const DIRECTIONS: [&str; 6] = ["down", "up", "north", "south", "west", "east"];
+
+// Ein Block unterwegs: was am Ziel ankommt und wann
+#[derive(Debug, Clone, Copy)]
+pub struct Moving {
+    state: u16,
+    arrives: i64,
+}
+
+fn offset(direction: &str) -> BlockPos {
+    match direction {
+        "down" => (0, -1, 0),
+        "up" => (0, 1, 0),
+        "north" => (0, 0, -1),
+        "south" => (0, 0, 1),
+        "west" => (-1, 0, 0),
+        _ => (1, 0, 0),
+    }
+}
+
+fn step((x, y, z): BlockPos, (dx, dy, dz): BlockPos, distance: i32) -> BlockPos {
+    (x + dx * distance, y + dy * distance, z + dz * distance)
+}
+
+fn get(world: &World, (x, y, z): BlockPos) -> u16 {
+    world.get_block(x, y, z)
+}
+
+fn is_sticky(state: u16) -> bool {
+    matches!(block::name(state), "minecraft:slime_block" | "minecraft:honey_block")
+}
+
+// Schleim und Honig kleben aneinander nicht fest
+fn sticks(a: u16, b: u16) -> bool {
+    let pair = (block::name(a), block::name(b));
+    if pair == ("minecraft:slime_block", "minecraft:honey_block") || pair == ("minecraft:honey_block", "minecraft:slime_block") {
+        return false;
+    }
+    is_sticky(a) || is_sticky(b)
+}
+
+fn pushable(world: &World, state: u16, position: BlockPos, movement: BlockPos, allow_destroy: bool) -> bool {
+    if state == block::AIR {
+        return true;
+    }
+    if movement.1 < 0 && position.1 == chunk::MIN_Y || movement.1 > 0 && position.1 == chunk::MIN_Y + chunk::HEIGHT - 1 {
+        return false;
+    }
+    match block::push_reaction(state) {
+        PushReaction::Block => false,
+        PushReaction::Destroy => allow_destroy,
+        PushReaction::Normal => world.block_entity(position).is_none(),
+    }
+}
+
+// Wie PistonStructureResolver bei Vanilla: die Blöcke, die mitgehen, und die, die dabei kaputt gehen. Schleim- und
+// Honigblöcke nehmen ihre Nachbarn mit
+struct Structure<'a> {
+    world: &'a World,
+    piston: BlockPos,
+    movement: BlockPos,
+    push: Vec<BlockPos>,
+    destroy: Vec<BlockPos>,
+}
+
+impl Structure<'_> {
+    fn resolve(world: &World, piston: BlockPos, facing: &str, extending: bool) -> Option<(Vec<BlockPos>, Vec<BlockPos>)> {
+        let (dx, dy, dz) = offset(facing);
+        // Beim Einfahren zieht ein klebriger Kolben den Block vor seinem Kopf heran
+        let (movement, start) = if extending {
+            ((dx, dy, dz), step(piston, (dx, dy, dz), 1))
+        } else {
+            ((-dx, -dy, -dz), step(piston, (dx, dy, dz), 2))
+        };
+        let mut structure = Structure { world, piston, movement, push: Vec::new(), destroy: Vec::new() };
+        let state = get(world, start);
+        if !pushable(world, state, start, movement, false) {
+            if extending && block::push_reaction(state) == PushReaction::Destroy {
+                return Some((Vec::new(), vec![start]));
+            }
+            return None;
+        }
+        if !structure.line(start) {
+            return None;
+        }
+        let mut i = 0;
+        while i < structure.push.len() {
+            let position = structure.push[i];
+            if is_sticky(get(world, position)) && !structure.branches(position) {
+                return None;
+            }
+            i += 1;
+        }
+        Some((structure.push, structure.destroy))
+    }
+
+    // Ein Block mit allem, was an ihm klebt, und allem, was er vor sich herschiebt
+    fn line(&mut self, origin: BlockPos) -> bool {
+        let world = self.world;
+        let mut state = get(world, origin);
+        if state == block::AIR || !pushable(world, state, origin, self.movement, false) || origin == self.piston || self.push.contains(&origin) {
+            return true;
+        }
+        let back = (-self.movement.0, -self.movement.1, -self.movement.2);
+        let mut count = 1;
+        if count + self.push.len() > PUSH_LIMIT {
+            return false;
+        }
+        while is_sticky(state) {
+            let position = step(origin, back, count as i32);
+            let previous = state;
+            state = get(world, position);
+            if state == block::AIR || !sticks(previous, state) || !pushable(world, state, position, self.movement, false) || position == self.piston {
+                break;
+            }
+            count += 1;
+            if count + self.push.len() > PUSH_LIMIT {
+                return false;
+            }
+        }
+        for i in (0..count).rev() {
+            self.push.push(step(origin, back, i as i32));
+        }
+        for distance in 1.. {
+            let position = step(origin, self.movement, distance);
+            // Trifft auf eine schon erfasste Reihe; was daran klebt, prüft resolve
+            if self.push.contains(&position) {
+                return true;
+            }
+            let state = get(world, position);
+            if state == block::AIR {
+                return true;
+            }
+            if !pushable(world, state, position, self.movement, true) || position == self.piston {
+                return false;
+            }
+            if block::push_reaction(state) == PushReaction::Destroy {
+                self.destroy.push(position);
+                return true;
+            }
+            if self.push.len() >= PUSH_LIMIT {
+                return false;
+            }
+            self.push.push(position);
+        }
+        true
+    }
+
+    // Was seitlich an einem Schleim- oder Honigblock klebt
+    fn branches(&mut self, from: BlockPos) -> bool {
+        let state = get(self.world, from);
+        for direction in DIRECTIONS {
+            let side = offset(direction);
+            if side.0 * self.movement.0 + side.1 * self.movement.1 + side.2 * self.movement.2 != 0 {
+                continue;
+            }
+            let neighbor = step(from, side, 1);
+            if sticks(get(self.world, neighbor), state) && !self.line(neighbor) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn piston_type(state: u16) -> &'static str {
+    if block::name(state) == "minecraft:sticky_piston" { "sticky" } else { "normal" }
+}
+
+fn moving_piston(facing: &str, kind: &str) -> u16 {
+    let state = block::with_property(block::MOVING_PISTON, "facing", facing).unwrap_or(block::MOVING_PISTON);
+    block::with_property(state, "type", kind).unwrap_or(state)
+}
+
+// Wie getNeighborSignal bei Vanilla: Strom von jeder Seite außer vorne, dazu von den Nachbarn des Blocks darüber
+fn is_powered(world: &World, (x, y, z): BlockPos, facing: &str) -> bool {
+    let above = (x, y + 1, z);
+    DIRECTIONS.iter().filter(|&&d| d != facing).any(|&d| redstone::power_into(world, step((x, y, z), offset(d), 1)) > 0)
+        || DIRECTIONS.iter().filter(|&&d| d != "down").any(|&d| redstone::power_into(world, step(above, offset(d), 1)) > 0)
+}
+
+fn send_action(players: &[Player], world: &World, (x, y, z): BlockPos, state: u16, action: u8, facing: &str) {
+    let center = (x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5);
+    let direction = DIRECTIONS.iter().position(|&d| d == facing).unwrap_or(0) as u8;
+    let sound = if action == ACTION_EXTEND { "minecraft:block.piston.extend" } else { "minecraft:block.piston.contract" };
+    for player in players.iter().filter(|p| entity::in_view(p, world.dimension, center)) {
+        let Some(block_id) = block::registry_id_in(block::name(state), player.connection.version) else { continue };
+        let mut packet_data = encode_position(x, y, z).to_be_bytes().to_vec();
+        packet_data.extend([action, direction]);
+        packet_data.extend(write_varint_to_vec(block_id));
+        let _ = player.connection.send(clientbound::BLOCK_ACTION, &packet_data);
+        let _ = worldevent::send_sound(&player.connection, sound, center, SOUND_VOLUME);
+    }
+}
+
+fn start_moving(world: &mut World, position: BlockPos, moving: u16, state: u16) {
+    world.set_block_and_notify(position, moving);
+    let arrives = world.time.age + MOVE_TICKS;
+    world.moving_blocks.insert(position, Moving { state, arrives });
+}
+
+// Wie moveBlocks bei Vanilla: zerstört, was im Weg ist, und setzt alles Bewegte als moving_piston an sein Ziel
+fn move_blocks(server: &Server, players: &[Player], world: &mut World, piston: BlockPos, facing: &str, extending: bool) -> bool {
+    let (dx, dy, dz) = offset(facing);
+    let head = step(piston, (dx, dy, dz), 1);
+    if !extending && block::name(get(world, head)) == "minecraft:piston_head" {
+        world.set_block_and_notify(head, block::AIR);
+    }
+    let Some((push, destroy)) = Structure::resolve(world, piston, facing, extending) else { return false };
+    let movement = if extending { (dx, dy, dz) } else { (-dx, -dy, -dz) };
+    let tile_drops = world.rules.get_bool(gamerule::DO_TILE_DROPS);
+    for &position in destroy.iter().rev() {
+        let state = get(world, position);
+        if world.set_block_and_notify(position, block::AIR) && tile_drops {
+            let center = (position.0 as f64 + 0.5, position.1 as f64 + 0.5, position.2 as f64 + 0.5);
+            for stack in loot::block_drops(server, state) {
+                entity::spawn_item(players, world, stack, center);
+            }
+        }
+    }
+    let states: Vec<u16> = push.iter().map(|&position| get(world, position)).collect();
+    let mut vacated: HashSet<BlockPos> = push.iter().copied().collect();
+    for (&position, &state) in push.iter().zip(&states).rev() {
+        let target = step(position, movement, 1);
+        vacated.remove(&target);
+        start_moving(world, target, moving_piston(facing, "normal"), state);
+    }
+    if extending {
+        let kind = piston_type(get(world, piston));
+        let head_state = block::with_property(block::PISTON_HEAD, "facing", facing).and_then(|s| block::with_property(s, "type", kind));
+        let head_state = head_state.unwrap_or(block::PISTON_HEAD);
+        vacated.remove(&head);
+        start_moving(world, head, moving_piston(facing, kind), head_state);
+    }
+    for position in vacated {
+        world.set_block_and_notify(position, block::AIR);
+    }
+    true
+}
+
+// Geplanter Tick nach einer Änderung in der Nachbarschaft: mit Strom fährt der Kolben aus, ohne wieder ein. Solange
+// sein Kopf noch unterwegs ist, wartet er; nach der Ankunft sieht er noch einmal nach
+pub fn scheduled_tick(server: &Server, players: &[Player], world: &mut World, position: BlockPos, state: u16) {
+    let Some(facing) = block::property(state, "facing") else { return };
+    let extended = block::property(state, "extended") == Some("true");
+    let powered = is_powered(world, position, facing);
+    if powered && !extended {
+        if Structure::resolve(world, position, facing, true).is_none() {
+            return;
+        }
+        send_action(players, world, position, state, ACTION_EXTEND, facing);
+        if move_blocks(server, players, world, position, facing, true) {
+            world.set_block_and_notify(position, block::with_property(state, "extended", "true").unwrap_or(state));
+        }
+    } else if !powered && extended {
+        let head = step(position, offset(facing), 1);
+        if get(world, head) == moving_piston(facing, piston_type(state)) {
+            return;
+        }
+        send_action(players, world, position, state, ACTION_RETRACT, facing);
+        let retracted = block::with_property(state, "extended", "false").unwrap_or(state);
+        start_moving(world, position, moving_piston(facing, piston_type(state)), retracted);
+        let (dx, dy, dz) = offset(facing);
+        let target = step(position, (dx, dy, dz), 2);
+        let pulled = get(world, target);
+        let pulls = piston_type(state) == "sticky"
+            && pulled != block::AIR
+            && pushable(world, pulled, target, (-dx, -dy, -dz), false)
+            && (block::push_reaction(pulled) == PushReaction::Normal || block::is_piston(pulled));
+        if !pulls || !move_blocks(server, players, world, position, facing, false) {
+            world.set_block_and_notify(head, block::AIR);
+        }
+    }
+}
+
+// Setzt angekommene Blöcke an ihr Ziel. Ein Kolben prüft danach seinen Strom, denn der kann sich unterwegs geändert
+// haben
+pub fn tick(world: &mut World) {
+    let now = world.time.age;
+    let arrived: Vec<(BlockPos, Moving)> = world.moving_blocks.iter().filter(|(_, m)| m.arrives <= now).map(|(&p, &m)| (p, m)).collect();
+    for (position, moving) in arrived {
+        world.moving_blocks.remove(&position);
+        if block::name(get(world, position)) != "minecraft:moving_piston" {
+            continue;
+        }
+        world.set_block_and_notify(position, moving.state);
+        if block::is_piston(moving.state) {
+            world.schedule_block_tick(position, 1);
+        } else if let Some(facing) = block::property(moving.state, "facing").filter(|_| block::name(moving.state) == "minecraft:piston_head") {
+            let (dx, dy, dz) = offset(facing);
+            world.schedule_block_tick(step(position, (-dx, -dy, -dz), 1), 1);
+        }
+    }
+}
+
+// Ein ausgefahrener Kolben verliert mit dem Sockel seinen Kopf; geht der Kopf verloren, gilt er wieder als eingefahren
+pub fn block_changed(world: &mut World, position: BlockPos, old: u16, new: u16) {
+    if block::name(old) == block::name(new) {
+        return;
+    }
+    let Some(facing) = block::property(old, "facing") else { return };
+    let (dx, dy, dz) = offset(facing);
+    if block::is_piston(old) && block::property(old, "extended") == Some("true") {
+        let head = step(position, (dx, dy, dz), 1);
+        let state = get(world, head);
+        if block::name(state) == "minecraft:piston_head" && block::property(state, "facing") == Some(facing) {
+            world.set_block_and_notify(head, block::AIR);
+        }
+    } else if block::name(old) == "minecraft:piston_head" {
+        let base = step(position, (-dx, -dy, -dz), 1);
+        let state = get(world, base);
+        if block::is_piston(state) && block::property(state, "facing") == Some(facing) {
+            world.set_block_and_notify(base, block::with_property(state, "extended", "false").unwrap_or(state));
+        }
+    }
+}
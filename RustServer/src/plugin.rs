@@ -0,0 +1,499 @@
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use crate::dimension::{self, Dimension};
+use crate::event::{Event, EventKind, EventPriority, ListenerId};
+use crate::{block, channel, portal, send_system_message, Server};
+
+pub const PLUGIN_DIR: &str = "plugins";
+// Muss bei jeder inkompatiblen Änderung an HostApi erhöht werden
+pub const PLUGIN_API_VERSION: u32 = 6;
+// Versionen 3 bis 6 haben nur Funktionen angehängt, ältere Plugins laufen unverändert
+const MIN_PLUGIN_API_VERSION: u32 = 2;
+
+const SYMBOL_API_VERSION: &[u8] = b"rustmc_plugin_api_version\0";
+const SYMBOL_ENABLE: &[u8] = b"rustmc_plugin_enable\0";
+const SYMBOL_DISABLE: &[u8] = b"rustmc_plugin_disable\0";
+
+// Rückgabe 0 = Erfolg, sonst wird die Verwendung des Befehls angezeigt
+pub type CommandCallback = extern "C" fn(user: *mut c_void, sender: *const c_char, args: *const c_char) -> i32;
+pub type EventCallback = extern "C" fn(user: *mut c_void, event: *mut PluginEvent);
+// data ist nur während des Aufrufs gültig und nicht NUL-terminiert
+pub type ChannelCallback = extern "C" fn(user: *mut c_void, player: *const c_char, data: *const u8, length: u32);
+
+type ApiVersionFn = extern "C" fn() -> u32;
+type EnableFn = extern "C" fn(api: *const HostApi) -> i32;
+type DisableFn = extern "C" fn();
+
+// Event, wie es ein Plugin-Listener sieht. Nur `cancelled` darf direkt verändert werden, die Nachricht über
+// set_event_message. Nicht belegte Felder sind NULL bzw. 0 (message nur bei Chat und ServerListPing, Block nur bei BlockBreak)
+#[repr(C)]
+pub struct PluginEvent {
+    pub kind: u32,
+    pub player: *const c_char,
+    pub message: *const c_char,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub state: u32,
+    pub cancelled: i32,
+    // Gehört dem Host: Ziel für set_event_message
+    pub reserved: *mut c_void,
+}
+
+// Funktionstabelle, die jedes Plugin beim Aktivieren erhält. Alle Funktionen erwarten `host` als erstes Argument
+#[repr(C)]
+pub struct HostApi {
+    pub api_version: u32,
+    pub host: *mut c_void,
+    pub log: extern "C" fn(host: *mut c_void, message: *const c_char),
+    pub register_command: extern "C" fn(host: *mut c_void, name: *const c_char, usage: *const c_char, callback: CommandCallback, user: *mut c_void) -> i32,
+    pub register_listener: extern "C" fn(host: *mut c_void, event: u32, priority: i32, callback: EventCallback, user: *mut c_void) -> i32,
+    pub send_message: extern "C" fn(host: *mut c_void, player: *const c_char, message: *const c_char) -> i32,
+    pub broadcast: extern "C" fn(host: *mut c_void, message: *const c_char),
+    pub kick_player: extern "C" fn(host: *mut c_void, player: *const c_char) -> i32,
+    pub player_position: extern "C" fn(host: *mut c_void, player: *const c_char, out: *mut f64) -> i32,
+    pub get_block: extern "C" fn(host: *mut c_void, x: i32, y: i32, z: i32) -> i32,
+    pub set_block: extern "C" fn(host: *mut c_void, x: i32, y: i32, z: i32, state: u32) -> i32,
+    pub set_event_message: extern "C" fn(host: *mut c_void, event: *mut PluginEvent, message: *const c_char) -> i32,
+    pub teleport: extern "C" fn(host: *mut c_void, player: *const c_char, world: *const c_char, x: f64, y: f64, z: f64) -> i32,
+    pub link_portal: extern "C" fn(host: *mut c_void, name: *const c_char, world: *const c_char, region: *const i32, destination: *const c_char, position: *const f64) -> i32,
+    pub unlink_portal: extern "C" fn(host: *mut c_void, name: *const c_char) -> i32,
+    pub player_brand: extern "C" fn(host: *mut c_void, player: *const c_char, out: *mut c_char, size: u32) -> i32,
+    pub register_channel: extern "C" fn(host: *mut c_void, channel: *const c_char, callback: ChannelCallback, user: *mut c_void) -> i32,
+    pub send_plugin_message: extern "C" fn(host: *mut c_void, player: *const c_char, channel: *const c_char, data: *const u8, length: u32) -> i32,
+    pub player_ping: extern "C" fn(host: *mut c_void, player: *const c_char) -> i32,
+}
+
+// Zustand hinter dem `host`-Zeiger eines Plugins
+struct PluginHost {
+    server: *const Server,
+    name: String,
+}
+
+// Zeiger des Plugins, der unverändert an seine Callbacks zurückgegeben wird
+#[derive(Clone, Copy)]
+struct UserData(*mut c_void);
+
+// Plugins müssen ihre Callbacks threadsicher implementieren (Events kommen aus den Client-Threads)
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+impl UserData {
+    // Methode statt Feldzugriff, damit Closures den ganzen (Send-)Wrapper einfangen
+    fn get(self) -> *mut c_void {
+        self.0
+    }
+}
+
+struct LoadedPlugin {
+    name: String,
+    library: *mut c_void,
+    disable: Option<DisableFn>,
+    commands: Vec<String>,
+    listeners: Vec<ListenerId>,
+    channels: Vec<String>,
+    _host: Box<PluginHost>,
+    _api: Box<HostApi>,
+}
+
+unsafe impl Send for LoadedPlugin {}
+
+#[derive(Default)]
+pub struct PluginManager {
+    plugins: Mutex<Vec<LoadedPlugin>>,
+}
+
+fn dl_error() -> String {
+    let error = unsafe { libc::dlerror() };
+    if error.is_null() {
+        "unbekannter Fehler".to_string()
+    } else {
+        unsafe { CStr::from_ptr(error) }.to_string_lossy().into_owned()
+    }
+}
+
+fn c_str(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned())
+}
+
+fn host<'a>(ptr: *mut c_void) -> &'a PluginHost {
+    unsafe { &*(ptr as *const PluginHost) }
+}
+
+fn server<'a>(ptr: *mut c_void) -> &'a Server {
+    unsafe { &*host(ptr).server }
+}
+
+// Das Plugin ist schon vor enable() eingetragen, damit Registrierungen zugeordnet werden können
+fn with_plugin<R>(ptr: *mut c_void, f: impl FnOnce(&mut LoadedPlugin) -> R) -> Option<R> {
+    let name = &host(ptr).name;
+    let mut plugins = server(ptr).plugins.plugins.lock().unwrap();
+    plugins.iter_mut().find(|p| &p.name == name).map(f)
+}
+
+extern "C" fn host_log(ptr: *mut c_void, message: *const c_char) {
+    if let Some(message) = c_str(message) {
+        info!("[{}] {}", host(ptr).name, message);
+    }
+}
+
+extern "C" fn host_register_command(ptr: *mut c_void, name: *const c_char, usage: *const c_char, callback: CommandCallback, user: *mut c_void) -> i32 {
+    let Some(name) = c_str(name) else { return -1 };
+    let usage = c_str(usage).unwrap_or_else(|| format!("/{}", name));
+    let user = UserData(user);
+    server(ptr).commands.lock().unwrap().register(&name, &usage, move |ctx, args| {
+        let sender = ctx.sender.name(ctx.server);
+        let sender = CString::new(sender).unwrap_or_default();
+        let args = CString::new(args.join(" ")).unwrap_or_default();
+        match callback(user.get(), sender.as_ptr(), args.as_ptr()) {
+            0 => Ok(()),
+            _ => Err("Command failed".to_string()),
+        }
+    });
+    with_plugin(ptr, |plugin| plugin.commands.push(name));
+    0
+}
+
+// Priorität 0 (lowest) bis 5 (monitor)
+extern "C" fn host_register_listener(ptr: *mut c_void, event: u32, priority: i32, callback: EventCallback, user: *mut c_void) -> i32 {
+    let (Some(kind), Some(priority)) = (EventKind::from_id(event), EventPriority::from_index(priority)) else { return -1 };
+    let user = UserData(user);
+    let id = server(ptr).events.listen_kind(kind, priority, move |_server, event| call_listener(callback, user, event));
+    match with_plugin(ptr, |plugin| plugin.listeners.push(id)) {
+        Some(()) => 0,
+        None => {
+            server(ptr).events.unlisten(id);
+            -1
+        }
+    }
+}
+
+fn call_listener(callback: EventCallback, user: UserData, event: &mut dyn Event) {
+    let player = CString::new(event.player()).unwrap_or_default();
+    let message = event.message().map(|m| CString::new(m).unwrap_or_default());
+    let (x, y, z, state) = event.block().unwrap_or_default();
+    let mut new_message: Option<String> = None;
+    let mut plugin_event = PluginEvent {
+        kind: event.kind().id(),
+        player: player.as_ptr(),
+        message: message.as_ref().map_or(std::ptr::null(), |m| m.as_ptr()),
+        x,
+        y,
+        z,
+        state: state as u32,
+        cancelled: event.is_cancelled() as i32,
+        reserved: &mut new_message as *mut Option<String> as *mut c_void,
+    };
+    callback(user.get(), &mut plugin_event);
+    event.set_cancelled(plugin_event.cancelled != 0);
+    if let Some(message) = new_message {
+        event.set_message(message);
+    }
+}
+
+// Nur während des Listener-Aufrufs gültig; -1 bei Events ohne Nachricht
+extern "C" fn host_set_event_message(_ptr: *mut c_void, event: *mut PluginEvent, message: *const c_char) -> i32 {
+    let Some(message) = c_str(message) else { return -1 };
+    let event = unsafe { event.as_mut() };
+    let Some(event) = event.filter(|e| !e.message.is_null() && !e.reserved.is_null()) else { return -1 };
+    unsafe { *(event.reserved as *mut Option<String>) = Some(message) };
+    0
+}
+
+extern "C" fn host_send_message(ptr: *mut c_void, player: *const c_char, message: *const c_char) -> i32 {
+    let (Some(player), Some(message)) = (c_str(player), c_str(message)) else { return -1 };
+    let players = server(ptr).players.lock().unwrap();
+    match players.iter().find(|p| p.username == player) {
+        Some(p) if send_system_message(&p.connection, &message).is_ok() => 0,
+        _ => -1,
+    }
+}
+
+extern "C" fn host_broadcast(ptr: *mut c_void, message: *const c_char) {
+    let Some(message) = c_str(message) else { return };
+    for player in server(ptr).players.lock().unwrap().iter() {
+        let _ = send_system_message(&player.connection, &message);
+    }
+}
+
+extern "C" fn host_kick_player(ptr: *mut c_void, player: *const c_char) -> i32 {
+    let Some(player) = c_str(player) else { return -1 };
+    let players = server(ptr).players.lock().unwrap();
+    match players.iter().find(|p| p.username == player) {
+        Some(p) => {
+            info!("[{}] Trenne {}", host(ptr).name, p.username);
+            p.connection.close();
+            0
+        }
+        None => -1,
+    }
+}
+
+// Schreibt x, y, z nach `out` (drei f64)
+extern "C" fn host_player_position(ptr: *mut c_void, player: *const c_char, out: *mut f64) -> i32 {
+    let Some(player) = c_str(player) else { return -1 };
+    if out.is_null() {
+        return -1;
+    }
+    let players = server(ptr).players.lock().unwrap();
+    match players.iter().find(|p| p.username == player) {
+        Some(p) => {
+            let (x, y, z) = p.position;
+            unsafe { std::slice::from_raw_parts_mut(out, 3).copy_from_slice(&[x, y, z]) };
+            0
+        }
+        None => -1,
+    }
+}
+
+extern "C" fn host_get_block(ptr: *mut c_void, x: i32, y: i32, z: i32) -> i32 {
+    server(ptr).world.lock().unwrap().get_block(x, y, z) as i32
+}
+
+extern "C" fn host_set_block(ptr: *mut c_void, x: i32, y: i32, z: i32, state: u32) -> i32 {
+    if state >= 1 << block::GLOBAL_BITS {
+        return -1;
+    }
+    let server = server(ptr);
+    if !server.world.lock().unwrap().set_block_and_notify((x, y, z), state as u16) {
+        return -1;
+    }
+    0
+}
+
+// Auch in eine andere Welt; "world" ist die Oberwelt
+extern "C" fn host_teleport(ptr: *mut c_void, player: *const c_char, world: *const c_char, x: f64, y: f64, z: f64) -> i32 {
+    let (Some(player), Some(world)) = (c_str(player), c_str(world)) else { return -1 };
+    let server = server(ptr);
+    let Some(uuid) = server.players.lock().unwrap().iter().find(|p| p.username == player).map(|p| p.uuid) else { return -1 };
+    let result = Dimension::by_world_name(&world).and_then(|d| dimension::teleport(server, uuid, d, (x, y, z), None));
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            warn!("[{}] Teleport von {} fehlgeschlagen: {}", host(ptr).name, player, e);
+            -1
+        }
+    }
+}
+
+// region zeigt auf zwei Ecken (sechs i32), position auf x, y, z (drei f64); ersetzt ein gleichnamiges Portal
+extern "C" fn host_link_portal(ptr: *mut c_void, name: *const c_char, world: *const c_char, region: *const i32, destination: *const c_char, position: *const f64) -> i32 {
+    let (Some(name), Some(world), Some(destination)) = (c_str(name), c_str(world), c_str(destination)) else { return -1 };
+    if region.is_null() || position.is_null() {
+        return -1;
+    }
+    let corners = unsafe { std::slice::from_raw_parts(region, 6) };
+    let (a, b) = ((corners[0], corners[1], corners[2]), (corners[3], corners[4], corners[5]));
+    let region = ((a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)), (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)));
+    let position = unsafe { std::slice::from_raw_parts(position, 3) };
+    match portal::new_portal(&name, &world, region, &destination, (position[0], position[1], position[2]), None) {
+        Ok(portal) => {
+            server(ptr).portals.lock().unwrap().link(portal);
+            0
+        }
+        Err(e) => {
+            warn!("[{}] Portal {} abgelehnt: {}", host(ptr).name, name, e);
+            -1
+        }
+    }
+}
+
+extern "C" fn host_unlink_portal(ptr: *mut c_void, name: *const c_char) -> i32 {
+    let Some(name) = c_str(name) else { return -1 };
+    if server(ptr).portals.lock().unwrap().unlink(&name) { 0 } else { -1 }
+}
+
+// Schreibt die Brand NUL-terminiert nach `out` und kürzt sie wie snprintf auf size - 1 Bytes. Liefert die volle
+// Länge ohne NUL, -1 wenn der Spieler nicht online ist oder noch keine Brand gemeldet hat
+extern "C" fn host_player_brand(ptr: *mut c_void, player: *const c_char, out: *mut c_char, size: u32) -> i32 {
+    let Some(player) = c_str(player) else { return -1 };
+    let players = server(ptr).players.lock().unwrap();
+    let Some(brand) = players.iter().find(|p| p.username == player).and_then(|p| p.brand.clone()) else { return -1 };
+    drop(players);
+    if !out.is_null() && size > 0 {
+        let length = brand.len().min(size as usize - 1);
+        let out = unsafe { std::slice::from_raw_parts_mut(out as *mut u8, length + 1) };
+        out[..length].copy_from_slice(&brand.as_bytes()[..length]);
+        out[length] = 0;
+    }
+    brand.len().min(i32::MAX as usize) as i32
+}
+
+// Geglättete Antwortzeit in Millisekunden, 0 bis zur ersten Keep-Alive-Antwort, -1 wenn der Spieler nicht online ist
+extern "C" fn host_player_ping(ptr: *mut c_void, player: *const c_char) -> i32 {
+    let Some(player) = c_str(player) else { return -1 };
+    let players = server(ptr).players.lock().unwrap();
+    players.iter().find(|p| p.username == player).map_or(-1, |p| p.ping().min(i32::MAX as u32) as i32)
+}
+
+extern "C" fn host_register_channel(ptr: *mut c_void, channel: *const c_char, callback: ChannelCallback, user: *mut c_void) -> i32 {
+    let Some(channel) = c_str(channel) else { return -1 };
+    let user = UserData(user);
+    let result = server(ptr).channels.register(&channel, move |_server, player, data| {
+        let player = CString::new(player.username.as_str()).unwrap_or_default();
+        callback(user.get(), player.as_ptr(), data.as_ptr(), data.len() as u32);
+    });
+    if let Err(e) = result {
+        warn!("[{}] {}", host(ptr).name, e);
+        return -1;
+    }
+    if with_plugin(ptr, |plugin| plugin.channels.push(channel.clone())).is_none() {
+        server(ptr).channels.unregister(&channel);
+        return -1;
+    }
+    channel::announce(server(ptr), &channel, true);
+    0
+}
+
+// Auch auf Kanälen, die der Client nicht angemeldet hat; Vanilla-Clients verwerfen solche Nachrichten
+extern "C" fn host_send_plugin_message(ptr: *mut c_void, player: *const c_char, channel: *const c_char, data: *const u8, length: u32) -> i32 {
+    let (Some(player), Some(channel)) = (c_str(player), c_str(channel)) else { return -1 };
+    if !channel::is_valid_channel(&channel) || (data.is_null() && length > 0) {
+        return -1;
+    }
+    let data = if length == 0 { &[][..] } else { unsafe { std::slice::from_raw_parts(data, length as usize) } };
+    let players = server(ptr).players.lock().unwrap();
+    match players.iter().find(|p| p.username == player) {
+        Some(p) if channel::send_plugin_message(&p.connection, &channel, data).is_ok() => 0,
+        _ => -1,
+    }
+}
+
+impl PluginManager {
+    pub fn new() -> PluginManager {
+        PluginManager::default()
+    }
+
+    // Lädt alle Bibliotheken aus plugins/. Der Server muss bis zum Prozessende leben (Aufruf nach Arc::new)
+    pub fn load_all(server: &Server) {
+        let dir = Path::new(PLUGIN_DIR);
+        if !dir.is_dir() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                warn!("Konnte Plugin-Verzeichnis {} nicht anlegen: {}", PLUGIN_DIR, e);
+            }
+            return;
+        }
+        let mut paths: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+            Err(e) => {
+                warn!("Konnte {} nicht lesen: {}", PLUGIN_DIR, e);
+                return;
+            }
+        };
+        paths.retain(|p| p.extension().is_some_and(|ext| ext == "so" || ext == "dylib"));
+        paths.sort();
+        for path in paths {
+            if let Err(e) = Self::load(server, &path) {
+                warn!("Plugin {} konnte nicht geladen werden: {}", path.display(), e);
+            }
+        }
+    }
+
+    fn load(server: &Server, path: &Path) -> Result<(), String> {
+        let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        if server.plugins.plugins.lock().unwrap().iter().any(|p| p.name == name) {
+            return Err(format!("Ein Plugin namens {} ist bereits geladen", name));
+        }
+        let c_path = CString::new(path.to_string_lossy().as_bytes()).map_err(|_| "Ungültiger Pfad".to_string())?;
+        let library = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if library.is_null() {
+            return Err(dl_error());
+        }
+        let symbol = |symbol: &[u8]| unsafe { libc::dlsym(library, symbol.as_ptr() as *const c_char) };
+        let (version, enable, disable) = (symbol(SYMBOL_API_VERSION), symbol(SYMBOL_ENABLE), symbol(SYMBOL_DISABLE));
+        if version.is_null() || enable.is_null() {
+            unsafe { libc::dlclose(library) };
+            return Err("Einstiegspunkte rustmc_plugin_api_version/rustmc_plugin_enable fehlen".to_string());
+        }
+        let version: ApiVersionFn = unsafe { std::mem::transmute(version) };
+        let enable: EnableFn = unsafe { std::mem::transmute(enable) };
+        let disable: Option<DisableFn> = (!disable.is_null()).then(|| unsafe { std::mem::transmute::<*mut c_void, DisableFn>(disable) });
+        let plugin_version = version();
+        if !(MIN_PLUGIN_API_VERSION..=PLUGIN_API_VERSION).contains(&plugin_version) {
+            unsafe { libc::dlclose(library) };
+            return Err(format!("Plugin-API-Version {} wird nicht unterstützt (erwartet {})", plugin_version, PLUGIN_API_VERSION));
+        }
+
+        let mut host = Box::new(PluginHost { server, name: name.clone() });
+        let api = Box::new(HostApi {
+            api_version: PLUGIN_API_VERSION,
+            host: &mut *host as *mut PluginHost as *mut c_void,
+            log: host_log,
+            register_command: host_register_command,
+            register_listener: host_register_listener,
+            send_message: host_send_message,
+            broadcast: host_broadcast,
+            kick_player: host_kick_player,
+            player_position: host_player_position,
+            get_block: host_get_block,
+            set_block: host_set_block,
+            set_event_message: host_set_event_message,
+            teleport: host_teleport,
+            link_portal: host_link_portal,
+            unlink_portal: host_unlink_portal,
+            player_brand: host_player_brand,
+            register_channel: host_register_channel,
+            send_plugin_message: host_send_plugin_message,
+            player_ping: host_player_ping,
+        });
+        let api_ptr: *const HostApi = &*api;
+        server.plugins.plugins.lock().unwrap().push(LoadedPlugin {
+            name: name.clone(),
+            library,
+            disable,
+            commands: Vec::new(),
+            listeners: Vec::new(),
+            channels: Vec::new(),
+            _host: host,
+            _api: api,
+        });
+        // enable() läuft ohne gehaltene Sperren, damit das Plugin die Host-API nutzen kann
+        let result = enable(api_ptr);
+        if result != 0 {
+            Self::unload(server, &name);
+            return Err(format!("rustmc_plugin_enable lieferte {}", result));
+        }
+        info!("Plugin {} geladen", name);
+        Ok(())
+    }
+
+    fn unload(server: &Server, name: &str) {
+        let plugin = {
+            let mut plugins = server.plugins.plugins.lock().unwrap();
+            let Some(index) = plugins.iter().position(|p| p.name == name) else { return };
+            plugins.remove(index)
+        };
+        {
+            let mut commands = server.commands.lock().unwrap();
+            for command in &plugin.commands {
+                commands.unregister(command);
+            }
+        }
+        for listener in &plugin.listeners {
+            server.events.unlisten(*listener);
+        }
+        for name in &plugin.channels {
+            if server.channels.unregister(name) {
+                channel::announce(server, name, false);
+            }
+        }
+        if let Some(disable) = plugin.disable {
+            disable();
+        }
+        unsafe { libc::dlclose(plugin.library) };
+        info!("Plugin {} entladen", plugin.name);
+    }
+
+    // Entlädt alle Plugins in umgekehrter Ladereihenfolge
+    pub fn unload_all(server: &Server) {
+        let names: Vec<String> = server.plugins.plugins.lock().unwrap().iter().map(|p| p.name.clone()).collect();
+        for name in names.iter().rev() {
+            Self::unload(server, name);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use uuid::Uuid;
+use crate::dimension::{self, Dimension};
+use crate::edit::Region;
+use crate::json::{self, Json};
+use crate::Server;
+
+// Portale gelten für alle Welten und liegen daher neben der Hauptwelt
+pub const PORTALS_FILE: &str = "portals.json";
+
+// Ein Bereich in einer Welt, der Spieler beim Betreten an ein Ziel in einer (auch anderen) Welt bringt
+#[derive(Debug, Clone)]
+pub struct Portal {
+    pub name: String,
+    // Dimensionsnamen mit Namespace, damit Portale in gerade entladene Welten erhalten bleiben
+    pub world: String,
+    pub region: Region,
+    pub destination: String,
+    pub position: (f64, f64, f64),
+    // Ohne Blickrichtung behält der Spieler seine
+    pub rotation: Option<(f32, f32)>,
+}
+
+fn array(values: &[f64]) -> Json {
+    Json::Array(values.iter().map(|v| Json::Number(*v)).collect())
+}
+
+fn numbers(value: Option<&Json>) -> Option<Vec<f64>> {
+    value?.as_array()?.iter().map(Json::as_f64).collect()
+}
+
+impl Portal {
+    fn contains(&self, dimension: Dimension, (x, y, z): (i32, i32, i32)) -> bool {
+        let (min, max) = self.region;
+        dimension.name() == self.world && (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y) && (min.2..=max.2).contains(&z)
+    }
+
+    fn to_json(&self) -> Json {
+        let (min, max) = self.region;
+        let mut entry = BTreeMap::new();
+        entry.insert("world".to_string(), Json::String(self.world.clone()));
+        entry.insert("from".to_string(), array(&[min.0 as f64, min.1 as f64, min.2 as f64]));
+        entry.insert("to".to_string(), array(&[max.0 as f64, max.1 as f64, max.2 as f64]));
+        entry.insert("destination".to_string(), Json::String(self.destination.clone()));
+        let (x, y, z) = self.position;
+        entry.insert("position".to_string(), array(&[x, y, z]));
+        if let Some((yaw, pitch)) = self.rotation {
+            entry.insert("rotation".to_string(), array(&[yaw as f64, pitch as f64]));
+        }
+        Json::Object(entry)
+    }
+
+    fn from_json(name: &str, entry: &Json) -> Option<Portal> {
+        let corner = |key: &str| match numbers(entry.get(key))?.as_slice() {
+            [x, y, z] => Some((*x as i32, *y as i32, *z as i32)),
+            _ => None,
+        };
+        let (a, b) = (corner("from")?, corner("to")?);
+        let position = match numbers(entry.get("position"))?.as_slice() {
+            [x, y, z] => (*x, *y, *z),
+            _ => return None,
+        };
+        let rotation = match numbers(entry.get("rotation")).as_deref() {
+            Some([yaw, pitch]) => Some((*yaw as f32, *pitch as f32)),
+            _ => None,
+        };
+        Some(Portal {
+            name: name.to_string(),
+            world: entry.get("world")?.as_str()?.to_string(),
+            region: ((a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)), (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2))),
+            destination: entry.get("destination")?.as_str()?.to_string(),
+            position,
+            rotation,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Portals {
+    portals: Vec<Portal>,
+    // Spieler, die gerade in einem Portal stehen; ausgelöst wird erst nach dem Verlassen wieder, sonst würde ein Ziel
+    // in einem Gegenportal sofort zurückschicken
+    inside: HashSet<Uuid>,
+}
+
+impl Portals {
+    // {"hub": {"world": "minecraft:overworld", "from": [..], "to": [..], "destination": "minecraft:creative", "position": [..]}}
+    pub fn load() -> Portals {
+        let Ok(text) = fs::read_to_string(PORTALS_FILE) else { return Portals::default() };
+        let entries = match json::parse(&text) {
+            Ok(Json::Object(entries)) => entries,
+            _ => {
+                warn!("{} ist kein gültiges Objekt, Portale werden ignoriert", PORTALS_FILE);
+                return Portals::default();
+            }
+        };
+        let portals: Vec<Portal> = entries.iter().filter_map(|(name, entry)| Portal::from_json(name, entry)).collect();
+        if portals.len() < entries.len() {
+            warn!("{} ungültige Portale in {} übersprungen", entries.len() - portals.len(), PORTALS_FILE);
+        }
+        Portals { portals, inside: HashSet::new() }
+    }
+
+    fn save(&self) {
+        let entries: BTreeMap<String, Json> = self.portals.iter().map(|p| (p.name.clone(), p.to_json())).collect();
+        if let Err(e) = fs::write(PORTALS_FILE, Json::Object(entries).to_json_string()) {
+            warn!("Konnte {} nicht schreiben: {}", PORTALS_FILE, e);
+        }
+    }
+
+    pub fn list(&self) -> &[Portal] {
+        &self.portals
+    }
+
+    // Ersetzt ein gleichnamiges Portal
+    pub fn link(&mut self, portal: Portal) {
+        self.portals.retain(|p| p.name != portal.name);
+        self.portals.push(portal);
+        self.save();
+    }
+
+    pub fn unlink(&mut self, name: &str) -> bool {
+        let count = self.portals.len();
+        self.portals.retain(|p| p.name != name);
+        let removed = self.portals.len() < count;
+        if removed {
+            self.save();
+        }
+        removed
+    }
+}
+
+// Prüft Quell- und Zielwelt und baut daraus ein Portal; Namen wie bei /world, "world" ist die Oberwelt
+pub fn new_portal(name: &str, world: &str, region: Region, destination: &str, position: (f64, f64, f64), rotation: Option<(f32, f32)>) -> Result<Portal, String> {
+    if name.is_empty() || name.len() > 32 || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("Invalid portal name '{}'", name));
+    }
+    let world = Dimension::by_world_name(world)?;
+    let destination = Dimension::by_world_name(destination)?;
+    Ok(Portal { name: name.to_string(), world: world.name().to_string(), region, destination: destination.name().to_string(), position, rotation })
+}
+
+// Schickt Spieler, die gerade ein Portal betreten haben, an dessen Ziel
+pub fn tick(server: &Server) {
+    let players: Vec<_> = server.players.lock().unwrap().iter().map(|p| (p.uuid, p.username.clone(), p.dimension, p.position)).collect();
+    let mut trips = Vec::new();
+    {
+        let mut portals = server.portals.lock().unwrap();
+        if portals.portals.is_empty() && portals.inside.is_empty() {
+            return;
+        }
+        let mut inside = HashSet::new();
+        for (uuid, username, dimension, (x, y, z)) in players {
+            let position = (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+            let Some(portal) = portals.portals.iter().find(|p| p.contains(dimension, position)) else { continue };
+            if !portals.inside.contains(&uuid) {
+                trips.push((uuid, username, portal.clone()));
+            }
+            inside.insert(uuid);
+        }
+        portals.inside = inside;
+    }
+    for (uuid, username, portal) in trips {
+        let result = Dimension::by_world_name(&portal.destination).and_then(|d| dimension::teleport(server, uuid, d, portal.position, portal.rotation));
+        match result {
+            Ok(()) => debug!("{} benutzt Portal {}", username, portal.name),
+            Err(e) => warn!("Portal {} konnte {} nicht bewegen: {}", portal.name, username, e),
+        }
+    }
+}
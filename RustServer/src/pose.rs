@@ -0,0 +1,110 @@
+use crate::{elytra, entity, sleep};
+use crate::error::ServerError;
+use crate::protocol::types::read_varint_from_cursor;
+use crate::{Player, World};
+
+// Player Command; Reittiere kommen später dazu
+const START_SNEAKING: i32 = 0;
+const STOP_SNEAKING: i32 = 1;
+const LEAVE_BED: i32 = 2;
+const START_SPRINTING: i32 = 3;
+const STOP_SPRINTING: i32 = 4;
+const START_GLIDING: i32 = 8;
+// Bits der Entity-Flags und Werte der Pose in den Metadaten
+const FLAG_ON_FIRE: u8 = 0x01;
+const FLAG_CROUCHING: u8 = 0x02;
+const FLAG_SPRINTING: u8 = 0x08;
+const FLAG_SWIMMING: u8 = 0x10;
+const FLAG_GLIDING: u8 = 0x80;
+const POSE_STANDING: i32 = 0;
+const POSE_GLIDING: i32 = 1;
+const POSE_SLEEPING: i32 = 2;
+const POSE_SWIMMING: i32 = 3;
+const POSE_CROUCHING: i32 = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PoseState {
+    pub sneaking: bool,
+    pub sprinting: bool,
+    // Mit der Elytra; endet am Boden oder wenn die Elytra nicht mehr trägt
+    pub gliding: bool,
+    // Sprinten unter Wasser; das entscheidet der Server wie Vanilla selbst
+    pub swimming: bool,
+    // Steht mit in den Entity-Flags; gesetzt von fire
+    pub on_fire: bool,
+    // Kopfteil des Betts, in dem der Spieler liegt; gesetzt von sleep
+    pub bed: Option<(i32, i32, i32)>,
+}
+
+impl PoseState {
+    pub fn flags(&self) -> u8 {
+        let flag = |set: bool, bit: u8| if set { bit } else { 0 };
+        flag(self.on_fire, FLAG_ON_FIRE)
+            | flag(self.sneaking, FLAG_CROUCHING)
+            | flag(self.sprinting, FLAG_SPRINTING)
+            | flag(self.swimming, FLAG_SWIMMING)
+            | flag(self.gliding, FLAG_GLIDING)
+    }
+
+    pub fn pose(&self) -> i32 {
+        if self.bed.is_some() {
+            POSE_SLEEPING
+        } else if self.gliding {
+            POSE_GLIDING
+        } else if self.swimming {
+            POSE_SWIMMING
+        } else if self.sneaking {
+            POSE_CROUCHING
+        } else {
+            POSE_STANDING
+        }
+    }
+}
+
+// Player Command: merkt sich Ducken und Sprinten und zeigt es den anderen Spielern in Sichtweite; "Bett verlassen"
+// weckt den Spieler
+pub fn handle_player_command(players: &mut [Player], world: &mut World, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let _entity_id = read_varint_from_cursor(cursor)?;
+    let action = read_varint_from_cursor(cursor)?;
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid) else { return Ok(()) };
+    if action == LEAVE_BED {
+        sleep::wake(players, world, index, true);
+        return Ok(());
+    }
+    let p = &mut players[index];
+    let before = p.pose;
+    match action {
+        START_SNEAKING => p.pose.sneaking = true,
+        STOP_SNEAKING => p.pose.sneaking = false,
+        // Mit zu wenig Nahrung sprintet ein unveränderter Client nicht; die Bewegungsprüfung rechnet dann mit Gehen
+        START_SPRINTING if p.hunger.can_sprint() || p.game_mode.allows_flight() => p.pose.sprinting = true,
+        STOP_SPRINTING => {
+            p.pose.sprinting = false;
+            p.movement.stop_sprinting();
+        }
+        // Der Client gleitet schon; darf er nicht, holt ihn die eigene Pose zurück
+        START_GLIDING if elytra::can_glide(p) => p.pose.gliding = true,
+        START_GLIDING => {
+            let _ = entity::send_pose(&p.connection, entity::OWN_PLAYER_ID, &p.pose);
+            return Ok(());
+        }
+        _ => return Ok(()),
+    }
+    if p.pose == before {
+        return Ok(());
+    }
+    trace!("{} duckt sich: {}, sprintet: {}, gleitet: {}", p.username, p.pose.sneaking, p.pose.sprinting, p.pose.gliding);
+    broadcast(players, index);
+    Ok(())
+}
+
+// Zeigt die Pose eines Spielers den anderen in Sichtweite; Zuschauer sieht niemand
+pub fn broadcast(players: &[Player], index: usize) {
+    let p = &players[index];
+    if !p.game_mode.is_tracked() {
+        return;
+    }
+    for viewer in players.iter().filter(|v| v.uuid != p.uuid && entity::in_view(v, p.dimension, p.position)) {
+        let _ = entity::send_pose(&viewer.connection, p.entity_id, &p.pose);
+    }
+}
@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::damage::{self, DamageSource};
+use crate::effect::{self, EffectInstance};
+use crate::item::ItemStack;
+use crate::selector::{Target, PLAYER_TYPE};
+use crate::worldevent::{self, WorldEvent};
+use crate::{bow, dimension, entity, farming, fluid, projectile, Mob, Player, Server, World};
+
+pub const SPLASH_POTION: &str = "minecraft:splash_potion";
+pub const LINGERING_POTION: &str = "minecraft:lingering_potion";
+const THROWN: &str = "minecraft:potion";
+const CLOUD: &str = "minecraft:area_effect_cloud";
+const SPLASH_THROW_SOUND: &str = "minecraft:entity.splash_potion.throw";
+const LINGERING_THROW_SOUND: &str = "minecraft:entity.lingering_potion.throw";
+// Geworfen wird wie bei Vanilla etwas über die Blickrichtung hinaus
+const THROW_PITCH: f32 = -20.0;
+const THROW_VELOCITY: f64 = 0.5;
+const EYE_HEIGHT: f64 = 1.62 - 0.1;
+// Pro Tick wie ThrowableProjectile bei Vanilla
+const AIR_DRAG: f64 = 0.99;
+const WATER_DRAG: f64 = 0.8;
+const GRAVITY: f64 = 0.05;
+// Wurftränke wirken bis vier Blöcke weit, zur Mitte hin stärker; zu kurze Wirkungen entfallen
+const SPLASH_RADIUS: f64 = 4.0;
+const MIN_SPLASH_DURATION: i32 = 20;
+// Verweiltränke: die Wolke wartet kurz, schrumpft dann bis zum Ende und mit jedem Getroffenen
+const CLOUD_RADIUS: f32 = 3.0;
+const CLOUD_RADIUS_ON_USE: f32 = -0.5;
+const CLOUD_MIN_RADIUS: f32 = 0.5;
+const CLOUD_DURATION: u32 = 600;
+const CLOUD_WAIT: u32 = 10;
+const CLOUD_HEIGHT: f64 = 0.5;
+const CLOUD_REAPPLICATION: u32 = 20;
+const CLOUD_CHECK_INTERVAL: u32 = 5;
+// Den schrumpfenden Radius bekommen die Clients nur ab und zu
+const CLOUD_SYNC_INTERVAL: u32 = 10;
+// In der Wolke wirkt ein Viertel der Dauer, Sofortwirkungen halb
+const CLOUD_DURATION_DIVISOR: i32 = 4;
+const CLOUD_INSTANT_FACTOR: f64 = 0.5;
+// Farbe von Tränken ohne Effekte, etwa Wasser
+const WATER_COLOR: i32 = 0x385DC6;
+// Heilung schadet Untoten, Schaden heilt sie
+const UNDEAD: &[&str] = &[
+    "bogged", "drowned", "husk", "phantom", "skeleton", "skeleton_horse", "stray", "wither", "wither_skeleton", "zoglin",
+    "zombie", "zombie_horse", "zombie_villager", "zombified_piglin",
+];
+
+// Effekt, Dauer in Ticks und Stufe
+type PotionEffect = (usize, i32, u8);
+type Vec3 = (f64, f64, f64);
+
+// Grundtränke in der Reihenfolge ihrer Registry-IDs ab 1.20.5, mit ihren Effekten wie bei Vanilla
+const POTIONS: &[(&str, &[PotionEffect])] = &[
+    ("water", &[]),
+    ("mundane", &[]),
+    ("thick", &[]),
+    ("awkward", &[]),
+    ("night_vision", &[(effect::NIGHT_VISION, 3600, 0)]),
+    ("long_night_vision", &[(effect::NIGHT_VISION, 9600, 0)]),
+    ("invisibility", &[(effect::INVISIBILITY, 3600, 0)]),
+    ("long_invisibility", &[(effect::INVISIBILITY, 9600, 0)]),
+    ("leaping", &[(effect::JUMP_BOOST, 3600, 0)]),
+    ("long_leaping", &[(effect::JUMP_BOOST, 9600, 0)]),
+    ("strong_leaping", &[(effect::JUMP_BOOST, 1800, 1)]),
+    ("fire_resistance", &[(effect::FIRE_RESISTANCE, 3600, 0)]),
+    ("long_fire_resistance", &[(effect::FIRE_RESISTANCE, 9600, 0)]),
+    ("swiftness", &[(effect::SPEED, 3600, 0)]),
+    ("long_swiftness", &[(effect::SPEED, 9600, 0)]),
+    ("strong_swiftness", &[(effect::SPEED, 1800, 1)]),
+    ("slowness", &[(effect::SLOWNESS, 1800, 0)]),
+    ("long_slowness", &[(effect::SLOWNESS, 4800, 0)]),
+    ("strong_slowness", &[(effect::SLOWNESS, 400, 3)]),
+    ("turtle_master", &[(effect::SLOWNESS, 400, 3), (effect::RESISTANCE, 400, 2)]),
+    ("long_turtle_master", &[(effect::SLOWNESS, 800, 3), (effect::RESISTANCE, 800, 2)]),
+    ("strong_turtle_master", &[(effect::SLOWNESS, 400, 5), (effect::RESISTANCE, 400, 3)]),
+    ("water_breathing", &[(effect::WATER_BREATHING, 3600, 0)]),
+    ("long_water_breathing", &[(effect::WATER_BREATHING, 9600, 0)]),
+    ("healing", &[(effect::INSTANT_HEALTH, 1, 0)]),
+    ("strong_healing", &[(effect::INSTANT_HEALTH, 1, 1)]),
+    ("harming", &[(effect::INSTANT_DAMAGE, 1, 0)]),
+    ("strong_harming", &[(effect::INSTANT_DAMAGE, 1, 1)]),
+    ("poison", &[(effect::POISON, 900, 0)]),
+    ("long_poison", &[(effect::POISON, 1800, 0)]),
+    ("strong_poison", &[(effect::POISON, 432, 1)]),
+    ("regeneration", &[(effect::REGENERATION, 900, 0)]),
+    ("long_regeneration", &[(effect::REGENERATION, 1800, 0)]),
+    ("strong_regeneration", &[(effect::REGENERATION, 450, 1)]),
+    ("strength", &[(effect::STRENGTH, 3600, 0)]),
+    ("long_strength", &[(effect::STRENGTH, 9600, 0)]),
+    ("strong_strength", &[(effect::STRENGTH, 1800, 1)]),
+    ("weakness", &[(effect::WEAKNESS, 1800, 0)]),
+    ("long_weakness", &[(effect::WEAKNESS, 4800, 0)]),
+    ("luck", &[(effect::LUCK, 6000, 0)]),
+    ("slow_falling", &[(effect::SLOW_FALLING, 1800, 0)]),
+    ("long_slow_falling", &[(effect::SLOW_FALLING, 4800, 0)]),
+    ("wind_charged", &[(effect::WIND_CHARGED, 3600, 0)]),
+    ("weaving", &[(effect::WEAVING, 3600, 0)]),
+    ("oozing", &[(effect::OOZING, 3600, 0)]),
+    ("infested", &[(effect::INFESTED, 3600, 0)]),
+];
+// Partikelfarben der Effekte, aus denen sich die Farbe eines Tranks mischt
+const EFFECT_COLORS: &[(usize, i32)] = &[
+    (effect::SPEED, 0x33EBFF), (effect::SLOWNESS, 0x8BAFE0), (effect::STRENGTH, 0xFFC700), (effect::INSTANT_HEALTH, 0xF82423),
+    (effect::INSTANT_DAMAGE, 0xA9656A), (effect::JUMP_BOOST, 0xFDFF84), (effect::REGENERATION, 0xCD5CAB),
+    (effect::RESISTANCE, 0x9146F0), (effect::FIRE_RESISTANCE, 0xFF9900), (effect::WATER_BREATHING, 0x98DAC0),
+    (effect::INVISIBILITY, 0xF6F6F6), (effect::NIGHT_VISION, 0xC2FF66), (effect::WEAKNESS, 0x484D48), (effect::POISON, 0x87A363),
+    (effect::LUCK, 0x59C106), (effect::SLOW_FALLING, 0xF3CFB9), (effect::WIND_CHARGED, 0xBDC9FF), (effect::WEAVING, 0x78695A),
+    (effect::OOZING, 0x99FFA3), (effect::INFESTED, 0x8C9B8C),
+];
+
+fn path(name: &str) -> &str {
+    name.strip_prefix("minecraft:").unwrap_or(name)
+}
+
+pub fn is_known(name: &str) -> bool {
+    registry_id(name).is_some()
+}
+
+// Registry-ID für die potion_contents-Komponente
+pub fn registry_id(name: &str) -> Option<i32> {
+    POTIONS.iter().position(|(potion, _)| *potion == path(name)).map(|id| id as i32)
+}
+
+fn effects(potion: Option<&str>) -> &'static [PotionEffect] {
+    potion.and_then(|name| POTIONS.iter().find(|(potion, _)| *potion == path(name))).map_or(&[], |(_, effects)| effects)
+}
+
+fn is_instant(id: usize) -> bool {
+    id == effect::INSTANT_HEALTH || id == effect::INSTANT_DAMAGE
+}
+
+// Mittel der Effektfarben, gewichtet wie bei Vanilla mit der Stufe
+fn color(effects: &[PotionEffect]) -> i32 {
+    let (mut sum, mut weight) = ([0.0f64; 3], 0.0);
+    for &(id, _, amplifier) in effects {
+        let Some(&(_, color)) = EFFECT_COLORS.iter().find(|(effect, _)| *effect == id) else { continue };
+        let factor = amplifier as f64 + 1.0;
+        for (channel, value) in sum.iter_mut().enumerate() {
+            *value += ((color >> (16 - channel * 8)) & 0xFF) as f64 * factor;
+        }
+        weight += factor;
+    }
+    if weight == 0.0 {
+        return WATER_COLOR;
+    }
+    let [r, g, b] = sum.map(|value| (value / weight) as i32);
+    r << 16 | g << 8 | b
+}
+
+// Ein fliegender Wurf- oder Verweiltrank
+#[derive(Debug, Clone)]
+pub struct Thrown {
+    owner: Uuid,
+    // Erst wenn der Trank die Hitbox des Werfers verlassen hat, kann er an ihm zerspringen
+    left_owner: bool,
+    pub stack: ItemStack,
+}
+
+// Wolke eines Verweiltranks
+#[derive(Debug, Clone)]
+pub struct Cloud {
+    potion: Option<String>,
+    pub color: i32,
+    pub radius: f32,
+    age: u32,
+    // Wer ab welchem Alter der Wolke wieder etwas abbekommt
+    affected: HashMap<Uuid, u32>,
+}
+
+impl Cloud {
+    // Solange die Wolke wartet, zeigt der Client nur wenige Partikel in der Mitte
+    pub fn is_waiting(&self) -> bool {
+        self.age < CLOUD_WAIT
+    }
+}
+
+// Was ein Ziel abbekommt, angewendet erst ohne gehaltene Sperren
+struct Dose {
+    target: Target,
+    effects: &'static [PotionEffect],
+    // Anteil der Dauer bzw. Stärke bei Sofortwirkung
+    duration: f64,
+    instant: f64,
+}
+
+// Use Item mit einem Wurf- oder Verweiltrank: er fliegt in Blickrichtung los. false, wenn das Item keiner ist
+pub fn use_item(players: &mut [Player], world: &mut World, index: usize, slot: usize) -> bool {
+    let player = &mut players[index];
+    let mut stack = player.inventory.slot(slot).clone();
+    if stack.item != SPLASH_POTION && stack.item != LINGERING_POTION {
+        return false;
+    }
+    let (yaw, pitch) = player.rotation;
+    let (x, _, z) = bow::look_direction(yaw, pitch);
+    let (_, y, _) = bow::look_direction(yaw, pitch + THROW_PITCH);
+    let length = (x * x + y * y + z * z).sqrt();
+    let position = (player.position.0, player.position.1 + EYE_HEIGHT, player.position.2);
+    let sound = if stack.item == SPLASH_POTION { SPLASH_THROW_SOUND } else { LINGERING_THROW_SOUND };
+    let owner = player.uuid;
+    farming::spend(player, slot, false);
+    stack.count = 1;
+    let mut mob = Mob::new(THROWN, position);
+    mob.velocity = bow::velocity((x / length, y / length, z / length), THROW_VELOCITY);
+    mob.potion = Some(Thrown { owner, left_owner: false, stack });
+    for viewer in players.iter().filter(|p| entity::in_view(p, world.dimension, position)) {
+        let _ = entity::send_mob(&viewer.connection, &mob);
+        let _ = worldevent::send_sound(&viewer.connection, sound, position, 0.5);
+    }
+    world.entities.update(mob.id, position);
+    world.mobs.push(mob);
+    true
+}
+
+// Lässt geworfene Tränke fliegen und zerspringen und die Wolken von Verweiltränken wirken. Die Positionen sendet der
+// Tracker. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut doses = Vec::new();
+    let players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let mut removed = Vec::new();
+        for index in 0..world.mobs.len() {
+            let keep = if world.mobs[index].potion.is_some() {
+                fly(&players, &mut world, index, &mut doses)
+            } else if world.mobs[index].cloud.is_some() {
+                linger(&players, &mut world, index, &mut doses)
+            } else {
+                true
+            };
+            if !keep {
+                removed.push(index);
+            }
+        }
+        let dimension = world.dimension;
+        for &index in removed.iter().rev() {
+            let mob = world.mobs.remove(index);
+            world.entities.remove(mob.id);
+            for viewer in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+                let _ = entity::send_remove(&viewer.connection, &[mob.entity_id]);
+            }
+        }
+        // Zersprungene Tränke haben ihre Wolken angehängt
+        let new_clouds = world.mobs.iter().filter(|m| m.cloud.as_ref().is_some_and(|cloud| cloud.age == 0));
+        for mob in new_clouds {
+            for viewer in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+                let _ = entity::send_mob(&viewer.connection, mob);
+            }
+        }
+    }
+    drop(players);
+    for dose in doses {
+        apply(server, &dose);
+    }
+}
+
+// Ein Flugtick; false, wenn der Trank zersprungen oder aus der Welt gefallen ist
+fn fly(players: &[Player], world: &mut World, index: usize, doses: &mut Vec<Dose>) -> bool {
+    let dimension = world.dimension;
+    let mob = &world.mobs[index];
+    let Some(thrown) = mob.potion.clone() else { return true };
+    let (position, velocity) = (mob.position, mob.velocity);
+    if position.1 < dimension.min_y() as f64 - 64.0 {
+        return false;
+    }
+    if !world.chunks.contains_key(&((position.0.floor() as i32) >> 4, (position.2.floor() as i32) >> 4)) {
+        return true;
+    }
+    let owner = players.iter().find(|p| p.uuid == thrown.owner && p.dimension == dimension);
+    let left_owner = thrown.left_owner || owner.is_none_or(|p| {
+        let (width, height) = entity::hitbox(PLAYER_TYPE);
+        let half = width / 2.0 + 1.0;
+        let (dx, dz, dy) = (position.0 - p.position.0, position.2 - p.position.2, position.1 - p.position.1);
+        dx.abs() > half || dz.abs() > half || dy < -1.0 || dy > height + 1.0
+    });
+    let block_hit = projectile::first_block(world, position, velocity);
+    let reach = block_hit.map_or(1.0, |(t, _)| t);
+    let mut target: Option<(f64, Target)> = None;
+    let mut closer = |t: Option<f64>, hit: &dyn Fn() -> Target| {
+        if let Some(t) = t.filter(|&t| t <= reach && target.as_ref().is_none_or(|(best, _)| t < *best)) {
+            target = Some((t, hit()));
+        }
+    };
+    for p in players.iter().filter(|p| p.dimension == dimension && p.health > 0.0 && p.game_mode.is_tracked()) {
+        if !left_owner && p.uuid == thrown.owner {
+            continue;
+        }
+        closer(projectile::intersect(position, velocity, p.position, entity::hitbox(PLAYER_TYPE)), &|| Target::of_player(p));
+    }
+    for m in world.mobs.iter().filter(|m| entity::is_living(&m.mob_type) && m.health > 0.0) {
+        closer(projectile::intersect(position, velocity, m.position, entity::hitbox(&m.mob_type)), &|| Target::of_mob(m, dimension));
+    }
+    let impact = target.as_ref().map(|(t, _)| *t).or(block_hit.map(|(t, _)| t));
+    if let Some(t) = impact {
+        let at = (position.0 + velocity.0 * t, position.1 + velocity.1 * t, position.2 + velocity.2 * t);
+        shatter(players, world, &thrown.stack, at, target.map(|(_, target)| target.uuid), doses);
+        return false;
+    }
+    let next = (position.0 + velocity.0, position.1 + velocity.1, position.2 + velocity.2);
+    let drag = if fluid::in_water(world, next) { WATER_DRAG } else { AIR_DRAG };
+    let mob = &mut world.mobs[index];
+    mob.position = next;
+    mob.velocity = (velocity.0 * drag, velocity.1 * drag - GRAVITY, velocity.2 * drag);
+    mob.potion.as_mut().unwrap().left_owner = left_owner;
+    let id = mob.id;
+    world.entities.update(id, next);
+    true
+}
+
+// Der Trank zerspringt bei position: ein Wurftrank trifft alles in der Nähe, ein Verweiltrank hinterlässt eine Wolke
+fn shatter(players: &[Player], world: &mut World, stack: &ItemStack, position: Vec3, direct: Option<Uuid>, doses: &mut Vec<Dose>) {
+    let potion = stack.components.potion.clone();
+    let effects = effects(potion.as_deref());
+    let color = color(effects);
+    let event = if effects.iter().any(|&(id, ..)| is_instant(id)) { WorldEvent::InstantSplashPotion(color) } else { WorldEvent::SplashPotion(color) };
+    let block = (position.0.floor() as i32, position.1.floor() as i32, position.2.floor() as i32);
+    world.play_event(event, block, None);
+    if stack.item == LINGERING_POTION {
+        let mut mob = Mob::new(CLOUD, position);
+        mob.cloud = Some(Cloud { potion, color, radius: CLOUD_RADIUS, age: 0, affected: HashMap::new() });
+        world.entities.update(mob.id, position);
+        world.mobs.push(mob);
+        return;
+    }
+    if effects.is_empty() {
+        return;
+    }
+    let dimension = world.dimension;
+    let mut splash = |target: Target, at: Vec3| {
+        let (dx, dy, dz) = (at.0 - position.0, at.1 - position.1, at.2 - position.2);
+        let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+        if distance >= SPLASH_RADIUS {
+            return;
+        }
+        let proximity = if direct == Some(target.uuid) { 1.0 } else { 1.0 - distance / SPLASH_RADIUS };
+        doses.push(Dose { target, effects, duration: proximity, instant: proximity });
+    };
+    for p in players.iter().filter(|p| p.dimension == dimension && p.health > 0.0 && p.game_mode.is_tracked()) {
+        splash(Target::of_player(p), p.position);
+    }
+    for m in world.mobs.iter().filter(|m| entity::is_living(&m.mob_type) && m.health > 0.0) {
+        splash(Target::of_mob(m, dimension), m.position);
+    }
+}
+
+// Ein Tick einer Wolke; false, wenn sie abgelaufen oder zu klein geworden ist
+fn linger(players: &[Player], world: &mut World, index: usize, doses: &mut Vec<Dose>) -> bool {
+    let dimension = world.dimension;
+    let mut inside = Vec::new();
+    {
+        let mob = &world.mobs[index];
+        let cloud = mob.cloud.as_ref().unwrap();
+        let radius = cloud.radius as f64;
+        let (x, y, z) = mob.position;
+        let covers = |at: Vec3, height: f64| {
+            let (dx, dz) = (at.0 - x, at.2 - z);
+            dx * dx + dz * dz <= radius * radius && at.1 <= y + CLOUD_HEIGHT && at.1 + height >= y
+        };
+        if !cloud.is_waiting() && (cloud.age + 1).is_multiple_of(CLOUD_CHECK_INTERVAL) {
+            for p in players.iter().filter(|p| p.dimension == dimension && p.health > 0.0 && p.game_mode.is_tracked()) {
+                if covers(p.position, entity::hitbox(PLAYER_TYPE).1) {
+                    inside.push(Target::of_player(p));
+                }
+            }
+            for m in world.mobs.iter().filter(|m| entity::is_living(&m.mob_type) && m.health > 0.0) {
+                if covers(m.position, entity::hitbox(&m.mob_type).1) {
+                    inside.push(Target::of_mob(m, dimension));
+                }
+            }
+        }
+    }
+    let mob = &mut world.mobs[index];
+    let (entity_id, position) = (mob.entity_id, mob.position);
+    let cloud = mob.cloud.as_mut().unwrap();
+    let was_waiting = cloud.is_waiting();
+    cloud.age += 1;
+    if cloud.age >= CLOUD_WAIT + CLOUD_DURATION {
+        return false;
+    }
+    let mut changed = was_waiting != cloud.is_waiting();
+    if !cloud.is_waiting() {
+        cloud.radius -= CLOUD_RADIUS / CLOUD_DURATION as f32;
+        let age = cloud.age;
+        cloud.affected.retain(|_, until| *until > age);
+        let effects = effects(cloud.potion.as_deref());
+        for target in inside.into_iter().filter(|_| !effects.is_empty()) {
+            if cloud.affected.contains_key(&target.uuid) {
+                continue;
+            }
+            cloud.affected.insert(target.uuid, age + CLOUD_REAPPLICATION);
+            cloud.radius += CLOUD_RADIUS_ON_USE;
+            changed = true;
+            doses.push(Dose { target, effects, duration: 1.0 / CLOUD_DURATION_DIVISOR as f64, instant: CLOUD_INSTANT_FACTOR });
+        }
+        if cloud.radius < CLOUD_MIN_RADIUS {
+            return false;
+        }
+    }
+    if changed || cloud.age.is_multiple_of(CLOUD_SYNC_INTERVAL) {
+        for viewer in players.iter().filter(|p| entity::in_view(p, dimension, position)) {
+            let _ = entity::send_cloud(&viewer.connection, entity_id, cloud);
+        }
+    }
+    true
+}
+
+fn apply(server: &Server, dose: &Dose) {
+    for &(id, duration, amplifier) in dose.effects {
+        if !is_instant(id) {
+            let duration = (duration as f64 * dose.duration + 0.5) as i32;
+            if duration > MIN_SPLASH_DURATION {
+                effect::apply(server, &dose.target, id, EffectInstance::new(amplifier, Some(duration), true));
+            }
+            continue;
+        }
+        let undead = UNDEAD.contains(&path(&dose.target.kind));
+        if (id == effect::INSTANT_HEALTH) != undead {
+            heal(server, &dose.target, (dose.instant * (4 << amplifier) as f64 + 0.5) as i32 as f32);
+        } else {
+            let amount = if id == effect::INSTANT_DAMAGE { 6 << amplifier } else { 4 << amplifier };
+            damage::damage(server, &dose.target, DamageSource::Magic, (dose.instant * amount as f64 + 0.5) as i32 as f32);
+        }
+    }
+}
+
+// Sofortheilung, bis zu den maximalen Lebenspunkten. Darf nur ohne gehaltene Sperren aufgerufen werden
+fn heal(server: &Server, target: &Target, amount: f32) {
+    let mut players = server.players.lock().unwrap();
+    if target.is_player() {
+        let Some(player) = players.iter_mut().find(|p| p.uuid == target.uuid && p.health > 0.0) else { return };
+        player.health = (player.health + amount).min(damage::MAX_HEALTH);
+        let _ = damage::send_health(player);
+        return;
+    }
+    let Some(lock) = dimension::world_with_mob(server, target.uuid) else { return };
+    let mut world = lock.lock().unwrap();
+    let dimension = world.dimension;
+    let Some(mob) = world.mobs.iter_mut().find(|m| m.id == target.uuid && m.health > 0.0) else { return };
+    mob.health = (mob.health + amount).min(entity::max_health(&mob.mob_type));
+    for player in players.iter_mut().filter(|p| entity::in_view(p, dimension, mob.position)) {
+        let _ = entity::send_health(&player.connection, mob.entity_id, mob.health);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use crate::dimension::{self, Dimension};
+use crate::worldgen::ChunkGenPool;
+use crate::{send_system_message, Chunk, Server};
+
+pub const MAX_RADIUS: i32 = 1000;
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+// Dauern die Ticks länger, fordert die Vorgenerierung nichts Neues an, bis der Server wieder hinterherkommt
+const MAX_MSPT: f64 = 40.0;
+// Auch das Überspringen vorhandener Chunks kostet Zeit
+const MAX_CHECKS_PER_TICK: usize = 4096;
+// So viele Ticks an Chunks dürfen noch ungeschrieben sein
+const MAX_BACKLOG_TICKS: usize = 4;
+
+// Eine laufende Vorgenerierung mit eigenem Generator-Pool, damit Chunks für Spieler nicht warten müssen
+pub struct Pregen {
+    dimension: Dimension,
+    pool: ChunkGenPool,
+    // Von der Mitte aus Ring für Ring
+    queue: VecDeque<(i32, i32)>,
+    in_flight: usize,
+    total: usize,
+    generated: usize,
+    skipped: usize,
+    paused: bool,
+    // Bekommt die Fortschrittsmeldungen zusätzlich zum Log
+    reporter: Option<Uuid>,
+    started: Instant,
+    last_report: Instant,
+}
+
+impl Pregen {
+    fn processed(&self) -> usize {
+        self.generated + self.skipped
+    }
+
+    pub fn status(&self) -> String {
+        let processed = self.processed();
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let rate = if elapsed > 0.0 { processed as f64 / elapsed } else { 0.0 };
+        let mut status = format!(
+            "{} chunks of {} in {} ({:.1}%), {:.0} chunks/s",
+            processed,
+            self.total,
+            self.dimension.name(),
+            processed as f64 * 100.0 / self.total.max(1) as f64,
+            rate
+        );
+        if self.paused {
+            status.push_str(", paused");
+        } else if rate > 0.0 {
+            let left = (self.total - processed) as f64 / rate;
+            status.push_str(&format!(", about {} left", format_duration(left as u64)));
+        }
+        status
+    }
+
+    fn report(&self, server: &Server, message: &str) {
+        info!("Vorgenerierung: {}", message);
+        let Some(uuid) = self.reporter else { return };
+        if let Some(player) = server.players.lock().unwrap().iter().find(|p| p.uuid == uuid) {
+            let _ = send_system_message(&player.connection, &format!("Pregeneration: {}", message));
+        }
+    }
+
+    // Vorhandene Chunks überspringen, für die übrigen bis pregen-chunks-per-tick gleichzeitig anfordern. Kommen die
+    // Chunk-Worker mit dem Schreiben nicht nach, wird gewartet
+    fn request_more(&mut self, server: &Server) -> Result<(), String> {
+        let world = dimension::world(server, self.dimension)?.lock().unwrap();
+        let limit = server.config.pregen_chunks_per_tick.max(1);
+        if world.storage.io().backlog() >= limit * MAX_BACKLOG_TICKS {
+            return Ok(());
+        }
+        let mut checks = 0;
+        while self.in_flight < limit && checks < MAX_CHECKS_PER_TICK {
+            let Some((x, z)) = self.queue.pop_front() else { break };
+            checks += 1;
+            if world.chunks.contains_key(&(x, z)) || world.storage.has_chunk(x, z) {
+                self.skipped += 1;
+                continue;
+            }
+            self.pool.request(x, z);
+            self.in_flight += 1;
+        }
+        Ok(())
+    }
+
+    // Übergibt fertige Chunks den Chunk-Workern; was inzwischen ein Spieler geladen hat, speichert die Welt selbst
+    fn save(&mut self, server: &Server, chunks: Vec<Chunk>) -> Result<(), String> {
+        let mut world = dimension::world(server, self.dimension)?.lock().unwrap();
+        for chunk in chunks {
+            if world.chunks.contains_key(&(chunk.x, chunk.z)) {
+                self.skipped += 1;
+                continue;
+            }
+            world.storage.save_chunk(chunk);
+            self.generated += 1;
+        }
+        Ok(())
+    }
+}
+
+// Quadrat mit radius Chunks um den Weltspawn; der Nether ist nicht geladen und lässt sich daher nicht vorgenerieren
+pub fn start(server: &Server, dimension: Dimension, radius: i32, reporter: Option<Uuid>) -> Result<String, String> {
+    if !(0..=MAX_RADIUS).contains(&radius) {
+        return Err(format!("The radius must be between 0 and {}", MAX_RADIUS));
+    }
+    let mut pregen = server.pregen.lock().unwrap();
+    if pregen.is_some() {
+        return Err("A pregeneration is already running".to_string());
+    }
+    let (seed, (x, _, z)) = {
+        let world = dimension::world(server, dimension)?.lock().unwrap();
+        (world.seed, world.spawn.position)
+    };
+    let (center_x, center_z) = (x >> 4, z >> 4);
+    let mut queue = VecDeque::new();
+    for ring in 0..=radius {
+        for dx in -ring..=ring {
+            for dz in -ring..=ring {
+                if dx.abs() == ring || dz.abs() == ring {
+                    queue.push_back((center_x + dx, center_z + dz));
+                }
+            }
+        }
+    }
+    // Nur die Hälfte der Kerne, der Rest bleibt für die Tick-Schleife und die Chunks der Spieler
+    let threads = thread::available_parallelism().map(|n| n.get() / 2).unwrap_or(1);
+    let task = Pregen {
+        dimension,
+        pool: ChunkGenPool::new(threads, seed, dimension),
+        total: queue.len(),
+        queue,
+        in_flight: 0,
+        generated: 0,
+        skipped: 0,
+        paused: false,
+        reporter,
+        started: Instant::now(),
+        last_report: Instant::now(),
+    };
+    let message = format!("Pregenerating {} chunks within {} chunks of ({}, {}) in {}", task.total, radius, center_x, center_z, dimension.name());
+    info!("Starte Vorgenerierung von {} Chunks in {}", task.total, dimension.name());
+    *pregen = Some(task);
+    Ok(message)
+}
+
+// Pausiert oder setzt fort; false, wenn der Zustand schon so war
+pub fn set_paused(server: &Server, paused: bool) -> Result<bool, String> {
+    let mut pregen = server.pregen.lock().unwrap();
+    let task = pregen.as_mut().ok_or("No pregeneration is running")?;
+    Ok(std::mem::replace(&mut task.paused, paused) != paused)
+}
+
+// Schon angeforderte Chunks werden noch fertig generiert, aber nicht mehr gespeichert
+pub fn cancel(server: &Server) -> Result<String, String> {
+    let task = server.pregen.lock().unwrap().take().ok_or("No pregeneration is running")?;
+    info!("Vorgenerierung abgebrochen nach {} Chunks", task.processed());
+    Ok(task.status())
+}
+
+pub fn status(server: &Server) -> Option<String> {
+    server.pregen.lock().unwrap().as_ref().map(Pregen::status)
+}
+
+// Übernimmt fertige Chunks und fordert neue an, solange der Server hinterherkommt und kein Backup gepackt wird.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut pregen = server.pregen.lock().unwrap();
+    let Some(task) = pregen.as_mut() else { return };
+    if server.backups.running() {
+        return;
+    }
+    let finished = task.pool.poll();
+    task.in_flight -= finished.len();
+    let mut result = if finished.is_empty() { Ok(()) } else { task.save(server, finished) };
+    let lagging = server.tick_stats.lock().unwrap().average_mspt() > MAX_MSPT;
+    if result.is_ok() && !task.paused && !lagging {
+        result = task.request_more(server);
+    }
+    if let Err(e) = result {
+        warn!("Vorgenerierung abgebrochen: {}", e);
+        *pregen = None;
+        return;
+    }
+    if task.queue.is_empty() && task.in_flight == 0 {
+        let elapsed = task.started.elapsed().as_secs();
+        let message = format!("Finished {} chunks in {} ({} generated, {} already existed)", task.total, format_duration(elapsed), task.generated, task.skipped);
+        task.report(server, &message);
+        *pregen = None;
+        return;
+    }
+    if !task.paused && task.last_report.elapsed() >= REPORT_INTERVAL {
+        task.last_report = Instant::now();
+        task.report(server, &task.status());
+    }
+}
+
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs / 60 % 60),
+    }
+}
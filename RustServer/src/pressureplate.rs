@@ -0,0 +1,142 @@
+use crate::{block, entity, farming, tripwire, Player, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Wie lange eine gedrückte Platte wartet, bevor sie nachsieht, ob noch etwas auf ihr steht
+const PRESSED_TICKS: i64 = 20;
+const WEIGHTED_PRESSED_TICKS: i64 = 10;
+// Ab so vielen Entities gibt eine beschwerte Platte vollen Strom
+const LIGHT_MAX_WEIGHT: usize = 15;
+const HEAVY_MAX_WEIGHT: usize = 150;
+const ITEM_HITBOX: (f64, f64) = (0.25, 0.25);
+const ORB_HITBOX: (f64, f64) = (0.5, 0.5);
+// Nur damit gedrückte Platten nach einem Neustart wieder loslassen
+pub const RANDOM_TICKING: &[&str] = &[
+    "minecraft:stone_pressure_plate", "minecraft:oak_pressure_plate", "minecraft:light_weighted_pressure_plate",
+    "minecraft:heavy_weighted_pressure_plate",
+];
+
+// Hitbox eines Entitys, das Druckplatten und Stolperdrähte auslösen kann; Zuschauer tun das nicht
+pub struct Trigger {
+    pub min: (f64, f64, f64),
+    pub max: (f64, f64, f64),
+    // Steinplatten reagieren nur auf Spieler und Mobs
+    pub living: bool,
+}
+
+impl Trigger {
+    fn new((x, y, z): (f64, f64, f64), (width, height): (f64, f64), living: bool) -> Trigger {
+        Trigger { min: (x - width / 2.0, y, z - width / 2.0), max: (x + width / 2.0, y + height, z + width / 2.0), living }
+    }
+
+    pub fn intersects(&self, min: (f64, f64, f64), max: (f64, f64, f64)) -> bool {
+        self.min.0 < max.0 && self.max.0 > min.0 && self.min.1 < max.1 && self.max.1 > min.1 && self.min.2 < max.2 && self.max.2 > min.2
+    }
+}
+
+pub fn triggers(players: &[Player], world: &World) -> Vec<Trigger> {
+    let players = players
+        .iter()
+        .filter(|p| p.dimension == world.dimension && p.game_mode.is_tracked())
+        .map(|p| Trigger::new(p.position, entity::hitbox("minecraft:player"), true));
+    let mobs = world.mobs.iter().map(|mob| Trigger::new(mob.position, entity::hitbox(&mob.mob_type), entity::is_living(&mob.mob_type)));
+    let items = world.items.iter().map(|item| Trigger::new(item.position, ITEM_HITBOX, false));
+    let orbs = world.orbs.iter().map(|orb| Trigger::new(orb.position, ORB_HITBOX, false));
+    players.chain(mobs).chain(items).chain(orbs).collect()
+}
+
+// Sieht jeden Tick nach, auf welchen Platten und Stolperdrähten etwas steht. Losgelassen wird nur in geplanten Ticks,
+// wie bei Vanilla
+pub fn tick(players: &[Player], world: &mut World) {
+    let triggers = triggers(players, world);
+    let mut touched = Vec::new();
+    for trigger in &triggers {
+        let y = trigger.min.1.floor() as i32;
+        for x in trigger.min.0.floor() as i32..=trigger.max.0.floor() as i32 {
+            for z in trigger.min.2.floor() as i32..=trigger.max.2.floor() as i32 {
+                if !touched.contains(&(x, y, z)) {
+                    touched.push((x, y, z));
+                }
+            }
+        }
+    }
+    for position in touched {
+        let state = world.get_block(position.0, position.1, position.2);
+        if block::is_pressure_plate(state) && power(state) == 0 {
+            check(players, world, position, state, &triggers);
+        } else if tripwire::is_wire(state) {
+            tripwire::entity_inside(players, world, position, state, &triggers);
+        }
+    }
+}
+
+fn power(state: u16) -> u8 {
+    match block::property(state, "power") {
+        Some(power) => power.parse().unwrap_or(0),
+        None => (block::property(state, "powered") == Some("true")) as u8 * 15,
+    }
+}
+
+fn with_power(state: u16, power: u8) -> u16 {
+    match block::property(state, "power") {
+        Some(_) => block::with_property(state, "power", &power.to_string()),
+        None => block::with_property(state, "powered", &(power > 0).to_string()),
+    }
+    .unwrap_or(state)
+}
+
+fn is_weighted(state: u16) -> bool {
+    block::property(state, "power").is_some()
+}
+
+// Strom je nach dem, was auf der Platte steht: Holz reagiert auf alles, Stein nur auf Lebewesen, beschwerte Platten
+// zählen die Entities
+fn pressure(state: u16, (x, y, z): BlockPos, triggers: &[Trigger]) -> u8 {
+    let (x, y, z) = (x as f64, y as f64, z as f64);
+    let on: Vec<&Trigger> = triggers.iter().filter(|t| t.intersects((x + 0.125, y, z + 0.125), (x + 0.875, y + 0.25, z + 0.875))).collect();
+    match block::name(state) {
+        "minecraft:stone_pressure_plate" => on.iter().any(|t| t.living) as u8 * 15,
+        "minecraft:light_weighted_pressure_plate" => on.len().min(LIGHT_MAX_WEIGHT) as u8,
+        "minecraft:heavy_weighted_pressure_plate" => on.len().min(HEAVY_MAX_WEIGHT).div_ceil(HEAVY_MAX_WEIGHT / 15) as u8,
+        _ => (!on.is_empty()) as u8 * 15,
+    }
+}
+
+fn click_sound(state: u16, on: bool) -> &'static str {
+    match (block::name(state), on) {
+        ("minecraft:stone_pressure_plate", true) => "minecraft:block.stone_pressure_plate.click_on",
+        ("minecraft:stone_pressure_plate", false) => "minecraft:block.stone_pressure_plate.click_off",
+        (_, true) if is_weighted(state) => "minecraft:block.metal_pressure_plate.click_on",
+        (_, false) if is_weighted(state) => "minecraft:block.metal_pressure_plate.click_off",
+        (_, true) => "minecraft:block.wooden_pressure_plate.click_on",
+        (_, false) => "minecraft:block.wooden_pressure_plate.click_off",
+    }
+}
+
+fn check(players: &[Player], world: &mut World, position: BlockPos, state: u16, triggers: &[Trigger]) {
+    let old = power(state);
+    let new = pressure(state, position, triggers);
+    if old != new {
+        world.set_block_and_notify(position, with_power(state, new));
+        if (old == 0) != (new == 0) {
+            farming::play_sound(players, world, click_sound(state, new > 0), position);
+        }
+    }
+    if new > 0 {
+        world.schedule_block_tick(position, if is_weighted(state) { WEIGHTED_PRESSED_TICKS } else { PRESSED_TICKS });
+    }
+}
+
+pub fn scheduled_tick(players: &[Player], world: &mut World, position: BlockPos, state: u16) {
+    if power(state) > 0 {
+        let triggers = triggers(players, world);
+        check(players, world, position, state, &triggers);
+    }
+}
+
+pub fn random_tick(world: &mut World, position: BlockPos) {
+    let state = world.get_block(position.0, position.1, position.2);
+    if power(state) > 0 && !world.block_ticks.is_scheduled(position) {
+        world.schedule_block_tick(position, 1);
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::dimension::Dimension;
+
+// Wo die Zeit gemessen wurde. Welt-Abschnitte liegen innerhalb der Tick-Abschnitte und werden getrennt aufgeführt;
+// Verbindungs-Threads laufen neben dem Tick, halten aber dieselben Sperren
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Tick(&'static str),
+    World(String, &'static str),
+    Connection(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Section {
+    time: Duration,
+    calls: u64,
+}
+
+#[derive(Debug)]
+struct Profile {
+    started: Instant,
+    ticks: u64,
+    tick_time: Duration,
+    worst_tick: Duration,
+    sections: BTreeMap<Key, Section>,
+}
+
+// Misst zwischen /debug start und /debug stop, wie lange die Teile des Servers brauchen. Solange er nicht läuft,
+// kostet eine Messung nur das Lesen von running
+#[derive(Debug, Default)]
+pub struct Profiler {
+    running: AtomicBool,
+    profile: Mutex<Option<Profile>>,
+}
+
+// Bucht beim Verlassen die Zeit seit dem Betreten
+pub struct Scope<'a> {
+    profiler: &'a Profiler,
+    key: Option<Key>,
+    started: Instant,
+}
+
+impl Drop for Scope<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.profiler.record(key, self.started.elapsed());
+        }
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    pub fn running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    // false, wenn schon gemessen wird
+    pub fn start(&self) -> bool {
+        let mut profile = self.profile.lock().unwrap();
+        if profile.is_some() {
+            return false;
+        }
+        *profile = Some(Profile { started: Instant::now(), ticks: 0, tick_time: Duration::ZERO, worst_tick: Duration::ZERO, sections: BTreeMap::new() });
+        self.running.store(true, Ordering::Relaxed);
+        true
+    }
+
+    // Beendet die Messung und gibt den Bericht zurück
+    pub fn stop(&self) -> Option<Vec<String>> {
+        self.running.store(false, Ordering::Relaxed);
+        self.profile.lock().unwrap().take().map(|profile| report(&profile))
+    }
+
+    pub fn report(&self) -> Option<Vec<String>> {
+        self.profile.lock().unwrap().as_ref().map(report)
+    }
+
+    fn scope_for(&self, key: Key) -> Scope<'_> {
+        Scope { profiler: self, key: self.running().then_some(key), started: Instant::now() }
+    }
+
+    // Ein Teil der Tick-Schleife
+    pub fn time<T>(&self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let _scope = self.scope_for(Key::Tick(name));
+        f()
+    }
+
+    // Innerhalb eines Tick-Abschnitts für eine einzelne Welt
+    pub fn world(&self, name: &'static str, dimension: Dimension) -> Scope<'_> {
+        if !self.running() {
+            return Scope { profiler: self, key: None, started: Instant::now() };
+        }
+        self.scope_for(Key::World(dimension.name().to_string(), name))
+    }
+
+    // Auf einem Verbindungs-Thread
+    pub fn connection(&self, name: &'static str) -> Scope<'_> {
+        self.scope_for(Key::Connection(name))
+    }
+
+    pub fn record_tick(&self, duration: Duration) {
+        if !self.running() {
+            return;
+        }
+        if let Some(profile) = self.profile.lock().unwrap().as_mut() {
+            profile.ticks += 1;
+            profile.tick_time += duration;
+            profile.worst_tick = profile.worst_tick.max(duration);
+        }
+    }
+
+    fn record(&self, key: Key, duration: Duration) {
+        if let Some(profile) = self.profile.lock().unwrap().as_mut() {
+            let section = profile.sections.entry(key).or_default();
+            section.time += duration;
+            section.calls += 1;
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Je Bereich nach Zeit absteigend, alles in Millisekunden pro Tick
+fn report(profile: &Profile) -> Vec<String> {
+    let ticks = profile.ticks.max(1) as f64;
+    let per_tick = |time: Duration| millis(time) / ticks;
+    let elapsed = profile.started.elapsed().as_secs_f64();
+    let mut lines = vec![format!(
+        "Profiled {} ticks in {:.1}s ({:.1} TPS): {:.2} ms per tick on average, worst {:.2} ms",
+        profile.ticks,
+        elapsed,
+        profile.ticks as f64 / elapsed.max(0.001),
+        per_tick(profile.tick_time),
+        millis(profile.worst_tick)
+    )];
+
+    let mut tick: Vec<(&str, Duration)> = Vec::new();
+    let mut worlds: BTreeMap<&str, Vec<(&str, Duration)>> = BTreeMap::new();
+    let mut connections: Vec<(&str, Section)> = Vec::new();
+    for (key, section) in &profile.sections {
+        match key {
+            Key::Tick(name) => tick.push((*name, section.time)),
+            Key::World(world, name) => worlds.entry(world).or_default().push((*name, section.time)),
+            Key::Connection(name) => connections.push((*name, *section)),
+        }
+    }
+    let total = profile.tick_time.max(Duration::from_nanos(1));
+    let measured: Duration = tick.iter().map(|(_, time)| *time).sum();
+    tick.push(("other", profile.tick_time.saturating_sub(measured)));
+    tick.sort_by_key(|(_, time)| std::cmp::Reverse(*time));
+    lines.push("Tick by subsystem:".to_string());
+    for (name, time) in tick {
+        lines.push(format!("  {}: {:.2} ms/tick ({:.1}%)", name, per_tick(time), time.as_secs_f64() * 100.0 / total.as_secs_f64()));
+    }
+
+    if !worlds.is_empty() {
+        lines.push("By world:".to_string());
+    }
+    for (world, mut sections) in worlds {
+        sections.sort_by_key(|(_, time)| std::cmp::Reverse(*time));
+        let sum: Duration = sections.iter().map(|(_, time)| *time).sum();
+        let parts: Vec<String> = sections.iter().map(|(name, time)| format!("{} {:.2}", name, per_tick(*time))).collect();
+        lines.push(format!("  {}: {:.2} ms/tick ({})", world, per_tick(sum), parts.join(", ")));
+    }
+
+    if !connections.is_empty() {
+        lines.push("Connection threads (alongside the tick, holding the same locks):".to_string());
+    }
+    connections.sort_by_key(|(_, section)| std::cmp::Reverse(section.time));
+    for (name, section) in connections {
+        let average = millis(section.time) / section.calls.max(1) as f64;
+        lines.push(format!("  {}: {:.2} ms/tick, {} calls averaging {:.3} ms", name, per_tick(section.time), section.calls, average));
+    }
+    lines
+}
@@ -0,0 +1,249 @@
+use rand::Rng;
+use uuid::Uuid;
+use crate::damage::{self, DamageSource, Hit};
+use crate::selector::{Target, PLAYER_TYPE};
+use crate::{block, dimension, end, entity, fire, fluid, motion, Mob, Player, Server, World};
+
+pub const ARROW: &str = "minecraft:arrow";
+// Grundschaden eines Pfeils, multipliziert mit seiner Geschwindigkeit
+pub const BASE_DAMAGE: f64 = 2.0;
+// Pro Tick wie AbstractArrow bei Vanilla
+const AIR_DRAG: f64 = 0.99;
+const WATER_DRAG: f64 = 0.6;
+const GRAVITY: f64 = 0.05;
+// Ein steckender Pfeil verschwindet nach einer Minute
+const DESPAWN_TICKS: u32 = 1200;
+// Um so viel größer als die Hitbox trifft ein Pfeil
+const HIT_MARGIN: f64 = 0.3;
+// Schrittweite für die Suche nach dem ersten festen Block auf der Flugbahn
+const RAY_STEP: f64 = 0.1;
+// Rückstoß je Stufe von Punch
+const PUNCH_KNOCKBACK: f64 = 0.6;
+
+type Vec3 = (f64, f64, f64);
+
+#[derive(Debug, Clone)]
+pub struct Arrow {
+    owner: Option<Uuid>,
+    // Erst wenn der Pfeil die Hitbox des Schützen verlassen hat, kann er ihn treffen
+    left_owner: bool,
+    pub damage: f64,
+    pub critical: bool,
+    // Flame: setzt getroffene Ziele in Brand
+    pub burning: bool,
+    punch: u32,
+    // Block, in dem der Pfeil steckt
+    stuck: Option<(i32, i32, i32)>,
+    life: u32,
+}
+
+impl Arrow {
+    pub fn new(owner: Option<Uuid>, damage: f64, critical: bool, punch: u32, burning: bool) -> Arrow {
+        Arrow { owner, left_owner: owner.is_none(), damage, critical, burning, punch, stuck: None, life: 0 }
+    }
+}
+
+struct ArrowHit {
+    target: Target,
+    // Spielername des Schützen, dem ein Abschuss auf der Anzeigetafel zählt
+    shooter: Option<String>,
+    amount: f32,
+    punch: u32,
+    burning: bool,
+    // Hinter dem Pfeil, damit der Rückstoß in Flugrichtung geht
+    source: Vec3,
+}
+
+fn length(v: Vec3) -> f64 {
+    (v.0 * v.0 + v.1 * v.1 + v.2 * v.2).sqrt()
+}
+
+// Yaw und Pitch in Flugrichtung, wie der Client sie für Pfeile erwartet
+fn rotation(velocity: Vec3) -> (f32, f32) {
+    let horizontal = (velocity.0 * velocity.0 + velocity.2 * velocity.2).sqrt();
+    (velocity.0.atan2(velocity.2).to_degrees() as f32, velocity.1.atan2(horizontal).to_degrees() as f32)
+}
+
+// Anteil der Strecke von from nach from + delta, an dem sie die Hitbox zuerst berührt
+pub fn intersect(from: Vec3, delta: Vec3, position: Vec3, (width, height): (f64, f64)) -> Option<f64> {
+    let half = width / 2.0 + HIT_MARGIN;
+    let min = [position.0 - half, position.1 - HIT_MARGIN, position.2 - half];
+    let max = [position.0 + half, position.1 + height + HIT_MARGIN, position.2 + half];
+    let (start, step) = ([from.0, from.1, from.2], [delta.0, delta.1, delta.2]);
+    let (mut enter, mut exit) = (0.0f64, 1.0f64);
+    for axis in 0..3 {
+        if step[axis].abs() < 1.0e-9 {
+            if start[axis] < min[axis] || start[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let (a, b) = ((min[axis] - start[axis]) / step[axis], (max[axis] - start[axis]) / step[axis]);
+        enter = enter.max(a.min(b));
+        exit = exit.min(a.max(b));
+        if enter > exit {
+            return None;
+        }
+    }
+    Some(enter)
+}
+
+// Erster fester Block auf der Strecke, mit dem Anteil der Strecke bis dorthin
+pub fn first_block(world: &World, from: Vec3, delta: Vec3) -> Option<(f64, (i32, i32, i32))> {
+    let steps = (length(delta) / RAY_STEP).ceil().max(1.0) as u32;
+    (1..=steps).find_map(|i| {
+        let t = i as f64 / steps as f64;
+        let (x, y, z) = ((from.0 + delta.0 * t).floor() as i32, (from.1 + delta.1 * t).floor() as i32, (from.2 + delta.2 * t).floor() as i32);
+        block::is_solid(world.get_block(x, y, z)).then_some((t, (x, y, z)))
+    })
+}
+
+// Schießt einen Pfeil ab; die Clients in Sichtweite bekommen ihn mit Geschwindigkeit und Flugrichtung
+pub fn shoot(players: &[Player], world: &mut World, position: Vec3, velocity: Vec3, arrow: Arrow) {
+    let mut mob = Mob::new(ARROW, position);
+    (mob.yaw, mob.pitch) = rotation(velocity);
+    mob.velocity = velocity;
+    mob.projectile = Some(arrow);
+    for viewer in players.iter().filter(|p| entity::in_view(p, world.dimension, position)) {
+        let _ = entity::send_mob(&viewer.connection, &mob);
+    }
+    world.entities.update(mob.id, position);
+    world.mobs.push(mob);
+}
+
+// Bewegt fliegende Pfeile, lässt sie in Blöcken stecken und trifft Spieler, Mobs und Endkristalle. Die Positionen
+// sendet der Tracker. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut hits = Vec::new();
+    let players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let _profile = server.profiler.world("entities", world.dimension);
+        let mut removed = Vec::new();
+        for index in 0..world.mobs.len() {
+            if world.mobs[index].projectile.is_some() && !fly(&players, &mut world, index, &mut hits) {
+                removed.push(index);
+            }
+        }
+        let dimension = world.dimension;
+        for &index in removed.iter().rev() {
+            let mob = world.mobs.remove(index);
+            world.entities.remove(mob.id);
+            for viewer in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+                let _ = entity::send_remove(&viewer.connection, &[mob.entity_id]);
+            }
+        }
+    }
+    drop(players);
+    for hit in hits {
+        match damage::damage_from(server, &hit.target, DamageSource::Arrow, hit.amount, hit.source) {
+            Hit::Hurt => {
+                motion::knockback(server, &hit.target, hit.source, motion::ATTACK_KNOCKBACK + hit.punch as f64 * PUNCH_KNOCKBACK);
+                if hit.burning {
+                    fire::ignite(server, &hit.target, fire::ARROW_TICKS);
+                }
+            }
+            Hit::Killed => {
+                if let Some(shooter) = &hit.shooter {
+                    let players = server.players.lock().unwrap();
+                    server.scoreboard.lock().unwrap().killed(&players, shooter, &hit.target);
+                }
+            }
+            Hit::Ignored => {}
+        }
+    }
+}
+
+// Ein Tick für einen Pfeil; false, wenn er verschwinden soll
+fn fly(players: &[Player], world: &mut World, index: usize, hits: &mut Vec<ArrowHit>) -> bool {
+    let dimension = world.dimension;
+    let mob = &world.mobs[index];
+    let Some(arrow) = mob.projectile.clone() else { return true };
+    let (position, velocity) = (mob.position, mob.velocity);
+    if let Some((x, y, z)) = arrow.stuck {
+        if block::is_solid(world.get_block(x, y, z)) {
+            let arrow = world.mobs[index].projectile.as_mut().unwrap();
+            arrow.life += 1;
+            return arrow.life < DESPAWN_TICKS;
+        }
+        // Der Block ist weg, der Pfeil fällt
+        world.mobs[index].projectile.as_mut().unwrap().stuck = None;
+    }
+    if position.1 < dimension.min_y() as f64 - 64.0 {
+        return false;
+    }
+    if !world.chunks.contains_key(&((position.0.floor() as i32) >> 4, (position.2.floor() as i32) >> 4)) {
+        return true;
+    }
+    let owner = arrow.owner.and_then(|uuid| players.iter().find(|p| p.uuid == uuid && p.dimension == dimension));
+    let left_owner = arrow.left_owner || owner.is_none_or(|p| {
+        let (width, height) = entity::hitbox(PLAYER_TYPE);
+        let half = width / 2.0 + 1.0;
+        let (dx, dz, dy) = (position.0 - p.position.0, position.2 - p.position.2, position.1 - p.position.1);
+        dx.abs() > half || dz.abs() > half || dy < -1.0 || dy > height + 1.0
+    });
+    let block_hit = first_block(world, position, velocity);
+    let reach = block_hit.map_or(1.0, |(t, _)| t);
+    let mut target: Option<(f64, Target)> = None;
+    let mut closer = |t: Option<f64>, hit: &dyn Fn() -> Target| {
+        if let Some(t) = t.filter(|&t| t <= reach && target.as_ref().is_none_or(|(best, _)| t < *best)) {
+            target = Some((t, hit()));
+        }
+    };
+    for p in players.iter().filter(|p| p.dimension == dimension && p.health > 0.0 && p.game_mode.is_tracked()) {
+        if !left_owner && arrow.owner == Some(p.uuid) {
+            continue;
+        }
+        closer(intersect(position, velocity, p.position, entity::hitbox(PLAYER_TYPE)), &|| Target::of_player(p));
+    }
+    for m in world.mobs.iter().filter(|m| entity::is_living(&m.mob_type) && m.health > 0.0 || m.mob_type == end::CRYSTAL) {
+        closer(intersect(position, velocity, m.position, entity::hitbox(&m.mob_type)), &|| Target::of_mob(m, dimension));
+    }
+    if let Some((_, target)) = target {
+        if target.game_mode.is_some_and(|mode| mode.is_invulnerable()) {
+            // Im Kreativmodus prallt der Pfeil ab
+            let mob = &mut world.mobs[index];
+            mob.velocity = (velocity.0 * -0.1, velocity.1 * -0.1, velocity.2 * -0.1);
+            mob.projectile.as_mut().unwrap().left_owner = true;
+            for viewer in players.iter().filter(|p| entity::in_view(p, dimension, position)) {
+                let _ = motion::send(&viewer.connection, mob.entity_id, mob.velocity);
+            }
+            return true;
+        }
+        let mut amount = (length(velocity) * arrow.damage).clamp(0.0, i32::MAX as f64).ceil() as i32;
+        if arrow.critical {
+            amount += rand::thread_rng().gen_range(0..amount / 2 + 2);
+        }
+        let source = (position.0 - velocity.0, position.1 - velocity.1, position.2 - velocity.2);
+        debug!("Pfeil trifft {} mit {} Schaden", target.name, amount);
+        let shooter = owner.map(|p| p.username.clone());
+        hits.push(ArrowHit { target, shooter, amount: amount as f32, punch: arrow.punch, burning: arrow.burning, source });
+        return false;
+    }
+    let mob = &mut world.mobs[index];
+    let arrow = mob.projectile.as_mut().unwrap();
+    arrow.left_owner = left_owner;
+    if let Some((t, block)) = block_hit {
+        // Stecken bleibt er knapp vor dem getroffenen Block
+        mob.position = (position.0 + velocity.0 * t * 0.95, position.1 + velocity.1 * t * 0.95, position.2 + velocity.2 * t * 0.95);
+        mob.velocity = (0.0, 0.0, 0.0);
+        arrow.stuck = Some(block);
+        arrow.critical = false;
+        let (id, entity_id, position) = (mob.id, mob.entity_id, mob.position);
+        world.entities.update(id, position);
+        for viewer in players.iter().filter(|p| entity::in_view(p, dimension, position)) {
+            let _ = entity::send_arrow_landed(&viewer.connection, entity_id);
+            let _ = motion::send(&viewer.connection, entity_id, (0.0, 0.0, 0.0));
+        }
+        return true;
+    }
+    let next = (position.0 + velocity.0, position.1 + velocity.1, position.2 + velocity.2);
+    let drag = if fluid::in_water(world, next) { WATER_DRAG } else { AIR_DRAG };
+    let mob = &mut world.mobs[index];
+    mob.position = next;
+    mob.velocity = (velocity.0 * drag, velocity.1 * drag - GRAVITY, velocity.2 * drag);
+    (mob.yaw, mob.pitch) = rotation(mob.velocity);
+    let id = mob.id;
+    world.entities.update(id, next);
+    true
+}
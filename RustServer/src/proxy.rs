@@ -0,0 +1,175 @@
+use std::io::{Cursor, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use byteorder::ReadBytesExt;
+use uuid::Uuid;
+use crate::protocol::types::{read_array, read_optional, read_string_from_cursor, read_uuid, read_varint_from_cursor, write_array, write_optional, write_string_to_vec};
+use crate::protocol::ProtocolError;
+use crate::{hash, json};
+
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+// Version 1 (MODERN_DEFAULT): Adresse, UUID, Name und Eigenschaften, ohne Signaturschlüssel
+pub const VELOCITY_FORWARDING_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileProperty {
+    pub name: String,
+    pub value: String,
+    pub signature: Option<String>,
+}
+
+// Profil-Eigenschaften wie in Login Success und Player Info Update
+pub fn write_properties(properties: &[ProfileProperty]) -> Vec<u8> {
+    write_array(properties, |property| {
+        let mut data = write_string_to_vec(&property.name);
+        data.extend(write_string_to_vec(&property.value));
+        data.extend(write_optional(property.signature.as_ref(), |signature| write_string_to_vec(signature)));
+        data
+    })
+}
+
+// Vom Proxy weitergereichte Spielerdaten
+#[derive(Debug, Clone)]
+pub struct ForwardedInfo {
+    pub host: String,
+    pub address: IpAddr,
+    pub uuid: Uuid,
+    pub username: Option<String>,
+    pub properties: Vec<ProfileProperty>,
+}
+
+pub fn parse_properties(value: &json::Json) -> Result<Vec<ProfileProperty>, String> {
+    let items = value.as_array().ok_or("Profil-Eigenschaften sind kein Array")?;
+    items
+        .iter()
+        .map(|item| {
+            let name = item.get("name").and_then(|v| v.as_str()).ok_or("Eigenschaft ohne Namen")?;
+            let value = item.get("value").and_then(|v| v.as_str()).ok_or("Eigenschaft ohne Wert")?;
+            Ok(ProfileProperty {
+                name: name.to_string(),
+                value: value.to_string(),
+                signature: item.get("signature").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            })
+        })
+        .collect()
+}
+
+// BungeeCord-Format der Handshake-Adresse: host\0ip\0uuid\0properties-json
+pub fn parse_bungeecord(server_address: &str) -> Result<ForwardedInfo, String> {
+    let parts: Vec<&str> = server_address.split('\0').collect();
+    if parts.len() < 3 {
+        return Err("Handshake enthält keine BungeeCord-Weiterleitungsdaten".to_string());
+    }
+    let address = parts[1].parse::<IpAddr>().map_err(|_| format!("Ungültige weitergeleitete IP: {}", parts[1]))?;
+    let uuid = Uuid::parse_str(parts[2]).map_err(|_| format!("Ungültige weitergeleitete UUID: {}", parts[2]))?;
+    let properties = match parts.get(3) {
+        Some(text) if !text.is_empty() => parse_properties(&json::parse(text)?)?,
+        _ => Vec::new(),
+    };
+    Ok(ForwardedInfo {
+        host: parts[0].to_string(),
+        address,
+        uuid,
+        username: None,
+        properties,
+    })
+}
+
+// Antwort auf die velocity:player_info-Anfrage: 32 Byte HMAC-SHA256 über den Rest, dann die Spielerdaten
+pub fn parse_velocity(secret: &[u8], data: &[u8]) -> Result<ForwardedInfo, String> {
+    if data.len() < 32 {
+        return Err("Velocity-Antwort zu kurz".to_string());
+    }
+    let (signature, payload) = data.split_at(32);
+    if !hash::constant_time_eq(signature, &hash::hmac_sha256(secret, payload)) {
+        return Err("Ungültige Velocity-Signatur (falsches forwarding secret?)".to_string());
+    }
+    let mut cursor = Cursor::new(payload.to_vec());
+    let version = read_varint_from_cursor(&mut cursor).map_err(|_| "Velocity-Version fehlt".to_string())?;
+    if version < VELOCITY_FORWARDING_VERSION as i32 {
+        return Err(format!("Nicht unterstützte Velocity-Weiterleitungsversion {}", version));
+    }
+    let address = read_string_from_cursor(&mut cursor)?;
+    let address = address.parse::<IpAddr>().map_err(|_| format!("Ungültige weitergeleitete IP: {}", address))?;
+    let uuid = read_uuid(&mut cursor).map_err(|_| "Weitergeleitete UUID fehlt".to_string())?;
+    let username = read_string_from_cursor(&mut cursor)?;
+    let properties = read_array(&mut cursor, |cursor| {
+        let name = read_string_from_cursor(cursor)?;
+        let value = read_string_from_cursor(cursor)?;
+        let signature = read_optional(cursor, read_string_from_cursor)?;
+        Ok::<_, ProtocolError>(ProfileProperty { name, value, signature })
+    })?;
+    Ok(ForwardedInfo {
+        host: String::new(),
+        address,
+        uuid,
+        username: Some(username),
+        properties,
+    })
+}
+
+const PROXY_V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+const PROXY_V1_MAX_LENGTH: usize = 107;
+
+// Liest einen PROXY-Protocol-Header (v1 oder v2) und liefert die echte Client-Adresse
+pub fn read_proxy_header(stream: &mut impl Read, peer: SocketAddr) -> Result<SocketAddr, String> {
+    let mut start = [0u8; 12];
+    stream.read_exact(&mut start).map_err(|e| format!("PROXY-Header fehlt: {}", e))?;
+    if start == PROXY_V2_SIGNATURE {
+        read_proxy_v2(stream, peer)
+    } else if start.starts_with(b"PROXY ") {
+        read_proxy_v1(stream, &start, peer)
+    } else {
+        Err("Verbindung ohne PROXY-Header".to_string())
+    }
+}
+
+fn read_proxy_v1(stream: &mut impl Read, start: &[u8], peer: SocketAddr) -> Result<SocketAddr, String> {
+    let mut line = start.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= PROXY_V1_MAX_LENGTH {
+            return Err("PROXY-v1-Header zu lang".to_string());
+        }
+        let byte = stream.read_u8().map_err(|e| format!("PROXY-v1-Header unvollständig: {}", e))?;
+        line.push(byte);
+    }
+    let line = String::from_utf8(line).map_err(|_| "PROXY-v1-Header ist kein ASCII".to_string())?;
+    let parts: Vec<&str> = line.trim_end().split(' ').collect();
+    match parts.get(1) {
+        Some(&"UNKNOWN") => Ok(peer),
+        Some(&"TCP4") | Some(&"TCP6") if parts.len() == 6 => {
+            let ip = parts[2].parse::<IpAddr>().map_err(|_| format!("Ungültige Quell-IP im PROXY-Header: {}", parts[2]))?;
+            let port = parts[4].parse::<u16>().map_err(|_| format!("Ungültiger Quell-Port im PROXY-Header: {}", parts[4]))?;
+            Ok(SocketAddr::new(ip, port))
+        }
+        _ => Err(format!("Ungültiger PROXY-v1-Header: {}", line.trim_end())),
+    }
+}
+
+fn read_proxy_v2(stream: &mut impl Read, peer: SocketAddr) -> Result<SocketAddr, String> {
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).map_err(|e| format!("PROXY-v2-Header unvollständig: {}", e))?;
+    let (version_command, family) = (header[0], header[1]);
+    let length = u16::from_be_bytes([header[2], header[3]]) as usize;
+    if version_command >> 4 != 2 {
+        return Err(format!("Nicht unterstützte PROXY-Version {}", version_command >> 4));
+    }
+    let mut body = vec![0u8; length];
+    stream.read_exact(&mut body).map_err(|e| format!("PROXY-v2-Adressen unvollständig: {}", e))?;
+    // LOCAL-Befehl (z.B. Health-Checks des Load Balancers): echte Verbindungsadresse verwenden
+    if version_command & 0x0F == 0 {
+        return Ok(peer);
+    }
+    match family >> 4 {
+        1 if body.len() >= 12 => {
+            let ip = Ipv4Addr::new(body[0], body[1], body[2], body[3]);
+            Ok(SocketAddr::new(IpAddr::V4(ip), u16::from_be_bytes([body[8], body[9]])))
+        }
+        2 if body.len() >= 36 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&body[..16]);
+            Ok(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(octets)), u16::from_be_bytes([body[32], body[33]])))
+        }
+        0 => Ok(peer),
+        _ => Err(format!("Nicht unterstützte PROXY-Adressfamilie 0x{:02X}", family)),
+    }
+}
@@ -0,0 +1,650 @@
+use crate::item::{self, ItemStack, Trim};
+use crate::json::Json;
+use crate::text::Text;
+
+// Liste von erlaubten Items für einen Slot (z.B. alle Bretterarten)
+pub type Ingredient = Vec<String>;
+
+#[derive(Debug, Clone)]
+pub enum Recipe {
+    Shaped {
+        width: usize,
+        height: usize,
+        ingredients: Vec<Option<Ingredient>>,
+        result: ItemStack,
+    },
+    Shapeless {
+        ingredients: Vec<Ingredient>,
+        result: ItemStack,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct SmeltingRecipe {
+    pub input: Ingredient,
+    pub result: ItemStack,
+}
+
+#[derive(Debug, Clone)]
+pub struct StonecuttingRecipe {
+    pub input: Ingredient,
+    pub result: ItemStack,
+}
+
+// Am Schmiedetisch: Vorlage, Basis-Item und Zusatz
+#[derive(Debug, Clone)]
+pub enum SmithingRecipe {
+    // Das Basis-Item wird samt seinen Komponenten zum Ergebnis, z.B. Diamant- zu Netheriteschwert
+    Transform {
+        template: Ingredient,
+        base: Ingredient,
+        addition: Ingredient,
+        result: Box<ItemStack>,
+    },
+    // Besatz mit dem Muster der Vorlage und dem Material des Zusatzes
+    Trim {
+        template: Ingredient,
+        base: Ingredient,
+        addition: Ingredient,
+    },
+}
+
+// Rezepte mit ihrer ID; ein Datenpaket ersetzt ein eingebautes Rezept gleicher ID
+pub struct RecipeRegistry {
+    crafting: Vec<(String, Recipe)>,
+    smelting: Vec<(String, SmeltingRecipe)>,
+    stonecutting: Vec<(String, StonecuttingRecipe)>,
+    smithing: Vec<(String, SmithingRecipe)>,
+}
+
+const WOOD_TYPES: [&str; 6] = ["oak", "spruce", "birch", "jungle", "acacia", "dark_oak"];
+// Steinsorten mit dem, was die Steinsäge daraus macht (Name, Anzahl)
+const STONECUTTING: &[(&str, &[(&str, u8)])] = &[
+    ("stone", &[("stone_slab", 2), ("stone_stairs", 1), ("stone_bricks", 1), ("stone_brick_slab", 2), ("stone_brick_stairs", 1), ("stone_brick_wall", 1), ("chiseled_stone_bricks", 1)]),
+    ("cobblestone", &[("cobblestone_slab", 2), ("cobblestone_stairs", 1), ("cobblestone_wall", 1)]),
+    ("granite", &[("polished_granite", 1), ("granite_slab", 2), ("granite_stairs", 1), ("granite_wall", 1), ("polished_granite_slab", 2), ("polished_granite_stairs", 1)]),
+    ("diorite", &[("polished_diorite", 1), ("diorite_slab", 2), ("diorite_stairs", 1), ("diorite_wall", 1), ("polished_diorite_slab", 2), ("polished_diorite_stairs", 1)]),
+    ("andesite", &[("polished_andesite", 1), ("andesite_slab", 2), ("andesite_stairs", 1), ("andesite_wall", 1), ("polished_andesite_slab", 2), ("polished_andesite_stairs", 1)]),
+    ("cobbled_deepslate", &[("polished_deepslate", 1), ("cobbled_deepslate_slab", 2), ("cobbled_deepslate_stairs", 1), ("cobbled_deepslate_wall", 1), ("deepslate_bricks", 1), ("deepslate_tiles", 1)]),
+];
+const NETHERITE_UPGRADES: [&str; 9] = ["helmet", "chestplate", "leggings", "boots", "sword", "pickaxe", "axe", "shovel", "hoe"];
+const TRIM_TEMPLATE_SUFFIX: &str = "_armor_trim_smithing_template";
+const ARMOR_MATERIALS: [&str; 6] = ["leather", "chainmail", "iron", "golden", "diamond", "netherite"];
+const ARMOR_PIECES: [&str; 4] = ["helmet", "chestplate", "leggings", "boots"];
+
+fn ingredient(items: &[&str]) -> Ingredient {
+    items.iter().map(|i| format!("minecraft:{}", i)).collect()
+}
+
+fn planks() -> Ingredient {
+    WOOD_TYPES.iter().map(|w| format!("minecraft:{}_planks", w)).collect()
+}
+
+fn result(item: &str, count: u8) -> ItemStack {
+    ItemStack::new(&format!("minecraft:{}", item), count)
+}
+
+fn id(name: &str) -> String {
+    format!("minecraft:{}", name)
+}
+
+// Ersetzt den Eintrag mit derselben ID oder hängt ihn an
+fn insert<T>(list: &mut Vec<(String, T)>, id: &str, entry: T) {
+    match list.iter_mut().find(|(existing, _)| existing == id) {
+        Some((_, existing)) => *existing = entry,
+        None => list.push((id.to_string(), entry)),
+    }
+}
+
+impl RecipeRegistry {
+    pub fn new() -> RecipeRegistry {
+        RecipeRegistry {
+            crafting: Vec::new(),
+            smelting: Vec::new(),
+            stonecutting: Vec::new(),
+            smithing: Vec::new(),
+        }
+    }
+
+    pub fn with_defaults() -> RecipeRegistry {
+        let mut registry = RecipeRegistry::new();
+        for wood in WOOD_TYPES {
+            let planks = format!("{}_planks", wood);
+            registry.add_shapeless(&id(&planks), vec![ingredient(&[&format!("{}_log", wood)])], result(&planks, 4));
+        }
+        registry.add_shaped(&id("stick"), &["#", "#"], &[('#', planks())], result("stick", 4));
+        registry.add_shaped(&id("crafting_table"), &["##", "##"], &[('#', planks())], result("crafting_table", 1));
+        registry.add_shaped(&id("furnace"), &["###", "# #", "###"], &[('#', ingredient(&["cobblestone"]))], result("furnace", 1));
+        registry.add_shaped(&id("chest"), &["###", "# #", "###"], &[('#', planks())], result("chest", 1));
+        registry.add_shaped(&id("torch"), &["C", "S"], &[('C', ingredient(&["coal", "charcoal"])), ('S', ingredient(&["stick"]))], result("torch", 4));
+        for (material, tool) in [("cobblestone", "stone"), ("iron_ingot", "iron"), ("diamond", "diamond")] {
+            let keys = [('X', ingredient(&[material])), ('S', ingredient(&["stick"]))];
+            for (kind, pattern) in [("pickaxe", &["XXX", " S ", " S "][..]), ("axe", &["XX", "XS", " S"]), ("shovel", &["X", "S", "S"]), ("sword", &["X", "X", "S"])] {
+                let name = format!("{}_{}", tool, kind);
+                registry.add_shaped(&id(&name), pattern, &keys, result(&name, 1));
+            }
+        }
+
+        registry.add_smelting(&id("iron_ingot"), ingredient(&["iron_ore", "raw_iron"]), result("iron_ingot", 1));
+        registry.add_smelting(&id("gold_ingot"), ingredient(&["gold_ore", "raw_gold"]), result("gold_ingot", 1));
+        registry.add_smelting(&id("glass"), ingredient(&["sand"]), result("glass", 1));
+        registry.add_smelting(&id("stone"), ingredient(&["cobblestone"]), result("stone", 1));
+        let logs: Vec<&str> = vec!["oak_log", "spruce_log", "birch_log", "jungle_log", "acacia_log", "dark_oak_log"];
+        registry.add_smelting(&id("charcoal"), ingredient(&logs), result("charcoal", 1));
+
+        for (input, results) in STONECUTTING {
+            for &(output, count) in *results {
+                registry.add_stonecutting(&id(&format!("{}_from_{}_stonecutting", output, input)), ingredient(&[input]), result(output, count));
+            }
+        }
+        for kind in NETHERITE_UPGRADES {
+            let upgrade = SmithingRecipe::Transform {
+                template: ingredient(&["netherite_upgrade_smithing_template"]),
+                base: ingredient(&[&format!("diamond_{}", kind)]),
+                addition: ingredient(&["netherite_ingot"]),
+                result: Box::new(result(&format!("netherite_{}", kind), 1)),
+            };
+            registry.add_smithing(&id(&format!("netherite_{}_smithing", kind)), upgrade);
+        }
+        // Wie #minecraft:trimmable_armor und #minecraft:trim_materials
+        let mut armor: Ingredient = ARMOR_MATERIALS.iter().flat_map(|material| ARMOR_PIECES.iter().map(move |piece| format!("minecraft:{}_{}", material, piece))).collect();
+        armor.push(id("turtle_helmet"));
+        let materials: Ingredient = item::TRIM_MATERIALS.iter().map(|(_, item)| item.to_string()).collect();
+        for (pattern, _, _) in item::TRIM_PATTERNS {
+            let template = format!("{}{}", pattern, TRIM_TEMPLATE_SUFFIX);
+            let trim = SmithingRecipe::Trim { template: vec![template.clone()], base: armor.clone(), addition: materials.clone() };
+            registry.add_smithing(&format!("{}_smithing_trim", template), trim);
+        }
+        registry
+    }
+
+    pub fn add_shaped(&mut self, id: &str, pattern: &[&str], keys: &[(char, Ingredient)], result: ItemStack) {
+        let height = pattern.len();
+        let width = pattern.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut ingredients = Vec::with_capacity(width * height);
+        for row in pattern {
+            let chars: Vec<char> = row.chars().collect();
+            for x in 0..width {
+                let slot = chars.get(x).and_then(|c| keys.iter().find(|(k, _)| k == c)).map(|(_, i)| i.clone());
+                ingredients.push(slot);
+            }
+        }
+        insert(&mut self.crafting, id, Recipe::Shaped { width, height, ingredients, result });
+    }
+
+    pub fn add_shapeless(&mut self, id: &str, ingredients: Vec<Ingredient>, result: ItemStack) {
+        insert(&mut self.crafting, id, Recipe::Shapeless { ingredients, result });
+    }
+
+    pub fn add_smelting(&mut self, id: &str, input: Ingredient, result: ItemStack) {
+        insert(&mut self.smelting, id, SmeltingRecipe { input, result });
+    }
+
+    pub fn add_stonecutting(&mut self, id: &str, input: Ingredient, result: ItemStack) {
+        insert(&mut self.stonecutting, id, StonecuttingRecipe { input, result });
+    }
+
+    pub fn add_smithing(&mut self, id: &str, recipe: SmithingRecipe) {
+        insert(&mut self.smithing, id, recipe);
+    }
+
+    pub fn crafting(&self) -> &[(String, Recipe)] {
+        &self.crafting
+    }
+
+    pub fn smelting(&self) -> &[(String, SmeltingRecipe)] {
+        &self.smelting
+    }
+
+    pub fn stonecutting(&self) -> &[(String, StonecuttingRecipe)] {
+        &self.stonecutting
+    }
+
+    pub fn smithing(&self) -> &[(String, SmithingRecipe)] {
+        &self.smithing
+    }
+
+    pub fn crafting_recipe(&self, id: &str) -> Option<&Recipe> {
+        self.crafting.iter().find(|(existing, _)| existing == id).map(|(_, recipe)| recipe)
+    }
+
+    // IDs aller Rezepte, in denen das Item als Zutat vorkommt
+    pub fn using(&self, item: &str) -> Vec<&str> {
+        let crafting = self.crafting.iter().filter(|(_, recipe)| recipe.ingredients().any(|ingredient| ingredient.iter().any(|i| i == item)));
+        let smelting = self.smelting.iter().filter(|(_, recipe)| recipe.input.iter().any(|i| i == item));
+        crafting.map(|(id, _)| id.as_str()).chain(smelting.map(|(id, _)| id.as_str())).collect()
+    }
+
+    // Berechnet das Ergebnis eines quadratischen Crafting-Grids (2x2 oder 3x3)
+    pub fn craft(&self, grid: &[ItemStack], grid_size: usize) -> Option<ItemStack> {
+        let trimmed = trim_grid(grid, grid_size)?;
+        self.crafting.iter().find(|(_, recipe)| recipe.matches(&trimmed)).map(|(_, recipe)| recipe.result().clone())
+    }
+
+    pub fn smelt(&self, input: &ItemStack) -> Option<ItemStack> {
+        if input.is_empty() {
+            return None;
+        }
+        self.smelting.iter().find(|(_, r)| r.input.contains(&input.item)).map(|(_, r)| r.result.clone())
+    }
+
+    // Die Auswahl der Steinsäge für ein Item, sortiert wie beim Client nach dem Übersetzungsschlüssel des Ergebnisses;
+    // der Client schickt den Index in dieser Liste
+    pub fn stonecutting_for(&self, input: &ItemStack) -> Vec<&StonecuttingRecipe> {
+        if input.is_empty() {
+            return Vec::new();
+        }
+        let mut recipes: Vec<&StonecuttingRecipe> = self.stonecutting.iter().map(|(_, r)| r).filter(|r| r.input.contains(&input.item)).collect();
+        recipes.sort_by_cached_key(|r| item::translation_key(&r.result.item));
+        recipes
+    }
+
+    // Ergebnis des Schmiedetisches; ein Besatz, den das Item schon trägt, ergibt nichts
+    pub fn smith(&self, template: &ItemStack, base: &ItemStack, addition: &ItemStack) -> Option<ItemStack> {
+        let recipe = self.smithing.iter().map(|(_, r)| r).find(|r| {
+            let (t, b, a) = r.ingredients();
+            t.contains(&template.item) && b.contains(&base.item) && a.contains(&addition.item)
+        })?;
+        match recipe {
+            SmithingRecipe::Transform { result, .. } => Some(ItemStack { components: base.components.clone(), ..(**result).clone() }),
+            SmithingRecipe::Trim { .. } => {
+                let pattern = template.item.strip_suffix(TRIM_TEMPLATE_SUFFIX).filter(|pattern| item::TRIM_PATTERNS.iter().any(|(p, _, _)| p == pattern))?;
+                let material = item::TRIM_MATERIALS.iter().find(|(_, item)| *item == addition.item)?.0;
+                let trim = Trim { material: material.to_string(), pattern: pattern.to_string() };
+                if base.components.trim.as_ref() == Some(&trim) {
+                    return None;
+                }
+                let mut result = ItemStack { count: 1, ..base.clone() };
+                result.components.trim = Some(trim);
+                Some(result)
+            }
+        }
+    }
+
+    // Alle Items, die in Vorlagen-, Basis- bzw. Zusatzslot des Schmiedetisches passen
+    pub fn smithing_inputs(&self) -> [Ingredient; 3] {
+        let mut inputs: [Ingredient; 3] = Default::default();
+        for (_, recipe) in &self.smithing {
+            let (template, base, addition) = recipe.ingredients();
+            for (slot, ingredient) in inputs.iter_mut().zip([template, base, addition]) {
+                for item in ingredient {
+                    if !slot.contains(item) {
+                        slot.push(item.clone());
+                    }
+                }
+            }
+        }
+        inputs
+    }
+}
+
+impl SmithingRecipe {
+    pub fn ingredients(&self) -> (&Ingredient, &Ingredient, &Ingredient) {
+        match self {
+            SmithingRecipe::Transform { template, base, addition, .. } | SmithingRecipe::Trim { template, base, addition } => (template, base, addition),
+        }
+    }
+}
+
+impl Recipe {
+    pub fn result(&self) -> &ItemStack {
+        match self {
+            Recipe::Shaped { result, .. } | Recipe::Shapeless { result, .. } => result,
+        }
+    }
+
+    fn ingredients(&self) -> impl Iterator<Item = &Ingredient> {
+        let (shaped, shapeless) = match self {
+            Recipe::Shaped { ingredients, .. } => (Some(ingredients), None),
+            Recipe::Shapeless { ingredients, .. } => (None, Some(ingredients)),
+        };
+        shaped.into_iter().flatten().flatten().chain(shapeless.into_iter().flatten())
+    }
+
+    // Gitterplätze der Zutaten in einem quadratischen Gitter, oben links beginnend; None, wenn das Rezept nicht
+    // hineinpasst
+    pub fn layout(&self, grid_size: usize) -> Option<Vec<(usize, &Ingredient)>> {
+        match self {
+            Recipe::Shaped { width, height, ingredients, .. } => (*width <= grid_size && *height <= grid_size).then(|| {
+                ingredients.iter().enumerate().filter_map(|(i, ingredient)| ingredient.as_ref().map(|ingredient| (i / width * grid_size + i % width, ingredient))).collect()
+            }),
+            Recipe::Shapeless { ingredients, .. } => (ingredients.len() <= grid_size * grid_size).then(|| ingredients.iter().enumerate().collect()),
+        }
+    }
+
+    fn matches(&self, trimmed: &TrimmedGrid) -> bool {
+        match self {
+            Recipe::Shaped { width, height, ingredients, .. } => {
+                if *width != trimmed.width || *height != trimmed.height {
+                    return false;
+                }
+                shaped_matches(ingredients, trimmed, false) || shaped_matches(ingredients, trimmed, true)
+            }
+            Recipe::Shapeless { ingredients, .. } => {
+                let items: Vec<&ItemStack> = trimmed.slots.iter().filter(|s| !s.is_empty()).collect();
+                if items.len() != ingredients.len() {
+                    return false;
+                }
+                let mut used = vec![false; items.len()];
+                ingredients.iter().all(|ingredient| {
+                    match items.iter().enumerate().position(|(i, item)| !used[i] && ingredient.contains(&item.item)) {
+                        Some(i) => {
+                            used[i] = true;
+                            true
+                        }
+                        None => false,
+                    }
+                })
+            }
+        }
+    }
+}
+
+struct TrimmedGrid {
+    width: usize,
+    height: usize,
+    slots: Vec<ItemStack>,
+}
+
+fn trim_grid(grid: &[ItemStack], grid_size: usize) -> Option<TrimmedGrid> {
+    if grid.len() != grid_size * grid_size {
+        return None;
+    }
+    let filled: Vec<(usize, usize)> = (0..grid.len())
+        .filter(|&i| !grid[i].is_empty())
+        .map(|i| (i % grid_size, i / grid_size))
+        .collect();
+    let min_x = filled.iter().map(|(x, _)| *x).min()?;
+    let max_x = filled.iter().map(|(x, _)| *x).max()?;
+    let min_y = filled.iter().map(|(_, y)| *y).min()?;
+    let max_y = filled.iter().map(|(_, y)| *y).max()?;
+    let mut slots = Vec::new();
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            slots.push(grid[y * grid_size + x].clone());
+        }
+    }
+    Some(TrimmedGrid {
+        width: max_x - min_x + 1,
+        height: max_y - min_y + 1,
+        slots,
+    })
+}
+
+fn shaped_matches(ingredients: &[Option<Ingredient>], trimmed: &TrimmedGrid, mirrored: bool) -> bool {
+    for y in 0..trimmed.height {
+        for x in 0..trimmed.width {
+            let rx = if mirrored { trimmed.width - 1 - x } else { x };
+            let slot = &trimmed.slots[y * trimmed.width + x];
+            let ok = match &ingredients[y * trimmed.width + rx] {
+                Some(ingredient) => !slot.is_empty() && ingredient.contains(&slot.item),
+                None => slot.is_empty(),
+            };
+            if !ok {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+pub fn fuel_ticks(fuel: &ItemStack) -> Option<u32> {
+    if fuel.is_empty() {
+        return None;
+    }
+    let name = fuel.item.strip_prefix("minecraft:").unwrap_or(&fuel.item);
+    match name {
+        "coal" | "charcoal" => Some(1600),
+        "coal_block" => Some(16000),
+        "lava_bucket" => Some(20000),
+        "stick" => Some(100),
+        _ if name.ends_with("_planks") || name.ends_with("_log") => Some(300),
+        _ => None,
+    }
+}
+
+// Ab diesen Kosten lehnt der Amboss außerhalb des Kreativmodus ab ("Zu teuer!")
+const TOO_EXPENSIVE: u32 = 40;
+const ENCHANTED_BOOK: &str = "minecraft:enchanted_book";
+
+// Verzauberungen im Amboss: Name, Höchststufe, Kosten je Stufe und Gruppe der Items, auf die sie passen
+const ENCHANTMENT_RULES: &[(&str, u32, u32, &str)] = &[
+    ("minecraft:protection", 4, 1, "armor"),
+    ("minecraft:fire_protection", 4, 2, "armor"),
+    ("minecraft:feather_falling", 4, 2, "feet"),
+    ("minecraft:blast_protection", 4, 4, "armor"),
+    ("minecraft:projectile_protection", 4, 2, "armor"),
+    ("minecraft:respiration", 3, 4, "head"),
+    ("minecraft:aqua_affinity", 1, 4, "head"),
+    ("minecraft:thorns", 3, 8, "armor"),
+    ("minecraft:depth_strider", 3, 4, "feet"),
+    ("minecraft:frost_walker", 2, 4, "feet"),
+    ("minecraft:binding_curse", 1, 8, "equippable"),
+    ("minecraft:soul_speed", 3, 8, "feet"),
+    ("minecraft:swift_sneak", 3, 8, "legs"),
+    ("minecraft:sharpness", 5, 1, "sharp_weapon"),
+    ("minecraft:smite", 5, 2, "weapon"),
+    ("minecraft:bane_of_arthropods", 5, 2, "weapon"),
+    ("minecraft:knockback", 2, 2, "sword"),
+    ("minecraft:fire_aspect", 2, 4, "fire_aspect"),
+    ("minecraft:looting", 3, 4, "sword"),
+    ("minecraft:sweeping_edge", 3, 4, "sword"),
+    ("minecraft:efficiency", 5, 1, "mining"),
+    ("minecraft:silk_touch", 1, 8, "mining_loot"),
+    ("minecraft:unbreaking", 3, 2, "durability"),
+    ("minecraft:fortune", 3, 4, "mining_loot"),
+    ("minecraft:power", 5, 1, "bow"),
+    ("minecraft:punch", 2, 4, "bow"),
+    ("minecraft:flame", 1, 4, "bow"),
+    ("minecraft:infinity", 1, 8, "bow"),
+    ("minecraft:luck_of_the_sea", 3, 4, "fishing"),
+    ("minecraft:lure", 3, 4, "fishing"),
+    ("minecraft:loyalty", 3, 1, "trident"),
+    ("minecraft:impaling", 5, 2, "trident"),
+    ("minecraft:riptide", 3, 4, "trident"),
+    ("minecraft:channeling", 1, 8, "trident"),
+    ("minecraft:multishot", 1, 4, "crossbow"),
+    ("minecraft:quick_charge", 3, 2, "crossbow"),
+    ("minecraft:piercing", 4, 1, "crossbow"),
+    ("minecraft:density", 5, 1, "mace"),
+    ("minecraft:breach", 4, 2, "mace"),
+    ("minecraft:wind_burst", 3, 4, "mace"),
+    ("minecraft:mending", 1, 4, "durability"),
+    ("minecraft:vanishing_curse", 1, 8, "vanishable"),
+];
+
+// Verzauberungen derselben Gruppe schließen sich gegenseitig aus
+const EXCLUSIVE_SETS: &[&[&str]] = &[
+    &["minecraft:protection", "minecraft:fire_protection", "minecraft:blast_protection", "minecraft:projectile_protection"],
+    &["minecraft:sharpness", "minecraft:smite", "minecraft:bane_of_arthropods", "minecraft:impaling", "minecraft:density", "minecraft:breach"],
+    &["minecraft:silk_touch", "minecraft:fortune"],
+    &["minecraft:infinity", "minecraft:mending"],
+    &["minecraft:riptide", "minecraft:loyalty"],
+    &["minecraft:riptide", "minecraft:channeling"],
+    &["minecraft:multishot", "minecraft:piercing"],
+    &["minecraft:depth_strider", "minecraft:frost_walker"],
+];
+
+// Ob eine Verzauberung auf ein Item passt, nach den Gruppen aus ENCHANTMENT_RULES bzw. denen des Zaubertischs
+pub fn supports(group: &str, item: &str) -> bool {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+    let kind = name.rsplit('_').next().unwrap_or(name);
+    let armor = ["helmet", "chestplate", "leggings", "boots"].contains(&kind);
+    match group {
+        "armor" => armor,
+        "head" => kind == "helmet",
+        "chest" => kind == "chestplate",
+        "legs" => kind == "leggings",
+        "feet" => kind == "boots",
+        "equippable" => armor || ["elytra", "carved_pumpkin", "head", "skull"].contains(&kind),
+        "sword" => kind == "sword",
+        "sharp_weapon" => kind == "sword" || kind == "axe",
+        "weapon" => kind == "sword" || kind == "axe" || name == "mace",
+        "fire_aspect" => kind == "sword" || name == "mace",
+        "mining_loot" => ["pickaxe", "shovel", "axe", "hoe"].contains(&kind),
+        "mining" => ["pickaxe", "shovel", "axe", "hoe", "shears"].contains(&kind),
+        "durability" => item::max_damage(item).is_some(),
+        "vanishable" => item::max_damage(item).is_some() || ["compass", "recovery_compass", "carved_pumpkin", "head", "skull"].contains(&kind),
+        "fishing" => name == "fishing_rod",
+        "bow" | "crossbow" | "trident" | "mace" => name == group,
+        _ => false,
+    }
+}
+
+pub fn exclusive(a: &str, b: &str) -> bool {
+    EXCLUSIVE_SETS.iter().any(|set| set.contains(&a) && set.contains(&b))
+}
+
+// Materialien, mit denen ein Item im Amboss repariert werden darf
+fn is_repair_material(item: &str, material: &str) -> bool {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+    let material = material.strip_prefix("minecraft:").unwrap_or(material);
+    match name {
+        "elytra" => return material == "phantom_membrane",
+        "turtle_helmet" => return material == "turtle_scute",
+        "wolf_armor" => return material == "armadillo_scute",
+        "mace" => return material == "breeze_rod",
+        "shield" => return material.ends_with("_planks"),
+        _ => {}
+    }
+    match name.split('_').next() {
+        Some("wooden") => material.ends_with("_planks"),
+        Some("stone") => ["cobblestone", "cobbled_deepslate", "blackstone"].contains(&material),
+        Some("leather") => material == "leather",
+        Some("chainmail" | "iron") => material == "iron_ingot",
+        Some("golden") => material == "gold_ingot",
+        Some("diamond") => material == "diamond",
+        Some("netherite") => material == "netherite_ingot",
+        _ => false,
+    }
+}
+
+// Klartext eines gespeicherten Namens, um ihn mit dem Namen aus Rename Item zu vergleichen
+fn plain_text(text: &Json) -> String {
+    match text {
+        Json::String(text) => text.clone(),
+        _ => {
+            let mut plain = text.get("text").and_then(Json::as_str).unwrap_or_default().to_string();
+            for child in text.get("extra").and_then(Json::as_array).into_iter().flatten() {
+                plain.push_str(&plain_text(child));
+            }
+            plain
+        }
+    }
+}
+
+// Ergebnis des Ambosses: das neue Item, die Kosten in Stufen und wie viele Reparaturmaterialien verbraucht werden.
+// 0 Materialien heißt, dass das rechte Item ganz verbraucht wird
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnvilResult {
+    pub result: ItemStack,
+    pub cost: u32,
+    pub materials: u8,
+}
+
+// Rechnet wie Vanilla: Reparatur mit Material oder einem zweiten gleichen Item, Verzauberungen vom zweiten Item
+// oder einem verzauberten Buch übernehmen, Umbenennen. Dazu kommt der Aufschlag früherer Arbeiten beider Items,
+// der sich für das Ergebnis verdoppelt. Ein leerer Name entfernt einen vorhandenen
+pub fn anvil(left: &ItemStack, right: &ItemStack, name: &str, creative: bool) -> Option<AnvilResult> {
+    if left.is_empty() {
+        return None;
+    }
+    let damage = |stack: &ItemStack| stack.components.damage.unwrap_or(0);
+    let right_penalty = if right.is_empty() { 0 } else { right.components.repair_cost };
+    let base = left.components.repair_cost.saturating_add(right_penalty);
+    let mut result = left.clone();
+    let mut cost: u32 = 0;
+    let mut materials = 0;
+    if !right.is_empty() {
+        let book = right.item == ENCHANTED_BOOK && !right.components.stored_enchantments.is_empty();
+        let max_damage = item::max_damage(&left.item);
+        if let Some(max) = max_damage.filter(|_| is_repair_material(&left.item, &right.item)) {
+            let mut repair = damage(&result).min(max / 4);
+            if repair == 0 {
+                return None;
+            }
+            while repair > 0 && materials < right.count {
+                let remaining = damage(&result) - repair;
+                result.components.damage = (remaining > 0).then_some(remaining);
+                cost += 1;
+                materials += 1;
+                repair = damage(&result).min(max / 4);
+            }
+        } else {
+            if !book && (right.item != left.item || max_damage.is_none()) {
+                return None;
+            }
+            if let Some(max) = max_damage.filter(|_| !book) {
+                let durability = (max - damage(left).min(max)) + (max - damage(right).min(max)) + max * 12 / 100;
+                let combined = max.saturating_sub(durability);
+                if combined < damage(&result) {
+                    result.components.damage = (combined > 0).then_some(combined);
+                    cost += 2;
+                }
+            }
+            let incoming = if book { &right.components.stored_enchantments } else { &right.components.enchantments };
+            let onto_book = result.item == ENCHANTED_BOOK;
+            let (mut applied, mut rejected) = (false, false);
+            for (enchantment, &level) in incoming {
+                let levels = if onto_book { &mut result.components.stored_enchantments } else { &mut result.components.enchantments };
+                let Some(&(_, max_level, anvil_cost, group)) = ENCHANTMENT_RULES.iter().find(|(n, ..)| n == enchantment) else { continue };
+                let current = levels.get(enchantment).copied().unwrap_or(0);
+                let level = if current == level { level + 1 } else { level.max(current) };
+                let mut fits = creative || onto_book || supports(group, &left.item);
+                for other in levels.keys().filter(|other| *other != enchantment) {
+                    if exclusive(enchantment, other) {
+                        fits = false;
+                        cost += 1;
+                    }
+                }
+                if !fits {
+                    rejected = true;
+                    continue;
+                }
+                applied = true;
+                let level = level.min(max_level);
+                levels.insert(enchantment.clone(), level);
+                let per_level = if book { (anvil_cost / 2).max(1) } else { anvil_cost };
+                cost += per_level * level;
+                if left.count > 1 {
+                    cost = TOO_EXPENSIVE;
+                }
+            }
+            if rejected && !applied {
+                return None;
+            }
+        }
+    }
+    let current_name = left.components.custom_name.as_ref().map(plain_text);
+    let rename_cost = if !name.trim().is_empty() {
+        if current_name.as_deref() == Some(name) {
+            0
+        } else {
+            result.components.custom_name = Some(Text::new(name).to_json());
+            1
+        }
+    } else if current_name.is_some() {
+        result.components.custom_name = None;
+        1
+    } else {
+        0
+    };
+    cost += rename_cost;
+    if cost == 0 {
+        return None;
+    }
+    let mut total = base.saturating_add(cost);
+    // Nur Umbenennen bleibt immer bezahlbar
+    if rename_cost == cost && total >= TOO_EXPENSIVE {
+        total = TOO_EXPENSIVE - 1;
+    }
+    if total >= TOO_EXPENSIVE && !creative {
+        return None;
+    }
+    let mut penalty = result.components.repair_cost.max(right_penalty);
+    if rename_cost != cost {
+        penalty = penalty.saturating_mul(2).saturating_add(1);
+    }
+    result.components.repair_cost = penalty;
+    Some(AnvilResult { result, cost: total, materials })
+}
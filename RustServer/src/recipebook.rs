@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use byteorder::ReadBytesExt;
+use crate::error::ServerError;
+use crate::item::{self, ItemStack};
+use crate::json::Json;
+use crate::protocol::clientbound;
+use crate::protocol::types::{read_bounded_string, read_varint_from_cursor, write_string_to_vec, write_varint_to_vec};
+use crate::protocol::version::ProtocolVersion;
+use crate::recipe::{Recipe, RecipeRegistry, SmeltingRecipe, SmithingRecipe, StonecuttingRecipe};
+use crate::{click, Connection, Player, Server};
+
+// Rezept-Serialisierer aus dem Register von 1.21.1; bis 1.20.4 schickt der Server ihren Namen
+const SHAPED: (i32, &str) = (0, "minecraft:crafting_shaped");
+const SHAPELESS: (i32, &str) = (1, "minecraft:crafting_shapeless");
+const SMELTING: (i32, &str) = (15, "minecraft:smelting");
+const STONECUTTING: (i32, &str) = (19, "minecraft:stonecutting");
+const SMITHING_TRANSFORM: (i32, &str) = (20, "minecraft:smithing_transform");
+const SMITHING_TRIM: (i32, &str) = (21, "minecraft:smithing_trim");
+// Reiter im Rezeptbuch; der Server kennt keine Kategorien und legt alles unter "Sonstiges"
+const CRAFTING_MISC: i32 = 3;
+const COOKING_MISC: i32 = 2;
+// Wie bei den meisten Ofenrezepten von Vanilla
+const SMELTING_EXPERIENCE: f32 = 0.1;
+const SMELTING_TIME: i32 = 200;
+// Aktion im Update-Recipe-Book-Paket
+const BOOK_INIT: i32 = 0;
+const BOOK_ADD: i32 = 1;
+// Werkbank, Ofen, Schmelzofen und Räucherofen
+const BOOKS: usize = 4;
+const MAX_ID_LENGTH: usize = 32767;
+
+// Freigeschaltete Rezepte eines Spielers, die davon noch nicht angesehenen und für jedes Buch, ob es offen ist und
+// nur Herstellbares zeigt
+#[derive(Debug, Clone, Default)]
+pub struct RecipeBook {
+    unlocked: BTreeSet<String>,
+    highlighted: BTreeSet<String>,
+    settings: [(bool, bool); BOOKS],
+    // Items, deren Rezepte schon freigeschaltet wurden; gilt nur für diese Sitzung
+    checked: HashSet<String>,
+}
+
+impl RecipeBook {
+    pub fn to_json(&self) -> Json {
+        let ids = |set: &BTreeSet<String>| Json::Array(set.iter().cloned().map(Json::String).collect());
+        let mut data = BTreeMap::new();
+        data.insert("recipes".to_string(), ids(&self.unlocked));
+        data.insert("to_be_displayed".to_string(), ids(&self.highlighted));
+        Json::Object(data)
+    }
+
+    fn write_settings(&self, packet_data: &mut Vec<u8>) {
+        for (open, filter) in self.settings {
+            packet_data.extend([open as u8, filter as u8]);
+        }
+    }
+}
+
+fn encode_ingredient(ingredient: &[String], version: ProtocolVersion) -> Option<Vec<u8>> {
+    let items: Vec<Vec<u8>> = ingredient.iter().filter(|item| item::protocol_id_in(item, version).is_some()).map(|item| ItemStack::new(item, 1).encode(version)).collect();
+    if items.is_empty() {
+        return None;
+    }
+    let mut data = write_varint_to_vec(items.len() as i32);
+    data.extend(items.concat());
+    Some(data)
+}
+
+fn encode_result(result: &ItemStack, version: ProtocolVersion) -> Option<Vec<u8>> {
+    item::protocol_id_in(&result.item, version).map(|_| result.encode(version))
+}
+
+// Gruppe und Reiter, um die sich das Rezeptbuch beim Sortieren kümmert
+fn header(category: i32) -> Vec<u8> {
+    let mut data = write_string_to_vec("");
+    data.extend(write_varint_to_vec(category));
+    data
+}
+
+// Rezepte mit Items, die diese Version nicht kennt, schickt der Server nicht
+fn encode_crafting(recipe: &Recipe, version: ProtocolVersion) -> Option<((i32, &'static str), Vec<u8>)> {
+    let mut data = header(CRAFTING_MISC);
+    match recipe {
+        Recipe::Shaped { width, height, ingredients, result } => {
+            data.extend(write_varint_to_vec(*width as i32));
+            data.extend(write_varint_to_vec(*height as i32));
+            for ingredient in ingredients {
+                match ingredient {
+                    Some(ingredient) => data.extend(encode_ingredient(ingredient, version)?),
+                    None => data.extend(write_varint_to_vec(0)),
+                }
+            }
+            data.extend(encode_result(result, version)?);
+            data.push(1);
+            Some((SHAPED, data))
+        }
+        Recipe::Shapeless { ingredients, result } => {
+            data.extend(write_varint_to_vec(ingredients.len() as i32));
+            for ingredient in ingredients {
+                data.extend(encode_ingredient(ingredient, version)?);
+            }
+            data.extend(encode_result(result, version)?);
+            Some((SHAPELESS, data))
+        }
+    }
+}
+
+fn encode_smelting(recipe: &SmeltingRecipe, version: ProtocolVersion) -> Option<((i32, &'static str), Vec<u8>)> {
+    let mut data = header(COOKING_MISC);
+    data.extend(encode_ingredient(&recipe.input, version)?);
+    data.extend(encode_result(&recipe.result, version)?);
+    data.extend(SMELTING_EXPERIENCE.to_be_bytes());
+    data.extend(write_varint_to_vec(SMELTING_TIME));
+    Some((SMELTING, data))
+}
+
+// Der Client rechnet damit die Auswahl der Steinsäge aus; nur mit derselben Liste versteht der Server, welches
+// Rezept gewählt wurde
+fn encode_stonecutting(recipe: &StonecuttingRecipe, version: ProtocolVersion) -> Option<((i32, &'static str), Vec<u8>)> {
+    let mut data = write_string_to_vec("");
+    data.extend(encode_ingredient(&recipe.input, version)?);
+    data.extend(encode_result(&recipe.result, version)?);
+    Some((STONECUTTING, data))
+}
+
+fn encode_smithing(recipe: &SmithingRecipe, version: ProtocolVersion) -> Option<((i32, &'static str), Vec<u8>)> {
+    let (template, base, addition) = recipe.ingredients();
+    let mut data = encode_ingredient(template, version)?;
+    data.extend(encode_ingredient(base, version)?);
+    data.extend(encode_ingredient(addition, version)?);
+    match recipe {
+        SmithingRecipe::Transform { result, .. } => {
+            data.extend(encode_result(result, version)?);
+            Some((SMITHING_TRANSFORM, data))
+        }
+        SmithingRecipe::Trim { .. } => Some((SMITHING_TRIM, data)),
+    }
+}
+
+// Update Recipes: alle Rezepte, die der Client im Buch und beim Vorhersagen des Crafting-Ergebnisses kennt
+pub fn send_recipes(connection: &Connection, recipes: &RecipeRegistry) -> Result<(), String> {
+    let version = connection.version;
+    let crafting = recipes.crafting().iter().filter_map(|(id, recipe)| Some((id, encode_crafting(recipe, version)?)));
+    let smelting = recipes.smelting().iter().filter_map(|(id, recipe)| Some((id, encode_smelting(recipe, version)?)));
+    let stonecutting = recipes.stonecutting().iter().filter_map(|(id, recipe)| Some((id, encode_stonecutting(recipe, version)?)));
+    let smithing = recipes.smithing().iter().filter_map(|(id, recipe)| Some((id, encode_smithing(recipe, version)?)));
+    let entries: Vec<_> = crafting.chain(smelting).chain(stonecutting).chain(smithing).collect();
+    let mut packet_data = write_varint_to_vec(entries.len() as i32);
+    for (id, ((serializer, name), data)) in entries {
+        if version.has_1_20_5_login() {
+            packet_data.extend(write_string_to_vec(id));
+            packet_data.extend(write_varint_to_vec(serializer));
+        } else {
+            packet_data.extend(write_string_to_vec(name));
+            packet_data.extend(write_string_to_vec(id));
+        }
+        packet_data.extend(data);
+    }
+    connection.send(clientbound::UPDATE_RECIPES, &packet_data)
+}
+
+fn write_ids<'a>(packet_data: &mut Vec<u8>, ids: impl ExactSizeIterator<Item = &'a String>) {
+    packet_data.extend(write_varint_to_vec(ids.len() as i32));
+    for id in ids {
+        packet_data.extend(write_string_to_vec(id));
+    }
+}
+
+// Beim Beitreten: das ganze Buch samt den noch hervorgehobenen Rezepten
+pub fn send_init(connection: &Connection, book: &RecipeBook) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(BOOK_INIT);
+    book.write_settings(&mut packet_data);
+    write_ids(&mut packet_data, book.unlocked.iter());
+    write_ids(&mut packet_data, book.highlighted.iter());
+    connection.send(clientbound::UPDATE_RECIPE_BOOK, &packet_data)
+}
+
+// Neu freigeschaltete Rezepte; der Client zeigt dafür eine Meldung
+fn send_add(connection: &Connection, book: &RecipeBook, ids: &[String]) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(BOOK_ADD);
+    book.write_settings(&mut packet_data);
+    write_ids(&mut packet_data, ids.iter());
+    connection.send(clientbound::UPDATE_RECIPE_BOOK, &packet_data)
+}
+
+// Nach dem Neuladen der Datenpakete bekommen alle die neuen Rezepte. Darf nur ohne gehaltene Sperren aufgerufen
+// werden
+pub fn resend(server: &Server) {
+    let players = server.players.lock().unwrap();
+    let datapacks = server.datapacks.lock().unwrap();
+    for player in players.iter() {
+        let _ = send_recipes(&player.connection, &datapacks.registries.recipes);
+    }
+}
+
+// Wie ein Vanilla-Rezept-Fortschritt: hat ein Spieler zum ersten Mal eine Zutat im Inventar, schalten sich alle
+// Rezepte frei, die sie verwenden
+fn unlock(player: &mut Player, recipes: &RecipeRegistry) {
+    let book = &mut player.recipe_book;
+    let new_items: Vec<&ItemStack> = player.inventory.slots().iter().filter(|stack| !stack.is_empty() && !book.checked.contains(&stack.item)).collect();
+    if new_items.is_empty() {
+        return;
+    }
+    let mut unlocked = Vec::new();
+    for stack in new_items {
+        book.checked.insert(stack.item.clone());
+        for id in recipes.using(&stack.item) {
+            if book.unlocked.insert(id.to_string()) {
+                book.highlighted.insert(id.to_string());
+                unlocked.push(id.to_string());
+            }
+        }
+    }
+    if !unlocked.is_empty() {
+        debug!("{} schaltet {} Rezept(e) frei", player.username, unlocked.len());
+        let _ = send_add(&player.connection, &player.recipe_book, &unlocked);
+    }
+}
+
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut players = server.players.lock().unwrap();
+    let datapacks = server.datapacks.lock().unwrap();
+    for player in players.iter_mut() {
+        unlock(player, &datapacks.registries.recipes);
+    }
+}
+
+// Change Recipe Book Settings: ein Buch wurde geöffnet, geschlossen oder sein Filter umgeschaltet
+pub fn handle_settings(players: &mut [Player], player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let book = read_varint_from_cursor(cursor)?;
+    let open = cursor.read_u8()?;
+    let filter = cursor.read_u8()?;
+    let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    if let Some(settings) = usize::try_from(book).ok().and_then(|book| p.recipe_book.settings.get_mut(book)) {
+        *settings = (open != 0, filter != 0);
+    }
+    Ok(())
+}
+
+// Set Seen Recipe: der Spieler hat ein hervorgehobenes Rezept angesehen
+pub fn handle_seen(players: &mut [Player], player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let id = read_bounded_string(cursor, MAX_ID_LENGTH)?;
+    let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    p.recipe_book.highlighted.remove(&id);
+    Ok(())
+}
+
+// Place Recipe: ein Klick auf ein Rezept im Buch legt die Zutaten ins Crafting-Gitter. Fehlen welche, zeigt der
+// Client das Rezept nur als Geisterbild. Der Server hat nur das 2x2-Gitter des eigenen Inventars
+pub fn handle_place(server: &Server, players: &mut [Player], player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let window_id = cursor.read_u8()?;
+    let id = read_bounded_string(cursor, MAX_ID_LENGTH)?;
+    let make_all = cursor.read_u8()?;
+    let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    if window_id != 0 || p.window.is_some() || !p.recipe_book.unlocked.contains(&id) {
+        debug!("Place Recipe von {} für {} in Fenster {} verworfen", p.username, id, window_id);
+        return Ok(());
+    }
+    let datapacks = server.datapacks.lock().unwrap();
+    let recipes = &datapacks.registries.recipes;
+    let Some(recipe) = recipes.crafting_recipe(&id) else { return Ok(()) };
+    if !click::place_recipe(p, recipes, recipe, make_all != 0) {
+        let mut packet_data = vec![window_id];
+        packet_data.extend(write_string_to_vec(&id));
+        let _ = p.connection.send(clientbound::PLACE_GHOST_RECIPE, &packet_data);
+    }
+    Ok(())
+}
+
@@ -0,0 +1,156 @@
+use crate::gamemode::GameMode;
+use crate::{block, door, farming, Player, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Stromquellen sind bisher Hebel, Knöpfe, Druckplatten und Stolperdrahthaken, Leitungen gibt es noch nicht: eine
+// eingeschaltete Quelle speist ihre Nachbarn und den Block, an dem sie hängt, und der wiederum seine Nachbarn
+
+const STONE_BUTTON_TICKS: i64 = 20;
+const WOODEN_BUTTON_TICKS: i64 = 30;
+// Nur damit gedrückte Knöpfe nach einem Neustart wieder herausspringen
+pub const RANDOM_TICKING: &[&str] = &["minecraft:stone_button", "minecraft:oak_button"];
+
+const NEIGHBORS: [BlockPos; 6] = [(0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1), (-1, 0, 0), (1, 0, 0)];
+
+fn neighbors((x, y, z): BlockPos) -> impl Iterator<Item = BlockPos> {
+    NEIGHBORS.iter().map(move |&(dx, dy, dz)| (x + dx, y + dy, z + dz))
+}
+
+pub fn is_button(state: u16) -> bool {
+    block::name(state).ends_with("_button")
+}
+
+fn is_switch(state: u16) -> bool {
+    block::name(state) == "minecraft:lever" || is_button(state)
+}
+
+fn is_source(state: u16) -> bool {
+    is_switch(state) || block::is_pressure_plate(state) || block::name(state) == "minecraft:tripwire_hook"
+}
+
+// Stärke des Stroms aus einer Quelle; nur beschwerte Druckplatten geben weniger als 15 ab
+fn power(state: u16) -> u8 {
+    if !is_source(state) {
+        0
+    } else if let Some(power) = block::property(state, "power") {
+        power.parse().unwrap_or(0)
+    } else {
+        (block::property(state, "powered") == Some("true")) as u8 * 15
+    }
+}
+
+fn is_on(state: u16) -> bool {
+    power(state) > 0
+}
+
+// Der Block, an dem eine Quelle hängt; Druckplatten liegen immer auf einem
+fn attached_to(state: u16, (x, y, z): BlockPos) -> BlockPos {
+    match block::property(state, "face") {
+        Some("floor") => (x, y - 1, z),
+        Some("ceiling") => (x, y + 1, z),
+        _ if block::is_pressure_plate(state) => (x, y - 1, z),
+        _ => {
+            let (dx, dz) = match block::property(state, "facing") {
+                Some("north") => (0, -1),
+                Some("south") => (0, 1),
+                Some("west") => (-1, 0),
+                _ => (1, 0),
+            };
+            (x - dx, y, z - dz)
+        }
+    }
+}
+
+// Strom, den ein Nachbarblock weitergibt: eine Quelle ihren eigenen, ein fester Block den der Quellen, die an ihm
+// hängen
+pub fn power_into(world: &World, neighbor: BlockPos) -> u8 {
+    let state = world.get_block(neighbor.0, neighbor.1, neighbor.2);
+    if !block::is_solid(state) {
+        return power(state);
+    }
+    neighbors(neighbor)
+        .filter(|&source| {
+            let state = world.get_block(source.0, source.1, source.2);
+            is_source(state) && attached_to(state, source) == neighbor
+        })
+        .map(|(x, y, z)| power(world.get_block(x, y, z)))
+        .max()
+        .unwrap_or(0)
+}
+
+// Stärkster Strom an einer Stelle, von einer Quelle direkt daneben oder an einem festen Nachbarblock
+pub fn power_at(world: &World, position: BlockPos) -> u8 {
+    neighbors(position).map(|neighbor| power_into(world, neighbor)).max().unwrap_or(0)
+}
+
+fn click_sound(state: u16, on: bool) -> &'static str {
+    match (block::name(state), on) {
+        ("minecraft:lever", _) => "minecraft:block.lever.click",
+        ("minecraft:stone_button", true) => "minecraft:block.stone_button.click_on",
+        ("minecraft:stone_button", false) => "minecraft:block.stone_button.click_off",
+        (_, true) => "minecraft:block.wooden_button.click_on",
+        (_, false) => "minecraft:block.wooden_button.click_off",
+    }
+}
+
+fn switch(players: &[Player], world: &mut World, position: BlockPos, state: u16, on: bool) {
+    let new = block::with_property(state, "powered", &on.to_string()).unwrap_or(state);
+    if world.set_block_and_notify(position, new) {
+        farming::play_sound(players, world, click_sound(state, on), position);
+    }
+}
+
+// Use Item On auf einen Hebel oder Knopf: der Hebel schaltet um, ein Knopf bleibt eine Weile gedrückt. false, wenn
+// nichts davon angeklickt wurde
+pub fn use_on(players: &[Player], world: &mut World, index: usize, position: BlockPos) -> bool {
+    let state = world.get_block(position.0, position.1, position.2);
+    if !is_switch(state) || players[index].game_mode == GameMode::Spectator {
+        return false;
+    }
+    let on = is_on(state);
+    if is_button(state) {
+        if !on {
+            switch(players, world, position, state, true);
+            let delay = if block::name(state) == "minecraft:stone_button" { STONE_BUTTON_TICKS } else { WOODEN_BUTTON_TICKS };
+            world.schedule_block_tick(position, delay);
+        }
+    } else {
+        switch(players, world, position, state, !on);
+    }
+    true
+}
+
+// Ein gedrückter Knopf springt wieder heraus
+pub fn scheduled_tick(players: &[Player], world: &mut World, position: BlockPos, state: u16) {
+    if is_on(state) {
+        switch(players, world, position, state, false);
+    }
+}
+
+pub fn random_tick(world: &mut World, position: BlockPos) {
+    let state = world.get_block(position.0, position.1, position.2);
+    if is_on(state) && !world.block_ticks.is_scheduled(position) {
+        world.schedule_block_tick(position, 1);
+    }
+}
+
+// Geht eine Quelle an, aus oder verloren, prüfen Türen, Falltüren, Zauntore und Kolben in Reichweite im nächsten Tick
+// ihren Strom neu
+pub fn block_changed(world: &mut World, position: BlockPos, old: u16, new: u16) {
+    if is_on(old) == is_on(new) {
+        return;
+    }
+    let source = if is_on(old) { old } else { new };
+    let support = attached_to(source, position);
+    let mechanisms: Vec<BlockPos> = neighbors(position)
+        .chain(neighbors(support))
+        .filter(|&(x, y, z)| {
+            let state = world.get_block(x, y, z);
+            door::is_openable(state) || block::is_piston(state)
+        })
+        .collect();
+    for mechanism in mechanisms {
+        world.schedule_block_tick(mechanism, 1);
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::Cursor;
+use byteorder::{BigEndian, ReadBytesExt};
+use uuid::Uuid;
+use crate::config::ServerConfig;
+use crate::error::ServerError;
+use crate::protocol::clientbound;
+use crate::protocol::types::{read_varint_from_cursor, write_string_to_vec};
+use crate::text::Text;
+use crate::{hash, kick_player, Connection, Player, Server};
+
+pub const REQUIRED_MESSAGE: &str = "This server requires a custom resource pack.";
+
+// Antwort des Clients auf Add Resource Pack
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Loaded,
+    Declined,
+    DownloadFailed,
+    Accepted,
+    Downloaded,
+    InvalidUrl,
+    ReloadFailed,
+    Discarded,
+}
+
+impl Status {
+    fn from_id(id: i32) -> Option<Status> {
+        Some(match id {
+            0 => Status::Loaded,
+            1 => Status::Declined,
+            2 => Status::DownloadFailed,
+            3 => Status::Accepted,
+            4 => Status::Downloaded,
+            5 => Status::InvalidUrl,
+            6 => Status::ReloadFailed,
+            7 => Status::Discarded,
+            _ => return None,
+        })
+    }
+
+    // Endgültig ohne Paket: bei Pflicht-Paketen ein Grund zum Trennen
+    fn is_failure(self) -> bool {
+        matches!(self, Status::Declined | Status::DownloadFailed | Status::InvalidUrl | Status::ReloadFailed | Status::Discarded)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourcePack {
+    pub id: Uuid,
+    pub url: String,
+    pub sha1: String,
+    pub required: bool,
+    pub prompt: Option<String>,
+}
+
+impl ResourcePack {
+    // None, wenn resource-pack leer ist
+    pub fn from_config(config: &ServerConfig) -> Option<ResourcePack> {
+        let url = config.resource_pack.trim();
+        if url.is_empty() {
+            return None;
+        }
+        let mut sha1 = config.resource_pack_sha1.trim().to_ascii_lowercase();
+        if !sha1.is_empty() && (sha1.len() != 40 || !sha1.bytes().all(|b| b.is_ascii_hexdigit())) {
+            warn!("resource-pack-sha1 ist kein gültiger SHA-1-Hash und wird ignoriert");
+            sha1.clear();
+        }
+        if sha1.is_empty() {
+            warn!("resource-pack ist ohne resource-pack-sha1 gesetzt, Clients laden das Paket bei jedem Beitritt neu");
+        }
+        // Ohne feste ID aus der URL ableiten, damit Clients das Paket über Neustarts hinweg wiedererkennen
+        let id = match config.resource_pack_id.trim() {
+            "" => {
+                let mut bytes: [u8; 16] = hash::sha1(url.as_bytes())[..16].try_into().unwrap();
+                bytes[6] = (bytes[6] & 0x0F) | 0x50;
+                bytes[8] = (bytes[8] & 0x3F) | 0x80;
+                Uuid::from_bytes(bytes)
+            }
+            id => match Uuid::parse_str(id) {
+                Ok(id) => id,
+                Err(_) => {
+                    warn!("resource-pack-id ist keine gültige UUID");
+                    return None;
+                }
+            },
+        };
+        let prompt = Some(config.resource_pack_prompt.trim()).filter(|p| !p.is_empty()).map(String::from);
+        Some(ResourcePack { id, url: url.to_string(), sha1, required: config.require_resource_pack, prompt })
+    }
+
+    pub fn send(&self, connection: &Connection) -> Result<(), String> {
+        let mut packet_data = self.id.as_bytes().to_vec();
+        packet_data.extend(write_string_to_vec(&self.url));
+        packet_data.extend(write_string_to_vec(&self.sha1));
+        packet_data.push(self.required as u8);
+        match &self.prompt {
+            Some(prompt) => {
+                packet_data.push(1);
+                packet_data.extend(Text::new(prompt.as_str()).to_nbt());
+            }
+            None => packet_data.push(0),
+        }
+        connection.send(clientbound::ADD_RESOURCE_PACK, &packet_data)
+    }
+}
+
+pub fn handle_response(server: &Server, player: &Player, cursor: &mut Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let id = cursor.read_u128::<BigEndian>()?;
+    let result = read_varint_from_cursor(cursor)?;
+    let Some(pack) = &server.resource_pack else { return Ok(()) };
+    // Antworten zu Paketen, die ein Plugin geschickt hat, gehen den Server nichts an
+    if Uuid::from_u128(id) != pack.id {
+        return Ok(());
+    }
+    let Some(status) = Status::from_id(result) else {
+        warn!("Unbekannter Ressourcenpaket-Status {} von {}", result, player.username);
+        return Ok(());
+    };
+    info!("Ressourcenpaket-Status von {}: {:?}", player.username, status);
+    if status.is_failure() && pack.required {
+        kick_player(server, &player.username, REQUIRED_MESSAGE);
+    }
+    Ok(())
+}
@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use crate::banlist::{self, Ban};
+use crate::command;
+use crate::console::{self, Permission};
+use crate::hash::constant_time_eq;
+use crate::json::{self, Json};
+use crate::metrics;
+use crate::protocol::{MINECRAFT_VERSION, PROTOCOL_VERSION};
+use crate::websocket;
+use crate::{kick_player, send_system_message, Server, KICK_REASON};
+
+const MAX_HEADER_SIZE: u64 = 8 * 1024;
+const MAX_BODY_SIZE: usize = 64 * 1024;
+const MAX_CONNECTIONS: usize = 16;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+// So lange wartet /api/command auf den Tick-Thread
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    // Ein leerer Body gilt als leeres Objekt, damit optionale Felder weggelassen werden können
+    fn json(&self) -> Result<Json, Response> {
+        if self.body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Json::Object(BTreeMap::new()));
+        }
+        let text = std::str::from_utf8(&self.body).map_err(|_| Response::error(400, "Body is not valid UTF-8"))?;
+        match json::parse(text) {
+            Ok(body @ Json::Object(_)) => Ok(body),
+            _ => Err(Response::error(400, "Body must be a JSON object")),
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: Json) -> Response {
+        Response { status: 200, content_type: "application/json", body: body.to_json_string() }
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        let body = object(vec![("error", Json::String(message.to_string()))]).to_json_string();
+        Response { status, content_type: "application/json", body }
+    }
+}
+
+fn object(entries: Vec<(&str, Json)>) -> Json {
+    Json::Object(entries.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+// Startet die REST-API in einem eigenen Thread, falls sie in server.properties aktiviert ist
+pub fn start(server: &Arc<Server>) {
+    let config = &server.config;
+    if !config.rest_api_enabled {
+        return;
+    }
+    if config.rest_api_token.is_empty() {
+        warn!("rest-api-enabled ist aktiv, aber rest-api-token ist leer! REST-API wird nicht gestartet");
+        return;
+    }
+    let address = format!("{}:{}", config.rest_api_address, config.rest_api_port);
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("REST-API konnte nicht an {} gebunden werden: {}", address, e);
+            return;
+        }
+    };
+    info!("REST-API lauscht auf {}", address);
+    let server = Arc::clone(server);
+    let active = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                active.fetch_sub(1, Ordering::SeqCst);
+                let _ = write_response(&mut stream, &Response::error(503, "Too many connections"));
+                continue;
+            }
+            let server = Arc::clone(&server);
+            let active = Arc::clone(&active);
+            thread::spawn(move || {
+                handle_connection(&server, stream);
+                active.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+}
+
+// Das volle Token erlaubt alles, das Lese-Token nur das Mitlesen der Konsole und /metrics.
+// Browser können bei WebSockets keine Header setzen, daher wird das Token auch als ?token= akzeptiert
+fn authenticate(server: &Server, request: &Request) -> Option<Permission> {
+    let header = request.header("Authorization").and_then(|v| v.strip_prefix("Bearer "));
+    let query = request.path.split_once('?').and_then(|(_, query)| query.split('&').find_map(|p| p.strip_prefix("token=")));
+    let token = header.or(query)?.trim().as_bytes();
+    let config = &server.config;
+    if constant_time_eq(token, config.rest_api_token.as_bytes()) {
+        Some(Permission::Execute)
+    } else if !config.rest_api_console_read_token.is_empty() && constant_time_eq(token, config.rest_api_console_read_token.as_bytes()) {
+        Some(Permission::Read)
+    } else {
+        None
+    }
+}
+
+fn handle_connection(server: &Server, mut stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+    let response = match read_request(&mut stream) {
+        Ok(request) => match authenticate(server, &request) {
+            Some(permission) if request.path.split('?').next() == Some("/api/console") => match websocket_key(&request) {
+                Ok(key) => {
+                    if stream.write_all(websocket::handshake_response(key).as_bytes()).is_ok() {
+                        console::run_session(server, stream, permission);
+                    }
+                    return;
+                }
+                Err(response) => response,
+            },
+            // Metriken sind reine Lesezugriffe und daher auch mit dem Lese-Token abrufbar
+            Some(_) if request.method == "GET" && request.path.split('?').next() == Some("/metrics") => Response {
+                status: 200,
+                content_type: "text/plain; version=0.0.4",
+                body: metrics::render(server),
+            },
+            Some(Permission::Execute) => route(server, &request),
+            _ => {
+                warn!("REST-Anfrage von {} mit ungültigem Token abgelehnt", peer);
+                Response::error(401, "Missing or invalid token")
+            }
+        },
+        Err(response) => response,
+    };
+    if let Err(e) = write_response(&mut stream, &response) {
+        warn!("REST-Antwort an {} fehlgeschlagen: {}", peer, e);
+    }
+}
+
+fn websocket_key(request: &Request) -> Result<&str, Response> {
+    let upgrade = request.header("Upgrade").is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+    if request.method != "GET" || !upgrade {
+        return Err(Response::error(400, "Expected a WebSocket upgrade request"));
+    }
+    if request.header("Sec-WebSocket-Version") != Some("13") {
+        return Err(Response::error(400, "Unsupported WebSocket version"));
+    }
+    request.header("Sec-WebSocket-Key").ok_or_else(|| Response::error(400, "Missing Sec-WebSocket-Key"))
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let mut head = (&mut reader).take(MAX_HEADER_SIZE);
+    let mut line = String::new();
+    let mut read_line = |line: &mut String| -> Result<(), Response> {
+        line.clear();
+        match head.read_line(line) {
+            Ok(_) if line.ends_with('\n') => Ok(()),
+            Ok(_) => Err(Response::error(431, "Request header too large")),
+            Err(_) => Err(Response::error(400, "Malformed request")),
+        }
+    };
+    read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else { return Err(Response::error(400, "Malformed request line")) };
+    let (method, path) = (method.to_string(), path.to_string());
+    let mut headers = Vec::new();
+    loop {
+        read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((key, value)) = header.split_once(':') else { return Err(Response::error(400, "Malformed header")) };
+        headers.push((key.trim().to_string(), value.trim().to_string()));
+    }
+    let mut request = Request { method, path, headers, body: Vec::new() };
+    let length = match request.header("Content-Length") {
+        Some(value) => value.parse::<usize>().map_err(|_| Response::error(400, "Invalid Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_BODY_SIZE {
+        return Err(Response::error(413, "Request body too large"));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body).map_err(|_| Response::error(400, "Incomplete request body"))?;
+    Ok(request)
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    let body = &response.body;
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        status_text(response.status),
+        response.content_type,
+        body.len()
+    );
+    if response.status == 401 {
+        head.push_str("WWW-Authenticate: Bearer\r\n");
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())
+}
+
+fn route(server: &Server, request: &Request) -> Response {
+    let path = request.path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let result = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api", "status"]) => Ok(status(server)),
+        ("GET", ["api", "players"]) => Ok(players(server)),
+        ("POST", ["api", "players", name, "kick"]) => kick(server, name, request),
+        ("POST", ["api", "players", name, "ban"]) => ban(server, name, request),
+        ("GET", ["api", "bans"]) => Ok(bans(server)),
+        ("DELETE", ["api", "bans", name]) => pardon(server, name),
+        ("POST", ["api", "broadcast"]) => broadcast(server, request),
+        ("POST", ["api", "command"]) => command(server, request),
+        _ => Err(Response::error(404, "Unknown endpoint")),
+    };
+    result.unwrap_or_else(|response| response)
+}
+
+fn status(server: &Server) -> Response {
+    let online = server.players.lock().unwrap().len();
+    let stats = server.tick_stats.lock().unwrap();
+    let tps = object(vec![("1m", Json::Number(stats.tps(60))), ("5m", Json::Number(stats.tps(300))), ("15m", Json::Number(stats.tps(900)))]);
+    let mspt = object(vec![("average", Json::Number(stats.average_mspt())), ("worst", Json::Number(stats.worst_mspt()))]);
+    drop(stats);
+    Response::ok(object(vec![
+        ("version", Json::String(MINECRAFT_VERSION.to_string())),
+        ("protocol", Json::Number(PROTOCOL_VERSION as f64)),
+        ("players", object(vec![("online", Json::Number(online as f64)), ("max", Json::Number(server.config.max_players as f64))])),
+        ("tps", tps),
+        ("mspt", mspt),
+    ]))
+}
+
+fn players(server: &Server) -> Response {
+    let players = server.players.lock().unwrap();
+    let list = players
+        .iter()
+        .map(|p| {
+            let (x, y, z) = p.position;
+            object(vec![
+                ("name", Json::String(p.username.clone())),
+                ("uuid", Json::String(p.uuid.to_string())),
+                ("brand", p.brand.clone().map(Json::String).unwrap_or(Json::Null)),
+                ("ping", Json::Number(p.ping() as f64)),
+                ("position", object(vec![("x", Json::Number(x)), ("y", Json::Number(y)), ("z", Json::Number(z))])),
+            ])
+        })
+        .collect();
+    Response::ok(Json::Array(list))
+}
+
+fn reason(request: &Request, default: &str) -> Result<String, Response> {
+    match request.json()?.get("reason") {
+        None | Some(Json::Null) => Ok(default.to_string()),
+        Some(Json::String(reason)) => Ok(reason.clone()),
+        Some(_) => Err(Response::error(400, "\"reason\" must be a string")),
+    }
+}
+
+fn kick(server: &Server, name: &str, request: &Request) -> Result<Response, Response> {
+    let reason = reason(request, KICK_REASON)?;
+    if !kick_player(server, name, &reason) {
+        return Err(Response::error(404, "Player is not online"));
+    }
+    Ok(Response::ok(object(vec![("kicked", Json::String(name.to_string()))])))
+}
+
+// Bannt auch Spieler, die gerade nicht online sind
+fn ban(server: &Server, name: &str, request: &Request) -> Result<Response, Response> {
+    if name.is_empty() || name.len() > 16 || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(Response::error(400, "Invalid player name"));
+    }
+    let reason = reason(request, banlist::DEFAULT_REASON)?;
+    let uuid = server.players.lock().unwrap().iter().find(|p| p.username.eq_ignore_ascii_case(name)).map(|p| p.uuid);
+    let ban = Ban::new(name, uuid, &reason, "REST");
+    let entry = ban.to_json();
+    if let Err(e) = server.bans.lock().unwrap().add(ban) {
+        warn!("{}", e);
+        return Err(Response::error(500, "Could not save the ban list"));
+    }
+    info!("[REST] {} wurde gebannt: {}", name, reason);
+    kick_player(server, name, &banlist::ban_message(&reason));
+    Ok(Response::ok(entry))
+}
+
+fn bans(server: &Server) -> Response {
+    Response::ok(Json::Array(server.bans.lock().unwrap().entries().iter().map(Ban::to_json).collect()))
+}
+
+fn pardon(server: &Server, name: &str) -> Result<Response, Response> {
+    match server.bans.lock().unwrap().remove(name) {
+        Ok(true) => {
+            info!("[REST] Bann von {} aufgehoben", name);
+            Ok(Response::ok(object(vec![("pardoned", Json::String(name.to_string()))])))
+        }
+        Ok(false) => Err(Response::error(404, "Player is not banned")),
+        Err(e) => {
+            warn!("{}", e);
+            Err(Response::error(500, "Could not save the ban list"))
+        }
+    }
+}
+
+fn required_string(request: &Request, field: &str) -> Result<String, Response> {
+    match request.json()?.get(field) {
+        Some(Json::String(value)) if !value.trim().is_empty() => Ok(value.clone()),
+        _ => Err(Response::error(400, &format!("\"{}\" must be a non-empty string", field))),
+    }
+}
+
+fn broadcast(server: &Server, request: &Request) -> Result<Response, Response> {
+    let message = required_string(request, "message")?;
+    info!("[REST] {}", message);
+    let players = server.players.lock().unwrap();
+    for player in players.iter() {
+        let _ = send_system_message(&player.connection, &message);
+    }
+    Ok(Response::ok(object(vec![("recipients", Json::Number(players.len() as f64))])))
+}
+
+fn command(server: &Server, request: &Request) -> Result<Response, Response> {
+    let line = required_string(request, "command")?;
+    info!("REST führt Befehl aus: /{}", line.trim_start_matches('/'));
+    let (output, completed) = command::run_remote(server, &line, COMMAND_TIMEOUT);
+    let output = output.into_iter().map(Json::String).collect();
+    Ok(Response::ok(object(vec![("output", Json::Array(output)), ("completed", Json::Bool(completed))])))
+}
@@ -0,0 +1,113 @@
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::{console, send_system_message, systemd, Server};
+
+// Damit Wrapper-Skripte und systemd (RestartForceExitStatus=75) einen Neustart von einem Stopp unterscheiden
+pub const EXIT_CODE: i32 = 75;
+// Bei diesen Restsekunden werden die Spieler erinnert
+const ANNOUNCE_AT: [u64; 8] = [60, 30, 10, 5, 4, 3, 2, 1];
+
+static HANGUP: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug)]
+pub struct PendingRestart {
+    at: Instant,
+    announced: Option<u64>,
+}
+
+extern "C" fn on_hangup(_: libc::c_int) {
+    HANGUP.store(true, Ordering::SeqCst);
+}
+
+// SIGHUP startet wie /restart mit restart-countdown neu
+pub fn handle_hangup() {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_hangup as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGHUP, &action, ptr::null_mut());
+    }
+}
+
+pub fn schedule(server: &Server, seconds: u64) -> Result<String, String> {
+    let mut restart = server.restart.lock().unwrap();
+    if restart.is_some() {
+        return Err("A restart is already scheduled".to_string());
+    }
+    info!("Neustart in {}s geplant", seconds);
+    *restart = Some(PendingRestart { at: Instant::now() + Duration::from_secs(seconds), announced: None });
+    Ok(format!("Restarting the server in {}", format_seconds(seconds)))
+}
+
+pub fn cancel(server: &Server) -> Result<(), String> {
+    server.restart.lock().unwrap().take().ok_or("No restart is scheduled")?;
+    info!("Neustart abgebrochen");
+    announce(server, "The server restart was cancelled");
+    Ok(())
+}
+
+// Zählt einen geplanten Neustart herunter und führt ihn aus. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    if HANGUP.swap(false, Ordering::SeqCst) {
+        info!("SIGHUP empfangen");
+        if let Err(e) = schedule(server, server.config.restart_countdown_secs) {
+            info!("{}", e);
+        }
+    }
+    let mut restart = server.restart.lock().unwrap();
+    let Some(pending) = restart.as_mut() else { return };
+    let left = pending.at.saturating_duration_since(Instant::now());
+    let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+    if seconds == 0 {
+        drop(restart);
+        restart_now(server);
+    }
+    if pending.announced.is_none() || (ANNOUNCE_AT.contains(&seconds) && pending.announced != Some(seconds)) {
+        pending.announced = Some(seconds);
+        drop(restart);
+        announce(server, &format!("The server is restarting in {}", format_seconds(seconds)));
+    }
+}
+
+fn announce(server: &Server, message: &str) {
+    info!("{}", message);
+    for player in server.players.lock().unwrap().iter() {
+        let _ = send_system_message(&player.connection, message);
+    }
+}
+
+// Speichert alles, trennt die Spieler mit restart-message und startet neu. Darf nur ohne gehaltene Sperren aufgerufen
+// werden
+pub fn restart_now(server: &Server) -> ! {
+    info!("Server wird neu gestartet...");
+    server.close(&server.config.restart_message);
+    if server.config.restart_exec {
+        reexec();
+    }
+    systemd::stopping();
+    console::LOG.release_stdout();
+    std::process::exit(EXIT_CODE);
+}
+
+// Ersetzt den Prozess durch die eigene Binärdatei mit denselben Argumenten; offene Sockets schließen dabei von selbst
+pub fn reexec() -> ! {
+    console::LOG.release_stdout();
+    // Bis der neue Prozess READY=1 meldet, gilt der Dienst für systemd als im Neuladen
+    systemd::notify("RELOADING=1");
+    systemd::inherit_listeners();
+    let error = Command::new("/proc/self/exe").args(std::env::args_os().skip(1)).exec();
+    error!("Konnte den Server nicht neu starten: {}", error);
+    std::process::exit(1);
+}
+
+fn format_seconds(seconds: u64) -> String {
+    if seconds == 1 {
+        "1 second".to_string()
+    } else {
+        format!("{} seconds", seconds)
+    }
+}
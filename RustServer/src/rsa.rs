@@ -0,0 +1,245 @@
+use std::cmp::Ordering;
+use crate::hash;
+
+// DigestInfo vor dem Hash in PKCS#1 v1.5 (RFC 8017, Abschnitt 9.2)
+const SHA1_PREFIX: &[u8] = &[0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14];
+const SHA256_PREFIX: &[u8] = &[0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05, 0x00, 0x04, 0x20];
+// OID 1.2.840.113549.1.1.1
+const RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+// Mojang signiert mit 4096 Bit; größere Schlüssel wären nur teuer
+const MAX_MODULUS_BYTES: usize = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Digest {
+    // Schlüsselsignaturen von Mojang
+    Sha1,
+    // Chat-Signaturen der Clients
+    Sha256,
+}
+
+// Nur zum Prüfen von Signaturen; privat wird hier nie gerechnet
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKey {
+    modulus: Natural,
+    exponent: Natural,
+    // Länge des Moduls in Bytes und damit jeder Signatur
+    size: usize,
+}
+
+impl PublicKey {
+    // SubjectPublicKeyInfo in DER, wie Java ihn mit getEncoded() liefert
+    pub fn from_der(der: &[u8]) -> Result<PublicKey, String> {
+        let mut info = Der::new(der).sequence()?;
+        let mut algorithm = info.sequence()?;
+        if algorithm.element(0x06)? != RSA_ENCRYPTION {
+            return Err("Not an RSA key".to_string());
+        }
+        let bits = info.element(0x03)?;
+        // Das erste Byte eines BIT STRING zählt die ungenutzten Bits am Ende
+        let mut key = Der::new(bits.strip_prefix(&[0]).ok_or("Invalid key bit string")?).sequence()?;
+        let modulus = key.element(0x02)?;
+        let exponent = key.element(0x02)?;
+        let modulus = strip_zeros(modulus);
+        if modulus.is_empty() || modulus.len() > MAX_MODULUS_BYTES {
+            return Err("Unsupported RSA key size".to_string());
+        }
+        Ok(PublicKey { modulus: Natural::from_be(modulus), exponent: Natural::from_be(exponent), size: modulus.len() })
+    }
+
+    // RSASSA-PKCS1-v1_5
+    pub fn verify(&self, digest: Digest, message: &[u8], signature: &[u8]) -> bool {
+        if signature.len() != self.size {
+            return false;
+        }
+        let signature = Natural::from_be(signature);
+        if signature.cmp(&self.modulus) != Ordering::Less {
+            return false;
+        }
+        let (prefix, hash) = match digest {
+            Digest::Sha1 => (SHA1_PREFIX, hash::sha1(message).to_vec()),
+            Digest::Sha256 => (SHA256_PREFIX, hash::sha256(message).to_vec()),
+        };
+        let Some(padding) = self.size.checked_sub(3 + prefix.len() + hash.len()).filter(|&n| n >= 8) else { return false };
+        let mut expected = vec![0x00, 0x01];
+        expected.extend(std::iter::repeat_n(0xFF, padding));
+        expected.push(0x00);
+        expected.extend(prefix);
+        expected.extend(hash);
+        signature.pow_mod(&self.exponent, &self.modulus).to_be(self.size) == expected
+    }
+}
+
+fn strip_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+// Gerade genug DER für einen SubjectPublicKeyInfo
+struct Der<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    fn new(data: &'a [u8]) -> Der<'a> {
+        Der { data }
+    }
+
+    fn element(&mut self, tag: u8) -> Result<&'a [u8], String> {
+        let (&found, rest) = self.data.split_first().ok_or("Truncated key")?;
+        if found != tag {
+            return Err(format!("Expected DER tag 0x{:02x}, found 0x{:02x}", tag, found));
+        }
+        let (&first, mut rest) = rest.split_first().ok_or("Truncated key")?;
+        let length = if first < 0x80 {
+            first as usize
+        } else {
+            let count = (first & 0x7F) as usize;
+            if count == 0 || count > 2 || rest.len() < count {
+                return Err("Invalid DER length".to_string());
+            }
+            let length = rest[..count].iter().fold(0, |length, &b| length << 8 | b as usize);
+            rest = &rest[count..];
+            length
+        };
+        if rest.len() < length {
+            return Err("Truncated key".to_string());
+        }
+        self.data = &rest[length..];
+        Ok(&rest[..length])
+    }
+
+    fn sequence(&mut self) -> Result<Der<'a>, String> {
+        self.element(0x30).map(Der::new)
+    }
+}
+
+// Natürliche Zahl als 32-Bit-Stellen, niederwertigste zuerst, ohne führende Nullen
+#[derive(Debug, Clone, PartialEq)]
+struct Natural(Vec<u32>);
+
+impl Natural {
+    fn from_be(bytes: &[u8]) -> Natural {
+        let mut limbs: Vec<u32> = bytes
+            .rchunks(4)
+            .map(|chunk| chunk.iter().fold(0, |limb, &b| limb << 8 | b as u32))
+            .collect();
+        trim(&mut limbs);
+        Natural(limbs)
+    }
+
+    // Vorne mit Nullen auf length Bytes aufgefüllt
+    fn to_be(&self, length: usize) -> Vec<u8> {
+        let bytes: Vec<u8> = self.0.iter().rev().flat_map(|limb| limb.to_be_bytes()).collect();
+        let bytes = strip_zeros(&bytes);
+        let mut out = vec![0; length.saturating_sub(bytes.len())];
+        out.extend(bytes);
+        out
+    }
+
+    fn bits(&self) -> usize {
+        self.0.last().map_or(0, |top| self.0.len() * 32 - top.leading_zeros() as usize)
+    }
+
+    fn bit(&self, index: usize) -> bool {
+        self.0.get(index / 32).is_some_and(|limb| limb >> (index % 32) & 1 == 1)
+    }
+
+    fn mul(&self, other: &Natural) -> Natural {
+        let mut out = vec![0u32; self.0.len() + other.0.len()];
+        for (i, &a) in self.0.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.0.iter().enumerate() {
+                let sum = out[i + j] as u64 + a as u64 * b as u64 + carry;
+                out[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            out[i + other.0.len()] = carry as u32;
+        }
+        trim(&mut out);
+        Natural(out)
+    }
+
+    // Schieben und Abziehen Bit für Bit; bei Exponent 65537 sind das nur 17 Reduktionen je Signatur
+    fn rem(&self, modulus: &Natural) -> Natural {
+        let mut rest = Natural(Vec::new());
+        for index in (0..self.bits()).rev() {
+            rest.shift_left_one(self.bit(index));
+            if rest.cmp(modulus) != Ordering::Less {
+                rest.sub_assign(modulus);
+            }
+        }
+        rest
+    }
+
+    fn pow_mod(&self, exponent: &Natural, modulus: &Natural) -> Natural {
+        let base = self.rem(modulus);
+        let mut result = Natural(vec![1]).rem(modulus);
+        for index in (0..exponent.bits()).rev() {
+            result = result.mul(&result).rem(modulus);
+            if exponent.bit(index) {
+                result = result.mul(&base).rem(modulus);
+            }
+        }
+        result
+    }
+
+    fn shift_left_one(&mut self, low: bool) {
+        let mut carry = low as u32;
+        for limb in &mut self.0 {
+            let next = *limb >> 31;
+            *limb = *limb << 1 | carry;
+            carry = next;
+        }
+        if carry != 0 {
+            self.0.push(carry);
+        }
+    }
+
+    // Nur mit self >= other
+    fn sub_assign(&mut self, other: &Natural) {
+        let mut borrow = 0i64;
+        for (i, limb) in self.0.iter_mut().enumerate() {
+            let difference = *limb as i64 - other.0.get(i).copied().unwrap_or(0) as i64 - borrow;
+            borrow = (difference < 0) as i64;
+            *limb = difference.rem_euclid(1 << 32) as u32;
+        }
+        trim(&mut self.0);
+    }
+
+    fn cmp(&self, other: &Natural) -> Ordering {
+        self.0.len().cmp(&other.0.len()).then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mit openssl erzeugt: 1024-Bit-Schlüssel, signiert wurde "RustServer"
+    const KEY: &str = "30819f300d06092a864886f70d010101050003818d0030818902818100ed04a7f2e195b87c61baf3f62ebdb8e94eeafe0881af772e10ef3e97d95f219dce5dbc2529cb1bbb9d271457c754b555249c332ec70a18085906d7a1ffc33266741159addc2c8895fa06147ed02d0953f887e67e6e5ce0267dfce72bd0d42d681f0c43e5def1fcc81357e084e675628605aac6a65323912932e775d70f8a4ba70203010001";
+    const SHA256_SIGNATURE: &str = "032261354033dd881af19ec3bf6856c7e6bccd870e53e62e53b87f664228c4cd7cd2700ef7a0ce79904c82f371c88c9cbdd8edd97266884cbdf76a493aa865ede5ff4788a569bf5206202221120deb9746b218b4571de15e1705344e07fb5e1e86dac7803ac675a2437554a307730e566727f7318c69fe882932cc6980a4b683";
+    const SHA1_SIGNATURE: &str = "e0a8e474dce51473cfe31ab619f1af6b8ff3e97eb473fb6b1e76bb09aab12f37ab8192e45b5f22a730b9853c42d60aaf6f71f35f5203a81ad9be8ef4c8ff0677d1596ae961f4daa95bd6bcc4ddbbfbeb87be15662aaa6acd0c0e13bb580ee80758b23437be43cbaab473147dc6bff35af72c17aac37d195240f2f9df09213b28";
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn verifies_openssl_signatures() {
+        let key = PublicKey::from_der(&hex(KEY)).unwrap();
+        assert!(key.verify(Digest::Sha256, b"RustServer", &hex(SHA256_SIGNATURE)));
+        assert!(key.verify(Digest::Sha1, b"RustServer", &hex(SHA1_SIGNATURE)));
+        assert!(!key.verify(Digest::Sha256, b"RustServer", &hex(SHA1_SIGNATURE)));
+        assert!(!key.verify(Digest::Sha256, b"rustserver", &hex(SHA256_SIGNATURE)));
+        let mut tampered = hex(SHA256_SIGNATURE);
+        tampered[100] ^= 1;
+        assert!(!key.verify(Digest::Sha256, b"RustServer", &tampered));
+        assert!(PublicKey::from_der(&hex(KEY)[..100]).is_err());
+    }
+}
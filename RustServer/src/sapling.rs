@@ -0,0 +1,125 @@
+use rand::Rng;
+use crate::worldevent::WorldEvent;
+use crate::worldgen::{self, Tree};
+use crate::{block, chunk, face_offset, farming, Player, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Setzling und der Baum daraus, dazu der Riesenbaum aus vier Setzlingen im Quadrat
+const SAPLINGS: &[(&str, Tree, Option<Tree>)] = &[
+    ("minecraft:oak_sapling", Tree::Oak, None),
+    ("minecraft:spruce_sapling", Tree::Spruce, Some(Tree::MegaSpruce)),
+    ("minecraft:jungle_sapling", Tree::Jungle, Some(Tree::MegaJungle)),
+];
+pub const RANDOM_TICKING: &[&str] = &["minecraft:oak_sapling", "minecraft:spruce_sapling", "minecraft:jungle_sapling"];
+// Setzlinge halten auf Erde, Gras und Ackerboden; mit dem Baum wird der Boden darunter zu Erde
+const SOIL: &[&str] = &["minecraft:dirt", "minecraft:grass_block", farming::FARMLAND];
+// Jeder siebte Zufallstick bei Licht bringt den Setzling eine Stufe weiter, Knochenmehl in 45 % der Fälle
+const GROWTH_CHANCE: u32 = 7;
+const BONE_MEAL_CHANCE: f64 = 0.45;
+// Mindesthelligkeit über dem Setzling, wie bei Vanilla
+const MIN_LIGHT: u8 = 9;
+const PLACE_SOUND: &str = "minecraft:block.grass.place";
+
+fn sapling(state: u16) -> Option<&'static (&'static str, Tree, Option<Tree>)> {
+    SAPLINGS.iter().find(|(name, _, _)| block::name(state) == *name)
+}
+
+// Use Item On mit einem Setzling oder mit Knochenmehl auf einen Setzling; false, wenn nichts davon passt
+pub fn use_on(players: &mut [Player], world: &mut World, index: usize, slot: usize, (x, y, z): BlockPos, face: i32) -> bool {
+    let held = players[index].inventory.slot(slot).item.clone();
+    if held == farming::BONE_MEAL {
+        if sapling(world.get_block(x, y, z)).is_none() {
+            return false;
+        }
+        farming::spend(&mut players[index], slot, false);
+        world.play_event(WorldEvent::BoneMeal, (x, y, z), None);
+        if rand::thread_rng().gen::<f64>() < BONE_MEAL_CHANCE {
+            advance(world, (x, y, z));
+        }
+        return true;
+    }
+    let Some(&(name, _, _)) = SAPLINGS.iter().find(|(name, _, _)| *name == held) else { return false };
+    let (dx, dy, dz) = face_offset(face);
+    let position = (x + dx, y + dy, z + dz);
+    if world.get_block(position.0, position.1, position.2) != block::AIR || !SOIL.contains(&block::name(world.get_block(position.0, position.1 - 1, position.2))) {
+        return false;
+    }
+    let Some(state) = block::state_id(name) else { return false };
+    if !world.set_block_and_notify(position, state) {
+        return false;
+    }
+    farming::spend(&mut players[index], slot, false);
+    farming::play_sound(players, world, PLACE_SOUND, position);
+    true
+}
+
+// Zufallstick: Setzlinge wachsen, wenn es über ihnen hell genug ist
+pub fn random_tick(world: &mut World, (x, y, z): BlockPos) {
+    if world.light_level((x, y + 1, z)) >= MIN_LIGHT && rand::thread_rng().gen_range(0..GROWTH_CHANCE) == 0 {
+        advance(world, (x, y, z));
+    }
+}
+
+// Erst die zweite Wachstumsstufe, danach der Baum
+fn advance(world: &mut World, (x, y, z): BlockPos) {
+    let state = world.get_block(x, y, z);
+    if block::property(state, "stage") == Some("0") {
+        if let Some(grown) = block::with_property(state, "stage", "1") {
+            world.set_block_and_notify((x, y, z), grown);
+        }
+        return;
+    }
+    grow(world, (x, y, z));
+}
+
+// Vier gleiche Setzlinge im Quadrat werden zum Riesenbaum, sonst wächst der einfache Baum. false, wenn dafür kein
+// Platz ist
+fn grow(world: &mut World, (x, y, z): BlockPos) -> bool {
+    let Some(&(name, tree, mega)) = sapling(world.get_block(x, y, z)) else { return false };
+    let mut rng = rand::thread_rng();
+    if let Some(mega) = mega {
+        let square = |(ox, oz): (i32, i32)| [(0, 0), (1, 0), (0, 1), (1, 1)].iter().all(|&(dx, dz)| block::name(world.get_block(ox + dx, y, oz + dz)) == name);
+        if let Some((ox, oz)) = [(x, z), (x - 1, z), (x, z - 1), (x - 1, z - 1)].into_iter().find(|&corner| square(corner)) {
+            return place(world, mega, (ox, y, oz), mega.random_height(&mut rng));
+        }
+    }
+    place(world, tree, (x, y, z), tree.random_height(&mut rng))
+}
+
+// Stamm und Krone dürfen nur Luft, Blätter und die Setzlinge des Baums ersetzen; steht etwas im Weg des Stamms,
+// wächst nichts, und Blätter lassen Gebautes aus
+fn place(world: &mut World, tree: Tree, origin: BlockPos, height: i32) -> bool {
+    let (x, y, z) = origin;
+    let blocks = worldgen::tree_blocks(tree, height);
+    let free = |state: u16| state == block::AIR || block::name(state).ends_with("_leaves") || sapling(state).is_some();
+    let loaded = |world: &World, (bx, _, bz): BlockPos| world.chunks.contains_key(&(bx >> 4, bz >> 4));
+    if y + height + 2 > chunk::MIN_Y + chunk::HEIGHT {
+        return false;
+    }
+    let width = if tree.is_mega() { 2 } else { 1 };
+    let trunk: Vec<BlockPos> = (0..width).flat_map(|dx| (0..width).map(move |dz| (x + dx, y, z + dz))).collect();
+    if !trunk.iter().all(|&(tx, ty, tz)| SOIL.contains(&block::name(world.get_block(tx, ty - 1, tz)))) {
+        return false;
+    }
+    let log = blocks[0].1;
+    let fits = blocks.iter().filter(|(_, state)| *state == log).all(|&((dx, dy, dz), _)| {
+        let position = (x + dx, y + dy, z + dz);
+        loaded(world, position) && free(world.get_block(position.0, position.1, position.2))
+    });
+    if !fits {
+        return false;
+    }
+    debug!("Setzling wächst bei {:?} zu {:?} mit Höhe {}", origin, tree, height);
+    for &(tx, ty, tz) in &trunk {
+        world.set_block_and_notify((tx, ty - 1, tz), block::DIRT);
+    }
+    for ((dx, dy, dz), state) in blocks {
+        let position = (x + dx, y + dy, z + dz);
+        let current = world.get_block(position.0, position.1, position.2);
+        if state == log || current == block::AIR || sapling(current).is_some() {
+            world.set_block_and_notify(position, state);
+        }
+    }
+    true
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use crate::json::Json;
+use crate::structure::{self, Placed, PlacedEntity, Placement};
+use crate::{block, gzip, nbt, BlockChange};
+
+// Wie bei WorldEdit liegen Schematics in einem eigenen Ordner neben der Welt
+pub const SCHEMATIC_DIR: &str = "schematics";
+const EXTENSION: &str = "schem";
+
+// Blöcke relativ zur kleinsten Ecke, dazu Block-Entities und Entities im Vanilla-NBT-Format. Dient auch als
+// Zwischenablage der Spieler
+#[derive(Debug, Clone, Default)]
+pub struct Schematic {
+    pub size: (i32, i32, i32),
+    // Von WorldEdit gespeicherter Abstand der Ecke zur Position beim Kopieren
+    pub offset: (i32, i32, i32),
+    pub blocks: Vec<BlockChange>,
+    pub block_entities: Vec<((i32, i32, i32), Json)>,
+    pub entities: Vec<(String, (f64, f64, f64), Json)>,
+    // Namen aus der Palette, die das Block-Register nicht kennt
+    pub unknown: Vec<String>,
+}
+
+fn number(value: Option<&Json>) -> Option<i32> {
+    value?.as_f64().map(|n| n as i32)
+}
+
+fn triple(value: Option<&Json>) -> Option<(i32, i32, i32)> {
+    match value?.as_array()?.as_slice() {
+        [x, y, z] => Some((x.as_f64()? as i32, y.as_f64()? as i32, z.as_f64()? as i32)),
+        _ => None,
+    }
+}
+
+// BlockData ist eine Folge von VarInts, einer je Block; das NBT-Byte-Array liefert sie als vorzeichenbehaftete Bytes
+fn read_varints(data: &[Json], count: usize) -> Result<Vec<usize>, String> {
+    let mut values = Vec::with_capacity(count);
+    let mut bytes = data.iter().map(|b| b.as_f64().unwrap_or(0.0) as i8 as u8);
+    while values.len() < count {
+        let mut value = 0usize;
+        for shift in (0..).step_by(7) {
+            let byte = bytes.next().ok_or("BlockData ist kürzer als das Schematic")?;
+            if shift > 28 {
+                return Err("VarInt in BlockData zu lang".to_string());
+            }
+            value |= ((byte & 0x7F) as usize) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        values.push(value);
+    }
+    Ok(values)
+}
+
+impl Schematic {
+    // Sponge-Format Version 2 (Wurzel "Schematic") und 3 (Wurzel mit Compound "Schematic" und Unterobjekt "Blocks")
+    pub fn parse(data: &[u8]) -> Result<Schematic, String> {
+        let data = if data.starts_with(&[0x1F, 0x8B]) { gzip::decompress(data)? } else { data.to_vec() };
+        let root = nbt::read(&data)?;
+        let schematic = root.get("Schematic").unwrap_or(&root);
+        let version = number(schematic.get("Version")).ok_or("Schematic ohne Version")?;
+        let (blocks, entry_data) = match version {
+            2 => (schematic, false),
+            3 => (schematic.get("Blocks").ok_or("Schematic ohne Blöcke")?, true),
+            other => return Err(format!("Schematic-Version {} wird nicht unterstützt", other)),
+        };
+        let dimension = |key: &str| number(schematic.get(key)).map(|n| n as u16 as i32).ok_or(format!("Schematic ohne {}", key));
+        let size = (dimension("Width")?, dimension("Height")?, dimension("Length")?);
+        let palette = blocks.get("Palette").and_then(Json::as_object).ok_or("Schematic ohne Palette")?;
+        let mut states: BTreeMap<usize, Option<u16>> = BTreeMap::new();
+        let mut unknown = Vec::new();
+        for (spec, index) in palette {
+            let state = block::parse_state(spec).ok();
+            if state.is_none() {
+                unknown.push(spec.split('[').next().unwrap_or(spec).to_string());
+            }
+            states.insert(number(Some(index)).ok_or("Ungültiger Paletteneintrag")? as usize, state);
+        }
+        unknown.sort();
+        unknown.dedup();
+        let block_data = blocks.get(if entry_data { "Data" } else { "BlockData" }).and_then(Json::as_array).ok_or("Schematic ohne Blockdaten")?;
+        let count = (size.0 * size.1 * size.2) as usize;
+        let mut schematic_blocks = Vec::with_capacity(count);
+        for (i, index) in read_varints(block_data, count)?.into_iter().enumerate() {
+            let Some(state) = states.get(&index).copied().ok_or(format!("Block {} verweist auf Palette {}", i, index))? else { continue };
+            let (x, z, y) = (i as i32 % size.0, i as i32 / size.0 % size.2, i as i32 / (size.0 * size.2));
+            schematic_blocks.push(((x, y, z), state));
+        }
+        // Block-Entities: in Version 2 liegen die Daten direkt im Eintrag, in Version 3 unter "Data"
+        let mut block_entities = Vec::new();
+        for entry in blocks.get("BlockEntities").and_then(Json::as_array).into_iter().flatten() {
+            let Some(pos) = triple(entry.get("Pos")) else { continue };
+            let Some(id) = entry.get("Id").and_then(Json::as_str) else { continue };
+            let mut data = if entry_data { entry.get("Data").and_then(Json::as_object).cloned().unwrap_or_default() } else { entry.as_object().cloned().unwrap_or_default() };
+            data.remove("Pos");
+            data.remove("Id");
+            data.insert("id".to_string(), Json::String(id.to_string()));
+            block_entities.push((pos, Json::Object(data)));
+        }
+        let mut entities = Vec::new();
+        for entry in schematic.get("Entities").and_then(Json::as_array).into_iter().flatten() {
+            let Some(id) = entry.get("Id").and_then(Json::as_str) else { continue };
+            let position = match entry.get("Pos").and_then(Json::as_array).map(Vec::as_slice) {
+                Some([x, y, z]) => (x.as_f64().unwrap_or(0.0), y.as_f64().unwrap_or(0.0), z.as_f64().unwrap_or(0.0)),
+                _ => continue,
+            };
+            let data = if entry_data { entry.get("Data").cloned().unwrap_or(Json::Object(BTreeMap::new())) } else { entry.clone() };
+            entities.push((id.to_string(), position, data));
+        }
+        let metadata = schematic.get("Metadata");
+        let offset = (
+            number(metadata.and_then(|m| m.get("WEOffsetX"))).unwrap_or(0),
+            number(metadata.and_then(|m| m.get("WEOffsetY"))).unwrap_or(0),
+            number(metadata.and_then(|m| m.get("WEOffsetZ"))).unwrap_or(0),
+        );
+        Ok(Schematic { size, offset, blocks: schematic_blocks, block_entities, entities, unknown })
+    }
+
+    // schematics/<Name>.schem; Namen mit Pfadtrennern oder ".." werden abgelehnt
+    pub fn load(dir: &str, name: &str) -> Result<Schematic, String> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+            return Err(format!("Invalid schematic name '{}'", name));
+        }
+        let file = if name.ends_with(&format!(".{}", EXTENSION)) { name.to_string() } else { format!("{}.{}", name, EXTENSION) };
+        let path = Path::new(dir).join(file);
+        let data = fs::read(&path).map_err(|_| format!("Schematic '{}' not found", name))?;
+        Schematic::parse(&data).map_err(|e| format!("Could not read schematic '{}': {}", name, e))
+    }
+
+    // Blöcke für das Einfügen an einer Position; gedreht wird wie bei WorldEdit um die Position selbst
+    pub fn resolve(&self, origin: (i32, i32, i32), placement: &Placement, include_air: bool) -> Placed {
+        let at = |(x, y, z): (i32, i32, i32)| {
+            let (x, y, z) = structure::transform((x + self.offset.0, y + self.offset.1, z + self.offset.2), placement);
+            (origin.0 + x, origin.1 + y, origin.2 + z)
+        };
+        let mut placed = Placed::default();
+        for &(pos, state) in &self.blocks {
+            if include_air || state != block::AIR {
+                placed.blocks.push((at(pos), structure::transform_state(state, placement)));
+            }
+        }
+        placed.block_entities = self.block_entities.iter().map(|(pos, data)| (at(*pos), data.clone())).collect();
+        for (kind, (x, y, z), data) in &self.entities {
+            let offset = (x + self.offset.0 as f64, y + self.offset.1 as f64, z + self.offset.2 as f64);
+            let (x, y, z) = structure::transform_point(offset, placement);
+            let position = (origin.0 as f64 + x, origin.1 as f64 + y, origin.2 as f64 + z);
+            placed.entities.push(PlacedEntity { kind: kind.clone(), position, yaw: 0.0, nbt: data.clone() });
+        }
+        placed
+    }
+}
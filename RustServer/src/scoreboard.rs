@@ -0,0 +1,370 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::json::{self, Json};
+use crate::protocol::clientbound;
+use crate::protocol::types::{write_string_to_vec, write_varint_to_vec};
+use crate::selector::{self, Target};
+use crate::text::Text;
+use crate::{block, entity, Connection, Player, Server};
+
+const SCOREBOARD_FILE: &str = "data/scoreboard.json";
+// Anzeigeplätze in der Reihenfolge ihrer Nummern im Protokoll
+pub const SLOTS: [&str; 3] = ["list", "sidebar", "below_name"];
+// Diese Kriterien führt der Server jeden Tick selbst nach, von Hand lassen sie sich nicht ändern
+const READ_ONLY: &[&str] = &["health", "food", "air", "level", "xp"];
+const CRITERIA: &[&str] = &["dummy", "deathCount", "playerKillCount", "totalKillCount"];
+// Statistiken, die der Server bisher zählt; minecraft.custom:<name>
+const CUSTOM_STATS: &[&str] = &["deaths", "player_kills", "mob_kills", "damage_dealt", "damage_taken", "leave_game"];
+
+const MODE_CREATE: u8 = 0;
+const MODE_REMOVE: u8 = 1;
+
+#[derive(Debug, Clone)]
+pub struct Objective {
+    pub name: String,
+    // dummy, deathCount, health oder eine Statistik wie minecraft.mined:minecraft.stone
+    pub criterion: String,
+    pub display_name: String,
+    // Herzen statt Zahlen in Tab-Liste und Seitenleiste, bei health von Anfang an
+    pub hearts: bool,
+    // Spielernamen oder UUIDs anderer Entities, wie bei Vanilla
+    pub scores: BTreeMap<String, i32>,
+}
+
+impl Objective {
+    pub fn new(name: &str, criterion: &str, display_name: Option<&str>) -> Objective {
+        Objective {
+            name: name.to_string(),
+            criterion: criterion.to_string(),
+            display_name: display_name.unwrap_or(name).to_string(),
+            hearts: criterion == "health",
+            scores: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        READ_ONLY.contains(&self.criterion.as_str())
+    }
+
+    fn to_json(&self) -> Json {
+        let mut data = BTreeMap::new();
+        data.insert("name".to_string(), Json::String(self.name.clone()));
+        data.insert("criterion".to_string(), Json::String(self.criterion.clone()));
+        data.insert("display_name".to_string(), Json::String(self.display_name.clone()));
+        data.insert("hearts".to_string(), Json::Bool(self.hearts));
+        let scores = self.scores.iter().map(|(holder, score)| (holder.clone(), Json::Number(*score as f64))).collect();
+        data.insert("scores".to_string(), Json::Object(scores));
+        Json::Object(data)
+    }
+
+    fn from_json(data: &Json) -> Option<Objective> {
+        let name = data.get("name")?.as_str()?;
+        let criterion = parse_criterion(data.get("criterion")?.as_str()?).ok()?;
+        let mut objective = Objective::new(name, &criterion, data.get("display_name").and_then(Json::as_str));
+        objective.hearts = data.get("hearts").and_then(Json::as_bool).unwrap_or(objective.hearts);
+        for (holder, score) in data.get("scores").and_then(Json::as_object).into_iter().flatten() {
+            if let Some(score) = score.as_f64() {
+                objective.scores.insert(holder.clone(), score as i32);
+            }
+        }
+        Some(objective)
+    }
+}
+
+// minecraft.stone wird zu ("minecraft", "stone"); ohne Punkt gilt der Namensraum minecraft
+fn split_id(id: &str) -> (&str, &str) {
+    id.split_once('.').unwrap_or(("minecraft", id))
+}
+
+// Prüft ein Kriterium und gibt es in der Schreibweise von Vanilla zurück, Statistiken also als
+// minecraft.<Typ>:<Namensraum>.<Name>
+pub fn parse_criterion(name: &str) -> Result<String, String> {
+    if CRITERIA.contains(&name) || READ_ONLY.contains(&name) {
+        return Ok(name.to_string());
+    }
+    let unknown = || format!("Unknown criterion '{}'", name);
+    let (kind, value) = name.split_once(':').ok_or_else(unknown)?;
+    let (kind_namespace, kind) = split_id(kind);
+    let (namespace, path) = split_id(value);
+    let id = format!("{}:{}", namespace, path);
+    let known = kind_namespace == "minecraft"
+        && match kind {
+            "mined" => block::state_id(&id).is_some(),
+            "killed" | "killed_by" => entity::is_known(&id),
+            "custom" => namespace == "minecraft" && CUSTOM_STATS.contains(&path),
+            _ => false,
+        };
+    if !known {
+        return Err(unknown());
+    }
+    Ok(format!("minecraft.{}:{}.{}", kind, namespace, path))
+}
+
+// Statistik für einen Block oder Entity-Typ, etwa stat("mined", "minecraft:stone")
+pub fn stat(kind: &str, id: &str) -> String {
+    format!("minecraft.{}:{}", kind, id.replacen(':', ".", 1))
+}
+
+// Ziele, Punktestände und Anzeigeplätze aller Welten, gespeichert bei der Oberwelt
+#[derive(Debug)]
+pub struct Scoreboard {
+    path: PathBuf,
+    objectives: Vec<Objective>,
+    display: [Option<String>; 3],
+    changed: bool,
+}
+
+impl Scoreboard {
+    pub fn load(world_dir: &str) -> Scoreboard {
+        let path = Path::new(world_dir).join(SCOREBOARD_FILE);
+        let mut scoreboard = Scoreboard { path, objectives: Vec::new(), display: [None, None, None], changed: false };
+        let Ok(text) = fs::read_to_string(&scoreboard.path) else { return scoreboard };
+        let data = match json::parse(&text) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("{} ist beschädigt: {}", scoreboard.path.display(), e);
+                return scoreboard;
+            }
+        };
+        let objectives = data.get("objectives").and_then(Json::as_array).map(Vec::as_slice).unwrap_or_default();
+        scoreboard.objectives = objectives.iter().filter_map(Objective::from_json).collect();
+        if scoreboard.objectives.len() < objectives.len() {
+            warn!("{} ungültige Ziele in {} übersprungen", objectives.len() - scoreboard.objectives.len(), scoreboard.path.display());
+        }
+        for (slot, name) in SLOTS.iter().zip(scoreboard.display.iter_mut()) {
+            *name = data.get("display").and_then(|d| d.get(slot)).and_then(Json::as_str).map(str::to_string);
+        }
+        scoreboard
+    }
+
+    fn to_json(&self) -> Json {
+        let mut data = BTreeMap::new();
+        data.insert("objectives".to_string(), Json::Array(self.objectives.iter().map(Objective::to_json).collect()));
+        let display = SLOTS.iter().zip(&self.display).filter_map(|(slot, name)| Some((slot.to_string(), Json::String(name.clone()?)))).collect();
+        data.insert("display".to_string(), Json::Object(display));
+        Json::Object(data)
+    }
+
+    // Pfad und Inhalt zum Speichern, falls sich seit dem letzten Mal etwas geändert hat
+    pub fn take_changed(&mut self) -> Option<(PathBuf, Vec<u8>)> {
+        if !self.changed {
+            return None;
+        }
+        self.changed = false;
+        Some((self.path.clone(), self.to_json().to_json_string().into_bytes()))
+    }
+
+    // Nach einem fehlgeschlagenen Speichern beim nächsten Mal erneut versuchen
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+
+    pub fn objectives(&self) -> &[Objective] {
+        &self.objectives
+    }
+
+    pub fn objective(&self, name: &str) -> Option<&Objective> {
+        self.objectives.iter().find(|o| o.name == name)
+    }
+
+    pub fn displayed(&self, slot: usize) -> Option<&str> {
+        self.display[slot].as_deref()
+    }
+
+    fn is_displayed(&self, name: &str) -> bool {
+        self.display.iter().any(|d| d.as_deref() == Some(name))
+    }
+
+    // Alles Angezeigte für einen Spieler, der gerade beitritt
+    pub fn send_all(&self, connection: &Connection) -> Result<(), String> {
+        for objective in self.objectives.iter().filter(|o| self.is_displayed(&o.name)) {
+            send_objective(connection, objective)?;
+        }
+        for (slot, name) in self.display.iter().enumerate() {
+            if let Some(name) = name {
+                send_display(connection, slot, name)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add(&mut self, objective: Objective) -> Result<(), String> {
+        if self.objective(&objective.name).is_some() {
+            return Err("An objective already exists by that name".to_string());
+        }
+        self.objectives.push(objective);
+        self.changed = true;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, players: &[Player], name: &str) -> Option<Objective> {
+        let index = self.objectives.iter().position(|o| o.name == name)?;
+        if self.is_displayed(name) {
+            for player in players {
+                let _ = send_remove_objective(&player.connection, name);
+            }
+        }
+        for slot in self.display.iter_mut().filter(|d| d.as_deref() == Some(name)) {
+            *slot = None;
+        }
+        self.changed = true;
+        Some(self.objectives.remove(index))
+    }
+
+    // Zeigt ein Ziel an einem Platz an oder leert ihn. Clients kennen nur angezeigte Ziele: ein neu angezeigtes
+    // bekommen sie samt Punkten, ein nirgends mehr angezeigtes wird bei ihnen entfernt
+    pub fn set_display(&mut self, players: &[Player], slot: usize, name: Option<&str>) {
+        let old = self.display[slot].take();
+        if let Some(objective) = name.and_then(|name| self.objective(name)).filter(|o| !self.is_displayed(&o.name)) {
+            for player in players {
+                let _ = send_objective(&player.connection, objective);
+            }
+        }
+        self.display[slot] = name.map(str::to_string);
+        for player in players {
+            let _ = send_display(&player.connection, slot, name.unwrap_or_default());
+        }
+        if let Some(old) = old.filter(|old| !self.is_displayed(old)) {
+            for player in players {
+                let _ = send_remove_objective(&player.connection, &old);
+            }
+        }
+        self.changed = true;
+    }
+
+    pub fn get(&self, objective: &str, holder: &str) -> Option<i32> {
+        self.objective(objective)?.scores.get(holder).copied()
+    }
+
+    // Setzt einen Punktestand mit value(bisheriger Stand) und schickt ihn allen, solange das Ziel angezeigt wird
+    pub fn update(&mut self, players: &[Player], objective: &str, holder: &str, value: impl FnOnce(Option<i32>) -> i32) -> i32 {
+        let displayed = self.is_displayed(objective);
+        let Some(objective) = self.objectives.iter_mut().find(|o| o.name == objective) else { return 0 };
+        let old = objective.scores.get(holder).copied();
+        let new = value(old);
+        if old == Some(new) {
+            return new;
+        }
+        objective.scores.insert(holder.to_string(), new);
+        self.changed = true;
+        if displayed {
+            for player in players {
+                let _ = send_score(&player.connection, holder, &objective.name, new);
+            }
+        }
+        new
+    }
+
+    // Ohne Ziel alle Punkte des Halters; false, wenn er keine hatte
+    pub fn reset(&mut self, players: &[Player], holder: &str, objective: Option<&str>) -> bool {
+        let mut removed = false;
+        for o in self.objectives.iter_mut().filter(|o| objective.is_none_or(|name| o.name == name)) {
+            if o.scores.remove(holder).is_some() {
+                removed = true;
+                if self.display.iter().any(|d| d.as_deref() == Some(o.name.as_str())) {
+                    for player in players {
+                        let _ = send_reset(&player.connection, holder, &o.name);
+                    }
+                }
+            }
+        }
+        self.changed |= removed;
+        removed
+    }
+
+    // Alle, die in irgendeinem Ziel einen Punktestand haben
+    pub fn holders(&self) -> Vec<&str> {
+        let mut holders: Vec<&str> = self.objectives.iter().flat_map(|o| o.scores.keys().map(String::as_str)).collect();
+        holders.sort_unstable();
+        holders.dedup();
+        holders
+    }
+
+    // Zählt bei allen Zielen mit einem der Kriterien hoch, angefangen bei 0
+    pub fn award(&mut self, players: &[Player], holder: &str, criteria: &[&str], amount: i32) {
+        let names: Vec<String> = self.objectives.iter().filter(|o| criteria.contains(&o.criterion.as_str())).map(|o| o.name.clone()).collect();
+        for name in names {
+            self.update(players, &name, holder, |old| old.unwrap_or(0).wrapping_add(amount));
+        }
+    }
+
+    // Ein Spieler hat ein Ziel getötet; ein getöteter Spieler zählt auch beim Opfer
+    pub fn killed(&mut self, players: &[Player], killer: &str, victim: &Target) {
+        let killed = stat("killed", &victim.kind);
+        if victim.is_player() {
+            self.award(players, killer, &["playerKillCount", "totalKillCount", "minecraft.custom:minecraft.player_kills", &killed], 1);
+            self.award(players, &victim.name, &[&stat("killed_by", selector::PLAYER_TYPE)], 1);
+        } else {
+            self.award(players, killer, &["totalKillCount", "minecraft.custom:minecraft.mob_kills", &killed], 1);
+        }
+    }
+
+    // Führt Gesundheit, Nahrung, Luft und Erfahrung der Spieler nach
+    pub fn tick(&mut self, players: &[Player]) {
+        for criterion in READ_ONLY {
+            let names: Vec<String> = self.objectives.iter().filter(|o| o.criterion == *criterion).map(|o| o.name.clone()).collect();
+            for name in names {
+                for player in players {
+                    let value = match *criterion {
+                        "health" => (player.health + player.absorption).ceil() as i32,
+                        "food" => player.hunger.food,
+                        "air" => player.air,
+                        "level" => player.experience.level,
+                        _ => player.experience.total(),
+                    };
+                    self.update(players, &name, &player.username, |_| value);
+                }
+            }
+        }
+    }
+}
+
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let players = server.players.lock().unwrap();
+    server.scoreboard.lock().unwrap().tick(&players);
+}
+
+// Update Objectives: Ziel anlegen, danach seine Punkte
+fn send_objective(connection: &Connection, objective: &Objective) -> Result<(), String> {
+    let mut packet_data = write_string_to_vec(&objective.name);
+    packet_data.push(MODE_CREATE);
+    packet_data.extend(Text::new(&objective.display_name).to_nbt());
+    packet_data.extend(write_varint_to_vec(objective.hearts as i32));
+    packet_data.push(0); // Kein Zahlenformat
+    connection.send(clientbound::UPDATE_OBJECTIVES, &packet_data)?;
+    for (holder, score) in &objective.scores {
+        send_score(connection, holder, &objective.name, *score)?;
+    }
+    Ok(())
+}
+
+fn send_remove_objective(connection: &Connection, name: &str) -> Result<(), String> {
+    let mut packet_data = write_string_to_vec(name);
+    packet_data.push(MODE_REMOVE);
+    connection.send(clientbound::UPDATE_OBJECTIVES, &packet_data)
+}
+
+// Ein leerer Name leert den Platz
+fn send_display(connection: &Connection, slot: usize, name: &str) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(slot as i32);
+    packet_data.extend(write_string_to_vec(name));
+    connection.send(clientbound::DISPLAY_OBJECTIVE, &packet_data)
+}
+
+fn send_score(connection: &Connection, holder: &str, objective: &str, score: i32) -> Result<(), String> {
+    let mut packet_data = write_string_to_vec(holder);
+    packet_data.extend(write_string_to_vec(objective));
+    packet_data.extend(write_varint_to_vec(score));
+    packet_data.push(0); // Kein eigener Anzeigename
+    packet_data.push(0); // Kein Zahlenformat
+    connection.send(clientbound::UPDATE_SCORE, &packet_data)
+}
+
+fn send_reset(connection: &Connection, holder: &str, objective: &str) -> Result<(), String> {
+    let mut packet_data = write_string_to_vec(holder);
+    packet_data.push(1);
+    packet_data.extend(write_string_to_vec(objective));
+    connection.send(clientbound::RESET_SCORE, &packet_data)
+}
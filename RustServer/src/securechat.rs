@@ -0,0 +1,171 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rustmc_protocol::clientbound;
+use uuid::Uuid;
+use crate::config::ServerConfig;
+use crate::error::ServerError;
+use crate::rsa::{Digest, PublicKey};
+use crate::{motd, Connection, Player, Server};
+
+// Mojangs Schlüssel, mit dem die Sitzungsschlüssel der Clients signiert sind; liegt dem Vanilla-Server in authlib bei
+pub const MOJANG_KEY_FILE: &str = "yggdrasil_session_pubkey.der";
+// Version des Signaturformats seit 1.19.3
+const SIGNATURE_VERSION: i32 = 1;
+
+const OUT_OF_ORDER: &str = "Out-of-order chat packet received. Did your system time change?";
+const INVALID_SIGNATURE: &str = "Received chat packet with missing or invalid signature.";
+const MISSING_KEY: &str = "Missing profile public key.\nThis server requires secure profiles.";
+const EXPIRED_KEY: &str = "Expired profile public key. Check that your system time is synchronized, and try restarting your game.";
+const INVALID_KEY: &str = "Invalid signature for profile public key.";
+const VALIDATION_FAILED: &str = "Chat message validation failure";
+
+static MOJANG_KEY: OnceLock<Option<PublicKey>> = OnceLock::new();
+
+// Chat-Zustand eines Spielers. Der Server schickt Chat nur als Systemnachricht weiter, eine Signatur zeigt also kein
+// anderer Client an; geprüft wird sie mit enforce-secure-chat trotzdem, damit nur ungefälschter Chat durchkommt
+#[derive(Debug, Clone, Default)]
+pub struct ChatState {
+    session: Option<Session>,
+    // Zeitstempel der letzten Nachricht in Millisekunden; die Reihenfolge gilt auch ohne Signaturen
+    last_timestamp: i64,
+}
+
+#[derive(Debug, Clone)]
+struct Session {
+    id: Uuid,
+    key: PublicKey,
+    expires_at: i64,
+    // Position in der Kette dieser Sitzung, zählt mit jeder signierten Nachricht hoch
+    index: i32,
+}
+
+#[derive(Debug)]
+pub struct SignedChat<'a> {
+    pub message: &'a str,
+    pub timestamp: i64,
+    pub salt: i64,
+    pub signature: Option<&'a [u8]>,
+    pub offset: i32,
+    pub acknowledged: [u8; 3],
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64)
+}
+
+fn mojang_key() -> Option<&'static PublicKey> {
+    MOJANG_KEY
+        .get_or_init(|| match fs::read(MOJANG_KEY_FILE) {
+            Ok(der) => PublicKey::from_der(&der).map_err(|e| warn!("{} ist ungültig: {}", MOJANG_KEY_FILE, e)).ok(),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => {
+                warn!("Konnte {} nicht lesen: {}", MOJANG_KEY_FILE, e);
+                None
+            }
+        })
+        .as_ref()
+}
+
+// Beim Start: Signaturen gehören zum Mojang-Konto, im Offline-Modus ohne Proxy passt die UUID nie dazu
+pub fn check_config(config: &ServerConfig) {
+    if !config.enforce_secure_chat {
+        return;
+    }
+    if !config.bungeecord && !config.velocity_forwarding {
+        warn!("enforce-secure-chat ist an, aber ohne Proxy-Weiterleitung haben Spieler Offline-UUIDs! Signierter Chat wird dann abgelehnt");
+    }
+    if mojang_key().is_none() {
+        warn!("{} fehlt, Sitzungsschlüssel der Clients werden nur auf Ablauf geprüft", MOJANG_KEY_FILE);
+    }
+}
+
+// Server Data nach dem Beitritt: MOTD für das Pausenmenü und bis 1.20.4 auch, ob signierter Chat erzwungen wird. Das
+// Icon kennt der Client schon aus der Serverliste
+pub fn send_server_data(server: &Server, connection: &Connection) -> Result<(), String> {
+    let mut packet_data = motd::legacy(&server.motd).to_nbt();
+    packet_data.push(0); // Hat Icon (Boolean)
+    if !connection.version.has_1_20_5_login() {
+        packet_data.push(server.config.enforce_secure_chat as u8);
+    }
+    connection.send(clientbound::SERVER_DATA, &packet_data)
+}
+
+// Player Session: der Client meldet den Schlüssel, mit dem er ab jetzt signiert. Ohne enforce-secure-chat wird er
+// nicht gebraucht
+pub fn handle_session(server: &Server, player: &Player, session_id: Uuid, expires_at: i64, public_key: Vec<u8>, key_signature: Vec<u8>) -> Result<(), ServerError> {
+    if !server.config.enforce_secure_chat {
+        return Ok(());
+    }
+    if expires_at < now_millis() {
+        return Err(ServerError::Kick(EXPIRED_KEY.to_string()));
+    }
+    if let Some(mojang) = mojang_key() {
+        let mut signed = Vec::with_capacity(24 + public_key.len());
+        let (most, least) = player.uuid.as_u64_pair();
+        signed.extend(most.to_be_bytes());
+        signed.extend(least.to_be_bytes());
+        signed.extend(expires_at.to_be_bytes());
+        signed.extend(&public_key);
+        if !mojang.verify(Digest::Sha1, &signed, &key_signature) {
+            return Err(ServerError::Kick(INVALID_KEY.to_string()));
+        }
+    }
+    let key = PublicKey::from_der(&public_key).map_err(|_| ServerError::Kick(INVALID_KEY.to_string()))?;
+    let mut players = server.players.lock().unwrap();
+    let Some(live) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    // Dieselbe Sitzung noch einmal, etwa nach einem Dimensionswechsel, setzt die Kette nicht zurück
+    if live.chat.session.as_ref().is_some_and(|s| s.id == session_id) {
+        return Ok(());
+    }
+    debug!("Chat-Sitzung {} von {} bis {}", session_id, player.username, expires_at);
+    live.chat.session = Some(Session { id: session_id, key, expires_at, index: 0 });
+    Ok(())
+}
+
+// Vor dem Weiterleiten einer Chat-Nachricht; ein Fehler trennt den Client wie bei Vanilla
+pub fn check_message(server: &Server, player: &Player, chat: &SignedChat) -> Result<(), ServerError> {
+    let mut players = server.players.lock().unwrap();
+    let Some(live) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    if chat.timestamp < live.chat.last_timestamp {
+        return Err(ServerError::Kick(OUT_OF_ORDER.to_string()));
+    }
+    live.chat.last_timestamp = chat.timestamp;
+    // Spielerchat schickt der Server nie, also kann der Client auch keinen gesehen haben
+    if chat.offset != 0 || chat.acknowledged != [0; 3] {
+        return Err(ServerError::Kick(VALIDATION_FAILED.to_string()));
+    }
+    if !server.config.enforce_secure_chat {
+        return Ok(());
+    }
+    let Some(session) = live.chat.session.as_mut() else { return Err(ServerError::Kick(MISSING_KEY.to_string())) };
+    if session.expires_at < now_millis() {
+        return Err(ServerError::Kick(EXPIRED_KEY.to_string()));
+    }
+    let Some(signature) = chat.signature else { return Err(ServerError::Kick(INVALID_SIGNATURE.to_string())) };
+    if !session.key.verify(Digest::Sha256, &signed_data(player.uuid, session, chat), signature) {
+        return Err(ServerError::Kick(INVALID_SIGNATURE.to_string()));
+    }
+    session.index += 1;
+    Ok(())
+}
+
+// Wie SignedMessageLink und SignedMessageBody bei Vanilla: Version, Glied der Kette, dann der Inhalt mit dem
+// Zeitstempel in Sekunden und den gesehenen Nachrichten (hier immer keine)
+fn signed_data(sender: Uuid, session: &Session, chat: &SignedChat) -> Vec<u8> {
+    let mut data = Vec::with_capacity(64 + chat.message.len());
+    data.extend(SIGNATURE_VERSION.to_be_bytes());
+    for uuid in [sender, session.id] {
+        let (most, least) = uuid.as_u64_pair();
+        data.extend(most.to_be_bytes());
+        data.extend(least.to_be_bytes());
+    }
+    data.extend(session.index.to_be_bytes());
+    data.extend(chat.salt.to_be_bytes());
+    data.extend(chat.timestamp.div_euclid(1000).to_be_bytes());
+    data.extend((chat.message.len() as i32).to_be_bytes());
+    data.extend(chat.message.as_bytes());
+    data.extend(0i32.to_be_bytes());
+    data
+}
@@ -0,0 +1,273 @@
+use rand::seq::SliceRandom;
+use uuid::Uuid;
+use crate::command::{CommandContext, CommandSender};
+use crate::dimension::{self, Dimension};
+use crate::{GameMode, Mob, Player, Server};
+
+pub const PLAYER_TYPE: &str = "minecraft:player";
+
+// Ein aufgelöstes Ziel; Momentaufnahme, damit Befehle ohne gehaltene Sperre weiterarbeiten können
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub uuid: Uuid,
+    pub name: String,
+    // Namespaced Entity-Typ, z.B. minecraft:player oder minecraft:zombie
+    pub kind: String,
+    pub position: (f64, f64, f64),
+    pub dimension: Dimension,
+    pub rotation: (f32, f32),
+    pub game_mode: Option<GameMode>,
+    pub tags: Vec<String>,
+}
+
+impl Target {
+    pub fn of_player(player: &Player) -> Target {
+        Target { uuid: player.uuid, name: player.username.clone(), kind: PLAYER_TYPE.to_string(), position: player.position, dimension: player.dimension, rotation: player.rotation, game_mode: Some(player.game_mode), tags: player.tags.clone() }
+    }
+
+    pub fn of_mob(mob: &Mob, dimension: Dimension) -> Target {
+        Target { uuid: mob.id, name: mob.display_name(), kind: namespaced(&mob.mob_type), position: mob.position, dimension, rotation: (0.0, 0.0), game_mode: None, tags: mob.tags.clone() }
+    }
+
+    pub fn is_player(&self) -> bool {
+        self.kind == PLAYER_TYPE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
+// Filter aus den eckigen Klammern; bei type, gamemode, name und tag dreht ! die Bedingung um. tag darf mehrfach
+// vorkommen und muss dann jedes Mal passen
+#[derive(Debug, Default)]
+struct Filters {
+    distance: Option<(Option<f64>, Option<f64>)>,
+    game_mode: Option<(bool, String)>,
+    kind: Option<(bool, String)>,
+    name: Option<(bool, String)>,
+    tags: Vec<(bool, String)>,
+    limit: Option<usize>,
+    sort: Option<Sort>,
+}
+
+fn namespaced(id: &str) -> String {
+    let id = id.to_ascii_lowercase();
+    if id.contains(':') { id } else { format!("minecraft:{}", id) }
+}
+
+// 5, 1..5, ..5 oder 5..
+fn parse_range(value: &str) -> Result<(Option<f64>, Option<f64>), String> {
+    let number = |s: &str| -> Result<Option<f64>, String> {
+        if s.is_empty() {
+            return Ok(None);
+        }
+        s.parse::<f64>().map(Some).map_err(|_| format!("Invalid range '{}'", value))
+    };
+    let (min, max) = match value.split_once("..") {
+        Some((min, max)) => (number(min)?, number(max)?),
+        None => {
+            let exact = number(value)?;
+            (exact, exact)
+        }
+    };
+    if min.is_none() && max.is_none() {
+        return Err(format!("Invalid range '{}'", value));
+    }
+    if min.is_some_and(|min| min < 0.0) || matches!((min, max), (Some(min), Some(max)) if min > max) {
+        return Err(format!("Invalid range '{}'", value));
+    }
+    Ok((min, max))
+}
+
+fn negatable(value: &str) -> (bool, String) {
+    match value.strip_prefix('!') {
+        Some(value) => (true, value.to_string()),
+        None => (false, value.to_string()),
+    }
+}
+
+fn parse_filters(arguments: &str) -> Result<Filters, String> {
+    let mut filters = Filters::default();
+    for argument in arguments.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+        let (key, value) = argument.split_once('=').ok_or_else(|| format!("Expected key=value in selector, got '{}'", argument))?;
+        let value = value.trim();
+        match key.trim() {
+            "distance" => filters.distance = Some(parse_range(value)?),
+            "gamemode" => {
+                let (negated, mode) = negatable(value);
+                if GameMode::from_name(&mode).is_none() {
+                    return Err(format!("Invalid game mode '{}'", mode));
+                }
+                filters.game_mode = Some((negated, mode));
+            }
+            "type" => {
+                let (negated, kind) = negatable(value);
+                filters.kind = Some((negated, namespaced(&kind)));
+            }
+            "name" => filters.name = Some(negatable(value)),
+            "tag" => filters.tags.push(negatable(value)),
+            "limit" => match value.parse::<usize>() {
+                Ok(limit) if limit > 0 => filters.limit = Some(limit),
+                _ => return Err("Limit must be at least 1".to_string()),
+            },
+            "sort" => {
+                filters.sort = Some(match value {
+                    "nearest" => Sort::Nearest,
+                    "furthest" => Sort::Furthest,
+                    "random" => Sort::Random,
+                    "arbitrary" => Sort::Arbitrary,
+                    _ => return Err(format!("Invalid sort type '{}'", value)),
+                })
+            }
+            other => return Err(format!("Unknown selector option '{}'", other)),
+        }
+    }
+    Ok(filters)
+}
+
+fn distance(a: (f64, f64, f64), b: (f64, f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+fn matches(target: &Target, filters: &Filters, origin: (f64, f64, f64)) -> bool {
+    if let Some((min, max)) = filters.distance {
+        let d = distance(target.position, origin);
+        if min.is_some_and(|min| d < min) || max.is_some_and(|max| d > max) {
+            return false;
+        }
+    }
+    if let Some((negated, mode)) = &filters.game_mode {
+        let is = target.game_mode.is_some() && target.game_mode == GameMode::from_name(mode);
+        // Nicht-Spieler haben keinen Spielmodus und fallen bei jedem gamemode-Filter heraus
+        if target.game_mode.is_none() || is == *negated {
+            return false;
+        }
+    }
+    if let Some((negated, kind)) = &filters.kind {
+        if (target.kind == *kind) == *negated {
+            return false;
+        }
+    }
+    if let Some((negated, name)) = &filters.name {
+        if (target.name == *name) == *negated {
+            return false;
+        }
+    }
+    // tag= passt nur auf Entities ohne Tags, tag=! auf alle mit mindestens einem
+    for (negated, tag) in &filters.tags {
+        let has = if tag.is_empty() { target.tags.is_empty() } else { target.tags.contains(tag) };
+        if has == *negated {
+            return false;
+        }
+    }
+    true
+}
+
+fn snapshot(server: &Server, players_only: bool) -> Vec<Target> {
+    let players = server.players.lock().unwrap();
+    let mut targets: Vec<Target> = players.iter().map(Target::of_player).collect();
+    drop(players);
+    if !players_only {
+        for world in dimension::worlds(server) {
+            let world = world.lock().unwrap();
+            targets.extend(world.mobs.iter().map(|mob| Target::of_mob(mob, world.dimension)));
+        }
+    }
+    targets
+}
+
+// Die Entity hinter @s: der ausführende Spieler oder die mit /execute as gewählte
+pub fn executor(ctx: &CommandContext) -> Option<Target> {
+    let uuid = ctx.entity?;
+    let players_only = matches!(ctx.sender, CommandSender::Player(id) if id == uuid);
+    snapshot(ctx.server, players_only).into_iter().find(|t| t.uuid == uuid)
+}
+
+// Ausführender und Bezugspunkt für distance und sort samt dessen Welt, auch aus /execute in; die Konsole steht am
+// Ursprung der Oberwelt
+fn origin(ctx: &CommandContext) -> (Option<Target>, (f64, f64, f64), Dimension) {
+    let sender = executor(ctx);
+    let position = ctx.position.or(sender.as_ref().map(|t| t.position)).unwrap_or((0.0, 0.0, 0.0));
+    let dimension = ctx.dimension.or(sender.as_ref().map(|t| t.dimension)).unwrap_or(Dimension::Overworld);
+    (sender, position, dimension)
+}
+
+// Löst @p, @a, @r, @e, @s (mit optionalen [Filtern]), Spielernamen und UUIDs auf.
+// Ein leeres Ergebnis ist ein Fehler, wie bei Vanilla
+pub fn resolve(ctx: &CommandContext, arg: &str) -> Result<Vec<Target>, String> {
+    let targets = resolve_all(ctx, arg)?;
+    if targets.is_empty() {
+        return Err(match arg.strip_prefix('@') {
+            None => format!("Player {} is not online", arg),
+            Some(selector) if selector.starts_with('e') => "No entity was found".to_string(),
+            Some(_) => "No player was found".to_string(),
+        });
+    }
+    Ok(targets)
+}
+
+// Wie resolve, aber ohne Treffer nur eine leere Liste, etwa für /execute if entity
+pub fn resolve_all(ctx: &CommandContext, arg: &str) -> Result<Vec<Target>, String> {
+    let Some(selector) = arg.strip_prefix('@') else {
+        let uuid = Uuid::parse_str(arg).ok();
+        let found = snapshot(ctx.server, uuid.is_none())
+            .into_iter()
+            .find(|t| (t.is_player() && t.name.eq_ignore_ascii_case(arg)) || Some(t.uuid) == uuid);
+        return Ok(found.into_iter().collect());
+    };
+    let (variable, filters) = match selector.split_once('[') {
+        Some((variable, rest)) => {
+            let arguments = rest.strip_suffix(']').ok_or("Expected ']' at the end of the selector")?;
+            (variable, parse_filters(arguments)?)
+        }
+        None => (selector, Filters::default()),
+    };
+    let (sender, origin, origin_dimension) = origin(ctx);
+    let (mut targets, default_sort, default_limit) = match variable {
+        "p" => (snapshot(ctx.server, true), Sort::Nearest, Some(1)),
+        "r" => (snapshot(ctx.server, true), Sort::Random, Some(1)),
+        "a" => (snapshot(ctx.server, true), Sort::Arbitrary, None),
+        "e" => (snapshot(ctx.server, false), Sort::Arbitrary, None),
+        "s" => (sender.into_iter().collect(), Sort::Arbitrary, None),
+        _ => return Err(format!("Unknown selector type '@{}'", variable)),
+    };
+    let sort = filters.sort.unwrap_or(default_sort);
+    // Entfernungen gibt es wie bei Vanilla nur innerhalb der Welt des Bezugspunkts
+    if filters.distance.is_some() || matches!(sort, Sort::Nearest | Sort::Furthest) {
+        targets.retain(|t| t.dimension == origin_dimension);
+    }
+    targets.retain(|t| matches(t, &filters, origin));
+    match sort {
+        Sort::Nearest => targets.sort_by(|a, b| distance(a.position, origin).total_cmp(&distance(b.position, origin))),
+        Sort::Furthest => targets.sort_by(|a, b| distance(b.position, origin).total_cmp(&distance(a.position, origin))),
+        Sort::Random => targets.shuffle(&mut rand::thread_rng()),
+        Sort::Arbitrary => {}
+    }
+    if let Some(limit) = filters.limit.or(default_limit) {
+        targets.truncate(limit);
+    }
+    Ok(targets)
+}
+
+// Für Befehle, die nur Spieler betreffen können
+pub fn resolve_players(ctx: &CommandContext, arg: &str) -> Result<Vec<Target>, String> {
+    let targets = resolve(ctx, arg)?;
+    if targets.iter().any(|t| !t.is_player()) {
+        return Err("Only players may be affected by this command, but the provided selector includes entities".to_string());
+    }
+    Ok(targets)
+}
+
+// Genau ein Ziel, etwa als Teleportziel
+pub fn resolve_single(ctx: &CommandContext, arg: &str) -> Result<Target, String> {
+    let mut targets = resolve(ctx, arg)?;
+    if targets.len() > 1 {
+        return Err("Only one entity is allowed, but the provided selector allows more than one".to_string());
+    }
+    Ok(targets.remove(0))
+}
@@ -0,0 +1,100 @@
+use std::io::Cursor;
+use byteorder::ReadBytesExt;
+use crate::lang;
+use crate::protocol::types::{read_bounded_string, read_varint_from_cursor};
+use crate::protocol::ProtocolError;
+use crate::VIEW_DISTANCE;
+
+// Vanilla rechnet mit mindestens zwei Chunks Sichtweite
+const MIN_VIEW_DISTANCE: i32 = 2;
+// Alle sieben Skin-Ebenen (Umhang, Jacke, Ärmel, Hosenbeine, Hut)
+const ALL_SKIN_PARTS: u8 = 0x7F;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChatMode {
+    Full,
+    CommandsOnly,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MainHand {
+    Left,
+    Right,
+}
+
+// Client Information: schickt der Client beim Beitritt und nach jeder Änderung in den Optionen
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientSettings {
+    pub locale: String,
+    // Wie vom Client gewünscht; der Server schickt höchstens VIEW_DISTANCE, siehe view_distance()
+    pub requested_view_distance: i32,
+    pub chat_mode: ChatMode,
+    pub chat_colors: bool,
+    pub skin_parts: u8,
+    pub main_hand: MainHand,
+    pub text_filtering: bool,
+    pub allow_server_listings: bool,
+}
+
+impl Default for ClientSettings {
+    fn default() -> ClientSettings {
+        ClientSettings {
+            locale: lang::DEFAULT_LOCALE.to_string(),
+            requested_view_distance: VIEW_DISTANCE,
+            chat_mode: ChatMode::Full,
+            chat_colors: true,
+            skin_parts: ALL_SKIN_PARTS,
+            main_hand: MainHand::Right,
+            text_filtering: false,
+            allow_server_listings: true,
+        }
+    }
+}
+
+impl ClientSettings {
+    pub fn read(cursor: &mut Cursor<Vec<u8>>) -> Result<ClientSettings, ProtocolError> {
+        let locale = read_bounded_string(cursor, lang::MAX_LOCALE_LENGTH)?;
+        let view_distance = cursor.read_i8()?;
+        let chat_mode = match read_varint_from_cursor(cursor)? {
+            0 => ChatMode::Full,
+            1 => ChatMode::CommandsOnly,
+            2 => ChatMode::Hidden,
+            other => return Err(ProtocolError::Malformed(format!("Unknown chat mode {}", other))),
+        };
+        let chat_colors = cursor.read_u8()? != 0;
+        let skin_parts = cursor.read_u8()? & ALL_SKIN_PARTS;
+        let main_hand = match read_varint_from_cursor(cursor)? {
+            0 => MainHand::Left,
+            1 => MainHand::Right,
+            other => return Err(ProtocolError::Malformed(format!("Unknown main hand {}", other))),
+        };
+        let text_filtering = cursor.read_u8()? != 0;
+        let allow_server_listings = cursor.read_u8()? != 0;
+        Ok(ClientSettings {
+            locale: lang::normalize_locale(&locale),
+            requested_view_distance: view_distance as i32,
+            chat_mode,
+            chat_colors,
+            skin_parts,
+            main_hand,
+            text_filtering,
+            allow_server_listings,
+        })
+    }
+
+    // Tatsächliche Sichtweite in Chunks für Chunk- und Entity-Versand
+    pub fn view_distance(&self) -> i32 {
+        self.requested_view_distance.clamp(MIN_VIEW_DISTANCE, VIEW_DISTANCE)
+    }
+
+    // Chatnachrichten anderer Spieler und private Nachrichten
+    pub fn accepts_chat(&self) -> bool {
+        self.chat_mode == ChatMode::Full
+    }
+
+    // Befehlsantworten und Servermeldungen; nur "Ausgeblendet" unterdrückt auch sie
+    pub fn accepts_system_messages(&self) -> bool {
+        self.chat_mode != ChatMode::Hidden
+    }
+}
@@ -0,0 +1,93 @@
+use crate::gamemode::GameMode;
+use crate::item;
+use crate::{bow, entity, Player, Server};
+
+pub const SHIELD: &str = "minecraft:shield";
+// Wie bei Vanilla wehrt der Schild erst ab, wenn er fünf Ticks oben ist
+const BLOCK_DELAY_TICKS: u32 = 5;
+// Entity Events, zu denen der Client den Ton spielt
+const EVENT_SHIELD_BLOCK: u8 = 29;
+const EVENT_SHIELD_BREAK: u8 = 30;
+// Erst ab drei Schaden nutzt sich der Schild ab, dann um 1 plus den abgerundeten Schaden
+const MIN_WEAR_DAMAGE: f32 = 3.0;
+
+// Ein erhobener Schild in Haupt- oder Zweithand
+#[derive(Debug, Clone)]
+pub struct Blocking {
+    slot: usize,
+    ticks: u32,
+}
+
+// Use Item mit einem Schild: der Spieler hebt ihn, bis er die Taste loslässt. false, wenn das Item kein Schild ist
+pub fn use_item(players: &mut [Player], index: usize, slot: usize) -> bool {
+    let player = &mut players[index];
+    if player.inventory.slot(slot).item != SHIELD {
+        return false;
+    }
+    player.blocking = Some(Blocking { slot, ticks: 0 });
+    let off_hand = slot != player.inventory.hand_slot(false);
+    bow::broadcast_hand(players, index, Some(off_hand));
+    true
+}
+
+// Schild senken, etwa beim Loslassen oder beim Wechsel des Items
+pub fn stop(players: &mut [Player], index: usize) {
+    if players[index].blocking.take().is_some() {
+        bow::broadcast_hand(players, index, None);
+    }
+}
+
+// Nur Treffer von vorn: die Richtung von der Quelle zum Spieler zeigt gegen die Blickrichtung
+fn faces(player: &Player, (x, _, z): (f64, f64, f64)) -> bool {
+    let Some(blocking) = &player.blocking else { return false };
+    if blocking.ticks < BLOCK_DELAY_TICKS {
+        return false;
+    }
+    let yaw = (player.rotation.0 as f64).to_radians();
+    let (dx, dz) = (player.position.0 - x, player.position.2 - z);
+    dx * -yaw.sin() + dz * yaw.cos() < 0.0
+}
+
+// Wehrt einen Treffer aus origin ab und nutzt den Schild ab; true, wenn der Spieler keinen Schaden nimmt
+pub fn block(players: &mut [Player], index: usize, origin: (f64, f64, f64), amount: f32) -> bool {
+    let player = &mut players[index];
+    let Some(slot) = player.blocking.as_ref().map(|blocking| blocking.slot).filter(|_| faces(player, origin)) else { return false };
+    let mut broken = false;
+    if amount >= MIN_WEAR_DAMAGE && player.game_mode != GameMode::Creative {
+        let mut stack = player.inventory.slot(slot).clone();
+        for _ in 0..1 + amount.floor() as u32 {
+            if item::wear(&mut stack) {
+                broken = true;
+                break;
+            }
+        }
+        player.inventory.set(slot, stack);
+        let _ = player.inventory.send_slot(&player.connection, slot);
+    }
+    let event = if broken { EVENT_SHIELD_BREAK } else { EVENT_SHIELD_BLOCK };
+    debug!("{} wehrt einen Treffer mit dem Schild ab", player.username);
+    let _ = entity::send_event(&player.connection, entity::OWN_PLAYER_ID, event);
+    let player = &players[index];
+    for viewer in players.iter().filter(|p| p.uuid != player.uuid && entity::in_view(p, player.dimension, player.position)) {
+        let _ = entity::send_event(&viewer.connection, player.entity_id, event);
+    }
+    if broken {
+        stop(players, index);
+    }
+    true
+}
+
+// Zählt, wie lange der Schild oben ist; wer stirbt oder ihn nicht mehr hält, senkt ihn. Darf nur ohne gehaltene
+// Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut players = server.players.lock().unwrap();
+    for index in 0..players.len() {
+        let player = &mut players[index];
+        let Some(blocking) = &mut player.blocking else { continue };
+        if player.health > 0.0 && player.inventory.slot(blocking.slot).item == SHIELD {
+            blocking.ticks += 1;
+            continue;
+        }
+        stop(&mut players, index);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use byteorder::{BigEndian, ReadBytesExt};
+use crate::blockentity::BlockEntity;
+use crate::error::ServerError;
+use crate::gamemode::GameMode;
+use crate::json::Json;
+use crate::protocol::clientbound;
+use crate::protocol::types::{decode_position, encode_position, read_bounded_string};
+use crate::text::Text;
+use crate::{block, entity, permission, Connection, Player, Server, World};
+
+const LINES: usize = 4;
+// Längste Zeile, die Vanilla im Update-Sign-Paket annimmt
+const MAX_LINE_LENGTH: usize = 384;
+// Wie weit der Spieler vom Schild entfernt sein darf, wenn er den Text abschickt
+const MAX_EDIT_DISTANCE: f64 = 8.0;
+const DEFAULT_COLOR: &str = "black";
+const DYE_COLORS: &[&str] = &[
+    "white", "orange", "magenta", "light_blue", "yellow", "lime", "pink", "gray", "light_gray", "cyan", "purple", "blue", "brown", "green", "red", "black",
+];
+
+// Eine Seite eines Schilds: vier Zeilen Klartext, Farbe und ob der Text leuchtet
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignText {
+    pub lines: [String; LINES],
+    pub color: String,
+    pub glowing: bool,
+}
+
+impl SignText {
+    pub fn new() -> SignText {
+        SignText { lines: Default::default(), color: DEFAULT_COLOR.to_string(), glowing: false }
+    }
+
+    // {"messages": ["..", ..], "color": "black", "has_glowing_text": false}
+    pub fn to_json(&self) -> Json {
+        let mut data = BTreeMap::new();
+        data.insert("messages".to_string(), Json::Array(self.lines.iter().map(|line| Json::String(line.clone())).collect()));
+        data.insert("color".to_string(), Json::String(self.color.clone()));
+        data.insert("has_glowing_text".to_string(), Json::Bool(self.glowing));
+        Json::Object(data)
+    }
+
+    pub fn from_json(data: Option<&Json>) -> SignText {
+        let mut text = SignText::new();
+        let Some(data) = data else { return text };
+        for (line, message) in text.lines.iter_mut().zip(data.get("messages").and_then(Json::as_array).into_iter().flatten()) {
+            *line = message.as_str().unwrap_or_default().to_string();
+        }
+        if let Some(color) = data.get("color").and_then(Json::as_str).filter(|c| DYE_COLORS.contains(c)) {
+            text.color = color.to_string();
+        }
+        text.glowing = data.get("has_glowing_text").and_then(Json::as_bool).unwrap_or(false);
+        text
+    }
+
+    // Für den Client sind die Zeilen Textkomponenten als JSON-Strings
+    pub fn to_nbt_json(&self) -> Json {
+        let mut data = self.to_json();
+        if let Json::Object(map) = &mut data {
+            let messages = self.lines.iter().map(|line| Json::String(Text::new(line.clone()).to_json_string())).collect();
+            map.insert("messages".to_string(), Json::Array(messages));
+        }
+        data
+    }
+}
+
+// Open Sign Editor: der Client zeigt den Editor für die Vorder- oder Rückseite
+fn send_editor(connection: &Connection, (x, y, z): (i32, i32, i32), front: bool) -> Result<(), String> {
+    let mut packet_data = encode_position(x, y, z).to_be_bytes().to_vec();
+    packet_data.push(front as u8);
+    connection.send(clientbound::OPEN_SIGN_EDITOR, &packet_data)
+}
+
+// Vorderseite zeigt bei stehenden Schildern in Richtung rotation * 22.5° (0 = Süden), bei Wandschildern in
+// Richtung facing. Wer davor steht, bearbeitet und färbt die Vorderseite
+fn faces_front(state: u16, (x, _, z): (i32, i32, i32), player: &Player) -> bool {
+    let (nx, nz) = match block::property(state, "facing") {
+        Some("north") => (0.0, -1.0),
+        Some("south") => (0.0, 1.0),
+        Some("west") => (-1.0, 0.0),
+        Some("east") => (1.0, 0.0),
+        _ => {
+            let rotation = block::property(state, "rotation").and_then(|r| r.parse::<f64>().ok()).unwrap_or(0.0);
+            let angle = (rotation * 22.5).to_radians();
+            (-angle.sin(), angle.cos())
+        }
+    };
+    (player.position.0 - (x as f64 + 0.5)) * nx + (player.position.2 - (z as f64 + 0.5)) * nz >= 0.0
+}
+
+// Schild, das der Spieler gerade bearbeiten darf; der Editor geht auf
+fn open_editor(world: &mut World, player: &Player, position: (i32, i32, i32), front: bool) {
+    if let Some(BlockEntity::Sign { editor, .. }) = world.block_entity_mut(position) {
+        *editor = Some(player.uuid);
+        let _ = send_editor(&player.connection, position, front);
+    }
+}
+
+// Schickt die Block-Entity an alle, die den Chunk sehen
+fn broadcast(players: &[Player], world: &World, position: (i32, i32, i32)) {
+    let Some(sign) = world.block_entity(position) else { return };
+    let center = (position.0 as f64 + 0.5, position.1 as f64, position.2 as f64 + 0.5);
+    for player in players.iter().filter(|p| entity::in_view(p, world.dimension, center)) {
+        let _ = sign.send_data(&player.connection, position);
+    }
+}
+
+// Rechtsklick auf ein Schild: Farbstoff färbt, Leuchttintenbeutel bringt den Text zum Leuchten, Tintenbeutel löscht
+// das Leuchten, Honigwabe wachst es; sonst öffnet sich der Editor. Gewachste Schilder bleiben, wie sie sind.
+// false, wenn der geklickte Block kein Schild ist
+pub fn use_on(server: &Server, players: &mut [Player], world: &mut World, index: usize, hand_slot: usize, position: (i32, i32, i32)) -> bool {
+    let state = world.get_block(position.0, position.1, position.2);
+    let player = &players[index];
+    let Some(BlockEntity::Sign { front, back, waxed, .. }) = world.block_entity(position) else { return false };
+    if *waxed || !may_edit(server, player) {
+        return true;
+    }
+    let is_front = faces_front(state, position, player);
+    let mut side = if is_front { front.clone() } else { back.clone() };
+    let held = player.inventory.slot(hand_slot).item.clone();
+    let changed = match held.strip_prefix("minecraft:") {
+        Some("glow_ink_sac") => !std::mem::replace(&mut side.glowing, true),
+        Some("ink_sac") => std::mem::replace(&mut side.glowing, false),
+        Some("honeycomb") => {
+            if let Some(BlockEntity::Sign { waxed, .. }) = world.block_entity_mut(position) {
+                *waxed = true;
+            }
+            true
+        }
+        Some(dye) if dye.strip_suffix("_dye").is_some_and(|color| DYE_COLORS.contains(&color)) => {
+            let color = dye.trim_end_matches("_dye").to_string();
+            std::mem::replace(&mut side.color, color) != side.color
+        }
+        _ => {
+            open_editor(world, player, position, is_front);
+            return true;
+        }
+    };
+    if !changed {
+        return true;
+    }
+    if let Some(BlockEntity::Sign { front, back, .. }) = world.block_entity_mut(position) {
+        *(if is_front { front } else { back }) = side;
+    }
+    world.dirty_chunks.insert((position.0 >> 4, position.2 >> 4));
+    let player = &mut players[index];
+    if player.game_mode != GameMode::Creative {
+        player.inventory.consume_one(hand_slot);
+        let _ = player.inventory.send_slot(&player.connection, hand_slot);
+    }
+    broadcast(players, world, position);
+    true
+}
+
+fn may_edit(server: &Server, player: &Player) -> bool {
+    player.game_mode != GameMode::Spectator && server.permissions.lock().unwrap().has(&player.username, Some(player.uuid), permission::BUILD)
+}
+
+// Setzt ein Schild aus der Hand an die angeklickte Seite: oben ein stehendes, zum Spieler gedreht, an den Seiten
+// ein Wandschild. Unter Blöcke hängen nur Hängeschilder, die es noch nicht gibt
+pub fn place(players: &mut [Player], world: &mut World, index: usize, hand_slot: usize, clicked: (i32, i32, i32), face: i32) {
+    let player = &players[index];
+    let Some(wood) = player.inventory.slot(hand_slot).item.strip_suffix("_sign").map(str::to_string) else { return };
+    let (x, y, z) = clicked;
+    let (position, spec) = match face {
+        1 => {
+            let rotation = ((180.0 + player.rotation.0 as f64) * 16.0 / 360.0 + 0.5).floor() as i32 & 15;
+            ((x, y + 1, z), format!("{}_sign[rotation={}]", wood, rotation))
+        }
+        2 => ((x, y, z - 1), format!("{}_wall_sign[facing=north]", wood)),
+        3 => ((x, y, z + 1), format!("{}_wall_sign[facing=south]", wood)),
+        4 => ((x - 1, y, z), format!("{}_wall_sign[facing=west]", wood)),
+        5 => ((x + 1, y, z), format!("{}_wall_sign[facing=east]", wood)),
+        _ => return,
+    };
+    let Ok(state) = block::parse_state(&spec) else { return };
+    if world.get_block(position.0, position.1, position.2) != block::AIR || !block::is_solid(world.get_block(x, y, z)) {
+        return;
+    }
+    if !world.set_block_and_notify(position, state) {
+        return;
+    }
+    let player = &mut players[index];
+    if player.game_mode != GameMode::Creative {
+        player.inventory.consume_one(hand_slot);
+        let _ = player.inventory.send_slot(&player.connection, hand_slot);
+    }
+    open_editor(world, &players[index], position, true);
+}
+
+// Update Sign: der Client schickt die vier Zeilen einer Seite, nachdem der Editor zu ist
+pub fn handle_update(players: &[Player], world: &mut World, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let position = cursor.read_i64::<BigEndian>()?;
+    let front = cursor.read_u8()?;
+    let mut lines: [String; LINES] = Default::default();
+    for line in lines.iter_mut() {
+        let text = read_bounded_string(cursor, MAX_LINE_LENGTH)?;
+        // Formatierungscodes und Steuerzeichen wie bei Vanilla herausfiltern
+        *line = text.chars().filter(|c| *c != '§' && !c.is_control()).collect();
+    }
+    let position = decode_position(position);
+    let Some(p) = players.iter().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    let center = (position.0 as f64 + 0.5, position.1 as f64 + 0.5, position.2 as f64 + 0.5);
+    let distance = ((p.position.0 - center.0).powi(2) + (p.position.1 - center.1).powi(2) + (p.position.2 - center.2).powi(2)).sqrt();
+    let Some(BlockEntity::Sign { front: front_text, back: back_text, waxed, editor }) = world.block_entity_mut(position) else { return Ok(()) };
+    if *editor != Some(p.uuid) || *waxed || distance > MAX_EDIT_DISTANCE {
+        debug!("{} darf das Schild bei {:?} nicht bearbeiten", p.username, position);
+        return Ok(());
+    }
+    *editor = None;
+    let side = if front != 0 { front_text } else { back_text };
+    side.lines = lines;
+    world.dirty_chunks.insert((position.0 >> 4, position.2 >> 4));
+    broadcast(players, world, position);
+    Ok(())
+}
@@ -0,0 +1,294 @@
+use crate::dimension::{self, Dimension};
+use crate::gamemode::GameMode;
+use crate::text::Text;
+use crate::{animation, block, entity, explosion, face_offset, gamerule, pose, send_action_bar, send_system_component, send_teleport, send_time, send_weather,
+    Player, Server, SpawnPoint, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Schlafen geht nachts oder bei Gewitter; bei Regen wird es etwas früher dunkel genug
+const NIGHT: (i64, i64) = (12542, 23459);
+const RAINY_NIGHT: (i64, i64) = (12010, 23991);
+// Erst nach so vielen Ticks im Bett zählt ein Spieler fürs Überspringen der Nacht
+const DEEP_SLEEP_TICKS: u32 = 100;
+// Abstand zur Mitte einer Betthälfte, waagerecht und senkrecht
+const BED_REACH: (f64, f64) = (3.0, 2.0);
+// Monster in diesem Umkreis um das Bett lassen niemanden schlafen
+const MONSTER_REACH: (f64, f64) = (8.0, 5.0);
+// Außerhalb der Oberwelt fliegt das Bett in die Luft
+const EXPLOSION_POWER: f64 = 5.0;
+// Der Spieler liegt auf der Matratze
+const LYING_HEIGHT: f64 = 0.6875;
+
+// Spieler ohne Zuschauer und wie viele davon im Bett liegen, wie zuletzt gemeldet
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SleepStatus {
+    active: usize,
+    sleeping: usize,
+}
+
+impl SleepStatus {
+    // Zählt neu; true, wenn sich etwas geändert hat und jemand schläft oder gerade noch geschlafen hat
+    fn update(&mut self, players: &[Player], dimension: Dimension) -> bool {
+        let before = *self;
+        let active: Vec<&Player> = players.iter().filter(|p| p.dimension == dimension && p.game_mode != GameMode::Spectator).collect();
+        self.active = active.len();
+        self.sleeping = active.iter().filter(|p| p.pose.bed.is_some()).count();
+        (before.sleeping > 0 || self.sleeping > 0) && (before.active != self.active || before.sleeping != self.sleeping)
+    }
+
+    // Mindestens einer; über 100 Prozent reichen nie alle Spieler
+    fn needed(&self, percentage: i32) -> usize {
+        ((self.active as f64 * percentage.max(0) as f64 / 100.0).ceil() as usize).max(1)
+    }
+}
+
+fn facing_offset(facing: &str) -> (i32, i32) {
+    match facing {
+        "north" => (0, -1),
+        "south" => (0, 1),
+        "west" => (-1, 0),
+        _ => (1, 0),
+    }
+}
+
+// Kopf- und Fußteil zu einer Hälfte des Betts
+fn halves(state: u16, (x, y, z): BlockPos) -> (BlockPos, BlockPos) {
+    let (dx, dz) = facing_offset(block::property(state, "facing").unwrap_or("north"));
+    if block::property(state, "part") == Some("head") {
+        ((x, y, z), (x - dx, y, z - dz))
+    } else {
+        ((x + dx, y, z + dz), (x, y, z))
+    }
+}
+
+fn set_occupied(world: &mut World, (x, y, z): BlockPos, occupied: bool) {
+    let state = world.get_block(x, y, z);
+    if let Some(updated) = block::with_property(state, "occupied", if occupied { "true" } else { "false" }) {
+        world.set_block_and_notify((x, y, z), updated);
+    }
+}
+
+fn may_sleep(world: &World) -> bool {
+    let (start, end) = if world.weather.raining { RAINY_NIGHT } else { NIGHT };
+    world.dimension.beds_work() && (world.weather.thundering || (start..=end).contains(&world.time.time_of_day))
+}
+
+fn in_reach(position: (f64, f64, f64), (x, y, z): BlockPos) -> bool {
+    (position.0 - (x as f64 + 0.5)).abs() <= BED_REACH.0 && (position.1 - (y as f64 + 0.5)).abs() <= BED_REACH.1 && (position.2 - (z as f64 + 0.5)).abs() <= BED_REACH.0
+}
+
+fn monsters_near(world: &World, (x, y, z): BlockPos) -> bool {
+    let center = (x as f64 + 0.5, y as f64, z as f64 + 0.5);
+    world.mobs.iter().any(|m| {
+        entity::is_hostile(&m.mob_type)
+            && (m.position.0 - center.0).abs() <= MONSTER_REACH.0
+            && (m.position.1 - center.1).abs() <= MONSTER_REACH.1
+            && (m.position.2 - center.2).abs() <= MONSTER_REACH.0
+    })
+}
+
+// Neben dem Bett auf festem Boden mit zwei freien Blöcken darüber, sonst auf der Matratze; None ohne Bett
+pub fn stand_up_position(world: &World, (x, y, z): BlockPos) -> Option<(f64, f64, f64)> {
+    let state = world.get_block(x, y, z);
+    if !block::is_bed(state) {
+        return None;
+    }
+    let (head, foot) = halves(state, (x, y, z));
+    let free = |bx: i32, by: i32, bz: i32| {
+        !block::is_solid(world.get_block(bx, by, bz)) && !block::is_solid(world.get_block(bx, by + 1, bz)) && block::is_solid(world.get_block(bx, by - 1, bz))
+    };
+    for (bx, by, bz) in [head, foot] {
+        for (dx, dz) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+            if free(bx + dx, by, bz + dz) {
+                return Some(((bx + dx) as f64 + 0.5, by as f64, (bz + dz) as f64 + 0.5));
+            }
+        }
+    }
+    Some((head.0 as f64 + 0.5, head.1 as f64 + block::collision_height(state), head.2 as f64 + 0.5))
+}
+
+// Setzt ein Bett aus der Hand an die angeklickte Seite: das Fußende dorthin, das Kopfende in Blickrichtung dahinter
+pub fn place(players: &mut [Player], world: &mut World, index: usize, slot: usize, (x, y, z): BlockPos, face: i32) -> bool {
+    let player = &players[index];
+    let Some(color) = player.inventory.slot(slot).item.strip_suffix("_bed").map(str::to_string) else { return false };
+    let (dx, dy, dz) = face_offset(face);
+    let foot = (x + dx, y + dy, z + dz);
+    let facing = ["south", "west", "north", "east"][((player.rotation.0 as f64 / 90.0 + 0.5).floor() as i32 & 3) as usize];
+    let (fx, fz) = facing_offset(facing);
+    let head = (foot.0 + fx, foot.1, foot.2 + fz);
+    let (Ok(foot_state), Ok(head_state)) = (
+        block::parse_state(&format!("{}_bed[facing={},part=foot]", color, facing)),
+        block::parse_state(&format!("{}_bed[facing={},part=head]", color, facing)),
+    ) else {
+        return false;
+    };
+    let fits = |(bx, by, bz): BlockPos| world.get_block(bx, by, bz) == block::AIR && block::is_solid(world.get_block(bx, by - 1, bz));
+    if !fits(foot) || !fits(head) || !world.set_block_and_notify(foot, foot_state) {
+        return false;
+    }
+    world.set_block_and_notify(head, head_state);
+    let player = &mut players[index];
+    if player.game_mode != GameMode::Creative {
+        player.inventory.consume_one(slot);
+        let _ = player.inventory.send_slot(&player.connection, slot);
+    }
+    true
+}
+
+// Geht eine Betthälfte verloren, verschwindet die andere mit
+pub fn block_changed(world: &mut World, position: BlockPos, old: u16, new: u16) {
+    if !block::is_bed(old) || block::is_bed(new) {
+        return;
+    }
+    let (head, foot) = halves(old, position);
+    let other = if head == position { foot } else { head };
+    if block::is_bed(world.get_block(other.0, other.1, other.2)) {
+        world.set_block_and_notify(other, block::AIR);
+    }
+}
+
+// Use Item On auf ein Bett: legt den Spieler hinein, wenn es Nacht ist und nichts dagegen spricht, und setzt dabei
+// seinen Spawnpunkt. false, wenn kein Bett angeklickt wurde
+pub fn use_on(server: &Server, players: &mut [Player], world: &mut World, index: usize, (x, y, z): BlockPos) -> bool {
+    let state = world.get_block(x, y, z);
+    if !block::is_bed(state) {
+        return false;
+    }
+    let (head, foot) = halves(state, (x, y, z));
+    let head_state = world.get_block(head.0, head.1, head.2);
+    if !block::is_bed(head_state) {
+        return true;
+    }
+    if !world.dimension.beds_work() {
+        world.set_block_and_notify(head, block::AIR);
+        let (dimension, center) = (world.dimension, (head.0 as f64 + 0.5, head.1 as f64 + 0.5, head.2 as f64 + 0.5));
+        debug!("Bett explodiert bei {:?} in {}", head, dimension.name());
+        server.scheduler.lock().unwrap().run_later(1, move |server| explosion::explode(server, dimension, center, EXPLOSION_POWER, explosion::Kind::Block));
+        return true;
+    }
+    let player = &players[index];
+    if player.pose.bed.is_some() || player.health <= 0.0 {
+        return true;
+    }
+    let refuse = |player: &Player, key: &str| {
+        let _ = send_action_bar(&player.connection, &Text::translate(key));
+        true
+    };
+    if block::property(head_state, "occupied") == Some("true") {
+        return refuse(player, "block.minecraft.bed.occupied");
+    }
+    if !in_reach(player.position, head) && !in_reach(player.position, foot) {
+        return refuse(player, "block.minecraft.bed.too_far_away");
+    }
+    if block::is_solid(world.get_block(head.0, head.1 + 1, head.2)) {
+        return refuse(player, "block.minecraft.bed.obstructed");
+    }
+    let spawn = SpawnPoint { position: head, angle: player.rotation.0 };
+    let player = &mut players[index];
+    if player.spawn_point.map(|s| s.position) != Some(head) {
+        let _ = send_system_component(&player.connection, &Text::translate("block.minecraft.set_spawn"));
+    }
+    player.spawn_point = Some(spawn);
+    if !may_sleep(world) {
+        return refuse(player, "block.minecraft.bed.no_sleep");
+    }
+    if player.game_mode != GameMode::Creative && monsters_near(world, head) {
+        return refuse(player, "block.minecraft.bed.not_safe");
+    }
+    debug!("{} schläft im Bett bei {:?}", player.username, head);
+    player.pose.bed = Some(head);
+    player.pose.sneaking = false;
+    player.pose.sprinting = false;
+    player.sleep_ticks = 0;
+    player.position = (head.0 as f64 + 0.5, head.1 as f64 + LYING_HEIGHT, head.2 as f64 + 0.5);
+    let _ = entity::send_pose(&player.connection, entity::OWN_PLAYER_ID, &player.pose);
+    pose::broadcast(players, index);
+    set_occupied(world, head, true);
+    true
+}
+
+// Holt den Spieler aus dem Bett; mit stand_up steht er daneben auf, sonst etwa nach dem Tod bleibt er, wo er ist
+pub fn wake(players: &mut [Player], world: &mut World, index: usize, stand_up: bool) {
+    let player = &mut players[index];
+    let Some(head) = player.pose.bed.take() else { return };
+    player.sleep_ticks = 0;
+    debug!("{} wacht auf", player.username);
+    if stand_up {
+        if let Some(position) = stand_up_position(world, head) {
+            player.position = position;
+            let _ = send_teleport(player);
+        }
+    }
+    let _ = animation::send(&player.connection, entity::OWN_PLAYER_ID, animation::LEAVE_BED);
+    let _ = entity::send_pose(&player.connection, entity::OWN_PLAYER_ID, &player.pose);
+    let (uuid, entity_id, dimension, position, tracked) = (player.uuid, player.entity_id, player.dimension, player.position, player.game_mode.is_tracked());
+    if tracked {
+        for viewer in players.iter().filter(|v| v.uuid != uuid && entity::in_view(v, dimension, position)) {
+            let _ = animation::send(&viewer.connection, entity_id, animation::LEAVE_BED);
+        }
+        pose::broadcast(players, index);
+    }
+    if block::is_bed(world.get_block(head.0, head.1, head.2)) {
+        set_occupied(world, head, false);
+    }
+}
+
+// Zählt die Schläfer jeder Welt, meldet den Stand über der Schnellleiste und überspringt die Nacht, sobald genug
+// Spieler laut playersSleepingPercentage lange genug schlafen. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        tick_world(&mut players, &mut world);
+    }
+}
+
+fn tick_world(players: &mut [Player], world: &mut World) {
+    let dimension = world.dimension;
+    for index in 0..players.len() {
+        let player = &mut players[index];
+        let Some((x, y, z)) = player.pose.bed.filter(|_| player.dimension == dimension) else { continue };
+        player.sleep_ticks = (player.sleep_ticks + 1).min(DEEP_SLEEP_TICKS);
+        // Tagsüber oder ohne Bett hält es keinen im Bett
+        if !block::is_bed(world.get_block(x, y, z)) || !may_sleep(world) {
+            wake(players, world, index, true);
+        }
+    }
+    let percentage = world.rules.get_int(gamerule::PLAYERS_SLEEPING_PERCENTAGE);
+    if world.sleep_status.update(players, dimension) {
+        let status = world.sleep_status;
+        let needed = status.needed(percentage);
+        let message = if status.sleeping >= needed {
+            Text::translate("sleep.skipping_night")
+        } else {
+            Text::translate("sleep.players_sleeping").with(status.sleeping.to_string()).with(needed.to_string())
+        };
+        for player in players.iter().filter(|p| p.dimension == dimension) {
+            let _ = send_action_bar(&player.connection, &message);
+        }
+    }
+    let status = world.sleep_status;
+    let needed = status.needed(percentage);
+    let deep = players.iter().filter(|p| p.dimension == dimension && p.game_mode != GameMode::Spectator && p.pose.bed.is_some() && p.sleep_ticks >= DEEP_SLEEP_TICKS).count();
+    if status.sleeping < needed || deep < needed {
+        return;
+    }
+    info!("Genug Spieler schlafen in {}, die Nacht wird übersprungen", dimension.name());
+    if world.rules.get_bool(gamerule::DO_DAYLIGHT_CYCLE) {
+        world.time.time_of_day = 0;
+    }
+    if world.rules.get_bool(gamerule::DO_WEATHER_CYCLE) && world.weather.raining {
+        world.weather.set(false, false, None);
+    }
+    for index in 0..players.len() {
+        if players[index].dimension == dimension {
+            wake(players, world, index, true);
+        }
+    }
+    let (time, weather) = (world.time, world.weather);
+    for player in players.iter().filter(|p| p.dimension == dimension) {
+        let _ = send_time(&player.connection, &time);
+        let _ = send_weather(&player.connection, &weather);
+    }
+}
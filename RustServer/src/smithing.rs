@@ -0,0 +1,13 @@
+use crate::item::ItemStack;
+use crate::recipe::RecipeRegistry;
+
+// Slots des Schmiedetisches: Vorlage, zu veredelndes Item, Material, Ergebnis
+pub const TEMPLATE: usize = 0;
+pub const BASE: usize = 1;
+pub const ADDITION: usize = 2;
+pub const RESULT: usize = 3;
+pub const SLOTS: usize = 4;
+
+pub fn update(recipes: &RecipeRegistry, slots: &mut [ItemStack]) {
+    slots[RESULT] = recipes.smith(&slots[TEMPLATE], &slots[BASE], &slots[ADDITION]).unwrap_or_else(ItemStack::empty);
+}
@@ -0,0 +1,100 @@
+use rand::Rng;
+use crate::chunk::Heightmap;
+use crate::{block, gamerule, World};
+
+type BlockPos = (i32, i32, i32);
+
+pub const RANDOM_TICKING: &[&str] = &["minecraft:snow", "minecraft:ice"];
+// Wie bei Vanilla würfelt jeder Chunk randomTickSpeed-mal pro Tick mit dieser Chance um eine Säule für Eis und Schnee
+const PRECIPITATION_CHANCE: u32 = 48;
+const MAX_LAYERS: i32 = 8;
+// Ab diesem Blocklicht friert nichts mehr und kein Schnee bleibt liegen; über MELT_LIGHT schmilzt beides
+const FREEZE_LIGHT: u8 = 10;
+const MELT_LIGHT: u8 = 11;
+
+// Blocklicht aus der Lichtberechnung; 0, solange der Chunk noch nicht gerechnet ist
+fn block_light(world: &World, position: BlockPos) -> u8 {
+    world.light.level_at(world.dimension, position).map_or(0, |(_, block)| block)
+}
+
+// Wie bei Vanilla lässt sich Schnee auf vollen Blöcken und vollen Schneeschichten nieder, nicht aber auf Eis
+fn can_hold_snow(state: u16) -> bool {
+    if block::is_snow(state) {
+        return block::property(state, "layers") == Some("8");
+    }
+    block::is_solid(state) && block::collision_height(state) == 1.0 && block::name(state) != "minecraft:ice"
+}
+
+fn covers_grass(state: u16) -> bool {
+    block::is_snow(state) || state == block::SNOW_BLOCK
+}
+
+// Schnee kam auf einen Grasblock oder ist von ihm verschwunden: snowy folgt wie bei Vanilla
+pub fn block_changed(world: &mut World, (x, y, z): BlockPos, old: u16, new: u16) {
+    if covers_grass(old) == covers_grass(new) {
+        return;
+    }
+    let below = world.get_block(x, y - 1, z);
+    if block::name(below) != "minecraft:grass_block" {
+        return;
+    }
+    if let Some(updated) = block::with_property(below, "snowy", if covers_grass(new) { "true" } else { "false" }) {
+        world.set_block_and_notify((x, y - 1, z), updated);
+    }
+}
+
+// Eis und Schnee in den Chunks, die ein Client geladen hat. Stilles Wasser friert in kalten Biomen bei jedem Wetter;
+// Schnee fällt nur, solange es dort schneit, und wächst bis snowAccumulationHeight Schichten
+pub fn tick(world: &mut World) {
+    let speed = world.rules.get_int(gamerule::RANDOM_TICK_SPEED).max(0);
+    let mut rng = rand::thread_rng();
+    let mut columns = Vec::new();
+    for &(chunk_x, chunk_z) in world.chunk_viewers.keys() {
+        for _ in 0..speed {
+            if rng.gen_range(0..PRECIPITATION_CHANCE) == 0 {
+                columns.push((chunk_x * 16 + rng.gen_range(0..16), chunk_z * 16 + rng.gen_range(0..16)));
+            }
+        }
+    }
+    for (x, z) in columns {
+        precipitation_tick(world, x, z);
+    }
+}
+
+fn precipitation_tick(world: &mut World, x: i32, z: i32) {
+    let Some(top) = world.surface_y(Heightmap::MotionBlocking, x, z) else { return };
+    let surface = (x, top - 1, z);
+    if world.get_block(x, top - 1, z) == block::WATER && world.is_cold(surface) && block_light(world, surface) < FREEZE_LIGHT {
+        world.set_block_and_notify(surface, block::ICE);
+    }
+    let height = world.rules.get_int(gamerule::SNOW_ACCUMULATION_HEIGHT).min(MAX_LAYERS);
+    if height <= 0 || !world.snows_at((x, top, z)) || !can_hold_snow(world.get_block(x, top - 1, z)) {
+        return;
+    }
+    let state = world.get_block(x, top, z);
+    let snow = if block::is_snow(state) {
+        let layers: i32 = block::property(state, "layers").and_then(|l| l.parse().ok()).unwrap_or(1);
+        if layers >= height {
+            return;
+        }
+        block::with_property(state, "layers", &(layers + 1).to_string())
+    } else if state == block::AIR {
+        Some(block::SNOW)
+    } else {
+        None
+    };
+    if let Some(snow) = snow.filter(|_| block_light(world, (x, top, z)) < FREEZE_LIGHT) {
+        world.set_block_and_notify((x, top, z), snow);
+    }
+}
+
+// Zufallstick: Schneeschichten und Eis schmelzen neben hellen Lichtquellen und in Biomen, in denen es regnen würde. Im
+// Nether verdampft das Schmelzwasser sofort
+pub fn random_tick(world: &mut World, (x, y, z): BlockPos) {
+    if world.is_cold((x, y, z)) && block_light(world, (x, y, z)) <= MELT_LIGHT {
+        return;
+    }
+    let state = world.get_block(x, y, z);
+    let melted = if block::is_snow(state) || world.dimension.kind().ultrawarm { block::AIR } else { block::WATER };
+    world.set_block_and_notify((x, y, z), melted);
+}
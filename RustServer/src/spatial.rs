@@ -0,0 +1,83 @@
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+fn bucket_of(position: (f64, f64, f64)) -> (i32, i32) {
+    ((position.0.floor() as i32) >> 4, (position.2.floor() as i32) >> 4)
+}
+
+// Nach Chunks gruppierter Index, damit Umgebungsabfragen nicht alle Entities durchlaufen
+#[derive(Default)]
+pub struct SpatialIndex {
+    buckets: HashMap<(i32, i32), HashSet<Uuid>>,
+    positions: HashMap<Uuid, (f64, f64, f64)>,
+}
+
+impl SpatialIndex {
+    pub fn new() -> SpatialIndex {
+        SpatialIndex::default()
+    }
+
+    pub fn update(&mut self, id: Uuid, position: (f64, f64, f64)) {
+        let new_bucket = bucket_of(position);
+        if let Some(old) = self.positions.insert(id, position) {
+            let old_bucket = bucket_of(old);
+            if old_bucket == new_bucket {
+                return;
+            }
+            self.remove_from_bucket(id, old_bucket);
+        }
+        self.buckets.entry(new_bucket).or_default().insert(id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn remove(&mut self, id: Uuid) {
+        if let Some(old) = self.positions.remove(&id) {
+            self.remove_from_bucket(id, bucket_of(old));
+        }
+    }
+
+    fn remove_from_bucket(&mut self, id: Uuid, bucket: (i32, i32)) {
+        if let Some(ids) = self.buckets.get_mut(&bucket) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.buckets.remove(&bucket);
+            }
+        }
+    }
+
+    pub fn query_radius(&self, center: (f64, f64, f64), radius: f64) -> Vec<Uuid> {
+        let min = (center.0 - radius, center.1 - radius, center.2 - radius);
+        let max = (center.0 + radius, center.1 + radius, center.2 + radius);
+        let radius_sq = radius * radius;
+        self.query_aabb(min, max)
+            .into_iter()
+            .filter(|id| {
+                let p = self.positions[id];
+                let (dx, dy, dz) = (p.0 - center.0, p.1 - center.1, p.2 - center.2);
+                dx * dx + dy * dy + dz * dz <= radius_sq
+            })
+            .collect()
+    }
+
+    pub fn query_aabb(&self, min: (f64, f64, f64), max: (f64, f64, f64)) -> Vec<Uuid> {
+        let (min_bx, min_bz) = bucket_of(min);
+        let (max_bx, max_bz) = bucket_of(max);
+        let mut found = Vec::new();
+        for bx in min_bx..=max_bx {
+            for bz in min_bz..=max_bz {
+                let Some(ids) = self.buckets.get(&(bx, bz)) else { continue };
+                for id in ids {
+                    let p = self.positions[id];
+                    let inside = p.0 >= min.0 && p.0 <= max.0 && p.1 >= min.1 && p.1 <= max.1 && p.2 >= min.2 && p.2 <= max.2;
+                    if inside {
+                        found.push(*id);
+                    }
+                }
+            }
+        }
+        found
+    }
+}
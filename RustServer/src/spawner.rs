@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+use rand::Rng;
+use crate::blockentity::BlockEntity;
+use crate::difficulty::Difficulty;
+use crate::gamemode::GameMode;
+use crate::json::Json;
+use crate::protocol::clientbound;
+use crate::protocol::types::{encode_position, write_varint_to_vec};
+use crate::worldevent::WorldEvent;
+use crate::{block, entity, farming, Mob, Player, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Block Action: der Client setzt seine Anzeige der Wartezeit zurück
+const ACTION_RESET_DELAY: u8 = 1;
+const EGG_SUFFIX: &str = "_spawn_egg";
+
+// Monsterspawner mit den Standardwerten von Vanilla; ohne Entity-Typ bleibt er leer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spawner {
+    pub entity: Option<String>,
+    // Ticks bis zum nächsten Versuch, -1 würfelt beim nächsten Tick neu
+    delay: i32,
+    min_delay: i32,
+    max_delay: i32,
+    spawn_count: i32,
+    max_nearby_entities: i32,
+    required_player_range: i32,
+    spawn_range: i32,
+}
+
+impl Default for Spawner {
+    fn default() -> Spawner {
+        Spawner { entity: None, delay: 20, min_delay: 200, max_delay: 800, spawn_count: 4, max_nearby_entities: 6, required_player_range: 16, spawn_range: 4 }
+    }
+}
+
+impl Spawner {
+    fn fields(&self) -> [(&'static str, &'static str, i32); 7] {
+        [
+            ("delay", "Delay", self.delay),
+            ("min_delay", "MinSpawnDelay", self.min_delay),
+            ("max_delay", "MaxSpawnDelay", self.max_delay),
+            ("spawn_count", "SpawnCount", self.spawn_count),
+            ("max_nearby_entities", "MaxNearbyEntities", self.max_nearby_entities),
+            ("required_player_range", "RequiredPlayerRange", self.required_player_range),
+            ("spawn_range", "SpawnRange", self.spawn_range),
+        ]
+    }
+
+    fn fields_mut(&mut self) -> [(&'static str, &mut i32); 7] {
+        [
+            ("delay", &mut self.delay),
+            ("min_delay", &mut self.min_delay),
+            ("max_delay", &mut self.max_delay),
+            ("spawn_count", &mut self.spawn_count),
+            ("max_nearby_entities", &mut self.max_nearby_entities),
+            ("required_player_range", &mut self.required_player_range),
+            ("spawn_range", &mut self.spawn_range),
+        ]
+    }
+
+    // Für level.json: {"entity": "minecraft:zombie", "delay": n, "min_delay": n, ...}
+    pub fn write_json(&self, data: &mut BTreeMap<String, Json>) {
+        if let Some(kind) = &self.entity {
+            data.insert("entity".to_string(), Json::String(kind.clone()));
+        }
+        for (key, _, value) in self.fields() {
+            data.insert(key.to_string(), Json::Number(value as f64));
+        }
+    }
+
+    pub fn from_json(data: &Json) -> Spawner {
+        let mut spawner = Spawner { entity: data.get("entity").and_then(Json::as_str).map(str::to_string), ..Spawner::default() };
+        for (key, value) in spawner.fields_mut() {
+            if let Some(number) = data.get(key).and_then(Json::as_f64) {
+                *value = number as i32;
+            }
+        }
+        spawner
+    }
+
+    // Was der Client für den drehenden Mob im Käfig braucht, mit den Namen von Vanilla
+    pub fn write_client_data(&self, data: &mut BTreeMap<String, Json>) {
+        for (_, name, value) in self.fields() {
+            data.insert(name.to_string(), Json::Number(value as f64));
+        }
+        let mut entity = BTreeMap::new();
+        if let Some(kind) = &self.entity {
+            entity.insert("id".to_string(), Json::String(kind.clone()));
+        }
+        data.insert("SpawnData".to_string(), Json::Object(BTreeMap::from([("entity".to_string(), Json::Object(entity))])));
+    }
+}
+
+fn player_near(players: &[Player], world: &World, (x, y, z): BlockPos, range: f64) -> bool {
+    let center = (x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5);
+    players.iter().any(|p| {
+        p.dimension == world.dimension
+            && p.game_mode != GameMode::Spectator
+            && p.health > 0.0
+            && (p.position.0 - center.0).powi(2) + (p.position.1 - center.1).powi(2) + (p.position.2 - center.2).powi(2) < range * range
+    })
+}
+
+fn send_reset_delay(players: &[Player], world: &World, (x, y, z): BlockPos) {
+    let center = (x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5);
+    for player in players.iter().filter(|p| entity::in_view(p, world.dimension, center)) {
+        let Some(block_id) = block::registry_id_in("minecraft:spawner", player.connection.version) else { continue };
+        let mut packet_data = encode_position(x, y, z).to_be_bytes().to_vec();
+        packet_data.extend([ACTION_RESET_DELAY, 0]);
+        packet_data.extend(write_varint_to_vec(block_id));
+        let _ = player.connection.send(clientbound::BLOCK_ACTION, &packet_data);
+    }
+}
+
+fn send_data(players: &[Player], world: &World, position: BlockPos) {
+    let Some(entity) = world.block_entity(position) else { return };
+    let center = (position.0 as f64 + 0.5, position.1 as f64 + 0.5, position.2 as f64 + 0.5);
+    for player in players.iter().filter(|p| entity::in_view(p, world.dimension, center)) {
+        let _ = entity.send_data(&player.connection, position);
+    }
+}
+
+// Würfelt die nächste Wartezeit aus und zeigt sie den Clients
+fn reset_delay(players: &[Player], world: &mut World, position: BlockPos) {
+    let Some(BlockEntity::Spawner(spawner)) = world.block_entity_mut(position) else { return };
+    spawner.delay = if spawner.max_delay <= spawner.min_delay { spawner.min_delay } else { rand::thread_rng().gen_range(spawner.min_delay..=spawner.max_delay) };
+    world.dirty_chunks.insert((position.0 >> 4, position.2 >> 4));
+    send_reset_delay(players, world, position);
+}
+
+// Jeder Spawner mit Spielern in Reichweite zählt herunter und setzt dann bis zu spawn_count Mobs in freie Blöcke
+// um sich, solange nicht schon max_nearby_entities desselben Typs in der Nähe sind
+pub fn tick(players: &[Player], world: &mut World) {
+    let spawners: Vec<BlockPos> = world
+        .chunks
+        .values()
+        .flat_map(|chunk| chunk.block_entities.iter().filter(|(_, entity)| matches!(entity, BlockEntity::Spawner(_))).map(|(&position, _)| position))
+        .collect();
+    for position in spawners {
+        tick_spawner(players, world, position);
+    }
+}
+
+fn tick_spawner(players: &[Player], world: &mut World, position: BlockPos) {
+    let Some(BlockEntity::Spawner(spawner)) = world.block_entity(position) else { return };
+    let spawner = (**spawner).clone();
+    let Some(kind) = spawner.entity.clone() else { return };
+    if !player_near(players, world, position, spawner.required_player_range as f64) {
+        return;
+    }
+    if spawner.delay == -1 {
+        reset_delay(players, world, position);
+        return;
+    }
+    if spawner.delay > 0 {
+        if let Some(BlockEntity::Spawner(spawner)) = world.block_entity_mut(position) {
+            spawner.delay -= 1;
+        }
+        return;
+    }
+    if (world.difficulty == Difficulty::Peaceful && entity::is_hostile(&kind)) || !world.settings.allows_spawn(&kind) {
+        return;
+    }
+    let (x, y, z) = position;
+    let range = spawner.spawn_range as f64;
+    let mut rng = rand::thread_rng();
+    let mut spawned = false;
+    let mut crowded = false;
+    for _ in 0..spawner.spawn_count {
+        let target = (x as f64 + (rng.gen::<f64>() - rng.gen::<f64>()) * range + 0.5, (y + rng.gen_range(-1..=1)) as f64, z as f64 + (rng.gen::<f64>() - rng.gen::<f64>()) * range + 0.5);
+        let (bx, by, bz) = (target.0.floor() as i32, target.1 as i32, target.2.floor() as i32);
+        if !world.chunks.contains_key(&(bx >> 4, bz >> 4)) || block::is_solid(world.get_block(bx, by, bz)) || block::is_solid(world.get_block(bx, by + 1, bz)) {
+            continue;
+        }
+        let nearby = world
+            .mobs
+            .iter()
+            .filter(|m| m.mob_type == kind)
+            .filter(|m| {
+                let inside = |value: f64, start: i32| value >= start as f64 - range && value <= start as f64 + 1.0 + range;
+                inside(m.position.0, x) && inside(m.position.1, y) && inside(m.position.2, z)
+            })
+            .count();
+        if nearby >= spawner.max_nearby_entities as usize {
+            crowded = true;
+            break;
+        }
+        let mut mob = Mob::new(&kind, target);
+        mob.yaw = rng.gen_range(0.0..360.0);
+        for player in players.iter().filter(|p| entity::in_view(p, world.dimension, target)) {
+            let _ = entity::send_mob(&player.connection, &mob);
+        }
+        world.spawn_mob(mob);
+        spawned = true;
+    }
+    if spawned {
+        trace!("Spawner bei {:?} setzt {}", position, kind);
+        world.play_event(WorldEvent::SpawnerSpawn, position, None);
+    }
+    if spawned || crowded {
+        reset_delay(players, world, position);
+    }
+}
+
+// Use Item On mit einem Spawn-Ei auf einen Spawner: der Spawner setzt danach diesen Typ. false, wenn nichts davon
+// passt
+pub fn use_on(players: &mut [Player], world: &mut World, index: usize, slot: usize, position: BlockPos) -> bool {
+    let held = players[index].inventory.slot(slot).item.clone();
+    let Some(kind) = held.strip_suffix(EGG_SUFFIX).filter(|kind| entity::is_known(kind)) else { return false };
+    let Some(BlockEntity::Spawner(spawner)) = world.block_entity_mut(position) else { return false };
+    spawner.entity = Some(kind.to_string());
+    debug!("{} stellt den Spawner bei {:?} auf {}", players[index].username, position, kind);
+    world.dirty_chunks.insert((position.0 >> 4, position.2 >> 4));
+    send_data(players, world, position);
+    farming::spend(&mut players[index], slot, false);
+    true
+}
@@ -0,0 +1,43 @@
+use crate::item::ItemStack;
+use crate::recipe::RecipeRegistry;
+use crate::window::{self, Window};
+use crate::Player;
+
+// Slots des Steinsägen-Fensters
+pub const INPUT: usize = 0;
+pub const RESULT: usize = 1;
+pub const SLOTS: usize = 2;
+pub const TAKE_SOUND: &str = "minecraft:ui.stonecutter.take_result";
+
+// Das gewählte Rezept; gilt, solange dasselbe Item im Eingabeslot liegt
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stonecutter {
+    selected: Option<usize>,
+    input: String,
+}
+
+impl Stonecutter {
+    // Ein anderes Item im Eingabeslot hebt die Auswahl auf, sonst gibt sie das Ergebnis vor
+    pub fn update(&mut self, recipes: &RecipeRegistry, slots: &mut [ItemStack]) {
+        if slots[INPUT].is_empty() || slots[INPUT].item != self.input {
+            self.selected = None;
+            self.input = slots[INPUT].item.clone();
+        }
+        let choices = recipes.stonecutting_for(&slots[INPUT]);
+        slots[RESULT] = self.selected.and_then(|i| choices.get(i)).map_or_else(ItemStack::empty, |recipe| recipe.result.clone());
+    }
+}
+
+// Click Container Button: der Knopf ist der Index in der Rezeptliste, die auch der Client für das Item kennt
+pub fn select(player: &mut Player, window: &mut Window, recipes: &RecipeRegistry, button: u8) {
+    let Some(stonecutter) = window.stonecutter.as_mut() else { return };
+    if recipes.stonecutting_for(&window.slots[INPUT]).len() <= button as usize {
+        debug!("{} wählt in der Steinsäge Rezept {}, das es nicht gibt", player.username, button);
+        return;
+    }
+    stonecutter.selected = Some(button as usize);
+    stonecutter.update(recipes, &mut window.slots);
+    window.set_sent(window.slots.clone());
+    let _ = window::send_slot(&player.connection, window, RESULT, &window.slots[RESULT]);
+}
+
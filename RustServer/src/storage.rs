@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use uuid::Uuid;
+use crate::chunkstorage::{self, write_atomic, ChunkStorageKind};
+use crate::difficulty::Difficulty;
+use crate::{dimension, item};
+use crate::end::DragonFight;
+use crate::function::ScheduledFunction;
+use crate::gamerule::GameRules;
+use crate::json::{self, Json};
+use crate::worldborder::WorldBorder;
+use crate::worldsettings::WorldSettings;
+use crate::{Chunk, ChunkFuture, ChunkIo, Player, Server, SpawnPoint, Weather, WorldTime};
+
+const DEFAULT_WORLD_DIR: &str = "world";
+static WORLD_DIR: OnceLock<String> = OnceLock::new();
+const LEVEL_FILE: &str = "level.json";
+const PLAYER_DIR: &str = "playerdata";
+// Das Ende liegt wie bei Vanilla als Unterordner in der Oberwelt
+const END_DIR: &str = "DIM1";
+// Chunks je Runde bei --force-upgrade
+const UPGRADE_BATCH: usize = 256;
+
+// Welt-Metadaten aus level.json
+#[derive(Debug, Clone)]
+pub struct LevelData {
+    pub seed: u64,
+    pub time: WorldTime,
+    pub weather: Weather,
+    pub rules: GameRules,
+    pub spawn: Option<SpawnPoint>,
+    pub border: WorldBorder,
+    pub scheduled_functions: Vec<ScheduledFunction>,
+    pub dragon_fight: Option<DragonFight>,
+    // Fehlen in Welten von vor den Einstellungen je Welt
+    pub difficulty: Option<Difficulty>,
+    pub settings: Option<WorldSettings>,
+}
+
+impl LevelData {
+    fn to_json(&self) -> Json {
+        let mut level = BTreeMap::new();
+        // Als Text, weil JSON-Zahlen keine 64 Bit genau darstellen
+        level.insert("seed".to_string(), Json::String(self.seed.to_string()));
+        level.insert("age".to_string(), Json::Number(self.time.age as f64));
+        level.insert("time_of_day".to_string(), Json::Number(self.time.time_of_day as f64));
+        level.insert("raining".to_string(), Json::Bool(self.weather.raining));
+        level.insert("rain_time".to_string(), Json::Number(self.weather.rain_time as f64));
+        level.insert("thundering".to_string(), Json::Bool(self.weather.thundering));
+        level.insert("thunder_time".to_string(), Json::Number(self.weather.thunder_time as f64));
+        level.insert("gamerules".to_string(), self.rules.to_json());
+        if let Some(spawn) = self.spawn {
+            level.insert("spawn".to_string(), spawn_json(spawn));
+        }
+        level.insert("world_border".to_string(), self.border.to_json());
+        level.insert("scheduled_functions".to_string(), Json::Array(self.scheduled_functions.iter().map(ScheduledFunction::to_json).collect()));
+        if let Some(fight) = &self.dragon_fight {
+            level.insert("dragon_fight".to_string(), fight.to_json());
+        }
+        if let Some(difficulty) = self.difficulty {
+            level.insert("difficulty".to_string(), Json::String(difficulty.to_string()));
+        }
+        if let Some(settings) = &self.settings {
+            level.insert("settings".to_string(), settings.to_json());
+        }
+        Json::Object(level)
+    }
+
+    fn from_json(level: &Json) -> Option<LevelData> {
+        let number = |key: &str| level.get(key).and_then(Json::as_f64);
+        Some(LevelData {
+            seed: level.get("seed")?.as_str()?.parse().ok()?,
+            time: WorldTime { age: number("age")? as i64, time_of_day: number("time_of_day")? as i64 },
+            weather: Weather {
+                raining: level.get("raining")?.as_bool()?,
+                rain_time: number("rain_time")? as i32,
+                thundering: level.get("thundering")?.as_bool()?,
+                thunder_time: number("thunder_time")? as i32,
+            },
+            // Welten von vor den Spielregeln haben noch keine
+            rules: level.get("gamerules").map_or_else(GameRules::new, GameRules::from_json),
+            spawn: level.get("spawn").and_then(parse_spawn),
+            border: level.get("world_border").map_or_else(WorldBorder::new, WorldBorder::from_json),
+            scheduled_functions: level
+                .get("scheduled_functions")
+                .and_then(Json::as_array)
+                .map(|entries| entries.iter().filter_map(ScheduledFunction::from_json).collect())
+                .unwrap_or_default(),
+            dragon_fight: level.get("dragon_fight").map(DragonFight::from_json),
+            difficulty: level.get("difficulty").and_then(Json::as_str).and_then(|d| d.parse().ok()),
+            settings: level.get("settings").map(WorldSettings::from_json),
+        })
+    }
+}
+
+// {"x": 0, "y": 65, "z": 0, "angle": 0.0}
+fn spawn_json(spawn: SpawnPoint) -> Json {
+    let (x, y, z) = spawn.position;
+    let mut data = BTreeMap::new();
+    data.insert("x".to_string(), Json::Number(x as f64));
+    data.insert("y".to_string(), Json::Number(y as f64));
+    data.insert("z".to_string(), Json::Number(z as f64));
+    data.insert("angle".to_string(), Json::Number(spawn.angle as f64));
+    Json::Object(data)
+}
+
+fn parse_spawn(json: &Json) -> Option<SpawnPoint> {
+    let number = |key: &str| json.get(key).and_then(Json::as_f64);
+    Some(SpawnPoint { position: (number("x")? as i32, number("y")? as i32, number("z")? as i32), angle: number("angle").unwrap_or(0.0) as f32 })
+}
+
+// Ordner der Oberwelt aus level-name und zugleich ihr Name in Befehlen; wird beim Start einmal gesetzt
+pub fn world_dir() -> &'static str {
+    WORLD_DIR.get().map_or(DEFAULT_WORLD_DIR, String::as_str)
+}
+
+// false, wenn der Ordner schon feststand
+pub fn set_world_dir(dir: &str) -> bool {
+    WORLD_DIR.set(dir.to_string()).is_ok()
+}
+
+pub fn end_dir() -> String {
+    format!("{}/{}", world_dir(), END_DIR)
+}
+
+// Ablage einer Welt: level.json, playerdata/<uuid>.json und die Chunks im Backend aus chunk-storage
+#[derive(Debug)]
+pub struct WorldStorage {
+    dir: PathBuf,
+    kind: ChunkStorageKind,
+    io: Arc<ChunkIo>,
+    // Beim Start eingelesen, damit fehlende Chunks keinen Dateisystemzugriff kosten
+    on_disk: HashSet<(i32, i32)>,
+}
+
+impl WorldStorage {
+    pub fn open(dir: &str, kind: ChunkStorageKind) -> WorldStorage {
+        let dir = PathBuf::from(dir);
+        let backend = chunkstorage::open(kind, &dir);
+        let on_disk = backend.list().into_iter().collect();
+        WorldStorage { dir, kind, io: Arc::new(ChunkIo::new(backend)), on_disk }
+    }
+
+    pub fn kind(&self) -> ChunkStorageKind {
+        self.kind
+    }
+
+    // Für entladene Welten, nachdem alles gespeichert ist
+    pub fn shut_down(&self) {
+        self.io.shut_down();
+    }
+
+    // Etwa zum Warten auf die Worker, nachdem die Weltsperre freigegeben ist
+    pub fn io(&self) -> Arc<ChunkIo> {
+        Arc::clone(&self.io)
+    }
+
+    pub fn load_level(&self) -> Option<LevelData> {
+        let path = self.dir.join(LEVEL_FILE);
+        let text = fs::read_to_string(&path).ok()?;
+        let level = json::parse(&text).ok().and_then(|level| LevelData::from_json(&level));
+        if level.is_none() {
+            warn!("{} ist beschädigt und wird ignoriert", path.display());
+        }
+        level
+    }
+
+    pub fn has_chunk(&self, x: i32, z: i32) -> bool {
+        self.on_disk.contains(&(x, z))
+    }
+
+    pub fn load_chunk(&self, x: i32, z: i32) -> ChunkFuture {
+        self.io.read(x, z)
+    }
+
+    // Liest jeden Chunk und schreibt ihn im aktuellen Format über das eingestellte Backend neu. Blockweise, damit nicht
+    // die ganze Welt auf einmal im Speicher liegt; gibt aktualisierte und fehlgeschlagene Chunks zurück
+    pub fn upgrade(&mut self) -> (usize, usize) {
+        let chunks: Vec<(i32, i32)> = self.on_disk.iter().copied().collect();
+        let (mut upgraded, mut failed) = (0, 0);
+        for batch in chunks.chunks(UPGRADE_BATCH) {
+            let reads: Vec<_> = batch.iter().map(|&(x, z)| self.io.read(x, z)).collect();
+            for result in reads.into_iter().map(ChunkFuture::wait) {
+                match result {
+                    Ok(chunk) => {
+                        self.io.write(chunk);
+                        upgraded += 1;
+                    }
+                    Err(e) => {
+                        warn!("{}", e);
+                        failed += 1;
+                    }
+                }
+            }
+            self.io.flush();
+        }
+        let unwritten = self.io.take_failed().len();
+        (upgraded - unwritten, failed + unwritten)
+    }
+
+    // Geschrieben wird später von einem Worker; bis dahin liefert load_chunk diesen Stand
+    pub fn save_chunk(&mut self, chunk: Chunk) {
+        self.on_disk.insert((chunk.x, chunk.z));
+        self.io.write(chunk);
+    }
+}
+
+fn player_file(dir: &Path, uuid: Uuid) -> PathBuf {
+    dir.join(PLAYER_DIR).join(format!("{}.json", uuid))
+}
+
+fn player_json(player: &Player) -> Json {
+    let mut data = BTreeMap::new();
+    data.insert("name".to_string(), Json::String(player.username.clone()));
+    let (x, y, z) = player.position;
+    data.insert("position".to_string(), Json::Array(vec![Json::Number(x), Json::Number(y), Json::Number(z)]));
+    data.insert("inventory".to_string(), player.inventory.to_json());
+    data.insert("ender_chest".to_string(), item::slots_to_json(&player.ender_chest));
+    data.insert("experience".to_string(), player.experience.to_json());
+    data.insert("recipe_book".to_string(), player.recipe_book.to_json());
+    data.insert("enchantment_seed".to_string(), Json::Number(player.enchantment_seed as f64));
+    data.insert("game_mode".to_string(), Json::String(player.game_mode.name().to_string()));
+    if !player.tags.is_empty() {
+        data.insert("tags".to_string(), Json::Array(player.tags.iter().cloned().map(Json::String).collect()));
+    }
+    if let Some(spawn) = player.spawn_point {
+        data.insert("spawn".to_string(), spawn_json(spawn));
+    }
+    Json::Object(data)
+}
+
+pub struct SaveSummary {
+    pub chunks: usize,
+    pub players: usize,
+    pub failed: usize,
+}
+
+// Kopiert unter den Sperren alles Geänderte heraus und schreibt es danach ohne Sperren. Die Chunks schreiben die
+// Chunk-Worker; mit flush wartet save_all auf sie. Fehlgeschlagene Chunks werden beim nächsten Mal erneut versucht
+pub fn save_all(server: &Server, flush: bool) -> SaveSummary {
+    let started = Instant::now();
+    let mut summary = SaveSummary { chunks: 0, players: 0, failed: 0 };
+    let players = server.players.lock().unwrap();
+    let player_files: Vec<(Uuid, String)> = players.iter().map(|p| (p.uuid, player_json(p).to_json_string())).collect();
+    // Je Welt: Ordner, level.json und geänderte Chunks; Spielerdaten liegen immer bei der Oberwelt
+    let mut worlds = Vec::new();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let failed = world.storage.io.take_failed();
+        world.dirty_chunks.extend(failed);
+        let mut dirty: Vec<(i32, i32)> = world.dirty_chunks.drain().collect();
+        dirty.sort();
+        for coords in dirty {
+            let Some(chunk) = world.chunks.get(&coords).cloned() else { continue };
+            world.storage.save_chunk(chunk);
+            summary.chunks += 1;
+        }
+        let level = LevelData {
+            seed: world.seed,
+            time: world.time,
+            weather: world.weather,
+            rules: world.rules.clone(),
+            spawn: Some(world.spawn),
+            border: world.border,
+            scheduled_functions: world.scheduled_functions.clone(),
+            dragon_fight: world.dragon_fight.clone(),
+            difficulty: Some(world.difficulty),
+            settings: Some(world.settings),
+        };
+        worlds.push((world.storage.dir.clone(), world.storage.io(), level.to_json().to_json_string()));
+    }
+    let player_dir = server.world.lock().unwrap().storage.dir.clone();
+    drop(players);
+
+    let mut report = |result: Result<(), String>| match result {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("{}", e);
+            summary.failed += 1;
+            false
+        }
+    };
+    for (dir, _, level) in &worlds {
+        report(write_atomic(&dir.join(LEVEL_FILE), level.as_bytes()));
+    }
+    let mut players_saved = 0;
+    for (uuid, data) in player_files {
+        if report(write_atomic(&player_file(&player_dir, uuid), data.as_bytes())) {
+            players_saved += 1;
+        }
+    }
+    summary.players = players_saved;
+    let maps = server.maps.lock().unwrap().take_changed();
+    let failed_maps: Vec<PathBuf> = maps.into_iter().filter(|(path, data)| !report(write_atomic(path, data))).map(|(path, _)| path).collect();
+    let mut store = server.maps.lock().unwrap();
+    for path in failed_maps {
+        store.mark_changed(&path);
+    }
+    drop(store);
+    let scoreboard = server.scoreboard.lock().unwrap().take_changed();
+    if scoreboard.is_some_and(|(path, data)| !report(write_atomic(&path, &data))) {
+        server.scoreboard.lock().unwrap().mark_changed();
+    }
+
+    if flush {
+        for (_, io, _) in &worlds {
+            summary.failed += io.flush();
+        }
+    }
+    info!(chunks = summary.chunks, players = summary.players, "Welt gespeichert in {}ms", started.elapsed().as_millis());
+    summary
+}
+
+// Läuft über den Scheduler, solange save-off es nicht abgeschaltet hat und kein Backup gepackt wird
+pub fn autosave(server: &Server) {
+    if server.autosave.load(Ordering::Relaxed) && !server.backups.running() {
+        save_all(server, false);
+    }
+}
+
+// Beim Verlassen, damit die Daten nicht bis zum nächsten Autosave verloren gehen können
+pub fn save_player(server: &Server, player: &Player) {
+    let dir = server.world.lock().unwrap().storage.dir.clone();
+    if let Err(e) = write_atomic(&player_file(&dir, player.uuid), player_json(player).to_json_string().as_bytes()) {
+        warn!("{}", e);
+    }
+}
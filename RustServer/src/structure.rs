@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::ops::Neg;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::blockentity::BlockEntity;
+use crate::json::{self, Json};
+use crate::{apply_block_changes, block, entity, gzip, nbt, BlockChange, Mob, Server};
+
+// Sonderblöcke aus Vorlagen: Datenmarkierungen, Jigsaw-Verbindungen und Stellen, die unverändert bleiben
+const STRUCTURE_BLOCK: &str = "minecraft:structure_block";
+const JIGSAW: &str = "minecraft:jigsaw";
+const STRUCTURE_VOID: &str = "minecraft:structure_void";
+const HORIZONTAL: [&str; 4] = ["north", "east", "south", "west"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    None,
+    Clockwise90,
+    Clockwise180,
+    Counterclockwise90,
+}
+
+impl Rotation {
+    pub fn parse(name: &str) -> Option<Rotation> {
+        match name {
+            "none" => Some(Rotation::None),
+            "clockwise_90" => Some(Rotation::Clockwise90),
+            "180" => Some(Rotation::Clockwise180),
+            "counterclockwise_90" => Some(Rotation::Counterclockwise90),
+            _ => None,
+        }
+    }
+
+    fn turns(self) -> usize {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 1,
+            Rotation::Clockwise180 => 2,
+            Rotation::Counterclockwise90 => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mirror {
+    #[default]
+    None,
+    // Spiegelt entlang der z-Achse bzw. der x-Achse, wie die gleichnamigen Optionen des Strukturblocks
+    LeftRight,
+    FrontBack,
+}
+
+impl Mirror {
+    pub fn parse(name: &str) -> Option<Mirror> {
+        match name {
+            "none" => Some(Mirror::None),
+            "left_right" => Some(Mirror::LeftRight),
+            "front_back" => Some(Mirror::FrontBack),
+            _ => None,
+        }
+    }
+}
+
+// Wie eine Vorlage gesetzt wird; integrity ist der Anteil der Blöcke, die tatsächlich gesetzt werden
+#[derive(Debug, Clone, Copy)]
+pub struct Placement {
+    pub rotation: Rotation,
+    pub mirror: Mirror,
+    pub integrity: f32,
+    pub seed: u64,
+}
+
+impl Default for Placement {
+    fn default() -> Placement {
+        Placement { rotation: Rotation::None, mirror: Mirror::None, integrity: 1.0, seed: 0 }
+    }
+}
+
+// Position relativ zur Ecke der Vorlage; wie bei Vanilla erst gespiegelt, dann um die Ecke gedreht
+pub fn transform((x, y, z): (i32, i32, i32), placement: &Placement) -> (i32, i32, i32) {
+    let (x, z) = turn((x, z), placement);
+    (x, y, z)
+}
+
+// Punkte wie Entity-Positionen landen im selben Block wie bei transform und behalten ihre Lage darin, gedreht um
+// die Blockmitte
+pub fn transform_point((x, y, z): (f64, f64, f64), placement: &Placement) -> (f64, f64, f64) {
+    let (bx, by, bz) = transform((x.floor() as i32, y.floor() as i32, z.floor() as i32), placement);
+    let (fx, fz) = turn((x.rem_euclid(1.0) - 0.5, z.rem_euclid(1.0) - 0.5), placement);
+    (bx as f64 + fx + 0.5, by as f64 + y.rem_euclid(1.0), bz as f64 + fz + 0.5)
+}
+
+fn turn<T: Neg<Output = T>>((x, z): (T, T), placement: &Placement) -> (T, T) {
+    let (x, z) = match placement.mirror {
+        Mirror::None => (x, z),
+        Mirror::LeftRight => (x, -z),
+        Mirror::FrontBack => (-x, z),
+    };
+    match placement.rotation {
+        Rotation::None => (x, z),
+        Rotation::Clockwise90 => (-z, x),
+        Rotation::Clockwise180 => (-x, -z),
+        Rotation::Counterclockwise90 => (z, -x),
+    }
+}
+
+// Dreht und spiegelt die richtungsabhängigen Eigenschaften: facing, axis, rotation (Schilder) und bei Truhen type
+pub fn transform_state(state: u16, placement: &Placement) -> u16 {
+    let mut state = state;
+    if let Some(facing) = block::property(state, "facing") {
+        if let Some(i) = HORIZONTAL.iter().position(|f| *f == facing) {
+            let i = match (placement.mirror, i) {
+                (Mirror::LeftRight, 0 | 2) | (Mirror::FrontBack, 1 | 3) => (i + 2) % 4,
+                _ => i,
+            };
+            let facing = HORIZONTAL[(i + placement.rotation.turns()) % 4];
+            state = block::with_property(state, "facing", facing).unwrap_or(state);
+        }
+    }
+    if let Some(axis) = block::property(state, "axis") {
+        if placement.rotation.turns() % 2 == 1 && axis != "y" {
+            state = block::with_property(state, "axis", if axis == "x" { "z" } else { "x" }).unwrap_or(state);
+        }
+    }
+    if let Some(rotation) = block::property(state, "rotation").and_then(|r| r.parse::<i32>().ok()) {
+        let mirrored = match placement.mirror {
+            Mirror::None => rotation,
+            Mirror::LeftRight => (8 - rotation).rem_euclid(16),
+            Mirror::FrontBack => (16 - rotation) % 16,
+        };
+        let rotation = (mirrored + 4 * placement.rotation.turns() as i32) % 16;
+        state = block::with_property(state, "rotation", &rotation.to_string()).unwrap_or(state);
+    }
+    if placement.mirror != Mirror::None {
+        let flipped = match block::property(state, "type") {
+            Some("left") => Some("right"),
+            Some("right") => Some("left"),
+            _ => None,
+        };
+        if let Some(flipped) = flipped {
+            state = block::with_property(state, "type", flipped).unwrap_or(state);
+        }
+    }
+    state
+}
+
+// Eintrag der Palette; state ist None für Sonderblöcke und Blöcke, die das Register nicht kennt
+#[derive(Debug, Clone)]
+struct PaletteEntry {
+    name: String,
+    state: Option<u16>,
+}
+
+#[derive(Debug, Clone)]
+struct TemplateBlock {
+    pos: (i32, i32, i32),
+    palette_index: usize,
+    nbt: Option<Json>,
+}
+
+#[derive(Debug, Clone)]
+struct TemplateEntity {
+    pos: (f64, f64, f64),
+    nbt: Json,
+}
+
+// Vanilla-Strukturvorlage (.nbt), wie sie Strukturblöcke speichern und Datenpakete unter structure/ mitbringen
+#[derive(Debug, Clone)]
+pub struct StructureTemplate {
+    pub size: (i32, i32, i32),
+    // Mehrere Paletten haben etwa Schiffswracks; beim Setzen wird eine per Seed gewählt
+    palettes: Vec<Vec<PaletteEntry>>,
+    blocks: Vec<TemplateBlock>,
+    entities: Vec<TemplateEntity>,
+}
+
+#[derive(Debug)]
+pub struct PlacedEntity {
+    pub kind: String,
+    pub position: (f64, f64, f64),
+    pub yaw: f32,
+    pub nbt: Json,
+}
+
+// Was eine Vorlage an einer Stelle ergibt, in Weltkoordinaten
+#[derive(Debug, Default)]
+pub struct Placed {
+    pub blocks: Vec<BlockChange>,
+    pub block_entities: Vec<((i32, i32, i32), Json)>,
+    pub entities: Vec<PlacedEntity>,
+    // Strukturblöcke im Datenmodus mit ihrem Text; gesetzt werden sie nicht
+    pub markers: Vec<((i32, i32, i32), String)>,
+}
+
+fn int_triple(value: Option<&Json>) -> Option<(i32, i32, i32)> {
+    match value?.as_array()?.as_slice() {
+        [x, y, z] => Some((x.as_f64()? as i32, y.as_f64()? as i32, z.as_f64()? as i32)),
+        _ => None,
+    }
+}
+
+fn palette_entry(entry: &Json) -> Result<PaletteEntry, String> {
+    let name = entry.get("Name").and_then(Json::as_str).ok_or("Paletteneintrag ohne Name")?.to_string();
+    let properties: Vec<String> = entry
+        .get("Properties")
+        .and_then(Json::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some(format!("{}={}", key, value.as_str()?)))
+        .collect();
+    let spec = if properties.is_empty() { name.clone() } else { format!("{}[{}]", name, properties.join(",")) };
+    let special = [STRUCTURE_BLOCK, JIGSAW, STRUCTURE_VOID].contains(&name.as_str());
+    let state = if special { None } else { block::parse_state(&spec).ok() };
+    Ok(PaletteEntry { name, state })
+}
+
+impl StructureTemplate {
+    // Vorlagen sind gzip-komprimiert; unkomprimierte werden ebenfalls gelesen
+    pub fn parse(data: &[u8]) -> Result<StructureTemplate, String> {
+        let data = if data.starts_with(&[0x1F, 0x8B]) { gzip::decompress(data)? } else { data.to_vec() };
+        let root = nbt::read(&data)?;
+        let size = int_triple(root.get("size")).ok_or("Vorlage ohne gültige Größe")?;
+        let palettes: Vec<&Json> = match (root.get("palette"), root.get("palettes").and_then(Json::as_array)) {
+            (Some(palette), _) => vec![palette],
+            (None, Some(palettes)) => palettes.iter().collect(),
+            (None, None) => return Err("Vorlage ohne Palette".to_string()),
+        };
+        let palettes = palettes
+            .into_iter()
+            .map(|palette| palette.as_array().ok_or("Palette ist keine Liste")?.iter().map(palette_entry).collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let length = palettes.iter().map(Vec::len).min().unwrap_or(0);
+        let mut blocks = Vec::new();
+        for entry in root.get("blocks").and_then(Json::as_array).into_iter().flatten() {
+            let pos = int_triple(entry.get("pos")).ok_or("Block der Vorlage ohne Position")?;
+            let palette_index = entry.get("state").and_then(Json::as_f64).map(|s| s as usize).filter(|&s| s < length).ok_or("Block der Vorlage mit ungültigem Zustand")?;
+            blocks.push(TemplateBlock { pos, palette_index, nbt: entry.get("nbt").cloned() });
+        }
+        let mut entities = Vec::new();
+        for entry in root.get("entities").and_then(Json::as_array).into_iter().flatten() {
+            let pos = match entry.get("pos").and_then(Json::as_array).map(Vec::as_slice) {
+                Some([x, y, z]) => (x.as_f64().unwrap_or(0.0), y.as_f64().unwrap_or(0.0), z.as_f64().unwrap_or(0.0)),
+                _ => continue,
+            };
+            let Some(nbt) = entry.get("nbt").filter(|nbt| nbt.get("id").is_some()) else { continue };
+            entities.push(TemplateEntity { pos, nbt: nbt.clone() });
+        }
+        Ok(StructureTemplate { size, palettes, blocks, entities })
+    }
+
+    // Blöcke aus der Palette, die das Register nicht kennt und die daher fehlen werden
+    pub fn unknown_blocks(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.palettes.iter().flatten().filter(|e| e.state.is_none() && ![STRUCTURE_BLOCK, JIGSAW, STRUCTURE_VOID].contains(&e.name.as_str())).map(|e| e.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    // Rechnet die Vorlage für eine Ecke in Weltkoordinaten um, ohne die Welt anzufassen; so kann auch der
+    // Generator Vorlagen direkt in Chunks übernehmen
+    pub fn resolve(&self, origin: (i32, i32, i32), placement: &Placement) -> Placed {
+        let mut rng = StdRng::seed_from_u64(placement.seed);
+        let palette = &self.palettes[rng.gen_range(0..self.palettes.len())];
+        let at = |pos| {
+            let (x, y, z) = transform(pos, placement);
+            (origin.0 + x, origin.1 + y, origin.2 + z)
+        };
+        let mut placed = Placed::default();
+        for template_block in &self.blocks {
+            let entry = &palette[template_block.palette_index];
+            let pos = at(template_block.pos);
+            let nbt = template_block.nbt.as_ref();
+            let state = match entry.name.as_str() {
+                STRUCTURE_VOID => continue,
+                STRUCTURE_BLOCK => {
+                    if nbt.and_then(|n| n.get("mode")).and_then(Json::as_str) == Some("DATA") {
+                        let metadata = nbt.and_then(|n| n.get("metadata")).and_then(Json::as_str).unwrap_or_default();
+                        placed.markers.push((pos, metadata.to_string()));
+                    }
+                    continue;
+                }
+                // Jigsaw-Blöcke werden zu dem Block, den sie nach dem Zusammensetzen sein sollen
+                JIGSAW => nbt.and_then(|n| n.get("final_state")).and_then(Json::as_str).and_then(|s| block::parse_state(s).ok()).unwrap_or(block::AIR),
+                _ => match entry.state {
+                    Some(state) => state,
+                    None => continue,
+                },
+            };
+            if placement.integrity < 1.0 && rng.gen::<f32>() >= placement.integrity {
+                continue;
+            }
+            placed.blocks.push((pos, transform_state(state, placement)));
+            if let Some(nbt) = nbt.filter(|_| entry.state.is_some()) {
+                placed.block_entities.push((pos, nbt.clone()));
+            }
+        }
+        for template_entity in &self.entities {
+            let Some(kind) = template_entity.nbt.get("id").and_then(Json::as_str) else { continue };
+            let (x, y, z) = transform_point(template_entity.pos, placement);
+            let position = (origin.0 as f64 + x, origin.1 as f64 + y, origin.2 as f64 + z);
+            // Blickrichtung wie die Position: 0 ist Süden, gespiegelt und dann in Vierteldrehungen gedreht
+            let yaw = template_entity.nbt.get("Rotation").and_then(|r| r.as_array()?.first()?.as_f64()).unwrap_or(0.0) as f32;
+            let yaw = match placement.mirror {
+                Mirror::None => yaw,
+                Mirror::LeftRight => 180.0 - yaw,
+                Mirror::FrontBack => -yaw,
+            };
+            let yaw = (yaw + 90.0 * placement.rotation.turns() as f32).rem_euclid(360.0);
+            placed.entities.push(PlacedEntity { kind: kind.to_string(), position, yaw, nbt: template_entity.nbt.clone() });
+        }
+        placed
+    }
+}
+
+// Block-Entity aus Vanilla-NBT ("Items" mit "Slot") im Format von BlockEntity::from_json
+fn block_entity_json(nbt: &Json, (x, y, z): (i32, i32, i32)) -> Json {
+    let mut data = nbt.as_object().cloned().unwrap_or_default();
+    data.insert("x".to_string(), Json::Number(x as f64));
+    data.insert("y".to_string(), Json::Number(y as f64));
+    data.insert("z".to_string(), Json::Number(z as f64));
+    if let Some(items) = data.remove("Items").as_ref().and_then(Json::as_array) {
+        let items = items
+            .iter()
+            .filter_map(|item| {
+                let mut entry: BTreeMap<String, Json> = item.as_object()?.clone();
+                let slot = entry.remove("Slot")?;
+                entry.insert("slot".to_string(), slot);
+                if let Some(count) = entry.remove("Count") {
+                    entry.entry("count".to_string()).or_insert(count);
+                }
+                Some(Json::Object(entry))
+            })
+            .collect();
+        data.insert("items".to_string(), Json::Array(items));
+    }
+    Json::Object(data)
+}
+
+// Setzt eine aufgelöste Vorlage in die Oberwelt und zeigt sie allen; gibt die Zahl der geänderten Blöcke zurück
+pub fn place(server: &Server, placed: &Placed) -> usize {
+    let count = apply_block_changes(server, &placed.blocks);
+    let players = server.players.lock().unwrap();
+    let mut world = server.world.lock().unwrap();
+    let dimension = world.dimension;
+    for (pos, nbt) in &placed.block_entities {
+        let Some((_, data)) = BlockEntity::from_json(&block_entity_json(nbt, *pos)) else { continue };
+        let Some(chunk) = world.chunks.get_mut(&(pos.0 >> 4, pos.2 >> 4)) else { continue };
+        // Nur übernehmen, wenn World::set_block für den Block dieselbe Art angelegt hat
+        let Some(existing) = chunk.block_entities.get_mut(pos).filter(|e| e.id() == data.id()) else { continue };
+        *existing = data;
+        for player in players.iter().filter(|p| p.dimension == dimension) {
+            let _ = existing.send_data(&player.connection, *pos);
+        }
+    }
+    for placed_entity in &placed.entities {
+        let (kind, position, nbt) = (&placed_entity.kind, placed_entity.position, &placed_entity.nbt);
+        if !entity::is_known(kind) || !entity::is_summonable(kind) {
+            continue;
+        }
+        let mut mob = Mob::new(kind, position);
+        mob.yaw = placed_entity.yaw;
+        mob.custom_name = nbt.get("CustomName").and_then(Json::as_str).and_then(|name| json::parse(name).ok());
+        mob.custom_name_visible = nbt.get("CustomNameVisible").and_then(Json::as_f64).is_some_and(|v| v != 0.0);
+        for player in players.iter().filter(|p| entity::in_view(p, world.dimension, position)) {
+            let _ = entity::send_mob(&player.connection, &mob);
+        }
+        world.spawn_mob(mob);
+    }
+    for (pos, metadata) in &placed.markers {
+        debug!("Datenmarkierung \"{}\" bei {:?}", metadata, pos);
+    }
+    count
+}
@@ -0,0 +1,126 @@
+use std::net::TcpListener;
+use std::os::fd::{FromRawFd, RawFd};
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+// Erster übergebener Socket bei Socket-Aktivierung, wie SD_LISTEN_FDS_START
+const LISTEN_FDS_START: RawFd = 3;
+
+// Anbindung an systemd ohne libsystemd. Außerhalb von systemd fehlen NOTIFY_SOCKET, WATCHDOG_USEC und LISTEN_FDS,
+// dann tut hier nichts etwas
+static WATCHDOG: OnceLock<Option<Watchdog>> = OnceLock::new();
+static ACTIVATED: OnceLock<Vec<RawFd>> = OnceLock::new();
+
+#[derive(Debug)]
+struct Watchdog {
+    // Halb so lang wie WatchdogSec, wie von systemd empfohlen
+    interval: Duration,
+    last_ping: Mutex<Instant>,
+}
+
+// sd_notify: eine Zeile je Zuweisung, etwa READY=1. Fehler nur im Debug-Log, der Server läuft auch ohne
+pub fn notify(state: &str) {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else { return };
+    let path = path.to_string_lossy();
+    let result = UnixDatagram::unbound().and_then(|socket| {
+        match path.strip_prefix('@') {
+            Some(name) => socket.send_to_addr(state.as_bytes(), &SocketAddr::from_abstract_name(name)?),
+            None => socket.send_to(state.as_bytes(), path.as_ref()),
+        }
+    });
+    if let Err(e) = result {
+        debug!("Konnte systemd nicht benachrichtigen: {}", e);
+    }
+}
+
+// Erst wenn Welten geladen sind und alle Listener offen, sonst verbindet sich ein früher Client ins Leere
+pub fn ready(port: u16) {
+    notify(&format!("READY=1\nSTATUS=Listening on port {}\nMAINPID={}", port, std::process::id()));
+}
+
+pub fn stopping() {
+    notify("STOPPING=1");
+}
+
+// Aus der Tick-Schleife: hängt sie, bleiben die Pings aus und systemd startet den Dienst nach WatchdogSec neu
+pub fn watchdog_tick() {
+    let Some(watchdog) = WATCHDOG.get_or_init(watchdog_from_env) else { return };
+    let mut last_ping = watchdog.last_ping.lock().unwrap();
+    if last_ping.elapsed() >= watchdog.interval {
+        *last_ping = Instant::now();
+        notify("WATCHDOG=1");
+    }
+}
+
+fn watchdog_from_env() -> Option<Watchdog> {
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    // Gilt die Variable einem anderen Prozess, etwa einem Wrapper-Skript, ist sie hier nur geerbt
+    if !for_this_process("WATCHDOG_PID") || usec == 0 {
+        return None;
+    }
+    info!("systemd-Watchdog aktiv ({}ms)", usec / 1000);
+    Some(Watchdog { interval: Duration::from_micros(usec / 2), last_ping: Mutex::new(Instant::now()) })
+}
+
+// Fehlt die PID-Variable, hat sie auch niemand für einen anderen Prozess gesetzt
+fn for_this_process(variable: &str) -> bool {
+    std::env::var(variable).map_or(true, |pid| pid.parse() == Ok(std::process::id()))
+}
+
+// Von systemd geöffnete Listener (ListenStream= in einer .socket-Unit); leer ohne Socket-Aktivierung. Jeder Aufruf
+// liefert neue Handles auf dieselben Sockets, gedacht ist er einmal beim Start
+pub fn listeners() -> Vec<TcpListener> {
+    ACTIVATED
+        .get_or_init(activated_fds)
+        .iter()
+        .map(|&fd| unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, LISTEN_FDS_START) })
+        .filter(|&fd| fd >= 0)
+        .map(|fd| unsafe { TcpListener::from_raw_fd(fd) })
+        .collect()
+}
+
+fn activated_fds() -> Vec<RawFd> {
+    let Some(count) = std::env::var("LISTEN_FDS").ok().and_then(|count| count.parse::<RawFd>().ok()) else { return Vec::new() };
+    if !for_this_process("LISTEN_PID") {
+        return Vec::new();
+    }
+    (LISTEN_FDS_START..LISTEN_FDS_START + count)
+        .filter(|&fd| {
+            let listening = is_listening_stream(fd);
+            if !listening {
+                warn!("Von systemd übergebener Socket {} ist kein lauschender TCP-Socket und wird ignoriert", fd);
+            }
+            listening
+        })
+        // Wie sd_listen_fds: Plugins und andere Kindprozesse sollen die Sockets nicht erben
+        .inspect(|&fd| set_cloexec(fd, true))
+        .collect()
+}
+
+fn is_listening_stream(fd: RawFd) -> bool {
+    let option = |name| {
+        let mut value: libc::c_int = 0;
+        let mut size = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+        let result = unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, name, &mut value as *mut libc::c_int as *mut libc::c_void, &mut size) };
+        (result == 0).then_some(value)
+    };
+    option(libc::SO_TYPE) == Some(libc::SOCK_STREAM) && option(libc::SO_ACCEPTCONN) == Some(1)
+}
+
+// Vor dem Neustart per exec: die übergebenen Sockets bleiben offen und LISTEN_FDS gilt weiter, weil exec die PID
+// behält. So nimmt der neue Prozess dieselben Sockets, und systemd puffert Verbindungen in der Zwischenzeit
+pub fn inherit_listeners() {
+    for &fd in ACTIVATED.get().into_iter().flatten() {
+        set_cloexec(fd, false);
+    }
+}
+
+fn set_cloexec(fd: RawFd, cloexec: bool) {
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFD);
+        let flags = if cloexec { flags | libc::FD_CLOEXEC } else { flags & !libc::FD_CLOEXEC };
+        libc::fcntl(fd, libc::F_SETFD, flags);
+    }
+}
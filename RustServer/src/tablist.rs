@@ -0,0 +1,49 @@
+use uuid::Uuid;
+use crate::gamemode::GameMode;
+use crate::protocol::clientbound;
+use crate::protocol::types::{write_array, write_string_to_vec, write_uuid, write_varint_to_vec};
+use crate::proxy;
+use crate::{Connection, Player};
+
+// Aktionen in Player Info Update, als EnumSet in einem Byte
+const ADD_PLAYER: u8 = 0x01;
+const UPDATE_GAME_MODE: u8 = 0x04;
+const UPDATE_LISTED: u8 = 0x08;
+const UPDATE_LATENCY: u8 = 0x10;
+
+// Neue Einträge samt Profil, damit der Client auch die Skins kennt
+pub fn send_add(connection: &Connection, players: &[&Player]) -> Result<(), String> {
+    let mut packet_data = vec![ADD_PLAYER | UPDATE_GAME_MODE | UPDATE_LISTED | UPDATE_LATENCY];
+    packet_data.extend(write_array(players, |player| {
+        let mut data = write_uuid(&player.uuid).to_vec();
+        data.extend(write_string_to_vec(&player.username));
+        data.extend(proxy::write_properties(&player.properties));
+        data.extend(write_varint_to_vec(player.game_mode.id() as i32));
+        data.push(1); // In der Tab-Liste sichtbar
+        data.extend(write_varint_to_vec(player.ping() as i32));
+        data
+    }));
+    connection.send(clientbound::PLAYER_INFO_UPDATE, &packet_data)
+}
+
+pub fn send_game_mode(connection: &Connection, uuid: Uuid, mode: GameMode) -> Result<(), String> {
+    let mut packet_data = vec![UPDATE_GAME_MODE];
+    packet_data.extend(write_varint_to_vec(1));
+    packet_data.extend(write_uuid(&uuid));
+    packet_data.extend(write_varint_to_vec(mode.id() as i32));
+    connection.send(clientbound::PLAYER_INFO_UPDATE, &packet_data)
+}
+
+pub fn send_latency(connection: &Connection, players: &[&Player]) -> Result<(), String> {
+    let mut packet_data = vec![UPDATE_LATENCY];
+    packet_data.extend(write_array(players, |player| {
+        let mut data = write_uuid(&player.uuid).to_vec();
+        data.extend(write_varint_to_vec(player.ping() as i32));
+        data
+    }));
+    connection.send(clientbound::PLAYER_INFO_UPDATE, &packet_data)
+}
+
+pub fn send_remove(connection: &Connection, uuids: &[Uuid]) -> Result<(), String> {
+    connection.send(clientbound::PLAYER_INFO_REMOVE, &write_array(uuids, |uuid| write_uuid(uuid).to_vec()))
+}
@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+use crate::json::Json;
+use crate::lang::{self, Part, Translations};
+use crate::nbt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decoration {
+    Bold,
+    Italic,
+    Underlined,
+    Strikethrough,
+    Obfuscated,
+}
+
+impl Decoration {
+    pub const ALL: [Decoration; 5] = [Decoration::Bold, Decoration::Italic, Decoration::Underlined, Decoration::Strikethrough, Decoration::Obfuscated];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Decoration::Bold => "bold",
+            Decoration::Italic => "italic",
+            Decoration::Underlined => "underlined",
+            Decoration::Strikethrough => "strikethrough",
+            Decoration::Obfuscated => "obfuscated",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClickEvent {
+    SuggestCommand(String),
+    CopyToClipboard(String),
+}
+
+impl ClickEvent {
+    fn action(&self) -> &'static str {
+        match self {
+            ClickEvent::SuggestCommand(_) => "suggest_command",
+            ClickEvent::CopyToClipboard(_) => "copy_to_clipboard",
+        }
+    }
+
+    fn value(&self) -> &str {
+        match self {
+            ClickEvent::SuggestCommand(value) | ClickEvent::CopyToClipboard(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HoverEvent {
+    ShowText(Box<Text>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Content {
+    Literal(String),
+    // Übersetzungsschlüssel mit Argumenten; fallback zeigt der Client, wenn er den Schlüssel nicht kennt
+    Translate { key: String, with: Vec<Text>, fallback: Option<String> },
+}
+
+impl Default for Content {
+    fn default() -> Content {
+        Content::Literal(String::new())
+    }
+}
+
+// Textkomponente für Chat, Kick-Gründe, Todesnachrichten und die MOTD. Nicht gesetzte Formatierung erbt der
+// Client vom Elternteil, deshalb sind Farbe und Dekorationen optional
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Text {
+    content: Content,
+    color: Option<String>,
+    decorations: [Option<bool>; 5],
+    click: Option<ClickEvent>,
+    hover: Option<HoverEvent>,
+    extra: Vec<Text>,
+}
+
+impl Text {
+    pub fn new(content: impl Into<String>) -> Text {
+        Text { content: Content::Literal(content.into()), ..Text::default() }
+    }
+
+    // Übersetzbarer Text wie {"translate": "multiplayer.player.joined", "with": [...]}
+    pub fn translate(key: impl Into<String>) -> Text {
+        Text { content: Content::Translate { key: key.into(), with: Vec::new(), fallback: None }, ..Text::default() }
+    }
+
+    // Nächstes Argument für %s; bei einfachem Text ohne Wirkung
+    pub fn with(mut self, argument: impl Into<Text>) -> Text {
+        if let Content::Translate { with, .. } = &mut self.content {
+            with.push(argument.into());
+        }
+        self
+    }
+
+    // Benannte Farbe ("gray") oder Hex-Farbe ("#ff8800")
+    pub fn color(mut self, color: impl Into<String>) -> Text {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn decoration(mut self, decoration: Decoration, enabled: bool) -> Text {
+        self.decorations[decoration.index()] = Some(enabled);
+        self
+    }
+
+    pub fn italic(self) -> Text {
+        self.decoration(Decoration::Italic, true)
+    }
+
+    pub fn click(mut self, event: ClickEvent) -> Text {
+        self.click = Some(event);
+        self
+    }
+
+    pub fn hover(mut self, text: Text) -> Text {
+        self.hover = Some(HoverEvent::ShowText(Box::new(text)));
+        self
+    }
+
+    pub fn append(mut self, child: impl Into<Text>) -> Text {
+        self.extra.push(child.into());
+        self
+    }
+
+    pub fn push(&mut self, child: impl Into<Text>) {
+        self.extra.push(child.into());
+    }
+
+    // Reiner Text ohne Formatierung und Kinder; wird kompakt als String übertragen
+    fn is_plain(&self) -> bool {
+        self.color.is_none() && self.decorations.iter().all(Option::is_none) && self.click.is_none() && self.hover.is_none() && self.extra.is_empty()
+    }
+
+    // Sichtbarer Text samt Kindern, etwa für Konsole und Log
+    pub fn plain(&self) -> String {
+        let mut text = match &self.content {
+            Content::Literal(text) => text.clone(),
+            Content::Translate { key, with, fallback } => lang::parts(fallback.as_deref().unwrap_or(key))
+                .into_iter()
+                .map(|part| match part {
+                    Part::Literal(text) => text,
+                    Part::Argument(index) => with.get(index).map(Text::plain).unwrap_or_default(),
+                })
+                .collect(),
+        };
+        for child in &self.extra {
+            text.push_str(&child.plain());
+        }
+        text
+    }
+
+    pub fn to_json(&self) -> Json {
+        let mut map = BTreeMap::new();
+        match &self.content {
+            Content::Literal(text) => {
+                map.insert("text".to_string(), Json::String(text.clone()));
+            }
+            Content::Translate { key, with, fallback } => {
+                map.insert("translate".to_string(), Json::String(key.clone()));
+                if !with.is_empty() {
+                    map.insert("with".to_string(), Json::Array(with.iter().map(Text::to_json).collect()));
+                }
+                if let Some(fallback) = fallback {
+                    map.insert("fallback".to_string(), Json::String(fallback.clone()));
+                }
+            }
+        }
+        if let Some(color) = &self.color {
+            map.insert("color".to_string(), Json::String(color.clone()));
+        }
+        for decoration in Decoration::ALL {
+            if let Some(enabled) = self.decorations[decoration.index()] {
+                map.insert(decoration.key().to_string(), Json::Bool(enabled));
+            }
+        }
+        if let Some(click) = &self.click {
+            let mut event = BTreeMap::new();
+            event.insert("action".to_string(), Json::String(click.action().to_string()));
+            event.insert("value".to_string(), Json::String(click.value().to_string()));
+            map.insert("clickEvent".to_string(), Json::Object(event));
+        }
+        if let Some(HoverEvent::ShowText(text)) = &self.hover {
+            let mut event = BTreeMap::new();
+            event.insert("action".to_string(), Json::String("show_text".to_string()));
+            event.insert("contents".to_string(), text.to_json());
+            map.insert("hoverEvent".to_string(), Json::Object(event));
+        }
+        if !self.extra.is_empty() {
+            map.insert("extra".to_string(), Json::Array(self.extra.iter().map(Text::to_json).collect()));
+        }
+        Json::Object(map)
+    }
+
+    // Für Login-Disconnect und Statusantwort, die Komponenten noch als JSON-String erwarten
+    pub fn to_json_string(&self) -> String {
+        self.to_json().to_json_string()
+    }
+
+    // Netzwerk-NBT für Pakete im Spiel (seit 1.20.3)
+    pub fn to_nbt(&self) -> Vec<u8> {
+        match &self.content {
+            Content::Literal(text) if self.is_plain() => nbt::from_json(&Json::String(text.clone())),
+            _ => nbt::from_json(&self.to_json()),
+        }
+    }
+
+    // Übersetzt Schlüssel, für die der Server einen Text in dieser Sprache hat. Die übrigen bleiben für den
+    // Client stehen, mit dem englischen Text als fallback für Schlüssel, die nur der Server kennt
+    pub fn localize(&self, translations: &Translations, locale: &str) -> Text {
+        let mut text = self.clone();
+        text.extra = self.extra.iter().map(|child| child.localize(translations, locale)).collect();
+        if let Some(HoverEvent::ShowText(hover)) = &self.hover {
+            text.hover = Some(HoverEvent::ShowText(Box::new(hover.localize(translations, locale))));
+        }
+        let Content::Translate { key, with, .. } = &self.content else { return text };
+        let with: Vec<Text> = with.iter().map(|argument| argument.localize(translations, locale)).collect();
+        let Some(template) = translations.get(locale, key) else {
+            let fallback = translations.get(lang::DEFAULT_LOCALE, key).map(String::from);
+            text.content = Content::Translate { key: key.clone(), with, fallback };
+            return text;
+        };
+        let parts = lang::parts(template).into_iter().map(|part| match part {
+            Part::Literal(literal) => Text::new(literal),
+            Part::Argument(index) => with.get(index).cloned().unwrap_or_default(),
+        });
+        text.content = Content::Literal(String::new());
+        text.extra = parts.chain(text.extra).collect();
+        text
+    }
+}
+
+impl From<&str> for Text {
+    fn from(content: &str) -> Text {
+        Text::new(content)
+    }
+}
+
+impl From<String> for Text {
+    fn from(content: String) -> Text {
+        Text::new(content)
+    }
+}
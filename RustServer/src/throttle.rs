@@ -0,0 +1,216 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::config::ServerConfig;
+use crate::protocol::serverbound;
+
+const LOGIN_WINDOW: Duration = Duration::from_secs(60);
+
+// Begrenzt Verbindungsversuche pro IP und die Zahl gleichzeitig nicht eingeloggter Verbindungen
+pub struct ConnectionThrottle {
+    max_per_second: usize,
+    cooldown: Duration,
+    max_pending: usize,
+    attempts: HashMap<IpAddr, VecDeque<Instant>>,
+    pending: Arc<AtomicUsize>,
+}
+
+// Zählt eine Verbindung als "nicht eingeloggt", bis sie gedroppt wird
+pub struct PendingGuard {
+    pending: Arc<AtomicUsize>,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ConnectionThrottle {
+    pub fn new(config: &ServerConfig) -> ConnectionThrottle {
+        ConnectionThrottle {
+            max_per_second: config.max_handshakes_per_second.max(1) as usize,
+            cooldown: Duration::from_millis(config.reconnect_cooldown_ms),
+            max_pending: config.max_pending_connections.max(1),
+            attempts: HashMap::new(),
+            pending: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn try_accept(&mut self, ip: IpAddr) -> Result<PendingGuard, String> {
+        let now = Instant::now();
+        let window = Duration::from_secs(1).max(self.cooldown);
+        if self.attempts.len() > 4096 {
+            self.attempts.retain(|_, times| times.back().is_some_and(|t| now - *t < window));
+        }
+        let times = self.attempts.entry(ip).or_default();
+        while times.front().is_some_and(|t| now - *t >= window) {
+            times.pop_front();
+        }
+        let last = times.back().copied();
+        let recent = times.iter().filter(|t| now - **t < Duration::from_secs(1)).count();
+        times.push_back(now);
+
+        if let Some(last) = last {
+            if now - last < self.cooldown {
+                return Err(format!("{} verbindet sich zu schnell erneut", ip));
+            }
+        }
+        if recent >= self.max_per_second {
+            return Err(format!("{} überschreitet {} Verbindungen pro Sekunde", ip, self.max_per_second));
+        }
+        if self.pending.load(Ordering::SeqCst) >= self.max_pending {
+            return Err(format!("Zu viele nicht eingeloggte Verbindungen ({}), lehne {} ab", self.max_pending, ip));
+        }
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        Ok(PendingGuard {
+            pending: Arc::clone(&self.pending),
+        })
+    }
+}
+
+// Login-Versuche pro IP in der letzten Minute, unabhängig von den Verbindungen oben: ein Bot, der sich langsam genug
+// verbindet, kommt durch ConnectionThrottle, loggt sich aber nicht beliebig oft ein. Wer das Limit überschreitet,
+// wird für login-throttle-secs ganz abgewiesen
+pub struct LoginThrottle {
+    max_per_minute: usize,
+    block: Duration,
+    attempts: HashMap<IpAddr, VecDeque<Instant>>,
+    blocked: HashMap<IpAddr, Instant>,
+}
+
+impl LoginThrottle {
+    pub fn new(config: &ServerConfig) -> LoginThrottle {
+        LoginThrottle {
+            max_per_minute: config.max_logins_per_minute as usize,
+            block: Duration::from_secs(config.login_throttle_secs),
+            attempts: HashMap::new(),
+            blocked: HashMap::new(),
+        }
+    }
+
+    pub fn try_login(&mut self, ip: IpAddr) -> Result<(), String> {
+        if self.max_per_minute == 0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        self.blocked.retain(|_, until| *until > now);
+        if let Some(until) = self.blocked.get(&ip) {
+            return Err(format!("{} ist noch {}s für Logins gesperrt", ip, (*until - now).as_secs() + 1));
+        }
+        if self.attempts.len() > 4096 {
+            self.attempts.retain(|_, times| times.back().is_some_and(|t| now - *t < LOGIN_WINDOW));
+        }
+        let times = self.attempts.entry(ip).or_default();
+        while times.front().is_some_and(|t| now - *t >= LOGIN_WINDOW) {
+            times.pop_front();
+        }
+        times.push_back(now);
+        if times.len() > self.max_per_minute {
+            times.clear();
+            self.blocked.insert(ip, now + self.block);
+            return Err(format!("{} überschreitet {} Logins pro Minute", ip, self.max_per_minute));
+        }
+        Ok(())
+    }
+}
+
+// Bewegungspakete kommen normal 20-mal pro Sekunde, mit Fahrzeugen und Verzögerung auch in Schüben
+const MAX_MOVEMENT_PER_SECOND: u32 = 100;
+// Schläge, Abbauen und Benutzen innerhalb eines Ticks (50 ms)
+const MAX_INTERACTIONS_PER_TICK: u32 = 8;
+// Wie bei Vanilla: jede Nachricht zählt 20, pro Tick wird 1 abgebaut, über 200 wird getrennt
+const CHAT_SPAM_COST: f64 = 20.0;
+const CHAT_SPAM_THRESHOLD: f64 = 200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PacketCategory {
+    Movement,
+    Chat,
+    Interaction,
+    Other,
+}
+
+impl PacketCategory {
+    // Erwartet die interne Paket-ID, siehe ProtocolVersion::serverbound
+    pub fn of(packet_id: i32) -> PacketCategory {
+        match packet_id {
+            serverbound::SET_PLAYER_POSITION | serverbound::SET_PLAYER_POSITION_AND_ROTATION | serverbound::SET_PLAYER_ROTATION => PacketCategory::Movement,
+            serverbound::CHAT_MESSAGE | serverbound::CHAT_COMMAND => PacketCategory::Chat,
+            serverbound::INTERACT | serverbound::PLAYER_ACTION => PacketCategory::Interaction,
+            _ => PacketCategory::Other,
+        }
+    }
+}
+
+// Zählt eingehende Pakete einer Verbindung nach Art, damit ein Client den Tick nicht mit Paketen fluten kann
+pub struct PacketLimiter {
+    max_per_second: u32,
+    second_start: Instant,
+    packets: u32,
+    movement: u32,
+    tick_start: Instant,
+    interactions: u32,
+    chat_spam: f64,
+    chat_updated: Instant,
+}
+
+impl PacketLimiter {
+    pub fn new(config: &ServerConfig) -> PacketLimiter {
+        let now = Instant::now();
+        PacketLimiter {
+            max_per_second: config.max_packets_per_second.max(1),
+            second_start: now,
+            packets: 0,
+            movement: 0,
+            tick_start: now,
+            interactions: 0,
+            chat_spam: 0.0,
+            chat_updated: now,
+        }
+    }
+
+    // Err mit dem Grund für die Trennung, wenn der Client ein Limit überschreitet
+    pub fn record(&mut self, category: PacketCategory) -> Result<(), String> {
+        let now = Instant::now();
+        if now - self.second_start >= Duration::from_secs(1) {
+            self.second_start = now;
+            self.packets = 0;
+            self.movement = 0;
+        }
+        self.packets += 1;
+        if self.packets > self.max_per_second {
+            return Err("Too many packets".to_string());
+        }
+        match category {
+            PacketCategory::Movement => {
+                self.movement += 1;
+                if self.movement > MAX_MOVEMENT_PER_SECOND {
+                    return Err("Too many movement packets".to_string());
+                }
+            }
+            PacketCategory::Chat => {
+                let ticks = (now - self.chat_updated).as_secs_f64() * 20.0;
+                self.chat_updated = now;
+                self.chat_spam = (self.chat_spam - ticks).max(0.0) + CHAT_SPAM_COST;
+                if self.chat_spam > CHAT_SPAM_THRESHOLD {
+                    return Err("Kicked for spamming".to_string());
+                }
+            }
+            PacketCategory::Interaction => {
+                if now - self.tick_start >= Duration::from_millis(50) {
+                    self.tick_start = now;
+                    self.interactions = 0;
+                }
+                self.interactions += 1;
+                if self.interactions > MAX_INTERACTIONS_PER_TICK {
+                    return Err("Too many interactions".to_string());
+                }
+            }
+            PacketCategory::Other => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::command::{self, CommandSender};
+use crate::{blocktick, bow, edit, effect, elytra, end, experience, fire, flush_block_changes, fluid, function, hunger, light, motion, movement, multiworld, portal, potion, pregen, projectile, recipebook, restart, scoreboard, shield, sleep, systemd, tnt, tracker,
+    vehicle, worldevent, Server};
+
+pub const TICKS_PER_SECOND: u64 = 20;
+pub const TICK_DURATION: Duration = Duration::from_millis(1000 / TICKS_PER_SECOND);
+
+pub const MSPT_SAMPLES: usize = 100;
+// Ein TPS-Messwert pro Sekunde, 15 Minuten lang
+const TPS_SAMPLES: usize = 900;
+
+pub type TaskId = u64;
+
+enum TaskKind {
+    Once(Box<dyn FnOnce(&Server) + Send>),
+    Repeating(u64, Box<dyn FnMut(&Server) + Send>),
+}
+
+struct ScheduledTask {
+    id: TaskId,
+    due_tick: u64,
+    kind: TaskKind,
+}
+
+// Verzögerte und wiederkehrende Aufgaben, ausgeführt im Haupt-Tick
+#[derive(Default)]
+pub struct Scheduler {
+    current_tick: u64,
+    next_id: TaskId,
+    tasks: Vec<ScheduledTask>,
+    // Im aktuellen Tick fällige Aufgaben, die schon entnommen, aber noch nicht abgebrochen wurden
+    in_flight: HashSet<TaskId>,
+}
+
+impl Scheduler {
+    pub fn new() -> Scheduler {
+        Scheduler::default()
+    }
+
+    fn schedule(&mut self, delay: u64, kind: TaskKind) -> TaskId {
+        self.next_id += 1;
+        self.tasks.push(ScheduledTask {
+            id: self.next_id,
+            due_tick: self.current_tick + delay.max(1),
+            kind,
+        });
+        self.next_id
+    }
+
+    pub fn run_later<F: FnOnce(&Server) + Send + 'static>(&mut self, delay_ticks: u64, task: F) -> TaskId {
+        self.schedule(delay_ticks, TaskKind::Once(Box::new(task)))
+    }
+
+    pub fn run_repeating<F: FnMut(&Server) + Send + 'static>(&mut self, delay_ticks: u64, period_ticks: u64, task: F) -> TaskId {
+        self.schedule(delay_ticks, TaskKind::Repeating(period_ticks.max(1), Box::new(task)))
+    }
+
+    pub fn cancel(&mut self, id: TaskId) -> bool {
+        let before = self.tasks.len();
+        self.tasks.retain(|t| t.id != id);
+        self.tasks.len() != before || self.in_flight.remove(&id)
+    }
+
+    // Zählt den Tick hoch und entnimmt alle fälligen Aufgaben
+    fn advance(&mut self) -> Vec<ScheduledTask> {
+        self.current_tick += 1;
+        let tick = self.current_tick;
+        let (due, pending): (Vec<_>, Vec<_>) = self.tasks.drain(..).partition(|t| t.due_tick <= tick);
+        self.tasks = pending;
+        self.in_flight = due.iter().map(|t| t.id).collect();
+        due
+    }
+
+    fn reschedule(&mut self, mut task: ScheduledTask, period: u64) {
+        // Während der eigenen Ausführung abgebrochen
+        if !self.in_flight.contains(&task.id) {
+            return;
+        }
+        task.due_tick = self.current_tick + period;
+        self.tasks.push(task);
+    }
+}
+
+// Gleitende Statistiken über Tick-Dauer (MSPT) und tatsächliche Ticks pro Sekunde
+pub struct TickStats {
+    durations: VecDeque<Duration>,
+    tps_samples: VecDeque<f64>,
+    second_start: Instant,
+    ticks_this_second: u32,
+}
+
+impl TickStats {
+    pub fn new() -> TickStats {
+        TickStats {
+            durations: VecDeque::with_capacity(MSPT_SAMPLES),
+            tps_samples: VecDeque::with_capacity(TPS_SAMPLES),
+            second_start: Instant::now(),
+            ticks_this_second: 0,
+        }
+    }
+
+    fn record_tick(&mut self, duration: Duration) {
+        if self.durations.len() == MSPT_SAMPLES {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(duration);
+
+        self.ticks_this_second += 1;
+        let elapsed = self.second_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            let tps = (self.ticks_this_second as f64 / elapsed.as_secs_f64()).min(TICKS_PER_SECOND as f64);
+            if self.tps_samples.len() == TPS_SAMPLES {
+                self.tps_samples.pop_front();
+            }
+            self.tps_samples.push_back(tps);
+            self.second_start = Instant::now();
+            self.ticks_this_second = 0;
+        }
+    }
+
+    // Durchschnittliche TPS der letzten `seconds` Sekunden
+    pub fn tps(&self, seconds: usize) -> f64 {
+        let samples: Vec<f64> = self.tps_samples.iter().rev().take(seconds).copied().collect();
+        if samples.is_empty() {
+            return TICKS_PER_SECOND as f64;
+        }
+        samples.iter().sum::<f64>() / samples.len() as f64
+    }
+
+    pub fn average_mspt(&self) -> f64 {
+        if self.durations.is_empty() {
+            return 0.0;
+        }
+        let total: Duration = self.durations.iter().sum();
+        total.as_secs_f64() * 1000.0 / self.durations.len() as f64
+    }
+
+    pub fn worst_mspt(&self) -> f64 {
+        self.durations.iter().max().map(|d| d.as_secs_f64() * 1000.0).unwrap_or(0.0)
+    }
+}
+
+fn run_pending_commands(server: &Server) {
+    let pending = std::mem::take(&mut *server.pending_commands.lock().unwrap());
+    for (sender, line) in pending {
+        command::execute(server, sender, &line);
+        if let CommandSender::Remote(id) = sender {
+            server.remote_replies.finish(id);
+        }
+    }
+}
+
+fn run_scheduled_tasks(server: &Server) {
+    let due = server.scheduler.lock().unwrap().advance();
+    for mut task in due {
+        if !server.scheduler.lock().unwrap().in_flight.contains(&task.id) {
+            continue;
+        }
+        match task.kind {
+            TaskKind::Once(f) => f(server),
+            TaskKind::Repeating(period, ref mut f) => {
+                f(server);
+                server.scheduler.lock().unwrap().reschedule(task, period);
+            }
+        }
+    }
+    server.scheduler.lock().unwrap().in_flight.clear();
+}
+
+pub fn run_tick_loop(server: Arc<Server>) {
+    info!("Tick-Schleife gestartet ({} TPS)", TICKS_PER_SECOND);
+    let mut next_tick = Instant::now();
+    let mut tick: u64 = 0;
+    loop {
+        tick += 1;
+        let tick_start = Instant::now();
+        server.watchdog.tick_started(tick);
+        let span = span!("tick", tick = tick);
+        let profiler = &server.profiler;
+        profiler.time("commands", || function::tick(&server));
+        profiler.time("commands", || run_pending_commands(&server));
+        profiler.time("commands", || restart::tick(&server));
+        profiler.time("scheduled tasks", || run_scheduled_tasks(&server));
+        profiler.time("worlds", || server.tick());
+        profiler.time("entities", || end::tick(&server));
+        profiler.time("players", || effect::tick(&server));
+        profiler.time("players", || hunger::tick(&server));
+        profiler.time("players", || elytra::tick(&server));
+        profiler.time("players", || bow::tick(&server));
+        profiler.time("players", || shield::tick(&server));
+        profiler.time("players", || recipebook::tick(&server));
+        profiler.time("block ticks", || fluid::tick(&server));
+        profiler.time("block ticks", || fire::tick(&server));
+        profiler.time("entities", || vehicle::tick(&server));
+        profiler.time("players", || sleep::tick(&server));
+        profiler.time("players", || movement::tick(&server));
+        profiler.time("players", || scoreboard::tick(&server));
+        profiler.time("block edits", || edit::tick(&server));
+        profiler.time("block ticks", || blocktick::tick(&server));
+        profiler.time("entities", || experience::tick(&server));
+        profiler.time("entities", || motion::tick(&server));
+        profiler.time("entities", || projectile::tick(&server));
+        profiler.time("entities", || potion::tick(&server));
+        profiler.time("chunk generation", || pregen::tick(&server));
+        profiler.time("chunk generation", || multiworld::tick(&server));
+        profiler.time("entities", || tnt::tick(&server));
+        profiler.time("players", || portal::tick(&server));
+        profiler.time("networking", || tracker::tick(&server));
+        profiler.time("networking", || flush_block_changes(&server));
+        profiler.time("lighting", || light::tick(&server));
+        profiler.time("networking", || worldevent::flush(&server));
+        let elapsed = tick_start.elapsed();
+        server.tick_stats.lock().unwrap().record_tick(elapsed);
+        profiler.record_tick(elapsed);
+        server.watchdog.tick_finished();
+        systemd::watchdog_tick();
+        drop(span);
+
+        next_tick += TICK_DURATION;
+        let now = Instant::now();
+        if next_tick > now {
+            thread::sleep(next_tick - now);
+        } else if now - next_tick > Duration::from_secs(2) {
+            // Server hängt hinterher: verpasste Ticks nicht nachholen
+            warn!("Server kommt nicht hinterher! {}ms hinter dem Zeitplan", (now - next_tick).as_millis());
+            next_tick = now;
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use rand::Rng;
+use crate::explosion::{self, Kind};
+use crate::fire::{self, FIRE_CHARGE, FLINT_AND_STEEL};
+use crate::{block, dimension, entity, worldevent, Mob, Player, Server, World};
+
+// Block und gezündete Entity heißen gleich
+pub const BLOCK: &str = "minecraft:tnt";
+pub const PRIMED: &str = "minecraft:tnt";
+// Vier Sekunden Zündschnur, danach eine Explosion der Stärke 4 knapp über dem Boden der Entity
+pub const FUSE_TICKS: u32 = 80;
+const POWER: f64 = 4.0;
+const CENTER_HEIGHT: f64 = 0.0625;
+// Beim Zünden hüpft TNT leicht in eine zufällige Richtung
+const HOP: f64 = 0.2;
+const SCATTER: f64 = 0.02;
+const PRIME_SOUND: &str = "minecraft:entity.tnt.primed";
+
+// Von einer anderen Explosion gezündetes TNT geht wie bei Vanilla nach einer halben bis anderthalb Sekunden hoch
+pub fn chain_fuse() -> u32 {
+    rand::thread_rng().gen_range(0..FUSE_TICKS / 4) + FUSE_TICKS / 8
+}
+
+// Macht aus dem TNT-Block an position gezündetes TNT; der Block muss schon entfernt sein
+pub fn prime(players: &[Player], world: &mut World, (x, y, z): (i32, i32, i32), fuse: u32) {
+    let position = (x as f64 + 0.5, y as f64, z as f64 + 0.5);
+    let angle = rand::thread_rng().gen::<f64>() * std::f64::consts::TAU;
+    let mut mob = Mob::new(PRIMED, position);
+    mob.velocity = (-angle.sin() * SCATTER, HOP, -angle.cos() * SCATTER);
+    mob.fuse = Some(fuse);
+    for viewer in players.iter().filter(|p| entity::in_view(p, world.dimension, position)) {
+        let _ = entity::send_mob(&viewer.connection, &mob);
+        let _ = worldevent::send_sound(&viewer.connection, PRIME_SOUND, position, 1.0);
+    }
+    world.entities.update(mob.id, position);
+    world.mobs.push(mob);
+}
+
+// Use Item On mit Feuerzeug oder Feuerkugel auf einen TNT-Block zündet ihn; false, wenn nichts gezündet wurde
+pub fn use_on(players: &mut [Player], world: &mut World, index: usize, slot: usize, position: (i32, i32, i32)) -> bool {
+    let held = players[index].inventory.slot(slot).item.clone();
+    if held != FLINT_AND_STEEL && held != FIRE_CHARGE || block::name(world.get_block(position.0, position.1, position.2)) != BLOCK {
+        return false;
+    }
+    if !world.set_block_and_notify(position, block::AIR) {
+        return false;
+    }
+    let player = &mut players[index];
+    debug!("{} zündet TNT bei {:?}", player.username, position);
+    fire::spend_igniter(player, slot);
+    prime(players, world, position, FUSE_TICKS);
+    true
+}
+
+// Brennt die Zündschnüre herunter und lässt abgebranntes TNT explodieren. Die Bewegung übernimmt motion, Fallen
+// ohne Geschwindigkeit die Welt. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let mut explosions = Vec::new();
+    let players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let dimension = world.dimension;
+        let _profile = server.profiler.world("entities", dimension);
+        let mut burnt = Vec::new();
+        for (index, mob) in world.mobs.iter_mut().enumerate() {
+            let Some(fuse) = &mut mob.fuse else { continue };
+            *fuse = fuse.saturating_sub(1);
+            if *fuse == 0 {
+                burnt.push(index);
+            }
+        }
+        for &index in burnt.iter().rev() {
+            let mob = world.mobs.remove(index);
+            world.entities.remove(mob.id);
+            for viewer in players.iter().filter(|p| entity::in_view(p, dimension, mob.position)) {
+                let _ = entity::send_remove(&viewer.connection, &[mob.entity_id]);
+            }
+            explosions.push((dimension, (mob.position.0, mob.position.1 + CENTER_HEIGHT, mob.position.2)));
+        }
+    }
+    drop(players);
+    for (dimension, center) in explosions {
+        explosion::explode(server, dimension, center, POWER, Kind::Tnt);
+    }
+}
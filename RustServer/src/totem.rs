@@ -0,0 +1,28 @@
+use crate::effect::{self, EffectInstance};
+use crate::{entity, Player};
+
+pub const TOTEM: &str = "minecraft:totem_of_undying";
+// Entity Event, zu dem der Client die Animation des Totems und den Ton spielt
+const EVENT_TOTEM: u8 = 35;
+// Wie bei Vanilla: Regeneration II für 45 Sekunden, Absorption II für 5 und Feuerresistenz für 40
+const EFFECTS: &[(usize, i32, u8)] = &[(effect::REGENERATION, 900, 1), (effect::ABSORPTION, 100, 1), (effect::FIRE_RESISTANCE, 800, 0)];
+
+// Ein tödlicher Treffer: hält der Spieler in einer Hand ein Totem, zerfällt es und er überlebt mit einem
+// Lebenspunkt. false, wenn er keins hält
+pub fn revive(players: &mut [Player], index: usize) -> bool {
+    let player = &mut players[index];
+    let hands = [player.inventory.hand_slot(false), player.inventory.hand_slot(true)];
+    let Some(slot) = hands.into_iter().find(|&slot| player.inventory.slot(slot).item == TOTEM) else { return false };
+    player.inventory.consume_one(slot);
+    let _ = player.inventory.send_slot(&player.connection, slot);
+    player.health = 1.0;
+    let effects: Vec<_> = EFFECTS.iter().map(|&(id, duration, amplifier)| (id, EffectInstance::new(amplifier, Some(duration), true))).collect();
+    effect::reset(player, &effects);
+    debug!("{} überlebt dank eines Totems der Unsterblichkeit", player.username);
+    let _ = entity::send_event(&player.connection, entity::OWN_PLAYER_ID, EVENT_TOTEM);
+    let player = &players[index];
+    for viewer in players.iter().filter(|p| p.uuid != player.uuid && entity::in_view(p, player.dimension, player.position)) {
+        let _ = entity::send_event(&viewer.connection, player.entity_id, EVENT_TOTEM);
+    }
+    true
+}
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use crate::dimension::{self, Dimension};
+use crate::item::ItemStack;
+use crate::{entity, vehicle};
+use crate::protocol::clientbound;
+use crate::protocol::types::{angle_to_byte, write_varint_to_vec};
+use crate::Server;
+
+// Spätestens nach so vielen Ticks geht die Position einer bewegten Entity wieder absolut heraus, wie bei Vanilla
+const TELEPORT_INTERVAL: u32 = 400;
+// Reine Drehungen gehen höchstens alle so viele Ticks heraus, wie bei Vanilla für die meisten Entities; mit einer
+// Bewegung reist die Drehung ohnehin kostenlos mit
+const ROTATION_INTERVAL: u32 = 3;
+// Mehr Pakete nimmt der Client nicht in ein Bündel auf
+const MAX_BUNDLE_PACKETS: usize = 4096;
+// Relative Bewegungen zählen in 1/4096 Block und müssen in ein i16 passen, also unter 8 Blöcken bleiben
+const DELTA_SCALE: f64 = 4096.0;
+
+// Was die Clients zuletzt von einer Entity erfahren haben
+struct Sent {
+    position: (f64, f64, f64),
+    rotation: (u8, u8),
+    head_yaw: u8,
+    since_teleport: u32,
+    since_rotation: u32,
+}
+
+// Bewegungen von Mobs und Spielern für die Clients in Sichtweite, einmal pro Tick
+#[derive(Default)]
+pub struct EntityTracker {
+    sent: HashMap<i32, Sent>,
+    // Andere Spieler samt Entity-ID, die ein Client gerade als Entity kennt
+    shown: HashMap<Uuid, HashMap<Uuid, i32>>,
+    // Hände und Rüstung der Spieler, wie sie die Clients zuletzt gesehen haben
+    equipment: HashMap<Uuid, Vec<ItemStack>>,
+}
+
+impl EntityTracker {
+    // Nach einem Wechsel der Dimension hat der Client alle Entities verworfen
+    pub fn forget_viewer(&mut self, viewer: Uuid) {
+        self.shown.remove(&viewer);
+    }
+}
+
+struct Moving {
+    id: i32,
+    // Spieler sehen nur die Clients, denen der Tracker sie gezeigt hat
+    player: Option<Uuid>,
+    dimension: Dimension,
+    position: (f64, f64, f64),
+    rotation: (f32, f32),
+    on_ground: bool,
+}
+
+fn encode(value: f64) -> i64 {
+    (value * DELTA_SCALE).round() as i64
+}
+
+fn rotation_packet(id: i32, rotation: (u8, u8), on_ground: bool) -> (i32, Vec<u8>) {
+    let mut packet_data = write_varint_to_vec(id);
+    packet_data.extend([rotation.0, rotation.1, on_ground as u8]);
+    (clientbound::UPDATE_ENTITY_ROTATION, packet_data)
+}
+
+fn head_packet(id: i32, head_yaw: u8) -> (i32, Vec<u8>) {
+    let mut packet_data = write_varint_to_vec(id);
+    packet_data.push(head_yaw);
+    (clientbound::SET_HEAD_ROTATION, packet_data)
+}
+
+impl Sent {
+    // Den Stand beim Spawnen kennt der Client schon aus dem Spawn-Paket
+    fn new(entity: &Moving) -> Sent {
+        let rotation = (angle_to_byte(entity.rotation.0), angle_to_byte(entity.rotation.1));
+        Sent { position: entity.position, rotation, head_yaw: rotation.0, since_teleport: 0, since_rotation: 0 }
+    }
+
+    // Pakete, die die Clients auf den neuen Stand bringen; leer, wenn sich nichts geändert hat. Kleinere Schritte
+    // als 1/4096 Block sammeln sich an, bis sie gesendet werden, gedrosselte Drehungen ebenso
+    fn update(&mut self, entity: &Moving) -> Vec<(i32, Vec<u8>)> {
+        let rotation = (angle_to_byte(entity.rotation.0), angle_to_byte(entity.rotation.1));
+        let (old, new) = (self.position, entity.position);
+        let delta = [encode(new.0) - encode(old.0), encode(new.1) - encode(old.1), encode(new.2) - encode(old.2)];
+        let moved = delta != [0, 0, 0];
+        self.since_teleport = self.since_teleport.saturating_add(1);
+        self.since_rotation = self.since_rotation.saturating_add(1);
+        if !moved && self.since_rotation < ROTATION_INTERVAL {
+            return Vec::new();
+        }
+        let rotated = rotation != self.rotation;
+        if rotated || moved {
+            self.since_rotation = 0;
+        }
+        let mut packets = Vec::new();
+        let mut packet_data = write_varint_to_vec(entity.id);
+        if moved && (self.since_teleport >= TELEPORT_INTERVAL || delta.iter().any(|d| i16::try_from(*d).is_err())) {
+            self.since_teleport = 0;
+            packet_data.extend(new.0.to_be_bytes());
+            packet_data.extend(new.1.to_be_bytes());
+            packet_data.extend(new.2.to_be_bytes());
+            packet_data.extend([rotation.0, rotation.1, entity.on_ground as u8]);
+            packets.push((clientbound::TELEPORT_ENTITY, packet_data));
+        } else if moved {
+            for d in delta {
+                packet_data.extend((d as i16).to_be_bytes());
+            }
+            if rotated {
+                packet_data.extend([rotation.0, rotation.1]);
+            }
+            packet_data.push(entity.on_ground as u8);
+            let id = if rotated { clientbound::UPDATE_ENTITY_POSITION_AND_ROTATION } else { clientbound::UPDATE_ENTITY_POSITION };
+            packets.push((id, packet_data));
+        } else if rotated {
+            packets.push(rotation_packet(entity.id, rotation, entity.on_ground));
+        }
+        if moved {
+            self.position = new;
+        }
+        self.rotation = rotation;
+        if rotation.0 != self.head_yaw {
+            self.head_yaw = rotation.0;
+            packets.push(head_packet(entity.id, rotation.0));
+        }
+        packets
+    }
+}
+
+// Schickt Bewegungen an alle, die die Entity sehen, geänderte Hände und Rüstung der Spieler an die, denen sie gezeigt
+// werden, und zeigt oder entfernt andere Spieler, wenn sie in Sichtweite kommen oder sie verlassen. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let players = server.players.lock().unwrap();
+    let mut moving: Vec<Moving> = players
+        .iter()
+        .filter(|p| p.game_mode.is_tracked())
+        .map(|p| Moving { id: p.entity_id, player: Some(p.uuid), dimension: p.dimension, position: p.position, rotation: p.rotation, on_ground: true })
+        .collect();
+    // Fahrzeuge samt Mitfahrern; wer einen Mitfahrer neu zu sehen bekommt, erfährt auch, worauf er sitzt
+    let mut vehicles: Vec<(i32, Vec<(Uuid, i32)>)> = Vec::new();
+    for world in dimension::worlds(server) {
+        let world = world.lock().unwrap();
+        vehicles.extend(world.mobs.iter().filter(|m| !m.passengers.is_empty()).map(|m| (m.entity_id, m.passengers.clone())));
+        moving.extend(world.mobs.iter().map(|m| Moving {
+            id: m.entity_id,
+            player: None,
+            dimension: world.dimension,
+            position: m.position,
+            rotation: (m.yaw, m.pitch),
+            on_ground: entity::has_gravity(&m.mob_type),
+        }));
+    }
+
+    let mut tracker = server.tracker.lock().unwrap();
+    let tracker = &mut *tracker;
+    // Alle Bewegungen eines Ticks gehen pro Client gebündelt heraus, damit er sie gemeinsam anwendet
+    let mut outgoing: Vec<Vec<(i32, Vec<u8>)>> = vec![Vec::new(); players.len()];
+    for entity in &moving {
+        let Some(sent) = tracker.sent.get_mut(&entity.id) else {
+            tracker.sent.insert(entity.id, Sent::new(entity));
+            continue;
+        };
+        let packets = sent.update(entity);
+        if packets.is_empty() {
+            continue;
+        }
+        let viewers = players.iter().enumerate().filter(|(_, viewer)| match entity.player {
+            Some(uuid) => tracker.shown.get(&viewer.uuid).is_some_and(|shown| shown.contains_key(&uuid)),
+            None => entity::in_view(viewer, entity.dimension, entity.position),
+        });
+        for (index, _) in viewers {
+            outgoing[index].extend(packets.iter().cloned());
+        }
+    }
+    for (viewer, packets) in players.iter().zip(outgoing) {
+        let send = |packets: &[(i32, Vec<u8>)]| packets.iter().try_for_each(|(id, packet_data)| viewer.connection.send(*id, packet_data));
+        let _ = match packets.len() {
+            0 => Ok(()),
+            1 => send(&packets),
+            _ => packets.chunks(MAX_BUNDLE_PACKETS).try_for_each(|chunk| entity::bundle(&viewer.connection, || send(chunk))),
+        };
+    }
+    let ids: HashSet<i32> = moving.iter().map(|e| e.id).collect();
+    tracker.sent.retain(|id, _| ids.contains(id));
+
+    tracker.shown.retain(|viewer, _| players.iter().any(|p| p.uuid == *viewer));
+    tracker.equipment.retain(|uuid, _| players.iter().any(|p| p.uuid == *uuid));
+    for player in players.iter() {
+        let equipment = player.inventory.equipment();
+        let sent = tracker.equipment.entry(player.uuid).or_insert_with(|| equipment.iter().map(|&stack| stack.clone()).collect());
+        let changed: Vec<(u8, &ItemStack)> = (0..equipment.len()).filter(|&slot| sent[slot] != *equipment[slot]).map(|slot| (slot as u8, equipment[slot])).collect();
+        if changed.is_empty() {
+            continue;
+        }
+        for viewer in players.iter().filter(|viewer| tracker.shown.get(&viewer.uuid).is_some_and(|shown| shown.contains_key(&player.uuid))) {
+            let _ = entity::send_equipment(&viewer.connection, player.entity_id, &changed);
+        }
+        *sent = equipment.iter().map(|&stack| stack.clone()).collect();
+    }
+    for viewer in players.iter() {
+        let shown = tracker.shown.entry(viewer.uuid).or_default();
+        let visible: Vec<_> = players.iter().filter(|p| p.uuid != viewer.uuid && p.game_mode.is_tracked() && entity::in_view(viewer, p.dimension, p.position)).collect();
+        let gone: Vec<(Uuid, i32)> = shown.iter().filter(|(uuid, _)| !visible.iter().any(|p| p.uuid == **uuid)).map(|(uuid, id)| (*uuid, *id)).collect();
+        if !gone.is_empty() {
+            let ids: Vec<i32> = gone.iter().map(|(_, id)| *id).collect();
+            let _ = entity::send_remove(&viewer.connection, &ids);
+            for (uuid, _) in gone {
+                shown.remove(&uuid);
+            }
+        }
+        for player in visible {
+            if shown.contains_key(&player.uuid) {
+                continue;
+            }
+            // Das Spawn-Paket kennt keinen Pitch, der Kopf folgt dem Yaw
+            let rotation = (angle_to_byte(player.rotation.0), angle_to_byte(player.rotation.1));
+            let equipment: Vec<(u8, &ItemStack)> = player.inventory.equipment().into_iter().enumerate().filter(|(_, stack)| !stack.is_empty()).map(|(slot, stack)| (slot as u8, stack)).collect();
+            let result = entity::bundle(&viewer.connection, || {
+                entity::send_player(&viewer.connection, player)?;
+                let (id, packet_data) = rotation_packet(player.entity_id, rotation, true);
+                viewer.connection.send(id, &packet_data)?;
+                let (id, packet_data) = head_packet(player.entity_id, rotation.0);
+                viewer.connection.send(id, &packet_data)?;
+                entity::send_equipment(&viewer.connection, player.entity_id, &equipment)?;
+                for (vehicle, passengers) in vehicles.iter().filter(|(_, passengers)| passengers.iter().any(|(uuid, _)| *uuid == player.uuid)) {
+                    let ids: Vec<i32> = passengers.iter().map(|&(uuid, id)| if uuid == viewer.uuid { entity::OWN_PLAYER_ID } else { id }).collect();
+                    vehicle::send_passengers(&viewer.connection, *vehicle, &ids)?;
+                }
+                Ok(())
+            });
+            if let Err(e) = result {
+                warn!("Fehler beim Zeigen von {} für {}: {}", player.username, viewer.username, e);
+            }
+            shown.insert(player.uuid, player.entity_id);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::pressureplate::{self, Trigger};
+use crate::{block, farming, Player, World};
+
+type BlockPos = (i32, i32, i32);
+
+// Weiter sucht ein Haken nicht nach seinem Gegenstück
+const MAX_LENGTH: i32 = 42;
+const PRESSED_TICKS: i64 = 10;
+// Nur damit ausgelöste Drähte nach einem Neustart wieder loslassen
+pub const RANDOM_TICKING: &[&str] = &["minecraft:tripwire"];
+
+const HOOK: &str = "minecraft:tripwire_hook";
+const WIRE: &str = "minecraft:tripwire";
+
+pub fn is_wire(state: u16) -> bool {
+    block::name(state) == WIRE
+}
+
+fn is_hook(state: u16) -> bool {
+    block::name(state) == HOOK
+}
+
+fn flag(state: u16, key: &str) -> bool {
+    block::property(state, key) == Some("true")
+}
+
+fn with_flag(state: u16, key: &str, value: bool) -> u16 {
+    block::with_property(state, key, &value.to_string()).unwrap_or(state)
+}
+
+fn facing_offset(facing: &str) -> (i32, i32) {
+    match facing {
+        "north" => (0, -1),
+        "south" => (0, 1),
+        "west" => (-1, 0),
+        _ => (1, 0),
+    }
+}
+
+fn opposite(facing: &str) -> &'static str {
+    match facing {
+        "north" => "south",
+        "south" => "north",
+        "west" => "east",
+        _ => "west",
+    }
+}
+
+// Ein Entity berührt den Draht; ausgelöst wird nur, was noch nicht ausgelöst ist
+pub fn entity_inside(players: &[Player], world: &mut World, position: BlockPos, state: u16, triggers: &[Trigger]) {
+    if !flag(state, "powered") {
+        check(players, world, position, state, triggers);
+    }
+}
+
+// Ein gespannter Draht liegt flach am Boden, ein loser ist höher
+fn check(players: &[Player], world: &mut World, (x, y, z): BlockPos, state: u16, triggers: &[Trigger]) {
+    let (min_y, max_y) = if flag(state, "attached") { (1.0 / 16.0, 2.5 / 16.0) } else { (0.0, 0.5) };
+    let (bx, by, bz) = (x as f64, y as f64, z as f64);
+    let occupied = triggers.iter().any(|t| t.intersects((bx, by + min_y, bz), (bx + 1.0, by + max_y, bz + 1.0)));
+    if occupied != flag(state, "powered") {
+        world.set_block_and_notify((x, y, z), with_flag(state, "powered", occupied));
+        update_hooks(players, world, (x, y, z));
+    }
+    if occupied {
+        world.schedule_block_tick((x, y, z), PRESSED_TICKS);
+    }
+}
+
+// Die Haken, zwischen denen ein Draht gespannt sein kann, in beiden Richtungen; jeder rechnet die ganze Linie neu aus
+fn hooks(world: &World, (x, y, z): BlockPos) -> Vec<BlockPos> {
+    let mut hooks = Vec::new();
+    for direction in ["north", "south", "west", "east"] {
+        let (dx, dz) = facing_offset(direction);
+        for i in 1..MAX_LENGTH {
+            let position = (x + dx * i, y, z + dz * i);
+            let state = world.get_block(position.0, position.1, position.2);
+            if is_hook(state) {
+                if block::property(state, "facing") == Some(opposite(direction)) {
+                    hooks.push(position);
+                }
+                break;
+            }
+            if !is_wire(state) {
+                break;
+            }
+        }
+    }
+    hooks
+}
+
+fn update_hooks(players: &[Player], world: &mut World, position: BlockPos) {
+    for hook in hooks(world, position) {
+        let state = world.get_block(hook.0, hook.1, hook.2);
+        calculate(players, world, hook, state);
+    }
+}
+
+// Wie TripWireHookBlock.calculateState bei Vanilla: gespannt ist ein Haken, wenn ihm in höchstens 41 Blöcken
+// Abstand ein anderer gegenübersteht und dazwischen nur Draht liegt, ausgelöst, wenn daran ein scharfer Draht ausgelöst
+// ist. Beide Haken und alle Drähte dazwischen bekommen den neuen Zustand
+fn calculate(players: &[Player], world: &mut World, (x, y, z): BlockPos, state: u16) {
+    let Some(facing) = block::property(state, "facing") else { return };
+    let (dx, dz) = facing_offset(facing);
+    let mut complete = true;
+    let mut activated = false;
+    let mut receiver = 0;
+    let mut wires = Vec::new();
+    for i in 1..MAX_LENGTH {
+        let position = (x + dx * i, y, z + dz * i);
+        let other = world.get_block(position.0, position.1, position.2);
+        if is_hook(other) {
+            if block::property(other, "facing") == Some(opposite(facing)) {
+                receiver = i;
+            }
+            break;
+        }
+        if is_wire(other) {
+            activated |= !flag(other, "disarmed") && flag(other, "powered");
+            wires.push((position, other));
+        } else {
+            complete = false;
+        }
+    }
+    let attached = complete && receiver > 1;
+    let activated = activated && attached;
+    let new = with_flag(with_flag(state, "attached", attached), "powered", activated);
+    if receiver > 0 {
+        let position = (x + dx * receiver, y, z + dz * receiver);
+        set_hook(players, world, position, block::with_property(new, "facing", opposite(facing)).unwrap_or(new));
+    }
+    set_hook(players, world, (x, y, z), new);
+    if flag(state, "attached") != attached {
+        for (position, wire) in wires {
+            world.set_block_and_notify(position, with_flag(wire, "attached", attached));
+        }
+    }
+}
+
+fn set_hook(players: &[Player], world: &mut World, position: BlockPos, state: u16) {
+    let old = world.get_block(position.0, position.1, position.2);
+    if !world.set_block_and_notify(position, state) {
+        return;
+    }
+    let sounds = [
+        (!flag(old, "attached") && flag(state, "attached"), "minecraft:block.tripwire.attach"),
+        (flag(old, "attached") && !flag(state, "attached"), "minecraft:block.tripwire.detach"),
+        (!flag(old, "powered") && flag(state, "powered"), "minecraft:block.tripwire.click_on"),
+        (flag(old, "powered") && !flag(state, "powered"), "minecraft:block.tripwire.click_off"),
+    ];
+    for (_, sound) in sounds.iter().filter(|(play, _)| *play) {
+        farming::play_sound(players, world, sound, position);
+    }
+}
+
+pub fn scheduled_tick(players: &[Player], world: &mut World, position: BlockPos, state: u16) {
+    if is_hook(state) {
+        calculate(players, world, position, state);
+    } else if is_wire(state) && flag(state, "powered") {
+        let triggers = pressureplate::triggers(players, world);
+        check(players, world, position, state, &triggers);
+    }
+}
+
+pub fn random_tick(world: &mut World, position: BlockPos) {
+    let state = world.get_block(position.0, position.1, position.2);
+    if flag(state, "powered") && !world.block_ticks.is_scheduled(position) {
+        world.schedule_block_tick(position, 1);
+    }
+}
+
+// Kommt ein Haken oder Draht dazu oder fällt weg, rechnen die Haken ihre Linie im nächsten Tick neu aus
+pub fn block_changed(world: &mut World, position: BlockPos, old: u16, new: u16) {
+    if block::name(old) == block::name(new) {
+        return;
+    }
+    let mut hooks = Vec::new();
+    if is_wire(old) || is_wire(new) {
+        hooks = self::hooks(world, position);
+    }
+    if is_hook(new) {
+        hooks.push(position);
+    }
+    if let Some(facing) = block::property(old, "facing").filter(|_| is_hook(old)) {
+        let (dx, dz) = facing_offset(facing);
+        let found = (1..MAX_LENGTH)
+            .map(|i| (position.0 + dx * i, position.1, position.2 + dz * i))
+            .find(|&(x, y, z)| is_hook(world.get_block(x, y, z)));
+        hooks.extend(found);
+    }
+    for hook in hooks {
+        world.schedule_block_tick(hook, 1);
+    }
+}
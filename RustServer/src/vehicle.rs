@@ -0,0 +1,227 @@
+use byteorder::{BigEndian, ReadBytesExt};
+use uuid::Uuid;
+use crate::dimension::{self, Dimension};
+use crate::error::ServerError;
+use crate::protocol::clientbound;
+use crate::protocol::types::{read_varint_from_cursor, write_varint_to_vec};
+use crate::{block, entity, send_teleport, Connection, Mob, Player, Server, World};
+
+// Plätze je Fahrzeug; Schweine und Schreiter bräuchten einen Sattel, den es noch nicht gibt
+const SEATS: &[(&str, usize)] = &[
+    ("boat", 2), ("camel", 2), ("chest_boat", 1), ("donkey", 1), ("horse", 1), ("minecart", 1), ("mule", 1),
+    ("skeleton_horse", 1), ("zombie_horse", 1),
+];
+const BOATS: &[&str] = &["boat", "chest_boat"];
+// Player Input: die Schleichtaste lässt absteigen
+const INPUT_UNMOUNT: u8 = 0x02;
+// Vom Auge bis zur Hitbox, mit demselben Spielraum wie bei Vanilla
+const MAX_REACH: f64 = 6.0;
+const EYE_HEIGHT: f64 = 1.62;
+// Weiter lässt auch Vanilla ein Fahrzeug in einem Paket nicht fahren
+const MAX_MOVE: f64 = 10.0;
+// Wer sich weiter vom Fahrzeug entfernt hat, etwa durch /tp, sitzt nicht mehr darauf
+const MAX_SEAT_DISTANCE: f64 = 8.0;
+
+fn path(kind: &str) -> &str {
+    kind.strip_prefix("minecraft:").unwrap_or(kind)
+}
+
+fn seats(kind: &str) -> usize {
+    let kind = path(kind);
+    SEATS.iter().find(|(name, _)| *name == kind).map_or(0, |&(_, seats)| seats)
+}
+
+fn distance(a: (f64, f64, f64), b: (f64, f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+// Set Passengers; eine leere Liste lässt alle absteigen
+pub fn send_passengers(connection: &Connection, vehicle: i32, passengers: &[i32]) -> Result<(), String> {
+    let mut packet_data = write_varint_to_vec(vehicle);
+    packet_data.extend(write_varint_to_vec(passengers.len() as i32));
+    for id in passengers {
+        packet_data.extend(write_varint_to_vec(*id));
+    }
+    connection.send(clientbound::SET_PASSENGERS, &packet_data)
+}
+
+// Move Vehicle: setzt das Fahrzeug beim Fahrer auf die Position des Servers zurück
+fn send_move(connection: &Connection, position: (f64, f64, f64), yaw: f32) -> Result<(), String> {
+    let mut packet_data = vec![];
+    packet_data.extend(position.0.to_be_bytes());
+    packet_data.extend(position.1.to_be_bytes());
+    packet_data.extend(position.2.to_be_bytes());
+    packet_data.extend(yaw.to_be_bytes());
+    packet_data.extend(0f32.to_be_bytes());
+    connection.send(clientbound::MOVE_VEHICLE, &packet_data)
+}
+
+// Zeigt allen in Sichtweite und den Mitfahrern, wer auf dem Fahrzeug sitzt; sich selbst kennt jeder unter OWN_PLAYER_ID
+fn broadcast(players: &[Player], dimension: Dimension, mob: &Mob) {
+    let riding = |viewer: &Player| mob.passengers.iter().any(|(uuid, _)| *uuid == viewer.uuid);
+    for viewer in players.iter().filter(|p| riding(p) || entity::in_view(p, dimension, mob.position)) {
+        let ids: Vec<i32> = mob.passengers.iter().map(|&(uuid, id)| if uuid == viewer.uuid { entity::OWN_PLAYER_ID } else { id }).collect();
+        let _ = send_passengers(&viewer.connection, mob.entity_id, &ids);
+    }
+}
+
+// Index des Fahrzeugs, auf dem der Spieler sitzt
+fn riding(world: &World, uuid: Uuid) -> Option<usize> {
+    world.mobs.iter().position(|m| m.passengers.iter().any(|(passenger, _)| *passenger == uuid))
+}
+
+fn in_reach(player: &Player, mob: &Mob) -> bool {
+    let eye = (player.position.0, player.position.1 + EYE_HEIGHT, player.position.2);
+    let (width, height) = entity::hitbox(&mob.mob_type);
+    let (x, y, z) = mob.position;
+    let closest = (eye.0.clamp(x - width / 2.0, x + width / 2.0), eye.1.clamp(y, y + height), eye.2.clamp(z - width / 2.0, z + width / 2.0));
+    distance(eye, closest) <= MAX_REACH
+}
+
+// Interact mit der Hand auf ein Boot, eine Lore oder ein Reittier: der Spieler steigt auf, solange ein Platz frei ist.
+// Wer dabei schleicht, steigt wie bei Vanilla nicht auf
+pub fn mount(server: &Server, player: &Player, entity_id: i32, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let _hand = read_varint_from_cursor(cursor)?;
+    let sneaking = cursor.read_u8()?;
+    if sneaking != 0 {
+        return Ok(());
+    }
+    let mut players = server.players.lock().unwrap();
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid && p.health > 0.0 && p.game_mode.is_tracked()) else { return Ok(()) };
+    let dimension = players[index].dimension;
+    let mut world = dimension::world_of(server, dimension).lock().unwrap();
+    if riding(&world, player.uuid).is_some() {
+        return Ok(());
+    }
+    let Some(vehicle) = world.mobs.iter().position(|m| m.entity_id == entity_id) else { return Ok(()) };
+    let mob = &world.mobs[vehicle];
+    if mob.passengers.len() >= seats(&mob.mob_type) || !in_reach(&players[index], mob) {
+        return Ok(());
+    }
+    let position = mob.position;
+    let rider = &mut players[index];
+    debug!("{} steigt auf {}", rider.username, mob.display_name());
+    world.mobs[vehicle].passengers.push((rider.uuid, rider.entity_id));
+    rider.position = position;
+    world.entities.update(rider.uuid, position);
+    broadcast(&players, dimension, &world.mobs[vehicle]);
+    Ok(())
+}
+
+// Setzt den Spieler wie Vanilla oben auf die Hitbox des Fahrzeugs und stimmt seinen Client darauf ab
+fn dismount(players: &mut [Player], world: &mut World, uuid: Uuid) {
+    let Some(vehicle) = riding(world, uuid) else { return };
+    let mob = &mut world.mobs[vehicle];
+    mob.passengers.retain(|(passenger, _)| *passenger != uuid);
+    let (_, height) = entity::hitbox(&mob.mob_type);
+    let seat = (mob.position.0, mob.position.1 + height, mob.position.2);
+    broadcast(players, world.dimension, &world.mobs[vehicle]);
+    let Some(player) = players.iter_mut().find(|p| p.uuid == uuid) else { return };
+    debug!("{} steigt ab", player.username);
+    player.position = seat;
+    world.entities.update(uuid, seat);
+    if let Err(e) = send_teleport(player) {
+        warn!("Fehler beim Absteigen von {}: {}", player.username, e);
+    }
+}
+
+// Player Input: schickt der Client nur, solange er mitfährt
+pub fn handle_input(players: &mut [Player], world: &mut World, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let _sideways = cursor.read_f32::<BigEndian>()?;
+    let _forward = cursor.read_f32::<BigEndian>()?;
+    let flags = cursor.read_u8()?;
+    if flags & INPUT_UNMOUNT != 0 {
+        dismount(players, world, player.uuid);
+    }
+    Ok(())
+}
+
+// Prüft eine Fahrt vom Fahrer; die Kollision mit Blöcken berechnet der Server nicht nach, er verbietet nur das Ziel
+fn check(world: &World, from: (f64, f64, f64), to: (f64, f64, f64)) -> Result<(), &'static str> {
+    if ![to.0, to.1, to.2].iter().all(|v| v.is_finite()) {
+        return Err("sent an invalid position");
+    }
+    if distance(from, to) > MAX_MOVE {
+        return Err("moved too quickly");
+    }
+    let (x, y, z) = (to.0.floor() as i32, to.1.floor() as i32, to.2.floor() as i32);
+    if !world.chunks.contains_key(&(x >> 4, z >> 4)) {
+        return Err("moved into an unloaded chunk");
+    }
+    if block::is_solid(world.get_block(x, y, z)) {
+        return Err("moved into a block");
+    }
+    Ok(())
+}
+
+// Move Vehicle: nur der lenkende Mitfahrer bewegt das Fahrzeug, die Mitfahrer kommen mit. Die neue Position sehen die
+// anderen im nächsten Tick über den Tracker; eine verworfene Fahrt setzt den Client zurück
+pub fn handle_move(players: &mut [Player], world: &mut World, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let x = cursor.read_f64::<BigEndian>()?;
+    let y = cursor.read_f64::<BigEndian>()?;
+    let z = cursor.read_f64::<BigEndian>()?;
+    let yaw = cursor.read_f32::<BigEndian>()?;
+    if !(x.is_finite() && y.is_finite() && z.is_finite() && yaw.is_finite()) {
+        return Err(ServerError::Kick("Invalid move vehicle packet received".to_string()));
+    }
+    let Some(vehicle) = riding(world, player.uuid) else { return Ok(()) };
+    let mob = &world.mobs[vehicle];
+    if mob.passengers[0].0 != player.uuid {
+        return Ok(());
+    }
+    let (from, to) = (mob.position, (x, y, z));
+    if let Err(reason) = check(world, from, to) {
+        debug!("Fahrt von {} verworfen: {}", player.username, reason);
+        let _ = send_move(&player.connection, from, mob.yaw);
+        return Ok(());
+    }
+    let mob = &mut world.mobs[vehicle];
+    mob.position = to;
+    mob.yaw = yaw;
+    let (id, passengers) = (mob.id, mob.passengers.clone());
+    world.entities.update(id, to);
+    for (uuid, _) in passengers {
+        if let Some(p) = players.iter_mut().find(|p| p.uuid == uuid) {
+            p.position = to;
+            world.entities.update(uuid, to);
+        }
+    }
+    Ok(())
+}
+
+// Paddle Boat: die anderen in Sichtweite sehen die Ruder des Fahrers, sein Client bewegt sie selbst
+pub fn handle_paddle(players: &mut [Player], world: &mut World, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let left = cursor.read_u8()?;
+    let right = cursor.read_u8()?;
+    let Some(vehicle) = riding(world, player.uuid) else { return Ok(()) };
+    let mob = &world.mobs[vehicle];
+    if mob.passengers[0].0 != player.uuid || !BOATS.contains(&path(&mob.mob_type)) {
+        return Ok(());
+    }
+    for viewer in players.iter().filter(|p| p.uuid != player.uuid && entity::in_view(p, world.dimension, mob.position)) {
+        let _ = entity::send_paddles(&viewer.connection, mob.entity_id, left != 0, right != 0);
+    }
+    Ok(())
+}
+
+// Wer nicht mehr mitfahren kann, steigt ab: nach dem Tod, einem Wechsel der Dimension, als Zuschauer, nach dem Trennen
+// oder wenn er sich vom Fahrzeug entfernt hat. Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn tick(server: &Server) {
+    let players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        let dimension = world.dimension;
+        for vehicle in 0..world.mobs.len() {
+            let mob = &mut world.mobs[vehicle];
+            if mob.passengers.is_empty() {
+                continue;
+            }
+            let (before, position) = (mob.passengers.len(), mob.position);
+            let seated = |p: &Player| p.dimension == dimension && p.health > 0.0 && p.game_mode.is_tracked() && distance(p.position, position) <= MAX_SEAT_DISTANCE;
+            mob.passengers.retain(|(uuid, _)| players.iter().any(|p| p.uuid == *uuid && seated(p)));
+            if mob.passengers.len() != before {
+                broadcast(&players, dimension, &world.mobs[vehicle]);
+            }
+        }
+    }
+}
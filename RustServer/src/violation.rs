@@ -0,0 +1,45 @@
+use crate::{permission, send_system_message, Player, Server};
+
+// Ab so vielen Verstößen einer Art werden Spieler mit server.alerts benachrichtigt
+const ALERT_THRESHOLD: f64 = 10.0;
+
+// Verstöße eines Spielers in einer Prüfung (Bewegung, Kampf); unauffälliges Verhalten baut sie wieder ab
+#[derive(Debug, Clone, Default)]
+pub struct Violations {
+    level: f64,
+    alerted: bool,
+}
+
+impl Violations {
+    pub fn level(&self) -> f64 {
+        self.level
+    }
+
+    pub fn decay(&mut self, amount: f64) {
+        self.level = (self.level - amount).max(0.0);
+        // Erst wer deutlich unter die Schwelle fällt, wird beim nächsten Mal erneut gemeldet
+        if self.alerted && self.level < ALERT_THRESHOLD / 2.0 {
+            self.alerted = false;
+        }
+    }
+
+    // true, wenn der Spieler damit die Schwelle erreicht und gemeldet werden soll
+    pub fn add(&mut self) -> bool {
+        self.level += 1.0;
+        if self.alerted || self.level < ALERT_THRESHOLD {
+            return false;
+        }
+        self.alerted = true;
+        true
+    }
+}
+
+// Meldung an alle Spieler mit server.alerts und ins Log
+pub fn alert(server: &Server, players: &[Player], suspect: &str, reason: &str, level: f64) {
+    warn!("Verdächtiges Verhalten von {}: {}", suspect, reason);
+    let message = format!("{} may be cheating: {} ({} violations)", suspect, reason, level.round());
+    let permissions = server.permissions.lock().unwrap();
+    for staff in players.iter().filter(|p| permissions.has(&p.username, Some(p.uuid), permission::ALERTS)) {
+        let _ = send_system_message(&staff.connection, &message);
+    }
+}
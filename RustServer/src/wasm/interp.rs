@@ -0,0 +1,822 @@
+use std::sync::Arc;
+use super::module::{ConstExpr, FuncType, Module, Reader, PAGE_SIZE};
+
+// Obergrenzen, damit ein Plugin weder den Stack noch den Speicher des Servers sprengen kann
+const MAX_CALL_DEPTH: usize = 512;
+const MAX_VALUE_STACK: usize = 1 << 20;
+
+pub type HostFunc<T> = fn(&mut Instance<T>, &[u64]) -> Result<Option<u64>, String>;
+
+struct Frame {
+    function: usize,
+    pc: usize,
+    locals_base: usize,
+    label_base: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Label {
+    target: usize,
+    height: usize,
+    arity: usize,
+    is_loop: bool,
+}
+
+// Instanz eines Moduls mit eigenem linearen Speicher. Werte liegen als rohe Bits in u64
+pub struct Instance<T> {
+    module: Arc<Module>,
+    host: Vec<HostFunc<T>>,
+    pub memory: Vec<u8>,
+    max_pages: usize,
+    globals: Vec<u64>,
+    table: Vec<Option<u32>>,
+    pub fuel: u64,
+    pub data: T,
+}
+
+fn eval_const(expr: ConstExpr, globals: &[u64]) -> u64 {
+    match expr {
+        ConstExpr::Value(value) => value,
+        ConstExpr::Global(index) => globals.get(index as usize).copied().unwrap_or(0),
+        ConstExpr::Func(index) => index as u64,
+    }
+}
+
+fn trunc_f64(value: f64, min: f64, max: f64) -> Result<f64, String> {
+    if value.is_nan() {
+        return Err("Ungültige Konvertierung (NaN)".to_string());
+    }
+    let value = value.trunc();
+    if value < min || value > max {
+        return Err("Ganzzahlüberlauf bei Konvertierung".to_string());
+    }
+    Ok(value)
+}
+
+fn f32_min(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        f32::NAN
+    } else if a == b {
+        f32::from_bits(a.to_bits() | b.to_bits())
+    } else {
+        a.min(b)
+    }
+}
+
+fn f32_max(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        f32::NAN
+    } else if a == b {
+        f32::from_bits(a.to_bits() & b.to_bits())
+    } else {
+        a.max(b)
+    }
+}
+
+fn f64_min(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == b {
+        f64::from_bits(a.to_bits() | b.to_bits())
+    } else {
+        a.min(b)
+    }
+}
+
+fn f64_max(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == b {
+        f64::from_bits(a.to_bits() & b.to_bits())
+    } else {
+        a.max(b)
+    }
+}
+
+impl<T> Instance<T> {
+    // `resolve` liefert zu jedem Import die Host-Funktion oder einen Fehler
+    pub fn new(module: Module, data: T, max_pages: usize, mut resolve: impl FnMut(&str, &str, &FuncType) -> Result<HostFunc<T>, String>) -> Result<Instance<T>, String> {
+        let mut host = Vec::new();
+        for import in &module.imports {
+            let ty = module.types.get(import.type_index as usize).ok_or("Import mit unbekanntem Typ")?;
+            host.push(resolve(&import.module, &import.name, ty)?);
+        }
+        let (min_pages, declared_max) = module.memory.unwrap_or((0, Some(0)));
+        let max_pages = declared_max.map_or(max_pages, |m| (m as usize).min(max_pages));
+        if min_pages as usize > max_pages {
+            return Err(format!("Modul verlangt {} Speicherseiten, erlaubt sind {}", min_pages, max_pages));
+        }
+        let mut globals = Vec::new();
+        for global in &module.globals {
+            let value = eval_const(*global, &globals);
+            globals.push(value);
+        }
+        let table_size = module.table.map_or(0, |(min, _)| min as usize);
+        let mut instance = Instance {
+            host,
+            memory: vec![0; min_pages as usize * PAGE_SIZE],
+            max_pages,
+            globals,
+            table: vec![None; table_size.min(1 << 16)],
+            fuel: u64::MAX,
+            data,
+            module: Arc::new(module),
+        };
+        let module = Arc::clone(&instance.module);
+        for segment in &module.elements {
+            let Some(offset) = segment.offset else { continue };
+            let offset = eval_const(offset, &instance.globals) as u32 as usize;
+            let end = offset + segment.functions.len();
+            if end > instance.table.len() {
+                return Err("Element-Segment außerhalb der Tabelle".to_string());
+            }
+            for (slot, &function) in instance.table[offset..end].iter_mut().zip(&segment.functions) {
+                *slot = (function != u32::MAX).then_some(function);
+            }
+        }
+        for segment in &module.data {
+            let Some(offset) = segment.offset else { continue };
+            let offset = eval_const(offset, &instance.globals) as u32 as usize;
+            let end = offset + segment.bytes.len();
+            if end > instance.memory.len() {
+                return Err("Daten-Segment außerhalb des Speichers".to_string());
+            }
+            instance.memory[offset..end].copy_from_slice(&segment.bytes);
+        }
+        if let Some(start) = module.start {
+            instance.invoke(start, &[])?;
+        }
+        Ok(instance)
+    }
+
+    pub fn has_export(&self, name: &str) -> bool {
+        self.module.exported_function(name).is_some()
+    }
+
+    pub fn call(&mut self, name: &str, args: &[u64]) -> Result<Vec<u64>, String> {
+        let index = self.module.exported_function(name).ok_or_else(|| format!("Export {} fehlt", name))?;
+        self.invoke(index, args)
+    }
+
+    pub fn read_bytes(&self, ptr: u32, len: u32) -> Result<&[u8], String> {
+        let start = ptr as usize;
+        let end = start.checked_add(len as usize).filter(|&end| end <= self.memory.len()).ok_or("Speicherzugriff außerhalb der Grenzen")?;
+        Ok(&self.memory[start..end])
+    }
+
+    pub fn read_string(&self, ptr: u32, len: u32) -> Result<String, String> {
+        Ok(String::from_utf8_lossy(self.read_bytes(ptr, len)?).into_owned())
+    }
+
+    pub fn write_bytes(&mut self, ptr: u32, bytes: &[u8]) -> Result<(), String> {
+        let start = ptr as usize;
+        let end = start.checked_add(bytes.len()).filter(|&end| end <= self.memory.len()).ok_or("Speicherzugriff außerhalb der Grenzen")?;
+        self.memory[start..end].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn effective_address(&self, base: u64, offset: u32, size: usize) -> Result<usize, String> {
+        let address = (base as u32) as usize + offset as usize;
+        if address + size > self.memory.len() {
+            return Err(format!("Speicherzugriff außerhalb der Grenzen (Adresse {})", address));
+        }
+        Ok(address)
+    }
+
+    fn load(&self, base: u64, offset: u32, size: usize) -> Result<u64, String> {
+        let address = self.effective_address(base, offset, size)?;
+        let mut bytes = [0u8; 8];
+        bytes[..size].copy_from_slice(&self.memory[address..address + size]);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn store(&mut self, base: u64, offset: u32, size: usize, value: u64) -> Result<(), String> {
+        let address = self.effective_address(base, offset, size)?;
+        self.memory[address..address + size].copy_from_slice(&value.to_le_bytes()[..size]);
+        Ok(())
+    }
+
+    fn block_arity(module: &Module, reader: &mut Reader) -> Result<(usize, usize), String> {
+        let block_type = reader.signed(33)?;
+        match block_type {
+            -64 => Ok((0, 0)),
+            -4..=-1 | -16 | -17 => Ok((0, 1)),
+            index if index >= 0 => {
+                let ty = module.types.get(index as usize).ok_or("Blocktyp mit unbekanntem Typ")?;
+                Ok((ty.params.len(), ty.results.len()))
+            }
+            other => Err(format!("Ungültiger Blocktyp {}", other)),
+        }
+    }
+
+    // Führt eine Funktion (Import oder eigene) ohne Rekursion des Host-Stacks aus
+    pub fn invoke(&mut self, function: u32, args: &[u64]) -> Result<Vec<u64>, String> {
+        let module = Arc::clone(&self.module);
+        let imports = module.imports.len();
+        let ty = module.function_type(function).ok_or("Unbekannte Funktion")?;
+        if args.len() != ty.params.len() {
+            return Err("Falsche Anzahl an Argumenten".to_string());
+        }
+        if (function as usize) < imports {
+            let host = self.host[function as usize];
+            let result = host(self, args)?;
+            return Ok(result.into_iter().collect());
+        }
+
+        let mut stack: Vec<u64> = Vec::new();
+        let mut locals: Vec<u64> = Vec::new();
+        let mut labels: Vec<Label> = Vec::new();
+        let mut frames: Vec<Frame> = Vec::new();
+        stack.extend_from_slice(args);
+
+        macro_rules! pop {
+            () => {
+                stack.pop().ok_or("Wertestapel leer")?
+            };
+        }
+        macro_rules! push {
+            ($value:expr) => {{
+                let value: u64 = $value;
+                stack.push(value)
+            }};
+        }
+
+        // Betritt eine eigene Funktion: Argumente vom Stapel in die Locals verschieben
+        let enter = |function: usize, stack: &mut Vec<u64>, locals: &mut Vec<u64>, labels: &mut Vec<Label>, frames: &mut Vec<Frame>| -> Result<(), String> {
+            if frames.len() >= MAX_CALL_DEPTH {
+                return Err("Aufruftiefe überschritten".to_string());
+            }
+            let body = &module.functions[function];
+            let ty = &module.types[body.type_index as usize];
+            let locals_base = locals.len();
+            let start = stack.len() - ty.params.len();
+            locals.extend(stack.drain(start..));
+            locals.extend(std::iter::repeat_n(0, body.locals.len()));
+            frames.push(Frame { function, pc: 0, locals_base, label_base: labels.len() });
+            labels.push(Label { target: body.code.len(), height: stack.len(), arity: ty.results.len(), is_loop: false });
+            Ok(())
+        };
+
+        enter(function as usize - imports, &mut stack, &mut locals, &mut labels, &mut frames)?;
+
+        loop {
+            let frame = frames.last_mut().unwrap();
+            let body = &module.functions[frame.function];
+            let code = &body.code;
+            let locals_base = frame.locals_base;
+            let label_base = frame.label_base;
+            if frame.pc >= code.len() {
+                return Err("Funktion ohne end".to_string());
+            }
+            if self.fuel == 0 {
+                return Err("Rechenbudget des Plugins erschöpft".to_string());
+            }
+            self.fuel -= 1;
+            if stack.len() > MAX_VALUE_STACK {
+                return Err("Wertestapel übergelaufen".to_string());
+            }
+
+            let mut reader = Reader::new(code);
+            reader.pos = frame.pc;
+            let op_pc = reader.pos;
+            let opcode = reader.byte()?;
+            // Zu dem Label springen, das `depth` Ebenen außen liegt; true = Funktion verlassen
+            let mut branch_to: Option<u32> = None;
+            let mut do_return = false;
+
+            match opcode {
+                0x00 => return Err("unreachable ausgeführt".to_string()),
+                0x01 => {}
+                0x02 | 0x03 => {
+                    let (params, results) = Self::block_arity(&module, &mut reader)?;
+                    let targets = body.blocks.get(&op_pc).ok_or("Block ohne Sprungziel")?;
+                    let height = stack.len().checked_sub(params).ok_or("Wertestapel leer")?;
+                    if opcode == 0x02 {
+                        labels.push(Label { target: targets.end_pc + 1, height, arity: results, is_loop: false });
+                    } else {
+                        labels.push(Label { target: reader.pos, height, arity: params, is_loop: true });
+                    }
+                }
+                0x04 => {
+                    let (params, results) = Self::block_arity(&module, &mut reader)?;
+                    let targets = *body.blocks.get(&op_pc).ok_or("Block ohne Sprungziel")?;
+                    let condition = pop!() as u32;
+                    let height = stack.len().checked_sub(params).ok_or("Wertestapel leer")?;
+                    let label = Label { target: targets.end_pc + 1, height, arity: results, is_loop: false };
+                    if condition != 0 {
+                        labels.push(label);
+                    } else if let Some(else_pc) = targets.else_pc {
+                        labels.push(label);
+                        reader.pos = else_pc + 1;
+                    } else {
+                        reader.pos = targets.end_pc + 1;
+                    }
+                }
+                0x05 => {
+                    // Ende des then-Zweigs: hinter das zugehörige end springen
+                    let label = labels.pop().ok_or("else ohne Block")?;
+                    reader.pos = label.target;
+                }
+                0x0B => {
+                    labels.pop();
+                    if labels.len() == label_base {
+                        do_return = true;
+                    }
+                }
+                0x0C => branch_to = Some(reader.u32()?),
+                0x0D => {
+                    let depth = reader.u32()?;
+                    if pop!() as u32 != 0 {
+                        branch_to = Some(depth);
+                    }
+                }
+                0x0E => {
+                    let count = reader.u32()?;
+                    let mut depths = Vec::with_capacity(count as usize + 1);
+                    for _ in 0..=count {
+                        depths.push(reader.u32()?);
+                    }
+                    let index = pop!() as u32 as usize;
+                    branch_to = Some(*depths.get(index).unwrap_or(depths.last().unwrap()));
+                }
+                0x0F => do_return = true,
+                0x10 | 0x11 => {
+                    let callee = if opcode == 0x10 {
+                        reader.u32()?
+                    } else {
+                        let type_index = reader.u32()?;
+                        reader.u32()?;
+                        let slot = pop!() as u32 as usize;
+                        let callee = self.table.get(slot).copied().flatten().ok_or("Indirekter Aufruf eines leeren Tabelleneintrags")?;
+                        let expected = module.types.get(type_index as usize).ok_or("Unbekannter Typ")?;
+                        if module.function_type(callee) != Some(expected) {
+                            return Err("Signatur des indirekten Aufrufs passt nicht".to_string());
+                        }
+                        callee
+                    };
+                    frames.last_mut().unwrap().pc = reader.pos;
+                    let callee_type = module.function_type(callee).ok_or("Unbekannte Funktion")?;
+                    if (callee as usize) < imports {
+                        let start = stack.len().checked_sub(callee_type.params.len()).ok_or("Wertestapel leer")?;
+                        let args: Vec<u64> = stack.drain(start..).collect();
+                        let host = self.host[callee as usize];
+                        let result = host(self, &args)?;
+                        stack.extend(result);
+                    } else {
+                        if stack.len() < callee_type.params.len() {
+                            return Err("Wertestapel leer".to_string());
+                        }
+                        enter(callee as usize - imports, &mut stack, &mut locals, &mut labels, &mut frames)?;
+                    }
+                    continue;
+                }
+                0x1A => {
+                    pop!();
+                }
+                0x1B | 0x1C => {
+                    if opcode == 0x1C {
+                        for _ in 0..reader.u32()? {
+                            reader.byte()?;
+                        }
+                    }
+                    let condition = pop!() as u32;
+                    let b = pop!();
+                    let a = pop!();
+                    push!(if condition != 0 { a } else { b });
+                }
+                0x20 => {
+                    let index = locals_base + reader.u32()? as usize;
+                    push!(*locals.get(index).ok_or("Unbekannte lokale Variable")?);
+                }
+                0x21 | 0x22 => {
+                    let index = locals_base + reader.u32()? as usize;
+                    let value = if opcode == 0x21 { pop!() } else { *stack.last().ok_or("Wertestapel leer")? };
+                    *locals.get_mut(index).ok_or("Unbekannte lokale Variable")? = value;
+                }
+                0x23 => {
+                    let index = reader.u32()? as usize;
+                    push!(*self.globals.get(index).ok_or("Unbekannte globale Variable")?);
+                }
+                0x24 => {
+                    let index = reader.u32()? as usize;
+                    let value = pop!();
+                    *self.globals.get_mut(index).ok_or("Unbekannte globale Variable")? = value;
+                }
+                0x28..=0x35 => {
+                    reader.u32()?;
+                    let offset = reader.u32()?;
+                    let base = pop!();
+                    let value = match opcode {
+                        0x28 | 0x2A => self.load(base, offset, 4)?,
+                        0x29 | 0x2B => self.load(base, offset, 8)?,
+                        0x2C => self.load(base, offset, 1)? as u8 as i8 as i32 as u32 as u64,
+                        0x2D => self.load(base, offset, 1)?,
+                        0x2E => self.load(base, offset, 2)? as u16 as i16 as i32 as u32 as u64,
+                        0x2F => self.load(base, offset, 2)?,
+                        0x30 => self.load(base, offset, 1)? as u8 as i8 as i64 as u64,
+                        0x31 => self.load(base, offset, 1)?,
+                        0x32 => self.load(base, offset, 2)? as u16 as i16 as i64 as u64,
+                        0x33 => self.load(base, offset, 2)?,
+                        0x34 => self.load(base, offset, 4)? as u32 as i32 as i64 as u64,
+                        _ => self.load(base, offset, 4)?,
+                    };
+                    push!(value);
+                }
+                0x36..=0x3E => {
+                    reader.u32()?;
+                    let offset = reader.u32()?;
+                    let value = pop!();
+                    let base = pop!();
+                    let size = match opcode {
+                        0x36 | 0x38 | 0x3E => 4,
+                        0x37 | 0x39 => 8,
+                        0x3A | 0x3C => 1,
+                        _ => 2,
+                    };
+                    self.store(base, offset, size, value)?;
+                }
+                0x3F => {
+                    reader.byte()?;
+                    push!((self.memory.len() / PAGE_SIZE) as u64);
+                }
+                0x40 => {
+                    reader.byte()?;
+                    let delta = pop!() as u32 as usize;
+                    let pages = self.memory.len() / PAGE_SIZE;
+                    if pages.saturating_add(delta) > self.max_pages {
+                        push!(u32::MAX as u64);
+                    } else {
+                        self.memory.resize((pages + delta) * PAGE_SIZE, 0);
+                        push!(pages as u64);
+                    }
+                }
+                0x41 => push!(reader.signed(32)? as i32 as u32 as u64),
+                0x42 => push!(reader.signed(64)? as u64),
+                0x43 => push!(u32::from_le_bytes(reader.bytes(4)?.try_into().unwrap()) as u64),
+                0x44 => push!(u64::from_le_bytes(reader.bytes(8)?.try_into().unwrap())),
+                0x45 => {
+                    let a = pop!() as u32;
+                    push!((a == 0) as u64);
+                }
+                0x46..=0x4F => {
+                    let b = pop!() as u32;
+                    let a = pop!() as u32;
+                    let result = match opcode {
+                        0x46 => a == b,
+                        0x47 => a != b,
+                        0x48 => (a as i32) < (b as i32),
+                        0x49 => a < b,
+                        0x4A => (a as i32) > (b as i32),
+                        0x4B => a > b,
+                        0x4C => (a as i32) <= (b as i32),
+                        0x4D => a <= b,
+                        0x4E => (a as i32) >= (b as i32),
+                        _ => a >= b,
+                    };
+                    push!(result as u64);
+                }
+                0x50 => {
+                    let a = pop!();
+                    push!((a == 0) as u64);
+                }
+                0x51..=0x5A => {
+                    let b = pop!();
+                    let a = pop!();
+                    let result = match opcode {
+                        0x51 => a == b,
+                        0x52 => a != b,
+                        0x53 => (a as i64) < (b as i64),
+                        0x54 => a < b,
+                        0x55 => (a as i64) > (b as i64),
+                        0x56 => a > b,
+                        0x57 => (a as i64) <= (b as i64),
+                        0x58 => a <= b,
+                        0x59 => (a as i64) >= (b as i64),
+                        _ => a >= b,
+                    };
+                    push!(result as u64);
+                }
+                0x5B..=0x60 => {
+                    let b = f32::from_bits(pop!() as u32);
+                    let a = f32::from_bits(pop!() as u32);
+                    let result = match opcode {
+                        0x5B => a == b,
+                        0x5C => a != b,
+                        0x5D => a < b,
+                        0x5E => a > b,
+                        0x5F => a <= b,
+                        _ => a >= b,
+                    };
+                    push!(result as u64);
+                }
+                0x61..=0x66 => {
+                    let b = f64::from_bits(pop!());
+                    let a = f64::from_bits(pop!());
+                    let result = match opcode {
+                        0x61 => a == b,
+                        0x62 => a != b,
+                        0x63 => a < b,
+                        0x64 => a > b,
+                        0x65 => a <= b,
+                        _ => a >= b,
+                    };
+                    push!(result as u64);
+                }
+                0x67..=0x69 => {
+                    let a = pop!() as u32;
+                    let result = match opcode {
+                        0x67 => a.leading_zeros(),
+                        0x68 => a.trailing_zeros(),
+                        _ => a.count_ones(),
+                    };
+                    push!(result as u64);
+                }
+                0x6A..=0x78 => {
+                    let b = pop!() as u32;
+                    let a = pop!() as u32;
+                    let result = match opcode {
+                        0x6A => a.wrapping_add(b),
+                        0x6B => a.wrapping_sub(b),
+                        0x6C => a.wrapping_mul(b),
+                        0x6D => {
+                            if b == 0 {
+                                return Err("Division durch null".to_string());
+                            }
+                            (a as i32).checked_div(b as i32).ok_or("Ganzzahlüberlauf")? as u32
+                        }
+                        0x6E => a.checked_div(b).ok_or("Division durch null")?,
+                        0x6F => {
+                            if b == 0 {
+                                return Err("Division durch null".to_string());
+                            }
+                            (a as i32).wrapping_rem(b as i32) as u32
+                        }
+                        0x70 => a.checked_rem(b).ok_or("Division durch null")?,
+                        0x71 => a & b,
+                        0x72 => a | b,
+                        0x73 => a ^ b,
+                        0x74 => a.wrapping_shl(b),
+                        0x75 => (a as i32).wrapping_shr(b) as u32,
+                        0x76 => a.wrapping_shr(b),
+                        0x77 => a.rotate_left(b & 31),
+                        _ => a.rotate_right(b & 31),
+                    };
+                    push!(result as u64);
+                }
+                0x79..=0x7B => {
+                    let a = pop!();
+                    let result = match opcode {
+                        0x79 => a.leading_zeros(),
+                        0x7A => a.trailing_zeros(),
+                        _ => a.count_ones(),
+                    };
+                    push!(result as u64);
+                }
+                0x7C..=0x8A => {
+                    let b = pop!();
+                    let a = pop!();
+                    let result = match opcode {
+                        0x7C => a.wrapping_add(b),
+                        0x7D => a.wrapping_sub(b),
+                        0x7E => a.wrapping_mul(b),
+                        0x7F => {
+                            if b == 0 {
+                                return Err("Division durch null".to_string());
+                            }
+                            (a as i64).checked_div(b as i64).ok_or("Ganzzahlüberlauf")? as u64
+                        }
+                        0x80 => a.checked_div(b).ok_or("Division durch null")?,
+                        0x81 => {
+                            if b == 0 {
+                                return Err("Division durch null".to_string());
+                            }
+                            (a as i64).wrapping_rem(b as i64) as u64
+                        }
+                        0x82 => a.checked_rem(b).ok_or("Division durch null")?,
+                        0x83 => a & b,
+                        0x84 => a | b,
+                        0x85 => a ^ b,
+                        0x86 => a.wrapping_shl(b as u32),
+                        0x87 => (a as i64).wrapping_shr(b as u32) as u64,
+                        0x88 => a.wrapping_shr(b as u32),
+                        0x89 => a.rotate_left((b & 63) as u32),
+                        _ => a.rotate_right((b & 63) as u32),
+                    };
+                    push!(result);
+                }
+                0x8B..=0x91 => {
+                    let a = f32::from_bits(pop!() as u32);
+                    let result = match opcode {
+                        0x8B => a.abs(),
+                        0x8C => -a,
+                        0x8D => a.ceil(),
+                        0x8E => a.floor(),
+                        0x8F => a.trunc(),
+                        0x90 => a.round_ties_even(),
+                        _ => a.sqrt(),
+                    };
+                    push!(result.to_bits() as u64);
+                }
+                0x92..=0x98 => {
+                    let b = f32::from_bits(pop!() as u32);
+                    let a = f32::from_bits(pop!() as u32);
+                    let result = match opcode {
+                        0x92 => a + b,
+                        0x93 => a - b,
+                        0x94 => a * b,
+                        0x95 => a / b,
+                        0x96 => f32_min(a, b),
+                        0x97 => f32_max(a, b),
+                        _ => a.copysign(b),
+                    };
+                    push!(result.to_bits() as u64);
+                }
+                0x99..=0x9F => {
+                    let a = f64::from_bits(pop!());
+                    let result = match opcode {
+                        0x99 => a.abs(),
+                        0x9A => -a,
+                        0x9B => a.ceil(),
+                        0x9C => a.floor(),
+                        0x9D => a.trunc(),
+                        0x9E => a.round_ties_even(),
+                        _ => a.sqrt(),
+                    };
+                    push!(result.to_bits());
+                }
+                0xA0..=0xA6 => {
+                    let b = f64::from_bits(pop!());
+                    let a = f64::from_bits(pop!());
+                    let result = match opcode {
+                        0xA0 => a + b,
+                        0xA1 => a - b,
+                        0xA2 => a * b,
+                        0xA3 => a / b,
+                        0xA4 => f64_min(a, b),
+                        0xA5 => f64_max(a, b),
+                        _ => a.copysign(b),
+                    };
+                    push!(result.to_bits());
+                }
+                0xA7 => {
+                    let a = pop!();
+                    push!(a as u32 as u64);
+                }
+                0xA8..=0xAB | 0xAE..=0xB1 => {
+                    let bits = pop!();
+                    let a = if matches!(opcode, 0xA8 | 0xA9 | 0xAE | 0xAF) { f32::from_bits(bits as u32) as f64 } else { f64::from_bits(bits) };
+                    let result = match opcode {
+                        0xA8 | 0xAA => trunc_f64(a, i32::MIN as f64, i32::MAX as f64)? as i32 as u32 as u64,
+                        0xA9 | 0xAB => trunc_f64(a, 0.0, u32::MAX as f64)? as u32 as u64,
+                        0xAE | 0xB0 => {
+                            let value = trunc_f64(a, i64::MIN as f64, i64::MAX as f64)?;
+                            if value >= 9.223_372_036_854_776e18 {
+                                return Err("Ganzzahlüberlauf bei Konvertierung".to_string());
+                            }
+                            value as i64 as u64
+                        }
+                        _ => {
+                            let value = trunc_f64(a, 0.0, u64::MAX as f64)?;
+                            if value >= 1.8446744073709552e19 {
+                                return Err("Ganzzahlüberlauf bei Konvertierung".to_string());
+                            }
+                            value as u64
+                        }
+                    };
+                    push!(result);
+                }
+                0xAC => {
+                    let a = pop!();
+                    push!(a as u32 as i32 as i64 as u64);
+                }
+                0xAD => {
+                    let a = pop!();
+                    push!(a as u32 as u64);
+                }
+                0xB2..=0xB6 => {
+                    let a = pop!();
+                    let result = match opcode {
+                        0xB2 => a as u32 as i32 as f32,
+                        0xB3 => a as u32 as f32,
+                        0xB4 => a as i64 as f32,
+                        0xB5 => a as f32,
+                        _ => f64::from_bits(a) as f32,
+                    };
+                    push!(result.to_bits() as u64);
+                }
+                0xB7..=0xBB => {
+                    let a = pop!();
+                    let result = match opcode {
+                        0xB7 => a as u32 as i32 as f64,
+                        0xB8 => a as u32 as f64,
+                        0xB9 => a as i64 as f64,
+                        0xBA => a as f64,
+                        _ => f32::from_bits(a as u32) as f64,
+                    };
+                    push!(result.to_bits());
+                }
+                // Reinterpret-Befehle: die Bits bleiben unverändert
+                0xBC..=0xBF => {}
+                0xC0 => {
+                    let a = pop!();
+                    push!(a as u8 as i8 as i32 as u32 as u64);
+                }
+                0xC1 => {
+                    let a = pop!();
+                    push!(a as u16 as i16 as i32 as u32 as u64);
+                }
+                0xC2 => {
+                    let a = pop!();
+                    push!(a as u8 as i8 as i64 as u64);
+                }
+                0xC3 => {
+                    let a = pop!();
+                    push!(a as u16 as i16 as i64 as u64);
+                }
+                0xC4 => {
+                    let a = pop!();
+                    push!(a as u32 as i32 as i64 as u64);
+                }
+                0xD2 => push!(reader.u32()? as u64),
+                0xFC => {
+                    let sub = reader.u32()?;
+                    match sub {
+                        // Sättigende Konvertierungen
+                        0..=7 => {
+                            let bits = pop!();
+                            let a = if sub < 2 || (4..6).contains(&sub) { f32::from_bits(bits as u32) as f64 } else { f64::from_bits(bits) };
+                            let result = match sub {
+                                0 | 2 => a as i32 as u32 as u64,
+                                1 | 3 => a as u32 as u64,
+                                4 | 6 => a as i64 as u64,
+                                _ => a as u64,
+                            };
+                            push!(result);
+                        }
+                        10 => {
+                            reader.byte()?;
+                            reader.byte()?;
+                            let len = pop!() as u32 as usize;
+                            let src = pop!() as u32 as usize;
+                            let dst = pop!() as u32 as usize;
+                            if src + len > self.memory.len() || dst + len > self.memory.len() {
+                                return Err("memory.copy außerhalb der Grenzen".to_string());
+                            }
+                            self.memory.copy_within(src..src + len, dst);
+                        }
+                        11 => {
+                            reader.byte()?;
+                            let len = pop!() as u32 as usize;
+                            let value = pop!() as u8;
+                            let dst = pop!() as u32 as usize;
+                            if dst + len > self.memory.len() {
+                                return Err("memory.fill außerhalb der Grenzen".to_string());
+                            }
+                            self.memory[dst..dst + len].fill(value);
+                        }
+                        other => return Err(format!("Nicht unterstützter Befehl 0xFC {}", other)),
+                    }
+                }
+                other => return Err(format!("Nicht unterstützter Befehl 0x{:02X}", other)),
+            }
+
+            if let Some(depth) = branch_to {
+                let index = labels.len().checked_sub(depth as usize + 1).filter(|&i| i >= label_base).ok_or("Ungültiges Sprungziel")?;
+                let label = labels[index];
+                let start = stack.len().checked_sub(label.arity).ok_or("Wertestapel leer")?;
+                let values: Vec<u64> = stack.drain(start..).collect();
+                stack.truncate(label.height);
+                stack.extend(values);
+                if label.is_loop {
+                    labels.truncate(index + 1);
+                } else {
+                    labels.truncate(index);
+                }
+                reader.pos = label.target;
+                if labels.len() == label_base {
+                    do_return = true;
+                }
+            }
+
+            if do_return {
+                let function_label = labels.get(label_base).copied();
+                let frame = frames.pop().unwrap();
+                let ty = &module.types[module.functions[frame.function].type_index as usize];
+                let start = stack.len().checked_sub(ty.results.len()).ok_or("Wertestapel leer")?;
+                let values: Vec<u64> = stack.drain(start..).collect();
+                if let Some(label) = function_label {
+                    stack.truncate(label.height);
+                }
+                stack.extend(values);
+                labels.truncate(frame.label_base);
+                locals.truncate(frame.locals_base);
+                if frames.is_empty() {
+                    return Ok(stack);
+                }
+                continue;
+            }
+
+            frames.last_mut().unwrap().pc = reader.pos;
+        }
+    }
+}
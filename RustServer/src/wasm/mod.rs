@@ -0,0 +1,500 @@
+pub mod interp;
+pub mod module;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, Weak};
+use crate::dimension::{self, Dimension};
+use crate::event::{EventKind, EventPriority, ListenerId};
+use crate::plugin::PLUGIN_DIR;
+use crate::tick::TaskId;
+use crate::{block, send_system_message, Server};
+use interp::{HostFunc, Instance};
+use module::{FuncType, Module, ValType};
+
+// Plugins importieren die Host-API aus "rustmc" (siehe resolve) und exportieren `memory`, optional
+// `enable`/`disable` sowie `alloc(len)` und je nach Nutzung `on_command`, `on_event` und `on_task`
+const HOST_MODULE: &str = "rustmc";
+const CAPABILITY_FILE_EXTENSION: &str = "capabilities";
+// 64 MiB linearer Speicher; das Rechenbudget (Befehle pro Aufruf) hält Endlosschleifen unter einem Tick
+const MAX_MEMORY_PAGES: usize = 1024;
+const FUEL_PER_CALL: u64 = 2_000_000;
+
+// Fähigkeiten, die einem Plugin gewährt werden können. Nicht gewährte Importe verhindern das Laden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    Commands,
+    Events,
+    Chat,
+    Players,
+    WorldRead,
+    WorldWrite,
+    Scheduler,
+}
+
+impl Capability {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Commands => "commands",
+            Capability::Events => "events",
+            Capability::Chat => "chat",
+            Capability::Players => "players",
+            Capability::WorldRead => "world.read",
+            Capability::WorldWrite => "world.write",
+            Capability::Scheduler => "scheduler",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Capability> {
+        [
+            Capability::Commands,
+            Capability::Events,
+            Capability::Chat,
+            Capability::Players,
+            Capability::WorldRead,
+            Capability::WorldWrite,
+            Capability::Scheduler,
+        ]
+        .into_iter()
+        .find(|c| c.name() == name)
+    }
+
+    // Ohne <plugin>.capabilities: alles außer Spieler-Eingriffen und Weltveränderungen
+    fn defaults() -> HashSet<Capability> {
+        [Capability::Commands, Capability::Events, Capability::Chat, Capability::WorldRead, Capability::Scheduler].into_iter().collect()
+    }
+}
+
+// Der Server lebt bis zum Prozessende, daher darf jede Plugin-Instanz einen Zeiger darauf halten
+#[derive(Clone, Copy)]
+struct ServerRef(*const Server);
+
+unsafe impl Send for ServerRef {}
+
+pub struct PluginState {
+    name: String,
+    server: ServerRef,
+    this: Weak<Mutex<WasmInstance>>,
+    commands: Vec<String>,
+    listeners: Vec<ListenerId>,
+    tasks: Vec<TaskId>,
+    // Das gerade zugestellte Event, abrufbar über event_message/event_block während on_event
+    event: Option<EventSnapshot>,
+}
+
+struct EventSnapshot {
+    message: Option<String>,
+    // Von set_event_message gesetzt
+    new_message: Option<String>,
+    block: Option<(i32, i32, i32, u16)>,
+    cancelled: bool,
+}
+
+type WasmInstance = Instance<PluginState>;
+
+#[derive(Default)]
+pub struct WasmPluginManager {
+    plugins: Mutex<Vec<Arc<Mutex<WasmInstance>>>>,
+}
+
+fn server(instance: &WasmInstance) -> &Server {
+    unsafe { &*instance.data.server.0 }
+}
+
+fn arg_i32(args: &[u64], index: usize) -> i32 {
+    args[index] as u32 as i32
+}
+
+fn arg_str(instance: &WasmInstance, args: &[u64], index: usize) -> Result<String, String> {
+    instance.read_string(args[index] as u32, args[index + 1] as u32)
+}
+
+fn ok(value: i32) -> Result<Option<u64>, String> {
+    Ok(Some(value as u32 as u64))
+}
+
+fn sig(params: &[ValType], results: &[ValType]) -> FuncType {
+    FuncType { params: params.to_vec(), results: results.to_vec() }
+}
+
+// Gibt dem Plugin einen String in seinem eigenen Speicher (über dessen `alloc`-Export)
+fn pass_string(instance: &mut WasmInstance, text: &str) -> Result<(u64, u64), String> {
+    let len = text.len() as u64;
+    let ptr = instance.call("alloc", &[len])?.first().copied().ok_or("alloc lieferte keinen Zeiger")?;
+    instance.write_bytes(ptr as u32, text.as_bytes())?;
+    Ok((ptr, len))
+}
+
+fn free_strings(instance: &mut WasmInstance, strings: &[(u64, u64)]) {
+    if instance.has_export("dealloc") {
+        for &(ptr, len) in strings {
+            let _ = instance.call("dealloc", &[ptr, len]);
+        }
+    }
+}
+
+fn host_log(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    info!("[{}] {}", instance.data.name, arg_str(instance, args, 0)?);
+    Ok(None)
+}
+
+fn host_register_command(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let name = arg_str(instance, args, 0)?;
+    let usage = arg_str(instance, args, 2)?;
+    if !instance.has_export("on_command") || !instance.has_export("alloc") {
+        return ok(-1);
+    }
+    let plugin = instance.data.this.clone();
+    let command = name.clone();
+    server(instance).commands.lock().unwrap().register(&name, &usage, move |ctx, args| {
+        let Some(plugin) = plugin.upgrade() else { return Err("Plugin is not loaded".to_string()) };
+        let sender = ctx.sender.name(ctx.server);
+        let mut instance = plugin.lock().unwrap();
+        let result = call_with_strings(&mut instance, "on_command", &[], &[&command, &sender, &args.join(" ")]);
+        match result {
+            Ok(values) if values.first() == Some(&0) => Ok(()),
+            Ok(_) => Err("Command failed".to_string()),
+            Err(e) => {
+                warn!("[{}] Fehler in Befehl /{}: {}", instance.data.name, command, e);
+                Err("An internal error occurred while running this command".to_string())
+            }
+        }
+    });
+    instance.data.commands.push(name);
+    ok(0)
+}
+
+fn host_register_listener(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let Some(kind) = EventKind::from_id(arg_i32(args, 0) as u32) else { return ok(-1) };
+    if !instance.has_export("on_event") || !instance.has_export("alloc") {
+        return ok(-1);
+    }
+    let plugin = instance.data.this.clone();
+    let id = server(instance).events.listen_kind(kind, EventPriority::Normal, move |_server, event| {
+        let Some(plugin) = plugin.upgrade() else { return };
+        let mut instance = plugin.lock().unwrap();
+        instance.data.event = Some(EventSnapshot {
+            message: event.message().map(str::to_string),
+            new_message: None,
+            block: event.block(),
+            cancelled: event.is_cancelled(),
+        });
+        let result = call_with_strings(&mut instance, "on_event", &[kind.id() as u64], &[event.player()]);
+        if let Some(snapshot) = instance.data.event.take() {
+            event.set_cancelled(snapshot.cancelled);
+            if let Some(message) = snapshot.new_message {
+                event.set_message(message);
+            }
+        }
+        if let Err(e) = result {
+            warn!("[{}] Fehler in Event {}: {}", instance.data.name, kind.name(), e);
+        }
+    });
+    instance.data.listeners.push(id);
+    ok(0)
+}
+
+// event_message(buf, cap): kopiert höchstens cap Bytes und liefert die volle Länge, -1 ohne Nachricht
+fn host_event_message(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let Some(message) = instance.data.event.as_ref().and_then(|e| e.message.clone()) else { return ok(-1) };
+    let len = message.len().min(arg_i32(args, 1).max(0) as usize);
+    instance.write_bytes(args[0] as u32, &message.as_bytes()[..len])?;
+    ok(message.len() as i32)
+}
+
+// Schreibt x, y, z und den Blockzustand als vier i32 (little endian) an `out`
+fn host_event_block(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let Some((x, y, z, state)) = instance.data.event.as_ref().and_then(|e| e.block) else { return ok(-1) };
+    let mut bytes = Vec::with_capacity(16);
+    for value in [x, y, z, state as i32] {
+        bytes.extend(value.to_le_bytes());
+    }
+    instance.write_bytes(args[0] as u32, &bytes)?;
+    ok(0)
+}
+
+fn host_event_cancelled(instance: &mut WasmInstance, _args: &[u64]) -> Result<Option<u64>, String> {
+    ok(instance.data.event.as_ref().is_some_and(|e| e.cancelled) as i32)
+}
+
+fn host_set_event_cancelled(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    if let Some(event) = instance.data.event.as_mut() {
+        event.cancelled = arg_i32(args, 0) != 0;
+    }
+    Ok(None)
+}
+
+// set_event_message(ptr, len): ersetzt die Chatnachricht; bei Events ohne Nachricht -1
+fn host_set_event_message(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let message = arg_str(instance, args, 0)?;
+    match instance.data.event.as_mut() {
+        Some(event) if event.message.is_some() => {
+            event.message = Some(message.clone());
+            event.new_message = Some(message);
+            ok(0)
+        }
+        _ => ok(-1),
+    }
+}
+
+fn host_send_message(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let player = arg_str(instance, args, 0)?;
+    let message = arg_str(instance, args, 2)?;
+    let players = server(instance).players.lock().unwrap();
+    match players.iter().find(|p| p.username == player) {
+        Some(p) if send_system_message(&p.connection, &message).is_ok() => ok(0),
+        _ => ok(-1),
+    }
+}
+
+fn host_broadcast(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let message = arg_str(instance, args, 0)?;
+    for player in server(instance).players.lock().unwrap().iter() {
+        let _ = send_system_message(&player.connection, &message);
+    }
+    Ok(None)
+}
+
+fn host_kick_player(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let player = arg_str(instance, args, 0)?;
+    let players = server(instance).players.lock().unwrap();
+    match players.iter().find(|p| p.username == player) {
+        Some(p) => {
+            info!("[{}] Trenne {}", instance.data.name, p.username);
+            p.connection.close();
+            ok(0)
+        }
+        None => ok(-1),
+    }
+}
+
+// Schreibt x, y, z als drei f64 (little endian) an `out`
+fn host_player_position(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let player = arg_str(instance, args, 0)?;
+    let position = server(instance).players.lock().unwrap().iter().find(|p| p.username == player).map(|p| p.position);
+    let Some((x, y, z)) = position else { return ok(-1) };
+    let mut bytes = Vec::with_capacity(24);
+    for value in [x, y, z] {
+        bytes.extend(value.to_le_bytes());
+    }
+    instance.write_bytes(args[2] as u32, &bytes)?;
+    ok(0)
+}
+
+// teleport(player, world, x, y, z): auch in eine andere Welt, "world" ist die Oberwelt
+fn host_teleport(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let player = arg_str(instance, args, 0)?;
+    let world = arg_str(instance, args, 2)?;
+    let position = (f64::from_bits(args[4]), f64::from_bits(args[5]), f64::from_bits(args[6]));
+    let server = server(instance);
+    let Some(uuid) = server.players.lock().unwrap().iter().find(|p| p.username == player).map(|p| p.uuid) else { return ok(-1) };
+    match Dimension::by_world_name(&world).and_then(|d| dimension::teleport(server, uuid, d, position, None)) {
+        Ok(()) => ok(0),
+        Err(e) => {
+            warn!("[{}] Teleport von {} fehlgeschlagen: {}", instance.data.name, player, e);
+            ok(-1)
+        }
+    }
+}
+
+fn host_get_block(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let state = server(instance).world.lock().unwrap().get_block(arg_i32(args, 0), arg_i32(args, 1), arg_i32(args, 2));
+    ok(state as i32)
+}
+
+fn host_set_block(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let (x, y, z, state) = (arg_i32(args, 0), arg_i32(args, 1), arg_i32(args, 2), arg_i32(args, 3) as u32);
+    if state >= 1 << block::GLOBAL_BITS {
+        return ok(-1);
+    }
+    let server = server(instance);
+    if !server.world.lock().unwrap().set_block_and_notify((x, y, z), state as u16) {
+        return ok(-1);
+    }
+    ok(0)
+}
+
+// schedule(delay, period, task): period 0 = einmalig. Ruft später den Export `on_task(task)` auf
+fn host_schedule(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    if !instance.has_export("on_task") {
+        return Ok(Some(0));
+    }
+    let (delay, period, task) = (args[0], args[1], arg_i32(args, 2));
+    let plugin = instance.data.this.clone();
+    let run = move |_server: &Server| {
+        if let Some(plugin) = plugin.upgrade() {
+            let mut instance = plugin.lock().unwrap();
+            instance.fuel = FUEL_PER_CALL;
+            if let Err(e) = instance.call("on_task", &[task as u32 as u64]) {
+                warn!("[{}] Fehler in geplanter Aufgabe {}: {}", instance.data.name, task, e);
+            }
+        }
+    };
+    let mut scheduler = server(instance).scheduler.lock().unwrap();
+    let id = if period == 0 { scheduler.run_later(delay, run) } else { scheduler.run_repeating(delay, period, run) };
+    drop(scheduler);
+    instance.data.tasks.push(id);
+    Ok(Some(id))
+}
+
+fn host_cancel(instance: &mut WasmInstance, args: &[u64]) -> Result<Option<u64>, String> {
+    let id = args[0];
+    if !instance.data.tasks.contains(&id) {
+        return ok(-1);
+    }
+    instance.data.tasks.retain(|t| *t != id);
+    server(instance).scheduler.lock().unwrap().cancel(id);
+    ok(0)
+}
+
+// Ruft einen Export mit zusätzlichen String-Argumenten (jeweils Zeiger und Länge) auf
+fn call_with_strings(instance: &mut WasmInstance, export: &str, args: &[u64], strings: &[&str]) -> Result<Vec<u64>, String> {
+    instance.fuel = FUEL_PER_CALL;
+    let mut passed = Vec::new();
+    for text in strings {
+        passed.push(pass_string(instance, text)?);
+    }
+    let mut all_args = args.to_vec();
+    for &(ptr, len) in &passed {
+        all_args.extend([ptr, len]);
+    }
+    let result = instance.call(export, &all_args);
+    free_strings(instance, &passed);
+    result
+}
+
+fn resolve(capabilities: &HashSet<Capability>, module: &str, name: &str, ty: &FuncType) -> Result<HostFunc<PluginState>, String> {
+    use ValType::{F64, I32, I64};
+    if module != HOST_MODULE {
+        return Err(format!("Unbekanntes Import-Modul {}", module));
+    }
+    let (capability, expected, function): (Option<Capability>, FuncType, HostFunc<PluginState>) = match name {
+        "log" => (None, sig(&[I32, I32], &[]), host_log),
+        "register_command" => (Some(Capability::Commands), sig(&[I32, I32, I32, I32], &[I32]), host_register_command),
+        "register_listener" => (Some(Capability::Events), sig(&[I32], &[I32]), host_register_listener),
+        "event_message" => (Some(Capability::Events), sig(&[I32, I32], &[I32]), host_event_message),
+        "event_block" => (Some(Capability::Events), sig(&[I32], &[I32]), host_event_block),
+        "event_cancelled" => (Some(Capability::Events), sig(&[], &[I32]), host_event_cancelled),
+        "set_event_cancelled" => (Some(Capability::Events), sig(&[I32], &[]), host_set_event_cancelled),
+        "set_event_message" => (Some(Capability::Events), sig(&[I32, I32], &[I32]), host_set_event_message),
+        "send_message" => (Some(Capability::Chat), sig(&[I32, I32, I32, I32], &[I32]), host_send_message),
+        "broadcast" => (Some(Capability::Chat), sig(&[I32, I32], &[]), host_broadcast),
+        "kick_player" => (Some(Capability::Players), sig(&[I32, I32], &[I32]), host_kick_player),
+        "player_position" => (Some(Capability::Players), sig(&[I32, I32, I32], &[I32]), host_player_position),
+        "teleport" => (Some(Capability::Players), sig(&[I32, I32, I32, I32, F64, F64, F64], &[I32]), host_teleport),
+        "get_block" => (Some(Capability::WorldRead), sig(&[I32, I32, I32], &[I32]), host_get_block),
+        "set_block" => (Some(Capability::WorldWrite), sig(&[I32, I32, I32, I32], &[I32]), host_set_block),
+        "schedule" => (Some(Capability::Scheduler), sig(&[I64, I64, I32], &[I64]), host_schedule),
+        "cancel" => (Some(Capability::Scheduler), sig(&[I64], &[I32]), host_cancel),
+        _ => return Err(format!("Unbekannte Host-Funktion {}.{}", module, name)),
+    };
+    if *ty != expected {
+        return Err(format!("Host-Funktion {} mit falscher Signatur importiert", name));
+    }
+    if let Some(capability) = capability {
+        if !capabilities.contains(&capability) {
+            return Err(format!("{} benötigt die Fähigkeit \"{}\", die nicht gewährt wurde", name, capability.name()));
+        }
+    }
+    Ok(function)
+}
+
+fn load_capabilities(path: &Path) -> Result<HashSet<Capability>, String> {
+    let path = path.with_extension(CAPABILITY_FILE_EXTENSION);
+    let Ok(text) = fs::read_to_string(&path) else { return Ok(Capability::defaults()) };
+    text.split_whitespace()
+        .filter(|word| !word.starts_with('#'))
+        .map(|word| Capability::parse(word).ok_or_else(|| format!("Unbekannte Fähigkeit \"{}\" in {}", word, path.display())))
+        .collect()
+}
+
+impl WasmPluginManager {
+    pub fn new() -> WasmPluginManager {
+        WasmPluginManager::default()
+    }
+
+    // Lädt alle .wasm-Dateien aus plugins/. Der Server muss bis zum Prozessende leben
+    pub fn load_all(server: &Server) {
+        let Ok(entries) = fs::read_dir(PLUGIN_DIR) else { return };
+        let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.extension().is_some_and(|ext| ext == "wasm")).collect();
+        paths.sort();
+        for path in paths {
+            if let Err(e) = Self::load(server, &path) {
+                warn!("WASM-Plugin {} konnte nicht geladen werden: {}", path.display(), e);
+            }
+        }
+    }
+
+    fn load(server: &Server, path: &Path) -> Result<(), String> {
+        let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        let module = Module::parse(&bytes)?;
+        let capabilities = load_capabilities(path)?;
+        let state = PluginState {
+            name: name.clone(),
+            server: ServerRef(server),
+            this: Weak::new(),
+            commands: Vec::new(),
+            listeners: Vec::new(),
+            tasks: Vec::new(),
+            event: None,
+        };
+        let mut instance = Instance::new(module, state, MAX_MEMORY_PAGES, |module, name, ty| resolve(&capabilities, module, name, ty))?;
+        let plugin = Arc::new_cyclic(|this| {
+            instance.data.this = this.clone();
+            Mutex::new(instance)
+        });
+        {
+            let mut instance = plugin.lock().unwrap();
+            if instance.has_export("enable") {
+                instance.fuel = FUEL_PER_CALL;
+                let result = instance.call("enable", &[]).and_then(|values| match values.first().map(|v| *v as u32 as i32) {
+                    None | Some(0) => Ok(()),
+                    Some(code) => Err(format!("enable lieferte {}", code)),
+                });
+                if let Err(e) = result {
+                    Self::cleanup(server, &mut instance);
+                    return Err(e);
+                }
+            }
+        }
+        let mut granted: Vec<&str> = capabilities.iter().map(|c| c.name()).collect();
+        granted.sort();
+        info!("WASM-Plugin {} geladen (Fähigkeiten: {})", name, granted.join(", "));
+        server.wasm_plugins.plugins.lock().unwrap().push(plugin);
+        Ok(())
+    }
+
+    // Entfernt alles, was das Plugin beim Server registriert hat
+    fn cleanup(server: &Server, instance: &mut WasmInstance) {
+        {
+            let mut commands = server.commands.lock().unwrap();
+            for command in instance.data.commands.drain(..) {
+                commands.unregister(&command);
+            }
+        }
+        for listener in instance.data.listeners.drain(..) {
+            server.events.unlisten(listener);
+        }
+        let mut scheduler = server.scheduler.lock().unwrap();
+        for task in instance.data.tasks.drain(..) {
+            scheduler.cancel(task);
+        }
+    }
+
+    pub fn unload_all(server: &Server) {
+        let plugins = std::mem::take(&mut *server.wasm_plugins.plugins.lock().unwrap());
+        for plugin in plugins.iter().rev() {
+            let mut instance = plugin.lock().unwrap();
+            if instance.has_export("disable") {
+                instance.fuel = FUEL_PER_CALL;
+                if let Err(e) = instance.call("disable", &[]) {
+                    warn!("[{}] Fehler beim Deaktivieren: {}", instance.data.name, e);
+                }
+            }
+            Self::cleanup(server, &mut instance);
+            info!("WASM-Plugin {} entladen", instance.data.name);
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+
+pub const PAGE_SIZE: usize = 65536;
+
+const MAGIC: &[u8] = b"\0asm";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValType {
+    I32,
+    I64,
+    F32,
+    F64,
+    FuncRef,
+    ExternRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FuncType {
+    pub params: Vec<ValType>,
+    pub results: Vec<ValType>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub module: String,
+    pub name: String,
+    pub type_index: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ConstExpr {
+    Value(u64),
+    Global(u32),
+    Func(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportKind {
+    Func,
+    Table,
+    Memory,
+    Global,
+}
+
+// Sprungziele eines Blocks: Position von `else` (nur bei if) und von `end`
+#[derive(Debug, Clone, Copy)]
+pub struct BlockTargets {
+    pub else_pc: Option<usize>,
+    pub end_pc: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub type_index: u32,
+    pub locals: Vec<ValType>,
+    pub code: Vec<u8>,
+    pub blocks: HashMap<usize, BlockTargets>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElementSegment {
+    pub offset: Option<ConstExpr>,
+    pub functions: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DataSegment {
+    pub offset: Option<ConstExpr>,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Module {
+    pub types: Vec<FuncType>,
+    pub imports: Vec<Import>,
+    pub functions: Vec<Function>,
+    pub table: Option<(u32, Option<u32>)>,
+    pub memory: Option<(u32, Option<u32>)>,
+    // Startwerte der globalen Variablen (Typ und Veränderbarkeit werden nicht geprüft)
+    pub globals: Vec<ConstExpr>,
+    pub exports: HashMap<String, (ExportKind, u32)>,
+    pub start: Option<u32>,
+    pub elements: Vec<ElementSegment>,
+    pub data: Vec<DataSegment>,
+    pub custom: HashMap<String, Vec<u8>>,
+}
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pub pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data, pos: 0 }
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self.data.get(self.pos).ok_or("Unerwartetes Ende des WASM-Moduls")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len()).ok_or("Unerwartetes Ende des WASM-Moduls")?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        let mut result = 0u64;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            result |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return u32::try_from(result).map_err(|_| "LEB128-Wert zu groß".to_string());
+            }
+        }
+        Err("LEB128-Wert zu lang".to_string())
+    }
+
+    // Vorzeichenbehaftetes LEB128 mit bis zu `bits` Bits
+    pub fn signed(&mut self, bits: u32) -> Result<i64, String> {
+        let mut result = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift < 64 {
+                result |= ((byte & 0x7F) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1i64 << shift;
+                }
+                return Ok(result);
+            }
+            if shift >= bits + 7 {
+                return Err("LEB128-Wert zu lang".to_string());
+            }
+        }
+    }
+
+    pub fn name(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?.to_vec()).map_err(|_| "Ungültiger UTF-8-Name im WASM-Modul".to_string())
+    }
+
+    fn val_type(&mut self) -> Result<ValType, String> {
+        match self.byte()? {
+            0x7F => Ok(ValType::I32),
+            0x7E => Ok(ValType::I64),
+            0x7D => Ok(ValType::F32),
+            0x7C => Ok(ValType::F64),
+            0x70 => Ok(ValType::FuncRef),
+            0x6F => Ok(ValType::ExternRef),
+            other => Err(format!("Unbekannter Werttyp 0x{:02X}", other)),
+        }
+    }
+
+    fn limits(&mut self) -> Result<(u32, Option<u32>), String> {
+        match self.byte()? {
+            0x00 => Ok((self.u32()?, None)),
+            0x01 => Ok((self.u32()?, Some(self.u32()?))),
+            other => Err(format!("Nicht unterstützte Limits 0x{:02X}", other)),
+        }
+    }
+
+    fn const_expr(&mut self) -> Result<ConstExpr, String> {
+        let expr = match self.byte()? {
+            0x41 => ConstExpr::Value(self.signed(32)? as i32 as u32 as u64),
+            0x42 => ConstExpr::Value(self.signed(64)? as u64),
+            0x43 => ConstExpr::Value(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()) as u64),
+            0x44 => ConstExpr::Value(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap())),
+            0x23 => ConstExpr::Global(self.u32()?),
+            0xD2 => ConstExpr::Func(self.u32()?),
+            other => return Err(format!("Nicht unterstützter konstanter Ausdruck 0x{:02X}", other)),
+        };
+        match self.byte()? {
+            0x0B => Ok(expr),
+            _ => Err("Konstanter Ausdruck ohne end".to_string()),
+        }
+    }
+}
+
+// Länge der Immediates eines Opcodes überspringen (für die Vorberechnung der Sprungziele)
+fn skip_immediates(reader: &mut Reader, opcode: u8) -> Result<(), String> {
+    match opcode {
+        0x02..=0x04 => {
+            reader.signed(33)?;
+        }
+        0x0C | 0x0D | 0x10 | 0x20..=0x24 | 0x25 | 0x26 | 0xD2 => {
+            reader.u32()?;
+        }
+        0x0E => {
+            let count = reader.u32()?;
+            for _ in 0..=count {
+                reader.u32()?;
+            }
+        }
+        0x11 => {
+            reader.u32()?;
+            reader.u32()?;
+        }
+        0x1C => {
+            let count = reader.u32()?;
+            for _ in 0..count {
+                reader.val_type()?;
+            }
+        }
+        0x28..=0x3E => {
+            reader.u32()?;
+            reader.u32()?;
+        }
+        0x3F | 0x40 => {
+            reader.byte()?;
+        }
+        0x41 => {
+            reader.signed(32)?;
+        }
+        0x42 => {
+            reader.signed(64)?;
+        }
+        0x43 => {
+            reader.bytes(4)?;
+        }
+        0x44 => {
+            reader.bytes(8)?;
+        }
+        0xD0 => {
+            reader.byte()?;
+        }
+        0xFC => match reader.u32()? {
+            0..=7 => {}
+            8 => {
+                reader.u32()?;
+                reader.byte()?;
+            }
+            9 | 13 | 15..=17 => {
+                reader.u32()?;
+            }
+            10 | 12 | 14 => {
+                reader.u32()?;
+                reader.u32()?;
+            }
+            11 => {
+                reader.byte()?;
+            }
+            other => return Err(format!("Nicht unterstützter Opcode 0xFC {}", other)),
+        },
+        0x00 | 0x01 | 0x05 | 0x0B | 0x0F | 0x1A | 0x1B | 0x45..=0xC4 | 0xD1 => {}
+        other => return Err(format!("Nicht unterstützter Opcode 0x{:02X}", other)),
+    }
+    Ok(())
+}
+
+fn scan_blocks(code: &[u8]) -> Result<HashMap<usize, BlockTargets>, String> {
+    let mut blocks = HashMap::new();
+    let mut open: Vec<(usize, Option<usize>)> = Vec::new();
+    let mut reader = Reader::new(code);
+    while !reader.at_end() {
+        let pc = reader.pos;
+        let opcode = reader.byte()?;
+        skip_immediates(&mut reader, opcode)?;
+        match opcode {
+            0x02..=0x04 => open.push((pc, None)),
+            0x05 => match open.last_mut() {
+                Some((_, else_pc)) => *else_pc = Some(pc),
+                None => return Err("else außerhalb eines Blocks".to_string()),
+            },
+            0x0B => {
+                // Das letzte end beendet den Funktionsrumpf selbst
+                if let Some((start, else_pc)) = open.pop() {
+                    blocks.insert(start, BlockTargets { else_pc, end_pc: pc });
+                }
+            }
+            _ => {}
+        }
+    }
+    if !open.is_empty() {
+        return Err("Block ohne end".to_string());
+    }
+    Ok(blocks)
+}
+
+impl Module {
+    pub fn parse(data: &[u8]) -> Result<Module, String> {
+        let mut reader = Reader::new(data);
+        if reader.bytes(4)? != MAGIC {
+            return Err("Keine WASM-Datei (magische Bytes fehlen)".to_string());
+        }
+        let version = u32::from_le_bytes(reader.bytes(4)?.try_into().unwrap());
+        if version != VERSION {
+            return Err(format!("Nicht unterstützte WASM-Version {}", version));
+        }
+        let mut module = Module::default();
+        let mut function_types = Vec::new();
+        while !reader.at_end() {
+            let id = reader.byte()?;
+            let len = reader.u32()? as usize;
+            let mut section = Reader::new(reader.bytes(len)?);
+            match id {
+                0 => {
+                    let name = section.name()?;
+                    module.custom.insert(name, section.bytes(len - section.pos)?.to_vec());
+                }
+                1 => {
+                    for _ in 0..section.u32()? {
+                        if section.byte()? != 0x60 {
+                            return Err("Ungültiger Funktionstyp".to_string());
+                        }
+                        let params = (0..section.u32()?).map(|_| section.val_type()).collect::<Result<_, _>>()?;
+                        let results = (0..section.u32()?).map(|_| section.val_type()).collect::<Result<_, _>>()?;
+                        module.types.push(FuncType { params, results });
+                    }
+                }
+                2 => {
+                    for _ in 0..section.u32()? {
+                        let module_name = section.name()?;
+                        let name = section.name()?;
+                        match section.byte()? {
+                            0x00 => module.imports.push(Import { module: module_name, name, type_index: section.u32()? }),
+                            _ => return Err(format!("Nur Funktions-Importe werden unterstützt ({}.{})", module_name, name)),
+                        }
+                    }
+                }
+                3 => {
+                    for _ in 0..section.u32()? {
+                        function_types.push(section.u32()?);
+                    }
+                }
+                4 => {
+                    for _ in 0..section.u32()? {
+                        section.val_type()?;
+                        module.table = Some(section.limits()?);
+                    }
+                }
+                5 => {
+                    for _ in 0..section.u32()? {
+                        module.memory = Some(section.limits()?);
+                    }
+                }
+                6 => {
+                    for _ in 0..section.u32()? {
+                        section.val_type()?;
+                        section.byte()?;
+                        module.globals.push(section.const_expr()?);
+                    }
+                }
+                7 => {
+                    for _ in 0..section.u32()? {
+                        let name = section.name()?;
+                        let kind = match section.byte()? {
+                            0 => ExportKind::Func,
+                            1 => ExportKind::Table,
+                            2 => ExportKind::Memory,
+                            _ => ExportKind::Global,
+                        };
+                        module.exports.insert(name, (kind, section.u32()?));
+                    }
+                }
+                8 => module.start = Some(section.u32()?),
+                9 => {
+                    for _ in 0..section.u32()? {
+                        module.elements.push(Self::element_segment(&mut section)?);
+                    }
+                }
+                10 => {
+                    let count = section.u32()? as usize;
+                    if count != function_types.len() {
+                        return Err("Anzahl der Funktionsrümpfe passt nicht zu den Deklarationen".to_string());
+                    }
+                    for &type_index in &function_types {
+                        let size = section.u32()? as usize;
+                        let mut body = Reader::new(section.bytes(size)?);
+                        let mut locals = Vec::new();
+                        for _ in 0..body.u32()? {
+                            let n = body.u32()?;
+                            if locals.len() + n as usize > 50_000 {
+                                return Err("Zu viele lokale Variablen".to_string());
+                            }
+                            let ty = body.val_type()?;
+                            locals.extend(std::iter::repeat_n(ty, n as usize));
+                        }
+                        let code = body.bytes(size - body.pos)?.to_vec();
+                        let blocks = scan_blocks(&code)?;
+                        module.functions.push(Function { type_index, locals, code, blocks });
+                    }
+                }
+                11 => {
+                    for _ in 0..section.u32()? {
+                        let offset = match section.u32()? {
+                            0 => Some(section.const_expr()?),
+                            1 => None,
+                            2 => {
+                                section.u32()?;
+                                Some(section.const_expr()?)
+                            }
+                            other => return Err(format!("Ungültiges Datensegment {}", other)),
+                        };
+                        let len = section.u32()? as usize;
+                        module.data.push(DataSegment { offset, bytes: section.bytes(len)?.to_vec() });
+                    }
+                }
+                12 => {}
+                other => return Err(format!("Unbekannte WASM-Sektion {}", other)),
+            }
+        }
+        for (index, function) in module.functions.iter().enumerate() {
+            if function.type_index as usize >= module.types.len() {
+                return Err(format!("Funktion {} verweist auf unbekannten Typ", index));
+            }
+        }
+        Ok(module)
+    }
+
+    fn element_segment(section: &mut Reader) -> Result<ElementSegment, String> {
+        let flags = section.u32()?;
+        let offset = match flags {
+            0 | 4 => Some(section.const_expr()?),
+            2 | 6 => {
+                section.u32()?;
+                Some(section.const_expr()?)
+            }
+            _ => None,
+        };
+        // elemkind bzw. Referenztyp
+        if matches!(flags, 1..=3) || matches!(flags, 5..=7) {
+            section.byte()?;
+        }
+        let mut functions = Vec::new();
+        for _ in 0..section.u32()? {
+            if flags & 4 == 0 {
+                functions.push(section.u32()?);
+            } else {
+                match section.const_expr()? {
+                    ConstExpr::Func(index) => functions.push(index),
+                    _ => functions.push(u32::MAX),
+                }
+            }
+        }
+        // Passive und deklarative Segmente werden nicht in die Tabelle geschrieben
+        let offset = if flags & 1 == 0 { offset } else { None };
+        Ok(ElementSegment { offset, functions })
+    }
+
+    pub fn function_type(&self, index: u32) -> Option<&FuncType> {
+        let imports = self.imports.len() as u32;
+        let type_index = if index < imports {
+            self.imports[index as usize].type_index
+        } else {
+            self.functions.get((index - imports) as usize)?.type_index
+        };
+        self.types.get(type_index as usize)
+    }
+
+    pub fn exported_function(&self, name: &str) -> Option<u32> {
+        match self.exports.get(name) {
+            Some((ExportKind::Func, index)) => Some(*index),
+            _ => None,
+        }
+    }
+}
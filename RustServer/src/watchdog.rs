@@ -0,0 +1,228 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::PathBuf;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Arc, Once};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::clock::UtcTime;
+use crate::chunkstorage::write_atomic;
+use crate::{console, restart, Server};
+
+const CRASH_REPORT_DIR: &str = "crash-reports";
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+// So lange wartet der Watchdog, bis ein Thread seinen Stack abgeliefert hat
+const CAPTURE_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WatchdogAction {
+    // Nur den Bericht schreiben, der Tick läuft weiter
+    None,
+    Kill,
+    // Startet die Binärdatei mit denselben Argumenten neu
+    Restart,
+}
+
+impl std::str::FromStr for WatchdogAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<WatchdogAction, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(WatchdogAction::None),
+            "kill" => Ok(WatchdogAction::Kill),
+            "restart" => Ok(WatchdogAction::Restart),
+            other => Err(format!("Unknown watchdog action '{}'", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for WatchdogAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            WatchdogAction::None => "none",
+            WatchdogAction::Kill => "kill",
+            WatchdogAction::Restart => "restart",
+        })
+    }
+}
+
+// Vom Tick-Thread fortgeschrieben, vom Watchdog-Thread gelesen
+#[derive(Debug)]
+pub struct Watchdog {
+    base: Instant,
+    // Millisekunden seit base plus eins, 0 zwischen zwei Ticks
+    tick_started: AtomicU64,
+    tick: AtomicU64,
+    tick_thread: AtomicI32,
+    armed: AtomicBool,
+}
+
+impl Watchdog {
+    pub fn new() -> Watchdog {
+        Watchdog { base: Instant::now(), tick_started: AtomicU64::new(0), tick: AtomicU64::new(0), tick_thread: AtomicI32::new(0), armed: AtomicBool::new(true) }
+    }
+
+    pub fn tick_started(&self, tick: u64) {
+        self.tick.store(tick, Ordering::Relaxed);
+        self.tick_thread.store(unsafe { libc::gettid() }, Ordering::Relaxed);
+        self.tick_started.store(self.base.elapsed().as_millis() as u64 + 1, Ordering::Relaxed);
+    }
+
+    pub fn tick_finished(&self) {
+        self.tick_started.store(0, Ordering::Relaxed);
+    }
+
+    // Beim Stoppen darf das Speichern länger dauern als ein Tick
+    pub fn disarm(&self) {
+        self.armed.store(false, Ordering::Relaxed);
+    }
+
+    // Laufender Tick und wie lange er schon dauert
+    fn current(&self) -> Option<(u64, Duration)> {
+        let started = self.tick_started.load(Ordering::Relaxed);
+        if started == 0 || !self.armed.load(Ordering::Relaxed) {
+            return None;
+        }
+        let running = self.base.elapsed().saturating_sub(Duration::from_millis(started - 1));
+        Some((self.tick.load(Ordering::Relaxed), running))
+    }
+}
+
+// Überwacht die Tick-Schleife, solange max-tick-time größer als 0 ist
+pub fn start(server: &Arc<Server>) {
+    if server.config.max_tick_time <= 0 {
+        return;
+    }
+    let limit = Duration::from_millis(server.config.max_tick_time as u64);
+    let server = Arc::clone(server);
+    thread::Builder::new()
+        .name("watchdog".to_string())
+        .spawn(move || {
+            // Jeden hängenden Tick nur einmal melden
+            let mut reported = 0;
+            loop {
+                thread::sleep(CHECK_INTERVAL);
+                let Some((tick, running)) = server.watchdog.current() else { continue };
+                if running >= limit && tick != reported {
+                    reported = tick;
+                    tick_hung(&server, tick, running);
+                }
+            }
+        })
+        .expect("Konnte Watchdog-Thread nicht starten");
+}
+
+fn tick_hung(server: &Server, tick: u64, running: Duration) {
+    error!("Tick {} läuft seit {:.1}s (max-tick-time {}ms), der Server hängt vermutlich", tick, running.as_secs_f64(), server.config.max_tick_time);
+    let report = crash_report(tick, running, server.watchdog.tick_thread.load(Ordering::Relaxed));
+    error!("{}", report);
+    match write_crash_report(&report) {
+        Ok(path) => error!("Absturzbericht gespeichert unter {}", path.display()),
+        Err(e) => error!("{}", e),
+    }
+    match server.config.watchdog_action {
+        WatchdogAction::None => {}
+        WatchdogAction::Kill => {
+            error!("Beende den Server, ohne zu speichern");
+            console::LOG.release_stdout();
+            std::process::exit(1);
+        }
+        WatchdogAction::Restart => {
+            error!("Starte den Server neu, ohne zu speichern");
+            restart::reexec();
+        }
+    }
+}
+
+fn crash_report(tick: u64, running: Duration, tick_thread: i32) -> String {
+    let now = UtcTime::now();
+    let mut report = format!(
+        "---- Minecraft Crash Report ----\n// Watchdog\n\nTime: {} {} UTC\nDescription: Watching Server\n\n\
+         A single server tick took {:.2} seconds (should be max {:.2})\nTick: {}\n\nThreads:\n",
+        now.date(),
+        now.time(),
+        running.as_secs_f64(),
+        crate::tick::TICK_DURATION.as_secs_f64(),
+        tick
+    );
+    let mut threads = threads();
+    // Der hängende Tick-Thread zuerst
+    threads.sort_by_key(|(tid, _)| *tid != tick_thread);
+    for (tid, name) in threads {
+        let note = if tid == tick_thread { ", ticking" } else { "" };
+        let stack = capture_stack(tid).map_or_else(|| "\tnot available\n".to_string(), |stack| format!("{}\n", stack));
+        report.push_str(&format!("\n\"{}\" (tid {}{}):\n{}", name, tid, note, stack));
+    }
+    report
+}
+
+// crash-reports/crash-2024-05-01_12.00.00-server.txt wie bei Vanilla
+fn write_crash_report(report: &str) -> Result<PathBuf, String> {
+    let now = UtcTime::now();
+    let path = PathBuf::from(CRASH_REPORT_DIR).join(format!("crash-{}_{}-server.txt", now.date(), now.time().replace(':', ".")));
+    write_atomic(&path, report.as_bytes())?;
+    Ok(path)
+}
+
+// Alle Threads des Prozesses außer dem eigenen, mit Namen
+fn threads() -> Vec<(i32, String)> {
+    let own = unsafe { libc::gettid() };
+    let Ok(entries) = fs::read_dir("/proc/self/task") else { return Vec::new() };
+    let mut threads: Vec<(i32, String)> = entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse().ok())
+        .filter(|tid| *tid != own)
+        .map(|tid| {
+            let name = fs::read_to_string(format!("/proc/self/task/{}/comm", tid)).map(|n| n.trim().to_string()).unwrap_or_default();
+            (tid, name)
+        })
+        .collect();
+    threads.sort();
+    threads
+}
+
+static CAPTURE_HANDLER: Once = Once::new();
+static CAPTURE_TARGET: AtomicI32 = AtomicI32::new(0);
+static CAPTURED: AtomicPtr<(i32, Backtrace)> = AtomicPtr::new(ptr::null_mut());
+
+// Läuft im Signal-Handler des angefragten Threads. Das ist nicht async-signal-safe: hängt der Thread gerade in malloc,
+// kommt nie etwas an und der Watchdog macht nach CAPTURE_TIMEOUT mit dem nächsten weiter
+extern "C" fn capture_handler(_: libc::c_int) {
+    let tid = unsafe { libc::gettid() };
+    if CAPTURE_TARGET.load(Ordering::SeqCst) != tid {
+        return;
+    }
+    let captured = Box::into_raw(Box::new((tid, Backtrace::force_capture())));
+    if CAPTURED.compare_exchange(ptr::null_mut(), captured, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        drop(unsafe { Box::from_raw(captured) });
+    }
+}
+
+// Lässt den Thread per Signal seinen eigenen Stack aufzeichnen; Rust kann fremde Threads nicht abwickeln
+fn capture_stack(tid: i32) -> Option<Backtrace> {
+    CAPTURE_HANDLER.call_once(|| unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = capture_handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGRTMIN(), &action, ptr::null_mut());
+    });
+    CAPTURE_TARGET.store(tid, Ordering::SeqCst);
+    if unsafe { libc::syscall(libc::SYS_tgkill, libc::getpid(), tid, libc::SIGRTMIN()) } != 0 {
+        return None;
+    }
+    let deadline = Instant::now() + CAPTURE_TIMEOUT;
+    while Instant::now() < deadline {
+        let captured = CAPTURED.swap(ptr::null_mut(), Ordering::SeqCst);
+        if !captured.is_null() {
+            let (from, backtrace) = *unsafe { Box::from_raw(captured) };
+            // Verspätete Antwort eines vorher angefragten Threads
+            if from == tid {
+                return Some(backtrace);
+            }
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    None
+}
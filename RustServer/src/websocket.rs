@@ -0,0 +1,118 @@
+use std::io::{Read, Write};
+use crate::hash::{base64_encode, sha1};
+
+// Fest vorgegeben in RFC 6455, Abschnitt 1.3
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+pub const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+pub const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+pub const OPCODE_PONG: u8 = 0xA;
+
+#[derive(Debug)]
+pub enum Frame {
+    Text(String),
+    Binary,
+    Ping(Vec<u8>),
+    Pong,
+    Close,
+}
+
+pub fn accept_key(key: &str) -> String {
+    base64_encode(&sha1(format!("{}{}", key.trim(), HANDSHAKE_GUID).as_bytes()))
+}
+
+pub fn handshake_response(key: &str) -> String {
+    format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    )
+}
+
+// Liest die nächste vollständige Nachricht; fragmentierte Nachrichten werden zusammengesetzt,
+// Steuer-Frames dürfen dazwischen liegen
+pub fn read_frame(stream: &mut impl Read) -> Result<Frame, String> {
+    let mut message: Option<(u8, Vec<u8>)> = None;
+    loop {
+        let mut head = [0u8; 2];
+        stream.read_exact(&mut head).map_err(|e| format!("Verbindung unterbrochen: {}", e))?;
+        let fin = head[0] & 0x80 != 0;
+        let opcode = head[0] & 0x0F;
+        if head[0] & 0x70 != 0 {
+            return Err("Reservierte Bits gesetzt".to_string());
+        }
+        // Clients müssen jeden Frame maskieren
+        if head[1] & 0x80 == 0 {
+            return Err("Unmaskierter Frame vom Client".to_string());
+        }
+        let length = match head[1] & 0x7F {
+            126 => {
+                let mut buf = [0u8; 2];
+                stream.read_exact(&mut buf).map_err(|e| e.to_string())?;
+                u16::from_be_bytes(buf) as u64
+            }
+            127 => {
+                let mut buf = [0u8; 8];
+                stream.read_exact(&mut buf).map_err(|e| e.to_string())?;
+                u64::from_be_bytes(buf)
+            }
+            n => n as u64,
+        };
+        let buffered = message.as_ref().map_or(0, |(_, data)| data.len());
+        if length > (MAX_MESSAGE_SIZE - buffered) as u64 {
+            return Err(format!("Nachricht größer als {} Bytes", MAX_MESSAGE_SIZE));
+        }
+        let mut mask = [0u8; 4];
+        stream.read_exact(&mut mask).map_err(|e| e.to_string())?;
+        let mut payload = vec![0u8; length as usize];
+        stream.read_exact(&mut payload).map_err(|e| e.to_string())?;
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+
+        if opcode >= OPCODE_CLOSE {
+            if !fin || payload.len() > 125 {
+                return Err("Ungültiger Steuer-Frame".to_string());
+            }
+            match opcode {
+                OPCODE_CLOSE => return Ok(Frame::Close),
+                OPCODE_PING => return Ok(Frame::Ping(payload)),
+                OPCODE_PONG => return Ok(Frame::Pong),
+                _ => return Err(format!("Unbekannter Opcode {}", opcode)),
+            }
+        }
+        match (&mut message, opcode) {
+            (None, OPCODE_TEXT | OPCODE_BINARY) => message = Some((opcode, payload)),
+            (Some((_, data)), OPCODE_CONTINUATION) => data.extend(payload),
+            _ => return Err("Unerwarteter Fragment-Frame".to_string()),
+        }
+        if fin {
+            let (opcode, data) = message.take().unwrap_or_default();
+            return match opcode {
+                OPCODE_TEXT => String::from_utf8(data).map(Frame::Text).map_err(|_| "Textnachricht ist kein UTF-8".to_string()),
+                _ => Ok(Frame::Binary),
+            };
+        }
+    }
+}
+
+// Server-Frames werden nie maskiert und nie fragmentiert
+pub fn write_frame(stream: &mut impl Write, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        n if n < 126 => frame.push(n as u8),
+        n if n <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend((n as u16).to_be_bytes());
+        }
+        n => {
+            frame.push(127);
+            frame.extend((n as u64).to_be_bytes());
+        }
+    }
+    frame.extend(payload);
+    stream.write_all(&frame)
+}
@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+use byteorder::ReadBytesExt;
+use crate::blockentity::BlockEntity;
+use crate::dimension::Dimension;
+use crate::error::ServerError;
+use crate::item::ItemStack;
+use crate::protocol::clientbound;
+use crate::protocol::types::{encode_position, write_varint_to_vec};
+use crate::text::Text;
+use crate::anvil::{self, Anvil};
+use crate::recipe::RecipeRegistry;
+use crate::stonecutter::{self, Stonecutter};
+use crate::enchanting::{self, Enchanting};
+use crate::{block, click, entity, farming, furnace, map, merchant, smithing, worldevent, Connection, Player, World};
+
+// Register minecraft:menu, in 1.20.4 und 1.21.1 gleich
+const MENU_GENERIC_9X3: i32 = 2;
+const MENU_GENERIC_9X6: i32 = 5;
+const MENU_ANVIL: i32 = 8;
+const MENU_CRAFTING: i32 = 12;
+const MENU_ENCHANTMENT: i32 = 13;
+const MENU_FURNACE: i32 = 14;
+const MENU_MERCHANT: i32 = 19;
+const MENU_SMITHING: i32 = 21;
+const MENU_CARTOGRAPHY_TABLE: i32 = 23;
+const MENU_STONECUTTER: i32 = 24;
+pub const ENDER_CHEST_SLOTS: usize = 27;
+// Werkbank: Ergebnis und 3x3-Gitter
+pub const CRAFTING_SLOTS: usize = 10;
+// Entfernung zur Blockmitte, ab der Vanilla einen Container schließt
+const MAX_DISTANCE: f64 = 8.0;
+// Block Action von Truhen: Zahl der Betrachter; solange sie größer 0 ist, steht der Deckel offen
+const ACTION_VIEWERS: u8 = 1;
+const LID_VOLUME: f32 = 0.5;
+// Vanilla zählt Fenster-IDs von 1 bis 100 durch, 0 ist das eigene Inventar
+const MAX_WINDOW_ID: u8 = 100;
+
+// Was hinter einem geöffneten Fenster steht
+#[derive(Debug, Clone, PartialEq)]
+pub enum Container {
+    // Eine Truhe oder beide Hälften einer Doppeltruhe, die obere Hälfte des Fensters zuerst
+    Chest(Vec<(i32, i32, i32)>),
+    Barrel((i32, i32, i32)),
+    // Zutat, Brennstoff und Ergebnis liegen im Block-Entity
+    Furnace((i32, i32, i32)),
+    // Zeigt den eigenen Endertruhen-Inhalt des Spielers
+    EnderChest((i32, i32, i32)),
+    // Die Items liegen im Fenster selbst und fallen beim Schließen zurück ins Inventar
+    Anvil((i32, i32, i32)),
+    CraftingTable((i32, i32, i32)),
+    CartographyTable((i32, i32, i32)),
+    Stonecutter((i32, i32, i32)),
+    SmithingTable((i32, i32, i32)),
+    EnchantingTable((i32, i32, i32)),
+    // Dorfbewohner oder fahrender Händler mit seiner Entity-ID
+    Merchant(i32),
+}
+
+impl Container {
+    fn positions(&self) -> Vec<(i32, i32, i32)> {
+        match self {
+            Container::Chest(halves) => halves.clone(),
+            Container::Barrel(position)
+            | Container::Furnace(position)
+            | Container::EnderChest(position)
+            | Container::Anvil(position)
+            | Container::CraftingTable(position)
+            | Container::CartographyTable(position)
+            | Container::Stonecutter(position)
+            | Container::SmithingTable(position)
+            | Container::EnchantingTable(position) => vec![*position],
+            Container::Merchant(_) => Vec::new(),
+        }
+    }
+
+    // Arbeitsblöcke ohne Block-Entity und Händler, deren Slots nur im Fenster liegen
+    pub fn is_workstation(&self) -> bool {
+        matches!(self, Container::Anvil(_) | Container::CraftingTable(_) | Container::CartographyTable(_) | Container::Stonecutter(_) | Container::SmithingTable(_) | Container::EnchantingTable(_) | Container::Merchant(_)
+        )
+    }
+
+    fn menu(&self) -> i32 {
+        match self {
+            Container::Chest(halves) if halves.len() == 2 => MENU_GENERIC_9X6,
+            Container::Furnace(_) => MENU_FURNACE,
+            Container::Anvil(_) => MENU_ANVIL,
+            Container::CraftingTable(_) => MENU_CRAFTING,
+            Container::CartographyTable(_) => MENU_CARTOGRAPHY_TABLE,
+            Container::Stonecutter(_) => MENU_STONECUTTER,
+            Container::SmithingTable(_) => MENU_SMITHING,
+            Container::EnchantingTable(_) => MENU_ENCHANTMENT,
+            Container::Merchant(_) => MENU_MERCHANT,
+            _ => MENU_GENERIC_9X3,
+        }
+    }
+
+    fn title(&self) -> Text {
+        Text::translate(match self {
+            Container::Chest(halves) if halves.len() == 2 => "container.chestDouble",
+            Container::Chest(_) => "container.chest",
+            Container::Barrel(_) => "container.barrel",
+            Container::Furnace(_) => "container.furnace",
+            Container::EnderChest(_) => "container.enderchest",
+            Container::Anvil(_) => "container.repair",
+            Container::CraftingTable(_) => "container.crafting",
+            Container::CartographyTable(_) => "container.cartography_table",
+            Container::Stonecutter(_) => "container.stonecutter",
+            Container::SmithingTable(_) => "container.upgrade",
+            Container::EnchantingTable(_) => "container.enchant",
+            Container::Merchant(_) => "entity.minecraft.villager",
+        })
+    }
+
+    // Aktueller Inhalt aus den Block-Entities bzw. der Endertruhe des Spielers
+    fn contents(&self, world: &World, player: &Player) -> Vec<ItemStack> {
+        match self {
+            Container::EnderChest(_) => player.ender_chest.clone(),
+            _ if self.is_workstation() => Vec::new(),
+            _ => self.positions().into_iter().flat_map(|position| world.block_entity(position).map(|e| e.items().to_vec()).unwrap_or_default()).collect(),
+        }
+    }
+
+    // Schreibt den Inhalt nach einem Klick zurück, bei Doppeltruhen aufgeteilt auf beide Hälften
+    fn store(&self, world: &mut World, ender_chest: &mut [ItemStack], contents: &[ItemStack]) {
+        match self {
+            Container::EnderChest(_) => return ender_chest.clone_from_slice(&contents[..ender_chest.len()]),
+            _ if self.is_workstation() => return,
+            _ => {}
+        }
+        let mut rest = contents;
+        for position in self.positions() {
+            let Some(entity) = world.block_entity_mut(position) else { continue };
+            let items = entity.items_mut();
+            let (own, next) = rest.split_at(items.len().min(rest.len()));
+            if items != own {
+                items[..own.len()].clone_from_slice(own);
+                world.dirty_chunks.insert((position.0 >> 4, position.2 >> 4));
+                furnace::wake(world, position);
+            }
+            rest = next;
+        }
+    }
+}
+
+// Ein offenes Fenster eines Spielers
+#[derive(Debug, Clone)]
+pub struct Window {
+    pub id: u8,
+    pub container: Container,
+    pub dimension: Dimension,
+    // Wird bei jedem Versand des Inhalts erhöht; der Client schickt sie bei Klicks zurück
+    pub state_id: i32,
+    // Zuletzt geschickter Inhalt, um Änderungen durch andere Spieler oder Trichter zu erkennen
+    sent: Vec<ItemStack>,
+    // Slots der Arbeitsblöcke, die nur im Fenster liegen, und der Zustand von Amboss, Steinsäge bzw. Zaubertisch
+    pub slots: Vec<ItemStack>,
+    pub anvil: Option<Anvil>,
+    pub stonecutter: Option<Stonecutter>,
+    pub enchanting: Option<Enchanting>,
+    // Zuletzt gewähltes Angebot im Handelsfenster
+    pub trade: usize,
+}
+
+impl Window {
+    pub fn contents(&self, world: &World, player: &Player) -> Vec<ItemStack> {
+        if self.container.is_workstation() {
+            return self.slots.clone();
+        }
+        self.container.contents(world, player)
+    }
+
+    pub fn store(&mut self, world: &mut World, ender_chest: &mut [ItemStack], contents: &[ItemStack]) {
+        if self.container.is_workstation() {
+            return self.slots = contents.to_vec();
+        }
+        self.container.store(world, ender_chest, contents)
+    }
+
+    // Inhalt, den der Client nach einem Klick bereits selbst kennt
+    pub fn set_sent(&mut self, contents: Vec<ItemStack>) {
+        self.sent = contents;
+    }
+}
+
+// Truhe, Doppeltruhe, Fass, Ofen, Endertruhe oder ein Arbeitsblock an der Stelle. Eine Doppeltruhe besteht aus zwei gleich
+// ausgerichteten Hälften; die Hälfte mit type=right ist oben im Fenster
+fn container_at(world: &World, (x, y, z): (i32, i32, i32)) -> Option<Container> {
+    let state = world.get_block(x, y, z);
+    let position = (x, y, z);
+    match block::name(state) {
+        "minecraft:anvil" | "minecraft:chipped_anvil" | "minecraft:damaged_anvil" => return Some(Container::Anvil(position)),
+        "minecraft:crafting_table" => return Some(Container::CraftingTable(position)),
+        "minecraft:cartography_table" => return Some(Container::CartographyTable(position)),
+        "minecraft:stonecutter" => return Some(Container::Stonecutter(position)),
+        "minecraft:smithing_table" => return Some(Container::SmithingTable(position)),
+        "minecraft:enchanting_table" => return Some(Container::EnchantingTable(position)),
+        _ => {}
+    }
+    world.block_entity(position)?;
+    match block::name(state) {
+        "minecraft:chest" => {
+            let facing = block::property(state, "facing")?;
+            let kind = block::property(state, "type")?;
+            // Die Partnerhälfte liegt bei type=left im Uhrzeigersinn neben der Blickrichtung
+            let (dx, dz) = match (facing, kind) {
+                ("north", "left") | ("south", "right") => (1, 0),
+                ("north", "right") | ("south", "left") => (-1, 0),
+                ("east", "left") | ("west", "right") => (0, 1),
+                ("east", "right") | ("west", "left") => (0, -1),
+                _ => return Some(Container::Chest(vec![position])),
+            };
+            let partner = (x + dx, y, z + dz);
+            let other = world.get_block(partner.0, partner.1, partner.2);
+            let opposite = if kind == "left" { "right" } else { "left" };
+            if block::name(other) != "minecraft:chest" || block::property(other, "facing") != Some(facing) || block::property(other, "type") != Some(opposite) || world.block_entity(partner).is_none() {
+                return Some(Container::Chest(vec![position]));
+            }
+            Some(Container::Chest(if kind == "right" { vec![position, partner] } else { vec![partner, position] }))
+        }
+        "minecraft:barrel" => Some(Container::Barrel(position)),
+        "minecraft:furnace" => Some(Container::Furnace(position)),
+        "minecraft:ender_chest" => Some(Container::EnderChest(position)),
+        _ => None,
+    }
+}
+
+// Truhen gehen nicht auf, wenn ein fester Block auf einer Hälfte liegt
+fn is_blocked(world: &World, container: &Container) -> bool {
+    match container {
+        Container::Barrel(_) | Container::Furnace(_) => false,
+        _ if container.is_workstation() => false,
+        _ => container.positions().into_iter().any(|(x, y, z)| block::is_solid(world.get_block(x, y + 1, z))),
+    }
+}
+
+// Open Screen und Inhalt. true, wenn der angeklickte Block ein Container ist, auch wenn er blockiert ist
+pub fn open(players: &mut [Player], world: &World, index: usize, position: (i32, i32, i32)) -> bool {
+    let Some(container) = container_at(world, position) else { return false };
+    if is_blocked(world, &container) {
+        return true;
+    }
+    let title = container.title();
+    show(&mut players[index], world, container, title);
+    true
+}
+
+// Öffnet ein neues Fenster für den Container; false, wenn der Client nicht erreichbar ist
+pub fn show(player: &mut Player, world: &World, container: Container, title: Text) -> bool {
+    player.window_id = player.window_id % MAX_WINDOW_ID + 1;
+    let slots = match container {
+        Container::Anvil(_) => anvil::SLOTS,
+        Container::CraftingTable(_) => CRAFTING_SLOTS,
+        Container::CartographyTable(_) => map::TABLE_SLOTS,
+        Container::Stonecutter(_) => stonecutter::SLOTS,
+        Container::SmithingTable(_) => smithing::SLOTS,
+        Container::EnchantingTable(_) => enchanting::SLOTS,
+        Container::Merchant(_) => merchant::SLOTS,
+        _ => 0,
+    };
+    let anvil = matches!(container, Container::Anvil(_)).then(Anvil::default);
+    let stonecutter = matches!(container, Container::Stonecutter(_)).then(Stonecutter::default);
+    let enchanting = match container {
+        Container::EnchantingTable(position) => Some(Enchanting::new(enchanting::bookshelves(world, position), player.enchantment_seed)),
+        _ => None,
+    };
+    let mut window = Window { id: player.window_id, container, dimension: world.dimension, state_id: 0, sent: Vec::new(), slots: vec![ItemStack::empty(); slots], anvil, stonecutter, enchanting, trade: 0 };
+    let mut packet_data = write_varint_to_vec(window.id as i32);
+    packet_data.extend(write_varint_to_vec(window.container.menu()));
+    packet_data.extend(title.to_nbt());
+    if player.connection.send(clientbound::OPEN_SCREEN, &packet_data).is_err() {
+        return false;
+    }
+    window.sent = window.contents(world, player);
+    let _ = send_content(&player.connection, &mut window, player);
+    if let Some(enchanting) = &window.enchanting {
+        let _ = enchanting.send(&player.connection, window.id);
+    }
+    debug!("{} öffnet {:?}", player.username, window.container);
+    player.window = Some(window);
+    true
+}
+
+// Set Container Content: Container-Slots, dann Hauptinventar und Schnellleiste des Spielers
+pub fn send_content(connection: &Connection, window: &mut Window, player: &Player) -> Result<(), String> {
+    window.state_id = window.state_id.wrapping_add(1);
+    let storage = player.inventory.storage();
+    let mut packet_data = vec![window.id];
+    packet_data.extend(write_varint_to_vec(window.state_id));
+    packet_data.extend(write_varint_to_vec((window.sent.len() + storage.len()) as i32));
+    for stack in window.sent.iter().chain(storage) {
+        packet_data.extend(stack.encode(connection.version));
+    }
+    packet_data.extend(player.carried.encode(connection.version));
+    connection.send(clientbound::SET_CONTAINER_CONTENT, &packet_data)
+}
+
+// Set Container Slot für einen Slot des Fensters, gezählt wie in Set Container Content
+pub fn send_slot(connection: &Connection, window: &Window, slot: usize, stack: &ItemStack) -> Result<(), String> {
+    let mut packet_data = vec![window.id];
+    packet_data.extend(write_varint_to_vec(window.state_id));
+    packet_data.extend((slot as i16).to_be_bytes());
+    packet_data.extend(stack.encode(connection.version));
+    connection.send(clientbound::SET_CONTAINER_SLOT, &packet_data)
+}
+
+fn send_close(connection: &Connection, id: u8) -> Result<(), String> {
+    connection.send(clientbound::CLOSE_CONTAINER, &[id])
+}
+
+// Schließt das Fenster von Serverseite, etwa beim Tod; Mauszeiger und Eingaben gehen zurück ins Inventar
+pub fn close(players: &mut [Player], world: &mut World, index: usize) {
+    let window = players[index].window.take();
+    if let Some(window) = &window {
+        let _ = send_close(&players[index].connection, window.id);
+    }
+    click::return_items(players, world, index, window);
+}
+
+// Close Container vom Client; Klicks in ein altes Fenster danach werden verworfen. Auch das eigene Inventar wird
+// geschlossen, dabei fallen Cursor und Crafting-Gitter zurück ins Inventar
+pub fn handle_close(players: &mut [Player], world: &mut World, player: &Player, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let id = cursor.read_u8()?;
+    let Some(index) = players.iter().position(|p| p.uuid == player.uuid) else { return Ok(()) };
+    let p = &mut players[index];
+    if id == 0 && p.window.is_none() {
+        click::return_items(players, world, index, None);
+    } else if p.window.as_ref().is_some_and(|w| w.id == id) {
+        let window = p.window.take();
+        click::return_items(players, world, index, window);
+    }
+    Ok(())
+}
+
+// Click Container Button: Knöpfe im Fenster, die Rezeptauswahl der Steinsäge und die drei Verzauberungen des
+// Zaubertischs
+pub fn handle_button(players: &mut [Player], world: &World, player: &Player, recipes: &RecipeRegistry, cursor: &mut std::io::Cursor<Vec<u8>>) -> Result<(), ServerError> {
+    let id = cursor.read_u8()?;
+    let button = cursor.read_u8()?;
+    let Some(p) = players.iter_mut().find(|p| p.uuid == player.uuid) else { return Ok(()) };
+    let Some(mut window) = p.window.take() else { return Ok(()) };
+    let mut enchanted = None;
+    if window.id == id && window.dimension == world.dimension {
+        match window.container.clone() {
+            Container::Stonecutter(_) => stonecutter::select(p, &mut window, recipes, button),
+            Container::EnchantingTable(position) if enchanting::enchant(p, &mut window, button) => enchanted = Some(position),
+            _ => {}
+        }
+    }
+    p.window = Some(window);
+    if let Some(position) = enchanted {
+        farming::play_sound(players, world, enchanting::USE_SOUND, position);
+    }
+    Ok(())
+}
+
+// Mitte des Containers, solange er noch steht: die Blockmitte bzw. der lebende Händler
+fn anchor(world: &World, container: &Container) -> Option<(f64, f64, f64)> {
+    if let Container::Merchant(entity_id) = container {
+        return world.mobs.iter().find(|m| m.entity_id == *entity_id && m.health > 0.0).map(|mob| mob.position);
+    }
+    let first = container.positions()[0];
+    (container_at(world, first).as_ref() == Some(container)).then_some((first.0 as f64 + 0.5, first.1 as f64 + 0.5, first.2 as f64 + 0.5))
+}
+
+// Ein Tick der Fenster in einer Welt: schließt Fenster, deren Container abgebaut wurde oder deren Händler fort ist,
+// die der Spieler verlassen hat oder die zu weit weg sind, schickt geänderte Inhalte neu und bewegt die Truhendeckel
+pub fn tick(players: &mut [Player], world: &mut World) {
+    for index in 0..players.len() {
+        let player = &mut players[index];
+        let Some(mut window) = player.window.take() else { continue };
+        if window.dimension != world.dimension {
+            player.window = Some(window);
+            continue;
+        }
+        let (x, y, z) = player.position;
+        let distance = anchor(world, &window.container).map(|center| ((x - center.0).powi(2) + (y - center.1).powi(2) + (z - center.2).powi(2)).sqrt());
+        if player.dimension != window.dimension || distance.is_none_or(|distance| distance > MAX_DISTANCE) {
+            let _ = send_close(&player.connection, window.id);
+            if player.dimension == window.dimension {
+                click::return_items(players, world, index, Some(window));
+            }
+            continue;
+        }
+        let contents = window.contents(world, player);
+        if contents != window.sent {
+            window.sent = contents;
+            let _ = send_content(&player.connection, &mut window, player);
+        }
+        player.window = Some(window);
+    }
+    update_lids(players, world);
+}
+
+// Klang beim Öffnen oder Schließen des Deckels. Eine Doppeltruhe klingt wie bei Vanilla nur einmal aus ihrer Mitte,
+// dafür gibt die rechte Hälfte den Versatz zur linken an
+fn lid_sound(state: u16, open: bool) -> Option<(&'static str, (f64, f64))> {
+    let sound = match (block::name(state), open) {
+        ("minecraft:ender_chest", true) => "minecraft:block.ender_chest.open",
+        ("minecraft:ender_chest", false) => "minecraft:block.ender_chest.close",
+        (_, true) => "minecraft:block.chest.open",
+        (_, false) => "minecraft:block.chest.close",
+    };
+    let offset = match (block::property(state, "type"), block::property(state, "facing")) {
+        (Some("left"), _) => return None,
+        (Some("right"), Some("north")) => (-0.5, 0.0),
+        (Some("right"), Some("south")) => (0.5, 0.0),
+        (Some("right"), Some("west")) => (0.0, 0.5),
+        (Some("right"), Some("east")) => (0.0, -0.5),
+        _ => (0.0, 0.0),
+    };
+    Some((sound, offset))
+}
+
+// Block Action an alle, die die Truhe sehen, sobald sich die Zahl ihrer Betrachter ändert
+fn update_lids(players: &[Player], world: &mut World) {
+    let mut viewers: HashMap<(i32, i32, i32), u8> = HashMap::new();
+    for window in players.iter().filter_map(|p| p.window.as_ref()).filter(|w| w.dimension == world.dimension) {
+        if matches!(window.container, Container::Barrel(_) | Container::Furnace(_)) || window.container.is_workstation() {
+            continue;
+        }
+        for position in window.container.positions() {
+            *viewers.entry(position).or_default() += 1;
+        }
+    }
+    let changed: Vec<((i32, i32, i32), u8)> = viewers
+        .iter()
+        .filter(|(position, count)| world.chest_viewers.get(position) != Some(count))
+        .map(|(&position, &count)| (position, count))
+        .chain(world.chest_viewers.keys().filter(|position| !viewers.contains_key(position)).map(|&position| (position, 0)))
+        .collect();
+    for ((x, y, z), count) in changed {
+        let state = world.get_block(x, y, z);
+        if !matches!(world.block_entity((x, y, z)), Some(BlockEntity::Chest { .. } | BlockEntity::EnderChest)) {
+            continue;
+        }
+        let center = (x as f64 + 0.5, y as f64, z as f64 + 0.5);
+        let previous = world.chest_viewers.get(&(x, y, z)).copied().unwrap_or(0);
+        let sound = match (previous, count) {
+            (0, 1..) => lid_sound(state, true),
+            (1.., 0) => lid_sound(state, false),
+            _ => None,
+        };
+        for player in players.iter().filter(|p| entity::in_view(p, world.dimension, center)) {
+            let Some(block_id) = block::registry_id_in(block::name(state), player.connection.version) else { continue };
+            let mut packet_data = encode_position(x, y, z).to_be_bytes().to_vec();
+            packet_data.extend([ACTION_VIEWERS, count]);
+            packet_data.extend(write_varint_to_vec(block_id));
+            let _ = player.connection.send(clientbound::BLOCK_ACTION, &packet_data);
+            if let Some((sound, offset)) = sound {
+                let position = (center.0 + offset.0, center.1 + 0.5, center.2 + offset.1);
+                let _ = worldevent::send_sound(&player.connection, sound, position, LID_VOLUME);
+            }
+        }
+    }
+    world.chest_viewers = viewers;
+}
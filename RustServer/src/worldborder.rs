@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use crate::json::Json;
+use crate::protocol::clientbound;
+use crate::protocol::types::{write_varint_to_vec, write_varlong_to_vec};
+use crate::Connection;
+
+pub const MIN_SIZE: f64 = 1.0;
+pub const MAX_SIZE: f64 = 59_999_968.0;
+// Weiter vom Ursprung darf der Mittelpunkt nicht liegen
+pub const MAX_CENTER: f64 = 29_999_984.0;
+const PORTAL_TELEPORT_BOUNDARY: i32 = 29_999_984;
+const MS_PER_TICK: i64 = 50;
+
+// Quadratische Weltgrenze. Beim Wachsen oder Schrumpfen interpoliert der Server linear zwischen from und
+// target; die Clients bekommen Start, Ziel und Restdauer und interpolieren selbst
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBorder {
+    pub center: (f64, f64),
+    from: f64,
+    target: f64,
+    lerp_total: i64,
+    lerp_remaining: i64,
+    pub warning_distance: i32,
+    pub warning_time: i32,
+}
+
+impl WorldBorder {
+    pub fn new() -> WorldBorder {
+        WorldBorder { center: (0.0, 0.0), from: MAX_SIZE, target: MAX_SIZE, lerp_total: 0, lerp_remaining: 0, warning_distance: 5, warning_time: 15 }
+    }
+
+    // Aktueller Durchmesser
+    pub fn size(&self) -> f64 {
+        if self.lerp_remaining <= 0 {
+            return self.target;
+        }
+        let progress = 1.0 - self.lerp_remaining as f64 / self.lerp_total as f64;
+        self.from + (self.target - self.from) * progress
+    }
+
+    pub fn set_size(&mut self, size: f64) {
+        self.from = size;
+        self.target = size;
+        self.lerp_total = 0;
+        self.lerp_remaining = 0;
+    }
+
+    // Vom aktuellen Durchmesser in duration Millisekunden auf size
+    pub fn lerp_to(&mut self, size: f64, duration: i64) {
+        if duration <= 0 {
+            return self.set_size(size);
+        }
+        self.from = self.size();
+        self.target = size;
+        self.lerp_total = duration;
+        self.lerp_remaining = duration;
+    }
+
+    pub fn tick(&mut self) {
+        if self.lerp_remaining > 0 {
+            self.lerp_remaining = (self.lerp_remaining - MS_PER_TICK).max(0);
+        }
+    }
+
+    // {"center_x": 0, "center_z": 0, "size": ..., "target": ..., "lerp_time": ms, ...} für level.json
+    pub fn to_json(self) -> Json {
+        let mut border = BTreeMap::new();
+        border.insert("center_x".to_string(), Json::Number(self.center.0));
+        border.insert("center_z".to_string(), Json::Number(self.center.1));
+        border.insert("size".to_string(), Json::Number(self.size()));
+        border.insert("target".to_string(), Json::Number(self.target));
+        border.insert("lerp_time".to_string(), Json::Number(self.lerp_remaining as f64));
+        border.insert("warning_distance".to_string(), Json::Number(self.warning_distance as f64));
+        border.insert("warning_time".to_string(), Json::Number(self.warning_time as f64));
+        Json::Object(border)
+    }
+
+    // Fehlende Einträge behalten ihren Standardwert; eine laufende Änderung setzt beim gespeicherten Stand fort
+    pub fn from_json(json: &Json) -> WorldBorder {
+        let mut border = WorldBorder::new();
+        let number = |key: &str| json.get(key).and_then(Json::as_f64);
+        border.center = (number("center_x").unwrap_or(0.0), number("center_z").unwrap_or(0.0));
+        let size = number("size").unwrap_or(MAX_SIZE).clamp(MIN_SIZE, MAX_SIZE);
+        border.set_size(size);
+        if let Some(target) = number("target") {
+            border.lerp_to(target.clamp(MIN_SIZE, MAX_SIZE), number("lerp_time").unwrap_or(0.0) as i64);
+        }
+        border.warning_distance = number("warning_distance").map_or(border.warning_distance, |d| d as i32);
+        border.warning_time = number("warning_time").map_or(border.warning_time, |t| t as i32);
+        border
+    }
+}
+
+// Initialize World Border beim Beitritt
+pub fn send_init(connection: &Connection, border: &WorldBorder) -> Result<(), String> {
+    let mut packet_data = border.center.0.to_be_bytes().to_vec();
+    packet_data.extend(border.center.1.to_be_bytes());
+    packet_data.extend(border.size().to_be_bytes());
+    packet_data.extend(border.target.to_be_bytes());
+    packet_data.extend(write_varlong_to_vec(border.lerp_remaining));
+    packet_data.extend(write_varint_to_vec(PORTAL_TELEPORT_BOUNDARY));
+    packet_data.extend(write_varint_to_vec(border.warning_distance));
+    packet_data.extend(write_varint_to_vec(border.warning_time));
+    connection.send(clientbound::INITIALIZE_WORLD_BORDER, &packet_data)
+}
+
+pub fn send_center(connection: &Connection, border: &WorldBorder) -> Result<(), String> {
+    let mut packet_data = border.center.0.to_be_bytes().to_vec();
+    packet_data.extend(border.center.1.to_be_bytes());
+    connection.send(clientbound::SET_BORDER_CENTER, &packet_data)
+}
+
+// Set Border Size bei sofortiger Änderung, sonst Set Border Lerp Size
+pub fn send_size(connection: &Connection, border: &WorldBorder) -> Result<(), String> {
+    if border.lerp_remaining <= 0 {
+        return connection.send(clientbound::SET_BORDER_SIZE, &border.target.to_be_bytes());
+    }
+    let mut packet_data = border.size().to_be_bytes().to_vec();
+    packet_data.extend(border.target.to_be_bytes());
+    packet_data.extend(write_varlong_to_vec(border.lerp_remaining));
+    connection.send(clientbound::SET_BORDER_LERP_SIZE, &packet_data)
+}
+
+pub fn send_warning_distance(connection: &Connection, border: &WorldBorder) -> Result<(), String> {
+    connection.send(clientbound::SET_BORDER_WARNING_DISTANCE, &write_varint_to_vec(border.warning_distance))
+}
+
+pub fn send_warning_time(connection: &Connection, border: &WorldBorder) -> Result<(), String> {
+    connection.send(clientbound::SET_BORDER_WARNING_DELAY, &write_varint_to_vec(border.warning_time))
+}
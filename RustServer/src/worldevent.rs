@@ -0,0 +1,100 @@
+use rand::Rng;
+use uuid::Uuid;
+use crate::dimension;
+use crate::protocol::types::{encode_position, write_string_to_vec, write_varint_to_vec};
+use crate::protocol::{assets, clientbound};
+use crate::{Connection, Server};
+
+// Sound Effect: Kategorie block für die Lautstärke-Regler des Clients
+const CATEGORY_BLOCKS: i32 = 4;
+
+// World Events, deren Klang oder Partikel der Client selbst kennt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorldEvent {
+    // Bruchpartikel und Abbauklang des Blockzustands
+    DestroyBlock(u16),
+    ExtinguishFire,
+    AnvilDestroyed,
+    AnvilUsed,
+    SmithingTableUsed,
+    // Grüne Partikel und Klang von Knochenmehl
+    BoneMeal,
+    // Rauch und Flammen am Spawner, wenn er Mobs setzt
+    SpawnerSpawn,
+    // Zerspringende Wurftränke in ihrer Farbe; mit Sofortwirkung glitzern die Partikel
+    SplashPotion(i32),
+    InstantSplashPotion(i32),
+}
+
+impl WorldEvent {
+    fn id(self) -> i32 {
+        match self {
+            WorldEvent::DestroyBlock(_) => 2001,
+            WorldEvent::ExtinguishFire => 1009,
+            WorldEvent::AnvilDestroyed => 1029,
+            WorldEvent::AnvilUsed => 1030,
+            WorldEvent::SmithingTableUsed => 1044,
+            WorldEvent::BoneMeal => 1505,
+            WorldEvent::SpawnerSpawn => 2004,
+            WorldEvent::SplashPotion(_) => 2002,
+            WorldEvent::InstantSplashPotion(_) => 2007,
+        }
+    }
+
+    fn send(self, connection: &Connection, (x, y, z): (i32, i32, i32)) -> Result<(), String> {
+        let data = match self {
+            WorldEvent::DestroyBlock(state) => assets::block_state(connection.version, state) as i32,
+            // Anzahl der Partikel
+            WorldEvent::BoneMeal => 15,
+            WorldEvent::SplashPotion(color) | WorldEvent::InstantSplashPotion(color) => color,
+            _ => 0,
+        };
+        let mut packet_data = self.id().to_be_bytes().to_vec();
+        packet_data.extend(encode_position(x, y, z).to_be_bytes());
+        packet_data.extend(data.to_be_bytes());
+        packet_data.push(0);
+        connection.send(clientbound::WORLD_EVENT, &packet_data)
+    }
+}
+
+// Ein am Ende des Ticks zu verschickendes World Event; wer es selbst ausgelöst hat, sieht es meist schon durch die
+// Vorhersage seines Clients
+#[derive(Debug, Clone, Copy)]
+pub struct QueuedEvent {
+    pub event: WorldEvent,
+    pub position: (i32, i32, i32),
+    pub except: Option<Uuid>,
+}
+
+// Schickt die World Events des Ticks wie Blockänderungen an alle, die den Chunk geladen haben.
+// Darf nur ohne gehaltene Sperren aufgerufen werden
+pub fn flush(server: &Server) {
+    let players = server.players.lock().unwrap();
+    for world in dimension::worlds(server) {
+        let mut world = world.lock().unwrap();
+        for queued in std::mem::take(&mut world.world_events) {
+            let (x, _, z) = queued.position;
+            let Some(viewers) = world.chunk_viewers.get(&(x >> 4, z >> 4)) else { continue };
+            let receivers = players.iter().filter(|p| p.dimension == world.dimension && viewers.contains(&p.uuid) && Some(p.uuid) != queued.except);
+            for player in receivers {
+                let _ = queued.event.send(&player.connection, queued.position);
+            }
+        }
+    }
+}
+
+// Sound Effect mit dem Namen des Klangs statt einer Registry-ID, wie Vanilla leicht in der Tonhöhe gestreut
+pub fn send_sound(connection: &Connection, sound: &str, (x, y, z): (f64, f64, f64), volume: f32) -> Result<(), String> {
+    let pitch = rand::thread_rng().gen_range(0.9f32..1.0);
+    let mut packet_data = write_varint_to_vec(0);
+    packet_data.extend(write_string_to_vec(sound));
+    packet_data.push(0);
+    packet_data.extend(write_varint_to_vec(CATEGORY_BLOCKS));
+    for v in [x, y, z] {
+        packet_data.extend(((v * 8.0) as i32).to_be_bytes());
+    }
+    packet_data.extend(volume.to_be_bytes());
+    packet_data.extend(pitch.to_be_bytes());
+    packet_data.extend(rand::random::<i64>().to_be_bytes());
+    connection.send(clientbound::SOUND_EFFECT, &packet_data)
+}
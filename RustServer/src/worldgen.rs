@@ -0,0 +1,614 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use crate::blockentity::BlockEntity;
+use crate::chunk;
+use crate::dimension::Dimension;
+use crate::json::{self, Json};
+use crate::{block, end, loot, Chunk, World};
+
+// Wie eine Welt ihre Chunks erzeugt; benannte Welten wählen ihn bei /world create, die Oberwelt über level-type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Generator {
+    Normal,
+    // Dasselbe Gelände mit anderen Parametern, siehe TerrainPreset
+    Amplified,
+    LargeBiomes,
+    Flat,
+    End,
+    // Ganz leer, etwa für Lobbys, Bauparzellen und Minispiele
+    Void,
+}
+
+impl Generator {
+    pub fn name(self) -> &'static str {
+        match self {
+            Generator::Normal => "normal",
+            Generator::Amplified => "amplified",
+            Generator::LargeBiomes => "large_biomes",
+            Generator::Flat => "flat",
+            Generator::End => "end",
+            Generator::Void => "void",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Generator> {
+        GENERATORS.into_iter().find(|g| g.name() == name)
+    }
+
+    // Für Fehlermeldungen und die Hilfe: normal, amplified, ...
+    pub fn names() -> String {
+        GENERATORS.map(Generator::name).join(", ")
+    }
+
+    fn terrain(self) -> &'static TerrainPreset {
+        match self {
+            Generator::Amplified => &AMPLIFIED_TERRAIN,
+            Generator::LargeBiomes => &LARGE_BIOMES_TERRAIN,
+            _ => &NORMAL_TERRAIN,
+        }
+    }
+}
+
+const GENERATORS: [Generator; 6] = [Generator::Normal, Generator::Amplified, Generator::LargeBiomes, Generator::Flat, Generator::End, Generator::Void];
+
+// Parameter des Geländegenerators: Grundhöhe, zufällige Unebenheit je Säule und darüber Hügel aus einem Rauschen, das
+// über die ganze Welt stetig ist und deshalb an Chunkgrenzen keine Kanten macht
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainPreset {
+    base_height: i32,
+    roughness: i32,
+    // Größte Höhe der Hügel über der Grundhöhe
+    hill_height: f64,
+    // Abstand der Gitterpunkte des Rauschens in Blöcken; größer heißt breitere Berge und Täler
+    hill_width: i32,
+    // Über 1 werden Täler flacher und Gipfel steiler
+    sharpness: f64,
+}
+
+// Ohne Hügel, damit bestehende Welten an neu generierten Chunks keine Kanten bekommen
+const NORMAL_TERRAIN: TerrainPreset = TerrainPreset { base_height: 64, roughness: 3, hill_height: 0.0, hill_width: 64, sharpness: 1.0 };
+// Steile Gipfel bis weit über die Wolken und Klippen an ihren Flanken
+const AMPLIFIED_TERRAIN: TerrainPreset = TerrainPreset { base_height: 64, roughness: 3, hill_height: 120.0, hill_width: 48, sharpness: 2.5 };
+// Alles viermal so weit: sanfte, breite Landschaften. Biome gibt es in der Oberwelt bisher nur eins
+const LARGE_BIOMES_TERRAIN: TerrainPreset = TerrainPreset { base_height: 64, roughness: 1, hill_height: 24.0, hill_width: 256, sharpness: 1.5 };
+
+// level-type nimmt auch die Namen von Vanilla wie minecraft:flat; default stammt aus älteren server.properties
+impl std::str::FromStr for Generator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Generator, String> {
+        let name = s.trim().to_ascii_lowercase();
+        match name.strip_prefix("minecraft:").unwrap_or(&name) {
+            "default" => Ok(Generator::Normal),
+            name => Generator::from_name(name).ok_or_else(|| format!("Unknown level type '{}', expected {}", s, Generator::names())),
+        }
+    }
+}
+
+impl std::fmt::Display for Generator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "minecraft:{}", self.name())
+    }
+}
+
+static OVERWORLD_GENERATOR: OnceLock<Generator> = OnceLock::new();
+
+// Wie der Weltordner einmal beim Start gesetzt; false, wenn er schon feststand
+pub fn set_overworld_generator(generator: Generator) -> bool {
+    OVERWORLD_GENERATOR.set(generator).is_ok()
+}
+
+pub fn overworld_generator() -> Generator {
+    OVERWORLD_GENERATOR.get().copied().unwrap_or(Generator::Normal)
+}
+
+// Worker-Pool für die Chunk-Generierung, damit weder Start noch Tick-Schleife blockieren
+pub struct ChunkGenPool {
+    requests: Option<Sender<(i32, i32)>>,
+    // Die Warteschlange der Worker, damit shut_down sie leeren kann
+    queue: Arc<Mutex<Receiver<(i32, i32)>>>,
+    completed: Receiver<Chunk>,
+    workers: Vec<JoinHandle<()>>,
+    pending: HashSet<(i32, i32)>,
+}
+
+impl ChunkGenPool {
+    pub fn new(threads: usize, seed: u64, dimension: Dimension) -> ChunkGenPool {
+        let (request_tx, request_rx) = mpsc::channel::<(i32, i32)>();
+        let (done_tx, done_rx) = mpsc::channel();
+        let request_rx = Arc::new(Mutex::new(request_rx));
+        let workers = (0..threads.max(1))
+            .map(|i| {
+                let request_rx = Arc::clone(&request_rx);
+                let done_tx = done_tx.clone();
+                thread::Builder::new()
+                    .name(format!("chunk-gen-{}", i))
+                    .spawn(move || loop {
+                        let request = request_rx.lock().unwrap().recv();
+                        let (x, z) = match request {
+                            Ok(coords) => coords,
+                            Err(_) => return,
+                        };
+                        let mut chunk = match dimension.generator() {
+                            generator @ (Generator::Normal | Generator::Amplified | Generator::LargeBiomes) => generate_chunk(seed, x, z, generator.terrain()),
+                            Generator::Flat => generate_flat_chunk(x, z),
+                            Generator::End => generate_end_chunk(seed, x, z),
+                            Generator::Void => generate_void_chunk(x, z),
+                        };
+                        fill_biomes(&mut chunk, dimension);
+                        if done_tx.send(chunk).is_err() {
+                            return;
+                        }
+                    })
+                    .expect("Konnte Chunk-Generator-Thread nicht starten")
+            })
+            .collect();
+        ChunkGenPool {
+            requests: Some(request_tx),
+            queue: request_rx,
+            completed: done_rx,
+            workers,
+            pending: HashSet::new(),
+        }
+    }
+
+    pub fn request(&mut self, x: i32, z: i32) {
+        if !self.pending.insert((x, z)) {
+            return;
+        }
+        if let Some(requests) = &self.requests {
+            let _ = requests.send((x, z));
+        }
+    }
+
+    // Liefert alle bisher fertigen Chunks, ohne zu warten
+    pub fn poll(&mut self) -> Vec<Chunk> {
+        let mut done = Vec::new();
+        while let Ok(chunk) = self.completed.try_recv() {
+            self.pending.remove(&(chunk.x, chunk.z));
+            done.push(chunk);
+        }
+        done
+    }
+
+    pub fn wait_next(&mut self) -> Option<Chunk> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let chunk = self.completed.recv().ok()?;
+        self.pending.remove(&(chunk.x, chunk.z));
+        Some(chunk)
+    }
+
+    // Verwirft noch nicht begonnene Anfragen und wartet, bis die Worker ihren letzten Chunk fertig haben; danach gehen
+    // Anfragen ins Leere
+    pub fn shut_down(&mut self) {
+        self.requests = None;
+        while self.queue.lock().unwrap().try_recv().is_ok() {}
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for ChunkGenPool {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+// Biom-Register (1.21.1), der Index ist die Protokoll-ID
+pub const BIOMES: &[&str] = &[
+    "badlands", "bamboo_jungle", "basalt_deltas", "beach", "birch_forest", "cherry_grove", "cold_ocean", "crimson_forest",
+    "dark_forest", "deep_cold_ocean", "deep_dark", "deep_frozen_ocean", "deep_lukewarm_ocean", "deep_ocean", "desert",
+    "dripstone_caves", "end_barrens", "end_highlands", "end_midlands", "eroded_badlands", "flower_forest", "forest",
+    "frozen_ocean", "frozen_peaks", "frozen_river", "grove", "ice_spikes", "jagged_peaks", "jungle", "lukewarm_ocean",
+    "lush_caves", "mangrove_swamp", "meadow", "mushroom_fields", "nether_wastes", "ocean", "old_growth_birch_forest",
+    "old_growth_pine_taiga", "old_growth_spruce_taiga", "plains", "river", "savanna", "savanna_plateau", "small_end_islands",
+    "snowy_beach", "snowy_plains", "snowy_slopes", "snowy_taiga", "soul_sand_valley", "sparse_jungle", "stony_peaks",
+    "stony_shore", "sunflower_plains", "swamp", "taiga", "the_end", "the_void", "warm_ocean", "warped_forest",
+    "windswept_forest", "windswept_gravelly_hills", "windswept_hills", "windswept_savanna", "wooded_badlands",
+];
+// Grundtemperatur der Biome wie bei Vanilla; was fehlt, ist so warm wie die Ebenen
+const TEMPERATURES: &[(&str, f32)] = &[
+    ("badlands", 2.0), ("bamboo_jungle", 0.95), ("basalt_deltas", 2.0), ("birch_forest", 0.6), ("cherry_grove", 0.5),
+    ("cold_ocean", 0.5), ("crimson_forest", 2.0), ("dark_forest", 0.7), ("deep_cold_ocean", 0.5), ("deep_frozen_ocean", 0.5),
+    ("deep_lukewarm_ocean", 0.5), ("deep_ocean", 0.5), ("desert", 2.0), ("end_barrens", 0.5), ("end_highlands", 0.5),
+    ("end_midlands", 0.5), ("eroded_badlands", 2.0), ("flower_forest", 0.7), ("forest", 0.7), ("frozen_ocean", 0.0),
+    ("frozen_peaks", -0.7), ("frozen_river", 0.0), ("grove", -0.2), ("ice_spikes", 0.0), ("jagged_peaks", -0.7), ("jungle", 0.95),
+    ("lukewarm_ocean", 0.5), ("lush_caves", 0.5), ("meadow", 0.5), ("mushroom_fields", 0.9), ("nether_wastes", 2.0), ("ocean", 0.5),
+    ("old_growth_birch_forest", 0.6), ("old_growth_pine_taiga", 0.3), ("old_growth_spruce_taiga", 0.25), ("river", 0.5),
+    ("savanna", 2.0), ("savanna_plateau", 2.0), ("small_end_islands", 0.5), ("snowy_beach", 0.05), ("snowy_plains", 0.0),
+    ("snowy_slopes", -0.3), ("snowy_taiga", -0.5), ("soul_sand_valley", 2.0), ("sparse_jungle", 0.95), ("stony_peaks", 1.0),
+    ("stony_shore", 0.2), ("taiga", 0.25), ("the_end", 0.5), ("the_void", 0.5), ("warm_ocean", 0.5), ("warped_forest", 2.0),
+    ("windswept_forest", 0.2), ("windswept_gravelly_hills", 0.2), ("windswept_hills", 0.2), ("windswept_savanna", 2.0),
+    ("wooded_badlands", 2.0),
+];
+const DEFAULT_TEMPERATURE: f32 = 0.8;
+// Hier fällt weder Regen noch Schnee
+const DRY_BIOMES: &[&str] = &[
+    "badlands", "basalt_deltas", "crimson_forest", "desert", "end_barrens", "end_highlands", "end_midlands", "eroded_badlands",
+    "nether_wastes", "savanna", "savanna_plateau", "small_end_islands", "soul_sand_valley", "the_end", "the_void", "warped_forest",
+    "windswept_savanna", "wooded_badlands",
+];
+// Strukturen von Vanilla 1.21.1; der Generator erzeugt bisher keine davon
+pub const STRUCTURES: &[&str] = &[
+    "ancient_city", "bastion_remnant", "buried_treasure", "desert_pyramid", "end_city", "fortress", "igloo",
+    "jungle_pyramid", "mansion", "mineshaft", "mineshaft_mesa", "monument", "nether_fossil", "ocean_ruin_cold",
+    "ocean_ruin_warm", "pillager_outpost", "ruined_portal", "ruined_portal_desert", "ruined_portal_jungle",
+    "ruined_portal_mountain", "ruined_portal_nether", "ruined_portal_ocean", "ruined_portal_swamp", "shipwreck",
+    "shipwreck_beached", "stronghold", "swamp_hut", "trail_ruins", "trial_chambers", "village_desert", "village_plains",
+    "village_savanna", "village_snowy", "village_taiga",
+];
+// Wie bei Vanilla: Suche im 32er-Raster bis 6400 Blöcke um den Ausgangspunkt
+const BIOME_SEARCH_STEP: i32 = 32;
+const VOID_PLATFORM_RADIUS: i32 = 2;
+const VOID_PLATFORM_Y: i32 = 63;
+// Abstand der Bonustruhe vom Spawn nach Osten, damit neue Spieler nicht auf ihr landen
+const BONUS_CHEST_OFFSET: i32 = 2;
+// minecraft:chests/spawn_bonus_chest aus Vanilla 1.21.1, falls kein Datenpaket die Tabelle liefert
+const BONUS_CHEST_LOOT: &str = r#"{"pools": [
+    {"rolls": 1, "entries": [{"type": "minecraft:item", "name": "minecraft:stone_axe"}, {"type": "minecraft:item", "name": "minecraft:wooden_axe", "weight": 3}]},
+    {"rolls": 1, "entries": [{"type": "minecraft:item", "name": "minecraft:stone_pickaxe"}, {"type": "minecraft:item", "name": "minecraft:wooden_pickaxe", "weight": 3}]},
+    {"rolls": 3, "entries": [
+        {"type": "minecraft:item", "name": "minecraft:apple", "weight": 5, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 2}}]},
+        {"type": "minecraft:item", "name": "minecraft:bread", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 2}}]},
+        {"type": "minecraft:item", "name": "minecraft:salmon", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 2}}]}
+    ]},
+    {"rolls": 4, "entries": [
+        {"type": "minecraft:item", "name": "minecraft:stick", "weight": 10, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 12}}]},
+        {"type": "minecraft:item", "name": "minecraft:oak_planks", "weight": 10, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 12}}]},
+        {"type": "minecraft:item", "name": "minecraft:oak_log", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 3}}]},
+        {"type": "minecraft:item", "name": "minecraft:spruce_log", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 3}}]},
+        {"type": "minecraft:item", "name": "minecraft:birch_log", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 3}}]},
+        {"type": "minecraft:item", "name": "minecraft:jungle_log", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 3}}]},
+        {"type": "minecraft:item", "name": "minecraft:acacia_log", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 3}}]},
+        {"type": "minecraft:item", "name": "minecraft:dark_oak_log", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 3}}]},
+        {"type": "minecraft:item", "name": "minecraft:mangrove_log", "weight": 3, "functions": [{"function": "minecraft:set_count", "count": {"min": 1, "max": 3}}]}
+    ]}
+]}"#;
+const BIOME_SEARCH_RADIUS: i32 = 6400;
+
+pub fn biome_id(name: &str) -> Option<u16> {
+    BIOMES.iter().position(|b| *b == name).map(|id| id as u16)
+}
+
+pub fn biome_name(id: u16) -> &'static str {
+    BIOMES.get(id as usize).copied().unwrap_or("plains")
+}
+
+pub fn temperature(biome: &str) -> f32 {
+    TEMPERATURES.iter().find(|(b, _)| *b == biome).map_or(DEFAULT_TEMPERATURE, |&(_, temperature)| temperature)
+}
+
+pub fn has_precipitation(biome: &str) -> bool {
+    !DRY_BIOMES.contains(&biome)
+}
+
+// Bisher hat jede Dimension nur ein Biom: Ebenen in der Oberwelt, Netherödnis und das Ende, je nach Himmel des
+// Dimensionstyps
+pub fn biome_at(dimension: Dimension, _x: i32, _z: i32) -> &'static str {
+    match dimension.kind().effects {
+        "minecraft:the_nether" => "nether_wastes",
+        "minecraft:the_end" => "the_end",
+        _ => "plains",
+    }
+}
+
+// Trägt das Biom jeder 4×4-Säule in alle Zellen des frisch generierten Chunks ein
+fn fill_biomes(chunk: &mut Chunk, dimension: Dimension) {
+    for x in (0..16).step_by(4) {
+        for z in (0..16).step_by(4) {
+            let biome = biome_at(dimension, chunk.x * 16 + x as i32, chunk.z * 16 + z as i32);
+            let id = biome_id(biome).unwrap_or(chunk::PLAINS_BIOME);
+            for y in (chunk::MIN_Y..chunk::MIN_Y + chunk::HEIGHT).step_by(4) {
+                chunk.set_biome(x, y, z, id);
+            }
+        }
+    }
+}
+
+// Nächste Säule mit dem Biom (Name ohne Namespace), ringweise vom Ausgangspunkt aus
+pub fn locate_biome(dimension: Dimension, origin: (i32, i32), biome: &str) -> Option<(i32, i32)> {
+    for ring in 0..=BIOME_SEARCH_RADIUS / BIOME_SEARCH_STEP {
+        let found = (-ring..=ring)
+            .flat_map(|dx| (-ring..=ring).map(move |dz| (dx, dz)))
+            .filter(|(dx, dz)| dx.abs() == ring || dz.abs() == ring)
+            .map(|(dx, dz)| (origin.0 + dx * BIOME_SEARCH_STEP, origin.1 + dz * BIOME_SEARCH_STEP))
+            .filter(|&(x, z)| biome_at(dimension, x, z) == biome)
+            .min_by_key(|&(x, z)| (x - origin.0) as i64 * (x - origin.0) as i64 + (z - origin.1) as i64 * (z - origin.1) as i64);
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+fn chunk_seed(seed: u64, x: i32, z: i32) -> u64 {
+    seed ^ (x as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (z as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+}
+
+pub fn generate_chunk(seed: u64, chunk_x: i32, chunk_z: i32, terrain: &TerrainPreset) -> Chunk {
+    let mut rng = StdRng::seed_from_u64(chunk_seed(seed, chunk_x, chunk_z));
+    let mut chunk = Chunk::new(chunk_x, chunk_z);
+    for x in 0..16 {
+        for z in 0..16 {
+            let hills = hill_height(seed, terrain, chunk_x * 16 + x as i32, chunk_z * 16 + z as i32);
+            let height = terrain.base_height + hills + rng.gen_range(-terrain.roughness..terrain.roughness);
+            for y in 0..=height {
+                let block_type = if y == height { block::GRASS_BLOCK } else { block::DIRT };
+                chunk.set_block(x, y, z, block_type);
+            }
+            // Bäume nur dort, wo die Krone komplett im Chunk liegt
+            let inside = (2..14).contains(&x) && (2..14).contains(&z);
+            if rng.gen_range(0..100) < 5 && inside {
+                generate_large_tree(&mut chunk, x as i32, height + 1, z as i32);
+            }
+        }
+    }
+    chunk
+}
+
+// Zwei Oktaven Wertrauschen: zufällige Höhen auf einem Gitter, dazwischen weich interpoliert
+fn hill_height(seed: u64, terrain: &TerrainPreset, x: i32, z: i32) -> i32 {
+    if terrain.hill_height == 0.0 {
+        return 0;
+    }
+    let coarse = value_noise(seed, x, z, terrain.hill_width);
+    let fine = value_noise(seed.rotate_left(17), x, z, (terrain.hill_width / 4).max(1));
+    let noise = (coarse * 0.8 + fine * 0.2).powf(terrain.sharpness);
+    (noise * terrain.hill_height).round() as i32
+}
+
+// Zwischen 0 und 1
+fn value_noise(seed: u64, x: i32, z: i32, width: i32) -> f64 {
+    let corner = |gx: i32, gz: i32| (chunk_seed(seed, gx, gz).wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64;
+    let (gx, gz) = (x.div_euclid(width), z.div_euclid(width));
+    let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+    let tx = smooth(x.rem_euclid(width) as f64 / width as f64);
+    let tz = smooth(z.rem_euclid(width) as f64 / width as f64);
+    let north = corner(gx, gz) + (corner(gx + 1, gz) - corner(gx, gz)) * tx;
+    let south = corner(gx, gz + 1) + (corner(gx + 1, gz + 1) - corner(gx, gz + 1)) * tx;
+    north + (south - north) * tz
+}
+
+// Wie die Standard-Superflach-Voreinstellung: Grundgestein, zwei Lagen Erde und Gras am unteren Rand der Welt
+pub fn generate_flat_chunk(chunk_x: i32, chunk_z: i32) -> Chunk {
+    let mut chunk = Chunk::new(chunk_x, chunk_z);
+    let layers = [block::BEDROCK, block::DIRT, block::DIRT, block::GRASS_BLOCK];
+    for x in 0..16 {
+        for z in 0..16 {
+            for (i, &state) in layers.iter().enumerate() {
+                chunk.set_block(x, chunk::MIN_Y + i as i32, z, state);
+            }
+        }
+    }
+    chunk
+}
+
+// Nur Luft; Biome bekommt der Chunk trotzdem, damit Himmel und Nebel stimmen
+pub fn generate_void_chunk(chunk_x: i32, chunk_z: i32) -> Chunk {
+    Chunk::new(chunk_x, chunk_z)
+}
+
+// Steinplattform um den Ursprung mit Bruchstein in der Mitte, damit neue Spieler in einer leeren Welt nicht sofort
+// fallen. Nur einmal beim Anlegen der Welt, danach gehört sie den Spielern
+pub fn place_spawn_platform(world: &mut World) {
+    for x in -VOID_PLATFORM_RADIUS..=VOID_PLATFORM_RADIUS {
+        for z in -VOID_PLATFORM_RADIUS..=VOID_PLATFORM_RADIUS {
+            let state = if (x, z) == (0, 0) { block::COBBLESTONE } else { block::STONE };
+            world.set_block(x, VOID_PLATFORM_Y, z, state);
+        }
+    }
+}
+
+// Wie Vanilla mit --bonusChest: eine Truhe mit Startausrüstung neben dem Spawn, auf den freien Seiten Fackeln. Nur
+// beim Anlegen der Welt; die Beute würfelt table, ohne Datenpaket die eingebaute Fassung
+pub fn place_bonus_chest(world: &mut World, table: Option<&Json>) {
+    let (spawn_x, _, spawn_z) = world.spawn.position;
+    let (x, z) = (spawn_x + BONUS_CHEST_OFFSET, spawn_z);
+    let y = world.highest_block_y(x, z) + 1;
+    if !block::is_solid(world.get_block(x, y - 1, z)) || !world.set_block(x, y, z, block::CHEST) {
+        warn!("Kein Platz für die Bonustruhe bei {} {} {}", x, y, z);
+        return;
+    }
+    let builtin = json::parse(BONUS_CHEST_LOOT).unwrap_or(Json::Null);
+    let loot = loot::generate(table.unwrap_or(&builtin));
+    if let Some(BlockEntity::Chest { items }) = world.block_entity_mut((x, y, z)) {
+        // Wie bei Vanilla über die Truhe verstreut
+        let mut slots: Vec<usize> = (0..items.len()).collect();
+        slots.shuffle(&mut rand::thread_rng());
+        for (slot, stack) in slots.into_iter().zip(loot) {
+            items[slot] = stack;
+        }
+    }
+    for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+        let (torch_x, torch_z) = (x + dx, z + dz);
+        if world.get_block(torch_x, y, torch_z) == block::AIR && block::is_solid(world.get_block(torch_x, y - 1, torch_z)) {
+            world.set_block(torch_x, y, torch_z, block::TORCH);
+        }
+    }
+    info!("Bonustruhe bei {} {} {} aufgestellt", x, y, z);
+}
+
+fn generate_large_tree(chunk: &mut Chunk, x: i32, y: i32, z: i32) {
+    for ((dx, dy, dz), state) in tree_blocks(Tree::Oak, 5) {
+        chunk.set_block((x + dx) as usize, y + dy, (z + dz) as usize, state);
+    }
+}
+
+// Baumformen für die Weltgenerierung und wachsende Setzlinge; Fichte und Tropenbaum gibt es auch als Riesenbaum mit
+// 2×2-Stamm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tree {
+    Oak,
+    Spruce,
+    Jungle,
+    MegaSpruce,
+    MegaJungle,
+}
+
+impl Tree {
+    pub fn is_mega(self) -> bool {
+        matches!(self, Tree::MegaSpruce | Tree::MegaJungle)
+    }
+
+    // Stammhöhen wie die Stammformen der Vanilla-Features
+    pub fn random_height(self, rng: &mut impl Rng) -> i32 {
+        match self {
+            Tree::Oak => rng.gen_range(4..=6),
+            Tree::Spruce => rng.gen_range(5..=8),
+            Tree::Jungle => rng.gen_range(4..=12),
+            Tree::MegaSpruce => rng.gen_range(13..=28),
+            Tree::MegaJungle => rng.gen_range(10..=29),
+        }
+    }
+
+    fn log(self) -> u16 {
+        match self {
+            Tree::Oak => block::OAK_LOG,
+            Tree::Spruce | Tree::MegaSpruce => block::SPRUCE_LOG,
+            Tree::Jungle | Tree::MegaJungle => block::JUNGLE_LOG,
+        }
+    }
+
+    fn leaves(self) -> u16 {
+        match self {
+            Tree::Oak => block::OAK_LEAVES,
+            Tree::Spruce | Tree::MegaSpruce => block::SPRUCE_LEAVES,
+            Tree::Jungle | Tree::MegaJungle => block::JUNGLE_LEAVES,
+        }
+    }
+}
+
+// Blöcke eines Baums relativ zum Fuß des Stamms, bei Riesenbäumen zu dessen Nordwestecke. Erst der Stamm, dann die
+// Blätter
+pub fn tree_blocks(tree: Tree, height: i32) -> Vec<((i32, i32, i32), u16)> {
+    let width = if tree.is_mega() { 2 } else { 1 };
+    let mut blocks = Vec::new();
+    for dy in 0..height {
+        for dx in 0..width {
+            for dz in 0..width {
+                blocks.push(((dx, dy, dz), tree.log()));
+            }
+        }
+    }
+    let leaves = tree.leaves();
+    match tree {
+        Tree::Oak | Tree::Jungle => {
+            for dx in -2i32..=2 {
+                for dz in -2i32..=2 {
+                    for dy in height - 1..=height + 1 {
+                        if dx.abs() + dz.abs() + (dy - (height - 1)) < 4 {
+                            blocks.push(((dx, dy, dz), leaves));
+                        }
+                    }
+                }
+            }
+        }
+        // Spitze Krone aus abwechselnd schmalen und breiten Lagen
+        Tree::Spruce => {
+            for depth in 0..height - 1 {
+                let radius = if depth % 2 == 1 { 1 + depth / 3 } else { depth / 3 };
+                leaf_layer(&mut blocks, leaves, width, height - depth, radius.min(2));
+            }
+        }
+        // Die Krone beginnt in halber Höhe und wird nach unten breiter
+        Tree::MegaSpruce => {
+            for depth in 0..=height / 2 {
+                leaf_layer(&mut blocks, leaves, width, height - depth, ((depth + 2) / 3).min(4));
+            }
+        }
+        Tree::MegaJungle => {
+            for (dy, radius) in [(-2, 3), (-1, 4), (0, 4), (1, 2)] {
+                leaf_layer(&mut blocks, leaves, width, height + dy, radius);
+            }
+        }
+    }
+    set_leaf_distances(&mut blocks, tree.log());
+    blocks
+}
+
+// Abstand jedes Blatts zum Stamm, damit frisch gewachsene Blätter nicht gleich zerfallen. Spätere Einträge
+// überschreiben frühere, also zählt für jede Stelle nur der letzte
+fn set_leaf_distances(blocks: &mut [((i32, i32, i32), u16)], log: u16) {
+    let last: HashMap<(i32, i32, i32), usize> = blocks.iter().enumerate().map(|(i, &(position, _))| (position, i)).collect();
+    let mut distances: HashMap<(i32, i32, i32), u32> = HashMap::new();
+    let mut queue: VecDeque<(i32, i32, i32)> = VecDeque::new();
+    for (&position, &i) in &last {
+        if blocks[i].1 == log {
+            distances.insert(position, 0);
+            queue.push_back(position);
+        }
+    }
+    while let Some((x, y, z)) = queue.pop_front() {
+        let distance = distances[&(x, y, z)] + 1;
+        for next in [(x + 1, y, z), (x - 1, y, z), (x, y + 1, z), (x, y - 1, z), (x, y, z + 1), (x, y, z - 1)] {
+            if last.get(&next).is_some_and(|&i| blocks[i].1 != log) && !distances.contains_key(&next) {
+                distances.insert(next, distance);
+                queue.push_back(next);
+            }
+        }
+    }
+    for (position, state) in blocks.iter_mut() {
+        if *state != log {
+            let distance = distances.get(position).copied().unwrap_or(7).min(7);
+            *state = block::with_property(*state, "distance", &distance.to_string()).unwrap_or(*state);
+        }
+    }
+}
+
+// Eine Blätterlage um den Stamm: bei einem Block Breite ein Quadrat ohne Ecken, bei 2×2-Stämmen ein Kreis um die
+// Mitte zwischen den vier Stammblöcken, in halben Blöcken gerechnet
+fn leaf_layer(blocks: &mut Vec<((i32, i32, i32), u16)>, leaves: u16, width: i32, dy: i32, radius: i32) {
+    for dx in -radius..radius + width {
+        for dz in -radius..radius + width {
+            let inside = if width == 1 {
+                radius == 0 || dx.abs() != radius || dz.abs() != radius
+            } else {
+                let (ox, oz) = (dx * 2 - 1, dz * 2 - 1);
+                ox * ox + oz * oz <= (radius * 2 + 1).pow(2) + 1
+            };
+            if inside {
+                blocks.push(((dx, dy, dz), leaves));
+            }
+        }
+    }
+}
+
+// Hauptinsel des Endes mit den Obsidiansäulen, dem noch leeren Austrittsportal und der Ankunftsplattform.
+// Außeninseln gibt es bisher nicht
+pub fn generate_end_chunk(seed: u64, chunk_x: i32, chunk_z: i32) -> Chunk {
+    let mut chunk = Chunk::new(chunk_x, chunk_z);
+    let spikes = end::spikes(seed);
+    for x in 0..16 {
+        for z in 0..16 {
+            let (world_x, world_z) = (chunk_x * 16 + x as i32, chunk_z * 16 + z as i32);
+            let column = end::island_column(world_x, world_z);
+            if let Some((top, bottom)) = column {
+                for y in bottom..=top {
+                    chunk.set_block(x, y, z, block::END_STONE);
+                }
+            }
+            for spike in spikes.iter().filter(|s| s.contains(world_x, world_z)) {
+                let bottom = column.map_or(end::ISLAND_TOP, |(_, bottom)| bottom);
+                for y in bottom..spike.height {
+                    chunk.set_block(x, y, z, block::OBSIDIAN);
+                }
+                let top = if (world_x, world_z) == (spike.x, spike.z) { block::BEDROCK } else { block::OBSIDIAN };
+                chunk.set_block(x, spike.height, z, top);
+            }
+        }
+    }
+    for ((x, y, z), state) in end::exit_portal(false).into_iter().chain(end::entry_platform()) {
+        if (x >> 4, z >> 4) == (chunk_x, chunk_z) {
+            chunk.set_block((x & 15) as usize, y, (z & 15) as usize, state);
+        }
+    }
+    chunk
+}
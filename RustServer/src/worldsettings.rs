@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use crate::config::ServerConfig;
+use crate::entity;
+use crate::json::Json;
+
+// Tiere im Sinne von spawn-animals, wie die Kategorie creature bei Vanilla
+pub const ANIMALS: &[&str] = &[
+    "armadillo", "bee", "camel", "cat", "chicken", "cow", "donkey", "fox", "frog", "goat", "horse", "llama", "mooshroom",
+    "mule", "ocelot", "panda", "parrot", "pig", "polar_bear", "rabbit", "sheep", "sniffer", "strider", "turtle", "wolf",
+];
+
+// Schalter je Welt, gespeichert in level.json; neue Welten übernehmen die Werte aus server.properties
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldSettings {
+    pub pvp: bool,
+    pub spawn_monsters: bool,
+    pub spawn_animals: bool,
+}
+
+impl Default for WorldSettings {
+    fn default() -> WorldSettings {
+        WorldSettings { pvp: true, spawn_monsters: true, spawn_animals: true }
+    }
+}
+
+impl WorldSettings {
+    pub const KEYS: &'static [&'static str] = &["pvp", "spawn-monsters", "spawn-animals"];
+
+    pub fn from_config(config: &ServerConfig) -> WorldSettings {
+        WorldSettings { pvp: config.pvp, spawn_monsters: config.spawn_monsters, spawn_animals: config.spawn_animals }
+    }
+
+    fn field(&mut self, key: &str) -> Option<&mut bool> {
+        match key {
+            "pvp" => Some(&mut self.pvp),
+            "spawn-monsters" => Some(&mut self.spawn_monsters),
+            "spawn-animals" => Some(&mut self.spawn_animals),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<bool> {
+        let mut settings = *self;
+        settings.field(key).map(|value| *value)
+    }
+
+    pub fn set(&mut self, key: &str, text: &str) -> Result<bool, String> {
+        let field = self.field(key).ok_or_else(|| format!("Unknown world setting '{}', expected {}", key, WorldSettings::KEYS.join(", ")))?;
+        *field = text.parse().map_err(|_| format!("Invalid boolean, expected 'true' or 'false' but found '{}'", text))?;
+        Ok(*field)
+    }
+
+    // Monster, Tiere und alles andere; Bosse, Dorfbewohner und Rüstungsständer kommen immer
+    pub fn allows_spawn(&self, kind: &str) -> bool {
+        if entity::is_hostile(kind) {
+            return self.spawn_monsters;
+        }
+        let path = kind.strip_prefix("minecraft:").unwrap_or(kind);
+        self.spawn_animals || !ANIMALS.contains(&path)
+    }
+
+    // {"pvp": true, "spawn-monsters": true, "spawn-animals": true}
+    pub fn to_json(self) -> Json {
+        let values: BTreeMap<String, Json> = WorldSettings::KEYS.iter().filter_map(|key| Some((key.to_string(), Json::Bool(self.get(key)?)))).collect();
+        Json::Object(values)
+    }
+
+    // Fehlende Einträge behalten ihren Standardwert
+    pub fn from_json(json: &Json) -> WorldSettings {
+        let mut settings = WorldSettings::default();
+        for key in WorldSettings::KEYS {
+            if let (Some(field), Some(Json::Bool(saved))) = (settings.field(key), json.get(key)) {
+                *field = *saved;
+            }
+        }
+        settings
+    }
+}
@@ -0,0 +1,189 @@
+use std::io::{self, Write};
+use crate::clock::UtcTime;
+use crate::gzip;
+
+const LOCAL_HEADER: u32 = 0x04034B50;
+const CENTRAL_HEADER: u32 = 0x02014B50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054B50;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+// Version 2.0: Deflate und Verzeichnisse
+const VERSION: u16 = 20;
+// Bit 11: Dateinamen sind UTF-8
+const FLAG_UTF8: u16 = 1 << 11;
+
+struct Entry {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+    time: u16,
+    date: u16,
+}
+
+// Schreibt ein ZIP-Archiv ohne Zip64, also bis 4 GiB und 65535 Einträge
+pub struct ZipWriter<W: Write> {
+    out: W,
+    offset: u64,
+    entries: Vec<Entry>,
+}
+
+fn too_large() -> io::Error {
+    io::Error::other("Archiv überschreitet die ZIP-Grenzen (4 GiB bzw. 65535 Einträge)")
+}
+
+fn checked(value: u64) -> io::Result<u32> {
+    u32::try_from(value).map_err(|_| too_large())
+}
+
+// MS-DOS-Zeitstempel wie im ZIP-Format vorgesehen, mit Sekunden in Zweierschritten
+fn dos_time(time: UtcTime) -> (u16, u16) {
+    let year = time.year.clamp(1980, 2107) as u16;
+    let dos_time = (time.hour << 11 | time.minute << 5 | (time.second / 2)) as u16;
+    let dos_date = (year - 1980) << 9 | (time.month << 5 | time.day) as u16;
+    (dos_time, dos_date)
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(out: W) -> ZipWriter<W> {
+        ZipWriter { out, offset: 0, entries: Vec::new() }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.out.write_all(bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    // Komprimiert mit Deflate, außer das Ergebnis wäre nicht kleiner
+    pub fn add_file(&mut self, name: &str, data: &[u8], modified: UtcTime) -> io::Result<()> {
+        if self.entries.len() == u16::MAX as usize {
+            return Err(too_large());
+        }
+        let deflated = gzip::deflate(data);
+        let (method, stored) = if deflated.len() < data.len() { (METHOD_DEFLATE, &deflated[..]) } else { (METHOD_STORED, data) };
+        let (time, date) = dos_time(modified);
+        let entry = Entry {
+            name: name.to_string(),
+            method,
+            crc: gzip::crc32(data),
+            compressed_size: checked(stored.len() as u64)?,
+            size: checked(data.len() as u64)?,
+            offset: checked(self.offset)?,
+            time,
+            date,
+        };
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend(LOCAL_HEADER.to_le_bytes());
+        header.extend(VERSION.to_le_bytes());
+        header.extend(FLAG_UTF8.to_le_bytes());
+        header.extend(entry.method.to_le_bytes());
+        header.extend(entry.time.to_le_bytes());
+        header.extend(entry.date.to_le_bytes());
+        header.extend(entry.crc.to_le_bytes());
+        header.extend(entry.compressed_size.to_le_bytes());
+        header.extend(entry.size.to_le_bytes());
+        header.extend((name.len() as u16).to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(name.as_bytes());
+        self.write(&header)?;
+        self.write(stored)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    // Schreibt das zentrale Verzeichnis und gibt den Ausgabestrom zurück
+    pub fn finish(mut self) -> io::Result<W> {
+        let start = checked(self.offset)?;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend(CENTRAL_HEADER.to_le_bytes());
+            directory.extend(VERSION.to_le_bytes());
+            directory.extend(VERSION.to_le_bytes());
+            directory.extend(FLAG_UTF8.to_le_bytes());
+            directory.extend(entry.method.to_le_bytes());
+            directory.extend(entry.time.to_le_bytes());
+            directory.extend(entry.date.to_le_bytes());
+            directory.extend(entry.crc.to_le_bytes());
+            directory.extend(entry.compressed_size.to_le_bytes());
+            directory.extend(entry.size.to_le_bytes());
+            directory.extend((entry.name.len() as u16).to_le_bytes());
+            // Extra-Feld, Kommentar, Start-Datenträger, interne und externe Attribute
+            directory.extend([0u8; 12]);
+            directory.extend(entry.offset.to_le_bytes());
+            directory.extend(entry.name.as_bytes());
+        }
+        let size = checked(directory.len() as u64)?;
+        self.write(&directory)?;
+        checked(self.offset)?;
+        let count = self.entries.len() as u16;
+        let mut end = Vec::with_capacity(22);
+        end.extend(END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        end.extend([0u8; 4]);
+        end.extend(count.to_le_bytes());
+        end.extend(count.to_le_bytes());
+        end.extend(size.to_le_bytes());
+        end.extend(start.to_le_bytes());
+        end.extend(0u16.to_le_bytes());
+        self.write(&end)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Result<u16, String> {
+    data.get(pos..pos + 2).map(|b| u16::from_le_bytes([b[0], b[1]])).ok_or_else(|| "ZIP-Archiv ist abgeschnitten".to_string())
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Result<u32, String> {
+    data.get(pos..pos + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).ok_or_else(|| "ZIP-Archiv ist abgeschnitten".to_string())
+}
+
+// Liest alle Dateien eines ZIP-Archivs (gespeichert oder Deflate) samt Prüfsummenkontrolle. Verzeichnisse werden übersprungen
+pub fn read_archive(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    // Das Ende des zentralen Verzeichnisses steht hinter einem bis zu 64 KiB langen Kommentar
+    let search_start = data.len().saturating_sub(22 + u16::MAX as usize);
+    let end = (search_start..data.len().saturating_sub(21))
+        .rev()
+        .find(|&pos| u32_at(data, pos) == Ok(END_OF_CENTRAL_DIRECTORY))
+        .ok_or("Kein ZIP-Archiv")?;
+    let count = u16_at(data, end + 10)? as usize;
+    let mut pos = u32_at(data, end + 16)? as usize;
+    let mut files = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(data, pos)? != CENTRAL_HEADER {
+            return Err("Beschädigtes zentrales Verzeichnis".to_string());
+        }
+        let method = u16_at(data, pos + 10)?;
+        let crc = u32_at(data, pos + 16)?;
+        let compressed_size = u32_at(data, pos + 20)? as usize;
+        let size = u32_at(data, pos + 24)? as usize;
+        let name_length = u16_at(data, pos + 28)? as usize;
+        let skip = u16_at(data, pos + 30)? as usize + u16_at(data, pos + 32)? as usize;
+        let offset = u32_at(data, pos + 42)? as usize;
+        let name = data.get(pos + 46..pos + 46 + name_length).ok_or("ZIP-Archiv ist abgeschnitten")?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        pos += 46 + name_length + skip;
+        if name.ends_with('/') {
+            continue;
+        }
+
+        if u32_at(data, offset)? != LOCAL_HEADER {
+            return Err(format!("Beschädigter Eintrag {}", name));
+        }
+        let start = offset + 30 + u16_at(data, offset + 26)? as usize + u16_at(data, offset + 28)? as usize;
+        let stored = data.get(start..start + compressed_size).ok_or("ZIP-Archiv ist abgeschnitten")?;
+        let content = match method {
+            METHOD_STORED => stored.to_vec(),
+            METHOD_DEFLATE => gzip::inflate(stored).map_err(|e| format!("{}: {}", name, e))?,
+            other => return Err(format!("{}: Kompressionsmethode {} wird nicht unterstützt", name, other)),
+        };
+        if content.len() != size || gzip::crc32(&content) != crc {
+            return Err(format!("{}: Prüfsumme stimmt nicht", name));
+        }
+        files.push((name, content));
+    }
+    Ok(files)
+}
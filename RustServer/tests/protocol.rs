@@ -0,0 +1,257 @@
+mod support;
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::thread;
+use std::time::{Duration, Instant};
+use support::client::{clientbound, serverbound, Packet, TestClient, PROTOCOL_VERSION};
+use support::TestServer;
+
+#[test]
+fn status_reports_version_and_players() {
+    let server = TestServer::start();
+    let json = TestClient::status(server.address).unwrap();
+    assert!(json.contains(&format!("\"protocol\":{}", PROTOCOL_VERSION)), "{}", json);
+    assert!(json.contains("\"online\":0"), "{}", json);
+    assert!(json.contains("\"description\""), "{}", json);
+
+    let (mut client, _) = TestClient::login(server.address, "Tester").unwrap();
+    client.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    let json = TestClient::status(server.address).unwrap();
+    assert!(json.contains("\"online\":1") && json.contains("\"name\":\"Tester\""), "{}", json);
+}
+
+#[test]
+fn login_succeeds_with_offline_uuid() {
+    let server = TestServer::start();
+    let (mut client, success) = TestClient::login(server.address, "Tester").unwrap();
+    assert_eq!(success.username, "Tester");
+    assert_eq!(success.uuid.get_version_num(), 3);
+    client.expect(clientbound::LOGIN_PLAY).unwrap();
+
+    // Derselbe Name bekommt wieder dieselbe UUID
+    drop(client);
+    let (_client, again) = TestClient::login(server.address, "Tester").unwrap();
+    assert_eq!(again.uuid, success.uuid);
+}
+
+#[test]
+fn configuration_phase_sends_registries_before_join_game() {
+    let server = TestServer::start();
+    let (mut client, success) = TestClient::login(server.address, "Tester").unwrap();
+    for registry in ["minecraft:dimension_type", "minecraft:worldgen/biome", "minecraft:damage_type", "minecraft:wolf_variant", "minecraft:enchantment"] {
+        assert!(success.registries.iter().any(|r| r == registry), "{} fehlt in {:?}", registry, success.registries);
+    }
+    // Nach Acknowledge Finish Configuration ist Join Game das erste Spielpaket
+    let first = client.read_until(Duration::from_secs(10), |_| true).unwrap().expect("Kein Paket nach der Konfiguration");
+    assert_eq!(first.id, clientbound::LOGIN_PLAY);
+}
+
+#[test]
+fn chunks_around_spawn_are_delivered() {
+    let server = TestServer::start();
+    let (mut client, _) = TestClient::login(server.address, "Tester").unwrap();
+    // Neue Welten spawnen über dem Ursprung
+    let wanted: HashSet<(i32, i32)> = (-1..=1).flat_map(|x| (-1..=1).map(move |z| (x, z))).collect();
+    let mut received = HashSet::new();
+    let done = client
+        .read_until(Duration::from_secs(30), |packet| {
+            if packet.id == clientbound::CHUNK_DATA {
+                received.insert((packet.data.i32().unwrap(), packet.data.i32().unwrap()));
+            }
+            wanted.is_subset(&received)
+        })
+        .unwrap();
+    assert!(done.is_some(), "Nur diese Chunks erhalten: {:?}", received);
+}
+
+#[test]
+fn keep_alive_is_answered_or_disconnects() {
+    let server = TestServer::with_properties(&[("keep-alive-interval", "1"), ("keep-alive-timeout", "2")]);
+    let (mut answering, _) = TestClient::login(server.address, "Answering").unwrap();
+    let (mut silent, _) = TestClient::login(server.address, "Silent").unwrap();
+    let answering = thread::spawn(move || {
+        let mut keep_alives = 0;
+        answering
+            .read_until(Duration::from_secs(8), |packet| {
+                keep_alives += usize::from(packet.id == clientbound::KEEP_ALIVE);
+                false
+            })
+            .map(|_| keep_alives)
+    });
+    silent.wait_closed(Duration::from_secs(8)).unwrap();
+    // Wer antwortet, bleibt die ganze Zeit verbunden
+    let keep_alives = answering.join().unwrap().unwrap();
+    assert!(keep_alives >= 4, "{} Keep-Alives in 8s", keep_alives);
+}
+
+#[test]
+fn malformed_movement_disconnects_only_the_sender() {
+    let server = TestServer::start();
+    let (mut broken, _) = TestClient::login(server.address, "Broken").unwrap();
+    broken.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    // Ein Byte zu kurz für z; on_ground fehlt ganz
+    broken.send(serverbound::SET_PLAYER_POSITION, &[0; 23]).unwrap();
+    broken.wait_closed(Duration::from_secs(8)).unwrap();
+
+    let (mut client, _) = TestClient::login(server.address, "Tester").unwrap();
+    client.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    // Der Server räumt den getrennten Spieler erst kurz nach dem Schließen der Verbindung weg
+    let json = status_until(server.address, |json| json.contains("\"online\":1"));
+    assert!(json.contains("\"online\":1") && json.contains("\"name\":\"Tester\""), "{}", json);
+}
+
+fn status_until(address: SocketAddr, done: impl Fn(&str) -> bool) -> String {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let json = TestClient::status(address).unwrap();
+        if done(&json) || Instant::now() >= deadline {
+            return json;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn dual_stack_and_extra_addresses_share_one_server() {
+    let server = TestServer::with_properties(&[("server-ip", "::"), ("server-addresses", "127.0.0.1:0")]);
+    assert_eq!(server.listening.len(), 2, "{:?}", server.listening);
+    let port = server.listening[0].port();
+    let extra = server.listening[1];
+    assert_ne!(extra.port(), port);
+    let (mut client, _) = TestClient::login(SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], port)), "Tester").unwrap();
+    client.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    // IPv4 über den Dual-Stack-Listener und über die zweite Adresse sehen denselben Spieler
+    for address in [SocketAddr::from(([127, 0, 0, 1], port)), extra] {
+        let json = TestClient::status(address).unwrap();
+        assert!(json.contains("\"online\":1") && json.contains("\"name\":\"Tester\""), "{}", json);
+    }
+}
+
+#[test]
+fn command_line_overrides_properties() {
+    let server = TestServer::with_args(&[("server-port", "1")], &["nogui", "--port", "0", "--property", "motd=Scripted"]);
+    let json = TestClient::status(server.address).unwrap();
+    assert!(json.contains("Scripted"), "{}", json);
+}
+
+#[test]
+fn login_floods_are_throttled_per_ip() {
+    let server = TestServer::with_properties(&[("max-logins-per-minute", "2"), ("login-throttle-secs", "60")]);
+    for name in ["First", "Second"] {
+        TestClient::login(server.address, name).unwrap();
+    }
+    let error = TestClient::login(server.address, "Third").unwrap_err();
+    assert!(error.contains("Connection throttled"), "{}", error);
+    // Die Sperre gilt auch nach dem Limit weiter, der Status bleibt erreichbar
+    assert!(TestClient::login(server.address, "Fourth").is_err());
+    assert!(TestClient::status(server.address).is_ok());
+}
+
+#[test]
+fn simultaneous_logins_cannot_exceed_max_players() {
+    let server = TestServer::with_properties(&[("max-players", "2")]);
+    let address = server.address;
+    let logins: Vec<_> = (0..6).map(|i| thread::spawn(move || TestClient::login(address, &format!("Player{}", i)))).collect();
+    let results: Vec<_> = logins.into_iter().map(|login| login.join().unwrap()).collect();
+    let errors: Vec<&String> = results.iter().filter_map(|result| result.as_ref().err()).collect();
+    assert_eq!(results.len() - errors.len(), 2, "{:?}", errors);
+    assert!(errors.iter().all(|e| e.contains("The server is full!")), "{:?}", errors);
+}
+
+#[test]
+fn unsigned_chat_is_relayed_in_order_unless_secure_chat_is_enforced() {
+    let server = TestServer::start();
+    let (mut client, _) = TestClient::login(server.address, "Tester").unwrap();
+    client.expect(clientbound::SERVER_DATA).unwrap();
+    client.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    client.chat("hello there", 1_700_000_001_000).unwrap();
+    let relayed = client.read_until(Duration::from_secs(5), |packet| packet.id == clientbound::SYSTEM_CHAT && packet.data.contains(b"hello there")).unwrap();
+    assert!(relayed.is_some(), "Chat kam nicht zurück");
+    // Ein älterer Zeitstempel als der letzte trennt wie bei Vanilla
+    client.chat("too late", 1_700_000_000_000).unwrap();
+    client.wait_closed(Duration::from_secs(5)).unwrap();
+
+    let server = TestServer::with_properties(&[("enforce-secure-chat", "true")]);
+    assert!(TestClient::status(server.address).unwrap().contains("\"enforcesSecureChat\":true"));
+    let (mut client, _) = TestClient::login(server.address, "Tester").unwrap();
+    client.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    client.chat("unsigned", 1_700_000_001_000).unwrap();
+    client.wait_closed(Duration::from_secs(5)).unwrap();
+}
+
+#[test]
+fn natural_spawning_stays_within_the_mob_cap() {
+    let properties = [("mob-cap-creature", "4"), ("spawn-interval-creature", "1"), ("mob-cap-monster", "0"), ("mob-cap-ambient", "0"), ("mob-cap-water", "0")];
+    let server = TestServer::with_properties(&properties);
+    let (mut client, _) = TestClient::login(server.address, "Tester").unwrap();
+    let mut spawned = HashSet::new();
+    client
+        .read_until(Duration::from_secs(15), |packet| {
+            if packet.id == clientbound::SPAWN_ENTITY {
+                spawned.insert(packet.data.varint().unwrap());
+            }
+            false
+        })
+        .unwrap();
+    // Die Obergrenze gilt für 17×17 Chunks, geladen sind um den Spawn weniger
+    assert!((1..=4).contains(&spawned.len()), "{} Tiere gespawnt", spawned.len());
+}
+
+// Die Welt entsteht im Hintergrund, bis dahin scheitert der Teleport
+fn enter_world(server: &mut TestServer, client: &mut TestClient, world: &str, player: &str) {
+    let deadline = Instant::now() + Duration::from_secs(60);
+    loop {
+        server.console(&format!("world tp {} {}", world, player));
+        if client.read_until(Duration::from_secs(1), |packet| packet.id == clientbound::RESPAWN).unwrap().is_some() {
+            return;
+        }
+        assert!(Instant::now() < deadline, "Die Welt {} wurde nicht fertig", world);
+    }
+}
+
+fn world_threads(server: &TestServer) -> usize {
+    ["chunk-gen", "light-", "chunk-io"].iter().map(|prefix| server.threads(prefix)).sum()
+}
+
+#[test]
+fn time_and_weather_reach_only_players_in_that_world() {
+    let mut server = TestServer::start();
+    let (mut home, _) = TestClient::login(server.address, "Home").unwrap();
+    let (mut away, _) = TestClient::login(server.address, "Away").unwrap();
+    home.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    away.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    server.console("world create second void");
+    enter_world(&mut server, &mut away, "second", "Away");
+
+    server.console("weather rain");
+    server.console("time set 18000");
+    let start_raining = |packet: &mut Packet| packet.id == clientbound::GAME_EVENT && packet.data.rest().first() == Some(&1);
+    let dusk = |packet: &mut Packet| {
+        packet.id == clientbound::UPDATE_TIME && packet.data.skip(8).is_ok() && (18000..18100).contains(&packet.data.i64().unwrap())
+    };
+    assert!(home.read_until(Duration::from_secs(5), start_raining).unwrap().is_some(), "Kein Regen in der Oberwelt");
+    assert!(home.read_until(Duration::from_secs(5), dusk).unwrap().is_some(), "Die Oberwelt hat die Zeit nicht übernommen");
+    let leaked = away.read_until(Duration::from_secs(3), |packet| start_raining(packet) || dusk(packet)).unwrap();
+    assert!(leaked.is_none(), "Die zweite Welt hat Wetter oder Zeit der Oberwelt bekommen: 0x{:02X}", leaked.unwrap().id);
+}
+
+#[test]
+fn unloading_a_world_stops_its_threads() {
+    let mut server = TestServer::start();
+    let (mut client, _) = TestClient::login(server.address, "Tester").unwrap();
+    client.expect(clientbound::SYNCHRONIZE_PLAYER_POSITION).unwrap();
+    let before = world_threads(&server);
+    for _ in 0..2 {
+        server.console("world create second void");
+        enter_world(&mut server, &mut client, "second", "Tester");
+        assert!(world_threads(&server) > before);
+        server.console("world unload second");
+        client.expect(clientbound::RESPAWN).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while world_threads(&server) != before {
+            assert!(Instant::now() < deadline, "{} Threads statt {} nach dem Entladen", world_threads(&server), before);
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
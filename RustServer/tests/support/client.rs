@@ -0,0 +1,298 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+// Eigene Paket-IDs statt der des Servers, damit ein versehentlich verschobenes Paket im Test auffällt
+pub const PROTOCOL_VERSION: i32 = 767;
+
+pub mod serverbound {
+    pub const HANDSHAKE: i32 = 0x00;
+    pub const STATUS_REQUEST: i32 = 0x00;
+    pub const PING_REQUEST: i32 = 0x01;
+    pub const LOGIN_START: i32 = 0x00;
+    pub const LOGIN_ACKNOWLEDGED: i32 = 0x03;
+    pub const ACKNOWLEDGE_FINISH_CONFIGURATION: i32 = 0x03;
+    pub const KNOWN_PACKS: i32 = 0x07;
+    pub const CONFIRM_TELEPORTATION: i32 = 0x00;
+    pub const CHAT_MESSAGE: i32 = 0x06;
+    pub const KEEP_ALIVE: i32 = 0x18;
+    pub const SET_PLAYER_POSITION: i32 = 0x1A;
+}
+
+pub mod clientbound {
+    pub const STATUS_RESPONSE: i32 = 0x00;
+    pub const LOGIN_DISCONNECT: i32 = 0x00;
+    pub const PONG_RESPONSE: i32 = 0x01;
+    pub const SPAWN_ENTITY: i32 = 0x01;
+    pub const LOGIN_SUCCESS: i32 = 0x02;
+    pub const FINISH_CONFIGURATION: i32 = 0x03;
+    pub const REGISTRY_DATA: i32 = 0x07;
+    pub const KNOWN_PACKS: i32 = 0x0E;
+    pub const GAME_EVENT: i32 = 0x22;
+    pub const KEEP_ALIVE: i32 = 0x26;
+    pub const CHUNK_DATA: i32 = 0x27;
+    pub const LOGIN_PLAY: i32 = 0x2B;
+    pub const SYNCHRONIZE_PLAYER_POSITION: i32 = 0x40;
+    pub const RESPAWN: i32 = 0x47;
+    pub const SERVER_DATA: i32 = 0x4B;
+    pub const UPDATE_TIME: i32 = 0x64;
+    pub const SYSTEM_CHAT: i32 = 0x6C;
+}
+
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub struct Packet {
+    pub id: i32,
+    pub data: Reader,
+}
+
+// Liest Felder der Reihe nach aus einem Paket
+#[derive(Debug)]
+pub struct Reader {
+    bytes: Vec<u8>,
+    position: usize,
+}
+
+impl Reader {
+    fn bytes(&mut self, count: usize) -> Result<&[u8], String> {
+        let end = self.position + count;
+        let bytes = self.bytes.get(self.position..end).ok_or("Paket zu kurz")?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub fn varint(&mut self) -> Result<i32, String> {
+        let mut value = 0u32;
+        for i in 0..5 {
+            let byte = self.bytes(1)?[0];
+            value |= u32::from(byte & 0x7F) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value as i32);
+            }
+        }
+        Err("VarInt zu lang".to_string())
+    }
+
+    pub fn string(&mut self) -> Result<String, String> {
+        let length = self.varint()? as usize;
+        String::from_utf8(self.bytes(length)?.to_vec()).map_err(|e| e.to_string())
+    }
+
+    pub fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_be_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    pub fn uuid(&mut self) -> Result<Uuid, String> {
+        Ok(Uuid::from_bytes(self.bytes(16)?.try_into().unwrap()))
+    }
+
+    pub fn rest(&mut self) -> Vec<u8> {
+        let rest = self.bytes[self.position..].to_vec();
+        self.position = self.bytes.len();
+        rest
+    }
+
+    pub fn skip(&mut self, count: usize) -> Result<(), String> {
+        self.bytes(count).map(|_| ())
+    }
+
+    // Für Textkomponenten in NBT, die hier niemand ganz zerlegen will
+    pub fn contains(&self, needle: &[u8]) -> bool {
+        self.bytes[self.position..].windows(needle.len()).any(|window| window == needle)
+    }
+}
+
+pub fn varint(value: i32) -> Vec<u8> {
+    let mut value = value as u32;
+    let mut bytes = Vec::new();
+    loop {
+        if value < 0x80 {
+            bytes.push(value as u8);
+            return bytes;
+        }
+        bytes.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+}
+
+pub fn string(value: &str) -> Vec<u8> {
+    let mut bytes = varint(value.len() as i32);
+    bytes.extend(value.as_bytes());
+    bytes
+}
+
+#[derive(Debug)]
+pub struct LoginSuccess {
+    pub uuid: Uuid,
+    pub username: String,
+    // Register aus Registry Data, in der Reihenfolge der Konfigurationsphase
+    pub registries: Vec<String>,
+}
+
+// Ein Client ohne Kompression und Verschlüsselung, wie ihn der Server im Offline-Modus erwartet
+#[derive(Debug)]
+pub struct TestClient {
+    stream: TcpStream,
+}
+
+impl TestClient {
+    pub fn connect(address: SocketAddr, next_state: i32) -> Result<TestClient, String> {
+        let stream = TcpStream::connect(address).map_err(|e| format!("Konnte {} nicht erreichen: {}", address, e))?;
+        let mut client = TestClient { stream };
+        let mut handshake = varint(PROTOCOL_VERSION);
+        handshake.extend(string("localhost"));
+        handshake.extend(address.port().to_be_bytes());
+        handshake.extend(varint(next_state));
+        client.send(serverbound::HANDSHAKE, &handshake)?;
+        Ok(client)
+    }
+
+    // Status-JSON und Ping; gibt das JSON zurück
+    pub fn status(address: SocketAddr) -> Result<String, String> {
+        let mut client = TestClient::connect(address, 1)?;
+        client.send(serverbound::STATUS_REQUEST, &[])?;
+        let json = client.expect(clientbound::STATUS_RESPONSE)?.data.string()?;
+        let payload = 0x0123_4567_89AB_CDEFi64;
+        client.send(serverbound::PING_REQUEST, &payload.to_be_bytes())?;
+        let pong = client.expect(clientbound::PONG_RESPONSE)?.data.i64()?;
+        if pong != payload {
+            return Err(format!("Pong {} statt {}", pong, payload));
+        }
+        Ok(json)
+    }
+
+    pub fn login(address: SocketAddr, username: &str) -> Result<(TestClient, LoginSuccess), String> {
+        let mut client = TestClient::connect(address, 2)?;
+        let mut start = string(username);
+        start.extend([0; 16]);
+        client.send(serverbound::LOGIN_START, &start)?;
+        let mut packet = client
+            .read_until(READ_TIMEOUT, |packet| packet.id == clientbound::LOGIN_SUCCESS || packet.id == clientbound::LOGIN_DISCONNECT)?
+            .ok_or("Kein Login Success erhalten")?;
+        if packet.id == clientbound::LOGIN_DISCONNECT {
+            return Err(format!("Abgewiesen: {}", packet.data.string()?));
+        }
+        let (uuid, username) = (packet.data.uuid()?, packet.data.string()?);
+        client.send(serverbound::LOGIN_ACKNOWLEDGED, &[])?;
+        let registries = client.configure()?;
+        Ok((client, LoginSuccess { uuid, username, registries }))
+    }
+
+    // Bestätigt die Known Packs des Servers unverändert, wie ein Client derselben Version, und sammelt die Register
+    // bis Finish Configuration
+    fn configure(&mut self) -> Result<Vec<String>, String> {
+        let mut registries = Vec::new();
+        loop {
+            let mut packet = self.read_within(READ_TIMEOUT)?.ok_or("Konfigurationsphase nicht beendet")?;
+            match packet.id {
+                clientbound::KNOWN_PACKS => {
+                    let packs = packet.data.rest();
+                    self.send(serverbound::KNOWN_PACKS, &packs)?;
+                }
+                clientbound::REGISTRY_DATA => registries.push(packet.data.string()?),
+                clientbound::FINISH_CONFIGURATION => {
+                    self.send(serverbound::ACKNOWLEDGE_FINISH_CONFIGURATION, &[])?;
+                    return Ok(registries);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn send(&mut self, id: i32, data: &[u8]) -> Result<(), String> {
+        let mut body = varint(id);
+        body.extend(data);
+        let mut frame = varint(body.len() as i32);
+        frame.extend(body);
+        self.stream.write_all(&frame).map_err(|e| format!("Senden fehlgeschlagen: {}", e))
+    }
+
+    // Unsignierter Chat ohne bestätigte Nachrichten, wie von einem Client ohne Chat-Sitzung
+    pub fn chat(&mut self, message: &str, timestamp: i64) -> Result<(), String> {
+        let mut data = string(message);
+        data.extend(timestamp.to_be_bytes());
+        data.extend(0i64.to_be_bytes());
+        data.push(0);
+        data.extend(varint(0));
+        data.extend([0; 3]);
+        self.send(serverbound::CHAT_MESSAGE, &data)
+    }
+
+    fn read_frame(&mut self) -> io::Result<Vec<u8>> {
+        let mut length = 0u32;
+        for i in 0..5 {
+            let mut byte = [0];
+            self.stream.read_exact(&mut byte)?;
+            length |= u32::from(byte[0] & 0x7F) << (7 * i);
+            if byte[0] & 0x80 == 0 {
+                let mut bytes = vec![0; length as usize];
+                self.stream.read_exact(&mut bytes)?;
+                return Ok(bytes);
+            }
+        }
+        Err(io::Error::new(ErrorKind::InvalidData, "VarInt zu lang"))
+    }
+
+    // Wartet höchstens timeout; None, wenn bis dahin nichts kam
+    fn read_within(&mut self, timeout: Duration) -> Result<Option<Packet>, String> {
+        self.stream.set_read_timeout(Some(timeout.max(Duration::from_millis(1)))).map_err(|e| e.to_string())?;
+        match self.read_frame() {
+            Ok(bytes) => {
+                let mut data = Reader { bytes, position: 0 };
+                let id = data.varint()?;
+                Ok(Some(Packet { id, data }))
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(None),
+            Err(e) => Err(format!("Lesen fehlgeschlagen: {}", e)),
+        }
+    }
+
+    // Überliest alles bis zum ersten Paket mit dieser ID
+    pub fn expect(&mut self, id: i32) -> Result<Packet, String> {
+        self.read_until(READ_TIMEOUT, |packet| packet.id == id)?.ok_or_else(|| format!("Kein Paket 0x{:02X} erhalten", id))
+    }
+
+    // Beantwortet dabei Keep-Alives und Teleports wie ein echter Client; None nach Ablauf von timeout
+    pub fn read_until(&mut self, timeout: Duration, mut matches: impl FnMut(&mut Packet) -> bool) -> Result<Option<Packet>, String> {
+        let deadline = Instant::now() + timeout;
+        while let Some(mut packet) = self.read_within(deadline.saturating_duration_since(Instant::now()))? {
+            if matches(&mut packet) {
+                return Ok(Some(packet));
+            }
+            match packet.id {
+                clientbound::KEEP_ALIVE => {
+                    let id = packet.data.i64()?;
+                    self.send(serverbound::KEEP_ALIVE, &id.to_be_bytes())?;
+                }
+                clientbound::SYNCHRONIZE_PLAYER_POSITION => {
+                    packet.data.skip(8 * 3 + 4 * 2 + 1)?;
+                    let teleport = packet.data.varint()?;
+                    self.send(serverbound::CONFIRM_TELEPORTATION, &varint(teleport))?;
+                }
+                _ => {}
+            }
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    // Liest, ohne zu antworten, bis der Server die Verbindung schließt
+    pub fn wait_closed(&mut self, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.read_within(deadline.saturating_duration_since(Instant::now())) {
+                Ok(Some(_)) if Instant::now() < deadline => continue,
+                Ok(_) => return Err(format!("Verbindung nach {:?} noch offen", timeout)),
+                Err(_) => return Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+pub mod client;
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Freier Port und keine Drosselung, weil alle Tests von 127.0.0.1 kommen
+const PROPERTIES: &[(&str, &str)] = &[
+    ("server-ip", "127.0.0.1"),
+    ("server-port", "0"),
+    ("max-handshakes-per-second", "1000"),
+    ("reconnect-cooldown-ms", "0"),
+    ("max-logins-per-minute", "0"),
+    ("log-to-file", "false"),
+    ("autosave-interval", "0"),
+];
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+// Der Server als eigener Prozess in einem leeren Verzeichnis; wird beim Drop beendet und aufgeräumt
+pub struct TestServer {
+    process: Child,
+    // Offen halten, sonst liest der Server auf der Konsole sofort das Ende
+    console: ChildStdin,
+    dir: PathBuf,
+    pub address: SocketAddr,
+    // Alle Listener in der Reihenfolge aus dem Log, der erste ist server-ip
+    pub listening: Vec<SocketAddr>,
+}
+
+impl TestServer {
+    pub fn start() -> TestServer {
+        TestServer::with_properties(&[])
+    }
+
+    // extra überschreibt die Vorgaben aus PROPERTIES
+    pub fn with_properties(extra: &[(&str, &str)]) -> TestServer {
+        TestServer::with_args(extra, &[])
+    }
+
+    // args gehen an die Kommandozeile des Servers
+    pub fn with_args(extra: &[(&str, &str)], args: &[&str]) -> TestServer {
+        let dir = std::env::temp_dir().join(format!("rustserver-test-{}-{}", std::process::id(), NEXT_DIR.fetch_add(1, Ordering::Relaxed)));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let properties: String = PROPERTIES
+            .iter()
+            .filter(|(key, _)| !extra.iter().any(|(k, _)| k == key))
+            .chain(extra)
+            .map(|(key, value)| format!("{}={}\n", key, value))
+            .collect();
+        fs::write(dir.join("server.properties"), properties).unwrap();
+
+        let mut process = Command::new(env!("CARGO_BIN_EXE_RustServer"))
+            .args(args)
+            .current_dir(&dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .expect("Konnte den Server nicht starten");
+        let console = process.stdin.take().unwrap();
+        let stdout = process.stdout.take().unwrap();
+        // Die Ausgabe muss auch nach dem Start gelesen werden, sonst blockiert der Server an der vollen Pipe
+        let (ready, port) = mpsc::channel();
+        thread::spawn(move || {
+            let mut listening = Vec::new();
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if let Some(address) = line.split("Lausche auf ").nth(1) {
+                    listening.extend(address.trim().parse::<SocketAddr>());
+                }
+                if let Some(port) = line.split("Server hört auf Port ").nth(1) {
+                    let _ = port
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or("")
+                        .trim_end_matches('.')
+                        .parse::<u16>()
+                        .map(|port| ready.send((port, std::mem::take(&mut listening))));
+                }
+                if std::env::var_os("TEST_SERVER_LOG").is_some() {
+                    println!("{}", line);
+                }
+            }
+        });
+        let (port, listening) = port.recv_timeout(STARTUP_TIMEOUT).expect("Der Server hat keinen Port gemeldet");
+        TestServer { process, console, dir, address: SocketAddr::from(([127, 0, 0, 1], port)), listening }
+    }
+
+    // Ein Befehl wie von der Serverkonsole
+    pub fn console(&mut self, command: &str) {
+        writeln!(self.console, "{}", command).expect("Konsole geschlossen");
+    }
+
+    // Laufende Threads des Servers, deren Name so beginnt; nur unter Linux
+    pub fn threads(&self, prefix: &str) -> usize {
+        let tasks = fs::read_dir(format!("/proc/{}/task", self.process.id())).expect("Keine Threads unter /proc");
+        tasks.filter_map(|task| fs::read_to_string(task.ok()?.path().join("comm")).ok()).filter(|name| name.starts_with(prefix)).count()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}